@@ -0,0 +1,1219 @@
+//! After-action reports
+//!
+//! Compiles what happened during a mission from persisted data: how long it
+//! ran, how closely each drone kept to the route, whether waypoints were
+//! reached on time, which alerts were raised and acknowledged, how battery
+//! and fuel were used, and how well CV tracking agreed with reported
+//! positions. The report is served as JSON, as a self-contained HTML page, or
+//! as a PDF document.
+
+use crate::mot::GROUND_TRUTH_MAX_SKEW;
+use drone_core::{distance_to_path_m, DroneId, GeoPosition, Mission, MissionId, MissionStatus, TrackingResult, WaypointId};
+use drone_db::{AlertRecord, DbClient, DbResult, RecordStream, TelemetryRecord, WaypointEventRecord};
+
+use chrono::{DateTime, Duration, Utc};
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Drones within this distance of the route are on it, for missions without
+/// a corridor
+pub const ON_ROUTE_TOLERANCE_M: f64 = 200.0;
+
+/// Arrivals up to this long after the expected time are on time, as in the
+/// mission KPIs
+pub const PUNCTUALITY_TOLERANCE: Duration = Duration::seconds(60);
+
+/// Most points kept per consumption curve
+pub const CURVE_POINTS: usize = 120;
+
+/// Output format of the report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Html,
+    Pdf,
+}
+
+/// Post-mission report
+#[derive(Debug, Clone, Serialize)]
+pub struct AfterActionReport {
+    pub mission_id: MissionId,
+    pub mission_name: String,
+    pub status: MissionStatus,
+    pub generated_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Until `generated_at` for a mission still running
+    pub duration_seconds: Option<f64>,
+    pub planned_distance_km: f64,
+    pub route_adherence: Vec<RouteAdherence>,
+    pub punctuality: Punctuality,
+    pub alerts: AlertSummary,
+    pub consumption: Vec<ConsumptionCurve>,
+    pub cv_tracking: CvTrackingQuality,
+}
+
+/// How closely a drone kept to the route
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteAdherence {
+    pub drone_id: DroneId,
+    pub samples: usize,
+    pub mean_deviation_m: Option<f64>,
+    pub max_deviation_m: Option<f64>,
+    /// Samples inside the corridor, or within [`ON_ROUTE_TOLERANCE_M`] of
+    /// the route without one
+    pub on_route_percent: Option<f64>,
+}
+
+/// Waypoint arrivals against their expected times
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Punctuality {
+    pub arrivals: usize,
+    /// Arrivals at waypoints with an expected arrival time
+    pub scheduled: usize,
+    pub on_time: usize,
+    pub late: usize,
+    pub mean_delay_seconds: Option<f64>,
+    pub waypoints: Vec<WaypointArrival>,
+    /// Waypoints passed over by go-direct commands; not counted as arrivals
+    pub skipped: Vec<WaypointSkip>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WaypointArrival {
+    pub waypoint_id: WaypointId,
+    pub drone_id: DroneId,
+    pub arrived_at: DateTime<Utc>,
+    pub expected_at: Option<DateTime<Utc>>,
+    /// Negative when early
+    pub delay_seconds: Option<f64>,
+    pub on_time: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WaypointSkip {
+    pub waypoint_id: WaypointId,
+    pub drone_id: DroneId,
+    pub skipped_at: DateTime<Utc>,
+}
+
+/// Alerts raised during the mission; acknowledged ones count as resolved
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertSummary {
+    pub raised: usize,
+    pub resolved: usize,
+    pub unresolved: usize,
+    pub by_severity: BTreeMap<String, usize>,
+    pub by_type: BTreeMap<String, usize>,
+    pub mean_time_to_resolve_seconds: Option<f64>,
+}
+
+/// A drone's battery and fuel over the mission
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumptionCurve {
+    pub drone_id: DroneId,
+    /// At most [`CURVE_POINTS`], evenly thinned, always with the last sample
+    pub points: Vec<ConsumptionPoint>,
+    /// Percentage points used, first sample to last
+    pub battery_used: Option<i32>,
+    pub fuel_used: Option<i32>,
+    pub battery_per_hour: Option<f64>,
+    pub fuel_per_hour: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConsumptionPoint {
+    pub at: DateTime<Utc>,
+    pub battery: i32,
+    pub fuel: i32,
+}
+
+/// CV tracking results of the mission's drones
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CvTrackingQuality {
+    pub results: usize,
+    /// Distinct frame timestamps
+    pub frames: usize,
+    pub drones_tracked: usize,
+    pub mean_confidence: Option<f64>,
+    pub mean_uncertainty_m: Option<f64>,
+    /// Results matched to a position report within a second
+    pub matched: usize,
+    /// Mean distance between matched estimates and reports
+    pub mean_error_m: Option<f64>,
+    pub max_error_m: Option<f64>,
+}
+
+/// Running mean
+#[derive(Debug, Clone, Copy, Default)]
+struct Mean {
+    sum: f64,
+    n: usize,
+}
+
+impl Mean {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.n += 1;
+    }
+
+    fn get(&self) -> Option<f64> {
+        (self.n > 0).then(|| self.sum / self.n as f64)
+    }
+}
+
+fn max(current: Option<f64>, value: f64) -> Option<f64> {
+    Some(current.map_or(value, |current| current.max(value)))
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
+}
+
+fn position(record: &TelemetryRecord) -> GeoPosition {
+    GeoPosition::new(record.latitude, record.longitude, record.altitude)
+}
+
+/// One drone's telemetry so far
+#[derive(Debug, Default)]
+struct DroneTally {
+    samples: usize,
+    deviation: Mean,
+    max_deviation: Option<f64>,
+    on_route: usize,
+    first: Option<ConsumptionPoint>,
+    last: Option<ConsumptionPoint>,
+    /// Every `stride`th sample, thinned by half whenever it doubles
+    /// [`CURVE_POINTS`]
+    points: Vec<ConsumptionPoint>,
+    stride: usize,
+    /// Last position report, and CV estimates after it waiting for the next
+    last_report: Option<(DateTime<Utc>, GeoPosition)>,
+    unmatched: Vec<(DateTime<Utc>, GeoPosition)>,
+}
+
+/// Tracking results so far
+#[derive(Debug, Default)]
+struct CvTally {
+    results: usize,
+    frames: usize,
+    last_frame: Option<DateTime<Utc>>,
+    drones: BTreeSet<DroneId>,
+    confidence: Mean,
+    uncertainty: Mean,
+    error: Mean,
+    max_error: Option<f64>,
+}
+
+impl CvTally {
+    fn matched(&mut self, estimate: &GeoPosition, reported: &GeoPosition) {
+        let error = estimate.distance_to(reported) * 1000.0;
+        self.error.add(error);
+        self.max_error = max(self.max_error, error);
+    }
+}
+
+/// Compiles a report from a mission's records, taken one at a time so the
+/// mission's history is never held in memory
+///
+/// Telemetry and tracking results must be added oldest first, interleaved
+/// by time, for estimates to be matched with position reports.
+#[derive(Debug)]
+pub struct ReportBuilder {
+    mission: Mission,
+    route: Vec<GeoPosition>,
+    drones: BTreeMap<DroneId, DroneTally>,
+    punctuality: Punctuality,
+    delay: Mean,
+    alerts: AlertSummary,
+    time_to_resolve: Mean,
+    cv: CvTally,
+}
+
+/// A record from one of the time-ordered sources merged while loading
+enum Timed {
+    Telemetry(DroneId, TelemetryRecord),
+    Tracking(TrackingResult),
+}
+
+impl Timed {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            Timed::Telemetry(_, row) => row.timestamp,
+            Timed::Tracking(result) => result.frame_timestamp,
+        }
+    }
+}
+
+impl ReportBuilder {
+    pub fn new(mission: Mission) -> Self {
+        let route = mission.waypoints.iter().map(|wp| wp.position).collect();
+        let drones = mission
+            .assigned_drones
+            .iter()
+            .map(|drone_id| (drone_id.clone(), DroneTally::default()))
+            .collect();
+        Self {
+            mission,
+            route,
+            drones,
+            punctuality: Punctuality::default(),
+            delay: Mean::default(),
+            alerts: AlertSummary::default(),
+            time_to_resolve: Mean::default(),
+            cv: CvTally::default(),
+        }
+    }
+
+    /// Stream the mission's records from its start (or creation) until its
+    /// end, or `now` while it runs
+    pub async fn load(db: &DbClient, mission: Mission, now: DateTime<Utc>) -> DbResult<Self> {
+        let from = mission.start_time.unwrap_or(mission.created_at);
+        let to = mission.end_time.unwrap_or(now).max(from);
+        let mission_id = mission.id.0;
+        let mut builder = Self::new(mission);
+        let drone_ids: BTreeSet<DroneId> = builder.drones.keys().cloned().collect();
+
+        for drone_id in &drone_ids {
+            let mut alerts = db.alerts().stream_range(drone_id, from, to).await?;
+            while let Some(alert) = alerts.try_next().await? {
+                builder.add_alert(&alert);
+            }
+        }
+        let mut events = db.waypoints().stream_range(&builder.mission.id, from, to).await?;
+        while let Some(event) = events.try_next().await? {
+            builder.add_waypoint_event(&event);
+        }
+
+        // Merge each drone's telemetry and the tracking results by time
+        let mut sources: Vec<RecordStream<Timed>> = Vec::with_capacity(drone_ids.len() + 1);
+        for drone_id in &drone_ids {
+            let drone = drone_id.clone();
+            // Rows written before missions were recorded carry no mission
+            sources.push(
+                db.telemetry()
+                    .stream_range(drone_id, from, to)
+                    .await?
+                    .try_filter(move |r| std::future::ready(r.mission_id.is_none_or(|id| id == mission_id)))
+                    .map_ok(move |row| Timed::Telemetry(drone.clone(), row))
+                    .boxed(),
+            );
+        }
+        sources.push(
+            db.tracking()
+                .stream_range(from, to)
+                .await?
+                .try_filter(move |r| std::future::ready(drone_ids.contains(&r.drone_id)))
+                .map_ok(Timed::Tracking)
+                .boxed(),
+        );
+        let mut heads = Vec::with_capacity(sources.len());
+        for source in &mut sources {
+            heads.push(source.try_next().await?);
+        }
+        while let Some(next) = (0..heads.len())
+            .filter(|i| heads[*i].is_some())
+            .min_by_key(|i| heads[*i].as_ref().map(Timed::at))
+        {
+            let record = std::mem::replace(&mut heads[next], sources[next].try_next().await?);
+            match record {
+                Some(Timed::Telemetry(drone_id, row)) => builder.add_telemetry(&drone_id, &row),
+                Some(Timed::Tracking(result)) => builder.add_tracking(&result),
+                None => {}
+            }
+        }
+        Ok(builder)
+    }
+
+    /// Add a position report of an assigned drone
+    pub fn add_telemetry(&mut self, drone_id: &DroneId, row: &TelemetryRecord) {
+        let Some(tally) = self.drones.get_mut(drone_id) else {
+            return;
+        };
+        let reported = position(row);
+        tally.samples += 1;
+        if let Some(deviation) = distance_to_path_m(&reported, &self.route) {
+            tally.deviation.add(deviation);
+            tally.max_deviation = max(tally.max_deviation, deviation);
+            let on_route = match &self.mission.corridor {
+                Some(corridor) => corridor.contains(&reported),
+                None => deviation <= ON_ROUTE_TOLERANCE_M,
+            };
+            if on_route {
+                tally.on_route += 1;
+            }
+        }
+
+        let point = ConsumptionPoint {
+            at: row.timestamp,
+            battery: row.battery_level,
+            fuel: row.fuel_level,
+        };
+        tally.first.get_or_insert(point);
+        tally.last = Some(point);
+        tally.stride = tally.stride.max(1);
+        if (tally.samples - 1) % tally.stride == 0 {
+            tally.points.push(point);
+            if tally.points.len() >= 2 * CURVE_POINTS {
+                let mut index = 0;
+                tally.points.retain(|_| {
+                    index += 1;
+                    index % 2 == 1
+                });
+                tally.stride *= 2;
+            }
+        }
+
+        // Estimates since the previous report take whichever report is closer
+        for (frame, estimate) in std::mem::take(&mut tally.unmatched) {
+            let closest = [tally.last_report, Some((row.timestamp, reported))]
+                .into_iter()
+                .flatten()
+                .map(|(at, reported)| ((at - frame).abs(), reported))
+                .min_by_key(|(skew, _)| *skew);
+            if let Some((skew, reported)) = closest {
+                if skew <= GROUND_TRUTH_MAX_SKEW {
+                    self.cv.matched(&estimate, &reported);
+                }
+            }
+        }
+        tally.last_report = Some((row.timestamp, reported));
+    }
+
+    /// Add a tracking result of an assigned drone
+    pub fn add_tracking(&mut self, result: &TrackingResult) {
+        let Some(tally) = self.drones.get_mut(&result.drone_id) else {
+            return;
+        };
+        let cv = &mut self.cv;
+        cv.results += 1;
+        if cv.last_frame != Some(result.frame_timestamp) {
+            cv.frames += 1;
+            cv.last_frame = Some(result.frame_timestamp);
+        }
+        if !cv.drones.contains(&result.drone_id) {
+            cv.drones.insert(result.drone_id.clone());
+        }
+        cv.confidence.add(result.confidence);
+        if let Some(uncertainty) = result.position_uncertainty_m {
+            cv.uncertainty.add(uncertainty);
+        }
+        if let Some(estimate) = result.estimated_position {
+            tally.unmatched.push((result.frame_timestamp, estimate));
+        }
+    }
+
+    /// Add an arrival or skip; other waypoint events are ignored
+    pub fn add_waypoint_event(&mut self, event: &WaypointEventRecord) {
+        let Some(waypoint_id) = event.waypoint_id.as_deref() else {
+            return;
+        };
+        match event.event_type.as_deref() {
+            None | Some("REACHED") => {
+                let Some(waypoint) = self.mission.waypoints.iter().find(|wp| wp.id.0 == waypoint_id) else {
+                    return;
+                };
+                let delay = waypoint.expected_arrival.map(|at| event.event_time - at);
+                let on_time = delay.map(|delay| delay <= PUNCTUALITY_TOLERANCE);
+                let p = &mut self.punctuality;
+                p.arrivals += 1;
+                match on_time {
+                    Some(true) => p.on_time += 1,
+                    Some(false) => p.late += 1,
+                    None => {}
+                }
+                p.scheduled = p.on_time + p.late;
+                if let Some(delay) = delay {
+                    self.delay.add(seconds(delay));
+                }
+                p.waypoints.push(WaypointArrival {
+                    waypoint_id: waypoint.id.clone(),
+                    drone_id: DroneId::new(event.drone_id.clone()),
+                    arrived_at: event.event_time,
+                    expected_at: waypoint.expected_arrival,
+                    delay_seconds: delay.map(seconds),
+                    on_time,
+                });
+            }
+            Some("SKIPPED") => self.punctuality.skipped.push(WaypointSkip {
+                waypoint_id: WaypointId::new(waypoint_id),
+                drone_id: DroneId::new(event.drone_id.clone()),
+                skipped_at: event.event_time,
+            }),
+            Some(_) => {}
+        }
+    }
+
+    /// Add an alert; acknowledged ones count as resolved
+    pub fn add_alert(&mut self, alert: &AlertRecord) {
+        let summary = &mut self.alerts;
+        summary.raised += 1;
+        let severity = alert.severity.clone().unwrap_or_else(|| "UNKNOWN".into());
+        let alert_type = alert.alert_type.clone().unwrap_or_else(|| "UNKNOWN".into());
+        *summary.by_severity.entry(severity).or_default() += 1;
+        *summary.by_type.entry(alert_type).or_default() += 1;
+        match alert.acknowledged_at {
+            Some(at) => {
+                summary.resolved += 1;
+                self.time_to_resolve.add(seconds(at - alert.created_at).max(0.0));
+            }
+            None => summary.unresolved += 1,
+        }
+    }
+
+    /// Compile the report as of `now`, on the clock the records were stamped with
+    pub fn finish(mut self, now: DateTime<Utc>) -> AfterActionReport {
+        let mission = &self.mission;
+        let duration_seconds = mission
+            .start_time
+            .map(|start| seconds(mission.end_time.unwrap_or(now) - start).max(0.0));
+
+        let mut route_adherence = Vec::with_capacity(self.drones.len());
+        let mut consumption = Vec::with_capacity(self.drones.len());
+        for (drone_id, tally) in &mut self.drones {
+            // Estimates after the last report can only match it
+            for (frame, estimate) in std::mem::take(&mut tally.unmatched) {
+                if let Some((_, reported)) = tally.last_report.filter(|(at, _)| (*at - frame).abs() <= GROUND_TRUTH_MAX_SKEW) {
+                    self.cv.matched(&estimate, &reported);
+                }
+            }
+            route_adherence.push(RouteAdherence {
+                drone_id: drone_id.clone(),
+                samples: tally.samples,
+                mean_deviation_m: tally.deviation.get(),
+                max_deviation_m: tally.max_deviation,
+                on_route_percent: tally.deviation.get().map(|_| tally.on_route as f64 * 100.0 / tally.deviation.n as f64),
+            });
+            consumption.push(consumption_curve(drone_id, tally));
+        }
+
+        self.punctuality.mean_delay_seconds = self.delay.get();
+        self.alerts.mean_time_to_resolve_seconds = self.time_to_resolve.get();
+        let cv = &self.cv;
+        AfterActionReport {
+            mission_id: mission.id.clone(),
+            mission_name: mission.name.clone(),
+            status: mission.status,
+            generated_at: now,
+            started_at: mission.start_time,
+            ended_at: mission.end_time,
+            duration_seconds,
+            planned_distance_km: mission.total_distance_km(),
+            route_adherence,
+            punctuality: self.punctuality,
+            alerts: self.alerts,
+            consumption,
+            cv_tracking: CvTrackingQuality {
+                results: cv.results,
+                frames: cv.frames,
+                drones_tracked: cv.drones.len(),
+                mean_confidence: cv.confidence.get(),
+                mean_uncertainty_m: cv.uncertainty.get(),
+                matched: cv.error.n,
+                mean_error_m: cv.error.get(),
+                max_error_m: cv.max_error,
+            },
+        }
+    }
+}
+
+fn consumption_curve(drone_id: &DroneId, tally: &DroneTally) -> ConsumptionCurve {
+    let step = tally.points.len().div_ceil(CURVE_POINTS).max(1);
+    let mut points: Vec<ConsumptionPoint> = tally.points.iter().step_by(step).copied().collect();
+    if let Some(last) = tally.last {
+        if points.last().is_some_and(|p| p.at != last.at) {
+            if points.len() >= CURVE_POINTS {
+                points.pop();
+            }
+            points.push(last);
+        }
+    }
+
+    let (first, last) = (tally.first, tally.last);
+    let hours = first
+        .zip(last)
+        .map(|(first, last)| seconds(last.at - first.at) / 3600.0)
+        .filter(|hours| *hours > 0.0);
+    let battery_used = first.zip(last).map(|(first, last)| first.battery - last.battery);
+    let fuel_used = first.zip(last).map(|(first, last)| first.fuel - last.fuel);
+
+    ConsumptionCurve {
+        drone_id: drone_id.clone(),
+        points,
+        battery_used,
+        fuel_used,
+        battery_per_hour: battery_used.zip(hours).map(|(used, hours)| used as f64 / hours),
+        fuel_per_hour: fuel_used.zip(hours).map(|(used, hours)| used as f64 / hours),
+    }
+}
+
+// ============================================================================
+// HTML RENDERING
+// ============================================================================
+
+/// Escape text for HTML element content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn opt(value: Option<f64>, decimals: usize) -> String {
+    value.map(|v| format!("{:.*}", decimals, v)).unwrap_or_else(|| "–".into())
+}
+
+/// Battery (blue) and fuel (orange) curves as an inline SVG, 0-100% tall
+fn curve_svg(curve: &ConsumptionCurve) -> String {
+    const WIDTH: f64 = 480.0;
+    const HEIGHT: f64 = 120.0;
+    let (Some(first), Some(last)) = (curve.points.first(), curve.points.last()) else {
+        return "<p>No telemetry</p>".into();
+    };
+    let span = seconds(last.at - first.at).max(1.0);
+    let line = |level: fn(&ConsumptionPoint) -> i32| {
+        curve
+            .points
+            .iter()
+            .map(|p| {
+                let x = seconds(p.at - first.at) / span * WIDTH;
+                let y = HEIGHT - level(p).clamp(0, 100) as f64 / 100.0 * HEIGHT;
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!(
+        r##"<svg viewBox="0 0 {w} {h}" width="{w}" height="{h}" role="img"><rect width="{w}" height="{h}" fill="none" stroke="#ccc"/><polyline fill="none" stroke="#2563eb" stroke-width="2" points="{battery}"/><polyline fill="none" stroke="#ea580c" stroke-width="2" points="{fuel}"/></svg>"##,
+        w = WIDTH,
+        h = HEIGHT,
+        battery = line(|p| p.battery),
+        fuel = line(|p| p.fuel),
+    )
+}
+
+impl AfterActionReport {
+    /// Self-contained HTML page of the report, laid out for printing
+    pub fn render_html(&self) -> String {
+        let mut html = String::new();
+        let name = escape(&self.mission_name);
+        let _ = write!(
+            html,
+            r#"<!DOCTYPE html>
+<html lang="en"><head><meta charset="utf-8"><title>After-action report: {name}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; color: #111; }}
+table {{ border-collapse: collapse; margin-bottom: 1.5em; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; }}
+th {{ background: #f3f4f6; }}
+.late {{ color: #b91c1c; }}
+@media print {{ section {{ break-inside: avoid; }} }}
+</style></head><body>
+<h1>After-action report: {name}</h1>
+<section><h2>Summary</h2><table>
+<tr><th>Mission</th><td>{id}</td></tr>
+<tr><th>Status</th><td>{status:?}</td></tr>
+<tr><th>Started</th><td>{started}</td></tr>
+<tr><th>Ended</th><td>{ended}</td></tr>
+<tr><th>Duration (min)</th><td>{duration}</td></tr>
+<tr><th>Planned distance (km)</th><td>{distance:.1}</td></tr>
+<tr><th>Generated</th><td>{generated}</td></tr>
+</table></section>
+"#,
+            id = self.mission_id,
+            status = self.status,
+            started = self.started_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "–".into()),
+            ended = self.ended_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "–".into()),
+            duration = opt(self.duration_seconds.map(|s| s / 60.0), 1),
+            distance = self.planned_distance_km,
+            generated = self.generated_at.to_rfc3339(),
+        );
+
+        html.push_str("<section><h2>Route adherence</h2><table><tr><th>Drone</th><th>Samples</th><th>Mean deviation (m)</th><th>Max deviation (m)</th><th>On route (%)</th></tr>\n");
+        for drone in &self.route_adherence {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(drone.drone_id.as_str()),
+                drone.samples,
+                opt(drone.mean_deviation_m, 1),
+                opt(drone.max_deviation_m, 1),
+                opt(drone.on_route_percent, 1),
+            );
+        }
+        html.push_str("</table></section>\n");
+
+        let p = &self.punctuality;
+        let _ = writeln!(
+            html,
+            "<section><h2>Waypoint punctuality</h2><p>{} arrivals, {} on time, {} late, mean delay {} s</p>",
+            p.arrivals,
+            p.on_time,
+            p.late,
+            opt(p.mean_delay_seconds, 0),
+        );
+        html.push_str("<table><tr><th>Waypoint</th><th>Drone</th><th>Arrived</th><th>Expected</th><th>Delay (s)</th></tr>\n");
+        for arrival in &p.waypoints {
+            let class = if arrival.on_time == Some(false) { r#" class="late""# } else { "" };
+            let _ = writeln!(
+                html,
+                "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                class,
+                escape(&arrival.waypoint_id.0),
+                escape(arrival.drone_id.as_str()),
+                arrival.arrived_at.to_rfc3339(),
+                arrival.expected_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "–".into()),
+                opt(arrival.delay_seconds, 0),
+            );
+        }
+        html.push_str("</table>\n");
+        if !p.skipped.is_empty() {
+            html.push_str("<h3>Skipped</h3><table><tr><th>Waypoint</th><th>Drone</th><th>Skipped</th></tr>\n");
+            for skip in &p.skipped {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape(&skip.waypoint_id.0),
+                    escape(skip.drone_id.as_str()),
+                    skip.skipped_at.to_rfc3339(),
+                );
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</section>\n");
+
+        let a = &self.alerts;
+        let _ = writeln!(
+            html,
+            "<section><h2>Alerts</h2><p>{} raised, {} resolved, {} unresolved, mean time to resolve {} s</p>",
+            a.raised,
+            a.resolved,
+            a.unresolved,
+            opt(a.mean_time_to_resolve_seconds, 0),
+        );
+        html.push_str("<table><tr><th>Severity</th><th>Count</th></tr>\n");
+        for (severity, count) in &a.by_severity {
+            let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(severity), count);
+        }
+        html.push_str("</table><table><tr><th>Type</th><th>Count</th></tr>\n");
+        for (alert_type, count) in &a.by_type {
+            let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(alert_type), count);
+        }
+        html.push_str("</table></section>\n");
+
+        html.push_str("<section><h2>Battery and fuel</h2><p>Battery in blue, fuel in orange.</p>\n");
+        for curve in &self.consumption {
+            let _ = writeln!(
+                html,
+                "<h3>{}</h3><p>Battery used {} pts ({} /h), fuel used {} pts ({} /h)</p>{}",
+                escape(curve.drone_id.as_str()),
+                curve.battery_used.map(|v| v.to_string()).unwrap_or_else(|| "–".into()),
+                opt(curve.battery_per_hour, 1),
+                curve.fuel_used.map(|v| v.to_string()).unwrap_or_else(|| "–".into()),
+                opt(curve.fuel_per_hour, 1),
+                curve_svg(curve),
+            );
+        }
+        html.push_str("</section>\n");
+
+        let cv = &self.cv_tracking;
+        let _ = write!(
+            html,
+            "<section><h2>CV tracking</h2><table>
+<tr><th>Results</th><td>{}</td></tr>
+<tr><th>Frames</th><td>{}</td></tr>
+<tr><th>Drones tracked</th><td>{}</td></tr>
+<tr><th>Mean confidence</th><td>{}</td></tr>
+<tr><th>Mean uncertainty (m)</th><td>{}</td></tr>
+<tr><th>Matched to reports</th><td>{}</td></tr>
+<tr><th>Mean / max error (m)</th><td>{} / {}</td></tr>
+</table></section>
+</body></html>
+",
+            cv.results,
+            cv.frames,
+            cv.drones_tracked,
+            opt(cv.mean_confidence, 2),
+            opt(cv.mean_uncertainty_m, 1),
+            cv.matched,
+            opt(cv.mean_error_m, 1),
+            opt(cv.max_error_m, 1),
+        );
+        html
+    }
+}
+
+// ============================================================================
+// PDF RENDERING
+// ============================================================================
+
+const PDF_PAGE_WIDTH: f64 = 595.0;
+const PDF_PAGE_HEIGHT: f64 = 842.0;
+const PDF_MARGIN: f64 = 50.0;
+const PDF_FONT_SIZE: f64 = 9.0;
+const PDF_LEADING: f64 = 12.0;
+
+/// Characters per line of 9 pt Courier between the margins
+const PDF_LINE_CHARS: usize = 90;
+
+/// A4 pages of Courier text and line drawings, built top to bottom
+struct PdfWriter {
+    pages: Vec<String>,
+    content: String,
+    y: f64,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            content: String::new(),
+            y: PDF_PAGE_HEIGHT - PDF_MARGIN,
+        }
+    }
+
+    /// Start a new page unless `height` still fits on this one
+    fn reserve(&mut self, height: f64) {
+        if self.y - height < PDF_MARGIN {
+            self.pages.push(std::mem::take(&mut self.content));
+            self.y = PDF_PAGE_HEIGHT - PDF_MARGIN;
+        }
+    }
+
+    /// Text as a PDF string in the standard encoding: printable ASCII, with
+    /// dashes kept and anything else replaced
+    fn literal(text: &str) -> String {
+        let mut literal = String::with_capacity(text.len() + 2);
+        literal.push('(');
+        for c in text.chars().take(PDF_LINE_CHARS) {
+            match c {
+                '(' | ')' | '\\' => {
+                    literal.push('\\');
+                    literal.push(c);
+                }
+                '–' | '—' => literal.push('-'),
+                ' '..='~' => literal.push(c),
+                _ => literal.push('?'),
+            }
+        }
+        literal.push(')');
+        literal
+    }
+
+    fn line(&mut self, text: &str) {
+        self.text("F1", PDF_FONT_SIZE, text);
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.y -= PDF_LEADING / 2.0;
+        self.text("F2", PDF_FONT_SIZE + 2.0, text);
+    }
+
+    fn text(&mut self, font: &str, size: f64, text: &str) {
+        self.reserve(PDF_LEADING);
+        self.y -= PDF_LEADING;
+        let _ = writeln!(
+            self.content,
+            "BT /{} {} Tf {} {:.1} Td {} Tj ET",
+            font,
+            size,
+            PDF_MARGIN,
+            self.y,
+            Self::literal(text)
+        );
+    }
+
+    /// Battery (blue) and fuel (orange) curves, 0-100% tall
+    fn curve(&mut self, curve: &ConsumptionCurve) {
+        const HEIGHT: f64 = 100.0;
+        let width = PDF_PAGE_WIDTH - 2.0 * PDF_MARGIN;
+        let (Some(first), Some(last)) = (curve.points.first(), curve.points.last()) else {
+            return;
+        };
+        self.reserve(HEIGHT + PDF_LEADING);
+        self.y -= HEIGHT + PDF_LEADING / 2.0;
+        let bottom = self.y;
+        let _ = writeln!(self.content, "0.8 G {} {:.1} {} {} re S", PDF_MARGIN, bottom, width, HEIGHT);
+        let span = seconds(last.at - first.at).max(1.0);
+        for (color, level) in [
+            ("0.15 0.39 0.92", (|p| p.battery) as fn(&ConsumptionPoint) -> i32),
+            ("0.92 0.35 0.05", |p| p.fuel),
+        ] {
+            let _ = write!(self.content, "{} RG 1.5 w", color);
+            for (i, p) in curve.points.iter().enumerate() {
+                let x = PDF_MARGIN + seconds(p.at - first.at) / span * width;
+                let y = bottom + level(p).clamp(0, 100) as f64 / 100.0 * HEIGHT;
+                let _ = write!(self.content, " {:.1} {:.1} {}", x, y, if i == 0 { "m" } else { "l" });
+            }
+            self.content.push_str(" S 1 w\n");
+        }
+    }
+
+    /// The document, with its cross-reference table
+    fn finish(mut self) -> Vec<u8> {
+        self.pages.push(self.content);
+        // Catalog, page tree and the two fonts, then a page and its content per page
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| 5 + 2 * i).collect();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+                page_ids.len()
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold >>".to_string(),
+        ];
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PDF_PAGE_WIDTH,
+                PDF_PAGE_HEIGHT,
+                id + 1
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{}endstream", page.len(), page));
+        }
+
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+        }
+        let xref = pdf.len();
+        let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        pdf.into_bytes()
+    }
+}
+
+impl AfterActionReport {
+    /// The report as a PDF document with the same sections as the HTML page
+    pub fn render_pdf(&self) -> Vec<u8> {
+        let mut pdf = PdfWriter::new();
+        let time = |at: Option<DateTime<Utc>>| at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "-".into());
+        pdf.heading(&format!("After-action report: {}", self.mission_name));
+        pdf.line(&format!("Mission          {}", self.mission_id));
+        pdf.line(&format!("Status           {:?}", self.status));
+        pdf.line(&format!("Started          {}", time(self.started_at)));
+        pdf.line(&format!("Ended            {}", time(self.ended_at)));
+        pdf.line(&format!("Duration (min)   {}", opt(self.duration_seconds.map(|s| s / 60.0), 1)));
+        pdf.line(&format!("Planned distance {:.1} km", self.planned_distance_km));
+        pdf.line(&format!("Generated        {}", self.generated_at.to_rfc3339()));
+
+        pdf.heading("Route adherence");
+        pdf.line(&format!("{:<20} {:>8} {:>14} {:>14} {:>12}", "Drone", "Samples", "Mean dev (m)", "Max dev (m)", "On route (%)"));
+        for drone in &self.route_adherence {
+            pdf.line(&format!(
+                "{:<20} {:>8} {:>14} {:>14} {:>12}",
+                drone.drone_id.as_str(),
+                drone.samples,
+                opt(drone.mean_deviation_m, 1),
+                opt(drone.max_deviation_m, 1),
+                opt(drone.on_route_percent, 1),
+            ));
+        }
+
+        let p = &self.punctuality;
+        pdf.heading("Waypoint punctuality");
+        pdf.line(&format!(
+            "{} arrivals, {} on time, {} late, mean delay {} s",
+            p.arrivals,
+            p.on_time,
+            p.late,
+            opt(p.mean_delay_seconds, 0)
+        ));
+        for arrival in &p.waypoints {
+            pdf.line(&format!(
+                "{:<16} {:<16} {} delay {} s{}",
+                arrival.waypoint_id.0,
+                arrival.drone_id.as_str(),
+                arrival.arrived_at.format("%Y-%m-%d %H:%M:%S"),
+                opt(arrival.delay_seconds, 0),
+                if arrival.on_time == Some(false) { " LATE" } else { "" },
+            ));
+        }
+        for skip in &p.skipped {
+            pdf.line(&format!(
+                "{:<16} {:<16} {} skipped",
+                skip.waypoint_id.0,
+                skip.drone_id.as_str(),
+                skip.skipped_at.format("%Y-%m-%d %H:%M:%S"),
+            ));
+        }
+
+        let a = &self.alerts;
+        pdf.heading("Alerts");
+        pdf.line(&format!(
+            "{} raised, {} resolved, {} unresolved, mean time to resolve {} s",
+            a.raised,
+            a.resolved,
+            a.unresolved,
+            opt(a.mean_time_to_resolve_seconds, 0)
+        ));
+        for (severity, count) in &a.by_severity {
+            pdf.line(&format!("{:<24} {:>6}", severity, count));
+        }
+        for (alert_type, count) in &a.by_type {
+            pdf.line(&format!("{:<24} {:>6}", alert_type, count));
+        }
+
+        pdf.heading("Battery and fuel");
+        pdf.line("Battery in blue, fuel in orange.");
+        for curve in &self.consumption {
+            pdf.line(&format!(
+                "{}: battery used {} pts ({} /h), fuel used {} pts ({} /h)",
+                curve.drone_id.as_str(),
+                curve.battery_used.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                opt(curve.battery_per_hour, 1),
+                curve.fuel_used.map(|v| v.to_string()).unwrap_or_else(|| "-".into()),
+                opt(curve.fuel_per_hour, 1),
+            ));
+            pdf.curve(curve);
+        }
+
+        let cv = &self.cv_tracking;
+        pdf.heading("CV tracking");
+        pdf.line(&format!("Results              {}", cv.results));
+        pdf.line(&format!("Frames               {}", cv.frames));
+        pdf.line(&format!("Drones tracked       {}", cv.drones_tracked));
+        pdf.line(&format!("Mean confidence      {}", opt(cv.mean_confidence, 2)));
+        pdf.line(&format!("Mean uncertainty (m) {}", opt(cv.mean_uncertainty_m, 1)));
+        pdf.line(&format!("Matched to reports   {}", cv.matched));
+        pdf.line(&format!("Mean / max error (m) {} / {}", opt(cv.mean_error_m, 1), opt(cv.max_error_m, 1)));
+        pdf.finish()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{Alert, AlertSeverity, AlertType, BoundingBox, DroneStatus, Telemetry, Waypoint};
+    use drone_db::{DbConfig, SqliteStore};
+
+    #[test]
+    fn test_compile_and_render() {
+        let t0 = Utc::now();
+        let at = |secs: i64| t0 + Duration::seconds(secs);
+        let mut mission = Mission::new("Convoy <Alpha>");
+        mission.add_waypoint(Waypoint::new("WP01", "Start", 34.50, 69.20));
+        mission.add_waypoint(Waypoint::new("WP02", "End", 34.60, 69.20));
+        mission.waypoints[1].expected_arrival = Some(at(1000));
+        mission.start_time = Some(t0);
+        mission.end_time = Some(at(3600));
+        mission.status = MissionStatus::Completed;
+        let drone = DroneId::new("REAPER-01");
+        mission.assign_drone(drone.clone());
+
+        // Northbound along the route, 300 m east of it for the last sample
+        let start = mission.waypoints[0].position;
+        let rows: Vec<TelemetryRecord> = (0..300)
+            .map(|i| {
+                let east_km = if i == 299 { 0.3 } else { 0.0 };
+                let telemetry = Telemetry {
+                    timestamp: at(i * 12),
+                    battery_level: (100 - i / 10) as u8,
+                    fuel_level: (90 - i / 20) as u8,
+                    ..Telemetry::default()
+                };
+                let position = start.destination(i as f64 * 0.03, 0.0).destination(east_km, 90.0);
+                TelemetryRecord::new(&drone, &position, &telemetry, DroneStatus::Moving, true, Some(&mission.id))
+            })
+            .collect();
+
+        let mission_id = mission.id.0;
+        let reached = |waypoint: &str, secs: i64| WaypointEventRecord {
+            mission_id,
+            event_time: at(secs),
+            drone_id: drone.0.clone(),
+            waypoint_id: Some(waypoint.into()),
+            waypoint_name: None,
+            latitude: None,
+            longitude: None,
+            event_type: Some("REACHED".into()),
+            speed_at_event: None,
+            altitude_at_event: None,
+            heading: None,
+        };
+        let alert = |severity: &str, acknowledged: Option<i64>| AlertRecord {
+            alert_id: uuid::Uuid::new_v4(),
+            created_at: at(100),
+            severity: Some(severity.into()),
+            alert_type: Some("LOW_BATTERY".into()),
+            message: None,
+            drone_id: Some(drone.0.clone()),
+            acknowledged_at: acknowledged.map(at),
+        };
+        let mut estimate = TrackingResult::new(drone.clone(), 1, BoundingBox::new(0, 0, 10, 10));
+        estimate.frame_timestamp = at(120);
+        estimate.estimated_position = Some(position(&rows[10]).destination(0.02, 90.0));
+        estimate.confidence = 0.8;
+
+        let mut builder = ReportBuilder::new(mission);
+        for (i, row) in rows.iter().enumerate() {
+            builder.add_telemetry(&drone, row);
+            if i == 10 {
+                builder.add_tracking(&estimate);
+            }
+        }
+        builder.add_waypoint_event(&reached("WP01", 0));
+        builder.add_waypoint_event(&WaypointEventRecord {
+            event_type: Some("SKIPPED".into()),
+            ..reached("WP01B", 500)
+        });
+        builder.add_waypoint_event(&reached("WP02", 1100));
+        builder.add_alert(&alert("WARNING", Some(160)));
+        builder.add_alert(&alert("CRITICAL", None));
+        let report = builder.finish(at(4000));
+
+        assert_eq!(report.duration_seconds, Some(3600.0));
+        let adherence = &report.route_adherence[0];
+        assert_eq!(adherence.samples, 300);
+        assert!((adherence.max_deviation_m.unwrap() - 300.0).abs() < 5.0);
+        assert!((adherence.on_route_percent.unwrap() - 299.0 / 3.0).abs() < 1e-9);
+
+        let punctuality = &report.punctuality;
+        assert_eq!((punctuality.arrivals, punctuality.scheduled, punctuality.late), (2, 1, 1));
+        assert_eq!(punctuality.mean_delay_seconds, Some(100.0));
+        assert_eq!(punctuality.skipped.len(), 1);
+        assert_eq!(punctuality.skipped[0].waypoint_id, WaypointId::new("WP01B"));
+
+        let alerts = &report.alerts;
+        assert_eq!((alerts.raised, alerts.resolved, alerts.unresolved), (2, 1, 1));
+        assert_eq!(alerts.by_type["LOW_BATTERY"], 2);
+        assert_eq!(alerts.mean_time_to_resolve_seconds, Some(60.0));
+
+        let curve = &report.consumption[0];
+        assert!(curve.points.len() <= CURVE_POINTS);
+        assert_eq!(curve.points.last().unwrap().at, at(299 * 12));
+        assert_eq!((curve.battery_used, curve.fuel_used), (Some(29), Some(14)));
+
+        let cv = &report.cv_tracking;
+        assert_eq!((cv.results, cv.matched), (1, 1));
+        assert!((cv.mean_error_m.unwrap() - 20.0).abs() < 0.5);
+
+        let html = report.render_html();
+        assert!(html.contains("Convoy &lt;Alpha&gt;"));
+        assert!(html.contains("<polyline"));
+        assert!(html.contains(r#"<tr class="late"><td>WP02</td>"#));
+        assert!(html.contains("<h3>Skipped</h3>"));
+
+        let pdf = report.render_pdf();
+        let text = String::from_utf8(pdf).unwrap();
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(After-action report: Convoy <Alpha>)"));
+        // The cross-reference table points at each object
+        let xref: usize = text.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert!(text[xref..].starts_with("xref"));
+        let offsets: Vec<usize> = text[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        for (i, offset) in offsets.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_streams_stored_records() {
+        let db = DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default());
+        // Whole milliseconds, as stored
+        let t0 = DateTime::from_timestamp_millis(Utc::now().timestamp_millis()).unwrap() - Duration::minutes(10);
+        let at = |secs: i64| t0 + Duration::seconds(secs);
+        let drone = DroneId::new("REAPER-01");
+        let mut mission = Mission::new("Stored");
+        mission.add_waypoint(Waypoint::new("WP01", "Start", 34.50, 69.20));
+        mission.add_waypoint(Waypoint::new("WP02", "End", 34.60, 69.20));
+        mission.start_time = Some(t0);
+        mission.assign_drone(drone.clone());
+
+        let start = mission.waypoints[0].position;
+        let rows = (0..20)
+            .map(|i| {
+                let telemetry = Telemetry {
+                    timestamp: at(i * 10),
+                    ..Telemetry::default()
+                };
+                let position = start.destination(i as f64 * 0.05, 0.0);
+                TelemetryRecord::new(&drone, &position, &telemetry, DroneStatus::Moving, true, Some(&mission.id))
+            })
+            .collect();
+        db.telemetry().insert_records(rows).await.unwrap();
+        // Between the reports at 50 s and 60 s, closer to the later one
+        let mut estimate = TrackingResult::new(drone.clone(), 1, BoundingBox::new(0, 0, 10, 10));
+        estimate.frame_timestamp = at(59) + Duration::milliseconds(500);
+        estimate.estimated_position = Some(start.destination(0.3, 0.0).destination(0.05, 90.0));
+        db.tracking().insert(&estimate).await.unwrap();
+
+        let mut acknowledged = Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "low").for_drone(drone.clone());
+        acknowledged.created_at = at(30);
+        let open = Alert {
+            id: uuid::Uuid::new_v4(),
+            created_at: at(40),
+            ..acknowledged.clone()
+        };
+        db.alerts().create(&acknowledged).await.unwrap();
+        db.alerts().create(&open).await.unwrap();
+        db.alerts().acknowledge(&drone, acknowledged.id, acknowledged.created_at, "operator", at(75)).await.unwrap();
+
+        let report = ReportBuilder::load(&db, mission, at(300)).await.unwrap().finish(at(300));
+        assert_eq!(report.route_adherence[0].samples, 20);
+        assert_eq!((report.alerts.resolved, report.alerts.unresolved), (1, 1));
+        assert_eq!(report.alerts.mean_time_to_resolve_seconds, Some(45.0));
+        assert_eq!(report.cv_tracking.matched, 1);
+        assert!((report.cv_tracking.mean_error_m.unwrap() - 50.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_curves_thin_while_streaming() {
+        let t0 = Utc::now();
+        let drone = DroneId::new("REAPER-01");
+        let mut mission = Mission::new("Long haul");
+        mission.add_waypoint(Waypoint::new("WP01", "Start", 34.50, 69.20));
+        mission.assign_drone(drone.clone());
+        let start = mission.waypoints[0].position;
+        let mut builder = ReportBuilder::new(mission);
+        for i in 0..10_000 {
+            let telemetry = Telemetry {
+                timestamp: t0 + Duration::seconds(i),
+                battery_level: (100 - i / 200) as u8,
+                ..Telemetry::default()
+            };
+            builder.add_telemetry(&drone, &TelemetryRecord::new(&drone, &start, &telemetry, DroneStatus::Moving, true, None));
+            assert!(builder.drones[&drone].points.len() < 2 * CURVE_POINTS);
+        }
+        let report = builder.finish(t0 + Duration::seconds(10_000));
+        let curve = &report.consumption[0];
+        assert!(curve.points.len() <= CURVE_POINTS);
+        assert_eq!(curve.points.last().unwrap().at, t0 + Duration::seconds(9_999));
+        assert_eq!(curve.battery_used, Some(49));
+        assert_eq!(report.route_adherence[0].samples, 10_000);
+    }
+}
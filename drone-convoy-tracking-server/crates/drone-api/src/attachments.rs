@@ -0,0 +1,409 @@
+//! Waypoint attachments
+//!
+//! Photos, documents and notes attached to waypoints as intel. Content goes
+//! to an object store under a generated key; the metadata (file name,
+//! content type, threat level, notes) goes to `waypoint_attachments` and is
+//! cached here for the waypoints API.
+
+use drone_core::{MissionId, ThreatLevel, WaypointAttachment, WaypointId};
+use drone_db::{DbClient, WaypointAttachmentRecord};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Default upload size limit (10 MiB)
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Content types shown in the browser; everything else is downloaded
+const INLINE_CONTENT_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp", "application/pdf", "text/plain"];
+
+/// Policy for served attachment content: no scripts, no subresources
+pub const ATTACHMENT_CSP: &str = "default-src 'none'; img-src 'self'; style-src 'unsafe-inline'; sandbox";
+
+/// `Content-Type` and `Content-Disposition` to serve an attachment with.
+/// Uploaded content types are not trusted: only the allow-listed ones are
+/// shown inline, the rest download as `application/octet-stream`.
+pub fn serving_headers(attachment: &WaypointAttachment) -> (String, String) {
+    let essence = attachment
+        .content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    // Quotes, backslashes and control characters would break out of the header value
+    let file_name: String = attachment
+        .file_name
+        .chars()
+        .map(|c| if c == '"' || c == '\\' || c.is_control() { '_' } else { c })
+        .collect();
+    if INLINE_CONTENT_TYPES.contains(&essence.as_str()) {
+        (essence, format!("inline; filename=\"{}\"", file_name))
+    } else {
+        ("application/octet-stream".into(), format!("attachment; filename=\"{}\"", file_name))
+    }
+}
+
+/// Attachment storage settings
+#[derive(Debug, Clone)]
+pub struct AttachmentConfig {
+    /// Root directory of the filesystem object store
+    pub dir: PathBuf,
+    /// Largest accepted upload
+    pub max_bytes: usize,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("./attachments"),
+            max_bytes: DEFAULT_MAX_ATTACHMENT_BYTES,
+        }
+    }
+}
+
+impl AttachmentConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            dir: std::env::var("ATTACHMENT_DIR").map(PathBuf::from).unwrap_or(defaults.dir),
+            max_bytes: std::env::var("ATTACHMENT_MAX_BYTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.max_bytes),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AttachmentError {
+    #[error("object store error: {0}")]
+    Storage(#[from] std::io::Error),
+
+    #[error("database error: {0}")]
+    Database(#[from] drone_db::DbError),
+
+    #[error("attachment content for {0} is missing from the object store")]
+    MissingContent(Uuid),
+}
+
+// ============================================================================
+// OBJECT STORE
+// ============================================================================
+
+/// Blob storage for attachment content
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn put(&self, key: &str, content: &[u8]) -> std::io::Result<()>;
+
+    /// `None` if there is no object under `key`
+    async fn get(&self, key: &str) -> std::io::Result<Option<Vec<u8>>>;
+
+    /// Deleting a missing object is not an error
+    async fn delete(&self, key: &str) -> std::io::Result<()>;
+}
+
+/// Object store on the local filesystem; keys are relative paths under `root`
+pub struct FsObjectStore {
+    root: PathBuf,
+}
+
+impl FsObjectStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl ObjectStore for FsObjectStore {
+    async fn put(&self, key: &str, content: &[u8]) -> std::io::Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write aside and rename so readers never see a partial object
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, content).await?;
+        tokio::fs::rename(&partial, &path).await
+    }
+
+    async fn get(&self, key: &str) -> std::io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.root.join(key)).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> std::io::Result<()> {
+        match tokio::fs::remove_file(self.root.join(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+// ============================================================================
+// SERVICE
+// ============================================================================
+
+/// Metadata supplied with an upload
+#[derive(Debug, Clone)]
+pub struct NewAttachment {
+    pub mission_id: MissionId,
+    pub waypoint_id: WaypointId,
+    pub file_name: String,
+    pub content_type: String,
+    pub threat_level: Option<ThreatLevel>,
+    pub notes: Option<String>,
+    pub uploaded_by: Option<String>,
+}
+
+/// Stores waypoint attachments and keeps their metadata in memory
+pub struct AttachmentService {
+    config: AttachmentConfig,
+    store: Arc<dyn ObjectStore>,
+    db: Option<Arc<DbClient>>,
+    attachments: RwLock<HashMap<Uuid, WaypointAttachment>>,
+}
+
+impl AttachmentService {
+    /// Service over the filesystem object store in `config.dir`
+    pub fn new(config: AttachmentConfig, db: Option<Arc<DbClient>>) -> Self {
+        let store = Arc::new(FsObjectStore::new(config.dir.clone()));
+        Self::with_store(config, store, db)
+    }
+
+    pub fn with_store(config: AttachmentConfig, store: Arc<dyn ObjectStore>, db: Option<Arc<DbClient>>) -> Self {
+        Self {
+            config,
+            store,
+            db,
+            attachments: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &AttachmentConfig {
+        &self.config
+    }
+
+    /// Store the content, then persist the metadata
+    pub async fn upload(
+        &self,
+        new: NewAttachment,
+        content: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<WaypointAttachment, AttachmentError> {
+        let id = Uuid::new_v4();
+        let attachment = WaypointAttachment {
+            id,
+            object_key: format!("{}/{}", new.mission_id.0, id),
+            mission_id: new.mission_id,
+            waypoint_id: new.waypoint_id,
+            file_name: new.file_name,
+            content_type: new.content_type,
+            size_bytes: content.len() as u64,
+            threat_level: new.threat_level,
+            notes: new.notes,
+            uploaded_by: new.uploaded_by,
+            created_at: now,
+        };
+
+        self.store.put(&attachment.object_key, content).await?;
+        if let Some(db) = &self.db {
+            if let Err(e) = db.waypoints().save_attachment(&to_record(&attachment)).await {
+                // Don't leave content nothing refers to
+                if let Err(e) = self.store.delete(&attachment.object_key).await {
+                    warn!("Failed to remove orphaned attachment {}: {}", attachment.id, e);
+                }
+                return Err(e.into());
+            }
+        }
+
+        self.attachments.write().insert(id, attachment.clone());
+        info!(
+            "Attachment {} ({}, {} bytes) added to waypoint {}",
+            id, attachment.file_name, attachment.size_bytes, attachment.waypoint_id
+        );
+        Ok(attachment)
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<WaypointAttachment> {
+        self.attachments.read().get(id).cloned()
+    }
+
+    /// An attachment's metadata and content; `None` if there is no such attachment
+    pub async fn download(&self, id: &Uuid) -> Result<Option<(WaypointAttachment, Vec<u8>)>, AttachmentError> {
+        let Some(attachment) = self.get(id) else {
+            return Ok(None);
+        };
+        let content = self
+            .store
+            .get(&attachment.object_key)
+            .await?
+            .ok_or(AttachmentError::MissingContent(*id))?;
+        Ok(Some((attachment, content)))
+    }
+
+    /// Remove an attachment; `None` if there was no such attachment
+    pub async fn delete(&self, id: &Uuid) -> Result<Option<WaypointAttachment>, AttachmentError> {
+        let Some(attachment) = self.get(id) else {
+            return Ok(None);
+        };
+        if let Some(db) = &self.db {
+            db.waypoints().delete_attachment(*id).await?;
+        }
+        self.attachments.write().remove(id);
+        self.store.delete(&attachment.object_key).await?;
+        info!("Attachment {} removed from waypoint {}", id, attachment.waypoint_id);
+        Ok(Some(attachment))
+    }
+
+    /// Attachments of a mission's waypoints, oldest first, optionally for one waypoint
+    pub fn list(&self, mission_id: &MissionId, waypoint_id: Option<&WaypointId>) -> Vec<WaypointAttachment> {
+        let mut attachments: Vec<_> = self
+            .attachments
+            .read()
+            .values()
+            .filter(|a| &a.mission_id == mission_id && waypoint_id.is_none_or(|wp| &a.waypoint_id == wp))
+            .cloned()
+            .collect();
+        attachments.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        attachments
+    }
+
+    /// A mission's attachments grouped by waypoint
+    pub fn by_waypoint(&self, mission_id: &MissionId) -> HashMap<WaypointId, Vec<WaypointAttachment>> {
+        let mut grouped: HashMap<WaypointId, Vec<WaypointAttachment>> = HashMap::new();
+        for attachment in self.list(mission_id, None) {
+            grouped.entry(attachment.waypoint_id.clone()).or_default().push(attachment);
+        }
+        grouped
+    }
+
+    /// Load persisted attachment metadata
+    pub async fn load(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut attachments = HashMap::new();
+        for record in db.waypoints().attachments().await? {
+            match from_record(&record) {
+                Ok(attachment) => {
+                    attachments.insert(attachment.id, attachment);
+                }
+                Err(e) => warn!("Skipping unreadable attachment {}: {}", record.id, e),
+            }
+        }
+        info!("Loaded {} waypoint attachments", attachments.len());
+        *self.attachments.write() = attachments;
+        Ok(())
+    }
+}
+
+fn to_record(attachment: &WaypointAttachment) -> WaypointAttachmentRecord {
+    WaypointAttachmentRecord {
+        id: attachment.id,
+        mission_id: attachment.mission_id.0,
+        waypoint_id: attachment.waypoint_id.0.clone(),
+        file_name: attachment.file_name.clone(),
+        content_type: attachment.content_type.clone(),
+        size_bytes: attachment.size_bytes as i64,
+        object_key: attachment.object_key.clone(),
+        threat_level: attachment
+            .threat_level
+            .and_then(|level| serde_json::to_value(level).ok())
+            .and_then(|v| v.as_str().map(str::to_string)),
+        notes: attachment.notes.clone(),
+        uploaded_by: attachment.uploaded_by.clone(),
+        created_at: attachment.created_at,
+    }
+}
+
+fn from_record(record: &WaypointAttachmentRecord) -> Result<WaypointAttachment, serde_json::Error> {
+    let threat_level = record
+        .threat_level
+        .as_ref()
+        .map(|level| serde_json::from_value(serde_json::Value::String(level.clone())))
+        .transpose()?;
+    Ok(WaypointAttachment {
+        id: record.id,
+        mission_id: MissionId(record.mission_id),
+        waypoint_id: WaypointId::new(record.waypoint_id.clone()),
+        file_name: record.file_name.clone(),
+        content_type: record.content_type.clone(),
+        size_bytes: record.size_bytes.max(0) as u64,
+        object_key: record.object_key.clone(),
+        threat_level,
+        notes: record.notes.clone(),
+        uploaded_by: record.uploaded_by.clone(),
+        created_at: record.created_at,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_db::{DbConfig, SqliteStore};
+
+    #[tokio::test]
+    async fn test_upload_download_delete() {
+        let dir = std::env::temp_dir().join(format!("attachments-{}", Uuid::new_v4()));
+        let config = AttachmentConfig { dir: dir.clone(), ..Default::default() };
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        let service = AttachmentService::new(config.clone(), Some(db.clone()));
+        let mission_id = MissionId::new();
+        let new = NewAttachment {
+            mission_id: mission_id.clone(),
+            waypoint_id: WaypointId::new("WP03"),
+            file_name: "bridge.jpg".into(),
+            content_type: "image/jpeg".into(),
+            threat_level: Some(ThreatLevel::High),
+            notes: Some("Checkpoint manned at dusk".into()),
+            uploaded_by: None,
+        };
+
+        let now = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let attachment = service.upload(new, b"jpeg bytes", now).await.unwrap();
+        assert_eq!(attachment.size_bytes, 10);
+        let (_, content) = service.download(&attachment.id).await.unwrap().unwrap();
+        assert_eq!(content, b"jpeg bytes");
+
+        // Metadata survives a restart
+        let reloaded = AttachmentService::new(config, Some(db));
+        reloaded.load().await.unwrap();
+        assert_eq!(reloaded.list(&mission_id, Some(&WaypointId::new("WP03"))), vec![attachment.clone()]);
+        assert!(reloaded.list(&mission_id, Some(&WaypointId::new("WP04"))).is_empty());
+
+        assert_eq!(
+            serving_headers(&attachment),
+            ("image/jpeg".to_string(), "inline; filename=\"bridge.jpg\"".to_string())
+        );
+        let html = WaypointAttachment {
+            file_name: "brief\".html".into(),
+            content_type: "text/html; charset=utf-8".into(),
+            ..attachment.clone()
+        };
+        assert_eq!(
+            serving_headers(&html),
+            ("application/octet-stream".to_string(), "attachment; filename=\"brief_.html\"".to_string())
+        );
+
+        assert!(reloaded.delete(&attachment.id).await.unwrap().is_some());
+        assert!(reloaded.download(&attachment.id).await.unwrap().is_none());
+        assert!(!dir.join(&attachment.object_key).exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
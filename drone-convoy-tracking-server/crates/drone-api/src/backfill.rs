@@ -0,0 +1,844 @@
+//! Batch import of historical telemetry
+//!
+//! Import jobs read CSV or JSON-lines files from the import directory, map
+//! source columns onto telemetry fields, validate and normalize every row and
+//! write the rows to the database in batches. After each batch the position
+//! reached in the file is saved to a checkpoint, so a job started again for
+//! the same file and settings carries on from there instead of starting over.
+
+use crate::export::ExportRow;
+use crate::validation::{Validate, ValidationErrors, MAX_ID_LEN};
+use drone_core::{GeoPosition, Telemetry, TelemetryLimits};
+use drone_db::{DbClient, TelemetryRecord};
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Rows written per database batch unless the request says otherwise
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Largest batch a request may ask for
+const MAX_BATCH_SIZE: usize = 10_000;
+
+/// Rejected rows reported individually on a job
+const MAX_ROW_ERRORS: usize = 20;
+
+/// Directory under the import directory holding checkpoints
+const CHECKPOINT_DIR: &str = ".checkpoints";
+
+/// Finished jobs kept for the status endpoints; the oldest go first
+const MAX_FINISHED_JOBS: usize = 100;
+
+/// Readings stored as non-null columns; a row without one is rejected
+/// rather than filled with a made-up value
+const REQUIRED_FIELDS: [&str; 6] = ["altitude", "heading", "speed", "battery_level", "fuel_level", "system_health"];
+
+// ============================================================================
+// REQUEST & JOB TYPES
+// ============================================================================
+
+/// Source file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// Header line followed by one record per line
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// How source timestamps are written
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    #[default]
+    Rfc3339,
+    UnixSeconds,
+    UnixMillis,
+    /// chrono format string for UTC times without an offset
+    Pattern(String),
+}
+
+/// Unit of the source speed column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedUnit {
+    #[default]
+    Kmh,
+    MetersPerSecond,
+    Knots,
+}
+
+impl SpeedUnit {
+    fn to_kmh(self, value: f64) -> f64 {
+        match self {
+            Self::Kmh => value,
+            Self::MetersPerSecond => value * 3.6,
+            Self::Knots => value * 1.852,
+        }
+    }
+}
+
+/// Unit of the source altitude column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AltitudeUnit {
+    #[default]
+    Meters,
+    Feet,
+}
+
+impl AltitudeUnit {
+    fn to_meters(self, value: f64) -> f64 {
+        match self {
+            Self::Meters => value,
+            Self::Feet => value * 0.3048,
+        }
+    }
+}
+
+/// Where telemetry fields are found in the source and how they are written
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldMapping {
+    /// Source column (CSV) or key path (JSONL, dotted) by telemetry field;
+    /// unmapped fields are read from a column of the same name
+    pub columns: HashMap<String, String>,
+    pub timestamp_format: TimestampFormat,
+    pub speed_unit: SpeedUnit,
+    pub altitude_unit: AltitudeUnit,
+}
+
+impl FieldMapping {
+    fn column<'a>(&'a self, field: &'a str) -> &'a str {
+        self.columns.get(field).map(String::as_str).unwrap_or(field)
+    }
+}
+
+/// Everything that decides how a file is read; a checkpoint only applies
+/// to a job with the same settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportSettings {
+    pub format: ImportFormat,
+    #[serde(default)]
+    pub mapping: FieldMapping,
+    /// CSV field separator
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// Drone for rows without a drone ID
+    #[serde(default)]
+    pub drone_id: Option<String>,
+    /// Mission for rows without a mission ID
+    #[serde(default)]
+    pub mission_id: Option<Uuid>,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Import request body
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportRequest {
+    /// File path relative to the import directory
+    pub file: String,
+    #[serde(flatten)]
+    pub settings: ImportSettings,
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    /// Continue from the file's checkpoint when there is one
+    #[serde(default = "default_true")]
+    pub resume: bool,
+}
+
+impl Validate for ImportRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if self.file.trim().is_empty() {
+            errors.add("file", "must not be empty");
+        } else if !Path::new(&self.file)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            errors.add("file", "must be a relative path inside the import directory");
+        }
+
+        for (field, column) in &self.settings.mapping.columns {
+            if !TelemetryRecord::COLUMNS.contains(&field.as_str()) {
+                errors.add(format!("mapping.columns.{}", field), "is not a telemetry field");
+            }
+            errors.check_len(&format!("mapping.columns.{}", field), column, MAX_ID_LEN);
+        }
+        if let TimestampFormat::Pattern(pattern) = &self.settings.mapping.timestamp_format {
+            errors.check_len("mapping.timestamp_format", pattern, MAX_ID_LEN);
+        }
+        if matches!(self.settings.delimiter, '"' | '\n' | '\r') {
+            errors.add("delimiter", "must not be a quote or line break");
+        }
+        if let Some(drone_id) = &self.settings.drone_id {
+            errors.check_len("drone_id", drone_id, MAX_ID_LEN);
+        }
+        if let Some(size) = self.batch_size {
+            if !(1..=MAX_BATCH_SIZE).contains(&size) {
+                errors.add("batch_size", format!("must be between 1 and {}", MAX_BATCH_SIZE));
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+/// Import job lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A rejected source row
+#[derive(Debug, Clone, Serialize)]
+pub struct RowError {
+    /// 1-based line number in the file
+    pub line: u64,
+    pub message: String,
+}
+
+/// Background import job
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub status: ImportStatus,
+    pub file: String,
+    pub format: ImportFormat,
+    pub bytes_total: u64,
+    pub bytes_read: u64,
+    pub percent: f64,
+    /// Non-empty data lines read, including those before a resumed checkpoint
+    pub rows_read: u64,
+    pub imported: u64,
+    pub rejected: u64,
+    /// Line the job continued after, when it resumed from a checkpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumed_at_line: Option<u64>,
+    /// The first rejected rows
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub row_errors: Vec<RowError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Saved progress through a source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Checkpoint {
+    settings: ImportSettings,
+    /// Size and modification time of the file when the checkpoint was taken
+    file_len: u64,
+    modified: Option<DateTime<Utc>>,
+    /// Byte offset and line number after the last committed row
+    offset: u64,
+    line: u64,
+    rows_read: u64,
+    imported: u64,
+    rejected: u64,
+}
+
+// ============================================================================
+// IMPORT MANAGER
+// ============================================================================
+
+/// Tracks import jobs and owns the import directory
+pub struct ImportManager {
+    dir: PathBuf,
+    jobs: DashMap<Uuid, ImportJob>,
+    /// Pending or running job by file; claimed atomically in `create`
+    active: DashMap<String, Uuid>,
+}
+
+impl ImportManager {
+    /// Create a manager reading source files from `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            jobs: DashMap::new(),
+            active: DashMap::new(),
+        }
+    }
+
+    /// Path of a source file named in a request
+    pub fn source_path(&self, file: &str) -> PathBuf {
+        self.dir.join(file)
+    }
+
+    /// Register a new pending job; fails with the active job's ID while
+    /// another job reads the file
+    pub fn create(&self, request: &ImportRequest) -> Result<ImportJob, Uuid> {
+        let id = match self.active.entry(request.file.clone()) {
+            Entry::Occupied(active) => return Err(*active.get()),
+            Entry::Vacant(slot) => *slot.insert(Uuid::new_v4()),
+        };
+        self.evict_finished();
+
+        let job = ImportJob {
+            id,
+            status: ImportStatus::Pending,
+            file: request.file.clone(),
+            format: request.settings.format,
+            bytes_total: 0,
+            bytes_read: 0,
+            percent: 0.0,
+            rows_read: 0,
+            imported: 0,
+            rejected: 0,
+            resumed_at_line: None,
+            row_errors: Vec::new(),
+            error: None,
+            created_at: Utc::now(),
+            completed_at: None,
+        };
+        self.jobs.insert(job.id, job.clone());
+        Ok(job)
+    }
+
+    /// Get a job by ID
+    pub fn get(&self, id: &Uuid) -> Option<ImportJob> {
+        self.jobs.get(id).map(|j| j.clone())
+    }
+
+    /// All jobs, newest first
+    pub fn list(&self) -> Vec<ImportJob> {
+        let mut jobs: Vec<ImportJob> = self.jobs.iter().map(|j| j.clone()).collect();
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+        jobs
+    }
+
+    fn checkpoint_path(&self, file: &str) -> PathBuf {
+        let name: Vec<_> = Path::new(file)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        self.dir.join(CHECKPOINT_DIR).join(format!("{}.json", name.join("__")))
+    }
+
+    /// Drop the oldest finished jobs beyond `MAX_FINISHED_JOBS`
+    fn evict_finished(&self) {
+        let mut finished: Vec<(DateTime<Utc>, Uuid)> = self
+            .jobs
+            .iter()
+            .filter_map(|job| job.completed_at.map(|at| (at, job.id)))
+            .collect();
+        if finished.len() <= MAX_FINISHED_JOBS {
+            return;
+        }
+        finished.sort();
+        for (_, id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+            self.jobs.remove(id);
+        }
+    }
+
+    /// Release the job's claim on its file
+    fn finish(&self, id: &Uuid) {
+        let file = self.jobs.get(id).map(|job| job.file.clone());
+        if let Some(file) = file {
+            self.active.remove_if(&file, |_, active| active == id);
+        }
+    }
+
+    fn update(&self, id: &Uuid, f: impl FnOnce(&mut ImportJob)) {
+        if let Some(mut job) = self.jobs.get_mut(id) {
+            f(&mut job);
+        }
+    }
+
+    fn mark_completed(&self, id: &Uuid) {
+        self.update(id, |job| {
+            job.status = ImportStatus::Completed;
+            job.percent = 100.0;
+            job.completed_at = Some(Utc::now());
+        });
+        self.finish(id);
+    }
+
+    fn mark_failed(&self, id: &Uuid, error: String) {
+        self.update(id, |job| {
+            job.status = ImportStatus::Failed;
+            job.error = Some(error);
+            job.completed_at = Some(Utc::now());
+        });
+        self.finish(id);
+    }
+}
+
+/// Run an import job to completion, recording the outcome on the manager
+pub async fn run_job(manager: Arc<ImportManager>, db: Arc<DbClient>, job_id: Uuid, request: ImportRequest) {
+    manager.update(&job_id, |job| job.status = ImportStatus::Running);
+
+    match import_file(&manager, &db, job_id, &request).await {
+        Ok(()) => {
+            if let Some(job) = manager.get(&job_id) {
+                info!(
+                    "Import {} of {} completed: {} rows imported, {} rejected",
+                    job_id, request.file, job.imported, job.rejected
+                );
+            }
+            manager.mark_completed(&job_id);
+        }
+        Err(e) => {
+            warn!("Import {} of {} failed: {:#}", job_id, request.file, e);
+            manager.mark_failed(&job_id, format!("{:#}", e));
+        }
+    }
+}
+
+async fn import_file(
+    manager: &ImportManager,
+    db: &DbClient,
+    job_id: Uuid,
+    request: &ImportRequest,
+) -> anyhow::Result<()> {
+    let settings = &request.settings;
+    let path = manager.source_path(&request.file);
+    let checkpoint_path = manager.checkpoint_path(&request.file);
+    let batch_size = request.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+
+    let file = tokio::fs::File::open(&path).await?;
+    let metadata = file.metadata().await?;
+    let file_len = metadata.len();
+    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+    let mut reader = BufReader::new(file);
+    let mut buf = Vec::new();
+
+    let mut checkpoint = Checkpoint {
+        settings: settings.clone(),
+        file_len,
+        modified,
+        offset: 0,
+        line: 0,
+        rows_read: 0,
+        imported: 0,
+        rejected: 0,
+    };
+
+    let header = match settings.format {
+        ImportFormat::Csv => {
+            checkpoint.offset = reader.read_until(b'\n', &mut buf).await? as u64;
+            checkpoint.line = 1;
+            let line = std::str::from_utf8(&buf)?.trim_start_matches('\u{feff}');
+            let columns = split_csv_line(line.trim_end_matches(['\r', '\n']), settings.delimiter)
+                .map_err(|e| anyhow::anyhow!("header: {}", e))?;
+            Some(columns.into_iter().enumerate().map(|(i, c)| (c.trim().to_string(), i)).collect())
+        }
+        ImportFormat::Jsonl => None,
+    };
+
+    let saved = if request.resume { load_checkpoint(&checkpoint_path).await } else { None };
+    let resumed_at_line = match saved {
+        Some(saved)
+            if saved.settings == *settings
+                && saved.file_len == file_len
+                && saved.modified == modified
+                && saved.offset >= checkpoint.offset =>
+        {
+            reader.seek(SeekFrom::Start(saved.offset)).await?;
+            info!("Import {} resuming {} after line {}", job_id, request.file, saved.line);
+            let line = saved.line;
+            checkpoint = saved;
+            Some(line)
+        }
+        _ => None,
+    };
+
+    manager.update(&job_id, |job| {
+        job.bytes_total = file_len;
+        job.resumed_at_line = resumed_at_line;
+    });
+
+    let limits = TelemetryLimits::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut offset = checkpoint.offset;
+    let mut line = checkpoint.line;
+    let mut rows_read = checkpoint.rows_read;
+    let mut rejected = checkpoint.rejected;
+    let mut row_errors = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf).await?;
+        if read > 0 {
+            offset += read as u64;
+            line += 1;
+            let parsed = std::str::from_utf8(&buf)
+                .map_err(|_| "line is not valid UTF-8".to_string())
+                .map(|text| text.trim_end_matches(['\r', '\n']))
+                .and_then(|text| {
+                    if text.trim().is_empty() {
+                        return Ok(None);
+                    }
+                    let row = match &header {
+                        Some(header) => SourceRow::Csv {
+                            header,
+                            fields: split_csv_line(text, settings.delimiter)?,
+                        },
+                        None => SourceRow::Json(serde_json::from_str(text).map_err(|e| e.to_string())?),
+                    };
+                    normalize(&row, settings, &limits, Utc::now()).map(Some)
+                });
+            match parsed {
+                Ok(None) => continue,
+                Ok(Some(record)) => {
+                    rows_read += 1;
+                    batch.push(record);
+                }
+                Err(message) => {
+                    rows_read += 1;
+                    rejected += 1;
+                    if row_errors.len() < MAX_ROW_ERRORS {
+                        row_errors.push(RowError { line, message });
+                    }
+                }
+            }
+        }
+
+        // Commit the batch, then move the checkpoint past it
+        let done = read == 0;
+        if batch.len() >= batch_size || done {
+            let written = batch.len() as u64;
+            if written > 0 {
+                db.telemetry().insert_records(std::mem::take(&mut batch)).await?;
+            }
+            checkpoint.offset = offset;
+            checkpoint.line = line;
+            checkpoint.rows_read = rows_read;
+            checkpoint.imported += written;
+            checkpoint.rejected = rejected;
+            if !done {
+                save_checkpoint(&checkpoint_path, &checkpoint).await?;
+            }
+            let errors = std::mem::take(&mut row_errors);
+            manager.update(&job_id, |job| {
+                job.bytes_read = offset;
+                job.percent = if file_len > 0 { offset as f64 * 100.0 / file_len as f64 } else { 100.0 };
+                job.rows_read = rows_read;
+                job.imported = checkpoint.imported;
+                job.rejected = rejected;
+                let room = MAX_ROW_ERRORS.saturating_sub(job.row_errors.len());
+                job.row_errors.extend(errors.into_iter().take(room));
+            });
+        }
+        if done {
+            break;
+        }
+    }
+
+    // A finished file needs no checkpoint
+    match tokio::fs::remove_file(&checkpoint_path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            warn!("Failed to remove import checkpoint {}: {}", checkpoint_path.display(), e)
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn load_checkpoint(path: &Path) -> Option<Checkpoint> {
+    let text = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&text)
+        .map_err(|e| warn!("Ignoring unreadable import checkpoint {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Replace the checkpoint file so a crash never leaves half of one
+async fn save_checkpoint(path: &Path, checkpoint: &Checkpoint) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(checkpoint)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+// ============================================================================
+// ROW PARSING
+// ============================================================================
+
+/// One source record
+enum SourceRow<'a> {
+    Csv {
+        header: &'a HashMap<String, usize>,
+        fields: Vec<String>,
+    },
+    Json(serde_json::Value),
+}
+
+impl SourceRow<'_> {
+    /// Non-empty value of a column or dotted key path
+    fn get(&self, column: &str) -> Option<String> {
+        let value = match self {
+            Self::Csv { header, fields } => fields.get(*header.get(column)?)?.trim().to_string(),
+            Self::Json(value) => {
+                let value = column.split('.').try_fold(value, |v, key| v.get(key))?;
+                match value {
+                    serde_json::Value::Null => return None,
+                    serde_json::Value::String(s) => s.trim().to_string(),
+                    other => other.to_string(),
+                }
+            }
+        };
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+/// Split one CSV line into fields, honouring double-quoted fields
+fn split_csv_line(line: &str, delimiter: char) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".into());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Build a telemetry record from a source row, or say why the row is rejected
+///
+/// Units are converted, percentages above 100 and out-of-range speed,
+/// heading and temperature are clamped the same way live telemetry is.
+/// Rows missing a `REQUIRED_FIELDS` reading are rejected; temperature and
+/// signal strength are optional.
+fn normalize(
+    row: &SourceRow<'_>,
+    settings: &ImportSettings,
+    limits: &TelemetryLimits,
+    now: DateTime<Utc>,
+) -> Result<TelemetryRecord, String> {
+    let mapping = &settings.mapping;
+    let get = |field: &str| row.get(mapping.column(field));
+    let number = |field: &str| -> Result<Option<f64>, String> {
+        get(field)
+            .map(|v| match v.parse::<f64>() {
+                Ok(n) if n.is_finite() => Ok(n),
+                _ => Err(format!("{}: '{}' is not a number", field, v)),
+            })
+            .transpose()
+    };
+    let required = |field: &str| number(field)?.ok_or_else(|| format!("{}: missing", field));
+    let percent = |field: &str| -> Result<Option<u8>, String> {
+        match number(field)? {
+            Some(n) if n < 0.0 => Err(format!("{}: {} is negative", field, n)),
+            n => Ok(n.map(|n| n.min(100.0).round() as u8)),
+        }
+    };
+
+    let drone_id = get("drone_id")
+        .or_else(|| settings.drone_id.clone())
+        .ok_or("drone_id: missing")?;
+    if drone_id.chars().count() > MAX_ID_LEN {
+        return Err(format!("drone_id: longer than {} characters", MAX_ID_LEN));
+    }
+
+    let raw_timestamp = get("timestamp").ok_or("timestamp: missing")?;
+    let timestamp = parse_timestamp(&raw_timestamp, &mapping.timestamp_format)
+        .ok_or_else(|| format!("timestamp: cannot read '{}'", raw_timestamp))?;
+    if timestamp > now + chrono::Duration::seconds(limits.max_clock_skew_secs) {
+        return Err(format!("timestamp: {} is in the future", timestamp.to_rfc3339()));
+    }
+
+    if let Some(field) = REQUIRED_FIELDS.iter().find(|field| get(field).is_none()) {
+        return Err(format!("{}: missing", field));
+    }
+    let altitude = mapping.altitude_unit.to_meters(required("altitude")?);
+    let position = GeoPosition::new(required("latitude")?, required("longitude")?, altitude);
+    if !position.is_valid() {
+        return Err(format!(
+            "position: latitude={} longitude={} out of range",
+            position.latitude, position.longitude
+        ));
+    }
+
+    let required_percent = |field: &str| percent(field)?.ok_or_else(|| format!("{}: missing", field));
+    let temperature = number("temperature")?;
+    let signal_strength = percent("signal_strength")?;
+    let defaults = Telemetry::default();
+    let mut telemetry = Telemetry {
+        battery_level: required_percent("battery_level")?,
+        fuel_level: required_percent("fuel_level")?,
+        system_health: required_percent("system_health")?,
+        speed: mapping.speed_unit.to_kmh(required("speed")?),
+        heading: required("heading")?,
+        signal_strength: signal_strength.unwrap_or(defaults.signal_strength),
+        temperature: temperature.unwrap_or(defaults.temperature),
+        timestamp,
+        sequence: None,
+        gimbal: None,
+    };
+    telemetry.sanitize_at(limits, now).map_err(|e| e.to_string())?;
+
+    let armed = get("armed")
+        .map(|v| match v.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok(true),
+            "false" | "0" | "no" => Ok(false),
+            _ => Err(format!("armed: '{}' is not a boolean", v)),
+        })
+        .transpose()?;
+    let mission_id = match get("mission_id") {
+        Some(v) => Some(Uuid::parse_str(&v).map_err(|_| format!("mission_id: '{}' is not a UUID", v))?),
+        None => settings.mission_id,
+    };
+
+    Ok(TelemetryRecord {
+        drone_id,
+        timestamp: telemetry.timestamp,
+        latitude: position.latitude,
+        longitude: position.longitude,
+        altitude: position.altitude,
+        heading: telemetry.heading,
+        speed: telemetry.speed,
+        battery_level: telemetry.battery_level as i32,
+        fuel_level: telemetry.fuel_level as i32,
+        system_health: telemetry.system_health as i32,
+        status: get("status").map(|s| s.to_uppercase()),
+        armed,
+        temperature: temperature.map(|_| telemetry.temperature),
+        signal_strength: signal_strength.map(|_| telemetry.signal_strength as i32),
+        mission_id,
+        gimbal: None,
+    })
+}
+
+fn parse_timestamp(value: &str, format: &TimestampFormat) -> Option<DateTime<Utc>> {
+    match format {
+        TimestampFormat::Rfc3339 => DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc)),
+        TimestampFormat::UnixSeconds => {
+            let seconds: f64 = value.parse().ok()?;
+            Utc.timestamp_millis_opt((seconds * 1000.0).round() as i64).single()
+        }
+        TimestampFormat::UnixMillis => Utc.timestamp_millis_opt(value.parse().ok()?).single(),
+        TimestampFormat::Pattern(pattern) => {
+            NaiveDateTime::parse_from_str(value, pattern).ok().map(|t| t.and_utc())
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::DroneId;
+    use drone_db::{DbConfig, SqliteStore};
+
+    #[tokio::test]
+    async fn test_import_validates_and_resumes_from_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("drone-import-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rows = [
+            "1700000000000,REAPER-01,34.50,69.20,10000,50,82,90,270,95",
+            "1700000001000,REAPER-01,34.51,69.21,10000,50,81,90,270,95",
+            "1700000002000,REAPER-01,134.0,69.22,10000,50,80,90,270,95",
+            "1700000003000,REAPER-01,34.53,69.23,10000,50,\"79\",90,270,95",
+            "1700000004000,REAPER-01,34.54,69.24,10000,50,78,90,270,95",
+            "1700000005000,REAPER-01,34.55,69.25,10000,50,77,90,,95",
+        ];
+        let header = "time,tail,lat,lon,alt_ft,spd_ms,batt,fuel,heading,system_health\n";
+        std::fs::write(dir.join("legacy.csv"), format!("{}{}\n", header, rows.join("\n"))).unwrap();
+
+        let manager = Arc::new(ImportManager::new(&dir));
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        let request: ImportRequest = serde_json::from_value(serde_json::json!({
+            "file": "legacy.csv",
+            "format": "csv",
+            "batch_size": 2,
+            "mapping": {
+                "columns": {
+                    "timestamp": "time", "drone_id": "tail", "latitude": "lat", "longitude": "lon",
+                    "altitude": "alt_ft", "speed": "spd_ms", "battery_level": "batt", "fuel_level": "fuel",
+                },
+                "timestamp_format": "unix_millis",
+                "speed_unit": "meters_per_second",
+                "altitude_unit": "feet",
+            },
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+
+        // A job interrupted after the first two rows left a checkpoint
+        let job = manager.create(&request).unwrap();
+        assert!(manager.create(&request).is_err());
+        let header_len = header.len() as u64;
+        let metadata = std::fs::metadata(dir.join("legacy.csv")).unwrap();
+        let checkpoint = Checkpoint {
+            settings: request.settings.clone(),
+            file_len: metadata.len(),
+            modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+            offset: header_len + rows[0].len() as u64 + rows[1].len() as u64 + 2,
+            line: 3,
+            rows_read: 2,
+            imported: 2,
+            rejected: 0,
+        };
+        save_checkpoint(&manager.checkpoint_path("legacy.csv"), &checkpoint).await.unwrap();
+
+        run_job(manager.clone(), db.clone(), job.id, request).await;
+        let job = manager.get(&job.id).unwrap();
+        assert_eq!(job.status, ImportStatus::Completed, "{:?}", job.error);
+        assert_eq!(job.resumed_at_line, Some(3));
+        assert_eq!((job.rows_read, job.imported, job.rejected), (6, 4, 2));
+        assert_eq!(job.row_errors[0].line, 4);
+        assert_eq!(job.row_errors[1].message, "heading: missing");
+        assert_eq!(job.percent, 100.0);
+        assert!(!manager.checkpoint_path("legacy.csv").exists());
+        // The finished job no longer holds the file
+        let request: ImportRequest = serde_json::from_value(serde_json::json!({"file": "legacy.csv", "format": "csv"})).unwrap();
+        assert!(manager.create(&request).is_ok());
+
+        // Only the rows after the checkpoint were written, normalized
+        let history = db.telemetry().get_history(&DroneId::new("REAPER-01"), 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        let (position, telemetry) = &history[0];
+        assert!((position.altitude - 3048.0).abs() < 1e-6);
+        assert!((telemetry.speed - 180.0).abs() < 1e-6);
+        assert_eq!(telemetry.battery_level, 78);
+        assert_eq!(history[1].1.battery_level, 79);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
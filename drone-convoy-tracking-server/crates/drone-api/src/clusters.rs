@@ -0,0 +1,293 @@
+//! Drone clustering for the map view
+//!
+//! Drones are bucketed into geohash cells at every precision level. Each
+//! position or status change moves a single drone between cells, so serving
+//! clusters for any zoom level is a read of the precomputed aggregates.
+
+use drone_core::{DroneId, DroneStatus, Event, EventPayload, EventType, GeoPosition};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Finest geohash precision indexed (~38 m x 19 m cells)
+const MAX_PRECISION: usize = 8;
+
+/// Clusters up to this size list their member drones
+const MEMBER_LIST_LIMIT: usize = 5;
+
+/// Zoom level used when the client does not pass one
+pub const DEFAULT_CLUSTER_ZOOM: u8 = 10;
+
+/// Highest map zoom level accepted
+pub const MAX_ZOOM: u8 = 22;
+
+/// Statuses in order of operator attention; a cluster reports the first present
+const STATUS_PRIORITY: [DroneStatus; 8] = [
+    DroneStatus::Offline,
+    DroneStatus::Unreachable,
+    DroneStatus::Rtb,
+    DroneStatus::Engaged,
+    DroneStatus::Maintenance,
+    DroneStatus::Loitering,
+    DroneStatus::Moving,
+    DroneStatus::Standby,
+];
+
+fn priority(status: DroneStatus) -> usize {
+    STATUS_PRIORITY
+        .iter()
+        .position(|s| *s == status)
+        .unwrap_or(STATUS_PRIORITY.len() - 1)
+}
+
+/// Geohash precision for a web map zoom level (0 = whole world)
+pub fn precision_for_zoom(zoom: u8) -> usize {
+    match zoom {
+        0..=2 => 1,
+        3..=4 => 2,
+        5..=7 => 3,
+        8..=9 => 4,
+        10..=12 => 5,
+        13..=14 => 6,
+        15..=17 => 7,
+        _ => MAX_PRECISION,
+    }
+}
+
+/// Running aggregate for one geohash cell
+#[derive(Debug, Default)]
+struct Cell {
+    lat_sum: f64,
+    lng_sum: f64,
+    status_counts: [usize; STATUS_PRIORITY.len()],
+    members: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+struct IndexedDrone {
+    position: Option<GeoPosition>,
+    /// Geohash at `MAX_PRECISION`; coarser cells are its prefixes
+    geohash: String,
+    status: DroneStatus,
+}
+
+#[derive(Debug, Default)]
+struct IndexState {
+    drones: HashMap<DroneId, IndexedDrone>,
+    /// Cells per precision level (index 0 = precision 1)
+    levels: Vec<HashMap<String, Cell>>,
+}
+
+impl IndexState {
+    fn add(&mut self, id: &DroneId, drone: &IndexedDrone) {
+        let Some(position) = drone.position else { return };
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            let cell = level.entry(drone.geohash[..=i].to_string()).or_default();
+            cell.lat_sum += position.latitude;
+            cell.lng_sum += position.longitude;
+            cell.status_counts[priority(drone.status)] += 1;
+            cell.members.insert(id.0.clone());
+        }
+    }
+
+    fn remove(&mut self, id: &DroneId, drone: &IndexedDrone) {
+        let Some(position) = drone.position else { return };
+        for (i, level) in self.levels.iter_mut().enumerate() {
+            let key = &drone.geohash[..=i];
+            let Some(cell) = level.get_mut(key) else { continue };
+            cell.members.remove(&id.0);
+            if cell.members.is_empty() {
+                level.remove(key);
+                continue;
+            }
+            cell.lat_sum -= position.latitude;
+            cell.lng_sum -= position.longitude;
+            cell.status_counts[priority(drone.status)] -= 1;
+        }
+    }
+
+    /// Replace a drone's contribution with an updated copy
+    fn update(&mut self, id: &DroneId, f: impl FnOnce(&mut IndexedDrone)) {
+        let mut drone = self.drones.remove(id).unwrap_or(IndexedDrone {
+            position: None,
+            geohash: String::new(),
+            status: DroneStatus::default(),
+        });
+        self.remove(id, &drone);
+        f(&mut drone);
+        self.add(id, &drone);
+        self.drones.insert(id.clone(), drone);
+    }
+}
+
+/// A group of drones sharing a geohash cell
+#[derive(Debug, Clone, Serialize)]
+pub struct DroneCluster {
+    pub geohash: String,
+    /// Centroid of member positions
+    pub latitude: f64,
+    pub longitude: f64,
+    pub count: usize,
+    /// Most attention-worthy status among members
+    pub status: String,
+    pub status_counts: BTreeMap<String, usize>,
+    /// Member drone IDs, only for small clusters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drone_ids: Option<Vec<String>>,
+}
+
+/// Clusters for one zoom level
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterView {
+    pub zoom: u8,
+    pub precision: usize,
+    pub total_drones: usize,
+    pub clusters: Vec<DroneCluster>,
+}
+
+/// Incrementally maintained geohash cluster index
+#[derive(Debug)]
+pub struct ClusterIndex {
+    state: RwLock<IndexState>,
+}
+
+impl Default for ClusterIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClusterIndex {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(IndexState {
+                drones: HashMap::new(),
+                levels: (0..MAX_PRECISION).map(|_| HashMap::new()).collect(),
+            }),
+        }
+    }
+
+    /// Move a drone to a new position
+    pub fn update_position(&self, drone_id: &DroneId, position: GeoPosition) {
+        self.state.write().update(drone_id, |drone| {
+            drone.geohash = position.geohash(MAX_PRECISION);
+            drone.position = Some(position);
+        });
+    }
+
+    /// Record a drone's status (drones without a position are not clustered yet)
+    pub fn update_status(&self, drone_id: &DroneId, status: DroneStatus) {
+        self.state.write().update(drone_id, |drone| drone.status = status);
+    }
+
+    /// Drop a drone from every cell
+    pub fn remove_drone(&self, drone_id: &DroneId) {
+        let mut state = self.state.write();
+        if let Some(drone) = state.drones.remove(drone_id) {
+            state.remove(drone_id, &drone);
+        }
+    }
+
+    /// Apply position and status changes and evictions from a tracker event
+    pub fn record_event(&self, event: &Event) {
+        match &event.payload {
+            EventPayload::DronePosition(e) => self.update_position(&e.drone_id, e.position),
+            EventPayload::DroneStatus(e) => self.update_status(&e.drone_id, e.new_status),
+            EventPayload::DroneConnection(e) if event.event_type == EventType::DroneEvicted => {
+                self.remove_drone(&e.drone_id)
+            }
+            _ => {}
+        }
+    }
+
+    /// Clusters for a map zoom level, largest first
+    pub fn clusters(&self, zoom: u8) -> ClusterView {
+        let precision = precision_for_zoom(zoom);
+        let state = self.state.read();
+
+        let mut clusters: Vec<DroneCluster> = state.levels[precision - 1]
+            .iter()
+            .map(|(geohash, cell)| {
+                let count = cell.members.len();
+                let status_counts = STATUS_PRIORITY
+                    .iter()
+                    .zip(cell.status_counts)
+                    .filter(|(_, n)| *n > 0)
+                    .map(|(status, n)| (status.to_string(), n))
+                    .collect();
+                let status = STATUS_PRIORITY
+                    .iter()
+                    .zip(cell.status_counts)
+                    .find(|(_, n)| *n > 0)
+                    .map(|(status, _)| status.to_string())
+                    .unwrap_or_default();
+
+                DroneCluster {
+                    geohash: geohash.clone(),
+                    latitude: cell.lat_sum / count as f64,
+                    longitude: cell.lng_sum / count as f64,
+                    count,
+                    status,
+                    status_counts,
+                    drone_ids: (count <= MEMBER_LIST_LIMIT)
+                        .then(|| cell.members.iter().cloned().collect()),
+                }
+            })
+            .collect();
+        clusters.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.geohash.cmp(&b.geohash)));
+
+        ClusterView {
+            zoom,
+            precision,
+            total_drones: clusters.iter().map(|c| c.count).sum(),
+            clusters,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clusters_follow_position_and_status_changes() {
+        let index = ClusterIndex::new();
+        let kabul = GeoPosition::new(34.5553, 69.2075, 3000.0);
+        let kandahar = GeoPosition::new(31.6133, 65.7101, 3000.0);
+
+        for i in 1..=3 {
+            index.update_position(&DroneId::new(format!("REAPER-{:02}", i)), kabul);
+        }
+        index.update_position(&DroneId::new("REAPER-04"), kandahar);
+        index.update_status(&DroneId::new("REAPER-02"), DroneStatus::Rtb);
+
+        // Regional zoom keeps the two cities in separate cells
+        let view = index.clusters(5);
+        assert_eq!(view.total_drones, 4);
+        assert_eq!(view.clusters.len(), 2);
+        let kabul_cluster = &view.clusters[0];
+        assert_eq!(kabul_cluster.count, 3);
+        assert_eq!(kabul_cluster.status, "RTB");
+        assert_eq!(kabul_cluster.status_counts["STANDBY"], 2);
+        assert!((kabul_cluster.latitude - 34.5553).abs() < 1e-9);
+
+        // Moving a drone updates both cells incrementally
+        index.update_position(&DroneId::new("REAPER-02"), kandahar);
+        let view = index.clusters(5);
+        assert_eq!(view.clusters[0].count, 2);
+        assert_eq!(view.clusters[1].count, 2);
+        let kandahar_cluster = view.clusters.iter().find(|c| c.status == "RTB").unwrap();
+        assert_eq!(
+            kandahar_cluster.drone_ids.as_deref(),
+            Some(&["REAPER-02".to_string(), "REAPER-04".to_string()][..])
+        );
+
+        // World view collapses everything into one cluster
+        assert_eq!(index.clusters(0).clusters.len(), 1);
+    }
+}
@@ -1,7 +1,25 @@
 //! API server configuration
 
-use drone_db::DbConfig;
+use crate::attachments::AttachmentConfig;
+use crate::coverage::CoverageConfig;
+use crate::handoff::HandoffConfig;
+use crate::leadership::LeadershipConfig;
+use crate::packages::PackageConfig;
+use crate::transport::TransportConfig;
+use crate::presentation::PresentationRules;
+use crate::push::PushConfig;
+use crate::simulation::SimulationConfig;
+use crate::tiles::TileConfig;
+use crate::validation::DEFAULT_MAX_BODY_BYTES;
+use drone_core::TenantId;
+use drone_db::{DbConfig, RetentionConfig};
+use drone_tracker::{
+    AltitudeConfig, ArrivalConfig, CheckpointConfig, CvPublisherConfig, DriftConfig, EventRetention, LosConfig, MissionSyncConfig, ProximityConfig, WindConfig,
+    WriteBreakerConfig,
+};
+use drone_websocket::{CompressionConfig, PresenceConfig, RateLimitConfig, SocketIoConfig};
 use serde::Deserialize;
+use std::path::PathBuf;
 
 /// API server configuration
 #[derive(Debug, Clone, Deserialize)]
@@ -15,9 +33,115 @@ pub struct ApiConfig {
     /// Enable CORS for all origins (development)
     pub cors_permissive: bool,
     /// Enable CV tracking
+    #[allow(dead_code)] // CV engine disabled for macOS build
     pub cv_enabled: bool,
     /// Simulation mode (generate fake data)
     pub simulation_mode: bool,
+    /// Simulation seed, tick and event recording
+    #[serde(skip)]
+    pub simulation: SimulationConfig,
+    /// Directory for telemetry export files
+    pub export_dir: PathBuf,
+    /// How long finished export jobs and their files are kept (seconds)
+    pub export_ttl_seconds: u64,
+    /// Directory holding historical telemetry files to import
+    pub import_dir: PathBuf,
+    /// Maximum request body size (bytes)
+    pub max_body_bytes: usize,
+    /// How long shutdown waits for WebSocket clients to disconnect (seconds)
+    pub ws_drain_seconds: u64,
+    /// WebSocket permessage-deflate settings
+    #[serde(skip)]
+    pub ws_compression: CompressionConfig,
+    /// Inbound WebSocket message limits and command quotas by role
+    #[serde(skip)]
+    pub ws_rate_limits: RateLimitConfig,
+    /// Socket.IO endpoint for legacy dashboards
+    #[serde(skip)]
+    pub ws_socketio: SocketIoConfig,
+    /// Operator IDs by API key for console presence
+    #[serde(skip)]
+    pub ws_presence: PresenceConfig,
+    /// Per-table retention periods and purge schedule
+    #[serde(skip)]
+    pub retention: RetentionConfig,
+    /// Age and count limits of the event replay history
+    #[serde(skip)]
+    pub event_retention: EventRetention,
+    /// Drone icon, color and blink rules for the map
+    #[serde(skip)]
+    pub presentation: PresentationRules,
+    /// CV result rate limit and persistence batching
+    #[serde(skip)]
+    pub cv_publisher: CvPublisherConfig,
+    /// CV calibration drift thresholds and auto-correction
+    #[serde(skip)]
+    pub cv_drift: DriftConfig,
+    /// Terrain line-of-sight prediction
+    #[serde(skip)]
+    pub los: LosConfig,
+    /// Predictive collision warnings
+    #[serde(skip)]
+    pub proximity: ProximityConfig,
+    /// Altitude band size and enforcement
+    #[serde(skip)]
+    pub altitude: AltitudeConfig,
+    /// Waypoint arrival and departure radii and dwell
+    #[serde(skip)]
+    pub arrival: ArrivalConfig,
+    /// Wind grid estimated from drone drift
+    #[serde(skip)]
+    pub wind: WindConfig,
+    /// Mission route sync to drone agents
+    #[serde(skip)]
+    pub mission_sync: MissionSyncConfig,
+    /// Checkpoint holds and acknowledgment escalation
+    #[serde(skip)]
+    pub checkpoint: CheckpointConfig,
+    /// Per-drone breakers for telemetry writes
+    #[serde(skip)]
+    pub write_breaker: WriteBreakerConfig,
+    /// FCM/APNs providers and notification templates
+    #[serde(skip)]
+    pub push: PushConfig,
+    /// Station identity and peer token for drone handoff
+    #[serde(skip)]
+    pub handoff: HandoffConfig,
+    /// Leader election between instances sharing a database
+    #[serde(skip)]
+    pub leadership: LeadershipConfig,
+    /// MAVLink socket and HTTP sidecar timeout for command transports
+    #[serde(skip)]
+    pub transport: TransportConfig,
+    /// Object store directory and upload limit for waypoint attachments
+    #[serde(skip)]
+    pub attachments: AttachmentConfig,
+    /// Grid precision and decay half-life of the coverage heatmap
+    #[serde(skip)]
+    pub coverage: CoverageConfig,
+    /// Mission package signing key and trusted signers
+    #[serde(skip)]
+    pub packages: PackageConfig,
+    /// Map tile upstream, cache directory and offline mode
+    #[serde(skip)]
+    pub tiles: TileConfig,
+    /// Tenant this configuration serves, set by `for_tenant`
+    #[serde(skip)]
+    pub tenant: Option<TenantId>,
+}
+
+/// Default WebSocket drain period on shutdown
+pub const DEFAULT_WS_DRAIN_SECONDS: u64 = 5;
+
+/// Default lifetime of a finished export
+pub const DEFAULT_EXPORT_TTL_SECONDS: u64 = 24 * 60 * 60;
+
+fn default_export_dir() -> PathBuf {
+    std::env::temp_dir().join("drone-convoy-exports")
+}
+
+fn default_import_dir() -> PathBuf {
+    std::env::temp_dir().join("drone-convoy-imports")
 }
 
 impl Default for ApiConfig {
@@ -29,6 +153,38 @@ impl Default for ApiConfig {
             cors_permissive: true,
             cv_enabled: true,
             simulation_mode: true,
+            simulation: SimulationConfig::default(),
+            export_dir: default_export_dir(),
+            export_ttl_seconds: DEFAULT_EXPORT_TTL_SECONDS,
+            import_dir: default_import_dir(),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            ws_drain_seconds: DEFAULT_WS_DRAIN_SECONDS,
+            ws_compression: CompressionConfig::default(),
+            ws_rate_limits: RateLimitConfig::default(),
+            ws_socketio: SocketIoConfig::default(),
+            ws_presence: PresenceConfig::default(),
+            retention: RetentionConfig::default(),
+            event_retention: EventRetention::default(),
+            presentation: PresentationRules::default(),
+            cv_publisher: CvPublisherConfig::default(),
+            cv_drift: DriftConfig::default(),
+            los: LosConfig::default(),
+            proximity: ProximityConfig::default(),
+            altitude: AltitudeConfig::default(),
+            arrival: ArrivalConfig::default(),
+            wind: WindConfig::default(),
+            mission_sync: MissionSyncConfig::default(),
+            checkpoint: CheckpointConfig::default(),
+            write_breaker: WriteBreakerConfig::default(),
+            push: PushConfig::default(),
+            handoff: HandoffConfig::default(),
+            leadership: LeadershipConfig::default(),
+            transport: TransportConfig::default(),
+            attachments: AttachmentConfig::default(),
+            coverage: CoverageConfig::default(),
+            packages: PackageConfig::default(),
+            tiles: TileConfig::default(),
+            tenant: None,
         }
     }
 }
@@ -60,6 +216,39 @@ impl ApiConfig {
             .map(|s| s == "true" || s == "1")
             .unwrap_or(true);
 
+        let export_dir = std::env::var("EXPORT_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| default_export_dir());
+
+        let export_ttl_seconds = std::env::var("EXPORT_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_EXPORT_TTL_SECONDS);
+
+        let import_dir = std::env::var("IMPORT_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| default_import_dir());
+
+        let max_body_bytes = std::env::var("MAX_BODY_BYTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_BODY_BYTES);
+
+        let ws_drain_seconds = std::env::var("WS_DRAIN_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_WS_DRAIN_SECONDS);
+
+        let retention = RetentionConfig::from_env().unwrap_or_else(|e| {
+            tracing::warn!("{}; using default retention policies", e);
+            RetentionConfig::default()
+        });
+
+        let presentation = PresentationRules::from_env().unwrap_or_else(|e| {
+            tracing::warn!("{:#}; using default presentation rules", e);
+            PresentationRules::default()
+        });
+
         Self {
             api_port,
             ws_port,
@@ -67,10 +256,43 @@ impl ApiConfig {
             cors_permissive,
             cv_enabled,
             simulation_mode,
+            simulation: SimulationConfig::from_env(),
+            export_dir,
+            export_ttl_seconds,
+            import_dir,
+            max_body_bytes,
+            ws_drain_seconds,
+            ws_compression: CompressionConfig::from_env(),
+            ws_rate_limits: RateLimitConfig::from_env(),
+            ws_socketio: SocketIoConfig::from_env(),
+            ws_presence: PresenceConfig::from_env(),
+            retention,
+            event_retention: EventRetention::from_env(),
+            presentation,
+            cv_publisher: CvPublisherConfig::from_env(),
+            cv_drift: DriftConfig::from_env(),
+            los: LosConfig::from_env(),
+            proximity: ProximityConfig::from_env(),
+            altitude: AltitudeConfig::from_env(),
+            arrival: ArrivalConfig::from_env(),
+            wind: WindConfig::from_env(),
+            mission_sync: MissionSyncConfig::from_env(),
+            checkpoint: CheckpointConfig::from_env(),
+            write_breaker: WriteBreakerConfig::from_env(),
+            push: PushConfig::from_env(),
+            handoff: HandoffConfig::from_env(),
+            leadership: LeadershipConfig::from_env(),
+            transport: TransportConfig::from_env(),
+            attachments: AttachmentConfig::from_env(),
+            coverage: CoverageConfig::from_env(),
+            packages: PackageConfig::from_env(),
+            tiles: TileConfig::from_env(),
+            tenant: None,
         }
     }
 
     /// Configuration for Docker environment
+    #[allow(dead_code)]
     pub fn docker() -> Self {
         Self {
             api_port: 3000,
@@ -79,6 +301,58 @@ impl ApiConfig {
             cors_permissive: true,
             cv_enabled: true,
             simulation_mode: true,
+            simulation: SimulationConfig::default(),
+            export_dir: default_export_dir(),
+            export_ttl_seconds: DEFAULT_EXPORT_TTL_SECONDS,
+            import_dir: default_import_dir(),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            ws_drain_seconds: DEFAULT_WS_DRAIN_SECONDS,
+            ws_compression: CompressionConfig::default(),
+            ws_rate_limits: RateLimitConfig::default(),
+            ws_socketio: SocketIoConfig::default(),
+            ws_presence: PresenceConfig::default(),
+            retention: RetentionConfig::default(),
+            event_retention: EventRetention::default(),
+            presentation: PresentationRules::default(),
+            cv_publisher: CvPublisherConfig::default(),
+            cv_drift: DriftConfig::default(),
+            los: LosConfig::default(),
+            proximity: ProximityConfig::default(),
+            altitude: AltitudeConfig::default(),
+            arrival: ArrivalConfig::default(),
+            wind: WindConfig::default(),
+            mission_sync: MissionSyncConfig::default(),
+            checkpoint: CheckpointConfig::default(),
+            write_breaker: WriteBreakerConfig::default(),
+            push: PushConfig::default(),
+            handoff: HandoffConfig::default(),
+            leadership: LeadershipConfig::default(),
+            transport: TransportConfig::default(),
+            attachments: AttachmentConfig::default(),
+            coverage: CoverageConfig::default(),
+            packages: PackageConfig::default(),
+            tiles: TileConfig::default(),
+            tenant: None,
         }
     }
+
+    /// The same settings over a tenant's own keyspace or database file,
+    /// export, import and attachment directories and simulation recording
+    pub fn for_tenant(&self, tenant: &TenantId) -> Self {
+        let mut config = self.clone();
+        config.tenant = Some(tenant.clone());
+        config.db = self.db.for_tenant(tenant);
+        config.export_dir = self.export_dir.join(tenant.as_str());
+        config.import_dir = self.import_dir.join(tenant.as_str());
+        config.attachments.dir = self.attachments.dir.join(tenant.as_str());
+        config.simulation.record_path = self.simulation.record_path.as_ref().map(|path| {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("simulation");
+            let name = match path.extension().and_then(|e| e.to_str()) {
+                Some(ext) => format!("{}_{}.{}", stem, tenant, ext),
+                None => format!("{}_{}", stem, tenant),
+            };
+            path.with_file_name(name)
+        });
+        config
+    }
 }
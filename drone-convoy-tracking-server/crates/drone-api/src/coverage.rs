@@ -0,0 +1,193 @@
+//! Coverage heatmap for the map view
+//!
+//! Every position report adds one unit of intensity to its geohash cell.
+//! Intensity decays exponentially with a configurable half-life, so the
+//! heatmap shows where the fleet has been recently. Decay is applied lazily:
+//! each cell keeps its intensity as of its last update and is brought
+//! forward when touched or read.
+
+use drone_core::{Event, EventPayload, GeoBounds, GeoPosition};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Cells that decayed below this intensity are dropped
+const MIN_INTENSITY: f64 = 0.01;
+
+/// Heatmap grid and decay settings
+#[derive(Debug, Clone)]
+pub struct CoverageConfig {
+    /// Geohash precision of the grid (6 = ~1.2 km x 0.6 km cells)
+    pub precision: usize,
+    /// Time for a cell's intensity to halve
+    pub half_life: Duration,
+}
+
+impl Default for CoverageConfig {
+    fn default() -> Self {
+        Self {
+            precision: 6,
+            half_life: Duration::from_secs(300),
+        }
+    }
+}
+
+impl CoverageConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            precision: std::env::var("COVERAGE_PRECISION")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|p| (1..=12).contains(p))
+                .unwrap_or(defaults.precision),
+            half_life: std::env::var("COVERAGE_HALF_LIFE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.half_life),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    intensity: f64,
+    updated: DateTime<Utc>,
+}
+
+/// One grid cell's current intensity
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatCell {
+    pub geohash: String,
+    /// Cell center
+    pub latitude: f64,
+    pub longitude: f64,
+    pub bounds: GeoBounds,
+    /// Decayed number of position reports
+    pub intensity: f64,
+}
+
+/// Heatmap cells in the requested area, most intense first
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapView {
+    pub precision: usize,
+    pub half_life_seconds: u64,
+    /// Highest intensity in `cells`, for scaling the shading
+    pub max_intensity: f64,
+    pub generated_at: DateTime<Utc>,
+    pub cells: Vec<HeatCell>,
+}
+
+/// Incrementally maintained, time-decayed coverage grid
+#[derive(Debug)]
+pub struct CoverageHeatmap {
+    config: CoverageConfig,
+    cells: RwLock<HashMap<String, Cell>>,
+}
+
+impl CoverageHeatmap {
+    pub fn new(config: CoverageConfig) -> Self {
+        Self {
+            config,
+            cells: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Intensity left of `intensity` after `elapsed`
+    fn decay(&self, intensity: f64, elapsed: chrono::Duration) -> f64 {
+        let elapsed = elapsed.num_milliseconds().max(0) as f64 / 1000.0;
+        intensity * 0.5f64.powf(elapsed / self.config.half_life.as_secs_f64())
+    }
+
+    /// Add a position report at `at`
+    pub fn record(&self, position: &GeoPosition, at: DateTime<Utc>) {
+        let geohash = position.geohash(self.config.precision);
+        let mut cells = self.cells.write();
+        let cell = cells.entry(geohash).or_insert(Cell { intensity: 0.0, updated: at });
+        // Reports may arrive slightly out of order; never decay backwards
+        let at = at.max(cell.updated);
+        cell.intensity = self.decay(cell.intensity, at - cell.updated) + 1.0;
+        cell.updated = at;
+    }
+
+    /// Apply position reports from a tracker event
+    pub fn record_event(&self, event: &Event, now: DateTime<Utc>) {
+        if let EventPayload::DronePosition(e) = &event.payload {
+            self.record(&e.position, now);
+        }
+    }
+
+    /// Cells intersecting `bounds` (all cells without), decayed to `now`
+    pub fn heatmap(&self, bounds: Option<&GeoBounds>, now: DateTime<Utc>) -> HeatmapView {
+        let mut cells = self.cells.write();
+        cells.retain(|_, cell| self.decay(cell.intensity, now - cell.updated) >= MIN_INTENSITY);
+
+        let mut heat: Vec<HeatCell> = cells
+            .iter()
+            .filter_map(|(geohash, cell)| {
+                let cell_bounds = GeoBounds::from_geohash(geohash)?;
+                if bounds.is_some_and(|b| !b.intersects(&cell_bounds)) {
+                    return None;
+                }
+                let center = cell_bounds.center();
+                Some(HeatCell {
+                    geohash: geohash.clone(),
+                    latitude: center.latitude,
+                    longitude: center.longitude,
+                    bounds: cell_bounds,
+                    intensity: self.decay(cell.intensity, now - cell.updated),
+                })
+            })
+            .collect();
+        heat.sort_by(|a, b| b.intensity.total_cmp(&a.intensity).then_with(|| a.geohash.cmp(&b.geohash)));
+
+        HeatmapView {
+            precision: self.config.precision,
+            half_life_seconds: self.config.half_life.as_secs(),
+            max_intensity: heat.first().map(|c| c.intensity).unwrap_or(0.0),
+            generated_at: now,
+            cells: heat,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intensity_decays_and_bounds_filter() {
+        let heatmap = CoverageHeatmap::new(CoverageConfig::default());
+        let t0 = Utc::now();
+        let kabul = GeoPosition::new(34.5553, 69.2075, 3000.0);
+        let kandahar = GeoPosition::new(31.6133, 65.7101, 3000.0);
+
+        heatmap.record(&kabul, t0);
+        heatmap.record(&kabul, t0);
+        heatmap.record(&kandahar, t0);
+
+        // One half-life later
+        let view = heatmap.heatmap(None, t0 + chrono::Duration::seconds(300));
+        assert_eq!(view.cells.len(), 2);
+        assert_eq!(view.cells[0].geohash, "tw1hwf");
+        assert!((view.cells[0].intensity - 1.0).abs() < 1e-9);
+        assert!((view.cells[1].intensity - 0.5).abs() < 1e-9);
+        assert!((view.max_intensity - 1.0).abs() < 1e-9);
+
+        let around_kabul = GeoBounds::from_center(&kabul, 10.0);
+        let view = heatmap.heatmap(Some(&around_kabul), t0 + chrono::Duration::seconds(300));
+        assert_eq!(view.cells.len(), 1);
+
+        // Long after the fleet left, the cells are gone
+        assert!(heatmap.heatmap(None, t0 + chrono::Duration::hours(2)).cells.is_empty());
+    }
+}
@@ -5,10 +5,13 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use crate::validation::FieldError;
+
 use serde::Serialize;
 use thiserror::Error;
 
 /// API error type
+#[allow(dead_code)]
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("Not found: {0}")]
@@ -23,16 +26,29 @@ pub enum ApiError {
     #[error("Forbidden: {0}")]
     Forbidden(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 
+    #[error("Bad gateway: {0}")]
+    BadGateway(String),
+
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
 
     #[error("Database error: {0}")]
     Database(String),
+
+    #[error("Validation failed: {0:?}")]
+    Validation(Vec<FieldError>),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
 }
 
+#[allow(dead_code)]
 impl ApiError {
     pub fn not_found(msg: impl Into<String>) -> Self {
         Self::NotFound(msg.into())
@@ -45,6 +61,14 @@ impl ApiError {
     pub fn internal(msg: impl Into<String>) -> Self {
         Self::Internal(msg.into())
     }
+
+    /// Single field-level validation failure
+    pub fn validation(field: impl Into<String>, msg: impl Into<String>) -> Self {
+        Self::Validation(vec![FieldError {
+            field: field.into(),
+            message: msg.into(),
+        }])
+    }
 }
 
 /// Error response body
@@ -53,7 +77,7 @@ struct ErrorResponse {
     error: String,
     message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    details: Option<String>,
+    details: Option<Vec<FieldError>>,
 }
 
 impl IntoResponse for ApiError {
@@ -63,15 +87,24 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg.clone()),
             ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "unauthorized", msg.clone()),
             ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, "forbidden", msg.clone()),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg.clone()),
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", msg.clone()),
+            ApiError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, "bad_gateway", msg.clone()),
             ApiError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, "service_unavailable", msg.clone()),
             ApiError::Database(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error", msg.clone()),
+            ApiError::Validation(_) => (StatusCode::UNPROCESSABLE_ENTITY, "validation_failed", "Request validation failed".into()),
+            ApiError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", msg.clone()),
+        };
+
+        let details = match self {
+            ApiError::Validation(fields) => Some(fields),
+            _ => None,
         };
 
         let body = Json(ErrorResponse {
             error: error_type.into(),
             message,
-            details: None,
+            details,
         });
 
         (status, body).into_response()
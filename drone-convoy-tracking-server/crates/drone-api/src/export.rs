@@ -0,0 +1,864 @@
+//! Bulk export of telemetry and waypoint events
+//!
+//! Export jobs run in the background: rows are streamed out of ScyllaDB in
+//! chunks and written to CSV or Parquet (via Arrow) under the export directory.
+//! Clients poll the job status and download the finished file. Finished jobs
+//! and their files are dropped once they are older than the export TTL.
+
+use crate::validation::{Validate, ValidationErrors, MAX_ID_LEN};
+use drone_core::{DroneId, MissionId};
+use drone_db::{DbClient, DbResult, TelemetryRecord, WaypointEventRecord};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int32Array, RecordBatch, StringArray,
+    TimestampMillisecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use futures::{Stream, StreamExt};
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Rows per chunk handed from the DB stream to the file writer
+const CHUNK_ROWS: usize = 1024;
+
+/// Default look-back window when no `from` is given (telemetry TTL)
+const DEFAULT_WINDOW_DAYS: i64 = 7;
+
+/// Maximum number of drones a single export may select
+const MAX_EXPORT_DRONES: usize = 256;
+
+/// How often expired exports are looked for
+const SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// ============================================================================
+// REQUEST & JOB TYPES
+// ============================================================================
+
+/// Output file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+/// Data set to export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportDataset {
+    #[default]
+    Telemetry,
+    WaypointEvents,
+}
+
+/// Export request body
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportRequest {
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub dataset: ExportDataset,
+    /// Mission to export (required for waypoint events, filters telemetry)
+    #[serde(default)]
+    pub mission_id: Option<Uuid>,
+    /// Drones to export telemetry for (empty = all known drones)
+    #[serde(default)]
+    pub drone_ids: Vec<String>,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl Validate for ExportRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if self.drone_ids.len() > MAX_EXPORT_DRONES {
+            errors.add(
+                "drone_ids",
+                format!("must list at most {} drones", MAX_EXPORT_DRONES),
+            );
+        }
+        for (i, id) in self.drone_ids.iter().enumerate() {
+            errors.check_len(&format!("drone_ids[{}]", i), id, MAX_ID_LEN);
+        }
+
+        let (from, to) = self.time_range();
+        if from > to {
+            errors.add("from", format!("must not be after to ({})", to));
+        }
+
+        errors.into_result()
+    }
+}
+
+impl ExportRequest {
+    /// Whether the data set needs a mission and none was given
+    pub fn missing_mission_id(&self) -> bool {
+        self.dataset == ExportDataset::WaypointEvents && self.mission_id.is_none()
+    }
+
+    /// Resolve the requested time range, applying defaults
+    pub fn time_range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let to = self.to.unwrap_or_else(Utc::now);
+        let from = self
+            .from
+            .unwrap_or_else(|| to - Duration::days(DEFAULT_WINDOW_DAYS));
+        (from, to)
+    }
+}
+
+/// Export job lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Background export job
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportJob {
+    pub id: Uuid,
+    pub status: ExportStatus,
+    pub format: ExportFormat,
+    pub dataset: ExportDataset,
+    pub rows: u64,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+// ============================================================================
+// EXPORT MANAGER
+// ============================================================================
+
+/// Tracks export jobs and owns the export directory
+pub struct ExportManager {
+    dir: PathBuf,
+    ttl: std::time::Duration,
+    jobs: DashMap<Uuid, ExportJob>,
+}
+
+impl ExportManager {
+    /// Create a manager writing files into `dir`, keeping finished exports
+    /// for `ttl`
+    pub fn new(dir: impl Into<PathBuf>, ttl: std::time::Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            jobs: DashMap::new(),
+        }
+    }
+
+    /// Register a new pending job for a request
+    pub fn create(&self, request: &ExportRequest) -> ExportJob {
+        let job = ExportJob {
+            id: Uuid::new_v4(),
+            status: ExportStatus::Pending,
+            format: request.format,
+            dataset: request.dataset,
+            rows: 0,
+            bytes: 0,
+            error: None,
+            created_at: Utc::now(),
+            completed_at: None,
+            download_url: None,
+        };
+        self.jobs.insert(job.id, job.clone());
+        job
+    }
+
+    /// Get a job by ID
+    pub fn get(&self, id: &Uuid) -> Option<ExportJob> {
+        self.jobs.get(id).map(|j| j.clone())
+    }
+
+    /// Path of the output file for a job
+    pub fn file_path(&self, id: &Uuid, format: ExportFormat) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, format.extension()))
+    }
+
+    fn update(&self, id: &Uuid, f: impl FnOnce(&mut ExportJob)) {
+        if let Some(mut job) = self.jobs.get_mut(id) {
+            f(&mut job);
+        }
+    }
+
+    fn mark_running(&self, id: &Uuid) {
+        self.update(id, |job| job.status = ExportStatus::Running);
+    }
+
+    fn mark_completed(&self, id: &Uuid, rows: u64, bytes: u64) {
+        self.update(id, |job| {
+            job.status = ExportStatus::Completed;
+            job.rows = rows;
+            job.bytes = bytes;
+            job.completed_at = Some(Utc::now());
+            job.download_url = Some(format!("/api/v1/export/{}/download", job.id));
+        });
+    }
+
+    fn mark_failed(&self, id: &Uuid, error: String) {
+        self.update(id, |job| {
+            job.status = ExportStatus::Failed;
+            job.error = Some(error);
+            job.completed_at = Some(Utc::now());
+        });
+    }
+
+    /// Drop jobs that finished more than the TTL ago and delete their files,
+    /// along with files in the export directory no job owns any more (left
+    /// by an earlier run) that are older than the TTL; returns the number
+    /// of jobs and files removed
+    pub async fn purge_expired(&self) -> usize {
+        let cutoff = Utc::now() - Duration::from_std(self.ttl).unwrap_or(Duration::MAX);
+        let expired: Vec<ExportJob> = self
+            .jobs
+            .iter()
+            .filter(|job| job.completed_at.is_some_and(|at| at < cutoff))
+            .map(|job| job.clone())
+            .collect();
+
+        let mut removed = 0;
+        for job in expired {
+            self.jobs.remove(&job.id);
+            removed += 1;
+            let path = self.file_path(&job.id, job.format);
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to delete expired export {}: {}", path.display(), e);
+                }
+            }
+        }
+
+        let Ok(mut entries) = tokio::fs::read_dir(&self.dir).await else {
+            return removed;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let owned = entry
+                .path()
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<Uuid>().ok())
+                .is_some_and(|id| self.jobs.contains_key(&id));
+            let modified = match entry.metadata().await.and_then(|m| m.modified()) {
+                Ok(modified) => DateTime::<Utc>::from(modified),
+                Err(_) => continue,
+            };
+            if owned || modified >= cutoff || !entry.file_type().await.is_ok_and(|t| t.is_file()) {
+                continue;
+            }
+            match tokio::fs::remove_file(entry.path()).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Failed to delete expired export {}: {}", entry.path().display(), e),
+            }
+        }
+        removed
+    }
+
+    /// Purge expired exports every `SWEEP_INTERVAL`
+    pub fn spawn_sweeper(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let removed = manager.purge_expired().await;
+                if removed > 0 {
+                    info!("Purged {} expired exports", removed);
+                }
+            }
+        })
+    }
+}
+
+/// Run an export job to completion, recording the outcome on the manager
+pub async fn run_job(
+    manager: Arc<ExportManager>,
+    db: Arc<DbClient>,
+    job_id: Uuid,
+    request: ExportRequest,
+    drone_ids: Vec<DroneId>,
+) {
+    manager.mark_running(&job_id);
+
+    let result = match tokio::fs::create_dir_all(&manager.dir).await {
+        Ok(()) => {
+            let path = manager.file_path(&job_id, request.format);
+            let rows = match request.dataset {
+                ExportDataset::Telemetry => {
+                    export_telemetry(&db, &request, &drone_ids, &path).await
+                }
+                ExportDataset::WaypointEvents => {
+                    export_waypoint_events(&db, &request, &path).await
+                }
+            };
+            match rows {
+                Ok(rows) => {
+                    let bytes = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+                    Ok((rows, bytes))
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => Err(e.into()),
+    };
+
+    match result {
+        Ok((rows, bytes)) => {
+            info!("Export {} completed: {} rows, {} bytes", job_id, rows, bytes);
+            manager.mark_completed(&job_id, rows, bytes);
+        }
+        Err(e) => {
+            warn!("Export {} failed: {}", job_id, e);
+            manager.mark_failed(&job_id, e.to_string());
+        }
+    }
+}
+
+async fn export_telemetry(
+    db: &DbClient,
+    request: &ExportRequest,
+    drone_ids: &[DroneId],
+    path: &Path,
+) -> anyhow::Result<u64> {
+    let (from, to) = request.time_range();
+    let mission_id = request.mission_id;
+    let (tx, writer) = spawn_writer::<TelemetryRecord>(path, request.format);
+
+    let produced = async {
+        for drone_id in drone_ids {
+            let rows = db.telemetry().stream_range(drone_id, from, to).await?;
+            let rows = rows.filter(move |r| {
+                let keep = match (r, mission_id) {
+                    (Ok(record), Some(id)) => record.mission_id == Some(id),
+                    _ => true,
+                };
+                futures::future::ready(keep)
+            });
+            forward_chunks(rows, &tx).await?;
+        }
+        anyhow::Ok(())
+    }
+    .await;
+
+    drop(tx);
+    let rows = writer.await??;
+    produced.map(|_| rows)
+}
+
+async fn export_waypoint_events(
+    db: &DbClient,
+    request: &ExportRequest,
+    path: &Path,
+) -> anyhow::Result<u64> {
+    let (from, to) = request.time_range();
+    let mission_id = request
+        .mission_id
+        .map(MissionId)
+        .ok_or_else(|| anyhow::anyhow!("mission_id is required for waypoint_events exports"))?;
+    let (tx, writer) = spawn_writer::<WaypointEventRecord>(path, request.format);
+
+    let produced = async {
+        let rows = db.waypoints().stream_range(&mission_id, from, to).await?;
+        forward_chunks(rows, &tx).await
+    }
+    .await;
+
+    drop(tx);
+    let rows = writer.await??;
+    produced.map(|_| rows)
+}
+
+/// Forward a row stream to the writer in chunks of `CHUNK_ROWS`
+async fn forward_chunks<R>(
+    rows: impl Stream<Item = DbResult<R>>,
+    tx: &mpsc::Sender<Vec<R>>,
+) -> anyhow::Result<()> {
+    let mut chunks = std::pin::pin!(rows.chunks(CHUNK_ROWS));
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.into_iter().collect::<DbResult<Vec<R>>>()?;
+        tx.send(chunk)
+            .await
+            .map_err(|_| anyhow::anyhow!("export writer stopped"))?;
+    }
+    Ok(())
+}
+
+/// Spawn a blocking writer fed through a bounded channel
+fn spawn_writer<R: ExportRow>(
+    path: &Path,
+    format: ExportFormat,
+) -> (
+    mpsc::Sender<Vec<R>>,
+    tokio::task::JoinHandle<anyhow::Result<u64>>,
+) {
+    let (tx, rx) = mpsc::channel(4);
+    let path = path.to_path_buf();
+    let handle = tokio::task::spawn_blocking(move || write_chunks(&path, format, rx));
+    (tx, handle)
+}
+
+// ============================================================================
+// FILE WRITERS
+// ============================================================================
+
+/// A record that can be written as a CSV line or an Arrow batch
+pub trait ExportRow: Send + 'static {
+    /// CSV header / Arrow field names
+    const COLUMNS: &'static [&'static str];
+
+    /// Column values rendered as CSV fields
+    fn csv_fields(&self) -> Vec<String>;
+
+    /// Arrow schema for Parquet output
+    fn schema() -> SchemaRef;
+
+    /// Convert a chunk of rows into an Arrow record batch
+    fn to_batch(rows: &[Self]) -> anyhow::Result<RecordBatch>
+    where
+        Self: Sized;
+}
+
+/// Write all chunks received on `rx` to `path`, returning the row count
+pub fn write_chunks<R: ExportRow>(
+    path: &Path,
+    format: ExportFormat,
+    mut rx: mpsc::Receiver<Vec<R>>,
+) -> anyhow::Result<u64> {
+    let file = File::create(path)?;
+    let mut rows = 0u64;
+
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, "{}", R::COLUMNS.join(","))?;
+            while let Some(chunk) = rx.blocking_recv() {
+                for row in &chunk {
+                    let line: Vec<String> =
+                        row.csv_fields().iter().map(|f| csv_escape(f)).collect();
+                    writeln!(writer, "{}", line.join(","))?;
+                }
+                rows += chunk.len() as u64;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Parquet => {
+            let mut writer = ArrowWriter::try_new(file, R::schema(), None)?;
+            while let Some(chunk) = rx.blocking_recv() {
+                if chunk.is_empty() {
+                    continue;
+                }
+                writer.write(&R::to_batch(&chunk)?)?;
+                rows += chunk.len() as u64;
+            }
+            writer.close()?;
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or newline
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn opt_to_string<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn timestamp_field(name: &str) -> Field {
+    Field::new(
+        name,
+        DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        false,
+    )
+}
+
+fn timestamp_array<'a>(values: impl Iterator<Item = &'a DateTime<Utc>>) -> ArrayRef {
+    Arc::new(
+        TimestampMillisecondArray::from_iter_values(values.map(|t| t.timestamp_millis()))
+            .with_timezone("UTC"),
+    )
+}
+
+impl ExportRow for TelemetryRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "drone_id",
+        "timestamp",
+        "latitude",
+        "longitude",
+        "altitude",
+        "heading",
+        "speed",
+        "battery_level",
+        "fuel_level",
+        "system_health",
+        "status",
+        "armed",
+        "temperature",
+        "signal_strength",
+        "mission_id",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.drone_id.clone(),
+            self.timestamp.to_rfc3339(),
+            self.latitude.to_string(),
+            self.longitude.to_string(),
+            self.altitude.to_string(),
+            self.heading.to_string(),
+            self.speed.to_string(),
+            self.battery_level.to_string(),
+            self.fuel_level.to_string(),
+            self.system_health.to_string(),
+            opt_to_string(&self.status),
+            opt_to_string(&self.armed),
+            opt_to_string(&self.temperature),
+            opt_to_string(&self.signal_strength),
+            opt_to_string(&self.mission_id),
+        ]
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("drone_id", DataType::Utf8, false),
+            timestamp_field("timestamp"),
+            Field::new("latitude", DataType::Float64, false),
+            Field::new("longitude", DataType::Float64, false),
+            Field::new("altitude", DataType::Float64, false),
+            Field::new("heading", DataType::Float64, false),
+            Field::new("speed", DataType::Float64, false),
+            Field::new("battery_level", DataType::Int32, false),
+            Field::new("fuel_level", DataType::Int32, false),
+            Field::new("system_health", DataType::Int32, false),
+            Field::new("status", DataType::Utf8, true),
+            Field::new("armed", DataType::Boolean, true),
+            Field::new("temperature", DataType::Float64, true),
+            Field::new("signal_strength", DataType::Int32, true),
+            Field::new("mission_id", DataType::Utf8, true),
+        ]))
+    }
+
+    fn to_batch(rows: &[Self]) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.drone_id.as_str()))),
+            timestamp_array(rows.iter().map(|r| &r.timestamp)),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.latitude))),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.longitude))),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.altitude))),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.heading))),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.speed))),
+            Arc::new(Int32Array::from_iter_values(rows.iter().map(|r| r.battery_level))),
+            Arc::new(Int32Array::from_iter_values(rows.iter().map(|r| r.fuel_level))),
+            Arc::new(Int32Array::from_iter_values(rows.iter().map(|r| r.system_health))),
+            Arc::new(StringArray::from_iter(rows.iter().map(|r| r.status.as_deref()))),
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|r| r.armed))),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.temperature))),
+            Arc::new(Int32Array::from_iter(rows.iter().map(|r| r.signal_strength))),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|r| r.mission_id.map(|m| m.to_string())),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+impl ExportRow for WaypointEventRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "mission_id",
+        "event_time",
+        "drone_id",
+        "waypoint_id",
+        "waypoint_name",
+        "latitude",
+        "longitude",
+        "event_type",
+        "speed_at_event",
+        "altitude_at_event",
+        "heading",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.mission_id.to_string(),
+            self.event_time.to_rfc3339(),
+            self.drone_id.clone(),
+            opt_to_string(&self.waypoint_id),
+            opt_to_string(&self.waypoint_name),
+            opt_to_string(&self.latitude),
+            opt_to_string(&self.longitude),
+            opt_to_string(&self.event_type),
+            opt_to_string(&self.speed_at_event),
+            opt_to_string(&self.altitude_at_event),
+            opt_to_string(&self.heading),
+        ]
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("mission_id", DataType::Utf8, false),
+            timestamp_field("event_time"),
+            Field::new("drone_id", DataType::Utf8, false),
+            Field::new("waypoint_id", DataType::Utf8, true),
+            Field::new("waypoint_name", DataType::Utf8, true),
+            Field::new("latitude", DataType::Float64, true),
+            Field::new("longitude", DataType::Float64, true),
+            Field::new("event_type", DataType::Utf8, true),
+            Field::new("speed_at_event", DataType::Float64, true),
+            Field::new("altitude_at_event", DataType::Float64, true),
+            Field::new("heading", DataType::Float64, true),
+        ]))
+    }
+
+    fn to_batch(rows: &[Self]) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|r| r.mission_id.to_string()),
+            )),
+            timestamp_array(rows.iter().map(|r| &r.event_time)),
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.drone_id.as_str()))),
+            Arc::new(StringArray::from_iter(rows.iter().map(|r| r.waypoint_id.as_deref()))),
+            Arc::new(StringArray::from_iter(rows.iter().map(|r| r.waypoint_name.as_deref()))),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.latitude))),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.longitude))),
+            Arc::new(StringArray::from_iter(rows.iter().map(|r| r.event_type.as_deref()))),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.speed_at_event))),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.altitude_at_event))),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.heading))),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn sample_record(i: i32) -> TelemetryRecord {
+        TelemetryRecord {
+            drone_id: format!("REAPER-{:02}", i),
+            timestamp: Utc::now(),
+            latitude: 34.5 + i as f64 * 0.01,
+            longitude: 69.2,
+            altitude: 3000.0,
+            heading: 45.0,
+            speed: 350.0,
+            battery_level: 90,
+            fuel_level: 80,
+            system_health: 99,
+            status: Some("MOVING".into()),
+            armed: None,
+            temperature: Some(42.0),
+            signal_strength: None,
+            mission_id: None,
+            gimbal: None,
+        }
+    }
+
+    fn write_sample(format: ExportFormat, rows: usize) -> (PathBuf, u64) {
+        let path = std::env::temp_dir().join(format!(
+            "drone-export-test-{}.{}",
+            Uuid::new_v4(),
+            format.extension()
+        ));
+        let (tx, rx) = mpsc::channel(4);
+        let records: Vec<_> = (0..rows as i32).map(sample_record).collect();
+        tx.try_send(records).unwrap();
+        drop(tx);
+        let written = write_chunks(&path, format, rx).unwrap();
+        (path, written)
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("REAPER-01"), "REAPER-01");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let (path, written) = write_sample(ExportFormat::Csv, 3);
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(written, 3);
+        let mut lines = content.lines();
+        assert_eq!(lines.next().unwrap(), TelemetryRecord::COLUMNS.join(","));
+        assert_eq!(lines.count(), 3);
+    }
+
+    #[test]
+    fn test_parquet_export() {
+        let (path, written) = write_sample(ExportFormat::Parquet, 5);
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata().clone();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(written, 5);
+        assert_eq!(metadata.num_rows(), 5);
+        assert_eq!(
+            metadata.schema_descr().num_columns(),
+            TelemetryRecord::COLUMNS.len()
+        );
+    }
+
+    #[test]
+    fn test_request_validation() {
+        let request: ExportRequest = serde_json::from_value(serde_json::json!({
+            "format": "csv",
+            "dataset": "waypoint_events",
+        }))
+        .unwrap();
+        assert!(request.missing_mission_id());
+
+        let now = Utc::now();
+        let request = ExportRequest {
+            format: ExportFormat::Parquet,
+            dataset: ExportDataset::Telemetry,
+            mission_id: None,
+            drone_ids: Vec::new(),
+            from: Some(now),
+            to: Some(now - Duration::hours(1)),
+        };
+        assert!(request.validate().is_err());
+
+        let request = ExportRequest { from: None, ..request };
+        assert!(request.validate().is_ok());
+        assert!(!request.missing_mission_id());
+    }
+
+    #[test]
+    fn test_job_lifecycle() {
+        let manager = ExportManager::new(std::env::temp_dir(), std::time::Duration::from_secs(60));
+        let request: ExportRequest =
+            serde_json::from_value(serde_json::json!({ "format": "parquet" })).unwrap();
+
+        let job = manager.create(&request);
+        assert_eq!(job.status, ExportStatus::Pending);
+
+        manager.mark_running(&job.id);
+        manager.mark_completed(&job.id, 10, 2048);
+
+        let job = manager.get(&job.id).unwrap();
+        assert_eq!(job.status, ExportStatus::Completed);
+        assert_eq!(job.rows, 10);
+        assert!(job.download_url.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_expired_exports_purged() {
+        let dir = std::env::temp_dir().join(format!("drone-export-ttl-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manager = ExportManager::new(&dir, std::time::Duration::from_secs(3600));
+        let request: ExportRequest =
+            serde_json::from_value(serde_json::json!({ "format": "csv" })).unwrap();
+
+        let expired = manager.create(&request);
+        manager.mark_completed(&expired.id, 1, 10);
+        manager.update(&expired.id, |job| job.completed_at = Some(Utc::now() - Duration::hours(2)));
+        let fresh = manager.create(&request);
+        manager.mark_completed(&fresh.id, 1, 10);
+        let running = manager.create(&request);
+        manager.mark_running(&running.id);
+        for job in [&expired, &fresh, &running] {
+            std::fs::write(manager.file_path(&job.id, job.format), "drone_id\n").unwrap();
+        }
+
+        assert_eq!(manager.purge_expired().await, 1);
+        assert!(manager.get(&expired.id).is_none());
+        assert!(!manager.file_path(&expired.id, expired.format).exists());
+        assert!(manager.get(&fresh.id).is_some());
+        assert!(manager.file_path(&fresh.id, fresh.format).exists());
+        assert!(manager.file_path(&running.id, running.format).exists());
+
+        // Files of a previous run are only removed once they are old enough
+        let orphan = dir.join(format!("{}.csv", Uuid::new_v4()));
+        std::fs::write(&orphan, "drone_id\n").unwrap();
+        assert_eq!(manager.purge_expired().await, 0);
+        let manager = ExportManager::new(&dir, std::time::Duration::ZERO);
+        assert_eq!(manager.purge_expired().await, 3);
+        assert!(!orphan.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_waypoint_export_without_mission_rejected() {
+        use crate::config::ApiConfig;
+        use crate::packages::PackageConfig;
+        use crate::state::AppState;
+        use axum::{body::Body, http::{Request, StatusCode}, routing::post, Router};
+        use drone_websocket::WebSocketHub;
+        use tower::ServiceExt;
+
+        let packages = PackageConfig {
+            key_path: std::env::temp_dir().join(format!("mission-key-{}", Uuid::new_v4())),
+            ..Default::default()
+        };
+        let state = AppState::new_without_db(ApiConfig { packages, ..Default::default() }, Arc::new(WebSocketHub::new()))
+            .await
+            .unwrap();
+        let app = Router::new().route("/export", post(crate::handlers::create_export)).with_state(state);
+        let export = |body: serde_json::Value| {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/export")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+
+        let body = serde_json::json!({ "format": "csv", "dataset": "waypoint_events" });
+        assert_eq!(export(body).await, StatusCode::BAD_REQUEST);
+        // With a mission the request is only refused for lack of a database
+        let body = serde_json::json!({ "format": "csv", "dataset": "waypoint_events", "mission_id": Uuid::new_v4() });
+        assert_eq!(export(body).await, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,355 @@
+//! Fleet-wide statistics
+//!
+//! Aggregates are maintained from tracker events as they arrive: battery and
+//! fuel levels are kept as histograms (so averages and minimums survive a
+//! drone's level changing), status counts move one drone at a time and the
+//! daily distance accumulates per position update. A stats request only
+//! copies the current aggregates.
+
+use drone_core::{
+    Alert, AlertSeverity, AlertType, DroneId, DroneStatus, Event, EventPayload, EventType,
+    GeoPosition, Telemetry, TelemetryLimits,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// An alert counts as active until it has not been re-raised for this long
+pub const ACTIVE_ALERT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Percentage histogram (index = level 0-100)
+#[derive(Debug)]
+struct LevelHistogram {
+    counts: [usize; 101],
+    sum: u64,
+    total: usize,
+}
+
+impl Default for LevelHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; 101],
+            sum: 0,
+            total: 0,
+        }
+    }
+}
+
+impl LevelHistogram {
+    fn add(&mut self, level: u8) {
+        let level = level.min(100);
+        self.counts[level as usize] += 1;
+        self.sum += level as u64;
+        self.total += 1;
+    }
+
+    fn remove(&mut self, level: u8) {
+        let level = level.min(100);
+        self.counts[level as usize] -= 1;
+        self.sum -= level as u64;
+        self.total -= 1;
+    }
+
+    fn stats(&self) -> LevelStats {
+        LevelStats {
+            average: (self.total > 0).then(|| self.sum as f64 / self.total as f64),
+            min: self.counts.iter().position(|n| *n > 0).map(|level| level as u8),
+        }
+    }
+}
+
+/// Average and minimum of a percentage across drones reporting it
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelStats {
+    pub average: Option<f64>,
+    pub min: Option<u8>,
+}
+
+/// Active alerts per severity
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertCounts {
+    pub info: usize,
+    pub warning: usize,
+    pub critical: usize,
+    pub emergency: usize,
+}
+
+impl AlertCounts {
+    fn add(&mut self, severity: AlertSeverity) {
+        match severity {
+            AlertSeverity::Info => self.info += 1,
+            AlertSeverity::Warning => self.warning += 1,
+            AlertSeverity::Critical => self.critical += 1,
+            AlertSeverity::Emergency => self.emergency += 1,
+        }
+    }
+}
+
+/// Distance between the two drones farthest apart
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvoySpread {
+    pub distance_km: f64,
+    pub drone_ids: [DroneId; 2],
+}
+
+/// Fleet-wide statistics snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetStats {
+    pub drone_count: usize,
+    pub battery: LevelStats,
+    pub fuel: LevelStats,
+    pub status_counts: BTreeMap<String, usize>,
+    /// UTC day `distance_today_km` covers
+    pub day: Option<NaiveDate>,
+    pub distance_today_km: f64,
+    pub active_alerts: AlertCounts,
+    pub convoy_spread: Option<ConvoySpread>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct FleetDrone {
+    status: Option<DroneStatus>,
+    battery: Option<u8>,
+    fuel: Option<u8>,
+    /// Last position and telemetry time, for distance accumulation
+    last_fix: Option<(GeoPosition, DateTime<Utc>)>,
+}
+
+#[derive(Debug, Default)]
+struct FleetState {
+    drones: HashMap<DroneId, FleetDrone>,
+    battery: LevelHistogram,
+    fuel: LevelHistogram,
+    status_counts: BTreeMap<String, usize>,
+    day: Option<NaiveDate>,
+    distance_today_km: f64,
+    /// Latest alert per drone and alert type
+    alerts: HashMap<(Option<DroneId>, AlertType), (AlertSeverity, DateTime<Utc>)>,
+    convoy_spread: Option<ConvoySpread>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl FleetState {
+    fn drone(&mut self, drone_id: &DroneId) -> &mut FleetDrone {
+        self.drones.entry(drone_id.clone()).or_default()
+    }
+
+    fn set_status(&mut self, drone_id: &DroneId, status: DroneStatus) {
+        if let Some(old) = self.drone(drone_id).status.replace(status) {
+            let old = old.to_string();
+            if let Some(n) = self.status_counts.get_mut(&old) {
+                *n -= 1;
+                if *n == 0 {
+                    self.status_counts.remove(&old);
+                }
+            }
+        }
+        *self.status_counts.entry(status.to_string()).or_default() += 1;
+    }
+
+    fn set_levels(&mut self, drone_id: &DroneId, telemetry: &Telemetry) {
+        let drone = self.drone(drone_id);
+        let old_battery = drone.battery.replace(telemetry.battery_level);
+        let old_fuel = drone.fuel.replace(telemetry.fuel_level);
+
+        if let Some(level) = old_battery {
+            self.battery.remove(level);
+        }
+        self.battery.add(telemetry.battery_level);
+        if let Some(level) = old_fuel {
+            self.fuel.remove(level);
+        }
+        self.fuel.add(telemetry.fuel_level);
+    }
+
+    fn record_fix(&mut self, drone_id: &DroneId, position: GeoPosition, at: DateTime<Utc>) {
+        let today = at.date_naive();
+        if self.day.is_none_or(|day| today > day) {
+            self.day = Some(today);
+            self.distance_today_km = 0.0;
+        }
+
+        let previous = self.drone(drone_id).last_fix.replace((position, at));
+        if let Some((from, from_at)) = previous.filter(|(_, t)| t.date_naive() == today) {
+            let hours = (at - from_at).num_milliseconds() as f64 / 3_600_000.0;
+            let km = from.distance_to(&position);
+            // Skip resets and other jumps no drone could fly
+            if hours > 0.0 && km / hours <= TelemetryLimits::default().max_speed_kmh {
+                self.distance_today_km += km;
+            }
+        }
+        self.convoy_spread = self.spread();
+    }
+
+    fn spread(&self) -> Option<ConvoySpread> {
+        let fixes: Vec<_> = self
+            .drones
+            .iter()
+            .filter_map(|(id, d)| d.last_fix.map(|(position, _)| (id, position)))
+            .collect();
+
+        let mut widest: Option<ConvoySpread> = None;
+        for (i, (a, pa)) in fixes.iter().enumerate() {
+            for (b, pb) in &fixes[i + 1..] {
+                let distance_km = pa.distance_to(pb);
+                if widest.as_ref().is_none_or(|w| distance_km > w.distance_km) {
+                    let mut pair = [(*a).clone(), (*b).clone()];
+                    pair.sort_by(|x, y| x.as_str().cmp(y.as_str()));
+                    widest = Some(ConvoySpread { distance_km, drone_ids: pair });
+                }
+            }
+        }
+        widest
+    }
+}
+
+/// Incrementally maintained fleet statistics
+#[derive(Debug, Default)]
+pub struct FleetStatsService {
+    state: RwLock<FleetState>,
+}
+
+impl FleetStatsService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a drone's status
+    pub fn update_status(&self, drone_id: &DroneId, status: DroneStatus) {
+        self.state.write().set_status(drone_id, status);
+    }
+
+    /// Record a position report
+    pub fn update_position(&self, drone_id: &DroneId, position: GeoPosition, telemetry: &Telemetry) {
+        let mut state = self.state.write();
+        state.set_levels(drone_id, telemetry);
+        state.record_fix(drone_id, position, telemetry.timestamp);
+        state.updated_at = Some(telemetry.timestamp);
+    }
+
+    /// Record a raised alert; repeats of the same drone and type replace it
+    pub fn record_alert(&self, alert: &Alert) {
+        let key = (alert.drone_id.clone(), alert.alert_type.clone());
+        let mut state = self.state.write();
+        if alert.acknowledged || alert.resolved {
+            state.alerts.remove(&key);
+        } else {
+            state.alerts.insert(key, (alert.severity, alert.created_at));
+        }
+    }
+
+    /// Apply a tracker event
+    pub fn record_event(&self, event: &Event) {
+        match &event.payload {
+            EventPayload::DronePosition(e) => self.update_position(&e.drone_id, e.position, &e.telemetry),
+            EventPayload::DroneTelemetry(e) => {
+                let mut state = self.state.write();
+                state.set_levels(&e.drone_id, &e.telemetry);
+                state.updated_at = Some(e.telemetry.timestamp);
+            }
+            EventPayload::DroneStatus(e) => self.update_status(&e.drone_id, e.new_status),
+            EventPayload::Alert(e) => match event.event_type {
+                EventType::AlertAcknowledged | EventType::AlertResolved => {
+                    let key = (e.alert.drone_id.clone(), e.alert.alert_type.clone());
+                    self.state.write().alerts.remove(&key);
+                }
+                _ => self.record_alert(&e.alert),
+            },
+            _ => {}
+        }
+    }
+
+    /// Current statistics; alerts not re-raised within the active window
+    /// are dropped
+    pub fn stats(&self, now: DateTime<Utc>) -> FleetStats {
+        let cutoff = now - chrono::Duration::from_std(ACTIVE_ALERT_WINDOW).unwrap_or_default();
+        let mut state = self.state.write();
+        state.alerts.retain(|_, (_, raised_at)| *raised_at >= cutoff);
+
+        let mut active_alerts = AlertCounts::default();
+        for (severity, _) in state.alerts.values() {
+            active_alerts.add(*severity);
+        }
+
+        FleetStats {
+            drone_count: state.drones.len(),
+            battery: state.battery.stats(),
+            fuel: state.fuel.stats(),
+            status_counts: state.status_counts.clone(),
+            day: state.day,
+            distance_today_km: state.distance_today_km,
+            active_alerts,
+            convoy_spread: state.convoy_spread.clone(),
+            updated_at: state.updated_at,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn telemetry(battery: u8, fuel: u8, at: DateTime<Utc>) -> Telemetry {
+        Telemetry {
+            battery_level: battery,
+            fuel_level: fuel,
+            timestamp: at,
+            ..Telemetry::default()
+        }
+    }
+
+    #[test]
+    fn test_incremental_fleet_stats() {
+        let fleet = FleetStatsService::new();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 23, 59, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let reaper_1 = DroneId::new("REAPER-01");
+        let reaper_2 = DroneId::new("REAPER-02");
+        let base = GeoPosition::new(34.5553, 69.2075, 3000.0);
+        let north = GeoPosition::new(34.5643, 69.2075, 3000.0); // ~1 km north
+
+        fleet.update_status(&reaper_1, DroneStatus::Standby);
+        fleet.update_status(&reaper_2, DroneStatus::Standby);
+        fleet.update_status(&reaper_1, DroneStatus::Moving);
+
+        fleet.update_position(&reaper_1, base, &telemetry(80, 60, at(0)));
+        fleet.update_position(&reaper_2, base, &telemetry(40, 90, at(0)));
+        fleet.update_position(&reaper_1, north, &telemetry(70, 60, at(30)));
+
+        let stats = fleet.stats(at(30));
+        assert_eq!(stats.drone_count, 2);
+        assert_eq!(stats.battery.min, Some(40));
+        assert_eq!(stats.battery.average, Some(55.0));
+        assert_eq!(stats.fuel.min, Some(60));
+        assert_eq!(stats.status_counts["MOVING"], 1);
+        assert_eq!(stats.status_counts["STANDBY"], 1);
+        assert!((stats.distance_today_km - 1.0).abs() < 0.01);
+        let spread = stats.convoy_spread.unwrap();
+        assert!((spread.distance_km - 1.0).abs() < 0.01);
+        assert_eq!(spread.drone_ids, [reaper_1.clone(), reaper_2.clone()]);
+
+        // Repeated alerts for the same condition count once, and expire
+        let low_fuel = Alert::new(AlertSeverity::Warning, AlertType::FuelLow, "Fuel low")
+            .for_drone(reaper_1.clone());
+        fleet.record_alert(&low_fuel);
+        fleet.record_alert(&low_fuel);
+        assert_eq!(fleet.stats(low_fuel.created_at).active_alerts.warning, 1);
+        assert_eq!(fleet.stats(low_fuel.created_at + chrono::Duration::minutes(5)).active_alerts.warning, 0);
+
+        // Distance resets at midnight, and a jump no drone could fly is not counted
+        fleet.update_position(&reaper_1, north, &telemetry(70, 60, at(61)));
+        fleet.update_position(&reaper_1, base, &telemetry(70, 60, at(62)));
+        let stats = fleet.stats(at(62));
+        assert_eq!(stats.day, Some(at(62).date_naive()));
+        assert_eq!(stats.distance_today_km, 0.0);
+    }
+}
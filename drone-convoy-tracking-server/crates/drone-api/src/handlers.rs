@@ -1,22 +1,60 @@
 //! API request handlers
 
+use crate::after_action::{ReportBuilder, ReportFormat};
+use crate::attachments::{self, AttachmentError, NewAttachment};
+use crate::backfill::{self, ImportRequest};
+use crate::clusters::{DEFAULT_CLUSTER_ZOOM, MAX_ZOOM};
 use crate::error::ApiError;
+use crate::export::{self, ExportRequest, ExportStatus};
+use crate::handoff::{HandoffAck, HandoffError};
+use crate::history::FleetStateAt;
+use crate::leadership::LeadershipStatus;
+use crate::logs::LogQuery;
+use crate::mot::{self, MotKind};
+use crate::packages::{PackageError, MAX_PACKAGE_BYTES, PACKAGE_CONTENT_TYPE};
+use crate::pagination::{ListQuery, ListSpec};
+use crate::uploads::{DraftMission, UploadError, MAX_BATCH_WAYPOINTS};
+use crate::simulation;
+use crate::push::{PushPlatform, PushPreferences, PushSubscription};
+use crate::route_render::{width_key, MAX_CORRIDOR_WIDTHS};
+use crate::sse::{self, EventStreamQuery, HEARTBEAT_INTERVAL};
+use crate::stats::SystemStats;
 use crate::state::AppState;
+use crate::supervisor::TaskHealth;
+use crate::timeline::{TimelineQuery, DEFAULT_PAGE_SIZE};
+use crate::tiles::{TileError, TileKey, TileService};
+use crate::validation::{cv_tuning_field, FieldError, Validate, ValidJson, ValidationErrors, MAX_ID_LEN};
 
 use axum::{
-    extract::{Path, State, Query},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{
+        sse::{KeepAlive, Sse},
+        IntoResponse,
+    },
     Json,
 };
+use drone_db::{Enforcement, PurgeReport, RetentionManager, RetentionTable};
+use drone_p2p::{P2pError, PeerRegistration};
+use drone_tracker::{
+    convoy::Formation, expand_members, AltitudeAssignment, BandError, LOWEST_BAND, BulkCommandReport, CheckpointHold, CommandTrigger,
+    ColorTaken, CommandOutcome, CommandResult, CvPublisherStats, CvTuningUpdateError, DroneGroup, DroneQuery, DroneSequenceStats, EnduranceProjection, HandoffFailure, HandoffPackage, RemoteOwner, ReportRejected, ScheduledAction, SourceConflict,
+    AlertRule, Condition, RegisteredSchema, SuppressionRule, SuppressionStats, SuppressionWindow, Zone,
+};
 use drone_core::{
-    Drone, DroneId, DroneStatus, GeoPosition, Mission, MissionStatus,
-    Telemetry, TrackingResult, Alert, AlertSeverity, AlertType,
-    DroneCommand, DroneCommandType,
+    simplify_path, spline_path, AlertPresentation, AlertSeverity, AlertThresholds, AlertType, ConvoyRole, CorridorSpec, CustomEvent, CustomEventError, CvTuning, Drone, DroneCommand, DroneCommandType, DroneId, DroneMarking, DronePresentation, DroneStatus,
+    DroneType, GeoBounds, GeoPosition, Geofence, GimbalState, HaloColor, Mission, MissionBuildError, MissionBuilder, MissionId, RouteMetrics, Telemetry, TelemetryField, TelemetryLimits, TelemetrySource,
+    ThreatLevel, ThresholdOverrides, TrackingResult, TransportBinding, TransportKind, Waypoint, WaypointAttachment,
+    WaypointId, GIMBAL_MAX_TILT, GIMBAL_MAX_ZOOM, GIMBAL_MIN_TILT, MAX_TIME_SCALE, MIN_TIME_SCALE,
 };
+use drone_core::custom::MAX_CUSTOM_TYPE_LEN;
 use serde::{Deserialize, Serialize};
 use chrono::Utc;
-use tracing::{info, debug};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use tracing::info;
 
 // ============================================================================
 // RESPONSE TYPES
@@ -37,12 +75,18 @@ pub struct StatusResponse {
     pub websocket_clients: usize,
     pub active_drones: usize,
     pub mission_status: String,
+    pub leadership: LeadershipStatus,
+    /// Supervised background tasks of this fleet and the shared ones
+    pub tasks: Vec<TaskHealth>,
 }
 
 #[derive(Serialize)]
 pub struct DroneListResponse {
-    pub drones: Vec<DroneResponse>,
+    /// `DroneResponse`s, trimmed to `fields` when given
+    pub drones: Vec<serde_json::Value>,
     pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -54,6 +98,12 @@ pub struct DroneResponse {
     pub telemetry: TelemetryResponse,
     pub armed: bool,
     pub current_waypoint: usize,
+    pub presentation: DronePresentation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<ConvoyRole>,
+    /// Station controlling the drone after a handoff; read-only here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub controlled_by: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -71,6 +121,8 @@ pub struct TelemetryResponse {
     pub speed: f64,
     pub heading: f64,
     pub signal_strength: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gimbal: Option<GimbalState>,
 }
 
 #[derive(Serialize)]
@@ -81,6 +133,29 @@ pub struct MissionResponse {
     pub waypoint_count: usize,
     pub assigned_drones: usize,
     pub total_distance_km: f64,
+    pub enforce_speed_limits: bool,
+}
+
+#[derive(Serialize)]
+pub struct MissionProgressResponse {
+    pub mission_id: String,
+    pub status: String,
+    /// Reserve margin endurance projections are checked against (percent)
+    pub reserve_percent: f64,
+    pub drones: Vec<DroneProgressResponse>,
+}
+
+#[derive(Serialize)]
+pub struct DroneProgressResponse {
+    pub drone_id: String,
+    pub status: String,
+    pub waypoints_reached: usize,
+    /// Progress along the current leg (0.0 - 1.0)
+    pub leg_progress: f64,
+    /// Projected battery and fuel at mission completion
+    pub endurance: Option<EnduranceProjection>,
+    /// Seconds to fly the rest of the route through the estimated wind
+    pub eta_seconds: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -90,6 +165,22 @@ pub struct WaypointResponse {
     pub latitude: f64,
     pub longitude: f64,
     pub waypoint_type: String,
+    /// Route distance from the first waypoint
+    pub cumulative_distance_km: f64,
+    /// Leg arriving at this waypoint (absent for the first one)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leg: Option<LegResponse>,
+    /// Photos, documents and notes, oldest first
+    pub attachments: Vec<WaypointAttachment>,
+}
+
+#[derive(Serialize)]
+pub struct LegResponse {
+    pub from: String,
+    pub distance_km: f64,
+    pub bearing_deg: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_limit_kmh: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -111,6 +202,15 @@ pub struct TrackingStatsResponse {
     pub active_tracks: usize,
     pub cv_enabled: bool,
     pub frames_processed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<CvPublisherStats>,
+}
+
+#[derive(Serialize)]
+pub struct TrackingSubmitResponse {
+    pub accepted: usize,
+    /// Results rejected because the publisher queue was full
+    pub dropped: usize,
 }
 
 #[derive(Serialize)]
@@ -122,6 +222,27 @@ pub struct AlertResponse {
     pub drone_id: Option<String>,
     pub acknowledged: bool,
     pub created_at: String,
+    /// Color, sound, priority and auto-dismiss hints
+    pub presentation: AlertPresentation,
+}
+
+#[derive(Serialize)]
+pub struct DroneThresholdsResponse {
+    pub drone_id: String,
+    pub drone_type: DroneType,
+    pub effective: AlertThresholds,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drone_overrides: Option<ThresholdOverrides>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_overrides: Option<ThresholdOverrides>,
+}
+
+#[derive(Serialize)]
+pub struct TypeThresholdsResponse {
+    pub drone_type: DroneType,
+    pub effective: AlertThresholds,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_overrides: Option<ThresholdOverrides>,
 }
 
 #[derive(Deserialize)]
@@ -131,6 +252,233 @@ pub struct CommandRequest {
     pub params: serde_json::Value,
 }
 
+impl CommandRequest {
+    /// Parse into a typed drone command, checking parameter names and bounds
+    pub fn command_type(&self) -> Result<DroneCommandType, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("command", &self.command, MAX_ID_LEN);
+
+        let empty = serde_json::Map::new();
+        let params = match &self.params {
+            serde_json::Value::Null => &empty,
+            serde_json::Value::Object(params) => params,
+            _ => {
+                errors.add("params", "must be an object");
+                &empty
+            }
+        };
+
+        let (allowed, command): (&[&str], _) = match self.command.to_ascii_lowercase().as_str() {
+            "start" => (&[], Some(DroneCommandType::Start)),
+            "pause" => (&[], Some(DroneCommandType::Pause)),
+            "resume" => (&[], Some(DroneCommandType::Resume)),
+            "return_to_base" | "rtb" => (&[], Some(DroneCommandType::ReturnToBase)),
+            "emergency_stop" => (&[], Some(DroneCommandType::EmergencyStop)),
+            "go_to_waypoint" => {
+                let waypoint_id = params.get("waypoint_id").and_then(|v| v.as_str());
+                match waypoint_id {
+                    Some(id) => errors.check_len("params.waypoint_id", id, MAX_ID_LEN),
+                    None => errors.add("params.waypoint_id", "is required and must be a string"),
+                }
+                (
+                    &["waypoint_id"],
+                    waypoint_id.map(|id| DroneCommandType::GoToWaypoint {
+                        waypoint_id: WaypointId::new(id),
+                    }),
+                )
+            }
+            "set_speed" => {
+                let speed = params.get("speed").and_then(|v| v.as_f64());
+                match speed {
+                    Some(speed) => errors.check_range(
+                        "params.speed",
+                        speed,
+                        0.0,
+                        TelemetryLimits::default().max_speed_kmh,
+                    ),
+                    None => errors.add("params.speed", "is required and must be a number (km/h)"),
+                }
+                (&["speed"], speed.map(|speed| DroneCommandType::SetSpeed { speed }))
+            }
+            "set_altitude" => {
+                let altitude = params.get("altitude").and_then(|v| v.as_f64());
+                match altitude {
+                    Some(altitude) => errors.check_range("params.altitude", altitude, 0.0, MAX_BAND_ALTITUDE_M),
+                    None => errors.add("params.altitude", "is required and must be a number (m)"),
+                }
+                (&["altitude"], altitude.map(|altitude| DroneCommandType::SetAltitude { altitude }))
+            }
+            "set_armed" => {
+                let armed = params.get("armed").and_then(|v| v.as_bool());
+                if armed.is_none() {
+                    errors.add("params.armed", "is required and must be a boolean");
+                }
+                (&["armed"], armed.map(|armed| DroneCommandType::SetArmed { armed }))
+            }
+            "set_gimbal" => {
+                let pan = params.get("pan").and_then(|v| v.as_f64());
+                let tilt = params.get("tilt").and_then(|v| v.as_f64());
+                match pan {
+                    Some(pan) => errors.check_range("params.pan", pan, -180.0, 180.0),
+                    None => errors.add("params.pan", "is required and must be a number (degrees)"),
+                }
+                match tilt {
+                    Some(tilt) => errors.check_range("params.tilt", tilt, GIMBAL_MIN_TILT, GIMBAL_MAX_TILT),
+                    None => errors.add("params.tilt", "is required and must be a number (degrees)"),
+                }
+                let zoom = optional_number(&mut errors, params, "zoom", 1.0, GIMBAL_MAX_ZOOM);
+                (
+                    &["pan", "tilt", "zoom"],
+                    pan.zip(tilt).map(|(pan, tilt)| DroneCommandType::SetGimbal { pan, tilt, zoom }),
+                )
+            }
+            "point_at" => {
+                let latitude = params.get("latitude").and_then(|v| v.as_f64());
+                let longitude = params.get("longitude").and_then(|v| v.as_f64());
+                match latitude {
+                    Some(latitude) => errors.check_range("params.latitude", latitude, -90.0, 90.0),
+                    None => errors.add("params.latitude", "is required and must be a number"),
+                }
+                match longitude {
+                    Some(longitude) => errors.check_range("params.longitude", longitude, -180.0, 180.0),
+                    None => errors.add("params.longitude", "is required and must be a number"),
+                }
+                let altitude = optional_number(&mut errors, params, "altitude", 0.0, MAX_BAND_ALTITUDE_M);
+                let zoom = optional_number(&mut errors, params, "zoom", 1.0, GIMBAL_MAX_ZOOM);
+                (
+                    &["latitude", "longitude", "altitude", "zoom"],
+                    latitude.zip(longitude).map(|(latitude, longitude)| DroneCommandType::PointAt {
+                        target: GeoPosition::new(latitude, longitude, altitude.unwrap_or(0.0)),
+                        zoom,
+                    }),
+                )
+            }
+            _ => {
+                errors.add(
+                    "command",
+                    "must be one of start, pause, resume, return_to_base, emergency_stop, \
+                     go_to_waypoint, set_speed, set_altitude, set_armed, set_gimbal, point_at",
+                );
+                (&[], None)
+            }
+        };
+
+        for key in params.keys().filter(|key| !allowed.contains(&key.as_str())) {
+            errors.add(format!("params.{}", key), "unexpected parameter");
+        }
+
+        match command {
+            Some(command) if errors.is_empty() => Ok(command),
+            _ => Err(errors),
+        }
+    }
+}
+
+/// An optional numeric command parameter, range-checked when present
+fn optional_number(
+    errors: &mut ValidationErrors,
+    params: &serde_json::Map<String, serde_json::Value>,
+    name: &str,
+    min: f64,
+    max: f64,
+) -> Option<f64> {
+    let field = format!("params.{}", name);
+    match params.get(name).map(|v| v.as_f64()) {
+        Some(Some(value)) => {
+            errors.check_range(&field, value, min, max);
+            Some(value)
+        }
+        Some(None) => {
+            errors.add(field, "must be a number");
+            None
+        }
+        None => None,
+    }
+}
+
+impl Validate for CommandRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.command_type().map(|_| ())
+    }
+}
+
+/// Longest operator note on a scheduled command
+pub const MAX_SCHEDULE_DESCRIPTION_LEN: usize = 200;
+
+/// Command to run when a scheduled trigger fires
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledActionRequest {
+    /// `command` and `params` as for `POST /drones/{id}/command`
+    DroneCommand {
+        drone_id: String,
+        #[serde(flatten)]
+        command: CommandRequest,
+    },
+    SetFormation { formation: Formation },
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleCommandRequest {
+    pub trigger: CommandTrigger,
+    pub action: ScheduledActionRequest,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl Validate for ScheduleCommandRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(description) = &self.description {
+            errors.check_len("description", description, MAX_SCHEDULE_DESCRIPTION_LEN);
+        }
+        if let CommandTrigger::Waypoint { waypoint_id, drone_id } = &self.trigger {
+            errors.check_len("trigger.waypoint_id", &waypoint_id.0, MAX_ID_LEN);
+            if let Some(drone_id) = drone_id {
+                errors.check_len("trigger.drone_id", &drone_id.0, MAX_ID_LEN);
+            }
+        }
+        if let ScheduledActionRequest::DroneCommand { drone_id, command } = &self.action {
+            errors.check_len("action.drone_id", drone_id, MAX_ID_LEN);
+            if let Err(command_errors) = command.command_type() {
+                errors.nest("action", command_errors);
+            }
+        }
+        errors.into_result()
+    }
+}
+
+/// Longest single clock step (one hour)
+pub const MAX_CLOCK_STEP_SECONDS: f64 = 3600.0;
+
+/// Simulation clock changes; omitted fields are left as they are
+#[derive(Debug, Deserialize)]
+pub struct ClockUpdateRequest {
+    /// Simulated seconds per wall-clock second
+    pub scale: Option<f64>,
+    pub paused: Option<bool>,
+    /// Advance simulated time by this many seconds
+    pub step_seconds: Option<f64>,
+}
+
+impl Validate for ClockUpdateRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(scale) = self.scale {
+            errors.check_range("scale", scale, MIN_TIME_SCALE, MAX_TIME_SCALE);
+        }
+        if let Some(step) = self.step_seconds {
+            if !(step.is_finite() && step > 0.0 && step <= MAX_CLOCK_STEP_SECONDS) {
+                errors.add(
+                    "step_seconds",
+                    format!("must be greater than 0 and at most {}", MAX_CLOCK_STEP_SECONDS),
+                );
+            }
+        }
+        errors.into_result()
+    }
+}
+
 // ============================================================================
 // HEALTH & STATUS HANDLERS
 // ============================================================================
@@ -168,17 +516,32 @@ pub async fn system_status(State(state): State<AppState>) -> impl IntoResponse {
     Json(StatusResponse {
         api: "running".into(),
         database: if state.has_db() { "connected" } else { "unavailable" }.into(),
-        //cv_engine: if state.has_cv() { "active" } else { "disabled" }.into(),
-        cv_engine: "disabled".into(),
+        cv_engine: if state.tracker.cv_pipeline().is_some() { "active" } else { "disabled" }.into(),
         websocket_clients: state.ws_client_count(),
         active_drones: state.drones.len(),
         mission_status,
+        leadership: state.leadership.status(),
+        tasks: state.tasks.health_of(state.tenant.as_ref()),
     })
 }
 
+/// Operator consoles connected over WebSocket and what each is viewing
+pub async fn list_operators(State(state): State<AppState>) -> impl IntoResponse {
+    let operators = state.ws_hub.presence(state.tenant.as_ref());
+    Json(serde_json::json!({
+        "total": operators.len(),
+        "operators": operators,
+    }))
+}
+
+/// Uptime, error counts and counters of every subsystem
+pub async fn get_system_stats(State(state): State<AppState>) -> impl IntoResponse {
+    Json(SystemStats::collect(&state))
+}
+
 /// Prometheus metrics endpoint
 pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
-    let metrics = format!(
+    let mut metrics = format!(
         r#"# HELP drone_convoy_drones_total Total number of drones
 # TYPE drone_convoy_drones_total gauge
 drone_convoy_drones_total {}
@@ -202,6 +565,148 @@ drone_convoy_db_connected {}
         if state.has_db() { 1 } else { 0 },
     );
 
+    let compression = state.ws_hub.compression_stats();
+    metrics.push_str(&format!(
+        "\n# HELP drone_convoy_websocket_compressed_messages_total WebSocket messages sent compressed\n\
+         # TYPE drone_convoy_websocket_compressed_messages_total counter\n\
+         drone_convoy_websocket_compressed_messages_total {}\n\
+         \n# HELP drone_convoy_websocket_uncompressed_messages_total Messages to deflate clients below the size threshold\n\
+         # TYPE drone_convoy_websocket_uncompressed_messages_total counter\n\
+         drone_convoy_websocket_uncompressed_messages_total {}\n\
+         \n# HELP drone_convoy_websocket_compression_bytes_total Compressed message bytes before and after deflate\n\
+         # TYPE drone_convoy_websocket_compression_bytes_total counter\n\
+         drone_convoy_websocket_compression_bytes_total{{stage=\"in\"}} {}\n\
+         drone_convoy_websocket_compression_bytes_total{{stage=\"out\"}} {}\n\
+         \n# HELP drone_convoy_websocket_compression_ratio Compressed size over original size\n\
+         # TYPE drone_convoy_websocket_compression_ratio gauge\n\
+         drone_convoy_websocket_compression_ratio {:.4}\n",
+        compression.compressed_messages,
+        compression.skipped_messages,
+        compression.bytes_in,
+        compression.bytes_out,
+        compression.ratio(),
+    ));
+
+    metrics.push_str(
+        "\n# HELP drone_convoy_websocket_throttled_total Inbound WebSocket messages dropped by rate limits and quotas\n\
+         # TYPE drone_convoy_websocket_throttled_total counter\n",
+    );
+    for throttled in state.ws_hub.throttle_stats() {
+        metrics.push_str(&format!(
+            "drone_convoy_websocket_throttled_total{{message_type=\"{}\",reason=\"{}\"}} {}\n",
+            throttled.message_type,
+            throttled.reason.as_str(),
+            throttled.count
+        ));
+    }
+
+    let validation = state.tracker.telemetry_validator();
+    metrics.push_str(
+        "\n# HELP drone_convoy_telemetry_rejected_total Telemetry samples rejected, by field\n\
+         # TYPE drone_convoy_telemetry_rejected_total counter\n",
+    );
+    for field in TelemetryField::ALL {
+        metrics.push_str(&format!(
+            "drone_convoy_telemetry_rejected_total{{field=\"{}\"}} {}\n",
+            field,
+            validation.rejected(field)
+        ));
+    }
+    metrics.push_str(
+        "\n# HELP drone_convoy_telemetry_clamped_total Telemetry fields clamped into range\n\
+         # TYPE drone_convoy_telemetry_clamped_total counter\n",
+    );
+    for field in TelemetryField::ALL {
+        metrics.push_str(&format!(
+            "drone_convoy_telemetry_clamped_total{{field=\"{}\"}} {}\n",
+            field,
+            validation.clamped(field)
+        ));
+    }
+
+    let suppression = state.tracker.suppression_stats();
+    metrics.push_str(
+        "\n# HELP drone_convoy_alerts_suppressed_total Alerts dropped by suppression windows, by alert type\n\
+         # TYPE drone_convoy_alerts_suppressed_total counter\n",
+    );
+    let mut suppressed: Vec<_> = suppression.by_type.into_iter().collect();
+    suppressed.sort();
+    for (alert_type, count) in suppressed {
+        metrics.push_str(&format!(
+            "drone_convoy_alerts_suppressed_total{{alert_type=\"{}\"}} {}\n",
+            alert_type, count
+        ));
+    }
+
+    let sequences = state.tracker.sequence_stats();
+    type Counter = (&'static str, &'static str, fn(&DroneSequenceStats) -> u64);
+    let counters: [Counter; 5] = [
+        ("duplicates", "Position reports dropped as duplicate deliveries", |s| s.duplicates),
+        ("sequence_gaps", "Jumps ahead in a drone's report sequence", |s| s.gaps),
+        ("missing", "Position reports never received, from sequence gaps", |s| s.missing),
+        ("late", "Position reports dropped for arriving after a newer one", |s| s.late),
+        ("sequence_restarts", "Drones that started numbering reports again", |s| s.restarts),
+    ];
+    for (name, help, value) in counters {
+        metrics.push_str(&format!(
+            "\n# HELP drone_convoy_telemetry_{name}_total {help}\n\
+             # TYPE drone_convoy_telemetry_{name}_total counter\n"
+        ));
+        for stats in &sequences {
+            metrics.push_str(&format!(
+                "drone_convoy_telemetry_{}_total{{drone_id=\"{}\"}} {}\n",
+                name,
+                stats.drone_id,
+                value(stats)
+            ));
+        }
+    }
+
+    let push = state.push.stats();
+    metrics.push_str(&format!(
+        "\n# HELP drone_convoy_push_subscriptions Devices subscribed to critical alert pushes\n\
+         # TYPE drone_convoy_push_subscriptions gauge\n\
+         drone_convoy_push_subscriptions {}\n\
+         \n# HELP drone_convoy_push_retries_total Push attempts retried after a transient failure\n\
+         # TYPE drone_convoy_push_retries_total counter\n\
+         drone_convoy_push_retries_total {}\n\
+         \n# HELP drone_convoy_push_deliveries_total Finished pushes, by platform and status\n\
+         # TYPE drone_convoy_push_deliveries_total counter\n",
+        push.subscriptions, push.retries,
+    ));
+    for ((platform, status), count) in &push.deliveries {
+        metrics.push_str(&format!(
+            "drone_convoy_push_deliveries_total{{platform=\"{}\",status=\"{}\"}} {}\n",
+            platform, status, count
+        ));
+    }
+
+    if let Some(retention) = &state.retention {
+        metrics.push_str(
+            "\n# HELP drone_convoy_retention_purged_rows_total Rows deleted by retention purge jobs, by table\n\
+             # TYPE drone_convoy_retention_purged_rows_total counter\n",
+        );
+        for table in RetentionTable::ALL {
+            metrics.push_str(&format!(
+                "drone_convoy_retention_purged_rows_total{{table=\"{}\"}} {}\n",
+                table.table_name(),
+                retention.purged_total(table)
+            ));
+        }
+    }
+
+    // Mission KPIs maintained by the tracker
+    metrics.push('\n');
+    metrics.push_str(&state.tracker.metrics().export_mission_kpis());
+
+    // Tracker entry counts, memory estimate and evictions
+    metrics.push('\n');
+    metrics.push_str(&state.tracker.metrics().export_tracker_metrics());
+
+    // Position update latency per hop
+    metrics.push('\n');
+    metrics.push_str(&state.tracker.metrics().export_latency_metrics());
+
     (StatusCode::OK, [("content-type", "text/plain")], metrics)
 }
 
@@ -209,15 +714,242 @@ drone_convoy_db_connected {}
 // DRONE HANDLERS
 // ============================================================================
 
-/// List all drones
-pub async fn list_drones(State(state): State<AppState>) -> impl IntoResponse {
-    let drones: Vec<DroneResponse> = state.get_all_drones()
+/// Drone list filters; every filter given must match
+#[derive(Debug, Default, Deserialize)]
+pub struct DroneListQuery {
+    /// Comma-separated statuses, e.g. `MOVING,RTB`
+    pub status: Option<String>,
+    pub min_battery: Option<f64>,
+    pub max_battery: Option<f64>,
+    pub min_fuel: Option<f64>,
+    pub max_fuel: Option<f64>,
+    /// Ground speed range (km/h)
+    pub min_speed: Option<f64>,
+    pub max_speed: Option<f64>,
+    /// `lat,lng,radius_km`
+    pub near: Option<String>,
+    /// `min_lat,min_lng,max_lat,max_lng`
+    pub bbox: Option<String>,
+    /// Mission ID the drone is assigned to
+    pub mission: Option<String>,
+}
+
+impl DroneListQuery {
+    /// Build the tracker query, checking every parameter
+    pub fn to_query(&self) -> Result<DroneQuery, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let mut query = DroneQuery::new();
+
+        if let Some(status) = &self.status {
+            let mut statuses = Vec::new();
+            for value in status.split(',').map(str::trim) {
+                match serde_json::from_value::<DroneStatus>(serde_json::Value::String(value.to_uppercase())) {
+                    Ok(status) => statuses.push(status),
+                    Err(_) => errors.add("status", format!("unknown status {}", value)),
+                }
+            }
+            query = query.status(statuses);
+        }
+
+        let ranges = [
+            ("battery", TelemetryField::BatteryLevel, self.min_battery, self.max_battery, 100.0),
+            ("fuel", TelemetryField::FuelLevel, self.min_fuel, self.max_fuel, 100.0),
+            (
+                "speed",
+                TelemetryField::Speed,
+                self.min_speed,
+                self.max_speed,
+                TelemetryLimits::default().max_speed_kmh,
+            ),
+        ];
+        for (name, field, min, max, limit) in ranges {
+            if min.is_none() && max.is_none() {
+                continue;
+            }
+            if let Some(min) = min {
+                errors.check_range(&format!("min_{}", name), min, 0.0, limit);
+            }
+            if let Some(max) = max {
+                errors.check_range(&format!("max_{}", name), max, 0.0, limit);
+            }
+            query = query.telemetry(field, min, max);
+        }
+
+        if let Some(near) = &self.near {
+            match parse_numbers::<3>(near) {
+                Some([lat, lng, radius_km]) => {
+                    errors.check_latitude("near", lat);
+                    errors.check_longitude("near", lng);
+                    errors.check_range("near", radius_km, 0.0, MAX_QUERY_RADIUS_KM);
+                    query = query.near(GeoPosition::new(lat, lng, 0.0), radius_km);
+                }
+                None => errors.add("near", "must be lat,lng,radius_km"),
+            }
+        }
+
+        if let Some(bbox) = &self.bbox {
+            match parse_numbers::<4>(bbox) {
+                Some([min_lat, min_lng, max_lat, max_lng]) => {
+                    errors.check_latitude("bbox", min_lat);
+                    errors.check_latitude("bbox", max_lat);
+                    errors.check_longitude("bbox", min_lng);
+                    errors.check_longitude("bbox", max_lng);
+                    query = query.within(GeoBounds::new(min_lat, max_lat, min_lng, max_lng));
+                }
+                None => errors.add("bbox", "must be min_lat,min_lng,max_lat,max_lng"),
+            }
+        }
+
+        if let Some(mission) = &self.mission {
+            match Uuid::parse_str(mission.trim()) {
+                Ok(id) => query = query.mission(MissionId(id)),
+                Err(_) => errors.add("mission", "must be a mission UUID"),
+            }
+        }
+
+        errors.into_result().map(|_| query)
+    }
+}
+
+/// Largest `near` radius (km)
+pub const MAX_QUERY_RADIUS_KM: f64 = 20_000.0;
+
+/// Parse exactly `N` comma-separated numbers
+fn parse_numbers<const N: usize>(value: &str) -> Option<[f64; N]> {
+    let numbers: Vec<f64> = value
+        .split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    numbers.try_into().ok()
+}
+
+/// Sorting and fields of the drone list
+const DRONE_LIST: ListSpec = ListSpec {
+    id_field: "id",
+    sortable: &[
+        "id",
+        "callsign",
+        "status",
+        "telemetry.battery_level",
+        "telemetry.fuel_level",
+        "telemetry.speed",
+        "position.altitude",
+    ],
+    selectable: &[
+        "callsign",
+        "status",
+        "position",
+        "telemetry",
+        "armed",
+        "current_waypoint",
+        "presentation",
+        "role",
+        "controlled_by",
+    ],
+    default_sort: "id",
+    default_descending: false,
+    ranked: &[],
+};
+
+/// List drones, optionally filtered by status, telemetry, area and mission
+pub async fn list_drones(
+    State(state): State<AppState>,
+    Query(query): Query<DroneListQuery>,
+    Query(list): Query<ListQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let query = query.to_query()?;
+    let list = list.params(DRONE_LIST)?;
+    let drones = state
+        .tracker
+        .query_drones(&query)
         .into_iter()
-        .map(drone_to_response)
-        .collect();
+        .map(|tracked| drone_to_response(&state, tracked.drone));
+
+    let page = list.paginate(drones);
+    Ok(Json(DroneListResponse {
+        drones: page.items,
+        total: page.total,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// Query parameters for drone clustering
+#[derive(Debug, Deserialize)]
+pub struct ClusterQuery {
+    /// Map zoom level (0 = whole world)
+    pub zoom: Option<u8>,
+}
+
+/// Get drones grouped into geohash clusters for a map zoom level
+pub async fn get_drone_clusters(
+    State(state): State<AppState>,
+    Query(query): Query<ClusterQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let zoom = query.zoom.unwrap_or(DEFAULT_CLUSTER_ZOOM);
+    if zoom > MAX_ZOOM {
+        return Err(ApiError::validation(
+            "zoom",
+            format!("must be between 0 and {}", MAX_ZOOM),
+        ));
+    }
+
+    Ok(Json(state.clusters.clusters(zoom)))
+}
+
+/// Query parameters for the coverage heatmap
+#[derive(Debug, Deserialize)]
+pub struct HeatmapQuery {
+    /// `min_lat,min_lng,max_lat,max_lng`
+    pub bounds: Option<String>,
+}
+
+impl HeatmapQuery {
+    /// Parse and check the map viewport
+    pub fn to_bounds(&self) -> Result<Option<GeoBounds>, ValidationErrors> {
+        let Some(bounds) = &self.bounds else {
+            return Ok(None);
+        };
+        let mut errors = ValidationErrors::new();
+        let mut parsed = None;
+        match parse_numbers::<4>(bounds) {
+            Some([min_lat, min_lng, max_lat, max_lng]) => {
+                errors.check_latitude("bounds", min_lat);
+                errors.check_latitude("bounds", max_lat);
+                errors.check_longitude("bounds", min_lng);
+                errors.check_longitude("bounds", max_lng);
+                if min_lat > max_lat || min_lng > max_lng {
+                    errors.add("bounds", "minimum must not exceed maximum");
+                }
+                parsed = Some(GeoBounds::new(min_lat, max_lat, min_lng, max_lng));
+            }
+            None => errors.add("bounds", "must be min_lat,min_lng,max_lat,max_lng"),
+        }
+        errors.into_result().map(|_| parsed)
+    }
+}
+
+/// Time-decayed coverage intensities of the geohash cells in the viewport
+pub async fn get_coverage_heatmap(
+    State(state): State<AppState>,
+    Query(query): Query<HeatmapQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let bounds = query.to_bounds()?;
+    Ok(Json(state.coverage.heatmap(bounds.as_ref(), state.tracker.clock().now())))
+}
 
-    let total = drones.len();
-    Json(DroneListResponse { drones, total })
+/// Route version published to drone agents and each drone's sync state
+pub async fn get_mission_sync(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.mission_sync_status())
+}
+
+/// Wind per grid cell, estimated from the drift of every drone
+pub async fn get_wind_field(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.wind_field())
+}
+
+/// Fleet-wide battery, fuel, status, distance, alert and spread aggregates
+pub async fn get_fleet_stats(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.fleet_stats.stats(Utc::now()))
 }
 
 /// Get single drone by ID
@@ -228,7 +960,7 @@ pub async fn get_drone(
     let drone_id = DroneId::new(&id);
     
     state.get_drone(&drone_id)
-        .map(|d| Json(drone_to_response(d)))
+        .map(|d| Json(drone_to_response(&state, d)))
         .ok_or_else(|| ApiError::not_found(format!("Drone {} not found", id)))
 }
 
@@ -247,10 +979,128 @@ pub async fn get_drone_telemetry(
             speed: d.telemetry.speed,
             heading: d.telemetry.heading,
             signal_strength: d.telemetry.signal_strength,
+            gimbal: d.telemetry.gimbal,
         }))
         .ok_or_else(|| ApiError::not_found(format!("Drone {} not found", id)))
 }
 
+/// Query parameters for a drone's position history
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Return a display-ready trail instead of the raw fixes
+    #[serde(default)]
+    pub smooth: bool,
+    /// Simplification tolerance (meters, default 10)
+    pub tolerance_m: Option<f64>,
+    /// Spline points per segment after simplification (default 4, 1 = none)
+    pub spline_samples: Option<usize>,
+}
+
+/// Largest accepted simplification tolerance (meters)
+const MAX_SMOOTHING_TOLERANCE_M: f64 = 1000.0;
+
+/// Largest accepted spline points per segment
+const MAX_SPLINE_SAMPLES: usize = 16;
+
+#[derive(Serialize)]
+pub struct HistoryPoint {
+    pub timestamp: chrono::DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+#[derive(Serialize)]
+pub struct HistoryResponse {
+    pub drone_id: String,
+    pub smoothed: bool,
+    /// Fixes in the raw history
+    pub raw_points: usize,
+    pub points: Vec<HistoryPoint>,
+}
+
+/// Get a drone's recent positions, optionally simplified and spline-smoothed
+pub async fn get_drone_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tolerance_m = query.tolerance_m.unwrap_or(10.0);
+    if !(0.0..=MAX_SMOOTHING_TOLERANCE_M).contains(&tolerance_m) {
+        return Err(ApiError::validation(
+            "tolerance_m",
+            format!("must be between 0 and {}", MAX_SMOOTHING_TOLERANCE_M),
+        ));
+    }
+    let spline_samples = query.spline_samples.unwrap_or(4);
+    if !(1..=MAX_SPLINE_SAMPLES).contains(&spline_samples) {
+        return Err(ApiError::validation(
+            "spline_samples",
+            format!("must be between 1 and {}", MAX_SPLINE_SAMPLES),
+        ));
+    }
+
+    let history = state
+        .tracker
+        .get_drone(&DroneId::new(&id))
+        .map(|tracked| tracked.position_history)
+        .ok_or_else(|| ApiError::not_found(format!("Drone {} not found", id)))?;
+
+    let fixes = if query.smooth {
+        smooth_history(&history, tolerance_m, spline_samples)
+    } else {
+        history.clone()
+    };
+
+    Ok(Json(HistoryResponse {
+        drone_id: id,
+        smoothed: query.smooth,
+        raw_points: history.len(),
+        points: fixes
+            .into_iter()
+            .map(|(timestamp, p)| HistoryPoint {
+                timestamp,
+                latitude: p.latitude,
+                longitude: p.longitude,
+                altitude: p.altitude,
+            })
+            .collect(),
+    }))
+}
+
+/// Simplify a timed track, then spline it; interpolated points get times
+/// interpolated between their segment's endpoints
+fn smooth_history(
+    history: &[(chrono::DateTime<Utc>, GeoPosition)],
+    tolerance_m: f64,
+    spline_samples: usize,
+) -> Vec<(chrono::DateTime<Utc>, GeoPosition)> {
+    let positions: Vec<GeoPosition> = history.iter().map(|(_, p)| *p).collect();
+    let kept: Vec<_> = simplify_path(&positions, tolerance_m)
+        .into_iter()
+        .map(|i| history[i])
+        .collect();
+
+    let kept_positions: Vec<GeoPosition> = kept.iter().map(|(_, p)| *p).collect();
+    let smoothed = spline_path(&kept_positions, spline_samples);
+    if smoothed.len() == kept.len() {
+        return kept;
+    }
+
+    smoothed
+        .into_iter()
+        .enumerate()
+        .map(|(i, position)| {
+            let segment = (i / spline_samples).min(kept.len() - 1);
+            let (start, _) = kept[segment];
+            let end = kept.get(segment + 1).map_or(start, |(t, _)| *t);
+            let fraction = (i % spline_samples) as f64 / spline_samples as f64;
+            let offset_ms = ((end - start).num_milliseconds() as f64 * fraction) as i64;
+            (start + chrono::Duration::milliseconds(offset_ms), position)
+        })
+        .collect()
+}
+
 /// Get drone position
 pub async fn get_drone_position(
     State(state): State<AppState>,
@@ -267,29 +1117,297 @@ pub async fn get_drone_position(
         .ok_or_else(|| ApiError::not_found(format!("Drone {} not found", id)))
 }
 
+/// Get the fused GPS/CV position and per-source residuals for a drone
+pub async fn get_drone_fusion(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    state
+        .tracker
+        .fusion_report(&drone.id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No position fixes for drone {} yet", id)))
+}
+
+/// Icon, color and blink rules used for drone presentation hints
+pub async fn get_presentation_rules(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.presentation.rules().clone())
+}
+
+/// Drone reachability and mesh partitions, with messages buffered for
+/// unreachable drones
+pub async fn get_mesh_partitions(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .reachability()
+        .map(Json)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))
+}
+
+/// This node's view of the mesh: peers, connections, gossip subscriptions
+/// and the drone registered to each peer
+pub async fn get_p2p_topology(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .mesh_topology()
+        .map(Json)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))
+}
+
+/// Register a drone's P2P public key for the peer allow-list
+#[derive(Debug, Deserialize)]
+pub struct PeerKeyRequest {
+    /// Hex: 32 raw Ed25519 bytes or a protobuf-encoded libp2p public key
+    pub public_key: String,
+}
+
+impl Validate for PeerKeyRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("public_key", &self.public_key, 1024);
+        errors.into_result()
+    }
+}
+
+impl From<P2pError> for ApiError {
+    fn from(err: P2pError) -> Self {
+        match err {
+            P2pError::InvalidKey(_) => ApiError::validation("public_key", err.to_string()),
+            P2pError::PeerNotFound(_) => ApiError::NotFound(err.to_string()),
+            _ => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+/// Whether the peer allow-list is on, registered peer keys and recently
+/// rejected peers
+pub async fn get_p2p_peers(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .peer_allow_list()
+        .map(Json)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))
+}
+
+/// Register (or replace) the peer public key for a drone
+pub async fn put_p2p_peer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<PeerKeyRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone_id = DroneId::new(&id);
+    let peer_id = state
+        .tracker
+        .register_peer_key(&drone_id, &req.public_key)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))??;
+    info!("Registered peer {} for drone {}", peer_id, drone_id);
+
+    Ok(Json(PeerRegistration { drone_id, peer_id }))
+}
+
+/// Remove a drone's peer key; under the allow-list the peer is disconnected
+pub async fn delete_p2p_peer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone_id = DroneId::new(&id);
+    state
+        .tracker
+        .unregister_peer(&drone_id)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))?
+        .ok_or_else(|| ApiError::not_found(format!("No peer registered for drone {}", drone_id)))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reordering counters for P2P position updates
+pub async fn get_mesh_jitter(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .p2p_jitter_stats()
+        .map(Json)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))
+}
+
+/// Link shaping decisions per message priority
+pub async fn get_mesh_shaping(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .p2p_shaping_stats()
+        .map(Json)
+        .ok_or_else(|| ApiError::ServiceUnavailable("P2P networking is disabled".into()))
+}
+
 /// Send command to drone
 pub async fn send_drone_command(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(req): Json<CommandRequest>,
+    ValidJson(req): ValidJson<CommandRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     let drone_id = DroneId::new(&id);
     
     if state.get_drone(&drone_id).is_none() {
         return Err(ApiError::not_found(format!("Drone {} not found", id)));
     }
+    let command = req.command_type()?;
+    let result = execute_drone_command(&state, &drone_id, &command).await?;
 
-    info!("Command {} sent to drone {}", req.command, id);
+    if let Some(mission) = state.get_mission() {
+        state.timeline.record_command(&mission, &drone_id, &req.command, &req.params);
+    }
 
-    // In real implementation, this would send command via P2P or queue
     Ok(Json(serde_json::json!({
-        "status": "accepted",
+        "status": result.outcome,
         "drone_id": id,
         "command": req.command,
+        "transport": state.tracker.transport_binding(&drone_id).kind,
     })))
 }
 
-/// Reset simulation to starting positions
+/// Run a drone command sent by a WebSocket console, with the same checks
+/// as `send_drone_command`
+pub async fn run_client_command(state: &AppState, command: DroneCommand) -> Result<CommandResult, ApiError> {
+    let DroneCommand { drone_id, command } = command;
+    if state.get_drone(&drone_id).is_none() {
+        return Err(ApiError::not_found(format!("Drone {} not found", drone_id)));
+    }
+    let result = execute_drone_command(state, &drone_id, &command).await?;
+
+    if let Some(mission) = state.get_mission() {
+        // Recorded in the command's wire form: its `type` and `params`
+        let wire = serde_json::to_value(&command).unwrap_or_default();
+        let name = wire["type"].as_str().unwrap_or_default();
+        state.timeline.record_command(&mission, &drone_id, name, &wire["params"]);
+    }
+    Ok(result)
+}
+
+/// Send a command to a locally controlled drone; a waypoint must be part
+/// of the active mission
+async fn execute_drone_command(
+    state: &AppState,
+    drone_id: &DroneId,
+    command: &DroneCommandType,
+) -> Result<CommandResult, ApiError> {
+    check_locally_controlled(state, drone_id)?;
+    if let DroneCommandType::GoToWaypoint { waypoint_id } = command {
+        check_mission_waypoint(state, "params.waypoint_id", waypoint_id)?;
+    }
+
+    let result = state.tracker.send_command(drone_id, command).await;
+    match result.outcome {
+        CommandOutcome::TransportError => Err(ApiError::BadGateway(result.error.unwrap_or_default())),
+        CommandOutcome::Failed | CommandOutcome::NotFound => Err(ApiError::Conflict(result.error.unwrap_or_default())),
+        CommandOutcome::Sent | CommandOutcome::Accepted => Ok(result),
+    }
+}
+
+/// Reject waypoints that are not part of the active mission
+fn check_mission_waypoint(
+    state: &AppState,
+    field: &str,
+    waypoint_id: &WaypointId,
+) -> Result<(), ApiError> {
+    let known = state
+        .get_mission()
+        .is_some_and(|m| m.waypoints.iter().any(|wp| &wp.id == waypoint_id));
+    if !known {
+        return Err(ApiError::validation(
+            field,
+            format!("waypoint {} is not part of the active mission", waypoint_id),
+        ));
+    }
+    Ok(())
+}
+
+/// Schedule a command for a simulated time or waypoint arrival
+pub async fn schedule_command(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ScheduleCommandRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let action = match req.action {
+        ScheduledActionRequest::DroneCommand { drone_id, command } => {
+            let drone_id = DroneId::new(drone_id);
+            if state.get_drone(&drone_id).is_none() {
+                return Err(ApiError::validation(
+                    "action.drone_id",
+                    format!("drone {} not found", drone_id),
+                ));
+            }
+            let command = command.command_type()?;
+            if let DroneCommandType::GoToWaypoint { waypoint_id } = &command {
+                check_mission_waypoint(&state, "action.params.waypoint_id", waypoint_id)?;
+            }
+            ScheduledAction::DroneCommand { drone_id, command }
+        }
+        ScheduledActionRequest::SetFormation { formation } => {
+            ScheduledAction::SetFormation { formation }
+        }
+    };
+
+    match &req.trigger {
+        CommandTrigger::At { at } => {
+            if *at <= state.clock.now() {
+                return Err(ApiError::validation(
+                    "trigger.at",
+                    "must be later than the current simulation time",
+                ));
+            }
+        }
+        CommandTrigger::Waypoint { waypoint_id, drone_id } => {
+            check_mission_waypoint(&state, "trigger.waypoint_id", waypoint_id)?;
+            if let Some(drone_id) = drone_id.as_ref().filter(|d| state.get_drone(d).is_none()) {
+                return Err(ApiError::validation(
+                    "trigger.drone_id",
+                    format!("drone {} not found", drone_id),
+                ));
+            }
+        }
+    }
+
+    let command = state
+        .tracker
+        .schedule_command(req.trigger, action, req.description)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    Ok((StatusCode::CREATED, Json(command)))
+}
+
+/// List pending, fired and cancelled scheduled commands
+pub async fn list_scheduled_commands(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    Json(state.tracker.scheduler().list())
+}
+
+/// Cancel a pending scheduled command
+pub async fn cancel_scheduled_command(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let schedule_id = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("Invalid scheduled command id: {}", id)))?;
+
+    let scheduled = state
+        .tracker
+        .scheduler()
+        .get(&schedule_id)
+        .ok_or_else(|| ApiError::not_found(format!("Scheduled command {} not found", id)))?;
+    let cancelled = state
+        .tracker
+        .cancel_scheduled_command(&schedule_id)
+        .await
+        .ok_or_else(|| {
+            ApiError::bad_request(format!(
+                "Scheduled command {} is already {}",
+                id,
+                scheduled.state.as_str()
+            ))
+        })?;
+    Ok(Json(cancelled))
+}
+
 /// Reset simulation to starting positions
 pub async fn reset_simulation(
     State(state): State<AppState>,
@@ -308,136 +1426,2847 @@ pub async fn reset_simulation(
 }
 
 // ============================================================================
-// MISSION HANDLERS
+// ALERT THRESHOLD HANDLERS
 // ============================================================================
 
-/// Get current mission
-pub async fn get_mission(State(state): State<AppState>) -> impl IntoResponse {
-    match state.get_mission() {
-        Some(mission) => Json(Some(mission_to_response(&mission))),
-        None => Json(None),
-    }
+/// Get effective alert thresholds and overrides for a drone
+pub async fn get_drone_thresholds(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    Ok(Json(drone_thresholds_response(&state, &drone)))
 }
 
-/// Start mission
-pub async fn start_mission(State(state): State<AppState>) -> impl IntoResponse {
-    if let Some(mut mission) = state.active_mission.write().take() {
-        mission.start();
-        *state.active_mission.write() = Some(mission.clone());
-        info!("Mission {} started", mission.name);
-        Json(serde_json::json!({"status": "started", "mission": mission.name}))
-    } else {
-        Json(serde_json::json!({"status": "error", "message": "No active mission"}))
-    }
-}
+/// Replace a drone's alert threshold overrides
+pub async fn set_drone_thresholds(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(overrides): ValidJson<ThresholdOverrides>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+
+    state.tracker
+        .resolve_thresholds(&drone.drone_type, Some(&overrides))
+        .validate()
+        .map_err(|e| ApiError::validation("thresholds", e))?;
+
+    state.tracker.set_drone_thresholds(&drone.id, overrides).await?;
+    info!("Alert thresholds updated for drone {}", id);
+
+    Ok(Json(drone_thresholds_response(&state, &drone)))
+}
+
+/// Remove a drone's alert threshold overrides
+pub async fn clear_drone_thresholds(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+
+    state.tracker
+        .set_drone_thresholds(&drone.id, ThresholdOverrides::default())
+        .await?;
+    info!("Alert thresholds cleared for drone {}", id);
+
+    Ok(Json(drone_thresholds_response(&state, &drone)))
+}
+
+/// Get alert threshold overrides for a drone type
+pub async fn get_type_thresholds(
+    State(state): State<AppState>,
+    Path(drone_type): Path<String>,
+) -> impl IntoResponse {
+    let drone_type = parse_drone_type(&drone_type);
+    Json(type_thresholds_response(&state, drone_type))
+}
+
+/// Replace alert threshold overrides for a drone type
+pub async fn set_type_thresholds(
+    State(state): State<AppState>,
+    Path(drone_type): Path<String>,
+    ValidJson(overrides): ValidJson<ThresholdOverrides>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone_type = parse_drone_type(&drone_type);
+
+    overrides
+        .apply_to(state.tracker.global_thresholds())
+        .validate()
+        .map_err(|e| ApiError::validation("thresholds", e))?;
+
+    state.tracker.set_type_thresholds(drone_type.clone(), overrides).await?;
+    info!("Alert thresholds updated for drone type {:?}", drone_type);
+
+    Ok(Json(type_thresholds_response(&state, drone_type)))
+}
+
+// ============================================================================
+// CONVOY ROLE HANDLERS
+// ============================================================================
+
+/// Convoy role assignment (`null` clears the role)
+#[derive(Deserialize)]
+pub struct DroneRoleRequest {
+    pub role: Option<ConvoyRole>,
+}
+
+impl Validate for DroneRoleRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        Ok(())
+    }
+}
+
+/// Assign a drone's convoy role, which sets its formation slot and alert policy
+pub async fn set_drone_role(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<DroneRoleRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    state.tracker.set_drone_role(&drone.id, req.role);
+
+    Ok(Json(drone_to_response(&state, drone)))
+}
+
+// ============================================================================
+// ALTITUDE BAND HANDLERS
+// ============================================================================
+
+/// Highest band centre an operator may assign (m)
+pub const MAX_BAND_ALTITUDE_M: f64 = 20_000.0;
+
+/// Band size, enforcement and every drone's band
+#[derive(Serialize)]
+pub struct AltitudeBandsResponse {
+    pub band_size_m: f64,
+    pub enforce: bool,
+    pub assignments: Vec<AltitudeAssignment>,
+}
+
+/// Current altitude band assignments
+pub async fn get_altitude_bands(State(state): State<AppState>) -> impl IntoResponse {
+    let config = state.tracker.altitude_config();
+    Json(AltitudeBandsResponse {
+        band_size_m: config.band_size_m,
+        enforce: config.enforce,
+        assignments: state.tracker.altitude_assignments(),
+    })
+}
+
+/// Band to move a drone to
+#[derive(Deserialize)]
+pub struct AltitudeBandRequest {
+    pub band: i32,
+}
+
+impl Validate for AltitudeBandRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.band < LOWEST_BAND {
+            errors.add("band", format!("must be at least {}", LOWEST_BAND));
+        }
+        errors.into_result()
+    }
+}
+
+/// Move a drone to a free altitude band
+pub async fn set_drone_altitude_band(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<AltitudeBandRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    let band_size = state.tracker.altitude_config().band_size_m;
+    if req.band as f64 * band_size > MAX_BAND_ALTITUDE_M {
+        return Err(ApiError::validation(
+            "band",
+            format!("must be centred at or below {} m", MAX_BAND_ALTITUDE_M),
+        ));
+    }
+
+    Ok(Json(state.tracker.assign_altitude_band(&drone.id, req.band)?))
+}
+
+impl From<BandError> for ApiError {
+    fn from(err: BandError) -> Self {
+        match err {
+            BandError::Taken { .. } => ApiError::Conflict(err.to_string()),
+            BandError::BelowLowest(_) => ApiError::validation("band", err.to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// DRONE GROUP HANDLERS
+// ============================================================================
+
+/// Maximum length of a group description
+pub const MAX_GROUP_DESCRIPTION_LEN: usize = 200;
+
+/// Group definition; `members` entries may be ranges like `REAPER-01..04`
+#[derive(Deserialize)]
+pub struct GroupRequest {
+    #[serde(default)]
+    pub members: Vec<String>,
+    /// Drones holding this convoy role are members too
+    pub role: Option<ConvoyRole>,
+    pub description: Option<String>,
+}
+
+impl GroupRequest {
+    /// Member IDs with ranges expanded
+    pub fn member_ids(&self) -> Result<Vec<DroneId>, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let mut members = Vec::new();
+        for (i, entry) in self.members.iter().enumerate() {
+            let field = format!("members[{}]", i);
+            errors.check_len(&field, entry, MAX_ID_LEN);
+            match expand_members(entry) {
+                Ok(ids) => members.extend(ids),
+                Err(e) => errors.add(field, e),
+            }
+        }
+        if members.is_empty() && self.role.is_none() {
+            errors.add("members", "must not be empty unless a role is given");
+        }
+        if let Some(description) = &self.description {
+            errors.check_len("description", description, MAX_GROUP_DESCRIPTION_LEN);
+        }
+        errors.into_result().map(|_| members)
+    }
+}
+
+impl Validate for GroupRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.member_ids().map(|_| ())
+    }
+}
+
+#[derive(Serialize)]
+pub struct GroupResponse {
+    #[serde(flatten)]
+    pub group: DroneGroup,
+    /// Explicit members plus current holders of the group's role
+    pub resolved_members: Vec<DroneId>,
+}
+
+#[derive(Serialize)]
+pub struct GroupListResponse {
+    pub groups: Vec<GroupResponse>,
+    pub total: usize,
+}
+
+#[derive(Serialize)]
+pub struct BulkCommandResponse {
+    pub command: String,
+    #[serde(flatten)]
+    pub report: BulkCommandReport,
+}
+
+/// List drone groups
+pub async fn list_groups(State(state): State<AppState>) -> impl IntoResponse {
+    let groups: Vec<_> = state
+        .tracker
+        .groups()
+        .into_iter()
+        .map(|group| group_response(&state, group))
+        .collect();
+    let total = groups.len();
+    Json(GroupListResponse { groups, total })
+}
+
+/// Get a drone group with its resolved members
+pub async fn get_group(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let group = state
+        .tracker
+        .group(&name)
+        .ok_or_else(|| ApiError::not_found(format!("Group {} not found", name)))?;
+    Ok(Json(group_response(&state, group)))
+}
+
+/// Create or replace a drone group
+pub async fn put_group(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    ValidJson(req): ValidJson<GroupRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    check_group_name(&name)?;
+    let members = req.member_ids()?;
+    if let Some(unknown) = members.iter().find(|id| state.tracker.get_drone(id).is_none()) {
+        return Err(ApiError::validation("members", format!("drone {} not found", unknown)));
+    }
+
+    let group = DroneGroup::new(name, members, req.role, req.description, state.clock.now());
+    let group = state.tracker.put_group(group).await?;
+    Ok(Json(group_response(&state, group)))
+}
+
+/// Delete a drone group
+pub async fn delete_group(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let group = state
+        .tracker
+        .delete_group(&name)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Group {} not found", name)))?;
+    Ok(Json(group_response(&state, group)))
+}
+
+/// Send a command to every drone in a group, with a result per drone
+pub async fn send_group_command(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    ValidJson(req): ValidJson<CommandRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let command = req.command_type()?;
+    if let DroneCommandType::GoToWaypoint { waypoint_id } = &command {
+        check_mission_waypoint(&state, "params.waypoint_id", waypoint_id)?;
+    }
+
+    let report = state
+        .tracker
+        .send_group_command(&name, &command)
+        .await
+        .ok_or_else(|| ApiError::not_found(format!("Group {} not found", name)))?;
+    if let Some(mission) = state.get_mission() {
+        for result in report.results.iter().filter(|r| r.outcome.is_success()) {
+            state.timeline.record_command(&mission, &result.drone_id, &req.command, &req.params);
+        }
+    }
+
+    Ok(Json(BulkCommandResponse {
+        command: req.command,
+        report,
+    }))
+}
+
+/// Group names appear in URLs: letters, digits, `-` and `_` only
+fn check_group_name(name: &str) -> Result<(), ApiError> {
+    let mut errors = ValidationErrors::new();
+    errors.check_len("name", name, MAX_ID_LEN);
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        errors.add("name", "may only contain letters, digits, '-' and '_'");
+    }
+    errors.into_result().map_err(ApiError::from)
+}
+
+fn group_response(state: &AppState, group: DroneGroup) -> GroupResponse {
+    GroupResponse {
+        resolved_members: state.tracker.group_members(&group),
+        group,
+    }
+}
+
+// ============================================================================
+// MISSION HANDLERS
+// ============================================================================
+
+/// Get current mission
+pub async fn get_mission(State(state): State<AppState>) -> impl IntoResponse {
+    match state.get_mission() {
+        Some(mission) => Json(Some(mission_to_response(&mission))),
+        None => Json(None),
+    }
+}
+
+/// Start mission
+pub async fn start_mission(State(state): State<AppState>) -> impl IntoResponse {
+    if let Some(mut mission) = state.active_mission.write().take() {
+        mission.start();
+        *state.active_mission.write() = Some(mission.clone());
+        state.tracker.mission_started();
+        info!("Mission {} started", mission.name);
+        state.timeline.record_lifecycle(&mission, format!("Mission {} started", mission.name));
+        Json(serde_json::json!({"status": "started", "mission": mission.name}))
+    } else {
+        Json(serde_json::json!({"status": "error", "message": "No active mission"}))
+    }
+}
 
 /// Pause mission
 pub async fn pause_mission(State(state): State<AppState>) -> impl IntoResponse {
     if let Some(mission) = state.active_mission.read().as_ref() {
         info!("Mission {} paused", mission.name);
+        state.timeline.record_lifecycle(mission, format!("Mission {} paused", mission.name));
+    }
+    Json(serde_json::json!({"status": "paused"}))
+}
+
+/// Resume mission
+pub async fn resume_mission(State(state): State<AppState>) -> impl IntoResponse {
+    if let Some(mission) = state.active_mission.read().as_ref() {
+        info!("Mission {} resumed", mission.name);
+        state.timeline.record_lifecycle(mission, format!("Mission {} resumed", mission.name));
+    }
+    Json(serde_json::json!({"status": "resumed"}))
+}
+
+/// Abort mission
+///
+/// Starts the tracker's abort sequence; the mission becomes `Aborted` once
+/// every assigned drone acknowledges the recall or the timeout expires.
+pub async fn abort_mission(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let report = state.tracker
+        .begin_abort(Some("Operator abort".into()))
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    if let Some(mission) = state.active_mission.read().as_ref() {
+        info!("Mission {} abort initiated", mission.name);
+        state.timeline.record_lifecycle(
+            mission,
+            format!("Mission {} abort initiated: recalling {} drones", mission.name, report.drones.len()),
+        );
+    }
+    Ok((StatusCode::ACCEPTED, Json(report)))
+}
+
+/// Get the current or most recent abort report
+pub async fn get_abort_report(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state.tracker
+        .abort_report()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("No mission abort has been initiated"))
+}
+
+/// Get a mission's timeline, grouped by phase
+pub async fn get_mission_timeline(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mission_id = Uuid::parse_str(&id)
+        .map(MissionId)
+        .map_err(|_| ApiError::bad_request(format!("Invalid mission id: {}", id)))?;
+
+    let is_active = state.get_mission().is_some_and(|m| m.id == mission_id);
+    if !is_active && !state.timeline.has_mission(&mission_id) {
+        return Err(ApiError::not_found(format!("Mission {} not found", id)));
+    }
+
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(str::parse::<u64>)
+        .transpose()
+        .map_err(|_| ApiError::bad_request("Invalid cursor"))?;
+
+    Ok(Json(state.timeline.page(
+        &mission_id,
+        cursor,
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AfterActionQuery {
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// Get a mission's after-action report, as JSON, an HTML page or a PDF
+pub async fn get_after_action_report(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AfterActionQuery>,
+) -> Result<axum::response::Response, ApiError> {
+    let mission_id = Uuid::parse_str(&id)
+        .map(MissionId)
+        .map_err(|_| ApiError::bad_request(format!("Invalid mission id: {}", id)))?;
+    let Some(db) = &state.db else {
+        return Err(ApiError::ServiceUnavailable("No database configured".into()));
+    };
+
+    let mission = match state.get_mission().filter(|m| m.id == mission_id) {
+        Some(mission) => mission,
+        None => db
+            .missions()
+            .get(&mission_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found(format!("Mission {} not found", id)))?,
+    };
+
+    // Records are stamped on the tracker's clock
+    let now = state.tracker.clock().now();
+    let report = ReportBuilder::load(db, mission, now).await?.finish(now);
+    Ok(match query.format {
+        ReportFormat::Json => Json(report).into_response(),
+        ReportFormat::Html => (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            report.render_html(),
+        )
+            .into_response(),
+        ReportFormat::Pdf => (
+            [(header::CONTENT_TYPE, "application/pdf")],
+            report.render_pdf(),
+        )
+            .into_response(),
+    })
+}
+
+/// Get a mission's telemetry completeness and gap report
+pub async fn get_mission_data_quality(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mission_id = Uuid::parse_str(&id)
+        .map(MissionId)
+        .map_err(|_| ApiError::bad_request(format!("Invalid mission id: {}", id)))?;
+
+    state
+        .tracker
+        .data_quality_report(&mission_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No telemetry recorded for mission {}", id)))
+}
+
+/// Default and largest number of dead-letter rows returned
+pub const DEFAULT_DEAD_LETTERS: usize = 50;
+pub const MAX_DEAD_LETTERS: usize = 500;
+
+/// Query parameters for a drone's quarantined telemetry
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadLetterQuery {
+    pub limit: Option<usize>,
+}
+
+/// Drones whose telemetry writes have failed or been quarantined
+pub async fn get_write_breakers(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.write_breakers())
+}
+
+/// Position update latency per hop, from ingestion to the client
+pub async fn get_latency_breakdown(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.metrics().latency_breakdown())
+}
+
+/// Recent log records, filtered by drone, mission and level
+pub async fn get_logs(
+    State(state): State<AppState>,
+    Query(query): Query<LogQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(state.logs.query(&query, state.tenant.as_ref())?))
+}
+
+/// A drone's quarantined telemetry rows, newest first
+pub async fn get_dead_letters(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_DEAD_LETTERS);
+    if limit == 0 || limit > MAX_DEAD_LETTERS {
+        return Err(ApiError::validation("limit", format!("must be between 1 and {}", MAX_DEAD_LETTERS)));
+    }
+    let db = state
+        .db
+        .clone()
+        .ok_or_else(|| ApiError::ServiceUnavailable("No database configured".into()))?;
+
+    Ok(Json(db.quality().dead_letters(&DroneId::new(&id), limit).await?))
+}
+
+/// Query parameters for a checkpoint acknowledgment
+#[derive(Debug, Deserialize)]
+pub struct CheckpointAckQuery {
+    /// Release only this drone (default: every drone held at the waypoint)
+    pub drone_id: Option<String>,
+    /// Operator acknowledging, recorded on the timeline
+    pub operator: Option<String>,
+}
+
+/// Longest operator name accepted on a checkpoint acknowledgment
+pub const MAX_OPERATOR_LEN: usize = 64;
+
+#[derive(Serialize)]
+pub struct CheckpointAckResponse {
+    pub waypoint_id: String,
+    pub released: Vec<CheckpointHold>,
+}
+
+/// Drones holding at checkpoints until acknowledged
+pub async fn list_checkpoint_holds(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.checkpoint_holds())
+}
+
+/// Acknowledge a checkpoint, releasing the drones holding there
+pub async fn acknowledge_checkpoint(
+    State(state): State<AppState>,
+    Path(wp): Path<String>,
+    Query(query): Query<CheckpointAckQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    if query.operator.as_ref().is_some_and(|o| o.chars().count() > MAX_OPERATOR_LEN) {
+        return Err(ApiError::validation(
+            "operator",
+            format!("must be at most {} characters", MAX_OPERATOR_LEN),
+        ));
+    }
+    let waypoint_id = WaypointId::new(&wp);
+    let drone_id = query.drone_id.as_deref().map(DroneId::new);
+    let released = state.tracker.acknowledge_checkpoint(
+        &waypoint_id,
+        drone_id.as_ref(),
+        query.operator.as_deref(),
+    );
+    if released.is_empty() {
+        return Err(ApiError::not_found(match &drone_id {
+            Some(drone_id) => format!("Drone {} is not holding at checkpoint {}", drone_id, wp),
+            None => format!("No drones holding at checkpoint {}", wp),
+        }));
+    }
+
+    if let Some(mission) = state.get_mission() {
+        for hold in &released {
+            let by = query.operator.as_deref().map(|o| format!(" by {}", o)).unwrap_or_default();
+            state.timeline.record_lifecycle(
+                &mission,
+                format!("Checkpoint {} acknowledged for {}{}", hold.waypoint_name, hold.drone_id, by),
+            );
+        }
+    }
+
+    Ok(Json(CheckpointAckResponse { waypoint_id: wp, released }))
+}
+
+/// Per-drone route progress with endurance projections
+pub async fn get_mission_progress(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let mission = state
+        .tracker
+        .get_mission()
+        .ok_or_else(|| ApiError::not_found("No active mission"))?;
+
+    let mut drones: Vec<DroneProgressResponse> = state
+        .tracker
+        .get_all_drones()
+        .into_iter()
+        .map(|tracked| DroneProgressResponse {
+            drone_id: tracked.drone.id.to_string(),
+            status: format!("{:?}", tracked.drone.status),
+            waypoints_reached: tracked.waypoint_index.min(mission.waypoints.len()),
+            leg_progress: tracked.waypoint_progress,
+            endurance: state.tracker.endurance_projection(&tracked.drone.id),
+            eta_seconds: state.tracker.route_eta_seconds(&tracked.drone.id),
+        })
+        .collect();
+    drones.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+
+    Ok(Json(MissionProgressResponse {
+        mission_id: mission.id.0.to_string(),
+        status: format!("{:?}", mission.status),
+        reserve_percent: state.tracker.endurance_config().reserve_percent,
+        drones,
+    }))
+}
+
+/// Get mission waypoints
+pub async fn get_waypoints(State(state): State<AppState>) -> impl IntoResponse {
+    let waypoints: Vec<WaypointResponse> = state.get_mission()
+        .map(|m| waypoints_to_response(&state, &m))
+        .unwrap_or_default();
+
+    Json(waypoints)
+}
+
+/// Leg speed limits for the active mission
+#[derive(Debug, Deserialize)]
+pub struct SpeedLimitsRequest {
+    /// km/h by waypoint ID, limiting the leg that arrives there; legs not
+    /// listed are unlimited
+    #[serde(default)]
+    pub limits: HashMap<WaypointId, f64>,
+    /// Command drones over a limit back down to it
+    #[serde(default)]
+    pub enforce: bool,
+}
+
+impl Validate for SpeedLimitsRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        for (waypoint_id, limit) in &self.limits {
+            errors.check_len(&format!("limits.{}", waypoint_id), &waypoint_id.0, MAX_ID_LEN);
+            errors.check_range(&format!("limits.{}", waypoint_id), *limit, 1.0, 1500.0);
+        }
+        errors.into_result()
+    }
+}
+
+/// Replace the active mission's leg speed limits while it runs
+pub async fn set_speed_limits(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<SpeedLimitsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mission = state.get_mission().ok_or_else(|| ApiError::not_found("No active mission"))?;
+    for waypoint_id in req.limits.keys() {
+        let field = format!("limits.{}", waypoint_id);
+        match mission.waypoints.iter().position(|wp| &wp.id == waypoint_id) {
+            Some(0) => return Err(ApiError::validation(field, "the first waypoint has no leg to limit")),
+            Some(_) => {}
+            None => return Err(ApiError::validation(field, "not a waypoint of the active mission")),
+        }
+    }
+
+    state.tracker.set_speed_limits(&req.limits, req.enforce);
+    let mission = {
+        let mut active = state.active_mission.write();
+        let mission = active.as_mut().ok_or_else(|| ApiError::not_found("No active mission"))?;
+        mission.set_speed_limits(&req.limits, req.enforce);
+        mission.clone()
+    };
+    Ok(Json(waypoints_to_response(&state, &mission)))
+}
+
+/// Corridor generated around the active mission's route
+#[derive(Debug, Deserialize)]
+pub struct CorridorRequest {
+    /// Full width in meters
+    pub width_m: f64,
+    /// Altitude ceiling in meters
+    #[serde(default)]
+    pub ceiling_m: Option<f64>,
+}
+
+impl Validate for CorridorRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_range("width_m", self.width_m, 10.0, 50_000.0);
+        if let Some(ceiling) = self.ceiling_m {
+            errors.check_range("ceiling_m", ceiling, 0.0, 20_000.0);
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct CorridorResponse {
+    /// How the corridor is generated; absent for a hand-drawn one
+    pub spec: Option<CorridorSpec>,
+    pub corridor: Option<Geofence>,
+}
+
+impl From<&Mission> for CorridorResponse {
+    fn from(mission: &Mission) -> Self {
+        Self {
+            spec: mission.auto_corridor,
+            corridor: mission.corridor.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RoutePolylineQuery {
+    /// Comma-separated corridor widths in meters, e.g. `200,1000`
+    pub widths: Option<String>,
+}
+
+impl RoutePolylineQuery {
+    /// Requested widths, deduplicated to the centimeter, in request order
+    pub fn widths(&self) -> Result<Vec<f64>, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let mut widths: Vec<f64> = Vec::new();
+        for value in self.widths.iter().flat_map(|w| w.split(',')).map(str::trim) {
+            match value.parse::<f64>() {
+                Ok(width) => {
+                    errors.check_range("widths", width, 10.0, 50_000.0);
+                    if !widths.iter().any(|&w| width_key(w) == width_key(width)) {
+                        widths.push(width);
+                    }
+                }
+                Err(_) => errors.add("widths", format!("{:?} is not a number", value)),
+            }
+        }
+        if widths.len() > MAX_CORRIDOR_WIDTHS {
+            errors.add("widths", format!("at most {} widths", MAX_CORRIDOR_WIDTHS));
+        }
+        errors.into_result().map(|_| widths)
+    }
+}
+
+/// The active mission's route as an encoded polyline, with corridor
+/// polygons at the requested widths
+pub async fn get_route_polyline(
+    State(state): State<AppState>,
+    Query(query): Query<RoutePolylineQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let widths = query.widths()?;
+    let mission = state.get_mission().ok_or_else(|| ApiError::not_found("No active mission"))?;
+    let cache = state.route_render.clone();
+    let rendered = tokio::task::spawn_blocking(move || cache.render(&mission, &widths))
+        .await
+        .map_err(|e| ApiError::internal(format!("Route rendering failed: {}", e)))?;
+    let route = rendered.ok_or_else(|| ApiError::validation("mission", "route has no waypoints"))?;
+    Ok(Json(route))
+}
+
+/// The active mission's corridor
+pub async fn get_mission_corridor(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let mission = state.get_mission().ok_or_else(|| ApiError::not_found("No active mission"))?;
+    Ok(Json(CorridorResponse::from(&mission)))
+}
+
+/// Generate the active mission's corridor from its route; it follows later
+/// waypoint edits
+pub async fn set_mission_corridor(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<CorridorRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let spec = CorridorSpec {
+        width_m: req.width_m,
+        ceiling_m: req.ceiling_m,
+    };
+    let mission = replace_auto_corridor(&state, Some(spec)).await?;
+    Ok(Json(CorridorResponse::from(&mission)))
+}
+
+/// Drop the active mission's generated corridor
+pub async fn delete_mission_corridor(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    replace_auto_corridor(&state, None).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check and set the active mission's generated corridor under the mission
+/// lock, store the mission, then hand the corridor to the tracker; a failed
+/// store restores the previous corridor
+async fn replace_auto_corridor(state: &AppState, spec: Option<CorridorSpec>) -> Result<Mission, ApiError> {
+    let (mission, previous) = {
+        let mut active = state.active_mission.write();
+        let mission = active.as_mut().ok_or_else(|| ApiError::not_found("No active mission"))?;
+        match spec.and_then(|spec| spec.ceiling_m) {
+            Some(ceiling) => {
+                if let Some(above) = mission.waypoints.iter().find(|wp| wp.position.altitude > ceiling) {
+                    return Err(ApiError::validation("ceiling_m", format!("waypoint {} is above the ceiling", above.id)));
+                }
+            }
+            None if spec.is_none() && mission.auto_corridor.is_none() => {
+                return Err(ApiError::not_found("The active mission has no generated corridor"));
+            }
+            None => {}
+        }
+        let previous = (mission.auto_corridor, mission.corridor.clone(), mission.updated_at);
+        mission.set_auto_corridor(spec);
+        (mission.clone(), previous)
+    };
+
+    if let Some(db) = &state.db {
+        if let Err(e) = db.missions().create(&mission).await {
+            if let Some(active) = state.active_mission.write().as_mut().filter(|m| m.id == mission.id) {
+                (active.auto_corridor, active.corridor, active.updated_at) = previous;
+            }
+            return Err(e.into());
+        }
+    }
+    state.tracker.set_auto_corridor(spec);
+    Ok(mission)
+}
+
+// ============================================================================
+// WAYPOINT ATTACHMENT HANDLERS
+// ============================================================================
+
+/// Longest attachment file name
+pub const MAX_FILE_NAME_LEN: usize = 255;
+
+/// Longest attachment notes
+pub const MAX_ATTACHMENT_NOTES_LEN: usize = 4096;
+
+/// Attachment metadata; the content is the request body
+#[derive(Deserialize)]
+pub struct AttachmentUploadQuery {
+    pub file_name: String,
+    pub threat_level: Option<ThreatLevel>,
+    pub notes: Option<String>,
+    pub uploaded_by: Option<String>,
+}
+
+impl Validate for AttachmentUploadQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("file_name", &self.file_name, MAX_FILE_NAME_LEN);
+        if self.file_name.chars().any(|c| c.is_control() || matches!(c, '/' | '\\' | '"')) {
+            errors.add("file_name", "must not contain path separators, quotes or control characters");
+        }
+        if let Some(notes) = &self.notes {
+            errors.check_len("notes", notes, MAX_ATTACHMENT_NOTES_LEN);
+        }
+        if let Some(uploaded_by) = &self.uploaded_by {
+            errors.check_len("uploaded_by", uploaded_by, MAX_ID_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+impl From<AttachmentError> for ApiError {
+    fn from(err: AttachmentError) -> Self {
+        match err {
+            AttachmentError::Database(e) => e.into(),
+            other => ApiError::internal(other.to_string()),
+        }
+    }
+}
+
+/// The active mission, if `waypoint_id` is one of its waypoints
+fn mission_with_waypoint(state: &AppState, waypoint_id: &WaypointId) -> Result<Mission, ApiError> {
+    state
+        .get_mission()
+        .filter(|m| m.waypoints.iter().any(|wp| &wp.id == waypoint_id))
+        .ok_or_else(|| ApiError::not_found(format!("Waypoint {} not found", waypoint_id)))
+}
+
+/// List a waypoint's attachments
+pub async fn list_waypoint_attachments(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<WaypointAttachment>>, ApiError> {
+    let waypoint_id = WaypointId::new(id);
+    let mission = mission_with_waypoint(&state, &waypoint_id)?;
+    Ok(Json(state.attachments.list(&mission.id, Some(&waypoint_id))))
+}
+
+/// Upload an attachment; the body is the content and `Content-Type` its type
+pub async fn upload_waypoint_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AttachmentUploadQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<impl IntoResponse, ApiError> {
+    query.validate()?;
+    let waypoint_id = WaypointId::new(id);
+    let mission = mission_with_waypoint(&state, &waypoint_id)?;
+
+    let max_bytes = state.attachments.config().max_bytes;
+    let content = axum::body::to_bytes(body, max_bytes).await.map_err(|_| {
+        ApiError::PayloadTooLarge(format!("Attachments are limited to {} bytes", max_bytes))
+    })?;
+    if content.is_empty() {
+        return Err(ApiError::validation("body", "attachment content is empty"));
+    }
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= MAX_FILE_NAME_LEN)
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    let new = NewAttachment {
+        mission_id: mission.id,
+        waypoint_id,
+        file_name: query.file_name,
+        content_type,
+        threat_level: query.threat_level,
+        notes: query.notes,
+        uploaded_by: query.uploaded_by,
+    };
+    let attachment = state.attachments.upload(new, &content, Utc::now()).await?;
+    Ok((StatusCode::CREATED, Json(attachment)))
+}
+
+/// Download an attachment's content
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (attachment, content) = state
+        .attachments
+        .download(&id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Attachment {} not found", id)))?;
+
+    let (content_type, disposition) = attachments::serving_headers(&attachment);
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (header::CONTENT_SECURITY_POLICY, attachments::ATTACHMENT_CSP.to_string()),
+        ],
+        content,
+    ))
+}
+
+/// Delete an attachment and its content
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WaypointAttachment>, ApiError> {
+    state
+        .attachments
+        .delete(&id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Attachment {} not found", id)))
+}
+
+// ============================================================================
+// MISSION PACKAGE HANDLERS
+// ============================================================================
+
+/// Query parameters for a mission package upload
+#[derive(Debug, Default, Deserialize)]
+pub struct PackageImportQuery {
+    /// Make the imported mission the active one
+    #[serde(default)]
+    pub activate: bool,
+}
+
+#[derive(Serialize)]
+pub struct PackageImportResponse {
+    pub mission: MissionResponse,
+    /// Hex-encoded public key of the signing station
+    pub signer: String,
+    pub version: u16,
+    pub exported_at: String,
+    pub activated: bool,
+}
+
+#[derive(Serialize)]
+pub struct PackageKeysResponse {
+    pub public_key: String,
+    pub trusted_keys: Vec<String>,
+}
+
+impl From<PackageError> for ApiError {
+    fn from(err: PackageError) -> Self {
+        match err {
+            PackageError::BadSignature | PackageError::UntrustedSigner(_) => ApiError::Forbidden(err.to_string()),
+            PackageError::Key(_) => ApiError::internal(err.to_string()),
+            other => ApiError::bad_request(other.to_string()),
+        }
+    }
+}
+
+impl From<MissionBuildError> for ApiError {
+    fn from(err: MissionBuildError) -> Self {
+        let field = match err {
+            MissionBuildError::EmptyName => "name",
+            MissionBuildError::DuplicateDrone(_) => "assigned_drones",
+            MissionBuildError::InvalidCorridor(_)
+            | MissionBuildError::InvalidCorridorSpec { .. }
+            | MissionBuildError::WaypointOutsideCorridor(_) => "corridor",
+            MissionBuildError::FormationWithoutDrones => "formation",
+            _ => "waypoints",
+        };
+        ApiError::validation(field, err.to_string())
+    }
+}
+
+/// Download a mission as a signed package file
+pub async fn export_mission_package(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mission_id = Uuid::parse_str(&id)
+        .map(MissionId)
+        .map_err(|_| ApiError::bad_request(format!("Invalid mission id: {}", id)))?;
+
+    let mission = match state.get_mission().filter(|m| m.id == mission_id) {
+        Some(mission) => Some(mission),
+        None => match &state.db {
+            Some(db) => db.missions().get(&mission_id).await?,
+            None => None,
+        },
+    }
+    .ok_or_else(|| ApiError::not_found(format!("Mission {} not found", id)))?;
+
+    let package = state.packages.package(&mission, Utc::now())?;
+    let disposition = format!("attachment; filename=\"mission-{}.dcmp\"", mission.id.0);
+    Ok((
+        [
+            (header::CONTENT_TYPE, PACKAGE_CONTENT_TYPE.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        package,
+    ))
+}
+
+/// Verify and import an uploaded mission package
+pub async fn import_mission_package(
+    State(state): State<AppState>,
+    Query(query): Query<PackageImportQuery>,
+    body: Body,
+) -> Result<impl IntoResponse, ApiError> {
+    let content = axum::body::to_bytes(body, MAX_PACKAGE_BYTES).await.map_err(|_| {
+        ApiError::PayloadTooLarge(format!("Mission packages are limited to {} bytes", MAX_PACKAGE_BYTES))
+    })?;
+    let package = state.packages.open(&content)?;
+    package.mission.validate()?;
+    let mission = MissionBuilder::from(package.mission).build()?;
+
+    if let Some(db) = &state.db {
+        db.missions().create(&mission).await?;
+    }
+    if query.activate {
+        *state.active_mission.write() = Some(mission.clone());
+        state.tracker.set_mission(mission.clone());
+        state.timeline.record_created(&mission);
+    }
+    info!(
+        "Imported mission package {} ({}) signed by {}",
+        mission.name, mission.id.0, package.signer
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(PackageImportResponse {
+            mission: mission_to_response(&mission),
+            signer: package.signer,
+            version: package.version,
+            exported_at: package.exported_at.to_rfc3339(),
+            activated: query.activate,
+        }),
+    ))
+}
+
+/// This station's package signing key and the keys it accepts
+pub async fn get_package_keys(State(state): State<AppState>) -> impl IntoResponse {
+    Json(PackageKeysResponse {
+        public_key: state.packages.public_key(),
+        trusted_keys: state.packages.trusted_keys(),
+    })
+}
+
+// ============================================================================
+// MISSION UPLOAD HANDLERS
+// ============================================================================
+
+impl From<UploadError> for ApiError {
+    fn from(err: UploadError) -> Self {
+        match err {
+            UploadError::NotFound(_) => ApiError::not_found(err.to_string()),
+            UploadError::OutOfSequence { .. } => ApiError::Conflict(err.to_string()),
+            UploadError::BatchSize | UploadError::TooManyWaypoints => ApiError::validation("waypoints", err.to_string()),
+            UploadError::ChecksumMismatch { .. } => ApiError::validation("checksum", err.to_string()),
+            UploadError::TooManyDrafts | UploadError::Finalizing(_) => ApiError::Conflict(err.to_string()),
+            UploadError::Build(err) => err.into(),
+        }
+    }
+}
+
+impl Validate for DraftMission {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("name", &self.name, MAX_ID_LEN);
+        for drone_id in &self.assigned_drones {
+            errors.check_len("assigned_drones", drone_id.as_str(), MAX_ID_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+/// One numbered batch of an uploaded route
+#[derive(Deserialize)]
+pub struct WaypointBatchRequest {
+    /// `0` for the first batch, then one more for each
+    pub sequence: u32,
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl Validate for WaypointBatchRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.waypoints.is_empty() || self.waypoints.len() > MAX_BATCH_WAYPOINTS {
+            errors.add("waypoints", format!("must hold between 1 and {} waypoints", MAX_BATCH_WAYPOINTS));
+        }
+        for waypoint in &self.waypoints {
+            errors.check_len("waypoints.id", &waypoint.id.0, MAX_ID_LEN);
+            errors.check_len("waypoints.name", &waypoint.name, MAX_ID_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+/// Checksum of the full route, and whether to activate the mission
+#[derive(Deserialize)]
+pub struct FinalizeUploadRequest {
+    pub checksum: String,
+    #[serde(default)]
+    pub activate: bool,
+}
+
+impl Validate for FinalizeUploadRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let checksum = self.checksum.trim();
+        if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+            errors.add("checksum", "must be a hex SHA-256 (64 characters)");
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct FinalizeUploadResponse {
+    pub mission: MissionResponse,
+    pub activated: bool,
+}
+
+fn parse_upload_id(id: &str) -> Result<MissionId, ApiError> {
+    Uuid::parse_str(id)
+        .map(MissionId)
+        .map_err(|_| ApiError::bad_request(format!("Invalid mission id: {}", id)))
+}
+
+/// Start a mission upload; waypoints follow in batches
+pub async fn create_mission_draft(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<DraftMission>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok((StatusCode::CREATED, Json(state.uploads.create(req, Utc::now())?)))
+}
+
+/// Append the next batch of waypoints to a draft
+pub async fn append_waypoint_batch(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<WaypointBatchRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mission_id = parse_upload_id(&id)?;
+    Ok(Json(state.uploads.append(&mission_id, req.sequence, req.waypoints, Utc::now())?))
+}
+
+/// Batches and waypoints received so far
+pub async fn get_mission_upload(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(state.uploads.progress(&parse_upload_id(&id)?)?))
+}
+
+/// Abandon a draft
+pub async fn discard_mission_upload(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    state.uploads.discard(&parse_upload_id(&id)?)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Verify the route checksum and turn the draft into a mission
+pub async fn finalize_mission_upload(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<FinalizeUploadRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mission_id = parse_upload_id(&id)?;
+    let mission = state.uploads.finalize(&mission_id, &req.checksum)?;
+
+    if let Some(db) = &state.db {
+        if let Err(e) = db.missions().create(&mission).await {
+            state.uploads.release(&mission_id, Utc::now());
+            return Err(e.into());
+        }
+    }
+    state.uploads.finalized(&mission_id);
+    if req.activate {
+        *state.active_mission.write() = Some(mission.clone());
+        state.tracker.set_mission(mission.clone());
+        state.timeline.record_created(&mission);
+    }
+    info!(
+        "Finalized mission upload {} ({}) with {} waypoints",
+        mission.name,
+        mission.id.0,
+        mission.waypoints.len()
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(FinalizeUploadResponse {
+            mission: mission_to_response(&mission),
+            activated: req.activate,
+        }),
+    ))
+}
+
+// ============================================================================
+// MAP TILE HANDLERS
+// ============================================================================
+
+impl From<TileError> for ApiError {
+    fn from(err: TileError) -> Self {
+        match err {
+            TileError::OutOfRange(_) => ApiError::bad_request(err.to_string()),
+            TileError::NotCached(_) | TileError::NotFound(_) => ApiError::not_found(err.to_string()),
+            TileError::Transport(_) | TileError::Upstream(_) => ApiError::BadGateway(err.to_string()),
+            TileError::Storage(_) => ApiError::internal(err.to_string()),
+        }
+    }
+}
+
+/// Serve a map tile from the cache or the upstream; `y` may carry an
+/// image extension (`/tiles/5/17/11.png`)
+pub async fn get_tile(
+    State(tiles): State<Arc<TileService>>,
+    Path((z, x, y)): Path<(u8, u32, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let y = y
+        .split_once('.')
+        .map_or(y.as_str(), |(y, _)| y)
+        .parse()
+        .map_err(|_| ApiError::bad_request(format!("Invalid tile row: {}", y)))?;
+    let tile = tiles.tile(TileKey { z, x, y }).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, tile.content_type),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+            (HeaderName::from_static("x-tile-cache"), if tile.cached { "hit" } else { "miss" }),
+        ],
+        tile.content,
+    ))
+}
+
+/// Tile cache usage and upstream counters
+pub async fn get_tile_stats(State(tiles): State<Arc<TileService>>) -> impl IntoResponse {
+    Json(tiles.stats())
+}
+
+// ============================================================================
+// ZONE OF INTEREST HANDLERS
+// ============================================================================
+
+/// Maximum vertices in a zone polygon
+pub const MAX_ZONE_VERTICES: usize = 500;
+
+#[derive(Deserialize)]
+pub struct ZoneVertex {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Zone definition: a named polygon with an optional altitude ceiling
+#[derive(Deserialize)]
+pub struct ZoneRequest {
+    pub name: String,
+    pub vertices: Vec<ZoneVertex>,
+    pub max_altitude: Option<f64>,
+}
+
+impl Validate for ZoneRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("name", &self.name, MAX_ID_LEN);
+        if self.vertices.len() < 3 || self.vertices.len() > MAX_ZONE_VERTICES {
+            errors.add("vertices", format!("must have between 3 and {} vertices", MAX_ZONE_VERTICES));
+        }
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            errors.check_latitude(&format!("vertices[{}].latitude", i), vertex.latitude);
+            errors.check_longitude(&format!("vertices[{}].longitude", i), vertex.longitude);
+        }
+        if let Some(max_altitude) = self.max_altitude {
+            errors.check_range("max_altitude", max_altitude, 0.0, 50_000.0);
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct ZoneListResponse {
+    pub zones: Vec<Zone>,
+    pub total: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ZoneStatsQuery {
+    /// Defaults to the active mission
+    pub mission_id: Option<String>,
+}
+
+/// List zones of interest
+pub async fn list_zones(State(state): State<AppState>) -> impl IntoResponse {
+    let zones = state.tracker.zones();
+    let total = zones.len();
+    Json(ZoneListResponse { zones, total })
+}
+
+/// Create a zone of interest
+pub async fn create_zone(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ZoneRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let vertices = req
+        .vertices
+        .iter()
+        .map(|v| GeoPosition::new(v.latitude, v.longitude, 0.0))
+        .collect();
+    let mut area = Geofence::new(req.name, vertices);
+    area.max_altitude = req.max_altitude;
+
+    let zone = state.tracker.add_zone(Zone::new(area, state.clock.now())).await?;
+    Ok((StatusCode::CREATED, Json(zone)))
+}
+
+/// Get a zone of interest
+pub async fn get_zone(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let zone_id = parse_zone_id(&id)?;
+    let zone = state
+        .tracker
+        .zone(&zone_id)
+        .ok_or_else(|| ApiError::not_found(format!("Zone {} not found", id)))?;
+    Ok(Json(zone))
+}
+
+/// Delete a zone of interest and its dwell statistics
+pub async fn delete_zone(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let zone_id = parse_zone_id(&id)?;
+    let zone = state
+        .tracker
+        .remove_zone(&zone_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Zone {} not found", id)))?;
+    Ok(Json(zone))
+}
+
+/// Per-drone dwell statistics for a zone during a mission
+pub async fn get_zone_stats(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ZoneStatsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let zone_id = parse_zone_id(&id)?;
+    let mission_id = match &query.mission_id {
+        Some(mission_id) => Uuid::parse_str(mission_id)
+            .map(MissionId)
+            .map_err(|_| ApiError::bad_request(format!("Invalid mission id: {}", mission_id)))?,
+        None => state
+            .tracker
+            .get_mission()
+            .map(|m| m.id)
+            .ok_or_else(|| ApiError::bad_request("No active mission; pass mission_id"))?,
+    };
+
+    let stats = state
+        .tracker
+        .zone_stats(&zone_id, &mission_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Zone {} not found", id)))?;
+    Ok(Json(stats))
+}
+
+fn parse_zone_id(id: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(id).map_err(|_| ApiError::bad_request(format!("Invalid zone id: {}", id)))
+}
+
+// ============================================================================
+// TRACKING HANDLERS
+// ============================================================================
+
+/// Get CV tracking results
+pub async fn get_tracking_results(State(state): State<AppState>) -> impl IntoResponse {
+    let tracks = state
+        .tracker
+        .cv_pipeline()
+        .map(|pipeline| pipeline.latest())
+        .unwrap_or_default();
+    let frame_timestamp = tracks
+        .iter()
+        .map(|t| t.frame_timestamp)
+        .max()
+        .unwrap_or_else(Utc::now);
+    Json(serde_json::json!({
+        "tracks": tracks,
+        "frame_timestamp": frame_timestamp.to_rfc3339(),
+    }))
+}
+
+/// Most tracking results accepted in one submission
+pub const MAX_TRACKING_BATCH: usize = 500;
+
+#[derive(Deserialize)]
+pub struct TrackingSubmitRequest {
+    pub results: Vec<TrackingResult>,
+}
+
+impl Validate for TrackingSubmitRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.results.len() > MAX_TRACKING_BATCH {
+            errors.add("results", format!("must contain at most {} results", MAX_TRACKING_BATCH));
+        }
+        for (i, result) in self.results.iter().enumerate() {
+            errors.check_len(&format!("results[{}].drone_id", i), &result.drone_id.0, MAX_ID_LEN);
+            errors.check_range(&format!("results[{}].confidence", i), result.confidence, 0.0, 1.0);
+        }
+        errors.into_result()
+    }
+}
+
+/// Submit CV tracking results for broadcast and persistence
+pub async fn submit_tracking_results(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<TrackingSubmitRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let pipeline = state
+        .tracker
+        .cv_pipeline()
+        .ok_or_else(|| ApiError::ServiceUnavailable("CV tracking is disabled".into()))?;
+
+    let total = req.results.len();
+    let accepted = req.results.into_iter().filter_map(|r| pipeline.submit(r).then_some(())).count();
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(TrackingSubmitResponse { accepted, dropped: total - accepted }),
+    ))
+}
+
+/// Get tracking statistics
+pub async fn get_tracking_stats(State(state): State<AppState>) -> impl IntoResponse {
+    let pipeline = state.tracker.cv_pipeline();
+    let publisher = pipeline.as_ref().map(|p| p.stats());
+
+    Json(TrackingStatsResponse {
+        active_tracks: pipeline.map(|p| p.latest().len()).unwrap_or(0),
+        cv_enabled: publisher.is_some(),
+        frames_processed: publisher.as_ref().map(|s| s.received).unwrap_or(0),
+        publisher,
+    })
+}
+
+/// CV calibration drift against GPS and the correction in effect
+pub async fn get_cv_drift(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.drift_report())
+}
+
+/// Correct later CV estimates by the latest drift estimate
+pub async fn apply_cv_drift_correction(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .apply_drift_correction()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("No drift estimate yet"))
+}
+
+/// Stop correcting CV estimates
+pub async fn clear_cv_drift_correction(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    if state.tracker.clear_drift_correction() {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found("No calibration correction applied"))
+    }
+}
+
+// ============================================================================
+// CV PARAMETER HANDLERS
+// ============================================================================
+
+/// Optimistic concurrency for CV parameter updates
+#[derive(Debug, Deserialize)]
+pub struct CvConfigQuery {
+    /// Refuse the update unless the parameters are still at this revision
+    pub revision: Option<u64>,
+}
+
+impl From<CvTuningUpdateError> for ApiError {
+    fn from(err: CvTuningUpdateError) -> Self {
+        match &err {
+            CvTuningUpdateError::Invalid(invalid) => ApiError::validation(cv_tuning_field(invalid), err.to_string()),
+            CvTuningUpdateError::RevisionMismatch { .. } => ApiError::Conflict(err.to_string()),
+            CvTuningUpdateError::Storage(_) => ApiError::Database(err.to_string()),
+        }
+    }
+}
+
+/// Current CV detection and tracking parameters
+pub async fn get_cv_config(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.tracker.cv_tuning())
+}
+
+/// Replace every CV parameter
+pub async fn put_cv_config(
+    State(state): State<AppState>,
+    Query(query): Query<CvConfigQuery>,
+    ValidJson(tuning): ValidJson<CvTuning>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(state.tracker.set_cv_tuning(tuning, query.revision).await?))
+}
+
+/// Change some CV parameters, given as a JSON merge patch
+pub async fn patch_cv_config(
+    State(state): State<AppState>,
+    Query(query): Query<CvConfigQuery>,
+    Json(patch): Json<serde_json::Value>,
+) -> Result<impl IntoResponse, ApiError> {
+    let current = state.tracker.cv_tuning();
+    let mut merged = serde_json::to_value(&current.tuning).map_err(|e| ApiError::internal(e.to_string()))?;
+    merge_cv_patch(&mut merged, &patch, "")?;
+    let tuning: CvTuning = serde_json::from_value(merged)
+        .map_err(|e| ApiError::validation("body", format!("Invalid CV parameters: {}", e)))?;
+
+    // Without an explicit revision, a change made since the read above still wins
+    let expected = query.revision.or(Some(current.revision));
+    Ok(Json(state.tracker.set_cv_tuning(tuning, expected).await?))
+}
+
+/// Merge `patch` into `target`, refusing keys the parameters do not have
+fn merge_cv_patch(target: &mut serde_json::Value, patch: &serde_json::Value, path: &str) -> Result<(), ApiError> {
+    match (target, patch) {
+        (serde_json::Value::Object(fields), serde_json::Value::Object(changes)) => {
+            for (key, change) in changes {
+                let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                let Some(value) = fields.get_mut(key) else {
+                    return Err(ApiError::validation(field, "Unknown CV parameter"));
+                };
+                merge_cv_patch(value, change, &field)?;
+            }
+            Ok(())
+        }
+        (target, patch) => {
+            *target = patch.clone();
+            Ok(())
+        }
+    }
+}
+
+// ============================================================================
+// ALERT HANDLERS
+// ============================================================================
+
+#[derive(Serialize)]
+pub struct AlertListResponse {
+    /// `AlertResponse`s, trimmed to `fields` when given
+    pub alerts: Vec<serde_json::Value>,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Sorting and fields of the alert list
+const ALERT_LIST: ListSpec = ListSpec {
+    id_field: "id",
+    sortable: &["created_at", "severity", "alert_type", "drone_id"],
+    selectable: &["severity", "alert_type", "message", "drone_id", "acknowledged", "created_at", "presentation"],
+    default_sort: "created_at",
+    default_descending: true,
+    ranked: &[("severity", &["INFO", "WARNING", "CRITICAL", "EMERGENCY"])],
+};
+
+/// List the active alerts of every tracked drone, newest first
+pub async fn list_alerts(
+    State(state): State<AppState>,
+    Query(list): Query<ListQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let list = list.params(ALERT_LIST)?;
+    let rules = state.presentation.rules();
+    let alerts: Vec<AlertResponse> = state
+        .tracker
+        .get_all_drones()
+        .into_iter()
+        .flat_map(|tracked| tracked.active_alerts)
+        .map(|alert| AlertResponse {
+            id: alert.id.to_string(),
+            severity: enum_name(&alert.severity),
+            alert_type: match &alert.alert_type {
+                AlertType::Custom(name) => name.clone(),
+                other => enum_name(other),
+            },
+            presentation: rules.alert_presentation(alert.severity, &alert.alert_type),
+            message: alert.message,
+            drone_id: alert.drone_id.map(|id| id.0),
+            acknowledged: alert.acknowledged,
+            created_at: alert.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    let page = list.paginate(alerts);
+    Ok(Json(AlertListResponse {
+        alerts: page.items,
+        total: page.total,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// `SCREAMING_SNAKE_CASE` name of a unit enum variant
+fn enum_name(value: &impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Who acknowledges an alert
+#[derive(Debug, Deserialize)]
+pub struct AlertAckQuery {
+    /// Operator acknowledging, stored with the alert
+    pub operator: Option<String>,
+}
+
+/// Acknowledge alert
+pub async fn acknowledge_alert(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AlertAckQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    if query.operator.as_ref().is_some_and(|o| o.chars().count() > MAX_OPERATOR_LEN) {
+        return Err(ApiError::validation(
+            "operator",
+            format!("must be at most {} characters", MAX_OPERATOR_LEN),
+        ));
+    }
+    let alert_id = Uuid::parse_str(&id).map_err(|_| ApiError::bad_request(format!("Invalid alert ID: {}", id)))?;
+    let by = query.operator.as_deref().unwrap_or("operator");
+    state
+        .tracker
+        .acknowledge_alert(alert_id, by)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found(format!("No active alert {}", id)))?;
+    Ok(Json(serde_json::json!({"status": "acknowledged", "alert_id": id})))
+}
+
+/// Longest operator note on a suppression rule
+pub const MAX_SUPPRESSION_REASON_LEN: usize = 200;
+
+/// New alert suppression rule
+#[derive(Deserialize)]
+pub struct SuppressionRequest {
+    /// Drone to silence (all drones if omitted)
+    pub drone_id: Option<String>,
+    /// Alert type to silence (all types if omitted)
+    pub alert_type: Option<AlertType>,
+    pub window: SuppressionWindow,
+    pub reason: Option<String>,
+}
+
+impl Validate for SuppressionRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(drone_id) = &self.drone_id {
+            errors.check_len("drone_id", drone_id, MAX_ID_LEN);
+        }
+        if let Some(reason) = &self.reason {
+            errors.check_len("reason", reason, MAX_SUPPRESSION_REASON_LEN);
+        }
+        if let SuppressionWindow::Scheduled { start, end } = &self.window {
+            if end <= start {
+                errors.add("window.end", "must be later than window.start");
+            }
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct SuppressionResponse {
+    #[serde(flatten)]
+    pub rule: SuppressionRule,
+    /// The rule is silencing alerts right now
+    pub in_effect: bool,
+}
+
+#[derive(Serialize)]
+pub struct SuppressionListResponse {
+    pub suppressions: Vec<SuppressionResponse>,
+    pub stats: SuppressionStats,
+}
+
+/// List suppression rules that have not expired, with suppressed-alert counters
+pub async fn list_suppressions(State(state): State<AppState>) -> impl IntoResponse {
+    let suppressions = state
+        .tracker
+        .suppressions()
+        .into_iter()
+        .map(|rule| suppression_response(&state, rule))
+        .collect();
+    Json(SuppressionListResponse {
+        suppressions,
+        stats: state.tracker.suppression_stats(),
+    })
+}
+
+/// Add an alert suppression rule
+pub async fn create_suppression(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<SuppressionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let drone_id = req.drone_id.map(DroneId::new);
+    if let Some(drone_id) = drone_id.as_ref().filter(|d| state.tracker.get_drone(d).is_none()) {
+        return Err(ApiError::validation("drone_id", format!("drone {} not found", drone_id)));
+    }
+    if let SuppressionWindow::Scheduled { end, .. } = &req.window {
+        if *end <= state.clock.now() {
+            return Err(ApiError::validation("window.end", "must be later than the current simulation time"));
+        }
+    }
+
+    let rule = state.tracker.add_suppression(drone_id, req.alert_type, req.window, req.reason);
+    Ok((StatusCode::CREATED, Json(suppression_response(&state, rule))))
+}
+
+/// Remove an alert suppression rule
+pub async fn remove_suppression(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rule_id = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("Invalid suppression id: {}", id)))?;
+    let rule = state
+        .tracker
+        .remove_suppression(&rule_id)
+        .ok_or_else(|| ApiError::not_found(format!("Suppression {} not found", id)))?;
+    info!("Alert suppression {} removed", id);
+    Ok(Json(suppression_response(&state, rule)))
+}
+
+fn suppression_response(state: &AppState, rule: SuppressionRule) -> SuppressionResponse {
+    let in_maintenance = |drone: &Drone| drone.status == DroneStatus::Maintenance;
+    let in_effect = match &rule.window {
+        SuppressionWindow::Scheduled { start, end } => {
+            let now = state.clock.now();
+            *start <= now && now < *end
+        }
+        SuppressionWindow::WhileMaintenance => match &rule.drone_id {
+            Some(drone_id) => state.tracker.get_drone(drone_id).is_some_and(|t| in_maintenance(&t.drone)),
+            None => state.tracker.get_all_drones().iter().any(|t| in_maintenance(&t.drone)),
+        },
+    };
+    SuppressionResponse { rule, in_effect }
+}
+
+/// Longest alert message a rule may carry
+pub const MAX_RULE_MESSAGE_LEN: usize = 500;
+
+/// Alert rule definition
+#[derive(Deserialize)]
+pub struct AlertRuleRequest {
+    pub name: String,
+    pub condition: Condition,
+    pub severity: AlertSeverity,
+    /// Alert message (defaults to the rule name and condition)
+    pub message: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Validate for AlertRuleRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("name", &self.name, MAX_ID_LEN);
+        if let Some(message) = &self.message {
+            errors.check_len("message", message, MAX_RULE_MESSAGE_LEN);
+        }
+        if let Err(e) = self.condition.check() {
+            errors.add("condition", e);
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct AlertRuleListResponse {
+    pub rules: Vec<AlertRule>,
+    pub total: usize,
+}
+
+/// List alert rules
+pub async fn list_alert_rules(State(state): State<AppState>) -> impl IntoResponse {
+    let rules = state.tracker.rules();
+    let total = rules.len();
+    Json(AlertRuleListResponse { rules, total })
+}
+
+/// Create an alert rule
+pub async fn create_alert_rule(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<AlertRuleRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut rule = AlertRule::new(req.name, req.condition, req.severity, state.clock.now());
+    rule.message = req.message;
+    rule.enabled = req.enabled;
+
+    let rule = state.tracker.save_rule(rule).await?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// Get an alert rule
+pub async fn get_alert_rule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rule_id = parse_rule_id(&id)?;
+    let rule = state
+        .tracker
+        .rule(&rule_id)
+        .ok_or_else(|| ApiError::not_found(format!("Alert rule {} not found", id)))?;
+    Ok(Json(rule))
+}
+
+/// Replace an alert rule's definition
+pub async fn update_alert_rule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<AlertRuleRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rule_id = parse_rule_id(&id)?;
+    let mut rule = state
+        .tracker
+        .rule(&rule_id)
+        .ok_or_else(|| ApiError::not_found(format!("Alert rule {} not found", id)))?;
+    rule.name = req.name;
+    rule.condition = req.condition;
+    rule.severity = req.severity;
+    rule.message = req.message;
+    rule.enabled = req.enabled;
+    rule.updated_at = state.clock.now();
+
+    let rule = state.tracker.save_rule(rule).await?;
+    Ok(Json(rule))
+}
+
+/// Delete an alert rule
+pub async fn delete_alert_rule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let rule_id = parse_rule_id(&id)?;
+    let rule = state
+        .tracker
+        .remove_rule(&rule_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Alert rule {} not found", id)))?;
+    Ok(Json(rule))
+}
+
+fn parse_rule_id(id: &str) -> Result<Uuid, ApiError> {
+    Uuid::parse_str(id).map_err(|_| ApiError::bad_request(format!("Invalid alert rule id: {}", id)))
+}
+
+// ============================================================================
+// CUSTOM EVENT HANDLERS
+// ============================================================================
+
+/// Longest accepted schema description
+pub const MAX_SCHEMA_DESCRIPTION_LEN: usize = 500;
+
+/// Longest custom event history window
+const MAX_CUSTOM_HISTORY_WINDOW: chrono::Duration = chrono::Duration::hours(24);
+
+impl From<CustomEventError> for ApiError {
+    fn from(err: CustomEventError) -> Self {
+        match err {
+            CustomEventError::InvalidType(_)
+            | CustomEventError::ReservedNamespace(_)
+            | CustomEventError::UnknownType(_) => ApiError::validation("event_type", err.to_string()),
+            CustomEventError::InvalidSchema { path, message } => {
+                ApiError::validation(format!("schema{}", path.trim_end_matches('/')), message)
+            }
+            CustomEventError::TooLarge { .. } => ApiError::validation("value", err.to_string()),
+            CustomEventError::Invalid { violations, .. } => ApiError::Validation(
+                violations
+                    .into_iter()
+                    .map(|v| FieldError {
+                        field: format!("value{}", v.path),
+                        message: v.message,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Register or replace a custom event type
+#[derive(Debug, Deserialize)]
+pub struct EventSchemaRequest {
+    /// JSON Schema the event values must match
+    pub schema: serde_json::Value,
+    pub description: Option<String>,
+}
+
+impl Validate for EventSchemaRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(description) = &self.description {
+            errors.check_len("description", description, MAX_SCHEMA_DESCRIPTION_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Serialize)]
+pub struct EventSchemaListResponse {
+    pub schemas: Vec<RegisteredSchema>,
+    pub total: usize,
+}
+
+/// List custom event types
+pub async fn list_event_schemas(State(state): State<AppState>) -> impl IntoResponse {
+    let schemas = state.tracker.event_schemas();
+    let total = schemas.len();
+    Json(EventSchemaListResponse { schemas, total })
+}
+
+/// Get the schema of a custom event type
+pub async fn get_event_schema(
+    State(state): State<AppState>,
+    Path(event_type): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    state
+        .tracker
+        .event_schema(&event_type)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Custom event type {} not registered", event_type)))
+}
+
+/// Register a custom event type, or replace its schema
+pub async fn put_event_schema(
+    State(state): State<AppState>,
+    Path(event_type): Path<String>,
+    ValidJson(req): ValidJson<EventSchemaRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let existed = state.tracker.event_schema(&event_type).is_some();
+    let schema = RegisteredSchema::new(event_type, req.schema, req.description, state.clock.now())?;
+    let schema = state.tracker.save_event_schema(schema).await?;
+    let status = if existed { StatusCode::OK } else { StatusCode::CREATED };
+    Ok((status, Json(schema)))
+}
+
+/// Remove a custom event type; stored events are kept
+pub async fn delete_event_schema(
+    State(state): State<AppState>,
+    Path(event_type): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let schema = state
+        .tracker
+        .remove_event_schema(&event_type)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Custom event type {} not registered", event_type)))?;
+    Ok(Json(schema))
+}
+
+/// Publish an event of a registered custom type
+#[derive(Debug, Deserialize)]
+pub struct CustomEventRequest {
+    pub event_type: String,
+    pub drone_id: Option<DroneId>,
+    pub value: serde_json::Value,
+}
+
+impl Validate for CustomEventRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("event_type", &self.event_type, MAX_CUSTOM_TYPE_LEN);
+        if let Some(drone_id) = &self.drone_id {
+            errors.check_len("drone_id", drone_id.as_str(), MAX_ID_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+/// Validate a custom event, broadcast it to subscribers and store it
+pub async fn publish_custom_event(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<CustomEventRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let event = CustomEvent {
+        event_type: req.event_type,
+        drone_id: req.drone_id,
+        value: req.value,
+    };
+    let event = state.tracker.publish_custom_event(event).await?;
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Query parameters for stored custom events
+#[derive(Debug, Deserialize)]
+pub struct CustomEventHistoryQuery {
+    pub from: Option<chrono::DateTime<Utc>>,
+    pub to: Option<chrono::DateTime<Utc>>,
+}
+
+/// A stored custom event
+#[derive(Debug, Serialize)]
+pub struct StoredCustomEvent {
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub timestamp: chrono::DateTime<Utc>,
+    pub drone_id: Option<String>,
+    pub mission_id: Option<Uuid>,
+    pub value: serde_json::Value,
+}
+
+/// Stored events of one custom type, oldest first; the last hour by default
+pub async fn get_custom_event_history(
+    State(state): State<AppState>,
+    Path(event_type): Path<String>,
+    Query(query): Query<CustomEventHistoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    use futures::TryStreamExt;
+
+    let to = query.to.unwrap_or_else(|| state.clock.now());
+    let from = query.from.unwrap_or(to - chrono::Duration::hours(1));
+    let mut errors = ValidationErrors::new();
+    if from > to {
+        errors.add("from", format!("must not be after to ({})", to));
+    } else if to - from > MAX_CUSTOM_HISTORY_WINDOW {
+        errors.add("to", format!("must be within {} hours of from", MAX_CUSTOM_HISTORY_WINDOW.num_hours()));
+    }
+    errors.into_result()?;
+
+    let db = state
+        .db
+        .clone()
+        .ok_or_else(|| ApiError::ServiceUnavailable("No database configured".into()))?;
+    let records: Vec<_> = db.custom_events().stream_range(&event_type, from, to).await?.try_collect().await?;
+    let events: Vec<StoredCustomEvent> = records
+        .into_iter()
+        .map(|record| StoredCustomEvent {
+            value: serde_json::from_str(&record.value).unwrap_or(serde_json::Value::Null),
+            event_id: record.event_id,
+            event_type: record.event_type,
+            timestamp: record.event_time,
+            drone_id: record.drone_id,
+            mission_id: record.mission_id,
+        })
+        .collect();
+    Ok(Json(events))
+}
+
+// ============================================================================
+// PUSH NOTIFICATION HANDLERS
+// ============================================================================
+
+/// Longest accepted device token
+pub const MAX_DEVICE_TOKEN_LEN: usize = 512;
+
+/// Device registration for critical alert pushes
+#[derive(Deserialize)]
+pub struct PushSubscriptionRequest {
+    pub user_id: String,
+    pub platform: PushPlatform,
+    pub device_token: String,
+    #[serde(flatten)]
+    pub preferences: PushPreferences,
+}
+
+impl Validate for PushSubscriptionRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("user_id", &self.user_id, MAX_ID_LEN);
+        errors.check_len("device_token", &self.device_token, MAX_DEVICE_TOKEN_LEN);
+        if !matches!(self.preferences.min_severity, AlertSeverity::Critical | AlertSeverity::Emergency) {
+            errors.add("min_severity", "must be CRITICAL or EMERGENCY");
+        }
+        for (i, drone_id) in self.preferences.drone_ids.iter().enumerate() {
+            errors.check_len(&format!("drone_ids[{}]", i), drone_id.as_str(), MAX_ID_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushSubscriptionQuery {
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushDeliveryQuery {
+    pub alert_id: Option<Uuid>,
+    pub user_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PushSubscriptionListResponse {
+    pub subscriptions: Vec<PushSubscription>,
+    pub total: usize,
+}
+
+/// List push subscriptions, optionally for one user
+pub async fn list_push_subscriptions(
+    State(state): State<AppState>,
+    Query(query): Query<PushSubscriptionQuery>,
+) -> impl IntoResponse {
+    let subscriptions = state.push.subscriptions(query.user_id.as_deref());
+    let total = subscriptions.len();
+    Json(PushSubscriptionListResponse { subscriptions, total })
+}
+
+/// Register a device for critical alert pushes
+pub async fn create_push_subscription(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<PushSubscriptionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    req.platform
+        .check_device_token(&req.device_token)
+        .map_err(|reason| ApiError::bad_request(format!("device_token: {}", reason)))?;
+    if !state.push.has_provider(req.platform) {
+        return Err(ApiError::validation(
+            "platform",
+            format!("no {} provider is configured", req.platform),
+        ));
+    }
+
+    let subscription = PushSubscription::new(
+        req.user_id,
+        req.platform,
+        req.device_token,
+        req.preferences,
+        Utc::now(),
+    );
+    let subscription = state.push.subscribe(subscription).await?;
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// Remove a push subscription
+pub async fn delete_push_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let subscription_id = Uuid::parse_str(&id)
+        .map_err(|_| ApiError::bad_request(format!("Invalid subscription id: {}", id)))?;
+    let subscription = state
+        .push
+        .unsubscribe(&subscription_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Subscription {} not found", id)))?;
+    Ok(Json(subscription))
+}
+
+/// Recent push deliveries and their status, newest first
+pub async fn list_push_deliveries(
+    State(state): State<AppState>,
+    Query(query): Query<PushDeliveryQuery>,
+) -> impl IntoResponse {
+    Json(state.push.deliveries(query.alert_id, query.user_id.as_deref()))
+}
+
+// ============================================================================
+// HANDOFF HANDLERS
+// ============================================================================
+
+/// Longest accepted peer station URL
+const MAX_PEER_URL_LEN: usize = 2048;
+
+#[derive(Debug, Deserialize)]
+pub struct HandoffRequest {
+    /// Base URL of the receiving station's API
+    pub peer_url: String,
+    /// API key of this fleet's tenant on a multi-tenant peer
+    #[serde(default)]
+    pub peer_api_key: Option<String>,
+}
+
+impl Validate for HandoffRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("peer_url", &self.peer_url, MAX_PEER_URL_LEN);
+        if !self.peer_url.starts_with("https://") {
+            errors.add("peer_url", "must be an https:// URL");
+        }
+        errors.into_result()
+    }
+}
+
+/// Most track points and alerts a handoff package may carry
+const MAX_HANDOFF_HISTORY: usize = 10_000;
+const MAX_HANDOFF_ALERTS: usize = 1000;
+
+impl Validate for HandoffPackage {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("origin_station", &self.origin_station, 128);
+        errors.check_len("drone.id", &self.drone.id.0, MAX_ID_LEN);
+        errors.check_len("drone.callsign", &self.drone.callsign, 128);
+        errors.check_latitude("drone.position.latitude", self.drone.position.latitude);
+        errors.check_longitude("drone.position.longitude", self.drone.position.longitude);
+        errors.check_range("waypoint_progress", self.waypoint_progress, 0.0, 1.0);
+        if self.position_history.len() > MAX_HANDOFF_HISTORY {
+            errors.add("position_history", format!("must have at most {} points", MAX_HANDOFF_HISTORY));
+        }
+        if self.active_alerts.len() > MAX_HANDOFF_ALERTS {
+            errors.add("active_alerts", format!("must have at most {} alerts", MAX_HANDOFF_ALERTS));
+        }
+        if let Some(Err(e)) = self.thresholds.as_ref().map(Validate::validate) {
+            errors.nest("thresholds", e);
+        }
+        errors.into_result()
+    }
+}
+
+/// Hand a drone to a peer station; on success it becomes read-only here
+pub async fn handoff_drone(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<HandoffRequest>,
+) -> Result<Json<RemoteOwner>, ApiError> {
+    let drone_id = DroneId::new(&id);
+    tracked_drone(&state, &id)?;
+    check_locally_controlled(&state, &drone_id)?;
+    if !state.handoff.permits(&req.peer_url) {
+        return Err(ApiError::Forbidden(format!("{} is not an allowed handoff peer", req.peer_url)));
+    }
+    let _guard = state
+        .tracker
+        .begin_handoff(&drone_id)
+        .ok_or_else(|| ApiError::Conflict(format!("A handoff of drone {} is already in progress", id)))?;
+
+    let station = &state.handoff.config().station_id;
+    let package = state
+        .tracker
+        .export_handoff(&drone_id, station)
+        .ok_or_else(|| ApiError::not_found(format!("Drone {} not found", id)))?;
+    let ack = state
+        .handoff
+        .send(&req.peer_url, req.peer_api_key.as_deref(), &package)
+        .await
+        .map_err(|e| match e {
+            HandoffError::Disabled => ApiError::ServiceUnavailable(e.to_string()),
+            e => ApiError::ServiceUnavailable(format!("Handoff of {} failed: {}", id, e)),
+        })?;
+
+    state.tracker.complete_handoff(&package, &ack.station, Some(req.peer_url)).await;
+    info!("Drone {} handed off to station {}", id, ack.station);
+    state
+        .tracker
+        .remote_owner(&drone_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::internal("handoff not recorded"))
+}
+
+/// Take ownership of a drone handed over by a peer station
+pub async fn accept_handoff(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ValidJson(package): ValidJson<HandoffPackage>,
+) -> Result<Json<HandoffAck>, ApiError> {
+    if !state.handoff.authorize(&headers) {
+        return Err(ApiError::Unauthorized("missing or invalid handoff token".into()));
+    }
+
+    let handoff_id = package.handoff_id;
+    let drone = package.drone.clone();
+    state.tracker.accept_handoff(package).await.map_err(|e| match e {
+        HandoffFailure::InProgress(_) | HandoffFailure::ControlledHere(_) => ApiError::Conflict(e.to_string()),
+        HandoffFailure::Store(e) => ApiError::from(e),
+    })?;
+    state.drones.insert(drone.id.clone(), drone);
+
+    Ok(Json(HandoffAck {
+        handoff_id,
+        station: state.handoff.config().station_id.clone(),
+    }))
+}
+
+/// Drones handed off to other stations
+pub async fn list_handoffs(State(state): State<AppState>) -> Json<Vec<RemoteOwner>> {
+    Json(state.tracker.remote_drones())
+}
+
+// ============================================================================
+// COMMAND TRANSPORT HANDLERS
+// ============================================================================
+
+/// A drone's command transport binding
+#[derive(Debug, Serialize)]
+pub struct DroneTransportResponse {
+    pub drone_id: DroneId,
+    pub binding: TransportBinding,
+    /// Whether this station can currently reach the bound transport
+    pub available: bool,
+}
+
+/// Registered transports and the drones bound to a transport other than the mesh
+#[derive(Debug, Serialize)]
+pub struct TransportsResponse {
+    pub available: Vec<TransportKind>,
+    pub bindings: Vec<DroneTransportResponse>,
+}
+
+fn drone_transport_response(state: &AppState, drone_id: DroneId, binding: TransportBinding) -> DroneTransportResponse {
+    let available = binding.kind == TransportKind::P2p
+        || state.tracker.available_transports().contains(&binding.kind);
+    DroneTransportResponse { drone_id, binding, available }
+}
+
+/// List command transports and per-drone bindings
+pub async fn list_transports(State(state): State<AppState>) -> Json<TransportsResponse> {
+    let bindings = state
+        .tracker
+        .transport_bindings()
+        .into_iter()
+        .map(|(drone_id, binding)| drone_transport_response(&state, drone_id, binding))
+        .collect();
+    Json(TransportsResponse {
+        available: state.tracker.available_transports(),
+        bindings,
+    })
+}
+
+/// Get the transport a drone's commands go over
+pub async fn get_drone_transport(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DroneTransportResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    let binding = state.tracker.transport_binding(&drone.id);
+    Ok(Json(drone_transport_response(&state, drone.id, binding)))
+}
+
+/// Bind a drone's commands to a transport
+pub async fn set_drone_transport(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(binding): ValidJson<TransportBinding>,
+) -> Result<Json<DroneTransportResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    check_locally_controlled(&state, &drone.id)?;
+
+    state.tracker.set_transport_binding(&drone.id, Some(binding.clone())).await?;
+    info!("Drone {} bound to command transport {}", id, binding.kind);
+
+    Ok(Json(drone_transport_response(&state, drone.id, binding)))
+}
+
+/// Return a drone's commands to the P2P mesh
+pub async fn clear_drone_transport(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DroneTransportResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+
+    state.tracker.set_transport_binding(&drone.id, None).await?;
+    info!("Drone {} returned to the P2P mesh for commands", id);
+
+    Ok(Json(drone_transport_response(&state, drone.id, TransportBinding::default())))
+}
+
+// ============================================================================
+// TELEMETRY SOURCE HANDLERS
+// ============================================================================
+
+/// A drone's designated telemetry source
+#[derive(Debug, Serialize)]
+pub struct DroneTelemetrySourceResponse {
+    pub drone_id: DroneId,
+    /// `None` = reports are accepted from any source
+    pub source: Option<TelemetrySource>,
+}
+
+/// Designations, and drones currently reporting from another source
+#[derive(Debug, Serialize)]
+pub struct TelemetrySourcesResponse {
+    pub designations: Vec<DroneTelemetrySourceResponse>,
+    pub conflicts: Vec<SourceConflict>,
+}
+
+#[derive(Deserialize)]
+pub struct TelemetrySourceRequest {
+    pub source: TelemetrySource,
+}
+
+impl Validate for TelemetrySourceRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        Ok(())
     }
-    Json(serde_json::json!({"status": "paused"}))
 }
 
-/// Resume mission
-pub async fn resume_mission(State(state): State<AppState>) -> impl IntoResponse {
-    if let Some(mission) = state.active_mission.read().as_ref() {
-        info!("Mission {} resumed", mission.name);
+/// A position report posted by a drone or its ground relay
+#[derive(Deserialize)]
+pub struct PositionReportRequest {
+    pub position: GeoPosition,
+    #[serde(default)]
+    pub telemetry: Telemetry,
+}
+
+impl Validate for PositionReportRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_range("position.latitude", self.position.latitude, -90.0, 90.0);
+        errors.check_range("position.longitude", self.position.longitude, -180.0, 180.0);
+        errors.into_result()
     }
-    Json(serde_json::json!({"status": "resumed"}))
 }
 
-/// Abort mission
-pub async fn abort_mission(State(state): State<AppState>) -> impl IntoResponse {
-    if let Some(mission) = state.active_mission.read().as_ref() {
-        info!("Mission {} aborted", mission.name);
+/// List telemetry source designations and ongoing conflicts
+pub async fn list_telemetry_sources(State(state): State<AppState>) -> Json<TelemetrySourcesResponse> {
+    let mut designations: Vec<_> = state
+        .tracker
+        .telemetry_sources()
+        .into_iter()
+        .map(|(drone_id, source)| DroneTelemetrySourceResponse { drone_id, source: Some(source) })
+        .collect();
+    designations.sort_by(|a, b| a.drone_id.as_str().cmp(b.drone_id.as_str()));
+    Json(TelemetrySourcesResponse {
+        designations,
+        conflicts: state.tracker.source_conflicts(),
+    })
+}
+
+/// Get where a drone's telemetry is taken from
+pub async fn get_drone_telemetry_source(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DroneTelemetrySourceResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    let source = state.tracker.telemetry_source(&drone.id);
+    Ok(Json(DroneTelemetrySourceResponse { drone_id: drone.id, source }))
+}
+
+/// Take a drone's telemetry only from one source
+pub async fn set_drone_telemetry_source(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<TelemetrySourceRequest>,
+) -> Result<Json<DroneTelemetrySourceResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+
+    state.tracker.set_telemetry_source(&drone.id, Some(req.source)).await?;
+    info!("Drone {} now takes telemetry from {}", id, req.source);
+
+    Ok(Json(DroneTelemetrySourceResponse { drone_id: drone.id, source: Some(req.source) }))
+}
+
+/// Accept a drone's telemetry from any source again
+pub async fn clear_drone_telemetry_source(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DroneTelemetrySourceResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+
+    state.tracker.set_telemetry_source(&drone.id, None).await?;
+    info!("Drone {} takes telemetry from any source", id);
+
+    Ok(Json(DroneTelemetrySourceResponse { drone_id: drone.id, source: None }))
+}
+
+/// Report a drone's position over the API
+pub async fn report_drone_position(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<PositionReportRequest>,
+) -> Result<StatusCode, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    check_locally_controlled(&state, &drone.id)?;
+
+    state
+        .tracker
+        .update_drone_position(&drone.id, req.position, req.telemetry)
+        .await
+        .map_err(|e| match e {
+            ReportRejected::Invalid(e) => ApiError::validation(e.field().to_string(), e.to_string()),
+            ReportRejected::RemotelyControlled { .. } | ReportRejected::WrongSource { .. } => {
+                ApiError::Conflict(e.to_string())
+            }
+        })?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+// ============================================================================
+// FLEET MARKING HANDLERS
+// ============================================================================
+
+/// A drone's halo color and icon
+#[derive(Debug, Serialize)]
+pub struct DroneMarkingResponse {
+    pub drone_id: DroneId,
+    /// `None` = unmarked
+    pub marking: Option<DroneMarking>,
+}
+
+/// Halo color and icon per marked drone
+#[derive(Debug, Serialize)]
+pub struct FleetMarkingsResponse {
+    pub markings: Vec<DroneMarkingResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct DroneMarkingRequest {
+    pub halo_color: HaloColor,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+impl Validate for DroneMarkingRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.halo_color.hue().is_none() {
+            errors.add("halo_color", "must be a saturated color, not a grey");
+        }
+        if let Some(icon) = &self.icon {
+            errors.check_len("icon", icon, MAX_ID_LEN);
+        }
+        errors.into_result()
     }
-    Json(serde_json::json!({"status": "aborted"}))
 }
 
-/// Get mission waypoints
-pub async fn get_waypoints(State(state): State<AppState>) -> impl IntoResponse {
-    let waypoints: Vec<WaypointResponse> = state.get_mission()
-        .map(|m| m.waypoints.iter().map(|wp| WaypointResponse {
-            id: wp.id.0.clone(),
-            name: wp.name.clone(),
-            latitude: wp.position.latitude,
-            longitude: wp.position.longitude,
-            waypoint_type: format!("{:?}", wp.waypoint_type),
-        }).collect())
-        .unwrap_or_default();
+/// List the fleet's halo colors and icons
+pub async fn list_fleet_markings(State(state): State<AppState>) -> Json<FleetMarkingsResponse> {
+    let markings = state
+        .tracker
+        .drone_markings()
+        .into_iter()
+        .map(|(drone_id, marking)| DroneMarkingResponse { drone_id, marking: Some(marking) })
+        .collect();
+    Json(FleetMarkingsResponse { markings })
+}
 
-    Json(waypoints)
+/// Get a drone's halo color and icon
+pub async fn get_drone_marking(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DroneMarkingResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    let marking = state.tracker.drone_marking(&drone.id);
+    Ok(Json(DroneMarkingResponse { drone_id: drone.id, marking }))
+}
+
+/// Paint a drone's halo color and choose its icon
+pub async fn set_drone_marking(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidJson(req): ValidJson<DroneMarkingRequest>,
+) -> Result<Json<DroneMarkingResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+    let marking = DroneMarking {
+        halo_color: req.halo_color,
+        icon: req.icon,
+    };
+
+    state
+        .tracker
+        .set_drone_marking(&drone.id, Some(marking.clone()))
+        .await
+        .map_err(|e| match e.downcast_ref::<ColorTaken>() {
+            Some(taken) => ApiError::Conflict(taken.to_string()),
+            None => ApiError::from(e),
+        })?;
+    info!("Drone {} marked with halo color {}", id, marking.halo_color);
+
+    Ok(Json(DroneMarkingResponse { drone_id: drone.id, marking: Some(marking) }))
+}
+
+/// Remove a drone's marking
+pub async fn clear_drone_marking(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DroneMarkingResponse>, ApiError> {
+    let drone = tracked_drone(&state, &id)?;
+
+    state.tracker.set_drone_marking(&drone.id, None).await?;
+    info!("Drone {} marking removed", id);
+
+    Ok(Json(DroneMarkingResponse { drone_id: drone.id, marking: None }))
 }
 
 // ============================================================================
-// TRACKING HANDLERS
+// EXPORT HANDLERS
 // ============================================================================
 
-/// Get CV tracking results
-pub async fn get_tracking_results(State(state): State<AppState>) -> impl IntoResponse {
-    // In real implementation, would return actual CV tracking data
-    Json(serde_json::json!({
-        "tracks": [],
-        "frame_timestamp": Utc::now().to_rfc3339(),
-    }))
+/// Start a background telemetry / waypoint event export
+pub async fn create_export(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ExportRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if req.missing_mission_id() {
+        return Err(ApiError::bad_request("mission_id is required for waypoint_events exports"));
+    }
+    let db = state.db.clone().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Database not available for export".into())
+    })?;
+
+    let drone_ids: Vec<DroneId> = if req.drone_ids.is_empty() {
+        state.drones.iter().map(|d| d.key().clone()).collect()
+    } else {
+        req.drone_ids.iter().map(DroneId::new).collect()
+    };
+
+    let job = state.exports.create(&req);
+    info!("Export {} queued ({:?} as {:?})", job.id, req.dataset, req.format);
+
+    tokio::spawn(export::run_job(state.exports.clone(), db, job.id, req, drone_ids));
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
-/// Get tracking statistics
-// pub async fn get_tracking_stats(State(state): State<AppState>) -> impl IntoResponse {
-//     let active_tracks = state.cv_engine
-//         .as_ref()
-//         .map(|e| e.read().active_track_count())
-//         .unwrap_or(0);
-
-//     Json(TrackingStatsResponse {
-//         active_tracks,
-//         cv_enabled: state.has_cv(),
-//         frames_processed: 0,
-//     })
-// }
-/// Get tracking statistics
-pub async fn get_tracking_stats(State(state): State<AppState>) -> impl IntoResponse {
-    // CV disabled for macOS build
-    let active_tracks = 0;
+/// Get export job status
+pub async fn get_export(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    state.exports.get(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Export {} not found", id)))
+}
 
-    Json(TrackingStatsResponse {
-        active_tracks,
-        cv_enabled: false,
-        frames_processed: 0,
-    })
+/// Download a completed export file as a chunked stream
+pub async fn download_export(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let job = state.exports.get(&id)
+        .ok_or_else(|| ApiError::not_found(format!("Export {} not found", id)))?;
+
+    if job.status != ExportStatus::Completed {
+        return Err(ApiError::bad_request(format!(
+            "Export {} is not ready (status: {:?})",
+            id, job.status
+        )));
+    }
+
+    let path = state.exports.file_path(&id, job.format);
+    let file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|e| ApiError::internal(format!("Export file unavailable: {}", e)))?;
+
+    let body = Body::from_stream(tokio_util::io::ReaderStream::new(file));
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        path.file_name().and_then(|n| n.to_str()).unwrap_or("export")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, job.format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+/// Query parameters for a MOTChallenge export
+#[derive(Debug, Deserialize)]
+pub struct MotExportQuery {
+    #[serde(default)]
+    pub kind: MotKind,
+    pub from: Option<chrono::DateTime<Utc>>,
+    pub to: Option<chrono::DateTime<Utc>>,
+}
+
+impl MotExportQuery {
+    /// Resolve and check the time range
+    pub fn time_range(&self) -> Result<(chrono::DateTime<Utc>, chrono::DateTime<Utc>), ValidationErrors> {
+        let to = self.to.unwrap_or_else(Utc::now);
+        let from = self.from.unwrap_or(to - mot::DEFAULT_WINDOW);
+        let mut errors = ValidationErrors::new();
+        if from > to {
+            errors.add("from", format!("must not be after to ({})", to));
+        } else if to - from > mot::MAX_WINDOW {
+            errors.add("to", format!("must be within {} hours of from", mot::MAX_WINDOW.num_hours()));
+        }
+        errors.into_result().map(|_| (from, to))
+    }
+}
+
+/// Query parameters for a historical fleet state
+#[derive(Debug, Deserialize)]
+pub struct StateAtQuery {
+    pub timestamp: chrono::DateTime<Utc>,
 }
 
+/// Fleet state at a past instant, rebuilt from persisted telemetry and alerts
+pub async fn get_state_at(
+    State(state): State<AppState>,
+    Query(query): Query<StateAtQuery>,
+) -> Result<Json<FleetStateAt>, ApiError> {
+    if query.timestamp > state.tracker.clock().now() {
+        return Err(ApiError::validation("timestamp", "must not be in the future"));
+    }
+    let db = state
+        .db
+        .clone()
+        .ok_or_else(|| ApiError::ServiceUnavailable("No database configured".into()))?;
+
+    // Drones registered in the database plus any only seen live
+    let mut drone_ids = db.drones().get_all().await?;
+    drone_ids.extend(state.drones.iter().map(|d| d.key().clone()));
+
+    Ok(Json(FleetStateAt::load(&db, drone_ids, query.timestamp).await?))
+}
+
+/// Stored CV results, or the matching ground truth, in MOTChallenge format
+pub async fn download_mot_export(
+    State(state): State<AppState>,
+    Query(query): Query<MotExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    use futures::TryStreamExt;
+
+    let (from, to) = query.time_range()?;
+    let db = state.db.clone().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Database not available for export".into())
+    })?;
+
+    let results: Vec<TrackingResult> = db.tracking().stream_range(from, to).await?.try_collect().await?;
+    let body = match query.kind {
+        MotKind::Detections => mot::detections(&results),
+        MotKind::GroundTruth => {
+            let skew = mot::GROUND_TRUTH_MAX_SKEW;
+            let drone_ids: Vec<DroneId> = state.drones.iter().map(|d| d.key().clone()).collect();
+            let mut tracks = Vec::with_capacity(drone_ids.len());
+            for drone_id in drone_ids {
+                let positions = db
+                    .telemetry()
+                    .stream_range(&drone_id, from - skew, to + skew)
+                    .await?
+                    .map_ok(|r| (r.timestamp, GeoPosition::new(r.latitude, r.longitude, r.altitude)))
+                    .try_collect()
+                    .await?;
+                tracks.push((drone_id, positions));
+            }
+            mot::ground_truth(&mot::frames(&results), &tracks, &simulation::route_camera(), skew)
+        }
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", query.kind.file_name())),
+        ],
+        body,
+    ))
+}
 
 // ============================================================================
-// ALERT HANDLERS
+// IMPORT HANDLERS
 // ============================================================================
 
-/// List alerts
-pub async fn list_alerts(State(_state): State<AppState>) -> impl IntoResponse {
-    // Demo alerts
-    let alerts = vec![
-        AlertResponse {
-            id: "alert-001".into(),
-            severity: "INFO".into(),
-            alert_type: "SYSTEM".into(),
-            message: "System initialized successfully".into(),
-            drone_id: None,
-            acknowledged: true,
-            created_at: Utc::now().to_rfc3339(),
-        },
-    ];
+/// Start a background import of a historical telemetry file
+pub async fn create_import(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ImportRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let db = state.db.clone().ok_or_else(|| {
+        ApiError::ServiceUnavailable("Database not available for import".into())
+    })?;
+
+    if !state.imports.source_path(&req.file).is_file() {
+        return Err(ApiError::not_found(format!("Import file {} not found", req.file)));
+    }
+
+    let job = state.imports.create(&req).map_err(|active| {
+        ApiError::Conflict(format!("Import {} is already reading {}", active, req.file))
+    })?;
+    info!("Import {} queued ({} as {:?})", job.id, req.file, req.settings.format);
 
-    Json(alerts)
+    tokio::spawn(backfill::run_job(state.imports.clone(), db, job.id, req));
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
-/// Acknowledge alert
-pub async fn acknowledge_alert(
-    State(_state): State<AppState>,
-    Path(id): Path<String>,
-) -> impl IntoResponse {
-    info!("Alert {} acknowledged", id);
-    Json(serde_json::json!({"status": "acknowledged", "alert_id": id}))
+/// List import jobs, newest first
+pub async fn list_imports(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.imports.list())
+}
+
+/// Get import job progress
+pub async fn get_import(
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    state.imports.get(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("Import {} not found", id)))
 }
 
 // ============================================================================
@@ -459,6 +4288,202 @@ pub async fn websocket_info(State(state): State<AppState>) -> impl IntoResponse
     })
 }
 
+/// Filters of the event history list
+#[derive(Debug, Default, Deserialize)]
+pub struct EventListQuery {
+    pub drone_ids: Option<String>,
+    pub mission_ids: Option<String>,
+    pub event_types: Option<String>,
+    /// Events at or after this time
+    pub from: Option<chrono::DateTime<Utc>>,
+    /// Events before this time
+    pub to: Option<chrono::DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct EventListResponse {
+    /// Events, trimmed to `fields` when given
+    pub events: Vec<serde_json::Value>,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Sorting and fields of the event list
+const EVENT_LIST: ListSpec = ListSpec {
+    id_field: "id",
+    sortable: &["timestamp", "event_type"],
+    selectable: &["timestamp", "event_type", "payload", "mission_id", "tenant_id"],
+    default_sort: "timestamp",
+    default_descending: true,
+    ranked: &[],
+};
+
+/// Events still in the event bus history, newest first
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventListQuery>,
+    Query(list): Query<ListQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter = EventStreamQuery {
+        drone_ids: query.drone_ids,
+        mission_ids: query.mission_ids,
+        event_types: query.event_types,
+        ..Default::default()
+    }
+    .filter()?;
+    let list = list.params(EVENT_LIST)?;
+
+    let from = query.from.unwrap_or(chrono::DateTime::<Utc>::MIN_UTC);
+    let to = query.to.unwrap_or(chrono::DateTime::<Utc>::MAX_UTC);
+    let events = match &filter.event_types {
+        Some(types) => state.events.events_of_type(types, from, to),
+        None => state.events.events_between(from, to),
+    };
+
+    let page = list.paginate(events.into_iter().filter(|e| filter.matches(e)));
+    Ok(Json(EventListResponse {
+        events: page.items,
+        total: page.total,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// Stream events over SSE, resuming after `Last-Event-ID` when given
+pub async fn stream_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<EventStreamQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let filter = query.filter()?;
+
+    // The header set by EventSource on reconnect wins over the query parameter
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or(query.last_event_id)
+        .map(|id| {
+            Uuid::parse_str(id.trim())
+                .map_err(|_| ApiError::validation("last_event_id", format!("invalid event id: {}", id)))
+        })
+        .transpose()?;
+
+    let stream = sse::event_stream(&state.events, filter, last_event_id, query.since);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(HEARTBEAT_INTERVAL).text("heartbeat")))
+}
+
+// ============================================================================
+// SIMULATION CLOCK HANDLERS
+// ============================================================================
+
+/// Current simulated time, scale and pause state
+pub async fn get_simulation_clock(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.clock.status())
+}
+
+/// Change the time scale, pause/resume or step the simulation clock
+pub async fn update_simulation_clock(
+    State(state): State<AppState>,
+    ValidJson(req): ValidJson<ClockUpdateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let clock = &state.clock;
+
+    if let Some(scale) = req.scale {
+        clock
+            .set_scale(scale)
+            .map_err(|e| ApiError::validation("scale", e.to_string()))?;
+    }
+    match req.paused {
+        Some(true) => clock.pause(),
+        Some(false) => clock.resume(),
+        None => {}
+    }
+    if let Some(step) = req.step_seconds {
+        clock.step(std::time::Duration::from_secs_f64(step));
+    }
+
+    let status = clock.status();
+    info!(
+        "Simulation clock: {}x{} at {}",
+        status.scale,
+        if status.paused { " (paused)" } else { "" },
+        status.now
+    );
+    Ok(Json(status))
+}
+
+// ============================================================================
+// RETENTION HANDLERS
+// ============================================================================
+
+#[derive(Serialize)]
+pub struct RetentionPolicyResponse {
+    pub table: RetentionTable,
+    pub retention_days: f64,
+    pub enforcement: Enforcement,
+    pub purged_rows: u64,
+}
+
+#[derive(Serialize)]
+pub struct RetentionResponse {
+    pub policies: Vec<RetentionPolicyResponse>,
+    pub purge_interval_seconds: u64,
+    pub dry_run: bool,
+    pub last_report: Option<PurgeReport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeQuery {
+    /// Count expired rows without deleting them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn retention_manager(state: &AppState) -> Result<&RetentionManager, ApiError> {
+    state
+        .retention
+        .as_deref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("No database configured".into()))
+}
+
+/// Retention policies, rows purged since startup and the last purge report
+pub async fn get_retention(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let manager = retention_manager(&state)?;
+    let config = manager.config();
+    let policies = config
+        .policies
+        .iter()
+        .map(|policy| RetentionPolicyResponse {
+            table: policy.table,
+            retention_days: policy.retention.as_secs_f64() / 86_400.0,
+            enforcement: manager.enforcement(policy.table),
+            purged_rows: manager.purged_total(policy.table),
+        })
+        .collect();
+
+    Ok(Json(RetentionResponse {
+        policies,
+        purge_interval_seconds: config.purge_interval.as_secs(),
+        dry_run: config.dry_run,
+        last_report: manager.last_report(),
+    }))
+}
+
+/// Run the purge jobs now, or report what they would delete
+pub async fn run_retention_purge(
+    State(state): State<AppState>,
+    Query(query): Query<PurgeQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let report = retention_manager(&state)?.run_once(state.clock.now(), query.dry_run).await;
+    info!(
+        "Retention purge{}: {} rows",
+        if query.dry_run { " (dry run)" } else { "" },
+        report.tables.iter().map(|t| t.rows).sum::<u64>()
+    );
+    Ok(Json(report))
+}
+
 // ============================================================================
 // STATE HANDLERS
 // ============================================================================
@@ -467,19 +4492,13 @@ pub async fn websocket_info(State(state): State<AppState>) -> impl IntoResponse
 pub async fn get_full_state(State(state): State<AppState>) -> impl IntoResponse {
     let drones: Vec<DroneResponse> = state.get_all_drones()
         .into_iter()
-        .map(drone_to_response)
+        .map(|d| drone_to_response(&state, d))
         .collect();
 
     let mission = state.get_mission().map(|m| mission_to_response(&m));
     
     let waypoints: Vec<WaypointResponse> = state.get_mission()
-        .map(|m| m.waypoints.iter().map(|wp| WaypointResponse {
-            id: wp.id.0.clone(),
-            name: wp.name.clone(),
-            latitude: wp.position.latitude,
-            longitude: wp.position.longitude,
-            waypoint_type: format!("{:?}", wp.waypoint_type),
-        }).collect())
+        .map(|m| waypoints_to_response(&state, &m))
         .unwrap_or_default();
 
     Json(FullStateResponse {
@@ -493,7 +4512,11 @@ pub async fn get_full_state(State(state): State<AppState>) -> impl IntoResponse
 // HELPER FUNCTIONS
 // ============================================================================
 
-fn drone_to_response(drone: Drone) -> DroneResponse {
+fn drone_to_response(state: &AppState, drone: Drone) -> DroneResponse {
+    let marking = state.tracker.drone_marking(&drone.id);
+    let presentation = state.presentation.present_drone(&drone, marking.as_ref(), Utc::now());
+    let role = state.tracker.convoy().role(&drone.id);
+    let controlled_by = state.tracker.remote_owner(&drone.id).map(|owner| owner.station);
     DroneResponse {
         id: drone.id.0,
         callsign: drone.callsign,
@@ -510,12 +4533,58 @@ fn drone_to_response(drone: Drone) -> DroneResponse {
             speed: drone.telemetry.speed,
             heading: drone.telemetry.heading,
             signal_strength: drone.telemetry.signal_strength,
+            gimbal: drone.telemetry.gimbal,
         },
         armed: drone.armed,
         current_waypoint: drone.current_waypoint_index,
+        presentation,
+        role,
+        controlled_by,
+    }
+}
+
+/// Handed-off drones are read-only until they are handed back
+fn check_locally_controlled(state: &AppState, drone_id: &DroneId) -> Result<(), ApiError> {
+    match state.tracker.remote_owner(drone_id) {
+        Some(owner) => Err(ApiError::Conflict(format!(
+            "Drone {} is controlled by station {}",
+            drone_id, owner.station
+        ))),
+        None => Ok(()),
     }
 }
 
+fn tracked_drone(state: &AppState, id: &str) -> Result<Drone, ApiError> {
+    state.tracker
+        .get_drone(&DroneId::new(id))
+        .map(|tracked| tracked.drone)
+        .ok_or_else(|| ApiError::not_found(format!("Drone {} not found", id)))
+}
+
+fn drone_thresholds_response(state: &AppState, drone: &Drone) -> DroneThresholdsResponse {
+    DroneThresholdsResponse {
+        drone_id: drone.id.0.clone(),
+        drone_type: drone.drone_type.clone(),
+        effective: state.tracker.thresholds_for(drone),
+        drone_overrides: state.tracker.drone_thresholds(&drone.id),
+        type_overrides: state.tracker.type_thresholds(&drone.drone_type),
+    }
+}
+
+fn type_thresholds_response(state: &AppState, drone_type: DroneType) -> TypeThresholdsResponse {
+    TypeThresholdsResponse {
+        effective: state.tracker.resolve_thresholds(&drone_type, None),
+        type_overrides: state.tracker.type_thresholds(&drone_type),
+        drone_type,
+    }
+}
+
+/// Parse a drone type path segment (e.g. `RQ4_GLOBAL_HAWK`), falling back to custom
+fn parse_drone_type(value: &str) -> DroneType {
+    serde_json::from_value(serde_json::Value::String(value.to_uppercase()))
+        .unwrap_or_else(|_| DroneType::Custom(value.to_string()))
+}
+
 fn mission_to_response(mission: &Mission) -> MissionResponse {
     MissionResponse {
         id: mission.id.0.to_string(),
@@ -524,5 +4593,41 @@ fn mission_to_response(mission: &Mission) -> MissionResponse {
         waypoint_count: mission.waypoints.len(),
         assigned_drones: mission.assigned_drones.len(),
         total_distance_km: mission.total_distance_km(),
+        enforce_speed_limits: mission.enforce_speed_limits,
     }
 }
+
+fn waypoints_to_response(state: &AppState, mission: &Mission) -> Vec<WaypointResponse> {
+    let mut attachments = state.attachments.by_waypoint(&mission.id);
+    let fresh;
+    let route = if mission.route.is_current(&mission.waypoints) {
+        &mission.route
+    } else {
+        fresh = RouteMetrics::compute(&mission.waypoints);
+        &fresh
+    };
+
+    mission
+        .waypoints
+        .iter()
+        .enumerate()
+        .map(|(i, wp)| {
+            let leg = i.checked_sub(1).and_then(|leg| route.legs.get(leg));
+            WaypointResponse {
+                id: wp.id.0.clone(),
+                name: wp.name.clone(),
+                latitude: wp.position.latitude,
+                longitude: wp.position.longitude,
+                waypoint_type: format!("{:?}", wp.waypoint_type),
+                cumulative_distance_km: leg.map(|l| l.cumulative_km).unwrap_or(0.0),
+                leg: leg.map(|l| LegResponse {
+                    from: l.from.0.clone(),
+                    distance_km: l.distance_km,
+                    bearing_deg: l.bearing_deg,
+                    speed_limit_kmh: wp.speed_limit_kmh,
+                }),
+                attachments: attachments.remove(&wp.id).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,297 @@
+//! Ground control station handoff over HTTP
+//!
+//! A station hands a drone to its peer by POSTing the drone's
+//! `HandoffPackage` to the peer's `/api/v1/handoff/accept`. Stations
+//! authenticate each other with a shared token sent in `X-Handoff-Token`;
+//! without `HANDOFF_TOKEN` a station neither sends nor accepts handoffs.
+//! The token only goes to the https peers listed in `HANDOFF_PEERS`.
+
+use crate::push::{HttpClient, HttpRequest, HyperClient};
+
+use axum::http::HeaderMap;
+use drone_tracker::HandoffPackage;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Header carrying the shared station token
+pub const HANDOFF_TOKEN_HEADER: &str = "x-handoff-token";
+
+/// Peer path that takes ownership of a handed-off drone
+pub const HANDOFF_ACCEPT_PATH: &str = "/api/v1/handoff/accept";
+
+/// Station identity and peer authentication
+#[derive(Clone)]
+pub struct HandoffConfig {
+    /// This station's name, reported to peers and shown on remote drones
+    pub station_id: String,
+    /// Token shared by all stations; handoff is disabled without one
+    pub token: Option<String>,
+    /// Base URLs of the stations drones may be handed to
+    pub peers: Vec<String>,
+    /// Timeout for the call to the peer
+    pub timeout: Duration,
+}
+
+// Keeps the token out of logs
+impl std::fmt::Debug for HandoffConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HandoffConfig")
+            .field("station_id", &self.station_id)
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field("peers", &self.peers)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Default for HandoffConfig {
+    fn default() -> Self {
+        Self {
+            station_id: "gcs-local".into(),
+            token: None,
+            peers: Vec::new(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HandoffConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            station_id: std::env::var("HANDOFF_STATION_ID").unwrap_or(defaults.station_id),
+            token: std::env::var("HANDOFF_TOKEN").ok().filter(|t| !t.is_empty()),
+            peers: std::env::var("HANDOFF_PEERS")
+                .map(|list| {
+                    list.split(',')
+                        .map(|url| url.trim().trim_end_matches('/').to_string())
+                        .filter(|url| !url.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.peers),
+            timeout: std::env::var("HANDOFF_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+        }
+    }
+}
+
+/// The receiving station's answer to an accepted handoff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffAck {
+    pub handoff_id: Uuid,
+    /// Station that now controls the drone
+    pub station: String,
+}
+
+#[derive(Debug, Error)]
+pub enum HandoffError {
+    #[error("handoff is disabled (HANDOFF_TOKEN is not set)")]
+    Disabled,
+
+    #[error("{0} is not an allowed handoff peer (HANDOFF_PEERS)")]
+    PeerNotAllowed(String),
+
+    #[error("peer unreachable: {0}")]
+    Transport(String),
+
+    #[error("peer refused the handoff ({status}): {body}")]
+    Rejected { status: u16, body: String },
+
+    #[error("invalid peer response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Sends handoffs to peer stations and authenticates incoming ones
+pub struct HandoffClient {
+    config: HandoffConfig,
+    http: Arc<dyn HttpClient>,
+}
+
+impl HandoffClient {
+    pub fn new(config: HandoffConfig) -> Self {
+        let http = Arc::new(HyperClient::new(config.timeout));
+        Self { config, http }
+    }
+
+    /// Replace the HTTP transport (tests)
+    #[allow(dead_code)]
+    pub fn with_http(mut self, http: Arc<dyn HttpClient>) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn config(&self) -> &HandoffConfig {
+        &self.config
+    }
+
+    /// Whether drones may be handed to the station at `peer_url`: it must
+    /// be https and listed in `HANDOFF_PEERS`
+    pub fn permits(&self, peer_url: &str) -> bool {
+        let url = peer_url.trim_end_matches('/');
+        url.starts_with("https://") && self.config.peers.iter().any(|peer| peer == url)
+    }
+
+    /// Whether a request carries this station's token
+    pub fn authorize(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.config.token else {
+            return false;
+        };
+        headers
+            .get(HANDOFF_TOKEN_HEADER)
+            .is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes()))
+    }
+
+    /// Deliver a package to the peer at `peer_url`; `api_key` reaches a
+    /// multi-tenant peer's tenant
+    pub async fn send(
+        &self,
+        peer_url: &str,
+        api_key: Option<&str>,
+        package: &HandoffPackage,
+    ) -> Result<HandoffAck, HandoffError> {
+        let token = self.config.token.as_ref().ok_or(HandoffError::Disabled)?;
+        if !self.permits(peer_url) {
+            return Err(HandoffError::PeerNotAllowed(peer_url.to_string()));
+        }
+        let mut headers = vec![(HANDOFF_TOKEN_HEADER.to_string(), token.clone())];
+        if let Some(key) = api_key {
+            headers.push(("x-api-key".to_string(), key.to_string()));
+        }
+        let body = serde_json::to_vec(package).map_err(|e| HandoffError::InvalidResponse(e.to_string()))?;
+        let request = HttpRequest {
+            url: format!("{}{}", peer_url.trim_end_matches('/'), HANDOFF_ACCEPT_PATH),
+            headers,
+            body,
+        };
+
+        let response = self
+            .http
+            .post(request)
+            .await
+            .map_err(|e| HandoffError::Transport(e.to_string()))?;
+        if !(200..300).contains(&response.status) {
+            return Err(HandoffError::Rejected {
+                status: response.status,
+                body: response.body,
+            });
+        }
+
+        let ack: HandoffAck = serde_json::from_str(&response.body)
+            .map_err(|e| HandoffError::InvalidResponse(e.to_string()))?;
+        if ack.handoff_id != package.handoff_id {
+            return Err(HandoffError::InvalidResponse(format!(
+                "acknowledged handoff {} instead of {}",
+                ack.handoff_id, package.handoff_id
+            )));
+        }
+        Ok(ack)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::push::{HttpResponse, PushError};
+    use async_trait::async_trait;
+    use drone_core::{Drone, DroneId};
+    use parking_lot::Mutex;
+
+    /// Answers like a peer station and keeps the requests
+    struct Peer {
+        status: u16,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for Peer {
+        async fn post(&self, request: HttpRequest) -> Result<HttpResponse, PushError> {
+            let package: HandoffPackage = serde_json::from_slice(&request.body).unwrap();
+            self.requests.lock().push(request);
+            let ack = HandoffAck { handoff_id: package.handoff_id, station: "gcs-south".into() };
+            Ok(HttpResponse {
+                status: self.status,
+                headers: Vec::new(),
+                body: serde_json::to_string(&ack).unwrap(),
+            })
+        }
+    }
+
+    fn package() -> HandoffPackage {
+        HandoffPackage {
+            handoff_id: Uuid::new_v4(),
+            origin_station: "gcs-north".into(),
+            issued_at: chrono::Utc::now(),
+            drone: Drone::new(DroneId::new("REAPER-01"), "Alpha Lead"),
+            mission_id: None,
+            waypoint_index: 0,
+            waypoint_progress: 0.0,
+            position_history: Vec::new(),
+            active_alerts: Vec::new(),
+            role: None,
+            thresholds: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_and_authorize() {
+        let config = HandoffConfig {
+            station_id: "gcs-north".into(),
+            token: Some("s3cret".into()),
+            peers: vec!["https://gcs-south:3000".into()],
+            ..Default::default()
+        };
+        let peer = Arc::new(Peer { status: 200, requests: Mutex::new(Vec::new()) });
+        let client = HandoffClient::new(config.clone()).with_http(peer.clone());
+
+        let ack = client.send("https://gcs-south:3000/", Some("tenant-key"), &package()).await.unwrap();
+        assert_eq!(ack.station, "gcs-south");
+        let request = peer.requests.lock().pop().unwrap();
+        assert_eq!(request.url, "https://gcs-south:3000/api/v1/handoff/accept");
+        assert!(request.headers.contains(&(HANDOFF_TOKEN_HEADER.into(), "s3cret".into())));
+        assert!(request.headers.contains(&("x-api-key".into(), "tenant-key".into())));
+
+        // The token never leaves for an unlisted or plain-http peer
+        for url in ["https://gcs-rogue:3000", "http://gcs-south:3000"] {
+            assert!(matches!(
+                client.send(url, None, &package()).await,
+                Err(HandoffError::PeerNotAllowed(_))
+            ));
+        }
+        assert!(peer.requests.lock().is_empty());
+
+        let refusing = Arc::new(Peer { status: 401, requests: Mutex::new(Vec::new()) });
+        let client = HandoffClient::new(config).with_http(refusing);
+        assert!(matches!(
+            client.send("https://gcs-south:3000", None, &package()).await,
+            Err(HandoffError::Rejected { status: 401, .. })
+        ));
+
+        let mut headers = HeaderMap::new();
+        assert!(!client.authorize(&headers));
+        headers.insert(HANDOFF_TOKEN_HEADER, "wrong".parse().unwrap());
+        assert!(!client.authorize(&headers));
+        headers.insert(HANDOFF_TOKEN_HEADER, "s3cret".parse().unwrap());
+        assert!(client.authorize(&headers));
+
+        let disabled = HandoffClient::new(HandoffConfig::default());
+        assert!(!disabled.authorize(&headers));
+        assert!(matches!(
+            disabled.send("https://gcs-south:3000", None, &package()).await,
+            Err(HandoffError::Disabled)
+        ));
+    }
+}
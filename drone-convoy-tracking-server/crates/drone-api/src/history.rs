@@ -0,0 +1,174 @@
+//! Historical fleet state
+//!
+//! Rebuilds what the fleet looked like at a past instant from persisted
+//! data: each drone's last telemetry row at or before the instant gives its
+//! position, status and levels, and its alerts raised within
+//! [`ACTIVE_ALERT_WINDOW`] before the instant (and not yet acknowledged then)
+//! are the ones active at the time, as on the live fleet view.
+
+use crate::fleet::ACTIVE_ALERT_WINDOW;
+use drone_core::{DroneId, GeoPosition};
+use drone_db::{AlertRecord, DbClient, DbResult};
+
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// How far back to look for a drone's last report; older drones are omitted
+pub const TELEMETRY_LOOKBACK: Duration = Duration::hours(1);
+
+/// Fleet state at a historical instant
+#[derive(Debug, Clone, Serialize)]
+pub struct FleetStateAt {
+    pub at: DateTime<Utc>,
+    pub drones: Vec<DroneStateAt>,
+}
+
+/// One drone as last reported before the instant
+#[derive(Debug, Clone, Serialize)]
+pub struct DroneStateAt {
+    pub drone_id: DroneId,
+    /// When the report was received
+    pub sampled_at: DateTime<Utc>,
+    pub position: GeoPosition,
+    /// Not recorded for rows written before statuses were persisted
+    pub status: Option<String>,
+    pub armed: Option<bool>,
+    pub battery: i32,
+    pub fuel: i32,
+    pub heading: f64,
+    pub speed: f64,
+    /// Newest active alert of each type
+    pub active_alerts: Vec<AlertRecord>,
+}
+
+impl FleetStateAt {
+    /// Reconstruct the state of `drone_ids` at `at`
+    pub async fn load(db: &DbClient, drone_ids: impl IntoIterator<Item = DroneId>, at: DateTime<Utc>) -> DbResult<Self> {
+        let drone_ids: BTreeSet<DroneId> = drone_ids.into_iter().collect();
+        let window = Duration::from_std(ACTIVE_ALERT_WINDOW).unwrap_or_default();
+
+        let mut drones = Vec::new();
+        for drone_id in drone_ids {
+            let Some(record) = db.telemetry().get_at(&drone_id, at - TELEMETRY_LOOKBACK, at).await? else {
+                continue;
+            };
+            let alerts: Vec<AlertRecord> = db.alerts().stream_range(&drone_id, at - window, at).await?.try_collect().await?;
+
+            drones.push(DroneStateAt {
+                drone_id,
+                sampled_at: record.timestamp,
+                position: GeoPosition::new(record.latitude, record.longitude, record.altitude),
+                status: record.status,
+                armed: record.armed,
+                battery: record.battery_level,
+                fuel: record.fuel_level,
+                heading: record.heading,
+                speed: record.speed,
+                active_alerts: active_alerts(alerts, at),
+            });
+        }
+
+        Ok(Self { at, drones })
+    }
+}
+
+/// Alerts unacknowledged at `at`, keeping the newest of each type
+fn active_alerts(alerts: Vec<AlertRecord>, at: DateTime<Utc>) -> Vec<AlertRecord> {
+    let mut latest: HashMap<Option<String>, AlertRecord> = HashMap::new();
+    for alert in alerts {
+        if alert.acknowledged_at.is_some_and(|acked| acked <= at) {
+            continue;
+        }
+        match latest.get(&alert.alert_type) {
+            Some(newer) if newer.created_at > alert.created_at => {}
+            _ => {
+                latest.insert(alert.alert_type.clone(), alert);
+            }
+        }
+    }
+
+    let mut active: Vec<AlertRecord> = latest.into_values().collect();
+    active.sort_by_key(|alert| alert.created_at);
+    active
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{Alert, AlertSeverity, AlertType, DroneStatus, Telemetry};
+    use drone_db::{DbConfig, SqliteStore, TelemetryRecord};
+
+    #[tokio::test]
+    async fn test_reconstructs_positions_statuses_and_alerts() {
+        let db = DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default());
+        let drone = DroneId::new("REAPER-01");
+        // Whole milliseconds, as stored
+        let start = DateTime::from_timestamp_millis(Utc::now().timestamp_millis()).unwrap() - Duration::minutes(10);
+        let telemetry = Telemetry::default();
+
+        let report = |seconds: i64, lat: f64, status: DroneStatus| {
+            let mut record = TelemetryRecord::new(
+                &drone,
+                &GeoPosition::new(lat, 69.2, 3000.0),
+                &telemetry,
+                status,
+                true,
+                None,
+            );
+            record.timestamp = start + Duration::seconds(seconds);
+            record
+        };
+        db.telemetry()
+            .insert_records(vec![
+                report(0, 34.50, DroneStatus::Moving),
+                report(30, 34.51, DroneStatus::Moving),
+                report(60, 34.52, DroneStatus::Rtb),
+            ])
+            .await
+            .unwrap();
+
+        let alert = |seconds: i64, alert_type: AlertType| {
+            let mut alert = Alert::new(AlertSeverity::Warning, alert_type, "test").for_drone(drone.clone());
+            alert.created_at = start + Duration::seconds(seconds);
+            alert
+        };
+        let fuel = alert(25, AlertType::FuelLow);
+        for alert in [alert(10, AlertType::BatteryLow), alert(20, AlertType::BatteryLow), fuel.clone()] {
+            db.alerts().create(&alert).await.unwrap();
+        }
+        db.alerts()
+            .acknowledge(&drone, fuel.id, fuel.created_at, "operator", start + Duration::seconds(50))
+            .await
+            .unwrap();
+
+        let state = FleetStateAt::load(&db, [drone.clone()], start + Duration::seconds(45)).await.unwrap();
+        assert_eq!(state.drones.len(), 1);
+        let at = &state.drones[0];
+        assert_eq!(at.sampled_at, start + Duration::seconds(30));
+        assert_eq!(at.position.latitude, 34.51);
+        assert_eq!(at.status.as_deref(), Some("MOVING"));
+        // Re-raised alerts count once, at their newest
+        assert_eq!(at.active_alerts.len(), 2);
+        assert_eq!(at.active_alerts[0].created_at, start + Duration::seconds(20));
+
+        // Once acknowledged the fuel alert no longer counts
+        let state = FleetStateAt::load(&db, [drone.clone()], start + Duration::seconds(55)).await.unwrap();
+        assert_eq!(state.drones[0].active_alerts.len(), 1);
+        assert_eq!(state.drones[0].active_alerts[0].created_at, start + Duration::seconds(20));
+
+        // Later the drone is returning and its alerts have lapsed
+        let state = FleetStateAt::load(&db, [drone.clone()], start + Duration::seconds(120)).await.unwrap();
+        assert_eq!(state.drones[0].status.as_deref(), Some("RTB"));
+        assert!(state.drones[0].active_alerts.is_empty());
+
+        // Nothing was reported before the first row
+        let state = FleetStateAt::load(&db, [drone], start - Duration::seconds(1)).await.unwrap();
+        assert!(state.drones.is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+//! Simulated flight kinematics
+//!
+//! Simulated drones fly a point-mass model rather than sliding along route
+//! legs. Heading changes no faster than the profile's turn rate, which a
+//! banked turn further limits at speed (rate = g·tan(bank) / v), and speed
+//! changes within the acceleration and deceleration limits. Legs are joined
+//! by fly-by turns that start `turn_lead_m` before the waypoint, so ground
+//! tracks curve through corners instead of snapping onto the next heading.
+
+use drone_core::DroneType;
+
+use serde::Deserialize;
+
+/// Standard gravity (m/s²)
+const GRAVITY_MS2: f64 = 9.806_65;
+
+/// Metres per degree of latitude, as used for simulated GPS noise
+pub const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Flight limits of one drone type
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct KinematicProfile {
+    /// Fastest heading change at any speed (deg/s)
+    pub max_turn_rate_deg_s: f64,
+    /// Steepest bank in a turn (deg)
+    pub max_bank_deg: f64,
+    /// Speed gained per second (m/s²)
+    pub max_accel_ms2: f64,
+    /// Speed shed per second (m/s²)
+    pub max_decel_ms2: f64,
+}
+
+impl KinematicProfile {
+    /// Built-in limits per airframe; custom types fly like a Reaper
+    pub fn for_type(drone_type: &DroneType) -> Self {
+        let (turn, bank, accel, decel) = match drone_type {
+            DroneType::Mq9Reaper | DroneType::Custom(_) => (6.0, 30.0, 1.5, 2.0),
+            DroneType::Mq1Predator => (8.0, 35.0, 1.2, 1.5),
+            DroneType::Rq4GlobalHawk => (3.0, 20.0, 0.8, 1.0),
+            DroneType::Mq1CGrayEagle => (7.0, 30.0, 1.5, 2.0),
+        };
+        Self {
+            max_turn_rate_deg_s: turn,
+            max_bank_deg: bank,
+            max_accel_ms2: accel,
+            max_decel_ms2: decel,
+        }
+    }
+
+    /// Check every limit is positive and the bank below 90°; `advance`
+    /// cannot fly a profile that fails this
+    pub fn validate(&self) -> Result<(), String> {
+        let limits = [
+            ("max_turn_rate_deg_s", self.max_turn_rate_deg_s),
+            ("max_bank_deg", self.max_bank_deg),
+            ("max_accel_ms2", self.max_accel_ms2),
+            ("max_decel_ms2", self.max_decel_ms2),
+        ];
+        for (field, value) in limits {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{} must be positive, got {}", field, value));
+            }
+        }
+        if self.max_bank_deg >= 90.0 {
+            return Err(format!("max_bank_deg must be below 90, got {}", self.max_bank_deg));
+        }
+        Ok(())
+    }
+
+    /// Turn rate available at `speed_ms` (deg/s)
+    pub fn turn_rate_at(&self, speed_ms: f64) -> f64 {
+        let banked = GRAVITY_MS2 * self.max_bank_deg.to_radians().tan() / speed_ms.max(1.0);
+        self.max_turn_rate_deg_s.min(banked.to_degrees())
+    }
+
+    /// Radius of the tightest turn at `speed_ms` (m)
+    pub fn turn_radius_m(&self, speed_ms: f64) -> f64 {
+        speed_ms / self.turn_rate_at(speed_ms).to_radians()
+    }
+
+    /// Distance before a waypoint at which a fly-by turn of `turn_deg`
+    /// should start so the arc joins the next leg, at most `max_m`
+    pub fn turn_lead_m(&self, speed_ms: f64, turn_deg: f64, max_m: f64) -> f64 {
+        let half = (turn_deg.abs().min(179.0) / 2.0).to_radians();
+        (self.turn_radius_m(speed_ms) * half.tan()).min(max_m)
+    }
+}
+
+/// Position, heading and speed of a simulated drone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KinematicState {
+    pub lat: f64,
+    pub lng: f64,
+    /// Degrees clockwise from north
+    pub heading: f64,
+    pub speed_ms: f64,
+}
+
+impl KinematicState {
+    /// Advance by `dt` seconds, steering towards `target_heading` and
+    /// `target_speed_ms` within the profile's limits
+    pub fn advance(&mut self, profile: &KinematicProfile, target_heading: f64, target_speed_ms: f64, dt: f64) {
+        let dv = (target_speed_ms - self.speed_ms).clamp(-profile.max_decel_ms2 * dt, profile.max_accel_ms2 * dt);
+        self.speed_ms = (self.speed_ms + dv).max(0.0);
+
+        let max_turn = profile.turn_rate_at(self.speed_ms) * dt;
+        let turn = heading_delta(self.heading, target_heading).clamp(-max_turn, max_turn);
+        self.heading = (self.heading + turn).rem_euclid(360.0);
+
+        let distance = self.speed_ms * dt;
+        let heading = self.heading.to_radians();
+        self.lat += distance * heading.cos() / METERS_PER_DEGREE;
+        self.lng += distance * heading.sin() / (METERS_PER_DEGREE * self.lat.to_radians().cos());
+    }
+
+    /// Ground distance to a point (m), flat-earth over the short legs flown here
+    pub fn distance_to(&self, lat: f64, lng: f64) -> f64 {
+        let north = (lat - self.lat) * METERS_PER_DEGREE;
+        let east = (lng - self.lng) * METERS_PER_DEGREE * self.lat.to_radians().cos();
+        north.hypot(east)
+    }
+
+    /// Bearing to a point (degrees clockwise from north)
+    pub fn bearing_to(&self, lat: f64, lng: f64) -> f64 {
+        let north = lat - self.lat;
+        let east = (lng - self.lng) * self.lat.to_radians().cos();
+        east.atan2(north).to_degrees().rem_euclid(360.0)
+    }
+}
+
+/// Signed shortest turn from heading `from` to `to`, in `[-180, 180)` degrees
+pub fn heading_delta(from: f64, to: f64) -> f64 {
+    (to - from + 540.0).rem_euclid(360.0) - 180.0
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns_and_speed_changes_are_rate_limited() {
+        let profile = KinematicProfile::for_type(&DroneType::Mq9Reaper);
+        let mut state = KinematicState {
+            lat: 34.5553,
+            lng: 69.2075,
+            heading: 0.0,
+            speed_ms: 0.0,
+        };
+
+        // Accelerates no faster than the limit while turning east
+        let dt = 0.25;
+        let mut previous = state;
+        for _ in 0..40 {
+            state.advance(&profile, 90.0, 10.0, dt);
+            assert!(state.speed_ms - previous.speed_ms <= profile.max_accel_ms2 * dt + 1e-9);
+            assert!(heading_delta(previous.heading, state.heading).abs() <= profile.max_turn_rate_deg_s * dt + 1e-9);
+            previous = state;
+        }
+        // 10 s at 6 deg/s covers 60 of the 90 degrees; full speed after 6.7 s
+        assert!((state.heading - 60.0).abs() < 1e-6);
+        assert_eq!(state.speed_ms, 10.0);
+        // The track has curved: moved both north and east
+        assert!(state.lat > 34.5553 && state.lng > 69.2075);
+
+        // A steep bank bounds the turn rate only at speed
+        assert_eq!(profile.turn_rate_at(10.0), 6.0);
+        assert!(profile.turn_rate_at(200.0) < 6.0);
+        assert!((profile.turn_radius_m(10.0) - 10.0 / 6f64.to_radians()).abs() < 1e-9);
+        assert_eq!(profile.turn_lead_m(10.0, 0.0, 80.0), 0.0);
+        assert_eq!(profile.turn_lead_m(10.0, 180.0, 80.0), 80.0);
+        assert_eq!(heading_delta(350.0, 10.0), 20.0);
+        assert_eq!(heading_delta(10.0, 350.0), -20.0);
+
+        // SIM_KINEMATICS format
+        let overrides: std::collections::HashMap<DroneType, KinematicProfile> = serde_json::from_str(
+            r#"{"RQ4_GLOBAL_HAWK": {"max_turn_rate_deg_s": 2.5, "max_bank_deg": 15, "max_accel_ms2": 0.5, "max_decel_ms2": 0.8}}"#,
+        )
+        .unwrap();
+        assert_eq!(overrides[&DroneType::Rq4GlobalHawk].max_turn_rate_deg_s, 2.5);
+        assert!(overrides[&DroneType::Rq4GlobalHawk].validate().is_ok());
+        for broken in [
+            KinematicProfile { max_decel_ms2: -1.0, ..profile },
+            KinematicProfile { max_turn_rate_deg_s: f64::NAN, ..profile },
+            KinematicProfile { max_bank_deg: 90.0, ..profile },
+        ] {
+            assert!(broken.validate().is_err());
+        }
+    }
+}
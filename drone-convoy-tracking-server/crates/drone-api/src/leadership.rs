@@ -0,0 +1,600 @@
+//! Leader/standby failover between API instances
+//!
+//! Instances sharing a database elect a leader through the `api-leader`
+//! lease. The leader renews it every third of the lease period; a standby
+//! tries at the same rate and takes over once the lease has run out for
+//! longer than the clock skew allowed between instances. A new leader
+//! reloads the state its predecessor persisted before it starts leading,
+//! and a leader shutting down gives the lease up. Only the leader runs the
+//! simulation, fires scheduled commands and accepts writes; a standby
+//! serves reads. Without `HA_ENABLED` (or without a database to hold the
+//! lease) every instance is its own leader.
+
+use crate::error::ApiError;
+use crate::handlers;
+use crate::state::AppState;
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use drone_core::TenantId;
+use drone_db::DbClient;
+use drone_websocket::WebSocketHub;
+use futures::future::BoxFuture;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Lease the API instances compete for
+pub const LEADER_LEASE: &str = "api-leader";
+
+/// Shortest lease accepted from the environment
+const MIN_LEASE: Duration = Duration::from_secs(3);
+
+/// Leader election settings
+#[derive(Debug, Clone)]
+pub struct LeadershipConfig {
+    /// Coordinate with other instances through the lease
+    pub enabled: bool,
+    /// This instance's name in the lease and in `/status`
+    pub instance_id: String,
+    /// How long a lease lasts without renewal; a standby takes over within
+    /// this period plus `max_clock_skew` after the leader stops
+    pub lease: Duration,
+    /// Largest clock difference expected between instances; a lapsed lease
+    /// is only taken over once it has been expired for this long
+    pub max_clock_skew: Duration,
+}
+
+impl Default for LeadershipConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            instance_id: default_instance_id(),
+            lease: Duration::from_secs(10),
+            max_clock_skew: Duration::from_secs(2),
+        }
+    }
+}
+
+impl LeadershipConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("HA_ENABLED")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(defaults.enabled),
+            instance_id: std::env::var("HA_INSTANCE_ID")
+                .ok()
+                .filter(|id| !id.is_empty())
+                .unwrap_or(defaults.instance_id),
+            lease: std::env::var("HA_LEASE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_secs)
+                .map(|lease| lease.max(MIN_LEASE))
+                .unwrap_or(defaults.lease),
+            max_clock_skew: std::env::var("HA_MAX_CLOCK_SKEW_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.max_clock_skew),
+        }
+    }
+
+    /// Renewal and takeover attempt period
+    pub fn renew_interval(&self) -> Duration {
+        self.lease / 3
+    }
+}
+
+/// Host name in containers, else a random name
+fn default_instance_id() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("api-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]))
+}
+
+/// Role of this instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Leader,
+    Standby,
+}
+
+/// Leadership as reported in `/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadershipStatus {
+    /// Whether instances coordinate; when false this instance always leads
+    pub enabled: bool,
+    pub role: Role,
+    pub instance_id: String,
+    /// Current lease holder, once known
+    pub leader_id: Option<String>,
+    pub leader_since: Option<DateTime<Utc>>,
+    pub lease_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Step run when this instance wins the lease, before it starts leading
+type PromotionHook = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// This instance's side of the leader election
+pub struct Leadership {
+    config: LeadershipConfig,
+    /// Lease store; `None` when election is off
+    db: Option<Arc<DbClient>>,
+    status: RwLock<LeadershipStatus>,
+    role_tx: watch::Sender<Role>,
+    promotion_hooks: Mutex<Vec<PromotionHook>>,
+    /// Set once the instance steps down for good
+    stepped_down: AtomicBool,
+}
+
+impl Leadership {
+    pub fn new(config: LeadershipConfig, db: Option<Arc<DbClient>>) -> Self {
+        let db = match (config.enabled, db) {
+            (true, Some(db)) => Some(db),
+            (true, None) => {
+                warn!("HA_ENABLED is set but no database is configured; running as leader");
+                None
+            }
+            (false, _) => None,
+        };
+
+        // A coordinating instance starts as standby until its first lease attempt
+        let role = if db.is_some() { Role::Standby } else { Role::Leader };
+        let status = LeadershipStatus {
+            enabled: db.is_some(),
+            role,
+            instance_id: config.instance_id.clone(),
+            leader_id: (role == Role::Leader).then(|| config.instance_id.clone()),
+            leader_since: None,
+            lease_expires_at: None,
+        };
+
+        Self {
+            config,
+            db,
+            status: RwLock::new(status),
+            role_tx: watch::channel(role).0,
+            promotion_hooks: Mutex::new(Vec::new()),
+            stepped_down: AtomicBool::new(false),
+        }
+    }
+
+    pub fn status(&self) -> LeadershipStatus {
+        self.status.read().clone()
+    }
+
+    pub fn is_leader(&self) -> bool {
+        *self.role_tx.borrow() == Role::Leader
+    }
+
+    /// Run `hook` each time this instance wins the lease, before its
+    /// leader-only tasks start and before it accepts writes
+    pub fn on_promotion<F, Fut>(&self, hook: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.promotion_hooks.lock().push(Arc::new(move || Box::pin(hook())));
+    }
+
+    /// Take or renew the lease, or learn who holds it
+    pub async fn renew(&self, now: DateTime<Utc>) {
+        let Some(db) = &self.db else { return };
+        if self.stepped_down.load(Ordering::Acquire) {
+            return;
+        }
+
+        let result = db
+            .leases()
+            .try_acquire(
+                LEADER_LEASE,
+                &self.config.instance_id,
+                now,
+                self.config.lease,
+                self.config.max_clock_skew,
+            )
+            .await;
+        let role = match result {
+            Ok(lease) => {
+                let role = if lease.is_held_by(&self.config.instance_id) {
+                    Role::Leader
+                } else {
+                    Role::Standby
+                };
+                let mut status = self.status.write();
+                status.leader_id = Some(lease.holder);
+                status.leader_since = Some(lease.acquired_at);
+                status.lease_expires_at = Some(lease.expires_at);
+                role
+            }
+            Err(e) => {
+                warn!("Leader lease renewal failed: {}", e);
+                db.health().record_error();
+
+                // Step down before our lease can run out under a standby
+                let status = self.status.read();
+                let renew_by = now + chrono::Duration::from_std(self.config.renew_interval()).unwrap_or_default();
+                match status.lease_expires_at {
+                    Some(expires) if status.role == Role::Leader && renew_by < expires => Role::Leader,
+                    _ => Role::Standby,
+                }
+            }
+        };
+        if role == Role::Leader && !self.is_leader() {
+            let hooks = self.promotion_hooks.lock().clone();
+            for hook in hooks {
+                hook().await;
+            }
+        }
+        self.set_role(role);
+    }
+
+    /// Stop leading and contending for good, giving the lease up if held
+    /// so a standby takes over without waiting for it to run out
+    pub async fn step_down(&self) {
+        self.stepped_down.store(true, Ordering::Release);
+        let was_leader = self.is_leader();
+        self.set_role(Role::Standby);
+        let Some(db) = &self.db else { return };
+        if !was_leader {
+            return;
+        }
+        match db.leases().release(LEADER_LEASE, &self.config.instance_id).await {
+            Ok(()) => info!("Instance {} released the leader lease", self.config.instance_id),
+            Err(e) => {
+                warn!("Failed to release the leader lease: {}", e);
+                db.health().record_error();
+            }
+        }
+    }
+
+    fn set_role(&self, role: Role) {
+        let mut status = self.status.write();
+        if status.role != role {
+            match role {
+                Role::Leader => info!("Instance {} is now the leader", self.config.instance_id),
+                Role::Standby => info!(
+                    "Instance {} is standby (leader: {})",
+                    self.config.instance_id,
+                    status.leader_id.as_deref().unwrap_or("unknown")
+                ),
+            }
+        }
+        status.role = role;
+        self.role_tx.send_if_modified(|current| std::mem::replace(current, role) != role);
+    }
+
+    /// Renew the lease periodically; no-op when election is off
+    pub fn spawn(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        self.db.as_ref()?;
+
+        let leadership = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(leadership.config.renew_interval());
+            loop {
+                interval.tick().await;
+                leadership.renew(Utc::now()).await;
+            }
+        }))
+    }
+
+    /// Run a task only while this instance leads: `start` is called on
+    /// every promotion and its task aborted on demotion
+    pub fn spawn_while_leader<F>(&self, name: &'static str, start: F)
+    where
+        F: Fn() -> JoinHandle<()> + Send + 'static,
+    {
+        let mut role = self.role_tx.subscribe();
+        tokio::spawn(async move {
+            while role.wait_for(|r| *r == Role::Leader).await.is_ok() {
+                info!("Starting {} on the leader", name);
+                let task = start();
+                let demoted = role.wait_for(|r| *r == Role::Standby).await.is_ok();
+                task.abort();
+                if !demoted {
+                    break;
+                }
+                info!("Stopped {} on standby", name);
+            }
+        });
+    }
+}
+
+/// Refuse requests that change state while this instance is standby;
+/// reads, and export jobs that only read, pass
+pub async fn reject_writes_on_standby(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let reads = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+        || request.uri().path() == "/api/v1/export";
+    if reads || state.leadership.is_leader() {
+        return next.run(request).await;
+    }
+    let status = state.leadership.status();
+    ApiError::ServiceUnavailable(format!(
+        "Instance {} is standby; send changes to the leader ({})",
+        status.instance_id,
+        status.leader_id.as_deref().unwrap_or("unknown")
+    ))
+    .into_response()
+}
+
+/// Run drone commands from WebSocket consoles on their tenant's state;
+/// like HTTP writes, they are dropped while this instance is standby
+pub fn route_ws_commands(hub: &WebSocketHub, states: &[AppState]) {
+    let states: HashMap<Option<TenantId>, AppState> =
+        states.iter().map(|state| (state.tenant.clone(), state.clone())).collect();
+    hub.set_command_handler(move |tenant, command| {
+        let Some(state) = states.get(&tenant) else {
+            warn!("No fleet for a WebSocket command to {}", command.drone_id);
+            return;
+        };
+        if !state.leadership.is_leader() {
+            warn!("Instance is standby; dropping WebSocket command to {}", command.drone_id);
+            return;
+        }
+        let state = state.clone();
+        tokio::spawn(async move {
+            let drone_id = command.drone_id.clone();
+            if let Err(e) = handlers::run_client_command(&state, command).await {
+                warn!("WebSocket command to {} failed: {}", drone_id, e);
+            }
+        });
+    });
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+    use crate::packages::PackageConfig;
+    use chrono::TimeZone;
+    use crate::timeline::TimelineEntryKind;
+    use drone_core::{DroneCommand, DroneCommandType, DroneId, DroneStatus, GeoPosition, MissionId, MissionStatus, Telemetry};
+    use drone_db::{DbConfig, SqliteStore, StorageBackend, TelemetryRecord};
+    use drone_tracker::{CommandTrigger, ScheduledAction};
+    use drone_websocket::WebSocketHub;
+    use std::sync::atomic::AtomicUsize;
+
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn instance(id: &str, db: &Arc<DbClient>) -> Leadership {
+        let config = LeadershipConfig {
+            enabled: true,
+            instance_id: id.into(),
+            lease: Duration::from_secs(9),
+            max_clock_skew: Duration::from_secs(2),
+        };
+        Leadership::new(config, Some(db.clone()))
+    }
+
+    #[tokio::test]
+    async fn test_standby_takes_over_on_lease_expiry() {
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        let primary = instance("api-a", &db);
+        let standby = instance("api-b", &db);
+        assert!(!primary.is_leader());
+
+        let t0 = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        primary.renew(t0).await;
+        standby.renew(t0).await;
+        assert!(primary.is_leader());
+        assert!(!standby.is_leader());
+        let status = standby.status();
+        assert_eq!(status.role, Role::Standby);
+        assert_eq!(status.leader_id.as_deref(), Some("api-a"));
+
+        // Renewals keep the standby out
+        let t1 = t0 + chrono::Duration::seconds(6);
+        primary.renew(t1).await;
+        standby.renew(t1).await;
+        assert!(!standby.is_leader());
+
+        // The primary stops renewing; the standby leads once the lease has
+        // been lapsed for longer than the allowed clock skew
+        standby.renew(t1 + chrono::Duration::seconds(10)).await;
+        assert!(!standby.is_leader());
+        let t2 = t1 + chrono::Duration::seconds(12);
+        standby.renew(t2).await;
+        assert!(standby.is_leader());
+        assert_eq!(standby.status().leader_since, Some(t2));
+
+        // The old leader learns it on its next attempt
+        primary.renew(t2).await;
+        assert!(!primary.is_leader());
+
+        // Without election every instance leads
+        let solo = Leadership::new(LeadershipConfig::default(), Some(db));
+        assert!(solo.is_leader());
+        assert!(!solo.status().enabled);
+    }
+
+    #[tokio::test]
+    async fn test_promotion_hooks_and_step_down() {
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        let primary = Arc::new(instance("api-a", &db));
+        let standby = instance("api-b", &db);
+
+        // Hooks run once per promotion, while the instance is not yet leading
+        let promotions = Arc::new(AtomicUsize::new(0));
+        let (counter, watched) = (promotions.clone(), primary.clone());
+        primary.on_promotion(move || {
+            assert!(!watched.is_leader());
+            counter.fetch_add(1, Ordering::SeqCst);
+            async {}
+        });
+        let t0 = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        primary.renew(t0).await;
+        primary.renew(t0 + chrono::Duration::seconds(3)).await;
+        assert!(primary.is_leader());
+        assert_eq!(promotions.load(Ordering::SeqCst), 1);
+
+        // Stepping down hands the lease over without waiting for it to run out
+        primary.step_down().await;
+        assert!(!primary.is_leader());
+        let t1 = t0 + chrono::Duration::seconds(4);
+        standby.renew(t1).await;
+        assert!(standby.is_leader());
+        primary.renew(t1 + chrono::Duration::seconds(60)).await;
+        assert!(!primary.is_leader());
+    }
+
+    #[tokio::test]
+    async fn test_new_leader_reloads_persisted_state() {
+        let path = std::env::temp_dir().join(format!("leadership-{}.db", uuid::Uuid::new_v4()));
+        let config = |id: &str| ApiConfig {
+            db: DbConfig {
+                backend: StorageBackend::Sqlite,
+                sqlite_path: path.clone(),
+                ..Default::default()
+            },
+            leadership: LeadershipConfig {
+                enabled: true,
+                instance_id: id.into(),
+                ..Default::default()
+            },
+            packages: PackageConfig {
+                key_path: std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4())),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let leader = AppState::new(config("api-a"), Arc::new(WebSocketHub::new())).await.unwrap();
+        let standby = AppState::new(config("api-b"), Arc::new(WebSocketHub::new())).await.unwrap();
+        let db = leader.db.clone().unwrap();
+
+        // The leader schedules a command, starts a new mission and hears from a drone
+        let drone_id = DroneId::new("REAPER-01");
+        let command = leader
+            .tracker
+            .schedule_command(
+                CommandTrigger::At { at: leader.clock.now() + chrono::Duration::minutes(5) },
+                ScheduledAction::DroneCommand { drone_id: drone_id.clone(), command: DroneCommandType::ReturnToBase },
+                None,
+            )
+            .await
+            .unwrap();
+        let mut mission = leader.get_mission().unwrap();
+        mission.id = MissionId::new();
+        mission.name = "Operation Night Watch".into();
+        mission.status = MissionStatus::Active;
+        db.missions().create(&mission).await.unwrap();
+        let position = GeoPosition::new(34.61, 69.12, 1500.0);
+        let telemetry = Telemetry {
+            timestamp: standby.clock.now() - chrono::Duration::seconds(5),
+            ..Default::default()
+        };
+        let report = TelemetryRecord::new(&drone_id, &position, &telemetry, DroneStatus::Moving, true, Some(&mission.id));
+        db.telemetry().insert_records(vec![report]).await.unwrap();
+
+        standby.reload_from_db().await;
+        assert!(standby.tracker.scheduler().get(&command.id).is_some());
+        assert_eq!(standby.get_mission().map(|m| m.id), Some(mission.id.clone()));
+        assert_eq!(standby.tracker.get_mission().map(|m| m.name), Some(mission.name));
+        let tracked = standby.tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.status, DroneStatus::Moving);
+        assert_eq!((tracked.drone.position.latitude, tracked.drone.position.longitude), (34.61, 69.12));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_standby_refuses_writes() {
+        let packages = PackageConfig {
+            key_path: std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4())),
+            ..Default::default()
+        };
+        let mut state = AppState::new_without_db(ApiConfig { packages, ..Default::default() }, Arc::new(WebSocketHub::new()))
+            .await
+            .unwrap();
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        state.leadership = Arc::new(instance("api-b", &db));
+
+        let ok = || async { StatusCode::OK };
+        let app = Router::new()
+            .route("/api/v1/zones", get(ok).post(ok).delete(ok))
+            .route("/api/v1/export", axum::routing::post(ok))
+            .layer(middleware::from_fn_with_state(state.clone(), reject_writes_on_standby))
+            .with_state(state);
+        let status = |method: &str, uri: &str| {
+            let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+
+        assert_eq!(status("GET", "/api/v1/zones").await, StatusCode::OK);
+        assert_eq!(status("POST", "/api/v1/export").await, StatusCode::OK);
+        assert_eq!(status("POST", "/api/v1/zones").await, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status("DELETE", "/api/v1/zones").await, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_ws_commands_run_on_their_tenant_leader() {
+        let state = |tenant: &'static str| async move {
+            let packages = PackageConfig {
+                key_path: std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4())),
+                ..Default::default()
+            };
+            AppState::new_without_db(ApiConfig { packages, ..Default::default() }, Arc::new(WebSocketHub::new()))
+                .await
+                .unwrap()
+                .with_tenant(TenantId::parse(tenant).unwrap())
+        };
+        let acme = state("acme").await;
+        let mut globex = state("globex").await;
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        globex.leadership = Arc::new(instance("api-b", &db));
+
+        let hub = WebSocketHub::new();
+        route_ws_commands(&hub, &[acme.clone(), globex.clone()]);
+        let console = |state: &AppState| {
+            let client_id = uuid::Uuid::new_v4();
+            let _ = hub.register_tenant_client(client_id, state.tenant.clone());
+            client_id
+        };
+        let commands = |state: &AppState| {
+            let mission = state.get_mission().unwrap();
+            let page = state.timeline.page(&mission.id, None, 100);
+            page.phases
+                .iter()
+                .flat_map(|phase| &phase.entries)
+                .filter(|entry| entry.kind == TimelineEntryKind::Command)
+                .map(|entry| entry.summary.clone())
+                .collect::<Vec<_>>()
+        };
+        let command = DroneCommand { drone_id: DroneId::new("REAPER-01"), command: DroneCommandType::ReturnToBase };
+
+        // Each console's command runs on its own tenant's leader
+        hub.handle_command(console(&acme), command.clone()).await;
+        for _ in 0..100 {
+            if !commands(&acme).is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(commands(&acme), ["ReturnToBase sent to REAPER-01"]);
+
+        // A standby drops it
+        hub.handle_command(console(&globex), command).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(commands(&globex).is_empty());
+        assert_eq!(commands(&acme).len(), 1);
+    }
+}
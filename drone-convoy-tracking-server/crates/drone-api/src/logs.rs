@@ -0,0 +1,328 @@
+//! Log output and recent log records
+//!
+//! Logs go to stdout as text, or as JSON lines with `LOG_FORMAT=json`. The
+//! tracker wraps position updates, commands and telemetry writes in spans
+//! carrying `drone_id` and `mission_id`; JSON lines include them, and so do
+//! the last `LOG_BUFFER_RECORDS` records kept in memory for
+//! `GET /api/v1/logs`, which filters them by drone, mission and level.
+//! Tenant requests and tenant trackers also carry `tenant_id`; a tenant's
+//! log query only sees its own records.
+
+use crate::validation::ValidationErrors;
+
+use chrono::{DateTime, Utc};
+use drone_core::TenantId;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+use tracing::{Event, Id, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Records returned when `limit` is not given
+pub const DEFAULT_LOG_LIMIT: usize = 100;
+
+/// Largest `limit`
+pub const MAX_LOG_LIMIT: usize = 1000;
+
+/// Log line format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Logging configuration, read before anything else so startup is logged
+#[derive(Debug, Clone)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    /// Recent records kept for the log query endpoint
+    pub buffer_records: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            buffer_records: 2000,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            format: match std::env::var("LOG_FORMAT").map(|v| v.to_ascii_lowercase()).as_deref() {
+                Ok("json") => LogFormat::Json,
+                _ => defaults.format,
+            },
+            buffer_records: std::env::var("LOG_BUFFER_RECORDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.buffer_records),
+        }
+    }
+}
+
+/// One log record with the drone, mission and tenant of its spans
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drone_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mission_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Other fields of the event and its spans
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    #[serde(skip)]
+    severity: Level,
+}
+
+/// Filters for recent log records
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogQuery {
+    pub drone_id: Option<String>,
+    pub mission_id: Option<String>,
+    /// Least severe level to return (`error`, `warn`, `info`, `debug`, `trace`)
+    pub level: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// The last records logged, oldest first
+#[derive(Debug)]
+pub struct LogBuffer {
+    capacity: usize,
+    records: Mutex<VecDeque<LogRecord>>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity.min(4096))),
+        }
+    }
+
+    fn push(&self, record: LogRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut records = self.records.lock();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// The most recent `limit` records matching the query, oldest first;
+    /// with a tenant, only that tenant's records
+    pub fn query(&self, query: &LogQuery, tenant: Option<&TenantId>) -> Result<Vec<LogRecord>, ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let limit = query.limit.unwrap_or(DEFAULT_LOG_LIMIT);
+        if !(1..=MAX_LOG_LIMIT).contains(&limit) {
+            errors.add("limit", format!("must be between 1 and {}", MAX_LOG_LIMIT));
+        }
+        let level = match query.level.as_deref().map(Level::from_str).transpose() {
+            Ok(level) => level,
+            Err(_) => {
+                errors.add("level", "must be one of error, warn, info, debug, trace");
+                None
+            }
+        };
+        errors.into_result()?;
+
+        let records = self.records.lock();
+        let mut matching: Vec<LogRecord> = records
+            .iter()
+            .rev()
+            .filter(|r| tenant.is_none_or(|tenant| r.tenant_id.as_deref() == Some(tenant.as_str())))
+            .filter(|r| query.drone_id.as_ref().is_none_or(|id| r.drone_id.as_ref() == Some(id)))
+            .filter(|r| query.mission_id.as_ref().is_none_or(|id| r.mission_id.as_ref() == Some(id)))
+            // Less verbose levels compare lower
+            .filter(|r| level.is_none_or(|level| r.severity <= level))
+            .take(limit)
+            .cloned()
+            .collect();
+        matching.reverse();
+        Ok(matching)
+    }
+}
+
+/// Initialize logging; returns the buffer of recent records
+pub fn init_logging(config: &LoggingConfig) -> Arc<LogBuffer> {
+    use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| {
+            EnvFilter::new("info,drone_api=debug,drone_websocket=debug")
+            //EnvFilter::new("info,drone_api=debug,drone_cv=debug,drone_websocket=debug")
+        });
+
+    let buffer = Arc::new(LogBuffer::new(config.buffer_records));
+    let json = config.format == LogFormat::Json;
+    tracing_subscriber::registry()
+        .with((!json).then(|| fmt::layer().with_target(true).with_thread_ids(true)))
+        .with(json.then(|| fmt::layer().json().with_current_span(true).with_span_list(false)))
+        .with(BufferLayer(buffer.clone()))
+        .with(filter)
+        .init();
+    buffer
+}
+
+// ============================================================================
+// SUBSCRIBER LAYER
+// ============================================================================
+
+/// Fields recorded on a span
+struct SpanFields(BTreeMap<String, String>);
+
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+/// Copies every event, with its spans' fields, into a `LogBuffer`
+struct BufferLayer(Arc<LogBuffer>);
+
+impl<S> Layer<S> for BufferLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Outer spans first, so inner spans and the event itself win
+        let mut fields = BTreeMap::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                    fields.extend(span_fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+            }
+        }
+        event.record(&mut FieldVisitor(&mut fields));
+
+        let metadata = event.metadata();
+        self.0.push(LogRecord {
+            timestamp: Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: fields.remove("message").unwrap_or_default(),
+            drone_id: fields.remove("drone_id"),
+            mission_id: fields.remove("mission_id"),
+            tenant_id: fields.remove("tenant_id"),
+            fields,
+            severity: *metadata.level(),
+        });
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_records_carry_span_context() {
+        let buffer = Arc::new(LogBuffer::new(3));
+        let subscriber = tracing_subscriber::registry().with(BufferLayer(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("startup");
+            let span = tracing::info_span!("position_update", drone_id = %"REAPER-01", mission_id = tracing::field::Empty);
+            let _entered = span.enter();
+            tracing::debug!("unrecorded mission");
+            span.record("mission_id", "m-1");
+            tracing::warn!(speed = 120.5, "too fast");
+            let _command = tracing::info_span!("command", drone_id = %"REAPER-02").entered();
+            tracing::info!("sent");
+        });
+
+        let all = buffer.query(&LogQuery::default(), None).unwrap();
+        // The first record fell out of the buffer
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].message, "unrecorded mission");
+
+        let reaper = |level: Option<&str>| LogQuery {
+            drone_id: Some("REAPER-01".into()),
+            level: level.map(str::to_string),
+            ..Default::default()
+        };
+        let records = buffer.query(&reaper(None), None).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].mission_id.as_deref(), Some("m-1"));
+        assert_eq!(records[1].fields["speed"], "120.5");
+
+        let warnings = buffer.query(&reaper(Some("warn")), None).unwrap();
+        assert_eq!(warnings.iter().map(|r| r.message.as_str()).collect::<Vec<_>>(), ["too fast"]);
+        // The inner span's drone wins
+        let inner = LogQuery { drone_id: Some("REAPER-02".into()), ..Default::default() };
+        assert_eq!(buffer.query(&inner, None).unwrap()[0].mission_id.as_deref(), Some("m-1"));
+
+        assert!(buffer.query(&LogQuery { level: Some("loud".into()), ..Default::default() }, None).is_err());
+    }
+
+    #[test]
+    fn test_tenants_only_see_their_own_records() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let subscriber = tracing_subscriber::registry().with(BufferLayer(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("startup");
+            for tenant in ["acme", "globex"] {
+                let _request = tracing::info_span!("tenant_request", tenant_id = %tenant).entered();
+                let _update = tracing::info_span!("position_update", drone_id = %"REAPER-01").entered();
+                tracing::info!("moved");
+            }
+        });
+
+        let acme = TenantId::try_from("acme".to_string()).unwrap();
+        let records = buffer.query(&LogQuery::default(), Some(&acme)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tenant_id.as_deref(), Some("acme"));
+        assert_eq!(records[0].drone_id.as_deref(), Some("REAPER-01"));
+        // Single-tenant deployments see everything
+        assert_eq!(buffer.query(&LogQuery::default(), None).unwrap().len(), 3);
+    }
+}
@@ -4,27 +4,63 @@
 //! Provides REST API endpoints for drone management and coordinates
 //! all backend services including WebSocket, CV tracking, and database.
 
+mod after_action;
+mod attachments;
+mod backfill;
+mod clusters;
+mod coverage;
 mod config;
 mod error;
+mod export;
+mod fleet;
 mod handlers;
+mod handoff;
+mod history;
+mod kinematics;
+mod leadership;
+mod logs;
+mod mot;
+mod packages;
+mod pagination;
+mod presentation;
+mod push;
+mod route_render;
 mod routes;
+mod simulation;
+mod sse;
+mod stats;
 mod state;
+mod supervisor;
+mod tenants;
+mod tiles;
+mod timeline;
+mod transport;
+mod uploads;
+mod validation;
 
 use crate::config::ApiConfig;
-use crate::routes::create_router;
+use crate::logs::LoggingConfig;
+use crate::routes::{create_router, create_tenant_router};
 use crate::state::AppState;
+use crate::supervisor::{joined, RestartPolicy, SupervisorConfig, TaskId, TaskSupervisor};
+use crate::tenants::TenantRegistry;
+use crate::tiles::TileService;
 
 use std::net::SocketAddr;
+use std::sync::Arc;
+use anyhow::Context;
+use std::time::Duration;
 use tokio::signal;
-use tracing::{info, error, Level};
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+use tracing::{info, error, warn};
 
-use drone_core::{DroneId, GeoPosition, Telemetry, Event};
+use drone_core::Alert;
+use drone_db::StorageBackend;
+use drone_websocket::WebSocketHub;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initialize logging
-    init_logging();
+    let logs = logs::init_logging(&LoggingConfig::from_env());
 
     info!("🚁 Starting Drone Convoy Tracking Server v0.1.0");
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -34,44 +70,79 @@ async fn main() -> anyhow::Result<()> {
     info!("Configuration loaded");
     info!("   API Port: {}", config.api_port);
     info!("   WebSocket Port: {}", config.ws_port);
-    info!("   ScyllaDB Hosts: {:?}", config.db.hosts);
+    match config.db.backend {
+        StorageBackend::Scylla => info!("   ScyllaDB Hosts: {:?}", config.db.hosts),
+        StorageBackend::Sqlite => info!("   SQLite Database: {}", config.db.sqlite_path.display()),
+    }
+
+    // A tenants file is fatal if broken: falling back to single-tenant
+    // would serve every fleet without authentication
+    let tenants = TenantRegistry::from_env()?;
+    if !tenants.is_empty() {
+        info!("   Tenants: {}", tenants.tenants().len());
+    }
+
+    // Initialize WebSocket hub
+    let mut hub = WebSocketHub::new()
+        .with_compression(config.ws_compression.clone())
+        .with_rate_limits(config.ws_rate_limits.clone())
+        .with_socketio(config.ws_socketio.clone())
+        .with_presence(config.ws_presence.clone());
+    if !tenants.is_empty() {
+        let registry = tenants.clone();
+        hub = hub.with_tenant_resolver(move |key| registry.resolve(key));
+    }
+    let ws_hub = Arc::new(hub);
+    info!("WebSocket hub initialized");
+
+    // One tile cache serves every tenant
+    let tiles = TileService::new(config.tiles.clone())
+        .with_context(|| format!("tile cache {}", config.tiles.cache_dir.display()))?;
+    let tiles = Arc::new(tiles);
 
-    // Initialize application state
+    // Every tenant's background tasks run under one supervisor
+    let tasks = Arc::new(TaskSupervisor::new(SupervisorConfig::from_env()));
+
+    // Initialize application state, one stack per tenant
     info!("Initializing application state...");
-    let state = match AppState::new(config.clone()).await {
-        Ok(state) => {
-            info!("Application state initialized");
-            state
-        }
-        Err(e) => {
-            error!("Failed to initialize application state: {}", e);
-            // Continue without database for development
-            info!("Running in degraded mode (no database)");
-            AppState::new_without_db(config.clone()).await?
+    let app = if tenants.is_empty() {
+        let state = init_state(config.clone(), ws_hub.clone())
+            .await?
+            .with_logs(logs.clone())
+            .with_tasks(tasks.clone());
+        spawn_state_tasks(&state);
+        leadership::route_ws_commands(&ws_hub, std::slice::from_ref(&state));
+        create_router(state, tiles)
+    } else {
+        let mut states = Vec::new();
+        for tenant in tenants.tenants() {
+            info!("Initializing tenant {} ({})", tenant.id, tenant.name);
+            let state = init_state(config.for_tenant(&tenant.id), ws_hub.clone())
+                .await?
+                .with_tenant(tenant.id.clone())
+                .with_logs(logs.clone())
+                .with_tasks(tasks.clone());
+            spawn_state_tasks(&state);
+            states.push(state);
         }
+        leadership::route_ws_commands(&ws_hub, &states);
+        create_tenant_router(&config, tenants, states, tiles)
     };
-
-    // Create router
-    let app = create_router(state.clone());
     info!("Routes configured");
 
     // Start WebSocket server in background
-    let ws_state = state.clone();
+    let ws_server_hub = ws_hub.clone();
     let ws_port = config.ws_port;
-    tokio::spawn(async move {
-        info!("Starting WebSocket server on port {}...", ws_port);
-        if let Err(e) = drone_websocket::start_server(ws_state.ws_hub.clone(), ws_port).await {
-            error!("WebSocket server error: {}", e);
+    tasks.spawn(TaskId::shared("websocket server"), RestartPolicy::OnFailure, move || {
+        let hub = ws_server_hub.clone();
+        async move {
+            info!("Starting WebSocket server on port {}...", ws_port);
+            drone_websocket::start_server(hub, ws_port)
+                .await
+                .context("WebSocket server")
         }
     });
 
-    // Start simulation task (generates fake drone data for PoC)
-    let sim_state = state.clone();
-    tokio::spawn(async move {
-        info!("Starting drone simulation...");
-        run_simulation(sim_state).await;
-    });
-
     // Start API server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.api_port));
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -86,26 +157,229 @@ async fn main() -> anyhow::Result<()> {
     //     .with_graceful_shutdown(shutdown_signal())
     //     .await?;
 
+    // Close WebSocket clients before HTTP connections are drained
+    let ws_drain = Duration::from_secs(config.ws_drain_seconds);
     axum::serve(listener, app.into_make_service())
-    .with_graceful_shutdown(shutdown_signal())
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        ws_hub.shutdown(ws_drain).await;
+    })
     .await?;
 
+    tasks.shutdown().await;
+
     info!("🛑 Server shutdown complete");
     Ok(())
 }
 
-/// Initialize logging with tracing
-fn init_logging() {
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| {
-            EnvFilter::new("info,drone_api=debug,drone_websocket=debug")
-            //EnvFilter::new("info,drone_api=debug,drone_cv=debug,drone_websocket=debug")
+/// Application state over the configured database, or degraded without one
+async fn init_state(config: ApiConfig, ws_hub: Arc<WebSocketHub>) -> anyhow::Result<AppState> {
+    match AppState::new(config.clone(), ws_hub.clone()).await {
+        Ok(state) => {
+            info!("Application state initialized");
+            Ok(state)
+        }
+        Err(e) => {
+            error!("Failed to initialize application state: {}", e);
+            // Continue without database for development
+            info!("Running in degraded mode (no database)");
+            AppState::new_without_db(config, ws_hub).await
+        }
+    }
+}
+
+/// Start the event forwarding, alerting and simulation tasks of one state
+/// under its supervisor
+fn spawn_state_tasks(state: &AppState) {
+    // Hub-side hops of position update latency count towards this fleet
+    let metrics = state.tracker.metrics();
+    state
+        .ws_hub
+        .set_latency_observer(state.tenant.clone(), move |hop, elapsed| metrics.record_update_latency(hop, elapsed));
+
+    let tasks = &state.tasks;
+    let task = |name: &'static str| TaskId::of(state.tenant.as_ref(), name);
+
+    // Forward tracker events to WebSocket clients
+    let forward_state = state.clone();
+    tasks.spawn(task("event forwarder"), RestartPolicy::OnFailure, move || {
+        forward_events(forward_state.clone())
+    });
+
+    // Record tracker alerts on the mission timeline and push critical ones;
+    // the receiver is shared so a restarted recorder picks up where it was
+    if let Some(alerts) = state.tracker.take_alert_receiver() {
+        let alerts = Arc::new(tokio::sync::Mutex::new(alerts));
+        let alert_state = state.clone();
+        tasks.spawn(task("alert recorder"), RestartPolicy::OnFailure, move || {
+            record_alerts(alert_state.clone(), alerts.clone())
         });
+    }
+
+    // Hold or contend for the leader lease when running several instances;
+    // a new leader first picks up the state its predecessor persisted, and
+    // a leader shutting down hands the lease straight on
+    let reload_state = state.clone();
+    state.leadership.on_promotion(move || {
+        let state = reload_state.clone();
+        async move { state.reload_from_db().await }
+    });
+    let leadership = state.leadership.clone();
+    tasks.adopt(task("leader lease"), RestartPolicy::Always, move || leadership.spawn());
+    let leadership = state.leadership.clone();
+    tasks.on_shutdown(async move { leadership.step_down().await });
+
+    // Fire time-triggered scheduled commands (leader only)
+    let (scheduler, scheduler_tasks, scheduler_task) = (state.tracker.clone(), tasks.clone(), task("command scheduler"));
+    state.leadership.spawn_while_leader("command scheduler", move || {
+        let scheduler = scheduler.clone();
+        scheduler_tasks.spawn(scheduler_task.clone(), RestartPolicy::Always, move || {
+            joined(scheduler.spawn_scheduler())
+        })
+    });
+
+    // Feed incoming mesh messages into the tracker when P2P is enabled
+    let tracker = state.tracker.clone();
+    tasks.adopt(task("p2p listener"), RestartPolicy::Always, move || tracker.spawn_p2p_listener());
+
+    // Track mesh partitions when P2P is enabled
+    let tracker = state.tracker.clone();
+    tasks.adopt(task("partition monitor"), RestartPolicy::Always, move || tracker.spawn_partition_monitor());
+
+    // Broadcast mission route versions to drone agents when P2P is enabled
+    let tracker = state.tracker.clone();
+    tasks.adopt(task("mission sync"), RestartPolicy::Always, move || tracker.spawn_mission_sync());
+
+    // Predict terrain line-of-sight loss when an elevation model is loaded
+    let tracker = state.tracker.clone();
+    tasks.adopt(task("line of sight monitor"), RestartPolicy::Always, move || tracker.spawn_los_monitor());
+
+    // Warn about drone pairs predicted to lose separation
+    let tracker = state.tracker.clone();
+    tasks.spawn(task("proximity monitor"), RestartPolicy::Always, move || {
+        joined(tracker.spawn_proximity_monitor())
+    });
+
+    // Take silent drones offline
+    let tracker = state.tracker.clone();
+    tasks.spawn(task("status monitor"), RestartPolicy::Always, move || {
+        joined(tracker.spawn_status_monitor())
+    });
+
+    // Drop long-silent drones and publish tracker memory gauges
+    let tracker = state.tracker.clone();
+    tasks.spawn(task("eviction sweeper"), RestartPolicy::Always, move || {
+        joined(tracker.spawn_eviction_sweeper())
+    });
 
-    tracing_subscriber::registry()
-        .with(fmt::layer().with_target(true).with_thread_ids(true))
-        .with(filter)
-        .init();
+    // Broadcast and persist CV tracking results; the publisher ends once
+    // its pipeline is dropped, a restart installs a new one
+    if state.config.cv_enabled {
+        let (tracker, cv_config) = (state.tracker.clone(), state.config.cv_publisher.clone());
+        tasks.spawn(task("cv publisher"), RestartPolicy::OnFailure, move || {
+            joined(tracker.spawn_cv_publisher(cv_config.clone()))
+        });
+        // On exit, publish and persist the queued results before stopping it
+        let tracker = state.tracker.clone();
+        tasks.on_shutdown(async move { tracker.close_cv_publisher().await });
+    }
+
+    // Drop finished exports and their files once they expire
+    let exports = state.exports.clone();
+    tasks.spawn(task("export sweeper"), RestartPolicy::Always, move || joined(exports.spawn_sweeper()));
+
+    // Apply table TTLs and run periodic purge jobs
+    if let Some(retention) = state.retention.clone() {
+        let clock = state.clock.clone();
+        tasks.spawn(task("retention"), RestartPolicy::Always, move || joined(retention.spawn(clock.clone())));
+    }
+
+    // Start simulation task (generates fake drone data for PoC); only the
+    // leader simulates, a standby picks up from its own state on takeover
+    if state.config.simulation_mode {
+        let (sim_state, sim_tasks, sim_task) = (state.clone(), tasks.clone(), task("simulation"));
+        let sim_config = state.config.simulation.clone();
+        state.leadership.spawn_while_leader("simulation", move || {
+            let (sim_state, sim_config) = (sim_state.clone(), sim_config.clone());
+            sim_tasks.spawn(sim_task.clone(), RestartPolicy::OnFailure, move || {
+                let (sim_state, sim_config) = (sim_state.clone(), sim_config.clone());
+                async move {
+                    info!("Starting drone simulation...");
+                    simulation::run(sim_state, sim_config).await;
+                    Ok(())
+                }
+            })
+        });
+    }
+}
+
+/// Decorate, record and broadcast tracker events until the tracker is gone
+async fn forward_events(state: AppState) -> anyhow::Result<()> {
+    let mut tracker_events = state.tracker.subscribe();
+    loop {
+        match tracker_events.recv().await {
+            Ok(mut event) => {
+                state.decorate_event(&mut event);
+                state.clusters.record_event(&event);
+                state.coverage.record_event(&event, state.tracker.clock().now());
+                state.fleet_stats.record_event(&event);
+                state.apply_mission_event(&event);
+                state.apply_eviction_event(&event);
+                if let Some(mission) = state.get_mission() {
+                    state.timeline.record_event(&mission, &event);
+                    event.mission_id.get_or_insert(mission.id);
+                }
+                if let Some(tenant) = &state.tenant {
+                    event.tenant_id.get_or_insert_with(|| tenant.clone());
+                }
+                state.events.publish(event.clone());
+                state.ws_hub.broadcast(event).await;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                warn!("Tracker event forwarder lagged by {} events", n);
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// Record, persist and push tracker alerts until the tracker is gone
+async fn record_alerts(
+    state: AppState,
+    alerts: Arc<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<Alert>>>,
+) -> anyhow::Result<()> {
+    let mut alerts = alerts.lock().await;
+    while let Some(alert) = alerts.recv().await {
+        state.fleet_stats.record_alert(&alert);
+        state.presentation.record_alert(&alert);
+        if let Some(mission) = state.get_mission() {
+            state.timeline.record_alert(&mission, &alert);
+        }
+
+        // Persisted alerts back the historical state endpoint; shutdown
+        // waits for the write
+        if let Some(db) = state.db.clone() {
+            let alert = alert.clone();
+            state.tasks.track(async move {
+                if let Err(e) = db.alerts().create(&alert).await {
+                    warn!("Failed to persist alert: {}", e);
+                    db.health().record_error();
+                }
+            });
+        }
+
+        // Retries back off, so pushes must not hold up the alert loop
+        let position = alert
+            .drone_id
+            .as_ref()
+            .and_then(|id| state.tracker.get_drone(id))
+            .map(|tracked| tracked.drone.position);
+        let push = state.push.clone();
+        state.tasks.track(async move {
+            push.notify(&alert, position).await;
+        });
+    }
+    Ok(())
 }
 
 /// Graceful shutdown handler
@@ -136,131 +410,3 @@ async fn shutdown_signal() {
         }
     }
 }
-
-/// Run drone simulation for demo purposes
-async fn run_simulation(state: AppState) {
-    use drone_core::{
-        Drone, DroneId, DroneStatus, GeoPosition, Telemetry, Waypoint,
-        Event, EventType, EventPayload, DronePositionEvent,
-    };
-    use chrono::Utc;
-    use std::time::Duration;
-
-    // Afghanistan waypoints (same as frontend)
-    let waypoints = vec![
-    ("Base Alpha", 34.5553, 69.2075),
-    ("Checkpoint Bravo", 34.5623, 69.2145),
-    ("Outpost Charlie", 34.5693, 69.2215),
-    ("Firebase Delta", 34.5763, 69.2285),
-    ("Sector Echo", 34.5833, 69.2355),
-    ("Point Foxtrot", 34.5903, 69.2425),
-    ("Zone Golf", 34.5973, 69.2495),
-    ("Camp Hotel", 34.6043, 69.2565),
-    ("Station India", 34.6113, 69.2635),
-    ("Forward Juliet", 34.6183, 69.2705),
-    ("Base Kilo", 34.6253, 69.2775),
-    ("Terminal Lima", 34.6323, 69.2845),
-];
-
-    // Initialize 12 drones
-    let mut drones: Vec<SimDrone> = (1..=12)
-        .map(|i| SimDrone {
-            id: DroneId::new(format!("REAPER-{:02}", i)),
-            waypoint_index: 0,
-            progress: 0.0,
-            speed: 0.8 + (i as f64 * 0.02), // Slight speed variation
-            battery: 100,
-            fuel: 100,
-        })
-        .collect();
-
-    let mut interval = tokio::time::interval(Duration::from_millis(500));
-    let speed_multiplier = 0.005; // Adjust for demo speed
-
-    loop {
-        interval.tick().await;
-
-        // Check for reset
-        if state.reset_flag.load(std::sync::atomic::Ordering::SeqCst) {
-            info!("Resetting simulation to start...");
-            for drone in &mut drones {
-                drone.waypoint_index = 0;
-                drone.progress = 0.0;
-                drone.battery = 100;
-                drone.fuel = 100;
-            }
-            state.reset_flag.store(false, std::sync::atomic::Ordering::SeqCst);
-        }
-
-        for drone in &mut drones {
-            // Update progress
-            drone.progress += speed_multiplier * drone.speed;
-
-            // Check waypoint transition
-            if drone.progress >= 1.0 {
-                drone.progress = 0.0;
-                drone.waypoint_index = (drone.waypoint_index + 1) % waypoints.len();
-            }
-
-            // Interpolate position between waypoints
-            let current_wp = &waypoints[drone.waypoint_index];
-            let next_wp = &waypoints[(drone.waypoint_index + 1) % waypoints.len()];
-
-            let lat = current_wp.1 + (next_wp.1 - current_wp.1) * drone.progress;
-            let lng = current_wp.2 + (next_wp.2 - current_wp.2) * drone.progress;
-            let alt = 3000.0 + (drone.id.0.chars().last().unwrap().to_digit(10).unwrap_or(0) as f64 * 100.0);
-
-            // Calculate heading
-            let heading = calculate_bearing(current_wp.1, current_wp.2, next_wp.1, next_wp.2);
-
-            // Drain battery/fuel slowly
-            drone.battery = (drone.battery as f64 - 0.001).max(20.0) as u8;
-            drone.fuel = (drone.fuel as f64 - 0.002).max(15.0) as u8;
-
-            // Create position update
-            let position = GeoPosition::new(lat, lng, alt);
-            let telemetry = Telemetry {
-                battery_level: drone.battery,
-                fuel_level: drone.fuel,
-                system_health: 95 + (drone.id.0.len() % 5) as u8,
-                speed: 350.0 + (drone.speed * 50.0),
-                heading,
-                signal_strength: 90 + (drone.waypoint_index % 10) as u8,
-                temperature: 42.0,
-                timestamp: Utc::now(),
-            };
-
-            // Broadcast via WebSocket
-            let event = Event::drone_position_updated(
-                drone.id.clone(),
-                position,
-                telemetry,
-            );
-
-            state.ws_hub.broadcast(event).await;
-        }
-    }
-}
-
-/// Simple simulation drone state
-struct SimDrone {
-    id: DroneId,
-    waypoint_index: usize,
-    progress: f64,
-    speed: f64,
-    battery: u8,
-    fuel: u8,
-}
-
-/// Calculate bearing between two coordinates
-fn calculate_bearing(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
-    let lat1 = lat1.to_radians();
-    let lat2 = lat2.to_radians();
-    let delta_lng = (lng2 - lng1).to_radians();
-
-    let y = delta_lng.sin() * lat2.cos();
-    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lng.cos();
-
-    let bearing = y.atan2(x).to_degrees();
-    (bearing + 360.0) % 360.0
-}
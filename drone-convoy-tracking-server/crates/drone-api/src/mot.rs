@@ -0,0 +1,192 @@
+//! MOTChallenge export of CV tracking results
+//!
+//! Stored `cv_tracking` rows become MOTChallenge text lines so standard
+//! trackers and the MOT evaluation kit can run against our data. Frames are
+//! numbered from 1 in frame timestamp order. The matching ground truth is
+//! each drone's reported position at a frame's timestamp, projected through
+//! the same virtual camera the simulation renders its synthetic detections
+//! with.
+
+use drone_core::{BoundingBox, DroneId, GeoPosition, TrackingResult};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::fmt::Write;
+
+/// Furthest a position report may be from a frame to count as ground truth
+pub const GROUND_TRUTH_MAX_SKEW: Duration = Duration::milliseconds(1000);
+
+/// Default window when no `from` is given
+pub const DEFAULT_WINDOW: Duration = Duration::hours(1);
+
+/// Longest window a single export may cover
+pub const MAX_WINDOW: Duration = Duration::days(1);
+
+/// A drone's reported positions, oldest first
+pub type Track = (DroneId, Vec<(DateTime<Utc>, GeoPosition)>);
+
+/// Which side of the benchmark to export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MotKind {
+    /// `det.txt`: stored CV results
+    #[default]
+    Detections,
+    /// `gt.txt`: reported drone positions on the same frames
+    GroundTruth,
+}
+
+impl MotKind {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Detections => "det.txt",
+            Self::GroundTruth => "gt.txt",
+        }
+    }
+}
+
+/// Straight-down virtual camera with a fixed footprint
+#[derive(Debug, Clone, Copy)]
+pub struct MotCamera {
+    /// Ground point under the image center
+    pub center: GeoPosition,
+    pub meters_per_pixel: f64,
+    pub width: i32,
+    pub height: i32,
+    /// Side of the square box drawn around a drone
+    pub box_px: i32,
+}
+
+impl MotCamera {
+    /// 1920x1080 frame at 10 m/px centered on `center`
+    pub fn over(center: GeoPosition) -> Self {
+        Self {
+            center,
+            meters_per_pixel: 10.0,
+            width: 1920,
+            height: 1080,
+            box_px: 24,
+        }
+    }
+
+    /// Box around `position`; `None` if it is outside the frame
+    pub fn project(&self, position: &GeoPosition) -> Option<BoundingBox> {
+        let (east, north) = self.center.offset_to_m(position);
+        let x = self.width as f64 / 2.0 + east / self.meters_per_pixel;
+        let y = self.height as f64 / 2.0 - north / self.meters_per_pixel;
+        if !(0.0..self.width as f64).contains(&x) || !(0.0..self.height as f64).contains(&y) {
+            return None;
+        }
+        let half = self.box_px / 2;
+        Some(BoundingBox::new(x.round() as i32 - half, y.round() as i32 - half, self.box_px, self.box_px))
+    }
+}
+
+/// Distinct frame timestamps of `results`, oldest first; frame `n` is at index `n - 1`
+pub fn frames(results: &[TrackingResult]) -> Vec<DateTime<Utc>> {
+    let mut frames: Vec<_> = results.iter().map(|r| r.frame_timestamp).collect();
+    frames.sort();
+    frames.dedup();
+    frames
+}
+
+fn frame_number(frames: &[DateTime<Utc>], at: DateTime<Utc>) -> Option<usize> {
+    frames.binary_search(&at).ok().map(|i| i + 1)
+}
+
+/// `frame,id,bb_left,bb_top,bb_width,bb_height,conf,-1,-1,-1` per result
+pub fn detections(results: &[TrackingResult]) -> String {
+    let frames = frames(results);
+    let mut rows: Vec<_> = results
+        .iter()
+        .filter_map(|r| Some((frame_number(&frames, r.frame_timestamp)?, r)))
+        .collect();
+    rows.sort_by_key(|(frame, r)| (*frame, r.tracking_id));
+
+    let mut out = String::new();
+    for (frame, r) in rows {
+        let b = &r.bbox;
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{:.4},-1,-1,-1",
+            frame, r.tracking_id, b.x, b.y, b.width, b.height, r.confidence
+        );
+    }
+    out
+}
+
+/// `frame,id,bb_left,bb_top,bb_width,bb_height,1,1,1` for every drone in view
+/// on every frame
+///
+/// A drone
+/// appears on a frame if it reported within `max_skew` of the frame
+/// timestamp; its ID is its 1-based place in `tracks` by drone ID.
+pub fn ground_truth(
+    frames: &[DateTime<Utc>],
+    tracks: &[Track],
+    camera: &MotCamera,
+    max_skew: Duration,
+) -> String {
+    let mut tracks: Vec<_> = tracks.iter().collect();
+    tracks.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut out = String::new();
+    for (frame, at) in frames.iter().enumerate() {
+        for (id, (_, positions)) in tracks.iter().enumerate() {
+            // Nearest report on either side of the frame
+            let i = positions.partition_point(|(t, _)| t < at);
+            let nearest = [i.checked_sub(1), Some(i)]
+                .into_iter()
+                .flatten()
+                .filter_map(|i| positions.get(i))
+                .min_by_key(|(t, _)| (*t - *at).abs());
+            let Some((_, position)) = nearest.filter(|(t, _)| (*t - *at).abs() <= max_skew) else {
+                continue;
+            };
+            if let Some(b) = camera.project(position) {
+                let _ = writeln!(out, "{},{},{},{},{},{},1,1,1", frame + 1, id + 1, b.x, b.y, b.width, b.height);
+            }
+        }
+    }
+    out
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detections_and_ground_truth_share_frames() {
+        let camera = MotCamera::over(GeoPosition::new(34.5938, 69.2460, 0.0));
+        let t0 = Utc::now();
+        let at = |ms: i64| t0 + Duration::milliseconds(ms);
+        let result = |drone: &str, tracking_id: u32, ms: i64| {
+            let mut result = TrackingResult::new(DroneId::new(drone), tracking_id, BoundingBox::new(950, 530, 24, 24));
+            result.frame_timestamp = at(ms);
+            result.confidence = 0.9;
+            result
+        };
+        let results = [result("REAPER-02", 2, 500), result("REAPER-01", 1, 500), result("REAPER-01", 1, 0)];
+
+        assert_eq!(
+            detections(&results),
+            "1,1,950,530,24,24,0.9000,-1,-1,-1\n2,1,950,530,24,24,0.9000,-1,-1,-1\n2,2,950,530,24,24,0.9000,-1,-1,-1\n"
+        );
+
+        // 100 m east of the center is 10 px right of it
+        let east = camera.center.offset_by_m(100.0, 0.0);
+        let tracks = vec![
+            (DroneId::new("REAPER-02"), vec![(at(480), east)]),
+            (DroneId::new("REAPER-01"), vec![(at(-100), camera.center), (at(2000), camera.center)]),
+        ];
+        let gt = ground_truth(&frames(&results), &tracks, &camera, Duration::milliseconds(250));
+        // REAPER-01's reports are too far from frame 2; REAPER-02 only reported near frame 2
+        assert_eq!(gt, "1,1,948,528,24,24,1,1,1\n2,2,958,528,24,24,1,1,1\n");
+
+        assert!(camera.project(&camera.center.offset_by_m(20_000.0, 0.0)).is_none());
+    }
+}
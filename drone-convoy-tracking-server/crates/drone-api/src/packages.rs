@@ -0,0 +1,359 @@
+//! Signed mission packages
+//!
+//! Missions are distributed to stations without network access as package
+//! files. A package starts with the `DCMP` magic and a big-endian `u16`
+//! format version, followed by a MessagePack envelope holding the signer's
+//! ed25519 public key, the signature and the MessagePack-encoded mission.
+//! The signature covers the magic, the version and the payload, so neither
+//! the mission nor the declared version can be altered without detection.
+//!
+//! Packages are accepted from this station's own key and from the keys
+//! listed in `MISSION_TRUSTED_KEYS`.
+
+use drone_core::Mission;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
+
+/// Leading bytes of every package file
+pub const PACKAGE_MAGIC: &[u8; 4] = b"DCMP";
+
+/// Format version written by this build
+pub const PACKAGE_VERSION: u16 = 1;
+
+/// Oldest format version this build can read
+pub const MIN_PACKAGE_VERSION: u16 = 1;
+
+/// Content type of package downloads and uploads
+pub const PACKAGE_CONTENT_TYPE: &str = "application/vnd.drone-convoy.mission-package";
+
+/// Largest accepted package upload (1 MiB)
+pub const MAX_PACKAGE_BYTES: usize = 1024 * 1024;
+
+const HEADER_LEN: usize = PACKAGE_MAGIC.len() + 2;
+
+#[derive(Debug, Error)]
+pub enum PackageError {
+    #[error("not a mission package")]
+    NotAPackage,
+
+    #[error("package format version {found} is not supported (expected {min}..={max})")]
+    UnsupportedVersion { found: u16, min: u16, max: u16 },
+
+    #[error("malformed package: {0}")]
+    Malformed(String),
+
+    #[error("package signature is invalid")]
+    BadSignature,
+
+    #[error("package is signed by untrusted key {0}")]
+    UntrustedSigner(String),
+
+    #[error("signing key error: {0}")]
+    Key(String),
+}
+
+/// Signing key location and trusted signers
+#[derive(Debug, Clone)]
+pub struct PackageConfig {
+    /// Hex-encoded ed25519 seed, readable only by its owner; generated on
+    /// first start and kept across restarts so peers can keep trusting it
+    pub key_path: PathBuf,
+    /// Hex-encoded public keys of other stations whose packages are accepted
+    pub trusted_keys: Vec<String>,
+}
+
+impl Default for PackageConfig {
+    fn default() -> Self {
+        Self {
+            key_path: PathBuf::from("./mission-signing.key"),
+            trusted_keys: Vec::new(),
+        }
+    }
+}
+
+impl PackageConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            key_path: std::env::var("MISSION_SIGNING_KEY_PATH")
+                .map(PathBuf::from)
+                .unwrap_or(defaults.key_path),
+            trusted_keys: std::env::var("MISSION_TRUSTED_KEYS")
+                .map(|s| {
+                    s.split(',')
+                        .map(|k| k.trim().to_lowercase())
+                        .filter(|k| !k.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.trusted_keys),
+        }
+    }
+}
+
+/// Signed part of a package
+#[derive(Debug, Serialize, Deserialize)]
+struct PackageContents {
+    mission: Mission,
+    exported_at: DateTime<Utc>,
+}
+
+/// MessagePack body following the header
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    #[serde(with = "serde_bytes")]
+    public_key: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+}
+
+/// A package that passed version, signature and trust checks
+#[derive(Debug, Clone)]
+pub struct VerifiedPackage {
+    pub version: u16,
+    /// Hex-encoded public key of the signer
+    pub signer: String,
+    pub exported_at: DateTime<Utc>,
+    pub mission: Mission,
+}
+
+/// Writes and verifies mission packages with this station's key
+pub struct MissionSigner {
+    key: SigningKey,
+    trusted: Vec<VerifyingKey>,
+}
+
+impl fmt::Debug for MissionSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MissionSigner")
+            .field("public_key", &self.public_key())
+            .field("trusted", &self.trusted.len())
+            .finish()
+    }
+}
+
+impl MissionSigner {
+    /// Signer over the configured key file, generating the key if missing
+    pub fn load(config: &PackageConfig) -> Result<Self, PackageError> {
+        let key = load_or_generate_key(&config.key_path)?;
+        let trusted = config
+            .trusted_keys
+            .iter()
+            .map(|k| parse_public_key(k))
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(key, trusted))
+    }
+
+    pub fn new(key: SigningKey, trusted: Vec<VerifyingKey>) -> Self {
+        Self { key, trusted }
+    }
+
+    /// Signer with a throwaway key
+    #[cfg(test)]
+    pub fn ephemeral() -> Self {
+        Self::new(SigningKey::generate(&mut OsRng), Vec::new())
+    }
+
+    /// Hex-encoded public key of this station
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// Hex-encoded public keys of the other stations accepted as signers
+    pub fn trusted_keys(&self) -> Vec<String> {
+        self.trusted.iter().map(|k| hex::encode(k.as_bytes())).collect()
+    }
+
+    /// Encode and sign a mission
+    pub fn package(&self, mission: &Mission, exported_at: DateTime<Utc>) -> Result<Vec<u8>, PackageError> {
+        let contents = PackageContents {
+            mission: mission.clone(),
+            exported_at,
+        };
+        let payload = rmp_serde::to_vec_named(&contents).map_err(|e| PackageError::Malformed(e.to_string()))?;
+        let signature = self.key.sign(&signed_bytes(PACKAGE_VERSION, &payload));
+        let envelope = Envelope {
+            public_key: self.key.verifying_key().as_bytes().to_vec(),
+            signature: signature.to_bytes().to_vec(),
+            payload,
+        };
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + envelope.payload.len() + 128);
+        bytes.extend_from_slice(PACKAGE_MAGIC);
+        bytes.extend_from_slice(&PACKAGE_VERSION.to_be_bytes());
+        rmp_serde::encode::write_named(&mut bytes, &envelope).map_err(|e| PackageError::Malformed(e.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Check a package's version, signature and signer and decode its mission
+    pub fn open(&self, bytes: &[u8]) -> Result<VerifiedPackage, PackageError> {
+        if bytes.len() < HEADER_LEN || &bytes[..PACKAGE_MAGIC.len()] != PACKAGE_MAGIC {
+            return Err(PackageError::NotAPackage);
+        }
+        let version = u16::from_be_bytes([bytes[4], bytes[5]]);
+        if !(MIN_PACKAGE_VERSION..=PACKAGE_VERSION).contains(&version) {
+            return Err(PackageError::UnsupportedVersion {
+                found: version,
+                min: MIN_PACKAGE_VERSION,
+                max: PACKAGE_VERSION,
+            });
+        }
+
+        let envelope: Envelope =
+            rmp_serde::from_slice(&bytes[HEADER_LEN..]).map_err(|e| PackageError::Malformed(e.to_string()))?;
+        let signer = VerifyingKey::try_from(envelope.public_key.as_slice())
+            .map_err(|_| PackageError::Malformed("invalid public key".into()))?;
+        let signature =
+            Signature::from_slice(&envelope.signature).map_err(|_| PackageError::BadSignature)?;
+        signer
+            .verify(&signed_bytes(version, &envelope.payload), &signature)
+            .map_err(|_| PackageError::BadSignature)?;
+        if signer != self.key.verifying_key() && !self.trusted.contains(&signer) {
+            return Err(PackageError::UntrustedSigner(hex::encode(signer.as_bytes())));
+        }
+
+        let contents: PackageContents =
+            rmp_serde::from_slice(&envelope.payload).map_err(|e| PackageError::Malformed(e.to_string()))?;
+        Ok(VerifiedPackage {
+            version,
+            signer: hex::encode(signer.as_bytes()),
+            exported_at: contents.exported_at,
+            mission: contents.mission,
+        })
+    }
+}
+
+/// Header and payload as covered by the signature
+fn signed_bytes(version: u16, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(PACKAGE_MAGIC);
+    bytes.extend_from_slice(&version.to_be_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+fn parse_public_key(hex_key: &str) -> Result<VerifyingKey, PackageError> {
+    let bytes: [u8; 32] = hex::decode(hex_key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| PackageError::Key(format!("invalid trusted key {}", hex_key)))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| PackageError::Key(format!("invalid trusted key {}", hex_key)))
+}
+
+fn load_or_generate_key(path: &Path) -> Result<SigningKey, PackageError> {
+    let key_error = |e: std::io::Error| PackageError::Key(format!("{}: {}", path.display(), e));
+    if path.exists() {
+        let text = fs::read_to_string(path).map_err(key_error)?;
+        let seed: [u8; 32] = hex::decode(text.trim())
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| PackageError::Key(format!("{}: not a hex-encoded ed25519 seed", path.display())))?;
+        return Ok(SigningKey::from_bytes(&seed));
+    }
+
+    let key = SigningKey::generate(&mut OsRng);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(key_error)?;
+    }
+    // Created owner-only, so the seed is never readable by others
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(key_error)?;
+    file.write_all(hex::encode(key.to_bytes()).as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(key_error)?;
+    info!(
+        "Generated mission signing key {} at {}",
+        hex::encode(key.verifying_key().as_bytes()),
+        path.display()
+    );
+    Ok(key)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Waypoint;
+
+    #[test]
+    fn test_signing_key_persists_owner_only() {
+        let dir = std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4()));
+        let config = PackageConfig { key_path: dir.join("signing.key"), trusted_keys: Vec::new() };
+        let first = MissionSigner::load(&config).unwrap();
+        assert_eq!(MissionSigner::load(&config).unwrap().public_key(), first.public_key());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&config.key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // An unreadable key is an error, not a silent new identity
+        fs::write(&config.key_path, "not a key").unwrap();
+        assert!(matches!(MissionSigner::load(&config), Err(PackageError::Key(_))));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_package_roundtrip_and_rejections() {
+        let station = MissionSigner::ephemeral();
+        let mut mission = Mission::new("Offline route");
+        mission.add_waypoint(Waypoint::new("WP01", "Base", 34.5, 69.15));
+        mission.add_waypoint(Waypoint::new("WP02", "Pass", 34.6, 69.3));
+        let at = Utc::now();
+
+        let bytes = station.package(&mission, at).unwrap();
+        assert_eq!(&bytes[..4], PACKAGE_MAGIC);
+        let package = station.open(&bytes).unwrap();
+        assert_eq!(package.version, PACKAGE_VERSION);
+        assert_eq!(package.signer, station.public_key());
+        assert_eq!(package.exported_at, at);
+        assert_eq!(package.mission.id, mission.id);
+        assert_eq!(package.mission.waypoints.len(), 2);
+
+        // Any altered byte in the payload breaks the signature
+        let mut tampered = bytes.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0x01;
+        assert!(matches!(station.open(&tampered), Err(PackageError::BadSignature)));
+
+        // So does bumping the declared version, once this build reads it
+        let mut bumped = bytes.clone();
+        bumped[5] = bumped[5].wrapping_add(1);
+        assert!(matches!(
+            station.open(&bumped),
+            Err(PackageError::UnsupportedVersion { found: 2, .. })
+        ));
+        assert!(matches!(station.open(b"PK\x03\x04"), Err(PackageError::NotAPackage)));
+
+        // Another station's packages need its key to be trusted
+        let other = MissionSigner::ephemeral();
+        let foreign = other.package(&mission, at).unwrap();
+        assert!(matches!(station.open(&foreign), Err(PackageError::UntrustedSigner(_))));
+        let trusting = MissionSigner::new(
+            station.key.clone(),
+            vec![parse_public_key(&other.public_key()).unwrap()],
+        );
+        assert_eq!(trusting.open(&foreign).unwrap().signer, other.public_key());
+    }
+}
@@ -0,0 +1,460 @@
+//! Drone and alert presentation hints for the map
+//!
+//! The frontend draws each drone with an icon chosen by drone type, a color
+//! chosen by status (overridden by low battery, fuel, health or signal) and
+//! blinks drones with a recent alert. Alerts get a color, sound, priority
+//! and auto-dismiss time by severity, adjusted per alert type. The mapping
+//! rules are loaded from a JSON file so each deployment can change them
+//! without a frontend release; the hints are computed here and sent with
+//! drone responses, position and alert events and the alerts API. A drone
+//! with a fleet marking is drawn with its own icon, and its halo color is
+//! sent along so the map matches the CV overlay.
+
+use drone_core::{
+    Alert, AlertPresentation, AlertSeverity, AlertType, Drone, DroneId, DroneMarking, DronePresentation, DroneStatus, DroneType,
+    Event, EventPayload, Telemetry,
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Telemetry level a health rule looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthMetric {
+    Battery,
+    Fuel,
+    SystemHealth,
+    Signal,
+}
+
+impl HealthMetric {
+    fn level(&self, telemetry: &Telemetry) -> u8 {
+        match self {
+            Self::Battery => telemetry.battery_level,
+            Self::Fuel => telemetry.fuel_level,
+            Self::SystemHealth => telemetry.system_health,
+            Self::Signal => telemetry.signal_strength,
+        }
+    }
+}
+
+/// Color a drone when a telemetry level is below a threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthRule {
+    pub metric: HealthMetric,
+    /// Percent
+    pub below: u8,
+    pub color: String,
+}
+
+/// How alerts of one severity are presented
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertStyle {
+    pub color: String,
+    /// Sound name (`None` for silent)
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Higher priorities are shown first
+    pub priority: u8,
+    /// `None` keeps the alert until acknowledged
+    #[serde(default)]
+    pub auto_dismiss_seconds: Option<u64>,
+}
+
+impl AlertStyle {
+    fn new(color: &str, sound: Option<&str>, priority: u8, auto_dismiss_seconds: Option<u64>) -> Self {
+        Self {
+            color: color.into(),
+            sound: sound.map(Into::into),
+            priority,
+            auto_dismiss_seconds,
+        }
+    }
+}
+
+/// Changes an alert type makes to its severity's style; unset fields keep it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertStyleOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_dismiss_seconds: Option<u64>,
+}
+
+/// Mapping from drone state to presentation hints
+///
+/// Fields missing from the rules file keep their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresentationRules {
+    /// Icon per drone type (`MQ9_REAPER`, ...; custom types by name)
+    pub icons: BTreeMap<String, String>,
+    pub default_icon: String,
+    /// Color per status (`MOVING`, `RTB`, ...)
+    pub status_colors: BTreeMap<String, String>,
+    pub default_color: String,
+    /// Checked in order; the first match replaces the status color
+    pub health_rules: Vec<HealthRule>,
+    /// Alerts at or above this severity make the drone blink
+    pub blink_severity: AlertSeverity,
+    /// How long a drone blinks after its last such alert
+    pub blink_seconds: u64,
+    /// Alert style per severity (`WARNING`, `CRITICAL`, ...)
+    pub alert_styles: BTreeMap<String, AlertStyle>,
+    pub default_alert_style: AlertStyle,
+    /// Per alert type (`COLLISION_WARNING`, ...; custom types by name)
+    pub alert_types: BTreeMap<String, AlertStyleOverride>,
+}
+
+impl Default for PresentationRules {
+    fn default() -> Self {
+        let map = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        Self {
+            icons: map(&[
+                ("MQ9_REAPER", "reaper"),
+                ("MQ1_PREDATOR", "predator"),
+                ("RQ4_GLOBAL_HAWK", "global-hawk"),
+                ("MQ1_C_GRAY_EAGLE", "gray-eagle"),
+            ]),
+            default_icon: "drone".into(),
+            status_colors: map(&[
+                ("STANDBY", "#94a3b8"),
+                ("MOVING", "#22c55e"),
+                ("ENGAGED", "#f97316"),
+                ("RTB", "#3b82f6"),
+                ("OFFLINE", "#6b7280"),
+                ("UNREACHABLE", "#a855f7"),
+                ("MAINTENANCE", "#eab308"),
+                ("LOITERING", "#06b6d4"),
+            ]),
+            default_color: "#94a3b8".into(),
+            health_rules: vec![
+                HealthRule { metric: HealthMetric::SystemHealth, below: 30, color: "#dc2626".into() },
+                HealthRule { metric: HealthMetric::Battery, below: 15, color: "#dc2626".into() },
+                HealthRule { metric: HealthMetric::Fuel, below: 15, color: "#dc2626".into() },
+                HealthRule { metric: HealthMetric::Battery, below: 30, color: "#f59e0b".into() },
+                HealthRule { metric: HealthMetric::Fuel, below: 30, color: "#f59e0b".into() },
+            ],
+            blink_severity: AlertSeverity::Warning,
+            blink_seconds: 30,
+            alert_styles: [
+                ("INFO", AlertStyle::new("#3b82f6", None, 0, Some(10))),
+                ("WARNING", AlertStyle::new("#f59e0b", Some("chime"), 1, Some(60))),
+                ("CRITICAL", AlertStyle::new("#dc2626", Some("alarm"), 2, None)),
+                ("EMERGENCY", AlertStyle::new("#7f1d1d", Some("siren"), 3, None)),
+            ]
+            .into_iter()
+            .map(|(severity, style)| (severity.to_string(), style))
+            .collect(),
+            default_alert_style: AlertStyle::new("#94a3b8", None, 0, Some(10)),
+            alert_types: [
+                (
+                    "COLLISION_WARNING",
+                    AlertStyleOverride {
+                        sound: Some("siren".into()),
+                        priority: Some(3),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "SIGNAL_LOST",
+                    AlertStyleOverride {
+                        color: Some("#a855f7".into()),
+                        priority: Some(2),
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into_iter()
+            .map(|(alert_type, style)| (alert_type.to_string(), style))
+            .collect(),
+        }
+    }
+}
+
+impl PresentationRules {
+    /// Load rules from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading presentation rules {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("parsing presentation rules {}", path.display()))
+    }
+
+    /// Rules from the file named by `PRESENTATION_RULES`, or the defaults
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var("PRESENTATION_RULES") {
+            Ok(path) => Self::from_file(path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    fn icon(&self, drone_type: &DroneType) -> &str {
+        self.icons
+            .get(&type_key(drone_type))
+            .unwrap_or(&self.default_icon)
+    }
+
+    fn color(&self, status: DroneStatus, telemetry: &Telemetry) -> &str {
+        self.health_rules
+            .iter()
+            .find(|rule| rule.metric.level(telemetry) < rule.below)
+            .map(|rule| &rule.color)
+            .or_else(|| self.status_colors.get(&status.to_string()))
+            .unwrap_or(&self.default_color)
+    }
+
+    /// Hints for an alert of `severity` and `alert_type`
+    pub fn alert_presentation(&self, severity: AlertSeverity, alert_type: &AlertType) -> AlertPresentation {
+        let style = enum_key(&severity)
+            .and_then(|key| self.alert_styles.get(&key))
+            .unwrap_or(&self.default_alert_style);
+        let mut presentation = AlertPresentation {
+            color: style.color.clone(),
+            sound: style.sound.clone(),
+            priority: style.priority,
+            auto_dismiss_seconds: style.auto_dismiss_seconds,
+        };
+        let key = match alert_type {
+            AlertType::Custom(name) => Some(name.clone()),
+            other => enum_key(other),
+        };
+        if let Some(changes) = key.and_then(|key| self.alert_types.get(&key)) {
+            if let Some(color) = &changes.color {
+                presentation.color = color.clone();
+            }
+            if let Some(sound) = &changes.sound {
+                presentation.sound = Some(sound.clone());
+            }
+            if let Some(priority) = changes.priority {
+                presentation.priority = priority;
+            }
+            if let Some(seconds) = changes.auto_dismiss_seconds {
+                presentation.auto_dismiss_seconds = Some(seconds);
+            }
+        }
+        presentation
+    }
+}
+
+fn type_key(drone_type: &DroneType) -> String {
+    match drone_type {
+        DroneType::Custom(name) => name.clone(),
+        other => enum_key(other).unwrap_or_default(),
+    }
+}
+
+/// Serialized name of a unit enum variant
+fn enum_key(value: &impl Serialize) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+}
+
+fn severity_rank(severity: AlertSeverity) -> u8 {
+    match severity {
+        AlertSeverity::Info => 0,
+        AlertSeverity::Warning => 1,
+        AlertSeverity::Critical => 2,
+        AlertSeverity::Emergency => 3,
+    }
+}
+
+/// Computes presentation hints from the rules and recent alerts
+#[derive(Debug, Default)]
+pub struct PresentationService {
+    rules: PresentationRules,
+    /// Latest blinking-severity alert per drone
+    alerts: RwLock<HashMap<DroneId, DateTime<Utc>>>,
+}
+
+impl PresentationService {
+    pub fn new(rules: PresentationRules) -> Self {
+        Self {
+            rules,
+            alerts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn rules(&self) -> &PresentationRules {
+        &self.rules
+    }
+
+    /// Track an alert; acknowledged or resolved alerts stop the blinking
+    pub fn record_alert(&self, alert: &Alert) {
+        let Some(drone_id) = &alert.drone_id else {
+            return;
+        };
+        let mut alerts = self.alerts.write();
+        if alert.acknowledged || alert.resolved {
+            alerts.remove(drone_id);
+        } else if severity_rank(alert.severity) >= severity_rank(self.rules.blink_severity) {
+            let raised_at = alerts.entry(drone_id.clone()).or_insert(alert.created_at);
+            *raised_at = (*raised_at).max(alert.created_at);
+        }
+    }
+
+    fn blinking(&self, drone_id: &DroneId, now: DateTime<Utc>) -> bool {
+        let window = chrono::Duration::seconds(self.rules.blink_seconds as i64);
+        self.alerts
+            .read()
+            .get(drone_id)
+            .is_some_and(|raised_at| now - *raised_at <= window)
+    }
+
+    pub fn present(
+        &self,
+        drone_id: &DroneId,
+        drone_type: &DroneType,
+        status: DroneStatus,
+        telemetry: &Telemetry,
+        marking: Option<&DroneMarking>,
+        now: DateTime<Utc>,
+    ) -> DronePresentation {
+        let icon = marking
+            .and_then(|marking| marking.icon.as_deref())
+            .unwrap_or_else(|| self.rules.icon(drone_type));
+        DronePresentation {
+            icon: icon.to_string(),
+            color: self.rules.color(status, telemetry).to_string(),
+            blink: self.blinking(drone_id, now),
+            halo_color: marking.map(|marking| marking.halo_color.to_string()),
+        }
+    }
+
+    pub fn present_drone(&self, drone: &Drone, marking: Option<&DroneMarking>, now: DateTime<Utc>) -> DronePresentation {
+        self.present(&drone.id, &drone.drone_type, drone.status, &drone.telemetry, marking, now)
+    }
+
+    /// Attach hints to an alert
+    pub fn decorate_alert(&self, alert: &mut Alert) {
+        alert.presentation = Some(self.rules.alert_presentation(alert.severity, &alert.alert_type));
+    }
+
+    /// Attach hints to a position event
+    pub fn decorate(
+        &self,
+        event: &mut Event,
+        drone_type: &DroneType,
+        status: DroneStatus,
+        marking: Option<&DroneMarking>,
+        now: DateTime<Utc>,
+    ) {
+        if let EventPayload::DronePosition(position) = &mut event.payload {
+            position.presentation =
+                Some(self.present(&position.drone_id, drone_type, status, &position.telemetry, marking, now));
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{AlertType, GeoPosition, HaloColor};
+
+    #[test]
+    fn test_rules_and_blinking() {
+        let rules: PresentationRules = serde_json::from_str(
+            r##"{"icons": {"Scout": "quad"}, "blink_severity": "CRITICAL", "blink_seconds": 10}"##,
+        )
+        .unwrap();
+        assert_eq!(rules.default_icon, "drone");
+        let service = PresentationService::new(rules);
+        let now = Utc::now();
+
+        let mut drone = Drone::new(DroneId::new("SCOUT-01"), "Scout");
+        drone.drone_type = DroneType::Custom("Scout".into());
+        drone.status = DroneStatus::Rtb;
+        drone.telemetry.battery_level = 80;
+        drone.telemetry.fuel_level = 80;
+        drone.telemetry.system_health = 100;
+        let hints = service.present_drone(&drone, None, now);
+        assert_eq!((hints.icon.as_str(), hints.color.as_str(), hints.blink), ("quad", "#3b82f6", false));
+
+        // Low battery overrides the status color
+        drone.telemetry.battery_level = 10;
+        assert_eq!(service.present_drone(&drone, None, now).color, "#dc2626");
+
+        // Only alerts at the blink severity blink, and only for a while
+        service.record_alert(&Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "Low").for_drone(drone.id.clone()));
+        assert!(!service.present_drone(&drone, None, now).blink);
+        let alert = Alert::new(AlertSeverity::Critical, AlertType::BatteryLow, "Critical").for_drone(drone.id.clone());
+        service.record_alert(&alert);
+        assert!(service.present_drone(&drone, None, alert.created_at).blink);
+        assert!(!service.present_drone(&drone, None, alert.created_at + chrono::Duration::seconds(11)).blink);
+
+        // The file's icon map replaces the default one as a whole
+        let mut event = Event::drone_position_updated(drone.id.clone(), GeoPosition::default(), drone.telemetry.clone());
+        service.decorate(&mut event, &DroneType::Mq9Reaper, DroneStatus::Moving, None, alert.created_at);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["payload"]["data"]["presentation"]["icon"], "drone");
+        assert_eq!(json["payload"]["data"]["presentation"]["blink"], true);
+        assert!(json["payload"]["data"]["presentation"].get("halo_color").is_none());
+
+        // A fleet marking brings its own icon and the halo color
+        let marking = DroneMarking::new(HaloColor::new(0, 200, 255)).with_icon("scout-lead");
+        service.decorate(&mut event, &DroneType::Mq9Reaper, DroneStatus::Moving, Some(&marking), now);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["payload"]["data"]["presentation"]["icon"], "scout-lead");
+        assert_eq!(json["payload"]["data"]["presentation"]["halo_color"], "#00c8ff");
+        let hints = service.present_drone(&drone, Some(&DroneMarking::new(HaloColor::GREEN)), now);
+        assert_eq!((hints.icon.as_str(), hints.halo_color.as_deref()), ("quad", Some("#00ff00")));
+    }
+
+    #[test]
+    fn test_alert_presentation() {
+        let rules: PresentationRules = serde_json::from_str(
+            r##"{"alert_types": {"ALTITUDE_BAND_VIOLATION": {"sound": "tone", "auto_dismiss_seconds": 30}}}"##,
+        )
+        .unwrap();
+        let warning = rules.alert_presentation(AlertSeverity::Warning, &AlertType::BatteryLow);
+        assert_eq!((warning.sound.as_deref(), warning.priority, warning.auto_dismiss_seconds), (Some("chime"), 1, Some(60)));
+
+        // Type overrides change only the fields they set; custom types match by name
+        let custom = rules.alert_presentation(AlertSeverity::Critical, &AlertType::Custom("ALTITUDE_BAND_VIOLATION".into()));
+        assert_eq!(custom.color, "#dc2626");
+        assert_eq!((custom.sound.as_deref(), custom.priority, custom.auto_dismiss_seconds), (Some("tone"), 2, Some(30)));
+        let defaults = PresentationRules::default();
+        assert_eq!(defaults.alert_presentation(AlertSeverity::Warning, &AlertType::CollisionWarning).priority, 3);
+
+        // Alert events carry the hints
+        let service = PresentationService::new(defaults);
+        let mut alert = Alert::new(AlertSeverity::Emergency, AlertType::SystemFailure, "Engine out");
+        let json = serde_json::to_value(&alert).unwrap();
+        assert!(json.get("presentation").is_none());
+        service.decorate_alert(&mut alert);
+        let json = serde_json::to_value(Event::alert(alert)).unwrap();
+        assert_eq!(json["payload"]["data"]["alert"]["presentation"]["sound"], "siren");
+        assert!(json["payload"]["data"]["alert"]["presentation"]["auto_dismiss_seconds"].is_null());
+    }
+
+    #[test]
+    fn test_shipped_rules_match_defaults() {
+        let shipped: PresentationRules =
+            serde_json::from_str(include_str!("../../../config/presentation.json")).unwrap();
+        assert_eq!(
+            serde_json::to_value(shipped).unwrap(),
+            serde_json::to_value(PresentationRules::default()).unwrap()
+        );
+    }
+}
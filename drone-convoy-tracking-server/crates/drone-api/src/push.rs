@@ -0,0 +1,1050 @@
+//! Push notifications for critical alerts
+//!
+//! Operators away from the console register a phone (an FCM or APNs device
+//! token) and get Critical and Emergency alerts as pushes carrying the drone,
+//! its position and a deep link into the app. Each subscription has its own
+//! preferences; every push attempt is tracked so operators can see whether a
+//! page actually went out.
+//!
+//! Providers sit behind [`PushProvider`] and talk HTTP through [`HttpClient`].
+//! The bundled client speaks HTTP/1.1 (over TLS for https URLs), so the
+//! provider endpoints point at the push gateway that holds the HTTP/2
+//! connections to FCM and APNs.
+
+use drone_core::{Alert, AlertSeverity, AlertType, DroneId, GeoPosition};
+use drone_db::{DbClient, PushSubscriptionRecord};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use openssl::ssl::{SslConnector, SslMethod};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Delivery attempts kept for the status endpoint
+pub const DELIVERY_HISTORY: usize = 1000;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// FCM HTTP v1 endpoint and credentials
+#[derive(Debug, Clone)]
+pub struct FcmConfig {
+    /// `.../v1/projects/<project>/messages:send`
+    pub url: String,
+    /// OAuth2 access token sent as a bearer token
+    pub access_token: String,
+}
+
+/// APNs endpoint and credentials
+#[derive(Debug, Clone)]
+pub struct ApnsConfig {
+    /// Base URL; the device token is appended as `/3/device/<token>`
+    pub url: String,
+    /// App bundle ID (`apns-topic`)
+    pub topic: String,
+    /// Provider JWT sent as a bearer token
+    pub auth_token: String,
+}
+
+/// Notification text; `{severity}`, `{alert_type}`, `{drone}`, `{message}`,
+/// `{alert_id}`, `{latitude}` and `{longitude}` are filled in per alert
+#[derive(Debug, Clone)]
+pub struct PushTemplate {
+    pub title: String,
+    pub body: String,
+    pub deep_link: String,
+}
+
+impl Default for PushTemplate {
+    fn default() -> Self {
+        Self {
+            title: "{severity}: {drone}".into(),
+            body: "{message}".into(),
+            deep_link: "dronetracker://alerts/{alert_id}".into(),
+        }
+    }
+}
+
+/// Push notification configuration
+#[derive(Debug, Clone)]
+pub struct PushConfig {
+    pub fcm: Option<FcmConfig>,
+    pub apns: Option<ApnsConfig>,
+    pub template: PushTemplate,
+    /// Attempts per push before it is marked failed
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after each attempt
+    pub retry_delay: Duration,
+    /// Provider request timeout
+    pub timeout: Duration,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            fcm: None,
+            apns: None,
+            template: PushTemplate::default(),
+            max_attempts: 3,
+            retry_delay: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl PushConfig {
+    /// Read `PUSH_FCM_URL`/`PUSH_FCM_TOKEN`, `PUSH_APNS_URL`/`PUSH_APNS_TOPIC`/
+    /// `PUSH_APNS_TOKEN`, `PUSH_TITLE`, `PUSH_BODY`, `PUSH_DEEP_LINK` and
+    /// `PUSH_MAX_ATTEMPTS`; a provider is enabled once all its variables are set
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+
+        let fcm = match (var("PUSH_FCM_URL"), var("PUSH_FCM_TOKEN")) {
+            (Some(url), Some(access_token)) => Some(FcmConfig { url, access_token }),
+            _ => None,
+        };
+        let apns = match (var("PUSH_APNS_URL"), var("PUSH_APNS_TOPIC"), var("PUSH_APNS_TOKEN")) {
+            (Some(url), Some(topic), Some(auth_token)) => Some(ApnsConfig { url, topic, auth_token }),
+            _ => None,
+        };
+
+        Self {
+            fcm,
+            apns,
+            template: PushTemplate {
+                title: var("PUSH_TITLE").unwrap_or(defaults.template.title),
+                body: var("PUSH_BODY").unwrap_or(defaults.template.body),
+                deep_link: var("PUSH_DEEP_LINK").unwrap_or(defaults.template.deep_link),
+            },
+            max_attempts: var("PUSH_MAX_ATTEMPTS")
+                .and_then(|s| s.parse().ok())
+                .filter(|&n| n > 0)
+                .unwrap_or(defaults.max_attempts),
+            ..defaults
+        }
+    }
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+/// Push delivery errors
+#[derive(Debug, Clone, Error)]
+pub enum PushError {
+    #[error("transport error: {0}")]
+    Transport(String),
+
+    #[error("provider returned {status}: {body}")]
+    Rejected { status: u16, body: String },
+
+    #[error("no {0} provider configured")]
+    NoProvider(PushPlatform),
+
+    #[error("malformed {0} device token")]
+    InvalidToken(PushPlatform),
+}
+
+impl PushError {
+    /// Worth another attempt: network trouble, throttling or a provider outage
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            Self::Rejected { status, .. } => *status == 429 || *status >= 500,
+            Self::NoProvider(_) | Self::InvalidToken(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Sends JSON POST requests for the push providers
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    async fn post(&self, request: HttpRequest) -> Result<HttpResponse, PushError>;
+}
+
+/// HTTP/1.1 client; https requests get a verified TLS connection of their own
+pub struct HyperClient {
+    client: Client<HttpConnector, Full<Bytes>>,
+    /// `None` if the system TLS library could not be set up
+    tls: Option<SslConnector>,
+    timeout: Duration,
+}
+
+impl HyperClient {
+    pub fn new(timeout: Duration) -> Self {
+        let tls = SslConnector::builder(SslMethod::tls_client())
+            .map(|builder| builder.build())
+            .map_err(|e| warn!("TLS unavailable, https requests will fail: {}", e))
+            .ok();
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            tls,
+            timeout,
+        }
+    }
+
+    async fn send(&self, mut req: hyper::Request<Full<Bytes>>) -> Result<hyper::Response<Incoming>, PushError> {
+        let transport = |e: &dyn std::fmt::Display| PushError::Transport(e.to_string());
+        if req.uri().scheme_str() != Some("https") {
+            return self.client.request(req).await.map_err(|e| transport(&e));
+        }
+
+        let tls = self.tls.as_ref().ok_or_else(|| PushError::Transport("TLS is unavailable".into()))?;
+        let host = req.uri().host().ok_or_else(|| PushError::Transport("URL has no host".into()))?.to_string();
+        let port = req.uri().port_u16().unwrap_or(443);
+        let tcp = tokio::net::TcpStream::connect((host.as_str(), port)).await.map_err(|e| transport(&e))?;
+        let ssl = tls.configure().and_then(|c| c.into_ssl(&host)).map_err(|e| transport(&e))?;
+        let mut stream = tokio_openssl::SslStream::new(ssl, tcp).map_err(|e| transport(&e))?;
+        std::pin::Pin::new(&mut stream).connect().await.map_err(|e| transport(&e))?;
+
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| transport(&e))?;
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        // Origin-form target with a Host header, as on a pooled connection
+        let authority = req.uri().authority().map(|a| a.to_string()).unwrap_or(host);
+        let target = req.uri().path_and_query().map_or("/", |p| p.as_str()).parse().map_err(|e| transport(&e))?;
+        *req.uri_mut() = target;
+        req.headers_mut().insert(
+            hyper::header::HOST,
+            authority.parse().map_err(|e| transport(&e))?,
+        );
+        sender.send_request(req).await.map_err(|e| transport(&e))
+    }
+}
+
+#[async_trait]
+impl HttpClient for HyperClient {
+    async fn post(&self, request: HttpRequest) -> Result<HttpResponse, PushError> {
+        let transport = |e: &dyn std::fmt::Display| PushError::Transport(e.to_string());
+
+        let mut builder = hyper::Request::post(&request.url)
+            .header(hyper::header::CONTENT_TYPE, "application/json");
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        let req = builder
+            .body(Full::new(Bytes::from(request.body)))
+            .map_err(|e| transport(&e))?;
+
+        let response = tokio::time::timeout(self.timeout, self.send(req))
+            .await
+            .map_err(|_| PushError::Transport("request timed out".into()))??;
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| transport(&e))?
+            .to_bytes();
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+}
+
+// ============================================================================
+// PROVIDERS
+// ============================================================================
+
+/// Mobile push service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushPlatform {
+    Fcm,
+    Apns,
+}
+
+impl PushPlatform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fcm => "fcm",
+            Self::Apns => "apns",
+        }
+    }
+
+    /// Check a device token's shape: APNs tokens are hex, FCM registration
+    /// tokens letters, digits, `-`, `_` and `:`. Tokens end up in provider
+    /// URLs, so nothing else gets through.
+    pub fn check_device_token(&self, token: &str) -> Result<(), String> {
+        if token.is_empty() {
+            return Err("must not be empty".into());
+        }
+        let valid = match self {
+            Self::Apns => token.chars().all(|c| c.is_ascii_hexdigit()),
+            Self::Fcm => token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':')),
+        };
+        if !valid {
+            return Err(match self {
+                Self::Apns => "APNs tokens are hexadecimal".into(),
+                Self::Fcm => "FCM tokens contain only letters, digits, '-', '_' and ':'".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PushPlatform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Rendered notification
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PushPayload {
+    pub title: String,
+    pub body: String,
+    /// Alert ID, severity, type, drone, position and `deep_link`
+    pub data: BTreeMap<String, String>,
+}
+
+/// Delivers a payload to one device
+#[async_trait]
+pub trait PushProvider: Send + Sync {
+    fn platform(&self) -> PushPlatform;
+
+    /// Returns the provider's message ID when it reports one
+    async fn send(&self, device_token: &str, payload: &PushPayload) -> Result<Option<String>, PushError>;
+}
+
+fn check_status(response: HttpResponse) -> Result<HttpResponse, PushError> {
+    if (200..300).contains(&response.status) {
+        Ok(response)
+    } else {
+        Err(PushError::Rejected {
+            status: response.status,
+            body: response.body,
+        })
+    }
+}
+
+/// Firebase Cloud Messaging (HTTP v1 API)
+pub struct FcmProvider {
+    config: FcmConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl FcmProvider {
+    pub fn new(config: FcmConfig, client: Arc<dyn HttpClient>) -> Self {
+        Self { config, client }
+    }
+}
+
+#[async_trait]
+impl PushProvider for FcmProvider {
+    fn platform(&self) -> PushPlatform {
+        PushPlatform::Fcm
+    }
+
+    async fn send(&self, device_token: &str, payload: &PushPayload) -> Result<Option<String>, PushError> {
+        let body = serde_json::json!({
+            "message": {
+                "token": device_token,
+                "notification": { "title": payload.title, "body": payload.body },
+                "data": payload.data,
+                "android": { "priority": "high" },
+            }
+        });
+        let response = self
+            .client
+            .post(HttpRequest {
+                url: self.config.url.clone(),
+                headers: vec![("authorization".into(), format!("Bearer {}", self.config.access_token))],
+                body: body.to_string().into_bytes(),
+            })
+            .await?;
+        let response = check_status(response)?;
+
+        // The v1 API answers with the message resource name
+        let name = serde_json::from_str::<serde_json::Value>(&response.body)
+            .ok()
+            .and_then(|v| v.get("name")?.as_str().map(str::to_string));
+        Ok(name)
+    }
+}
+
+/// Apple Push Notification service
+pub struct ApnsProvider {
+    config: ApnsConfig,
+    client: Arc<dyn HttpClient>,
+}
+
+impl ApnsProvider {
+    pub fn new(config: ApnsConfig, client: Arc<dyn HttpClient>) -> Self {
+        Self { config, client }
+    }
+}
+
+#[async_trait]
+impl PushProvider for ApnsProvider {
+    fn platform(&self) -> PushPlatform {
+        PushPlatform::Apns
+    }
+
+    async fn send(&self, device_token: &str, payload: &PushPayload) -> Result<Option<String>, PushError> {
+        // Subscriptions stored before tokens were checked must not reach the URL
+        if PushPlatform::Apns.check_device_token(device_token).is_err() {
+            return Err(PushError::InvalidToken(PushPlatform::Apns));
+        }
+        let mut body = serde_json::json!({
+            "aps": {
+                "alert": { "title": payload.title, "body": payload.body },
+                "sound": "default",
+                "interruption-level": "time-sensitive",
+            }
+        });
+        for (key, value) in &payload.data {
+            body[key] = serde_json::Value::String(value.clone());
+        }
+
+        let response = self
+            .client
+            .post(HttpRequest {
+                url: format!("{}/3/device/{}", self.config.url.trim_end_matches('/'), device_token),
+                headers: vec![
+                    ("authorization".into(), format!("bearer {}", self.config.auth_token)),
+                    ("apns-topic".into(), self.config.topic.clone()),
+                    ("apns-push-type".into(), "alert".into()),
+                    ("apns-priority".into(), "10".into()),
+                ],
+                body: body.to_string().into_bytes(),
+            })
+            .await?;
+        let response = check_status(response)?;
+        Ok(response.header("apns-id").map(str::to_string))
+    }
+}
+
+// ============================================================================
+// SUBSCRIPTIONS
+// ============================================================================
+
+/// What a subscriber wants pushed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushPreferences {
+    /// `CRITICAL` or `EMERGENCY`
+    #[serde(default = "default_min_severity")]
+    pub min_severity: AlertSeverity,
+    /// Only alerts for these drones (empty = every drone)
+    #[serde(default)]
+    pub drone_ids: Vec<DroneId>,
+    /// Only these alert types (empty = every type)
+    #[serde(default)]
+    pub alert_types: Vec<AlertType>,
+}
+
+fn default_min_severity() -> AlertSeverity {
+    AlertSeverity::Critical
+}
+
+impl Default for PushPreferences {
+    fn default() -> Self {
+        Self {
+            min_severity: default_min_severity(),
+            drone_ids: Vec::new(),
+            alert_types: Vec::new(),
+        }
+    }
+}
+
+/// A user's device registered for pushes
+#[derive(Debug, Clone, Serialize)]
+pub struct PushSubscription {
+    pub id: Uuid,
+    pub user_id: String,
+    pub platform: PushPlatform,
+    pub device_token: String,
+    #[serde(flatten)]
+    pub preferences: PushPreferences,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PushSubscription {
+    pub fn new(
+        user_id: impl Into<String>,
+        platform: PushPlatform,
+        device_token: impl Into<String>,
+        preferences: PushPreferences,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user_id.into(),
+            platform,
+            device_token: device_token.into(),
+            preferences,
+            created_at: now,
+        }
+    }
+
+    /// Whether this subscriber wants `alert` pushed
+    pub fn wants(&self, alert: &Alert) -> bool {
+        let prefs = &self.preferences;
+        severity_rank(alert.severity) >= severity_rank(prefs.min_severity)
+            && (prefs.drone_ids.is_empty()
+                || alert.drone_id.as_ref().is_some_and(|id| prefs.drone_ids.contains(id)))
+            && (prefs.alert_types.is_empty() || prefs.alert_types.contains(&alert.alert_type))
+    }
+
+    pub fn to_record(&self) -> PushSubscriptionRecord {
+        PushSubscriptionRecord {
+            id: self.id,
+            user_id: self.user_id.clone(),
+            platform: self.platform.as_str().into(),
+            device_token: self.device_token.clone(),
+            preferences: serde_json::to_string(&self.preferences).unwrap_or_default(),
+            created_at: self.created_at,
+        }
+    }
+
+    pub fn from_record(record: &PushSubscriptionRecord) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: record.id,
+            user_id: record.user_id.clone(),
+            platform: serde_json::from_value(serde_json::Value::String(record.platform.clone()))?,
+            device_token: record.device_token.clone(),
+            preferences: serde_json::from_str(&record.preferences)?,
+            created_at: record.created_at,
+        })
+    }
+}
+
+fn severity_rank(severity: AlertSeverity) -> u8 {
+    match severity {
+        AlertSeverity::Info => 0,
+        AlertSeverity::Warning => 1,
+        AlertSeverity::Critical => 2,
+        AlertSeverity::Emergency => 3,
+    }
+}
+
+// ============================================================================
+// DELIVERY TRACKING
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+/// One alert pushed to one subscription
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryRecord {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub subscription_id: Uuid,
+    pub user_id: String,
+    pub platform: PushPlatform,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub provider_message_id: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Push counters for the metrics endpoint
+#[derive(Debug, Clone, Default)]
+pub struct PushStats {
+    pub subscriptions: usize,
+    /// Finished pushes by platform and final status
+    pub deliveries: BTreeMap<(String, String), u64>,
+    /// Attempts that were retried
+    pub retries: u64,
+}
+
+// ============================================================================
+// NOTIFIER
+// ============================================================================
+
+/// Forwards critical alerts to subscribed devices
+pub struct PushNotifier {
+    config: PushConfig,
+    db: Option<Arc<DbClient>>,
+    providers: HashMap<PushPlatform, Arc<dyn PushProvider>>,
+    subscriptions: RwLock<HashMap<Uuid, PushSubscription>>,
+    deliveries: RwLock<VecDeque<DeliveryRecord>>,
+    stats: RwLock<PushStats>,
+}
+
+impl PushNotifier {
+    /// Notifier with the providers enabled in `config`
+    pub fn new(config: PushConfig, db: Option<Arc<DbClient>>) -> Self {
+        let client: Arc<dyn HttpClient> = Arc::new(HyperClient::new(config.timeout));
+        let mut notifier = Self {
+            config: config.clone(),
+            db,
+            providers: HashMap::new(),
+            subscriptions: RwLock::new(HashMap::new()),
+            deliveries: RwLock::new(VecDeque::new()),
+            stats: RwLock::new(PushStats::default()),
+        };
+        if let Some(fcm) = config.fcm {
+            notifier = notifier.with_provider(Arc::new(FcmProvider::new(fcm, client.clone())));
+        }
+        if let Some(apns) = config.apns {
+            notifier = notifier.with_provider(Arc::new(ApnsProvider::new(apns, client)));
+        }
+        notifier
+    }
+
+    /// Add or replace the provider for its platform
+    pub fn with_provider(mut self, provider: Arc<dyn PushProvider>) -> Self {
+        self.providers.insert(provider.platform(), provider);
+        self
+    }
+
+    pub fn has_provider(&self, platform: PushPlatform) -> bool {
+        self.providers.contains_key(&platform)
+    }
+
+    /// Register a device and persist the subscription
+    pub async fn subscribe(&self, subscription: PushSubscription) -> anyhow::Result<PushSubscription> {
+        if let Some(db) = &self.db {
+            db.notifications().save_subscription(&subscription.to_record()).await?;
+        }
+        self.subscriptions.write().insert(subscription.id, subscription.clone());
+        info!(
+            "Push subscription {} added for {} ({})",
+            subscription.id, subscription.user_id, subscription.platform
+        );
+        Ok(subscription)
+    }
+
+    /// Remove a subscription; `None` if there was no such subscription
+    pub async fn unsubscribe(&self, id: &Uuid) -> anyhow::Result<Option<PushSubscription>> {
+        let Some(subscription) = self.subscriptions.write().remove(id) else {
+            return Ok(None);
+        };
+        if let Some(db) = &self.db {
+            db.notifications().delete_subscription(*id).await?;
+        }
+        info!("Push subscription {} removed", id);
+        Ok(Some(subscription))
+    }
+
+    /// Subscriptions, oldest first, optionally for one user
+    pub fn subscriptions(&self, user_id: Option<&str>) -> Vec<PushSubscription> {
+        let mut subscriptions: Vec<_> = self
+            .subscriptions
+            .read()
+            .values()
+            .filter(|s| user_id.is_none_or(|user| s.user_id == user))
+            .cloned()
+            .collect();
+        subscriptions.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        subscriptions
+    }
+
+    /// Load persisted subscriptions
+    pub async fn load(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut subscriptions = HashMap::new();
+        for record in db.notifications().subscriptions().await? {
+            match PushSubscription::from_record(&record) {
+                Ok(subscription) => {
+                    subscriptions.insert(subscription.id, subscription);
+                }
+                Err(e) => warn!("Skipping unreadable push subscription {}: {}", record.id, e),
+            }
+        }
+        info!("Loaded {} push subscriptions", subscriptions.len());
+        *self.subscriptions.write() = subscriptions;
+        Ok(())
+    }
+
+    /// Render the notification for an alert
+    pub fn payload(&self, alert: &Alert, position: Option<&GeoPosition>) -> PushPayload {
+        let drone = alert.drone_id.as_ref().map(|id| id.to_string());
+        let coordinate = |value: Option<f64>| value.map(|v| format!("{:.5}", v)).unwrap_or_default();
+        let latitude = coordinate(position.map(|p| p.latitude));
+        let longitude = coordinate(position.map(|p| p.longitude));
+        let severity = serde_json::to_value(alert.severity)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let alert_type = match &alert.alert_type {
+            AlertType::Custom(name) => name.clone(),
+            other => serde_json::to_value(other)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+        };
+
+        let render = |template: &str| {
+            template
+                .replace("{severity}", &severity)
+                .replace("{alert_type}", &alert_type)
+                .replace("{drone}", drone.as_deref().unwrap_or("Fleet"))
+                .replace("{message}", &alert.message)
+                .replace("{alert_id}", &alert.id.to_string())
+                .replace("{latitude}", &latitude)
+                .replace("{longitude}", &longitude)
+        };
+
+        let template = &self.config.template;
+        let mut data = BTreeMap::from([
+            ("alert_id".to_string(), alert.id.to_string()),
+            ("severity".to_string(), severity.clone()),
+            ("alert_type".to_string(), alert_type.clone()),
+            ("deep_link".to_string(), render(&template.deep_link)),
+        ]);
+        if let Some(drone) = &drone {
+            data.insert("drone_id".into(), drone.clone());
+        }
+        if position.is_some() {
+            data.insert("latitude".into(), latitude.clone());
+            data.insert("longitude".into(), longitude.clone());
+        }
+
+        PushPayload {
+            title: render(&template.title),
+            body: render(&template.body),
+            data,
+        }
+    }
+
+    /// Push an alert to every subscriber that wants it. Warnings and info
+    /// alerts are never pushed.
+    pub async fn notify(&self, alert: &Alert, position: Option<GeoPosition>) -> Vec<DeliveryRecord> {
+        if severity_rank(alert.severity) < severity_rank(AlertSeverity::Critical) {
+            return Vec::new();
+        }
+        let targets: Vec<PushSubscription> = self
+            .subscriptions
+            .read()
+            .values()
+            .filter(|s| s.wants(alert))
+            .cloned()
+            .collect();
+        if targets.is_empty() {
+            return Vec::new();
+        }
+
+        let payload = self.payload(alert, position.as_ref());
+        let mut records = Vec::with_capacity(targets.len());
+        for subscription in targets {
+            records.push(self.deliver(alert, &subscription, &payload).await);
+        }
+        records
+    }
+
+    async fn deliver(
+        &self,
+        alert: &Alert,
+        subscription: &PushSubscription,
+        payload: &PushPayload,
+    ) -> DeliveryRecord {
+        let now = Utc::now();
+        let mut record = DeliveryRecord {
+            id: Uuid::new_v4(),
+            alert_id: alert.id,
+            subscription_id: subscription.id,
+            user_id: subscription.user_id.clone(),
+            platform: subscription.platform,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            provider_message_id: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.record(&record);
+
+        let mut delay = self.config.retry_delay;
+        let result = loop {
+            record.attempts += 1;
+            let result = match self.providers.get(&subscription.platform) {
+                Some(provider) => provider.send(&subscription.device_token, payload).await,
+                None => Err(PushError::NoProvider(subscription.platform)),
+            };
+            match result {
+                Err(e) if e.is_retryable() && record.attempts < self.config.max_attempts => {
+                    self.stats.write().retries += 1;
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(message_id) => {
+                record.status = DeliveryStatus::Delivered;
+                record.provider_message_id = message_id;
+            }
+            Err(e) => {
+                warn!(
+                    "Push of alert {} to {} ({}) failed: {}",
+                    alert.id, subscription.user_id, subscription.platform, e
+                );
+                record.status = DeliveryStatus::Failed;
+                record.error = Some(e.to_string());
+            }
+        }
+        record.updated_at = Utc::now();
+        self.record(&record);
+
+        let status = serde_json::to_value(record.status)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        *self
+            .stats
+            .write()
+            .deliveries
+            .entry((record.platform.to_string(), status))
+            .or_default() += 1;
+        record
+    }
+
+    /// Insert or update a delivery in the bounded history
+    fn record(&self, record: &DeliveryRecord) {
+        let mut deliveries = self.deliveries.write();
+        match deliveries.iter_mut().find(|d| d.id == record.id) {
+            Some(existing) => *existing = record.clone(),
+            None => {
+                if deliveries.len() == DELIVERY_HISTORY {
+                    deliveries.pop_front();
+                }
+                deliveries.push_back(record.clone());
+            }
+        }
+    }
+
+    /// Recent deliveries, newest first, optionally for one alert or user
+    pub fn deliveries(&self, alert_id: Option<Uuid>, user_id: Option<&str>) -> Vec<DeliveryRecord> {
+        self.deliveries
+            .read()
+            .iter()
+            .rev()
+            .filter(|d| alert_id.is_none_or(|id| d.alert_id == id))
+            .filter(|d| user_id.is_none_or(|user| d.user_id == user))
+            .cloned()
+            .collect()
+    }
+
+    pub fn stats(&self) -> PushStats {
+        let mut stats = self.stats.read().clone();
+        stats.subscriptions = self.subscriptions.read().len();
+        stats
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// Answers with scripted statuses (then 200) and records requests
+    #[derive(Default)]
+    struct ScriptedClient {
+        statuses: Mutex<VecDeque<u16>>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for ScriptedClient {
+        async fn post(&self, request: HttpRequest) -> Result<HttpResponse, PushError> {
+            self.requests.lock().push(request);
+            let status = self.statuses.lock().pop_front().unwrap_or(200);
+            Ok(HttpResponse {
+                status,
+                headers: vec![("apns-id".into(), "apns-1".into())],
+                body: r#"{"name":"projects/demo/messages/1"}"#.into(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_critical_alerts_pushed_per_preferences_with_retries() {
+        let client = Arc::new(ScriptedClient::default());
+        let config = PushConfig {
+            retry_delay: Duration::from_millis(1),
+            ..PushConfig::default()
+        };
+        let fcm = FcmConfig {
+            url: "http://gateway/fcm".into(),
+            access_token: "token".into(),
+        };
+        let notifier = PushNotifier::new(config, None)
+            .with_provider(Arc::new(FcmProvider::new(fcm, client.clone())));
+        assert!(!notifier.has_provider(PushPlatform::Apns));
+
+        let now = Utc::now();
+        let everything = notifier
+            .subscribe(PushSubscription::new("ops-lead", PushPlatform::Fcm, "device-1", PushPreferences::default(), now))
+            .await
+            .unwrap();
+        let emergencies_only = PushPreferences {
+            min_severity: AlertSeverity::Emergency,
+            ..PushPreferences::default()
+        };
+        notifier
+            .subscribe(PushSubscription::new("pilot", PushPlatform::Fcm, "device-2", emergencies_only, now))
+            .await
+            .unwrap();
+        let iphone = notifier
+            .subscribe(PushSubscription::new("pilot", PushPlatform::Apns, "device-3", PushPreferences::default(), now))
+            .await
+            .unwrap();
+
+        // Warnings never go out
+        let warning = Alert::new(AlertSeverity::Warning, AlertType::FuelLow, "Fuel at 18%");
+        assert!(notifier.notify(&warning, None).await.is_empty());
+
+        // A critical alert reaches the lead after one retry; the APNs device
+        // has no provider
+        client.statuses.lock().push_back(503);
+        let alert = Alert::new(AlertSeverity::Critical, AlertType::BatteryLow, "Battery at 8%")
+            .for_drone(DroneId::new("REAPER-07"));
+        let position = GeoPosition::new(34.55531, 69.20749, 3000.0);
+        let records = notifier.notify(&alert, Some(position)).await;
+        assert_eq!(records.len(), 2);
+        let delivered = records.iter().find(|r| r.subscription_id == everything.id).unwrap();
+        assert_eq!(delivered.status, DeliveryStatus::Delivered);
+        assert_eq!(delivered.attempts, 2);
+        assert_eq!(delivered.provider_message_id.as_deref(), Some("projects/demo/messages/1"));
+        let failed = records.iter().find(|r| r.subscription_id == iphone.id).unwrap();
+        assert_eq!((failed.status, failed.attempts), (DeliveryStatus::Failed, 1));
+
+        let body: serde_json::Value = serde_json::from_slice(&client.requests.lock()[1].body).unwrap();
+        let message = &body["message"];
+        assert_eq!(message["token"], "device-1");
+        assert_eq!(message["notification"]["title"], "CRITICAL: REAPER-07");
+        assert_eq!(message["data"]["drone_id"], "REAPER-07");
+        assert_eq!(message["data"]["latitude"], "34.55531");
+        assert_eq!(message["data"]["deep_link"], format!("dronetracker://alerts/{}", alert.id));
+
+        assert_eq!(notifier.deliveries(Some(alert.id), None).len(), 2);
+        assert_eq!(notifier.deliveries(None, Some("pilot")).len(), 1);
+        let stats = notifier.stats();
+        assert_eq!(stats.retries, 1);
+        assert_eq!(stats.deliveries[&("fcm".to_string(), "delivered".to_string())], 1);
+
+        // Rejections other than throttling and outages are not retried
+        client.statuses.lock().push_back(400);
+        let records = notifier.notify(&alert, None).await;
+        let rejected = records.iter().find(|r| r.subscription_id == everything.id).unwrap();
+        assert_eq!((rejected.status, rejected.attempts), (DeliveryStatus::Failed, 1));
+
+        let record = everything.to_record();
+        let restored = PushSubscription::from_record(&record).unwrap();
+        assert_eq!(restored.platform, PushPlatform::Fcm);
+        assert_eq!(restored.preferences, everything.preferences);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_device_tokens_rejected() {
+        use crate::config::ApiConfig;
+        use crate::packages::PackageConfig;
+        use crate::state::AppState;
+        use axum::{body::Body, http::{Request, StatusCode}, routing::post, Router};
+        use drone_websocket::WebSocketHub;
+        use tower::ServiceExt;
+
+        assert!(PushPlatform::Apns.check_device_token(&"a1B2".repeat(16)).is_ok());
+        assert!(PushPlatform::Fcm.check_device_token("dQw4w9WgXcQ:APA91b-Hx_7").is_ok());
+        for token in ["", "../../3/device/x", "abcd?x=1", "abcd efgh"] {
+            assert!(PushPlatform::Apns.check_device_token(token).is_err(), "{:?}", token);
+            assert!(PushPlatform::Fcm.check_device_token(token).is_err(), "{:?}", token);
+        }
+        assert!(PushPlatform::Apns.check_device_token("dQw4w9WgXcQ").is_err());
+
+        // A stored token that slipped through never reaches the APNs URL
+        let client = Arc::new(ScriptedClient::default());
+        let apns = ApnsProvider::new(
+            ApnsConfig { url: "http://gateway/apns".into(), topic: "app".into(), auth_token: "jwt".into() },
+            client.clone(),
+        );
+        let payload = PushPayload { title: "t".into(), body: "b".into(), data: BTreeMap::new() };
+        let err = apns.send("../fcm", &payload).await.unwrap_err();
+        assert!(matches!(err, PushError::InvalidToken(PushPlatform::Apns)));
+        assert!(!err.is_retryable());
+        assert!(client.requests.lock().is_empty());
+
+        let packages = PackageConfig {
+            key_path: std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4())),
+            ..Default::default()
+        };
+        let state = AppState::new_without_db(ApiConfig { packages, ..Default::default() }, Arc::new(WebSocketHub::new()))
+            .await
+            .unwrap();
+        let app = Router::new()
+            .route("/subscriptions", post(crate::handlers::create_push_subscription))
+            .with_state(state);
+        let subscribe = |platform: &str, token: &str| {
+            let body = serde_json::json!({ "user_id": "pilot", "platform": platform, "device_token": token });
+            let request = Request::builder()
+                .method("POST")
+                .uri("/subscriptions")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+        assert_eq!(subscribe("apns", "../../3/device/x").await, StatusCode::BAD_REQUEST);
+        assert_eq!(subscribe("fcm", "token/with/slashes").await, StatusCode::BAD_REQUEST);
+        // Well-formed, but no provider is configured
+        assert_eq!(subscribe("apns", &"ab".repeat(32)).await, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
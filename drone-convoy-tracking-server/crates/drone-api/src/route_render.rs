@@ -0,0 +1,222 @@
+//! Pre-rendered mission route geometry
+//!
+//! Buffering a route into a corridor is slow on mobile clients, so the
+//! server encodes the route and its corridors as Google polylines and keeps
+//! them until the route changes. Only the latest route is cached; a
+//! waypoint edit or another mission starts over.
+
+use drone_core::{encode_polyline, route_corridor, CorridorSpec, GeoPosition, Mission, MissionId};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Most corridor widths in one request
+pub const MAX_CORRIDOR_WIDTHS: usize = 8;
+
+/// Corridors cached per route before the oldest widths are dropped
+const MAX_CACHED_CORRIDORS: usize = 32;
+
+/// Route as an encoded polyline, with corridors at the requested widths
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutePolyline {
+    pub mission_id: MissionId,
+    /// Decimal places of the encoding
+    pub precision: u32,
+    pub polyline: Arc<str>,
+    /// Waypoints in the polyline
+    pub points: usize,
+    pub corridors: Vec<Arc<CorridorPolygon>>,
+}
+
+/// Corridor around the route as an encoded polygon ring (not closed: the
+/// last vertex connects back to the first), with any areas the route
+/// encloses without covering as encoded hole rings
+#[derive(Debug, Serialize)]
+pub struct CorridorPolygon {
+    pub width_m: f64,
+    pub polygon: String,
+    pub vertices: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<String>,
+}
+
+struct CachedRoute {
+    mission_id: MissionId,
+    fingerprint: u64,
+    polyline: Arc<str>,
+    /// By width key, with the order they were computed in
+    corridors: HashMap<u64, Arc<CorridorPolygon>>,
+    order: Vec<u64>,
+}
+
+/// Encoded route and corridors of the latest route rendered. The lock is
+/// only held to look up and store encodings; buffering happens outside it.
+#[derive(Default)]
+pub struct RouteRenderCache {
+    cached: Mutex<Option<CachedRoute>>,
+}
+
+impl RouteRenderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route of `mission` with a corridor for each width (meters), from
+    /// the cache where the route is unchanged. `None` when the mission has
+    /// no waypoints to buffer or a width is not positive.
+    pub fn render(&self, mission: &Mission, widths: &[f64]) -> Option<RoutePolyline> {
+        let route: Vec<GeoPosition> = mission.waypoints.iter().map(|wp| wp.position).collect();
+        if route.is_empty() {
+            return None;
+        }
+        let fingerprint = fingerprint(&route);
+        let is_current = |c: &CachedRoute| c.mission_id == mission.id && c.fingerprint == fingerprint;
+
+        let (polyline, mut corridors) = match self.cached.lock().as_ref().filter(|c| is_current(c)) {
+            Some(entry) => (
+                Some(entry.polyline.clone()),
+                widths.iter().map(|&w| entry.corridors.get(&width_key(w)).cloned()).collect(),
+            ),
+            None => (None, vec![None; widths.len()]),
+        };
+
+        let polyline = polyline.unwrap_or_else(|| encode_polyline(&route).into());
+        let mut computed = Vec::new();
+        for (slot, &width_m) in corridors.iter_mut().zip(widths) {
+            if slot.is_none() {
+                let corridor = Arc::new(corridor_polygon(&route, width_m)?);
+                computed.push((width_key(width_m), corridor.clone()));
+                *slot = Some(corridor);
+            }
+        }
+
+        let mut cached = self.cached.lock();
+        if !cached.as_ref().is_some_and(is_current) {
+            *cached = Some(CachedRoute {
+                mission_id: mission.id.clone(),
+                fingerprint,
+                polyline: polyline.clone(),
+                corridors: HashMap::new(),
+                order: Vec::new(),
+            });
+        }
+        let entry = cached.as_mut().expect("route cached above");
+        for (key, corridor) in computed {
+            if entry.corridors.contains_key(&key) {
+                continue;
+            }
+            if entry.order.len() >= MAX_CACHED_CORRIDORS {
+                let oldest = entry.order.remove(0);
+                entry.corridors.remove(&oldest);
+            }
+            entry.corridors.insert(key, corridor);
+            entry.order.push(key);
+        }
+
+        Some(RoutePolyline {
+            mission_id: mission.id.clone(),
+            precision: 5,
+            polyline,
+            points: route.len(),
+            corridors: corridors.into_iter().flatten().collect(),
+        })
+    }
+}
+
+/// Cache key of a corridor width: the width in centimeters
+pub fn width_key(width_m: f64) -> u64 {
+    (width_m * 100.0).round() as u64
+}
+
+/// Corridor of `width_m` around a non-empty route, encoded
+fn corridor_polygon(route: &[GeoPosition], width_m: f64) -> Option<CorridorPolygon> {
+    let fence = route_corridor("corridor", route, &CorridorSpec::new(width_m))?;
+    Some(CorridorPolygon {
+        width_m,
+        polygon: encode_polyline(&fence.vertices),
+        vertices: fence.vertices.len(),
+        holes: fence.holes.iter().map(|hole| encode_polyline(hole)).collect(),
+    })
+}
+
+/// Hash of the route's coordinates
+fn fingerprint(route: &[GeoPosition]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for point in route {
+        point.latitude.to_bits().hash(&mut hasher);
+        point.longitude.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{decode_polyline, Waypoint};
+
+    #[test]
+    fn test_render_is_cached_until_the_route_changes() {
+        let mut mission = Mission::new("Route");
+        for (i, lng) in [69.20, 69.22, 69.25].into_iter().enumerate() {
+            mission.add_waypoint(Waypoint::new(format!("WP{}", i), format!("Waypoint {}", i), 34.55, lng));
+        }
+        let cache = RouteRenderCache::new();
+
+        let first = cache.render(&mission, &[200.0, 1000.0]).unwrap();
+        assert_eq!(first.points, 3);
+        assert_eq!(decode_polyline(&first.polyline).unwrap().len(), 3);
+        assert_eq!(first.corridors.len(), 2);
+        let ring = decode_polyline(&first.corridors[1].polygon).unwrap();
+        assert_eq!(ring.len(), first.corridors[1].vertices);
+
+        // Same route: the same encodings come back
+        let again = cache.render(&mission, &[1000.0]).unwrap();
+        assert!(Arc::ptr_eq(&first.polyline, &again.polyline));
+        assert!(Arc::ptr_eq(&first.corridors[1], &again.corridors[0]));
+
+        mission.waypoints[1].position.latitude += 0.01;
+        let moved = cache.render(&mission, &[1000.0]).unwrap();
+        assert!(!Arc::ptr_eq(&first.corridors[1], &moved.corridors[0]));
+        assert_ne!(first.polyline, moved.polyline);
+
+        assert!(cache.render(&Mission::new("Empty"), &[200.0]).is_none());
+    }
+
+    #[test]
+    fn test_render_encodes_corridor_holes() {
+        // A bow tie closes a loop the corridor does not cover
+        let a = GeoPosition::new(34.55, 69.20, 0.0);
+        let bow = [a, a.destination(2.0, 90.0), a.destination(1.414, 45.0), a.destination(1.414, 135.0)];
+        let mut mission = Mission::new("Bow");
+        for (i, point) in bow.iter().enumerate() {
+            mission.add_waypoint(Waypoint::new(format!("WP{}", i), format!("Waypoint {}", i), point.latitude, point.longitude));
+        }
+
+        let rendered = RouteRenderCache::new().render(&mission, &[100.0]).unwrap();
+        let corridor = &rendered.corridors[0];
+        assert_eq!(corridor.holes.len(), 1);
+        assert!(decode_polyline(&corridor.holes[0]).unwrap().len() >= 3);
+        let body = serde_json::to_value(corridor).unwrap();
+        assert_eq!(body["holes"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_too_many_widths_are_refused() {
+        use crate::handlers::RoutePolylineQuery;
+
+        let widths = (1..=MAX_CORRIDOR_WIDTHS + 1).map(|i| (i * 100).to_string()).collect::<Vec<_>>().join(",");
+        let query = RoutePolylineQuery { widths: Some(widths) };
+        assert!(query.widths().is_err());
+
+        // Widths within a centimeter of each other are one width
+        let query = RoutePolylineQuery { widths: Some("200,200.001,1000".into()) };
+        assert_eq!(query.widths().unwrap(), vec![200.0, 1000.0]);
+    }
+}
@@ -1,10 +1,17 @@
 //! API route definitions
 
+use crate::config::ApiConfig;
 use crate::handlers;
+use crate::leadership;
 use crate::state::AppState;
+use crate::stats;
+use crate::tenants::{self, TenantRegistry, TenantRouter};
+use crate::tiles::TileService;
 
 use axum::{
-    routing::{get, post, put, delete},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
 use tower_http::{
@@ -12,12 +19,70 @@ use tower_http::{
     trace::TraceLayer,
     compression::CompressionLayer,
 };
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Create the main application router
-pub fn create_router(state: AppState) -> Router {
-    // CORS configuration
-    let cors = if state.config.cors_permissive {
+pub fn create_router(state: AppState, tiles: Arc<TileService>) -> Router {
+    let cors = cors_layer(&state.config);
+    let body_limit = DefaultBodyLimit::max(state.config.max_body_bytes);
+    let server_errors = middleware::from_fn_with_state(state.clone(), stats::count_server_errors);
+    let standby = middleware::from_fn_with_state(state.clone(), leadership::reject_writes_on_standby);
+
+    api_routes()
+        .layer(standby)
+        .layer(server_errors)
+        .layer(body_limit)
+        .with_state(state)
+        .merge(tile_routes(tiles))
+        // Apply middleware
+        .layer(cors)
+        .layer(TraceLayer::new_for_http())
+        .layer(CompressionLayer::new())
+}
+
+/// Create the multi-tenant router: one full API per tenant, chosen by API key.
+/// Only `/health` and the shared map tiles answer without a key.
+pub fn create_tenant_router(
+    config: &ApiConfig,
+    tenants: TenantRegistry,
+    states: Vec<AppState>,
+    tiles: Arc<TileService>,
+) -> Router {
+    let routers: HashMap<_, _> = states
+        .into_iter()
+        .filter_map(|state| {
+            let tenant = state.tenant.clone()?;
+            let router = api_routes()
+                .layer(middleware::from_fn_with_state(state.clone(), leadership::reject_writes_on_standby))
+                .layer(middleware::from_fn_with_state(state.clone(), stats::count_server_errors))
+                .layer(DefaultBodyLimit::max(state.config.max_body_bytes))
+                .layer(CompressionLayer::new())
+                .with_state(state);
+            Some((tenant, router))
+        })
+        .collect();
+
+    Router::new()
+        .route("/health", get(handlers::health_check))
+        .merge(tile_routes(tiles))
+        .fallback(tenants::dispatch)
+        .layer(cors_layer(config))
+        .layer(TraceLayer::new_for_http())
+        .with_state(Arc::new(TenantRouter::new(tenants, routers)))
+}
+
+/// Base map tile proxy
+fn tile_routes<S: Clone + Send + Sync + 'static>(tiles: Arc<TileService>) -> Router<S> {
+    Router::new()
+        .route("/tiles/{z}/{x}/{y}", get(handlers::get_tile))
+        .route("/api/v1/tiles/stats", get(handlers::get_tile_stats))
+        .with_state(tiles)
+}
+
+fn cors_layer(config: &ApiConfig) -> CorsLayer {
+    if config.cors_permissive {
         CorsLayer::new()
             .allow_origin(Any)
             .allow_methods(Any)
@@ -28,50 +93,263 @@ pub fn create_router(state: AppState) -> Router {
             .allow_origin(["http://localhost:8080".parse().unwrap()])
             .allow_methods(Any)
             .allow_headers(Any)
-    };
+    }
+}
 
+fn api_routes() -> Router<AppState> {
     Router::new()
         // Health & Status
         .route("/health", get(handlers::health_check))
         .route("/ready", get(handlers::readiness_check))
         .route("/status", get(handlers::system_status))
+        .route("/api/v1/stats", get(handlers::get_system_stats))
+        .route("/api/v1/state/at", get(handlers::get_state_at))
+        .route("/api/v1/operators", get(handlers::list_operators))
+        .route("/api/v1/logs", get(handlers::get_logs))
         
         // Metrics (Prometheus format)
         .route("/metrics", get(handlers::metrics))
+        .route("/api/v1/metrics/latency", get(handlers::get_latency_breakdown))
         
         // Drones API
         .route("/api/v1/drones", get(handlers::list_drones))
+        .route("/api/v1/drones/clusters", get(handlers::get_drone_clusters))
+        .route("/api/v1/fleet/stats", get(handlers::get_fleet_stats))
+        .route("/api/v1/coverage/heatmap", get(handlers::get_coverage_heatmap))
+        .route("/api/v1/wind", get(handlers::get_wind_field))
         .route("/api/v1/drones/{id}", get(handlers::get_drone))
-        .route("/api/v1/drones/{id}/telemetry", get(handlers::get_drone_telemetry))
+        .route(
+            "/api/v1/drones/{id}/telemetry",
+            get(handlers::get_drone_telemetry).post(handlers::report_drone_position),
+        )
+        .route("/api/v1/drones/{id}/history", get(handlers::get_drone_history))
         .route("/api/v1/drones/{id}/position", get(handlers::get_drone_position))
+        .route("/api/v1/drones/{id}/fusion", get(handlers::get_drone_fusion))
+        .route("/api/v1/mesh/partitions", get(handlers::get_mesh_partitions))
+        .route("/api/v1/mesh/jitter", get(handlers::get_mesh_jitter))
+        .route("/api/v1/mesh/shaping", get(handlers::get_mesh_shaping))
+        .route("/api/v1/p2p/topology", get(handlers::get_p2p_topology))
+        .route("/api/v1/p2p/peers", get(handlers::get_p2p_peers))
+        .route(
+            "/api/v1/p2p/peers/{id}",
+            put(handlers::put_p2p_peer).delete(handlers::delete_p2p_peer),
+        )
+        .route("/api/v1/presentation/rules", get(handlers::get_presentation_rules))
         .route("/api/v1/drones/{id}/command", post(handlers::send_drone_command))
+        .route(
+            "/api/v1/commands/scheduled",
+            get(handlers::list_scheduled_commands).post(handlers::schedule_command),
+        )
+        .route(
+            "/api/v1/commands/scheduled/{id}",
+            delete(handlers::cancel_scheduled_command),
+        )
+        .route(
+            "/api/v1/drones/{id}/thresholds",
+            get(handlers::get_drone_thresholds)
+                .put(handlers::set_drone_thresholds)
+                .delete(handlers::clear_drone_thresholds),
+        )
+        .route("/api/v1/drones/{id}/role", put(handlers::set_drone_role))
+        .route("/api/v1/drones/{id}/altitude-band", put(handlers::set_drone_altitude_band))
+        .route("/api/v1/altitude/bands", get(handlers::get_altitude_bands))
+        .route("/api/v1/groups", get(handlers::list_groups))
+        .route(
+            "/api/v1/groups/{name}",
+            get(handlers::get_group)
+                .put(handlers::put_group)
+                .delete(handlers::delete_group),
+        )
+        .route("/api/v1/groups/{name}/command", post(handlers::send_group_command))
+        .route(
+            "/api/v1/thresholds/types/{drone_type}",
+            get(handlers::get_type_thresholds).put(handlers::set_type_thresholds),
+        )
         
         // Mission API
         .route("/api/v1/mission", get(handlers::get_mission))
         .route("/api/v1/mission/start", post(handlers::start_mission))
         .route("/api/v1/mission/pause", post(handlers::pause_mission))
         .route("/api/v1/mission/resume", post(handlers::resume_mission))
-        .route("/api/v1/mission/abort", post(handlers::abort_mission))
+        .route(
+            "/api/v1/mission/abort",
+            post(handlers::abort_mission).get(handlers::get_abort_report),
+        )
+        .route("/api/v1/mission/progress", get(handlers::get_mission_progress))
         .route("/api/v1/mission/waypoints", get(handlers::get_waypoints))
+        .route("/api/v1/mission/route/polyline", get(handlers::get_route_polyline))
+        .route("/api/v1/mission/sync", get(handlers::get_mission_sync))
+        .route("/api/v1/mission/speed-limits", put(handlers::set_speed_limits))
+        .route(
+            "/api/v1/mission/corridor",
+            get(handlers::get_mission_corridor)
+                .put(handlers::set_mission_corridor)
+                .delete(handlers::delete_mission_corridor),
+        )
+        .route(
+            "/api/v1/mission/waypoints/{id}/attachments",
+            get(handlers::list_waypoint_attachments).post(handlers::upload_waypoint_attachment)
+                // The handler enforces the attachment limit instead of the global one
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/api/v1/attachments/{id}",
+            get(handlers::download_attachment).delete(handlers::delete_attachment),
+        )
+        .route("/api/v1/mission/checkpoints", get(handlers::list_checkpoint_holds))
+        .route(
+            "/api/v1/mission/checkpoints/{wp}/ack",
+            post(handlers::acknowledge_checkpoint),
+        )
         .route("/api/v1/mission/reset", post(handlers::reset_simulation))
+        .route("/api/v1/missions/{id}/timeline", get(handlers::get_mission_timeline))
+        .route("/api/v1/missions/{id}/data-quality", get(handlers::get_mission_data_quality))
+        .route("/api/v1/missions/{id}/after-action", get(handlers::get_after_action_report))
+        .route("/api/v1/telemetry/breakers", get(handlers::get_write_breakers))
+        .route("/api/v1/drones/{id}/dead-letters", get(handlers::get_dead_letters))
+        .route("/api/v1/missions/{id}/package", get(handlers::export_mission_package))
+        .route(
+            "/api/v1/missions/packages",
+            post(handlers::import_mission_package)
+                // The handler enforces the package limit instead of the global one
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route("/api/v1/missions/packages/key", get(handlers::get_package_keys))
+        .route("/api/v1/missions/drafts", post(handlers::create_mission_draft))
+        .route("/api/v1/missions/{id}/waypoints/batch", post(handlers::append_waypoint_batch))
+        .route(
+            "/api/v1/missions/{id}/upload",
+            get(handlers::get_mission_upload).delete(handlers::discard_mission_upload),
+        )
+        .route("/api/v1/missions/{id}/finalize", post(handlers::finalize_mission_upload))
+
+        // Zones of interest
+        .route("/api/v1/zones", get(handlers::list_zones).post(handlers::create_zone))
+        .route(
+            "/api/v1/zones/{id}",
+            get(handlers::get_zone).delete(handlers::delete_zone),
+        )
+        .route("/api/v1/zones/{id}/stats", get(handlers::get_zone_stats))
+
+        // Data retention
+        .route("/api/v1/retention", get(handlers::get_retention))
+        .route("/api/v1/retention/purge", post(handlers::run_retention_purge))
+
+        // Simulation clock
+        .route(
+            "/api/v1/simulation/clock",
+            get(handlers::get_simulation_clock).put(handlers::update_simulation_clock),
+        )
         
         // CV Tracking API
-        .route("/api/v1/tracking", get(handlers::get_tracking_results))
+        .route(
+            "/api/v1/tracking",
+            get(handlers::get_tracking_results).post(handlers::submit_tracking_results),
+        )
         .route("/api/v1/tracking/stats", get(handlers::get_tracking_stats))
+        .route("/api/v1/tracking/drift", get(handlers::get_cv_drift))
+        .route(
+            "/api/v1/tracking/drift/correction",
+            post(handlers::apply_cv_drift_correction).delete(handlers::clear_cv_drift_correction),
+        )
+        .route(
+            "/api/v1/cv/config",
+            get(handlers::get_cv_config)
+                .put(handlers::put_cv_config)
+                .patch(handlers::patch_cv_config),
+        )
         
         // Alerts API
         .route("/api/v1/alerts", get(handlers::list_alerts))
         .route("/api/v1/alerts/{id}/acknowledge", post(handlers::acknowledge_alert))
+        .route(
+            "/api/v1/alerts/suppressions",
+            get(handlers::list_suppressions).post(handlers::create_suppression),
+        )
+        .route(
+            "/api/v1/alerts/suppressions/{id}",
+            delete(handlers::remove_suppression),
+        )
+        .route(
+            "/api/v1/alerts/rules",
+            get(handlers::list_alert_rules).post(handlers::create_alert_rule),
+        )
+        .route(
+            "/api/v1/alerts/rules/{id}",
+            get(handlers::get_alert_rule)
+                .put(handlers::update_alert_rule)
+                .delete(handlers::delete_alert_rule),
+        )
+
+        // Custom events
+        .route("/api/v1/events/schemas", get(handlers::list_event_schemas))
+        .route(
+            "/api/v1/events/schemas/{event_type}",
+            get(handlers::get_event_schema)
+                .put(handlers::put_event_schema)
+                .delete(handlers::delete_event_schema),
+        )
+        .route("/api/v1/events/custom", post(handlers::publish_custom_event))
+        .route(
+            "/api/v1/events/custom/{event_type}",
+            get(handlers::get_custom_event_history),
+        )
+        
+        // Push notifications
+        .route(
+            "/api/v1/notifications/subscriptions",
+            get(handlers::list_push_subscriptions).post(handlers::create_push_subscription),
+        )
+        .route(
+            "/api/v1/notifications/subscriptions/{id}",
+            delete(handlers::delete_push_subscription),
+        )
+        .route("/api/v1/notifications/deliveries", get(handlers::list_push_deliveries))
+
+        // Ground control station handoff
+        .route("/api/v1/drones/{id}/handoff", post(handlers::handoff_drone))
+        .route("/api/v1/handoff/accept", post(handlers::accept_handoff))
+        .route("/api/v1/handoffs", get(handlers::list_handoffs))
+        // Command transports
+        .route("/api/v1/transports", get(handlers::list_transports))
+        .route(
+            "/api/v1/drones/{id}/transport",
+            get(handlers::get_drone_transport)
+                .put(handlers::set_drone_transport)
+                .delete(handlers::clear_drone_transport),
+        )
+        // Telemetry sources
+        .route("/api/v1/telemetry-sources", get(handlers::list_telemetry_sources))
+        .route(
+            "/api/v1/drones/{id}/telemetry-source",
+            get(handlers::get_drone_telemetry_source)
+                .put(handlers::set_drone_telemetry_source)
+                .delete(handlers::clear_drone_telemetry_source),
+        )
+        // Fleet markings
+        .route("/api/v1/fleet/markings", get(handlers::list_fleet_markings))
+        .route(
+            "/api/v1/drones/{id}/marking",
+            get(handlers::get_drone_marking)
+                .put(handlers::set_drone_marking)
+                .delete(handlers::clear_drone_marking),
+        )
+        
+        // Export API
+        .route("/api/v1/export", post(handlers::create_export))
+        .route("/api/v1/export/mot", get(handlers::download_mot_export))
+        .route("/api/v1/export/{id}", get(handlers::get_export))
+        .route("/api/v1/export/{id}/download", get(handlers::download_export))
+
+        // Historical telemetry import
+        .route("/api/v1/import", get(handlers::list_imports).post(handlers::create_import))
+        .route("/api/v1/import/{id}", get(handlers::get_import))
         
         // WebSocket info
         .route("/api/v1/ws/info", get(handlers::websocket_info))
+        .route("/api/v1/events", get(handlers::list_events))
+        .route("/api/v1/events/stream", get(handlers::stream_events))
         
         // State snapshot (for frontend initialization)
         .route("/api/v1/state", get(handlers::get_full_state))
-        
-        // Apply middleware
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
-        .layer(CompressionLayer::new())
-        .with_state(state)
 }
\ No newline at end of file
@@ -0,0 +1,760 @@
+//! Demo drone simulation
+//!
+//! Simulated drones fly the default mission route and report through the
+//! tracker like real ones. Every run draws its noise (GPS jitter, signal
+//! fluctuation) from a seeded RNG. With `SIM_SEED` set the run is fully
+//! deterministic: simulated time comes from a virtual clock advanced by
+//! exactly one tick per iteration, so a seed always reproduces the same
+//! event sequence. `SIM_RECORD` writes the run's events as normalized JSON
+//! lines that can be compared against a golden recording.
+//! `SIM_SYNTHETIC_CV` adds detections from a virtual camera over the route,
+//! the counterpart of the MOTChallenge ground-truth export.
+//! Drones fly the route under turn-rate and acceleration limits set per
+//! drone type (see `kinematics`), overridable with `SIM_KINEMATICS`.
+
+use crate::kinematics::{heading_delta, KinematicProfile, KinematicState, METERS_PER_DEGREE};
+use crate::mot::MotCamera;
+use crate::state::AppState;
+use drone_core::{DroneId, DroneType, Event, GeoPosition, Telemetry, TelemetrySource, TrackingResult};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Simulated cruise ground speed before per-drone variation (km/h)
+const DEMO_CRUISE_KMH: f64 = 36.0;
+
+/// Amplitude of simulated GPS noise (m)
+const GPS_JITTER_M: f64 = 2.0;
+
+/// Amplitude of synthetic detection box noise (px)
+const DETECTION_JITTER_PX: f64 = 2.0;
+
+/// Longest integration step of the flight model (s)
+const SUBSTEP_S: f64 = 0.25;
+
+/// A waypoint counts as reached within this distance (m)
+const CAPTURE_RADIUS_M: f64 = 15.0;
+
+/// Earliest a fly-by turn starts before its waypoint (m), well inside the
+/// tracker's arrival threshold
+const MAX_TURN_LEAD_M: f64 = 80.0;
+
+/// Loiter circle radius (m), inside the tracker's arrival threshold
+const LOITER_RADIUS_M: f64 = 65.0;
+
+/// Afghanistan waypoints (same as frontend)
+const WAYPOINTS: [(&str, f64, f64); 12] = [
+    ("Base Alpha", 34.5553, 69.2075),
+    ("Checkpoint Bravo", 34.5623, 69.2145),
+    ("Outpost Charlie", 34.5693, 69.2215),
+    ("Firebase Delta", 34.5763, 69.2285),
+    ("Sector Echo", 34.5833, 69.2355),
+    ("Point Foxtrot", 34.5903, 69.2425),
+    ("Zone Golf", 34.5973, 69.2495),
+    ("Camp Hotel", 34.6043, 69.2565),
+    ("Station India", 34.6113, 69.2635),
+    ("Forward Juliet", 34.6183, 69.2705),
+    ("Base Kilo", 34.6253, 69.2775),
+    ("Terminal Lima", 34.6323, 69.2845),
+];
+
+/// Start of simulated time in deterministic runs
+pub fn simulation_epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Virtual camera whose frame covers the whole route
+pub fn route_camera() -> MotCamera {
+    let lats = WAYPOINTS.iter().map(|(_, lat, _)| *lat);
+    let lngs = WAYPOINTS.iter().map(|(_, _, lng)| *lng);
+    let (min_lat, max_lat) = lats.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let (min_lng, max_lng) = lngs.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    MotCamera::over(GeoPosition::new((min_lat + max_lat) / 2.0, (min_lng + max_lng) / 2.0, 0.0))
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Simulation configuration
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// Fixed seed for a deterministic run (`None` = random seed, wall-clock time)
+    pub seed: Option<u64>,
+    /// Interval between simulation steps
+    pub tick: Duration,
+    /// Write the run's normalized events here as JSON lines
+    pub record_path: Option<PathBuf>,
+    /// Feed detections from `route_camera` into the CV pipeline
+    pub synthetic_cv: bool,
+    /// Flight limits replacing the built-in ones for these drone types
+    pub kinematics: HashMap<DroneType, KinematicProfile>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            tick: Duration::from_millis(500),
+            record_path: None,
+            synthetic_cv: false,
+            kinematics: HashMap::new(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Load from `SIM_SEED`, `SIM_TICK_MS`, `SIM_RECORD`, `SIM_SYNTHETIC_CV`
+    /// and `SIM_KINEMATICS` (JSON object of profiles keyed by drone type)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            seed: std::env::var("SIM_SEED").ok().and_then(|s| s.parse().ok()),
+            tick: std::env::var("SIM_TICK_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.tick),
+            record_path: std::env::var("SIM_RECORD").ok().map(PathBuf::from),
+            synthetic_cv: std::env::var("SIM_SYNTHETIC_CV").is_ok_and(|s| s == "true" || s == "1"),
+            kinematics: std::env::var("SIM_KINEMATICS")
+                .ok()
+                .and_then(|s| match parse_kinematics(&s) {
+                    Ok(profiles) => Some(profiles),
+                    Err(e) => {
+                        warn!("Ignoring invalid SIM_KINEMATICS: {}", e);
+                        None
+                    }
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Parse `SIM_KINEMATICS`, refusing any profile `advance` cannot fly
+fn parse_kinematics(value: &str) -> Result<HashMap<DroneType, KinematicProfile>, String> {
+    let profiles: HashMap<DroneType, KinematicProfile> = serde_json::from_str(value).map_err(|e| e.to_string())?;
+    for (drone_type, profile) in &profiles {
+        profile.validate().map_err(|e| format!("{:?}: {}", drone_type, e))?;
+    }
+    Ok(profiles)
+}
+
+// ============================================================================
+// SIMULATION
+// ============================================================================
+
+/// SplitMix64; small, fast and identical on every platform
+struct SimRng(u64);
+
+impl SimRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[-1, 1)`
+    fn signed_unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// Simple simulation drone state
+struct SimDrone {
+    id: DroneId,
+    /// Waypoint being flown to
+    target: usize,
+    kinematics: KinematicState,
+    /// Cruise speed multiplier
+    speed: f64,
+    battery: u8,
+    fuel: u8,
+    /// Holding at the current waypoint until this simulated time
+    loiter_until: Option<DateTime<Utc>>,
+    /// Position on the loiter circle (radians)
+    loiter_angle: f64,
+    /// 1.0 to circle clockwise, -1.0 anticlockwise
+    loiter_direction: f64,
+    /// Flying back to base after a mission abort
+    returning: bool,
+    /// Sequence number of the last position report
+    sequence: u64,
+}
+
+/// Simulated convoy, advanced one step at a time
+pub struct Simulation {
+    drones: Vec<SimDrone>,
+    rng: SimRng,
+    last_tick: DateTime<Utc>,
+    /// Camera for synthetic detections
+    camera: Option<MotCamera>,
+    /// Per-type flight limit overrides
+    profiles: HashMap<DroneType, KinematicProfile>,
+}
+
+impl SimDrone {
+    /// Parked at the first waypoint, nose towards the second
+    fn at_base(&mut self) {
+        let (_, lat, lng) = WAYPOINTS[0];
+        let (_, next_lat, next_lng) = WAYPOINTS[1];
+        self.target = 1;
+        self.kinematics = KinematicState {
+            lat,
+            lng,
+            heading: calculate_bearing(lat, lng, next_lat, next_lng),
+            speed_ms: 0.0,
+        };
+    }
+
+    /// Waypoint after the current target, `None` once home on a return
+    fn next_target(&self) -> Option<usize> {
+        match (self.returning, self.target) {
+            (true, 0) => None,
+            (true, target) => Some(target - 1),
+            (false, target) => Some((target + 1) % WAYPOINTS.len()),
+        }
+    }
+}
+
+impl Simulation {
+    /// `drone_count` drones (REAPER-01 onwards) at the first waypoint at `now`
+    pub fn new(seed: u64, drone_count: usize, now: DateTime<Utc>) -> Self {
+        let drones = (1..=drone_count)
+            .map(|i| {
+                let mut drone = SimDrone {
+                    id: DroneId::new(format!("REAPER-{:02}", i)),
+                    target: 1,
+                    kinematics: KinematicState {
+                        lat: 0.0,
+                        lng: 0.0,
+                        heading: 0.0,
+                        speed_ms: 0.0,
+                    },
+                    speed: 0.8 + (i as f64 * 0.02), // Slight speed variation
+                    battery: 100,
+                    fuel: 100,
+                    loiter_until: None,
+                    loiter_angle: 0.0,
+                    loiter_direction: 1.0,
+                    returning: false,
+                    sequence: 0,
+                };
+                drone.at_base();
+                drone
+            })
+            .collect();
+
+        Self {
+            drones,
+            rng: SimRng(seed),
+            last_tick: now,
+            camera: None,
+            profiles: HashMap::new(),
+        }
+    }
+
+    /// Fly these drone types with the given limits instead of the built-in
+    /// ones; invalid profiles are skipped
+    pub fn with_kinematics(mut self, profiles: HashMap<DroneType, KinematicProfile>) -> Self {
+        self.profiles = profiles
+            .into_iter()
+            .filter(|(drone_type, profile)| match profile.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Ignoring kinematic profile for {:?}: {}", drone_type, e);
+                    false
+                }
+            })
+            .collect();
+        self
+    }
+
+    /// Also report what `camera` would detect after every step
+    pub fn with_synthetic_cv(mut self, camera: MotCamera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Move every drone by the simulated time since the last step
+    pub async fn step(&mut self, state: &AppState) {
+        // Movement follows simulated time, so scaling or pausing the clock
+        // speeds up or freezes the demo without changing reported speeds
+        let now = state.clock.now();
+        let dt_seconds = (now - self.last_tick).num_milliseconds() as f64 / 1000.0;
+        if dt_seconds <= 0.0 {
+            // Clock paused
+            return;
+        }
+        self.last_tick = now;
+
+        // Check for reset
+        if state.reset_flag.load(std::sync::atomic::Ordering::SeqCst) {
+            info!("Resetting simulation to start...");
+            for drone in &mut self.drones {
+                drone.at_base();
+                drone.battery = 100;
+                drone.fuel = 100;
+                drone.loiter_until = None;
+                drone.returning = false;
+            }
+            state.reset_flag.store(false, std::sync::atomic::Ordering::SeqCst);
+        }
+
+        // Hold times come from the mission, which lists the same waypoints in order
+        let loiter_times: Vec<Option<u32>> = state
+            .get_mission()
+            .map(|m| m.waypoints.iter().map(|wp| wp.loiter_time_seconds).collect())
+            .unwrap_or_default();
+
+        // Drones recalled by a mission abort
+        let recalled: Vec<DroneId> = state
+            .tracker
+            .abort_report()
+            .filter(|r| state.get_mission().is_some_and(|m| m.id == r.mission_id))
+            .map(|r| r.drones.into_iter().map(|d| d.drone_id).collect())
+            .unwrap_or_default();
+
+        let camera = self.camera;
+        let rng = &mut self.rng;
+        for (index, drone) in self.drones.iter_mut().enumerate() {
+            if !drone.returning && recalled.contains(&drone.id) {
+                // Acknowledge the recall and turn back along the route
+                drone.returning = true;
+                drone.loiter_until = None;
+                drone.target = drone.target.saturating_sub(1);
+                state.tracker.acknowledge_abort(&drone.id, None);
+            }
+
+            if drone.loiter_until.is_some_and(|until| now >= until) {
+                drone.loiter_until = None;
+            }
+
+            let drone_type = state
+                .tracker
+                .inspect_drone(&drone.id, |t| t.drone.drone_type.clone())
+                .unwrap_or_default();
+            let profile = self
+                .profiles
+                .get(&drone_type)
+                .copied()
+                .unwrap_or_else(|| KinematicProfile::for_type(&drone_type));
+            let cruise_ms = DEMO_CRUISE_KMH * drone.speed / 3.6;
+
+            // Integrate in short steps so turns stay smooth when the clock
+            // is scaled up; progress pauses while loitering
+            let mut elapsed = 0.0;
+            while elapsed < dt_seconds && drone.loiter_until.is_none() {
+                let dt = (dt_seconds - elapsed).min(SUBSTEP_S);
+                elapsed += dt;
+
+                let (_, lat, lng) = WAYPOINTS[drone.target];
+                let next = drone.next_target();
+                let kin = &mut drone.kinematics;
+                let Some(next) = next else {
+                    // Home: brake to a stop over the base
+                    let distance = kin.distance_to(lat, lng);
+                    if distance < 1.0 {
+                        kin.speed_ms = 0.0;
+                        break;
+                    }
+                    let speed = cruise_ms.min((2.0 * profile.max_decel_ms2 * distance).sqrt());
+                    kin.advance(&profile, kin.bearing_to(lat, lng), speed, dt);
+                    continue;
+                };
+
+                kin.advance(&profile, kin.bearing_to(lat, lng), cruise_ms, dt);
+
+                // Start the banked turn onto the next leg early enough for the
+                // arc to join it; pass the waypoint if the turn is too tight
+                let (_, next_lat, next_lng) = WAYPOINTS[next];
+                let distance = kin.distance_to(lat, lng);
+                let bearing = kin.bearing_to(lat, lng);
+                let turn = heading_delta(bearing, calculate_bearing(lat, lng, next_lat, next_lng));
+                let lead = profile.turn_lead_m(kin.speed_ms, turn, MAX_TURN_LEAD_M);
+                let passed = distance < MAX_TURN_LEAD_M && heading_delta(kin.heading, bearing).abs() > 90.0;
+                if distance > lead.max(CAPTURE_RADIUS_M) && !passed {
+                    continue;
+                }
+
+                // Waypoint transition
+                let reached = drone.target;
+                drone.target = next;
+                if let Some(seconds) = loiter_times
+                    .get(reached)
+                    .copied()
+                    .flatten()
+                    .filter(|s| *s > 0 && !drone.returning)
+                {
+                    // Circle in the direction the drone is already turning
+                    let kin = &drone.kinematics;
+                    let (_, center_lat, center_lng) = WAYPOINTS[reached];
+                    let from_center = calculate_bearing(center_lat, center_lng, kin.lat, kin.lng);
+                    drone.loiter_direction = if heading_delta(from_center, kin.heading) >= 0.0 { 1.0 } else { -1.0 };
+                    drone.loiter_angle = from_center.to_radians();
+                    drone.loiter_until = Some(now + chrono::Duration::seconds(seconds as i64));
+                } else if elapsed < dt_seconds {
+                    // Report the arrival position so the tracker registers the
+                    // waypoint even when a large clock step flies past it
+                    let kin = drone.kinematics;
+                    report_position(state, rng, drone, kin.lat, kin.lng, kin.heading, kin.speed_ms * 3.6, now).await;
+                }
+            }
+
+            if drone.loiter_until.is_some() {
+                // Circle the waypoint just reached, heading along the tangent,
+                // no faster than the drone can turn
+                let (_, center_lat, center_lng) = WAYPOINTS[(drone.target + WAYPOINTS.len() - 1) % WAYPOINTS.len()];
+                let kin = &mut drone.kinematics;
+                let rate = (cruise_ms / LOITER_RADIUS_M).min(profile.turn_rate_at(cruise_ms).to_radians());
+                drone.loiter_angle += drone.loiter_direction * rate * dt_seconds;
+                let radius_deg = LOITER_RADIUS_M / METERS_PER_DEGREE;
+                kin.lat = center_lat + radius_deg * drone.loiter_angle.cos();
+                kin.lng = center_lng + radius_deg * drone.loiter_angle.sin() / center_lat.to_radians().cos();
+                kin.heading = (drone.loiter_angle.to_degrees() + drone.loiter_direction * 90.0).rem_euclid(360.0);
+                kin.speed_ms = rate * LOITER_RADIUS_M;
+            }
+            let KinematicState { lat, lng, heading, speed_ms } = drone.kinematics;
+
+            // Drain battery/fuel slowly
+            drone.battery = (drone.battery as f64 - 0.001).max(20.0) as u8;
+            drone.fuel = (drone.fuel as f64 - 0.002).max(15.0) as u8;
+
+            let reported = report_position(state, rng, drone, lat, lng, heading, speed_ms * 3.6, now).await;
+            if let (Some(camera), Some(position)) = (&camera, reported) {
+                detect(state, rng, camera, &drone.id, index as u32 + 1, &position, now);
+            }
+        }
+    }
+}
+
+/// Run the demo simulation until the process exits
+pub async fn run(state: AppState, config: SimulationConfig) {
+    let seed = config.seed.unwrap_or_else(|| uuid::Uuid::new_v4().as_u64_pair().0);
+    match config.seed {
+        Some(_) => info!("Deterministic simulation, seed {}, tick {:?}", seed, config.tick),
+        None => info!("Simulation seed {} (set SIM_SEED={} to replay the noise)", seed, seed),
+    }
+
+    let mut recorder = config.record_path.as_ref().and_then(|path| {
+        match std::fs::File::create(path) {
+            Ok(file) => {
+                info!("Recording simulation events to {}", path.display());
+                Some((std::io::BufWriter::new(file), EventRecording::default(), state.tracker.subscribe()))
+            }
+            Err(e) => {
+                warn!("Cannot record simulation to {}: {}", path.display(), e);
+                None
+            }
+        }
+    });
+
+    let mut simulation = Simulation::new(seed, 12, state.clock.now()).with_kinematics(config.kinematics.clone());
+    if config.synthetic_cv {
+        info!("Synthetic CV detections enabled");
+        simulation = simulation.with_synthetic_cv(route_camera());
+    }
+    let mut interval = tokio::time::interval(config.tick);
+    loop {
+        interval.tick().await;
+
+        // A seeded run's clock is virtual and only moves here
+        if config.seed.is_some() {
+            state.clock.step(config.tick.mul_f64(state.clock.scale()));
+        }
+        simulation.step(&state).await;
+
+        if let Some((writer, recording, events)) = &mut recorder {
+            let mut written = Ok(());
+            while let Ok(event) = events.try_recv() {
+                let line = recording.record(&event);
+                written = written.and_then(|_| writeln!(writer, "{}", line));
+            }
+            if let Err(e) = written.and_then(|_| writer.flush()) {
+                warn!("Stopped recording simulation events: {}", e);
+                recorder = None;
+            }
+        }
+    }
+}
+
+/// Feed a simulated position to the tracker (alerts, waypoints,
+/// persistence); its events are forwarded to WebSocket clients.
+/// Returns the reported position, `None` for handed-off drones and drones
+/// whose telemetry comes from elsewhere.
+#[allow(clippy::too_many_arguments)]
+async fn report_position(
+    state: &AppState,
+    rng: &mut SimRng,
+    drone: &mut SimDrone,
+    lat: f64,
+    lng: f64,
+    heading: f64,
+    speed_kmh: f64,
+    at: DateTime<Utc>,
+) -> Option<GeoPosition> {
+    // Handed-off drones report to the station that now controls them
+    if state.tracker.is_remote(&drone.id) {
+        return None;
+    }
+    // Real drones flying alongside the simulated ones
+    if state
+        .tracker
+        .telemetry_source(&drone.id)
+        .is_some_and(|source| source != TelemetrySource::Simulated)
+    {
+        return None;
+    }
+
+    let alt = 3000.0 + (drone.id.0.chars().last().unwrap().to_digit(10).unwrap_or(0) as f64 * 100.0);
+    let jitter_deg = GPS_JITTER_M / 111_320.0;
+    let lat = lat + rng.signed_unit() * jitter_deg;
+    let lng = lng + rng.signed_unit() * jitter_deg / lat.to_radians().cos();
+    let position = GeoPosition::new(lat, lng, alt);
+    drone.sequence += 1;
+    let telemetry = Telemetry {
+        battery_level: drone.battery,
+        fuel_level: drone.fuel,
+        system_health: 95 + (drone.id.0.len() % 5) as u8,
+        speed: speed_kmh,
+        heading,
+        signal_strength: 88 + rng.below(10) as u8,
+        temperature: 42.0,
+        timestamp: at,
+        sequence: Some(drone.sequence),
+        gimbal: None,
+    };
+
+    if let Err(e) = state.tracker
+        .update_drone_position_from(&drone.id, TelemetrySource::Simulated, position, telemetry)
+        .await
+    {
+        error!("Tracker update failed for {}: {}", drone.id, e);
+    }
+    Some(position)
+}
+
+/// Submit the box `camera` sees around a drone's reported position, with
+/// some pixel noise, as a CV result
+fn detect(
+    state: &AppState,
+    rng: &mut SimRng,
+    camera: &MotCamera,
+    drone_id: &DroneId,
+    tracking_id: u32,
+    position: &GeoPosition,
+    at: DateTime<Utc>,
+) {
+    let (Some(pipeline), Some(mut bbox)) = (state.tracker.cv_pipeline(), camera.project(position)) else {
+        return;
+    };
+    bbox.x += (rng.signed_unit() * DETECTION_JITTER_PX).round() as i32;
+    bbox.y += (rng.signed_unit() * DETECTION_JITTER_PX).round() as i32;
+    let mut result = TrackingResult::new(drone_id.clone(), tracking_id, bbox);
+    result.confidence = 0.75 + 0.25 * (rng.below(1000) as f64 / 1000.0);
+    result.frame_timestamp = at;
+    pipeline.submit(result);
+}
+
+/// Calculate bearing between two coordinates
+fn calculate_bearing(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    let lat1 = lat1.to_radians();
+    let lat2 = lat2.to_radians();
+    let delta_lng = (lng2 - lng1).to_radians();
+
+    let y = delta_lng.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lng.cos();
+
+    let bearing = y.atan2(x).to_degrees();
+    (bearing + 360.0) % 360.0
+}
+
+// ============================================================================
+// RECORDING
+// ============================================================================
+
+/// Event stream with run-specific values normalized away
+///
+/// Event IDs, envelope timestamps and `created_at` fields come from the
+/// wall clock or fresh UUIDs, so they are dropped; other UUIDs are replaced
+/// by `uuid-N` in order of first appearance. Two runs with the same seed
+/// produce identical recordings.
+#[derive(Debug, Default)]
+pub struct EventRecording {
+    /// Events as compact JSON lines
+    events: Vec<String>,
+    uuids: HashMap<String, usize>,
+}
+
+impl EventRecording {
+    /// Normalize and append an event, returning its recorded form
+    pub fn record(&mut self, event: &Event) -> &str {
+        let mut value = serde_json::to_value(event).unwrap_or(Value::Null);
+        if let Value::Object(envelope) = &mut value {
+            envelope.remove("id");
+            envelope.remove("timestamp");
+        }
+        self.normalize(&mut value);
+        self.events.push(value.to_string());
+        self.events.last().expect("just pushed")
+    }
+
+    fn normalize(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("created_at");
+                map.values_mut().for_each(|v| self.normalize(v));
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.normalize(v)),
+            Value::String(s) if uuid::Uuid::parse_str(s).is_ok() => {
+                let next = self.uuids.len();
+                let n = *self.uuids.entry(s.clone()).or_insert(next);
+                *s = format!("uuid-{}", n);
+            }
+            _ => {}
+        }
+    }
+
+    #[allow(dead_code)] // golden recordings
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+
+    /// One event per line
+    #[allow(dead_code)] // golden recordings
+    pub fn to_jsonl(&self) -> String {
+        self.events.iter().map(|e| format!("{}\n", e)).collect()
+    }
+
+    /// Parse a recording written by `to_jsonl` or `SIM_RECORD`
+    #[allow(dead_code)] // golden recordings
+    pub fn from_jsonl(jsonl: &str) -> Vec<String> {
+        jsonl.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect()
+    }
+
+    /// First event that differs from `expected`, as a readable message
+    #[allow(dead_code)] // golden recordings
+    pub fn first_difference(&self, expected: &[String]) -> Option<String> {
+        if let Some((i, (got, want))) = self.events.iter().zip(expected).enumerate().find(|(_, (a, b))| a != b) {
+            return Some(format!("event {} differs:\n  expected {}\n  got      {}", i, want, got));
+        }
+        (self.events.len() != expected.len())
+            .then(|| format!("expected {} events, got {}", expected.len(), self.events.len()))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+    use crate::packages::PackageConfig;
+    use drone_websocket::WebSocketHub;
+    use std::sync::Arc;
+
+    const GOLDEN_SEED_42: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/simulation_seed_42.jsonl");
+
+    async fn sim_state(simulation: SimulationConfig) -> AppState {
+        let packages = PackageConfig {
+            key_path: std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4())),
+            ..Default::default()
+        };
+        let config = ApiConfig { simulation, packages, ..Default::default() };
+        AppState::new_without_db(config, Arc::new(WebSocketHub::new()))
+            .await
+            .unwrap()
+    }
+
+    /// Three drones, eight ticks on the virtual clock
+    async fn record_run(seed: u64) -> EventRecording {
+        let simulation = SimulationConfig {
+            seed: Some(seed),
+            ..Default::default()
+        };
+        let tick = simulation.tick;
+        let state = sim_state(simulation).await;
+        let mut events = state.tracker.subscribe();
+
+        let mut sim = Simulation::new(seed, 3, state.clock.now());
+        let mut recording = EventRecording::default();
+        for _ in 0..8 {
+            state.clock.step(tick);
+            sim.step(&state).await;
+            while let Ok(event) = events.try_recv() {
+                recording.record(&event);
+            }
+        }
+        recording
+    }
+
+    #[tokio::test]
+    async fn test_seeded_runs_replay_golden_recording() {
+        let run = record_run(42).await;
+        assert!(!run.events().is_empty());
+        assert_eq!(run.first_difference(record_run(42).await.events()), None);
+        assert!(run.first_difference(record_run(7).await.events()).is_some());
+
+        // UPDATE_GOLDEN=1 rewrites the recording after an intended change
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            std::fs::write(GOLDEN_SEED_42, run.to_jsonl()).unwrap();
+        }
+        let golden = EventRecording::from_jsonl(&std::fs::read_to_string(GOLDEN_SEED_42).unwrap());
+        if let Some(diff) = run.first_difference(&golden) {
+            panic!("{} does not match this run: {}", GOLDEN_SEED_42, diff);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_simulated_reports_run_through_the_tracker() {
+        use drone_core::{AlertType, EventType, ThresholdOverrides};
+
+        let simulation = SimulationConfig { seed: Some(42), ..Default::default() };
+        let tick = simulation.tick;
+        let state = sim_state(simulation).await;
+        let mut events = state.tracker.subscribe();
+        let mut alerts = state.tracker.take_alert_receiver().unwrap();
+        // Only REAPER-02 counts a nearly full battery as low
+        let reaper_02 = DroneId::new("REAPER-02");
+        let overrides = ThresholdOverrides { battery_warning: Some(100), ..Default::default() };
+        state.tracker.set_drone_thresholds(&reaper_02, overrides).await.unwrap();
+
+        let mut sim = Simulation::new(42, 3, state.clock.now());
+        state.clock.step(tick);
+        sim.step(&state).await;
+
+        for id in ["REAPER-01", "REAPER-02", "REAPER-03"] {
+            let tracked = state.tracker.get_drone(&DroneId::new(id)).unwrap();
+            assert_eq!(tracked.drone.telemetry.sequence, Some(1), "{}", id);
+        }
+        let mut updated = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if event.event_type == EventType::DronePositionUpdated {
+                updated.push(event);
+            }
+        }
+        assert_eq!(updated.len(), 3);
+
+        let mut battery_low = Vec::new();
+        while let Ok(alert) = alerts.try_recv() {
+            if alert.alert_type == AlertType::BatteryLow {
+                battery_low.extend(alert.drone_id);
+            }
+        }
+        assert_eq!(battery_low, vec![reaper_02]);
+    }
+}
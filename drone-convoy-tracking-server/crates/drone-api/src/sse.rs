@@ -0,0 +1,217 @@
+//! Server-Sent Events bridge for the event bus
+//!
+//! Clients get the same drone, mission and event type filters as WebSocket
+//! subscribers. Each SSE message carries the event UUID as its `id`, so a
+//! reconnecting client's `Last-Event-ID` replays everything it missed that
+//! is still in the bus history. Clients without an ID can resume from a
+//! time with `since`.
+
+use crate::error::ApiError;
+
+use axum::response::sse::Event as SseEvent;
+use chrono::{DateTime, Utc};
+use drone_core::{DroneId, Event, EventFilter, EventType, MissionId};
+use drone_tracker::EventBus;
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// Interval between keep-alive comments
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Query parameters for the event stream (lists are comma-separated)
+#[derive(Debug, Default, Deserialize)]
+pub struct EventStreamQuery {
+    pub drone_ids: Option<String>,
+    pub mission_ids: Option<String>,
+    pub event_types: Option<String>,
+    /// Fallback for clients that cannot set the `Last-Event-ID` header
+    pub last_event_id: Option<String>,
+    /// Replay retained events from this time when there is no last event ID
+    /// (or it has left the history)
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl EventStreamQuery {
+    /// Parse the list parameters into an event filter
+    pub fn filter(&self) -> Result<EventFilter, ApiError> {
+        let mission_ids = split(&self.mission_ids)
+            .map(|ids| {
+                ids.map(|id| {
+                    Uuid::parse_str(id).map(MissionId).map_err(|_| {
+                        ApiError::validation("mission_ids", format!("invalid mission id: {}", id))
+                    })
+                })
+                .collect::<Result<_, _>>()
+            })
+            .transpose()?;
+
+        let event_types = split(&self.event_types)
+            .map(|types| {
+                types
+                    .map(|t| {
+                        serde_json::from_value::<EventType>(t.to_uppercase().into()).map_err(|_| {
+                            ApiError::validation("event_types", format!("unknown event type: {}", t))
+                        })
+                    })
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?;
+
+        Ok(EventFilter {
+            drone_ids: split(&self.drone_ids).map(|ids| ids.map(DroneId::new).collect()),
+            mission_ids,
+            event_types,
+            // Each tenant has its own event bus
+            tenant_id: None,
+        })
+    }
+}
+
+fn split(value: &Option<String>) -> Option<impl Iterator<Item = &str>> {
+    value
+        .as_deref()
+        .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()))
+}
+
+/// Events to send a reconnecting client before going live
+#[derive(Debug, Default)]
+struct Replay {
+    /// Missed events that pass the filter, oldest first
+    events: Vec<Event>,
+    /// IDs of every replayed history entry, to drop duplicates from the live feed
+    seen: HashSet<Uuid>,
+    /// The last event ID was not found in history, or events since the
+    /// resume time have been evicted
+    gap: bool,
+}
+
+fn replay(bus: &EventBus, filter: &EventFilter, last_event_id: Option<Uuid>, since: Option<DateTime<Utc>>) -> Replay {
+    let after_id = last_event_id.map(|id| bus.events_after(id));
+    let (missed, gap) = match (after_id, since) {
+        (Some(Some(missed)), _) => (missed, false),
+        (_, Some(since)) => {
+            let missed = match &filter.event_types {
+                Some(types) => bus.events_of_type(types, since, DateTime::<Utc>::MAX_UTC),
+                None => bus.events_between(since, DateTime::<Utc>::MAX_UTC),
+            };
+            let evicted = bus.evicted_count() > 0 && bus.oldest_retained().is_none_or(|oldest| since < oldest);
+            (missed, evicted)
+        }
+        (Some(None), None) => return Replay { gap: true, ..Default::default() },
+        (None, None) => return Replay::default(),
+    };
+
+    Replay {
+        seen: missed.iter().map(|e| e.id).collect(),
+        events: missed.into_iter().filter(|e| filter.matches(e)).collect(),
+        gap,
+    }
+}
+
+/// SSE message for an event, named after its event type
+fn to_sse(event: &Event) -> Result<SseEvent, axum::Error> {
+    let name = serde_json::to_value(event.event_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    SseEvent::default()
+        .id(event.id.to_string())
+        .event(name)
+        .json_data(event)
+}
+
+/// Filtered event stream, resuming after `last_event_id` or from `since`
+/// when given
+pub fn event_stream(
+    bus: &EventBus,
+    filter: EventFilter,
+    last_event_id: Option<Uuid>,
+    since: Option<DateTime<Utc>>,
+) -> impl Stream<Item = Result<SseEvent, axum::Error>> {
+    // Subscribe before reading history so nothing published in between is lost
+    let receiver = bus.subscribe();
+    let replay = replay(bus, &filter, last_event_id, since);
+
+    let notice = replay.gap.then(|| {
+        Ok(SseEvent::default().comment("last event id not in history; streaming live events"))
+    });
+    let backlog = replay.events.iter().map(to_sse).collect::<Vec<_>>();
+
+    let live = stream::unfold(
+        (receiver, filter, replay.seen),
+        |(mut receiver, filter, mut seen)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if seen.remove(&event.id) || !filter.matches(&event) {
+                            continue;
+                        }
+                        return Some((to_sse(&event), (receiver, filter, seen)));
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        let comment = SseEvent::default().comment(format!("lagged: {} events dropped", n));
+                        return Some((Ok(comment), (receiver, filter, seen)));
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+
+    stream::iter(notice.into_iter().chain(backlog)).chain(live)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::DroneStatus;
+
+    #[test]
+    fn test_replay_after_last_event_id() {
+        let bus = EventBus::new(16);
+        let events: Vec<_> = ["REAPER-01", "REAPER-02", "REAPER-01"]
+            .iter()
+            .map(|id| Event::drone_status_changed(DroneId::new(*id), DroneStatus::Standby, DroneStatus::Moving))
+            .collect();
+        for event in &events {
+            bus.publish(event.clone());
+        }
+
+        let query = EventStreamQuery {
+            drone_ids: Some("REAPER-01, ".into()),
+            event_types: Some("drone_status_changed".into()),
+            ..Default::default()
+        };
+        let filter = query.filter().unwrap();
+
+        let resumed = replay(&bus, &filter, Some(events[0].id), None);
+        assert!(!resumed.gap);
+        assert_eq!(resumed.seen.len(), 2);
+        assert_eq!(resumed.events.len(), 1);
+        assert_eq!(resumed.events[0].id, events[2].id);
+
+        assert!(replay(&bus, &filter, Some(Uuid::new_v4()), None).gap);
+        assert!(replay(&bus, &filter, None, None).events.is_empty());
+
+        // An unknown ID falls back to the resume time
+        let since = replay(&bus, &filter, Some(Uuid::new_v4()), Some(events[1].timestamp));
+        assert!(!since.gap);
+        assert_eq!(since.events.len(), 1);
+        assert_eq!(since.events[0].id, events[2].id);
+
+        let bad = EventStreamQuery {
+            event_types: Some("NOT_AN_EVENT".into()),
+            ..Default::default()
+        };
+        assert!(matches!(bad.filter(), Err(ApiError::Validation(_))));
+    }
+}
@@ -1,16 +1,43 @@
 //! Application state management
 
+use crate::attachments::AttachmentService;
+use crate::backfill::ImportManager;
+use crate::clusters::ClusterIndex;
+use crate::coverage::CoverageHeatmap;
 use crate::config::ApiConfig;
-use drone_core::{Drone, DroneId, Mission, GeoPosition, Waypoint, WaypointType};
+use crate::export::ExportManager;
+use crate::fleet::FleetStatsService;
+use crate::handoff::HandoffClient;
+use crate::leadership::Leadership;
+use crate::logs::LogBuffer;
+use crate::packages::MissionSigner;
+use crate::presentation::PresentationService;
+use crate::push::PushNotifier;
+use crate::route_render::RouteRenderCache;
+use crate::simulation::simulation_epoch;
+use crate::supervisor::{SupervisorConfig, TaskSupervisor};
+use crate::timeline::TimelineRecorder;
+use crate::transport::{HttpSidecarTransport, TransportConfig};
+use crate::uploads::MissionUploads;
+use drone_core::{
+    Drone, DroneId, Event, EventPayload, EventType, Mission, MissionBuilder, MissionId, MissionStatus, SimulationClock,
+    SubsystemHealth, TenantId, Waypoint, WaypointType,
+};
 //use drone_cv::CvEngine;
-use drone_db::DbClient;
+use drone_db::{DbClient, RetentionManager};
+use drone_tracker::{AltitudeConfig, ArrivalConfig, CheckpointConfig, DriftConfig, DroneTracker, EventBus, LosConfig, MavlinkTransport, MissionSyncConfig, ProximityConfig, TrackerConfig, WindConfig, WriteBreakerConfig};
 use drone_websocket::WebSocketHub;
 
+use chrono::Utc;
 use dashmap::DashMap;
 use parking_lot::RwLock;
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+use std::sync::{Arc, atomic::AtomicBool};
+use std::time::Duration;
 use tracing::{info, warn};
 
+/// How far back a new leader looks for the drones' last reports
+const RELOAD_LOOKBACK: Duration = Duration::from_secs(3600);
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
@@ -18,7 +45,7 @@ pub struct AppState {
     pub config: ApiConfig,
     /// Database client (optional - may not be available)
     pub db: Option<Arc<DbClient>>,
-    /// WebSocket hub for real-time updates
+    /// WebSocket hub for real-time updates (shared by all tenants)
     pub ws_hub: Arc<WebSocketHub>,
     /// CV engine for tracking
     //pub cv_engine: Option<Arc<RwLock<CvEngine>>>,
@@ -28,11 +55,55 @@ pub struct AppState {
     pub active_mission: Arc<RwLock<Option<Mission>>>,
     /// Simulation reset flag
     pub reset_flag: Arc<AtomicBool>,
+    /// Telemetry export jobs
+    pub exports: Arc<ExportManager>,
+    /// Historical telemetry import jobs
+    pub imports: Arc<ImportManager>,
+    /// Tracking coordinator (alerts, waypoint progress, persistence)
+    pub tracker: Arc<DroneTracker>,
+    /// Per-mission event timeline
+    pub timeline: Arc<TimelineRecorder>,
+    /// Geohash clusters of drone positions for the map view
+    pub clusters: Arc<ClusterIndex>,
+    /// Time-decayed coverage grid of recent drone positions
+    pub coverage: Arc<CoverageHeatmap>,
+    /// Fleet-wide aggregates maintained from tracker events
+    pub fleet_stats: Arc<FleetStatsService>,
+    /// Mission-tagged events with replay history for SSE clients
+    pub events: EventBus,
+    /// Simulated time shared by the demo simulation and the tracker
+    pub clock: Arc<SimulationClock>,
+    /// Table TTLs and purge jobs (only with a database)
+    pub retention: Option<Arc<RetentionManager>>,
+    /// Drone icon, color and blink hints for the map
+    pub presentation: Arc<PresentationService>,
+    /// Critical alert pushes to subscribed devices
+    pub push: Arc<PushNotifier>,
+    /// Drone handoff to and from peer ground control stations
+    pub handoff: Arc<HandoffClient>,
+    /// Leader/standby role when running more than one instance
+    pub leadership: Arc<Leadership>,
+    /// Photos, documents and notes attached to waypoints
+    pub attachments: Arc<AttachmentService>,
+    /// Signs exported mission packages and verifies imported ones
+    pub packages: Arc<MissionSigner>,
+    /// Missions being uploaded in waypoint batches
+    pub uploads: Arc<MissionUploads>,
+    /// Encoded route and corridor polygons of the active mission
+    pub route_render: Arc<RouteRenderCache>,
+    /// Tenant this state belongs to in a multi-tenant deployment
+    pub tenant: Option<TenantId>,
+    /// API start time and 5xx responses
+    pub health: Arc<SubsystemHealth>,
+    /// Recent log records (shared by all tenants)
+    pub logs: Arc<LogBuffer>,
+    /// Background tasks and their health (shared by all tenants)
+    pub tasks: Arc<TaskSupervisor>,
 }
 
 impl AppState {
     /// Create new application state with all components
-    pub async fn new(config: ApiConfig) -> anyhow::Result<Self> {
+    pub async fn new(config: ApiConfig, ws_hub: Arc<WebSocketHub>) -> anyhow::Result<Self> {
         // Initialize database
         let db = match DbClient::new(config.db.clone()).await {
             Ok(client) => {
@@ -61,10 +132,6 @@ impl AppState {
         //     None
         // };
 
-        // Initialize WebSocket hub
-        let ws_hub = Arc::new(WebSocketHub::new());
-        info!("WebSocket hub initialized");
-
         // Initialize drone cache with 12 REAPER drones
         let drones = Arc::new(DashMap::new());
         for i in 1..=12 {
@@ -76,8 +143,37 @@ impl AppState {
 
         // Create default mission
         let mission = create_default_mission();
+        let clock = Arc::new(create_clock(&config));
+        let tracker = create_tracker(db.clone(), clock.clone(), &drones, &mission, &config.transport, &config.cv_drift, &config.los, &config.proximity, &config.altitude, &config.arrival, &config.wind, &config.mission_sync, &config.checkpoint, &config.write_breaker, config.tenant.as_ref())
+            .await?;
+        let timeline = Arc::new(TimelineRecorder::new());
+        timeline.record_created(&mission);
         let active_mission = Arc::new(RwLock::new(Some(mission)));
         let reset_flag = Arc::new(AtomicBool::new(false));
+        let exports = Arc::new(ExportManager::new(
+            config.export_dir.clone(),
+            Duration::from_secs(config.export_ttl_seconds),
+        ));
+        let imports = Arc::new(ImportManager::new(config.import_dir.clone()));
+        let clusters = create_cluster_index(&drones);
+        let coverage = Arc::new(CoverageHeatmap::new(config.coverage.clone()));
+        let fleet_stats = create_fleet_stats(&drones);
+        let retention = db
+            .clone()
+            .map(|db| Arc::new(RetentionManager::new(db, config.retention.clone())));
+        let events = EventBus::with_retention(1024, config.event_retention.clone());
+        let presentation = Arc::new(PresentationService::new(config.presentation.clone()));
+        let push = Arc::new(PushNotifier::new(config.push.clone(), db.clone()));
+        let handoff = Arc::new(HandoffClient::new(config.handoff.clone()));
+        let leadership = Arc::new(Leadership::new(config.leadership.clone(), db.clone()));
+        let packages = Arc::new(create_mission_signer(&config)?);
+        if let Err(e) = push.load().await {
+            warn!("Failed to load push subscriptions: {}", e);
+        }
+        let attachments = Arc::new(AttachmentService::new(config.attachments.clone(), db.clone()));
+        if let Err(e) = attachments.load().await {
+            warn!("Failed to load waypoint attachments: {}", e);
+        }
 
         Ok(Self {
             config,
@@ -87,19 +183,39 @@ impl AppState {
             drones,
             active_mission,
             reset_flag,
+            exports,
+            imports,
+            tracker,
+            timeline,
+            clusters,
+            coverage,
+            fleet_stats,
+            events,
+            clock,
+            retention,
+            presentation,
+            push,
+            handoff,
+            leadership,
+            attachments,
+            packages,
+            uploads: Arc::new(MissionUploads::new()),
+            route_render: Arc::new(RouteRenderCache::new()),
+            tenant: None,
+            health: Arc::new(SubsystemHealth::default()),
+            logs: Arc::new(LogBuffer::new(0)),
+            tasks: Arc::new(TaskSupervisor::new(SupervisorConfig::default())),
         })
     }
 
     /// Create state without database (degraded mode)
-    pub async fn new_without_db(config: ApiConfig) -> anyhow::Result<Self> {
+    pub async fn new_without_db(config: ApiConfig, ws_hub: Arc<WebSocketHub>) -> anyhow::Result<Self> {
         // let cv_engine = if config.cv_enabled {
         //     CvEngine::new().ok().map(|e| Arc::new(RwLock::new(e)))
         // } else {
         //     None
         // };
 
-        let ws_hub = Arc::new(WebSocketHub::new());
-        
         let drones = Arc::new(DashMap::new());
         for i in 1..=12 {
             let id = DroneId::new(format!("REAPER-{:02}", i));
@@ -108,8 +224,28 @@ impl AppState {
         }
 
         let mission = create_default_mission();
+        let clock = Arc::new(create_clock(&config));
+        let tracker = create_tracker(None, clock.clone(), &drones, &mission, &config.transport, &config.cv_drift, &config.los, &config.proximity, &config.altitude, &config.arrival, &config.wind, &config.mission_sync, &config.checkpoint, &config.write_breaker, config.tenant.as_ref())
+            .await?;
+        let timeline = Arc::new(TimelineRecorder::new());
+        timeline.record_created(&mission);
         let active_mission = Arc::new(RwLock::new(Some(mission)));
         let reset_flag = Arc::new(AtomicBool::new(false));
+        let exports = Arc::new(ExportManager::new(
+            config.export_dir.clone(),
+            Duration::from_secs(config.export_ttl_seconds),
+        ));
+        let imports = Arc::new(ImportManager::new(config.import_dir.clone()));
+        let clusters = create_cluster_index(&drones);
+        let coverage = Arc::new(CoverageHeatmap::new(config.coverage.clone()));
+        let fleet_stats = create_fleet_stats(&drones);
+        let events = EventBus::with_retention(1024, config.event_retention.clone());
+        let presentation = Arc::new(PresentationService::new(config.presentation.clone()));
+        let push = Arc::new(PushNotifier::new(config.push.clone(), None));
+        let handoff = Arc::new(HandoffClient::new(config.handoff.clone()));
+        let leadership = Arc::new(Leadership::new(config.leadership.clone(), None));
+        let packages = Arc::new(create_mission_signer(&config)?);
+        let attachments = Arc::new(AttachmentService::new(config.attachments.clone(), None));
 
         Ok(Self {
             config,
@@ -119,15 +255,55 @@ impl AppState {
             drones,
             active_mission,
             reset_flag,
+            exports,
+            imports,
+            tracker,
+            timeline,
+            clusters,
+            coverage,
+            fleet_stats,
+            events,
+            clock,
+            retention: None,
+            presentation,
+            push,
+            handoff,
+            leadership,
+            attachments,
+            packages,
+            uploads: Arc::new(MissionUploads::new()),
+            route_render: Arc::new(RouteRenderCache::new()),
+            tenant: None,
+            health: Arc::new(SubsystemHealth::default()),
+            logs: Arc::new(LogBuffer::new(0)),
+            tasks: Arc::new(TaskSupervisor::new(SupervisorConfig::default())),
         })
     }
 
+    /// Scope this state to a tenant
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Serve log queries from the buffer `init_logging` fills
+    pub fn with_logs(mut self, logs: Arc<LogBuffer>) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// Run background tasks under the supervisor `main` shuts down
+    pub fn with_tasks(mut self, tasks: Arc<TaskSupervisor>) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
     /// Check if database is available
     pub fn has_db(&self) -> bool {
         self.db.is_some()
     }
 
-    /// Check if CV engine is available
+    // Check if CV engine is available
     // pub fn has_cv(&self) -> bool {
     //     self.cv_engine.is_some()
     // }
@@ -138,6 +314,7 @@ impl AppState {
     }
 
     /// Update drone in cache
+    #[allow(dead_code)]
     pub fn update_drone(&self, drone: Drone) {
         self.drones.insert(drone.id.clone(), drone);
     }
@@ -152,17 +329,257 @@ impl AppState {
         self.active_mission.read().clone()
     }
 
+    /// Attach presentation hints to a position or alert event
+    pub fn decorate_event(&self, event: &mut Event) {
+        if let EventPayload::Alert(alert) = &mut event.payload {
+            self.presentation.decorate_alert(&mut alert.alert);
+            return;
+        }
+        let EventPayload::DronePosition(position) = &event.payload else {
+            return;
+        };
+        let Some((drone_type, status)) = self
+            .tracker
+            .inspect_drone(&position.drone_id, |t| (t.drone.drone_type.clone(), t.drone.status))
+        else {
+            return;
+        };
+        let marking = self.tracker.drone_marking(&position.drone_id);
+        self.presentation.decorate(event, &drone_type, status, marking.as_ref(), Utc::now());
+    }
+
+    /// Mirror tracker-driven mission status changes (e.g. a completed abort)
+    pub fn apply_mission_event(&self, event: &Event) {
+        let EventPayload::Mission(change) = &event.payload else {
+            return;
+        };
+        let mut active = self.active_mission.write();
+        let Some(mission) = active.as_mut().filter(|m| m.id == change.mission_id) else {
+            return;
+        };
+        if mission.status == change.status {
+            return;
+        }
+        let previous = mission.status;
+        match change.status {
+            MissionStatus::Aborted => mission.abort(),
+            MissionStatus::Completed => mission.complete(),
+            status => mission.status = status,
+        }
+        drop(active);
+        self.persist_mission_status(change.mission_id.clone(), previous, change.status);
+
+        if let Some(mission) = self.get_mission() {
+            let summary = change
+                .message
+                .clone()
+                .unwrap_or_else(|| format!("Mission {} {:?}", mission.name, mission.status));
+            self.timeline.record_lifecycle(&mission, summary);
+        }
+    }
+
+    /// Write a mission status change only if the stored status is still
+    /// `from`, so a change another instance wrote first is not overwritten
+    fn persist_mission_status(&self, mission_id: MissionId, from: MissionStatus, to: MissionStatus) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        self.tasks.track(async move {
+            let (from, to) = (format!("{:?}", from), format!("{:?}", to));
+            match db.missions().transition_status(&mission_id, &from, &to).await {
+                Ok(true) => {}
+                Ok(false) => warn!("Mission {} is no longer {} in the database; not setting it {}", mission_id, from, to),
+                Err(e) => {
+                    warn!("Failed to persist mission {} status: {}", mission_id, e);
+                    db.health().record_error();
+                }
+            }
+        });
+    }
+
+    /// Pick up what the previous leader persisted: scheduled commands, the
+    /// drones' last reports and the mission they were flying
+    pub async fn reload_from_db(&self) {
+        let Some(db) = &self.db else {
+            return;
+        };
+
+        if let Err(e) = self.tracker.load_scheduled_commands().await {
+            warn!("Failed to reload scheduled commands: {}", e);
+            db.health().record_error();
+        }
+        let reported = match self.tracker.load_last_reports(RELOAD_LOOKBACK).await {
+            Ok(mission_id) => mission_id,
+            Err(e) => {
+                warn!("Failed to reload drone reports: {}", e);
+                db.health().record_error();
+                None
+            }
+        };
+
+        // The mission of the newest report, else the stored copy of ours
+        let Some(mission_id) = reported.or_else(|| self.get_mission().map(|m| m.id)) else {
+            return;
+        };
+        match db.missions().get(&mission_id).await {
+            Ok(Some(mission)) => {
+                info!("Resuming mission {} ({:?})", mission.name, mission.status);
+                *self.active_mission.write() = Some(mission.clone());
+                self.tracker.set_mission(mission);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to reload mission {}: {}", mission_id, e);
+                db.health().record_error();
+            }
+        }
+    }
+
+    /// Forget a drone the tracker evicted
+    pub fn apply_eviction_event(&self, event: &Event) {
+        if let (EventType::DroneEvicted, EventPayload::DroneConnection(e)) = (&event.event_type, &event.payload) {
+            self.drones.remove(&e.drone_id);
+        }
+    }
+
     /// Get connected WebSocket client count
     pub fn ws_client_count(&self) -> usize {
-        self.ws_hub.client_count()
+        match &self.tenant {
+            Some(tenant) => self.ws_hub.tenant_client_count(tenant),
+            None => self.ws_hub.client_count(),
+        }
+    }
+}
+
+/// Mission package signer over the configured key; a key that cannot be
+/// loaded stops startup rather than signing with an identity no peer trusts
+fn create_mission_signer(config: &ApiConfig) -> anyhow::Result<MissionSigner> {
+    MissionSigner::load(&config.packages)
+        .map_err(|e| anyhow::anyhow!("{} (MISSION_SIGNING_KEY_PATH)", e))
+}
+
+/// Simulated time source; a seeded simulation runs on a virtual clock
+fn create_clock(config: &ApiConfig) -> SimulationClock {
+    match config.simulation.seed {
+        Some(_) => SimulationClock::virtual_at(simulation_epoch()),
+        None => SimulationClock::new(),
+    }
+}
+
+/// Create the drone tracker and register the cached drones with it
+#[allow(clippy::too_many_arguments)]
+async fn create_tracker(
+    db: Option<Arc<DbClient>>,
+    clock: Arc<SimulationClock>,
+    drones: &DashMap<DroneId, Drone>,
+    mission: &Mission,
+    transport: &TransportConfig,
+    drift: &DriftConfig,
+    los: &LosConfig,
+    proximity: &ProximityConfig,
+    altitude: &AltitudeConfig,
+    arrival: &ArrivalConfig,
+    wind: &WindConfig,
+    mission_sync: &MissionSyncConfig,
+    checkpoint: &CheckpointConfig,
+    write_breaker: &WriteBreakerConfig,
+    tenant: Option<&TenantId>,
+) -> anyhow::Result<Arc<DroneTracker>> {
+    let config = TrackerConfig {
+        db_enabled: db.is_some(),
+        drift: drift.clone(),
+        los: los.clone(),
+        proximity: proximity.clone(),
+        altitude: altitude.clone(),
+        arrival: arrival.clone(),
+        wind: wind.clone(),
+        mission_sync: mission_sync.clone(),
+        checkpoint: checkpoint.clone(),
+        write_breaker: write_breaker.clone(),
+        tenant: tenant.cloned(),
+        ..Default::default()
+    };
+
+    let mut tracker = DroneTracker::new(config).await?;
+    if let Some(db) = db {
+        tracker.set_database(db);
+    }
+    tracker.set_clock(clock);
+
+    for drone in drones.iter() {
+        tracker.register_drone(drone.value().clone());
+    }
+    tracker.set_mission(mission.clone());
+
+    // Fly the fleet as one convoy in drone ID order
+    let mut order: Vec<DroneId> = drones.iter().map(|d| d.key().clone()).collect();
+    order.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    tracker.convoy().set_order(order);
+
+    match MavlinkTransport::bind(&transport.mavlink_bind).await {
+        Ok(mavlink) => tracker.register_transport(Arc::new(mavlink)),
+        Err(e) => warn!("MAVLink transport unavailable ({}): {}", transport.mavlink_bind, e),
+    }
+    tracker.register_transport(Arc::new(HttpSidecarTransport::new(transport.sidecar_timeout)));
+
+    if let Err(e) = tracker.load_scheduled_commands().await {
+        warn!("Failed to load scheduled commands: {}", e);
+    }
+    if let Err(e) = tracker.load_thresholds().await {
+        warn!("Failed to load alert threshold overrides: {}", e);
     }
+    if let Err(e) = tracker.load_groups().await {
+        warn!("Failed to load drone groups: {}", e);
+    }
+    if let Err(e) = tracker.load_zones().await {
+        warn!("Failed to load zones of interest: {}", e);
+    }
+    if let Err(e) = tracker.load_rules().await {
+        warn!("Failed to load alert rules: {}", e);
+    }
+    if let Err(e) = tracker.load_event_schemas().await {
+        warn!("Failed to load custom event schemas: {}", e);
+    }
+    if let Err(e) = tracker.load_handoffs().await {
+        warn!("Failed to load handed-off drones: {}", e);
+    }
+    if let Err(e) = tracker.load_transport_bindings().await {
+        warn!("Failed to load command transport bindings: {}", e);
+    }
+    if let Err(e) = tracker.load_telemetry_sources().await {
+        warn!("Failed to load telemetry source designations: {}", e);
+    }
+    if let Err(e) = tracker.load_drone_markings().await {
+        warn!("Failed to load drone markings: {}", e);
+    }
+    if let Err(e) = tracker.load_cv_tuning().await {
+        warn!("Failed to load CV parameters: {}", e);
+    }
+
+    Ok(Arc::new(tracker))
+}
+
+/// Create the cluster index, seeded with drone statuses; drones join
+/// clusters once they report a position
+fn create_cluster_index(drones: &DashMap<DroneId, Drone>) -> Arc<ClusterIndex> {
+    let clusters = Arc::new(ClusterIndex::new());
+    for drone in drones.iter() {
+        clusters.update_status(drone.key(), drone.status);
+    }
+    clusters
+}
+
+/// Create the fleet statistics service, seeded with drone statuses
+fn create_fleet_stats(drones: &DashMap<DroneId, Drone>) -> Arc<FleetStatsService> {
+    let fleet_stats = Arc::new(FleetStatsService::new());
+    for drone in drones.iter() {
+        fleet_stats.update_status(drone.key(), drone.status);
+    }
+    fleet_stats
 }
 
 /// Create default Afghanistan convoy mission
 fn create_default_mission() -> Mission {
-    let mut mission = Mission::new("Operation Desert Watch");
-    mission.description = Some("Convoy escort mission across 12 strategic waypoints in Afghanistan".into());
-
     let waypoints = vec![
         ("WP01", "Base Alpha", 34.5553, 69.2075, WaypointType::Origin),
         ("WP02", "Checkpoint Bravo", 34.6234, 69.1123, WaypointType::Checkpoint),
@@ -178,16 +595,20 @@ fn create_default_mission() -> Mission {
         ("WP12", "Terminal Lima", 35.3234, 67.9234, WaypointType::Destination),
     ];
 
-    for (id, name, lat, lng, wp_type) in waypoints {
+    let waypoints = waypoints.into_iter().map(|(id, name, lat, lng, wp_type)| {
         let mut wp = Waypoint::new(id, name, lat, lng);
+        if wp_type == WaypointType::Rally {
+            wp.loiter_time_seconds = Some(30);
+        }
         wp.waypoint_type = wp_type;
-        mission.add_waypoint(wp);
-    }
-
-    // Assign all 12 drones
-    for i in 1..=12 {
-        mission.assign_drone(DroneId::new(format!("REAPER-{:02}", i)));
-    }
+        wp
+    });
 
-    mission
+    MissionBuilder::new("Operation Desert Watch")
+        .with_description("Convoy escort mission across 12 strategic waypoints in Afghanistan")
+        .with_waypoints(waypoints)
+        // Assign all 12 drones
+        .assign_drones((1..=12).map(|i| DroneId::new(format!("REAPER-{:02}", i))))
+        .build()
+        .expect("default mission is valid")
 }
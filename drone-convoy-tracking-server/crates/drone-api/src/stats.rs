@@ -0,0 +1,167 @@
+//! Unified subsystem statistics
+//!
+//! Gathers the counters of every layer (API, tracker, P2P, CV, WebSocket and
+//! database) into one response for the admin dashboard. Each section carries
+//! the subsystem's start time, uptime and error count; optional subsystems
+//! that are disabled or unavailable are reported as `null`. API errors are
+//! the 5xx responses counted by [`count_server_errors`].
+
+use crate::state::AppState;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use drone_core::HealthReport;
+use drone_db::StorageBackend;
+use drone_p2p::P2pStats;
+use drone_tracker::{CvPublisherStats, TrackerFootprint};
+use drone_websocket::CompressionStats;
+use serde::Serialize;
+
+/// Statistics of every subsystem
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemStats {
+    pub generated_at: DateTime<Utc>,
+    pub api: ApiStats,
+    pub tracker: TrackerStats,
+    pub p2p: Option<P2pStats>,
+    pub cv: Option<CvStats>,
+    pub websocket: WebSocketStats,
+    pub database: Option<DatabaseStats>,
+}
+
+/// HTTP API; errors are 5xx responses
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStats {
+    #[serde(flatten)]
+    pub health: HealthReport,
+}
+
+/// Tracking coordinator; errors are failed command dispatches
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackerStats {
+    #[serde(flatten)]
+    pub health: HealthReport,
+    pub running: bool,
+    pub footprint: TrackerFootprint,
+}
+
+/// CV publishing pipeline; errors are failed batch writes
+#[derive(Debug, Clone, Serialize)]
+pub struct CvStats {
+    #[serde(flatten)]
+    pub health: HealthReport,
+    pub publisher: CvPublisherStats,
+}
+
+/// WebSocket hub; errors are failed connections, sends and receives
+#[derive(Debug, Clone, Serialize)]
+pub struct WebSocketStats {
+    #[serde(flatten)]
+    pub health: HealthReport,
+    pub clients: usize,
+    pub messages: usize,
+    pub compression: CompressionStats,
+}
+
+/// Database; errors are failed writes and health checks
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseStats {
+    #[serde(flatten)]
+    pub health: HealthReport,
+    pub backend: StorageBackend,
+}
+
+impl SystemStats {
+    /// Snapshot every subsystem of `state`
+    pub fn collect(state: &AppState) -> Self {
+        let now = Utc::now();
+        let tracker = &state.tracker;
+        Self {
+            generated_at: now,
+            api: ApiStats {
+                health: state.health.report(now),
+            },
+            tracker: TrackerStats {
+                health: tracker.health().report(now),
+                running: tracker.is_running(),
+                footprint: tracker.footprint(),
+            },
+            p2p: tracker.p2p_stats(),
+            cv: tracker.cv_pipeline().map(|cv| CvStats {
+                health: cv.health().report(now),
+                publisher: cv.stats(),
+            }),
+            websocket: WebSocketStats {
+                health: state.ws_hub.health().report(now),
+                clients: state.ws_hub.client_count(),
+                messages: state.ws_hub.message_count(),
+                compression: state.ws_hub.compression_stats(),
+            },
+            database: state.db.as_ref().map(|db| DatabaseStats {
+                health: db.health().report(now),
+                backend: db.config().backend,
+            }),
+        }
+    }
+}
+
+/// Middleware counting 5xx responses as API errors
+pub async fn count_server_errors(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    if response.status().is_server_error() {
+        state.health.record_error();
+    }
+    response
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+    use crate::packages::PackageConfig;
+
+    use axum::{body::Body, http::StatusCode, middleware, routing::get, Router};
+    use drone_websocket::WebSocketHub;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_collects_subsystems_and_counts_server_errors() {
+        let packages = PackageConfig {
+            key_path: std::env::temp_dir().join(format!("mission-key-{}", uuid::Uuid::new_v4())),
+            ..Default::default()
+        };
+        let state = AppState::new_without_db(ApiConfig { packages, ..Default::default() }, Arc::new(WebSocketHub::new()))
+            .await
+            .unwrap();
+        let app = Router::new()
+            .route("/ok", get(|| async { StatusCode::OK }))
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .layer(middleware::from_fn_with_state(state.clone(), count_server_errors));
+        for uri in ["/ok", "/missing", "/fail", "/fail"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let stats = SystemStats::collect(&state);
+        assert_eq!(stats.api.health.errors, 2);
+        assert_eq!(stats.tracker.health.errors, 0);
+        assert_eq!(stats.tracker.footprint.drones, 12);
+        assert_eq!(stats.websocket.clients, 0);
+        assert!(stats.database.is_none());
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["api"]["errors"], 2);
+        assert!(json["tracker"]["uptime_seconds"].is_u64());
+        assert!(json["database"].is_null());
+    }
+}
@@ -0,0 +1,531 @@
+//! Background task supervision
+//!
+//! The API's long-running tasks (event forwarding, alert recording, the
+//! leader lease, the simulation, the tracker's monitors, retention and the
+//! WebSocket server) run under one [`TaskSupervisor`]. A task that panics or
+//! fails is logged and restarted after a backoff that doubles from
+//! `SUPERVISOR_BACKOFF_MS` up to `SUPERVISOR_MAX_BACKOFF_SECS`; a run that
+//! lasted at least the longest backoff starts the backoff over. Each task's
+//! state is reported in `/status`. On exit the registered flush steps run
+//! first (e.g. the CV publisher writing its queued results), then every task
+//! is stopped and one-off writes started with [`TaskSupervisor::track`] are
+//! awaited, all within `SUPERVISOR_SHUTDOWN_SECS`.
+
+use chrono::{DateTime, Utc};
+use drone_core::TenantId;
+use futures::future::{self, BoxFuture};
+use futures::FutureExt;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, warn};
+
+/// Restart backoff and shutdown settings
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// Wait before the first restart
+    pub initial_backoff: Duration,
+    /// Longest wait between restarts
+    pub max_backoff: Duration,
+    /// How long shutdown waits for tasks to stop
+    pub shutdown_timeout: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            shutdown_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl SupervisorConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        let initial_backoff = env("SUPERVISOR_BACKOFF_MS")
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.initial_backoff);
+        Self {
+            initial_backoff,
+            max_backoff: env("SUPERVISOR_MAX_BACKOFF_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.max_backoff)
+                .max(initial_backoff),
+            shutdown_timeout: env("SUPERVISOR_SHUTDOWN_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.shutdown_timeout),
+        }
+    }
+}
+
+/// When a task that ended is started again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    /// Whenever it ends: the task is meant to run forever
+    Always,
+    /// Only after a panic or an error
+    OnFailure,
+}
+
+/// Where a supervised task is at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Failed (or ended under `Always`) and waiting out the backoff
+    Restarting,
+    /// Ended successfully and not restarted
+    Finished,
+    /// Stopped by shutdown or leadership demotion
+    Stopped,
+}
+
+/// A task's name and the tenant it works for (`None` for shared tasks)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskId {
+    pub tenant: Option<TenantId>,
+    pub name: &'static str,
+}
+
+impl TaskId {
+    /// A task serving every tenant
+    pub fn shared(name: &'static str) -> Self {
+        Self { tenant: None, name }
+    }
+
+    /// A task of one state (`tenant` is `None` in single-tenant mode)
+    pub fn of(tenant: Option<&TenantId>, name: &'static str) -> Self {
+        Self {
+            tenant: tenant.cloned(),
+            name,
+        }
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.tenant {
+            Some(tenant) => write!(f, "{} ({})", self.name, tenant),
+            None => f.write_str(self.name),
+        }
+    }
+}
+
+/// Task health as reported in `/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskHealth {
+    pub name: &'static str,
+    #[serde(skip)]
+    pub tenant: Option<TenantId>,
+    pub state: TaskState,
+    pub policy: RestartPolicy,
+    /// Restarts since the API started
+    pub restarts: u32,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
+/// Runs background tasks, restarts them and stops them on exit
+pub struct TaskSupervisor {
+    config: SupervisorConfig,
+    tasks: RwLock<Vec<TaskHealth>>,
+    /// Supervising loops and one-off writes, awaited on shutdown
+    tracker: TaskTracker,
+    shutdown_tx: watch::Sender<bool>,
+    /// Run on shutdown before tasks are stopped
+    flush_steps: Mutex<Vec<BoxFuture<'static, ()>>>,
+}
+
+impl TaskSupervisor {
+    pub fn new(config: SupervisorConfig) -> Self {
+        Self {
+            config,
+            tasks: RwLock::new(Vec::new()),
+            tracker: TaskTracker::new(),
+            shutdown_tx: watch::channel(false).0,
+            flush_steps: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Run the future `start` makes under `policy`, making a new one for
+    /// every restart. Aborting the returned handle stops the task.
+    pub fn spawn<F, Fut>(self: &Arc<Self>, id: TaskId, policy: RestartPolicy, start: F) -> JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.tracker.spawn(Arc::clone(self).supervise(id, policy, start))
+    }
+
+    /// Supervise tasks spawned elsewhere: `start` spawns the first run now
+    /// and a new one on every restart. Nothing is supervised when it spawns
+    /// nothing (e.g. a monitor of a disabled feature).
+    pub fn adopt<F>(self: &Arc<Self>, id: TaskId, policy: RestartPolicy, start: F) -> Option<JoinHandle<()>>
+    where
+        F: Fn() -> Option<JoinHandle<()>> + Send + Sync + 'static,
+    {
+        // A panic while starting counts as a failed first run
+        let first = match std::panic::catch_unwind(AssertUnwindSafe(&start)) {
+            Ok(task) => Ok(task?),
+            Err(panic) => Err(panic),
+        };
+        let first = Mutex::new(Some(first));
+        Some(self.spawn(id, policy, move || {
+            let task = match first.lock().take() {
+                Some(first) => first.map(Some),
+                None => Ok(start()),
+            };
+            async move {
+                match task {
+                    Ok(Some(task)) => joined(task).await,
+                    Ok(None) => Ok(()),
+                    Err(panic) => std::panic::resume_unwind(panic),
+                }
+            }
+        }))
+    }
+
+    /// Run a one-off future, such as a database write, that shutdown waits
+    /// for instead of stopping
+    pub fn track<F>(&self, task: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tracker.spawn(task)
+    }
+
+    /// Run `step` on shutdown before any task is stopped, e.g. to let a
+    /// task write what it holds
+    pub fn on_shutdown(&self, step: impl Future<Output = ()> + Send + 'static) {
+        self.flush_steps.lock().push(step.boxed());
+    }
+
+    /// Health of every task, shared tasks first
+    pub fn health(&self) -> Vec<TaskHealth> {
+        let mut tasks = self.tasks.read().clone();
+        tasks.sort_by(|a, b| a.tenant.cmp(&b.tenant).then(a.name.cmp(b.name)));
+        tasks
+    }
+
+    /// Health of a state's tasks and the shared ones
+    pub fn health_of(&self, tenant: Option<&TenantId>) -> Vec<TaskHealth> {
+        self.health()
+            .into_iter()
+            .filter(|task| task.tenant.is_none() || task.tenant.as_ref() == tenant)
+            .collect()
+    }
+
+    /// Run the flush steps, then stop every task and wait for one-off
+    /// writes, all within `shutdown_timeout`
+    pub async fn shutdown(&self) {
+        let deadline = Instant::now() + self.config.shutdown_timeout;
+        let steps = std::mem::take(&mut *self.flush_steps.lock());
+        if tokio::time::timeout_at(deadline, future::join_all(steps)).await.is_err() {
+            warn!("Background tasks did not flush within {:?}", self.config.shutdown_timeout);
+        }
+
+        self.shutdown_tx.send_replace(true);
+        self.tracker.close();
+        if tokio::time::timeout_at(deadline, self.tracker.wait()).await.is_err() {
+            warn!("Background tasks did not stop within {:?}", self.config.shutdown_timeout);
+        }
+        info!("Background tasks stopped");
+    }
+
+    async fn supervise<F, Fut>(self: Arc<Self>, id: TaskId, policy: RestartPolicy, start: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let mut shutdown = self.shutdown_tx.subscribe();
+        let _stopped = StopGuard {
+            supervisor: Arc::clone(&self),
+            id: id.clone(),
+        };
+        let mut backoff = self.config.initial_backoff;
+
+        loop {
+            self.started(&id, policy);
+            let started = Instant::now();
+            // `start` is called inside the caught future, so a panic making
+            // the run is caught too
+            let run = AssertUnwindSafe(async { start().await }).catch_unwind();
+            let outcome = tokio::select! {
+                outcome = run => outcome,
+                _ = shutdown.wait_for(|stop| *stop) => return,
+            };
+
+            let failure = match outcome {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(format!("{:#}", e)),
+                Err(panic) => Some(format!("panicked: {}", panic_message(&*panic))),
+            };
+            let restart = match policy {
+                RestartPolicy::Always => true,
+                RestartPolicy::OnFailure => failure.is_some(),
+            };
+            match &failure {
+                Some(e) => error!("Task {} failed: {}", id, e),
+                None => info!("Task {} finished", id),
+            }
+
+            if !restart {
+                self.ended(&id, TaskState::Finished, failure);
+                return;
+            }
+
+            if started.elapsed() >= self.config.max_backoff {
+                backoff = self.config.initial_backoff;
+            }
+            self.ended(&id, TaskState::Restarting, failure);
+            warn!("Restarting task {} in {:?}", id, backoff);
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = shutdown.wait_for(|stop| *stop) => return,
+            }
+            backoff = (backoff * 2).min(self.config.max_backoff);
+            self.update(&id, |task| task.restarts += 1);
+        }
+    }
+
+    /// Mark a task running, registering it on its first start
+    fn started(&self, id: &TaskId, policy: RestartPolicy) {
+        let mut tasks = self.tasks.write();
+        let now = Utc::now();
+        match tasks.iter_mut().find(|task| task.tenant == id.tenant && task.name == id.name) {
+            Some(task) => {
+                task.state = TaskState::Running;
+                task.policy = policy;
+                task.started_at = now;
+            }
+            None => tasks.push(TaskHealth {
+                name: id.name,
+                tenant: id.tenant.clone(),
+                state: TaskState::Running,
+                policy,
+                restarts: 0,
+                started_at: now,
+                last_error: None,
+                last_failure_at: None,
+            }),
+        }
+    }
+
+    fn ended(&self, id: &TaskId, state: TaskState, failure: Option<String>) {
+        self.update(id, |task| {
+            task.state = state;
+            if failure.is_some() {
+                task.last_error = failure;
+                task.last_failure_at = Some(Utc::now());
+            }
+        });
+    }
+
+    fn update(&self, id: &TaskId, f: impl FnOnce(&mut TaskHealth)) {
+        let mut tasks = self.tasks.write();
+        if let Some(task) = tasks.iter_mut().find(|task| task.tenant == id.tenant && task.name == id.name) {
+            f(task);
+        }
+    }
+}
+
+/// Marks a task stopped when its supervising loop ends without settling
+/// it: on shutdown, or when the loop is aborted (leadership demotion)
+struct StopGuard {
+    supervisor: Arc<TaskSupervisor>,
+    id: TaskId,
+}
+
+impl Drop for StopGuard {
+    fn drop(&mut self) {
+        self.supervisor.update(&self.id, |task| {
+            if matches!(task.state, TaskState::Running | TaskState::Restarting) {
+                task.state = TaskState::Stopped;
+            }
+        });
+    }
+}
+
+/// Await a task spawned elsewhere as a supervised run: its panic is
+/// re-raised, and the task is aborted if the run is dropped first
+pub async fn joined(task: JoinHandle<()>) -> anyhow::Result<()> {
+    struct AbortOnDrop(JoinHandle<()>);
+    impl Drop for AbortOnDrop {
+        fn drop(&mut self) {
+            self.0.abort();
+        }
+    }
+
+    let mut task = AbortOnDrop(task);
+    match (&mut task.0).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn supervisor() -> Arc<TaskSupervisor> {
+        Arc::new(TaskSupervisor::new(SupervisorConfig {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(4),
+            shutdown_timeout: Duration::from_secs(1),
+        }))
+    }
+
+    fn task(supervisor: &TaskSupervisor, name: &str) -> TaskHealth {
+        supervisor.health().into_iter().find(|task| task.name == name).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_panicking_task_restarts_with_backoff() {
+        let supervisor = supervisor();
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        supervisor.spawn(TaskId::shared("flaky"), RestartPolicy::OnFailure, move || {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if run < 3 {
+                    panic!("run {} blew up", run);
+                }
+                Ok(())
+            }
+        });
+
+        // Restarts after 1s, 2s and 4s
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(task(&supervisor, "flaky").state, TaskState::Restarting);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+
+        let flaky = task(&supervisor, "flaky");
+        assert_eq!(flaky.state, TaskState::Finished);
+        assert_eq!(flaky.restarts, 3);
+        assert_eq!(flaky.last_error.as_deref(), Some("panicked: run 2 blew up"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_adopted_task_panicking_on_start_restarts() {
+        let supervisor = supervisor();
+        let starts = Arc::new(AtomicU32::new(0));
+        let counter = starts.clone();
+        let adopted = supervisor.adopt(TaskId::shared("listener"), RestartPolicy::Always, move || {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("receiver not ready");
+            }
+            Some(tokio::spawn(std::future::pending()))
+        });
+        assert!(adopted.is_some());
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let listener = task(&supervisor, "listener");
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+        assert_eq!(listener.state, TaskState::Running);
+        assert_eq!(listener.restarts, 1);
+        assert_eq!(listener.last_error.as_deref(), Some("panicked: receiver not ready"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_flushes_then_waits_for_writes() {
+        let supervisor = supervisor();
+        let (flush_tx, flush_rx) = tokio::sync::oneshot::channel::<()>();
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+        let channels = Mutex::new(Some((flush_rx, done_tx)));
+        // The task ends by itself once asked to flush, before it would be stopped
+        supervisor.spawn(TaskId::shared("publisher"), RestartPolicy::OnFailure, move || {
+            let channels = channels.lock().take();
+            async move {
+                if let Some((flush, done)) = channels {
+                    let _ = flush.await;
+                    let _ = done.send(());
+                }
+                Ok(())
+            }
+        });
+        supervisor.on_shutdown(async move {
+            let _ = flush_tx.send(());
+            let _ = done_rx.await;
+        });
+        let written = Arc::new(AtomicU32::new(0));
+        let write = written.clone();
+        supervisor.track(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            write.fetch_add(1, Ordering::SeqCst);
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        supervisor.shutdown().await;
+        assert_eq!(task(&supervisor, "publisher").state, TaskState::Finished);
+        assert_eq!(written.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_and_abort_stop_tasks() {
+        let supervisor = supervisor();
+        let tenant = TenantId::try_from("acme".to_string()).unwrap();
+        let (adopted_tx, adopted_rx) = tokio::sync::oneshot::channel::<()>();
+        let adopted_tx = Mutex::new(Some(adopted_tx));
+        supervisor.adopt(TaskId::of(Some(&tenant), "monitor"), RestartPolicy::Always, move || {
+            let tx = adopted_tx.lock().take();
+            Some(tokio::spawn(async move {
+                let _tx = tx;
+                std::future::pending::<()>().await;
+            }))
+        });
+        let leader_only = supervisor.spawn(TaskId::shared("simulation"), RestartPolicy::Always, || {
+            std::future::pending()
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Aborting the returned handle stops the task, as on demotion
+        leader_only.abort();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(task(&supervisor, "simulation").state, TaskState::Stopped);
+        assert_eq!(task(&supervisor, "monitor").state, TaskState::Running);
+        assert_eq!(supervisor.health_of(None).len(), 1);
+
+        // Shutdown stops (and aborts) the adopted task too
+        supervisor.shutdown().await;
+        assert_eq!(task(&supervisor, "monitor").state, TaskState::Stopped);
+        assert!(adopted_rx.await.is_err());
+    }
+}
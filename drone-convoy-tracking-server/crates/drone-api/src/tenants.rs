@@ -0,0 +1,229 @@
+//! Multi-tenant hosting
+//!
+//! Several customer fleets can share one deployment. Every tenant gets its
+//! own service stack (tracker, drone cache, missions, timeline, event bus,
+//! push subscriptions) over its own storage: a tenant-scoped keyspace or
+//! SQLite file. Nothing below the router is shared, so a request can only
+//! ever see its own tenant's data; the in-memory log buffer is the one
+//! shared piece and filters by the tenant recorded on each line. Requests
+//! pick their stack by API key; WebSocket clients are scoped to their
+//! tenant by the shared hub.
+//!
+//! Without a tenants file the server runs single-tenant and needs no key.
+
+use crate::error::ApiError;
+
+use anyhow::{bail, Context};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Request, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use drone_core::TenantId;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tower::ServiceExt;
+use tracing::{info_span, Instrument};
+
+/// A customer fleet and the API keys that reach it
+#[derive(Clone, Deserialize)]
+pub struct TenantConfig {
+    pub id: TenantId,
+    pub name: String,
+    pub api_keys: Vec<String>,
+}
+
+// Keeps API keys out of logs
+impl std::fmt::Debug for TenantConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TenantConfig")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("api_keys", &self.api_keys.len())
+            .finish()
+    }
+}
+
+/// Configured tenants, by API key
+#[derive(Debug, Clone, Default)]
+pub struct TenantRegistry {
+    tenants: Vec<TenantConfig>,
+    keys: HashMap<String, TenantId>,
+}
+
+impl TenantRegistry {
+    /// Every tenant needs a unique ID and at least one key; keys may not be shared
+    pub fn new(tenants: Vec<TenantConfig>) -> anyhow::Result<Self> {
+        let mut keys = HashMap::new();
+        for (i, tenant) in tenants.iter().enumerate() {
+            if tenants[..i].iter().any(|t| t.id == tenant.id) {
+                bail!("duplicate tenant {}", tenant.id);
+            }
+            if tenant.api_keys.is_empty() {
+                bail!("tenant {} has no API keys", tenant.id);
+            }
+            for key in &tenant.api_keys {
+                if key.trim().is_empty() {
+                    bail!("tenant {} has an empty API key", tenant.id);
+                }
+                if keys.insert(key.clone(), tenant.id.clone()).is_some() {
+                    bail!("API key of tenant {} is used by another tenant", tenant.id);
+                }
+            }
+        }
+        Ok(Self { tenants, keys })
+    }
+
+    /// Load the JSON array of tenants named by `TENANTS_FILE`, if set
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var("TENANTS_FILE") {
+            Ok(path) => Self::from_file(path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("reading tenants file {}", path.display()))?;
+        let tenants = serde_json::from_str(&json)
+            .with_context(|| format!("parsing tenants file {}", path.display()))?;
+        Self::new(tenants)
+    }
+
+    /// No tenants configured: the deployment is single-tenant
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty()
+    }
+
+    pub fn tenants(&self) -> &[TenantConfig] {
+        &self.tenants
+    }
+
+    pub fn resolve(&self, api_key: &str) -> Option<TenantId> {
+        self.keys.get(api_key).cloned()
+    }
+}
+
+/// API key from `X-Api-Key`, a bearer `Authorization` header or the
+/// `api_key` query parameter (for `EventSource` clients, which cannot set headers)
+pub fn api_key(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    header("x-api-key")
+        .map(str::to_string)
+        .or_else(|| {
+            header("authorization")
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .map(str::to_string)
+        })
+        .or_else(|| {
+            uri.query()?
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(name, _)| *name == "api_key")
+                .map(|(_, value)| value.to_string())
+        })
+        .filter(|key| !key.is_empty())
+}
+
+/// Per-tenant routers behind API key authentication
+pub struct TenantRouter {
+    registry: TenantRegistry,
+    routers: HashMap<TenantId, Router>,
+}
+
+impl TenantRouter {
+    pub fn new(registry: TenantRegistry, routers: HashMap<TenantId, Router>) -> Self {
+        Self { registry, routers }
+    }
+}
+
+/// Hand a request to its tenant's router
+pub async fn dispatch(State(tenants): State<Arc<TenantRouter>>, request: Request<Body>) -> Response {
+    let tenant = api_key(request.headers(), request.uri()).and_then(|key| tenants.registry.resolve(&key));
+    let Some((tenant, router)) = tenant.and_then(|tenant| tenants.routers.get(&tenant).cloned().map(|router| (tenant, router))) else {
+        return ApiError::Unauthorized("missing or unknown API key".into()).into_response();
+    };
+    // Log lines carry the tenant so the shared log buffer can be scoped
+    let span = info_span!("tenant_request", tenant_id = %tenant);
+    match router.oneshot(request).instrument(span).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get};
+
+    #[tokio::test]
+    async fn test_requests_reach_only_their_tenant() {
+        let tenant = |id: &str, keys: &[&str]| TenantConfig {
+            id: TenantId::parse(id).unwrap(),
+            name: id.to_uppercase(),
+            api_keys: keys.iter().map(|k| k.to_string()).collect(),
+        };
+        assert!(TenantRegistry::new(vec![tenant("acme", &["k1"]), tenant("globex", &["k1"])]).is_err());
+        assert!(TenantRegistry::new(vec![tenant("acme", &[])]).is_err());
+        assert!(TenantRegistry::new(vec![tenant("acme", &["k1"]), tenant("acme", &["k2"])]).is_err());
+
+        let registry = TenantRegistry::new(vec![tenant("acme", &["acme-1", "acme-2"]), tenant("globex", &["globex-1"])]).unwrap();
+        let routers = registry
+            .tenants()
+            .iter()
+            .map(|t| {
+                let name = t.name.clone();
+                (t.id.clone(), Router::new().route("/whoami", get(move || async move { name })))
+            })
+            .collect();
+        let app = Router::new()
+            .fallback(dispatch)
+            .with_state(Arc::new(TenantRouter::new(registry, routers)));
+
+        let call = |request: Request<Body>| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+        let request = |uri: &str| Request::builder().uri(uri);
+
+        let (status, _) = call(request("/whoami").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = call(request("/whoami").header("x-api-key", "nope").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let acme = request("/whoami").header("x-api-key", "acme-2").body(Body::empty()).unwrap();
+        assert_eq!(call(acme).await, (StatusCode::OK, "ACME".to_string()));
+        let globex = request("/whoami").header("authorization", "Bearer globex-1").body(Body::empty()).unwrap();
+        assert_eq!(call(globex).await, (StatusCode::OK, "GLOBEX".to_string()));
+        let sse = request("/whoami?api_key=acme-1").body(Body::empty()).unwrap();
+        assert_eq!(call(sse).await, (StatusCode::OK, "ACME".to_string()));
+    }
+
+    #[test]
+    fn test_tenant_config_scopes_file_directories() {
+        let config = crate::config::ApiConfig::default();
+        let acme = config.for_tenant(&TenantId::parse("acme").unwrap());
+        assert_eq!(acme.export_dir, config.export_dir.join("acme"));
+        assert_eq!(acme.attachments.dir, config.attachments.dir.join("acme"));
+
+        // Backfill reads only the tenant's own import files
+        let imports = crate::backfill::ImportManager::new(acme.import_dir.clone());
+        assert_eq!(imports.source_path("flight.csv"), config.import_dir.join("acme").join("flight.csv"));
+        let globex = config.for_tenant(&TenantId::parse("globex").unwrap());
+        assert_ne!(globex.import_dir, acme.import_dir);
+    }
+}
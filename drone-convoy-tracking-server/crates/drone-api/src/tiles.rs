@@ -0,0 +1,639 @@
+//! Map tile proxy and cache
+//!
+//! The frontend's base map is served from `/tiles/:z/:x/:y` instead of public
+//! tile servers, which some networks block. Tiles are fetched from the
+//! configured upstream on first request and kept in a size-bounded disk
+//! cache that evicts the least recently served tiles. In offline mode the
+//! upstream is never contacted and only cached tiles are served, so a cache
+//! seeded before deployment keeps working on a closed network. Concurrent
+//! requests for a tile that is not cached share a single upstream fetch.
+//!
+//! Like the push and handoff clients, the upstream is fetched over plain
+//! HTTP/1.1; point `TILE_UPSTREAM` at a local tile server or a
+//! TLS-terminating proxy.
+
+use async_trait::async_trait;
+use http_body_util::{BodyExt, Empty, Limited};
+use hyper::body::Bytes;
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Largest accepted upstream tile (bytes)
+pub const MAX_TILE_BYTES: usize = 2 * 1024 * 1024;
+
+/// Extension of cached tile files
+const TILE_EXTENSION: &str = "tile";
+
+/// Tile proxy settings
+#[derive(Debug, Clone)]
+pub struct TileConfig {
+    /// Upstream URL template with `{z}`, `{x}` and `{y}` placeholders
+    pub upstream: Option<String>,
+    /// Cache directory, one `z/x/y.tile` file per tile
+    pub cache_dir: PathBuf,
+    /// Cache size limit (bytes)
+    pub max_cache_bytes: u64,
+    /// Serve cached tiles only, never contacting the upstream
+    pub offline: bool,
+    /// Highest zoom level served
+    pub max_zoom: u8,
+    /// Timeout for an upstream fetch
+    pub timeout: Duration,
+    /// `User-Agent` sent upstream (public tile servers require one)
+    pub user_agent: String,
+}
+
+impl Default for TileConfig {
+    fn default() -> Self {
+        Self {
+            upstream: None,
+            cache_dir: PathBuf::from("./tile-cache"),
+            max_cache_bytes: 512 * 1024 * 1024,
+            offline: false,
+            max_zoom: 19,
+            timeout: Duration::from_secs(10),
+            user_agent: "drone-convoy-tracker/0.1".into(),
+        }
+    }
+}
+
+impl TileConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            upstream: env("TILE_UPSTREAM").filter(|s| !s.is_empty()),
+            cache_dir: env("TILE_CACHE_DIR").map(PathBuf::from).unwrap_or(defaults.cache_dir),
+            max_cache_bytes: env("TILE_CACHE_MAX_MB")
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(defaults.max_cache_bytes),
+            offline: env("TILE_OFFLINE").map(|s| s == "true" || s == "1").unwrap_or(defaults.offline),
+            max_zoom: env("TILE_MAX_ZOOM").and_then(|s| s.parse().ok()).unwrap_or(defaults.max_zoom),
+            timeout: env("TILE_TIMEOUT_SECS")
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            user_agent: env("TILE_USER_AGENT").unwrap_or(defaults.user_agent),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TileError {
+    #[error("tile {0} is outside the tile grid")]
+    OutOfRange(TileKey),
+
+    #[error("tile {0} is not cached")]
+    NotCached(TileKey),
+
+    #[error("tile {0} does not exist upstream")]
+    NotFound(TileKey),
+
+    /// Message carries the upstream's origin only; its path and query may
+    /// hold an API key
+    #[error("tile upstream unreachable: {0}")]
+    Transport(String),
+
+    #[error("tile upstream answered {0}")]
+    Upstream(u16),
+
+    #[error("tile cache error: {0}")]
+    Storage(#[from] std::io::Error),
+}
+
+/// Slippy map tile coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileKey {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl std::fmt::Display for TileKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.z, self.x, self.y)
+    }
+}
+
+impl TileKey {
+    /// Whether the tile exists at its zoom level
+    pub fn is_valid(&self, max_zoom: u8) -> bool {
+        self.z <= max_zoom.min(30) && u64::from(self.x.max(self.y)) < 1u64 << self.z
+    }
+
+    fn path(&self, dir: &Path) -> PathBuf {
+        dir.join(self.z.to_string())
+            .join(self.x.to_string())
+            .join(format!("{}.{}", self.y, TILE_EXTENSION))
+    }
+
+    fn url(&self, template: &str) -> String {
+        template
+            .replace("{z}", &self.z.to_string())
+            .replace("{x}", &self.x.to_string())
+            .replace("{y}", &self.y.to_string())
+    }
+}
+
+/// A tile ready to serve
+#[derive(Debug, Clone)]
+pub struct Tile {
+    pub content: Bytes,
+    pub content_type: &'static str,
+    /// Served from the cache rather than fetched
+    pub cached: bool,
+}
+
+/// Cache and upstream counters
+#[derive(Debug, Clone, Serialize)]
+pub struct TileStats {
+    pub upstream: Option<String>,
+    pub offline: bool,
+    pub tiles: usize,
+    pub bytes: u64,
+    pub max_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub upstream_errors: u64,
+}
+
+// ============================================================================
+// UPSTREAM
+// ============================================================================
+
+/// Fetches tiles from the upstream server
+#[async_trait]
+pub trait TileFetcher: Send + Sync {
+    /// Tile content, or `None` if the upstream has no such tile
+    async fn fetch(&self, url: &str) -> Result<Option<Bytes>, TileError>;
+}
+
+/// HTTP/1.1 tile fetcher over plain TCP
+pub struct HyperTileFetcher {
+    client: Client<HttpConnector, Empty<Bytes>>,
+    timeout: Duration,
+    user_agent: String,
+}
+
+impl HyperTileFetcher {
+    pub fn new(timeout: Duration, user_agent: impl Into<String>) -> Self {
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            timeout,
+            user_agent: user_agent.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl TileFetcher for HyperTileFetcher {
+    async fn fetch(&self, url: &str) -> Result<Option<Bytes>, TileError> {
+        let origin = redact_url(url);
+        let transport = |e: &dyn std::fmt::Display| {
+            TileError::Transport(format!("{}: {}", origin, e.to_string().replace(url, &origin)))
+        };
+
+        let request = hyper::Request::get(url)
+            .header(hyper::header::USER_AGENT, &self.user_agent)
+            .body(Empty::new())
+            .map_err(|e| transport(&e))?;
+        let fetch = async {
+            let response = self.client.request(request).await.map_err(|e| transport(&e))?;
+            let status = response.status().as_u16();
+            if status == 404 {
+                return Ok(None);
+            }
+            if !(200..300).contains(&status) {
+                return Err(TileError::Upstream(status));
+            }
+            let body = Limited::new(response.into_body(), MAX_TILE_BYTES)
+                .collect()
+                .await
+                .map_err(|e| transport(&e))?;
+            Ok(Some(body.to_bytes()))
+        };
+        tokio::time::timeout(self.timeout, fetch)
+            .await
+            .map_err(|_| TileError::Transport(format!("{}: request timed out", origin)))?
+    }
+}
+
+/// Scheme and host of an upstream URL, without the path and query that
+/// may carry an API key
+pub fn redact_url(url: &str) -> String {
+    match url.parse::<hyper::Uri>() {
+        Ok(uri) => match (uri.scheme_str(), uri.authority()) {
+            (Some(scheme), Some(authority)) => {
+                // Credentials in the authority are dropped too
+                let host = authority.as_str().rsplit('@').next().unwrap_or_default();
+                format!("{}://{}/…", scheme, host)
+            }
+            _ => "tile upstream".into(),
+        },
+        Err(_) => "tile upstream".into(),
+    }
+}
+
+// ============================================================================
+// DISK CACHE
+// ============================================================================
+
+/// Recency order and sizes of the cached tiles
+#[derive(Debug, Default)]
+struct CacheIndex {
+    /// Tile → (last use, size)
+    entries: HashMap<TileKey, (u64, u64)>,
+    /// Last use → tile, oldest first
+    order: BTreeMap<u64, TileKey>,
+    bytes: u64,
+    clock: u64,
+}
+
+impl CacheIndex {
+    fn touch(&mut self, key: TileKey) -> bool {
+        self.clock += 1;
+        let Some((used, _)) = self.entries.get_mut(&key) else {
+            return false;
+        };
+        self.order.remove(used);
+        *used = self.clock;
+        self.order.insert(self.clock, key);
+        true
+    }
+
+    fn insert(&mut self, key: TileKey, size: u64) {
+        self.remove(&key);
+        self.clock += 1;
+        self.entries.insert(key, (self.clock, size));
+        self.order.insert(self.clock, key);
+        self.bytes += size;
+    }
+
+    fn remove(&mut self, key: &TileKey) {
+        if let Some((used, size)) = self.entries.remove(key) {
+            self.order.remove(&used);
+            self.bytes -= size;
+        }
+    }
+
+    /// Drop least recently used tiles until at most `max_bytes` remain
+    fn evict(&mut self, max_bytes: u64) -> Vec<TileKey> {
+        let mut evicted = Vec::new();
+        while self.bytes > max_bytes {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some((_, size)) = self.entries.remove(&key) {
+                self.bytes -= size;
+            }
+            evicted.push(key);
+        }
+        evicted
+    }
+}
+
+/// Size-bounded LRU tile cache on disk
+#[derive(Debug)]
+pub struct TileCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: Mutex<CacheIndex>,
+}
+
+impl TileCache {
+    /// Cache over `dir`, indexing the tiles already there oldest first
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut found = Vec::new();
+        for z in read_numbered_dir(&dir)?.into_iter().filter(|z| *z <= 30) {
+            let z_dir = dir.join(z.to_string());
+            for x in read_numbered_dir(&z_dir)? {
+                for entry in std::fs::read_dir(z_dir.join(x.to_string()))?.flatten() {
+                    let path = entry.path();
+                    if path.extension().and_then(|e| e.to_str()) != Some(TILE_EXTENSION) {
+                        continue;
+                    }
+                    let Some(y) = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok()) else {
+                        continue;
+                    };
+                    let Ok(metadata) = entry.metadata() else {
+                        continue;
+                    };
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    found.push((modified, TileKey { z: z as u8, x, y }, metadata.len()));
+                }
+            }
+        }
+        found.sort_by_key(|(modified, key, _)| (*modified, *key));
+
+        let mut index = CacheIndex::default();
+        for (_, key, size) in found {
+            index.insert(key, size);
+        }
+        Ok(Self {
+            dir,
+            max_bytes,
+            index: Mutex::new(index),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cached tile content, marking it recently used
+    pub async fn get(&self, key: TileKey) -> Option<Bytes> {
+        if !self.index.lock().touch(key) {
+            return None;
+        }
+        match tokio::fs::read(key.path(&self.dir)).await {
+            Ok(content) => Some(Bytes::from(content)),
+            Err(_) => {
+                // Removed behind our back
+                self.index.lock().remove(&key);
+                None
+            }
+        }
+    }
+
+    /// Store a tile, evicting the least recently used ones over the limit;
+    /// returns the number of tiles evicted
+    pub async fn put(&self, key: TileKey, content: &[u8]) -> std::io::Result<usize> {
+        let path = key.path(&self.dir);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Readers never see a partially written tile
+        let temp = path.with_extension(format!("{}.{}", TILE_EXTENSION, Uuid::new_v4().simple()));
+        tokio::fs::write(&temp, content).await?;
+        tokio::fs::rename(&temp, &path).await?;
+
+        let evicted = {
+            let mut index = self.index.lock();
+            index.insert(key, content.len() as u64);
+            index.evict(self.max_bytes)
+        };
+        for key in &evicted {
+            if let Err(e) = tokio::fs::remove_file(key.path(&self.dir)).await {
+                warn!("Failed to evict tile {}: {}", key, e);
+            }
+        }
+        Ok(evicted.len())
+    }
+
+    /// Number of cached tiles and their total size
+    pub fn usage(&self) -> (usize, u64) {
+        let index = self.index.lock();
+        (index.entries.len(), index.bytes)
+    }
+}
+
+/// Numeric subdirectory names of `dir`
+fn read_numbered_dir(dir: &Path) -> std::io::Result<Vec<u32>> {
+    Ok(std::fs::read_dir(dir)?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect())
+}
+
+// ============================================================================
+// SERVICE
+// ============================================================================
+
+/// Serves tiles from the cache, falling back to the upstream
+pub struct TileService {
+    config: TileConfig,
+    cache: TileCache,
+    fetcher: Arc<dyn TileFetcher>,
+    /// Upstream fetch in progress by tile; later requests wait on it
+    inflight: Mutex<HashMap<TileKey, Arc<tokio::sync::Mutex<()>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    upstream_errors: AtomicU64,
+}
+
+impl TileService {
+    pub fn new(config: TileConfig) -> std::io::Result<Self> {
+        let cache = TileCache::open(&config.cache_dir, config.max_cache_bytes)?;
+        let (tiles, bytes) = cache.usage();
+        info!(
+            "Tile cache at {} holds {} tiles ({} bytes){}",
+            cache.dir().display(),
+            tiles,
+            bytes,
+            if config.offline { ", offline" } else { "" }
+        );
+        let fetcher = Arc::new(HyperTileFetcher::new(config.timeout, config.user_agent.clone()));
+        Ok(Self {
+            config,
+            cache,
+            fetcher,
+            inflight: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            upstream_errors: AtomicU64::new(0),
+        })
+    }
+
+    /// Replace the upstream fetcher (tests)
+    #[allow(dead_code)]
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn TileFetcher>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// The tile at `key`, from the cache or fetched and cached
+    pub async fn tile(&self, key: TileKey) -> Result<Tile, TileError> {
+        if !key.is_valid(self.config.max_zoom) {
+            return Err(TileError::OutOfRange(key));
+        }
+        if let Some(content) = self.cache.get(key).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Tile {
+                content_type: content_type(&content),
+                content,
+                cached: true,
+            });
+        }
+
+        let upstream = match &self.config.upstream {
+            Some(upstream) if !self.config.offline => upstream,
+            _ => return Err(TileError::NotCached(key)),
+        };
+
+        let gate = self.inflight.lock().entry(key).or_default().clone();
+        let fetched = {
+            let _fetching = gate.lock().await;
+            self.fetch_and_cache(key, upstream).await
+        };
+        {
+            // The map's reference and ours: nobody else is waiting
+            let mut inflight = self.inflight.lock();
+            if Arc::strong_count(&gate) <= 2 {
+                inflight.remove(&key);
+            }
+        }
+        fetched
+    }
+
+    /// Fetch and cache a tile, unless a request it waited on already did
+    async fn fetch_and_cache(&self, key: TileKey, upstream: &str) -> Result<Tile, TileError> {
+        if let Some(content) = self.cache.get(key).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Tile {
+                content_type: content_type(&content),
+                content,
+                cached: true,
+            });
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let content = match self.fetcher.fetch(&key.url(upstream)).await {
+            Ok(Some(content)) => content,
+            Ok(None) => return Err(TileError::NotFound(key)),
+            Err(e) => {
+                self.upstream_errors.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+
+        // A tile that cannot be cached is still served
+        match self.cache.put(key, &content).await {
+            Ok(evicted) => {
+                self.evictions.fetch_add(evicted as u64, Ordering::Relaxed);
+            }
+            Err(e) => warn!("Failed to cache tile {}: {}", key, e),
+        }
+        Ok(Tile {
+            content_type: content_type(&content),
+            content,
+            cached: false,
+        })
+    }
+
+    pub fn stats(&self) -> TileStats {
+        let (tiles, bytes) = self.cache.usage();
+        TileStats {
+            upstream: self.config.upstream.as_deref().map(redact_url),
+            offline: self.config.offline,
+            tiles,
+            bytes,
+            max_bytes: self.config.max_cache_bytes,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            upstream_errors: self.upstream_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Image type from the tile's leading bytes
+fn content_type(content: &[u8]) -> &'static str {
+    match content {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        // Gzipped vector tiles
+        [0x1F, 0x8B, ..] => "application/vnd.mapbox-vector-tile",
+        _ => "application/octet-stream",
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100-byte PNG-looking tiles, counting fetches
+    #[derive(Default)]
+    struct FakeUpstream {
+        fetches: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl TileFetcher for FakeUpstream {
+        async fn fetch(&self, url: &str) -> Result<Option<Bytes>, TileError> {
+            self.fetches.lock().push(url.to_string());
+            tokio::task::yield_now().await;
+            if url.ends_with("/404.png") {
+                return Ok(None);
+            }
+            let mut content = vec![0x89, b'P', b'N', b'G'];
+            content.resize(100, 0);
+            Ok(Some(Bytes::from(content)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cache_evict_and_offline() {
+        let dir = std::env::temp_dir().join(format!("tile-cache-{}", Uuid::new_v4()));
+        let upstream = Arc::new(FakeUpstream::default());
+        let config = TileConfig {
+            upstream: Some("http://tiles.local/{z}/{x}/{y}.png".into()),
+            cache_dir: dir.clone(),
+            max_cache_bytes: 250,
+            ..Default::default()
+        };
+        let service = TileService::new(config.clone()).unwrap().with_fetcher(upstream.clone());
+        let key = |z, x, y| TileKey { z, x, y };
+
+        let first = service.tile(key(3, 4, 5)).await.unwrap();
+        assert!(!first.cached);
+        assert_eq!(first.content_type, "image/png");
+        assert_eq!(upstream.fetches.lock().as_slice(), ["http://tiles.local/3/4/5.png"]);
+        assert!(service.tile(key(3, 4, 5)).await.unwrap().cached);
+        assert_eq!(upstream.fetches.lock().len(), 1);
+
+        // Concurrent misses share one fetch
+        let (a, b) = tokio::join!(service.tile(key(3, 4, 6)), service.tile(key(3, 4, 6)));
+        assert!(a.unwrap().cached != b.unwrap().cached);
+        assert_eq!(upstream.fetches.lock().len(), 2);
+        assert!(service.inflight.lock().is_empty());
+
+        // Room for two tiles: touching 3/4/5 makes 3/4/6 the one evicted
+        service.tile(key(3, 4, 6)).await.unwrap();
+        service.tile(key(3, 4, 5)).await.unwrap();
+        service.tile(key(3, 4, 7)).await.unwrap();
+        let stats = service.stats();
+        assert_eq!((stats.tiles, stats.bytes, stats.evictions), (2, 200, 1));
+        assert_eq!((stats.hits, stats.misses), (4, 3));
+        assert_eq!(stats.upstream.as_deref(), Some("http://tiles.local/…"));
+        assert_eq!(redact_url("https://user:pw@tiles.example.com/{z}/{x}/{y}.png?key=s3cret"), "https://tiles.example.com/…");
+        assert!(!dir.join("3/4/6.tile").exists());
+
+        assert!(matches!(service.tile(key(3, 8, 0)).await, Err(TileError::OutOfRange(_))));
+        assert!(matches!(service.tile(key(20, 0, 0)).await, Err(TileError::OutOfRange(_))));
+        assert!(matches!(service.tile(key(10, 0, 404)).await, Err(TileError::NotFound(_))));
+
+        // Offline, a restarted proxy serves what it cached and nothing else
+        let offline = TileService::new(TileConfig { offline: true, ..config })
+            .unwrap()
+            .with_fetcher(upstream.clone());
+        assert_eq!(offline.stats().tiles, 2);
+        let fetches = upstream.fetches.lock().len();
+        assert!(offline.tile(key(3, 4, 7)).await.unwrap().cached);
+        assert!(matches!(offline.tile(key(3, 4, 6)).await, Err(TileError::NotCached(_))));
+        assert_eq!(upstream.fetches.lock().len(), fetches);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,400 @@
+//! Mission timeline
+//!
+//! Records mission lifecycle changes, waypoint arrivals, alerts and operator
+//! commands per mission, and serves them chronologically grouped by mission
+//! phase with cursor-based pagination.
+
+use drone_core::{
+    Alert, AlertSeverity, DroneId, Event, EventPayload, Mission, MissionId, MissionStatus,
+    WaypointEventType,
+};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Maximum entries kept per mission (oldest dropped first)
+const MAX_ENTRIES_PER_MISSION: usize = 10_000;
+
+/// Default and maximum page size
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Mission phase an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelinePhase {
+    Planning,
+    Active,
+    Completed,
+}
+
+impl From<MissionStatus> for TimelinePhase {
+    fn from(status: MissionStatus) -> Self {
+        match status {
+            MissionStatus::Planning => Self::Planning,
+            MissionStatus::Active | MissionStatus::Paused => Self::Active,
+            MissionStatus::Completed | MissionStatus::Aborted => Self::Completed,
+        }
+    }
+}
+
+/// Kind of timeline entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEntryKind {
+    Lifecycle,
+    WaypointArrival,
+    WaypointDeparture,
+    WaypointSkip,
+    Alert,
+    Command,
+}
+
+/// A single timeline entry
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    /// Monotonic sequence number, used as the pagination cursor
+    pub cursor: u64,
+    pub timestamp: DateTime<Utc>,
+    pub phase: TimelinePhase,
+    pub kind: TimelineEntryKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drone_id: Option<DroneId>,
+    pub summary: String,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub details: serde_json::Value,
+}
+
+/// Entries of one phase, in chronological order
+#[derive(Debug, Serialize)]
+pub struct TimelinePhaseGroup {
+    pub phase: TimelinePhase,
+    pub entries: Vec<TimelineEntry>,
+}
+
+/// One page of a mission timeline
+#[derive(Debug, Serialize)]
+pub struct TimelinePage {
+    pub mission_id: String,
+    pub phases: Vec<TimelinePhaseGroup>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    pub total_entries: usize,
+}
+
+/// Timeline query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Records timeline entries for every mission
+pub struct TimelineRecorder {
+    next_cursor: AtomicU64,
+    missions: DashMap<MissionId, RwLock<VecDeque<TimelineEntry>>>,
+    /// Last recorded severity per (drone, alert type), to skip repeats
+    alert_levels: DashMap<(DroneId, String), AlertSeverity>,
+}
+
+impl TimelineRecorder {
+    pub fn new() -> Self {
+        Self {
+            next_cursor: AtomicU64::new(1),
+            missions: DashMap::new(),
+            alert_levels: DashMap::new(),
+        }
+    }
+
+    /// Record a lifecycle change using the mission's current status
+    pub fn record_lifecycle(&self, mission: &Mission, summary: impl Into<String>) {
+        self.record(
+            mission,
+            Utc::now(),
+            TimelineEntryKind::Lifecycle,
+            None,
+            summary.into(),
+            serde_json::json!({ "status": mission.status }),
+        );
+    }
+
+    /// Record the mission's creation, if nothing was recorded for it yet
+    pub fn record_created(&self, mission: &Mission) {
+        if self.missions.contains_key(&mission.id) {
+            return;
+        }
+        self.record(
+            mission,
+            mission.created_at,
+            TimelineEntryKind::Lifecycle,
+            None,
+            format!("Mission {} created", mission.name),
+            serde_json::json!({
+                "status": mission.status,
+                "waypoints": mission.waypoints.len(),
+                "assigned_drones": mission.assigned_drones.len(),
+            }),
+        );
+    }
+
+    /// Record a tracker event if it belongs on the timeline
+    pub fn record_event(&self, mission: &Mission, event: &Event) {
+        match &event.payload {
+            EventPayload::Waypoint(waypoint) => {
+                let name = mission
+                    .waypoints
+                    .iter()
+                    .find(|wp| wp.id == waypoint.waypoint_id)
+                    .map(|wp| wp.name.clone())
+                    .unwrap_or_else(|| waypoint.waypoint_id.to_string());
+                let (kind, verb) = match waypoint.event_type {
+                    WaypointEventType::Departed => (TimelineEntryKind::WaypointDeparture, "departed"),
+                    WaypointEventType::Skipped => (TimelineEntryKind::WaypointSkip, "skipped"),
+                    _ => (TimelineEntryKind::WaypointArrival, "reached"),
+                };
+                self.record(
+                    mission,
+                    event.timestamp,
+                    kind,
+                    Some(waypoint.drone_id.clone()),
+                    format!("{} {} {}", waypoint.drone_id, verb, name),
+                    serde_json::json!({
+                        "waypoint_id": waypoint.waypoint_id,
+                        "position": waypoint.position,
+                    }),
+                );
+            }
+            EventPayload::Alert(alert) => self.record_alert(mission, &alert.alert),
+            EventPayload::ScheduledCommand(fired) => self.record(
+                mission,
+                event.timestamp,
+                TimelineEntryKind::Command,
+                fired.drone_id.clone(),
+                format!("Scheduled: {}", fired.description),
+                serde_json::json!({ "schedule_id": fired.schedule_id }),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Record an alert when its severity changes for the drone and alert type
+    pub fn record_alert(&self, mission: &Mission, alert: &Alert) {
+        if let Some(drone_id) = &alert.drone_id {
+            let key = (drone_id.clone(), format!("{:?}", alert.alert_type));
+            if self.alert_levels.insert(key, alert.severity) == Some(alert.severity) {
+                return;
+            }
+        }
+
+        self.record(
+            mission,
+            alert.created_at,
+            TimelineEntryKind::Alert,
+            alert.drone_id.clone(),
+            alert.message.clone(),
+            serde_json::json!({
+                "alert_id": alert.id,
+                "severity": alert.severity,
+                "alert_type": alert.alert_type,
+            }),
+        );
+    }
+
+    /// Record an operator command
+    pub fn record_command(
+        &self,
+        mission: &Mission,
+        drone_id: &DroneId,
+        command: &str,
+        params: &serde_json::Value,
+    ) {
+        self.record(
+            mission,
+            Utc::now(),
+            TimelineEntryKind::Command,
+            Some(drone_id.clone()),
+            format!("{} sent to {}", command, drone_id),
+            serde_json::json!({ "command": command, "params": params }),
+        );
+    }
+
+    fn record(
+        &self,
+        mission: &Mission,
+        timestamp: DateTime<Utc>,
+        kind: TimelineEntryKind,
+        drone_id: Option<DroneId>,
+        summary: String,
+        details: serde_json::Value,
+    ) {
+        let entry = TimelineEntry {
+            cursor: self.next_cursor.fetch_add(1, Ordering::Relaxed),
+            timestamp,
+            phase: mission.status.into(),
+            kind,
+            drone_id,
+            summary,
+            details,
+        };
+
+        let timeline = self.missions.entry(mission.id.clone()).or_default();
+        let mut entries = timeline.write();
+        entries.push_back(entry);
+        if entries.len() > MAX_ENTRIES_PER_MISSION {
+            entries.pop_front();
+        }
+    }
+
+    /// Whether anything was recorded for a mission
+    pub fn has_mission(&self, mission_id: &MissionId) -> bool {
+        self.missions.contains_key(mission_id)
+    }
+
+    /// Fetch entries after `cursor`, grouped by phase
+    pub fn page(&self, mission_id: &MissionId, cursor: Option<u64>, limit: usize) -> TimelinePage {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let after = cursor.unwrap_or(0);
+
+        let (mut entries, total_entries) = match self.missions.get(mission_id) {
+            Some(timeline) => {
+                let timeline = timeline.read();
+                let entries: Vec<TimelineEntry> = timeline
+                    .iter()
+                    .filter(|entry| entry.cursor > after)
+                    .take(limit + 1)
+                    .cloned()
+                    .collect();
+                (entries, timeline.len())
+            }
+            None => (Vec::new(), 0),
+        };
+
+        let next_cursor = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|entry| entry.cursor.to_string())
+        } else {
+            None
+        };
+
+        // Entries are recorded as they happen, but backdated ones (creation,
+        // alerts) may land slightly out of order
+        entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.cursor.cmp(&b.cursor)));
+
+        let mut phases: Vec<TimelinePhaseGroup> = Vec::new();
+        for entry in entries {
+            match phases.iter_mut().find(|group| group.phase == entry.phase) {
+                Some(group) => group.entries.push(entry),
+                None => phases.push(TimelinePhaseGroup {
+                    phase: entry.phase,
+                    entries: vec![entry],
+                }),
+            }
+        }
+        phases.sort_by_key(|group| group.phase as u8);
+
+        TimelinePage {
+            mission_id: mission_id.to_string(),
+            phases,
+            next_cursor,
+            total_entries,
+        }
+    }
+}
+
+impl Default for TimelineRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{AlertType, GeoPosition, Waypoint, WaypointId};
+
+    fn mission() -> Mission {
+        let mut mission = Mission::new("Timeline Test");
+        mission.add_waypoint(Waypoint::new("WP01", "Base Alpha", 34.5553, 69.2075));
+        mission
+    }
+
+    #[test]
+    fn test_entries_grouped_by_phase() {
+        let recorder = TimelineRecorder::new();
+        let mut mission = mission();
+        let drone_id = DroneId::new("REAPER-01");
+
+        recorder.record_created(&mission);
+        recorder.record_command(&mission, &drone_id, "arm", &serde_json::Value::Null);
+        mission.start();
+        recorder.record_lifecycle(&mission, "Mission started");
+        recorder.record_event(
+            &mission,
+            &Event::waypoint_reached(drone_id.clone(), WaypointId::new("WP01"), GeoPosition::default()),
+        );
+        mission.complete();
+        recorder.record_lifecycle(&mission, "Mission completed");
+
+        let page = recorder.page(&mission.id, None, DEFAULT_PAGE_SIZE);
+        let phases: Vec<_> = page.phases.iter().map(|g| (g.phase, g.entries.len())).collect();
+        assert_eq!(
+            phases,
+            vec![
+                (TimelinePhase::Planning, 2),
+                (TimelinePhase::Active, 2),
+                (TimelinePhase::Completed, 1),
+            ]
+        );
+        assert_eq!(page.phases[1].entries[1].kind, TimelineEntryKind::WaypointArrival);
+        assert_eq!(page.phases[1].entries[1].summary, "REAPER-01 reached Base Alpha");
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_cursor_pagination() {
+        let recorder = TimelineRecorder::new();
+        let mission = mission();
+        for i in 0..5 {
+            recorder.record_lifecycle(&mission, format!("entry {}", i));
+        }
+
+        let first = recorder.page(&mission.id, None, 2);
+        assert_eq!(first.phases[0].entries.len(), 2);
+        let cursor: u64 = first.next_cursor.unwrap().parse().unwrap();
+
+        let second = recorder.page(&mission.id, Some(cursor), 10);
+        assert_eq!(second.phases[0].entries.len(), 3);
+        assert_eq!(second.phases[0].entries[0].summary, "entry 2");
+        assert!(second.next_cursor.is_none());
+        assert_eq!(second.total_entries, 5);
+    }
+
+    #[test]
+    fn test_repeated_alerts_recorded_once() {
+        let recorder = TimelineRecorder::new();
+        let mission = mission();
+        let alert = || {
+            Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "Battery low: 25%")
+                .for_drone(DroneId::new("REAPER-01"))
+        };
+
+        recorder.record_alert(&mission, &alert());
+        recorder.record_alert(&mission, &alert());
+        recorder.record_alert(
+            &mission,
+            &Alert::new(AlertSeverity::Critical, AlertType::BatteryLow, "Battery critical: 12%")
+                .for_drone(DroneId::new("REAPER-01")),
+        );
+
+        assert_eq!(recorder.page(&mission.id, None, 10).total_entries, 2);
+    }
+}
@@ -0,0 +1,157 @@
+//! Command transports that need the API's HTTP client
+//!
+//! The tracker carries commands over the mesh and MAVLink itself; drones
+//! bound to an HTTP sidecar get theirs as a `DroneCommand` POSTed to the
+//! sidecar's `/command`.
+
+use crate::push::{HttpClient, HttpRequest, HyperClient};
+
+use async_trait::async_trait;
+use drone_core::{DroneCommand, DroneCommandType, DroneId, TransportBinding, TransportKind};
+use drone_tracker::{CommandOutcome, CommandTransport, TransportError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Path on the sidecar that takes commands
+pub const SIDECAR_COMMAND_PATH: &str = "/command";
+
+/// Command transport settings
+#[derive(Debug, Clone)]
+pub struct TransportConfig {
+    /// Local UDP address MAVLink frames are sent from
+    pub mavlink_bind: String,
+    /// Timeout for the call to an HTTP sidecar
+    pub sidecar_timeout: Duration,
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            mavlink_bind: "0.0.0.0:0".into(),
+            sidecar_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl TransportConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            mavlink_bind: std::env::var("MAVLINK_BIND").unwrap_or(defaults.mavlink_bind),
+            sidecar_timeout: std::env::var("SIDECAR_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.sidecar_timeout),
+        }
+    }
+}
+
+/// Commands POSTed as JSON to an HTTP sidecar next to the autopilot
+pub struct HttpSidecarTransport {
+    http: Arc<dyn HttpClient>,
+}
+
+impl HttpSidecarTransport {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            http: Arc::new(HyperClient::new(timeout)),
+        }
+    }
+
+    /// Replace the HTTP client (tests)
+    #[allow(dead_code)]
+    pub fn with_http(http: Arc<dyn HttpClient>) -> Self {
+        Self { http }
+    }
+}
+
+#[async_trait]
+impl CommandTransport for HttpSidecarTransport {
+    fn kind(&self) -> TransportKind {
+        TransportKind::HttpSidecar
+    }
+
+    async fn send(
+        &self,
+        drone_id: &DroneId,
+        binding: &TransportBinding,
+        command: &DroneCommandType,
+    ) -> Result<CommandOutcome, TransportError> {
+        let endpoint = binding.endpoint.as_deref().ok_or_else(|| TransportError::Binding {
+            kind: TransportKind::HttpSidecar,
+            message: "no endpoint".into(),
+        })?;
+        let body = serde_json::to_vec(&DroneCommand {
+            drone_id: drone_id.clone(),
+            command: command.clone(),
+        })
+        .map_err(|e| TransportError::delivery(TransportKind::HttpSidecar, e.to_string()))?;
+        let request = HttpRequest {
+            url: format!("{}{}", endpoint.trim_end_matches('/'), SIDECAR_COMMAND_PATH),
+            headers: Vec::new(),
+            body,
+        };
+
+        let response = self
+            .http
+            .post(request)
+            .await
+            .map_err(|e| TransportError::delivery(TransportKind::HttpSidecar, e.to_string()))?;
+        if !(200..300).contains(&response.status) {
+            return Err(TransportError::delivery(
+                TransportKind::HttpSidecar,
+                format!("sidecar answered {}: {}", response.status, response.body),
+            ));
+        }
+        Ok(CommandOutcome::Sent)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::push::{HttpResponse, PushError};
+    use parking_lot::Mutex;
+
+    struct Sidecar {
+        status: u16,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpClient for Sidecar {
+        async fn post(&self, request: HttpRequest) -> Result<HttpResponse, PushError> {
+            self.requests.lock().push(request);
+            Ok(HttpResponse {
+                status: self.status,
+                headers: Vec::new(),
+                body: "busy".into(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sidecar_posts_command() {
+        let drone_id = DroneId::new("REAPER-01");
+        let binding = TransportBinding::new(TransportKind::HttpSidecar).with_endpoint("http://10.0.0.5:8080/");
+        let sidecar = Arc::new(Sidecar { status: 202, requests: Mutex::new(Vec::new()) });
+        let transport = HttpSidecarTransport::with_http(sidecar.clone());
+
+        let outcome = transport.send(&drone_id, &binding, &DroneCommandType::ReturnToBase).await.unwrap();
+        assert_eq!(outcome, CommandOutcome::Sent);
+        let request = sidecar.requests.lock().pop().unwrap();
+        assert_eq!(request.url, "http://10.0.0.5:8080/command");
+        let sent: DroneCommand = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(sent.drone_id, drone_id);
+        assert!(matches!(sent.command, DroneCommandType::ReturnToBase));
+
+        let failing = HttpSidecarTransport::with_http(Arc::new(Sidecar { status: 503, requests: Mutex::new(Vec::new()) }));
+        let err = failing.send(&drone_id, &binding, &DroneCommandType::Pause).await.unwrap_err();
+        assert_eq!(err.to_string(), "http_sidecar delivery failed: sidecar answered 503: busy");
+    }
+}
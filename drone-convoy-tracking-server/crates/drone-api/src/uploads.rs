@@ -0,0 +1,352 @@
+//! Incremental mission upload
+//!
+//! Routes with thousands of waypoints do not fit in one request body. A
+//! client creates a draft with the mission's name, drones, corridor and
+//! formation, appends the route in numbered batches (`0`, `1`, ...) and then
+//! finalizes the draft with a checksum over the whole route. Finalizing runs
+//! the usual [`MissionBuilder`] checks and hands back the mission, which is
+//! only then stored or activated; the draft is removed once that succeeded
+//! ([`MissionUploads::finalized`]) and released for another try otherwise
+//! ([`MissionUploads::release`]).
+//!
+//! The checksum is the hex SHA-256 of one line per waypoint, in route order:
+//! `{id};{latitude:.7};{longitude:.7};{altitude:.2}\n`. Drafts left idle
+//! for [`DRAFT_TTL`] are discarded, and at most [`MAX_DRAFTS`] are open.
+
+use drone_core::{CorridorSpec, DroneId, Formation, Geofence, Mission, MissionBuildError, MissionBuilder, MissionId, Waypoint};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Most waypoints in one batch
+pub const MAX_BATCH_WAYPOINTS: usize = 500;
+
+/// Most waypoints in an uploaded route
+pub const MAX_UPLOAD_WAYPOINTS: usize = 10_000;
+
+/// How long a draft is kept without a new batch
+pub const DRAFT_TTL: Duration = Duration::from_secs(3600);
+
+/// Most drafts open at once
+pub const MAX_DRAFTS: usize = 32;
+
+/// Upload failures
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum UploadError {
+    #[error("no mission upload {0}")]
+    NotFound(MissionId),
+
+    #[error("expected batch {expected}, got batch {got}")]
+    OutOfSequence { expected: u32, got: u32 },
+
+    #[error("a batch must hold between 1 and {} waypoints", MAX_BATCH_WAYPOINTS)]
+    BatchSize,
+
+    #[error("a route is limited to {} waypoints", MAX_UPLOAD_WAYPOINTS)]
+    TooManyWaypoints,
+
+    #[error("checksum {got} does not match the uploaded route ({expected})")]
+    ChecksumMismatch { expected: String, got: String },
+
+    #[error("{} mission uploads are already open", MAX_DRAFTS)]
+    TooManyDrafts,
+
+    #[error("mission upload {0} is being finalized")]
+    Finalizing(MissionId),
+
+    #[error(transparent)]
+    Build(#[from] MissionBuildError),
+}
+
+/// Mission settings given when the draft is created
+#[derive(Debug, Clone, Deserialize)]
+pub struct DraftMission {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub assigned_drones: Vec<DroneId>,
+    #[serde(default)]
+    pub corridor: Option<Geofence>,
+    /// Generate the corridor from the uploaded route instead
+    #[serde(default)]
+    pub auto_corridor: Option<CorridorSpec>,
+    #[serde(default)]
+    pub formation: Option<Formation>,
+    #[serde(default)]
+    pub enforce_speed_limits: bool,
+}
+
+/// Where an upload stands
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UploadProgress {
+    pub mission_id: MissionId,
+    /// Sequence number the next batch must carry
+    pub next_sequence: u32,
+    pub waypoints: usize,
+    /// Checksum of the route uploaded so far
+    pub checksum: String,
+}
+
+#[derive(Debug)]
+struct Draft {
+    mission: DraftMission,
+    waypoints: Vec<Waypoint>,
+    next_sequence: u32,
+    hasher: Sha256,
+    updated_at: DateTime<Utc>,
+    /// Handed out by `finalize`, awaiting `finalized` or `release`
+    finalizing: bool,
+}
+
+impl Draft {
+    fn progress(&self, mission_id: &MissionId) -> UploadProgress {
+        UploadProgress {
+            mission_id: mission_id.clone(),
+            next_sequence: self.next_sequence,
+            waypoints: self.waypoints.len(),
+            checksum: hex::encode(self.hasher.clone().finalize()),
+        }
+    }
+}
+
+/// Add the checksum lines of `waypoints` to `hasher`
+fn hash_waypoints(hasher: &mut Sha256, waypoints: &[Waypoint]) {
+    for waypoint in waypoints {
+        let position = &waypoint.position;
+        hasher.update(format!(
+            "{};{:.7};{:.7};{:.2}\n",
+            waypoint.id, position.latitude, position.longitude, position.altitude
+        ));
+    }
+}
+
+/// Drafts being uploaded
+#[derive(Debug, Default)]
+pub struct MissionUploads {
+    drafts: RwLock<HashMap<MissionId, Draft>>,
+}
+
+impl MissionUploads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a draft, dropping drafts left idle for too long
+    pub fn create(&self, mission: DraftMission, now: DateTime<Utc>) -> Result<UploadProgress, UploadError> {
+        let ttl = chrono::Duration::from_std(DRAFT_TTL).unwrap_or(chrono::Duration::MAX);
+        let mission_id = MissionId::new();
+        let draft = Draft {
+            mission,
+            waypoints: Vec::new(),
+            next_sequence: 0,
+            hasher: Sha256::new(),
+            updated_at: now,
+            finalizing: false,
+        };
+        let progress = draft.progress(&mission_id);
+
+        let mut drafts = self.drafts.write();
+        drafts.retain(|_, draft| draft.finalizing || now - draft.updated_at < ttl);
+        if drafts.len() >= MAX_DRAFTS {
+            return Err(UploadError::TooManyDrafts);
+        }
+        drafts.insert(mission_id, draft);
+        Ok(progress)
+    }
+
+    /// Append batch `sequence` of the route
+    pub fn append(
+        &self,
+        mission_id: &MissionId,
+        sequence: u32,
+        waypoints: Vec<Waypoint>,
+        now: DateTime<Utc>,
+    ) -> Result<UploadProgress, UploadError> {
+        let mut drafts = self.drafts.write();
+        let draft = drafts
+            .get_mut(mission_id)
+            .ok_or_else(|| UploadError::NotFound(mission_id.clone()))?;
+        if draft.finalizing {
+            return Err(UploadError::Finalizing(mission_id.clone()));
+        }
+        if sequence != draft.next_sequence {
+            return Err(UploadError::OutOfSequence {
+                expected: draft.next_sequence,
+                got: sequence,
+            });
+        }
+        if waypoints.is_empty() || waypoints.len() > MAX_BATCH_WAYPOINTS {
+            return Err(UploadError::BatchSize);
+        }
+        if draft.waypoints.len() + waypoints.len() > MAX_UPLOAD_WAYPOINTS {
+            return Err(UploadError::TooManyWaypoints);
+        }
+
+        hash_waypoints(&mut draft.hasher, &waypoints);
+        draft.waypoints.extend(waypoints);
+        draft.next_sequence += 1;
+        draft.updated_at = now;
+        Ok(draft.progress(mission_id))
+    }
+
+    pub fn progress(&self, mission_id: &MissionId) -> Result<UploadProgress, UploadError> {
+        self.drafts
+            .read()
+            .get(mission_id)
+            .map(|draft| draft.progress(mission_id))
+            .ok_or_else(|| UploadError::NotFound(mission_id.clone()))
+    }
+
+    /// Check the route against `checksum` and build the mission; the draft
+    /// is kept if either fails so the client can fix it. On success the
+    /// draft is held until [`Self::finalized`] or [`Self::release`], so a
+    /// second finalize cannot build it twice.
+    pub fn finalize(&self, mission_id: &MissionId, checksum: &str) -> Result<Mission, UploadError> {
+        let mut drafts = self.drafts.write();
+        let draft = drafts
+            .get_mut(mission_id)
+            .ok_or_else(|| UploadError::NotFound(mission_id.clone()))?;
+        if draft.finalizing {
+            return Err(UploadError::Finalizing(mission_id.clone()));
+        }
+        let expected = hex::encode(draft.hasher.clone().finalize());
+        if !expected.eq_ignore_ascii_case(checksum.trim()) {
+            return Err(UploadError::ChecksumMismatch {
+                expected,
+                got: checksum.to_string(),
+            });
+        }
+
+        let settings = &draft.mission;
+        let mut builder = MissionBuilder::new(settings.name.clone())
+            .with_waypoints(draft.waypoints.iter().cloned())
+            .assign_drones(settings.assigned_drones.iter().cloned())
+            .enforce_speed_limits(settings.enforce_speed_limits);
+        if let Some(description) = &settings.description {
+            builder = builder.with_description(description.clone());
+        }
+        if let Some(corridor) = &settings.corridor {
+            builder = builder.with_corridor(corridor.clone());
+        }
+        if let Some(spec) = settings.auto_corridor {
+            builder = builder.with_auto_corridor(spec);
+        }
+        if let Some(formation) = &settings.formation {
+            builder = builder.with_formation(*formation);
+        }
+        let mut mission = builder.build()?;
+        mission.id = mission_id.clone();
+        draft.finalizing = true;
+        Ok(mission)
+    }
+
+    /// Drop a draft whose mission has been stored
+    pub fn finalized(&self, mission_id: &MissionId) {
+        self.drafts.write().remove(mission_id);
+    }
+
+    /// Give a draft back after its mission could not be stored, so the
+    /// client can finalize it again
+    pub fn release(&self, mission_id: &MissionId, now: DateTime<Utc>) {
+        if let Some(draft) = self.drafts.write().get_mut(mission_id) {
+            draft.finalizing = false;
+            draft.updated_at = now;
+        }
+    }
+
+    /// Abandon a draft
+    pub fn discard(&self, mission_id: &MissionId) -> Result<(), UploadError> {
+        self.drafts
+            .write()
+            .remove(mission_id)
+            .map(|_| ())
+            .ok_or_else(|| UploadError::NotFound(mission_id.clone()))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route_checksum(waypoints: &[Waypoint]) -> String {
+        let mut hasher = Sha256::new();
+        hash_waypoints(&mut hasher, waypoints);
+        hex::encode(hasher.finalize())
+    }
+
+    fn route(from: usize, to: usize) -> Vec<Waypoint> {
+        (from..to)
+            .map(|i| Waypoint::new(format!("WP{:04}", i), format!("Point {}", i), 34.0 + i as f64 * 0.001, 69.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_batches_in_sequence_then_finalize() {
+        let uploads = MissionUploads::new();
+        let now = Utc::now();
+        let draft = |name: &str| DraftMission {
+            name: name.into(),
+            description: None,
+            assigned_drones: vec![DroneId::new("REAPER-01")],
+            corridor: None,
+            auto_corridor: None,
+            formation: None,
+            enforce_speed_limits: false,
+        };
+        let id = uploads.create(draft("Long Haul"), now).unwrap().mission_id;
+
+        let progress = uploads.append(&id, 0, route(0, 500), now).unwrap();
+        assert_eq!((progress.next_sequence, progress.waypoints), (1, 500));
+        assert_eq!(uploads.append(&id, 0, route(0, 500), now), Err(UploadError::OutOfSequence { expected: 1, got: 0 }));
+        assert_eq!(uploads.append(&id, 1, route(500, 1001), now), Err(UploadError::BatchSize));
+        uploads.append(&id, 1, route(500, 1000), now).unwrap();
+        let progress = uploads.append(&id, 2, route(1000, 1200), now).unwrap();
+        assert_eq!(progress.checksum, route_checksum(&route(0, 1200)));
+
+        // A wrong checksum keeps the draft; the right one builds the mission
+        assert!(matches!(uploads.finalize(&id, "00"), Err(UploadError::ChecksumMismatch { .. })));
+        let mission = uploads.finalize(&id, &progress.checksum.to_uppercase()).unwrap();
+        assert_eq!(mission.id, id);
+        assert_eq!(mission.waypoints.len(), 1200);
+        assert_eq!(mission.waypoints[700].id.to_string(), "WP0700");
+
+        // The draft is held until the mission is stored, and back if that fails
+        assert!(matches!(uploads.finalize(&id, &progress.checksum), Err(UploadError::Finalizing(_))));
+        assert_eq!(uploads.append(&id, 3, route(1200, 1201), now), Err(UploadError::Finalizing(id.clone())));
+        uploads.release(&id, now);
+        uploads.finalize(&id, &progress.checksum).unwrap();
+        uploads.finalized(&id);
+        assert_eq!(uploads.progress(&id), Err(UploadError::NotFound(id.clone())));
+
+        // Builder checks run on finalize
+        let id = uploads.create(draft("Loop"), now).unwrap().mission_id;
+        let mut batch = route(0, 2);
+        batch.push(batch[0].clone());
+        uploads.append(&id, 0, batch.clone(), now).unwrap();
+        assert!(matches!(
+            uploads.finalize(&id, &route_checksum(&batch)),
+            Err(UploadError::Build(MissionBuildError::DuplicateWaypoint(_)))
+        ));
+
+        // Idle drafts are dropped when the next one starts
+        let later = now + chrono::Duration::from_std(DRAFT_TTL).unwrap();
+        uploads.create(draft("Next"), later).unwrap();
+        assert_eq!(uploads.progress(&id), Err(UploadError::NotFound(id.clone())));
+
+        // Open drafts are capped
+        for _ in 1..MAX_DRAFTS {
+            uploads.create(draft("More"), later).unwrap();
+        }
+        assert_eq!(uploads.create(draft("One too many"), later), Err(UploadError::TooManyDrafts));
+    }
+}
@@ -0,0 +1,353 @@
+//! Request body validation
+//!
+//! `ValidJson<T>` replaces `Json<T>` for POST/PUT bodies: malformed JSON is a
+//! 400, while bodies that parse but break a rule are rejected with 422 and a
+//! list of field-level errors.
+
+use crate::error::ApiError;
+
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::StatusCode,
+    Json,
+};
+use drone_core::{CvTuning, CvTuningError, Mission, ThresholdOverrides, TransportBinding, Waypoint};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Default global request body limit (bytes)
+pub const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Maximum length of identifiers (drone, waypoint, command names)
+pub const MAX_ID_LEN: usize = 64;
+
+/// Maximum length of a command transport endpoint
+pub const MAX_ENDPOINT_LEN: usize = 2048;
+
+/// A single rule violation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Collected rule violations for one request body
+#[derive(Debug, Default)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Require a non-empty string of at most `max` characters
+    pub fn check_len(&mut self, field: &str, value: &str, max: usize) {
+        if value.trim().is_empty() {
+            self.add(field, "must not be empty");
+        } else if value.chars().count() > max {
+            self.add(field, format!("must be at most {} characters", max));
+        }
+    }
+
+    /// Require a finite number within `[min, max]`
+    pub fn check_range(&mut self, field: &str, value: f64, min: f64, max: f64) {
+        if !value.is_finite() || value < min || value > max {
+            self.add(field, format!("must be between {} and {}", min, max));
+        }
+    }
+
+    pub fn check_latitude(&mut self, field: &str, value: f64) {
+        self.check_range(field, value, -90.0, 90.0);
+    }
+
+    pub fn check_longitude(&mut self, field: &str, value: f64) {
+        self.check_range(field, value, -180.0, 180.0);
+    }
+
+    /// Add another body's violations with their fields under `prefix`
+    pub fn nest(&mut self, prefix: &str, other: ValidationErrors) {
+        for error in other.0 {
+            self.add(format!("{}.{}", prefix, error.field), error.message);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[allow(dead_code)]
+    pub fn fields(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::Validation(errors.0)
+    }
+}
+
+/// Request bodies that can check their own field rules
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// JSON body extractor that also runs `Validate`
+pub struct ValidJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ValidJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        value.validate()?;
+        Ok(ValidJson(value))
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            // Well-formed JSON with the wrong shape or types
+            JsonRejection::JsonDataError(e) => ApiError::Validation(vec![FieldError {
+                field: "body".into(),
+                message: e.body_text(),
+            }]),
+            e if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                ApiError::PayloadTooLarge(e.body_text())
+            }
+            e => ApiError::BadRequest(e.body_text()),
+        }
+    }
+}
+
+impl Validate for Waypoint {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("id", &self.id.0, MAX_ID_LEN);
+        errors.check_len("name", &self.name, 128);
+        errors.check_latitude("position.latitude", self.position.latitude);
+        errors.check_longitude("position.longitude", self.position.longitude);
+        errors.check_range("position.altitude", self.position.altitude, -500.0, 20_000.0);
+        if let Some(seconds) = self.loiter_time_seconds {
+            errors.check_range("loiter_time_seconds", seconds as f64, 0.0, 3600.0);
+        }
+        if let Some(limit) = self.speed_limit_kmh {
+            errors.check_range("speed_limit_kmh", limit, 1.0, 1500.0);
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for Mission {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("name", &self.name, 128);
+        if let Some(description) = &self.description {
+            errors.check_len("description", description, 2048);
+        }
+        if self.waypoints.is_empty() {
+            errors.add("waypoints", "must not be empty");
+        }
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            if let Err(e) = waypoint.validate() {
+                errors.nest(&format!("waypoints[{}]", i), e);
+            }
+        }
+        if self.waypoints.first().is_some_and(|wp| wp.speed_limit_kmh.is_some()) {
+            errors.add("waypoints[0].speed_limit_kmh", "the first waypoint has no leg to limit");
+        }
+        for (i, drone_id) in self.assigned_drones.iter().enumerate() {
+            errors.check_len(&format!("assigned_drones[{}]", i), &drone_id.0, MAX_ID_LEN);
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for ThresholdOverrides {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let fields = [
+            ("battery_warning", self.battery_warning),
+            ("battery_critical", self.battery_critical),
+            ("fuel_warning", self.fuel_warning),
+            ("fuel_critical", self.fuel_critical),
+        ];
+        for (field, value) in fields {
+            if value.is_some_and(|v| v > 100) {
+                errors.add(field, "must be a percentage between 0 and 100");
+            }
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for TransportBinding {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(endpoint) = &self.endpoint {
+            errors.check_len("endpoint", endpoint, MAX_ENDPOINT_LEN);
+        }
+        if let Err(e) = TransportBinding::validate(self) {
+            errors.add("endpoint", e);
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for CvTuning {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Err(e) = CvTuning::validate(self) {
+            errors.add(cv_tuning_field(&e), e.to_string());
+        }
+        errors.into_result()
+    }
+}
+
+/// Parameter a CV tuning violation is reported against
+pub fn cv_tuning_field(error: &CvTuningError) -> &'static str {
+    match error {
+        CvTuningError::OutOfRange { field, .. } => field,
+        CvTuningError::RadiusOrder { .. } => "halo.min_radius",
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_checks() {
+        let mut errors = ValidationErrors::new();
+        errors.check_len("name", "REAPER-01", MAX_ID_LEN);
+        errors.check_latitude("latitude", 34.5);
+        assert!(errors.is_empty());
+
+        errors.check_len("name", "  ", MAX_ID_LEN);
+        errors.check_len("id", &"x".repeat(MAX_ID_LEN + 1), MAX_ID_LEN);
+        errors.check_latitude("latitude", 91.0);
+        errors.check_longitude("longitude", f64::NAN);
+
+        let fields: Vec<_> = errors.fields().iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "id", "latitude", "longitude"]);
+        assert!(errors.into_result().is_err());
+    }
+
+    #[test]
+    fn test_waypoint_coordinates() {
+        assert!(Waypoint::new("WP01", "Base Alpha", 34.5553, 69.2075).validate().is_ok());
+
+        let errors = Waypoint::new("WP01", "Base Alpha", 134.0, -200.0)
+            .validate()
+            .unwrap_err();
+        let fields: Vec<_> = errors.fields().iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["position.latitude", "position.longitude"]);
+    }
+
+    #[tokio::test]
+    async fn test_command_params_rejected_with_field_errors() {
+        use crate::handlers::CommandRequest;
+        use axum::body::Body;
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let ok = ValidJson::<CommandRequest>::from_request(
+            request(r#"{"command":"set_speed","params":{"speed":320}}"#),
+            &(),
+        )
+        .await;
+        assert!(ok.is_ok());
+
+        let err = ValidJson::<CommandRequest>::from_request(
+            request(r#"{"command":"set_speed","params":{"speed":-5,"altitude":10}}"#),
+            &(),
+        )
+        .await
+        .err()
+        .unwrap();
+        match err {
+            ApiError::Validation(fields) => {
+                let names: Vec<_> = fields.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(names, vec!["params.speed", "params.altitude"]);
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+
+        let malformed =
+            ValidJson::<CommandRequest>::from_request(request("{not json"), &()).await;
+        assert!(matches!(malformed, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_command_errors_nested_under_action() {
+        use crate::handlers::ScheduleCommandRequest;
+        use axum::body::Body;
+
+        let request = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let ok = ValidJson::<ScheduleCommandRequest>::from_request(
+            request(
+                r#"{"trigger":{"type":"waypoint","waypoint_id":"WP06"},
+                    "action":{"type":"set_formation","formation":"vee"}}"#,
+            ),
+            &(),
+        )
+        .await;
+        assert!(ok.is_ok());
+
+        let err = ValidJson::<ScheduleCommandRequest>::from_request(
+            request(
+                r#"{"trigger":{"type":"at","at":"2025-01-01T14:00:00Z"},
+                    "action":{"type":"drone_command","drone_id":"REAPER-01",
+                              "command":"set_speed","params":{"speed":-5}}}"#,
+            ),
+            &(),
+        )
+        .await
+        .err()
+        .unwrap();
+        match err {
+            ApiError::Validation(fields) => {
+                let names: Vec<_> = fields.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(names, vec!["action.params.speed"]);
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Distance benchmarks: Haversine vs the equirectangular fast path
+//!
+//! Run with `cargo bench -p drone-core --bench distance`.
+
+use drone_core::GeoPosition;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 2_000_000;
+
+/// Pairs like the tracker's waypoint and proximity checks: a few hundred meters to a few km
+fn short_pairs() -> Vec<(GeoPosition, GeoPosition)> {
+    (0..64)
+        .map(|i| {
+            let origin = GeoPosition::new(31.0 + i as f64 * 0.05, 65.0 + i as f64 * 0.07, 0.0);
+            (origin, origin.destination(0.2 + (i % 16) as f64 * 0.5, i as f64 * 23.0))
+        })
+        .collect()
+}
+
+fn long_pairs() -> Vec<(GeoPosition, GeoPosition)> {
+    short_pairs()
+        .into_iter()
+        .map(|(origin, _)| (origin, origin.destination(450.0, 200.0)))
+        .collect()
+}
+
+fn time(pairs: &[(GeoPosition, GeoPosition)], f: impl Fn(&GeoPosition, &GeoPosition) -> f64) -> Duration {
+    let start = Instant::now();
+    let mut total = 0.0;
+    for i in 0..ITERATIONS {
+        let (a, b) = &pairs[i as usize % pairs.len()];
+        total += f(black_box(a), black_box(b));
+    }
+    black_box(total);
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration, baseline: Duration) {
+    println!(
+        "{:<32} {:>7.2} ns/op  {:>5.2}x",
+        name,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64,
+        baseline.as_secs_f64() / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    for (label, pairs) in [("short (<10 km)", short_pairs()), ("long (450 km)", long_pairs())] {
+        println!("{}", label);
+        // Warm up caches and frequency scaling
+        time(&pairs, GeoPosition::haversine_distance_to);
+
+        let haversine = time(&pairs, GeoPosition::haversine_distance_to);
+        report("haversine_distance_to", haversine, haversine);
+        report("equirectangular_distance_to", time(&pairs, GeoPosition::equirectangular_distance_to), haversine);
+        report("distance_to", time(&pairs, GeoPosition::distance_to), haversine);
+    }
+}
@@ -0,0 +1,281 @@
+//! Mission builder
+//!
+//! Assembles a [`Mission`] from its parts and checks the result as a whole
+//! before handing it out: waypoint IDs are unique, positions and leg speed
+//! limits are sane, every waypoint lies inside the corridor and a formation
+//! has drones to fly it. Route metrics, and a corridor generated from the
+//! route, are computed once, on build.
+
+use crate::{CorridorSpec, DroneId, Formation, Geofence, Mission, Waypoint, WaypointId};
+
+use thiserror::Error;
+
+/// A mission configuration that cannot be flown
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum MissionBuildError {
+    #[error("mission name must not be empty")]
+    EmptyName,
+
+    #[error("mission needs at least one waypoint")]
+    NoWaypoints,
+
+    #[error("waypoint {0} appears more than once")]
+    DuplicateWaypoint(WaypointId),
+
+    #[error("waypoint {waypoint} has an invalid position: latitude={lat}, longitude={lng}")]
+    InvalidPosition { waypoint: WaypointId, lat: f64, lng: f64 },
+
+    #[error("waypoint {0} is first and has no leg to limit")]
+    SpeedLimitOnFirstWaypoint(WaypointId),
+
+    #[error("waypoint {waypoint} has an invalid speed limit: {kmh} km/h")]
+    InvalidSpeedLimit { waypoint: WaypointId, kmh: f64 },
+
+    #[error("drone {0} is assigned more than once")]
+    DuplicateDrone(DroneId),
+
+    #[error("corridor {0} needs at least three vertices")]
+    InvalidCorridor(String),
+
+    #[error("corridor width must be positive and the ceiling finite, got {width_m} m")]
+    InvalidCorridorSpec { width_m: f64 },
+
+    #[error("waypoint {0} lies outside the corridor")]
+    WaypointOutsideCorridor(WaypointId),
+
+    #[error("a formation needs at least two assigned drones")]
+    FormationWithoutDrones,
+}
+
+/// Fluent construction of a validated [`Mission`]
+#[derive(Debug, Clone)]
+pub struct MissionBuilder {
+    mission: Mission,
+}
+
+impl MissionBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            mission: Mission::new(name),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.mission.description = Some(description.into());
+        self
+    }
+
+    /// Append waypoints to the route, in order
+    pub fn with_waypoints(mut self, waypoints: impl IntoIterator<Item = Waypoint>) -> Self {
+        self.mission.waypoints.extend(waypoints);
+        self
+    }
+
+    /// Keep every waypoint inside `corridor`
+    pub fn with_corridor(mut self, corridor: Geofence) -> Self {
+        self.mission.corridor = Some(corridor);
+        self
+    }
+
+    /// Generate the corridor from the route, replacing any given one
+    pub fn with_auto_corridor(mut self, spec: CorridorSpec) -> Self {
+        self.mission.auto_corridor = Some(spec);
+        self
+    }
+
+    /// Assign drones to the mission, in convoy order
+    pub fn assign_drones(mut self, drones: impl IntoIterator<Item = DroneId>) -> Self {
+        self.mission.assigned_drones.extend(drones);
+        self
+    }
+
+    pub fn with_formation(mut self, formation: Formation) -> Self {
+        self.mission.formation = Some(formation);
+        self
+    }
+
+    pub fn enforce_speed_limits(mut self, enforce: bool) -> Self {
+        self.mission.enforce_speed_limits = enforce;
+        self
+    }
+
+    /// Validate the configuration and return the mission
+    pub fn build(self) -> Result<Mission, MissionBuildError> {
+        let mut mission = self.mission;
+        if mission.name.trim().is_empty() {
+            return Err(MissionBuildError::EmptyName);
+        }
+        if mission.waypoints.is_empty() {
+            return Err(MissionBuildError::NoWaypoints);
+        }
+
+        for (i, waypoint) in mission.waypoints.iter().enumerate() {
+            if mission.waypoints[..i].iter().any(|wp| wp.id == waypoint.id) {
+                return Err(MissionBuildError::DuplicateWaypoint(waypoint.id.clone()));
+            }
+            if !waypoint.position.is_valid() {
+                return Err(MissionBuildError::InvalidPosition {
+                    waypoint: waypoint.id.clone(),
+                    lat: waypoint.position.latitude,
+                    lng: waypoint.position.longitude,
+                });
+            }
+            match waypoint.speed_limit_kmh {
+                Some(_) if i == 0 => return Err(MissionBuildError::SpeedLimitOnFirstWaypoint(waypoint.id.clone())),
+                // NaN fails the comparison, so it is rejected too
+                Some(kmh) if !(kmh > 0.0 && kmh.is_finite()) => {
+                    return Err(MissionBuildError::InvalidSpeedLimit {
+                        waypoint: waypoint.id.clone(),
+                        kmh,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        for (i, drone_id) in mission.assigned_drones.iter().enumerate() {
+            if mission.assigned_drones[..i].contains(drone_id) {
+                return Err(MissionBuildError::DuplicateDrone(drone_id.clone()));
+            }
+        }
+
+        if let Some(spec) = &mission.auto_corridor {
+            if !spec.is_valid() {
+                return Err(MissionBuildError::InvalidCorridorSpec { width_m: spec.width_m });
+            }
+        }
+        mission.refresh_route();
+
+        if let Some(corridor) = &mission.corridor {
+            if corridor.vertices.len() < 3 {
+                return Err(MissionBuildError::InvalidCorridor(corridor.name.clone()));
+            }
+            if let Some(outside) = mission.waypoints.iter().find(|wp| !corridor.contains(&wp.position)) {
+                return Err(MissionBuildError::WaypointOutsideCorridor(outside.id.clone()));
+            }
+        }
+
+        if mission.formation.is_some() && mission.assigned_drones.len() < 2 {
+            return Err(MissionBuildError::FormationWithoutDrones);
+        }
+
+        Ok(mission)
+    }
+}
+
+/// Re-check an existing mission, keeping its ID and timestamps
+impl From<Mission> for MissionBuilder {
+    fn from(mission: Mission) -> Self {
+        Self { mission }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GeoPosition;
+
+    fn route() -> Vec<Waypoint> {
+        vec![
+            Waypoint::new("WP01", "Start", 34.50, 69.20),
+            Waypoint::new("WP02", "Middle", 34.60, 69.10),
+            Waypoint::new("WP03", "End", 34.70, 69.00),
+        ]
+    }
+
+    fn drones(n: usize) -> Vec<DroneId> {
+        (1..=n).map(|i| DroneId::new(format!("REAPER-{:02}", i))).collect()
+    }
+
+    #[test]
+    fn test_builds_valid_missions_and_rejects_invalid_ones() {
+        let corridor = Geofence::new(
+            "Route",
+            vec![
+                GeoPosition::new(34.4, 69.3, 0.0),
+                GeoPosition::new(34.8, 69.3, 0.0),
+                GeoPosition::new(34.8, 68.9, 0.0),
+                GeoPosition::new(34.4, 68.9, 0.0),
+            ],
+        );
+        let mission = MissionBuilder::new("Convoy")
+            .with_description("Escort")
+            .with_waypoints(route())
+            .with_corridor(corridor.clone())
+            .assign_drones(drones(3))
+            .with_formation(Formation::Vee)
+            .build()
+            .unwrap();
+        assert_eq!(mission.waypoints.len(), 3);
+        assert_eq!(mission.assigned_drones.len(), 3);
+        assert_eq!(mission.formation, Some(Formation::Vee));
+        assert!(mission.route.is_current(&mission.waypoints));
+
+        // Re-validating keeps the mission's identity
+        let id = mission.id.clone();
+        assert_eq!(MissionBuilder::from(mission).build().unwrap().id, id);
+
+        let base = || MissionBuilder::new("Convoy").with_waypoints(route());
+        assert_eq!(MissionBuilder::new(" ").with_waypoints(route()).build().unwrap_err(), MissionBuildError::EmptyName);
+        assert_eq!(MissionBuilder::new("Convoy").build().unwrap_err(), MissionBuildError::NoWaypoints);
+        assert_eq!(
+            base().with_waypoints([Waypoint::new("WP02", "Again", 34.7, 69.0)]).build().unwrap_err(),
+            MissionBuildError::DuplicateWaypoint(WaypointId::new("WP02"))
+        );
+        assert!(matches!(
+            base().with_waypoints([Waypoint::new("WP04", "Off", 95.0, 69.0)]).build(),
+            Err(MissionBuildError::InvalidPosition { .. })
+        ));
+
+        let mut limited = route();
+        limited[0].speed_limit_kmh = Some(80.0);
+        assert!(matches!(
+            MissionBuilder::new("Convoy").with_waypoints(limited.clone()).build(),
+            Err(MissionBuildError::SpeedLimitOnFirstWaypoint(_))
+        ));
+        limited[0].speed_limit_kmh = None;
+        limited[1].speed_limit_kmh = Some(f64::NAN);
+        assert!(matches!(
+            MissionBuilder::new("Convoy").with_waypoints(limited).build(),
+            Err(MissionBuildError::InvalidSpeedLimit { .. })
+        ));
+
+        assert_eq!(
+            base().assign_drones(drones(2)).assign_drones(drones(1)).build().unwrap_err(),
+            MissionBuildError::DuplicateDrone(DroneId::new("REAPER-01"))
+        );
+        assert_eq!(
+            base().with_corridor(Geofence::new("Thin", vec![])).build().unwrap_err(),
+            MissionBuildError::InvalidCorridor("Thin".into())
+        );
+        assert_eq!(
+            base()
+                .with_waypoints([Waypoint::new("WP04", "Far", 35.5, 68.0)])
+                .with_corridor(corridor)
+                .build()
+                .unwrap_err(),
+            MissionBuildError::WaypointOutsideCorridor(WaypointId::new("WP04"))
+        );
+        assert_eq!(
+            base().assign_drones(drones(1)).with_formation(Formation::Line).build().unwrap_err(),
+            MissionBuildError::FormationWithoutDrones
+        );
+
+        // A generated corridor replaces the given one and follows the route
+        let mission = base()
+            .with_waypoints([Waypoint::new("WP04", "Far", 35.5, 68.0)])
+            .with_corridor(Geofence::new("Thin", vec![]))
+            .with_auto_corridor(CorridorSpec::new(2000.0))
+            .build()
+            .unwrap();
+        assert_eq!(mission.corridor.as_ref().unwrap().name, "Convoy corridor");
+        assert_eq!(
+            base().with_auto_corridor(CorridorSpec::new(0.0)).build().unwrap_err(),
+            MissionBuildError::InvalidCorridorSpec { width_m: 0.0 }
+        );
+    }
+}
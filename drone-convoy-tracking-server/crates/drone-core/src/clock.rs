@@ -0,0 +1,202 @@
+//! Simulation clock
+//!
+//! Simulated time runs at an adjustable multiple of wall-clock time and can
+//! be paused and stepped. Everything that stamps or compares simulated
+//! events (telemetry timestamps, loiter timers, ETAs) reads the same clock,
+//! so they stay consistent when a demo is fast-forwarded.
+
+use crate::error::{CoreError, CoreResult};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Slowest supported time scale
+pub const MIN_TIME_SCALE: f64 = 0.1;
+
+/// Fastest supported time scale
+pub const MAX_TIME_SCALE: f64 = 100.0;
+
+#[derive(Debug)]
+struct ClockAnchor {
+    /// Simulated time at `wall`
+    sim: DateTime<Utc>,
+    wall: Instant,
+    scale: f64,
+    paused: bool,
+}
+
+impl ClockAnchor {
+    fn now(&self) -> DateTime<Utc> {
+        if self.paused {
+            return self.sim;
+        }
+        let elapsed = self.wall.elapsed().mul_f64(self.scale);
+        self.sim + chrono::Duration::from_std(elapsed).unwrap_or_default()
+    }
+
+    /// Restart elapsed-time accounting from the current instant
+    fn rebase(&mut self) {
+        self.sim = self.now();
+        self.wall = Instant::now();
+    }
+}
+
+/// Current clock settings
+#[derive(Debug, Clone, Serialize)]
+pub struct ClockStatus {
+    /// Current simulated time
+    pub now: DateTime<Utc>,
+    pub scale: f64,
+    pub paused: bool,
+    /// Simulated time minus wall-clock time
+    pub offset_seconds: f64,
+}
+
+/// Scalable, pausable simulation clock; starts at wall-clock time, 1x
+#[derive(Debug)]
+pub struct SimulationClock {
+    anchor: Mutex<ClockAnchor>,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        Self {
+            anchor: Mutex::new(ClockAnchor {
+                sim: Utc::now(),
+                wall: Instant::now(),
+                scale: 1.0,
+                paused: false,
+            }),
+        }
+    }
+
+    /// Virtual clock frozen at `start`; time only moves through `step`
+    ///
+    /// Used by deterministic simulation runs, where simulated time must not
+    /// depend on how fast the host executes the run.
+    pub fn virtual_at(start: DateTime<Utc>) -> Self {
+        Self {
+            anchor: Mutex::new(ClockAnchor {
+                sim: start,
+                wall: Instant::now(),
+                scale: 1.0,
+                paused: true,
+            }),
+        }
+    }
+
+    fn anchor(&self) -> std::sync::MutexGuard<'_, ClockAnchor> {
+        self.anchor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Current simulated time
+    pub fn now(&self) -> DateTime<Utc> {
+        self.anchor().now()
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.anchor().scale
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.anchor().paused
+    }
+
+    /// Change the time scale without a jump in simulated time
+    pub fn set_scale(&self, scale: f64) -> CoreResult<()> {
+        if !(MIN_TIME_SCALE..=MAX_TIME_SCALE).contains(&scale) {
+            return Err(CoreError::Configuration(format!(
+                "time scale must be between {} and {}",
+                MIN_TIME_SCALE, MAX_TIME_SCALE
+            )));
+        }
+        let mut anchor = self.anchor();
+        anchor.rebase();
+        anchor.scale = scale;
+        Ok(())
+    }
+
+    /// Freeze simulated time
+    pub fn pause(&self) {
+        let mut anchor = self.anchor();
+        anchor.rebase();
+        anchor.paused = true;
+    }
+
+    /// Resume from where simulated time was frozen
+    pub fn resume(&self) {
+        let mut anchor = self.anchor();
+        anchor.wall = Instant::now();
+        anchor.paused = false;
+    }
+
+    /// Advance simulated time by `duration` (typically while paused)
+    pub fn step(&self, duration: Duration) {
+        let mut anchor = self.anchor();
+        anchor.sim += chrono::Duration::from_std(duration).unwrap_or_default();
+    }
+
+    pub fn status(&self) -> ClockStatus {
+        let anchor = self.anchor();
+        let now = anchor.now();
+        ClockStatus {
+            now,
+            scale: anchor.scale,
+            paused: anchor.paused,
+            offset_seconds: (now - Utc::now()).num_milliseconds() as f64 / 1000.0,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_step_and_scale() {
+        let clock = SimulationClock::new();
+        clock.pause();
+        let frozen = clock.now();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(clock.now(), frozen);
+
+        clock.step(Duration::from_secs(90));
+        assert_eq!(clock.now(), frozen + chrono::Duration::seconds(90));
+
+        assert!(clock.set_scale(0.05).is_err());
+        assert!(clock.set_scale(250.0).is_err());
+        clock.set_scale(100.0).unwrap();
+        assert_eq!(clock.now(), frozen + chrono::Duration::seconds(90));
+
+        // 20 ms of wall time is 2 s of simulated time at 100x
+        clock.resume();
+        std::thread::sleep(Duration::from_millis(20));
+        let elapsed = clock.now() - frozen - chrono::Duration::seconds(90);
+        assert!(elapsed >= chrono::Duration::seconds(2));
+        assert!(clock.status().offset_seconds > 90.0);
+    }
+
+    #[test]
+    fn test_virtual_clock_only_moves_by_step() {
+        let start = DateTime::parse_from_rfc3339("2023-11-14T22:13:20Z").unwrap().with_timezone(&Utc);
+        let clock = SimulationClock::virtual_at(start);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), start);
+        assert!(clock.is_paused());
+
+        clock.step(Duration::from_millis(500));
+        assert_eq!(clock.now(), start + chrono::Duration::milliseconds(500));
+    }
+}
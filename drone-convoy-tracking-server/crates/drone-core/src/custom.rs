@@ -0,0 +1,507 @@
+//! Integrator-defined events
+//!
+//! Integrators publish their own event types through the bus without adding
+//! variants here. A custom type is named `<namespace>.<NAME>` (for example
+//! `acme.SENSOR_POD_STATUS`) and must have a JSON Schema registered before
+//! events of that type are accepted. `EventSchema` compiles the subset of
+//! JSON Schema we validate against; keywords outside that subset are rejected
+//! at registration rather than silently ignored.
+
+use crate::DroneId;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+/// Longest accepted custom event type, namespace included
+pub const MAX_CUSTOM_TYPE_LEN: usize = 64;
+
+/// Namespace kept for event types defined in this crate
+pub const RESERVED_NAMESPACE: &str = "core";
+
+/// Largest custom event value, serialized
+pub const MAX_CUSTOM_VALUE_BYTES: usize = 16 * 1024;
+
+/// Deepest schema nesting accepted at registration
+const MAX_SCHEMA_DEPTH: usize = 16;
+
+/// Keywords that carry no validation meaning and are accepted as-is
+const ANNOTATIONS: &[&str] = &["$schema", "$id", "$comment", "title", "description", "default", "examples"];
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// An event whose type and payload are defined by an integrator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomEvent {
+    /// Namespaced type, e.g. `acme.SENSOR_POD_STATUS`
+    pub event_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drone_id: Option<DroneId>,
+    /// Payload, already validated against the type's schema
+    pub value: Value,
+}
+
+/// A custom event or schema that cannot be accepted
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CustomEventError {
+    #[error("invalid custom event type {0:?}: use <namespace>.<NAME>, e.g. acme.SENSOR_POD_STATUS")]
+    InvalidType(String),
+
+    #[error("namespace \"{RESERVED_NAMESPACE}\" is reserved: {0}")]
+    ReservedNamespace(String),
+
+    #[error("invalid schema at {path}: {message}")]
+    InvalidSchema { path: String, message: String },
+
+    #[error("no schema registered for {0}")]
+    UnknownType(String),
+
+    #[error("value is {size} bytes, limit is {MAX_CUSTOM_VALUE_BYTES}")]
+    TooLarge { size: usize },
+
+    #[error("{event_type} does not match its schema: {}", join(.violations))]
+    Invalid {
+        event_type: String,
+        violations: Vec<SchemaViolation>,
+    },
+}
+
+/// Where a value breaks its schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+fn join(violations: &[SchemaViolation]) -> String {
+    violations.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; ")
+}
+
+/// Check a custom event type name
+pub fn validate_type_name(name: &str) -> Result<(), CustomEventError> {
+    let invalid = || CustomEventError::InvalidType(name.to_string());
+    if name.len() > MAX_CUSTOM_TYPE_LEN {
+        return Err(invalid());
+    }
+    let (namespace, local) = name.split_once('.').ok_or_else(invalid)?;
+    let namespace_ok = namespace.starts_with(|c: char| c.is_ascii_lowercase())
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    let local_ok = local.starts_with(|c: char| c.is_ascii_uppercase())
+        && local
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if !namespace_ok || !local_ok {
+        return Err(invalid());
+    }
+    if namespace == RESERVED_NAMESPACE {
+        return Err(CustomEventError::ReservedNamespace(name.to_string()));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// SCHEMAS
+// ============================================================================
+
+/// A compiled JSON Schema
+#[derive(Debug, Clone)]
+pub struct EventSchema {
+    source: Value,
+    root: Node,
+}
+
+impl EventSchema {
+    /// Compile a schema document, rejecting keywords we do not validate
+    pub fn compile(source: Value) -> Result<Self, CustomEventError> {
+        let root = Node::compile(&source, "", 0)?;
+        Ok(Self { source, root })
+    }
+
+    /// The document the schema was compiled from
+    pub fn source(&self) -> &Value {
+        &self.source
+    }
+
+    /// Every place `value` breaks the schema
+    pub fn validate(&self, value: &Value) -> Result<(), Vec<SchemaViolation>> {
+        let mut violations = Vec::new();
+        self.root.check(value, "", &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Validate a whole event, size limit included
+    pub fn check_event(&self, event: &CustomEvent) -> Result<(), CustomEventError> {
+        let size = serde_json::to_vec(&event.value).map(|b| b.len()).unwrap_or(usize::MAX);
+        if size > MAX_CUSTOM_VALUE_BYTES {
+            return Err(CustomEventError::TooLarge { size });
+        }
+        self.validate(&event.value).map_err(|violations| CustomEventError::Invalid {
+            event_type: event.event_type.clone(),
+            violations,
+        })
+    }
+}
+
+/// Serializes as the source document
+impl Serialize for EventSchema {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.source.serialize(serializer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JsonType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => Self::Null,
+            "boolean" => Self::Boolean,
+            "integer" => Self::Integer,
+            "number" => Self::Number,
+            "string" => Self::String,
+            "array" => Self::Array,
+            "object" => Self::Object,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match (self, value) {
+            (Self::Null, Value::Null)
+            | (Self::Boolean, Value::Bool(_))
+            | (Self::Number, Value::Number(_))
+            | (Self::String, Value::String(_))
+            | (Self::Array, Value::Array(_))
+            | (Self::Object, Value::Object(_)) => true,
+            (Self::Integer, Value::Number(n)) => {
+                n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+            }
+            _ => false,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+enum Additional {
+    #[default]
+    Allow,
+    Deny,
+    Schema(Box<Node>),
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// `false` schema: nothing matches
+    reject: bool,
+    types: Option<Vec<JsonType>>,
+    properties: BTreeMap<String, Node>,
+    required: Vec<String>,
+    additional: Additional,
+    items: Option<Box<Node>>,
+    allowed: Option<Vec<Value>>,
+    constant: Option<Value>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+}
+
+fn schema_error(path: &str, message: impl Into<String>) -> CustomEventError {
+    CustomEventError::InvalidSchema {
+        path: if path.is_empty() { "/".into() } else { path.to_string() },
+        message: message.into(),
+    }
+}
+
+fn number(path: &str, key: &str, value: &Value) -> Result<f64, CustomEventError> {
+    value
+        .as_f64()
+        .ok_or_else(|| schema_error(path, format!("{} must be a number", key)))
+}
+
+fn count(path: &str, key: &str, value: &Value) -> Result<usize, CustomEventError> {
+    value
+        .as_u64()
+        .map(|n| n as usize)
+        .ok_or_else(|| schema_error(path, format!("{} must be a non-negative integer", key)))
+}
+
+impl Node {
+    fn compile(schema: &Value, path: &str, depth: usize) -> Result<Self, CustomEventError> {
+        if depth > MAX_SCHEMA_DEPTH {
+            return Err(schema_error(path, format!("nested deeper than {} levels", MAX_SCHEMA_DEPTH)));
+        }
+        let map = match schema {
+            Value::Bool(accept) => return Ok(Self { reject: !accept, ..Self::default() }),
+            Value::Object(map) => map,
+            _ => return Err(schema_error(path, "schema must be an object or a boolean")),
+        };
+
+        let mut node = Self::default();
+        for (key, value) in map {
+            match key.as_str() {
+                "type" => node.types = Some(Self::compile_types(value, path)?),
+                "properties" => node.properties = Self::compile_properties(value, path, depth)?,
+                "required" => {
+                    node.required = value
+                        .as_array()
+                        .and_then(|names| names.iter().map(|n| n.as_str().map(String::from)).collect())
+                        .ok_or_else(|| schema_error(path, "required must be an array of strings"))?;
+                }
+                "additionalProperties" => {
+                    node.additional = match value {
+                        Value::Bool(true) => Additional::Allow,
+                        Value::Bool(false) => Additional::Deny,
+                        other => Additional::Schema(Box::new(Self::compile(
+                            other,
+                            &format!("{}/additionalProperties", path),
+                            depth + 1,
+                        )?)),
+                    };
+                }
+                "items" => {
+                    node.items = Some(Box::new(Self::compile(value, &format!("{}/items", path), depth + 1)?));
+                }
+                "enum" => {
+                    node.allowed = Some(
+                        value
+                            .as_array()
+                            .cloned()
+                            .ok_or_else(|| schema_error(path, "enum must be an array"))?,
+                    );
+                }
+                "const" => node.constant = Some(value.clone()),
+                "minimum" => node.minimum = Some(number(path, key, value)?),
+                "maximum" => node.maximum = Some(number(path, key, value)?),
+                "exclusiveMinimum" => node.exclusive_minimum = Some(number(path, key, value)?),
+                "exclusiveMaximum" => node.exclusive_maximum = Some(number(path, key, value)?),
+                "minLength" => node.min_length = Some(count(path, key, value)?),
+                "maxLength" => node.max_length = Some(count(path, key, value)?),
+                "minItems" => node.min_items = Some(count(path, key, value)?),
+                "maxItems" => node.max_items = Some(count(path, key, value)?),
+                k if ANNOTATIONS.contains(&k) => {}
+                other => return Err(schema_error(path, format!("unsupported keyword {:?}", other))),
+            }
+        }
+        Ok(node)
+    }
+
+    fn compile_types(value: &Value, path: &str) -> Result<Vec<JsonType>, CustomEventError> {
+        let names: Vec<&Value> = match value {
+            Value::Array(names) => names.iter().collect(),
+            single => vec![single],
+        };
+        names
+            .into_iter()
+            .map(|n| {
+                n.as_str()
+                    .and_then(JsonType::parse)
+                    .ok_or_else(|| schema_error(path, format!("unknown type {}", n)))
+            })
+            .collect()
+    }
+
+    fn compile_properties(
+        value: &Value,
+        path: &str,
+        depth: usize,
+    ) -> Result<BTreeMap<String, Node>, CustomEventError> {
+        let props: &Map<String, Value> = value
+            .as_object()
+            .ok_or_else(|| schema_error(path, "properties must be an object"))?;
+        props
+            .iter()
+            .map(|(name, schema)| {
+                let child = Self::compile(schema, &format!("{}/properties/{}", path, name), depth + 1)?;
+                Ok((name.clone(), child))
+            })
+            .collect()
+    }
+
+    fn check(&self, value: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+        let mut fail = |message: String| {
+            out.push(SchemaViolation {
+                path: path.to_string(),
+                message,
+            })
+        };
+
+        if self.reject {
+            fail("no value is allowed here".into());
+            return;
+        }
+        if let Some(types) = &self.types {
+            if !types.iter().any(|t| t.matches(value)) {
+                let names: Vec<_> = types.iter().map(|t| t.name()).collect();
+                fail(format!("expected {}", names.join(" or ")));
+                // Remaining keywords assume the declared type
+                return;
+            }
+        }
+        if let Some(allowed) = &self.allowed {
+            if !allowed.contains(value) {
+                fail(format!("{} is not one of the allowed values", value));
+            }
+        }
+        if let Some(constant) = &self.constant {
+            if constant != value {
+                fail(format!("must equal {}", constant));
+            }
+        }
+
+        match value {
+            Value::Number(n) => {
+                let n = n.as_f64().unwrap_or(f64::NAN);
+                if self.minimum.is_some_and(|min| n < min) {
+                    fail(format!("{} is below the minimum {}", n, self.minimum.unwrap_or_default()));
+                }
+                if self.maximum.is_some_and(|max| n > max) {
+                    fail(format!("{} is above the maximum {}", n, self.maximum.unwrap_or_default()));
+                }
+                if self.exclusive_minimum.is_some_and(|min| n <= min) {
+                    fail(format!("{} must be above {}", n, self.exclusive_minimum.unwrap_or_default()));
+                }
+                if self.exclusive_maximum.is_some_and(|max| n >= max) {
+                    fail(format!("{} must be below {}", n, self.exclusive_maximum.unwrap_or_default()));
+                }
+            }
+            Value::String(s) => {
+                let len = s.chars().count();
+                if self.min_length.is_some_and(|min| len < min) {
+                    fail(format!("shorter than {} characters", self.min_length.unwrap_or_default()));
+                }
+                if self.max_length.is_some_and(|max| len > max) {
+                    fail(format!("longer than {} characters", self.max_length.unwrap_or_default()));
+                }
+            }
+            Value::Array(items) => {
+                if self.min_items.is_some_and(|min| items.len() < min) {
+                    fail(format!("fewer than {} items", self.min_items.unwrap_or_default()));
+                }
+                if self.max_items.is_some_and(|max| items.len() > max) {
+                    fail(format!("more than {} items", self.max_items.unwrap_or_default()));
+                }
+                if let Some(item_schema) = &self.items {
+                    for (i, item) in items.iter().enumerate() {
+                        item_schema.check(item, &format!("{}/{}", path, i), out);
+                    }
+                }
+            }
+            Value::Object(fields) => {
+                for name in &self.required {
+                    if !fields.contains_key(name) {
+                        fail(format!("missing required property {:?}", name));
+                    }
+                }
+                for (name, field) in fields {
+                    let child_path = format!("{}/{}", path, name);
+                    match (self.properties.get(name), &self.additional) {
+                        (Some(schema), _) => schema.check(field, &child_path, out),
+                        (None, Additional::Allow) => {}
+                        (None, Additional::Deny) => out.push(SchemaViolation {
+                            path: child_path,
+                            message: "property is not allowed".into(),
+                        }),
+                        (None, Additional::Schema(schema)) => schema.check(field, &child_path, out),
+                    }
+                }
+            }
+            Value::Null | Value::Bool(_) => {}
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_validation() {
+        assert!(validate_type_name("acme.SENSOR_POD_STATUS").is_ok());
+        assert!(validate_type_name("SENSOR_POD_STATUS").is_err());
+        assert!(validate_type_name("acme.sensor").is_err());
+        assert!(matches!(
+            validate_type_name("core.DRONE_POSITION"),
+            Err(CustomEventError::ReservedNamespace(_))
+        ));
+
+        assert!(matches!(
+            EventSchema::compile(json!({ "type": "object", "patternProperties": {} })),
+            Err(CustomEventError::InvalidSchema { .. })
+        ));
+
+        let schema = EventSchema::compile(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "required": ["pod", "temperature_c"],
+            "additionalProperties": false,
+            "properties": {
+                "pod": { "type": "string", "enum": ["left", "right"] },
+                "temperature_c": { "type": "number", "minimum": -40, "maximum": 85 },
+                "faults": { "type": "array", "items": { "type": "integer" }, "maxItems": 4 }
+            }
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({ "pod": "left", "temperature_c": 21.5 })).is_ok());
+
+        let violations = schema
+            .validate(&json!({ "pod": "top", "temperature_c": 90, "faults": [1, "x"], "extra": 1 }))
+            .unwrap_err();
+        let mut paths: Vec<_> = violations.iter().map(|v| v.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, ["/extra", "/faults/1", "/pod", "/temperature_c"]);
+
+        let missing = schema.validate(&json!({ "pod": "left" })).unwrap_err();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].message.contains("temperature_c"));
+    }
+}
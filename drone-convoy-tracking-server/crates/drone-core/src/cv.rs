@@ -0,0 +1,172 @@
+//! CV detection and tracking parameters
+//!
+//! Halo detection (HSV thresholds, Hough circle parameters) and track
+//! association settings live here rather than in `drone-cv` so the API can
+//! serve and validate them without linking OpenCV. `CvTuning` is the part
+//! operators may change while the engine runs; `validate` rejects values
+//! outside the ranges OpenCV and the tracker accept.
+
+use crate::HaloColor;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Halo detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaloConfig {
+    /// Target halo color (default: red)
+    pub color: HaloColor,
+    /// Color tolerance for detection (HSV)
+    pub hue_tolerance: f64,
+    pub saturation_min: f64,
+    pub value_min: f64,
+    /// Hough circle detection parameters
+    pub min_radius: i32,
+    pub max_radius: i32,
+    pub dp: f64,           // Inverse ratio of accumulator resolution
+    pub min_dist: f64,     // Minimum distance between circle centers
+    pub param1: f64,       // Canny edge detector threshold
+    pub param2: f64,       // Accumulator threshold for circle centers
+    /// Minimum confidence for detection
+    pub min_confidence: f64,
+}
+
+impl Default for HaloConfig {
+    fn default() -> Self {
+        Self {
+            color: HaloColor::RED,
+            hue_tolerance: 15.0,
+            saturation_min: 100.0,
+            value_min: 100.0,
+            min_radius: 15,
+            max_radius: 100,
+            dp: 1.0,
+            min_dist: 50.0,
+            param1: 100.0,
+            param2: 30.0,
+            min_confidence: 0.7,
+        }
+    }
+}
+
+/// Tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingConfig {
+    /// Maximum frames to keep tracking without detection
+    pub max_frames_to_skip: u32,
+    /// IoU threshold for track association
+    pub iou_threshold: f64,
+    /// Kalman filter process noise
+    pub kalman_process_noise: f64,
+    /// Kalman filter measurement noise
+    pub kalman_measurement_noise: f64,
+    /// Maximum number of active tracks
+    pub max_tracks: usize,
+    /// Minimum frames to confirm a new track
+    pub min_frames_to_confirm: u32,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        Self {
+            max_frames_to_skip: 10,
+            iou_threshold: 0.3,
+            kalman_process_noise: 0.01,
+            kalman_measurement_noise: 0.1,
+            max_tracks: 50,
+            min_frames_to_confirm: 3,
+        }
+    }
+}
+
+/// A CV parameter outside its accepted range
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CvTuningError {
+    #[error("{field} must be between {min} and {max}, got {value}")]
+    OutOfRange {
+        field: &'static str,
+        min: f64,
+        max: f64,
+        value: f64,
+    },
+
+    #[error("halo.min_radius ({min}) must be below halo.max_radius ({max})")]
+    RadiusOrder { min: i32, max: i32 },
+}
+
+/// Detection and tracking parameters that can change at runtime
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CvTuning {
+    pub halo: HaloConfig,
+    pub tracking: TrackingConfig,
+}
+
+impl CvTuning {
+    /// Check every parameter against its bounds
+    pub fn validate(&self) -> Result<(), CvTuningError> {
+        let (h, t) = (&self.halo, &self.tracking);
+        // OpenCV hue runs 0-180, so a tolerance past 90 matches every color
+        check("halo.hue_tolerance", h.hue_tolerance, 0.0, 90.0)?;
+        check("halo.saturation_min", h.saturation_min, 0.0, 255.0)?;
+        check("halo.value_min", h.value_min, 0.0, 255.0)?;
+        check("halo.min_radius", h.min_radius as f64, 1.0, 2000.0)?;
+        check("halo.max_radius", h.max_radius as f64, 1.0, 2000.0)?;
+        if h.min_radius >= h.max_radius {
+            return Err(CvTuningError::RadiusOrder {
+                min: h.min_radius,
+                max: h.max_radius,
+            });
+        }
+        check("halo.dp", h.dp, 1.0, 4.0)?;
+        check("halo.min_dist", h.min_dist, 1.0, 2000.0)?;
+        check("halo.param1", h.param1, 1.0, 500.0)?;
+        check("halo.param2", h.param2, 1.0, 500.0)?;
+        check("halo.min_confidence", h.min_confidence, 0.0, 1.0)?;
+
+        check("tracking.max_frames_to_skip", t.max_frames_to_skip as f64, 0.0, 300.0)?;
+        check("tracking.iou_threshold", t.iou_threshold, 0.01, 1.0)?;
+        check("tracking.kalman_process_noise", t.kalman_process_noise, 1e-6, 100.0)?;
+        check("tracking.kalman_measurement_noise", t.kalman_measurement_noise, 1e-6, 100.0)?;
+        check("tracking.max_tracks", t.max_tracks as f64, 1.0, 500.0)?;
+        check("tracking.min_frames_to_confirm", t.min_frames_to_confirm as f64, 1.0, 60.0)?;
+        Ok(())
+    }
+}
+
+fn check(field: &'static str, value: f64, min: f64, max: f64) -> Result<(), CvTuningError> {
+    // NaN fails both comparisons, so it is rejected too
+    if value >= min && value <= max {
+        Ok(())
+    } else {
+        Err(CvTuningError::OutOfRange { field, min, max, value })
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuning_bounds() {
+        assert!(CvTuning::default().validate().is_ok());
+
+        let mut tuning = CvTuning::default();
+        tuning.halo.param2 = 0.0;
+        assert!(matches!(
+            tuning.validate(),
+            Err(CvTuningError::OutOfRange { field: "halo.param2", .. })
+        ));
+
+        let mut tuning = CvTuning::default();
+        tuning.tracking.iou_threshold = f64::NAN;
+        assert!(tuning.validate().is_err());
+
+        let mut tuning = CvTuning::default();
+        tuning.halo.min_radius = 120;
+        assert_eq!(tuning.validate(), Err(CvTuningError::RadiusOrder { min: 120, max: 100 }));
+    }
+}
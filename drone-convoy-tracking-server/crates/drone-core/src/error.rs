@@ -61,4 +61,10 @@ impl CoreError {
     }
 }
 
+impl From<crate::validation::TelemetryError> for CoreError {
+    fn from(err: crate::validation::TelemetryError) -> Self {
+        Self::InvalidTelemetry(err.to_string())
+    }
+}
+
 pub type CoreResult<T> = Result<T, CoreError>;
@@ -5,11 +5,14 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Instant;
 use uuid::Uuid;
 
 use crate::{
-    Alert, BoundingBox, DetectedHalo, Drone, DroneId, DroneStatus, GeoPosition, 
-    Mission, MissionId, MissionStatus, Telemetry, TrackingResult, WaypointId,
+    Alert, ConvoyRole, CustomEvent, CvTuning, DerivedMotion, Drone, DroneId, DroneMarking, DroneStatus, GeoPosition,
+    Mission, MissionId, MissionStatus, Telemetry, TenantId, TrackingResult, WaypointId,
 };
 
 /// Event envelope for all system events
@@ -19,6 +22,12 @@ pub struct Event {
     pub timestamp: DateTime<Utc>,
     pub event_type: EventType,
     pub payload: EventPayload,
+    /// Mission the event belongs to (absent for system-wide events)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mission_id: Option<MissionId>,
+    /// Tenant whose fleet produced the event (absent in single-tenant deployments)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<TenantId>,
 }
 
 impl Event {
@@ -28,6 +37,58 @@ impl Event {
             timestamp: Utc::now(),
             event_type,
             payload,
+            mission_id: None,
+            tenant_id: None,
+        }
+    }
+
+    /// Tag the event with the mission it belongs to
+    pub fn with_mission(mut self, mission_id: MissionId) -> Self {
+        self.mission_id = Some(mission_id);
+        self
+    }
+
+    /// Tag the event with the tenant it belongs to
+    pub fn with_tenant(mut self, tenant_id: TenantId) -> Self {
+        self.tenant_id = Some(tenant_id);
+        self
+    }
+
+    /// Hop timings of a position update
+    pub fn timings(&self) -> Option<&UpdateTimings> {
+        match &self.payload {
+            EventPayload::DronePosition(update) => update.timings.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn timings_mut(&mut self) -> Option<&mut UpdateTimings> {
+        match &mut self.payload {
+            EventPayload::DronePosition(update) => update.timings.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Drone the event refers to, if any
+    pub fn drone_id(&self) -> Option<&DroneId> {
+        match &self.payload {
+            EventPayload::DronePosition(e) => Some(&e.drone_id),
+            EventPayload::DroneStatus(e) => Some(&e.drone_id),
+            EventPayload::DroneTelemetry(e) => Some(&e.drone_id),
+            EventPayload::DroneConnection(e) => Some(&e.drone_id),
+            EventPayload::Waypoint(e) => Some(&e.drone_id),
+            EventPayload::WaypointApproach(e) => Some(&e.drone_id),
+            EventPayload::Alert(e) => e.alert.drone_id.as_ref(),
+            EventPayload::ScheduledCommand(e) => e.drone_id.as_ref(),
+            EventPayload::Zone(e) => Some(&e.drone_id),
+            EventPayload::Custom(e) => e.drone_id.as_ref(),
+            EventPayload::FleetMarking(e) => Some(&e.drone_id),
+            EventPayload::CvTracking(_)
+            | EventPayload::CvConfig(_)
+            | EventPayload::Presence(_)
+            | EventPayload::Mission(_)
+            | EventPayload::System(_)
+            | EventPayload::FullState(_) => None,
         }
     }
 
@@ -38,6 +99,10 @@ impl Event {
                 drone_id,
                 position,
                 telemetry,
+                presentation: None,
+                role: None,
+                motion: None,
+                timings: None,
             }),
         )
     }
@@ -53,6 +118,18 @@ impl Event {
         )
     }
 
+    /// A drone dropped from tracking after going silent or to make room
+    pub fn drone_evicted(drone_id: DroneId) -> Self {
+        Self::new(
+            EventType::DroneEvicted,
+            EventPayload::DroneConnection(DroneConnectionEvent {
+                drone_id,
+                connected: false,
+                peer_id: None,
+            }),
+        )
+    }
+
     pub fn waypoint_reached(drone_id: DroneId, waypoint_id: WaypointId, position: GeoPosition) -> Self {
         Self::new(
             EventType::WaypointReached,
@@ -65,6 +142,77 @@ impl Event {
         )
     }
 
+    pub fn waypoint_departed(drone_id: DroneId, waypoint_id: WaypointId, position: GeoPosition) -> Self {
+        Self::new(
+            EventType::WaypointDeparted,
+            EventPayload::Waypoint(WaypointEvent {
+                drone_id,
+                waypoint_id,
+                position,
+                event_type: WaypointEventType::Departed,
+            }),
+        )
+    }
+
+    /// A go-direct command took the drone past a waypoint it never reached
+    pub fn waypoint_skipped(drone_id: DroneId, waypoint_id: WaypointId, position: GeoPosition) -> Self {
+        Self::new(
+            EventType::WaypointSkipped,
+            EventPayload::Waypoint(WaypointEvent {
+                drone_id,
+                waypoint_id,
+                position,
+                event_type: WaypointEventType::Skipped,
+            }),
+        )
+    }
+
+    /// Integrator-defined event; validate it against its schema first
+    pub fn custom(event: CustomEvent) -> Self {
+        Self::new(EventType::Custom, EventPayload::Custom(event))
+    }
+
+    pub fn operator_joined(presence: OperatorPresence) -> Self {
+        Self::new(EventType::OperatorJoined, EventPayload::Presence(PresenceEvent { presence }))
+    }
+
+    pub fn operator_focus_changed(presence: OperatorPresence) -> Self {
+        Self::new(EventType::OperatorFocusChanged, EventPayload::Presence(PresenceEvent { presence }))
+    }
+
+    pub fn operator_left(presence: OperatorPresence) -> Self {
+        Self::new(EventType::OperatorLeft, EventPayload::Presence(PresenceEvent { presence }))
+    }
+
+    pub fn waypoint_approaching(approach: WaypointApproachEvent) -> Self {
+        Self::new(
+            EventType::WaypointApproaching,
+            EventPayload::WaypointApproach(approach),
+        )
+    }
+
+    pub fn mission_status_changed(
+        mission_id: MissionId,
+        status: MissionStatus,
+        message: Option<String>,
+    ) -> Self {
+        let event_type = match status {
+            MissionStatus::Planning | MissionStatus::Active => EventType::MissionStarted,
+            MissionStatus::Paused => EventType::MissionPaused,
+            MissionStatus::Completed => EventType::MissionCompleted,
+            MissionStatus::Aborted => EventType::MissionAborted,
+        };
+        Self::new(
+            event_type,
+            EventPayload::Mission(MissionEvent {
+                mission_id: mission_id.clone(),
+                status,
+                message,
+            }),
+        )
+        .with_mission(mission_id)
+    }
+
     pub fn cv_tracking_update(result: TrackingResult) -> Self {
         Self::new(
             EventType::CvTrackingUpdate,
@@ -74,16 +222,48 @@ impl Event {
         )
     }
 
+    /// CV parameters replaced by an operator
+    pub fn cv_config_changed(revision: u64, tuning: CvTuning) -> Self {
+        Self::new(
+            EventType::CvConfigChanged,
+            EventPayload::CvConfig(CvConfigEvent { revision, tuning }),
+        )
+    }
+
+    /// A drone's halo color or icon set (or cleared, with `None`)
+    pub fn fleet_marking_changed(drone_id: DroneId, marking: Option<DroneMarking>) -> Self {
+        Self::new(
+            EventType::FleetMarkingChanged,
+            EventPayload::FleetMarking(FleetMarkingEvent { drone_id, marking }),
+        )
+    }
+
     pub fn alert(alert: Alert) -> Self {
         Self::new(
             EventType::AlertRaised,
             EventPayload::Alert(AlertEvent { alert }),
         )
     }
+
+    /// A drone entered (`dwell_seconds` is `None`) or left a zone of interest
+    pub fn zone_transition(zone: ZoneEvent) -> Self {
+        let event_type = match zone.dwell_seconds {
+            None => EventType::ZoneEntered,
+            Some(_) => EventType::ZoneExited,
+        };
+        Self::new(event_type, EventPayload::Zone(zone))
+    }
+
+    pub fn scheduled_command_fired(fired: ScheduledCommandEvent) -> Self {
+        Self::new(
+            EventType::ScheduledCommandFired,
+            EventPayload::ScheduledCommand(fired),
+        )
+    }
 }
 
 /// Type of event
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EventType {
     // Drone events
@@ -92,6 +272,7 @@ pub enum EventType {
     DroneTelemetryUpdated,
     DroneConnected,
     DroneDisconnected,
+    DroneEvicted,
     
     // Mission events
     MissionStarted,
@@ -102,17 +283,36 @@ pub enum EventType {
     // Waypoint events
     WaypointReached,
     WaypointDeparted,
+    WaypointApproaching,
+    WaypointSkipped,
+
+    // Zone events
+    ZoneEntered,
+    ZoneExited,
     
     // CV tracking events
     CvTrackingUpdate,
     HaloDetected,
     TrackingLost,
+    CvConfigChanged,
+    FleetMarkingChanged,
     
     // Alert events
     AlertRaised,
     AlertAcknowledged,
     AlertResolved,
+
+    // Command events
+    ScheduledCommandFired,
+
+    // Integrator-defined events, named by their payload
+    Custom,
     
+    // Operator console events
+    OperatorJoined,
+    OperatorFocusChanged,
+    OperatorLeft,
+
     // System events
     SystemHealthUpdate,
     ConnectionEstablished,
@@ -129,10 +329,17 @@ pub enum EventPayload {
     DroneConnection(DroneConnectionEvent),
     Mission(MissionEvent),
     Waypoint(WaypointEvent),
+    WaypointApproach(WaypointApproachEvent),
     CvTracking(CvTrackingEvent),
+    CvConfig(CvConfigEvent),
+    FleetMarking(FleetMarkingEvent),
     Alert(AlertEvent),
+    ScheduledCommand(ScheduledCommandEvent),
+    Zone(ZoneEvent),
     System(SystemEvent),
     FullState(FullStateEvent),
+    Custom(CustomEvent),
+    Presence(PresenceEvent),
 }
 
 /// Drone position update event
@@ -141,6 +348,82 @@ pub struct DronePositionEvent {
     pub drone_id: DroneId,
     pub position: GeoPosition,
     pub telemetry: Telemetry,
+    /// Map rendering hints, added by the API before events reach clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation: Option<DronePresentation>,
+    /// Convoy role, if one is assigned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<ConvoyRole>,
+    /// Smoothed heading, climb rate, acceleration and turn rate from the tracker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub motion: Option<DerivedMotion>,
+    /// When the update passed each server hop, for latency measurement
+    #[serde(skip)]
+    pub timings: Option<UpdateTimings>,
+}
+
+/// When a position update passed each hop inside the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateTimings {
+    /// The report reached the tracker
+    pub ingested: Instant,
+    /// The tracker finished with it and published the event
+    pub processed: Instant,
+    /// The WebSocket hub broadcast it
+    pub broadcast: Option<Instant>,
+}
+
+impl UpdateTimings {
+    pub fn new(ingested: Instant, processed: Instant) -> Self {
+        Self { ingested, processed, broadcast: None }
+    }
+}
+
+/// A stretch of a position update's path from ingestion to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyHop {
+    /// Report received to tracker event published
+    Tracker,
+    /// Tracker event to WebSocket broadcast (API decoration and fan-out)
+    Broadcast,
+    /// Broadcast to the frame written to a client's socket
+    Delivery,
+    /// Socket to client, estimated as half a ping round trip
+    Client,
+}
+
+impl LatencyHop {
+    pub const ALL: [LatencyHop; 4] = [Self::Tracker, Self::Broadcast, Self::Delivery, Self::Client];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tracker => "tracker",
+            Self::Broadcast => "broadcast",
+            Self::Delivery => "delivery",
+            Self::Client => "client",
+        }
+    }
+}
+
+impl fmt::Display for LatencyHop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How the map should draw a drone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DronePresentation {
+    /// Icon name for the drone type
+    pub icon: String,
+    /// Marker color (CSS color) for the status and health
+    pub color: String,
+    /// The drone has a recent alert
+    pub blink: bool,
+    /// Halo color painted on the drone (CSS color), as drawn on the CV overlay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halo_color: Option<String>,
 }
 
 /// Drone status change event
@@ -193,18 +476,87 @@ pub struct WaypointEvent {
     pub event_type: WaypointEventType,
 }
 
+/// Pre-arrival notification for an upcoming waypoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaypointApproachEvent {
+    pub drone_id: DroneId,
+    pub waypoint_id: WaypointId,
+    pub waypoint_name: String,
+    pub position: GeoPosition,
+    pub distance_meters: f64,
+    pub eta_seconds: f64,
+}
+
+/// A drone crossing a zone of interest boundary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneEvent {
+    pub drone_id: DroneId,
+    pub zone_id: Uuid,
+    pub zone_name: String,
+    pub position: GeoPosition,
+    /// Length of the visit just ended (exits only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwell_seconds: Option<f64>,
+}
+
 /// Computer vision tracking event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CvTrackingEvent {
     pub results: Vec<TrackingResult>,
 }
 
+/// Current CV parameters after a change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvConfigEvent {
+    /// Increases with every change
+    pub revision: u64,
+    pub tuning: CvTuning,
+}
+
+/// A drone's marking after a change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetMarkingEvent {
+    pub drone_id: DroneId,
+    /// `None` when the marking was removed
+    pub marking: Option<DroneMarking>,
+}
+
 /// Alert event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertEvent {
     pub alert: Alert,
 }
 
+/// A scheduled command whose trigger fired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledCommandEvent {
+    pub schedule_id: Uuid,
+    /// Target drone (`None` for convoy-wide commands)
+    pub drone_id: Option<DroneId>,
+    /// Human-readable command and trigger
+    pub description: String,
+}
+
+/// A connected operator console and what it is looking at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperatorPresence {
+    /// WebSocket connection of the console
+    pub client_id: Uuid,
+    /// Operator the connection's API key belongs to; `None` when anonymous
+    pub operator_id: Option<String>,
+    pub connected_since: DateTime<Utc>,
+    /// Drone the client last reported having selected
+    pub viewing_drone: Option<DroneId>,
+    /// Mission the client last reported viewing
+    pub viewing_mission: Option<MissionId>,
+}
+
+/// An operator console joining, changing focus or leaving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceEvent {
+    pub presence: OperatorPresence,
+}
+
 /// System health event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemEvent {
@@ -236,17 +588,62 @@ pub enum ServerMessage {
     /// Batch of events
     EventBatch(Vec<Event>),
     /// Error message
-    Error { code: String, message: String },
+    Error {
+        code: String,
+        message: String,
+        /// When a throttled request may be retried
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
     /// Heartbeat/ping
     Ping { timestamp: i64 },
 }
 
+/// Drone, mission and event type filter for event consumers (`None` = all)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    pub drone_ids: Option<HashSet<DroneId>>,
+    pub mission_ids: Option<HashSet<MissionId>>,
+    pub event_types: Option<HashSet<EventType>>,
+    /// Only this tenant's events; unlike the other filters, events without
+    /// a tenant tag do not pass
+    pub tenant_id: Option<TenantId>,
+}
+
+impl EventFilter {
+    /// Whether an event passes the filter.
+    /// Events without a drone or mission tag pass those filters.
+    pub fn matches(&self, event: &Event) -> bool {
+        let drone_ok = match (&self.drone_ids, event.drone_id()) {
+            (Some(drones), Some(drone_id)) => drones.contains(drone_id),
+            _ => true,
+        };
+        let mission_ok = match (&self.mission_ids, &event.mission_id) {
+            (Some(missions), Some(mission_id)) => missions.contains(mission_id),
+            _ => true,
+        };
+        let type_ok = self
+            .event_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&event.event_type));
+        let tenant_ok = self.tenant_id.is_none() || self.tenant_id == event.tenant_id;
+
+        drone_ok && mission_ok && type_ok && tenant_ok
+    }
+}
+
 /// Message sent from client to server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")]
 pub enum ClientMessage {
-    /// Subscribe to specific drone updates
-    Subscribe { drone_ids: Option<Vec<DroneId>> },
+    /// Subscribe to specific drone and/or mission updates (`None` = all)
+    Subscribe {
+        drone_ids: Option<Vec<DroneId>>,
+        #[serde(default)]
+        mission_ids: Option<Vec<MissionId>>,
+        #[serde(default)]
+        event_types: Option<Vec<EventType>>,
+    },
     /// Unsubscribe from updates
     Unsubscribe { drone_ids: Option<Vec<DroneId>> },
     /// Request current state
@@ -255,6 +652,13 @@ pub enum ClientMessage {
     DroneCommand(DroneCommand),
     /// Heartbeat/pong
     Pong { timestamp: i64 },
+    /// Drone and mission the operator is looking at (`None` = nothing),
+    /// shared with the other consoles
+    Focus {
+        drone_id: Option<DroneId>,
+        #[serde(default)]
+        mission_id: Option<MissionId>,
+    },
 }
 
 /// Command sent to a drone
@@ -282,8 +686,15 @@ pub enum DroneCommandType {
     GoToWaypoint { waypoint_id: WaypointId },
     /// Set speed
     SetSpeed { speed: f64 },
+    /// Climb or descend to an altitude (m)
+    SetAltitude { altitude: f64 },
     /// Arm/disarm weapons
     SetArmed { armed: bool },
+    /// Turn the camera gimbal (degrees relative to the drone) and zoom
+    SetGimbal { pan: f64, tilt: f64, zoom: Option<f64> },
+    /// Point the camera at a position; the tracker turns it into
+    /// `SetGimbal` from the drone's current position and heading
+    PointAt { target: GeoPosition, zoom: Option<f64> },
 }
 
 // ============================================================================
@@ -325,4 +736,40 @@ mod tests {
         let json = serde_json::to_string(&msg).unwrap();
         assert!(json.contains("Ping"));
     }
+
+    #[test]
+    fn test_event_filter() {
+        let mission_id = MissionId::new();
+        let position = Event::drone_status_changed(
+            DroneId::new("REAPER-01"),
+            DroneStatus::Standby,
+            DroneStatus::Moving,
+        )
+        .with_mission(mission_id.clone());
+        let system = Event::mission_status_changed(MissionId::new(), MissionStatus::Active, None);
+
+        let mut filter = EventFilter::default();
+        assert!(filter.matches(&position));
+
+        filter.drone_ids = Some([DroneId::new("REAPER-02")].into_iter().collect());
+        assert!(!filter.matches(&position));
+        filter.drone_ids = Some([DroneId::new("REAPER-01")].into_iter().collect());
+        filter.mission_ids = Some([mission_id].into_iter().collect());
+        assert!(filter.matches(&position));
+        // Mission events carry no drone, but are scoped to their own mission
+        assert!(!filter.matches(&system));
+
+        filter.mission_ids = None;
+        filter.event_types = Some([EventType::MissionStarted].into_iter().collect());
+        assert!(!filter.matches(&position));
+        assert!(filter.matches(&system));
+
+        // Tenant scoping is strict: untagged events are withheld too
+        let acme = TenantId::parse("acme").unwrap();
+        filter.event_types = None;
+        filter.tenant_id = Some(acme.clone());
+        assert!(!filter.matches(&system));
+        assert!(!filter.matches(&system.clone().with_tenant(TenantId::parse("globex").unwrap())));
+        assert!(filter.matches(&system.with_tenant(acme)));
+    }
 }
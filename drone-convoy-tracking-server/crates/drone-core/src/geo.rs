@@ -1,11 +1,21 @@
 //! Geographic types and calculations for drone positioning
 
 use serde::{Deserialize, Serialize};
-use std::f64::consts::PI;
+use std::fmt;
 
 /// Earth's radius in kilometers
 const EARTH_RADIUS_KM: f64 = 6371.0;
 
+/// Largest separation (|Δlat| + |Δlng| in degrees) measured with the
+/// equirectangular approximation, about 11 km north-south
+const FAST_DISTANCE_MAX_DEGREES: f64 = 0.1;
+
+/// Closer to the poles meridians converge too fast for the approximation
+const FAST_DISTANCE_MAX_LATITUDE: f64 = 80.0;
+
+/// Base32 alphabet used by geohashes
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
 /// Geographic position with latitude, longitude, and altitude
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct GeoPosition {
@@ -50,9 +60,23 @@ impl GeoPosition {
             && self.longitude <= 180.0
     }
 
-    /// Calculate distance to another position using Haversine formula
+    /// Calculate distance to another position
     /// Returns distance in kilometers
+    ///
+    /// Short hops (under ~11 km, below 80° latitude) use the equirectangular
+    /// approximation, which stays within 0.01% of Haversine there at
+    /// under half the cost (see `benches/distance.rs`); longer distances use Haversine.
     pub fn distance_to(&self, other: &GeoPosition) -> f64 {
+        let separation = (other.latitude - self.latitude).abs() + (other.longitude - self.longitude).abs();
+        if separation < FAST_DISTANCE_MAX_DEGREES && self.latitude.abs() < FAST_DISTANCE_MAX_LATITUDE {
+            self.equirectangular_distance_to(other)
+        } else {
+            self.haversine_distance_to(other)
+        }
+    }
+
+    /// Great-circle distance in kilometers (Haversine formula)
+    pub fn haversine_distance_to(&self, other: &GeoPosition) -> f64 {
         let lat1 = self.latitude.to_radians();
         let lat2 = other.latitude.to_radians();
         let delta_lat = (other.latitude - self.latitude).to_radians();
@@ -65,6 +89,15 @@ impl GeoPosition {
         EARTH_RADIUS_KM * c
     }
 
+    /// Approximate distance in kilometers, treating the area between the two
+    /// positions as flat. Only accurate for short distances away from the poles.
+    pub fn equirectangular_distance_to(&self, other: &GeoPosition) -> f64 {
+        let mean_lat = ((self.latitude + other.latitude) / 2.0).to_radians();
+        let x = (other.longitude - self.longitude).to_radians() * mean_lat.cos();
+        let y = (other.latitude - self.latitude).to_radians();
+        EARTH_RADIUS_KM * (x * x + y * y).sqrt()
+    }
+
     /// Calculate bearing to another position
     /// Returns bearing in degrees (0-360)
     pub fn bearing_to(&self, other: &GeoPosition) -> f64 {
@@ -98,6 +131,27 @@ impl GeoPosition {
         GeoPosition::new(lat2.to_degrees(), lng2.to_degrees(), self.altitude)
     }
 
+    /// Planar offset of `other` from this position in meters (east, north)
+    ///
+    /// Equirectangular approximation; good to a few kilometers.
+    pub fn offset_to_m(&self, other: &GeoPosition) -> (f64, f64) {
+        let m_per_deg = EARTH_RADIUS_KM * 1000.0 * std::f64::consts::PI / 180.0;
+        (
+            (other.longitude - self.longitude) * m_per_deg * self.latitude.to_radians().cos(),
+            (other.latitude - self.latitude) * m_per_deg,
+        )
+    }
+
+    /// Position `east_m` and `north_m` meters away (inverse of `offset_to_m`)
+    pub fn offset_by_m(&self, east_m: f64, north_m: f64) -> GeoPosition {
+        let m_per_deg = EARTH_RADIUS_KM * 1000.0 * std::f64::consts::PI / 180.0;
+        GeoPosition::new(
+            self.latitude + north_m / m_per_deg,
+            self.longitude + east_m / (m_per_deg * self.latitude.to_radians().cos()),
+            self.altitude,
+        )
+    }
+
     /// Interpolate between two positions
     /// fraction: 0.0 = self, 1.0 = other
     pub fn interpolate(&self, other: &GeoPosition, fraction: f64) -> GeoPosition {
@@ -119,6 +173,103 @@ impl GeoPosition {
     pub fn to_array(&self) -> [f64; 3] {
         [self.latitude, self.longitude, self.altitude]
     }
+
+    /// Geohash of this position with `precision` characters (1-12)
+    pub fn geohash(&self, precision: usize) -> String {
+        let precision = precision.clamp(1, 12);
+        let mut lat_range = (-90.0, 90.0);
+        let mut lng_range = (-180.0, 180.0);
+        let mut hash = String::with_capacity(precision);
+        let mut even = true;
+
+        while hash.len() < precision {
+            let mut index = 0usize;
+            for _ in 0..5 {
+                let (range, value) = if even {
+                    (&mut lng_range, self.longitude)
+                } else {
+                    (&mut lat_range, self.latitude)
+                };
+                let mid = (range.0 + range.1) / 2.0;
+                index <<= 1;
+                if value >= mid {
+                    index |= 1;
+                    range.0 = mid;
+                } else {
+                    range.1 = mid;
+                }
+                even = !even;
+            }
+            hash.push(GEOHASH_ALPHABET[index] as char);
+        }
+
+        hash
+    }
+
+    /// Fixed-point wire form, see [`CompactPosition`]
+    pub fn to_compact(&self) -> CompactPosition {
+        CompactPosition {
+            lat_e6: (self.latitude * MICRODEGREES).round() as i32,
+            lng_e6: (self.longitude * MICRODEGREES).round() as i32,
+            alt_m: self.altitude.round().clamp(0.0, u16::MAX as f64) as u16,
+        }
+    }
+}
+
+// ============================================================================
+// COMPACT ENCODING
+// ============================================================================
+
+/// Fixed-point units per degree
+const MICRODEGREES: f64 = 1_000_000.0;
+
+/// Position as 10 bytes instead of three `f64`s
+///
+/// Latitude and longitude are whole micro-degrees, within 0.5e-6° (about
+/// 6 cm) of the original; altitude is whole meters, within 0.5 m, clamped
+/// to 0-65535 m. Converting a decoded position back gives the same
+/// compact value, so repeated round trips lose nothing more. Serialized as
+/// a `[lat_e6, lng_e6, alt_m]` tuple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "(i32, i32, u16)", into = "(i32, i32, u16)")]
+pub struct CompactPosition {
+    pub lat_e6: i32,
+    pub lng_e6: i32,
+    pub alt_m: u16,
+}
+
+impl CompactPosition {
+    pub fn to_position(&self) -> GeoPosition {
+        GeoPosition::new(
+            self.lat_e6 as f64 / MICRODEGREES,
+            self.lng_e6 as f64 / MICRODEGREES,
+            self.alt_m as f64,
+        )
+    }
+}
+
+impl From<GeoPosition> for CompactPosition {
+    fn from(position: GeoPosition) -> Self {
+        position.to_compact()
+    }
+}
+
+impl From<CompactPosition> for GeoPosition {
+    fn from(compact: CompactPosition) -> Self {
+        compact.to_position()
+    }
+}
+
+impl From<(i32, i32, u16)> for CompactPosition {
+    fn from((lat_e6, lng_e6, alt_m): (i32, i32, u16)) -> Self {
+        Self { lat_e6, lng_e6, alt_m }
+    }
+}
+
+impl From<CompactPosition> for (i32, i32, u16) {
+    fn from(compact: CompactPosition) -> Self {
+        (compact.lat_e6, compact.lng_e6, compact.alt_m)
+    }
 }
 
 /// Geographic bounding box for area queries
@@ -162,6 +313,38 @@ impl GeoBounds {
             && position.longitude <= self.max_lng
     }
 
+    /// The cell a geohash covers; `None` for an empty or invalid hash
+    pub fn from_geohash(hash: &str) -> Option<Self> {
+        if hash.is_empty() {
+            return None;
+        }
+        let mut lat_range = (-90.0, 90.0);
+        let mut lng_range = (-180.0, 180.0);
+        let mut even = true;
+        for c in hash.bytes() {
+            let index = GEOHASH_ALPHABET.iter().position(|a| *a == c)?;
+            for bit in (0..5).rev() {
+                let range = if even { &mut lng_range } else { &mut lat_range };
+                let mid = (range.0 + range.1) / 2.0;
+                if index & (1 << bit) != 0 {
+                    range.0 = mid;
+                } else {
+                    range.1 = mid;
+                }
+                even = !even;
+            }
+        }
+        Some(Self::new(lat_range.0, lat_range.1, lng_range.0, lng_range.1))
+    }
+
+    /// Whether the two boxes share any area or edge
+    pub fn intersects(&self, other: &GeoBounds) -> bool {
+        self.min_lat <= other.max_lat
+            && other.min_lat <= self.max_lat
+            && self.min_lng <= other.max_lng
+            && other.min_lng <= self.max_lng
+    }
+
     /// Get the center of these bounds
     pub fn center(&self) -> GeoPosition {
         GeoPosition::new(
@@ -177,6 +360,9 @@ impl GeoBounds {
 pub struct Geofence {
     pub name: String,
     pub vertices: Vec<GeoPosition>,
+    /// Areas inside `vertices` that are not part of the fence
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<Vec<GeoPosition>>,
     pub max_altitude: Option<f64>,
 }
 
@@ -185,11 +371,12 @@ impl Geofence {
         Self {
             name: name.into(),
             vertices,
+            holes: Vec::new(),
             max_altitude: None,
         }
     }
 
-    /// Check if a position is inside this geofence using ray casting
+    /// Check if a position is inside this geofence and none of its holes
     pub fn contains(&self, position: &GeoPosition) -> bool {
         if self.vertices.len() < 3 {
             return false;
@@ -202,27 +389,7 @@ impl Geofence {
             }
         }
 
-        // Ray casting algorithm
-        let mut inside = false;
-        let n = self.vertices.len();
-        let mut j = n - 1;
-
-        for i in 0..n {
-            let vi = &self.vertices[i];
-            let vj = &self.vertices[j];
-
-            if ((vi.longitude > position.longitude) != (vj.longitude > position.longitude))
-                && (position.latitude
-                    < (vj.latitude - vi.latitude) * (position.longitude - vi.longitude)
-                        / (vj.longitude - vi.longitude)
-                        + vi.latitude)
-            {
-                inside = !inside;
-            }
-            j = i;
-        }
-
-        inside
+        ring_contains(&self.vertices, position) && !self.holes.iter().any(|hole| ring_contains(hole, position))
     }
 
     /// Get the bounding box of this geofence
@@ -236,6 +403,730 @@ impl Geofence {
     }
 }
 
+/// Whether `position` is inside the polygon `vertices`, by ray casting
+fn ring_contains(vertices: &[GeoPosition], position: &GeoPosition) -> bool {
+    if vertices.len() < 3 {
+        return false;
+    }
+    let mut inside = false;
+    let n = vertices.len();
+    let mut j = n - 1;
+
+    for i in 0..n {
+        let vi = &vertices[i];
+        let vj = &vertices[j];
+
+        if ((vi.longitude > position.longitude) != (vj.longitude > position.longitude))
+            && (position.latitude
+                < (vj.latitude - vi.latitude) * (position.longitude - vi.longitude)
+                    / (vj.longitude - vi.longitude)
+                    + vi.latitude)
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
+// ============================================================================
+// ROUTE CORRIDORS
+// ============================================================================
+
+/// Largest angle in degrees between two vertices of a corridor's rounded ends
+/// and outer corners
+const CORRIDOR_ARC_STEP_DEG: f64 = 30.0;
+
+/// Distance in degrees within which corridor outline points coincide
+const CORRIDOR_EPS_DEG: f64 = 1e-10;
+
+/// Gap in degrees (about a millimeter) bridged when joining the corridor
+/// outline into rings
+const CORRIDOR_JOIN_DEG: f64 = 1e-8;
+
+/// Route points closer than this (meters) to the previous one are skipped
+const CORRIDOR_MIN_SPACING_M: f64 = 1.0;
+
+/// Shape of a corridor generated around a route
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CorridorSpec {
+    /// Full width in meters, centered on the route
+    pub width_m: f64,
+    /// Altitude ceiling in meters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ceiling_m: Option<f64>,
+}
+
+impl CorridorSpec {
+    pub fn new(width_m: f64) -> Self {
+        Self {
+            width_m,
+            ceiling_m: None,
+        }
+    }
+
+    pub fn with_ceiling(mut self, ceiling_m: f64) -> Self {
+        self.ceiling_m = Some(ceiling_m);
+        self
+    }
+
+    /// Width is positive and both numbers are finite
+    pub fn is_valid(&self) -> bool {
+        self.width_m > 0.0 && self.width_m.is_finite() && self.ceiling_m.is_none_or(f64::is_finite)
+    }
+}
+
+/// Buffer the polyline through `route` by half of `spec.width_m` on each
+/// side into a corridor polygon: the union of a buffer with rounded ends
+/// around each leg, so outer corners are rounded, inner ones sharp, and a
+/// route crossing itself stays inside where it crosses. Areas the route
+/// encloses without covering become holes. A single point gives a circle.
+/// `None` for an empty route or an invalid spec.
+pub fn route_corridor(name: impl Into<String>, route: &[GeoPosition], spec: &CorridorSpec) -> Option<Geofence> {
+    if !spec.is_valid() {
+        return None;
+    }
+    let mut points: Vec<GeoPosition> = Vec::with_capacity(route.len());
+    for point in route {
+        if points.last().is_none_or(|last: &GeoPosition| last.distance_to(point) * 1000.0 >= CORRIDOR_MIN_SPACING_M) {
+            points.push(*point);
+        }
+    }
+    let first = *points.first()?;
+    let half_km = spec.width_m / 2000.0;
+
+    // Points on the arc of radius `half_km` around `center`, from bearing
+    // `from` turning clockwise by `sweep` degrees, both ends included
+    let arc = |vertices: &mut Vec<Planar>, center: &GeoPosition, from: f64, sweep: f64| {
+        let steps = (sweep.abs() / CORRIDOR_ARC_STEP_DEG).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let bearing = from + sweep * step as f64 / steps as f64;
+            let point = center.destination(half_km, bearing.rem_euclid(360.0));
+            vertices.push([point.longitude, point.latitude]);
+        }
+    };
+
+    let pieces: Vec<Vec<Planar>> = if points.len() == 1 {
+        let mut circle = Vec::new();
+        arc(&mut circle, &first, 0.0, 360.0 - CORRIDOR_ARC_STEP_DEG);
+        vec![circle]
+    } else {
+        points
+            .windows(2)
+            .map(|leg| {
+                // Behind the start, then ahead of the end on the arriving bearing
+                let mut buffer = Vec::new();
+                arc(&mut buffer, &leg[0], leg[0].bearing_to(&leg[1]) + 90.0, 180.0);
+                arc(&mut buffer, &leg[1], leg[1].bearing_to(&leg[0]) + 90.0, 180.0);
+                buffer
+            })
+            .collect()
+    };
+    // Largest distance in degrees from a leg to its buffer's edge
+    let reach = half_km / (111.195 * first.latitude.to_radians().cos().max(0.01));
+
+    let mut rings: Vec<(f64, Vec<Planar>)> = union_outline(pieces, reach)
+        .into_iter()
+        .map(|ring| (signed_area(&ring), ring))
+        .collect();
+    let outer = (0..rings.len()).max_by(|a, b| rings[*a].0.total_cmp(&rings[*b].0))?;
+    let (_, outer) = rings.swap_remove(outer);
+
+    let to_positions = |ring: Vec<Planar>| -> Vec<GeoPosition> {
+        ring.into_iter().map(|[lng, lat]| GeoPosition::new(lat, lng, 0.0)).collect()
+    };
+    // Slivers left by rounding are dropped
+    let min_hole = (CORRIDOR_JOIN_DEG * 1000.0).powi(2);
+    let mut corridor = Geofence::new(name, to_positions(outer));
+    corridor.holes = rings
+        .into_iter()
+        .filter(|(area, _)| *area < -min_hole)
+        .map(|(_, ring)| to_positions(ring))
+        .collect();
+    corridor.max_altitude = spec.ceiling_m;
+    Some(corridor)
+}
+
+/// Point in degrees, longitude first
+type Planar = [f64; 2];
+
+fn sub(a: Planar, b: Planar) -> Planar {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn cross(a: Planar, b: Planar) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn dot(a: Planar, b: Planar) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+/// Twice the area of a ring, positive when counterclockwise
+fn signed_area(ring: &[Planar]) -> f64 {
+    (0..ring.len()).map(|i| cross(ring[i], ring[(i + 1) % ring.len()])).sum()
+}
+
+/// Distance from `p` to the segment `a`-`b`
+fn segment_distance(p: Planar, a: Planar, b: Planar) -> f64 {
+    let ab = sub(b, a);
+    let length = dot(ab, ab);
+    let t = if length > 0.0 { (dot(sub(p, a), ab) / length).clamp(0.0, 1.0) } else { 0.0 };
+    let d = sub(p, [a[0] + ab[0] * t, a[1] + ab[1] * t]);
+    dot(d, d).sqrt()
+}
+
+/// Where segments `a`-`b` and `c`-`d` cross, as the fraction along each and
+/// the point; parallel segments never cross
+fn segment_crossing(a: Planar, b: Planar, c: Planar, d: Planar) -> Option<(f64, f64, Planar)> {
+    let (r, s) = (sub(b, a), sub(d, c));
+    let denominator = cross(r, s);
+    if denominator.abs() <= 1e-9 * dot(r, r).sqrt() * dot(s, s).sqrt() {
+        return None;
+    }
+    let ac = sub(c, a);
+    let t = cross(ac, s) / denominator;
+    let u = cross(ac, r) / denominator;
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| (t, u, [a[0] + r[0] * t, a[1] + r[1] * t]))
+}
+
+/// Grid cells of `size` degrees, each listing the segments passing near it
+struct SegmentGrid {
+    size: f64,
+    cells: std::collections::HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SegmentGrid {
+    fn new(size: f64) -> Self {
+        Self {
+            size,
+            cells: std::collections::HashMap::new(),
+        }
+    }
+
+    fn cell(&self, p: Planar) -> (i64, i64) {
+        ((p[0] / self.size).floor() as i64, (p[1] / self.size).floor() as i64)
+    }
+
+    /// File segment `id` under every cell within one cell of it, sampling
+    /// it every half cell
+    fn insert(&mut self, id: usize, a: Planar, b: Planar) {
+        let d = sub(b, a);
+        let steps = (dot(d, d).sqrt() / (self.size / 2.0)).ceil() as usize;
+        let mut filed = std::collections::HashSet::new();
+        for step in 0..=steps {
+            let t = if steps == 0 { 0.0 } else { step as f64 / steps as f64 };
+            let (x, y) = self.cell([a[0] + d[0] * t, a[1] + d[1] * t]);
+            for cell in [-1, 0, 1].into_iter().flat_map(|dx| [-1, 0, 1].map(|dy| (x + dx, y + dy))) {
+                if filed.insert(cell) {
+                    self.cells.entry(cell).or_default().push(id);
+                }
+            }
+        }
+    }
+
+    fn near(&self, p: Planar) -> &[usize] {
+        self.cells.get(&self.cell(p)).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// Outline of the union of `pieces`, each a simple polygon whose points
+/// are all within `reach` of its first-to-middle spine: counterclockwise
+/// outer rings and clockwise holes
+fn union_outline(mut pieces: Vec<Vec<Planar>>, reach: f64) -> Vec<Vec<Planar>> {
+    for piece in &mut pieces {
+        if signed_area(piece) < 0.0 {
+            piece.reverse();
+        }
+    }
+    let edges: Vec<(usize, Planar, Planar)> = pieces
+        .iter()
+        .enumerate()
+        .flat_map(|(p, ring)| (0..ring.len()).map(move |k| (p, ring[k], ring[(k + 1) % ring.len()])))
+        .collect();
+    let mean_edge = edges.iter().map(|(_, a, b)| (sub(*b, *a)[0].abs()).max(sub(*b, *a)[1].abs())).sum::<f64>()
+        / edges.len().max(1) as f64;
+    let size = (2.0 * reach).max(mean_edge).max(CORRIDOR_JOIN_DEG * 10.0);
+
+    let mut edge_grid = SegmentGrid::new(size);
+    for (id, (_, a, b)) in edges.iter().enumerate() {
+        edge_grid.insert(id, *a, *b);
+    }
+    // Each piece filed along its spine, which every point of it is within `reach` of
+    let mut piece_grid = SegmentGrid::new(size);
+    for (id, piece) in pieces.iter().enumerate() {
+        piece_grid.insert(id, piece[0], piece[piece.len() / 2]);
+    }
+
+    // Split edges where they cross edges of other pieces; each crossing is
+    // handled in the cell it falls in, and both edges get the same point
+    let mut splits: Vec<Vec<(f64, Planar)>> = vec![Vec::new(); edges.len()];
+    for (cell, ids) in &edge_grid.cells {
+        for (n, &e) in ids.iter().enumerate() {
+            for &f in &ids[n + 1..] {
+                let ((pe, a, b), (pf, c, d)) = (edges[e], edges[f]);
+                if pe == pf {
+                    continue;
+                }
+                if let Some((t, u, point)) = segment_crossing(a, b, c, d) {
+                    if edge_grid.cell(point) == *cell {
+                        splits[e].push((t, point));
+                        splits[f].push((u, point));
+                    }
+                }
+            }
+        }
+    }
+
+    // Keep the parts of edges outside every other piece; of edges shared by
+    // two pieces keep one when both are on the same side, none otherwise
+    let mut kept: Vec<(Planar, Planar)> = Vec::new();
+    for (e, (piece, a, b)) in edges.iter().enumerate() {
+        let mut cuts = std::mem::take(&mut splits[e]);
+        cuts.sort_by(|x, y| x.0.total_cmp(&y.0));
+        let points: Vec<Planar> = std::iter::once(*a).chain(cuts.into_iter().map(|(_, p)| p)).chain([*b]).collect();
+        for part in points.windows(2) {
+            let (from, to) = (part[0], part[1]);
+            let direction = sub(to, from);
+            if dot(direction, direction).sqrt() <= CORRIDOR_EPS_DEG {
+                continue;
+            }
+            let middle = [(from[0] + to[0]) / 2.0, (from[1] + to[1]) / 2.0];
+            let covered = piece_grid.near(middle).iter().any(|&other| {
+                if other == *piece {
+                    return false;
+                }
+                let ring = &pieces[other];
+                let shared = (0..ring.len()).map(|k| (ring[k], ring[(k + 1) % ring.len()])).find(|(c, d)| {
+                    segment_distance(middle, *c, *d) <= CORRIDOR_EPS_DEG
+                });
+                match shared {
+                    Some((c, d)) => dot(direction, sub(d, c)) < 0.0 || other < *piece,
+                    None => planar_ring_contains(ring, middle),
+                }
+            });
+            if !covered {
+                kept.push((from, to));
+            }
+        }
+    }
+
+    join_rings(kept)
+}
+
+/// Whether `p` is inside `ring`, by ray casting
+fn planar_ring_contains(ring: &[Planar], p: Planar) -> bool {
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (vi, vj) = (ring[i], ring[j]);
+        if (vi[0] > p[0]) != (vj[0] > p[0]) && p[1] < (vj[1] - vi[1]) * (p[0] - vi[0]) / (vj[0] - vi[0]) + vi[1] {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Chain outline segments end to start into closed rings; chains that do
+/// not close are dropped
+fn join_rings(segments: Vec<(Planar, Planar)>) -> Vec<Vec<Planar>> {
+    let key = |p: Planar| ((p[0] / CORRIDOR_JOIN_DEG).round() as i64, (p[1] / CORRIDOR_JOIN_DEG).round() as i64);
+    let mut starts: std::collections::HashMap<(i64, i64), Vec<usize>> = std::collections::HashMap::new();
+    for (i, (from, _)) in segments.iter().enumerate() {
+        starts.entry(key(*from)).or_default().push(i);
+    }
+    let mut used = vec![false; segments.len()];
+    let next = |end: Planar, used: &[bool]| -> Option<usize> {
+        let (x, y) = key(end);
+        [-1, 0, 1]
+            .into_iter()
+            .flat_map(|dx| [-1, 0, 1].map(move |dy| (x + dx, y + dy)))
+            .filter_map(|cell| starts.get(&cell))
+            .flatten()
+            .copied()
+            .filter(|i| !used[*i])
+            .map(|i| (i, segment_distance(segments[i].0, end, end)))
+            .filter(|(_, gap)| *gap <= CORRIDOR_JOIN_DEG)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    };
+
+    let mut rings = Vec::new();
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let start = segments[first].0;
+        let mut ring = vec![start];
+        let mut end = segments[first].1;
+        let closed = loop {
+            if ring.len() > 2 && segment_distance(start, end, end) <= CORRIDOR_JOIN_DEG {
+                break true;
+            }
+            match next(end, &used) {
+                Some(i) => {
+                    used[i] = true;
+                    ring.push(segments[i].0);
+                    end = segments[i].1;
+                }
+                None => break false,
+            }
+        };
+        if closed {
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+// ============================================================================
+// POLYLINE ENCODING
+// ============================================================================
+
+/// Units per degree of the Google encoded polyline format
+const POLYLINE_SCALE: f64 = 1e5;
+
+/// Encode positions with the Google polyline algorithm at 5 decimal places
+/// (about 1 m); altitude is dropped
+pub fn encode_polyline(points: &[GeoPosition]) -> String {
+    let mut encoded = String::with_capacity(points.len() * 8);
+    let (mut prev_lat, mut prev_lng) = (0i64, 0i64);
+    for point in points {
+        let lat = (point.latitude * POLYLINE_SCALE).round() as i64;
+        let lng = (point.longitude * POLYLINE_SCALE).round() as i64;
+        for delta in [lat - prev_lat, lng - prev_lng] {
+            // Zig-zag the sign into the low bit, then 5-bit chunks, low first
+            let mut value = if delta < 0 { !(delta << 1) } else { delta << 1 } as u64;
+            while value >= 0x20 {
+                encoded.push((((value & 0x1f) | 0x20) as u8 + 63) as char);
+                value >>= 5;
+            }
+            encoded.push((value as u8 + 63) as char);
+        }
+        (prev_lat, prev_lng) = (lat, lng);
+    }
+    encoded
+}
+
+/// Decode a Google encoded polyline; `None` if it is malformed
+pub fn decode_polyline(encoded: &str) -> Option<Vec<GeoPosition>> {
+    let mut bytes = encoded.bytes();
+    let mut next = || -> Option<Option<i64>> {
+        let (mut value, mut shift) = (0u64, 0);
+        loop {
+            let Some(byte) = bytes.next() else {
+                return if shift == 0 { Some(None) } else { None };
+            };
+            let chunk = u64::from(byte.checked_sub(63).filter(|c| *c < 0x40)?);
+            value |= (chunk & 0x1f) << shift;
+            shift += 5;
+            if chunk < 0x20 {
+                let delta = (value >> 1) as i64;
+                return Some(Some(if value & 1 == 1 { !delta } else { delta }));
+            }
+            if shift > 60 {
+                return None;
+            }
+        }
+    };
+
+    let mut points = Vec::new();
+    let (mut lat, mut lng) = (0i64, 0i64);
+    while let Some(dlat) = next()? {
+        lat += dlat;
+        lng += next()??;
+        points.push(GeoPosition::new(lat as f64 / POLYLINE_SCALE, lng as f64 / POLYLINE_SCALE, 0.0));
+    }
+    Some(points)
+}
+
+// ============================================================================
+// PATH SMOOTHING
+// ============================================================================
+
+/// Horizontal distance in meters from `p` to the segment `a`-`b`
+fn segment_distance_m(p: &GeoPosition, a: &GeoPosition, b: &GeoPosition) -> f64 {
+    let (bx, by) = a.offset_to_m(b);
+    let (px, py) = a.offset_to_m(p);
+    let len_sq = bx * bx + by * by;
+    let t = if len_sq > 0.0 {
+        ((px * bx + py * by) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((px - t * bx).powi(2) + (py - t * by).powi(2)).sqrt()
+}
+
+/// Horizontal distance in meters from `p` to the nearest leg of `path`; a
+/// single-point path measures to that point, an empty one gives `None`
+pub fn distance_to_path_m(p: &GeoPosition, path: &[GeoPosition]) -> Option<f64> {
+    match path {
+        [] => None,
+        [only] => Some(segment_distance_m(p, only, only)),
+        _ => path
+            .windows(2)
+            .map(|leg| segment_distance_m(p, &leg[0], &leg[1]))
+            .min_by(|a, b| a.total_cmp(b)),
+    }
+}
+
+/// Douglas-Peucker simplification: indices of the points to keep so that no
+/// dropped point is more than `tolerance_m` meters from the simplified path.
+/// The first and last points are always kept.
+pub fn simplify_path(points: &[GeoPosition], tolerance_m: f64) -> Vec<usize> {
+    if points.len() < 3 {
+        return (0..points.len()).collect();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| (i, segment_distance_m(&points[i], &points[start], &points[end])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, _)) = farthest.filter(|(_, d)| *d > tolerance_m) {
+            keep[i] = true;
+            ranges.push((start, i));
+            ranges.push((i, end));
+        }
+    }
+
+    keep.iter().enumerate().filter(|(_, k)| **k).map(|(i, _)| i).collect()
+}
+
+/// Catmull-Rom spline through `points`, inserting `samples_per_segment - 1`
+/// interpolated positions between each pair. The curve passes through every
+/// input point.
+pub fn spline_path(points: &[GeoPosition], samples_per_segment: usize) -> Vec<GeoPosition> {
+    if points.len() < 3 || samples_per_segment < 2 {
+        return points.to_vec();
+    }
+
+    let catmull_rom = |p0: f64, p1: f64, p2: f64, p3: f64, t: f64| {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        0.5 * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+    };
+
+    let last = points.len() - 1;
+    let mut smoothed = Vec::with_capacity(last * samples_per_segment + 1);
+    for i in 0..last {
+        // End segments reuse their endpoint as the missing control point
+        let p0 = &points[i.saturating_sub(1)];
+        let (p1, p2) = (&points[i], &points[i + 1]);
+        let p3 = &points[(i + 2).min(last)];
+
+        for step in 0..samples_per_segment {
+            let t = step as f64 / samples_per_segment as f64;
+            smoothed.push(GeoPosition::new(
+                catmull_rom(p0.latitude, p1.latitude, p2.latitude, p3.latitude, t),
+                catmull_rom(p0.longitude, p1.longitude, p2.longitude, p3.longitude, t),
+                catmull_rom(p0.altitude, p1.altitude, p2.altitude, p3.altitude, t),
+            ));
+        }
+    }
+    smoothed.push(points[last]);
+    smoothed
+}
+
+// ============================================================================
+// LINE OF SIGHT
+// ============================================================================
+
+/// Earth radius multiplier for radio paths in a standard atmosphere
+pub const RADIO_EARTH_FACTOR: f64 = 4.0 / 3.0;
+
+/// Ground elevation source
+pub trait ElevationModel: Send + Sync {
+    /// Terrain height in meters above sea level; `None` outside the model
+    fn elevation_at(&self, position: &GeoPosition) -> Option<f64>;
+}
+
+/// Elevations on a regular latitude/longitude grid, bilinearly interpolated
+#[derive(Clone)]
+pub struct ElevationGrid {
+    /// Center of the south-west cell
+    south: f64,
+    west: f64,
+    cell_deg: f64,
+    rows: usize,
+    cols: usize,
+    /// Row-major from the southern row; NaN where there is no data
+    heights: Vec<f64>,
+}
+
+impl fmt::Debug for ElevationGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ElevationGrid")
+            .field("south", &self.south)
+            .field("west", &self.west)
+            .field("cell_deg", &self.cell_deg)
+            .field("rows", &self.rows)
+            .field("cols", &self.cols)
+            .finish()
+    }
+}
+
+impl ElevationGrid {
+    /// Grid whose south-west cell is centered on (`south`, `west`), with
+    /// `heights` row-major from the southern row
+    pub fn new(south: f64, west: f64, cell_deg: f64, rows: usize, cols: usize, heights: Vec<f64>) -> Result<Self, String> {
+        if rows < 2 || cols < 2 {
+            return Err("grid must be at least 2x2".into());
+        }
+        if cell_deg.is_nan() || cell_deg <= 0.0 {
+            return Err("cell size must be positive".into());
+        }
+        if heights.len() != rows * cols {
+            return Err(format!("expected {} heights, got {}", rows * cols, heights.len()));
+        }
+        Ok(Self { south, west, cell_deg, rows, cols, heights })
+    }
+
+    /// Grid sampled from `height(latitude, longitude)` at every cell center
+    pub fn from_fn(
+        south: f64,
+        west: f64,
+        cell_deg: f64,
+        rows: usize,
+        cols: usize,
+        height: impl Fn(f64, f64) -> f64,
+    ) -> Result<Self, String> {
+        let heights = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| height(south + row as f64 * cell_deg, west + col as f64 * cell_deg))
+            .collect();
+        Self::new(south, west, cell_deg, rows, cols, heights)
+    }
+
+    /// Parse an ESRI ASCII grid (`.asc`) in geographic coordinates
+    pub fn parse_ascii(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty()).peekable();
+        let mut header = std::collections::HashMap::new();
+        while let Some(line) = lines.peek() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(value), None) = (parts.next(), parts.next(), parts.next()) else {
+                break;
+            };
+            if key.parse::<f64>().is_ok() {
+                break;
+            }
+            let value: f64 = value.parse().map_err(|_| format!("invalid value for {}", key))?;
+            header.insert(key.to_ascii_lowercase(), value);
+            lines.next();
+        }
+
+        let get = |key: &str| header.get(key).copied();
+        let cols = get("ncols").ok_or("missing ncols")? as usize;
+        let rows = get("nrows").ok_or("missing nrows")? as usize;
+        let cell_deg = get("cellsize").ok_or("missing cellsize")?;
+        let west = match (get("xllcenter"), get("xllcorner")) {
+            (Some(center), _) => center,
+            (None, Some(corner)) => corner + cell_deg / 2.0,
+            (None, None) => return Err("missing xllcorner".into()),
+        };
+        let south = match (get("yllcenter"), get("yllcorner")) {
+            (Some(center), _) => center,
+            (None, Some(corner)) => corner + cell_deg / 2.0,
+            (None, None) => return Err("missing yllcorner".into()),
+        };
+        let nodata = get("nodata_value");
+
+        // Rows are listed north to south
+        let mut north_first = Vec::with_capacity(rows * cols);
+        for token in lines.flat_map(str::split_whitespace) {
+            let value: f64 = token.parse().map_err(|_| format!("invalid height {:?}", token))?;
+            north_first.push(if Some(value) == nodata { f64::NAN } else { value });
+        }
+        if north_first.len() != rows * cols {
+            return Err(format!("expected {} heights, got {}", rows * cols, north_first.len()));
+        }
+        let heights = north_first.chunks(cols.max(1)).rev().flatten().copied().collect();
+        Self::new(south, west, cell_deg, rows, cols, heights)
+    }
+
+    fn height(&self, row: usize, col: usize) -> f64 {
+        self.heights[row * self.cols + col]
+    }
+}
+
+impl ElevationModel for ElevationGrid {
+    fn elevation_at(&self, position: &GeoPosition) -> Option<f64> {
+        // Tolerate rounding on the outer cell centers
+        const EDGE: f64 = 1e-9;
+        let y = (position.latitude - self.south) / self.cell_deg;
+        let x = (position.longitude - self.west) / self.cell_deg;
+        let (max_y, max_x) = ((self.rows - 1) as f64, (self.cols - 1) as f64);
+        if !(-EDGE..=max_y + EDGE).contains(&y) || !(-EDGE..=max_x + EDGE).contains(&x) {
+            return None;
+        }
+        let (y, x) = (y.clamp(0.0, max_y), x.clamp(0.0, max_x));
+        let (row, col) = ((y as usize).min(self.rows - 2), (x as usize).min(self.cols - 2));
+        let (fy, fx) = (y - row as f64, x - col as f64);
+        let south = self.height(row, col) * (1.0 - fx) + self.height(row, col + 1) * fx;
+        let north = self.height(row + 1, col) * (1.0 - fx) + self.height(row + 1, col + 1) * fx;
+        let height = south * (1.0 - fy) + north * fy;
+        (!height.is_nan()).then_some(height)
+    }
+}
+
+/// Result of a line-of-sight check
+#[derive(Debug, Clone, Copy)]
+pub struct LineOfSight {
+    /// No terrain rises above the straight path
+    pub clear: bool,
+    /// Smallest height of the path above the terrain in meters (negative
+    /// when blocked; infinite when no terrain was sampled)
+    pub clearance_m: f64,
+    /// Terrain point that blocks the path the most
+    pub obstruction: Option<GeoPosition>,
+}
+
+/// Check the straight path from `from` to `to` against `terrain`
+///
+/// The terrain is sampled every `step_m` meters between the endpoints and
+/// raised by the Earth's bulge for radio paths, so long paths can be
+/// blocked by the horizon. Points outside the model do not obstruct.
+pub fn line_of_sight(from: &GeoPosition, to: &GeoPosition, terrain: &dyn ElevationModel, step_m: f64) -> LineOfSight {
+    let distance_m = from.distance_to(to) * 1000.0;
+    let steps = (distance_m / step_m.max(1.0)).ceil().max(1.0) as usize;
+    let radius_m = EARTH_RADIUS_KM * 1000.0 * RADIO_EARTH_FACTOR;
+
+    let mut clearance_m = f64::INFINITY;
+    let mut obstruction = None;
+    for i in 1..steps {
+        let t = i as f64 / steps as f64;
+        let point = from.interpolate(to, t);
+        let Some(ground) = terrain.elevation_at(&point) else {
+            continue;
+        };
+        let (near, far) = (distance_m * t, distance_m * (1.0 - t));
+        let bulge = near * far / (2.0 * radius_m);
+        let clearance = point.altitude - ground - bulge;
+        if clearance < clearance_m {
+            clearance_m = clearance;
+            if clearance < 0.0 {
+                obstruction = Some(GeoPosition::new(point.latitude, point.longitude, ground));
+            }
+        }
+    }
+    LineOfSight {
+        clear: clearance_m >= 0.0,
+        clearance_m,
+        obstruction,
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -254,6 +1145,38 @@ mod tests {
         assert!(distance > 400.0 && distance < 500.0);
     }
 
+    #[test]
+    fn test_fast_distance_accuracy() {
+        let mut worst: f64 = 0.0;
+        for lat in (-79..80).step_by(3) {
+            let origin = GeoPosition::new(lat as f64 + 0.123, 69.2075, 0.0);
+            for bearing in (0..360).step_by(15) {
+                for km in [0.005, 0.1, 1.0, 3.0, 7.5] {
+                    let other = origin.destination(km, bearing as f64);
+                    let exact = origin.haversine_distance_to(&other);
+                    let fast = origin.equirectangular_distance_to(&other);
+                    worst = worst.max((fast - exact).abs() / exact);
+                }
+            }
+        }
+        assert!(worst < 1e-4, "worst relative error {}", worst);
+
+        // Short hops take the fast path, long ones and polar ones stay exact
+        let a = GeoPosition::new(34.5553, 69.2075, 0.0);
+        let near = a.destination(2.0, 45.0);
+        assert_eq!(a.distance_to(&near), a.equirectangular_distance_to(&near));
+        let far = GeoPosition::new(31.6133, 65.7101, 0.0);
+        assert_eq!(a.distance_to(&far), a.haversine_distance_to(&far));
+        let polar = GeoPosition::new(85.0, 10.0, 0.0);
+        let polar_near = polar.destination(1.0, 90.0);
+        assert_eq!(polar.distance_to(&polar_near), polar.haversine_distance_to(&polar_near));
+
+        // Across the antimeridian the degree separation is large, so Haversine is used
+        let west = GeoPosition::new(10.0, 179.99, 0.0);
+        let east = GeoPosition::new(10.0, -179.99, 0.0);
+        assert!((west.distance_to(&east) - 2.19).abs() < 0.01);
+    }
+
     #[test]
     fn test_bearing_calculation() {
         let origin = GeoPosition::new(0.0, 0.0, 0.0);
@@ -289,6 +1212,19 @@ mod tests {
         assert!(!bounds.contains(&outside));
     }
 
+    #[test]
+    fn test_geohash() {
+        let kabul = GeoPosition::new(34.5553, 69.2075, 0.0);
+        assert_eq!(kabul.geohash(6), "tw1hwf");
+        assert_eq!(kabul.geohash(3), "tw1");
+        assert_eq!(GeoPosition::new(57.64911, 10.40744, 0.0).geohash(11), "u4pruydqqvj");
+
+        let cell = GeoBounds::from_geohash("tw1hwf").unwrap();
+        assert!(cell.contains(&kabul));
+        assert_eq!(cell.center().geohash(6), "tw1hwf");
+        assert!(GeoBounds::from_geohash("tw1a").is_none());
+    }
+
     #[test]
     fn test_position_validity() {
         let valid = GeoPosition::new(45.0, 90.0, 1000.0);
@@ -299,4 +1235,163 @@ mod tests {
         assert!(!invalid_lat.is_valid());
         assert!(!invalid_lng.is_valid());
     }
+
+    #[test]
+    fn test_compact_position_round_trip() {
+        let position = GeoPosition::new(-34.5553219, 169.2075004, 3000.4);
+        let compact = position.to_compact();
+        assert_eq!(compact, CompactPosition { lat_e6: -34555322, lng_e6: 169207500, alt_m: 3000 });
+
+        let decoded = compact.to_position();
+        assert!((decoded.latitude - position.latitude).abs() <= 0.5e-6);
+        assert!((decoded.longitude - position.longitude).abs() <= 0.5e-6);
+        assert!((decoded.altitude - position.altitude).abs() <= 0.5);
+        assert_eq!(decoded.to_compact(), compact);
+
+        // Out-of-range altitudes clamp; the wire form is a tuple
+        assert_eq!(GeoPosition::new(0.0, 0.0, -20.0).to_compact().alt_m, 0);
+        assert_eq!(GeoPosition::new(0.0, 0.0, 70_000.0).to_compact().alt_m, u16::MAX);
+        assert_eq!(serde_json::to_string(&compact).unwrap(), "[-34555322,169207500,3000]");
+    }
+
+    #[test]
+    fn test_simplify_and_spline_path() {
+        // A straight northbound track with ~3 m of jitter and one 200 m corner
+        let origin = GeoPosition::new(34.5553, 69.2075, 3000.0);
+        let mut track: Vec<GeoPosition> = (0..20)
+            .map(|i| {
+                let jitter_m = if i % 2 == 0 { 3.0 } else { -3.0 };
+                origin.destination(i as f64 * 0.05, 0.0).destination(jitter_m / 1000.0, 90.0)
+            })
+            .collect();
+        track.push(track[19].destination(0.2, 90.0));
+
+        let offset = origin.destination(0.5, 0.0).destination(0.04, 270.0);
+        assert!((distance_to_path_m(&offset, &[track[0], track[19]]).unwrap() - 40.0).abs() < 0.5);
+        assert!((distance_to_path_m(&offset, &[track[0]]).unwrap() - offset.distance_to(&track[0]) * 1000.0).abs() < 1.0);
+        assert_eq!(distance_to_path_m(&offset, &[]), None);
+
+        let kept = simplify_path(&track, 10.0);
+        assert_eq!(kept, vec![0, 19, 20]);
+        assert_eq!(simplify_path(&track, 1.0).len(), track.len());
+        assert_eq!(simplify_path(&track[..2], 10.0), vec![0, 1]);
+
+        let corner: Vec<_> = kept.iter().map(|&i| track[i]).collect();
+        let smoothed = spline_path(&corner, 4);
+        assert_eq!(smoothed.len(), 2 * 4 + 1);
+        // Passes through the kept points
+        for (i, p) in corner.iter().enumerate() {
+            assert!(smoothed[i * 4].distance_to(p) < 1e-9);
+        }
+        assert_eq!(spline_path(&corner, 1).len(), corner.len());
+    }
+
+    #[test]
+    fn test_polyline_encoding() {
+        // Example from the format's documentation
+        let points = [
+            GeoPosition::new(38.5, -120.2, 0.0),
+            GeoPosition::new(40.7, -120.95, 0.0),
+            GeoPosition::new(43.252, -126.453, 0.0),
+        ];
+        let encoded = encode_polyline(&points);
+        assert_eq!(encoded, "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+
+        let decoded = decode_polyline(&encoded).unwrap();
+        assert_eq!(decoded.len(), 3);
+        for (a, b) in decoded.iter().zip(&points) {
+            assert!((a.latitude - b.latitude).abs() < 1e-9 && (a.longitude - b.longitude).abs() < 1e-9);
+        }
+        assert_eq!(encode_polyline(&[]), "");
+        assert_eq!(decode_polyline("").unwrap().len(), 0);
+        // Truncated mid-number, and a latitude without its longitude
+        assert!(decode_polyline("_p~").is_none());
+        assert!(decode_polyline("_p~iF").is_none());
+    }
+
+    #[test]
+    fn test_route_corridor() {
+        // East 2 km, then a right turn south 2 km, in a 200 m corridor
+        let a = GeoPosition::new(34.5, 69.2, 3000.0);
+        let b = a.destination(2.0, 90.0);
+        let c = b.destination(2.0, 180.0);
+        let spec = CorridorSpec::new(200.0).with_ceiling(4000.0);
+        let corridor = route_corridor("Route", &[a, a, b, c], &spec).unwrap();
+        assert_eq!(corridor.max_altitude, Some(4000.0));
+
+        let inside = [
+            a,
+            a.destination(1.0, 90.0).destination(0.09, 0.0),
+            // Rounded outer corner, sharp inner one
+            b.destination(0.09, 45.0),
+            b.destination(0.13, 225.0),
+            c.destination(0.09, 180.0),
+        ];
+        for p in &inside {
+            assert!(corridor.contains(p), "{:?} should be inside", p);
+        }
+        let outside = [
+            a.destination(1.0, 90.0).destination(0.11, 0.0),
+            a.destination(0.11, 270.0),
+            b.destination(0.11, 45.0),
+            b.destination(0.15, 225.0),
+            c.destination(1.0, 0.0).destination(0.11, 270.0),
+        ];
+        for p in &outside {
+            assert!(!corridor.contains(p), "{:?} should be outside", p);
+        }
+        let mut high = b;
+        high.altitude = 4500.0;
+        assert!(!corridor.contains(&high));
+
+        // Left turns and U-turns too; a single point is a circle
+        let hairpin = [a, b, a.destination(0.1, 0.0), a.destination(0.2, 0.0)];
+        let corridor = route_corridor("Hairpin", &hairpin, &CorridorSpec::new(50.0)).unwrap();
+        assert!(hairpin.iter().all(|p| corridor.contains(p)));
+        // A route crossing itself stays inside at the crossing; the loop it
+        // closes is a hole
+        let bow = [a, a.destination(2.0, 90.0), a.destination(1.414, 45.0), a.destination(1.414, 135.0)];
+        let corridor = route_corridor("Bow", &bow, &CorridorSpec::new(100.0)).unwrap();
+        assert!(corridor.contains(&a.destination(1.0, 90.0)));
+        assert!(corridor.contains(&a.destination(1.0, 90.0).destination(0.04, 0.0)));
+        assert_eq!(corridor.holes.len(), 1);
+        assert!(!corridor.contains(&a.destination(1.33, 90.0).destination(0.33, 0.0)));
+        assert!(corridor.contains(&bow[2].destination(0.04, 270.0)));
+        let circle = route_corridor("Point", &[a], &CorridorSpec::new(200.0)).unwrap();
+        assert!(circle.contains(&a.destination(0.09, 123.0)));
+        assert!(!circle.contains(&a.destination(0.11, 123.0)));
+
+        assert!(route_corridor("Empty", &[], &spec).is_none());
+        assert!(route_corridor("Flat", &[a, b], &CorridorSpec::new(-5.0)).is_none());
+    }
+
+    #[test]
+    fn test_line_of_sight_over_ridge() {
+        // A 1000 m north-south ridge at 69.25 E on flat 500 m ground
+        let grid = ElevationGrid::from_fn(34.4, 69.1, 0.005, 41, 61, |_, lng| {
+            if (lng - 69.25).abs() < 0.006 { 1500.0 } else { 500.0 }
+        })
+        .unwrap();
+        assert!((grid.elevation_at(&GeoPosition::new(34.5, 69.15, 0.0)).unwrap() - 500.0).abs() < 1e-9);
+        assert!(grid.elevation_at(&GeoPosition::new(35.0, 69.15, 0.0)).is_none());
+
+        let station = GeoPosition::new(34.5, 69.15, 510.0);
+        let low = GeoPosition::new(34.5, 69.35, 1200.0);
+        let blocked = line_of_sight(&station, &low, &grid, 100.0);
+        assert!(!blocked.clear);
+        let ridge = blocked.obstruction.unwrap();
+        assert!((ridge.longitude - 69.25).abs() < 0.01 && ridge.altitude > 1000.0);
+
+        let high = GeoPosition::new(34.5, 69.35, 3000.0);
+        let clear = line_of_sight(&station, &high, &grid, 100.0);
+        assert!(clear.clear && clear.obstruction.is_none());
+
+        let asc = "ncols 3\nnrows 2\nxllcorner 69.0\nyllcorner 34.0\ncellsize 0.1\nNODATA_value -9999\n\
+                   10 20 30\n40 50 -9999\n";
+        let parsed = ElevationGrid::parse_ascii(asc).unwrap();
+        // The first listed row is the northern one
+        assert_eq!(parsed.elevation_at(&GeoPosition::new(34.15, 69.05, 0.0)), Some(10.0));
+        assert_eq!(parsed.elevation_at(&GeoPosition::new(34.05, 69.05, 0.0)), Some(40.0));
+        assert!(parsed.elevation_at(&GeoPosition::new(34.05, 69.25, 0.0)).is_none());
+    }
 }
@@ -0,0 +1,83 @@
+//! Subsystem uptime and error counts
+//!
+//! Each long-lived subsystem (tracker, P2P mesh, CV pipeline, WebSocket hub,
+//! database client) keeps one of these so the API can report how long it
+//! has been up and how many operations have failed since it started.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Start time and failure counter of a subsystem
+#[derive(Debug)]
+pub struct SubsystemHealth {
+    started_at: DateTime<Utc>,
+    errors: AtomicU64,
+}
+
+/// Point-in-time view of a subsystem's health
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub started_at: DateTime<Utc>,
+    pub uptime_seconds: u64,
+    pub errors: u64,
+}
+
+impl SubsystemHealth {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            started_at,
+            errors: AtomicU64::new(0),
+        }
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    /// Count one failed operation
+    pub fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    pub fn report(&self, now: DateTime<Utc>) -> HealthReport {
+        HealthReport {
+            started_at: self.started_at,
+            uptime_seconds: (now - self.started_at).num_seconds().max(0) as u64,
+            errors: self.errors(),
+        }
+    }
+}
+
+impl Default for SubsystemHealth {
+    fn default() -> Self {
+        Self::new(Utc::now())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_uptime_and_errors() {
+        let started = Utc::now();
+        let health = SubsystemHealth::new(started);
+        health.record_error();
+        health.record_error();
+
+        let report = health.report(started + chrono::Duration::seconds(90));
+        assert_eq!(report.uptime_seconds, 90);
+        assert_eq!(report.errors, 2);
+        // A clock behind the start time reports zero uptime
+        assert_eq!(health.report(started - chrono::Duration::seconds(5)).uptime_seconds, 0);
+    }
+}
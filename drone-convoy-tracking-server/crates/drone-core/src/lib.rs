@@ -5,23 +5,85 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use uuid::Uuid;
 
+pub mod builder;
+pub mod clock;
+pub mod custom;
+pub mod cv;
 pub mod error;
 pub mod events;
 pub mod geo;
+pub mod health;
+pub mod validation;
 
+pub use builder::{MissionBuildError, MissionBuilder};
+pub use clock::{ClockStatus, SimulationClock, MAX_TIME_SCALE, MIN_TIME_SCALE};
+pub use custom::{CustomEvent, CustomEventError, EventSchema, SchemaViolation};
+pub use cv::{CvTuning, CvTuningError, HaloConfig, TrackingConfig};
 pub use error::CoreError;
 pub use events::*;
 pub use geo::*;
+pub use health::{HealthReport, SubsystemHealth};
+pub use validation::{
+    TelemetryError, TelemetryField, TelemetryLimits, TelemetryValidator, ValidationStats,
+};
+
+// ============================================================================
+// TENANTS
+// ============================================================================
+
+/// Longest tenant ID; it becomes part of keyspace and file names
+pub const MAX_TENANT_ID_LEN: usize = 32;
+
+/// Customer whose fleet is isolated from every other tenant's
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Lowercase letters, digits and `_`, starting with a letter
+    pub fn parse(id: impl Into<String>) -> Result<Self, CoreError> {
+        let id = id.into();
+        let valid = id.len() <= MAX_TENANT_ID_LEN
+            && id.starts_with(|c: char| c.is_ascii_lowercase())
+            && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            return Err(CoreError::Configuration(format!(
+                "invalid tenant id {:?}: use up to {} lowercase letters, digits and '_', starting with a letter",
+                id, MAX_TENANT_ID_LEN
+            )));
+        }
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = CoreError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::parse(id)
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
 
 // ============================================================================
 // DRONE MODELS
 // ============================================================================
 
 /// Unique identifier for a drone
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct DroneId(pub String);
 
 impl DroneId {
@@ -53,10 +115,11 @@ impl From<&str> for DroneId {
 }
 
 /// Operational status of a drone
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DroneStatus {
     /// Drone is powered on but stationary
+    #[default]
     Standby,
     /// Drone is actively moving along route
     Moving,
@@ -66,8 +129,12 @@ pub enum DroneStatus {
     Rtb,
     /// Drone has lost connection or is offline
     Offline,
+    /// Drone is alive on the far side of a mesh partition
+    Unreachable,
     /// Drone is in maintenance mode
     Maintenance,
+    /// Drone is holding (circling) at a loiter waypoint
+    Loitering,
 }
 
 impl fmt::Display for DroneStatus {
@@ -78,21 +145,48 @@ impl fmt::Display for DroneStatus {
             DroneStatus::Engaged => write!(f, "ENGAGED"),
             DroneStatus::Rtb => write!(f, "RTB"),
             DroneStatus::Offline => write!(f, "OFFLINE"),
+            DroneStatus::Unreachable => write!(f, "UNREACHABLE"),
             DroneStatus::Maintenance => write!(f, "MAINTENANCE"),
+            DroneStatus::Loitering => write!(f, "LOITERING"),
         }
     }
 }
 
-impl Default for DroneStatus {
-    fn default() -> Self {
-        Self::Standby
+impl DroneStatus {
+    /// Whether a drone can go from this status to `to`.
+    ///
+    /// A link can drop and come back in any state but maintenance, which
+    /// is entered on the ground and left for standby. A returning drone
+    /// does not engage or loiter until it is sent back out.
+    pub fn can_transition_to(self, to: DroneStatus) -> bool {
+        use DroneStatus::*;
+        match (self, to) {
+            (from, to) if from == to => true,
+            (Maintenance, to) => to == Standby,
+            (from, Maintenance) => matches!(from, Standby | Offline),
+            (Rtb, Engaged | Loitering) => false,
+            _ => true,
+        }
+    }
+
+    /// This status changed to `to`, if the transition is allowed
+    pub fn transition_to(self, to: DroneStatus) -> Result<DroneStatus, CoreError> {
+        if self.can_transition_to(to) {
+            Ok(to)
+        } else {
+            Err(CoreError::InvalidStateTransition {
+                from: self.to_string(),
+                to: to.to_string(),
+            })
+        }
     }
 }
 
 /// Type of military drone
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DroneType {
+    #[default]
     Mq9Reaper,
     Mq1Predator,
     Rq4GlobalHawk,
@@ -100,12 +194,47 @@ pub enum DroneType {
     Custom(String),
 }
 
-impl Default for DroneType {
-    fn default() -> Self {
-        Self::Mq9Reaper
+/// A drone's position within the convoy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConvoyRole {
+    /// Flies ahead of the convoy
+    Scout,
+    /// Flanks the convoy body
+    Escort,
+    /// Center of the convoy
+    Cargo,
+}
+
+impl fmt::Display for ConvoyRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvoyRole::Scout => write!(f, "SCOUT"),
+            ConvoyRole::Escort => write!(f, "ESCORT"),
+            ConvoyRole::Cargo => write!(f, "CARGO"),
+        }
     }
 }
 
+/// Convoy formation types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Formation {
+    /// Single file line
+    #[default]
+    Line,
+    /// V-shape formation
+    Vee,
+    /// Diamond formation
+    Diamond,
+    /// Echelon (diagonal) formation
+    Echelon,
+    /// Column formation
+    Column,
+    /// Spread formation
+    Spread,
+}
+
 /// Complete drone state including position and telemetry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Drone {
@@ -177,6 +306,13 @@ pub struct Telemetry {
     pub temperature: f64,
     /// Timestamp of this telemetry reading
     pub timestamp: DateTime<Utc>,
+    /// Per-drone report counter set by the sender, used to drop duplicate
+    /// deliveries and detect lost reports (`None` = sender does not number them)
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    /// Camera gimbal attitude (`None` = not reported)
+    #[serde(default)]
+    pub gimbal: Option<GimbalState>,
 }
 
 impl Default for Telemetry {
@@ -190,6 +326,8 @@ impl Default for Telemetry {
             signal_strength: 100,
             temperature: 25.0,
             timestamp: Utc::now(),
+            sequence: None,
+            gimbal: None,
         }
     }
 }
@@ -218,6 +356,74 @@ impl Telemetry {
     }
 }
 
+/// Lowest gimbal tilt (straight down)
+pub const GIMBAL_MIN_TILT: f64 = -90.0;
+/// Highest gimbal tilt
+pub const GIMBAL_MAX_TILT: f64 = 30.0;
+/// Largest optical zoom factor
+pub const GIMBAL_MAX_ZOOM: f64 = 30.0;
+
+/// Camera gimbal attitude, relative to the drone
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GimbalState {
+    /// Degrees right of the drone's heading (-180 to 180)
+    pub pan: f64,
+    /// Degrees above the horizon; -90 looks straight down
+    pub tilt: f64,
+    /// Optical zoom factor, 1 being the widest view
+    pub zoom: f64,
+}
+
+impl Default for GimbalState {
+    fn default() -> Self {
+        Self {
+            pan: 0.0,
+            tilt: GIMBAL_MIN_TILT,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl GimbalState {
+    /// Attitude that points the camera of a drone at `from`, flying on
+    /// `heading`, at `target`; tilt is clamped to the gimbal's range
+    pub fn pointing_at(from: &GeoPosition, heading: f64, target: &GeoPosition, zoom: f64) -> Self {
+        let ground_m = from.distance_to(target) * 1000.0;
+        let pan = (from.bearing_to(target) - heading + 180.0).rem_euclid(360.0) - 180.0;
+        let tilt = (target.altitude - from.altitude).atan2(ground_m).to_degrees();
+        Self {
+            pan,
+            tilt: tilt.clamp(GIMBAL_MIN_TILT, GIMBAL_MAX_TILT),
+            zoom: zoom.clamp(1.0, GIMBAL_MAX_ZOOM),
+        }
+    }
+
+    /// Where the line of sight of a drone at `from` meets the ground
+    /// (altitude 0); `None` when the camera looks at or above the horizon
+    pub fn ground_point(&self, from: &GeoPosition, heading: f64) -> Option<GeoPosition> {
+        if self.tilt >= 0.0 || from.altitude <= 0.0 {
+            return None;
+        }
+        let ground_m = from.altitude / (-self.tilt).to_radians().tan();
+        let mut point = from.destination(ground_m / 1000.0, (heading + self.pan).rem_euclid(360.0));
+        point.altitude = 0.0;
+        Some(point)
+    }
+}
+
+/// Motion the tracker derives from successive position reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DerivedMotion {
+    /// Low-pass filtered course over ground (degrees, 0-360)
+    pub smoothed_heading: f64,
+    /// Vertical speed, positive when climbing (m/s)
+    pub climb_rate: f64,
+    /// Rate of change of ground speed (m/s²)
+    pub acceleration: f64,
+    /// Rate of change of the smoothed heading, positive to the right (deg/s)
+    pub turn_rate: f64,
+}
+
 // ============================================================================
 // WAYPOINT MODELS
 // ============================================================================
@@ -248,6 +454,9 @@ pub struct Waypoint {
     pub expected_arrival: Option<DateTime<Utc>>,
     pub actual_arrival: Option<DateTime<Utc>>,
     pub loiter_time_seconds: Option<u32>,
+    /// Speed limit (km/h) on the leg arriving at this waypoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_limit_kmh: Option<f64>,
 }
 
 impl Waypoint {
@@ -260,6 +469,7 @@ impl Waypoint {
             expected_arrival: None,
             actual_arrival: None,
             loiter_time_seconds: None,
+            speed_limit_kmh: None,
         }
     }
 }
@@ -282,6 +492,38 @@ pub enum WaypointType {
     Emergency,
 }
 
+/// Assessed threat at a waypoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ThreatLevel {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// Photo, document or note attached to a waypoint as intel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaypointAttachment {
+    pub id: Uuid,
+    pub mission_id: MissionId,
+    pub waypoint_id: WaypointId,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+    /// Where the content is kept in the object store
+    #[serde(skip)]
+    pub object_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threat_level: Option<ThreatLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploaded_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 // ============================================================================
 // MISSION MODELS
 // ============================================================================
@@ -313,9 +555,10 @@ impl fmt::Display for MissionId {
 }
 
 /// Mission status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum MissionStatus {
+    #[default]
     Planning,
     Active,
     Paused,
@@ -323,12 +566,6 @@ pub enum MissionStatus {
     Aborted,
 }
 
-impl Default for MissionStatus {
-    fn default() -> Self {
-        Self::Planning
-    }
-}
-
 /// A convoy mission with route and assigned drones
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mission {
@@ -342,6 +579,21 @@ pub struct Mission {
     pub end_time: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Leg distances and bearings, refreshed whenever waypoints change
+    #[serde(default)]
+    pub route: RouteMetrics,
+    /// Command drones over a leg's speed limit back down to it
+    #[serde(default)]
+    pub enforce_speed_limits: bool,
+    /// Area every waypoint must lie in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corridor: Option<Geofence>,
+    /// Regenerate `corridor` from the route whenever it is refreshed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_corridor: Option<CorridorSpec>,
+    /// Formation the assigned drones fly when the mission is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formation: Option<Formation>,
 }
 
 impl Mission {
@@ -358,12 +610,64 @@ impl Mission {
             end_time: None,
             created_at: now,
             updated_at: now,
+            route: RouteMetrics::default(),
+            enforce_speed_limits: false,
+            corridor: None,
+            auto_corridor: None,
+            formation: None,
         }
     }
 
     /// Add a waypoint to the mission route
     pub fn add_waypoint(&mut self, waypoint: Waypoint) {
         self.waypoints.push(waypoint);
+        self.refresh_route();
+        self.updated_at = Utc::now();
+    }
+
+    /// Recompute route metrics and any generated corridor; call after
+    /// editing `waypoints` directly
+    pub fn refresh_route(&mut self) {
+        self.route = RouteMetrics::compute(&self.waypoints);
+        if let Some(spec) = &self.auto_corridor {
+            let route: Vec<GeoPosition> = self.waypoints.iter().map(|wp| wp.position).collect();
+            self.corridor = route_corridor(format!("{} corridor", self.name), &route, spec);
+        }
+    }
+
+    /// Generate the corridor from the route and keep it in sync with
+    /// waypoint edits; `None` stops generating and drops the corridor
+    pub fn set_auto_corridor(&mut self, spec: Option<CorridorSpec>) {
+        if spec.is_none() && self.auto_corridor.is_some() {
+            self.corridor = None;
+        }
+        self.auto_corridor = spec;
+        self.refresh_route();
+        self.updated_at = Utc::now();
+    }
+
+    /// Leg arriving at the waypoint at `index` (none for the first waypoint)
+    pub fn leg_to(&self, index: usize) -> Option<&RouteLeg> {
+        if !self.route.is_current(&self.waypoints) {
+            return None;
+        }
+        index.checked_sub(1).and_then(|leg| self.route.legs.get(leg))
+    }
+
+    /// Speed limit (km/h) on the leg arriving at the waypoint at `index`
+    pub fn speed_limit_to(&self, index: usize) -> Option<f64> {
+        if index == 0 {
+            return None;
+        }
+        self.waypoints.get(index)?.speed_limit_kmh
+    }
+
+    /// Replace the leg speed limits, keyed by the waypoint each leg arrives at
+    pub fn set_speed_limits(&mut self, limits: &HashMap<WaypointId, f64>, enforce: bool) {
+        for waypoint in &mut self.waypoints {
+            waypoint.speed_limit_kmh = limits.get(&waypoint.id).copied();
+        }
+        self.enforce_speed_limits = enforce;
         self.updated_at = Utc::now();
     }
 
@@ -389,16 +693,78 @@ impl Mission {
         self.updated_at = Utc::now();
     }
 
+    /// Abort the mission
+    pub fn abort(&mut self) {
+        self.status = MissionStatus::Aborted;
+        self.end_time = Some(Utc::now());
+        self.updated_at = Utc::now();
+    }
+
     /// Get total route distance in kilometers
     pub fn total_distance_km(&self) -> f64 {
-        if self.waypoints.len() < 2 {
-            return 0.0;
+        if self.route.is_current(&self.waypoints) {
+            return self.route.total_distance_km;
         }
+        RouteMetrics::compute(&self.waypoints).total_distance_km
+    }
+}
+
+/// One leg of a mission route, between consecutive waypoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteLeg {
+    pub from: WaypointId,
+    pub to: WaypointId,
+    /// Great-circle distance in kilometers
+    pub distance_km: f64,
+    /// Initial bearing in degrees (0-360)
+    pub bearing_deg: f64,
+    /// Route distance from the first waypoint to the end of this leg
+    pub cumulative_km: f64,
+}
+
+/// Precomputed route geometry for a mission
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteMetrics {
+    pub legs: Vec<RouteLeg>,
+    pub total_distance_km: f64,
+}
 
-        self.waypoints
+impl RouteMetrics {
+    /// Compute legs for consecutive waypoint pairs
+    pub fn compute(waypoints: &[Waypoint]) -> Self {
+        let mut cumulative_km = 0.0;
+        let legs: Vec<RouteLeg> = waypoints
             .windows(2)
-            .map(|w| w[0].position.distance_to(&w[1].position))
-            .sum()
+            .map(|w| {
+                let distance_km = w[0].position.distance_to(&w[1].position);
+                cumulative_km += distance_km;
+                RouteLeg {
+                    from: w[0].id.clone(),
+                    to: w[1].id.clone(),
+                    distance_km,
+                    bearing_deg: w[0].position.bearing_to(&w[1].position),
+                    cumulative_km,
+                }
+            })
+            .collect();
+
+        Self {
+            legs,
+            total_distance_km: cumulative_km,
+        }
+    }
+
+    /// Whether these metrics still describe `waypoints` (same waypoint sequence)
+    pub fn is_current(&self, waypoints: &[Waypoint]) -> bool {
+        if waypoints.len() < 2 {
+            return self.legs.is_empty();
+        }
+        self.legs.len() == waypoints.len() - 1
+            && self
+                .legs
+                .iter()
+                .zip(waypoints.windows(2))
+                .all(|(leg, w)| leg.from == w[0].id && leg.to == w[1].id)
     }
 }
 
@@ -430,7 +796,7 @@ impl BoundingBox {
 }
 
 /// RGB color for halo visualization
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct HaloColor {
     pub r: u8,
     pub g: u8,
@@ -450,6 +816,42 @@ impl HaloColor {
     pub fn to_bgr(&self) -> (u8, u8, u8) {
         (self.b, self.g, self.r)
     }
+
+    /// Hue in degrees (0-360); `None` for greys, which have too little
+    /// color to tell a hue
+    pub fn hue(&self) -> Option<f64> {
+        let (r, g, b) = (self.r as f64, self.g as f64, self.b as f64);
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        if chroma < MIN_HUE_CHROMA {
+            return None;
+        }
+        let sector = if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        Some(sector * 60.0)
+    }
+
+    /// Angle between two hues in degrees (0-180); `None` if either is grey
+    pub fn hue_difference(&self, other: &HaloColor) -> Option<f64> {
+        let difference = (self.hue()? - other.hue()?).abs();
+        Some(difference.min(360.0 - difference))
+    }
+}
+
+/// Least spread between the strongest and weakest channel for a color to
+/// have a hue
+const MIN_HUE_CHROMA: f64 = 32.0;
+
+/// CSS hex notation, e.g. `#ff0000`
+impl fmt::Display for HaloColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
 }
 
 impl Default for HaloColor {
@@ -488,8 +890,14 @@ pub struct TrackingResult {
     pub bbox: BoundingBox,
     pub halo: Option<DetectedHalo>,
     pub estimated_position: Option<GeoPosition>,
+    /// Horizontal error radius of `estimated_position` (meters)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_uncertainty_m: Option<f64>,
     pub confidence: f64,
     pub frame_timestamp: DateTime<Utc>,
+    /// Camera the frame came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera_id: Option<String>,
 }
 
 impl TrackingResult {
@@ -500,8 +908,10 @@ impl TrackingResult {
             bbox,
             halo: None,
             estimated_position: None,
+            position_uncertainty_m: None,
             confidence: 1.0,
             frame_timestamp: Utc::now(),
+            camera_id: None,
         }
     }
 
@@ -531,7 +941,7 @@ pub enum AlertSeverity {
 }
 
 /// Type of alert
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum AlertType {
     BatteryLow,
@@ -557,6 +967,22 @@ pub struct Alert {
     pub created_at: DateTime<Utc>,
     pub acknowledged: bool,
     pub resolved: bool,
+    /// Frontend hints, added by the API before alerts reach clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presentation: Option<AlertPresentation>,
+}
+
+/// How the frontend should present an alert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertPresentation {
+    /// Banner color (CSS color)
+    pub color: String,
+    /// Sound to play (`None` for silent)
+    pub sound: Option<String>,
+    /// Higher priorities are shown first
+    pub priority: u8,
+    /// Dismiss after this long (`None` keeps it until acknowledged)
+    pub auto_dismiss_seconds: Option<u64>,
 }
 
 impl Alert {
@@ -571,6 +997,7 @@ impl Alert {
             created_at: Utc::now(),
             acknowledged: false,
             resolved: false,
+            presentation: None,
         }
     }
 
@@ -585,6 +1012,195 @@ impl Alert {
     }
 }
 
+/// Battery and fuel alert thresholds, in percent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertThresholds {
+    pub battery_warning: u8,
+    pub battery_critical: u8,
+    pub fuel_warning: u8,
+    pub fuel_critical: u8,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        Self {
+            battery_warning: 30,
+            battery_critical: 15,
+            fuel_warning: 25,
+            fuel_critical: 10,
+        }
+    }
+}
+
+impl AlertThresholds {
+    /// Check that levels are percentages and critical does not exceed warning
+    pub fn validate(&self) -> Result<(), String> {
+        let levels = [
+            self.battery_warning,
+            self.battery_critical,
+            self.fuel_warning,
+            self.fuel_critical,
+        ];
+        if levels.iter().any(|&l| l > 100) {
+            return Err("thresholds must be between 0 and 100".into());
+        }
+        if self.battery_critical > self.battery_warning {
+            return Err("battery_critical must not exceed battery_warning".into());
+        }
+        if self.fuel_critical > self.fuel_warning {
+            return Err("fuel_critical must not exceed fuel_warning".into());
+        }
+        Ok(())
+    }
+}
+
+/// Partial threshold override; unset fields fall through to the less specific level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_warning: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_critical: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel_warning: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel_critical: Option<u8>,
+}
+
+impl ThresholdOverrides {
+    /// Layer these overrides on top of `base`
+    pub fn apply_to(&self, base: AlertThresholds) -> AlertThresholds {
+        AlertThresholds {
+            battery_warning: self.battery_warning.unwrap_or(base.battery_warning),
+            battery_critical: self.battery_critical.unwrap_or(base.battery_critical),
+            fuel_warning: self.fuel_warning.unwrap_or(base.fuel_warning),
+            fuel_critical: self.fuel_critical.unwrap_or(base.fuel_critical),
+        }
+    }
+
+    /// True if no field is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// ============================================================================
+// COMMAND TRANSPORT
+// ============================================================================
+
+/// Link a drone's commands travel over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    /// The P2P mesh
+    #[default]
+    P2p,
+    /// MAVLink over UDP
+    Mavlink,
+    /// An HTTP sidecar next to the autopilot
+    HttpSidecar,
+}
+
+impl fmt::Display for TransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::P2p => write!(f, "p2p"),
+            Self::Mavlink => write!(f, "mavlink"),
+            Self::HttpSidecar => write!(f, "http_sidecar"),
+        }
+    }
+}
+
+/// How commands reach one drone
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportBinding {
+    pub kind: TransportKind,
+    /// `host:port` for MAVLink, base URL for an HTTP sidecar; unused by P2P
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// MAVLink target system; defaults to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_id: Option<u8>,
+}
+
+impl TransportBinding {
+    pub fn new(kind: TransportKind) -> Self {
+        Self {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Check the binding carries what its transport needs
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.kind, self.endpoint.as_deref()) {
+            (TransportKind::P2p, _) => Ok(()),
+            (TransportKind::Mavlink, Some(endpoint)) if endpoint.contains(':') => Ok(()),
+            (TransportKind::Mavlink, _) => Err("mavlink binding needs a host:port endpoint".into()),
+            (TransportKind::HttpSidecar, Some(endpoint)) if endpoint.starts_with("http://") => Ok(()),
+            (TransportKind::HttpSidecar, _) => {
+                Err("http_sidecar binding needs an http:// endpoint".into())
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TELEMETRY SOURCE
+// ============================================================================
+
+/// Where a drone's position reports come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetrySource {
+    /// The built-in simulation
+    Simulated,
+    /// The P2P mesh
+    P2p,
+    /// Reports posted to the REST API
+    Api,
+}
+
+impl fmt::Display for TelemetrySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Simulated => write!(f, "simulated"),
+            Self::P2p => write!(f, "p2p"),
+            Self::Api => write!(f, "api"),
+        }
+    }
+}
+
+// ============================================================================
+// FLEET MARKINGS
+// ============================================================================
+
+/// How a drone is painted: the halo color CV tells it apart by, and the
+/// icon it is drawn with on the map
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DroneMarking {
+    pub halo_color: HaloColor,
+    /// Icon name; `None` keeps the icon for the drone type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+impl DroneMarking {
+    pub fn new(halo_color: HaloColor) -> Self {
+        Self { halo_color, icon: None }
+    }
+
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -593,6 +1209,49 @@ impl Alert {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_gimbal_points_at_target() {
+        let drone = GeoPosition::new(34.55, 69.20, 1000.0);
+        let target = GeoPosition::new(34.55, 69.20, 0.0).destination(1.0, 90.0);
+
+        // Due east of a drone flying north: 90° right, 45° down
+        let gimbal = GimbalState::pointing_at(&drone, 0.0, &target, 4.0);
+        assert!((gimbal.pan - 90.0).abs() < 0.1);
+        assert!((gimbal.tilt + 45.0).abs() < 0.1);
+        assert_eq!(gimbal.zoom, 4.0);
+        let seen = gimbal.ground_point(&drone, 0.0).unwrap();
+        assert!(seen.distance_to(&target) < 0.005);
+
+        // Flying east, the target is dead ahead; above the horizon nothing is seen
+        assert!(GimbalState::pointing_at(&drone, 100.0, &target, 1.0).pan.abs() < 10.1);
+        let up = GimbalState::pointing_at(&drone, 0.0, &GeoPosition::new(34.56, 69.20, 5000.0), 99.0);
+        assert_eq!((up.tilt, up.zoom), (GIMBAL_MAX_TILT, GIMBAL_MAX_ZOOM));
+        assert!(up.ground_point(&drone, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        assert!(DroneStatus::Standby.can_transition_to(DroneStatus::Moving));
+        assert!(DroneStatus::Moving.can_transition_to(DroneStatus::Offline));
+        assert!(DroneStatus::Offline.can_transition_to(DroneStatus::Rtb));
+        assert!(!DroneStatus::Rtb.can_transition_to(DroneStatus::Engaged));
+        assert!(!DroneStatus::Moving.can_transition_to(DroneStatus::Maintenance));
+        assert!(!DroneStatus::Maintenance.can_transition_to(DroneStatus::Moving));
+        assert!(matches!(
+            DroneStatus::Maintenance.transition_to(DroneStatus::Offline),
+            Err(CoreError::InvalidStateTransition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tenant_id_validation() {
+        assert_eq!(TenantId::parse("acme_air2").unwrap().as_str(), "acme_air2");
+        for bad in ["", "Acme", "2fast", "acme-air", "a".repeat(MAX_TENANT_ID_LEN + 1).as_str()] {
+            assert!(TenantId::parse(bad).is_err(), "{:?}", bad);
+        }
+        assert!(serde_json::from_str::<TenantId>("\"drop;table\"").is_err());
+    }
+
     #[test]
     fn test_drone_creation() {
         let drone = Drone::new("REAPER-01", "Alpha Lead");
@@ -620,6 +1279,49 @@ mod tests {
         assert!(distance > 0.0);
     }
 
+    #[test]
+    fn test_route_legs_cached() {
+        let mut mission = Mission::new("Test Mission");
+        mission.add_waypoint(Waypoint::new("WP1", "Start", 34.5553, 69.2075));
+        mission.add_waypoint(Waypoint::new("WP2", "Middle", 34.6234, 69.1123));
+        mission.add_waypoint(Waypoint::new("WP3", "End", 34.7012, 69.0456));
+
+        assert_eq!(mission.route.legs.len(), 2);
+        let leg = mission.leg_to(2).unwrap();
+        assert_eq!(leg.from, WaypointId::new("WP2"));
+        assert!((leg.cumulative_km - mission.total_distance_km()).abs() < 1e-9);
+        assert!(leg.bearing_deg > 270.0 && leg.bearing_deg < 360.0);
+        assert!(mission.leg_to(0).is_none());
+
+        // Direct edits leave the cache stale until refreshed
+        mission.waypoints.pop();
+        assert!(mission.leg_to(1).is_none());
+        assert!((mission.total_distance_km() - mission.route.legs[0].distance_km).abs() < 1e-9);
+        mission.refresh_route();
+        assert_eq!(mission.route.legs.len(), 1);
+    }
+
+    #[test]
+    fn test_threshold_overrides() {
+        let base = AlertThresholds::default();
+        let overrides = ThresholdOverrides {
+            battery_critical: Some(25),
+            ..Default::default()
+        };
+
+        let effective = overrides.apply_to(base);
+        assert_eq!(effective.battery_critical, 25);
+        assert_eq!(effective.battery_warning, base.battery_warning);
+        assert!(effective.validate().is_ok());
+
+        let invalid = ThresholdOverrides {
+            fuel_critical: Some(50),
+            ..Default::default()
+        };
+        assert!(invalid.apply_to(base).validate().is_err());
+        assert!(ThresholdOverrides::default().is_empty());
+    }
+
     #[test]
     fn test_bounding_box_center() {
         let bbox = BoundingBox::new(100, 100, 50, 50);
@@ -632,4 +1334,16 @@ mod tests {
         let color = HaloColor::RED;
         assert_eq!(color.to_bgr(), (0, 0, 255));
     }
+
+    #[test]
+    fn test_halo_color_hex_and_hue() {
+        assert_eq!(HaloColor::new(255, 128, 0).to_string(), "#ff8000");
+        assert_eq!(HaloColor::RED.hue(), Some(0.0));
+        assert_eq!(HaloColor::CYAN.hue(), Some(180.0));
+        assert_eq!(HaloColor::new(128, 128, 140).hue(), None);
+        // Hues wrap around at red
+        let magenta_red = HaloColor::new(255, 0, 64);
+        assert!((magenta_red.hue_difference(&HaloColor::new(255, 64, 0)).unwrap() - 30.1).abs() < 0.1);
+        assert_eq!(HaloColor::BLUE.hue_difference(&HaloColor::GREEN), Some(120.0));
+    }
 }
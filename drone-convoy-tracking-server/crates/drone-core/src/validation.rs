@@ -0,0 +1,419 @@
+//! Telemetry validation and sanitization
+//!
+//! Raw telemetry can carry any `f64`: negative speeds, 720° headings or NaN
+//! temperatures would otherwise reach the database and the UI. Non-finite
+//! values and impossible positions are rejected; recoverable out-of-range
+//! values are clamped. `TelemetryValidator` counts both per field.
+
+use crate::{GeoPosition, Telemetry};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+
+/// Telemetry field a validation result refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetryField {
+    BatteryLevel,
+    FuelLevel,
+    SystemHealth,
+    SignalStrength,
+    Speed,
+    Heading,
+    Temperature,
+    Timestamp,
+    Position,
+}
+
+impl TelemetryField {
+    pub const ALL: [TelemetryField; 9] = [
+        Self::BatteryLevel,
+        Self::FuelLevel,
+        Self::SystemHealth,
+        Self::SignalStrength,
+        Self::Speed,
+        Self::Heading,
+        Self::Temperature,
+        Self::Timestamp,
+        Self::Position,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BatteryLevel => "battery_level",
+            Self::FuelLevel => "fuel_level",
+            Self::SystemHealth => "system_health",
+            Self::SignalStrength => "signal_strength",
+            Self::Speed => "speed",
+            Self::Heading => "heading",
+            Self::Temperature => "temperature",
+            Self::Timestamp => "timestamp",
+            Self::Position => "position",
+        }
+    }
+}
+
+impl fmt::Display for TelemetryField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a telemetry sample failed validation
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TelemetryError {
+    #[error("{field} is not a finite number")]
+    NotFinite { field: TelemetryField },
+
+    #[error("{field} out of range: {value} (allowed {min}..={max})")]
+    OutOfRange {
+        field: TelemetryField,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+
+    #[error("Invalid position: latitude={lat}, longitude={lng}")]
+    InvalidPosition { lat: f64, lng: f64 },
+}
+
+impl TelemetryError {
+    /// Field that caused the error
+    pub fn field(&self) -> TelemetryField {
+        match self {
+            Self::NotFinite { field } | Self::OutOfRange { field, .. } => *field,
+            Self::InvalidPosition { .. } => TelemetryField::Position,
+        }
+    }
+}
+
+/// Accepted value ranges
+#[derive(Debug, Clone, Copy)]
+pub struct TelemetryLimits {
+    /// Maximum plausible ground speed (km/h)
+    pub max_speed_kmh: f64,
+    /// Internal temperature range (°C)
+    pub min_temperature_c: f64,
+    pub max_temperature_c: f64,
+    /// How far ahead of the local clock a reading may be stamped (seconds)
+    pub max_clock_skew_secs: i64,
+}
+
+impl Default for TelemetryLimits {
+    fn default() -> Self {
+        Self {
+            max_speed_kmh: 1500.0,
+            min_temperature_c: -80.0,
+            max_temperature_c: 150.0,
+            max_clock_skew_secs: 30,
+        }
+    }
+}
+
+impl Telemetry {
+    /// Check every field without modifying anything
+    pub fn validate(&self, limits: &TelemetryLimits) -> Result<(), TelemetryError> {
+        let mut copy = self.clone();
+        match copy.sanitize(limits)?.first() {
+            None => Ok(()),
+            Some(&field) => Err(TelemetryError::OutOfRange {
+                field,
+                value: self.raw_value(field),
+                min: limits.min_for(field),
+                max: limits.max_for(field),
+            }),
+        }
+    }
+
+    /// Clamp recoverable values into range, returning the fields changed.
+    /// Non-finite values cannot be repaired and are rejected.
+    pub fn sanitize(&mut self, limits: &TelemetryLimits) -> Result<Vec<TelemetryField>, TelemetryError> {
+        self.sanitize_at(limits, Utc::now())
+    }
+
+    /// [`sanitize`](Self::sanitize) with an explicit current time for the
+    /// clock skew check (e.g. simulated time)
+    pub fn sanitize_at(
+        &mut self,
+        limits: &TelemetryLimits,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<TelemetryField>, TelemetryError> {
+        for (field, value) in [
+            (TelemetryField::Speed, self.speed),
+            (TelemetryField::Heading, self.heading),
+            (TelemetryField::Temperature, self.temperature),
+        ] {
+            if !value.is_finite() {
+                return Err(TelemetryError::NotFinite { field });
+            }
+        }
+
+        let mut clamped = Vec::new();
+
+        for (field, value) in [
+            (TelemetryField::BatteryLevel, &mut self.battery_level),
+            (TelemetryField::FuelLevel, &mut self.fuel_level),
+            (TelemetryField::SystemHealth, &mut self.system_health),
+            (TelemetryField::SignalStrength, &mut self.signal_strength),
+        ] {
+            if *value > 100 {
+                *value = 100;
+                clamped.push(field);
+            }
+        }
+
+        let speed = self.speed.clamp(0.0, limits.max_speed_kmh);
+        if speed != self.speed {
+            self.speed = speed;
+            clamped.push(TelemetryField::Speed);
+        }
+
+        let heading = self.heading.rem_euclid(360.0);
+        if heading != self.heading {
+            self.heading = heading;
+            clamped.push(TelemetryField::Heading);
+        }
+
+        let temperature = self
+            .temperature
+            .clamp(limits.min_temperature_c, limits.max_temperature_c);
+        if temperature != self.temperature {
+            self.temperature = temperature;
+            clamped.push(TelemetryField::Temperature);
+        }
+
+        if self.timestamp > now + chrono::Duration::seconds(limits.max_clock_skew_secs) {
+            self.timestamp = now;
+            clamped.push(TelemetryField::Timestamp);
+        }
+
+        Ok(clamped)
+    }
+
+    fn raw_value(&self, field: TelemetryField) -> f64 {
+        match field {
+            TelemetryField::BatteryLevel => self.battery_level as f64,
+            TelemetryField::FuelLevel => self.fuel_level as f64,
+            TelemetryField::SystemHealth => self.system_health as f64,
+            TelemetryField::SignalStrength => self.signal_strength as f64,
+            TelemetryField::Speed => self.speed,
+            TelemetryField::Heading => self.heading,
+            TelemetryField::Temperature => self.temperature,
+            TelemetryField::Timestamp => self.timestamp.timestamp() as f64,
+            TelemetryField::Position => f64::NAN,
+        }
+    }
+}
+
+impl TelemetryLimits {
+    fn min_for(&self, field: TelemetryField) -> f64 {
+        match field {
+            TelemetryField::Temperature => self.min_temperature_c,
+            TelemetryField::Timestamp => f64::NEG_INFINITY,
+            _ => 0.0,
+        }
+    }
+
+    fn max_for(&self, field: TelemetryField) -> f64 {
+        match field {
+            TelemetryField::Speed => self.max_speed_kmh,
+            TelemetryField::Heading => 360.0,
+            TelemetryField::Temperature => self.max_temperature_c,
+            TelemetryField::Timestamp => {
+                (Utc::now().timestamp() + self.max_clock_skew_secs) as f64
+            }
+            _ => 100.0,
+        }
+    }
+}
+
+/// Reject positions with non-finite or out-of-range coordinates
+pub fn validate_position(position: &GeoPosition) -> Result<(), TelemetryError> {
+    if !(position.latitude.is_finite()
+        && position.longitude.is_finite()
+        && position.altitude.is_finite())
+    {
+        return Err(TelemetryError::NotFinite {
+            field: TelemetryField::Position,
+        });
+    }
+    if !position.is_valid() {
+        return Err(TelemetryError::InvalidPosition {
+            lat: position.latitude,
+            lng: position.longitude,
+        });
+    }
+    Ok(())
+}
+
+/// Rejected / clamped counts per field
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationStats {
+    pub rejected: BTreeMap<TelemetryField, u64>,
+    pub clamped: BTreeMap<TelemetryField, u64>,
+}
+
+/// Sanitizes incoming telemetry and counts what it had to fix or reject
+#[derive(Debug)]
+pub struct TelemetryValidator {
+    limits: TelemetryLimits,
+    rejected: [AtomicU64; TelemetryField::ALL.len()],
+    clamped: [AtomicU64; TelemetryField::ALL.len()],
+}
+
+impl TelemetryValidator {
+    pub fn new(limits: TelemetryLimits) -> Self {
+        Self {
+            limits,
+            rejected: Default::default(),
+            clamped: Default::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &TelemetryLimits {
+        &self.limits
+    }
+
+    /// Validate a position/telemetry pair, returning the sanitized telemetry
+    pub fn check(&self, position: &GeoPosition, telemetry: Telemetry) -> Result<Telemetry, TelemetryError> {
+        self.check_at(position, telemetry, Utc::now())
+    }
+
+    /// [`check`](Self::check) against an explicit current time
+    pub fn check_at(
+        &self,
+        position: &GeoPosition,
+        mut telemetry: Telemetry,
+        now: DateTime<Utc>,
+    ) -> Result<Telemetry, TelemetryError> {
+        let result = validate_position(position).and_then(|_| telemetry.sanitize_at(&self.limits, now));
+
+        match result {
+            Ok(clamped) => {
+                for field in clamped {
+                    self.clamped[field as usize].fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(telemetry)
+            }
+            Err(e) => {
+                self.rejected[e.field() as usize].fetch_add(1, Ordering::Relaxed);
+                Err(e)
+            }
+        }
+    }
+
+    /// Samples rejected because of a field
+    pub fn rejected(&self, field: TelemetryField) -> u64 {
+        self.rejected[field as usize].load(Ordering::Relaxed)
+    }
+
+    /// Samples where a field was clamped
+    pub fn clamped(&self, field: TelemetryField) -> u64 {
+        self.clamped[field as usize].load(Ordering::Relaxed)
+    }
+
+    /// Non-zero counters per field
+    pub fn stats(&self) -> ValidationStats {
+        let mut stats = ValidationStats::default();
+        for field in TelemetryField::ALL {
+            let rejected = self.rejected(field);
+            if rejected > 0 {
+                stats.rejected.insert(field, rejected);
+            }
+            let clamped = self.clamped(field);
+            if clamped > 0 {
+                stats.clamped.insert(field, clamped);
+            }
+        }
+        stats
+    }
+}
+
+impl Default for TelemetryValidator {
+    fn default() -> Self {
+        Self::new(TelemetryLimits::default())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_clamps_out_of_range_values() {
+        let mut telemetry = Telemetry {
+            speed: -12.0,
+            heading: 720.5,
+            battery_level: 140,
+            temperature: 400.0,
+            ..Telemetry::default()
+        };
+
+        let clamped = telemetry.sanitize(&TelemetryLimits::default()).unwrap();
+
+        assert_eq!(telemetry.speed, 0.0);
+        assert!((telemetry.heading - 0.5).abs() < 1e-9);
+        assert_eq!(telemetry.battery_level, 100);
+        assert_eq!(telemetry.temperature, 150.0);
+        assert_eq!(
+            clamped,
+            vec![
+                TelemetryField::BatteryLevel,
+                TelemetryField::Speed,
+                TelemetryField::Heading,
+                TelemetryField::Temperature,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_typed_errors() {
+        let limits = TelemetryLimits::default();
+        assert!(Telemetry::default().validate(&limits).is_ok());
+
+        let nan = Telemetry { temperature: f64::NAN, ..Telemetry::default() };
+        assert_eq!(
+            nan.validate(&limits),
+            Err(TelemetryError::NotFinite { field: TelemetryField::Temperature })
+        );
+
+        let heading = Telemetry { heading: -90.0, ..Telemetry::default() };
+        assert!(matches!(
+            heading.validate(&limits),
+            Err(TelemetryError::OutOfRange { field: TelemetryField::Heading, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validator_counts_per_field() {
+        let validator = TelemetryValidator::default();
+        let position = GeoPosition::new(34.5, 69.2, 3000.0);
+
+        let fixed = validator
+            .check(&position, Telemetry { speed: -1.0, ..Telemetry::default() })
+            .unwrap();
+        assert_eq!(fixed.speed, 0.0);
+        assert!(validator
+            .check(&position, Telemetry { speed: f64::INFINITY, ..Telemetry::default() })
+            .is_err());
+        assert!(validator
+            .check(&GeoPosition::new(f64::NAN, 69.2, 0.0), Telemetry::default())
+            .is_err());
+        assert!(validator
+            .check(&GeoPosition::new(95.0, 69.2, 0.0), Telemetry::default())
+            .is_err());
+
+        assert_eq!(validator.clamped(TelemetryField::Speed), 1);
+        assert_eq!(validator.rejected(TelemetryField::Speed), 1);
+        assert_eq!(validator.rejected(TelemetryField::Position), 2);
+        assert_eq!(validator.stats().rejected.len(), 2);
+    }
+}
@@ -1,7 +1,12 @@
 //! Configuration for the CV module
 
+use crate::projection::ProjectionConfig;
+use crate::scheduler::SchedulerConfig;
 use drone_core::HaloColor;
+pub use drone_core::{CvTuning, HaloConfig, TrackingConfig};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// Configuration for the CV engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +17,15 @@ pub struct CvConfig {
     pub tracking: TrackingConfig,
     /// Rendering settings
     pub rendering: RenderingConfig,
+    /// Frame-rate governor settings
+    #[serde(default)]
+    pub governor: GovernorConfig,
+    /// Geo-projection strategy per camera ID
+    #[serde(default)]
+    pub cameras: HashMap<String, ProjectionConfig>,
+    /// Multi-camera scheduler settings
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
 }
 
 impl Default for CvConfig {
@@ -20,74 +34,9 @@ impl Default for CvConfig {
             halo: HaloConfig::default(),
             tracking: TrackingConfig::default(),
             rendering: RenderingConfig::default(),
-        }
-    }
-}
-
-/// Halo detection configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HaloConfig {
-    /// Target halo color (default: red)
-    pub color: HaloColor,
-    /// Color tolerance for detection (HSV)
-    pub hue_tolerance: f64,
-    pub saturation_min: f64,
-    pub value_min: f64,
-    /// Hough circle detection parameters
-    pub min_radius: i32,
-    pub max_radius: i32,
-    pub dp: f64,           // Inverse ratio of accumulator resolution
-    pub min_dist: f64,     // Minimum distance between circle centers
-    pub param1: f64,       // Canny edge detector threshold
-    pub param2: f64,       // Accumulator threshold for circle centers
-    /// Minimum confidence for detection
-    pub min_confidence: f64,
-}
-
-impl Default for HaloConfig {
-    fn default() -> Self {
-        Self {
-            color: HaloColor::RED,
-            hue_tolerance: 15.0,
-            saturation_min: 100.0,
-            value_min: 100.0,
-            min_radius: 15,
-            max_radius: 100,
-            dp: 1.0,
-            min_dist: 50.0,
-            param1: 100.0,
-            param2: 30.0,
-            min_confidence: 0.7,
-        }
-    }
-}
-
-/// Tracking configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TrackingConfig {
-    /// Maximum frames to keep tracking without detection
-    pub max_frames_to_skip: u32,
-    /// IoU threshold for track association
-    pub iou_threshold: f64,
-    /// Kalman filter process noise
-    pub kalman_process_noise: f64,
-    /// Kalman filter measurement noise
-    pub kalman_measurement_noise: f64,
-    /// Maximum number of active tracks
-    pub max_tracks: usize,
-    /// Minimum frames to confirm a new track
-    pub min_frames_to_confirm: u32,
-}
-
-impl Default for TrackingConfig {
-    fn default() -> Self {
-        Self {
-            max_frames_to_skip: 10,
-            iou_threshold: 0.3,
-            kalman_process_noise: 0.01,
-            kalman_measurement_noise: 0.1,
-            max_tracks: 50,
-            min_frames_to_confirm: 3,
+            governor: GovernorConfig::default(),
+            cameras: HashMap::new(),
+            scheduler: SchedulerConfig::default(),
         }
     }
 }
@@ -131,6 +80,43 @@ impl Default for RenderingConfig {
     }
 }
 
+/// Frame-rate governor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GovernorConfig {
+    /// Enable adaptive frame skipping
+    pub enabled: bool,
+    /// Target processing rate (frames per second)
+    pub target_fps: f64,
+    /// Frames older than this are dropped without processing
+    pub max_frame_age: Duration,
+    /// EWMA smoothing factor for latency (0-1, higher reacts faster)
+    pub latency_smoothing: f64,
+    /// Reduce processing resolution under load
+    pub downsample_enabled: bool,
+    /// Lowest resolution scale when downsampling
+    pub min_scale: f64,
+    /// Scale change per adaptation step
+    pub scale_step: f64,
+    /// Load (latency / frame budget) below which resolution is restored
+    pub upscale_load: f64,
+}
+
+impl Default for GovernorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_fps: 30.0,
+            max_frame_age: Duration::from_millis(200),
+            latency_smoothing: 0.2,
+            downsample_enabled: true,
+            min_scale: 0.5,
+            scale_step: 0.1,
+            upscale_load: 0.6,
+        }
+    }
+}
+
 impl CvConfig {
     /// Create config optimized for red halo detection
     pub fn red_halo_tracking() -> Self {
@@ -172,6 +158,10 @@ impl CvConfig {
                 max_frames_to_skip: 5,
                 ..Default::default()
             },
+            governor: GovernorConfig {
+                min_scale: 0.35,
+                ..Default::default()
+            },
             ..Default::default()
         }
     }
@@ -1,15 +1,19 @@
 //! Halo detection using Hough Circle Transform
 //!
 //! Detects circular halos around drones using color filtering and
-//! the Hough Circle Transform algorithm.
+//! the Hough Circle Transform algorithm. With fleet colors set, halos of
+//! every drone's color are detected and reported in that color, so tracks
+//! can be associated with drones by color.
 
-use crate::{CvConfig, CvError, CvResult};
+use crate::{CvConfig, CvError, CvResult, HaloConfig};
 use drone_core::{DetectedHalo, HaloColor};
 use tracing::{debug, trace};
 
 /// Halo detector using OpenCV
 pub struct HaloDetector {
     config: CvConfig,
+    /// Halo colors painted on the fleet (empty = the configured color)
+    fleet_colors: Vec<HaloColor>,
     /// Detection statistics
     stats: DetectionStats,
 }
@@ -28,15 +32,61 @@ impl HaloDetector {
     pub fn new(config: &CvConfig) -> CvResult<Self> {
         Ok(Self {
             config: config.clone(),
+            fleet_colors: Vec::new(),
             stats: DetectionStats::default(),
         })
     }
 
+    /// Replace the detection parameters used from the next frame on
+    pub fn set_halo_config(&mut self, halo: HaloConfig) {
+        self.config.halo = halo;
+    }
+
+    /// Detect halos of these colors from the next frame on (empty = the
+    /// configured color only)
+    pub fn set_fleet_colors(&mut self, colors: Vec<HaloColor>) {
+        self.fleet_colors = colors;
+    }
+
+    /// Colors halos are detected in
+    pub fn target_colors(&self) -> Vec<HaloColor> {
+        if self.fleet_colors.is_empty() {
+            vec![self.config.halo.color]
+        } else {
+            self.fleet_colors.clone()
+        }
+    }
+
+    /// The target color a pixel shows, if it is saturated and bright enough
+    /// and its hue is within tolerance of one
+    pub fn matching_target(&self, pixel: HaloColor, targets: &[HaloColor]) -> Option<HaloColor> {
+        let halo_config = &self.config.halo;
+        let value = pixel.r.max(pixel.g).max(pixel.b) as f64;
+        let chroma = value - pixel.r.min(pixel.g).min(pixel.b) as f64;
+        let saturation = if value > 0.0 { chroma / value * 255.0 } else { 0.0 };
+        if value < halo_config.value_min || saturation < halo_config.saturation_min {
+            return None;
+        }
+        targets
+            .iter()
+            .filter_map(|target| Some((*target, self.hue_match(target, &pixel)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(target, _)| target)
+    }
+
+    /// Hue difference between two colors (degrees) if they are within the
+    /// detection tolerance of each other
+    pub fn hue_match(&self, a: &HaloColor, b: &HaloColor) -> Option<f64> {
+        // OpenCV hues run 0-180, so the tolerance is in half degrees
+        a.hue_difference(b)
+            .filter(|difference| *difference <= self.config.halo.hue_tolerance * 2.0)
+    }
+
     /// Detect halos in a frame
     /// 
     /// Process:
     /// 1. Convert to HSV color space
-    /// 2. Filter for target halo color (red)
+    /// 2. Filter for the target halo colors
     /// 3. Apply morphological operations
     /// 4. Detect circles using Hough Transform
     /// 5. Validate and return detections
@@ -54,23 +104,22 @@ impl HaloDetector {
         let mut hsv = Mat::default();
         imgproc::cvt_color(frame, &mut hsv, imgproc::COLOR_BGR2HSV, 0)?;
 
-        // Create mask for red color (wraps around in HSV)
+        // Mask every target color (ranges around red wrap around in HSV)
         let halo_config = &self.config.halo;
-        
-        // Red has hue around 0 and 180 in OpenCV (0-180 range)
-        let lower_red1 = Scalar::new(0.0, halo_config.saturation_min, halo_config.value_min, 0.0);
-        let upper_red1 = Scalar::new(halo_config.hue_tolerance, 255.0, 255.0, 0.0);
-        
-        let lower_red2 = Scalar::new(180.0 - halo_config.hue_tolerance, halo_config.saturation_min, halo_config.value_min, 0.0);
-        let upper_red2 = Scalar::new(180.0, 255.0, 255.0, 0.0);
+        let targets = self.target_colors();
 
-        let mut mask1 = Mat::default();
-        let mut mask2 = Mat::default();
-        core::in_range(&hsv, &lower_red1, &upper_red1, &mut mask1)?;
-        core::in_range(&hsv, &lower_red2, &upper_red2, &mut mask2)?;
-
-        let mut mask = Mat::default();
-        core::bitwise_or(&mask1, &mask2, &mut mask, &core::no_array())?;
+        let mut mask = Mat::zeros(hsv.rows(), hsv.cols(), core::CV_8UC1)?.to_mat()?;
+        for target in &targets {
+            for (lower_hue, upper_hue) in hue_ranges(target, halo_config.hue_tolerance) {
+                let lower = Scalar::new(lower_hue, halo_config.saturation_min, halo_config.value_min, 0.0);
+                let upper = Scalar::new(upper_hue, 255.0, 255.0, 0.0);
+                let mut range = Mat::default();
+                core::in_range(&hsv, &lower, &upper, &mut range)?;
+                let mut combined = Mat::default();
+                core::bitwise_or(&mask, &range, &mut combined, &core::no_array())?;
+                mask = combined;
+            }
+        }
 
         // Morphological operations to clean up mask
         let kernel = imgproc::get_structuring_element(
@@ -113,15 +162,15 @@ impl HaloDetector {
             let center_y = circle[1] as i32;
             let radius = circle[2] as i32;
 
-            // Calculate confidence based on circle quality
-            let confidence = self.calculate_confidence(frame, center_x, center_y, radius)?;
+            // Calculate confidence and color from the circumference
+            let (color, confidence) = self.sample_halo(frame, center_x, center_y, radius, &targets)?;
 
             if confidence >= halo_config.min_confidence {
                 detections.push(DetectedHalo {
                     center_x,
                     center_y,
                     radius,
-                    color: HaloColor::RED,
+                    color,
                     confidence,
                 });
             }
@@ -139,20 +188,20 @@ impl HaloDetector {
         Ok(Vec::new())
     }
 
-    /// Calculate detection confidence
+    /// Halo color and detection confidence: the target color most pixels
+    /// along the circle circumference show, and the share that show it
     #[cfg(feature = "opencv")]
-    fn calculate_confidence(
+    fn sample_halo(
         &self,
         frame: &opencv::core::Mat,
         center_x: i32,
         center_y: i32,
         radius: i32,
-    ) -> CvResult<f64> {
-        use opencv::{core::Mat, prelude::*};
+        targets: &[HaloColor],
+    ) -> CvResult<(HaloColor, f64)> {
+        use opencv::prelude::*;
 
-        // Verify the circle has strong red color
-        // Sample pixels along the circle circumference
-        let mut red_pixels = 0;
+        let mut votes = vec![0usize; targets.len()];
         let sample_count = 16;
         
         for i in 0..sample_count {
@@ -161,15 +210,25 @@ impl HaloDetector {
             let py = (center_y as f64 + radius as f64 * angle.sin()) as i32;
             
             if px >= 0 && px < frame.cols() && py >= 0 && py < frame.rows() {
+                // BGR format
                 let pixel = frame.at_2d::<opencv::core::Vec3b>(py, px)?;
-                // Check if pixel is reddish (BGR format)
-                if pixel[2] > 150 && pixel[2] > pixel[1] && pixel[2] > pixel[0] {
-                    red_pixels += 1;
+                let pixel = HaloColor::new(pixel[2], pixel[1], pixel[0]);
+                if let Some(target) = self.matching_target(pixel, targets) {
+                    if let Some(idx) = targets.iter().position(|t| *t == target) {
+                        votes[idx] += 1;
+                    }
                 }
             }
         }
 
-        Ok(red_pixels as f64 / sample_count as f64)
+        let (idx, matched) = votes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, count)| **count)
+            .map(|(idx, count)| (idx, *count))
+            .unwrap_or((0, 0));
+        let color = targets.get(idx).copied().unwrap_or_default();
+        Ok((color, matched as f64 / sample_count as f64))
     }
 
     /// Get detection statistics
@@ -183,6 +242,20 @@ impl HaloDetector {
     }
 }
 
+/// OpenCV hue ranges (0-180) within `tolerance` of `color`, split in two
+/// where they wrap around red
+pub fn hue_ranges(color: &HaloColor, tolerance: f64) -> Vec<(f64, f64)> {
+    let hue = color.hue().unwrap_or(0.0) / 2.0;
+    let (lower, upper) = (hue - tolerance, hue + tolerance);
+    if lower < 0.0 {
+        vec![(0.0, upper), (180.0 + lower, 180.0)]
+    } else if upper > 180.0 {
+        vec![(lower, 180.0), (0.0, upper - 180.0)]
+    } else {
+        vec![(lower, upper)]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,4 +266,23 @@ mod tests {
         let detector = HaloDetector::new(&config);
         assert!(detector.is_ok());
     }
+
+    #[test]
+    fn test_fleet_colors_and_hue_ranges() {
+        let mut detector = HaloDetector::new(&CvConfig::default()).unwrap();
+        assert_eq!(detector.target_colors(), vec![HaloColor::RED]);
+        assert_eq!(hue_ranges(&HaloColor::RED, 15.0), vec![(0.0, 15.0), (165.0, 180.0)]);
+        assert_eq!(hue_ranges(&HaloColor::GREEN, 15.0), vec![(45.0, 75.0)]);
+
+        let fleet = vec![HaloColor::RED, HaloColor::GREEN, HaloColor::BLUE];
+        detector.set_fleet_colors(fleet.clone());
+        assert_eq!(detector.target_colors(), fleet);
+
+        // Shaded pixels still match their halo's color; dull ones match none
+        let targets = detector.target_colors();
+        assert_eq!(detector.matching_target(HaloColor::new(40, 200, 60), &targets), Some(HaloColor::GREEN));
+        assert_eq!(detector.matching_target(HaloColor::new(200, 10, 30), &targets), Some(HaloColor::RED));
+        assert_eq!(detector.matching_target(HaloColor::new(150, 140, 145), &targets), None);
+        assert_eq!(detector.matching_target(HaloColor::new(0, 40, 0), &targets), None);
+    }
 }
@@ -20,6 +20,9 @@ pub enum CvError {
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    #[error("Invalid CV parameters: {0}")]
+    Tuning(#[from] drone_core::CvTuningError),
+
     #[error("Camera calibration error: {0}")]
     Calibration(String),
 
@@ -0,0 +1,308 @@
+//! Frame-rate governor for real-time CV processing
+//!
+//! Measures per-frame processing latency and decides which frames to process
+//! so the pipeline keeps up with the camera. When processing falls behind,
+//! frames are skipped (stale frames first) and, optionally, the processing
+//! resolution is reduced until latency recovers.
+
+use crate::config::GovernorConfig;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Window used to compute effective FPS and skip ratio
+const STATS_WINDOW: Duration = Duration::from_secs(1);
+
+/// Decision for an incoming frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameDecision {
+    /// Process the frame at the given resolution scale (1.0 = full resolution)
+    Process { scale: f64 },
+    /// Drop the frame
+    Skip(SkipReason),
+}
+
+/// Why a frame was skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Frame is older than the configured maximum age
+    Stale,
+    /// Processing is slower than the frame budget; thinning the stream
+    Overloaded,
+}
+
+/// Governor statistics snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GovernorStats {
+    pub frames_seen: u64,
+    pub frames_processed: u64,
+    pub frames_skipped: u64,
+    /// Frames processed per second over the last window
+    pub effective_fps: f64,
+    /// Fraction of frames skipped over the last window
+    pub skip_ratio: f64,
+    /// Smoothed processing latency in milliseconds
+    pub avg_latency_ms: f64,
+    /// Current processing resolution scale
+    pub scale: f64,
+}
+
+/// Adaptive frame governor
+#[derive(Debug)]
+pub struct FrameGovernor {
+    config: GovernorConfig,
+    /// Exponentially weighted processing latency
+    avg_latency: Option<Duration>,
+    /// Current resolution scale
+    scale: f64,
+    /// Frames to skip between processed frames
+    skip_interval: u32,
+    /// Frames skipped since the last processed frame
+    skipped_in_row: u32,
+    /// Recent (time, processed) decisions for windowed stats
+    recent: VecDeque<(Instant, bool)>,
+    frames_seen: u64,
+    frames_processed: u64,
+    frames_skipped: u64,
+}
+
+impl FrameGovernor {
+    /// Create a new governor
+    pub fn new(config: GovernorConfig) -> Self {
+        Self {
+            config,
+            avg_latency: None,
+            scale: 1.0,
+            skip_interval: 0,
+            skipped_in_row: 0,
+            recent: VecDeque::new(),
+            frames_seen: 0,
+            frames_processed: 0,
+            frames_skipped: 0,
+        }
+    }
+
+    /// Per-frame processing budget derived from the target FPS
+    pub fn frame_budget(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.config.target_fps.max(1.0))
+    }
+
+    /// Decide whether to process a frame captured at `captured_at`
+    pub fn admit(&mut self, captured_at: Instant) -> FrameDecision {
+        self.admit_at(captured_at, Instant::now())
+    }
+
+    /// Decide whether to process a frame, given the current time
+    pub fn admit_at(&mut self, captured_at: Instant, now: Instant) -> FrameDecision {
+        self.frames_seen += 1;
+
+        let decision = if !self.config.enabled {
+            FrameDecision::Process { scale: 1.0 }
+        } else if now.saturating_duration_since(captured_at) > self.config.max_frame_age {
+            FrameDecision::Skip(SkipReason::Stale)
+        } else if self.skipped_in_row < self.skip_interval {
+            FrameDecision::Skip(SkipReason::Overloaded)
+        } else {
+            FrameDecision::Process { scale: self.scale }
+        };
+
+        match decision {
+            FrameDecision::Process { .. } => self.skipped_in_row = 0,
+            FrameDecision::Skip(_) => {
+                self.skipped_in_row += 1;
+                self.frames_skipped += 1;
+            }
+        }
+
+        self.push_recent(now, matches!(decision, FrameDecision::Process { .. }));
+        decision
+    }
+
+    /// Record the processing latency of an admitted frame and adapt
+    pub fn record_latency(&mut self, latency: Duration) {
+        self.frames_processed += 1;
+
+        let alpha = self.config.latency_smoothing.clamp(0.01, 1.0);
+        let avg = match self.avg_latency {
+            Some(prev) => prev.mul_f64(1.0 - alpha) + latency.mul_f64(alpha),
+            None => latency,
+        };
+        self.avg_latency = Some(avg);
+
+        let budget = self.frame_budget();
+        let load = avg.as_secs_f64() / budget.as_secs_f64();
+
+        // Skip enough frames that processing fits in the frame budget
+        self.skip_interval = (load.ceil() as u32).saturating_sub(1);
+
+        if self.config.downsample_enabled {
+            if load > 1.0 {
+                self.scale = (self.scale - self.config.scale_step).max(self.config.min_scale);
+            } else if load < self.config.upscale_load {
+                self.scale = (self.scale + self.config.scale_step).min(1.0);
+            }
+        }
+    }
+
+    /// Current resolution scale
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Snapshot statistics
+    pub fn stats(&self) -> GovernorStats {
+        self.stats_at(Instant::now())
+    }
+
+    /// Snapshot statistics relative to `now`
+    pub fn stats_at(&self, now: Instant) -> GovernorStats {
+        let window: Vec<_> = self
+            .recent
+            .iter()
+            .filter(|(t, _)| now.saturating_duration_since(*t) <= STATS_WINDOW)
+            .collect();
+
+        let processed = window.iter().filter(|(_, p)| *p).count();
+        let skip_ratio = if window.is_empty() {
+            0.0
+        } else {
+            (window.len() - processed) as f64 / window.len() as f64
+        };
+
+        GovernorStats {
+            frames_seen: self.frames_seen,
+            frames_processed: self.frames_processed,
+            frames_skipped: self.frames_skipped,
+            effective_fps: processed as f64 / STATS_WINDOW.as_secs_f64(),
+            skip_ratio,
+            avg_latency_ms: self
+                .avg_latency
+                .map(|l| l.as_secs_f64() * 1000.0)
+                .unwrap_or(0.0),
+            scale: self.scale,
+        }
+    }
+
+    /// Reset adaptation state and counters
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    fn push_recent(&mut self, now: Instant, processed: bool) {
+        self.recent.push_back((now, processed));
+        while let Some((t, _)) = self.recent.front() {
+            if now.saturating_duration_since(*t) > STATS_WINDOW {
+                self.recent.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for FrameGovernor {
+    fn default() -> Self {
+        Self::new(GovernorConfig::default())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> GovernorConfig {
+        GovernorConfig {
+            target_fps: 30.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_processes_all_frames_when_fast() {
+        let mut governor = FrameGovernor::new(config());
+        let start = Instant::now();
+
+        for i in 0..30 {
+            let now = start + Duration::from_millis(i * 33);
+            assert!(matches!(
+                governor.admit_at(now, now),
+                FrameDecision::Process { scale } if scale == 1.0
+            ));
+            governor.record_latency(Duration::from_millis(10));
+        }
+
+        let stats = governor.stats_at(start + Duration::from_millis(29 * 33));
+        assert_eq!(stats.frames_skipped, 0);
+        assert_eq!(stats.skip_ratio, 0.0);
+        assert_eq!(stats.effective_fps, 30.0);
+    }
+
+    #[test]
+    fn test_skips_when_overloaded() {
+        let mut governor = FrameGovernor::new(GovernorConfig {
+            downsample_enabled: false,
+            ..config()
+        });
+        let start = Instant::now();
+
+        for i in 0..30 {
+            let now = start + Duration::from_millis(i * 33);
+            if let FrameDecision::Process { .. } = governor.admit_at(now, now) {
+                // Roughly 3x the 33ms frame budget
+                governor.record_latency(Duration::from_millis(100));
+            }
+        }
+
+        let stats = governor.stats_at(start + Duration::from_millis(29 * 33));
+        assert!(stats.frames_skipped > 0);
+        assert!(stats.skip_ratio > 0.5);
+        assert!(stats.effective_fps < 15.0);
+    }
+
+    #[test]
+    fn test_drops_stale_frames() {
+        let mut governor = FrameGovernor::new(config());
+        let now = Instant::now();
+        let captured = now - Duration::from_secs(1);
+
+        assert_eq!(
+            governor.admit_at(captured, now),
+            FrameDecision::Skip(SkipReason::Stale)
+        );
+    }
+
+    #[test]
+    fn test_downsamples_under_load_and_recovers() {
+        let mut governor = FrameGovernor::new(config());
+
+        for _ in 0..10 {
+            governor.record_latency(Duration::from_millis(80));
+        }
+        let loaded_scale = governor.scale();
+        assert!(loaded_scale < 1.0);
+        assert!(loaded_scale >= governor.config.min_scale);
+
+        for _ in 0..50 {
+            governor.record_latency(Duration::from_millis(2));
+        }
+        assert_eq!(governor.scale(), 1.0);
+    }
+
+    #[test]
+    fn test_disabled_governor_processes_everything() {
+        let mut governor = FrameGovernor::new(GovernorConfig {
+            enabled: false,
+            ..config()
+        });
+        let now = Instant::now();
+
+        governor.record_latency(Duration::from_millis(500));
+        assert_eq!(
+            governor.admit_at(now - Duration::from_secs(5), now),
+            FrameDecision::Process { scale: 1.0 }
+        );
+    }
+}
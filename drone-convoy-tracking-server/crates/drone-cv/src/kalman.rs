@@ -100,15 +100,15 @@ impl KalmanTracker {
     }
 
     /// Update with a new measurement
+    ///
+    /// Corrects the current state; call [`predict`](Self::predict) first to
+    /// advance it to the measurement's frame.
     pub fn update(&mut self, measured_x: f64, measured_y: f64) -> (f64, f64) {
         if !self.initialized {
             self.initialize(measured_x, measured_y);
             return (measured_x, measured_y);
         }
 
-        // First predict
-        self.predict();
-
         // Measurement residual: y = z - H*x
         let residual_x = measured_x - self.state[0];
         let residual_y = measured_y - self.state[1];
@@ -276,6 +276,7 @@ mod tests {
         for i in 0..10 {
             let measured_x = 100.0 + i as f64 * 10.0;
             let measured_y = 200.0;
+            tracker.predict();
             tracker.update(measured_x, measured_y);
         }
 
@@ -290,13 +291,16 @@ mod tests {
         let mut tracker = KalmanTracker::new(0.01, 0.1);
         tracker.set_dt(1.0);
 
-        // Initialize with known velocity
+        // Two measurements establish the velocity
         tracker.initialize(100.0, 100.0);
-        tracker.update(110.0, 100.0); // Moving 10 pixels right
+        for x in [110.0, 120.0] {
+            tracker.predict();
+            tracker.update(x, 100.0); // Moving 10 pixels right
+        }
 
         // Predict next position
         let predicted = tracker.predict();
-        // Should be around 120 (110 + 10)
-        assert!(predicted.0 > 115.0 && predicted.0 < 125.0);
+        // Should be around 130 (120 + 10)
+        assert!(predicted.0 > 125.0 && predicted.0 < 135.0);
     }
 }
@@ -5,7 +5,7 @@
 //! - Red halo detection using Hough Circle Transform
 //! - Multi-object tracking with unique IDs
 //! - Kalman filtering for smooth position prediction
-//! - Geo-coordinate projection from camera view
+//! - Geo-coordinate projection from camera view (pinhole or GCP homography)
 //!
 //! ## Red Halo Tracking
 //!
@@ -15,6 +15,22 @@
 //! 2. Tracks halos across frames with unique IDs
 //! 3. Draws tracking overlays with ID and geo coordinates
 //! 4. Uses Kalman filtering for smooth position prediction
+//!
+//! When each drone carries its own halo color ([`CvEngine::set_drone_color`],
+//! fed from `FleetMarkingChanged` events by [`follow_markings`]), halos of all fleet colors are
+//! detected and a new track is associated with the drone painted in its color.
+//!
+//! ## Real-time Operation
+//!
+//! A [`FrameGovernor`] measures processing latency and skips stale or excess
+//! frames (optionally downsampling) so the pipeline never falls behind the camera.
+//! With several cameras, a [`MultiCameraScheduler`] processes their frames
+//! concurrently on a bounded worker pool, one tracker per camera.
+//!
+//! ## Testing
+//!
+//! [`SyntheticVideo`] renders scenes of moving halos with noise, occlusions
+//! and crossings, together with ground-truth tracks to score the pipeline.
 
 pub mod detector;
 pub mod kalman;
@@ -22,57 +38,59 @@ pub mod tracker;
 pub mod renderer;
 pub mod error;
 pub mod config;
+pub mod governor;
+pub mod projection;
+pub mod synthetic;
+pub mod scheduler;
 
 pub use detector::HaloDetector;
 pub use kalman::KalmanTracker;
 pub use tracker::DroneTracker;
 pub use renderer::OverlayRenderer;
-pub use error::CvError;
-pub use config::CvConfig;
-
-use drone_core::{BoundingBox, DetectedHalo, DroneId, GeoPosition, HaloColor, TrackingResult};
+pub use error::{CvError, CvResult};
+pub use config::{CvConfig, CvTuning, GovernorConfig, HaloConfig, TrackingConfig};
+pub use governor::{FrameDecision, FrameGovernor, GovernorStats, SkipReason};
+pub use projection::{
+    CameraCalibration, GeoEstimate, GeoProjector, GroundControlPoint, HomographyProjector,
+    PinholeProjector, ProjectionConfig,
+};
+pub use scheduler::{CameraFrame, CameraResults, CameraStats, FrameSource, MultiCameraScheduler, SchedulerConfig};
+pub use synthetic::{
+    GroundTruthPoint, GroundTruthTrack, Occluder, SyntheticClip, SyntheticFrame, SyntheticNoise,
+    SyntheticObject, SyntheticScene, SyntheticVideo,
+};
+
+use drone_core::{BoundingBox, DetectedHalo, DroneId, DroneMarking, Event, EventPayload, FleetMarkingEvent, GeoPosition, HaloColor, TrackingResult};
 use chrono::Utc;
-use parking_lot::RwLock;
+use drone_telemetry::MetricsCollector;
+use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
+/// Camera ID used when a frame does not name its camera
+pub const DEFAULT_CAMERA_ID: &str = "default";
+
 /// Main computer vision engine that coordinates all CV operations
 pub struct CvEngine {
-    config: CvConfig,
+    /// Held for reading while a frame is processed, so a tuning change
+    /// applies between frames
+    config: RwLock<CvConfig>,
     detector: Arc<RwLock<HaloDetector>>,
     tracker: Arc<RwLock<DroneTracker>>,
     renderer: Arc<RwLock<OverlayRenderer>>,
-    /// Camera calibration parameters for geo-projection
-    camera_matrix: Option<CameraCalibration>,
+    /// Geo-projection strategy per camera ID
+    projectors: HashMap<String, Arc<dyn GeoProjector>>,
     /// Active tracking sessions
     active_tracks: Arc<RwLock<HashMap<u32, ActiveTrack>>>,
-}
-
-/// Camera calibration for geo-projection
-#[derive(Debug, Clone)]
-pub struct CameraCalibration {
-    pub focal_length_x: f64,
-    pub focal_length_y: f64,
-    pub principal_point_x: f64,
-    pub principal_point_y: f64,
-    pub camera_altitude: f64,
-    pub camera_position: GeoPosition,
-    pub camera_heading: f64,
-}
-
-impl Default for CameraCalibration {
-    fn default() -> Self {
-        Self {
-            focal_length_x: 1000.0,
-            focal_length_y: 1000.0,
-            principal_point_x: 640.0,
-            principal_point_y: 360.0,
-            camera_altitude: 5000.0,
-            camera_position: GeoPosition::new(34.5553, 69.2075, 5000.0),
-            camera_heading: 0.0,
-        }
-    }
+    /// Halo color painted on each drone
+    drone_colors: RwLock<HashMap<DroneId, HaloColor>>,
+    /// Adaptive frame skipping / downsampling
+    governor: Arc<Mutex<FrameGovernor>>,
+    /// Metrics sink for governor statistics (optional)
+    metrics: Option<Arc<MetricsCollector>>,
 }
 
 /// Active tracking session for a detected drone
@@ -100,14 +118,28 @@ impl CvEngine {
         let detector = HaloDetector::new(&config)?;
         let tracker = DroneTracker::new(&config)?;
         let renderer = OverlayRenderer::new(&config)?;
+        let governor = FrameGovernor::new(config.governor.clone());
+
+        let mut projectors: HashMap<String, Arc<dyn GeoProjector>> = HashMap::new();
+        for (camera_id, projection) in &config.cameras {
+            let projector = projection.build()?;
+            info!("Camera {} uses {} projection", camera_id, projector.name());
+            projectors.insert(camera_id.clone(), Arc::from(projector));
+        }
+        projectors
+            .entry(DEFAULT_CAMERA_ID.to_string())
+            .or_insert_with(|| Arc::new(PinholeProjector::new(CameraCalibration::default())));
 
         Ok(Self {
-            config,
+            config: RwLock::new(config),
             detector: Arc::new(RwLock::new(detector)),
             tracker: Arc::new(RwLock::new(tracker)),
             renderer: Arc::new(RwLock::new(renderer)),
-            camera_matrix: Some(CameraCalibration::default()),
+            projectors,
             active_tracks: Arc::new(RwLock::new(HashMap::new())),
+            drone_colors: RwLock::new(HashMap::new()),
+            governor: Arc::new(Mutex::new(governor)),
+            metrics: None,
         })
     }
 
@@ -120,30 +152,116 @@ impl CvEngine {
     /// 4. Return tracking results
     #[cfg(feature = "opencv")]
     pub fn process_frame(&self, frame: &opencv::core::Mat) -> Result<Vec<TrackingResult>, CvError> {
-        use opencv::prelude::*;
+        self.process_camera_frame(DEFAULT_CAMERA_ID, frame)
+    }
+
+    /// Process a frame from a specific camera, using that camera's projector
+    #[cfg(feature = "opencv")]
+    pub fn process_camera_frame(
+        &self,
+        camera_id: &str,
+        frame: &opencv::core::Mat,
+    ) -> Result<Vec<TrackingResult>, CvError> {
+        self.process_frame_scaled(camera_id, frame, 1.0)
+    }
+
+    /// Process a frame subject to the frame governor
+    ///
+    /// Returns `None` when the frame was skipped (stale, or the pipeline is
+    /// behind). Under load the frame may be downsampled before detection;
+    /// results are always reported in full-resolution pixel coordinates.
+    #[cfg(feature = "opencv")]
+    pub fn process_frame_governed(
+        &self,
+        frame: &opencv::core::Mat,
+        captured_at: std::time::Instant,
+    ) -> Result<Option<Vec<TrackingResult>>, CvError> {
+        use opencv::{core, imgproc};
+
+        let decision = self.governor.lock().admit(captured_at);
+        let scale = match decision {
+            FrameDecision::Process { scale } => scale,
+            FrameDecision::Skip(reason) => {
+                debug!("Skipping frame ({:?})", reason);
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_cv_frame_skipped();
+                }
+                self.report_governor_stats();
+                return Ok(None);
+            }
+        };
+
+        let start = std::time::Instant::now();
+        let results = if scale < 1.0 {
+            let mut scaled = core::Mat::default();
+            imgproc::resize(
+                frame,
+                &mut scaled,
+                core::Size::default(),
+                scale,
+                scale,
+                imgproc::INTER_AREA,
+            )?;
+            self.process_frame_scaled(DEFAULT_CAMERA_ID, &scaled, scale)?
+        } else {
+            self.process_frame_scaled(DEFAULT_CAMERA_ID, frame, 1.0)?
+        };
+        let latency = start.elapsed();
+
+        self.governor.lock().record_latency(latency);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_cv_frame(latency.as_secs_f64(), results.len() as u64);
+        }
+        self.report_governor_stats();
+
+        Ok(Some(results))
+    }
 
-        // Step 1: Detect halos
-        let detections = {
+    /// Process a frame that was resized by `scale` from the camera resolution
+    #[cfg(feature = "opencv")]
+    fn process_frame_scaled(
+        &self,
+        camera_id: &str,
+        frame: &opencv::core::Mat,
+        scale: f64,
+    ) -> Result<Vec<TrackingResult>, CvError> {
+        let projector = self.projector(camera_id)?;
+        let _tuning = self.config.read();
+
+        // Step 1: Detect halos (mapped back to full-resolution coordinates)
+        let detections: Vec<DetectedHalo> = {
             let detector = self.detector.read();
             detector.detect(frame)?
-        };
+        }
+        .into_iter()
+        .map(|halo| unscale_halo(halo, scale))
+        .collect();
 
         debug!("Detected {} halos in frame", detections.len());
 
-        // Step 2: Update tracker
-        let tracks = {
-            let mut tracker = self.tracker.write();
-            tracker.update(&detections)?
-        };
+        // Steps 2-3: Update tracker, project and build results
+        self.track_detections(camera_id, projector.as_ref(), &detections, &self.tracker)
+    }
+
+    /// Update `tracker` with a frame's detections and build results tagged
+    /// with the camera, projecting with its projector
+    fn track_detections(
+        &self,
+        camera_id: &str,
+        projector: &dyn GeoProjector,
+        detections: &[DetectedHalo],
+        tracker: &RwLock<DroneTracker>,
+    ) -> Result<Vec<TrackingResult>, CvError> {
+        let mut tracks = tracker.write().update(detections)?;
+        self.associate_by_color(&mut tracks, tracker);
 
-        // Step 3: Project to geo coordinates and build results
         let mut results = Vec::with_capacity(tracks.len());
-        let calibration = self.camera_matrix.as_ref();
 
         for track in tracks {
-            let estimated_position = calibration.map(|cal| {
-                self.project_to_geo(track.last_detection.center_x, track.last_detection.center_y, cal)
-            });
+            let estimate = projector.project(
+                track.last_detection.center_x as f64,
+                track.last_detection.center_y as f64,
+            );
 
             let bbox = BoundingBox::new(
                 track.last_detection.center_x - track.last_detection.radius,
@@ -157,9 +275,11 @@ impl CvEngine {
 
             let mut result = TrackingResult::new(drone_id, track.tracking_id, bbox);
             result.halo = Some(track.last_detection.clone());
-            result.estimated_position = estimated_position;
+            result.estimated_position = estimate.map(|e| e.position);
+            result.position_uncertainty_m = estimate.map(|e| e.uncertainty_m);
             result.confidence = track.confidence;
             result.frame_timestamp = Utc::now();
+            result.camera_id = Some(camera_id.to_string());
 
             results.push(result);
         }
@@ -167,6 +287,33 @@ impl CvEngine {
         Ok(results)
     }
 
+    /// Associate tracks without a drone with the drone painted in their
+    /// halo color, unless that drone is already tracked
+    fn associate_by_color(&self, tracks: &mut [ActiveTrack], tracker: &RwLock<DroneTracker>) {
+        let colors = self.drone_colors.read();
+        if colors.is_empty() {
+            return;
+        }
+        let detector = self.detector.read();
+        let mut tracker = tracker.write();
+        for track in tracks.iter_mut().filter(|track| track.drone_id.is_none()) {
+            let Some(drone_id) = colors
+                .iter()
+                .filter_map(|(drone_id, color)| Some((drone_id, detector.hue_match(color, &track.last_detection.color)?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(drone_id, _)| drone_id.clone())
+            else {
+                continue;
+            };
+            if tracker.track_of(&drone_id).is_some() {
+                continue;
+            }
+            debug!("Track {} has {}'s halo color", track.tracking_id, drone_id);
+            tracker.associate_drone(track.tracking_id, drone_id.clone());
+            track.drone_id = Some(drone_id);
+        }
+    }
+
     /// Process a frame and render tracking overlays
     /// 
     /// Returns the frame with red halos, tracking IDs, and geo coordinates drawn
@@ -183,38 +330,22 @@ impl CvEngine {
         Ok(results)
     }
 
-    /// Project pixel coordinates to geographic coordinates
-    fn project_to_geo(&self, pixel_x: i32, pixel_y: i32, cal: &CameraCalibration) -> GeoPosition {
-        // Simplified pinhole camera model projection
-        // In production, this would use proper camera calibration and terrain models
-        
-        let dx = (pixel_x as f64 - cal.principal_point_x) / cal.focal_length_x;
-        let dy = (pixel_y as f64 - cal.principal_point_y) / cal.focal_length_y;
-
-        // Convert to ground coordinates (assuming flat terrain)
-        let ground_x = dx * cal.camera_altitude;
-        let ground_y = dy * cal.camera_altitude;
-
-        // Convert to lat/lng offset (simplified)
-        // ~111km per degree latitude, varies for longitude
-        let lat_offset = ground_y / 111000.0;
-        let lng_offset = ground_x / (111000.0 * cal.camera_position.latitude.to_radians().cos());
-
-        // Rotate by camera heading
-        let heading_rad = cal.camera_heading.to_radians();
-        let rotated_lat = lat_offset * heading_rad.cos() - lng_offset * heading_rad.sin();
-        let rotated_lng = lat_offset * heading_rad.sin() + lng_offset * heading_rad.cos();
-
-        GeoPosition::new(
-            cal.camera_position.latitude + rotated_lat,
-            cal.camera_position.longitude + rotated_lng,
-            0.0, // Ground level
-        )
+    /// Projector for a camera
+    fn projector(&self, camera_id: &str) -> Result<Arc<dyn GeoProjector>, CvError> {
+        self.projectors
+            .get(camera_id)
+            .cloned()
+            .ok_or_else(|| CvError::Calibration(format!("no projector configured for camera {}", camera_id)))
     }
 
-    /// Set camera calibration parameters
+    /// Set pinhole calibration parameters for the default camera
     pub fn set_camera_calibration(&mut self, calibration: CameraCalibration) {
-        self.camera_matrix = Some(calibration);
+        self.set_projector(DEFAULT_CAMERA_ID, Arc::new(PinholeProjector::new(calibration)));
+    }
+
+    /// Select the projection strategy for a camera
+    pub fn set_projector(&mut self, camera_id: impl Into<String>, projector: Arc<dyn GeoProjector>) {
+        self.projectors.insert(camera_id.into(), projector);
     }
 
     /// Associate a tracking ID with a specific drone ID
@@ -223,6 +354,48 @@ impl CvEngine {
         tracker.associate_drone(tracking_id, drone_id);
     }
 
+    /// Set (or clear, with `None`) the halo color painted on a drone. Halos
+    /// of every fleet color are detected from the next frame on.
+    pub fn set_drone_color(&self, drone_id: DroneId, color: Option<HaloColor>) {
+        let mut colors = self.drone_colors.write();
+        match color {
+            Some(color) => {
+                colors.insert(drone_id, color);
+            }
+            None => {
+                colors.remove(&drone_id);
+            }
+        }
+        self.detect_fleet_colors(&colors);
+    }
+
+    /// Replace every drone's halo color with the fleet's markings
+    pub fn set_fleet_markings(&self, markings: &[(DroneId, DroneMarking)]) {
+        let mut colors = self.drone_colors.write();
+        *colors = markings
+            .iter()
+            .map(|(drone_id, marking)| (drone_id.clone(), marking.halo_color))
+            .collect();
+        self.detect_fleet_colors(&colors);
+    }
+
+    fn detect_fleet_colors(&self, colors: &HashMap<DroneId, HaloColor>) {
+        let mut fleet: Vec<HaloColor> = colors.values().copied().collect();
+        fleet.sort_by_key(|color| (color.r, color.g, color.b));
+        fleet.dedup();
+        self.detector.write().set_fleet_colors(fleet);
+    }
+
+    /// Apply a `FleetMarkingChanged` event
+    pub fn apply_marking(&self, event: &FleetMarkingEvent) {
+        self.set_drone_color(event.drone_id.clone(), event.marking.as_ref().map(|marking| marking.halo_color));
+    }
+
+    /// Halo color painted on a drone
+    pub fn drone_color(&self, drone_id: &DroneId) -> Option<HaloColor> {
+        self.drone_colors.read().get(drone_id).copied()
+    }
+
     /// Get current active track count
     pub fn active_track_count(&self) -> usize {
         let tracker = self.tracker.read();
@@ -230,9 +403,68 @@ impl CvEngine {
     }
 
     /// Get configuration
-    pub fn config(&self) -> &CvConfig {
-        &self.config
+    pub fn config(&self) -> CvConfig {
+        self.config.read().clone()
+    }
+
+    /// Current detection and tracking parameters
+    pub fn tuning(&self) -> CvTuning {
+        let config = self.config.read();
+        CvTuning {
+            halo: config.halo.clone(),
+            tracking: config.tracking.clone(),
+        }
+    }
+
+    /// Validate and apply new detection and tracking parameters. Frames in
+    /// progress finish with the old values; the next frame sees all new ones.
+    pub fn apply_tuning(&self, tuning: &CvTuning) -> Result<(), CvError> {
+        self.apply_tuning_to(tuning, &[])
+    }
+
+    /// Apply tuning to the engine and to `trackers` kept outside it, all
+    /// between the same two frames
+    fn apply_tuning_to(&self, tuning: &CvTuning, trackers: &[&RwLock<DroneTracker>]) -> Result<(), CvError> {
+        tuning.validate()?;
+        let mut config = self.config.write();
+        self.detector.write().set_halo_config(tuning.halo.clone());
+        self.tracker.write().set_tracking_config(tuning.tracking.clone());
+        for tracker in trackers {
+            tracker.write().set_tracking_config(tuning.tracking.clone());
+        }
+        config.halo = tuning.halo.clone();
+        config.tracking = tuning.tracking.clone();
+        info!("Applied CV tuning: {:?}", tuning);
+        Ok(())
+    }
+
+    /// Attach a metrics collector for frame governor statistics
+    pub fn set_metrics(&mut self, metrics: Arc<MetricsCollector>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Get frame governor statistics
+    pub fn governor_stats(&self) -> GovernorStats {
+        self.governor.lock().stats()
+    }
+
+    /// Publish governor gauges to the metrics collector
+    fn report_governor_stats(&self) {
+        if let Some(metrics) = &self.metrics {
+            let stats = self.governor_stats();
+            metrics.set_cv_governor(stats.effective_fps, stats.skip_ratio, stats.scale);
+        }
+    }
+}
+
+/// Map a halo detected on a downsampled frame back to full resolution
+fn unscale_halo(mut halo: DetectedHalo, scale: f64) -> DetectedHalo {
+    if scale > 0.0 && scale < 1.0 {
+        halo.center_x = (halo.center_x as f64 / scale).round() as i32;
+        halo.center_y = (halo.center_y as f64 / scale).round() as i32;
+        halo.radius = (halo.radius as f64 / scale).round() as i32;
     }
+    halo
 }
 
 impl Default for CvEngine {
@@ -265,14 +497,22 @@ pub struct SimulatedDrone {
 impl CvEngine {
     /// Process a simulated frame (for testing without OpenCV)
     pub fn process_simulated_frame(&self, frame: &SimulatedFrame) -> Vec<TrackingResult> {
-        let calibration = self.camera_matrix.as_ref();
+        self.process_simulated_camera_frame(DEFAULT_CAMERA_ID, frame)
+            .unwrap_or_default()
+    }
+
+    /// Process a simulated frame from a specific camera
+    pub fn process_simulated_camera_frame(
+        &self,
+        camera_id: &str,
+        frame: &SimulatedFrame,
+    ) -> Result<Vec<TrackingResult>, CvError> {
+        let projector = self.projector(camera_id)?;
 
-        frame.drones.iter().enumerate().map(|(idx, drone)| {
+        Ok(frame.drones.iter().enumerate().map(|(idx, drone)| {
             let tracking_id = idx as u32 + 1;
             
-            let estimated_position = calibration.map(|cal| {
-                self.project_to_geo(drone.pixel_x, drone.pixel_y, cal)
-            });
+            let estimate = projector.project(drone.pixel_x as f64, drone.pixel_y as f64);
 
             let bbox = BoundingBox::new(
                 drone.pixel_x - drone.halo_radius,
@@ -285,21 +525,82 @@ impl CvEngine {
                 center_x: drone.pixel_x,
                 center_y: drone.pixel_y,
                 radius: drone.halo_radius,
-                color: HaloColor::RED,
+                color: self.drone_color(&drone.id).unwrap_or(HaloColor::RED),
                 confidence: 0.95,
             };
 
             let mut result = TrackingResult::new(drone.id.clone(), tracking_id, bbox);
             result.halo = Some(halo);
-            result.estimated_position = estimated_position;
+            result.estimated_position = estimate.map(|e| e.position);
+            result.position_uncertainty_m = estimate.map(|e| e.uncertainty_m);
             result.confidence = 0.95;
             result.frame_timestamp = Utc::now();
 
             result
-        }).collect()
+        }).collect())
     }
 }
 
+/// Keep a running pipeline's halo colors on the fleet markings: applies
+/// `markings()` (the tracker's `drone_markings()`), then every
+/// `FleetMarkingChanged` event from `events` (the tracker's `subscribe()`)
+/// with `CvEngine::apply_marking`. Each event changes one drone, so a
+/// follower that lagged behind reloads `markings()` instead.
+pub fn follow_markings<M>(engine: Arc<CvEngine>, markings: M, mut events: broadcast::Receiver<Event>) -> JoinHandle<()>
+where
+    M: Fn() -> Vec<(DroneId, DroneMarking)> + Send + 'static,
+{
+    tokio::spawn(async move {
+        engine.set_fleet_markings(&markings());
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let EventPayload::FleetMarking(change) = &event.payload {
+                        engine.apply_marking(change);
+                        debug!("Applied fleet marking of {}", change.drone_id);
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Fleet marking follower missed {} events, reloading markings", missed);
+                    engine.set_fleet_markings(&markings());
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+/// Keep a running pipeline on the operator's CV parameters: applies
+/// `initial` (the tracker's `cv_tuning()`), then every `CvConfigChanged`
+/// event from `events` (the tracker's `subscribe()`). `apply` is
+/// `CvEngine::apply_tuning`, or `MultiCameraScheduler::apply_tuning` when
+/// cameras run under a scheduler.
+pub fn follow_tuning<F>(initial: CvTuning, mut events: broadcast::Receiver<Event>, apply: F) -> JoinHandle<()>
+where
+    F: Fn(&CvTuning) -> Result<(), CvError> + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(e) = apply(&initial) {
+            warn!("Failed to apply CV parameters: {}", e);
+        }
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let EventPayload::CvConfig(change) = &event.payload {
+                        match apply(&change.tuning) {
+                            Ok(()) => debug!("CV parameters at revision {}", change.revision),
+                            Err(e) => warn!("Failed to apply CV parameters revision {}: {}", change.revision, e),
+                        }
+                    }
+                }
+                // Every change carries the full parameter set, so the next one catches up
+                Err(RecvError::Lagged(missed)) => warn!("CV tuning follower missed {} events", missed),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -314,17 +615,152 @@ mod tests {
         assert!(engine.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_follow_tuning() {
+        let engine = Arc::new(CvEngine::new().unwrap());
+        let (tx, rx) = broadcast::channel(8);
+        let mut initial = engine.tuning();
+        initial.halo.param2 = 45.0;
+        let follower = {
+            let engine = engine.clone();
+            follow_tuning(initial.clone(), rx, move |tuning| engine.apply_tuning(tuning))
+        };
+
+        let mut changed = initial;
+        changed.tracking.max_tracks = 10;
+        tx.send(Event::cv_config_changed(1, changed)).unwrap();
+        drop(tx);
+        follower.await.unwrap();
+        assert_eq!(engine.config().halo.param2, 45.0);
+        assert_eq!(engine.config().tracking.max_tracks, 10);
+    }
+
+    #[tokio::test]
+    async fn test_follow_markings() {
+        let engine = Arc::new(CvEngine::new().unwrap());
+        let (a, b) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"));
+        let (tx, rx) = broadcast::channel(8);
+        let initial = vec![(a.clone(), DroneMarking::new(HaloColor::RED))];
+        let follower = follow_markings(engine.clone(), move || initial.clone(), rx);
+
+        tx.send(Event::fleet_marking_changed(b.clone(), Some(DroneMarking::new(HaloColor::CYAN)))).unwrap();
+        tx.send(Event::fleet_marking_changed(a.clone(), None)).unwrap();
+        drop(tx);
+        follower.await.unwrap();
+        assert_eq!(engine.drone_color(&a), None);
+        assert_eq!(engine.drone_color(&b), Some(HaloColor::CYAN));
+    }
+
+    #[test]
+    fn test_apply_tuning() {
+        let engine = CvEngine::new().unwrap();
+        let mut tuning = engine.tuning();
+        tuning.halo.param2 = 45.0;
+        tuning.tracking.max_tracks = 10;
+        engine.apply_tuning(&tuning).unwrap();
+        assert_eq!(engine.config().halo.param2, 45.0);
+        assert_eq!(engine.config().tracking.max_tracks, 10);
+
+        tuning.halo.dp = 0.0;
+        assert!(matches!(engine.apply_tuning(&tuning), Err(CvError::Tuning(_))));
+        assert_eq!(engine.config().halo.dp, 1.0);
+    }
+
     #[test]
     fn test_geo_projection() {
         let engine = CvEngine::new().unwrap();
-        let cal = CameraCalibration::default();
+        let projector = engine.projector(DEFAULT_CAMERA_ID).unwrap();
         
         // Center pixel should project to camera position
-        let pos = engine.project_to_geo(640, 360, &cal);
+        let pos = projector.project(640.0, 360.0).unwrap().position;
         assert!((pos.latitude - 34.5553).abs() < 0.01);
         assert!((pos.longitude - 69.2075).abs() < 0.01);
     }
 
+    #[test]
+    fn test_projector_per_camera() {
+        let pinhole = PinholeProjector::new(CameraCalibration::default());
+        let gcp = |x: f64, y: f64| GroundControlPoint::new(x, y, pinhole.project(x, y).unwrap().position);
+        let mut config = CvConfig::default();
+        config.cameras.insert(
+            "gimbal-2".into(),
+            ProjectionConfig::Homography {
+                ground_control_points: vec![gcp(0.0, 0.0), gcp(1280.0, 0.0), gcp(1280.0, 720.0), gcp(0.0, 720.0)],
+                pixel_sigma: 2.0,
+            },
+        );
+        let engine = CvEngine::with_config(config).unwrap();
+
+        let frame = SimulatedFrame {
+            width: 1280,
+            height: 720,
+            drones: vec![SimulatedDrone {
+                id: DroneId::new("REAPER-01"),
+                pixel_x: 640,
+                pixel_y: 360,
+                halo_radius: 20,
+            }],
+        };
+        let default = engine.process_simulated_frame(&frame);
+        let gimbal = engine.process_simulated_camera_frame("gimbal-2", &frame).unwrap();
+        assert_eq!(default[0].position_uncertainty_m, Some(5.0));
+        assert!((gimbal[0].position_uncertainty_m.unwrap() - 10.0).abs() < 0.01);
+        assert!(engine.process_simulated_camera_frame("unknown", &frame).is_err());
+    }
+
+    #[test]
+    fn test_unscale_halo() {
+        let halo = DetectedHalo::new(200, 100, 20);
+        let full = unscale_halo(halo.clone(), 0.5);
+        assert_eq!((full.center_x, full.center_y, full.radius), (400, 200, 40));
+
+        let unchanged = unscale_halo(halo, 1.0);
+        assert_eq!(unchanged.center_x, 200);
+    }
+
+    #[test]
+    fn test_tracks_associated_by_halo_color() {
+        let engine = CvEngine::new().expect("engine");
+        let (red, green) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"));
+        engine.set_drone_color(red, Some(HaloColor::RED));
+        engine.set_drone_color(green.clone(), Some(HaloColor::GREEN));
+        assert_eq!(engine.detector.read().target_colors(), vec![HaloColor::GREEN, HaloColor::RED]);
+
+        let halo = |x: i32, color: HaloColor| DetectedHalo {
+            color,
+            ..DetectedHalo::new(x, 300, 20)
+        };
+        let projector = engine.projector(DEFAULT_CAMERA_ID).expect("default camera projector");
+        // Three frames confirm a track
+        let track = |frame: &[DetectedHalo]| {
+            (0..3)
+                .map(|_| engine.track_detections(DEFAULT_CAMERA_ID, projector.as_ref(), frame, &engine.tracker).expect("tracking failed"))
+                .last()
+                .expect("no frames tracked")
+        };
+        let drone_at = |results: &[TrackingResult], x: i32| {
+            results
+                .iter()
+                .find(|r| r.halo.as_ref().is_some_and(|h| h.center_x == x))
+                .map(|r| r.drone_id.as_str().to_string())
+                .unwrap_or_else(|| panic!("no confirmed track at x={}", x))
+        };
+
+        let results = track(&[halo(400, HaloColor::new(40, 210, 50)), halo(800, HaloColor::RED), halo(1000, HaloColor::BLUE)]);
+        assert_eq!(drone_at(&results, 400), "REAPER-02");
+        assert_eq!(drone_at(&results, 800), "REAPER-01");
+        // No drone is painted blue
+        assert!(drone_at(&results, 1000).starts_with("TRACK-"));
+
+        // A second red halo does not take REAPER-01 from its track
+        let results = track(&[halo(400, HaloColor::GREEN), halo(800, HaloColor::RED), halo(100, HaloColor::RED)]);
+        assert_eq!(drone_at(&results, 800), "REAPER-01");
+        assert!(drone_at(&results, 100).starts_with("TRACK-"));
+
+        engine.set_drone_color(green, None);
+        assert_eq!(engine.detector.read().target_colors(), vec![HaloColor::RED]);
+    }
+
     #[test]
     fn test_simulated_frame_processing() {
         let engine = CvEngine::new().unwrap();
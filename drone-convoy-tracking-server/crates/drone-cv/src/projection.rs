@@ -0,0 +1,493 @@
+//! Pixel-to-ground projection strategies
+//!
+//! A [`GeoProjector`] maps a pixel to a ground position together with an
+//! uncertainty radius. The pinhole projector assumes flat terrain below a
+//! calibrated camera; the homography projector is fitted to surveyed ground
+//! control points and does not need the camera pose at all.
+
+use crate::error::{CvError, CvResult};
+use drone_core::GeoPosition;
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// Meters per degree of latitude (and of longitude at the equator)
+const METERS_PER_DEGREE: f64 = 111_000.0;
+
+/// Default detection noise in pixels used for uncertainty estimates
+pub const DEFAULT_PIXEL_SIGMA: f64 = 1.0;
+
+/// Minimum ground control points for homography calibration
+pub const MIN_CONTROL_POINTS: usize = 4;
+
+/// Projected ground position with its uncertainty
+#[derive(Debug, Clone, Copy)]
+pub struct GeoEstimate {
+    pub position: GeoPosition,
+    /// Estimated horizontal error radius (meters)
+    pub uncertainty_m: f64,
+}
+
+/// Strategy for mapping image pixels to ground coordinates
+pub trait GeoProjector: Debug + Send + Sync {
+    /// Short strategy name for logs and diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Project a pixel; `None` if it does not map to the ground (e.g. above the horizon)
+    fn project(&self, pixel_x: f64, pixel_y: f64) -> Option<GeoEstimate>;
+}
+
+// ============================================================================
+// PINHOLE
+// ============================================================================
+
+/// Camera calibration for geo-projection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraCalibration {
+    pub focal_length_x: f64,
+    pub focal_length_y: f64,
+    pub principal_point_x: f64,
+    pub principal_point_y: f64,
+    pub camera_altitude: f64,
+    pub camera_position: GeoPosition,
+    pub camera_heading: f64,
+}
+
+impl Default for CameraCalibration {
+    fn default() -> Self {
+        Self {
+            focal_length_x: 1000.0,
+            focal_length_y: 1000.0,
+            principal_point_x: 640.0,
+            principal_point_y: 360.0,
+            camera_altitude: 5000.0,
+            camera_position: GeoPosition::new(34.5553, 69.2075, 5000.0),
+            camera_heading: 0.0,
+        }
+    }
+}
+
+/// Nadir-looking pinhole camera over flat terrain
+#[derive(Debug, Clone)]
+pub struct PinholeProjector {
+    calibration: CameraCalibration,
+    pixel_sigma: f64,
+}
+
+impl PinholeProjector {
+    pub fn new(calibration: CameraCalibration) -> Self {
+        Self {
+            calibration,
+            pixel_sigma: DEFAULT_PIXEL_SIGMA,
+        }
+    }
+
+    /// Set the detection noise (pixels) used for uncertainty
+    pub fn with_pixel_sigma(mut self, pixel_sigma: f64) -> Self {
+        self.pixel_sigma = pixel_sigma;
+        self
+    }
+
+    pub fn calibration(&self) -> &CameraCalibration {
+        &self.calibration
+    }
+}
+
+impl GeoProjector for PinholeProjector {
+    fn name(&self) -> &'static str {
+        "pinhole"
+    }
+
+    fn project(&self, pixel_x: f64, pixel_y: f64) -> Option<GeoEstimate> {
+        // In production, this would use proper camera calibration and terrain models
+        let cal = &self.calibration;
+        let dx = (pixel_x - cal.principal_point_x) / cal.focal_length_x;
+        let dy = (pixel_y - cal.principal_point_y) / cal.focal_length_y;
+
+        // Convert to ground coordinates (assuming flat terrain)
+        let ground_x = dx * cal.camera_altitude;
+        let ground_y = dy * cal.camera_altitude;
+
+        // Convert to lat/lng offset (simplified)
+        let lat_offset = ground_y / METERS_PER_DEGREE;
+        let lng_offset =
+            ground_x / (METERS_PER_DEGREE * cal.camera_position.latitude.to_radians().cos());
+
+        // Rotate by camera heading
+        let heading_rad = cal.camera_heading.to_radians();
+        let rotated_lat = lat_offset * heading_rad.cos() - lng_offset * heading_rad.sin();
+        let rotated_lng = lat_offset * heading_rad.sin() + lng_offset * heading_rad.cos();
+
+        // Ground sample distance grows with the off-nadir angle, and the
+        // flat-earth assumption gets worse the further out we look
+        let focal = (cal.focal_length_x + cal.focal_length_y) / 2.0;
+        let ground_sample = cal.camera_altitude / focal;
+        let uncertainty_m = self.pixel_sigma * ground_sample * (1.0 + dx * dx + dy * dy);
+
+        Some(GeoEstimate {
+            position: GeoPosition::new(
+                cal.camera_position.latitude + rotated_lat,
+                cal.camera_position.longitude + rotated_lng,
+                0.0, // Ground level
+            ),
+            uncertainty_m,
+        })
+    }
+}
+
+// ============================================================================
+// HOMOGRAPHY
+// ============================================================================
+
+/// A surveyed ground point and where it appears in the image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundControlPoint {
+    pub pixel_x: f64,
+    pub pixel_y: f64,
+    pub position: GeoPosition,
+}
+
+impl GroundControlPoint {
+    pub fn new(pixel_x: f64, pixel_y: f64, position: GeoPosition) -> Self {
+        Self { pixel_x, pixel_y, position }
+    }
+}
+
+type Matrix3 = [[f64; 3]; 3];
+
+/// Plane-to-plane projection fitted to ground control points
+#[derive(Debug, Clone)]
+pub struct HomographyProjector {
+    /// Pixel -> local east/north meters around `origin`
+    homography: Matrix3,
+    origin: GeoPosition,
+    /// Sign of the projective scale for pixels on the ground side of the horizon
+    ground_side: f64,
+    /// RMS reprojection error of the control points (meters)
+    residual_m: f64,
+    pixel_sigma: f64,
+}
+
+impl HomographyProjector {
+    /// Fit a homography to at least four non-collinear control points
+    ///
+    /// With more than four points the fit is least-squares and the RMS
+    /// residual feeds into the projection uncertainty.
+    pub fn calibrate(points: &[GroundControlPoint]) -> CvResult<Self> {
+        if points.len() < MIN_CONTROL_POINTS {
+            return Err(CvError::Calibration(format!(
+                "homography needs at least {} ground control points, got {}",
+                MIN_CONTROL_POINTS,
+                points.len()
+            )));
+        }
+
+        let n = points.len() as f64;
+        let origin = GeoPosition::new(
+            points.iter().map(|p| p.position.latitude).sum::<f64>() / n,
+            points.iter().map(|p| p.position.longitude).sum::<f64>() / n,
+            0.0,
+        );
+
+        let pixels: Vec<[f64; 2]> = points.iter().map(|p| [p.pixel_x, p.pixel_y]).collect();
+        let ground: Vec<[f64; 2]> = points.iter().map(|p| to_local(&origin, &p.position)).collect();
+
+        // Normalize both point sets for a well-conditioned solve
+        let pixel_norm = normalization(&pixels);
+        let ground_norm = normalization(&ground);
+        let pixels_n: Vec<_> = pixels.iter().map(|p| apply(&pixel_norm, *p)).collect();
+        let ground_n: Vec<_> = ground.iter().map(|p| apply(&ground_norm, *p)).collect();
+
+        // Least squares on the normal equations with h33 fixed to 1
+        let mut ata = [[0.0; 8]; 8];
+        let mut atb = [0.0; 8];
+        for (p, g) in pixels_n.iter().zip(&ground_n) {
+            let [x, y] = *p;
+            let [u, v] = *g;
+            let rows = [
+                ([x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u], u),
+                ([0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v], v),
+            ];
+            for (row, b) in rows {
+                for i in 0..8 {
+                    atb[i] += row[i] * b;
+                    for j in 0..8 {
+                        ata[i][j] += row[i] * row[j];
+                    }
+                }
+            }
+        }
+        let h = solve(ata, atb).ok_or_else(|| {
+            CvError::Calibration("ground control points are degenerate (collinear or duplicated)".into())
+        })?;
+
+        let normalized = [[h[0], h[1], h[2]], [h[3], h[4], h[5]], [h[6], h[7], 1.0]];
+        let homography = multiply(&multiply(&invert_normalization(&ground_norm), &normalized), &pixel_norm);
+
+        let center = [
+            pixels.iter().map(|p| p[0]).sum::<f64>() / n,
+            pixels.iter().map(|p| p[1]).sum::<f64>() / n,
+        ];
+        let ground_side = scale(&homography, center).signum();
+
+        let mut projector = Self {
+            homography,
+            origin,
+            ground_side,
+            residual_m: 0.0,
+            pixel_sigma: DEFAULT_PIXEL_SIGMA,
+        };
+
+        let mut squared = 0.0;
+        for (p, g) in pixels.iter().zip(&ground) {
+            let [east, north] = projector.to_ground(*p).ok_or_else(|| {
+                CvError::Calibration("ground control point projects beyond the horizon".into())
+            })?;
+            squared += (east - g[0]).powi(2) + (north - g[1]).powi(2);
+        }
+        projector.residual_m = (squared / n).sqrt();
+
+        Ok(projector)
+    }
+
+    /// Set the detection noise (pixels) used for uncertainty
+    pub fn with_pixel_sigma(mut self, pixel_sigma: f64) -> Self {
+        self.pixel_sigma = pixel_sigma;
+        self
+    }
+
+    /// RMS reprojection error of the control points (meters)
+    pub fn residual_m(&self) -> f64 {
+        self.residual_m
+    }
+
+    fn to_ground(&self, pixel: [f64; 2]) -> Option<[f64; 2]> {
+        let w = scale(&self.homography, pixel);
+        if w.abs() < 1e-12 || w.signum() != self.ground_side {
+            return None;
+        }
+        let [x, y] = pixel;
+        let h = &self.homography;
+        Some([
+            (h[0][0] * x + h[0][1] * y + h[0][2]) / w,
+            (h[1][0] * x + h[1][1] * y + h[1][2]) / w,
+        ])
+    }
+}
+
+impl GeoProjector for HomographyProjector {
+    fn name(&self) -> &'static str {
+        "homography"
+    }
+
+    fn project(&self, pixel_x: f64, pixel_y: f64) -> Option<GeoEstimate> {
+        let ground = self.to_ground([pixel_x, pixel_y])?;
+
+        // Local ground sample distance from the neighbouring pixels
+        let ground_sample = [[pixel_x + 1.0, pixel_y], [pixel_x, pixel_y + 1.0]]
+            .iter()
+            .filter_map(|p| self.to_ground(*p))
+            .map(|g| ((g[0] - ground[0]).powi(2) + (g[1] - ground[1]).powi(2)).sqrt())
+            .fold(0.0, f64::max);
+        let pixel_error = self.pixel_sigma * ground_sample;
+
+        Some(GeoEstimate {
+            position: from_local(&self.origin, ground),
+            uncertainty_m: (self.residual_m.powi(2) + pixel_error.powi(2)).sqrt(),
+        })
+    }
+}
+
+/// East/north meters from `origin` (equirectangular, fine over a camera footprint)
+fn to_local(origin: &GeoPosition, position: &GeoPosition) -> [f64; 2] {
+    [
+        (position.longitude - origin.longitude)
+            * METERS_PER_DEGREE
+            * origin.latitude.to_radians().cos(),
+        (position.latitude - origin.latitude) * METERS_PER_DEGREE,
+    ]
+}
+
+fn from_local(origin: &GeoPosition, [east, north]: [f64; 2]) -> GeoPosition {
+    GeoPosition::new(
+        origin.latitude + north / METERS_PER_DEGREE,
+        origin.longitude + east / (METERS_PER_DEGREE * origin.latitude.to_radians().cos()),
+        0.0,
+    )
+}
+
+/// Similarity transform moving points to zero mean and sqrt(2) mean distance
+fn normalization(points: &[[f64; 2]]) -> Matrix3 {
+    let n = points.len() as f64;
+    let cx = points.iter().map(|p| p[0]).sum::<f64>() / n;
+    let cy = points.iter().map(|p| p[1]).sum::<f64>() / n;
+    let mean_dist = points
+        .iter()
+        .map(|p| ((p[0] - cx).powi(2) + (p[1] - cy).powi(2)).sqrt())
+        .sum::<f64>()
+        / n;
+    let s = if mean_dist > 0.0 { std::f64::consts::SQRT_2 / mean_dist } else { 1.0 };
+    [[s, 0.0, -s * cx], [0.0, s, -s * cy], [0.0, 0.0, 1.0]]
+}
+
+fn invert_normalization(t: &Matrix3) -> Matrix3 {
+    let s = t[0][0];
+    [[1.0 / s, 0.0, -t[0][2] / s], [0.0, 1.0 / s, -t[1][2] / s], [0.0, 0.0, 1.0]]
+}
+
+fn apply(t: &Matrix3, [x, y]: [f64; 2]) -> [f64; 2] {
+    [t[0][0] * x + t[0][1] * y + t[0][2], t[1][0] * x + t[1][1] * y + t[1][2]]
+}
+
+fn scale(h: &Matrix3, [x, y]: [f64; 2]) -> f64 {
+    h[2][0] * x + h[2][1] * y + h[2][2]
+}
+
+fn multiply(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// Gaussian elimination with partial pivoting; `None` if singular
+fn solve(mut a: [[f64; 8]; 8], mut b: [f64; 8]) -> Option<[f64; 8]> {
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-10 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        let pivot_b = b[col];
+        for (row, rhs) in lower.iter_mut().zip(&mut b[col + 1..]) {
+            let factor = row[col] / pivot_row[col];
+            for (cell, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *cell -= factor * p;
+            }
+            *rhs -= factor * pivot_b;
+        }
+    }
+
+    let mut x = [0.0; 8];
+    for row in (0..8).rev() {
+        let sum: f64 = (row + 1..8).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+/// Projection strategy for one camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum ProjectionConfig {
+    Pinhole {
+        calibration: CameraCalibration,
+        #[serde(default = "default_pixel_sigma")]
+        pixel_sigma: f64,
+    },
+    Homography {
+        ground_control_points: Vec<GroundControlPoint>,
+        #[serde(default = "default_pixel_sigma")]
+        pixel_sigma: f64,
+    },
+}
+
+fn default_pixel_sigma() -> f64 {
+    DEFAULT_PIXEL_SIGMA
+}
+
+impl Default for ProjectionConfig {
+    fn default() -> Self {
+        Self::Pinhole {
+            calibration: CameraCalibration::default(),
+            pixel_sigma: DEFAULT_PIXEL_SIGMA,
+        }
+    }
+}
+
+impl ProjectionConfig {
+    /// Build the configured projector (homographies are calibrated here)
+    pub fn build(&self) -> CvResult<Box<dyn GeoProjector>> {
+        Ok(match self {
+            Self::Pinhole { calibration, pixel_sigma } => Box::new(
+                PinholeProjector::new(calibration.clone()).with_pixel_sigma(*pixel_sigma),
+            ),
+            Self::Homography { ground_control_points, pixel_sigma } => Box::new(
+                HomographyProjector::calibrate(ground_control_points)?.with_pixel_sigma(*pixel_sigma),
+            ),
+        })
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinhole_uncertainty_grows_off_nadir() {
+        let projector = PinholeProjector::new(CameraCalibration::default());
+        let center = projector.project(640.0, 360.0).unwrap();
+        let corner = projector.project(0.0, 0.0).unwrap();
+
+        assert!((center.position.latitude - 34.5553).abs() < 1e-9);
+        // 5000 m altitude / 1000 px focal length = 5 m per pixel at nadir
+        assert!((center.uncertainty_m - 5.0).abs() < 1e-9);
+        assert!(corner.uncertainty_m > center.uncertainty_m);
+    }
+
+    #[test]
+    fn test_homography_recovers_pinhole_ground_plane() {
+        // Control points taken from the pinhole model are reproduced exactly
+        let pinhole = PinholeProjector::new(CameraCalibration::default());
+        let gcp = |x: f64, y: f64| GroundControlPoint::new(x, y, pinhole.project(x, y).unwrap().position);
+        let points = [gcp(100.0, 80.0), gcp(1180.0, 90.0), gcp(1150.0, 650.0), gcp(120.0, 640.0)];
+
+        let homography = HomographyProjector::calibrate(&points).unwrap();
+        assert!(homography.residual_m() < 1e-6);
+
+        let expected = pinhole.project(700.0, 300.0).unwrap();
+        let projected = homography.project(700.0, 300.0).unwrap();
+        assert!(expected.position.distance_to(&projected.position) * 1000.0 < 0.01);
+        assert!((projected.uncertainty_m - 5.0).abs() < 0.01);
+
+        // Too few or collinear points are rejected
+        assert!(HomographyProjector::calibrate(&points[..3]).is_err());
+        let line: Vec<_> = (0..4).map(|i| gcp(100.0 * i as f64, 100.0 * i as f64)).collect();
+        assert!(HomographyProjector::calibrate(&line).is_err());
+    }
+
+    #[test]
+    fn test_homography_rejects_points_beyond_horizon() {
+        // Oblique view: image rows converge towards a horizon above the frame
+        let origin = GeoPosition::new(34.5553, 69.2075, 0.0);
+        let points = [
+            GroundControlPoint::new(0.0, 700.0, from_local(&origin, [-50.0, 0.0])),
+            GroundControlPoint::new(1280.0, 700.0, from_local(&origin, [50.0, 0.0])),
+            GroundControlPoint::new(1000.0, 300.0, from_local(&origin, [50.0, 200.0])),
+            GroundControlPoint::new(280.0, 300.0, from_local(&origin, [-50.0, 200.0])),
+            GroundControlPoint::new(640.0, 500.0, from_local(&origin, [0.0, 67.0])),
+        ];
+        let projector = HomographyProjector::calibrate(&points).unwrap();
+        assert!(projector.residual_m() > 0.0);
+
+        let near = projector.project(640.0, 650.0).unwrap();
+        let far = projector.project(640.0, 320.0).unwrap();
+        assert!(far.uncertainty_m > near.uncertainty_m);
+        assert!(projector.project(640.0, -2000.0).is_none());
+    }
+}
@@ -286,9 +286,13 @@ impl OverlayRenderer {
             
             if let Some(pos) = &result.estimated_position {
                 output.push_str(&format!(
-                    "  Geo: {:.6}°N, {:.6}°E\n",
+                    "  Geo: {:.6}°N, {:.6}°E",
                     pos.latitude, pos.longitude
                 ));
+                if let Some(uncertainty) = result.position_uncertainty_m {
+                    output.push_str(&format!(" (±{:.1} m)", uncertainty));
+                }
+                output.push('\n');
             }
             
             output.push_str(&format!(
@@ -329,14 +333,17 @@ mod tests {
                 bbox: BoundingBox::new(100, 100, 60, 60),
                 halo: Some(DetectedHalo::new(130, 130, 30)),
                 estimated_position: Some(GeoPosition::new(34.5553, 69.2075, 0.0)),
+                position_uncertainty_m: Some(5.0),
                 confidence: 0.95,
                 frame_timestamp: chrono::Utc::now(),
+                camera_id: None,
             },
         ];
 
         let text = renderer.format_overlay_text(&results);
         assert!(text.contains("REAPER-01"));
         assert!(text.contains("34.5553"));
+        assert!(text.contains("±5.0 m"));
         assert!(text.contains("95.0%"));
     }
 }
@@ -0,0 +1,480 @@
+//! Multi-camera frame scheduling
+//!
+//! [`CvEngine`] processes one frame at a time. With several cameras the
+//! [`MultiCameraScheduler`] takes frames from all of them and processes the
+//! cameras concurrently on at most `max_workers` blocking threads. Each
+//! camera gets its own [`DroneTracker`], so track IDs and Kalman state never
+//! mix between views, and a camera's frames are always processed in order.
+//! Results carry their camera ID; frame rate and latency are kept per camera
+//! and published to the metrics collector when one is attached.
+
+use crate::{CvEngine, CvError, CvTuning, DroneTracker, SimulatedFrame};
+use drone_core::{DetectedHalo, DroneId, HaloColor, TrackingResult};
+use drone_telemetry::MetricsCollector;
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+
+/// Window used to compute per-camera FPS
+const STATS_WINDOW: Duration = Duration::from_secs(1);
+
+/// EWMA smoothing factor for per-camera latency
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// A frame the scheduler can run detection on
+pub trait CameraFrame: Send + 'static {
+    /// Halos in the frame, in full-resolution pixel coordinates
+    fn detect(&self, engine: &CvEngine) -> Result<Vec<DetectedHalo>, CvError>;
+}
+
+#[cfg(feature = "opencv")]
+impl CameraFrame for opencv::core::Mat {
+    fn detect(&self, engine: &CvEngine) -> Result<Vec<DetectedHalo>, CvError> {
+        engine.detector.read().detect(self)
+    }
+}
+
+/// Simulated drones are detected exactly where they are
+impl CameraFrame for SimulatedFrame {
+    fn detect(&self, _engine: &CvEngine) -> Result<Vec<DetectedHalo>, CvError> {
+        Ok(self
+            .drones
+            .iter()
+            .map(|drone| DetectedHalo {
+                center_x: drone.pixel_x,
+                center_y: drone.pixel_y,
+                radius: drone.halo_radius,
+                color: HaloColor::RED,
+                confidence: 0.95,
+            })
+            .collect())
+    }
+}
+
+/// A camera delivering frames
+pub trait FrameSource: Send {
+    type Frame: CameraFrame;
+
+    fn camera_id(&self) -> &str;
+
+    /// Next frame, `None` once the source has ended
+    fn next_frame(&mut self) -> Result<Option<Self::Frame>, CvError>;
+}
+
+/// Multi-camera scheduler configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Cameras processed at the same time
+    pub max_workers: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_workers: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+        }
+    }
+}
+
+/// Outcome of one frame
+#[derive(Debug)]
+pub struct CameraResults {
+    pub camera_id: String,
+    pub results: Result<Vec<TrackingResult>, CvError>,
+    pub latency: Duration,
+}
+
+/// Processing statistics of one camera
+#[derive(Debug, Clone, Serialize)]
+pub struct CameraStats {
+    pub camera_id: String,
+    pub frames_processed: u64,
+    pub frames_failed: u64,
+    /// Frames processed per second over the last window
+    pub fps: f64,
+    /// Smoothed processing latency in milliseconds
+    pub avg_latency_ms: f64,
+    pub active_tracks: usize,
+}
+
+/// A camera's tracker and statistics
+struct CameraPipeline {
+    tracker: RwLock<DroneTracker>,
+    stats: Mutex<PipelineStats>,
+}
+
+#[derive(Debug, Default)]
+struct PipelineStats {
+    frames_processed: u64,
+    frames_failed: u64,
+    avg_latency: Option<Duration>,
+    /// Completion times of recent frames
+    recent: VecDeque<Instant>,
+}
+
+impl PipelineStats {
+    /// Record a frame and return the camera's FPS
+    fn record(&mut self, latency: Duration, ok: bool, now: Instant) -> f64 {
+        if !ok {
+            self.frames_failed += 1;
+            return self.fps(now);
+        }
+        self.frames_processed += 1;
+        self.avg_latency = Some(match self.avg_latency {
+            Some(prev) => prev.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING),
+            None => latency,
+        });
+        self.recent.push_back(now);
+        while self
+            .recent
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) > STATS_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        self.fps(now)
+    }
+
+    fn fps(&self, now: Instant) -> f64 {
+        let frames = self
+            .recent
+            .iter()
+            .filter(|t| now.saturating_duration_since(**t) <= STATS_WINDOW)
+            .count();
+        frames as f64 / STATS_WINDOW.as_secs_f64()
+    }
+}
+
+/// Processes frames of several cameras concurrently
+pub struct MultiCameraScheduler {
+    engine: Arc<CvEngine>,
+    cameras: RwLock<HashMap<String, Arc<CameraPipeline>>>,
+    workers: Arc<Semaphore>,
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl MultiCameraScheduler {
+    /// Scheduler using the engine's detector, projectors and tuning, with
+    /// the worker count from its `scheduler` configuration
+    pub fn new(engine: Arc<CvEngine>) -> Self {
+        let max_workers = engine.config().scheduler.max_workers.max(1);
+        info!("Multi-camera scheduler with {} workers", max_workers);
+        Self {
+            engine,
+            cameras: RwLock::new(HashMap::new()),
+            workers: Arc::new(Semaphore::new(max_workers)),
+            metrics: None,
+        }
+    }
+
+    /// Attach a metrics collector for per-camera FPS and latency
+    pub fn set_metrics(&mut self, metrics: Arc<MetricsCollector>) {
+        self.metrics = Some(metrics);
+    }
+
+    pub fn engine(&self) -> &Arc<CvEngine> {
+        &self.engine
+    }
+
+    /// Pipeline of a camera, created on its first frame; the camera must
+    /// have a projector
+    fn pipeline(&self, camera_id: &str) -> Result<Arc<CameraPipeline>, CvError> {
+        if let Some(pipeline) = self.cameras.read().get(camera_id) {
+            return Ok(pipeline.clone());
+        }
+        self.engine.projector(camera_id)?;
+        let tracker = DroneTracker::new(&self.engine.config())?;
+        let pipeline = self
+            .cameras
+            .write()
+            .entry(camera_id.to_string())
+            .or_insert_with(|| {
+                debug!("Tracker created for camera {}", camera_id);
+                Arc::new(CameraPipeline {
+                    tracker: RwLock::new(tracker),
+                    stats: Mutex::new(PipelineStats::default()),
+                })
+            })
+            .clone();
+        Ok(pipeline)
+    }
+
+    /// Process frames tagged with their camera ID. Cameras run concurrently;
+    /// several frames of one camera are processed in the given order by the
+    /// same worker. Returns one entry per frame, grouped by camera in order
+    /// of first appearance.
+    pub async fn process_frames<F: CameraFrame>(&self, frames: Vec<(String, F)>) -> Vec<CameraResults> {
+        let mut groups: Vec<(String, Vec<F>)> = Vec::new();
+        for (camera_id, frame) in frames {
+            match groups.iter_mut().find(|(id, _)| *id == camera_id) {
+                Some((_, group)) => group.push(frame),
+                None => groups.push((camera_id, vec![frame])),
+            }
+        }
+
+        let mut workers = Vec::with_capacity(groups.len());
+        for (camera_id, frames) in groups {
+            let pipeline = match self.pipeline(&camera_id) {
+                Ok(pipeline) => pipeline,
+                Err(e) => {
+                    warn!("Dropping {} frames of camera {}: {}", frames.len(), camera_id, e);
+                    workers.push(Err((camera_id, frames.len(), Some(e))));
+                    continue;
+                }
+            };
+            // Waits here while every worker is busy
+            let Ok(permit) = self.workers.clone().acquire_owned().await else {
+                workers.push(Err((camera_id, frames.len(), None)));
+                continue;
+            };
+            let engine = self.engine.clone();
+            let metrics = self.metrics.clone();
+            let handle = tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                frames
+                    .iter()
+                    .map(|frame| process_one(&engine, &camera_id, &pipeline, frame, metrics.as_deref()))
+                    .collect::<Vec<_>>()
+            });
+            workers.push(Ok(handle));
+        }
+
+        let mut results = Vec::new();
+        for worker in workers {
+            match worker {
+                Ok(handle) => match handle.await {
+                    Ok(camera_results) => results.extend(camera_results),
+                    Err(e) => warn!("Camera worker failed: {}", e),
+                },
+                Err((camera_id, frames, mut first)) => {
+                    for _ in 0..frames {
+                        let error = first
+                            .take()
+                            .unwrap_or_else(|| CvError::ResourceUnavailable(format!("camera {} was not processed", camera_id)));
+                        results.push(CameraResults {
+                            camera_id: camera_id.clone(),
+                            results: Err(error),
+                            latency: Duration::ZERO,
+                        });
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Take the next frame of every source and process them together;
+    /// sources that have ended are skipped, so an empty result means all
+    /// have ended
+    pub async fn poll_sources<S: FrameSource>(&self, sources: &mut [S]) -> Vec<CameraResults> {
+        let mut frames = Vec::with_capacity(sources.len());
+        let mut failed = Vec::new();
+        for source in sources.iter_mut() {
+            match source.next_frame() {
+                Ok(Some(frame)) => frames.push((source.camera_id().to_string(), frame)),
+                Ok(None) => {}
+                Err(e) => failed.push(CameraResults {
+                    camera_id: source.camera_id().to_string(),
+                    results: Err(e),
+                    latency: Duration::ZERO,
+                }),
+            }
+        }
+        let mut results = self.process_frames(frames).await;
+        results.extend(failed);
+        results
+    }
+
+    /// Associate a camera's tracking ID with a drone
+    pub fn associate_drone(&self, camera_id: &str, tracking_id: u32, drone_id: DroneId) -> bool {
+        match self.cameras.read().get(camera_id) {
+            Some(pipeline) => {
+                pipeline.tracker.write().associate_drone(tracking_id, drone_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply tuning to the engine and every camera's tracker
+    pub fn apply_tuning(&self, tuning: &CvTuning) -> Result<(), CvError> {
+        let cameras = self.cameras.read();
+        let trackers: Vec<_> = cameras.values().map(|pipeline| &pipeline.tracker).collect();
+        self.engine.apply_tuning_to(tuning, &trackers)
+    }
+
+    /// Drop a camera's tracker and statistics
+    pub fn remove_camera(&self, camera_id: &str) -> bool {
+        self.cameras.write().remove(camera_id).is_some()
+    }
+
+    /// Statistics of every camera that has sent a frame, by camera ID
+    pub fn camera_stats(&self) -> Vec<CameraStats> {
+        let now = Instant::now();
+        let mut stats: Vec<CameraStats> = self
+            .cameras
+            .read()
+            .iter()
+            .map(|(camera_id, pipeline)| {
+                let window = pipeline.stats.lock();
+                CameraStats {
+                    camera_id: camera_id.clone(),
+                    frames_processed: window.frames_processed,
+                    frames_failed: window.frames_failed,
+                    fps: window.fps(now),
+                    avg_latency_ms: window.avg_latency.map(|l| l.as_secs_f64() * 1000.0).unwrap_or(0.0),
+                    active_tracks: pipeline.tracker.read().active_count(),
+                }
+            })
+            .collect();
+        stats.sort_by(|a, b| a.camera_id.cmp(&b.camera_id));
+        stats
+    }
+}
+
+/// Detect, track and project one frame on a worker thread
+fn process_one<F: CameraFrame>(
+    engine: &CvEngine,
+    camera_id: &str,
+    pipeline: &CameraPipeline,
+    frame: &F,
+    metrics: Option<&MetricsCollector>,
+) -> CameraResults {
+    let start = Instant::now();
+    let results = engine.projector(camera_id).and_then(|projector| {
+        // Tuning applies between frames, as on the engine
+        let _tuning = engine.config.read();
+        let detections = frame.detect(engine)?;
+        engine.track_detections(camera_id, projector.as_ref(), &detections, &pipeline.tracker)
+    });
+    let latency = start.elapsed();
+
+    let fps = pipeline.stats.lock().record(latency, results.is_ok(), Instant::now());
+    match (&results, metrics) {
+        (Ok(tracked), Some(metrics)) => {
+            metrics.record_cv_camera_frame(camera_id, latency.as_secs_f64(), tracked.len() as u64);
+            metrics.set_cv_camera_fps(camera_id, fps);
+        }
+        (Err(e), _) => warn!("Frame from camera {} failed: {}", camera_id, e),
+        _ => {}
+    }
+
+    CameraResults {
+        camera_id: camera_id.to_string(),
+        results,
+        latency,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CameraCalibration, CvConfig, ProjectionConfig, SimulatedDrone, DEFAULT_CAMERA_ID};
+
+    /// Replays frames of one drone moving right
+    struct Replay {
+        camera_id: String,
+        frames: VecDeque<SimulatedFrame>,
+    }
+
+    impl Replay {
+        fn new(camera_id: &str, frames: usize) -> Self {
+            let frames = (0..frames as i32)
+                .map(|i| SimulatedFrame {
+                    width: 1280,
+                    height: 720,
+                    drones: vec![SimulatedDrone {
+                        id: DroneId::new("REAPER-01"),
+                        pixel_x: 400 + i * 2,
+                        pixel_y: 300,
+                        halo_radius: 30,
+                    }],
+                })
+                .collect();
+            Self {
+                camera_id: camera_id.into(),
+                frames,
+            }
+        }
+    }
+
+    impl FrameSource for Replay {
+        type Frame = SimulatedFrame;
+
+        fn camera_id(&self) -> &str {
+            &self.camera_id
+        }
+
+        fn next_frame(&mut self) -> Result<Option<SimulatedFrame>, CvError> {
+            Ok(self.frames.pop_front())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cameras_tracked_separately() {
+        let mut config = CvConfig::default();
+        config.scheduler.max_workers = 2;
+        config.cameras.insert(
+            "gimbal-2".into(),
+            ProjectionConfig::Pinhole {
+                calibration: CameraCalibration::default(),
+                pixel_sigma: 1.0,
+            },
+        );
+        let mut scheduler = MultiCameraScheduler::new(Arc::new(CvEngine::with_config(config).unwrap()));
+        let metrics = Arc::new(MetricsCollector::new().unwrap());
+        scheduler.set_metrics(metrics.clone());
+
+        // Tracks confirm on the third frame, in each camera independently
+        let mut sources = vec![Replay::new(DEFAULT_CAMERA_ID, 4), Replay::new("gimbal-2", 3)];
+        for round in 0..3 {
+            let results = scheduler.poll_sources(&mut sources).await;
+            assert_eq!(results.len(), 2);
+            for camera in &results {
+                let tracked = camera.results.as_ref().unwrap();
+                assert_eq!(tracked.len(), if round == 2 { 1 } else { 0 });
+                for result in tracked {
+                    assert_eq!(result.tracking_id, 1);
+                    assert_eq!(result.camera_id.as_deref(), Some(camera.camera_id.as_str()));
+                }
+            }
+        }
+        let results = scheduler.poll_sources(&mut sources).await;
+        assert_eq!(results.len(), 1);
+        assert!(scheduler.poll_sources(&mut sources).await.is_empty());
+
+        // Several frames of one camera in one call keep their order
+        let frames = Replay::new("gimbal-2", 2).frames.into_iter().map(|f| ("gimbal-2".to_string(), f));
+        let mut frames: Vec<_> = frames.collect();
+        frames.push(("unknown".into(), Replay::new("unknown", 1).frames[0].clone()));
+        let results = scheduler.process_frames(frames).await;
+        assert_eq!(results.iter().map(|r| r.camera_id.as_str()).collect::<Vec<_>>(), ["gimbal-2", "gimbal-2", "unknown"]);
+        assert!(matches!(results[2].results, Err(CvError::Calibration(_))));
+
+        let stats = scheduler.camera_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].camera_id.as_str(), stats[0].frames_processed), (DEFAULT_CAMERA_ID, 4));
+        assert_eq!((stats[1].camera_id.as_str(), stats[1].frames_processed), ("gimbal-2", 5));
+        assert!(stats[1].fps > 0.0);
+        assert_eq!(stats[1].active_tracks, 1);
+        let snapshot = metrics.snapshot("after");
+        snapshot.assert_value(r#"drone_convoy_cv_camera_frames_total{camera="gimbal-2"}"#, 5.0);
+
+        assert!(scheduler.associate_drone("gimbal-2", 1, DroneId::new("REAPER-01")));
+        let mut tuning = scheduler.engine().tuning();
+        tuning.tracking.max_tracks = 10;
+        scheduler.apply_tuning(&tuning).unwrap();
+        assert!(scheduler.remove_camera("gimbal-2"));
+        assert_eq!(scheduler.camera_stats().len(), 1);
+    }
+}
@@ -0,0 +1,405 @@
+//! Synthetic video for CV integration tests
+//!
+//! Renders drones as moving red halos with known positions so the full
+//! detect → track → project pipeline can be scored against ground truth.
+//! Scenes can add detection noise, clutter (false halos), rectangular
+//! occluders the drones pass behind, and crossing paths. With the `opencv`
+//! feature each frame is a BGR `Mat`; otherwise it carries the halos a
+//! detector would have found.
+//!
+//! Noise comes from a seeded generator, so a scene renders the same clip
+//! every time.
+
+use crate::CvResult;
+use drone_core::{DetectedHalo, DroneId, HaloColor};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A drone moving in a straight line across the image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticObject {
+    pub drone_id: DroneId,
+    /// Center at frame 0 (pixels)
+    pub start: (f64, f64),
+    /// Pixels per frame
+    pub velocity: (f64, f64),
+    pub radius: i32,
+}
+
+impl SyntheticObject {
+    pub fn center_at(&self, frame: u64) -> (f64, f64) {
+        let t = frame as f64;
+        (self.start.0 + self.velocity.0 * t, self.start.1 + self.velocity.1 * t)
+    }
+}
+
+/// Rectangle hiding any halo whose center is inside it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Occluder {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Occluder {
+    pub fn hides(&self, (x, y): (f64, f64)) -> bool {
+        x >= self.x as f64
+            && x < (self.x + self.width) as f64
+            && y >= self.y as f64
+            && y < (self.y + self.height) as f64
+    }
+}
+
+/// Noise applied to rendered frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticNoise {
+    /// Standard deviation of the rendered halo center (pixels)
+    pub position_sigma_px: f64,
+    /// Probability a visible halo is missing from a frame
+    pub dropout_probability: f64,
+    /// False halos per frame
+    pub clutter_per_frame: usize,
+    /// Standard deviation of per-pixel intensity noise (`opencv` only)
+    pub pixel_sigma: f64,
+}
+
+impl Default for SyntheticNoise {
+    fn default() -> Self {
+        Self {
+            position_sigma_px: 1.0,
+            dropout_probability: 0.0,
+            clutter_per_frame: 0,
+            pixel_sigma: 8.0,
+        }
+    }
+}
+
+/// Scene description for the generator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticScene {
+    pub width: i32,
+    pub height: i32,
+    pub fps: f64,
+    pub start_time: DateTime<Utc>,
+    pub halo_color: HaloColor,
+    pub objects: Vec<SyntheticObject>,
+    pub occluders: Vec<Occluder>,
+    pub noise: SyntheticNoise,
+    pub seed: u64,
+}
+
+impl SyntheticScene {
+    /// Empty 1280x720 scene at 30 fps
+    pub fn new(seed: u64) -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            fps: 30.0,
+            start_time: Utc::now(),
+            halo_color: HaloColor::RED,
+            objects: Vec::new(),
+            occluders: Vec::new(),
+            noise: SyntheticNoise::default(),
+            seed,
+        }
+    }
+
+    /// Two drones whose paths cross in the middle of the image after
+    /// `frames / 2` frames, with an occluder on one of the paths
+    pub fn crossing(seed: u64, frames: u64) -> Self {
+        let mut scene = Self::new(seed);
+        let (w, h) = (scene.width as f64, scene.height as f64);
+        let half = (frames / 2).max(1) as f64;
+        let radius = 30;
+        scene.objects = vec![
+            SyntheticObject {
+                drone_id: DroneId::new("REAPER-01"),
+                start: (w * 0.1, h * 0.2),
+                velocity: (w * 0.4 / half, h * 0.3 / half),
+                radius,
+            },
+            SyntheticObject {
+                drone_id: DroneId::new("REAPER-02"),
+                start: (w * 0.9, h * 0.2),
+                velocity: (-w * 0.4 / half, h * 0.3 / half),
+                radius,
+            },
+        ];
+        scene.occluders = vec![Occluder {
+            x: (w * 0.2) as i32,
+            y: (h * 0.2) as i32,
+            width: (w * 0.1) as i32,
+            height: (h * 0.15) as i32,
+        }];
+        scene
+    }
+
+    pub fn with_object(mut self, object: SyntheticObject) -> Self {
+        self.objects.push(object);
+        self
+    }
+
+    pub fn with_occluder(mut self, occluder: Occluder) -> Self {
+        self.occluders.push(occluder);
+        self
+    }
+
+    pub fn with_noise(mut self, noise: SyntheticNoise) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    fn in_bounds(&self, (x, y): (f64, f64)) -> bool {
+        x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64
+    }
+}
+
+/// True position of one drone in one frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundTruthPoint {
+    pub frame: u64,
+    pub timestamp: DateTime<Utc>,
+    pub center_x: f64,
+    pub center_y: f64,
+    pub radius: i32,
+    /// In the image and not behind an occluder
+    pub visible: bool,
+}
+
+/// Ground-truth track of one drone over a clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundTruthTrack {
+    pub drone_id: DroneId,
+    pub points: Vec<GroundTruthPoint>,
+}
+
+impl GroundTruthTrack {
+    /// Frames in which the drone can be seen
+    pub fn visible_frames(&self) -> usize {
+        self.points.iter().filter(|p| p.visible).count()
+    }
+}
+
+/// One generated frame
+pub struct SyntheticFrame {
+    pub index: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Halos drawn in the frame, clutter included
+    pub halos: Vec<DetectedHalo>,
+    /// Rendered BGR image
+    #[cfg(feature = "opencv")]
+    pub mat: opencv::core::Mat,
+}
+
+/// Rendered frames with their ground truth
+pub struct SyntheticClip {
+    pub frames: Vec<SyntheticFrame>,
+    pub ground_truth: Vec<GroundTruthTrack>,
+}
+
+/// Frame-by-frame generator for a scene
+pub struct SyntheticVideo {
+    scene: SyntheticScene,
+    rng: SplitMix64,
+    frame: u64,
+    truth: Vec<GroundTruthTrack>,
+}
+
+impl SyntheticVideo {
+    pub fn new(scene: SyntheticScene) -> Self {
+        let truth = scene
+            .objects
+            .iter()
+            .map(|o| GroundTruthTrack {
+                drone_id: o.drone_id.clone(),
+                points: Vec::new(),
+            })
+            .collect();
+        Self {
+            rng: SplitMix64(scene.seed),
+            scene,
+            frame: 0,
+            truth,
+        }
+    }
+
+    pub fn scene(&self) -> &SyntheticScene {
+        &self.scene
+    }
+
+    /// Render the next frame and record its ground truth
+    pub fn next_frame(&mut self) -> CvResult<SyntheticFrame> {
+        let index = self.frame;
+        self.frame += 1;
+        let timestamp = self.scene.start_time
+            + Duration::microseconds((index as f64 * 1_000_000.0 / self.scene.fps) as i64);
+
+        let noise = self.scene.noise.clone();
+        let color = self.scene.halo_color;
+        let mut halos = Vec::new();
+        for (object, track) in self.scene.objects.iter().zip(&mut self.truth) {
+            let center = object.center_at(index);
+            let visible = self.scene.in_bounds(center)
+                && !self.scene.occluders.iter().any(|o| o.hides(center));
+            track.points.push(GroundTruthPoint {
+                frame: index,
+                timestamp,
+                center_x: center.0,
+                center_y: center.1,
+                radius: object.radius,
+                visible,
+            });
+
+            if !visible || self.rng.next_f64() < noise.dropout_probability {
+                continue;
+            }
+            let x = center.0 + self.rng.gaussian(noise.position_sigma_px);
+            let y = center.1 + self.rng.gaussian(noise.position_sigma_px);
+            halos.push(halo(color, x, y, object.radius));
+        }
+
+        for _ in 0..noise.clutter_per_frame {
+            let x = self.rng.next_f64() * self.scene.width as f64;
+            let y = self.rng.next_f64() * self.scene.height as f64;
+            let radius = 10 + (self.rng.next_f64() * 20.0) as i32;
+            halos.push(halo(color, x, y, radius));
+        }
+
+        Ok(SyntheticFrame {
+            index,
+            timestamp,
+            #[cfg(feature = "opencv")]
+            mat: self.render(&halos)?,
+            halos,
+        })
+    }
+
+    /// Render `frames` frames
+    pub fn render_clip(mut self, frames: u64) -> CvResult<SyntheticClip> {
+        let frames = (0..frames).map(|_| self.next_frame()).collect::<CvResult<Vec<_>>>()?;
+        Ok(SyntheticClip {
+            frames,
+            ground_truth: self.truth,
+        })
+    }
+
+    /// Ground truth for the frames rendered so far
+    pub fn ground_truth(&self) -> &[GroundTruthTrack] {
+        &self.truth
+    }
+
+    #[cfg(feature = "opencv")]
+    fn render(&mut self, halos: &[DetectedHalo]) -> CvResult<opencv::core::Mat> {
+        use opencv::{
+            core::{self, Mat, Point, Rect, Scalar, CV_16SC3, CV_8UC3},
+            imgproc,
+            prelude::*,
+        };
+
+        let scene = &self.scene;
+        let mut frame =
+            Mat::new_rows_cols_with_default(scene.height, scene.width, CV_8UC3, Scalar::new(70.0, 90.0, 60.0, 0.0))?;
+
+        let (b, g, r) = scene.halo_color.to_bgr();
+        for halo in halos {
+            imgproc::circle(
+                &mut frame,
+                Point::new(halo.center_x, halo.center_y),
+                halo.radius,
+                Scalar::new(b as f64, g as f64, r as f64, 0.0),
+                (halo.radius / 6).max(2),
+                imgproc::LINE_AA,
+                0,
+            )?;
+        }
+
+        for occluder in &scene.occluders {
+            imgproc::rectangle(
+                &mut frame,
+                Rect::new(occluder.x, occluder.y, occluder.width, occluder.height),
+                Scalar::new(120.0, 120.0, 120.0, 0.0),
+                -1, // Filled
+                imgproc::LINE_8,
+                0,
+            )?;
+        }
+
+        if scene.noise.pixel_sigma > 0.0 {
+            core::set_rng_seed(self.rng.next_u64() as i32)?;
+            let mut noise = Mat::new_rows_cols_with_default(scene.height, scene.width, CV_16SC3, Scalar::all(0.0))?;
+            core::randn(&mut noise, &Scalar::all(0.0), &Scalar::all(scene.noise.pixel_sigma))?;
+            let mut noisy = Mat::default();
+            core::add(&frame, &noise, &mut noisy, &core::no_array(), CV_8UC3)?;
+            frame = noisy;
+        }
+
+        Ok(frame)
+    }
+}
+
+fn halo(color: HaloColor, x: f64, y: f64, radius: i32) -> DetectedHalo {
+    let mut halo = DetectedHalo::new(x.round() as i32, y.round() as i32, radius);
+    halo.color = color;
+    halo.confidence = 1.0;
+    halo
+}
+
+/// Small seeded generator; reproducible without a `rand` dependency
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Normal with mean 0 (Box-Muller)
+    fn gaussian(&mut self, sigma: f64) -> f64 {
+        if sigma <= 0.0 {
+            return 0.0;
+        }
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        sigma * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossing_scene_ground_truth() {
+        let frames = 60;
+        let clip = SyntheticVideo::new(SyntheticScene::crossing(7, frames))
+            .render_clip(frames)
+            .unwrap();
+        assert_eq!(clip.frames.len(), 60);
+
+        // The paths cross at the image center halfway through
+        let [a, b] = [&clip.ground_truth[0], &clip.ground_truth[1]];
+        let (pa, pb) = (&a.points[30], &b.points[30]);
+        assert!((pa.center_x - pb.center_x).abs() < 1e-6 && (pa.center_y - pb.center_y).abs() < 1e-6);
+
+        // REAPER-01 passes behind the occluder; REAPER-02 is always visible
+        assert!(a.visible_frames() < 60);
+        assert_eq!(b.visible_frames(), 60);
+        let hidden = a.points.iter().find(|p| !p.visible).unwrap();
+        assert_eq!(clip.frames[hidden.frame as usize].halos.len(), 1);
+
+        // Same seed, same noise
+        let again = SyntheticVideo::new(SyntheticScene::crossing(7, frames)).render_clip(frames).unwrap();
+        let centers = |c: &SyntheticClip| c.frames.iter().flat_map(|f| f.halos.iter().map(|h| (h.center_x, h.center_y))).collect::<Vec<_>>();
+        assert_eq!(centers(&clip), centers(&again));
+    }
+}
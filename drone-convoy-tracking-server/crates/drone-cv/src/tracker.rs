@@ -4,7 +4,7 @@
 //! for detection-to-track association and Kalman filtering for
 //! position prediction.
 
-use crate::{ActiveTrack, CvConfig, CvError, CvResult, KalmanTracker};
+use crate::{ActiveTrack, CvConfig, CvError, CvResult, KalmanTracker, TrackingConfig};
 use drone_core::{DetectedHalo, DroneId, HaloColor};
 use std::collections::HashMap;
 use tracing::{debug, trace, warn};
@@ -46,6 +46,11 @@ impl DroneTracker {
         })
     }
 
+    /// Replace the association parameters; existing tracks are kept
+    pub fn set_tracking_config(&mut self, tracking: TrackingConfig) {
+        self.config.tracking = tracking;
+    }
+
     /// Update tracker with new detections
     /// 
     /// This method:
@@ -86,9 +91,10 @@ impl DroneTracker {
 
         // Step 4: Create new tracks for unmatched detections
         let matched_detections: Vec<usize> = associations.values().copied().collect();
+        let mut created = Vec::new();
         for (idx, detection) in detections.iter().enumerate() {
             if !matched_detections.contains(&idx) && self.tracks.len() < self.config.tracking.max_tracks {
-                self.create_track(detection);
+                created.push(self.create_track(detection));
             }
         }
 
@@ -107,9 +113,10 @@ impl DroneTracker {
             self.drone_associations.remove(&id);
         }
 
-        // Increment frames_since_detection for unmatched tracks
+        // Increment frames_since_detection for unmatched tracks; new tracks
+        // were seen in this frame
         for (id, track) in self.tracks.iter_mut() {
-            if !associations.contains_key(id) {
+            if !associations.contains_key(id) && !created.contains(id) {
                 track.frames_since_detection += 1;
                 track.consecutive_detections = 0;
             }
@@ -271,8 +278,16 @@ impl DroneTracker {
 
     /// Associate a tracking ID with a specific drone
     pub fn associate_drone(&mut self, tracking_id: u32, drone_id: DroneId) {
-        self.drone_associations.insert(tracking_id, drone_id);
         debug!("Associated track {} with drone {}", tracking_id, drone_id);
+        self.drone_associations.insert(tracking_id, drone_id);
+    }
+
+    /// Track associated with a drone, if any
+    pub fn track_of(&self, drone_id: &DroneId) -> Option<u32> {
+        self.drone_associations
+            .iter()
+            .find(|(_, associated)| *associated == drone_id)
+            .map(|(tracking_id, _)| *tracking_id)
     }
 
     /// Get the number of active tracks
@@ -353,12 +368,11 @@ mod tests {
         // Not confirmed yet
         assert_eq!(tracker.active_count(), 0);
 
-        // Multiple updates to confirm
-        for _ in 0..5 {
-            let _ = tracker.update(&detections).unwrap();
-        }
-
-        // Now should be confirmed
+        // The third consecutive detection confirms it
+        let _ = tracker.update(&detections).unwrap();
+        assert_eq!(tracker.active_count(), 0);
+        let tracks = tracker.update(&detections).unwrap();
         assert_eq!(tracker.active_count(), 1);
+        assert_eq!(tracks[0].tracking_id, 1);
     }
 }
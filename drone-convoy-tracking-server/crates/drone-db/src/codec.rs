@@ -0,0 +1,348 @@
+//! Delta/varint codec for compact telemetry history
+//!
+//! In compact storage mode one row holds a drone's samples for one minute.
+//! Samples are quantized (coordinates to 1e-7°, about 1 cm; altitude,
+//! heading, speed, temperature and gimbal attitude to 0.01) and each field
+//! is stored as a zigzag varint delta from the previous sample, so a drone
+//! flying a steady course costs a couple of bytes per field instead of a
+//! full row. Status, armed flag and mission ID are only written when they
+//! change.
+
+use crate::{DbError, DbResult, TelemetryRecord};
+
+use drone_core::GimbalState;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Span of one compact bucket
+pub const BUCKET_MILLIS: i64 = 60_000;
+
+const VERSION: u8 = 1;
+
+const COORDINATE_SCALE: f64 = 1e7;
+const VALUE_SCALE: f64 = 100.0;
+
+const HAS_TEMPERATURE: u8 = 1 << 0;
+const HAS_SIGNAL: u8 = 1 << 1;
+const STATUS_CHANGED: u8 = 1 << 2;
+const ARMED_CHANGED: u8 = 1 << 3;
+const MISSION_CHANGED: u8 = 1 << 4;
+const HAS_GIMBAL: u8 = 1 << 5;
+
+/// Start of the bucket a timestamp (ms) falls into
+pub fn bucket_start(timestamp_ms: i64) -> i64 {
+    timestamp_ms.div_euclid(BUCKET_MILLIS) * BUCKET_MILLIS
+}
+
+/// Values carried from one sample to the next
+#[derive(Default)]
+struct State {
+    timestamp: i64,
+    latitude: i64,
+    longitude: i64,
+    altitude: i64,
+    heading: i64,
+    speed: i64,
+    battery_level: i64,
+    fuel_level: i64,
+    system_health: i64,
+    temperature: i64,
+    signal_strength: i64,
+    gimbal_pan: i64,
+    gimbal_tilt: i64,
+    gimbal_zoom: i64,
+    status: Option<String>,
+    armed: Option<bool>,
+    mission_id: Option<uuid::Uuid>,
+}
+
+/// Encode samples (oldest first) into a bucket blob
+pub fn encode(samples: &[TelemetryRecord]) -> Vec<u8> {
+    let mut out = vec![VERSION];
+    write_varint(&mut out, samples.len() as u64);
+
+    let mut prev = State::default();
+    for sample in samples {
+        let mut flags = 0;
+        if sample.temperature.is_some() {
+            flags |= HAS_TEMPERATURE;
+        }
+        if sample.signal_strength.is_some() {
+            flags |= HAS_SIGNAL;
+        }
+        if sample.status != prev.status {
+            flags |= STATUS_CHANGED;
+        }
+        if sample.armed != prev.armed {
+            flags |= ARMED_CHANGED;
+        }
+        if sample.mission_id != prev.mission_id {
+            flags |= MISSION_CHANGED;
+        }
+        if sample.gimbal.is_some() {
+            flags |= HAS_GIMBAL;
+        }
+        out.push(flags);
+
+        let mut delta = |prev: &mut i64, value: i64| {
+            write_varint(&mut out, zigzag(value.wrapping_sub(*prev)));
+            *prev = value;
+        };
+        delta(&mut prev.timestamp, sample.timestamp.timestamp_millis());
+        delta(&mut prev.latitude, quantize(sample.latitude, COORDINATE_SCALE));
+        delta(&mut prev.longitude, quantize(sample.longitude, COORDINATE_SCALE));
+        delta(&mut prev.altitude, quantize(sample.altitude, VALUE_SCALE));
+        delta(&mut prev.heading, quantize(sample.heading, VALUE_SCALE));
+        delta(&mut prev.speed, quantize(sample.speed, VALUE_SCALE));
+        delta(&mut prev.battery_level, sample.battery_level as i64);
+        delta(&mut prev.fuel_level, sample.fuel_level as i64);
+        delta(&mut prev.system_health, sample.system_health as i64);
+        if let Some(temperature) = sample.temperature {
+            delta(&mut prev.temperature, quantize(temperature, VALUE_SCALE));
+        }
+        if let Some(signal) = sample.signal_strength {
+            delta(&mut prev.signal_strength, signal as i64);
+        }
+        if let Some(gimbal) = sample.gimbal {
+            delta(&mut prev.gimbal_pan, quantize(gimbal.pan, VALUE_SCALE));
+            delta(&mut prev.gimbal_tilt, quantize(gimbal.tilt, VALUE_SCALE));
+            delta(&mut prev.gimbal_zoom, quantize(gimbal.zoom, VALUE_SCALE));
+        }
+
+        if flags & STATUS_CHANGED != 0 {
+            match &sample.status {
+                Some(status) => {
+                    write_varint(&mut out, status.len() as u64 + 1);
+                    out.extend_from_slice(status.as_bytes());
+                }
+                None => write_varint(&mut out, 0),
+            }
+            prev.status = sample.status.clone();
+        }
+        if flags & ARMED_CHANGED != 0 {
+            out.push(match sample.armed {
+                None => 0,
+                Some(false) => 1,
+                Some(true) => 2,
+            });
+            prev.armed = sample.armed;
+        }
+        if flags & MISSION_CHANGED != 0 {
+            match sample.mission_id {
+                Some(id) => {
+                    out.push(1);
+                    out.extend_from_slice(id.as_bytes());
+                }
+                None => out.push(0),
+            }
+            prev.mission_id = sample.mission_id;
+        }
+    }
+    out
+}
+
+/// Decode a bucket blob back into records for `drone_id`, oldest first
+pub fn decode(blob: &[u8], drone_id: &str) -> DbResult<Vec<TelemetryRecord>> {
+    let mut reader = Reader { blob, pos: 0 };
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(corrupt(format!("unsupported version {}", version)));
+    }
+    let count = reader.varint()? as usize;
+
+    let mut prev = State::default();
+    let mut samples = Vec::with_capacity(count.min(blob.len()));
+    for _ in 0..count {
+        let flags = reader.byte()?;
+        let mut delta = |prev: &mut i64| -> DbResult<i64> {
+            *prev = prev.wrapping_add(unzigzag(reader.varint()?));
+            Ok(*prev)
+        };
+
+        let timestamp = delta(&mut prev.timestamp)?;
+        let latitude = delta(&mut prev.latitude)? as f64 / COORDINATE_SCALE;
+        let longitude = delta(&mut prev.longitude)? as f64 / COORDINATE_SCALE;
+        let altitude = delta(&mut prev.altitude)? as f64 / VALUE_SCALE;
+        let heading = delta(&mut prev.heading)? as f64 / VALUE_SCALE;
+        let speed = delta(&mut prev.speed)? as f64 / VALUE_SCALE;
+        let battery_level = delta(&mut prev.battery_level)? as i32;
+        let fuel_level = delta(&mut prev.fuel_level)? as i32;
+        let system_health = delta(&mut prev.system_health)? as i32;
+        let temperature = if flags & HAS_TEMPERATURE != 0 {
+            Some(delta(&mut prev.temperature)? as f64 / VALUE_SCALE)
+        } else {
+            None
+        };
+        let signal_strength = if flags & HAS_SIGNAL != 0 {
+            Some(delta(&mut prev.signal_strength)? as i32)
+        } else {
+            None
+        };
+        let gimbal = if flags & HAS_GIMBAL != 0 {
+            Some(GimbalState {
+                pan: delta(&mut prev.gimbal_pan)? as f64 / VALUE_SCALE,
+                tilt: delta(&mut prev.gimbal_tilt)? as f64 / VALUE_SCALE,
+                zoom: delta(&mut prev.gimbal_zoom)? as f64 / VALUE_SCALE,
+            })
+        } else {
+            None
+        };
+
+        if flags & STATUS_CHANGED != 0 {
+            prev.status = match reader.varint()? {
+                0 => None,
+                len => {
+                    let bytes = reader.bytes(len as usize - 1)?;
+                    Some(String::from_utf8(bytes.to_vec()).map_err(|e| corrupt(e.to_string()))?)
+                }
+            };
+        }
+        if flags & ARMED_CHANGED != 0 {
+            prev.armed = match reader.byte()? {
+                0 => None,
+                1 => Some(false),
+                _ => Some(true),
+            };
+        }
+        if flags & MISSION_CHANGED != 0 {
+            prev.mission_id = match reader.byte()? {
+                0 => None,
+                _ => {
+                    let bytes: [u8; 16] = reader.bytes(16)?.try_into().expect("16 bytes");
+                    Some(uuid::Uuid::from_bytes(bytes))
+                }
+            };
+        }
+
+        samples.push(TelemetryRecord {
+            drone_id: drone_id.to_string(),
+            timestamp: from_millis(timestamp),
+            latitude,
+            longitude,
+            altitude,
+            heading,
+            speed,
+            battery_level,
+            fuel_level,
+            system_health,
+            status: prev.status.clone(),
+            armed: prev.armed,
+            temperature,
+            signal_strength,
+            mission_id: prev.mission_id,
+            gimbal,
+        });
+    }
+    Ok(samples)
+}
+
+fn quantize(value: f64, scale: f64) -> i64 {
+    (value * scale).round() as i64
+}
+
+fn from_millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn corrupt(reason: impl std::fmt::Display) -> DbError {
+    DbError::Serialization(format!("corrupt compact telemetry: {}", reason))
+}
+
+struct Reader<'a> {
+    blob: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> DbResult<u8> {
+        let byte = *self.blob.get(self.pos).ok_or_else(|| corrupt("truncated"))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> DbResult<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.blob.len());
+        let end = end.ok_or_else(|| corrupt("truncated"))?;
+        let bytes = &self.blob[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> DbResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(corrupt("varint too long"))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(second: i64, latitude: f64) -> TelemetryRecord {
+        TelemetryRecord {
+            drone_id: "REAPER-01".into(),
+            timestamp: from_millis(1_700_000_000_000 + second * 1000),
+            latitude,
+            longitude: -69.2125,
+            altitude: 3048.5,
+            heading: 271.25,
+            speed: 312.4,
+            battery_level: 90 - second as i32,
+            fuel_level: 80,
+            system_health: 100,
+            status: Some("MOVING".into()),
+            armed: Some(false),
+            temperature: (second % 2 == 0).then_some(-12.5),
+            signal_strength: Some(95),
+            mission_id: None,
+            gimbal: (second % 3 == 0).then_some(GimbalState { pan: second as f64 - 30.25, tilt: -45.5, zoom: 1.0 }),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_and_size() {
+        // Latitudes already on the 1e-7° grid survive quantization exactly
+        let mut samples: Vec<_> = (0..60).map(|s| sample(s, (345_000_000 + s * 1000) as f64 / 1e7)).collect();
+        samples[30].mission_id = Some(uuid::Uuid::new_v4());
+        samples[31].mission_id = samples[30].mission_id;
+        samples[40].status = None;
+
+        let blob = encode(&samples);
+        assert_eq!(decode(&blob, "REAPER-01").unwrap(), samples);
+
+        // Steady flight costs a handful of bytes per sample
+        assert!(blob.len() < samples.len() * 20, "{} bytes", blob.len());
+
+        assert!(decode(&blob[..blob.len() - 1], "REAPER-01").is_err());
+        assert_eq!(bucket_start(-1), -BUCKET_MILLIS);
+        assert_eq!(zigzag(-1), 1);
+        assert_eq!(unzigzag(zigzag(i64::MIN)), i64::MIN);
+    }
+}
@@ -0,0 +1,187 @@
+//! Per-operation consistency levels for the ScyllaDB backend
+//!
+//! High-volume telemetry is written and read at `ONE`; mission state is
+//! written and read at `LOCAL_QUORUM` so a read always sees (and repairs)
+//! the latest acknowledged write. Mission status transitions use
+//! lightweight transactions at `LOCAL_SERIAL`.
+
+use crate::{DbError, DbResult};
+
+use scylla::query::Query;
+use scylla::statement::{Consistency, SerialConsistency};
+use serde::{Deserialize, Serialize};
+
+/// CQL consistency level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConsistencyLevel {
+    Any,
+    One,
+    Two,
+    Three,
+    Quorum,
+    All,
+    LocalQuorum,
+    EachQuorum,
+    LocalOne,
+}
+
+impl std::str::FromStr for ConsistencyLevel {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(s.trim().to_ascii_uppercase().into())
+            .map_err(|_| DbError::Configuration(format!("unknown consistency level: {}", s)))
+    }
+}
+
+impl From<ConsistencyLevel> for Consistency {
+    fn from(level: ConsistencyLevel) -> Self {
+        match level {
+            ConsistencyLevel::Any => Consistency::Any,
+            ConsistencyLevel::One => Consistency::One,
+            ConsistencyLevel::Two => Consistency::Two,
+            ConsistencyLevel::Three => Consistency::Three,
+            ConsistencyLevel::Quorum => Consistency::Quorum,
+            ConsistencyLevel::All => Consistency::All,
+            ConsistencyLevel::LocalQuorum => Consistency::LocalQuorum,
+            ConsistencyLevel::EachQuorum => Consistency::EachQuorum,
+            ConsistencyLevel::LocalOne => Consistency::LocalOne,
+        }
+    }
+}
+
+/// Consistency of the Paxos phase of lightweight transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SerialConsistencyLevel {
+    Serial,
+    LocalSerial,
+}
+
+impl std::str::FromStr for SerialConsistencyLevel {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(s.trim().to_ascii_uppercase().into())
+            .map_err(|_| DbError::Configuration(format!("unknown serial consistency level: {}", s)))
+    }
+}
+
+impl From<SerialConsistencyLevel> for SerialConsistency {
+    fn from(level: SerialConsistencyLevel) -> Self {
+        match level {
+            SerialConsistencyLevel::Serial => SerialConsistency::Serial,
+            SerialConsistencyLevel::LocalSerial => SerialConsistency::LocalSerial,
+        }
+    }
+}
+
+/// Consistency levels per class of operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsistencyConfig {
+    /// Telemetry inserts
+    pub telemetry_write: ConsistencyLevel,
+    /// Telemetry reads and exports
+    pub telemetry_read: ConsistencyLevel,
+    /// Mission creation and status updates
+    pub mission_write: ConsistencyLevel,
+    /// Mission reads
+    pub mission_read: ConsistencyLevel,
+    /// Serial consistency for conditional (LWT) mission status updates
+    pub mission_serial: SerialConsistencyLevel,
+    /// Everything else (waypoint events, registry, alerts)
+    pub default: ConsistencyLevel,
+}
+
+impl Default for ConsistencyConfig {
+    fn default() -> Self {
+        Self {
+            telemetry_write: ConsistencyLevel::One,
+            telemetry_read: ConsistencyLevel::One,
+            mission_write: ConsistencyLevel::LocalQuorum,
+            mission_read: ConsistencyLevel::LocalQuorum,
+            mission_serial: SerialConsistencyLevel::LocalSerial,
+            default: ConsistencyLevel::LocalQuorum,
+        }
+    }
+}
+
+impl ConsistencyConfig {
+    /// Load overrides from `DB_CONSISTENCY_*` environment variables
+    pub fn from_env() -> DbResult<Self> {
+        let mut config = Self::default();
+
+        let levels = [
+            ("DB_CONSISTENCY_TELEMETRY_WRITE", &mut config.telemetry_write),
+            ("DB_CONSISTENCY_TELEMETRY_READ", &mut config.telemetry_read),
+            ("DB_CONSISTENCY_MISSION_WRITE", &mut config.mission_write),
+            ("DB_CONSISTENCY_MISSION_READ", &mut config.mission_read),
+            ("DB_CONSISTENCY_DEFAULT", &mut config.default),
+        ];
+        for (var, level) in levels {
+            if let Ok(value) = std::env::var(var) {
+                *level = value.parse()?;
+            }
+        }
+        if let Ok(value) = std::env::var("DB_SERIAL_CONSISTENCY_MISSION") {
+            config.mission_serial = value.parse()?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Build an unprepared statement with an explicit consistency level
+pub fn statement(text: &str, consistency: ConsistencyLevel) -> Query {
+    let mut query = Query::new(text);
+    query.set_consistency(consistency.into());
+    query
+}
+
+/// Build a conditional (LWT) statement
+pub fn conditional_statement(
+    text: &str,
+    consistency: ConsistencyLevel,
+    serial: SerialConsistencyLevel,
+) -> Query {
+    let mut query = statement(text, consistency);
+    query.set_serial_consistency(Some(serial.into()));
+    query
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_and_parsing() {
+        let config = ConsistencyConfig::default();
+        assert_eq!(config.telemetry_write, ConsistencyLevel::One);
+        assert_eq!(config.mission_write, ConsistencyLevel::LocalQuorum);
+        assert_eq!(config.mission_serial, SerialConsistencyLevel::LocalSerial);
+
+        assert_eq!("local_quorum".parse::<ConsistencyLevel>().unwrap(), ConsistencyLevel::LocalQuorum);
+        assert_eq!(" ONE ".parse::<ConsistencyLevel>().unwrap(), ConsistencyLevel::One);
+        assert!("SERIAL".parse::<ConsistencyLevel>().is_err());
+        assert_eq!("serial".parse::<SerialConsistencyLevel>().unwrap(), SerialConsistencyLevel::Serial);
+
+        let query = conditional_statement(
+            "UPDATE missions SET status = ? WHERE mission_id = ? IF status = ?",
+            config.mission_write,
+            config.mission_serial,
+        );
+        assert_eq!(query.get_consistency(), Some(Consistency::LocalQuorum));
+        assert_eq!(query.get_serial_consistency(), Some(SerialConsistency::LocalSerial));
+
+        // Partial configs fall back to the defaults
+        let parsed: ConsistencyConfig = serde_json::from_str(r#"{"telemetry_write":"ANY"}"#).unwrap();
+        assert_eq!(parsed.telemetry_write, ConsistencyLevel::Any);
+        assert_eq!(parsed.mission_read, ConsistencyLevel::LocalQuorum);
+    }
+}
@@ -40,4 +40,10 @@ impl DbError {
     }
 }
 
+impl From<rusqlite::Error> for DbError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Query(e.to_string())
+    }
+}
+
 pub type DbResult<T> = Result<T, DbError>;
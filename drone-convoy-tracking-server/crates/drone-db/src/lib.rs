@@ -1,26 +1,94 @@
 //! # Drone DB - ScyllaDB Integration
 //!
 //! Provides persistence layer for drone telemetry, waypoint events,
-//! CV tracking results, and mission data using ScyllaDB.
+//! CV tracking results, and mission data using ScyllaDB, with an
+//! embedded SQLite backend for small deployments.
 
+pub mod codec;
+pub mod consistency;
 pub mod error;
 pub mod repository;
+pub mod retention;
 pub mod migrations;
+pub mod sqlite;
 
+pub use consistency::{ConsistencyConfig, ConsistencyLevel, SerialConsistencyLevel};
 pub use error::{DbError, DbResult};
-pub use repository::*;
+pub use repository::{
+    AlertStore, CustomEventStore, DataQualityStore, DroneStore, LeaseStore, MissionStore, RecordStream, RetentionStore,
+    ScheduleStore, TelemetryStore, TrackingStore, WaypointStore, ZoneStore, NotificationStore,
+};
+pub use retention::{
+    Enforcement, PurgeReport, RetentionConfig, RetentionManager, RetentionPolicy, RetentionTable,
+    TablePurge,
+};
+pub use sqlite::SqliteStore;
 
 use drone_core::{
-    Alert, Drone, DroneId, GeoPosition, Mission, MissionId, Telemetry, 
-    TrackingResult, WaypointId,
+    Alert, Drone, DroneId, DroneMarking, DroneStatus, DroneType, GeoPosition, GimbalState, Mission, MissionId, SubsystemHealth, Telemetry,
+    TelemetrySource, TenantId, ThresholdOverrides, TrackingResult, TransportBinding, WaypointId,
 };
-use scylla::{Session, SessionBuilder};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{StreamExt, TryStreamExt};
+use scylla::batch::{Batch, BatchType};
+use scylla::frame::value::CqlTimestamp;
+use scylla::{ExecutionProfile, Session, SessionBuilder};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{info, warn};
 
 use serde::{Deserialize, Serialize};
 
+/// Storage backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// ScyllaDB cluster
+    #[default]
+    Scylla,
+    /// Embedded SQLite database file
+    Sqlite,
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "scylla" | "scylladb" => Ok(Self::Scylla),
+            "sqlite" => Ok(Self::Sqlite),
+            other => Err(DbError::Configuration(format!("unknown storage backend: {}", other))),
+        }
+    }
+}
+
+/// Telemetry history layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryStorage {
+    /// One `drone_telemetry` row per sample
+    #[default]
+    Row,
+    /// One `drone_telemetry_compact` row per drone per minute, samples
+    /// delta-encoded (see [`codec`]); SQLite backend only
+    Compact,
+}
+
+impl std::str::FromStr for TelemetryStorage {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "row" | "rows" => Ok(Self::Row),
+            "compact" => Ok(Self::Compact),
+            other => Err(DbError::Configuration(format!("unknown telemetry storage mode: {}", other))),
+        }
+    }
+}
+
 /// Database configuration
 // #[derive(Debug, Clone, Serialize, Deserialize)]
 // pub struct DbConfig {
@@ -36,6 +104,8 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbConfig {
+    #[serde(default)]
+    pub backend: StorageBackend,
     pub hosts: Vec<String>,
     pub keyspace: String,
     #[serde(skip)]
@@ -44,6 +114,19 @@ pub struct DbConfig {
     pub query_timeout: Duration,
     #[serde(default)]
     pub ssl_enabled: bool,
+    /// Database file used by the SQLite backend
+    #[serde(default = "default_sqlite_path")]
+    pub sqlite_path: PathBuf,
+    /// Per-operation consistency levels (ScyllaDB only)
+    #[serde(default)]
+    pub consistency: ConsistencyConfig,
+    /// Telemetry history layout
+    #[serde(default)]
+    pub telemetry_storage: TelemetryStorage,
+}
+
+fn default_sqlite_path() -> PathBuf {
+    PathBuf::from("drone_convoy.db")
 }
 
 fn default_connection_timeout() -> Duration {
@@ -57,11 +140,15 @@ fn default_query_timeout() -> Duration {
 impl Default for DbConfig {
     fn default() -> Self {
         Self {
+            backend: StorageBackend::default(),
             hosts: vec!["127.0.0.1:9042".to_string()],
             keyspace: "drone_convoy".to_string(),
-            connection_timeout: Duration::from_secs(10),
-            query_timeout: Duration::from_secs(5),
+            connection_timeout: default_connection_timeout(),
+            query_timeout: default_query_timeout(),
             ssl_enabled: false,
+            sqlite_path: default_sqlite_path(),
+            consistency: ConsistencyConfig::default(),
+            telemetry_storage: TelemetryStorage::default(),
         }
     }
 }
@@ -77,13 +164,56 @@ impl DbConfig {
         let keyspace = std::env::var("SCYLLA_KEYSPACE")
             .unwrap_or_else(|_| "drone_convoy".to_string());
 
+        let backend = match std::env::var("DB_BACKEND") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                warn!("{}; falling back to ScyllaDB", e);
+                StorageBackend::Scylla
+            }),
+            Err(_) => StorageBackend::Scylla,
+        };
+
+        let sqlite_path = std::env::var("SQLITE_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| default_sqlite_path());
+
+        let consistency = ConsistencyConfig::from_env().unwrap_or_else(|e| {
+            warn!("{}; using default consistency levels", e);
+            ConsistencyConfig::default()
+        });
+
+        let telemetry_storage = match std::env::var("TELEMETRY_STORAGE") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                warn!("{}; storing telemetry row per sample", e);
+                TelemetryStorage::Row
+            }),
+            Err(_) => TelemetryStorage::Row,
+        };
+
         Self {
+            backend,
             hosts,
             keyspace,
+            sqlite_path,
+            consistency,
+            telemetry_storage,
             ..Default::default()
         }
     }
 
+    /// Storage scoped to one tenant: keyspace `<keyspace>_<tenant>` on
+    /// ScyllaDB, `<name>_<tenant>.db` next to the SQLite file
+    pub fn for_tenant(&self, tenant: &TenantId) -> Self {
+        let mut config = self.clone();
+        config.keyspace = format!("{}_{}", self.keyspace, tenant);
+        let stem = self
+            .sqlite_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "drone_convoy".into());
+        config.sqlite_path = self.sqlite_path.with_file_name(format!("{}_{}.db", stem, tenant));
+        config
+    }
+
     pub fn docker() -> Self {
         Self {
             hosts: vec![
@@ -97,24 +227,705 @@ impl DbConfig {
     }
 }
 
+/// Flat telemetry row as stored in `drone_telemetry`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryRecord {
+    pub drone_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub heading: f64,
+    pub speed: f64,
+    pub battery_level: i32,
+    pub fuel_level: i32,
+    pub system_health: i32,
+    pub status: Option<String>,
+    pub armed: Option<bool>,
+    pub temperature: Option<f64>,
+    pub signal_strength: Option<i32>,
+    pub mission_id: Option<uuid::Uuid>,
+    /// Camera gimbal attitude, on drones reporting one
+    #[serde(default)]
+    pub gimbal: Option<GimbalState>,
+}
+
+impl TelemetryRecord {
+    /// Row for a position report, with the drone's status and arming at the time
+    pub fn new(
+        drone_id: &DroneId,
+        position: &GeoPosition,
+        telemetry: &Telemetry,
+        status: DroneStatus,
+        armed: bool,
+        mission_id: Option<&MissionId>,
+    ) -> Self {
+        Self {
+            drone_id: drone_id.as_str().to_string(),
+            timestamp: telemetry.timestamp,
+            latitude: position.latitude,
+            longitude: position.longitude,
+            altitude: position.altitude,
+            heading: telemetry.heading,
+            speed: telemetry.speed,
+            battery_level: telemetry.battery_level as i32,
+            fuel_level: telemetry.fuel_level as i32,
+            system_health: telemetry.system_health as i32,
+            status: Some(status.to_string()),
+            armed: Some(armed),
+            temperature: Some(telemetry.temperature),
+            signal_strength: Some(telemetry.signal_strength as i32),
+            mission_id: mission_id.map(|m| m.0),
+            gimbal: telemetry.gimbal,
+        }
+    }
+
+    /// Position and telemetry of the row; fields the row does not store
+    /// are left at their defaults
+    pub fn position_telemetry(&self) -> (GeoPosition, Telemetry) {
+        let position = GeoPosition::new(self.latitude, self.longitude, self.altitude);
+        let telemetry = Telemetry {
+            battery_level: self.battery_level.clamp(0, 100) as u8,
+            fuel_level: self.fuel_level.clamp(0, 100) as u8,
+            system_health: self.system_health.clamp(0, 100) as u8,
+            speed: self.speed,
+            heading: self.heading,
+            signal_strength: self.signal_strength.unwrap_or_default().clamp(0, 100) as u8,
+            temperature: self.temperature.unwrap_or_default(),
+            timestamp: self.timestamp,
+            sequence: None,
+            gimbal: self.gimbal,
+        };
+        (position, telemetry)
+    }
+
+    /// Drone status at the time of the row, if recorded
+    pub fn drone_status(&self) -> Option<DroneStatus> {
+        let status = self.status.as_deref()?;
+        serde_json::from_value(status.to_uppercase().into()).ok()
+    }
+}
+
+/// Flat alert row as stored in `alerts`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub alert_id: uuid::Uuid,
+    pub created_at: DateTime<Utc>,
+    pub severity: Option<String>,
+    pub alert_type: Option<String>,
+    pub message: Option<String>,
+    pub drone_id: Option<String>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+/// Flat waypoint event row as stored in `waypoint_events`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaypointEventRecord {
+    pub mission_id: uuid::Uuid,
+    pub event_time: DateTime<Utc>,
+    pub drone_id: String,
+    pub waypoint_id: Option<String>,
+    pub waypoint_name: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub event_type: Option<String>,
+    pub speed_at_event: Option<f64>,
+    pub altitude_at_event: Option<f64>,
+    pub heading: Option<f64>,
+}
+
+/// Period without telemetry from a drone, as stored in `telemetry_gaps`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryGapRecord {
+    pub mission_id: uuid::Uuid,
+    pub drone_id: String,
+    pub gap_start: DateTime<Utc>,
+    pub gap_end: DateTime<Utc>,
+    pub duration_ms: i64,
+}
+
+/// Longest drone ID kept in a dead-letter key (bytes); the payload keeps
+/// all of it
+pub const MAX_DEAD_LETTER_ID_LEN: usize = 128;
+
+/// Telemetry row kept out of `drone_telemetry`, as stored in
+/// `telemetry_dead_letters`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetterRecord {
+    pub drone_id: String,
+    pub quarantined_at: DateTime<Utc>,
+    pub id: uuid::Uuid,
+    /// Why the row was not written
+    pub reason: String,
+    /// The row as JSON
+    pub payload: String,
+}
+
+impl DeadLetterRecord {
+    pub fn new(record: &TelemetryRecord, reason: impl Into<String>, now: DateTime<Utc>) -> Self {
+        Self {
+            drone_id: truncate_bytes(&record.drone_id, MAX_DEAD_LETTER_ID_LEN).to_string(),
+            quarantined_at: now,
+            id: uuid::Uuid::new_v4(),
+            reason: reason.into(),
+            payload: serde_json::to_string(record).unwrap_or_default(),
+        }
+    }
+}
+
+/// Longest prefix of `s` within `max` bytes that ends on a char boundary
+fn truncate_bytes(s: &str, max: usize) -> &str {
+    let mut end = s.len().min(max);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Scheduled command, as stored in `scheduled_commands`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledCommandRecord {
+    pub id: uuid::Uuid,
+    /// `pending`, `fired` or `cancelled`
+    pub state: String,
+    /// Trigger and command as JSON
+    pub payload: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Named drone group, as stored in `drone_groups`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DroneGroupRecord {
+    pub name: String,
+    /// Explicit member drone IDs
+    pub members: Vec<String>,
+    /// Convoy role whose holders are also members (`SCOUT`, `ESCORT`, `CARGO`)
+    pub role: Option<String>,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Last tracked state of an evicted drone, as stored in `drone_snapshots`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DroneSnapshotRecord {
+    pub drone_id: String,
+    /// Drone, waypoint progress and active alerts as JSON
+    pub snapshot: String,
+    pub last_update: DateTime<Utc>,
+    pub evicted_at: DateTime<Utc>,
+}
+
+/// Live CV detection and tracking parameters, as stored in `cv_tuning`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CvTuningRecord {
+    pub revision: i64,
+    /// `CvTuning` as JSON
+    pub tuning: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Station controlling a drone handed off from here, as stored in
+/// `drone_registry.handed_off_to`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoffOwnerRecord {
+    pub handoff_id: uuid::Uuid,
+    pub station: String,
+    pub url: Option<String>,
+    pub handed_off_at: DateTime<Utc>,
+}
+
+/// Zone of interest, as stored in `zones`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoneRecord {
+    pub id: uuid::Uuid,
+    pub name: String,
+    /// Polygon vertices as JSON `[[lat, lng], ...]`
+    pub vertices: String,
+    pub max_altitude: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Push notification subscription, as stored in `push_subscriptions`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushSubscriptionRecord {
+    pub id: uuid::Uuid,
+    pub user_id: String,
+    /// `fcm` or `apns`
+    pub platform: String,
+    pub device_token: String,
+    /// Delivery preferences as JSON
+    pub preferences: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// JSON Schema for an integrator-defined event type, as stored in
+/// `custom_event_schemas`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomEventSchemaRecord {
+    pub event_type: String,
+    /// Schema document as JSON
+    pub schema: String,
+    pub description: Option<String>,
+    pub registered_at: DateTime<Utc>,
+}
+
+/// Integrator-defined event, as stored in `custom_events`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomEventRecord {
+    pub event_type: String,
+    pub event_time: DateTime<Utc>,
+    pub event_id: uuid::Uuid,
+    pub drone_id: Option<String>,
+    pub mission_id: Option<uuid::Uuid>,
+    /// Event value as JSON
+    pub value: String,
+}
+
+/// Leader lease, as stored in `leases`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaseRecord {
+    pub name: String,
+    pub holder: String,
+    /// When the current holder first took the lease; kept across renewals
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl LeaseRecord {
+    pub fn is_held_by(&self, holder: &str) -> bool {
+        self.holder == holder
+    }
+}
+
+/// Conditional alert rule, as stored in `alert_rules`; `condition` is the
+/// JSON condition tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRuleRecord {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub condition: String,
+    pub severity: String,
+    pub message: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Waypoint attachment metadata, as stored in `waypoint_attachments`; the
+/// content itself lives in the object store under `object_key`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaypointAttachmentRecord {
+    pub id: uuid::Uuid,
+    pub mission_id: uuid::Uuid,
+    pub waypoint_id: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub object_key: String,
+    pub threat_level: Option<String>,
+    pub notes: Option<String>,
+    pub uploaded_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A drone's dwell statistics for one zone during one mission, as stored in `zone_dwell`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoneDwellRecord {
+    pub zone_id: uuid::Uuid,
+    pub mission_id: uuid::Uuid,
+    pub drone_id: String,
+    pub visits: i32,
+    /// Time inside over all completed visits
+    pub time_inside_ms: i64,
+    pub first_entry: Option<DateTime<Utc>>,
+    pub last_entry: Option<DateTime<Utc>>,
+    pub last_exit: Option<DateTime<Utc>>,
+}
+
+type TelemetryRow = (
+    String,
+    CqlTimestamp,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<i32>,
+    Option<i32>,
+    Option<i32>,
+    Option<String>,
+    Option<bool>,
+    Option<f64>,
+    Option<i32>,
+    Option<uuid::Uuid>,
+    Option<(f64, f64, f64)>,
+);
+
+type WaypointEventRow = (
+    uuid::Uuid,
+    CqlTimestamp,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<f64>,
+    Option<f64>,
+    Option<String>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+);
+
+type TelemetryGapRow = (uuid::Uuid, CqlTimestamp, String, CqlTimestamp, i64);
+
+type DeadLetterRow = (String, CqlTimestamp, uuid::Uuid, String, String);
+
+type ScheduledCommandRow = (uuid::Uuid, String, String, CqlTimestamp, CqlTimestamp);
+
+type DroneGroupRow = (
+    String,
+    Option<Vec<String>>,
+    Option<String>,
+    Option<String>,
+    CqlTimestamp,
+    CqlTimestamp,
+);
+
+type DroneSnapshotRow = (String, String, CqlTimestamp, CqlTimestamp);
+
+type CvTuningRow = (i64, String, CqlTimestamp);
+
+type ZoneRow = (uuid::Uuid, String, String, Option<f64>, CqlTimestamp);
+
+type PushSubscriptionRow = (uuid::Uuid, String, String, String, String, CqlTimestamp);
+
+type CustomEventSchemaRow = (String, String, Option<String>, CqlTimestamp);
+
+type CustomEventRow = (String, CqlTimestamp, uuid::Uuid, Option<String>, Option<uuid::Uuid>, String);
+
+type AlertRow = (
+    uuid::Uuid,
+    CqlTimestamp,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<CqlTimestamp>,
+);
+
+type LeaseRow = (String, String, CqlTimestamp, CqlTimestamp);
+
+type AlertRuleRow = (uuid::Uuid, String, String, String, Option<String>, bool, CqlTimestamp, CqlTimestamp);
+
+type WaypointAttachmentRow = (
+    uuid::Uuid,
+    uuid::Uuid,
+    String,
+    String,
+    String,
+    i64,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    CqlTimestamp,
+);
+
+type ZoneDwellRow = (
+    uuid::Uuid,
+    uuid::Uuid,
+    String,
+    Option<i32>,
+    Option<i64>,
+    Option<CqlTimestamp>,
+    Option<CqlTimestamp>,
+    Option<CqlTimestamp>,
+);
+
+fn from_cql_timestamp(ts: CqlTimestamp) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ts.0).single().unwrap_or_default()
+}
+
+impl From<TelemetryRow> for TelemetryRecord {
+    fn from(row: TelemetryRow) -> Self {
+        Self {
+            drone_id: row.0,
+            timestamp: from_cql_timestamp(row.1),
+            latitude: row.2.unwrap_or_default(),
+            longitude: row.3.unwrap_or_default(),
+            altitude: row.4.unwrap_or_default(),
+            heading: row.5.unwrap_or_default(),
+            speed: row.6.unwrap_or_default(),
+            battery_level: row.7.unwrap_or_default(),
+            fuel_level: row.8.unwrap_or_default(),
+            system_health: row.9.unwrap_or_default(),
+            status: row.10,
+            armed: row.11,
+            temperature: row.12,
+            signal_strength: row.13,
+            mission_id: row.14,
+            gimbal: row.15.map(|(pan, tilt, zoom)| GimbalState { pan, tilt, zoom }),
+        }
+    }
+}
+
+impl From<AlertRow> for AlertRecord {
+    fn from(row: AlertRow) -> Self {
+        Self {
+            alert_id: row.0,
+            created_at: from_cql_timestamp(row.1),
+            severity: row.2,
+            alert_type: row.3,
+            message: row.4,
+            drone_id: row.5,
+            acknowledged_at: row.6.map(from_cql_timestamp),
+        }
+    }
+}
+
+impl From<WaypointEventRow> for WaypointEventRecord {
+    fn from(row: WaypointEventRow) -> Self {
+        Self {
+            mission_id: row.0,
+            event_time: from_cql_timestamp(row.1),
+            drone_id: row.2,
+            waypoint_id: row.3,
+            waypoint_name: row.4,
+            latitude: row.5,
+            longitude: row.6,
+            event_type: row.7,
+            speed_at_event: row.8,
+            altitude_at_event: row.9,
+            heading: row.10,
+        }
+    }
+}
+
+impl From<TelemetryGapRow> for TelemetryGapRecord {
+    fn from(row: TelemetryGapRow) -> Self {
+        Self {
+            mission_id: row.0,
+            gap_start: from_cql_timestamp(row.1),
+            drone_id: row.2,
+            gap_end: from_cql_timestamp(row.3),
+            duration_ms: row.4,
+        }
+    }
+}
+
+impl From<DeadLetterRow> for DeadLetterRecord {
+    fn from(row: DeadLetterRow) -> Self {
+        Self {
+            drone_id: row.0,
+            quarantined_at: from_cql_timestamp(row.1),
+            id: row.2,
+            reason: row.3,
+            payload: row.4,
+        }
+    }
+}
+
+impl From<ScheduledCommandRow> for ScheduledCommandRecord {
+    fn from(row: ScheduledCommandRow) -> Self {
+        Self {
+            id: row.0,
+            state: row.1,
+            payload: row.2,
+            created_at: from_cql_timestamp(row.3),
+            updated_at: from_cql_timestamp(row.4),
+        }
+    }
+}
+
+impl From<ZoneRow> for ZoneRecord {
+    fn from(row: ZoneRow) -> Self {
+        Self {
+            id: row.0,
+            name: row.1,
+            vertices: row.2,
+            max_altitude: row.3,
+            created_at: from_cql_timestamp(row.4),
+        }
+    }
+}
+
+impl From<PushSubscriptionRow> for PushSubscriptionRecord {
+    fn from(row: PushSubscriptionRow) -> Self {
+        Self {
+            id: row.0,
+            user_id: row.1,
+            platform: row.2,
+            device_token: row.3,
+            preferences: row.4,
+            created_at: from_cql_timestamp(row.5),
+        }
+    }
+}
+
+impl From<CustomEventSchemaRow> for CustomEventSchemaRecord {
+    fn from(row: CustomEventSchemaRow) -> Self {
+        Self {
+            event_type: row.0,
+            schema: row.1,
+            description: row.2,
+            registered_at: from_cql_timestamp(row.3),
+        }
+    }
+}
+
+impl From<CustomEventRow> for CustomEventRecord {
+    fn from(row: CustomEventRow) -> Self {
+        Self {
+            event_type: row.0,
+            event_time: from_cql_timestamp(row.1),
+            event_id: row.2,
+            drone_id: row.3,
+            mission_id: row.4,
+            value: row.5,
+        }
+    }
+}
+
+impl From<LeaseRow> for LeaseRecord {
+    fn from(row: LeaseRow) -> Self {
+        Self {
+            name: row.0,
+            holder: row.1,
+            acquired_at: from_cql_timestamp(row.2),
+            expires_at: from_cql_timestamp(row.3),
+        }
+    }
+}
+
+impl From<AlertRuleRow> for AlertRuleRecord {
+    fn from(row: AlertRuleRow) -> Self {
+        Self {
+            id: row.0,
+            name: row.1,
+            condition: row.2,
+            severity: row.3,
+            message: row.4,
+            enabled: row.5,
+            created_at: from_cql_timestamp(row.6),
+            updated_at: from_cql_timestamp(row.7),
+        }
+    }
+}
+
+impl From<WaypointAttachmentRow> for WaypointAttachmentRecord {
+    fn from(row: WaypointAttachmentRow) -> Self {
+        Self {
+            id: row.0,
+            mission_id: row.1,
+            waypoint_id: row.2,
+            file_name: row.3,
+            content_type: row.4,
+            size_bytes: row.5,
+            object_key: row.6,
+            threat_level: row.7,
+            notes: row.8,
+            uploaded_by: row.9,
+            created_at: from_cql_timestamp(row.10),
+        }
+    }
+}
+
+impl From<ZoneDwellRow> for ZoneDwellRecord {
+    fn from(row: ZoneDwellRow) -> Self {
+        Self {
+            zone_id: row.0,
+            mission_id: row.1,
+            drone_id: row.2,
+            visits: row.3.unwrap_or_default(),
+            time_inside_ms: row.4.unwrap_or_default(),
+            first_entry: row.5.map(from_cql_timestamp),
+            last_entry: row.6.map(from_cql_timestamp),
+            last_exit: row.7.map(from_cql_timestamp),
+        }
+    }
+}
+
+impl From<DroneSnapshotRow> for DroneSnapshotRecord {
+    fn from(row: DroneSnapshotRow) -> Self {
+        Self {
+            drone_id: row.0,
+            snapshot: row.1,
+            last_update: from_cql_timestamp(row.2),
+            evicted_at: from_cql_timestamp(row.3),
+        }
+    }
+}
+
+impl From<CvTuningRow> for CvTuningRecord {
+    fn from(row: CvTuningRow) -> Self {
+        Self {
+            revision: row.0,
+            tuning: row.1,
+            updated_at: from_cql_timestamp(row.2),
+        }
+    }
+}
+
+impl From<DroneGroupRow> for DroneGroupRecord {
+    fn from(row: DroneGroupRow) -> Self {
+        Self {
+            name: row.0,
+            members: row.1.unwrap_or_default(),
+            role: row.2,
+            description: row.3,
+            created_at: from_cql_timestamp(row.4),
+            updated_at: from_cql_timestamp(row.5),
+        }
+    }
+}
+
+/// Backend connection handle
+enum Backend {
+    Scylla(Arc<Session>),
+    Sqlite(SqliteStore),
+}
+
 /// Main database client
 pub struct DbClient {
-    session: Arc<Session>,
+    backend: Backend,
     config: DbConfig,
-    telemetry_repo: TelemetryRepository,
-    waypoint_repo: WaypointRepository,
-    tracking_repo: TrackingRepository,
-    mission_repo: MissionRepository,
-    drone_repo: DroneRepository,
-    alert_repo: AlertRepository,
+    /// Connection time and failed writes or health checks
+    health: SubsystemHealth,
+    telemetry_repo: Arc<dyn TelemetryStore>,
+    waypoint_repo: Arc<dyn WaypointStore>,
+    tracking_repo: Arc<dyn TrackingStore>,
+    mission_repo: Arc<dyn MissionStore>,
+    drone_repo: Arc<dyn DroneStore>,
+    alert_repo: Arc<dyn AlertStore>,
+    quality_repo: Arc<dyn DataQualityStore>,
+    schedule_repo: Arc<dyn ScheduleStore>,
+    zone_repo: Arc<dyn ZoneStore>,
+    notification_repo: Arc<dyn NotificationStore>,
+    custom_event_repo: Arc<dyn CustomEventStore>,
+    lease_repo: Arc<dyn LeaseStore>,
+    retention_repo: Arc<dyn RetentionStore>,
 }
 
 impl DbClient {
     pub async fn new(config: DbConfig) -> DbResult<Self> {
+        match config.backend {
+            StorageBackend::Scylla => Self::connect_scylla(config).await,
+            StorageBackend::Sqlite => Self::open_sqlite(config).await,
+        }
+    }
+
+    async fn connect_scylla(config: DbConfig) -> DbResult<Self> {
         info!("Connecting to ScyllaDB cluster: {:?}", config.hosts);
 
+        let consistency = config.consistency;
+        let profile = ExecutionProfile::builder()
+            .consistency(consistency.default.into())
+            .build();
+
         let session = SessionBuilder::new()
             .known_nodes(&config.hosts)
+            .default_execution_profile_handle(profile.into_handle())
             .connection_timeout(config.connection_timeout)
             .use_keyspace(&config.keyspace, false)
             .build()
@@ -123,49 +934,149 @@ impl DbClient {
 
         let session = Arc::new(session);
         info!("Connected to ScyllaDB");
+        if config.telemetry_storage == TelemetryStorage::Compact {
+            warn!("Compact telemetry storage is not supported on ScyllaDB; storing a row per sample");
+        }
 
         Ok(Self {
-            telemetry_repo: TelemetryRepository::new(session.clone()),
-            waypoint_repo: WaypointRepository::new(session.clone()),
-            tracking_repo: TrackingRepository::new(session.clone()),
-            mission_repo: MissionRepository::new(session.clone()),
-            drone_repo: DroneRepository::new(session.clone()),
-            alert_repo: AlertRepository::new(session.clone()),
-            session,
+            telemetry_repo: Arc::new(TelemetryRepository::new(session.clone(), consistency)),
+            waypoint_repo: Arc::new(WaypointRepository::new(session.clone())),
+            tracking_repo: Arc::new(TrackingRepository::new(session.clone())),
+            mission_repo: Arc::new(MissionRepository::new(session.clone(), consistency)),
+            drone_repo: Arc::new(DroneRepository::new(session.clone())),
+            alert_repo: Arc::new(AlertRepository::new(session.clone())),
+            quality_repo: Arc::new(DataQualityRepository::new(session.clone())),
+            schedule_repo: Arc::new(ScheduleRepository::new(session.clone())),
+            zone_repo: Arc::new(ZoneRepository::new(session.clone())),
+            notification_repo: Arc::new(NotificationRepository::new(session.clone())),
+            custom_event_repo: Arc::new(CustomEventRepository::new(session.clone())),
+            lease_repo: Arc::new(LeaseRepository::new(session.clone(), consistency)),
+            retention_repo: Arc::new(RetentionRepository::new(session.clone())),
+            backend: Backend::Scylla(session),
             config,
+            health: SubsystemHealth::default(),
         })
     }
 
-    pub fn session(&self) -> Arc<Session> {
-        self.session.clone()
+    async fn open_sqlite(config: DbConfig) -> DbResult<Self> {
+        info!("Opening SQLite database: {}", config.sqlite_path.display());
+
+        let store = SqliteStore::open(config.sqlite_path.clone()).await?;
+        Ok(Self::from_sqlite(store, config))
+    }
+
+    /// Build a client around an already opened SQLite store
+    pub fn from_sqlite(store: SqliteStore, config: DbConfig) -> Self {
+        let store = store.with_telemetry_storage(config.telemetry_storage);
+        Self {
+            telemetry_repo: Arc::new(store.clone()),
+            waypoint_repo: Arc::new(store.clone()),
+            tracking_repo: Arc::new(store.clone()),
+            mission_repo: Arc::new(store.clone()),
+            drone_repo: Arc::new(store.clone()),
+            alert_repo: Arc::new(store.clone()),
+            quality_repo: Arc::new(store.clone()),
+            schedule_repo: Arc::new(store.clone()),
+            zone_repo: Arc::new(store.clone()),
+            notification_repo: Arc::new(store.clone()),
+            custom_event_repo: Arc::new(store.clone()),
+            lease_repo: Arc::new(store.clone()),
+            retention_repo: Arc::new(store.clone()),
+            backend: Backend::Sqlite(store),
+            config,
+            health: SubsystemHealth::default(),
+        }
+    }
+
+    /// ScyllaDB session, when running on the Scylla backend
+    pub fn session(&self) -> Option<Arc<Session>> {
+        match &self.backend {
+            Backend::Scylla(session) => Some(session.clone()),
+            Backend::Sqlite(_) => None,
+        }
+    }
+
+    pub fn backend(&self) -> StorageBackend {
+        match self.backend {
+            Backend::Scylla(_) => StorageBackend::Scylla,
+            Backend::Sqlite(_) => StorageBackend::Sqlite,
+        }
+    }
+
+    pub fn config(&self) -> &DbConfig {
+        &self.config
+    }
+
+    pub fn telemetry(&self) -> &dyn TelemetryStore {
+        self.telemetry_repo.as_ref()
+    }
+
+    pub fn waypoints(&self) -> &dyn WaypointStore {
+        self.waypoint_repo.as_ref()
+    }
+
+    pub fn tracking(&self) -> &dyn TrackingStore {
+        self.tracking_repo.as_ref()
+    }
+
+    pub fn missions(&self) -> &dyn MissionStore {
+        self.mission_repo.as_ref()
+    }
+
+    pub fn drones(&self) -> &dyn DroneStore {
+        self.drone_repo.as_ref()
+    }
+
+    pub fn alerts(&self) -> &dyn AlertStore {
+        self.alert_repo.as_ref()
+    }
+
+    pub fn quality(&self) -> &dyn DataQualityStore {
+        self.quality_repo.as_ref()
     }
 
-    pub fn telemetry(&self) -> &TelemetryRepository {
-        &self.telemetry_repo
+    pub fn schedules(&self) -> &dyn ScheduleStore {
+        self.schedule_repo.as_ref()
     }
 
-    pub fn waypoints(&self) -> &WaypointRepository {
-        &self.waypoint_repo
+    pub fn zones(&self) -> &dyn ZoneStore {
+        self.zone_repo.as_ref()
     }
 
-    pub fn tracking(&self) -> &TrackingRepository {
-        &self.tracking_repo
+    pub fn notifications(&self) -> &dyn NotificationStore {
+        self.notification_repo.as_ref()
     }
 
-    pub fn missions(&self) -> &MissionRepository {
-        &self.mission_repo
+    pub fn custom_events(&self) -> &dyn CustomEventStore {
+        self.custom_event_repo.as_ref()
     }
 
-    pub fn drones(&self) -> &DroneRepository {
-        &self.drone_repo
+    pub fn leases(&self) -> &dyn LeaseStore {
+        self.lease_repo.as_ref()
     }
 
-    pub fn alerts(&self) -> &AlertRepository {
-        &self.alert_repo
+    pub fn retention(&self) -> &dyn RetentionStore {
+        self.retention_repo.as_ref()
+    }
+
+    /// Uptime and failure count; callers that log a failed write record it here
+    pub fn health(&self) -> &SubsystemHealth {
+        &self.health
     }
 
     pub async fn health_check(&self) -> DbResult<bool> {
-        let result = self.session
+        let session = match &self.backend {
+            Backend::Scylla(session) => session,
+            Backend::Sqlite(store) => {
+                let healthy = store.health_check().await;
+                if !matches!(healthy, Ok(true)) {
+                    self.health.record_error();
+                }
+                return healthy;
+            }
+        };
+
+        let result = session
             .query_unpaged("SELECT now() FROM system.local", &[])
             .await;
         
@@ -173,13 +1084,18 @@ impl DbClient {
             Ok(_) => Ok(true),
             Err(e) => {
                 warn!("Database health check failed: {}", e);
+                self.health.record_error();
                 Ok(false)
             }
         }
     }
 
     pub async fn run_migrations(&self) -> DbResult<()> {
-        migrations::run_all(&self.session).await
+        match &self.backend {
+            Backend::Scylla(session) => migrations::run_all(session).await,
+            // Schema is created when the SQLite store is opened
+            Backend::Sqlite(_) => Ok(()),
+        }
     }
 }
 
@@ -191,14 +1107,43 @@ impl DbClient {
 #[derive(Clone)]
 pub struct TelemetryRepository {
     session: Arc<Session>,
+    consistency: ConsistencyConfig,
 }
 
 impl TelemetryRepository {
-    pub fn new(session: Arc<Session>) -> Self {
-        Self { session }
+    pub fn new(session: Arc<Session>, consistency: ConsistencyConfig) -> Self {
+        Self { session, consistency }
+    }
+
+    /// The telemetry table's `default_time_to_live` in seconds (`None` when
+    /// rows do not expire)
+    async fn table_ttl(&self) -> DbResult<Option<i64>> {
+        let Some(keyspace) = self.session.get_keyspace() else {
+            return Ok(None);
+        };
+        let query = "SELECT default_time_to_live FROM system_schema.tables \
+                     WHERE keyspace_name = ? AND table_name = 'drone_telemetry'";
+        let result = self
+            .session
+            .query_unpaged(query, (keyspace.as_str(),))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        let ttl = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .maybe_first_row::<(Option<i32>,)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+            .and_then(|(ttl,)| ttl);
+        Ok(ttl.filter(|ttl| *ttl > 0).map(i64::from))
     }
+}
+
+/// Imported rows per unlogged batch
+const IMPORT_BATCH_ROWS: usize = 100;
 
-    pub async fn insert(
+#[async_trait]
+impl TelemetryStore for TelemetryRepository {
+    async fn insert(
         &self,
         drone_id: &DroneId,
         position: &GeoPosition,
@@ -209,8 +1154,8 @@ impl TelemetryRepository {
             INSERT INTO drone_telemetry (
                 drone_id, timestamp, latitude, longitude, altitude,
                 heading, speed, battery_level, fuel_level, system_health,
-                status, armed, temperature, signal_strength, mission_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                status, armed, temperature, signal_strength, mission_id, gimbal
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#;
 
         let timestamp_ms = telemetry.timestamp.timestamp_millis();
@@ -218,7 +1163,7 @@ impl TelemetryRepository {
 
         self.session
             .query_unpaged(
-                query,
+                consistency::statement(query, self.consistency.telemetry_write),
                 (
                     drone_id.as_str(),
                     timestamp_ms,
@@ -235,6 +1180,7 @@ impl TelemetryRepository {
                     telemetry.temperature,
                     telemetry.signal_strength as i32,
                     mission_uuid,
+                    telemetry.gimbal.map(|g| (g.pan, g.tilt, g.zoom)),
                 ),
             )
             .await
@@ -243,7 +1189,81 @@ impl TelemetryRepository {
         Ok(())
     }
 
-    pub async fn get_latest(
+    async fn insert_records(&self, records: Vec<TelemetryRecord>) -> DbResult<()> {
+        // The TTL goes in the statement text: the bound row is already at
+        // the driver's 16-value tuple limit
+        let query = |ttl: i32| {
+            format!(
+                "INSERT INTO drone_telemetry (
+                    drone_id, timestamp, latitude, longitude, altitude,
+                    heading, speed, battery_level, fuel_level, system_health,
+                    status, armed, temperature, signal_strength, mission_id, gimbal
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                USING TTL {}",
+                ttl
+            )
+        };
+
+        // Historical rows expire the table TTL after their own timestamp,
+        // not after the import; rows already past it are not written
+        let table_ttl = self.table_ttl().await?;
+        let now = Utc::now();
+        let mut by_drone: BTreeMap<String, Vec<TelemetryRecord>> = BTreeMap::new();
+        for record in records {
+            by_drone.entry(record.drone_id.clone()).or_default().push(record);
+        }
+
+        // A drone's rows share a partition, so each batch stays on one
+        for rows in by_drone.values() {
+            for chunk in rows.chunks(IMPORT_BATCH_ROWS) {
+                let mut batch = Batch::new(BatchType::Unlogged);
+                batch.set_consistency(self.consistency.telemetry_write.into());
+                let mut values = Vec::with_capacity(chunk.len());
+                for record in chunk {
+                    let ttl = match table_ttl {
+                        Some(ttl) => {
+                            let remaining = ttl - (now - record.timestamp).num_seconds().max(0);
+                            if remaining <= 0 {
+                                continue;
+                            }
+                            remaining as i32
+                        }
+                        None => 0,
+                    };
+                    batch.append_statement(query(ttl).as_str());
+                    values.push((
+                        record.drone_id.as_str(),
+                        record.timestamp.timestamp_millis(),
+                        record.latitude,
+                        record.longitude,
+                        record.altitude,
+                        record.heading,
+                        record.speed,
+                        record.battery_level,
+                        record.fuel_level,
+                        record.system_health,
+                        record.status.as_deref(),
+                        record.armed,
+                        record.temperature,
+                        record.signal_strength,
+                        record.mission_id,
+                        record.gimbal.map(|g| (g.pan, g.tilt, g.zoom)),
+                    ));
+                }
+                if values.is_empty() {
+                    continue;
+                }
+                self.session
+                    .batch(&batch, values)
+                    .await
+                    .map_err(|e| DbError::Query(e.to_string()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_latest(
         &self,
         drone_id: &DroneId,
     ) -> DbResult<Option<(GeoPosition, Telemetry)>> {
@@ -258,7 +1278,10 @@ impl TelemetryRepository {
 
         let result = self
             .session
-            .query_unpaged(query, (drone_id.as_str(),))
+            .query_unpaged(
+                consistency::statement(query, self.consistency.telemetry_read),
+                (drone_id.as_str(),),
+            )
             .await
             .map_err(|e| DbError::Query(e.to_string()))?;
 
@@ -274,7 +1297,7 @@ impl TelemetryRepository {
     }
 
 
-    pub async fn get_history(
+    async fn get_history(
         &self,
         drone_id: &DroneId,
         _limit: i32,
@@ -293,31 +1316,107 @@ impl TelemetryRepository {
         let _ = drone_id; // suppress warning
         Ok(Vec::new())
     }
-}
-
-/// Repository for waypoint events
-#[derive(Clone)]
-pub struct WaypointRepository {
-    session: Arc<Session>,
-}
-
-impl WaypointRepository {
-    pub fn new(session: Arc<Session>) -> Self {
-        Self { session }
-    }
 
-    pub async fn record_reached(
+    /// Stream raw telemetry rows for a drone within `[from, to]`, oldest first
+    async fn stream_range(
         &self,
         drone_id: &DroneId,
-        waypoint_id: &WaypointId,
-        mission_id: &MissionId,
-        position: &GeoPosition,
-    ) -> DbResult<()> {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<TelemetryRecord>> {
         let query = r#"
-            INSERT INTO waypoint_events (
-                drone_id, waypoint_id, mission_id, event_type,
-                timestamp, latitude, longitude, altitude
-            ) VALUES (?, ?, ?, 'REACHED', toTimestamp(now()), ?, ?, ?)
+            SELECT drone_id, timestamp, latitude, longitude, altitude,
+                   heading, speed, battery_level, fuel_level, system_health,
+                   status, armed, temperature, signal_strength, mission_id, gimbal
+            FROM drone_telemetry
+            WHERE drone_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp ASC
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(
+                consistency::statement(query, self.consistency.telemetry_read),
+                (
+                    drone_id.as_str(),
+                    CqlTimestamp(from.timestamp_millis()),
+                    CqlTimestamp(to.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<TelemetryRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(rows.map(|row| {
+            row.map(TelemetryRecord::from)
+                .map_err(|e| DbError::Serialization(e.to_string()))
+        })
+        .boxed())
+    }
+
+    async fn get_at(
+        &self,
+        drone_id: &DroneId,
+        since: DateTime<Utc>,
+        at: DateTime<Utc>,
+    ) -> DbResult<Option<TelemetryRecord>> {
+        let query = r#"
+            SELECT drone_id, timestamp, latitude, longitude, altitude,
+                   heading, speed, battery_level, fuel_level, system_health,
+                   status, armed, temperature, signal_strength, mission_id, gimbal
+            FROM drone_telemetry
+            WHERE drone_id = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp DESC
+            LIMIT 1
+        "#;
+
+        let row = self
+            .session
+            .query_unpaged(
+                consistency::statement(query, self.consistency.telemetry_read),
+                (
+                    drone_id.as_str(),
+                    CqlTimestamp(since.timestamp_millis()),
+                    CqlTimestamp(at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .maybe_first_row::<TelemetryRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(row.map(TelemetryRecord::from))
+    }
+}
+
+/// Repository for waypoint events
+#[derive(Clone)]
+pub struct WaypointRepository {
+    session: Arc<Session>,
+}
+
+impl WaypointRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+
+    /// Insert a `waypoint_events` row of the given type, timestamped now
+    async fn insert_event(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+        event_type: &str,
+    ) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO waypoint_events (
+                drone_id, waypoint_id, mission_id, event_type,
+                timestamp, latitude, longitude, altitude
+            ) VALUES (?, ?, ?, ?, toTimestamp(now()), ?, ?, ?)
         "#;
 
         self.session
@@ -327,6 +1426,7 @@ impl WaypointRepository {
                     drone_id.as_str(),
                     waypoint_id.0.as_str(),
                     mission_id.0,
+                    event_type,
                     position.latitude,
                     position.longitude,
                     position.altitude,
@@ -339,9 +1439,135 @@ impl WaypointRepository {
     }
 }
 
+#[async_trait]
+impl WaypointStore for WaypointRepository {
+    async fn record_reached(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+    ) -> DbResult<()> {
+        self.insert_event(drone_id, waypoint_id, mission_id, position, "REACHED").await
+    }
+
+    async fn record_skipped(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+    ) -> DbResult<()> {
+        self.insert_event(drone_id, waypoint_id, mission_id, position, "SKIPPED").await
+    }
+
+    /// Stream waypoint events for a mission within `[from, to]`, oldest first
+    async fn stream_range(
+        &self,
+        mission_id: &MissionId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<WaypointEventRecord>> {
+        let query = r#"
+            SELECT mission_id, event_time, drone_id, waypoint_id, waypoint_name,
+                   latitude, longitude, event_type, speed_at_event,
+                   altitude_at_event, heading
+            FROM waypoint_events
+            WHERE mission_id = ? AND event_time >= ? AND event_time <= ?
+            ORDER BY event_time ASC
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(
+                query,
+                (
+                    mission_id.0,
+                    CqlTimestamp(from.timestamp_millis()),
+                    CqlTimestamp(to.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<WaypointEventRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(rows.map(|row| {
+            row.map(WaypointEventRecord::from)
+                .map_err(|e| DbError::Serialization(e.to_string()))
+        })
+        .boxed())
+    }
+
+    async fn save_attachment(&self, attachment: &WaypointAttachmentRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO waypoint_attachments (
+                id, mission_id, waypoint_id, file_name, content_type, size_bytes,
+                object_key, threat_level, notes, uploaded_by, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    attachment.id,
+                    attachment.mission_id,
+                    attachment.waypoint_id.as_str(),
+                    attachment.file_name.as_str(),
+                    attachment.content_type.as_str(),
+                    attachment.size_bytes,
+                    attachment.object_key.as_str(),
+                    attachment.threat_level.as_deref(),
+                    attachment.notes.as_deref(),
+                    attachment.uploaded_by.as_deref(),
+                    CqlTimestamp(attachment.created_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_attachment(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.session
+            .query_unpaged("DELETE FROM waypoint_attachments WHERE id = ?", (id,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn attachments(&self) -> DbResult<Vec<WaypointAttachmentRecord>> {
+        let query = r#"
+            SELECT id, mission_id, waypoint_id, file_name, content_type, size_bytes,
+                   object_key, threat_level, notes, uploaded_by, created_at
+            FROM waypoint_attachments
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<WaypointAttachmentRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut attachments: Vec<WaypointAttachmentRecord> = rows
+            .map_ok(WaypointAttachmentRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        attachments.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(attachments)
+    }
+}
+
 /// Repository for CV tracking results
 #[derive(Clone)]
 pub struct TrackingRepository {
+    #[allow(dead_code)] // Used by the OpenCV-enabled insert path
     session: Arc<Session>,
 }
 
@@ -349,11 +1575,14 @@ impl TrackingRepository {
     pub fn new(session: Arc<Session>) -> Self {
         Self { session }
     }
+}
 
+#[async_trait]
+impl TrackingStore for TrackingRepository {
     /// Insert CV tracking result
     /// NOTE: Commented out for macOS build (requires OpenCV/Xcode 15)
     /// Uncomment for Linux builds with OpenCV support
-    pub async fn insert(&self, _result: &TrackingResult) -> DbResult<()> {
+    async fn insert(&self, _result: &TrackingResult) -> DbResult<()> {
         // TODO: Re-enable for Linux builds with OpenCV
         /*
         // Split into two queries to avoid 16-tuple limit
@@ -432,41 +1661,94 @@ impl TrackingRepository {
         Ok(()) // Stubbed for macOS
     }
 
-    pub async fn insert_batch(&self, results: &[TrackingResult]) -> DbResult<()> {
-        for result in results {
-            self.insert(result).await?;
-        }
+    /// Nothing is stored while inserts are stubbed
+    async fn stream_range(&self, _from: DateTime<Utc>, _to: DateTime<Utc>) -> DbResult<RecordStream<TrackingResult>> {
+        Ok(futures::stream::empty().boxed())
+    }
+
+    async fn save_cv_tuning(&self, tuning: &CvTuningRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO cv_tuning (
+                id, revision, tuning, updated_at
+            ) VALUES ('current', ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    tuning.revision,
+                    tuning.tuning.as_str(),
+                    CqlTimestamp(tuning.updated_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
         Ok(())
     }
+
+    async fn cv_tuning(&self) -> DbResult<Option<CvTuningRecord>> {
+        let query = "SELECT revision, tuning, updated_at FROM cv_tuning WHERE id = 'current'";
+
+        let row = self
+            .session
+            .query_unpaged(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .maybe_first_row::<CvTuningRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(row.map(CvTuningRecord::from))
+    }
 }
 
 /// Repository for missions
 #[derive(Clone)]
 pub struct MissionRepository {
     session: Arc<Session>,
+    consistency: ConsistencyConfig,
 }
 
 impl MissionRepository {
-    pub fn new(session: Arc<Session>) -> Self {
-        Self { session }
+    pub fn new(session: Arc<Session>, consistency: ConsistencyConfig) -> Self {
+        Self { session, consistency }
     }
+}
 
-    pub async fn create(&self, mission: &Mission) -> DbResult<()> {
+#[async_trait]
+impl MissionStore for MissionRepository {
+    async fn create(&self, mission: &Mission) -> DbResult<()> {
         let query = r#"
             INSERT INTO missions (
                 mission_id, created_at, name, description, status,
-                start_time, end_time, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                start_time, end_time, corridor, auto_corridor, body, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#;
 
         let created_at_ms = mission.created_at.timestamp_millis();
         let start_time_ms = mission.start_time.map(|t| t.timestamp_millis());
         let end_time_ms = mission.end_time.map(|t| t.timestamp_millis());
         let updated_at_ms = mission.updated_at.timestamp_millis();
+        let corridor = mission
+            .corridor
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+        let auto_corridor = mission
+            .auto_corridor
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+        let body = serde_json::to_string(mission).map_err(|e| DbError::Serialization(e.to_string()))?;
 
         self.session
             .query_unpaged(
-                query,
+                consistency::statement(query, self.consistency.mission_write),
                 (
                     mission.id.0,
                     created_at_ms,
@@ -475,6 +1757,9 @@ impl MissionRepository {
                     format!("{:?}", mission.status),
                     start_time_ms,
                     end_time_ms,
+                    corridor,
+                    auto_corridor,
+                    body,
                     updated_at_ms,
                 ),
             )
@@ -484,31 +1769,94 @@ impl MissionRepository {
         Ok(())
     }
 
-    pub async fn update_status(&self, mission_id: &MissionId, status: &str) -> DbResult<()> {
+    async fn update_status(&self, mission_id: &MissionId, status: &str) -> DbResult<()> {
         let query = r#"
             UPDATE missions SET status = ?, updated_at = toTimestamp(now())
             WHERE mission_id = ?
         "#;
 
         self.session
-            .query_unpaged(query, (status, mission_id.0))
+            .query_unpaged(
+                consistency::statement(query, self.consistency.mission_write),
+                (status, mission_id.0),
+            )
             .await
             .map_err(|e| DbError::Query(e.to_string()))?;
 
         Ok(())
     }
 
-    pub async fn get(&self, mission_id: &MissionId) -> DbResult<Option<Mission>> {
-        let query = "SELECT * FROM missions WHERE mission_id = ?";
+    async fn transition_status(
+        &self,
+        mission_id: &MissionId,
+        expected: &str,
+        status: &str,
+    ) -> DbResult<bool> {
+        let query = r#"
+            UPDATE missions SET status = ?, updated_at = toTimestamp(now())
+            WHERE mission_id = ?
+            IF status = ?
+        "#;
 
-        let _result = self
+        let result = self
             .session
-            .query_unpaged(query, (mission_id.0,))
+            .query_unpaged(
+                consistency::conditional_statement(
+                    query,
+                    self.consistency.mission_write,
+                    self.consistency.mission_serial,
+                ),
+                (status, mission_id.0, expected),
+            )
             .await
             .map_err(|e| DbError::Query(e.to_string()))?;
 
-        // TODO: Parse row into Mission
-        Ok(None)
+        // LWT results start with an `[applied]` column, followed by the
+        // current values when the condition failed
+        let rows = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        let applied = rows
+            .maybe_first_row::<scylla::frame::response::result::Row>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+            .and_then(|row| row.columns.into_iter().next().flatten())
+            .and_then(|value| value.as_boolean())
+            .unwrap_or(false);
+
+        Ok(applied)
+    }
+
+    async fn get(&self, mission_id: &MissionId) -> DbResult<Option<Mission>> {
+        let query = "SELECT body, status, updated_at FROM missions WHERE mission_id = ?";
+
+        let row = self
+            .session
+            .query_unpaged(
+                consistency::statement(query, self.consistency.mission_read),
+                (mission_id.0,),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .maybe_first_row::<(Option<String>, Option<String>, Option<CqlTimestamp>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        // Rows written before the body column have nothing to rebuild from
+        let Some((Some(body), status, updated_at)) = row else {
+            return Ok(None);
+        };
+        let mut mission: Mission =
+            serde_json::from_str(&body).map_err(|e| DbError::Serialization(e.to_string()))?;
+        // Status updates only touch the status column
+        if let Some(status) = status.and_then(|s| serde_json::from_value(s.to_uppercase().into()).ok()) {
+            mission.status = status;
+        }
+        if let Some(updated_at) = updated_at.and_then(|t| Utc.timestamp_millis_opt(t.0).single()) {
+            mission.updated_at = updated_at;
+        }
+
+        Ok(Some(mission))
     }
 }
 
@@ -522,8 +1870,11 @@ impl DroneRepository {
     pub fn new(session: Arc<Session>) -> Self {
         Self { session }
     }
+}
 
-    pub async fn register(&self, drone: &Drone) -> DbResult<()> {
+#[async_trait]
+impl DroneStore for DroneRepository {
+    async fn register(&self, drone: &Drone) -> DbResult<()> {
         let query = r#"
             INSERT INTO drone_registry (
                 drone_id, callsign, drone_type, operational, registered_at, updated_at
@@ -546,7 +1897,7 @@ impl DroneRepository {
         Ok(())
     }
 
-    pub async fn get_all(&self) -> DbResult<Vec<DroneId>> {
+    async fn get_all(&self) -> DbResult<Vec<DroneId>> {
         let query =
             "SELECT drone_id FROM drone_registry WHERE operational = true ALLOW FILTERING";
 
@@ -559,82 +1910,1341 @@ impl DroneRepository {
         // TODO: Parse rows
         Ok(Vec::new())
     }
-}
 
-/// Repository for alerts
-#[derive(Clone)]
-pub struct AlertRepository {
-    session: Arc<Session>,
-}
+    /// Store (or clear, when empty) a drone's alert threshold overrides
+    async fn set_alert_thresholds(
+        &self,
+        drone_id: &DroneId,
+        overrides: &ThresholdOverrides,
+    ) -> DbResult<()> {
+        let query = r#"
+            UPDATE drone_registry SET alert_thresholds = ?, updated_at = toTimestamp(now())
+            WHERE drone_id = ?
+        "#;
 
-impl AlertRepository {
-    pub fn new(session: Arc<Session>) -> Self {
-        Self { session }
+        let encoded = encode_overrides(overrides)?;
+
+        self.session
+            .query_unpaged(query, (encoded, drone_id.as_str()))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
     }
 
-    pub async fn create(&self, alert: &Alert) -> DbResult<()> {
-        let drone_id = alert.drone_id.as_ref().map(|d| d.as_str());
+    /// Load all per-drone alert threshold overrides
+    async fn get_alert_thresholds(&self) -> DbResult<Vec<(DroneId, ThresholdOverrides)>> {
+        let query = "SELECT drone_id, alert_thresholds FROM drone_registry";
+
+        let result = self
+            .session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows_result = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let mut overrides = Vec::new();
+        for row in rows_result
+            .rows::<(String, Option<String>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+        {
+            let (drone_id, encoded) = row.map_err(|e| DbError::Serialization(e.to_string()))?;
+            if let Some(encoded) = encoded {
+                overrides.push((DroneId::new(drone_id), decode_overrides(&encoded)?));
+            }
+        }
+
+        Ok(overrides)
+    }
 
+    /// Store (or clear, when empty) alert threshold overrides for a drone type
+    async fn set_type_alert_thresholds(
+        &self,
+        drone_type: &DroneType,
+        overrides: &ThresholdOverrides,
+    ) -> DbResult<()> {
         let query = r#"
-            INSERT INTO alerts (
-                alert_id, created_at, severity, alert_type, message,
-                drone_id, acknowledged, resolved
-            ) VALUES (?, toTimestamp(now()), ?, ?, ?, ?, false, false)
+            INSERT INTO drone_type_thresholds (drone_type, alert_thresholds, updated_at)
+            VALUES (?, ?, toTimestamp(now()))
         "#;
 
+        let key = serde_json::to_string(drone_type)
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+        let encoded = encode_overrides(overrides)?;
+
         self.session
-            .query_unpaged(
-                query,
-                (
-                    alert.id,
-                    format!("{:?}", alert.severity),
-                    format!("{:?}", alert.alert_type),
-                    alert.message.as_str(),
-                    drone_id.unwrap_or(""),
-                ),
-            )
+            .query_unpaged(query, (key, encoded))
             .await
             .map_err(|e| DbError::Query(e.to_string()))?;
 
         Ok(())
     }
 
-    pub async fn acknowledge(
+    /// Load all per-type alert threshold overrides
+    async fn get_type_alert_thresholds(
+        &self,
+    ) -> DbResult<Vec<(DroneType, ThresholdOverrides)>> {
+        let query = "SELECT drone_type, alert_thresholds FROM drone_type_thresholds";
+
+        let result = self
+            .session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows_result = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let mut overrides = Vec::new();
+        for row in rows_result
+            .rows::<(String, Option<String>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+        {
+            let (key, encoded) = row.map_err(|e| DbError::Serialization(e.to_string()))?;
+            let drone_type: DroneType = serde_json::from_str(&key)
+                .map_err(|e| DbError::Serialization(e.to_string()))?;
+            if let Some(encoded) = encoded {
+                overrides.push((drone_type, decode_overrides(&encoded)?));
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    /// Store (or clear, with `None`) a drone's command transport binding
+    async fn set_transport_binding(
         &self,
         drone_id: &DroneId,
-        alert_id: uuid::Uuid,
-        by: &str,
+        binding: Option<&TransportBinding>,
     ) -> DbResult<()> {
         let query = r#"
-            UPDATE alerts SET acknowledged = true, acknowledged_by = ?, 
-                             acknowledged_at = toTimestamp(now())
-            WHERE drone_id = ? AND alert_id = ?
+            UPDATE drone_registry SET command_transport = ?, updated_at = toTimestamp(now())
+            WHERE drone_id = ?
         "#;
 
+        let encoded = binding
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
         self.session
-            .query_unpaged(query, (by, drone_id.as_str(), alert_id))
+            .query_unpaged(query, (encoded, drone_id.as_str()))
             .await
             .map_err(|e| DbError::Query(e.to_string()))?;
 
         Ok(())
     }
-}
 
-// ============================================================================
-// TESTS
-// ============================================================================
+    /// Store (or clear, with `None`) a drone's designated telemetry source
+    async fn set_telemetry_source(&self, drone_id: &DroneId, source: Option<TelemetrySource>) -> DbResult<()> {
+        let query = r#"
+            UPDATE drone_registry SET telemetry_source = ?, updated_at = toTimestamp(now())
+            WHERE drone_id = ?
+        "#;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        self.session
+            .query_unpaged(query, (source.map(|source| source.to_string()), drone_id.as_str()))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
 
-    #[test]
-    fn test_db_config_default() {
-        let config = DbConfig::default();
-        assert_eq!(config.hosts.len(), 1);
+        Ok(())
+    }
+
+    /// Load all telemetry source designations
+    async fn get_telemetry_sources(&self) -> DbResult<Vec<(DroneId, TelemetrySource)>> {
+        let query = "SELECT drone_id, telemetry_source FROM drone_registry";
+
+        let result = self
+            .session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows_result = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let mut sources = Vec::new();
+        for row in rows_result
+            .rows::<(String, Option<String>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+        {
+            let (drone_id, source) = row.map_err(|e| DbError::Serialization(e.to_string()))?;
+            if let Some(source) = source.and_then(|source| parse_telemetry_source(&drone_id, &source)) {
+                sources.push((DroneId::new(drone_id), source));
+            }
+        }
+
+        Ok(sources)
+    }
+
+    /// Store (or clear, with `None`) a drone's halo color and icon
+    async fn set_drone_marking(&self, drone_id: &DroneId, marking: Option<&DroneMarking>) -> DbResult<()> {
+        let query = r#"
+            UPDATE drone_registry SET fleet_marking = ?, updated_at = toTimestamp(now())
+            WHERE drone_id = ?
+        "#;
+
+        let encoded = marking
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        self.session
+            .query_unpaged(query, (encoded, drone_id.as_str()))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Load all drone markings
+    async fn get_drone_markings(&self) -> DbResult<Vec<(DroneId, DroneMarking)>> {
+        let query = "SELECT drone_id, fleet_marking FROM drone_registry";
+
+        let result = self
+            .session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows_result = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let mut markings = Vec::new();
+        for row in rows_result
+            .rows::<(String, Option<String>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+        {
+            let (drone_id, encoded) = row.map_err(|e| DbError::Serialization(e.to_string()))?;
+            if let Some(encoded) = encoded {
+                let marking = serde_json::from_str(&encoded)
+                    .map_err(|e| DbError::Serialization(e.to_string()))?;
+                markings.push((DroneId::new(drone_id), marking));
+            }
+        }
+
+        Ok(markings)
+    }
+
+    /// Record (or clear, with `None`) the station a drone was handed off to
+    async fn set_handoff_owner(&self, drone_id: &DroneId, owner: Option<&HandoffOwnerRecord>) -> DbResult<()> {
+        let query = r#"
+            UPDATE drone_registry SET handed_off_to = ?, updated_at = toTimestamp(now())
+            WHERE drone_id = ?
+        "#;
+
+        let encoded = owner
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        self.session
+            .query_unpaged(query, (encoded, drone_id.as_str()))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Load the owners of all drones handed off from here
+    async fn get_handoff_owners(&self) -> DbResult<Vec<(DroneId, HandoffOwnerRecord)>> {
+        let query = "SELECT drone_id, handed_off_to FROM drone_registry";
+
+        let result = self
+            .session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows_result = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let mut owners = Vec::new();
+        for row in rows_result
+            .rows::<(String, Option<String>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+        {
+            let (drone_id, encoded) = row.map_err(|e| DbError::Serialization(e.to_string()))?;
+            if let Some(encoded) = encoded {
+                let owner = serde_json::from_str(&encoded)
+                    .map_err(|e| DbError::Serialization(e.to_string()))?;
+                owners.push((DroneId::new(drone_id), owner));
+            }
+        }
+
+        Ok(owners)
+    }
+
+    /// Load all command transport bindings
+    async fn get_transport_bindings(&self) -> DbResult<Vec<(DroneId, TransportBinding)>> {
+        let query = "SELECT drone_id, command_transport FROM drone_registry";
+
+        let result = self
+            .session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows_result = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let mut bindings = Vec::new();
+        for row in rows_result
+            .rows::<(String, Option<String>)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+        {
+            let (drone_id, encoded) = row.map_err(|e| DbError::Serialization(e.to_string()))?;
+            if let Some(encoded) = encoded {
+                let binding = serde_json::from_str(&encoded)
+                    .map_err(|e| DbError::Serialization(e.to_string()))?;
+                bindings.push((DroneId::new(drone_id), binding));
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    async fn save_group(&self, group: &DroneGroupRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO drone_groups (
+                name, members, role, description, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    group.name.as_str(),
+                    &group.members,
+                    group.role.as_deref(),
+                    group.description.as_deref(),
+                    CqlTimestamp(group.created_at.timestamp_millis()),
+                    CqlTimestamp(group.updated_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_group(&self, name: &str) -> DbResult<()> {
+        self.session
+            .query_unpaged("DELETE FROM drone_groups WHERE name = ?", (name,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn groups(&self) -> DbResult<Vec<DroneGroupRecord>> {
+        let query = "SELECT name, members, role, description, created_at, updated_at FROM drone_groups";
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<DroneGroupRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut groups: Vec<DroneGroupRecord> = rows
+            .map_ok(DroneGroupRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(groups)
+    }
+
+    async fn save_snapshot(&self, snapshot: &DroneSnapshotRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO drone_snapshots (
+                drone_id, snapshot, last_update, evicted_at
+            ) VALUES (?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    snapshot.drone_id.as_str(),
+                    snapshot.snapshot.as_str(),
+                    CqlTimestamp(snapshot.last_update.timestamp_millis()),
+                    CqlTimestamp(snapshot.evicted_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn snapshot(&self, drone_id: &DroneId) -> DbResult<Option<DroneSnapshotRecord>> {
+        let query = "SELECT drone_id, snapshot, last_update, evicted_at FROM drone_snapshots WHERE drone_id = ?";
+
+        let row = self
+            .session
+            .query_unpaged(query, (drone_id.as_str(),))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .maybe_first_row::<DroneSnapshotRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(row.map(DroneSnapshotRecord::from))
+    }
+}
+
+/// Encode threshold overrides as JSON, mapping "no overrides" to NULL
+fn encode_overrides(overrides: &ThresholdOverrides) -> DbResult<Option<String>> {
+    if overrides.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(overrides)
+        .map(Some)
+        .map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+fn decode_overrides(encoded: &str) -> DbResult<ThresholdOverrides> {
+    serde_json::from_str(encoded).map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+/// A stored telemetry source; unknown ones (such as `mavlink`, which is no
+/// longer a source) are skipped, so the drone takes reports from any source
+fn parse_telemetry_source(drone_id: &str, source: &str) -> Option<TelemetrySource> {
+    let parsed = serde_json::from_value(serde_json::Value::String(source.to_string())).ok();
+    if parsed.is_none() {
+        warn!("Ignoring unknown telemetry source {:?} of drone {}", source, drone_id);
+    }
+    parsed
+}
+
+/// Repository for alerts
+#[derive(Clone)]
+pub struct AlertRepository {
+    session: Arc<Session>,
+}
+
+impl AlertRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl AlertStore for AlertRepository {
+    async fn create(&self, alert: &Alert) -> DbResult<()> {
+        let drone_id = alert.drone_id.as_ref().map(|d| d.as_str());
+
+        let query = r#"
+            INSERT INTO alerts (
+                alert_id, created_at, severity, alert_type, message,
+                drone_id, acknowledged, resolved
+            ) VALUES (?, ?, ?, ?, ?, ?, false, false)
+        "#;
+
+        // The alert's own time, which acknowledgements address the row by
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    alert.id,
+                    CqlTimestamp(alert.created_at.timestamp_millis()),
+                    format!("{:?}", alert.severity),
+                    format!("{:?}", alert.alert_type),
+                    alert.message.as_str(),
+                    drone_id.unwrap_or(""),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn acknowledge(
+        &self,
+        drone_id: &DroneId,
+        alert_id: uuid::Uuid,
+        created_at: DateTime<Utc>,
+        by: &str,
+        at: DateTime<Utc>,
+    ) -> DbResult<()> {
+        let query = r#"
+            UPDATE alerts SET acknowledged = true, acknowledged_by = ?, acknowledged_at = ?
+            WHERE drone_id = ? AND created_at = ? AND alert_id = ?
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    by,
+                    CqlTimestamp(at.timestamp_millis()),
+                    drone_id.as_str(),
+                    CqlTimestamp(created_at.timestamp_millis()),
+                    alert_id,
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn stream_range(
+        &self,
+        drone_id: &DroneId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<AlertRecord>> {
+        let query = r#"
+            SELECT alert_id, created_at, severity, alert_type, message,
+                   drone_id, acknowledged_at
+            FROM alerts
+            WHERE drone_id = ? AND created_at >= ? AND created_at <= ?
+            ORDER BY created_at ASC
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(
+                query,
+                (
+                    drone_id.as_str(),
+                    CqlTimestamp(from.timestamp_millis()),
+                    CqlTimestamp(to.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<AlertRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(rows.map(|row| {
+            row.map(AlertRecord::from)
+                .map_err(|e| DbError::Serialization(e.to_string()))
+        })
+        .boxed())
+    }
+
+    async fn save_rule(&self, rule: &AlertRuleRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO alert_rules (
+                id, name, condition, severity, message, enabled, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    rule.id,
+                    rule.name.as_str(),
+                    rule.condition.as_str(),
+                    rule.severity.as_str(),
+                    rule.message.as_deref(),
+                    rule.enabled,
+                    CqlTimestamp(rule.created_at.timestamp_millis()),
+                    CqlTimestamp(rule.updated_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_rule(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.session
+            .query_unpaged("DELETE FROM alert_rules WHERE id = ?", (id,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn rules(&self) -> DbResult<Vec<AlertRuleRecord>> {
+        let query = r#"
+            SELECT id, name, condition, severity, message, enabled, created_at, updated_at
+            FROM alert_rules
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<AlertRuleRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut rules: Vec<AlertRuleRecord> = rows
+            .map_ok(AlertRuleRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        rules.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(rules)
+    }
+}
+
+/// Repository for telemetry gaps
+#[derive(Clone)]
+pub struct DataQualityRepository {
+    session: Arc<Session>,
+}
+
+impl DataQualityRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl DataQualityStore for DataQualityRepository {
+    async fn record_gap(&self, gap: &TelemetryGapRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO telemetry_gaps (
+                mission_id, gap_start, drone_id, gap_end, duration_ms
+            ) VALUES (?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    gap.mission_id,
+                    CqlTimestamp(gap.gap_start.timestamp_millis()),
+                    gap.drone_id.as_str(),
+                    CqlTimestamp(gap.gap_end.timestamp_millis()),
+                    gap.duration_ms,
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn gaps_for_mission(&self, mission_id: &MissionId) -> DbResult<Vec<TelemetryGapRecord>> {
+        let query = r#"
+            SELECT mission_id, gap_start, drone_id, gap_end, duration_ms
+            FROM telemetry_gaps
+            WHERE mission_id = ?
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(query, (mission_id.0,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<TelemetryGapRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        rows.map_ok(TelemetryGapRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await
+    }
+
+    async fn quarantine(&self, record: &DeadLetterRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO telemetry_dead_letters (
+                drone_id, quarantined_at, id, reason, payload
+            ) VALUES (?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    record.drone_id.as_str(),
+                    CqlTimestamp(record.quarantined_at.timestamp_millis()),
+                    record.id,
+                    record.reason.as_str(),
+                    record.payload.as_str(),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn dead_letters(&self, drone_id: &DroneId, limit: usize) -> DbResult<Vec<DeadLetterRecord>> {
+        let query = r#"
+            SELECT drone_id, quarantined_at, id, reason, payload
+            FROM telemetry_dead_letters
+            WHERE drone_id = ?
+            LIMIT ?
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(query, (drone_id.as_str(), limit.min(i32::MAX as usize) as i32))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<DeadLetterRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        rows.map_ok(DeadLetterRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await
+    }
+}
+
+/// Repository for push notification subscriptions
+#[derive(Clone)]
+pub struct NotificationRepository {
+    session: Arc<Session>,
+}
+
+impl NotificationRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl NotificationStore for NotificationRepository {
+    async fn save_subscription(&self, subscription: &PushSubscriptionRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO push_subscriptions (
+                id, user_id, platform, device_token, preferences, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    subscription.id,
+                    subscription.user_id.as_str(),
+                    subscription.platform.as_str(),
+                    subscription.device_token.as_str(),
+                    subscription.preferences.as_str(),
+                    CqlTimestamp(subscription.created_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_subscription(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.session
+            .query_unpaged("DELETE FROM push_subscriptions WHERE id = ?", (id,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn subscriptions(&self) -> DbResult<Vec<PushSubscriptionRecord>> {
+        let query = "SELECT id, user_id, platform, device_token, preferences, created_at FROM push_subscriptions";
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<PushSubscriptionRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut subscriptions: Vec<PushSubscriptionRecord> = rows
+            .map_ok(PushSubscriptionRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        subscriptions.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(subscriptions)
+    }
+}
+
+/// Repository for integrator-defined event schemas and events
+#[derive(Clone)]
+pub struct CustomEventRepository {
+    session: Arc<Session>,
+}
+
+impl CustomEventRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl CustomEventStore for CustomEventRepository {
+    async fn save_schema(&self, schema: &CustomEventSchemaRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO custom_event_schemas (
+                event_type, schema, description, registered_at
+            ) VALUES (?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    schema.event_type.as_str(),
+                    schema.schema.as_str(),
+                    schema.description.as_deref(),
+                    CqlTimestamp(schema.registered_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_schema(&self, event_type: &str) -> DbResult<()> {
+        self.session
+            .query_unpaged("DELETE FROM custom_event_schemas WHERE event_type = ?", (event_type,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn schemas(&self) -> DbResult<Vec<CustomEventSchemaRecord>> {
+        let query = "SELECT event_type, schema, description, registered_at FROM custom_event_schemas";
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<CustomEventSchemaRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut schemas: Vec<CustomEventSchemaRecord> = rows
+            .map_ok(CustomEventSchemaRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        schemas.sort_by(|a, b| {
+            a.registered_at
+                .cmp(&b.registered_at)
+                .then_with(|| a.event_type.cmp(&b.event_type))
+        });
+        Ok(schemas)
+    }
+
+    async fn insert_event(&self, event: &CustomEventRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO custom_events (
+                event_type, event_time, event_id, drone_id, mission_id, value
+            ) VALUES (?, ?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    event.event_type.as_str(),
+                    CqlTimestamp(event.event_time.timestamp_millis()),
+                    event.event_id,
+                    event.drone_id.as_deref(),
+                    event.mission_id,
+                    event.value.as_str(),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn stream_range(
+        &self,
+        event_type: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<CustomEventRecord>> {
+        let query = r#"
+            SELECT event_type, event_time, event_id, drone_id, mission_id, value
+            FROM custom_events
+            WHERE event_type = ? AND event_time >= ? AND event_time <= ?
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(
+                query,
+                (
+                    event_type,
+                    CqlTimestamp(from.timestamp_millis()),
+                    CqlTimestamp(to.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<CustomEventRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        Ok(rows
+            .map(|row| {
+                row.map(CustomEventRecord::from)
+                    .map_err(|e| DbError::Serialization(e.to_string()))
+            })
+            .boxed())
+    }
+}
+
+/// Repository for zones of interest and dwell statistics
+#[derive(Clone)]
+pub struct ZoneRepository {
+    session: Arc<Session>,
+}
+
+impl ZoneRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl ZoneStore for ZoneRepository {
+    async fn save_zone(&self, zone: &ZoneRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO zones (
+                id, name, vertices, max_altitude, created_at
+            ) VALUES (?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    zone.id,
+                    zone.name.as_str(),
+                    zone.vertices.as_str(),
+                    zone.max_altitude,
+                    CqlTimestamp(zone.created_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_zone(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.session
+            .query_unpaged("DELETE FROM zones WHERE id = ?", (id,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        self.session
+            .query_unpaged("DELETE FROM zone_dwell WHERE zone_id = ?", (id,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn zones(&self) -> DbResult<Vec<ZoneRecord>> {
+        let query = "SELECT id, name, vertices, max_altitude, created_at FROM zones";
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<ZoneRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut zones: Vec<ZoneRecord> = rows
+            .map_ok(ZoneRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        zones.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(zones)
+    }
+
+    async fn save_dwell(&self, dwell: &ZoneDwellRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO zone_dwell (
+                zone_id, mission_id, drone_id, visits, time_inside_ms,
+                first_entry, last_entry, last_exit
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#;
+
+        let timestamp = |ts: Option<DateTime<Utc>>| ts.map(|ts| CqlTimestamp(ts.timestamp_millis()));
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    dwell.zone_id,
+                    dwell.mission_id,
+                    dwell.drone_id.as_str(),
+                    dwell.visits,
+                    dwell.time_inside_ms,
+                    timestamp(dwell.first_entry),
+                    timestamp(dwell.last_entry),
+                    timestamp(dwell.last_exit),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn dwell_for_zone(&self, zone_id: uuid::Uuid, mission_id: &MissionId) -> DbResult<Vec<ZoneDwellRecord>> {
+        let query = r#"
+            SELECT zone_id, mission_id, drone_id, visits, time_inside_ms,
+                   first_entry, last_entry, last_exit
+            FROM zone_dwell
+            WHERE zone_id = ? AND mission_id = ?
+        "#;
+
+        let rows = self
+            .session
+            .query_iter(query, (zone_id, mission_id.0))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<ZoneDwellRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        rows.map_ok(ZoneDwellRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await
+    }
+}
+
+/// Repository for scheduled commands
+#[derive(Clone)]
+pub struct ScheduleRepository {
+    session: Arc<Session>,
+}
+
+impl ScheduleRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+}
+
+#[async_trait]
+impl ScheduleStore for ScheduleRepository {
+    async fn save_scheduled_command(&self, command: &ScheduledCommandRecord) -> DbResult<()> {
+        let query = r#"
+            INSERT INTO scheduled_commands (
+                id, state, payload, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?)
+        "#;
+
+        self.session
+            .query_unpaged(
+                query,
+                (
+                    command.id,
+                    command.state.as_str(),
+                    command.payload.as_str(),
+                    CqlTimestamp(command.created_at.timestamp_millis()),
+                    CqlTimestamp(command.updated_at.timestamp_millis()),
+                ),
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn scheduled_commands(&self) -> DbResult<Vec<ScheduledCommandRecord>> {
+        let query = "SELECT id, state, payload, created_at, updated_at FROM scheduled_commands";
+
+        let rows = self
+            .session
+            .query_iter(query, ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<ScheduledCommandRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        let mut commands: Vec<ScheduledCommandRecord> = rows
+            .map_ok(ScheduledCommandRecord::from)
+            .map_err(|e| DbError::Serialization(e.to_string()))
+            .try_collect()
+            .await?;
+        commands.sort_by_key(|c| c.created_at);
+        Ok(commands)
+    }
+}
+
+/// Leases on ScyllaDB: lightweight transactions at the mission write and
+/// serial consistency, so two instances never both see themselves holding it
+#[derive(Clone)]
+pub struct LeaseRepository {
+    session: Arc<Session>,
+    consistency: ConsistencyConfig,
+}
+
+impl LeaseRepository {
+    pub fn new(session: Arc<Session>, consistency: ConsistencyConfig) -> Self {
+        Self { session, consistency }
+    }
+
+    /// Run a conditional statement and report whether it applied
+    async fn conditional(
+        &self,
+        query: &str,
+        values: impl scylla::serialize::row::SerializeRow,
+    ) -> DbResult<bool> {
+        let result = self
+            .session
+            .query_unpaged(
+                consistency::conditional_statement(
+                    query,
+                    self.consistency.mission_write,
+                    self.consistency.mission_serial,
+                ),
+                values,
+            )
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+
+        let rows = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        let applied = rows
+            .maybe_first_row::<scylla::frame::response::result::Row>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+            .and_then(|row| row.columns.into_iter().next().flatten())
+            .and_then(|value| value.as_boolean())
+            .unwrap_or(false);
+
+        Ok(applied)
+    }
+}
+
+#[async_trait]
+impl LeaseStore for LeaseRepository {
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        ttl: Duration,
+        grace: Duration,
+    ) -> DbResult<LeaseRecord> {
+        let now_ts = CqlTimestamp(now.timestamp_millis());
+        let expires = CqlTimestamp((now + chrono::Duration::from_std(ttl).unwrap_or_default()).timestamp_millis());
+        let lapsed_before = CqlTimestamp((now - chrono::Duration::from_std(grace).unwrap_or_default()).timestamp_millis());
+
+        // Renew our own lease, else take over an expired one, else create it
+        let renewed = self
+            .conditional(
+                "UPDATE leases SET expires_at = ? WHERE name = ? IF holder = ?",
+                (expires, name, holder),
+            )
+            .await?;
+        if !renewed {
+            let taken = self
+                .conditional(
+                    "UPDATE leases SET holder = ?, acquired_at = ?, expires_at = ? WHERE name = ? IF expires_at < ?",
+                    (holder, now_ts, expires, name, lapsed_before),
+                )
+                .await?;
+            if !taken {
+                self.conditional(
+                    "INSERT INTO leases (name, holder, acquired_at, expires_at) VALUES (?, ?, ?, ?) IF NOT EXISTS",
+                    (name, holder, now_ts, expires),
+                )
+                .await?;
+            }
+        }
+
+        let query = "SELECT name, holder, acquired_at, expires_at FROM leases WHERE name = ?";
+        let result = self
+            .session
+            .query_unpaged(consistency::statement(query, self.consistency.mission_read), (name,))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        let rows = result
+            .into_rows_result()
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        rows.maybe_first_row::<LeaseRow>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?
+            .map(LeaseRecord::from)
+            .ok_or_else(|| DbError::NotFound(format!("lease {}", name)))
+    }
+
+    async fn release(&self, name: &str, holder: &str) -> DbResult<()> {
+        self.conditional("DELETE FROM leases WHERE name = ? IF holder = ?", (name, holder))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Retention enforcement on ScyllaDB: table TTLs, plus a scan-and-delete
+/// purge for settled scheduled commands
+#[derive(Clone)]
+pub struct RetentionRepository {
+    session: Arc<Session>,
+}
+
+impl RetentionRepository {
+    pub fn new(session: Arc<Session>) -> Self {
+        Self { session }
+    }
+
+    /// IDs of settled scheduled commands last updated before `cutoff`
+    async fn expired_commands(&self, cutoff: DateTime<Utc>) -> DbResult<Vec<uuid::Uuid>> {
+        let rows = self
+            .session
+            .query_iter("SELECT id, state, updated_at FROM scheduled_commands", ())
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+            .rows_stream::<(uuid::Uuid, String, CqlTimestamp)>()
+            .map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        rows.map_err(|e| DbError::Serialization(e.to_string()))
+            .try_filter_map(|(id, state, updated_at)| async move {
+                let expired = state != "pending" && updated_at.0 < cutoff.timestamp_millis();
+                Ok(expired.then_some(id))
+            })
+            .try_collect()
+            .await
+    }
+}
+
+#[async_trait]
+impl RetentionStore for RetentionRepository {
+    fn ttl_supported(&self, table: RetentionTable) -> bool {
+        table.supports_ttl()
+    }
+
+    async fn set_table_ttl(&self, table: RetentionTable, ttl: Duration) -> DbResult<()> {
+        if !table.supports_ttl() {
+            return Err(DbError::Configuration(format!(
+                "{} cannot expire rows by TTL",
+                table.table_name()
+            )));
+        }
+        let query = format!(
+            "ALTER TABLE {} WITH default_time_to_live = {}",
+            table.table_name(),
+            ttl.as_secs()
+        );
+        self.session
+            .query_unpaged(query, &[])
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn count_expired(&self, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64> {
+        match table {
+            RetentionTable::ScheduledCommands => Ok(self.expired_commands(cutoff).await?.len() as u64),
+            _ => Err(DbError::Configuration(format!(
+                "{} expires rows by TTL",
+                table.table_name()
+            ))),
+        }
+    }
+
+    async fn purge_expired(&self, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64> {
+        let RetentionTable::ScheduledCommands = table else {
+            return Err(DbError::Configuration(format!(
+                "{} expires rows by TTL",
+                table.table_name()
+            )));
+        };
+
+        let expired = self.expired_commands(cutoff).await?;
+        for id in &expired {
+            self.session
+                .query_unpaged("DELETE FROM scheduled_commands WHERE id = ?", (id,))
+                .await
+                .map_err(|e| DbError::Query(e.to_string()))?;
+        }
+        Ok(expired.len() as u64)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_db_config_default() {
+        let config = DbConfig::default();
+        assert_eq!(config.hosts.len(), 1);
         assert_eq!(config.keyspace, "drone_convoy");
     }
 
+    #[test]
+    fn test_db_config_for_tenant() {
+        let config = DbConfig {
+            sqlite_path: PathBuf::from("/var/lib/drones/convoy.db"),
+            ..DbConfig::default()
+        };
+        let tenant = config.for_tenant(&TenantId::parse("acme").unwrap());
+        assert_eq!(tenant.keyspace, "drone_convoy_acme");
+        assert_eq!(tenant.sqlite_path, PathBuf::from("/var/lib/drones/convoy_acme.db"));
+        assert_eq!(tenant.hosts, config.hosts);
+    }
+
+    #[test]
+    fn test_telemetry_record_from_row() {
+        let row: TelemetryRow = (
+            "REAPER-01".to_string(),
+            CqlTimestamp(1_700_000_000_000),
+            Some(34.5),
+            Some(69.2),
+            Some(3000.0),
+            Some(90.0),
+            Some(120.0),
+            Some(80),
+            None,
+            Some(100),
+            Some("MOVING".to_string()),
+            Some(false),
+            None,
+            Some(95),
+            None,
+            Some((10.0, -30.0, 4.0)),
+        );
+        let record = TelemetryRecord::from(row);
+        assert_eq!(record.timestamp.timestamp_millis(), 1_700_000_000_000);
+        assert_eq!(record.fuel_level, 0);
+        assert_eq!(record.status.as_deref(), Some("MOVING"));
+        let (_, telemetry) = record.position_telemetry();
+        assert_eq!(telemetry.gimbal, Some(GimbalState { pan: 10.0, tilt: -30.0, zoom: 4.0 }));
+    }
+
+    #[test]
+    fn test_threshold_overrides_encoding() {
+        assert_eq!(encode_overrides(&ThresholdOverrides::default()).unwrap(), None);
+
+        let overrides = ThresholdOverrides {
+            fuel_warning: Some(40),
+            ..Default::default()
+        };
+        let encoded = encode_overrides(&overrides).unwrap().unwrap();
+        assert_eq!(decode_overrides(&encoded).unwrap(), overrides);
+    }
+
     #[test]
     fn test_db_config_docker() {
         let config = DbConfig::docker();
@@ -1,6 +1,6 @@
 //! Database migrations
 
-use crate::{DbError, DbResult};
+use crate::DbResult;
 use scylla::Session;
 use std::sync::Arc;
 use tracing::info;
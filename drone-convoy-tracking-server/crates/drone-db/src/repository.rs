@@ -1,6 +1,355 @@
-//! Repository re-exports
-//! 
-//! All repositories are defined in lib.rs for simplicity.
-//! This module exists for potential future expansion.
+//! Storage traits
+//!
+//! Each backend (ScyllaDB in lib.rs, SQLite in sqlite.rs) implements these
+//! traits; `DbClient` hands them out as trait objects so callers don't care
+//! which backend is configured.
 
-// Re-exports from lib.rs are sufficient
+use crate::retention::RetentionTable;
+use crate::{
+    AlertRecord, AlertRuleRecord, CustomEventRecord, CustomEventSchemaRecord, CvTuningRecord, DbResult, DeadLetterRecord, DroneGroupRecord, DroneSnapshotRecord, HandoffOwnerRecord, LeaseRecord, ScheduledCommandRecord, TelemetryGapRecord, TelemetryRecord, WaypointEventRecord,
+    PushSubscriptionRecord, WaypointAttachmentRecord, ZoneDwellRecord, ZoneRecord,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use drone_core::{
+    Alert, Drone, DroneId, DroneMarking, DroneType, GeoPosition, Mission, MissionId, Telemetry, TelemetrySource,
+    ThresholdOverrides, TrackingResult, TransportBinding, WaypointId,
+};
+use futures::stream::BoxStream;
+
+/// Stream of rows returned by range queries
+pub type RecordStream<T> = BoxStream<'static, DbResult<T>>;
+
+/// Drone telemetry storage
+#[async_trait]
+pub trait TelemetryStore: Send + Sync {
+    async fn insert(
+        &self,
+        drone_id: &DroneId,
+        position: &GeoPosition,
+        telemetry: &Telemetry,
+        mission_id: Option<&MissionId>,
+    ) -> DbResult<()>;
+
+    /// Write complete telemetry rows (e.g. historical imports); a row with
+    /// the same drone and timestamp as a stored one replaces it
+    async fn insert_records(&self, records: Vec<TelemetryRecord>) -> DbResult<()>;
+
+    async fn get_latest(&self, drone_id: &DroneId) -> DbResult<Option<(GeoPosition, Telemetry)>>;
+
+    /// Most recent telemetry for a drone, newest first
+    async fn get_history(
+        &self,
+        drone_id: &DroneId,
+        limit: i32,
+    ) -> DbResult<Vec<(GeoPosition, Telemetry)>>;
+
+    /// Stream raw telemetry rows for a drone within `[from, to]`, oldest first
+    async fn stream_range(
+        &self,
+        drone_id: &DroneId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<TelemetryRecord>>;
+
+    /// The drone's last row at or before `at`, looking back no further
+    /// than `since`; reads that one row, not the range
+    async fn get_at(
+        &self,
+        drone_id: &DroneId,
+        since: DateTime<Utc>,
+        at: DateTime<Utc>,
+    ) -> DbResult<Option<TelemetryRecord>>;
+}
+
+/// Waypoint event storage
+#[async_trait]
+pub trait WaypointStore: Send + Sync {
+    async fn record_reached(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+    ) -> DbResult<()>;
+
+    /// Record a waypoint passed over by a go-direct command, with the
+    /// drone's position when the command was accepted
+    async fn record_skipped(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+    ) -> DbResult<()>;
+
+    /// Stream waypoint events for a mission within `[from, to]`, oldest first
+    async fn stream_range(
+        &self,
+        mission_id: &MissionId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<WaypointEventRecord>>;
+
+    /// Insert or replace a waypoint attachment's metadata
+    async fn save_attachment(&self, attachment: &WaypointAttachmentRecord) -> DbResult<()>;
+
+    async fn delete_attachment(&self, id: uuid::Uuid) -> DbResult<()>;
+
+    /// All waypoint attachments, oldest first
+    async fn attachments(&self) -> DbResult<Vec<WaypointAttachmentRecord>>;
+}
+
+/// CV tracking result storage
+#[async_trait]
+pub trait TrackingStore: Send + Sync {
+    async fn insert(&self, result: &TrackingResult) -> DbResult<()>;
+
+    async fn insert_batch(&self, results: &[TrackingResult]) -> DbResult<()> {
+        for result in results {
+            self.insert(result).await?;
+        }
+        Ok(())
+    }
+
+    /// Stream results with frame timestamps within `[from, to]`, oldest first
+    async fn stream_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> DbResult<RecordStream<TrackingResult>>;
+
+    /// Replace the saved live CV parameters
+    async fn save_cv_tuning(&self, tuning: &CvTuningRecord) -> DbResult<()>;
+
+    /// Last saved live CV parameters
+    async fn cv_tuning(&self) -> DbResult<Option<CvTuningRecord>>;
+}
+
+/// Mission storage
+#[async_trait]
+pub trait MissionStore: Send + Sync {
+    async fn create(&self, mission: &Mission) -> DbResult<()>;
+
+    async fn update_status(&self, mission_id: &MissionId, status: &str) -> DbResult<()>;
+
+    /// Set the status only if it is currently `expected` (compare-and-set)
+    ///
+    /// Returns false when another writer changed the status first.
+    async fn transition_status(
+        &self,
+        mission_id: &MissionId,
+        expected: &str,
+        status: &str,
+    ) -> DbResult<bool>;
+
+    async fn get(&self, mission_id: &MissionId) -> DbResult<Option<Mission>>;
+}
+
+/// Drone registry storage
+#[async_trait]
+pub trait DroneStore: Send + Sync {
+    async fn register(&self, drone: &Drone) -> DbResult<()>;
+
+    async fn get_all(&self) -> DbResult<Vec<DroneId>>;
+
+    /// Store (or clear, when empty) a drone's alert threshold overrides
+    async fn set_alert_thresholds(
+        &self,
+        drone_id: &DroneId,
+        overrides: &ThresholdOverrides,
+    ) -> DbResult<()>;
+
+    /// Load all per-drone alert threshold overrides
+    async fn get_alert_thresholds(&self) -> DbResult<Vec<(DroneId, ThresholdOverrides)>>;
+
+    /// Store (or clear, when empty) alert threshold overrides for a drone type
+    async fn set_type_alert_thresholds(
+        &self,
+        drone_type: &DroneType,
+        overrides: &ThresholdOverrides,
+    ) -> DbResult<()>;
+
+    /// Load all per-type alert threshold overrides
+    async fn get_type_alert_thresholds(&self) -> DbResult<Vec<(DroneType, ThresholdOverrides)>>;
+
+    /// Store (or clear, with `None`) a drone's command transport binding
+    async fn set_transport_binding(
+        &self,
+        drone_id: &DroneId,
+        binding: Option<&TransportBinding>,
+    ) -> DbResult<()>;
+
+    /// Load all command transport bindings
+    async fn get_transport_bindings(&self) -> DbResult<Vec<(DroneId, TransportBinding)>>;
+
+    /// Store (or clear, with `None`) a drone's designated telemetry source
+    async fn set_telemetry_source(&self, drone_id: &DroneId, source: Option<TelemetrySource>) -> DbResult<()>;
+
+    /// Load all telemetry source designations
+    async fn get_telemetry_sources(&self) -> DbResult<Vec<(DroneId, TelemetrySource)>>;
+
+    /// Store (or clear, with `None`) a drone's halo color and icon
+    async fn set_drone_marking(&self, drone_id: &DroneId, marking: Option<&DroneMarking>) -> DbResult<()>;
+
+    /// Load all drone markings
+    async fn get_drone_markings(&self) -> DbResult<Vec<(DroneId, DroneMarking)>>;
+
+    /// Record (or clear, with `None`) the station a drone was handed off to
+    async fn set_handoff_owner(&self, drone_id: &DroneId, owner: Option<&HandoffOwnerRecord>) -> DbResult<()>;
+
+    /// Load the owners of all drones handed off from here
+    async fn get_handoff_owners(&self) -> DbResult<Vec<(DroneId, HandoffOwnerRecord)>>;
+
+    /// Insert or replace a drone group
+    async fn save_group(&self, group: &DroneGroupRecord) -> DbResult<()>;
+
+    async fn delete_group(&self, name: &str) -> DbResult<()>;
+
+    /// All drone groups, by name
+    async fn groups(&self) -> DbResult<Vec<DroneGroupRecord>>;
+
+    /// Store the final state of a drone dropped from tracking, replacing
+    /// any earlier snapshot of it
+    async fn save_snapshot(&self, snapshot: &DroneSnapshotRecord) -> DbResult<()>;
+
+    async fn snapshot(&self, drone_id: &DroneId) -> DbResult<Option<DroneSnapshotRecord>>;
+}
+
+/// Telemetry data quality storage
+#[async_trait]
+pub trait DataQualityStore: Send + Sync {
+    async fn record_gap(&self, gap: &TelemetryGapRecord) -> DbResult<()>;
+
+    /// All gaps recorded for a mission, oldest first
+    async fn gaps_for_mission(&self, mission_id: &MissionId) -> DbResult<Vec<TelemetryGapRecord>>;
+
+    /// Keep a telemetry row that could not or should not be written
+    async fn quarantine(&self, record: &DeadLetterRecord) -> DbResult<()>;
+
+    /// A drone's quarantined rows, newest first
+    async fn dead_letters(&self, drone_id: &DroneId, limit: usize) -> DbResult<Vec<DeadLetterRecord>>;
+}
+
+/// Scheduled command storage
+#[async_trait]
+pub trait ScheduleStore: Send + Sync {
+    /// Insert or replace a scheduled command
+    async fn save_scheduled_command(&self, command: &ScheduledCommandRecord) -> DbResult<()>;
+
+    /// All scheduled commands, oldest first
+    async fn scheduled_commands(&self) -> DbResult<Vec<ScheduledCommandRecord>>;
+}
+
+/// Zones of interest and their per-mission dwell statistics
+#[async_trait]
+pub trait ZoneStore: Send + Sync {
+    async fn save_zone(&self, zone: &ZoneRecord) -> DbResult<()>;
+
+    async fn delete_zone(&self, id: uuid::Uuid) -> DbResult<()>;
+
+    async fn zones(&self) -> DbResult<Vec<ZoneRecord>>;
+
+    /// Insert or replace a drone's dwell statistics for a zone and mission
+    async fn save_dwell(&self, dwell: &ZoneDwellRecord) -> DbResult<()>;
+
+    /// Dwell statistics for a zone during a mission, by drone ID
+    async fn dwell_for_zone(&self, zone_id: uuid::Uuid, mission_id: &MissionId) -> DbResult<Vec<ZoneDwellRecord>>;
+}
+
+/// Push notification subscriptions
+#[async_trait]
+pub trait NotificationStore: Send + Sync {
+    /// Insert or replace a subscription
+    async fn save_subscription(&self, subscription: &PushSubscriptionRecord) -> DbResult<()>;
+
+    async fn delete_subscription(&self, id: uuid::Uuid) -> DbResult<()>;
+
+    async fn subscriptions(&self) -> DbResult<Vec<PushSubscriptionRecord>>;
+}
+
+/// Integrator-defined event schemas and the events published under them
+#[async_trait]
+pub trait CustomEventStore: Send + Sync {
+    /// Insert or replace the schema for an event type
+    async fn save_schema(&self, schema: &CustomEventSchemaRecord) -> DbResult<()>;
+
+    async fn delete_schema(&self, event_type: &str) -> DbResult<()>;
+
+    /// All registered schemas, oldest first
+    async fn schemas(&self) -> DbResult<Vec<CustomEventSchemaRecord>>;
+
+    async fn insert_event(&self, event: &CustomEventRecord) -> DbResult<()>;
+
+    /// Stream events of one type within `[from, to]`, oldest first
+    async fn stream_range(
+        &self,
+        event_type: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<CustomEventRecord>>;
+}
+
+/// Named, time-limited leases used to elect one leader among API instances
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    /// Take or renew the lease `name` for `holder` until `now + ttl`.
+    /// Succeeds when the lease is free, already held by `holder`, or expired
+    /// for longer than `grace`, the clock skew allowed between holders;
+    /// either way returns the lease as it stands afterwards
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        ttl: std::time::Duration,
+        grace: std::time::Duration,
+    ) -> DbResult<LeaseRecord>;
+
+    /// Give the lease up, if `holder` still holds it
+    async fn release(&self, name: &str, holder: &str) -> DbResult<()>;
+}
+
+/// Retention enforcement
+#[async_trait]
+pub trait RetentionStore: Send + Sync {
+    /// Whether `table` expires rows through a table TTL on this backend
+    fn ttl_supported(&self, table: RetentionTable) -> bool;
+
+    /// Set the default TTL for new rows in `table`
+    async fn set_table_ttl(&self, table: RetentionTable, ttl: std::time::Duration) -> DbResult<()>;
+
+    /// Count rows older than `cutoff`
+    async fn count_expired(&self, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64>;
+
+    /// Delete rows older than `cutoff`, returning how many were deleted
+    async fn purge_expired(&self, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64>;
+}
+
+/// Alert storage
+#[async_trait]
+pub trait AlertStore: Send + Sync {
+    async fn create(&self, alert: &Alert) -> DbResult<()>;
+
+    /// Record that `by` acknowledged the alert raised at `created_at`
+    async fn acknowledge(
+        &self,
+        drone_id: &DroneId,
+        alert_id: uuid::Uuid,
+        created_at: DateTime<Utc>,
+        by: &str,
+        at: DateTime<Utc>,
+    ) -> DbResult<()>;
+
+    /// Stream alerts raised for a drone within `[from, to]`, oldest first
+    async fn stream_range(
+        &self,
+        drone_id: &DroneId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<AlertRecord>>;
+
+    /// Insert or replace a conditional alert rule
+    async fn save_rule(&self, rule: &AlertRuleRecord) -> DbResult<()>;
+
+    async fn delete_rule(&self, id: uuid::Uuid) -> DbResult<()>;
+
+    /// All alert rules, oldest first
+    async fn rules(&self) -> DbResult<Vec<AlertRuleRecord>>;
+}
@@ -0,0 +1,443 @@
+//! Data retention
+//!
+//! Each time-series table has a retention period. On ScyllaDB the period is
+//! applied as the table's `default_time_to_live`, so new rows expire on
+//! their own; tables that cannot use a TTL (and every table on SQLite) are
+//! purged periodically instead. A dry run counts what a purge would delete
+//! without deleting it.
+
+use crate::{DbClient, DbError, DbResult};
+
+use chrono::{DateTime, Utc};
+use drone_core::SimulationClock;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Table with a retention policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionTable {
+    Telemetry,
+    CvTracking,
+    Alerts,
+    WaypointEvents,
+    TelemetryGaps,
+    /// Fired and cancelled commands only; pending ones are never purged
+    ScheduledCommands,
+    DeadLetters,
+    CustomEvents,
+}
+
+impl RetentionTable {
+    pub const ALL: [RetentionTable; 8] = [
+        Self::Telemetry,
+        Self::CvTracking,
+        Self::Alerts,
+        Self::WaypointEvents,
+        Self::TelemetryGaps,
+        Self::ScheduledCommands,
+        Self::DeadLetters,
+        Self::CustomEvents,
+    ];
+
+    /// Table name (same on both backends)
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            Self::Telemetry => "drone_telemetry",
+            Self::CvTracking => "cv_tracking",
+            Self::Alerts => "alerts",
+            Self::WaypointEvents => "waypoint_events",
+            Self::TelemetryGaps => "telemetry_gaps",
+            Self::ScheduledCommands => "scheduled_commands",
+            Self::DeadLetters => "telemetry_dead_letters",
+            Self::CustomEvents => "custom_events",
+        }
+    }
+
+    /// Column a row's age is measured from
+    pub fn time_column(&self) -> &'static str {
+        match self {
+            Self::Telemetry => "timestamp",
+            Self::CvTracking => "frame_timestamp",
+            Self::Alerts => "created_at",
+            Self::WaypointEvents => "event_time",
+            Self::TelemetryGaps => "gap_end",
+            Self::ScheduledCommands => "updated_at",
+            Self::DeadLetters => "quarantined_at",
+            Self::CustomEvents => "event_time",
+        }
+    }
+
+    /// Whether rows can expire through a table TTL; pending scheduled
+    /// commands must outlive any retention period
+    pub fn supports_ttl(&self) -> bool {
+        !matches!(self, Self::ScheduledCommands)
+    }
+
+    fn env_key(&self) -> String {
+        format!("RETENTION_{}_DAYS", self.table_name().to_ascii_uppercase())
+    }
+}
+
+/// Retention period for one table
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub table: RetentionTable,
+    pub retention: Duration,
+}
+
+/// Retention configuration
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    pub policies: Vec<RetentionPolicy>,
+    /// Time between purge runs
+    pub purge_interval: Duration,
+    /// Count expired rows instead of deleting them
+    pub dry_run: bool,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        let days = |table: RetentionTable, days: u64| RetentionPolicy {
+            table,
+            retention: Duration::from_secs(days * DAY),
+        };
+        Self {
+            policies: vec![
+                days(RetentionTable::Telemetry, 30),
+                days(RetentionTable::CvTracking, 7),
+                days(RetentionTable::Alerts, 365),
+                days(RetentionTable::WaypointEvents, 30),
+                days(RetentionTable::TelemetryGaps, 30),
+                days(RetentionTable::ScheduledCommands, 30),
+                days(RetentionTable::DeadLetters, 30),
+                days(RetentionTable::CustomEvents, 30),
+            ],
+            purge_interval: Duration::from_secs(60 * 60),
+            dry_run: false,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Defaults overridden by `RETENTION_<TABLE>_DAYS`,
+    /// `RETENTION_PURGE_INTERVAL_SECS` and `RETENTION_DRY_RUN`
+    pub fn from_env() -> DbResult<Self> {
+        let mut config = Self::default();
+
+        for policy in &mut config.policies {
+            let key = policy.table.env_key();
+            if let Ok(value) = std::env::var(&key) {
+                let days: u64 = value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|d| *d > 0)
+                    .ok_or_else(|| DbError::Configuration(format!("{} must be a positive number of days", key)))?;
+                policy.retention = Duration::from_secs(days * DAY);
+            }
+        }
+        if let Ok(value) = std::env::var("RETENTION_PURGE_INTERVAL_SECS") {
+            let secs: u64 = value.trim().parse().ok().filter(|s| *s > 0).ok_or_else(|| {
+                DbError::Configuration("RETENTION_PURGE_INTERVAL_SECS must be a positive number".into())
+            })?;
+            config.purge_interval = Duration::from_secs(secs);
+        }
+        if let Ok(value) = std::env::var("RETENTION_DRY_RUN") {
+            config.dry_run = matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes");
+        }
+
+        Ok(config)
+    }
+
+    pub fn policy(&self, table: RetentionTable) -> Option<&RetentionPolicy> {
+        self.policies.iter().find(|p| p.table == table)
+    }
+}
+
+/// How a table's retention is enforced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    /// Rows expire through the table TTL
+    Ttl,
+    /// Rows are deleted by the purge job
+    Purge,
+}
+
+/// Purge result for one table
+#[derive(Debug, Clone, Serialize)]
+pub struct TablePurge {
+    pub table: RetentionTable,
+    pub retention_days: f64,
+    pub enforcement: Enforcement,
+    /// Rows older than this are expired
+    pub cutoff: DateTime<Utc>,
+    /// Rows deleted, or rows that would be deleted in a dry run
+    pub rows: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of one purge run
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeReport {
+    pub run_at: DateTime<Utc>,
+    pub dry_run: bool,
+    pub tables: Vec<TablePurge>,
+}
+
+/// Applies table TTLs and runs purge jobs
+pub struct RetentionManager {
+    db: Arc<DbClient>,
+    config: RetentionConfig,
+    /// Rows purged since startup, indexed like `RetentionTable::ALL`
+    purged: [AtomicU64; 8],
+    last_report: RwLock<Option<PurgeReport>>,
+}
+
+impl RetentionManager {
+    pub fn new(db: Arc<DbClient>, config: RetentionConfig) -> Self {
+        Self {
+            db,
+            config,
+            purged: Default::default(),
+            last_report: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &RetentionConfig {
+        &self.config
+    }
+
+    /// How retention is enforced for a table on the configured backend
+    pub fn enforcement(&self, table: RetentionTable) -> Enforcement {
+        if self.db.retention().ttl_supported(table) {
+            Enforcement::Ttl
+        } else {
+            Enforcement::Purge
+        }
+    }
+
+    /// Set the TTL of every table whose retention is enforced by TTL
+    pub async fn apply_ttls(&self) -> DbResult<()> {
+        for policy in &self.config.policies {
+            if self.enforcement(policy.table) == Enforcement::Ttl {
+                self.db.retention().set_table_ttl(policy.table, policy.retention).await?;
+                info!(
+                    "Retention for {}: {} day TTL",
+                    policy.table.table_name(),
+                    policy.retention.as_secs() / DAY
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Run every purge job once; `dry_run` only counts expired rows
+    pub async fn run_once(&self, now: DateTime<Utc>, dry_run: bool) -> PurgeReport {
+        let mut tables = Vec::with_capacity(self.config.policies.len());
+
+        for policy in &self.config.policies {
+            let table = policy.table;
+            let cutoff = now - chrono::Duration::from_std(policy.retention).unwrap_or_default();
+            let enforcement = self.enforcement(table);
+            let result = match (enforcement, dry_run) {
+                (Enforcement::Ttl, _) => Ok(0),
+                (Enforcement::Purge, true) => self.db.retention().count_expired(table, cutoff).await,
+                (Enforcement::Purge, false) => self.db.retention().purge_expired(table, cutoff).await,
+            };
+
+            let (rows, error) = match result {
+                Ok(rows) => (rows, None),
+                Err(e) => {
+                    warn!("Retention purge of {} failed: {}", table.table_name(), e);
+                    (0, Some(e.to_string()))
+                }
+            };
+            if !dry_run && rows > 0 {
+                self.purged[table_index(table)].fetch_add(rows, Ordering::Relaxed);
+                info!("Purged {} expired rows from {}", rows, table.table_name());
+            }
+
+            tables.push(TablePurge {
+                table,
+                retention_days: policy.retention.as_secs_f64() / DAY as f64,
+                enforcement,
+                cutoff,
+                rows,
+                error,
+            });
+        }
+
+        let report = PurgeReport {
+            run_at: now,
+            dry_run,
+            tables,
+        };
+        *self.last_report.write() = Some(report.clone());
+        report
+    }
+
+    /// Most recent purge report
+    pub fn last_report(&self) -> Option<PurgeReport> {
+        self.last_report.read().clone()
+    }
+
+    /// Rows purged from a table since startup
+    pub fn purged_total(&self, table: RetentionTable) -> u64 {
+        self.purged[table_index(table)].load(Ordering::Relaxed)
+    }
+
+    /// Apply TTLs, then purge on the configured interval, aging rows
+    /// against `clock`
+    pub fn spawn(self: &Arc<Self>, clock: Arc<SimulationClock>) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = manager.apply_ttls().await {
+                warn!("Failed to apply retention TTLs: {}", e);
+            }
+            let mut interval = tokio::time::interval(manager.config.purge_interval);
+            loop {
+                interval.tick().await;
+                manager.run_once(clock.now(), manager.config.dry_run).await;
+            }
+        })
+    }
+}
+
+fn table_index(table: RetentionTable) -> usize {
+    RetentionTable::ALL.iter().position(|t| *t == table).unwrap_or_default()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CustomEventRecord, DbConfig, DeadLetterRecord, ScheduledCommandRecord, SqliteStore, TelemetryRecord};
+    use drone_core::{Alert, AlertSeverity, AlertType, DroneStatus, GeoPosition, Telemetry};
+
+    #[tokio::test]
+    async fn test_dry_run_then_purge() {
+        let db = Arc::new(DbClient::from_sqlite(
+            SqliteStore::open_in_memory().unwrap(),
+            DbConfig::default(),
+        ));
+        let now = Utc::now();
+
+        let drone_id = drone_core::DroneId::new("REAPER-01");
+        let mut old_alert = Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "Battery low")
+            .for_drone(drone_id.clone());
+        old_alert.created_at = now - chrono::Duration::days(400);
+        db.alerts().create(&old_alert).await.unwrap();
+        let recent = Alert::new(AlertSeverity::Warning, AlertType::FuelLow, "Fuel low").for_drone(drone_id);
+        db.alerts().create(&recent).await.unwrap();
+
+        let old = now - chrono::Duration::days(60);
+        let record = TelemetryRecord::new(
+            &drone_core::DroneId::new("REAPER-01"),
+            &GeoPosition::new(34.5, 69.2, 3000.0),
+            &Telemetry::default(),
+            DroneStatus::Moving,
+            true,
+            None,
+        );
+        for at in [old, now] {
+            db.quality().quarantine(&DeadLetterRecord::new(&record, "write failed", at)).await.unwrap();
+        }
+        for state in ["pending", "fired"] {
+            db.schedules()
+                .save_scheduled_command(&ScheduledCommandRecord {
+                    id: uuid::Uuid::new_v4(),
+                    state: state.into(),
+                    payload: "{}".into(),
+                    created_at: old,
+                    updated_at: old,
+                })
+                .await
+                .unwrap();
+        }
+
+        for at in [old, now] {
+            db.custom_events()
+                .insert_event(&CustomEventRecord {
+                    event_type: "acme.SENSOR_POD_STATUS".into(),
+                    event_time: at,
+                    event_id: uuid::Uuid::new_v4(),
+                    drone_id: None,
+                    mission_id: None,
+                    value: "{}".into(),
+                })
+                .await
+                .unwrap();
+        }
+
+        let manager = RetentionManager::new(db.clone(), RetentionConfig::default());
+        let rows = |report: &PurgeReport, table| {
+            report.tables.iter().find(|t| t.table == table).unwrap().rows
+        };
+
+        let dry = manager.run_once(now, true).await;
+        assert!(dry.tables.iter().all(|t| t.enforcement == Enforcement::Purge));
+        assert_eq!(rows(&dry, RetentionTable::Alerts), 1);
+        assert_eq!(rows(&dry, RetentionTable::ScheduledCommands), 1);
+        assert_eq!(rows(&dry, RetentionTable::DeadLetters), 1);
+        assert_eq!(rows(&dry, RetentionTable::CustomEvents), 1);
+        assert_eq!(manager.purged_total(RetentionTable::Alerts), 0);
+
+        let purge = manager.run_once(now, false).await;
+        assert_eq!(rows(&purge, RetentionTable::Alerts), 1);
+        assert_eq!(manager.purged_total(RetentionTable::Alerts), 1);
+        assert_eq!(manager.purged_total(RetentionTable::ScheduledCommands), 1);
+        assert_eq!(manager.purged_total(RetentionTable::DeadLetters), 1);
+        assert_eq!(manager.purged_total(RetentionTable::CustomEvents), 1);
+
+        // The pending command and the recent alert survive
+        assert_eq!(db.schedules().scheduled_commands().await.unwrap()[0].state, "pending");
+        assert_eq!(rows(&manager.run_once(now, true).await, RetentionTable::Alerts), 0);
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_purge_ages_against_clock() {
+        let db = Arc::new(DbClient::from_sqlite(
+            SqliteStore::open_in_memory().unwrap(),
+            DbConfig::default(),
+        ));
+        // A simulation running a year behind the wall clock
+        let sim_now = Utc::now() - chrono::Duration::days(365);
+        let clock = Arc::new(SimulationClock::virtual_at(sim_now));
+
+        let drone_id = drone_core::DroneId::new("REAPER-01");
+        for age in [400, 30] {
+            let mut alert = Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "Battery low")
+                .for_drone(drone_id.clone());
+            alert.created_at = sim_now - chrono::Duration::days(age);
+            db.alerts().create(&alert).await.unwrap();
+        }
+
+        let config = RetentionConfig { dry_run: true, ..Default::default() };
+        let manager = Arc::new(RetentionManager::new(db, config));
+        let task = manager.spawn(clock);
+        let report = loop {
+            if let Some(report) = manager.last_report() {
+                break report;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+        task.abort();
+
+        let alerts = report.tables.iter().find(|t| t.table == RetentionTable::Alerts).unwrap();
+        assert_eq!(report.run_at, sim_now);
+        assert_eq!(alerts.cutoff, sim_now - chrono::Duration::days(365));
+        assert_eq!(alerts.rows, 1);
+    }
+}
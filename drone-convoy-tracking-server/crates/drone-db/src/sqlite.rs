@@ -0,0 +1,2754 @@
+//! SQLite storage backend
+//!
+//! Single-file fallback for small deployments that don't want to run a
+//! ScyllaDB cluster. Implements the same storage traits as the Scylla
+//! repositories; queries run on the blocking thread pool.
+
+use crate::repository::{
+    AlertStore, CustomEventStore, DataQualityStore, DroneStore, LeaseStore, MissionStore, RecordStream, RetentionStore,
+    ScheduleStore, TelemetryStore,
+    TrackingStore, WaypointStore, ZoneStore, NotificationStore,
+};
+use crate::retention::RetentionTable;
+use crate::{
+    codec, decode_overrides, encode_overrides, parse_telemetry_source, AlertRecord, AlertRuleRecord, CustomEventRecord,
+    CustomEventSchemaRecord, CvTuningRecord, DbError, DbResult, DroneGroupRecord, DroneSnapshotRecord, HandoffOwnerRecord, LeaseRecord,
+    ScheduledCommandRecord,
+    TelemetryGapRecord, TelemetryRecord, TelemetryStorage, DeadLetterRecord,
+    WaypointAttachmentRecord, WaypointEventRecord, ZoneDwellRecord, ZoneRecord, PushSubscriptionRecord,
+};
+use drone_core::{
+    Alert, Drone, DroneId, DroneMarking, DroneType, GeoPosition, GimbalState, Mission, MissionId, MissionStatus, Telemetry,
+    TelemetrySource, ThresholdOverrides, TrackingResult, TransportBinding, WaypointId,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{StreamExt, TryStreamExt};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Rows fetched per query when streaming ranges
+const PAGE_SIZE: i64 = 1000;
+
+/// Time span fetched per query when streaming telemetry
+const TELEMETRY_WINDOW_MILLIS: i64 = 20 * codec::BUCKET_MILLIS;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS drone_telemetry (
+    drone_id        TEXT NOT NULL,
+    timestamp       INTEGER NOT NULL,
+    latitude        REAL,
+    longitude       REAL,
+    altitude        REAL,
+    heading         REAL,
+    speed           REAL,
+    battery_level   INTEGER,
+    fuel_level      INTEGER,
+    system_health   INTEGER,
+    status          TEXT,
+    armed           INTEGER,
+    temperature     REAL,
+    signal_strength INTEGER,
+    mission_id      TEXT,
+    gimbal_pan      REAL,
+    gimbal_tilt     REAL,
+    gimbal_zoom     REAL,
+    PRIMARY KEY (drone_id, timestamp)
+);
+
+CREATE TABLE IF NOT EXISTS drone_telemetry_compact (
+    drone_id     TEXT NOT NULL,
+    bucket_start INTEGER NOT NULL,
+    bucket_end   INTEGER NOT NULL,
+    sample_count INTEGER NOT NULL,
+    samples      BLOB NOT NULL,
+    PRIMARY KEY (drone_id, bucket_start)
+);
+
+CREATE TABLE IF NOT EXISTS waypoint_events (
+    mission_id        TEXT NOT NULL,
+    event_time        INTEGER NOT NULL,
+    drone_id          TEXT NOT NULL,
+    waypoint_id       TEXT,
+    waypoint_name     TEXT,
+    latitude          REAL,
+    longitude         REAL,
+    event_type        TEXT,
+    speed_at_event    REAL,
+    altitude_at_event REAL,
+    heading           REAL
+);
+CREATE INDEX IF NOT EXISTS idx_waypoint_events_mission
+    ON waypoint_events (mission_id, event_time);
+
+CREATE TABLE IF NOT EXISTS cv_tracking (
+    drone_id        TEXT NOT NULL,
+    frame_timestamp INTEGER NOT NULL,
+    tracking_id     INTEGER,
+    confidence      REAL,
+    halo_detected   INTEGER,
+    result          TEXT NOT NULL,
+    PRIMARY KEY (drone_id, frame_timestamp)
+);
+
+CREATE TABLE IF NOT EXISTS cv_tuning (
+    id         TEXT PRIMARY KEY,
+    revision   INTEGER NOT NULL,
+    tuning     TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS missions (
+    mission_id  TEXT PRIMARY KEY,
+    name        TEXT NOT NULL,
+    status      TEXT NOT NULL,
+    body        TEXT NOT NULL,
+    created_at  INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS drone_registry (
+    drone_id         TEXT PRIMARY KEY,
+    callsign         TEXT,
+    drone_type       TEXT,
+    operational      INTEGER,
+    alert_thresholds TEXT,
+    command_transport TEXT,
+    telemetry_source TEXT,
+    fleet_marking    TEXT,
+    handed_off_to    TEXT,
+    registered_at    INTEGER,
+    updated_at       INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS drone_type_thresholds (
+    drone_type       TEXT PRIMARY KEY,
+    alert_thresholds TEXT,
+    updated_at       INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS drone_groups (
+    name        TEXT PRIMARY KEY,
+    members     TEXT NOT NULL,
+    role        TEXT,
+    description TEXT,
+    created_at  INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS drone_snapshots (
+    drone_id    TEXT PRIMARY KEY,
+    snapshot    TEXT NOT NULL,
+    last_update INTEGER NOT NULL,
+    evicted_at  INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS zones (
+    id           TEXT PRIMARY KEY,
+    name         TEXT NOT NULL,
+    vertices     TEXT NOT NULL,
+    max_altitude REAL,
+    created_at   INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS zone_dwell (
+    zone_id        TEXT NOT NULL,
+    mission_id     TEXT NOT NULL,
+    drone_id       TEXT NOT NULL,
+    visits         INTEGER NOT NULL,
+    time_inside_ms INTEGER NOT NULL,
+    first_entry    INTEGER,
+    last_entry     INTEGER,
+    last_exit      INTEGER,
+    PRIMARY KEY (zone_id, mission_id, drone_id)
+);
+
+CREATE TABLE IF NOT EXISTS push_subscriptions (
+    id           TEXT PRIMARY KEY,
+    user_id      TEXT NOT NULL,
+    platform     TEXT NOT NULL,
+    device_token TEXT NOT NULL,
+    preferences  TEXT NOT NULL,
+    created_at   INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS custom_event_schemas (
+    event_type    TEXT PRIMARY KEY,
+    schema        TEXT NOT NULL,
+    description   TEXT,
+    registered_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS custom_events (
+    event_type TEXT NOT NULL,
+    event_time INTEGER NOT NULL,
+    event_id   TEXT NOT NULL,
+    drone_id   TEXT,
+    mission_id TEXT,
+    value      TEXT NOT NULL,
+    PRIMARY KEY (event_type, event_time, event_id)
+);
+
+CREATE TABLE IF NOT EXISTS leases (
+    name        TEXT PRIMARY KEY,
+    holder      TEXT NOT NULL,
+    acquired_at INTEGER NOT NULL,
+    expires_at  INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS waypoint_attachments (
+    id           TEXT PRIMARY KEY,
+    mission_id   TEXT NOT NULL,
+    waypoint_id  TEXT NOT NULL,
+    file_name    TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes   INTEGER NOT NULL,
+    object_key   TEXT NOT NULL,
+    threat_level TEXT,
+    notes        TEXT,
+    uploaded_by  TEXT,
+    created_at   INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    alert_id        TEXT PRIMARY KEY,
+    created_at      INTEGER NOT NULL,
+    severity        TEXT,
+    alert_type      TEXT,
+    message         TEXT,
+    drone_id        TEXT,
+    acknowledged    INTEGER NOT NULL DEFAULT 0,
+    acknowledged_by TEXT,
+    acknowledged_at INTEGER,
+    resolved        INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id         TEXT PRIMARY KEY,
+    name       TEXT NOT NULL,
+    condition  TEXT NOT NULL,
+    severity   TEXT NOT NULL,
+    message    TEXT,
+    enabled    INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS telemetry_gaps (
+    mission_id  TEXT NOT NULL,
+    gap_start   INTEGER NOT NULL,
+    drone_id    TEXT NOT NULL,
+    gap_end     INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    PRIMARY KEY (mission_id, gap_start, drone_id)
+);
+
+CREATE TABLE IF NOT EXISTS telemetry_dead_letters (
+    drone_id       TEXT NOT NULL,
+    quarantined_at INTEGER NOT NULL,
+    id             TEXT NOT NULL,
+    reason         TEXT NOT NULL,
+    payload        TEXT NOT NULL,
+    PRIMARY KEY (drone_id, quarantined_at, id)
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_commands (
+    id         TEXT PRIMARY KEY,
+    state      TEXT NOT NULL,
+    payload    TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+"#;
+
+const TELEMETRY_COLUMNS: &str = "drone_id, timestamp, latitude, longitude, altitude, \
+    heading, speed, battery_level, fuel_level, system_health, \
+    status, armed, temperature, signal_strength, mission_id, \
+    gimbal_pan, gimbal_tilt, gimbal_zoom";
+
+const WAYPOINT_EVENT_COLUMNS: &str = "mission_id, event_time, drone_id, waypoint_id, \
+    waypoint_name, latitude, longitude, event_type, speed_at_event, \
+    altitude_at_event, heading";
+
+/// SQLite-backed implementation of every storage trait
+#[derive(Clone)]
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+    telemetry_storage: TelemetryStorage,
+}
+
+impl SqliteStore {
+    /// Open (or create) a database file and apply the schema
+    pub async fn open(path: PathBuf) -> DbResult<Self> {
+        let conn = tokio::task::spawn_blocking(move || Connection::open(path))
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?
+            .map_err(|e| DbError::Connection(e.to_string()))?;
+        Self::init(conn)
+    }
+
+    /// Open a private in-memory database
+    pub fn open_in_memory() -> DbResult<Self> {
+        let conn =
+            Connection::open_in_memory().map_err(|e| DbError::Connection(e.to_string()))?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> DbResult<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| DbError::Migration(e.to_string()))?;
+        // Files created before the gimbal columns
+        for column in ["gimbal_pan", "gimbal_tilt", "gimbal_zoom"] {
+            add_column(&conn, "drone_telemetry", column, "REAL")?;
+        }
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            telemetry_storage: TelemetryStorage::default(),
+        })
+    }
+
+    /// Write new telemetry in the given layout
+    ///
+    /// Reads always cover both layouts, so switching modes keeps the
+    /// history written before the switch.
+    pub fn with_telemetry_storage(mut self, storage: TelemetryStorage) -> Self {
+        self.telemetry_storage = storage;
+        self
+    }
+
+    pub async fn health_check(&self) -> DbResult<bool> {
+        self.call(|conn| Ok(conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))? == 1))
+            .await
+    }
+
+    /// Run a closure against the connection on the blocking pool
+    async fn call<T, F>(&self, f: F) -> DbResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> DbResult<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || f(&conn.lock()))
+            .await
+            .map_err(|e| DbError::Query(e.to_string()))?
+    }
+
+    /// Stream a query page by page, `fetch(conn, offset, limit)` returning one page
+    fn paged<T, F>(&self, fetch: F) -> RecordStream<T>
+    where
+        T: Send + 'static,
+        F: Fn(&Connection, i64, i64) -> DbResult<Vec<T>> + Send + Sync + 'static,
+    {
+        let store = self.clone();
+        let fetch = Arc::new(fetch);
+
+        futures::stream::unfold(Some(0i64), move |offset| {
+            let store = store.clone();
+            let fetch = fetch.clone();
+            async move {
+                let offset = offset?;
+                let page = store.call(move |conn| fetch(conn, offset, PAGE_SIZE)).await;
+                let next = match &page {
+                    Ok(rows) if rows.len() as i64 == PAGE_SIZE => Some(offset + PAGE_SIZE),
+                    _ => None,
+                };
+                Some((page, next))
+            }
+        })
+        .flat_map(|page| {
+            futures::stream::iter(match page {
+                Ok(rows) => rows.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            })
+        })
+        .boxed()
+    }
+}
+
+fn millis(ts: DateTime<Utc>) -> i64 {
+    ts.timestamp_millis()
+}
+
+fn from_millis(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+fn parse_uuid(value: Option<String>) -> Option<uuid::Uuid> {
+    value.and_then(|v| uuid::Uuid::parse_str(&v).ok())
+}
+
+/// Add a nullable column to an existing table if it is missing
+fn add_column(conn: &Connection, table: &str, column: &str, kind: &str) -> DbResult<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?
+        .exists(params![column])?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, kind), [])
+            .map_err(|e| DbError::Migration(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn telemetry_record(row: &Row<'_>) -> rusqlite::Result<TelemetryRecord> {
+    Ok(TelemetryRecord {
+        drone_id: row.get(0)?,
+        timestamp: from_millis(row.get(1)?),
+        latitude: row.get::<_, Option<f64>>(2)?.unwrap_or_default(),
+        longitude: row.get::<_, Option<f64>>(3)?.unwrap_or_default(),
+        altitude: row.get::<_, Option<f64>>(4)?.unwrap_or_default(),
+        heading: row.get::<_, Option<f64>>(5)?.unwrap_or_default(),
+        speed: row.get::<_, Option<f64>>(6)?.unwrap_or_default(),
+        battery_level: row.get::<_, Option<i32>>(7)?.unwrap_or_default(),
+        fuel_level: row.get::<_, Option<i32>>(8)?.unwrap_or_default(),
+        system_health: row.get::<_, Option<i32>>(9)?.unwrap_or_default(),
+        status: row.get(10)?,
+        armed: row.get(11)?,
+        temperature: row.get(12)?,
+        signal_strength: row.get(13)?,
+        mission_id: parse_uuid(row.get(14)?),
+        gimbal: match (row.get(15)?, row.get(16)?, row.get(17)?) {
+            (Some(pan), Some(tilt), Some(zoom)) => Some(GimbalState { pan, tilt, zoom }),
+            _ => None,
+        },
+    })
+}
+
+fn alert_record(row: &Row<'_>) -> rusqlite::Result<AlertRecord> {
+    Ok(AlertRecord {
+        alert_id: parse_uuid(row.get(0)?).unwrap_or_default(),
+        created_at: from_millis(row.get(1)?),
+        severity: row.get(2)?,
+        alert_type: row.get(3)?,
+        message: row.get(4)?,
+        drone_id: row.get(5)?,
+        acknowledged_at: row.get::<_, Option<i64>>(6)?.map(from_millis),
+    })
+}
+
+fn waypoint_event_record(row: &Row<'_>) -> rusqlite::Result<WaypointEventRecord> {
+    Ok(WaypointEventRecord {
+        mission_id: parse_uuid(row.get(0)?).unwrap_or_default(),
+        event_time: from_millis(row.get(1)?),
+        drone_id: row.get(2)?,
+        waypoint_id: row.get(3)?,
+        waypoint_name: row.get(4)?,
+        latitude: row.get(5)?,
+        longitude: row.get(6)?,
+        event_type: row.get(7)?,
+        speed_at_event: row.get(8)?,
+        altitude_at_event: row.get(9)?,
+        heading: row.get(10)?,
+    })
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> DbResult<String> {
+    serde_json::to_string(value).map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+// ============================================================================
+// TRAIT IMPLEMENTATIONS
+// ============================================================================
+
+#[async_trait]
+impl TelemetryStore for SqliteStore {
+    async fn insert(
+        &self,
+        drone_id: &DroneId,
+        position: &GeoPosition,
+        telemetry: &Telemetry,
+        mission_id: Option<&MissionId>,
+    ) -> DbResult<()> {
+        let record = TelemetryRecord {
+            drone_id: drone_id.as_str().to_string(),
+            timestamp: telemetry.timestamp,
+            latitude: position.latitude,
+            longitude: position.longitude,
+            altitude: position.altitude,
+            heading: telemetry.heading,
+            speed: telemetry.speed,
+            battery_level: telemetry.battery_level as i32,
+            fuel_level: telemetry.fuel_level as i32,
+            system_health: telemetry.system_health as i32,
+            status: Some("MOVING".to_string()),
+            armed: Some(false),
+            temperature: Some(telemetry.temperature),
+            signal_strength: Some(telemetry.signal_strength as i32),
+            mission_id: mission_id.map(|m| m.0),
+            gimbal: telemetry.gimbal,
+        };
+
+        match self.telemetry_storage {
+            TelemetryStorage::Row => self.call(move |conn| insert_telemetry_row(conn, &record)).await,
+            TelemetryStorage::Compact => self.call(move |conn| insert_telemetry_compact(conn, record)).await,
+        }
+    }
+
+    async fn insert_records(&self, records: Vec<TelemetryRecord>) -> DbResult<()> {
+        let storage = self.telemetry_storage;
+        self.call(move |conn| insert_telemetry_batch(conn, records, storage)).await
+    }
+
+    async fn get_latest(&self, drone_id: &DroneId) -> DbResult<Option<(GeoPosition, Telemetry)>> {
+        Ok(self.get_history(drone_id, 1).await?.into_iter().next())
+    }
+
+    async fn get_history(
+        &self,
+        drone_id: &DroneId,
+        limit: i32,
+    ) -> DbResult<Vec<(GeoPosition, Telemetry)>> {
+        let drone_id = drone_id.as_str().to_string();
+        let limit = limit.max(0) as usize;
+
+        self.call(move |conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM drone_telemetry WHERE drone_id = ?1 \
+                 ORDER BY timestamp DESC LIMIT ?2",
+                TELEMETRY_COLUMNS
+            ))?;
+            let mut records = stmt
+                .query_map(params![drone_id, limit as i64], telemetry_record)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            // Newest compact buckets until they alone cover the limit
+            let mut stmt = conn.prepare(
+                "SELECT samples FROM drone_telemetry_compact WHERE drone_id = ?1 \
+                 ORDER BY bucket_start DESC",
+            )?;
+            let mut buckets = stmt.query(params![drone_id])?;
+            let mut compact = 0;
+            while compact < limit {
+                let Some(bucket) = buckets.next()? else {
+                    break;
+                };
+                let samples = codec::decode(&bucket.get::<_, Vec<u8>>(0)?, &drone_id)?;
+                compact += samples.len();
+                records.extend(samples);
+            }
+
+            records.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+            records.truncate(limit);
+            Ok(records.iter().map(TelemetryRecord::position_telemetry).collect())
+        })
+        .await
+    }
+
+    async fn get_at(
+        &self,
+        drone_id: &DroneId,
+        since: DateTime<Utc>,
+        at: DateTime<Utc>,
+    ) -> DbResult<Option<TelemetryRecord>> {
+        let drone_id = drone_id.as_str().to_string();
+        let (from, to) = (millis(since), millis(at));
+        self.call(move |conn| telemetry_at(conn, &drone_id, from, to)).await
+    }
+
+    async fn stream_range(
+        &self,
+        drone_id: &DroneId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<TelemetryRecord>> {
+        let drone_id = drone_id.as_str().to_string();
+        let (from, to) = (millis(from), millis(to));
+        let store = self.clone();
+
+        // One query per time window, so both layouts can be merged in order
+        let windows = futures::stream::unfold(Some(from), move |start| {
+            let store = store.clone();
+            let drone_id = drone_id.clone();
+            async move {
+                let start = start?;
+                let end = start.saturating_add(TELEMETRY_WINDOW_MILLIS - 1).min(to);
+                let page = store
+                    .call(move |conn| telemetry_window(conn, &drone_id, start, end))
+                    .await;
+                let next = (page.is_ok() && end < to).then_some(end + 1);
+                Some((page, next))
+            }
+        });
+
+        Ok(windows
+            .map_ok(|rows| futures::stream::iter(rows.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed())
+    }
+}
+
+fn insert_telemetry_row(conn: &Connection, record: &TelemetryRecord) -> DbResult<()> {
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO drone_telemetry ({}) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+            TELEMETRY_COLUMNS
+        ),
+        params![
+            record.drone_id,
+            millis(record.timestamp),
+            record.latitude,
+            record.longitude,
+            record.altitude,
+            record.heading,
+            record.speed,
+            record.battery_level,
+            record.fuel_level,
+            record.system_health,
+            record.status,
+            record.armed,
+            record.temperature,
+            record.signal_strength,
+            record.mission_id.map(|m| m.to_string()),
+            record.gimbal.map(|g| g.pan),
+            record.gimbal.map(|g| g.tilt),
+            record.gimbal.map(|g| g.zoom),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Add a sample to its minute bucket, replacing one with the same timestamp
+fn insert_telemetry_compact(conn: &Connection, record: TelemetryRecord) -> DbResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    merge_compact_samples(&tx, vec![record])?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Write many samples in one transaction
+fn insert_telemetry_batch(
+    conn: &Connection,
+    records: Vec<TelemetryRecord>,
+    storage: TelemetryStorage,
+) -> DbResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    match storage {
+        TelemetryStorage::Row => {
+            for record in &records {
+                insert_telemetry_row(&tx, record)?;
+            }
+        }
+        TelemetryStorage::Compact => {
+            let mut buckets: BTreeMap<(String, i64), Vec<TelemetryRecord>> = BTreeMap::new();
+            for record in records {
+                let key = (record.drone_id.clone(), codec::bucket_start(millis(record.timestamp)));
+                buckets.entry(key).or_default().push(record);
+            }
+            for samples in buckets.into_values() {
+                merge_compact_samples(&tx, samples)?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Merge samples of one drone and minute bucket into the stored bucket
+fn merge_compact_samples(conn: &Connection, records: Vec<TelemetryRecord>) -> DbResult<()> {
+    let Some(first) = records.first() else {
+        return Ok(());
+    };
+    let bucket = codec::bucket_start(millis(first.timestamp));
+    let drone_id = first.drone_id.clone();
+
+    let existing: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT samples FROM drone_telemetry_compact WHERE drone_id = ?1 AND bucket_start = ?2",
+            params![drone_id, bucket],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let mut samples = match existing {
+        Some(blob) => codec::decode(&blob, &drone_id)?,
+        None => Vec::new(),
+    };
+    for record in records {
+        match samples.binary_search_by_key(&record.timestamp, |s| s.timestamp) {
+            Ok(i) => samples[i] = record,
+            Err(i) => samples.insert(i, record),
+        }
+    }
+
+    let bucket_end = samples.last().map(|s| millis(s.timestamp)).unwrap_or(bucket);
+    conn.execute(
+        "INSERT OR REPLACE INTO drone_telemetry_compact \
+         (drone_id, bucket_start, bucket_end, sample_count, samples) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![drone_id, bucket, bucket_end, samples.len() as i64, codec::encode(&samples)],
+    )?;
+    Ok(())
+}
+
+/// Samples from both layouts within `[from, to]` (ms), oldest first
+/// Newest raw or compacted row within `[from, to]`
+fn telemetry_at(conn: &Connection, drone_id: &str, from: i64, to: i64) -> DbResult<Option<TelemetryRecord>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM drone_telemetry \
+         WHERE drone_id = ?1 AND timestamp >= ?2 AND timestamp <= ?3 \
+         ORDER BY timestamp DESC LIMIT 1",
+        TELEMETRY_COLUMNS
+    ))?;
+    let raw = stmt
+        .query_row(params![drone_id, from, to], telemetry_record)
+        .optional()?;
+
+    // Buckets newest first, stopping at the first with a sample in range
+    let mut stmt = conn.prepare_cached(
+        "SELECT samples FROM drone_telemetry_compact \
+         WHERE drone_id = ?1 AND bucket_end >= ?2 AND bucket_start <= ?3 \
+         ORDER BY bucket_start DESC",
+    )?;
+    let mut buckets = stmt.query(params![drone_id, from, to])?;
+    let mut compact = None;
+    while let Some(bucket) = buckets.next()? {
+        let samples = codec::decode(&bucket.get::<_, Vec<u8>>(0)?, drone_id)?;
+        compact = samples
+            .into_iter()
+            .filter(|s| (from..=to).contains(&millis(s.timestamp)))
+            .max_by_key(|s| s.timestamp);
+        if compact.is_some() {
+            break;
+        }
+    }
+
+    Ok(match (raw, compact) {
+        (Some(raw), Some(compact)) => Some(if compact.timestamp > raw.timestamp { compact } else { raw }),
+        (raw, compact) => raw.or(compact),
+    })
+}
+
+fn telemetry_window(conn: &Connection, drone_id: &str, from: i64, to: i64) -> DbResult<Vec<TelemetryRecord>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM drone_telemetry \
+         WHERE drone_id = ?1 AND timestamp >= ?2 AND timestamp <= ?3 \
+         ORDER BY timestamp ASC",
+        TELEMETRY_COLUMNS
+    ))?;
+    let mut records = stmt
+        .query_map(params![drone_id, from, to], telemetry_record)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT samples FROM drone_telemetry_compact \
+         WHERE drone_id = ?1 AND bucket_end >= ?2 AND bucket_start <= ?3 \
+         ORDER BY bucket_start ASC",
+    )?;
+    let buckets = stmt
+        .query_map(params![drone_id, from, to], |row| row.get::<_, Vec<u8>>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    if buckets.is_empty() {
+        return Ok(records);
+    }
+    for bucket in buckets {
+        let samples = codec::decode(&bucket, drone_id)?;
+        records.extend(samples.into_iter().filter(|s| (from..=to).contains(&millis(s.timestamp))));
+    }
+    records.sort_by_key(|r| r.timestamp);
+    Ok(records)
+}
+
+impl SqliteStore {
+    /// Insert a `waypoint_events` row of the given type, timestamped now
+    async fn insert_waypoint_event(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+        event_type: &'static str,
+    ) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let waypoint_id = waypoint_id.0.clone();
+        let mission_id = mission_id.0.to_string();
+        let position = *position;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO waypoint_events (
+                    mission_id, event_time, drone_id, waypoint_id, event_type,
+                    latitude, longitude, altitude_at_event
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    mission_id,
+                    millis(Utc::now()),
+                    drone_id,
+                    waypoint_id,
+                    event_type,
+                    position.latitude,
+                    position.longitude,
+                    position.altitude,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl WaypointStore for SqliteStore {
+    async fn record_reached(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+    ) -> DbResult<()> {
+        self.insert_waypoint_event(drone_id, waypoint_id, mission_id, position, "REACHED").await
+    }
+
+    async fn record_skipped(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+    ) -> DbResult<()> {
+        self.insert_waypoint_event(drone_id, waypoint_id, mission_id, position, "SKIPPED").await
+    }
+
+    async fn stream_range(
+        &self,
+        mission_id: &MissionId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<WaypointEventRecord>> {
+        let mission_id = mission_id.0.to_string();
+        let (from, to) = (millis(from), millis(to));
+
+        Ok(self.paged(move |conn, offset, limit| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT {} FROM waypoint_events \
+                 WHERE mission_id = ?1 AND event_time >= ?2 AND event_time <= ?3 \
+                 ORDER BY event_time ASC, rowid ASC LIMIT ?4 OFFSET ?5",
+                WAYPOINT_EVENT_COLUMNS
+            ))?;
+            let rows = stmt
+                .query_map(params![mission_id, from, to, limit, offset], waypoint_event_record)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }))
+    }
+
+    async fn save_attachment(&self, attachment: &WaypointAttachmentRecord) -> DbResult<()> {
+        let attachment = attachment.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO waypoint_attachments (
+                    id, mission_id, waypoint_id, file_name, content_type, size_bytes,
+                    object_key, threat_level, notes, uploaded_by, created_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    attachment.id.to_string(),
+                    attachment.mission_id.to_string(),
+                    attachment.waypoint_id,
+                    attachment.file_name,
+                    attachment.content_type,
+                    attachment.size_bytes,
+                    attachment.object_key,
+                    attachment.threat_level,
+                    attachment.notes,
+                    attachment.uploaded_by,
+                    millis(attachment.created_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_attachment(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.call(move |conn| {
+            conn.execute("DELETE FROM waypoint_attachments WHERE id = ?1", params![id.to_string()])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn attachments(&self) -> DbResult<Vec<WaypointAttachmentRecord>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, mission_id, waypoint_id, file_name, content_type, size_bytes, \
+                        object_key, threat_level, notes, uploaded_by, created_at \
+                 FROM waypoint_attachments ORDER BY created_at ASC, id ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(WaypointAttachmentRecord {
+                        id: parse_uuid(row.get(0)?).unwrap_or_default(),
+                        mission_id: parse_uuid(row.get(1)?).unwrap_or_default(),
+                        waypoint_id: row.get(2)?,
+                        file_name: row.get(3)?,
+                        content_type: row.get(4)?,
+                        size_bytes: row.get(5)?,
+                        object_key: row.get(6)?,
+                        threat_level: row.get(7)?,
+                        notes: row.get(8)?,
+                        uploaded_by: row.get(9)?,
+                        created_at: from_millis(row.get(10)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+}
+
+const INSERT_TRACKING: &str = "INSERT OR REPLACE INTO cv_tracking (
+    drone_id, frame_timestamp, tracking_id, confidence, halo_detected, result
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+
+type TrackingRow = (String, i64, u32, f64, bool, String);
+
+fn tracking_row(result: &TrackingResult) -> DbResult<TrackingRow> {
+    Ok((
+        result.drone_id.as_str().to_string(),
+        millis(result.frame_timestamp),
+        result.tracking_id,
+        result.confidence,
+        result.halo.is_some(),
+        to_json(result)?,
+    ))
+}
+
+#[async_trait]
+impl TrackingStore for SqliteStore {
+    async fn insert(&self, result: &TrackingResult) -> DbResult<()> {
+        self.insert_batch(std::slice::from_ref(result)).await
+    }
+
+    /// Insert all results in one transaction
+    async fn insert_batch(&self, results: &[TrackingResult]) -> DbResult<()> {
+        let rows = results.iter().map(tracking_row).collect::<DbResult<Vec<_>>>()?;
+
+        self.call(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(INSERT_TRACKING)?;
+                for (drone_id, frame_timestamp, tracking_id, confidence, halo_detected, body) in &rows {
+                    stmt.execute(params![drone_id, frame_timestamp, tracking_id, confidence, halo_detected, body])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn stream_range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> DbResult<RecordStream<TrackingResult>> {
+        let (from, to) = (millis(from), millis(to));
+
+        Ok(self.paged(move |conn, offset, limit| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT result FROM cv_tracking \
+                 WHERE frame_timestamp >= ?1 AND frame_timestamp <= ?2 \
+                 ORDER BY frame_timestamp ASC, drone_id ASC LIMIT ?3 OFFSET ?4",
+            )?;
+            let bodies = stmt
+                .query_map(params![from, to, limit, offset], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            bodies
+                .iter()
+                .map(|body| serde_json::from_str(body).map_err(|e| DbError::Serialization(e.to_string())))
+                .collect()
+        }))
+    }
+
+    async fn save_cv_tuning(&self, tuning: &CvTuningRecord) -> DbResult<()> {
+        let tuning = tuning.clone();
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO cv_tuning (id, revision, tuning, updated_at) \
+                 VALUES ('current', ?1, ?2, ?3)",
+                params![tuning.revision, tuning.tuning, millis(tuning.updated_at)],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn cv_tuning(&self) -> DbResult<Option<CvTuningRecord>> {
+        self.call(|conn| {
+            let tuning = conn
+                .query_row(
+                    "SELECT revision, tuning, updated_at FROM cv_tuning WHERE id = 'current'",
+                    [],
+                    |row| {
+                        Ok(CvTuningRecord {
+                            revision: row.get(0)?,
+                            tuning: row.get(1)?,
+                            updated_at: from_millis(row.get(2)?),
+                        })
+                    },
+                )
+                .optional()?;
+            Ok(tuning)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl MissionStore for SqliteStore {
+    async fn create(&self, mission: &Mission) -> DbResult<()> {
+        let body = to_json(mission)?;
+        let mission_id = mission.id.0.to_string();
+        let name = mission.name.clone();
+        let status = format!("{:?}", mission.status);
+        let created_at = millis(mission.created_at);
+        let updated_at = millis(mission.updated_at);
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO missions (
+                    mission_id, name, status, body, created_at, updated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![mission_id, name, status, body, created_at, updated_at],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_status(&self, mission_id: &MissionId, status: &str) -> DbResult<()> {
+        let mission_id = mission_id.0.to_string();
+        let status = status.to_string();
+
+        self.call(move |conn| {
+            conn.execute(
+                "UPDATE missions SET status = ?1, updated_at = ?2 WHERE mission_id = ?3",
+                params![status, millis(Utc::now()), mission_id],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn transition_status(
+        &self,
+        mission_id: &MissionId,
+        expected: &str,
+        status: &str,
+    ) -> DbResult<bool> {
+        let mission_id = mission_id.0.to_string();
+        let expected = expected.to_string();
+        let status = status.to_string();
+
+        // A single conditional UPDATE is atomic under SQLite's write lock
+        self.call(move |conn| {
+            let changed = conn.execute(
+                "UPDATE missions SET status = ?1, updated_at = ?2
+                 WHERE mission_id = ?3 AND status = ?4",
+                params![status, millis(Utc::now()), mission_id, expected],
+            )?;
+            Ok(changed > 0)
+        })
+        .await
+    }
+
+    async fn get(&self, mission_id: &MissionId) -> DbResult<Option<Mission>> {
+        let mission_id = mission_id.0.to_string();
+
+        let row = self
+            .call(move |conn| {
+                Ok(conn
+                    .query_row(
+                        "SELECT body, status, updated_at FROM missions WHERE mission_id = ?1",
+                        params![mission_id],
+                        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?)),
+                    )
+                    .optional()?)
+            })
+            .await?;
+
+        let Some((body, status, updated_at)) = row else {
+            return Ok(None);
+        };
+
+        let mut mission: Mission =
+            serde_json::from_str(&body).map_err(|e| DbError::Serialization(e.to_string()))?;
+        // Status updates only touch the status column
+        if let Ok(status) = serde_json::from_value::<MissionStatus>(status.to_uppercase().into()) {
+            mission.status = status;
+        }
+        mission.updated_at = from_millis(updated_at);
+
+        Ok(Some(mission))
+    }
+}
+
+#[async_trait]
+impl DroneStore for SqliteStore {
+    async fn register(&self, drone: &Drone) -> DbResult<()> {
+        let drone_id = drone.id.as_str().to_string();
+        let callsign = drone.callsign.clone();
+        let drone_type = format!("{:?}", drone.drone_type);
+
+        self.call(move |conn| {
+            let now = millis(Utc::now());
+            conn.execute(
+                "INSERT INTO drone_registry (
+                    drone_id, callsign, drone_type, operational, registered_at, updated_at
+                ) VALUES (?1, ?2, ?3, 1, ?4, ?4)
+                ON CONFLICT (drone_id) DO UPDATE SET
+                    callsign = excluded.callsign,
+                    drone_type = excluded.drone_type,
+                    operational = 1,
+                    updated_at = excluded.updated_at",
+                params![drone_id, callsign, drone_type, now],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_all(&self) -> DbResult<Vec<DroneId>> {
+        self.call(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT drone_id FROM drone_registry WHERE operational = 1")?;
+            let ids = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .map(|id| id.map(DroneId::new))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn set_alert_thresholds(
+        &self,
+        drone_id: &DroneId,
+        overrides: &ThresholdOverrides,
+    ) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let encoded = encode_overrides(overrides)?;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO drone_registry (drone_id, alert_thresholds, updated_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (drone_id) DO UPDATE SET
+                    alert_thresholds = excluded.alert_thresholds,
+                    updated_at = excluded.updated_at",
+                params![drone_id, encoded, millis(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_alert_thresholds(&self) -> DbResult<Vec<(DroneId, ThresholdOverrides)>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT drone_id, alert_thresholds FROM drone_registry \
+                 WHERE alert_thresholds IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(id, encoded)| Ok((DroneId::new(id), decode_overrides(&encoded)?)))
+                .collect()
+        })
+        .await
+    }
+
+    async fn set_type_alert_thresholds(
+        &self,
+        drone_type: &DroneType,
+        overrides: &ThresholdOverrides,
+    ) -> DbResult<()> {
+        let key = to_json(drone_type)?;
+        let encoded = encode_overrides(overrides)?;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO drone_type_thresholds (
+                    drone_type, alert_thresholds, updated_at
+                ) VALUES (?1, ?2, ?3)",
+                params![key, encoded, millis(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_type_alert_thresholds(&self) -> DbResult<Vec<(DroneType, ThresholdOverrides)>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT drone_type, alert_thresholds FROM drone_type_thresholds \
+                 WHERE alert_thresholds IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(key, encoded)| {
+                    let drone_type: DroneType = serde_json::from_str(&key)
+                        .map_err(|e| DbError::Serialization(e.to_string()))?;
+                    Ok((drone_type, decode_overrides(&encoded)?))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn set_transport_binding(
+        &self,
+        drone_id: &DroneId,
+        binding: Option<&TransportBinding>,
+    ) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let encoded = binding.map(to_json).transpose()?;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO drone_registry (drone_id, command_transport, updated_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (drone_id) DO UPDATE SET
+                    command_transport = excluded.command_transport,
+                    updated_at = excluded.updated_at",
+                params![drone_id, encoded, millis(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_telemetry_source(&self, drone_id: &DroneId, source: Option<TelemetrySource>) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let source = source.map(|source| source.to_string());
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO drone_registry (drone_id, telemetry_source, updated_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (drone_id) DO UPDATE SET
+                    telemetry_source = excluded.telemetry_source,
+                    updated_at = excluded.updated_at",
+                params![drone_id, source, millis(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_telemetry_sources(&self) -> DbResult<Vec<(DroneId, TelemetrySource)>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT drone_id, telemetry_source FROM drone_registry \
+                 WHERE telemetry_source IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            Ok(rows
+                .into_iter()
+                .filter_map(|(id, source)| parse_telemetry_source(&id, &source).map(|source| (DroneId::new(id), source)))
+                .collect())
+        })
+        .await
+    }
+
+    async fn set_drone_marking(&self, drone_id: &DroneId, marking: Option<&DroneMarking>) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let encoded = marking.map(to_json).transpose()?;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO drone_registry (drone_id, fleet_marking, updated_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (drone_id) DO UPDATE SET
+                    fleet_marking = excluded.fleet_marking,
+                    updated_at = excluded.updated_at",
+                params![drone_id, encoded, millis(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_drone_markings(&self) -> DbResult<Vec<(DroneId, DroneMarking)>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT drone_id, fleet_marking FROM drone_registry \
+                 WHERE fleet_marking IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(id, encoded)| {
+                    let marking = serde_json::from_str(&encoded)
+                        .map_err(|e| DbError::Serialization(e.to_string()))?;
+                    Ok((DroneId::new(id), marking))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn set_handoff_owner(&self, drone_id: &DroneId, owner: Option<&HandoffOwnerRecord>) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let encoded = owner.map(to_json).transpose()?;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT INTO drone_registry (drone_id, handed_off_to, updated_at)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (drone_id) DO UPDATE SET
+                    handed_off_to = excluded.handed_off_to,
+                    updated_at = excluded.updated_at",
+                params![drone_id, encoded, millis(Utc::now())],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_handoff_owners(&self) -> DbResult<Vec<(DroneId, HandoffOwnerRecord)>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT drone_id, handed_off_to FROM drone_registry \
+                 WHERE handed_off_to IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(id, encoded)| {
+                    let owner = serde_json::from_str(&encoded)
+                        .map_err(|e| DbError::Serialization(e.to_string()))?;
+                    Ok((DroneId::new(id), owner))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn get_transport_bindings(&self) -> DbResult<Vec<(DroneId, TransportBinding)>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT drone_id, command_transport FROM drone_registry \
+                 WHERE command_transport IS NOT NULL",
+            )?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(id, encoded)| {
+                    let binding = serde_json::from_str(&encoded)
+                        .map_err(|e| DbError::Serialization(e.to_string()))?;
+                    Ok((DroneId::new(id), binding))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn save_group(&self, group: &DroneGroupRecord) -> DbResult<()> {
+        let group = group.clone();
+        let members =
+            serde_json::to_string(&group.members).map_err(|e| DbError::Serialization(e.to_string()))?;
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO drone_groups (
+                    name, members, role, description, created_at, updated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    group.name,
+                    members,
+                    group.role,
+                    group.description,
+                    millis(group.created_at),
+                    millis(group.updated_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_group(&self, name: &str) -> DbResult<()> {
+        let name = name.to_string();
+        self.call(move |conn| {
+            conn.execute("DELETE FROM drone_groups WHERE name = ?1", params![name])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn groups(&self) -> DbResult<Vec<DroneGroupRecord>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT name, members, role, description, created_at, updated_at \
+                 FROM drone_groups ORDER BY name ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(name, members, role, description, created_at, updated_at)| {
+                    Ok(DroneGroupRecord {
+                        name,
+                        members: serde_json::from_str(&members)
+                            .map_err(|e| DbError::Serialization(e.to_string()))?,
+                        role,
+                        description,
+                        created_at: from_millis(created_at),
+                        updated_at: from_millis(updated_at),
+                    })
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn save_snapshot(&self, snapshot: &DroneSnapshotRecord) -> DbResult<()> {
+        let snapshot = snapshot.clone();
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO drone_snapshots (
+                    drone_id, snapshot, last_update, evicted_at
+                ) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    snapshot.drone_id,
+                    snapshot.snapshot,
+                    millis(snapshot.last_update),
+                    millis(snapshot.evicted_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn snapshot(&self, drone_id: &DroneId) -> DbResult<Option<DroneSnapshotRecord>> {
+        let drone_id = drone_id.as_str().to_string();
+        self.call(move |conn| {
+            let snapshot = conn
+                .query_row(
+                    "SELECT drone_id, snapshot, last_update, evicted_at \
+                     FROM drone_snapshots WHERE drone_id = ?1",
+                    params![drone_id],
+                    |row| {
+                        Ok(DroneSnapshotRecord {
+                            drone_id: row.get(0)?,
+                            snapshot: row.get(1)?,
+                            last_update: from_millis(row.get(2)?),
+                            evicted_at: from_millis(row.get(3)?),
+                        })
+                    },
+                )
+                .optional()?;
+            Ok(snapshot)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl AlertStore for SqliteStore {
+    async fn create(&self, alert: &Alert) -> DbResult<()> {
+        let alert_id = alert.id.to_string();
+        let created_at = millis(alert.created_at);
+        let severity = format!("{:?}", alert.severity);
+        let alert_type = format!("{:?}", alert.alert_type);
+        let message = alert.message.clone();
+        let drone_id = alert.drone_id.as_ref().map(|d| d.as_str().to_string());
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO alerts (
+                    alert_id, created_at, severity, alert_type, message, drone_id
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![alert_id, created_at, severity, alert_type, message, drone_id],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn acknowledge(
+        &self,
+        drone_id: &DroneId,
+        alert_id: uuid::Uuid,
+        _created_at: DateTime<Utc>,
+        by: &str,
+        at: DateTime<Utc>,
+    ) -> DbResult<()> {
+        let drone_id = drone_id.as_str().to_string();
+        let alert_id = alert_id.to_string();
+        let by = by.to_string();
+
+        let updated = self
+            .call(move |conn| {
+                Ok(conn.execute(
+                    "UPDATE alerts SET acknowledged = 1, acknowledged_by = ?1, acknowledged_at = ?2
+                    WHERE alert_id = ?3 AND drone_id = ?4",
+                    params![by, millis(at), alert_id, drone_id],
+                )?)
+            })
+            .await?;
+
+        if updated == 0 {
+            return Err(DbError::not_found("alert"));
+        }
+        Ok(())
+    }
+
+    async fn stream_range(
+        &self,
+        drone_id: &DroneId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<AlertRecord>> {
+        let drone_id = drone_id.as_str().to_string();
+        let (from, to) = (millis(from), millis(to));
+
+        Ok(self.paged(move |conn, offset, limit| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT alert_id, created_at, severity, alert_type, message, drone_id, acknowledged_at \
+                 FROM alerts WHERE drone_id = ?1 AND created_at >= ?2 AND created_at <= ?3 \
+                 ORDER BY created_at ASC, alert_id ASC LIMIT ?4 OFFSET ?5",
+            )?;
+            let rows = stmt
+                .query_map(params![drone_id, from, to, limit, offset], alert_record)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }))
+    }
+
+    async fn save_rule(&self, rule: &AlertRuleRecord) -> DbResult<()> {
+        let rule = rule.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO alert_rules (
+                    id, name, condition, severity, message, enabled, created_at, updated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    rule.id.to_string(),
+                    rule.name,
+                    rule.condition,
+                    rule.severity,
+                    rule.message,
+                    rule.enabled,
+                    millis(rule.created_at),
+                    millis(rule.updated_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_rule(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.call(move |conn| {
+            conn.execute("DELETE FROM alert_rules WHERE id = ?1", params![id.to_string()])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn rules(&self) -> DbResult<Vec<AlertRuleRecord>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, name, condition, severity, message, enabled, created_at, updated_at \
+                 FROM alert_rules ORDER BY created_at ASC, id ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(AlertRuleRecord {
+                        id: parse_uuid(row.get(0)?).unwrap_or_default(),
+                        name: row.get(1)?,
+                        condition: row.get(2)?,
+                        severity: row.get(3)?,
+                        message: row.get(4)?,
+                        enabled: row.get(5)?,
+                        created_at: from_millis(row.get(6)?),
+                        updated_at: from_millis(row.get(7)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl DataQualityStore for SqliteStore {
+    async fn record_gap(&self, gap: &TelemetryGapRecord) -> DbResult<()> {
+        let gap = gap.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO telemetry_gaps (
+                    mission_id, gap_start, drone_id, gap_end, duration_ms
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    gap.mission_id.to_string(),
+                    millis(gap.gap_start),
+                    gap.drone_id,
+                    millis(gap.gap_end),
+                    gap.duration_ms,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn gaps_for_mission(&self, mission_id: &MissionId) -> DbResult<Vec<TelemetryGapRecord>> {
+        let mission_id = mission_id.0;
+
+        self.call(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT gap_start, drone_id, gap_end, duration_ms FROM telemetry_gaps \
+                 WHERE mission_id = ?1 ORDER BY gap_start ASC, drone_id ASC",
+            )?;
+            let rows = stmt
+                .query_map(params![mission_id.to_string()], |row| {
+                    Ok(TelemetryGapRecord {
+                        mission_id,
+                        gap_start: from_millis(row.get(0)?),
+                        drone_id: row.get(1)?,
+                        gap_end: from_millis(row.get(2)?),
+                        duration_ms: row.get(3)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn quarantine(&self, record: &DeadLetterRecord) -> DbResult<()> {
+        let record = record.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO telemetry_dead_letters (
+                    drone_id, quarantined_at, id, reason, payload
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    record.drone_id,
+                    millis(record.quarantined_at),
+                    record.id.to_string(),
+                    record.reason,
+                    record.payload,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn dead_letters(&self, drone_id: &DroneId, limit: usize) -> DbResult<Vec<DeadLetterRecord>> {
+        let drone_id = drone_id.as_str().to_string();
+        let limit = limit.min(i64::MAX as usize) as i64;
+
+        self.call(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT drone_id, quarantined_at, id, reason, payload FROM telemetry_dead_letters \
+                 WHERE drone_id = ?1 ORDER BY quarantined_at DESC, id ASC LIMIT ?2",
+            )?;
+            let rows = stmt
+                .query_map(params![drone_id, limit], |row| {
+                    Ok(DeadLetterRecord {
+                        drone_id: row.get(0)?,
+                        quarantined_at: from_millis(row.get(1)?),
+                        id: parse_uuid(row.get(2)?).unwrap_or_default(),
+                        reason: row.get(3)?,
+                        payload: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ScheduleStore for SqliteStore {
+    async fn save_scheduled_command(&self, command: &ScheduledCommandRecord) -> DbResult<()> {
+        let command = command.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO scheduled_commands (
+                    id, state, payload, created_at, updated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    command.id.to_string(),
+                    command.state,
+                    command.payload,
+                    millis(command.created_at),
+                    millis(command.updated_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn scheduled_commands(&self) -> DbResult<Vec<ScheduledCommandRecord>> {
+        self.call(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, state, payload, created_at, updated_at FROM scheduled_commands \
+                 ORDER BY created_at ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(ScheduledCommandRecord {
+                        id: parse_uuid(row.get(0)?).unwrap_or_default(),
+                        state: row.get(1)?,
+                        payload: row.get(2)?,
+                        created_at: from_millis(row.get(3)?),
+                        updated_at: from_millis(row.get(4)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl NotificationStore for SqliteStore {
+    async fn save_subscription(&self, subscription: &PushSubscriptionRecord) -> DbResult<()> {
+        let subscription = subscription.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO push_subscriptions (
+                    id, user_id, platform, device_token, preferences, created_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    subscription.id.to_string(),
+                    subscription.user_id,
+                    subscription.platform,
+                    subscription.device_token,
+                    subscription.preferences,
+                    millis(subscription.created_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_subscription(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.call(move |conn| {
+            conn.execute("DELETE FROM push_subscriptions WHERE id = ?1", params![id.to_string()])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn subscriptions(&self) -> DbResult<Vec<PushSubscriptionRecord>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, user_id, platform, device_token, preferences, created_at \
+                 FROM push_subscriptions ORDER BY created_at ASC, id ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(PushSubscriptionRecord {
+                        id: parse_uuid(row.get(0)?).unwrap_or_default(),
+                        user_id: row.get(1)?,
+                        platform: row.get(2)?,
+                        device_token: row.get(3)?,
+                        preferences: row.get(4)?,
+                        created_at: from_millis(row.get(5)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl CustomEventStore for SqliteStore {
+    async fn save_schema(&self, schema: &CustomEventSchemaRecord) -> DbResult<()> {
+        let schema = schema.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO custom_event_schemas (
+                    event_type, schema, description, registered_at
+                ) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    schema.event_type,
+                    schema.schema,
+                    schema.description,
+                    millis(schema.registered_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_schema(&self, event_type: &str) -> DbResult<()> {
+        let event_type = event_type.to_string();
+
+        self.call(move |conn| {
+            conn.execute("DELETE FROM custom_event_schemas WHERE event_type = ?1", params![event_type])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn schemas(&self) -> DbResult<Vec<CustomEventSchemaRecord>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT event_type, schema, description, registered_at \
+                 FROM custom_event_schemas ORDER BY registered_at ASC, event_type ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(CustomEventSchemaRecord {
+                        event_type: row.get(0)?,
+                        schema: row.get(1)?,
+                        description: row.get(2)?,
+                        registered_at: from_millis(row.get(3)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn insert_event(&self, event: &CustomEventRecord) -> DbResult<()> {
+        let event = event.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO custom_events (
+                    event_type, event_time, event_id, drone_id, mission_id, value
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    event.event_type,
+                    millis(event.event_time),
+                    event.event_id.to_string(),
+                    event.drone_id,
+                    event.mission_id.map(|id| id.to_string()),
+                    event.value,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn stream_range(
+        &self,
+        event_type: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> DbResult<RecordStream<CustomEventRecord>> {
+        let event_type = event_type.to_string();
+        let (from, to) = (millis(from), millis(to));
+
+        Ok(self.paged(move |conn, offset, limit| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT event_type, event_time, event_id, drone_id, mission_id, value \
+                 FROM custom_events WHERE event_type = ?1 AND event_time >= ?2 AND event_time <= ?3 \
+                 ORDER BY event_time ASC, event_id ASC LIMIT ?4 OFFSET ?5",
+            )?;
+            let rows = stmt
+                .query_map(params![event_type, from, to, limit, offset], |row| {
+                    Ok(CustomEventRecord {
+                        event_type: row.get(0)?,
+                        event_time: from_millis(row.get(1)?),
+                        event_id: parse_uuid(row.get(2)?).unwrap_or_default(),
+                        drone_id: row.get(3)?,
+                        mission_id: parse_uuid(row.get(4)?),
+                        value: row.get(5)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        }))
+    }
+}
+
+#[async_trait]
+impl LeaseStore for SqliteStore {
+    async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        now: DateTime<Utc>,
+        ttl: std::time::Duration,
+        grace: std::time::Duration,
+    ) -> DbResult<LeaseRecord> {
+        let (name, holder) = (name.to_string(), holder.to_string());
+        let expires_at = now + chrono::Duration::from_std(ttl).unwrap_or_default();
+        let lapsed_before = now - chrono::Duration::from_std(grace).unwrap_or_default();
+
+        self.call(move |conn| {
+            // One statement, so the check and the write can't interleave with
+            // another instance on the same file
+            conn.execute(
+                "INSERT INTO leases (name, holder, acquired_at, expires_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(name) DO UPDATE SET
+                    acquired_at = CASE WHEN leases.holder = excluded.holder
+                        THEN leases.acquired_at ELSE excluded.acquired_at END,
+                    holder = excluded.holder,
+                    expires_at = excluded.expires_at
+                 WHERE leases.holder = excluded.holder OR leases.expires_at < ?5",
+                params![name, holder, millis(now), millis(expires_at), millis(lapsed_before)],
+            )?;
+            let lease = conn.query_row(
+                "SELECT name, holder, acquired_at, expires_at FROM leases WHERE name = ?1",
+                params![name],
+                |row| {
+                    Ok(LeaseRecord {
+                        name: row.get(0)?,
+                        holder: row.get(1)?,
+                        acquired_at: from_millis(row.get(2)?),
+                        expires_at: from_millis(row.get(3)?),
+                    })
+                },
+            )?;
+            Ok(lease)
+        })
+        .await
+    }
+
+    async fn release(&self, name: &str, holder: &str) -> DbResult<()> {
+        let (name, holder) = (name.to_string(), holder.to_string());
+
+        self.call(move |conn| {
+            conn.execute("DELETE FROM leases WHERE name = ?1 AND holder = ?2", params![name, holder])?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ZoneStore for SqliteStore {
+    async fn save_zone(&self, zone: &ZoneRecord) -> DbResult<()> {
+        let zone = zone.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO zones (
+                    id, name, vertices, max_altitude, created_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    zone.id.to_string(),
+                    zone.name,
+                    zone.vertices,
+                    zone.max_altitude,
+                    millis(zone.created_at),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_zone(&self, id: uuid::Uuid) -> DbResult<()> {
+        self.call(move |conn| {
+            conn.execute("DELETE FROM zones WHERE id = ?1", params![id.to_string()])?;
+            conn.execute("DELETE FROM zone_dwell WHERE zone_id = ?1", params![id.to_string()])?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn zones(&self) -> DbResult<Vec<ZoneRecord>> {
+        self.call(|conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, name, vertices, max_altitude, created_at FROM zones \
+                 ORDER BY created_at ASC, id ASC",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(ZoneRecord {
+                        id: parse_uuid(row.get(0)?).unwrap_or_default(),
+                        name: row.get(1)?,
+                        vertices: row.get(2)?,
+                        max_altitude: row.get(3)?,
+                        created_at: from_millis(row.get(4)?),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn save_dwell(&self, dwell: &ZoneDwellRecord) -> DbResult<()> {
+        let dwell = dwell.clone();
+
+        self.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO zone_dwell (
+                    zone_id, mission_id, drone_id, visits, time_inside_ms,
+                    first_entry, last_entry, last_exit
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    dwell.zone_id.to_string(),
+                    dwell.mission_id.to_string(),
+                    dwell.drone_id,
+                    dwell.visits,
+                    dwell.time_inside_ms,
+                    dwell.first_entry.map(millis),
+                    dwell.last_entry.map(millis),
+                    dwell.last_exit.map(millis),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn dwell_for_zone(&self, zone_id: uuid::Uuid, mission_id: &MissionId) -> DbResult<Vec<ZoneDwellRecord>> {
+        let mission_id = mission_id.0;
+
+        self.call(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT drone_id, visits, time_inside_ms, first_entry, last_entry, last_exit \
+                 FROM zone_dwell WHERE zone_id = ?1 AND mission_id = ?2 ORDER BY drone_id ASC",
+            )?;
+            let rows = stmt
+                .query_map(params![zone_id.to_string(), mission_id.to_string()], |row| {
+                    Ok(ZoneDwellRecord {
+                        zone_id,
+                        mission_id,
+                        drone_id: row.get(0)?,
+                        visits: row.get(1)?,
+                        time_inside_ms: row.get(2)?,
+                        first_entry: row.get::<_, Option<i64>>(3)?.map(from_millis),
+                        last_entry: row.get::<_, Option<i64>>(4)?.map(from_millis),
+                        last_exit: row.get::<_, Option<i64>>(5)?.map(from_millis),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(rows)
+        })
+        .await
+    }
+}
+
+/// `WHERE` clause selecting a table's expired rows (`?1` = cutoff millis)
+fn expired_rows(table: RetentionTable) -> String {
+    let mut clause = format!("{} < ?1", table.time_column());
+    if table == RetentionTable::ScheduledCommands {
+        clause.push_str(" AND state != 'pending'");
+    }
+    clause
+}
+
+#[async_trait]
+impl RetentionStore for SqliteStore {
+    fn ttl_supported(&self, _table: RetentionTable) -> bool {
+        false
+    }
+
+    async fn set_table_ttl(&self, table: RetentionTable, _ttl: std::time::Duration) -> DbResult<()> {
+        Err(DbError::Configuration(format!(
+            "SQLite has no table TTLs; {} is purged instead",
+            table.table_name()
+        )))
+    }
+
+    async fn count_expired(&self, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64> {
+        self.call(move |conn| {
+            let count: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE {}", table.table_name(), expired_rows(table)),
+                params![millis(cutoff)],
+                |row| row.get(0),
+            )?;
+            Ok(count as u64 + expired_compact_samples(conn, table, cutoff)?)
+        })
+        .await
+    }
+
+    async fn purge_expired(&self, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64> {
+        self.call(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            let deleted = tx.execute(
+                &format!("DELETE FROM {} WHERE {}", table.table_name(), expired_rows(table)),
+                params![millis(cutoff)],
+            )?;
+            let samples = expired_compact_samples(&tx, table, cutoff)?;
+            if samples > 0 {
+                tx.execute(
+                    "DELETE FROM drone_telemetry_compact WHERE bucket_end < ?1",
+                    params![millis(cutoff)],
+                )?;
+            }
+            tx.commit()?;
+            Ok(deleted as u64 + samples)
+        })
+        .await
+    }
+}
+
+/// Samples in compact telemetry buckets that expired entirely (telemetry only)
+fn expired_compact_samples(conn: &Connection, table: RetentionTable, cutoff: DateTime<Utc>) -> DbResult<u64> {
+    if table != RetentionTable::Telemetry {
+        return Ok(0);
+    }
+    let samples: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(sample_count), 0) FROM drone_telemetry_compact WHERE bucket_end < ?1",
+        params![millis(cutoff)],
+        |row| row.get(0),
+    )?;
+    Ok(samples as u64)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{HaloColor, TransportKind};
+    use futures::TryStreamExt;
+
+    #[tokio::test]
+    async fn test_telemetry_roundtrip_and_range() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let start = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+
+        for i in 0..5 {
+            let telemetry = Telemetry {
+                battery_level: 90 - i as u8,
+                timestamp: start + chrono::Duration::seconds(i),
+                ..Default::default()
+            };
+            let position = GeoPosition::new(34.5 + i as f64 * 0.01, 69.2, 3000.0);
+            TelemetryStore::insert(&store, &drone_id, &position, &telemetry, None)
+                .await
+                .unwrap();
+        }
+
+        let (position, telemetry) = store.get_latest(&drone_id).await.unwrap().unwrap();
+        assert_eq!(telemetry.battery_level, 86);
+        assert!((position.latitude - 34.54).abs() < 1e-9);
+
+        let records: Vec<TelemetryRecord> = TelemetryStore::stream_range(
+            &store,
+            &drone_id,
+            start + chrono::Duration::seconds(1),
+            start + chrono::Duration::seconds(3),
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].battery_level, 89);
+        assert_eq!(records[2].battery_level, 87);
+    }
+
+    #[tokio::test]
+    async fn test_compact_telemetry_history() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let start = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let insert = |store: SqliteStore, second: i64| {
+            let drone_id = drone_id.clone();
+            async move {
+                let telemetry = Telemetry {
+                    battery_level: 90,
+                    timestamp: start + chrono::Duration::seconds(second),
+                    ..Default::default()
+                };
+                let position = GeoPosition::new(34.5 + second as f64 * 1e-4, 69.2, 3000.0);
+                TelemetryStore::insert(&store, &drone_id, &position, &telemetry, None)
+                    .await
+                    .unwrap();
+            }
+        };
+
+        // Ten seconds in rows, then 110 seconds compact across three buckets
+        for second in 0..10 {
+            insert(store.clone(), second).await;
+        }
+        let store = store.with_telemetry_storage(TelemetryStorage::Compact);
+        for second in 10..120 {
+            insert(store.clone(), second).await;
+        }
+        let buckets: i64 = store
+            .call(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM drone_telemetry_compact", [], |r| r.get(0))?))
+            .await
+            .unwrap();
+        assert_eq!(buckets, 3);
+
+        let history = store.get_history(&drone_id, 15).await.unwrap();
+        assert_eq!(history.len(), 15);
+        assert_eq!(history[0].1.timestamp, start + chrono::Duration::seconds(119));
+        assert!((history[0].0.latitude - 34.5119).abs() < 1e-6);
+
+        // The range spans both layouts and returns each sample once, in order
+        let records: Vec<TelemetryRecord> = TelemetryStore::stream_range(
+            &store,
+            &drone_id,
+            start + chrono::Duration::seconds(5),
+            start + chrono::Duration::seconds(64),
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(records.len(), 60);
+        assert!(records.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        // Buckets (20 s into a minute at `start`) are purged once their last sample expires
+        let cutoff = start + chrono::Duration::seconds(90);
+        assert_eq!(store.count_expired(RetentionTable::Telemetry, cutoff).await.unwrap(), 10 + 30);
+        assert_eq!(store.purge_expired(RetentionTable::Telemetry, cutoff).await.unwrap(), 40);
+        assert_eq!(store.get_history(&drone_id, 200).await.unwrap().len(), 80);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert_records() {
+        let start = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let record = |drone: &str, second: i64, battery: i32| TelemetryRecord {
+            drone_id: drone.to_string(),
+            timestamp: start + chrono::Duration::seconds(second),
+            latitude: 34.5,
+            longitude: 69.2,
+            altitude: 3000.0,
+            heading: 90.0,
+            speed: 300.0,
+            battery_level: battery,
+            fuel_level: 80,
+            system_health: 100,
+            status: Some("MOVING".into()),
+            armed: Some(true),
+            temperature: None,
+            signal_strength: Some(95),
+            mission_id: None,
+            gimbal: (second % 2 == 0).then_some(GimbalState { pan: 15.5, tilt: -45.0, zoom: 2.0 }),
+        };
+
+        for storage in [TelemetryStorage::Row, TelemetryStorage::Compact] {
+            let store = SqliteStore::open_in_memory().unwrap().with_telemetry_storage(storage);
+            let batch: Vec<_> = (0..90).map(|s| record("REAPER-01", s, 90)).chain([record("REAPER-02", 0, 70)]).collect();
+            store.insert_records(batch).await.unwrap();
+            // Re-importing a sample replaces it
+            store.insert_records(vec![record("REAPER-01", 89, 50)]).await.unwrap();
+
+            let history = store.get_history(&DroneId::new("REAPER-01"), 200).await.unwrap();
+            assert_eq!(history.len(), 90, "{:?}", storage);
+            assert_eq!(history[0].1.battery_level, 50);
+            assert!(history[0].1.gimbal.is_none());
+            assert_eq!(history[1].1.gimbal.map(|g| g.tilt), Some(-45.0), "{:?}", storage);
+            assert_eq!(store.get_history(&DroneId::new("REAPER-02"), 10).await.unwrap().len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gimbal_columns_are_added_to_older_files() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE drone_telemetry (drone_id TEXT NOT NULL, timestamp INTEGER NOT NULL, latitude REAL, longitude REAL, altitude REAL, heading REAL, speed REAL, battery_level INTEGER, fuel_level INTEGER, system_health INTEGER, status TEXT, armed INTEGER, temperature REAL, signal_strength INTEGER, mission_id TEXT, PRIMARY KEY (drone_id, timestamp));").unwrap();
+        let store = SqliteStore::init(conn).unwrap();
+
+        let telemetry = Telemetry {
+            gimbal: Some(GimbalState { pan: 5.0, tilt: -20.0, zoom: 3.0 }),
+            ..Default::default()
+        };
+        let drone_id = DroneId::new("REAPER-01");
+        TelemetryStore::insert(&store, &drone_id, &GeoPosition::new(34.5, 69.2, 3000.0), &telemetry, None).await.unwrap();
+        let (_, latest) = store.get_latest(&drone_id).await.unwrap().unwrap();
+        assert_eq!(latest.gimbal, telemetry.gimbal);
+    }
+
+    #[tokio::test]
+    async fn test_mission_status_update() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mission = Mission::new("Operation Test");
+
+        MissionStore::create(&store, &mission).await.unwrap();
+        store.update_status(&mission.id, "Active").await.unwrap();
+
+        // Only one of two racing operators wins the transition
+        assert!(store.transition_status(&mission.id, "Active", "Paused").await.unwrap());
+        assert!(!store.transition_status(&mission.id, "Active", "Aborted").await.unwrap());
+        store.update_status(&mission.id, "Active").await.unwrap();
+
+        let loaded = store.get(&mission.id).await.unwrap().unwrap();
+        assert_eq!(loaded.name, "Operation Test");
+        assert_eq!(loaded.status, MissionStatus::Active);
+        assert!(store.get(&MissionId::new()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_threshold_overrides_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let overrides = ThresholdOverrides {
+            battery_critical: Some(20),
+            ..Default::default()
+        };
+
+        store
+            .set_alert_thresholds(&DroneId::new("REAPER-01"), &overrides)
+            .await
+            .unwrap();
+        store
+            .set_type_alert_thresholds(&DroneType::Rq4GlobalHawk, &overrides)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get_alert_thresholds().await.unwrap(),
+            vec![(DroneId::new("REAPER-01"), overrides)]
+        );
+        assert_eq!(
+            store.get_type_alert_thresholds().await.unwrap(),
+            vec![(DroneType::Rq4GlobalHawk, overrides)]
+        );
+
+        // Clearing stores NULL, which is skipped on load
+        store
+            .set_alert_thresholds(&DroneId::new("REAPER-01"), &ThresholdOverrides::default())
+            .await
+            .unwrap();
+        assert!(store.get_alert_thresholds().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transport_binding_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let binding = TransportBinding::new(TransportKind::Mavlink).with_endpoint("10.0.0.5:14550");
+
+        store.set_transport_binding(&drone_id, Some(&binding)).await.unwrap();
+        assert_eq!(
+            store.get_transport_bindings().await.unwrap(),
+            vec![(drone_id.clone(), binding)]
+        );
+
+        store.set_transport_binding(&drone_id, None).await.unwrap();
+        assert!(store.get_transport_bindings().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drone_marking_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let marking = DroneMarking::new(HaloColor::CYAN).with_icon("reaper-lead");
+
+        store.set_drone_marking(&drone_id, Some(&marking)).await.unwrap();
+        assert_eq!(store.get_drone_markings().await.unwrap(), vec![(drone_id.clone(), marking)]);
+
+        store.set_drone_marking(&drone_id, None).await.unwrap();
+        assert!(store.get_drone_markings().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handoff_owner_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let owner = HandoffOwnerRecord {
+            handoff_id: uuid::Uuid::new_v4(),
+            station: "gcs-south".into(),
+            url: Some("https://gcs-south.example:3000".into()),
+            handed_off_at: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+        };
+
+        store.set_handoff_owner(&drone_id, Some(&owner)).await.unwrap();
+        assert_eq!(store.get_handoff_owners().await.unwrap(), vec![(drone_id.clone(), owner)]);
+
+        store.set_handoff_owner(&drone_id, None).await.unwrap();
+        assert!(store.get_handoff_owners().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_source_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+
+        store.set_telemetry_source(&drone_id, Some(TelemetrySource::P2p)).await.unwrap();
+        assert_eq!(
+            store.get_telemetry_sources().await.unwrap(),
+            vec![(drone_id.clone(), TelemetrySource::P2p)]
+        );
+
+        store.set_telemetry_source(&drone_id, None).await.unwrap();
+        assert!(store.get_telemetry_sources().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_gaps_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mission_id = MissionId::new();
+        let start = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+
+        for (drone_id, offset) in [("REAPER-02", 30), ("REAPER-01", 10)] {
+            let gap = TelemetryGapRecord {
+                mission_id: mission_id.0,
+                drone_id: drone_id.to_string(),
+                gap_start: start + chrono::Duration::seconds(offset),
+                gap_end: start + chrono::Duration::seconds(offset + 12),
+                duration_ms: 12_000,
+            };
+            store.record_gap(&gap).await.unwrap();
+        }
+
+        let gaps = store.gaps_for_mission(&mission_id).await.unwrap();
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].drone_id, "REAPER-01");
+        assert_eq!(gaps[1].gap_end, start + chrono::Duration::seconds(42));
+        assert!(store.gaps_for_mission(&MissionId::new()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_newest_first() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let start = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let record = TelemetryRecord::new(
+            &drone_id,
+            &GeoPosition::new(34.5, 69.2, 3000.0),
+            &Telemetry::default(),
+            drone_core::DroneStatus::Moving,
+            true,
+            None,
+        );
+
+        for (reason, offset) in [("write failed", 0), ("breaker open", 5)] {
+            let letter = DeadLetterRecord::new(&record, reason, start + chrono::Duration::seconds(offset));
+            store.quarantine(&letter).await.unwrap();
+        }
+
+        let letters = store.dead_letters(&drone_id, 10).await.unwrap();
+        assert_eq!(letters.len(), 2);
+        assert_eq!(letters[0].reason, "breaker open");
+        assert_eq!(serde_json::from_str::<TelemetryRecord>(&letters[1].payload).unwrap(), record);
+        assert_eq!(store.dead_letters(&drone_id, 1).await.unwrap().len(), 1);
+        assert!(store.dead_letters(&DroneId::new("REAPER-02"), 10).await.unwrap().is_empty());
+
+        // Over-long IDs are cut to whole characters within the byte limit
+        let mut long = record.clone();
+        long.drone_id = "é".repeat(crate::MAX_DEAD_LETTER_ID_LEN);
+        let letter = DeadLetterRecord::new(&long, "drone_id too long", start);
+        assert_eq!(letter.drone_id, "é".repeat(crate::MAX_DEAD_LETTER_ID_LEN / 2));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_commands_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let created = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let mut record = ScheduledCommandRecord {
+            id: uuid::Uuid::new_v4(),
+            state: "pending".into(),
+            payload: r#"{"trigger":{"type":"at"}}"#.into(),
+            created_at: created,
+            updated_at: created,
+        };
+        store.save_scheduled_command(&record).await.unwrap();
+
+        // Saving again replaces the row
+        record.state = "fired".into();
+        record.updated_at = created + chrono::Duration::minutes(5);
+        store.save_scheduled_command(&record).await.unwrap();
+
+        assert_eq!(store.scheduled_commands().await.unwrap(), vec![record]);
+    }
+
+    #[tokio::test]
+    async fn test_drone_groups_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let created = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let group = |name: &str, members: &[&str], role: Option<&str>| DroneGroupRecord {
+            name: name.into(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+            role: role.map(str::to_string),
+            description: None,
+            created_at: created,
+            updated_at: created,
+        };
+        let lead = group("lead", &["REAPER-01", "REAPER-02"], None);
+        let escorts = group("escorts", &[], Some("ESCORT"));
+        store.save_group(&lead).await.unwrap();
+        store.save_group(&escorts).await.unwrap();
+        assert_eq!(store.groups().await.unwrap(), vec![escorts.clone(), lead]);
+
+        store.delete_group("lead").await.unwrap();
+        assert_eq!(store.groups().await.unwrap(), vec![escorts]);
+    }
+
+    #[tokio::test]
+    async fn test_drone_snapshot_replaces_earlier() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let created = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let drone_id = DroneId::new("REAPER-07");
+        assert_eq!(store.snapshot(&drone_id).await.unwrap(), None);
+        let mut snapshot = DroneSnapshotRecord {
+            drone_id: drone_id.as_str().into(),
+            snapshot: "{}".into(),
+            last_update: created,
+            evicted_at: created + chrono::Duration::hours(24),
+        };
+        store.save_snapshot(&snapshot).await.unwrap();
+        snapshot.snapshot = r#"{"waypoint_index":3}"#.into();
+        store.save_snapshot(&snapshot).await.unwrap();
+        assert_eq!(store.snapshot(&drone_id).await.unwrap(), Some(snapshot));
+    }
+
+    #[tokio::test]
+    async fn test_push_subscription_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let created = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let mut subscription = PushSubscriptionRecord {
+            id: uuid::Uuid::new_v4(),
+            user_id: "ops-lead".into(),
+            platform: "fcm".into(),
+            device_token: "token-1".into(),
+            preferences: "{}".into(),
+            created_at: created,
+        };
+        store.save_subscription(&subscription).await.unwrap();
+        subscription.preferences = r#"{"min_severity":"EMERGENCY"}"#.into();
+        store.save_subscription(&subscription).await.unwrap();
+        assert_eq!(store.subscriptions().await.unwrap(), vec![subscription.clone()]);
+
+        store.delete_subscription(subscription.id).await.unwrap();
+        assert!(store.subscriptions().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alert_rule_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let rule = AlertRuleRecord {
+            id: uuid::Uuid::new_v4(),
+            name: "Low battery far from base".into(),
+            condition: r#"{"all":[{"field":"battery_level","op":"lt","value":40.0}]}"#.into(),
+            severity: "CRITICAL".into(),
+            message: None,
+            enabled: true,
+            created_at: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+            updated_at: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+        };
+        AlertStore::save_rule(&store, &rule).await.unwrap();
+        let disabled = AlertRuleRecord { enabled: false, ..rule.clone() };
+        AlertStore::save_rule(&store, &disabled).await.unwrap();
+        assert_eq!(AlertStore::rules(&store).await.unwrap(), vec![disabled]);
+
+        AlertStore::delete_rule(&store, rule.id).await.unwrap();
+        assert!(AlertStore::rules(&store).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_event_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let registered = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let schema = CustomEventSchemaRecord {
+            event_type: "acme.SENSOR_POD_STATUS".into(),
+            schema: r#"{"type":"object"}"#.into(),
+            description: Some("Sensor pod health".into()),
+            registered_at: registered,
+        };
+        store.save_schema(&schema).await.unwrap();
+        assert_eq!(store.schemas().await.unwrap(), vec![schema.clone()]);
+
+        let event = CustomEventRecord {
+            event_type: schema.event_type.clone(),
+            event_time: registered + chrono::Duration::seconds(5),
+            event_id: uuid::Uuid::new_v4(),
+            drone_id: Some("REAPER-01".into()),
+            mission_id: None,
+            value: r#"{"pod":"left"}"#.into(),
+        };
+        store.insert_event(&event).await.unwrap();
+        let events: Vec<_> = CustomEventStore::stream_range(
+            &store,
+            &schema.event_type,
+            registered,
+            registered + chrono::Duration::minutes(1),
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(events, vec![event]);
+
+        store.delete_schema(&schema.event_type).await.unwrap();
+        assert!(store.schemas().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lease_takeover_after_expiry() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let t0 = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let ttl = std::time::Duration::from_secs(10);
+        let grace = std::time::Duration::from_secs(2);
+
+        let lease = store.try_acquire("api-leader", "a", t0, ttl, grace).await.unwrap();
+        assert!(lease.is_held_by("a"));
+
+        // Held and unexpired: b is refused, a renews keeping its start time
+        let later = t0 + chrono::Duration::seconds(5);
+        assert!(store.try_acquire("api-leader", "b", later, ttl, grace).await.unwrap().is_held_by("a"));
+        let renewed = store.try_acquire("api-leader", "a", later, ttl, grace).await.unwrap();
+        assert_eq!(renewed.acquired_at, t0);
+        assert_eq!(renewed.expires_at, later + chrono::Duration::seconds(10));
+
+        // a stops renewing; b takes over once the lease has run out for
+        // longer than the allowed clock skew
+        let lapsed = later + chrono::Duration::seconds(11);
+        assert!(store.try_acquire("api-leader", "b", lapsed, ttl, grace).await.unwrap().is_held_by("a"));
+        let expired = later + chrono::Duration::seconds(13);
+        let taken = store.try_acquire("api-leader", "b", expired, ttl, grace).await.unwrap();
+        assert!(taken.is_held_by("b"));
+        assert_eq!(taken.acquired_at, expired);
+
+        // Releasing someone else's lease is a no-op
+        store.release("api-leader", "a").await.unwrap();
+        assert!(store.try_acquire("api-leader", "a", expired, ttl, grace).await.unwrap().is_held_by("b"));
+        store.release("api-leader", "b").await.unwrap();
+        assert!(store.try_acquire("api-leader", "a", expired, ttl, grace).await.unwrap().is_held_by("a"));
+    }
+
+    #[tokio::test]
+    async fn test_waypoint_attachment_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let attachment = WaypointAttachmentRecord {
+            id: uuid::Uuid::new_v4(),
+            mission_id: uuid::Uuid::new_v4(),
+            waypoint_id: "WP03".into(),
+            file_name: "bridge.jpg".into(),
+            content_type: "image/jpeg".into(),
+            size_bytes: 48_213,
+            object_key: "attachments/wp03/bridge.jpg".into(),
+            threat_level: Some("HIGH".into()),
+            notes: None,
+            uploaded_by: Some("intel-1".into()),
+            created_at: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+        };
+        WaypointStore::save_attachment(&store, &attachment).await.unwrap();
+        assert_eq!(WaypointStore::attachments(&store).await.unwrap(), vec![attachment.clone()]);
+
+        WaypointStore::delete_attachment(&store, attachment.id).await.unwrap();
+        assert!(WaypointStore::attachments(&store).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tracking_range() {
+        use drone_core::BoundingBox;
+
+        let store = SqliteStore::open_in_memory().unwrap();
+        let t0 = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let result = |drone: &str, offset_ms: i64| {
+            let mut result = TrackingResult::new(DroneId::new(drone), 1, BoundingBox::new(10, 20, 24, 24));
+            result.frame_timestamp = t0 + chrono::Duration::milliseconds(offset_ms);
+            result
+        };
+        let results = [result("REAPER-02", 500), result("REAPER-01", 500), result("REAPER-01", 0), result("REAPER-01", 2000)];
+        TrackingStore::insert_batch(&store, &results).await.unwrap();
+
+        let found: Vec<TrackingResult> = TrackingStore::stream_range(&store, t0, t0 + chrono::Duration::seconds(1))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let keys: Vec<_> = found.iter().map(|r| (r.drone_id.as_str(), r.frame_timestamp - t0)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("REAPER-01", chrono::Duration::zero()),
+                ("REAPER-01", chrono::Duration::milliseconds(500)),
+                ("REAPER-02", chrono::Duration::milliseconds(500)),
+            ]
+        );
+        assert_eq!((found[0].bbox.x, found[0].bbox.width), (10, 24));
+    }
+
+    #[tokio::test]
+    async fn test_cv_tuning_replaces_earlier() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        assert_eq!(store.cv_tuning().await.unwrap(), None);
+        let mut tuning = CvTuningRecord {
+            revision: 1,
+            tuning: "{}".into(),
+            updated_at: Utc.timestamp_millis_opt(1_700_000_000_000).unwrap(),
+        };
+        store.save_cv_tuning(&tuning).await.unwrap();
+        tuning.revision = 2;
+        store.save_cv_tuning(&tuning).await.unwrap();
+        assert_eq!(store.cv_tuning().await.unwrap(), Some(tuning));
+    }
+
+    #[tokio::test]
+    async fn test_zone_dwell_roundtrip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let created = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let zone = ZoneRecord {
+            id: uuid::Uuid::new_v4(),
+            name: "Objective Alpha".into(),
+            vertices: "[[34.5,69.1],[34.6,69.1],[34.6,69.2]]".into(),
+            max_altitude: None,
+            created_at: created,
+        };
+        store.save_zone(&zone).await.unwrap();
+        assert_eq!(store.zones().await.unwrap(), vec![zone.clone()]);
+
+        let mission = MissionId::new();
+        let mut dwell = ZoneDwellRecord {
+            zone_id: zone.id,
+            mission_id: mission.0,
+            drone_id: "REAPER-01".into(),
+            visits: 1,
+            time_inside_ms: 0,
+            first_entry: Some(created),
+            last_entry: Some(created),
+            last_exit: None,
+        };
+        store.save_dwell(&dwell).await.unwrap();
+        dwell.time_inside_ms = 90_000;
+        dwell.last_exit = Some(created + chrono::Duration::seconds(90));
+        store.save_dwell(&dwell).await.unwrap();
+        assert_eq!(store.dwell_for_zone(zone.id, &mission).await.unwrap(), vec![dwell]);
+        assert!(store.dwell_for_zone(zone.id, &MissionId::new()).await.unwrap().is_empty());
+
+        store.delete_zone(zone.id).await.unwrap();
+        assert!(store.zones().await.unwrap().is_empty());
+        assert!(store.dwell_for_zone(zone.id, &mission).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//! Peer allow-list
+//!
+//! With the allow-list on, only peers registered against a drone may hold a
+//! connection or publish on the gossip topic. Peers are registered by public
+//! key and identified by the PeerId derived from it, so a node cannot pass
+//! as a registered peer without its private key. Rejected peers are
+//! disconnected, logged and kept in a short list for operators.
+
+use crate::{P2pError, P2pResult};
+
+use chrono::{DateTime, Utc};
+use drone_core::DroneId;
+use libp2p::identity::{ed25519, PublicKey};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Rejections kept for the allow-list view
+pub const REJECTION_LOG_LEN: usize = 100;
+
+/// A drone and the peer registered for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeerRegistration {
+    pub drone_id: DroneId,
+    pub peer_id: String,
+}
+
+/// A peer turned away by the allow-list
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerRejection {
+    pub peer_id: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Allow-list mode, registered peers and recent rejections
+#[derive(Debug, Clone, Serialize)]
+pub struct AllowListView {
+    pub enabled: bool,
+    pub registrations: Vec<PeerRegistration>,
+    /// Rejections since startup
+    pub rejected_total: u64,
+    /// Latest rejections, newest first
+    pub recent_rejections: Vec<PeerRejection>,
+}
+
+/// Bounded log of rejected peers
+#[derive(Debug, Default)]
+pub struct RejectionLog {
+    recent: VecDeque<PeerRejection>,
+    total: u64,
+}
+
+impl RejectionLog {
+    pub fn record(&mut self, rejection: PeerRejection) {
+        if self.recent.len() == REJECTION_LOG_LEN {
+            self.recent.pop_back();
+        }
+        self.recent.push_front(rejection);
+        self.total += 1;
+    }
+
+    /// Newest first
+    pub fn recent(&self) -> Vec<PeerRejection> {
+        self.recent.iter().cloned().collect()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// Parse a hex public key: 32 raw Ed25519 bytes or a protobuf-encoded
+/// libp2p public key, as printed by `libp2p-identity`
+pub fn parse_public_key(hex_key: &str) -> P2pResult<PublicKey> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|e| P2pError::InvalidKey(e.to_string()))?;
+    if bytes.len() == 32 {
+        return ed25519::PublicKey::try_from_bytes(&bytes)
+            .map(PublicKey::from)
+            .map_err(|e| P2pError::InvalidKey(e.to_string()));
+    }
+    PublicKey::try_decode_protobuf(&bytes).map_err(|e| P2pError::InvalidKey(e.to_string()))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::identity::Keypair;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_public_key_formats_and_rejection_log() {
+        let keypair = Keypair::generate_ed25519();
+        let public = keypair.public();
+        let peer_id = PeerId::from(public.clone());
+
+        let protobuf = hex::encode(public.encode_protobuf());
+        assert_eq!(PeerId::from(parse_public_key(&protobuf).unwrap()), peer_id);
+        let raw = hex::encode(public.clone().try_into_ed25519().unwrap().to_bytes());
+        assert_eq!(PeerId::from(parse_public_key(&raw).unwrap()), peer_id);
+        assert!(matches!(parse_public_key("not hex"), Err(P2pError::InvalidKey(_))));
+        assert!(matches!(parse_public_key("00ff"), Err(P2pError::InvalidKey(_))));
+
+        let mut log = RejectionLog::default();
+        for _ in 0..REJECTION_LOG_LEN + 5 {
+            log.record(PeerRejection {
+                peer_id: PeerId::random().to_string(),
+                at: Utc::now(),
+            });
+        }
+        assert_eq!(log.total(), REJECTION_LOG_LEN as u64 + 5);
+        assert_eq!(log.recent().len(), REJECTION_LOG_LEN);
+    }
+}
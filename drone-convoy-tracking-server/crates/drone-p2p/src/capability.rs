@@ -0,0 +1,179 @@
+//! Peer capability negotiation
+//!
+//! Peers advertise what they support in their `DiscoveryResponse`. The
+//! capability strings are free-form on the wire so older and newer builds
+//! can talk to each other; unknown strings are ignored. Encoding features
+//! are used only when both ends advertise them, while roles (relay,
+//! formation follower) describe the peer alone and steer routing.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A feature or role a peer can advertise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Capability {
+    /// Decodes deflate-compressed frames
+    SupportsCompression,
+    /// Decodes protobuf frames
+    SupportsProtobuf,
+    /// Forwards direct messages to drones it can reach
+    RelayCapable,
+    /// Flies formation positions from `FormationCommand`s
+    FormationFollower,
+    /// Decodes fixed-point positions in position updates
+    CompactPositions,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 5] = [
+        Self::SupportsCompression,
+        Self::SupportsProtobuf,
+        Self::RelayCapable,
+        Self::FormationFollower,
+        Self::CompactPositions,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SupportsCompression => "supports-compression",
+            Self::SupportsProtobuf => "supports-protobuf",
+            Self::RelayCapable => "relay-capable",
+            Self::FormationFollower => "formation-follower",
+            Self::CompactPositions => "compact-positions",
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Capability {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|c| c.as_str() == s).ok_or(())
+    }
+}
+
+/// Set of capabilities, serialized as the list of capability strings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<String>", into = "Vec<String>")]
+pub struct CapabilitySet(u8);
+
+impl CapabilitySet {
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn of(capabilities: &[Capability]) -> Self {
+        let mut set = Self::empty();
+        for capability in capabilities {
+            set.insert(*capability);
+        }
+        set
+    }
+
+    /// What this build can decode
+    ///
+    /// `supports-protobuf` is recognized in peer advertisements but no
+    /// protobuf codec is compiled in, so it is never advertised locally.
+    pub fn local() -> Self {
+        Self::of(&[Capability::SupportsCompression, Capability::CompactPositions])
+    }
+
+    /// Parse advertised strings, skipping ones this build does not know
+    pub fn from_advertised<S: AsRef<str>>(advertised: &[S]) -> Self {
+        let known: Vec<Capability> = advertised.iter().filter_map(|s| s.as_ref().parse().ok()).collect();
+        Self::of(&known)
+    }
+
+    /// Capability strings for a `DiscoveryResponse`
+    pub fn advertise(&self) -> Vec<String> {
+        self.iter().map(|c| c.as_str().to_string()).collect()
+    }
+
+    pub fn insert(&mut self, capability: Capability) {
+        self.0 |= capability.bit();
+    }
+
+    pub fn contains(&self, capability: Capability) -> bool {
+        self.0 & capability.bit() != 0
+    }
+
+    /// Capabilities both sides have
+    pub fn intersection(&self, other: CapabilitySet) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Capability> + '_ {
+        Capability::ALL.into_iter().filter(|c| self.contains(*c))
+    }
+}
+
+impl From<Vec<String>> for CapabilitySet {
+    fn from(advertised: Vec<String>) -> Self {
+        Self::from_advertised(&advertised)
+    }
+}
+
+impl From<CapabilitySet> for Vec<String> {
+    fn from(set: CapabilitySet) -> Self {
+        set.advertise()
+    }
+}
+
+/// Frame encoding chosen for a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireFormat {
+    /// Plain bincode
+    Bincode,
+    /// Bincode, deflate-compressed when that is smaller
+    Deflate,
+}
+
+impl WireFormat {
+    /// Best format both ends can decode
+    pub fn negotiate(local: CapabilitySet, remote: CapabilitySet) -> Self {
+        if local.intersection(remote).contains(Capability::SupportsCompression) {
+            Self::Deflate
+        } else {
+            Self::Bincode
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advertise_and_negotiate() {
+        let remote = CapabilitySet::from_advertised(&["relay-capable", "supports-compression", "warp-drive"]);
+        assert!(remote.contains(Capability::RelayCapable));
+        assert!(!remote.contains(Capability::FormationFollower));
+        assert_eq!(remote.advertise(), vec!["supports-compression", "relay-capable"]);
+
+        let json = serde_json::to_string(&remote).unwrap();
+        assert_eq!(json, r#"["supports-compression","relay-capable"]"#);
+        assert_eq!(serde_json::from_str::<CapabilitySet>(&json).unwrap(), remote);
+
+        // Compression needs both ends; a protobuf-only peer gets plain bincode
+        assert_eq!(WireFormat::negotiate(CapabilitySet::local(), remote), WireFormat::Deflate);
+        let protobuf_only = CapabilitySet::of(&[Capability::SupportsProtobuf]);
+        assert_eq!(WireFormat::negotiate(CapabilitySet::local(), protobuf_only), WireFormat::Bincode);
+    }
+}
@@ -28,6 +28,21 @@ pub enum P2pError {
 
     #[error("Protocol error: {0}")]
     Protocol(String),
+
+    #[error("Keystore error: {0}")]
+    Keystore(String),
+
+    #[error("Outbound WAL error: {0}")]
+    Wal(String),
+
+    #[error("Invalid public key: {0}")]
+    InvalidKey(String),
+
+    #[error("Link over budget, dropped {0}")]
+    OverBudget(String),
+
+    #[error("Peer not admitted by the allow-list: {0}")]
+    NotAdmitted(String),
 }
 
 impl P2pError {
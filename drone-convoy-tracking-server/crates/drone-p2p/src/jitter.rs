@@ -0,0 +1,175 @@
+//! Jitter buffer for gossiped position updates
+//!
+//! Gossip does not preserve order, so a drone's position updates can arrive
+//! shuffled and make it jump backwards on the map. Updates are held for a
+//! short window after arrival and released per drone in sender timestamp
+//! order. An update no newer than the last one released for its drone is
+//! stale and dropped.
+
+use crate::protocol::PositionUpdateData;
+use drone_core::DroneId;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Jitter buffer configuration
+#[derive(Debug, Clone)]
+pub struct JitterConfig {
+    /// How long an update is held after arrival
+    pub window: Duration,
+    /// Held updates per drone before the oldest is released early
+    pub max_pending: usize,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(200),
+            max_pending: 32,
+        }
+    }
+}
+
+/// Jitter buffer counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JitterStats {
+    pub received: u64,
+    pub released: u64,
+    /// Arrived after a newer update of the same drone and were put back in order
+    pub reordered: u64,
+    /// Arrived after a newer update had already been released
+    pub dropped_stale: u64,
+    /// Held updates right now
+    pub pending: usize,
+}
+
+#[derive(Debug)]
+struct Held {
+    arrived_at: DateTime<Utc>,
+    update: PositionUpdateData,
+}
+
+#[derive(Debug, Default)]
+struct DroneQueue {
+    pending: BTreeMap<(DateTime<Utc>, Uuid), Held>,
+    last_released: Option<DateTime<Utc>>,
+}
+
+/// Per-drone reordering of position updates
+#[derive(Debug, Default)]
+pub struct JitterBuffer {
+    config: JitterConfig,
+    drones: HashMap<DroneId, DroneQueue>,
+    stats: JitterStats,
+}
+
+impl JitterBuffer {
+    pub fn new(config: JitterConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Hold an update sent at `sent_at` (message ID breaks timestamp ties)
+    pub fn push(&mut self, message_id: Uuid, sent_at: DateTime<Utc>, update: PositionUpdateData, arrived_at: DateTime<Utc>) {
+        self.stats.received += 1;
+        let queue = self.drones.entry(update.drone_id.clone()).or_default();
+
+        if queue.last_released.is_some_and(|last| sent_at <= last) {
+            self.stats.dropped_stale += 1;
+            return;
+        }
+        if queue.pending.keys().next_back().is_some_and(|(latest, _)| sent_at < *latest) {
+            self.stats.reordered += 1;
+        }
+        queue.pending.insert((sent_at, message_id), Held { arrived_at, update });
+        self.stats.pending += 1;
+    }
+
+    /// Updates ready at `now`, in timestamp order per drone
+    ///
+    /// A drone's updates are released from the oldest while its hold
+    /// window has passed, or while the drone holds more than `max_pending`.
+    pub fn release(&mut self, now: DateTime<Utc>) -> Vec<PositionUpdateData> {
+        let window = chrono::Duration::from_std(self.config.window).unwrap_or_default();
+        let mut released = Vec::new();
+
+        for queue in self.drones.values_mut() {
+            loop {
+                let overflowing = queue.pending.len() > self.config.max_pending;
+                let Some(entry) = queue.pending.first_entry() else {
+                    break;
+                };
+                if !overflowing && now - entry.get().arrived_at < window {
+                    break;
+                }
+                let ((sent_at, _), held) = entry.remove_entry();
+                queue.last_released = Some(sent_at);
+                released.push(held.update);
+            }
+        }
+
+        self.stats.released += released.len() as u64;
+        self.stats.pending -= released.len();
+        released
+    }
+
+    pub fn stats(&self) -> JitterStats {
+        self.stats.clone()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use drone_core::{GeoPosition, Telemetry};
+
+    fn update(drone: &str, latitude: f64) -> PositionUpdateData {
+        PositionUpdateData {
+            drone_id: DroneId::new(drone),
+            position: GeoPosition::new(latitude, 69.2, 3000.0),
+            telemetry: Telemetry::default(),
+        }
+    }
+
+    #[test]
+    fn test_reorders_within_window_and_drops_stale() {
+        let mut buffer = JitterBuffer::new(JitterConfig {
+            window: Duration::from_millis(200),
+            max_pending: 3,
+        });
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |ms: i64| start + chrono::Duration::milliseconds(ms);
+        let latitudes = |updates: Vec<PositionUpdateData>| updates.iter().map(|u| u.position.latitude).collect::<Vec<_>>();
+
+        // Sent at 0, 100, 200 ms; the 100 ms update arrives last
+        buffer.push(Uuid::new_v4(), at(0), update("REAPER-01", 34.0), at(10));
+        buffer.push(Uuid::new_v4(), at(200), update("REAPER-01", 34.2), at(20));
+        buffer.push(Uuid::new_v4(), at(100), update("REAPER-01", 34.1), at(30));
+        assert!(buffer.release(at(100)).is_empty());
+        assert_eq!(latitudes(buffer.release(at(210))), vec![34.0]);
+        assert_eq!(latitudes(buffer.release(at(230))), vec![34.1, 34.2]);
+
+        // Older than the last released update
+        buffer.push(Uuid::new_v4(), at(150), update("REAPER-01", 34.15), at(240));
+
+        // Overflow releases the oldest early
+        for (ms, lat) in [(300, 34.3), (400, 34.4), (500, 34.5), (600, 34.6)] {
+            buffer.push(Uuid::new_v4(), at(ms), update("REAPER-01", lat), at(500));
+        }
+        assert_eq!(latitudes(buffer.release(at(510))), vec![34.3]);
+
+        let stats = buffer.stats();
+        assert_eq!((stats.received, stats.released, stats.pending), (8, 4, 3));
+        assert_eq!((stats.reordered, stats.dropped_stale), (1, 1));
+    }
+}
@@ -0,0 +1,369 @@
+//! Persistent node identity
+//!
+//! Stores the libp2p keypair on disk so the local PeerId survives restarts,
+//! optionally encrypted with a passphrase (Argon2id + ChaCha20-Poly1305).
+//! Drone ↔ peer registrations are kept next to the key file and migrated
+//! when the local identity changes.
+
+use crate::{P2pError, P2pResult};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use drone_core::DroneId;
+use libp2p::identity::Keypair;
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+const KEYSTORE_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Keystore passphrase (redacted in debug output)
+#[derive(Clone)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    pub fn new(passphrase: impl Into<String>) -> Self {
+        Self(passphrase.into())
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(***)")
+    }
+}
+
+/// On-disk key file
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    peer_id: String,
+    /// Hex-encoded protobuf keypair, or ciphertext when encrypted
+    key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption: Option<Encryption>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Encryption {
+    salt: String,
+    nonce: String,
+}
+
+/// On-disk drone ↔ peer registrations
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistrationFile {
+    /// Local peer ID the registrations were written under
+    local_peer_id: Option<String>,
+    drones: HashMap<DroneId, String>,
+}
+
+/// File-based keystore for the local node identity
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    path: PathBuf,
+    passphrase: Option<Passphrase>,
+}
+
+impl Keystore {
+    /// Create a keystore backed by the key file at `path`
+    pub fn new(path: impl Into<PathBuf>, passphrase: Option<Passphrase>) -> Self {
+        Self {
+            path: path.into(),
+            passphrase,
+        }
+    }
+
+    /// Path of the key file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the registrations file kept next to the key file
+    pub fn registrations_path(&self) -> PathBuf {
+        self.path.with_extension("peers.json")
+    }
+
+    /// Load the stored keypair, generating and saving a new one if none exists
+    pub fn load_or_generate(&self) -> P2pResult<Keypair> {
+        if let Some(keypair) = self.load()? {
+            info!("Loaded node identity from {}", self.path.display());
+            return Ok(keypair);
+        }
+
+        let keypair = Keypair::generate_ed25519();
+        self.save(&keypair)?;
+        info!("Generated new node identity at {}", self.path.display());
+        Ok(keypair)
+    }
+
+    /// Load the stored keypair, if the key file exists
+    pub fn load(&self) -> P2pResult<Option<Keypair>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(P2pError::Keystore(e.to_string())),
+        };
+
+        let file: KeyFile = serde_json::from_str(&contents)
+            .map_err(|e| P2pError::Serialization(e.to_string()))?;
+        if file.version != KEYSTORE_VERSION {
+            return Err(P2pError::Keystore(format!(
+                "unsupported keystore version {}",
+                file.version
+            )));
+        }
+
+        let ciphertext = decode_hex(&file.key)?;
+        let encoded = match (&file.encryption, &self.passphrase) {
+            (Some(enc), Some(passphrase)) => decrypt(passphrase, enc, &ciphertext)?,
+            (Some(_), None) => {
+                return Err(P2pError::Keystore(
+                    "keystore is encrypted but no passphrase was configured".into(),
+                ))
+            }
+            (None, _) => ciphertext,
+        };
+
+        let keypair = Keypair::from_protobuf_encoding(&encoded)
+            .map_err(|e| P2pError::Keystore(e.to_string()))?;
+
+        let peer_id = PeerId::from(keypair.public());
+        if peer_id.to_string() != file.peer_id {
+            return Err(P2pError::Keystore(format!(
+                "key file peer ID {} does not match its key ({})",
+                file.peer_id, peer_id
+            )));
+        }
+
+        // Upgrade plaintext key files once a passphrase is configured
+        if file.encryption.is_none() && self.passphrase.is_some() {
+            self.save(&keypair)?;
+            info!("Encrypted existing node identity at {}", self.path.display());
+        }
+
+        Ok(Some(keypair))
+    }
+
+    /// Write the keypair, encrypting it when a passphrase is configured
+    pub fn save(&self, keypair: &Keypair) -> P2pResult<()> {
+        let encoded = keypair
+            .to_protobuf_encoding()
+            .map_err(|e| P2pError::Keystore(e.to_string()))?;
+
+        let (key, encryption) = match &self.passphrase {
+            Some(passphrase) => {
+                let (ciphertext, encryption) = encrypt(passphrase, &encoded)?;
+                (hex::encode(ciphertext), Some(encryption))
+            }
+            None => (hex::encode(encoded), None),
+        };
+
+        let file = KeyFile {
+            version: KEYSTORE_VERSION,
+            peer_id: PeerId::from(keypair.public()).to_string(),
+            key,
+            encryption,
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| P2pError::Serialization(e.to_string()))?;
+
+        write_private(&self.path, json.as_bytes())
+    }
+
+    /// Load drone ↔ peer registrations, migrating entries that pointed at a
+    /// previous local identity to `local_peer_id`
+    pub fn load_registrations(&self, local_peer_id: &PeerId) -> P2pResult<HashMap<DroneId, PeerId>> {
+        let path = self.registrations_path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(P2pError::Keystore(e.to_string())),
+        };
+
+        let file: RegistrationFile = serde_json::from_str(&contents)
+            .map_err(|e| P2pError::Serialization(e.to_string()))?;
+
+        let local = local_peer_id.to_string();
+        let previous = file.local_peer_id.filter(|previous| *previous != local);
+
+        let mut registrations = HashMap::new();
+        let mut migrated = 0;
+        for (drone_id, peer) in file.drones {
+            let peer = if previous.as_deref() == Some(peer.as_str()) {
+                migrated += 1;
+                *local_peer_id
+            } else {
+                match peer.parse::<PeerId>() {
+                    Ok(peer) => peer,
+                    Err(e) => {
+                        warn!("Dropping registration for {}: {}", drone_id, e);
+                        continue;
+                    }
+                }
+            };
+            registrations.insert(drone_id, peer);
+        }
+
+        if previous.is_some() {
+            info!(
+                "Local identity changed; migrated {} drone registrations to {}",
+                migrated, local_peer_id
+            );
+            self.save_registrations(local_peer_id, &registrations)?;
+        }
+
+        Ok(registrations)
+    }
+
+    /// Persist drone ↔ peer registrations
+    pub fn save_registrations(
+        &self,
+        local_peer_id: &PeerId,
+        registrations: &HashMap<DroneId, PeerId>,
+    ) -> P2pResult<()> {
+        let file = RegistrationFile {
+            local_peer_id: Some(local_peer_id.to_string()),
+            drones: registrations
+                .iter()
+                .map(|(drone_id, peer)| (drone_id.clone(), peer.to_string()))
+                .collect(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| P2pError::Serialization(e.to_string()))?;
+
+        write_private(&self.registrations_path(), json.as_bytes())
+    }
+}
+
+fn derive_key(passphrase: &Passphrase, salt: &[u8]) -> P2pResult<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.0.as_bytes(), salt, &mut key)
+        .map_err(|e| P2pError::Keystore(e.to_string()))?;
+    Ok(key)
+}
+
+fn encrypt(passphrase: &Passphrase, plaintext: &[u8]) -> P2pResult<(Vec<u8>, Encryption)> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|e| P2pError::Keystore(e.to_string()))?;
+
+    Ok((
+        ciphertext,
+        Encryption {
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+        },
+    ))
+}
+
+fn decrypt(passphrase: &Passphrase, encryption: &Encryption, ciphertext: &[u8]) -> P2pResult<Vec<u8>> {
+    let salt = decode_hex(&encryption.salt)?;
+    let nonce = decode_hex(&encryption.nonce)?;
+    if nonce.len() != 12 {
+        return Err(P2pError::Keystore("invalid nonce length".into()));
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext)
+        .map_err(|_| P2pError::Keystore("wrong passphrase or corrupted keystore".into()))
+}
+
+fn decode_hex(value: &str) -> P2pResult<Vec<u8>> {
+    hex::decode(value).map_err(|e| P2pError::Serialization(e.to_string()))
+}
+
+/// Write a file readable only by the owner
+fn write_private(path: &Path, contents: &[u8]) -> P2pResult<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| P2pError::Keystore(e.to_string()))?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents).map_err(|e| P2pError::Keystore(e.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600))
+            .map_err(|e| P2pError::Keystore(e.to_string()))?;
+    }
+
+    fs::rename(&tmp, path).map_err(|e| P2pError::Keystore(e.to_string()))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("drone-keystore-test-{}", uuid::Uuid::new_v4()))
+            .join("identity.key")
+    }
+
+    #[test]
+    fn test_identity_survives_reload() {
+        let path = temp_path();
+        let keystore = Keystore::new(&path, None);
+
+        let first = PeerId::from(keystore.load_or_generate().unwrap().public());
+        let second = PeerId::from(keystore.load_or_generate().unwrap().public());
+        assert_eq!(first, second);
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_encrypted_keystore_requires_passphrase() {
+        let path = temp_path();
+        let keystore = Keystore::new(&path, Some(Passphrase::new("hunter2")));
+        let peer_id = PeerId::from(keystore.load_or_generate().unwrap().public());
+
+        assert!(fs::read_to_string(&path).unwrap().contains("\"encryption\""));
+        assert!(Keystore::new(&path, None).load().is_err());
+        assert!(Keystore::new(&path, Some(Passphrase::new("wrong"))).load().is_err());
+
+        let reloaded = keystore.load().unwrap().unwrap();
+        assert_eq!(PeerId::from(reloaded.public()), peer_id);
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_registrations_migrate_to_new_identity() {
+        let path = temp_path();
+        let keystore = Keystore::new(&path, None);
+        let old_local = PeerId::random();
+        let remote = PeerId::random();
+
+        let mut registrations = HashMap::new();
+        registrations.insert(DroneId::new("REAPER-01"), old_local);
+        registrations.insert(DroneId::new("REAPER-02"), remote);
+        keystore.save_registrations(&old_local, &registrations).unwrap();
+
+        let new_local = PeerId::random();
+        let loaded = keystore.load_registrations(&new_local).unwrap();
+        assert_eq!(loaded[&DroneId::new("REAPER-01")], new_local);
+        assert_eq!(loaded[&DroneId::new("REAPER-02")], remote);
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -8,27 +8,56 @@
 //! - Kademlia DHT for peer discovery
 //! - mDNS for local network discovery
 //! - Direct messaging between specific drones
+//! - Persistent node identity
+//! - Optional allow-list of peers registered against drones
+//! - Optional per-peer bandwidth shaping for constrained-link testing
+//! - Chunked, hash-checked mission route sync to drone agents
 
+pub mod allowlist;
+pub mod capability;
 pub mod error;
+pub mod jitter;
+pub mod keystore;
+pub mod mission_sync;
 pub mod network;
+pub mod partition;
 pub mod protocol;
+pub mod shaper;
+pub mod topology;
+pub mod wal;
 
+pub use allowlist::{AllowListView, PeerRegistration, PeerRejection};
+pub use capability::{Capability, CapabilitySet, WireFormat};
 pub use error::{P2pError, P2pResult};
-pub use network::DroneNetwork;
-pub use protocol::{DroneMessage, MessageType};
+pub use jitter::{JitterConfig, JitterStats};
+pub use keystore::{Keystore, Passphrase};
+pub use mission_sync::{MissionRoute, DEFAULT_CHUNK_SIZE};
+pub use network::{Connection, ConnectionEvent, ConnectionRoute, DroneNetwork, NetworkStats};
+pub use partition::{PartitionConfig, Reachability, ReachabilityChanges, ReachabilityView};
+pub use protocol::{DroneMessage, InboundMessage, MessageType};
+pub use shaper::{MessagePriority, PriorityStats, ShaperConfig, ShapingStats};
+pub use topology::{MeshTopology, PeerView};
+pub use wal::{OutboundWal, PendingMessage, SeenMessages, WalSync};
 
-use drone_core::{DroneId, GeoPosition, Telemetry};
+use allowlist::RejectionLog;
+use drone_core::{DroneId, GeoPosition, HealthReport, SimulationClock, SubsystemHealth, Telemetry};
+use jitter::JitterBuffer;
+use partition::{OutboundBuffer, PartitionDetector};
+use shaper::{BandwidthShaper, ShapeDecision};
+use protocol::{DiscoveryResponseData, FormationCommandData, PositionUpdateData};
+
+use chrono::{DateTime, Utc};
 use libp2p::{
-    gossipsub, identify, kad, mdns, noise, 
-    swarm::{NetworkBehaviour, SwarmEvent},
-    tcp, yamux, Multiaddr, PeerId, Swarm,
+    Multiaddr, PeerId,
 };
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
 /// P2P network configuration
 #[derive(Debug, Clone)]
@@ -43,6 +72,26 @@ pub struct P2pConfig {
     pub gossip_topic: String,
     /// Heartbeat interval
     pub heartbeat_interval: Duration,
+    /// Key file for a persistent node identity (None = ephemeral)
+    pub identity_path: Option<PathBuf>,
+    /// Passphrase used to encrypt the key file
+    pub identity_passphrase: Option<Passphrase>,
+    /// Partition detection and outbound buffering
+    pub partition: PartitionConfig,
+    /// Capabilities advertised in discovery responses
+    pub capabilities: CapabilitySet,
+    /// Reordering of incoming position updates
+    pub jitter: JitterConfig,
+    /// Write-ahead log for unacknowledged direct messages (None = in memory only)
+    pub wal_path: Option<PathBuf>,
+    /// Logged messages older than this are not replayed
+    pub wal_ttl: Duration,
+    /// Message IDs remembered for dropping redelivered copies
+    pub dedup_capacity: usize,
+    /// Only admit peers registered against a drone
+    pub allow_list: bool,
+    /// Link bandwidth limits (None = unshaped)
+    pub shaping: Option<ShaperConfig>,
 }
 
 impl Default for P2pConfig {
@@ -53,10 +102,62 @@ impl Default for P2pConfig {
             mdns_enabled: true,
             gossip_topic: "drone-convoy".into(),
             heartbeat_interval: Duration::from_secs(1),
+            identity_path: None,
+            identity_passphrase: None,
+            partition: PartitionConfig::default(),
+            capabilities: CapabilitySet::local(),
+            jitter: JitterConfig::default(),
+            wal_path: None,
+            wal_ttl: Duration::from_secs(600),
+            dedup_capacity: 4096,
+            allow_list: false,
+            shaping: None,
         }
     }
 }
 
+impl P2pConfig {
+    /// Default configuration with the identity taken from
+    /// `P2P_IDENTITY_PATH` / `P2P_IDENTITY_PASSPHRASE`, the outbound log
+    /// from `P2P_WAL_PATH` / `P2P_WAL_TTL_SECS`, the allow-list mode
+    /// from `P2P_ALLOW_LIST` and the link rate from `P2P_LINK_KBPS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            identity_path: std::env::var("P2P_IDENTITY_PATH").ok().map(PathBuf::from),
+            identity_passphrase: std::env::var("P2P_IDENTITY_PASSPHRASE")
+                .ok()
+                .map(Passphrase::new),
+            wal_path: std::env::var("P2P_WAL_PATH").ok().map(PathBuf::from),
+            wal_ttl: std::env::var("P2P_WAL_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.wal_ttl),
+            allow_list: std::env::var("P2P_ALLOW_LIST").is_ok_and(|v| v == "true" || v == "1"),
+            shaping: std::env::var("P2P_LINK_KBPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(ShaperConfig::new),
+            ..defaults
+        }
+    }
+}
+
+/// Health and traffic summary of the P2P layer
+#[derive(Debug, Clone, Serialize)]
+pub struct P2pStats {
+    #[serde(flatten)]
+    pub health: HealthReport,
+    pub network: NetworkStats,
+    pub jitter: JitterStats,
+    /// Outbound messages not yet acknowledged
+    pub unacknowledged: usize,
+    /// Peers turned away by the allow-list since startup
+    pub rejected_peers: u64,
+    pub shaping: ShapingStats,
+}
+
 /// Peer information
 #[derive(Debug, Clone)]
 pub struct PeerInfo {
@@ -64,6 +165,9 @@ pub struct PeerInfo {
     pub drone_id: Option<DroneId>,
     pub addresses: Vec<Multiaddr>,
     pub last_seen: chrono::DateTime<chrono::Utc>,
+    /// Capabilities from the peer's latest discovery response
+    pub capabilities: CapabilitySet,
+    pub formation_role: Option<String>,
 }
 
 /// P2P network manager
@@ -75,10 +179,38 @@ pub struct P2pManager {
     peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
     /// Drone ID to Peer ID mapping
     drone_peers: Arc<RwLock<HashMap<DroneId, PeerId>>>,
-    /// Message sender
+    /// Outgoing message sender
     message_tx: mpsc::Sender<DroneMessage>,
-    /// Message receiver
+    /// Outgoing message receiver, drained by the swarm
     message_rx: Arc<RwLock<Option<mpsc::Receiver<DroneMessage>>>>,
+    /// Incoming message sender, fed by the swarm
+    inbound_tx: mpsc::Sender<InboundMessage>,
+    /// Incoming message receiver
+    inbound_rx: Arc<RwLock<Option<mpsc::Receiver<InboundMessage>>>>,
+    /// Identity keystore (None when running with an ephemeral identity)
+    keystore: Option<Keystore>,
+    /// Drone reachability from direct contact and peer reports
+    partition: RwLock<PartitionDetector>,
+    /// Direct messages held for unreachable drones
+    outbound: RwLock<OutboundBuffer>,
+    /// Time source of reachability and the outbound buffer
+    clock: RwLock<Arc<SimulationClock>>,
+    /// Incoming position updates waiting to be released in order
+    jitter: Mutex<JitterBuffer>,
+    /// Last sequence number stamped on each drone's outgoing position updates
+    position_sequences: Mutex<HashMap<DroneId, u64>>,
+    /// Swarm connections and traffic counters
+    network: DroneNetwork,
+    /// Unacknowledged direct messages (None without `wal_path`)
+    wal: Option<Mutex<OutboundWal>>,
+    /// IDs of messages already handled
+    seen: Mutex<SeenMessages>,
+    /// Peers turned away by the allow-list
+    rejections: Mutex<RejectionLog>,
+    /// Start time and failed sends or log writes
+    health: SubsystemHealth,
+    /// Link token buckets (None = unshaped)
+    shaper: Option<Mutex<BandwidthShaper>>,
 }
 
 impl P2pManager {
@@ -86,28 +218,87 @@ impl P2pManager {
     pub async fn new(config: P2pConfig) -> P2pResult<Self> {
         info!("🌐 Initializing P2P network...");
 
-        // Generate keypair
-        let local_key = libp2p::identity::Keypair::generate_ed25519();
+        // Load the persistent identity, or generate an ephemeral one
+        let keystore = config
+            .identity_path
+            .as_ref()
+            .map(|path| Keystore::new(path, config.identity_passphrase.clone()));
+        let local_key = match &keystore {
+            Some(keystore) => keystore.load_or_generate()?,
+            None => libp2p::identity::Keypair::generate_ed25519(),
+        };
         let local_peer_id = PeerId::from(local_key.public());
         info!("Local peer ID: {}", local_peer_id);
 
+        let drone_peers = match &keystore {
+            Some(keystore) => keystore.load_registrations(&local_peer_id)?,
+            None => HashMap::new(),
+        };
+        if !drone_peers.is_empty() {
+            info!("Restored {} drone registrations", drone_peers.len());
+        }
+
         let (message_tx, message_rx) = mpsc::channel(1024);
+        let (inbound_tx, inbound_rx) = mpsc::channel(1024);
+        let partition = PartitionDetector::new(config.partition.clone());
+        let jitter = JitterBuffer::new(config.jitter.clone());
+        let network = DroneNetwork::new(config.clone());
+        let wal = match &config.wal_path {
+            Some(path) => {
+                let wal = OutboundWal::open(path, config.wal_ttl, Utc::now())?;
+                if !wal.pending().is_empty() {
+                    info!("{} unacknowledged outbound messages in {}", wal.pending().len(), path.display());
+                }
+                Some(Mutex::new(wal))
+            }
+            None => None,
+        };
+        let seen = SeenMessages::new(config.dedup_capacity);
+        let shaper = config.shaping.clone().map(|shaping| {
+            info!("Shaping P2P links to {} kbit/s", shaping.rate_kbps);
+            Mutex::new(BandwidthShaper::new(shaping))
+        });
 
         Ok(Self {
             config,
             local_peer_id,
             peers: Arc::new(RwLock::new(HashMap::new())),
-            drone_peers: Arc::new(RwLock::new(HashMap::new())),
+            drone_peers: Arc::new(RwLock::new(drone_peers)),
             message_tx,
             message_rx: Arc::new(RwLock::new(Some(message_rx))),
+            inbound_tx,
+            inbound_rx: Arc::new(RwLock::new(Some(inbound_rx))),
+            keystore,
+            partition: RwLock::new(partition),
+            outbound: RwLock::new(OutboundBuffer::new()),
+            clock: RwLock::new(Arc::new(SimulationClock::new())),
+            jitter: Mutex::new(jitter),
+            position_sequences: Mutex::new(HashMap::new()),
+            network,
+            wal,
+            seen: Mutex::new(seen),
+            rejections: Mutex::new(RejectionLog::default()),
+            health: SubsystemHealth::default(),
+            shaper,
         })
     }
 
+    /// Time buffered messages by `clock`, the one whose time is passed to
+    /// `observe` and `assess_reachability`
+    pub fn set_clock(&self, clock: Arc<SimulationClock>) {
+        *self.clock.write() = clock;
+    }
+
     /// Get local peer ID
     pub fn local_peer_id(&self) -> PeerId {
         self.local_peer_id
     }
 
+    /// Interval between heartbeats
+    pub fn heartbeat_interval(&self) -> Duration {
+        self.config.heartbeat_interval
+    }
+
     /// Get number of connected peers
     pub fn peer_count(&self) -> usize {
         self.peers.read().len()
@@ -122,6 +313,137 @@ impl P2pManager {
     pub fn register_drone(&self, drone_id: DroneId, peer_id: PeerId) {
         self.drone_peers.write().insert(drone_id.clone(), peer_id);
         info!("Registered drone {} with peer {}", drone_id, peer_id);
+        self.persist_registrations();
+    }
+
+    /// Remove a drone's peer registration
+    ///
+    /// Under the allow-list, a peer left without any registration is
+    /// disconnected.
+    pub fn unregister_drone(&self, drone_id: &DroneId) -> Option<PeerId> {
+        let removed = self.drone_peers.write().remove(drone_id);
+        if let Some(peer_id) = removed {
+            self.persist_registrations();
+            if !self.is_admitted(&peer_id) {
+                info!("Peer {} is no longer registered; disconnecting", peer_id);
+                self.disconnect(&peer_id);
+            }
+        }
+        removed
+    }
+
+    // ========================================================================
+    // ALLOW-LIST
+    // ========================================================================
+
+    /// Register a drone's peer by its hex public key (raw Ed25519 or
+    /// protobuf-encoded); returns the derived peer ID
+    pub fn register_peer_key(&self, drone_id: DroneId, public_key: &str) -> P2pResult<PeerId> {
+        let peer_id = PeerId::from(allowlist::parse_public_key(public_key)?);
+        self.register_drone(drone_id, peer_id);
+        Ok(peer_id)
+    }
+
+    /// Whether a peer may connect and publish
+    pub fn is_admitted(&self, peer_id: &PeerId) -> bool {
+        !self.config.allow_list
+            || *peer_id == self.local_peer_id
+            || self.drone_peers.read().values().any(|p| p == peer_id)
+    }
+
+    /// Check a peer the swarm connected to or received gossip from;
+    /// unregistered peers are disconnected and logged
+    pub fn admit(&self, peer_id: &PeerId, at: DateTime<Utc>) -> bool {
+        if self.is_admitted(peer_id) {
+            return true;
+        }
+        warn!("Rejected unregistered peer {}", peer_id);
+        self.rejections.lock().record(PeerRejection {
+            peer_id: peer_id.to_string(),
+            at,
+        });
+        self.disconnect(peer_id);
+        false
+    }
+
+    /// Record a connection opened by the swarm if the peer is admitted
+    pub fn admit_connection(&self, peer_id: PeerId, route: ConnectionRoute, at: DateTime<Utc>) -> bool {
+        let admitted = self.admit(&peer_id, at);
+        if admitted {
+            self.network.connection_established(peer_id, route, at);
+        }
+        admitted
+    }
+
+    /// Apply a swarm connection event to the mesh view; `false` if the
+    /// connection was refused by the allow-list
+    pub fn connection_event(&self, event: ConnectionEvent, at: DateTime<Utc>) -> bool {
+        match event {
+            ConnectionEvent::Established { peer_id, route, address } => {
+                if !self.admit_connection(peer_id, route, at) {
+                    return false;
+                }
+                let drone_id = self
+                    .drone_peers
+                    .read()
+                    .iter()
+                    .find(|(_, p)| **p == peer_id)
+                    .map(|(drone_id, _)| drone_id.clone());
+                let mut peers = self.peers.write();
+                let info = peers.entry(peer_id).or_insert_with(|| PeerInfo {
+                    peer_id,
+                    drone_id,
+                    addresses: Vec::new(),
+                    last_seen: at,
+                    capabilities: CapabilitySet::default(),
+                    formation_role: None,
+                });
+                info.last_seen = info.last_seen.max(at);
+                if let Some(address) = address.filter(|a| !info.addresses.contains(a)) {
+                    info.addresses.push(address);
+                }
+            }
+            ConnectionEvent::Closed { peer_id } => self.network.connection_closed(&peer_id),
+            ConnectionEvent::Subscribed { peer_id, topic } => self.network.peer_subscribed(&peer_id, &topic),
+            ConnectionEvent::Unsubscribed { peer_id, topic } => self.network.peer_unsubscribed(&peer_id, &topic),
+        }
+        true
+    }
+
+    fn disconnect(&self, peer_id: &PeerId) {
+        self.network.connection_closed(peer_id);
+        self.peers.write().remove(peer_id);
+    }
+
+    /// Allow-list mode, registrations and recent rejections
+    pub fn allow_list(&self) -> AllowListView {
+        let mut registrations: Vec<PeerRegistration> = self
+            .drone_peers
+            .read()
+            .iter()
+            .map(|(drone_id, peer_id)| PeerRegistration {
+                drone_id: drone_id.clone(),
+                peer_id: peer_id.to_string(),
+            })
+            .collect();
+        registrations.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+        let rejections = self.rejections.lock();
+        AllowListView {
+            enabled: self.config.allow_list,
+            registrations,
+            rejected_total: rejections.total(),
+            recent_rejections: rejections.recent(),
+        }
+    }
+
+    fn persist_registrations(&self) {
+        if let Some(keystore) = &self.keystore {
+            let registrations = self.drone_peers.read().clone();
+            if let Err(e) = keystore.save_registrations(&self.local_peer_id, &registrations) {
+                warn!("Failed to persist drone registrations: {}", e);
+                self.health.record_error();
+            }
+        }
     }
 
     /// Get peer ID for a drone
@@ -130,42 +452,508 @@ impl P2pManager {
     }
 
     /// Broadcast a message to all peers
+    ///
+    /// Gossip is shaped on this node's own link.
     pub async fn broadcast(&self, message: DroneMessage) -> P2pResult<()> {
+        let bytes = message.to_bytes().map(|b| b.len()).unwrap_or(0);
+        self.shaped_send(self.local_peer_id, message, bytes).await
+    }
+
+    /// Send a `bytes`-long frame within the link's budget. A message that
+    /// has to wait for budget is handed to a timer task, so the caller never
+    /// waits on the link; one the shaper drops is an `OverBudget` error.
+    async fn shaped_send(&self, link: PeerId, message: DroneMessage, bytes: usize) -> P2pResult<()> {
+        let Some(shaper) = &self.shaper else {
+            return self.send(message, bytes).await;
+        };
+        let priority = MessagePriority::of(&message);
+        let decision = shaper.lock().admit(link, priority, bytes, std::time::Instant::now());
+        match decision {
+            ShapeDecision::Send => self.send(message, bytes).await,
+            ShapeDecision::Delay(wait) => {
+                let tx = self.message_tx.clone();
+                let network = self.network.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(wait).await;
+                    let id = message.id;
+                    match tx.send(message).await {
+                        Ok(()) => network.record_message_sent(bytes as u64),
+                        Err(_) => debug!("Dropped delayed message {}: P2P stopped", id),
+                    }
+                });
+                Ok(())
+            }
+            ShapeDecision::Drop => {
+                debug!("Link to {} over budget; dropped {:?} message {}", link, priority, message.id);
+                Err(P2pError::OverBudget(format!("{:?} message {} to {}", priority, message.id, link)))
+            }
+        }
+    }
+
+    async fn send(&self, message: DroneMessage, bytes: usize) -> P2pResult<()> {
         self.message_tx.send(message).await
-            .map_err(|e| P2pError::send(e.to_string()))?;
+            .map_err(|e| {
+                self.health.record_error();
+                P2pError::send(e.to_string())
+            })?;
+        self.network.record_message_sent(bytes as u64);
         Ok(())
     }
 
     /// Send position update to all peers
+    ///
+    /// Telemetry without a sequence number gets the next one for the drone,
+    /// so receivers can drop gossip duplicates and notice lost updates.
     pub async fn broadcast_position(
         &self,
         drone_id: DroneId,
         position: GeoPosition,
-        telemetry: Telemetry,
+        mut telemetry: Telemetry,
     ) -> P2pResult<()> {
+        let sequence = {
+            let mut sequences = self.position_sequences.lock();
+            let last = sequences.entry(drone_id.clone()).or_default();
+            *last = telemetry.sequence.unwrap_or(*last + 1);
+            *last
+        };
+        telemetry.sequence = Some(sequence);
         let message = DroneMessage::position_update(drone_id, position, telemetry);
         self.broadcast(message).await
     }
 
     /// Send direct message to specific drone
+    ///
+    /// The message is written to the outbound WAL first and stays there
+    /// until the drone acknowledges it or it expires; the message is not
+    /// sent if it could not be logged. Messages for a drone on the far side
+    /// of a partition go through a relay-capable drone that reports reaching
+    /// it, or are buffered and replayed when it is heard again if there is
+    /// none.
     pub async fn send_to_drone(
         &self,
         target: &DroneId,
         message: DroneMessage,
     ) -> P2pResult<()> {
-        if let Some(_peer_id) = self.get_drone_peer(target) {
-            // In real implementation, would use direct protocol
-            self.broadcast(message).await
+        if let Some(wal) = &self.wal {
+            if let Err(e) = self.log_outbound(wal, target, &message).await {
+                warn!("Failed to log message {} for {}: {}", message.id, target, e);
+                self.health.record_error();
+                return Err(e);
+            }
+        }
+        self.deliver(target, message).await
+    }
+
+    /// Append to the WAL under its lock, then wait for the disk outside it
+    async fn log_outbound(&self, wal: &Mutex<OutboundWal>, target: &DroneId, message: &DroneMessage) -> P2pResult<()> {
+        let syncer = {
+            let mut wal = wal.lock();
+            wal.append(target, message, Utc::now())?;
+            wal.syncer()
+        };
+        tokio::task::spawn_blocking(move || syncer.sync())
+            .await
+            .map_err(|e| P2pError::Wal(e.to_string()))?
+    }
+
+    async fn deliver(&self, target: &DroneId, message: DroneMessage) -> P2pResult<()> {
+        if self.partition.read().state(target) == Some(Reachability::Unreachable) {
+            if let Some(relay) = self.relay_for(target) {
+                info!("Drone {} is unreachable; relaying message {} via {}", target, message.id, relay);
+                let relayed = DroneMessage::relay(message.sender.clone(), relay, target.clone(), message);
+                return self.broadcast(relayed).await;
+            }
+            info!("Drone {} is unreachable; buffering message {}", target, message.id);
+            let limit = self.config.partition.buffer_per_drone;
+            let now = self.clock.read().now();
+            self.outbound.write().push(target, message, now, limit);
+            return Ok(());
+        }
+
+        if let Some(peer_id) = self.get_drone_peer(target) {
+            // In real implementation, would use direct protocol. The drone
+            // gets its negotiated form, shaped and counted at its frame size
+            let frame = self.encode_for(target, &message)?;
+            let message = if self.compact_positions_for(target) {
+                message.with_compact_positions()
+            } else {
+                message
+            };
+            self.shaped_send(peer_id, message, frame.len()).await
         } else {
             Err(P2pError::peer_not_found(target.as_str()))
         }
     }
 
-    /// Take the message receiver (can only be called once)
+    /// Record an incoming message's ID; `false` for a copy of a message
+    /// already handled, such as one replayed from a sender's WAL
+    pub fn accept(&self, message: &DroneMessage) -> bool {
+        self.seen.lock().insert(message.id)
+    }
+
+    /// Resend the unacknowledged, unexpired messages in the outbound WAL
+    /// with their original IDs; returns how many were sent
+    pub async fn replay_outbound(&self) -> usize {
+        let Some(wal) = &self.wal else {
+            return 0;
+        };
+        let pending = {
+            let mut wal = wal.lock();
+            wal.expire(Utc::now());
+            wal.pending().to_vec()
+        };
+        let mut replayed = 0;
+        for entry in pending {
+            match self.deliver(&entry.target, entry.message).await {
+                Ok(()) => replayed += 1,
+                Err(e) => warn!("Failed to replay message to {}: {}", entry.target, e),
+            }
+        }
+        replayed
+    }
+
+    /// Drop expired messages from the outbound WAL; returns how many
+    pub fn expire_outbound(&self) -> usize {
+        self.wal.as_ref().map_or(0, |wal| wal.lock().expire(Utc::now()))
+    }
+
+    /// Direct messages in the WAL still waiting for an acknowledgement
+    pub fn unacknowledged(&self) -> usize {
+        self.wal.as_ref().map_or(0, |wal| wal.lock().pending().len())
+    }
+
+    /// Note an incoming message for reachability tracking
+    ///
+    /// Unrelayed messages count as direct contact with the sender;
+    /// reachability reports are recorded whichever way they arrived.
+    /// Discovery responses update the sender's capabilities and
+    /// acknowledgements clear the message from the outbound WAL.
+    pub fn observe(&self, message: &DroneMessage, at: DateTime<Utc>) {
+        {
+            let mut partition = self.partition.write();
+            if message.is_direct() {
+                partition.note_heard(&message.sender, at);
+            }
+            if let MessageType::Reachability(report) = &message.message_type {
+                partition.record_report(&report.drone_id, report.reachable.clone(), at);
+            }
+        }
+        if let MessageType::DiscoveryResponse(response) = &message.message_type {
+            self.record_discovery(response, at);
+        }
+        if let (MessageType::Ack(ack), Some(wal)) = (&message.message_type, &self.wal) {
+            if let Err(e) = wal.lock().ack(ack.message_id) {
+                warn!("Failed to log acknowledgement of {}: {}", ack.message_id, e);
+                self.health.record_error();
+            }
+        }
+    }
+
+    // ========================================================================
+    // POSITION ORDERING
+    // ========================================================================
+
+    /// Hold a position update in the jitter buffer; `false` for other messages
+    pub fn buffer_position(&self, message: &DroneMessage, at: DateTime<Utc>) -> bool {
+        let MessageType::PositionUpdate(update) = &message.message_type else {
+            return false;
+        };
+        self.jitter.lock().push(message.id, message.timestamp, update.clone(), at);
+        true
+    }
+
+    /// Position updates whose hold window has passed, oldest first per drone
+    pub fn release_positions(&self, now: DateTime<Utc>) -> Vec<PositionUpdateData> {
+        self.jitter.lock().release(now)
+    }
+
+    /// How long position updates are held for reordering
+    pub fn jitter_window(&self) -> Duration {
+        self.config.jitter.window
+    }
+
+    pub fn jitter_stats(&self) -> JitterStats {
+        self.jitter.lock().stats()
+    }
+
+    // ========================================================================
+    // CAPABILITIES
+    // ========================================================================
+
+    /// Capabilities this node advertises
+    pub fn local_capabilities(&self) -> CapabilitySet {
+        self.config.capabilities
+    }
+
+    /// Discovery response advertising this node's capabilities
+    pub fn discovery_response(&self, sender: DroneId) -> DroneMessage {
+        DroneMessage::discovery_response(sender, self.config.capabilities, None)
+    }
+
+    /// Store a peer's advertised capabilities in its `PeerInfo`
+    ///
+    /// Only drones registered with a peer ID are recorded.
+    pub fn record_discovery(&self, response: &DiscoveryResponseData, at: DateTime<Utc>) {
+        let Some(peer_id) = self.get_drone_peer(&response.drone_id) else {
+            debug!("Discovery response from unregistered drone {}", response.drone_id);
+            return;
+        };
+        let capabilities = CapabilitySet::from_advertised(&response.capabilities);
+        let mut peers = self.peers.write();
+        let info = peers.entry(peer_id).or_insert_with(|| PeerInfo {
+            peer_id,
+            drone_id: Some(response.drone_id.clone()),
+            addresses: Vec::new(),
+            last_seen: at,
+            capabilities,
+            formation_role: None,
+        });
+        info.last_seen = info.last_seen.max(at);
+        info.capabilities = capabilities;
+        info.formation_role = response.formation_role.clone();
+    }
+
+    /// Known peers with their capabilities
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        self.peers.read().values().cloned().collect()
+    }
+
+    /// Capabilities a drone advertised (empty before its discovery response)
+    pub fn peer_capabilities(&self, drone_id: &DroneId) -> CapabilitySet {
+        self.get_drone_peer(drone_id)
+            .and_then(|peer_id| self.peers.read().get(&peer_id).map(|info| info.capabilities))
+            .unwrap_or_default()
+    }
+
+    /// Frame encoding to use for a drone
+    pub fn wire_format_for(&self, drone_id: &DroneId) -> WireFormat {
+        WireFormat::negotiate(self.config.capabilities, self.peer_capabilities(drone_id))
+    }
+
+    /// Whether both ends decode fixed-point positions
+    pub fn compact_positions_for(&self, drone_id: &DroneId) -> bool {
+        self.config
+            .capabilities
+            .intersection(self.peer_capabilities(drone_id))
+            .contains(Capability::CompactPositions)
+    }
+
+    /// Encode a message for a drone in the format it negotiated
+    pub fn encode_for(&self, drone_id: &DroneId, message: &DroneMessage) -> P2pResult<Vec<u8>> {
+        let format = self.wire_format_for(drone_id);
+        if self.compact_positions_for(drone_id) {
+            message.clone().with_compact_positions().encode(format)
+        } else {
+            message.encode(format)
+        }
+    }
+
+    /// Directly reachable relay-capable drone that reports reaching `target`
+    fn relay_for(&self, target: &DroneId) -> Option<DroneId> {
+        let partition = self.partition.read();
+        partition.reporters_of(target).into_iter().find(|relay| {
+            partition.state(relay) == Some(Reachability::Reachable)
+                && self.peer_capabilities(relay).contains(Capability::RelayCapable)
+        })
+    }
+
+    /// Drones among `drones` that advertise `formation-follower`
+    pub fn formation_followers(&self, drones: &[DroneId]) -> Vec<DroneId> {
+        drones
+            .iter()
+            .filter(|d| self.peer_capabilities(d).contains(Capability::FormationFollower))
+            .cloned()
+            .collect()
+    }
+
+    /// Send a formation command to each follower in it, skipping drones
+    /// that cannot fly formation positions; returns the recipients
+    pub async fn send_formation_command(
+        &self,
+        sender: DroneId,
+        command: FormationCommandData,
+    ) -> P2pResult<Vec<DroneId>> {
+        let drones: Vec<DroneId> = command.positions.iter().map(|p| p.drone_id.clone()).collect();
+        let followers = self.formation_followers(&drones);
+        for drone_id in &followers {
+            let message = DroneMessage::new(sender.clone(), MessageType::FormationCommand(command.clone()));
+            self.send_to_drone(drone_id, message).await?;
+        }
+        if followers.len() < drones.len() {
+            debug!(
+                "Formation command {} skipped {} drones without formation-follower",
+                command.command_id,
+                drones.len() - followers.len()
+            );
+        }
+        Ok(followers)
+    }
+
+    /// A fresh peer report says the drone is alive
+    pub fn reported_alive(&self, drone_id: &DroneId, now: DateTime<Utc>) -> bool {
+        self.partition.read().reported_alive(drone_id, now)
+    }
+
+    /// Reclassify drone reachability and replay buffered messages for
+    /// drones that healed
+    pub async fn assess_reachability(&self, now: DateTime<Utc>) -> ReachabilityChanges {
+        let changes = self.partition.write().assess(now);
+        self.expire_outbound();
+
+        for drone_id in &changes.healed {
+            let max_age = self.config.partition.buffer_max_age;
+            let replay = self.outbound.write().drain(drone_id, now, max_age);
+            if replay.is_empty() {
+                continue;
+            }
+            info!("Drone {} reachable again; replaying {} messages", drone_id, replay.len());
+            for message in replay {
+                if let Err(e) = self.send_to_drone(drone_id, message).await {
+                    warn!("Failed to replay message to {}: {}", drone_id, e);
+                }
+            }
+        }
+        changes
+    }
+
+    /// Current reachability, partitions and buffered message counts
+    pub fn reachability(&self, now: DateTime<Utc>) -> ReachabilityView {
+        let partition = self.partition.read();
+        let drones = partition
+            .known()
+            .map(|(drone_id, state)| (drone_id.clone(), state))
+            .collect();
+        ReachabilityView {
+            assessed_at: now,
+            drones,
+            partitions: partition.partitions(now),
+            buffered: self.outbound.read().counts(),
+        }
+    }
+
+    /// When the manager started and how many sends or log writes failed
+    pub fn health(&self) -> &SubsystemHealth {
+        &self.health
+    }
+
+    /// Shaping decisions per priority (disabled without `shaping`)
+    pub fn shaping_stats(&self) -> ShapingStats {
+        self.shaper.as_ref().map(|shaper| shaper.lock().stats()).unwrap_or_default()
+    }
+
+    /// Health, traffic and delivery counters in one summary
+    pub fn stats(&self, now: DateTime<Utc>) -> P2pStats {
+        P2pStats {
+            health: self.health.report(now),
+            network: self.network.get_stats(),
+            jitter: self.jitter_stats(),
+            unacknowledged: self.unacknowledged(),
+            rejected_peers: self.rejections.lock().total(),
+            shaping: self.shaping_stats(),
+        }
+    }
+
+    /// Swarm connections and traffic counters
+    pub fn network(&self) -> &DroneNetwork {
+        &self.network
+    }
+
+    /// This node's view of the mesh: every peer it knows from the swarm,
+    /// discovery or drone registrations
+    pub fn topology(&self, now: DateTime<Utc>) -> MeshTopology {
+        let connections = self.network.connections();
+        let known = self.peers();
+        let mut drones: HashMap<PeerId, DroneId> = HashMap::new();
+        for (drone_id, peer_id) in self.drone_peers.read().iter() {
+            // Several registrations for one peer report the first by ID
+            let entry = drones.entry(*peer_id).or_insert_with(|| drone_id.clone());
+            if drone_id < entry {
+                *entry = drone_id.clone();
+            }
+        }
+
+        let peer_ids: BTreeSet<PeerId> = connections
+            .keys()
+            .chain(known.iter().map(|p| &p.peer_id))
+            .chain(drones.keys())
+            .filter(|peer_id| **peer_id != self.local_peer_id)
+            .copied()
+            .collect();
+
+        let partition = self.partition.read();
+        let mut peers: Vec<PeerView> = peer_ids
+            .into_iter()
+            .map(|peer_id| {
+                let info = known.iter().find(|p| p.peer_id == peer_id);
+                let connection = connections.get(&peer_id);
+                let drone_id = drones
+                    .get(&peer_id)
+                    .cloned()
+                    .or_else(|| info.and_then(|i| i.drone_id.clone()));
+                PeerView {
+                    peer_id: peer_id.to_string(),
+                    addresses: info
+                        .map(|i| i.addresses.iter().map(|a| a.to_string()).collect())
+                        .unwrap_or_default(),
+                    topics: connection.map(|c| c.topics.iter().cloned().collect()).unwrap_or_default(),
+                    route: connection.map(|c| c.route),
+                    connected_since: connection.map(|c| c.established_at),
+                    connection_age_secs: connection.map(|c| (now - c.established_at).num_seconds().max(0)),
+                    last_seen: info.map(|i| i.last_seen),
+                    last_heard_directly: drone_id.as_ref().and_then(|d| partition.last_heard(d)),
+                    reachability: drone_id.as_ref().and_then(|d| partition.state(d)),
+                    drone_id,
+                }
+            })
+            .collect();
+        peers.sort_by(|a, b| a.drone_id.cmp(&b.drone_id).then_with(|| a.peer_id.cmp(&b.peer_id)));
+
+        let mut disconnected_drones: Vec<DroneId> = self
+            .drone_peers
+            .read()
+            .iter()
+            .filter(|(_, peer_id)| **peer_id != self.local_peer_id && !connections.contains_key(peer_id))
+            .map(|(drone_id, _)| drone_id.clone())
+            .collect();
+        disconnected_drones.sort();
+
+        MeshTopology {
+            assessed_at: now,
+            local_peer_id: self.local_peer_id.to_string(),
+            listen_addrs: self.config.listen_addrs.iter().map(|a| a.to_string()).collect(),
+            topics: vec![self.config.gossip_topic.clone()],
+            peers,
+            disconnected_drones,
+            stats: self.network.get_stats(),
+        }
+    }
+
+    /// Take the outgoing message receiver (can only be called once)
     pub fn take_message_receiver(&self) -> Option<mpsc::Receiver<DroneMessage>> {
         self.message_rx.write().take()
     }
 
+    /// Hand a message `peer_id` sent over the mesh to the incoming
+    /// receiver; refused, and the peer disconnected, if the allow-list does
+    /// not admit it
+    pub async fn receive(&self, peer_id: PeerId, message: DroneMessage) -> P2pResult<()> {
+        if !self.admit(&peer_id, Utc::now()) {
+            return Err(P2pError::NotAdmitted(peer_id.to_string()));
+        }
+        let bytes = message.to_bytes().map(|b| b.len() as u64).unwrap_or(0);
+        self.inbound_tx
+            .send(InboundMessage { peer_id, message })
+            .await
+            .map_err(|e| P2pError::send(e.to_string()))?;
+        self.network.record_message_received(bytes);
+        Ok(())
+    }
+
+    /// Take the incoming message receiver (can only be called once)
+    pub fn take_inbound_receiver(&self) -> Option<mpsc::Receiver<InboundMessage>> {
+        self.inbound_rx.write().take()
+    }
+
     /// Start the P2P network (runs in background)
     pub async fn start(&self) -> P2pResult<()> {
         info!("🚀 Starting P2P network on {:?}", self.config.listen_addrs);
@@ -178,6 +966,11 @@ impl P2pManager {
         
         // For now, we just log that we're "running"
         info!("✅ P2P network started (simulation mode)");
+
+        let replayed = self.replay_outbound().await;
+        if replayed > 0 {
+            info!("Replayed {} unacknowledged outbound messages", replayed);
+        }
         
         Ok(())
     }
@@ -205,6 +998,7 @@ impl Default for P2pManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::CommandKind;
 
     #[tokio::test]
     async fn test_p2p_manager_creation() {
@@ -224,4 +1018,285 @@ mod tests {
         
         assert_eq!(manager.get_drone_peer(&drone_id), Some(peer_id));
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shaped_sends_wait_off_the_send_path() {
+        let heartbeat = || DroneMessage::heartbeat(DroneId::new("REAPER-01"));
+        let bytes = heartbeat().to_bytes().unwrap().len();
+        // 1000 bytes/s with room for one heartbeat, which then takes `bytes` ms to refill
+        let manager = P2pManager::new(P2pConfig {
+            shaping: Some(ShaperConfig {
+                burst_bytes: bytes,
+                telemetry_max_delay: Duration::from_millis(bytes as u64 + 10),
+                ..ShaperConfig::new(8)
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let mut rx = manager.take_message_receiver().unwrap();
+
+        manager.broadcast(heartbeat()).await.unwrap();
+        manager.broadcast(heartbeat()).await.unwrap();
+        assert!(rx.try_recv().is_ok());
+        // The second waits for budget on a timer, not in `broadcast`
+        assert!(rx.try_recv().is_err());
+        assert!(matches!(manager.broadcast(heartbeat()).await, Err(P2pError::OverBudget(_))));
+        assert!(rx.recv().await.is_some());
+        assert_eq!(manager.shaping_stats().telemetry.dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_position_numbers_updates() {
+        let manager = P2pManager::new(P2pConfig::default()).await.unwrap();
+        let mut rx = manager.take_message_receiver().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let position = GeoPosition::new(34.5553, 69.2075, 3000.0);
+        let mut sequences = Vec::new();
+        let mut sent = |rx: &mut mpsc::Receiver<DroneMessage>| match rx.try_recv().unwrap().message_type {
+            MessageType::PositionUpdate(update) => sequences.push(update.telemetry.sequence),
+            other => panic!("unexpected {:?}", other),
+        };
+
+        manager.broadcast_position(drone_id.clone(), position, Telemetry::default()).await.unwrap();
+        sent(&mut rx);
+        manager.broadcast_position(drone_id.clone(), position, Telemetry::default()).await.unwrap();
+        sent(&mut rx);
+        // A sender that numbers its own reports keeps its numbers
+        let numbered = Telemetry { sequence: Some(40), ..Default::default() };
+        manager.broadcast_position(drone_id.clone(), position, numbered).await.unwrap();
+        sent(&mut rx);
+        manager.broadcast_position(drone_id, position, Telemetry::default()).await.unwrap();
+        sent(&mut rx);
+
+        assert_eq!(sequences, vec![Some(1), Some(2), Some(40), Some(41)]);
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_steer_encoding_and_routing() {
+        let manager = P2pManager::new(P2pConfig::default()).await.unwrap();
+        let mut rx = manager.take_message_receiver().unwrap();
+        let [relay, cut_off] = ["REAPER-03", "REAPER-07"].map(DroneId::new);
+        manager.register_drone(relay.clone(), PeerId::random());
+        manager.register_drone(cut_off.clone(), PeerId::random());
+
+        let now = Utc::now();
+        let advertised = CapabilitySet::of(&[Capability::RelayCapable, Capability::SupportsCompression]);
+        manager.observe(&DroneMessage::discovery_response(relay.clone(), advertised, None), now);
+        manager.observe(&DroneMessage::reachability(relay.clone(), vec![cut_off.clone()]), now);
+        manager.assess_reachability(now).await;
+
+        assert_eq!(manager.peer_capabilities(&relay), advertised);
+        assert_eq!(manager.wire_format_for(&relay), WireFormat::Deflate);
+        assert_eq!(manager.wire_format_for(&cut_off), WireFormat::Bincode);
+        assert!(manager.formation_followers(&[relay.clone(), cut_off.clone()]).is_empty());
+
+        // The unreachable drone is served through the relay instead of the buffer
+        let command = DroneMessage::heartbeat(DroneId::new("GCS"));
+        manager.send_to_drone(&cut_off, command.clone()).await.unwrap();
+        let sent = rx.try_recv().unwrap();
+        assert!(matches!(
+            sent.message_type,
+            MessageType::Relay(ref r) if r.relay_id == relay && r.target == cut_off && r.message.id == command.id
+        ));
+        assert_eq!(manager.reachability(now).buffered.get(&cut_off), None);
+
+        // Direct sends go out in the form the drone negotiated, counted at its frame size
+        let compact = DroneId::new("REAPER-05");
+        manager.register_drone(compact.clone(), PeerId::random());
+        let advertised = CapabilitySet::of(&[Capability::CompactPositions, Capability::SupportsCompression]);
+        manager.observe(&DroneMessage::discovery_response(compact.clone(), advertised, None), now);
+        let update = DroneMessage::position_update(compact.clone(), GeoPosition::new(34.5, 69.2, 3000.0), Telemetry::default());
+        let frame = manager.encode_for(&compact, &update).unwrap();
+        let before = manager.network().get_stats().bytes_sent;
+        manager.send_to_drone(&compact, update).await.unwrap();
+        assert!(matches!(rx.try_recv().unwrap().message_type, MessageType::CompactPositionUpdate(_)));
+        assert_eq!(manager.network().get_stats().bytes_sent - before, frame.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_messages_age_by_the_partition_clock() {
+        let manager = P2pManager::new(P2pConfig::default()).await.unwrap();
+        let mut rx = manager.take_message_receiver().unwrap();
+        let clock = Arc::new(SimulationClock::new());
+        clock.pause();
+        manager.set_clock(clock.clone());
+        let [peer, cut_off] = ["REAPER-02", "REAPER-07"].map(DroneId::new);
+        manager.register_drone(cut_off.clone(), PeerId::random());
+
+        manager.observe(&DroneMessage::heartbeat(cut_off.clone()), clock.now());
+        manager.assess_reachability(clock.now()).await;
+        clock.step(Duration::from_secs(10));
+        manager.observe(&DroneMessage::reachability(peer, vec![cut_off.clone()]), clock.now());
+        manager.assess_reachability(clock.now()).await;
+        manager.send_to_drone(&cut_off, DroneMessage::heartbeat(DroneId::new("GCS"))).await.unwrap();
+        assert_eq!(manager.reachability(clock.now()).buffered.get(&cut_off), Some(&1));
+
+        // Healed after the buffer's max age on the same clock: nothing is replayed
+        clock.step(Duration::from_secs(301));
+        manager.observe(&DroneMessage::heartbeat(cut_off.clone()), clock.now());
+        assert_eq!(manager.assess_reachability(clock.now()).await.healed, vec![cut_off]);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_topology_joins_connections_and_registrations() {
+        let manager = P2pManager::new(P2pConfig::default()).await.unwrap();
+        let [connected, silent] = [PeerId::random(), PeerId::random()];
+        manager.register_drone(DroneId::new("REAPER-01"), connected);
+        manager.register_drone(DroneId::new("REAPER-02"), silent);
+
+        let now = Utc::now();
+        let address: Multiaddr = "/ip4/10.0.0.7/tcp/4001".parse().unwrap();
+        manager.connection_event(
+            ConnectionEvent::Established { peer_id: connected, route: ConnectionRoute::Relayed, address: Some(address) },
+            now - chrono::Duration::seconds(30),
+        );
+        manager.connection_event(
+            ConnectionEvent::Subscribed { peer_id: connected, topic: "drone-convoy".into() },
+            now,
+        );
+        let _outbound = manager.take_message_receiver().unwrap();
+        manager.broadcast(DroneMessage::heartbeat(DroneId::new("GCS"))).await.unwrap();
+        manager.observe(&DroneMessage::heartbeat(DroneId::new("REAPER-01")), now);
+        manager.assess_reachability(now).await;
+
+        let topology = manager.topology(now);
+        assert_eq!(topology.topics, vec!["drone-convoy".to_string()]);
+        assert_eq!(topology.peers.len(), 2);
+        let peer = &topology.peers[0];
+        assert_eq!(peer.drone_id, Some(DroneId::new("REAPER-01")));
+        assert_eq!(peer.route, Some(ConnectionRoute::Relayed));
+        assert_eq!(peer.connection_age_secs, Some(30));
+        assert_eq!(peer.topics, vec!["drone-convoy".to_string()]);
+        assert_eq!(peer.last_heard_directly, Some(now));
+        assert_eq!(peer.reachability, Some(Reachability::Reachable));
+        assert_eq!(peer.addresses, vec!["/ip4/10.0.0.7/tcp/4001".to_string()]);
+        assert_eq!(topology.peers[1].route, None);
+        assert_eq!(topology.disconnected_drones, vec![DroneId::new("REAPER-02")]);
+        assert_eq!((topology.stats.peers_connected, topology.stats.messages_sent), (1, 1));
+
+        manager.connection_event(ConnectionEvent::Closed { peer_id: connected }, now);
+        let topology = manager.topology(now);
+        assert_eq!(topology.peers[0].route, None);
+        assert_eq!(topology.stats.peers_connected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_commands_replay_after_restart() {
+        let path = std::env::temp_dir().join(format!("drone-p2p-wal-{}.jsonl", uuid::Uuid::new_v4()));
+        let config_for = |path: &std::path::Path| P2pConfig {
+            wal_path: Some(path.to_path_buf()),
+            ..Default::default()
+        };
+        let config = config_for(&path);
+        let target = DroneId::new("REAPER-01");
+        let command = |kind| DroneMessage::command(DroneId::new("GCS"), target.clone(), kind);
+        let (acked, lost) = (command(CommandKind::ReturnToBase), command(CommandKind::EmergencyStop));
+
+        let first = P2pManager::new(config.clone()).await.unwrap();
+        first.register_drone(target.clone(), PeerId::random());
+        first.send_to_drone(&target, acked.clone()).await.unwrap();
+        first.send_to_drone(&target, lost.clone()).await.unwrap();
+        first.observe(&DroneMessage::ack(target.clone(), acked.id, true), Utc::now());
+        assert_eq!(first.unacknowledged(), 1);
+        drop(first);
+
+        let second = P2pManager::new(config).await.unwrap();
+        let mut rx = second.take_message_receiver().unwrap();
+        second.register_drone(target.clone(), PeerId::random());
+        second.start().await.unwrap();
+        let replayed = rx.try_recv().unwrap();
+        assert_eq!(replayed.id, lost.id);
+        assert!(rx.try_recv().is_err());
+
+        // The receiver handles the replayed copy once
+        assert!(second.accept(&replayed));
+        assert!(!second.accept(&replayed));
+        drop(second);
+
+        // Unacknowledged messages expire while the node runs
+        let expiring = P2pManager::new(P2pConfig { wal_ttl: Duration::ZERO, ..config_for(&path) }).await.unwrap();
+        expiring.register_drone(target.clone(), PeerId::random());
+        expiring.send_to_drone(&target, command(CommandKind::ReturnToBase)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(expiring.expire_outbound(), 1);
+        assert_eq!(expiring.unacknowledged(), 0);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_allow_list_admits_registered_peers_only() {
+        let manager = P2pManager::new(P2pConfig {
+            allow_list: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let key = libp2p::identity::Keypair::generate_ed25519().public();
+        let registered = manager
+            .register_peer_key(drone_id.clone(), &hex::encode(key.encode_protobuf()))
+            .unwrap();
+        assert_eq!(registered, PeerId::from(key));
+        assert!(matches!(
+            manager.register_peer_key(drone_id.clone(), "zz"),
+            Err(P2pError::InvalidKey(_))
+        ));
+
+        let now = Utc::now();
+        let stranger = PeerId::random();
+        assert!(manager.admit_connection(registered, ConnectionRoute::Direct, now));
+        assert!(!manager.admit_connection(stranger, ConnectionRoute::Direct, now));
+        assert!(manager.admit(&manager.local_peer_id(), now));
+        assert!(manager.network().connections().contains_key(&registered));
+        assert!(!manager.network().connections().contains_key(&stranger));
+
+        let view = manager.allow_list();
+        assert_eq!(view.registrations.len(), 1);
+        assert_eq!(view.rejected_total, 1);
+        assert_eq!(view.recent_rejections[0].peer_id, stranger.to_string());
+
+        // Dropping the registration disconnects the peer and bars it
+        assert_eq!(manager.unregister_drone(&drone_id), Some(registered));
+        assert!(manager.network().connections().is_empty());
+        assert!(!manager.admit(&registered, now));
+
+        // Gossip from an unregistered peer never reaches the inbox
+        let mut inbox = manager.take_inbound_receiver().unwrap();
+        let update = DroneMessage::heartbeat(drone_id.clone());
+        assert!(matches!(
+            manager.receive(registered, update.clone()).await,
+            Err(P2pError::NotAdmitted(_))
+        ));
+        assert!(inbox.try_recv().is_err());
+        assert_eq!(manager.allow_list().rejected_total, 3);
+
+        // Without the allow-list anyone is admitted
+        let open = P2pManager::new(P2pConfig::default()).await.unwrap();
+        assert!(open.admit_connection(stranger, ConnectionRoute::Direct, now));
+        let mut inbox = open.take_inbound_receiver().unwrap();
+        open.receive(stranger, update).await.unwrap();
+        assert_eq!(inbox.try_recv().unwrap().peer_id, stranger);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_identity_and_registrations() {
+        let dir = std::env::temp_dir().join(format!("drone-p2p-test-{}", uuid::Uuid::new_v4()));
+        let config = P2pConfig {
+            identity_path: Some(dir.join("identity.key")),
+            ..Default::default()
+        };
+
+        let first = P2pManager::new(config.clone()).await.unwrap();
+        let remote = PeerId::random();
+        first.register_drone(DroneId::new("REAPER-02"), remote);
+
+        let second = P2pManager::new(config).await.unwrap();
+        assert_eq!(second.local_peer_id(), first.local_peer_id());
+        assert_eq!(second.get_drone_peer(&DroneId::new("REAPER-02")), Some(remote));
+
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
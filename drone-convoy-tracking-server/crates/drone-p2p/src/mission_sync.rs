@@ -0,0 +1,241 @@
+//! Versioned mission routes for syncing to drone agents
+//!
+//! The ground station publishes a manifest: the route's version, its hash
+//! and the hash of every `chunk_size` waypoints. An agent compares the
+//! chunk hashes with the route it holds, requests the chunks that differ
+//! and assembles the new version from those and the chunks it kept. When
+//! waypoints change, a diff takes agents on the previous version straight
+//! to the next one. Hashes are SHA-256 over the bincode encoding, so every
+//! build computes the same ones.
+
+use crate::error::{P2pError, P2pResult};
+use crate::protocol::{
+    MissionChunkData, MissionDiffData, MissionManifestData, RouteWaypoint, WaypointEdit,
+};
+use drone_core::{Mission, MissionId};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Waypoints per chunk unless configured otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 16;
+
+/// One version of a mission's route
+#[derive(Debug, Clone)]
+pub struct MissionRoute {
+    pub mission_id: MissionId,
+    pub version: u64,
+    pub waypoints: Vec<RouteWaypoint>,
+}
+
+impl MissionRoute {
+    pub fn new(mission_id: MissionId, version: u64, waypoints: Vec<RouteWaypoint>) -> Self {
+        Self { mission_id, version, waypoints }
+    }
+
+    /// Route of `mission` as `version`
+    pub fn from_mission(mission: &Mission, version: u64) -> Self {
+        Self::new(mission.id.clone(), version, mission.waypoints.iter().map(RouteWaypoint::from).collect())
+    }
+
+    /// Hex SHA-256 of the whole route
+    pub fn hash(&self) -> String {
+        digest(&self.waypoints)
+    }
+
+    /// Same mission and waypoints as `other`, whatever the versions
+    pub fn same_route(&self, other: &MissionRoute) -> bool {
+        self.mission_id == other.mission_id && self.hash() == other.hash()
+    }
+
+    pub fn chunk_count(&self, chunk_size: usize) -> usize {
+        self.waypoints.len().div_ceil(chunk_size.max(1))
+    }
+
+    /// Manifest announcing this version in chunks of `chunk_size` waypoints
+    pub fn manifest(&self, chunk_size: usize) -> MissionManifestData {
+        let chunk_size = chunk_size.max(1);
+        MissionManifestData {
+            mission_id: self.mission_id.clone(),
+            version: self.version,
+            hash: self.hash(),
+            waypoint_count: self.waypoints.len() as u32,
+            chunk_size: chunk_size as u32,
+            chunk_hashes: self.waypoints.chunks(chunk_size).map(digest).collect(),
+        }
+    }
+
+    /// Chunk `index` of this version, if the route has it
+    pub fn chunk(&self, index: u32, chunk_size: usize) -> Option<MissionChunkData> {
+        let waypoints = self.waypoints.chunks(chunk_size.max(1)).nth(index as usize)?;
+        Some(MissionChunkData {
+            mission_id: self.mission_id.clone(),
+            version: self.version,
+            index,
+            waypoints: waypoints.to_vec(),
+        })
+    }
+
+    /// Chunks of `manifest` this route lacks or holds differently
+    pub fn missing_chunks(&self, manifest: &MissionManifestData) -> Vec<u32> {
+        let held = if self.mission_id == manifest.mission_id {
+            self.manifest(manifest.chunk_size as usize).chunk_hashes
+        } else {
+            Vec::new()
+        };
+        (0..manifest.chunk_hashes.len())
+            .filter(|&i| held.get(i) != Some(&manifest.chunk_hashes[i]))
+            .map(|i| i as u32)
+            .collect()
+    }
+
+    /// Build the route `manifest` announces from the received `chunks`,
+    /// taking any chunk not received from `held`
+    ///
+    /// Every chunk and the whole route are checked against the manifest.
+    pub fn assemble(
+        manifest: &MissionManifestData,
+        held: Option<&MissionRoute>,
+        chunks: &[MissionChunkData],
+    ) -> P2pResult<Self> {
+        let chunk_size = manifest.chunk_size.max(1) as usize;
+        let held = held.filter(|route| route.mission_id == manifest.mission_id);
+        let mut waypoints = Vec::with_capacity(manifest.waypoint_count as usize);
+
+        for (index, expected) in manifest.chunk_hashes.iter().enumerate() {
+            let received = chunks
+                .iter()
+                .find(|c| c.index as usize == index && c.version == manifest.version && c.mission_id == manifest.mission_id)
+                .map(|c| c.waypoints.as_slice());
+            let kept = held.and_then(|route| route.waypoints.chunks(chunk_size).nth(index));
+            let chunk = [received, kept]
+                .into_iter()
+                .flatten()
+                .find(|chunk| &digest(chunk) == expected)
+                .ok_or_else(|| P2pError::Protocol(format!("route chunk {} missing or corrupt", index)))?;
+            waypoints.extend_from_slice(chunk);
+        }
+
+        let route = Self::new(manifest.mission_id.clone(), manifest.version, waypoints);
+        if route.waypoints.len() != manifest.waypoint_count as usize || route.hash() != manifest.hash {
+            return Err(P2pError::Protocol(format!("route version {} does not match its hash", manifest.version)));
+        }
+        Ok(route)
+    }
+
+    /// Edits taking `base` to this route
+    pub fn diff_from(&self, base: &MissionRoute) -> MissionDiffData {
+        let edits = self
+            .waypoints
+            .iter()
+            .enumerate()
+            .filter(|(i, waypoint)| base.waypoints.get(*i).is_none_or(|old| encode(old) != encode(*waypoint)))
+            .map(|(i, waypoint)| WaypointEdit {
+                index: i as u32,
+                waypoint: waypoint.clone(),
+            })
+            .collect();
+        MissionDiffData {
+            mission_id: self.mission_id.clone(),
+            base_version: base.version,
+            version: self.version,
+            hash: self.hash(),
+            waypoint_count: self.waypoints.len() as u32,
+            edits,
+        }
+    }
+
+    /// Route after applying `diff`, which must start from this version
+    pub fn apply(&self, diff: &MissionDiffData) -> P2pResult<Self> {
+        if diff.mission_id != self.mission_id || diff.base_version != self.version {
+            return Err(P2pError::Protocol(format!(
+                "diff from version {} does not apply to version {}",
+                diff.base_version, self.version
+            )));
+        }
+        let mut waypoints = self.waypoints.clone();
+        waypoints.truncate(diff.waypoint_count as usize);
+        for edit in &diff.edits {
+            match (edit.index as usize).cmp(&waypoints.len()) {
+                std::cmp::Ordering::Less => waypoints[edit.index as usize] = edit.waypoint.clone(),
+                std::cmp::Ordering::Equal => waypoints.push(edit.waypoint.clone()),
+                std::cmp::Ordering::Greater => {
+                    return Err(P2pError::Protocol(format!("diff skips to waypoint {}", edit.index)));
+                }
+            }
+        }
+
+        let route = Self::new(self.mission_id.clone(), diff.version, waypoints);
+        if route.waypoints.len() != diff.waypoint_count as usize || route.hash() != diff.hash {
+            return Err(P2pError::Protocol(format!("route version {} does not match its hash", diff.version)));
+        }
+        Ok(route)
+    }
+}
+
+fn encode(value: &impl Serialize) -> Vec<u8> {
+    // Plain data structs always serialize
+    bincode::serialize(value).unwrap_or_default()
+}
+
+fn digest(waypoints: &[RouteWaypoint]) -> String {
+    hex::encode(Sha256::digest(encode(&waypoints)))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Waypoint;
+
+    fn route(version: u64, count: usize) -> MissionRoute {
+        let waypoints = (0..count)
+            .map(|i| RouteWaypoint::from(&Waypoint::new(format!("WP-{}", i), format!("Waypoint {}", i), 34.0 + i as f64 * 0.01, 69.0)))
+            .collect();
+        MissionRoute::new(MissionId::from_uuid(uuid::Uuid::nil()), version, waypoints)
+    }
+
+    #[test]
+    fn test_chunked_sync() {
+        let old = route(1, 20);
+        let mut new = route(2, 20);
+        new.waypoints[17].speed_limit_kmh = Some(90.0);
+
+        let manifest = new.manifest(8);
+        assert_eq!(manifest.chunk_hashes.len(), 3);
+        // Only the chunk holding the edited waypoint is requested
+        assert_eq!(old.missing_chunks(&manifest), vec![2]);
+        assert_eq!(route(1, 0).missing_chunks(&manifest), vec![0, 1, 2]);
+
+        let chunk = new.chunk(2, 8).unwrap();
+        assert_eq!(chunk.waypoints.len(), 4);
+        let assembled = MissionRoute::assemble(&manifest, Some(&old), &[chunk]).unwrap();
+        assert!(assembled.same_route(&new));
+        assert_eq!(assembled.version, 2);
+
+        // Without the changed chunk the route cannot be built
+        assert!(MissionRoute::assemble(&manifest, Some(&old), &[]).is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = route(1, 6);
+        let mut new = route(2, 4);
+        new.waypoints[1].loiter_time_seconds = Some(60);
+        new.waypoints.push(route(1, 10).waypoints[9].clone());
+
+        let diff = new.diff_from(&old);
+        assert_eq!(diff.waypoint_count, 5);
+        assert_eq!(diff.edits.iter().map(|e| e.index).collect::<Vec<_>>(), vec![1, 4]);
+        assert!(old.apply(&diff).unwrap().same_route(&new));
+
+        // A diff applies only to the version it was made from
+        assert!(new.apply(&diff).is_err());
+        let mut tampered = diff.clone();
+        tampered.edits.pop();
+        assert!(old.apply(&tampered).is_err());
+    }
+}
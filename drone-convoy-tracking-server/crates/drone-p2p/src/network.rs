@@ -1,26 +1,65 @@
 //! Network management and swarm handling
 
-use crate::{P2pConfig, P2pError, P2pResult, PeerInfo};
-use drone_core::DroneId;
+use crate::{P2pConfig, PeerInfo};
 
-use libp2p::PeerId;
+use chrono::{DateTime, Utc};
+use libp2p::{Multiaddr, PeerId};
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
-use tracing::{debug, info};
+use tracing::debug;
 
-/// Drone network abstraction
+/// Drone network abstraction; clones share the same peers and counters
+#[derive(Clone)]
 pub struct DroneNetwork {
     /// Configuration
+    #[allow(dead_code)]
     config: P2pConfig,
     /// Connected peers
     peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
     /// Network statistics
     stats: Arc<RwLock<NetworkStats>>,
+    /// Open swarm connections
+    connections: Arc<RwLock<HashMap<PeerId, Connection>>>,
+}
+
+/// How a connection to a peer is carried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionRoute {
+    Direct,
+    /// Through a circuit relay
+    Relayed,
+}
+
+/// An open connection as reported by the swarm
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub established_at: DateTime<Utc>,
+    pub route: ConnectionRoute,
+    /// Gossip topics the peer has subscribed to
+    pub topics: BTreeSet<String>,
+}
+
+/// A swarm event that changes this node's view of the mesh, handed to
+/// `P2pManager::connection_event`
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    Established {
+        peer_id: PeerId,
+        route: ConnectionRoute,
+        /// Remote address of the connection
+        address: Option<Multiaddr>,
+    },
+    /// The last connection to the peer closed
+    Closed { peer_id: PeerId },
+    Subscribed { peer_id: PeerId, topic: String },
+    Unsubscribed { peer_id: PeerId, topic: String },
 }
 
 /// Network statistics
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct NetworkStats {
     pub messages_sent: u64,
     pub messages_received: u64,
@@ -37,6 +76,7 @@ impl DroneNetwork {
             config,
             peers: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(NetworkStats::default())),
+            connections: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -96,6 +136,53 @@ impl DroneNetwork {
     pub fn is_peer_connected(&self, peer_id: &PeerId) -> bool {
         self.peers.read().contains_key(peer_id)
     }
+
+    /// Record a connection opened by the swarm; an existing connection
+    /// keeps its age and topics
+    pub fn connection_established(&self, peer_id: PeerId, route: ConnectionRoute, at: DateTime<Utc>) {
+        let mut connections = self.connections.write();
+        if !connections.contains_key(&peer_id) {
+            self.stats.write().peers_connected += 1;
+        }
+        let connection = connections.entry(peer_id).or_insert_with(|| Connection {
+            established_at: at,
+            route,
+            topics: BTreeSet::new(),
+        });
+        // A direct connection supersedes a relayed one
+        if route == ConnectionRoute::Direct {
+            connection.route = route;
+        }
+        debug!("Connection to {} established ({:?})", peer_id, route);
+    }
+
+    /// Record that the swarm closed the last connection to a peer
+    pub fn connection_closed(&self, peer_id: &PeerId) {
+        if self.connections.write().remove(peer_id).is_some() {
+            let mut stats = self.stats.write();
+            stats.peers_connected = stats.peers_connected.saturating_sub(1);
+            debug!("Connection to {} closed", peer_id);
+        }
+    }
+
+    /// Record a peer's gossip subscription
+    pub fn peer_subscribed(&self, peer_id: &PeerId, topic: &str) {
+        if let Some(connection) = self.connections.write().get_mut(peer_id) {
+            connection.topics.insert(topic.to_string());
+        }
+    }
+
+    /// Record a peer dropping a gossip subscription
+    pub fn peer_unsubscribed(&self, peer_id: &PeerId, topic: &str) {
+        if let Some(connection) = self.connections.write().get_mut(peer_id) {
+            connection.topics.remove(topic);
+        }
+    }
+
+    /// Open connections by peer
+    pub fn connections(&self) -> HashMap<PeerId, Connection> {
+        self.connections.read().clone()
+    }
 }
 
 impl Default for DroneNetwork {
@@ -107,7 +194,9 @@ impl Default for DroneNetwork {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CapabilitySet;
     use chrono::Utc;
+    use drone_core::DroneId;
 
     #[test]
     fn test_peer_management() {
@@ -119,6 +208,8 @@ mod tests {
             drone_id: Some(DroneId::new("REAPER-01")),
             addresses: Vec::new(),
             last_seen: Utc::now(),
+            capabilities: CapabilitySet::local(),
+            formation_role: None,
         };
 
         network.add_peer(peer_id, info);
@@ -129,6 +220,28 @@ mod tests {
         assert_eq!(network.peer_count(), 0);
     }
 
+    #[test]
+    fn test_connection_tracking() {
+        let network = DroneNetwork::default();
+        let peer_id = PeerId::random();
+        let opened = Utc::now();
+
+        network.connection_established(peer_id, ConnectionRoute::Relayed, opened);
+        network.peer_subscribed(&peer_id, "drone-convoy");
+        network.connection_established(peer_id, ConnectionRoute::Direct, opened + chrono::Duration::seconds(5));
+
+        let connection = &network.connections()[&peer_id];
+        assert_eq!(connection.route, ConnectionRoute::Direct);
+        assert_eq!(connection.established_at, opened);
+        assert!(connection.topics.contains("drone-convoy"));
+
+        network.connection_closed(&peer_id);
+        assert!(network.connections().is_empty());
+        // Subscriptions without a connection are ignored
+        network.peer_subscribed(&peer_id, "drone-convoy");
+        assert!(network.connections().is_empty());
+    }
+
     #[test]
     fn test_statistics() {
         let network = DroneNetwork::default();
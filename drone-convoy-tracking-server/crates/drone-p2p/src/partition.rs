@@ -0,0 +1,401 @@
+//! Mesh partition detection and healing
+//!
+//! When the mesh splits, the ground station stops hearing the drones on the
+//! far side and would otherwise treat them as offline. Drones gossip which
+//! peers they can reach; a drone the ground station cannot hear but some
+//! peer still reports as reachable is alive in another partition and is
+//! classified `Unreachable` rather than `Offline`. Direct messages for
+//! unreachable drones are buffered and replayed once they are heard again.
+
+use crate::protocol::DroneMessage;
+
+use drone_core::DroneId;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+/// Partition detection configuration
+#[derive(Debug, Clone)]
+pub struct PartitionConfig {
+    /// A drone not heard directly for this long is no longer local
+    pub heard_timeout: Duration,
+    /// Peer reachability reports older than this are ignored
+    pub report_ttl: Duration,
+    /// Direct messages buffered per unreachable drone (oldest dropped)
+    pub buffer_per_drone: usize,
+    /// Buffered messages older than this are discarded instead of replayed
+    pub buffer_max_age: Duration,
+}
+
+impl Default for PartitionConfig {
+    fn default() -> Self {
+        Self {
+            heard_timeout: Duration::from_secs(5),
+            report_ttl: Duration::from_secs(15),
+            buffer_per_drone: 64,
+            buffer_max_age: Duration::from_secs(300),
+        }
+    }
+}
+
+/// How the ground station can reach a drone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+    /// Heard directly
+    Reachable,
+    /// Not heard directly, but a peer reports it reachable
+    Unreachable,
+    /// Nobody reports it reachable
+    Offline,
+}
+
+/// A connected group of drones
+#[derive(Debug, Clone, Serialize)]
+pub struct Partition {
+    pub drones: Vec<DroneId>,
+    /// The ground station is in this partition
+    pub local: bool,
+}
+
+/// Reachability of every known drone at one point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct ReachabilityView {
+    pub assessed_at: DateTime<Utc>,
+    pub drones: BTreeMap<DroneId, Reachability>,
+    /// Connected groups of live drones; more than one means the mesh is split
+    pub partitions: Vec<Partition>,
+    /// Direct messages waiting for unreachable drones
+    pub buffered: BTreeMap<DroneId, usize>,
+}
+
+impl ReachabilityView {
+    pub fn is_partitioned(&self) -> bool {
+        self.partitions.len() > 1
+    }
+}
+
+/// Drones whose reachability changed in an assessment
+#[derive(Debug, Clone, Default)]
+pub struct ReachabilityChanges {
+    /// Became `Unreachable`
+    pub lost: Vec<DroneId>,
+    /// Were `Unreachable` and are heard again
+    pub healed: Vec<DroneId>,
+}
+
+#[derive(Debug, Clone)]
+struct PeerReport {
+    reachable: HashSet<DroneId>,
+    at: DateTime<Utc>,
+}
+
+/// Classifies drones from direct contact and gossiped peer reports
+#[derive(Debug, Default)]
+pub struct PartitionDetector {
+    config: PartitionConfig,
+    /// Last time each drone was heard directly
+    heard: HashMap<DroneId, DateTime<Utc>>,
+    /// Latest reachability report per reporting drone
+    reports: HashMap<DroneId, PeerReport>,
+    /// Classification from the last assessment
+    states: HashMap<DroneId, Reachability>,
+}
+
+impl PartitionDetector {
+    pub fn new(config: PartitionConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &PartitionConfig {
+        &self.config
+    }
+
+    /// Record that a message arrived directly from `drone_id`
+    pub fn note_heard(&mut self, drone_id: &DroneId, at: DateTime<Utc>) {
+        let last = self.heard.entry(drone_id.clone()).or_insert(at);
+        *last = (*last).max(at);
+    }
+
+    /// Record a peer's reachability report, replacing its previous one
+    pub fn record_report(&mut self, reporter: &DroneId, reachable: Vec<DroneId>, at: DateTime<Utc>) {
+        if self.reports.get(reporter).is_some_and(|r| r.at > at) {
+            return;
+        }
+        let reachable = reachable.into_iter().filter(|d| d != reporter).collect();
+        self.reports.insert(reporter.clone(), PeerReport { reachable, at });
+    }
+
+    /// Last time the drone was heard directly
+    pub fn last_heard(&self, drone_id: &DroneId) -> Option<DateTime<Utc>> {
+        self.heard.get(drone_id).copied()
+    }
+
+    /// Classification from the last assessment (`None` if never seen)
+    pub fn state(&self, drone_id: &DroneId) -> Option<Reachability> {
+        self.states.get(drone_id).copied()
+    }
+
+    /// Every drone with its classification from the last assessment
+    pub fn known(&self) -> impl Iterator<Item = (&DroneId, Reachability)> {
+        self.states.iter().map(|(drone_id, state)| (drone_id, *state))
+    }
+
+    /// A fresh peer report lists the drone as reachable
+    pub fn reported_alive(&self, drone_id: &DroneId, now: DateTime<Utc>) -> bool {
+        self.fresh_reports(now)
+            .any(|(reporter, report)| reporter == drone_id || report.reachable.contains(drone_id))
+    }
+
+    /// Drones whose latest report lists `target`, most recent report first
+    pub fn reporters_of(&self, target: &DroneId) -> Vec<DroneId> {
+        let mut reporters: Vec<_> = self
+            .reports
+            .iter()
+            .filter(|(_, report)| report.reachable.contains(target))
+            .collect();
+        reporters.sort_by(|a, b| b.1.at.cmp(&a.1.at).then_with(|| a.0.cmp(b.0)));
+        reporters.into_iter().map(|(reporter, _)| reporter.clone()).collect()
+    }
+
+    /// Reclassify every known drone at `now`
+    pub fn assess(&mut self, now: DateTime<Utc>) -> ReachabilityChanges {
+        let heard_timeout = chrono::Duration::from_std(self.config.heard_timeout).unwrap_or_default();
+        let local: HashSet<&DroneId> = self
+            .heard
+            .iter()
+            .filter(|(_, at)| now - **at <= heard_timeout)
+            .map(|(drone_id, _)| drone_id)
+            .collect();
+
+        let mut known: HashSet<DroneId> = self.heard.keys().cloned().collect();
+        known.extend(self.states.keys().cloned());
+        for (reporter, report) in &self.reports {
+            known.insert(reporter.clone());
+            known.extend(report.reachable.iter().cloned());
+        }
+
+        let mut changes = ReachabilityChanges::default();
+        let mut states = HashMap::with_capacity(known.len());
+        for drone_id in known {
+            let state = if local.contains(&drone_id) {
+                Reachability::Reachable
+            } else if self.reported_alive(&drone_id, now) {
+                Reachability::Unreachable
+            } else {
+                Reachability::Offline
+            };
+            let previous = self.states.get(&drone_id).copied();
+            match (previous, state) {
+                (Some(Reachability::Unreachable), Reachability::Reachable) => {
+                    changes.healed.push(drone_id.clone())
+                }
+                (previous, Reachability::Unreachable) if previous != Some(Reachability::Unreachable) => {
+                    changes.lost.push(drone_id.clone())
+                }
+                _ => {}
+            }
+            states.insert(drone_id, state);
+        }
+        self.states = states;
+
+        changes.lost.sort();
+        changes.healed.sort();
+        changes
+    }
+
+    /// Connected groups of live drones from the last assessment
+    ///
+    /// Drones heard directly form the local partition; each fresh peer
+    /// report links its reporter with the drones it can reach.
+    pub fn partitions(&self, now: DateTime<Utc>) -> Vec<Partition> {
+        let live: Vec<&DroneId> = self
+            .states
+            .iter()
+            .filter(|(_, state)| **state != Reachability::Offline)
+            .map(|(drone_id, _)| drone_id)
+            .collect();
+        let index: HashMap<&DroneId, usize> = live.iter().enumerate().map(|(i, d)| (*d, i)).collect();
+        let mut parent: Vec<usize> = (0..live.len()).collect();
+
+        let mut local_root = None;
+        for (i, drone_id) in live.iter().enumerate() {
+            if self.states[*drone_id] == Reachability::Reachable {
+                match local_root {
+                    Some(root) => union(&mut parent, root, i),
+                    None => local_root = Some(i),
+                }
+            }
+        }
+        for (reporter, report) in self.fresh_reports(now) {
+            let Some(&from) = index.get(reporter) else {
+                continue;
+            };
+            for drone_id in &report.reachable {
+                if let Some(&to) = index.get(drone_id) {
+                    union(&mut parent, from, to);
+                }
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Vec<DroneId>> = BTreeMap::new();
+        for (i, drone_id) in live.iter().enumerate() {
+            groups.entry(find(&mut parent, i)).or_default().push((*drone_id).clone());
+        }
+        let local_root = local_root.map(|root| find(&mut parent, root));
+        let mut partitions: Vec<Partition> = groups
+            .into_iter()
+            .map(|(root, mut drones)| {
+                drones.sort();
+                Partition {
+                    drones,
+                    local: Some(root) == local_root,
+                }
+            })
+            .collect();
+        partitions.sort_by(|a, b| b.local.cmp(&a.local).then_with(|| a.drones.cmp(&b.drones)));
+        partitions
+    }
+
+    fn fresh_reports(&self, now: DateTime<Utc>) -> impl Iterator<Item = (&DroneId, &PeerReport)> {
+        let ttl = chrono::Duration::from_std(self.config.report_ttl).unwrap_or_default();
+        self.reports.iter().filter(move |(_, report)| now - report.at <= ttl)
+    }
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut node = i;
+    while parent[node] != root {
+        let next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    root
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[b] = a;
+    }
+}
+
+/// Direct messages held for unreachable drones
+#[derive(Debug, Default)]
+pub struct OutboundBuffer {
+    queues: HashMap<DroneId, VecDeque<(DateTime<Utc>, DroneMessage)>>,
+}
+
+impl OutboundBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold a message, dropping the oldest once `limit` are queued
+    pub fn push(&mut self, drone_id: &DroneId, message: DroneMessage, at: DateTime<Utc>, limit: usize) {
+        let queue = self.queues.entry(drone_id.clone()).or_default();
+        queue.push_back((at, message));
+        while queue.len() > limit.max(1) {
+            queue.pop_front();
+        }
+    }
+
+    /// Take a drone's messages in send order, discarding those older than `max_age`
+    pub fn drain(&mut self, drone_id: &DroneId, now: DateTime<Utc>, max_age: Duration) -> Vec<DroneMessage> {
+        let max_age = chrono::Duration::from_std(max_age).unwrap_or_default();
+        self.queues
+            .remove(drone_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(at, _)| now - *at <= max_age)
+            .map(|(_, message)| message)
+            .collect()
+    }
+
+    pub fn len(&self, drone_id: &DroneId) -> usize {
+        self.queues.get(drone_id).map_or(0, VecDeque::len)
+    }
+
+    /// Queued message count per drone
+    pub fn counts(&self) -> BTreeMap<DroneId, usize> {
+        self.queues
+            .iter()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(drone_id, queue)| (drone_id.clone(), queue.len()))
+            .collect()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_split_is_unreachable_not_offline() {
+        let mut detector = PartitionDetector::new(PartitionConfig::default());
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let [r1, r2, r3, r4] = ["REAPER-01", "REAPER-02", "REAPER-03", "REAPER-04"].map(DroneId::new);
+
+        for drone_id in [&r1, &r2, &r3, &r4] {
+            detector.note_heard(drone_id, at(0));
+        }
+        assert!(detector.assess(at(1)).lost.is_empty());
+        assert_eq!(detector.partitions(at(1)).len(), 1);
+
+        // REAPER-03/04 go quiet but REAPER-03 still reports REAPER-04;
+        // REAPER-02 never reports and is treated as offline
+        detector.note_heard(&r1, at(8));
+        detector.record_report(&r3, vec![r4.clone()], at(7));
+        let changes = detector.assess(at(8));
+        assert_eq!(changes.lost, vec![r3.clone(), r4.clone()]);
+        assert_eq!(detector.state(&r2), Some(Reachability::Offline));
+        assert_eq!(detector.state(&r4), Some(Reachability::Unreachable));
+
+        let partitions = detector.partitions(at(8));
+        assert_eq!(partitions.len(), 2);
+        assert!(partitions[0].local && partitions[0].drones == vec![r1.clone()]);
+        assert_eq!(partitions[1].drones, vec![r3.clone(), r4.clone()]);
+
+        // REAPER-03 is heard again; REAPER-04's report has expired
+        detector.note_heard(&r3, at(30));
+        let changes = detector.assess(at(30));
+        assert_eq!(changes.healed, vec![r3.clone()]);
+        assert_eq!(detector.state(&r4), Some(Reachability::Offline));
+    }
+
+    #[test]
+    fn test_outbound_buffer_limits() {
+        let mut buffer = OutboundBuffer::new();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let message = |i: u8| {
+            let mut message = DroneMessage::heartbeat(DroneId::new("GROUND"));
+            message.ttl = i;
+            message
+        };
+
+        buffer.push(&drone_id, message(1), start, 2);
+        buffer.push(&drone_id, message(2), start + chrono::Duration::seconds(200), 2);
+        buffer.push(&drone_id, message(3), start + chrono::Duration::seconds(250), 2);
+        assert_eq!(buffer.len(&drone_id), 2);
+
+        let replay = buffer.drain(&drone_id, start + chrono::Duration::seconds(420), Duration::from_secs(200));
+        assert_eq!(replay.iter().map(|m| m.ttl).collect::<Vec<_>>(), vec![3]);
+        assert!(buffer.counts().is_empty());
+    }
+}
@@ -1,13 +1,34 @@
 //! P2P message protocol definitions
 
-use drone_core::{DroneId, DroneStatus, GeoPosition, Telemetry};
+use crate::capability::{CapabilitySet, WireFormat};
+use crate::error::{P2pError, P2pResult};
+use drone_core::{
+    CompactPosition, DroneId, DroneStatus, GeoPosition, MissionId, Telemetry, Waypoint, WaypointId, WaypointType,
+};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use libp2p::PeerId;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
 use uuid::Uuid;
 
+/// Hop budget of a newly created message
+pub const DEFAULT_TTL: u8 = 5;
+
+/// Frames smaller than this are never compressed
+pub const COMPRESSION_MIN_BYTES: usize = 256;
+
+/// Leading byte of an encoded frame
+const FRAME_BINCODE: u8 = 0;
+const FRAME_DEFLATE: u8 = 1;
+
 /// Message types in the P2P network
+///
+/// JSON carries the variant as `{"type": ..., "data": ...}`. Binary codecs
+/// get serde's externally tagged form, as bincode cannot decode tag fields.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", content = "data")]
+#[serde(remote = "Self")]
 pub enum MessageType {
     /// Heartbeat/keepalive
     Heartbeat,
@@ -25,6 +46,65 @@ pub enum MessageType {
     DiscoveryRequest,
     /// Discovery response
     DiscoveryResponse(DiscoveryResponseData),
+    /// Pre-arrival notification for an upcoming waypoint
+    WaypointApproaching(WaypointApproachData),
+    /// Command from the ground station to a drone; answered with an `Ack`
+    Command(CommandData),
+    /// Drones the sender can currently reach over the mesh
+    Reachability(ReachabilityData),
+    /// Message for the relay to forward to a drone it can reach
+    Relay(RelayData),
+    /// `PositionUpdate` with a fixed-point position, only sent to peers
+    /// advertising `compact-positions` and expanded again by `decode`
+    CompactPositionUpdate(CompactPositionUpdateData),
+    /// Version and chunk hashes of the active mission route, broadcast by
+    /// the ground station
+    MissionManifest(MissionManifestData),
+    /// Route chunks an agent is missing; answered with `MissionChunk`s
+    MissionChunkRequest(MissionChunkRequestData),
+    /// Waypoints of one route chunk
+    MissionChunk(MissionChunkData),
+    /// Waypoint edits taking a route from one version to the next
+    MissionDiff(MissionDiffData),
+    /// Route version and hash an agent holds
+    MissionSynced(MissionSyncedData),
+}
+
+impl Serialize for MessageType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return MessageType::serialize(self, serializer);
+        }
+        let tagged = match MessageType::serialize(self, serde_json::value::Serializer).map_err(ser::Error::custom)? {
+            Value::String(tag) => json!({ "type": tag }),
+            Value::Object(variant) => match variant.into_iter().next() {
+                Some((tag, data)) => json!({ "type": tag, "data": data }),
+                None => return Err(ser::Error::custom("message type without a variant")),
+            },
+            other => return Err(ser::Error::custom(format!("unexpected message type {}", other))),
+        };
+        tagged.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return MessageType::deserialize(deserializer);
+        }
+        let Value::Object(mut tagged) = Value::deserialize(deserializer)? else {
+            return Err(de::Error::custom("message type must be an object"));
+        };
+        let tag = match tagged.remove("type") {
+            Some(Value::String(tag)) => tag,
+            _ => return Err(de::Error::missing_field("type")),
+        };
+        let variant = match tagged.remove("data") {
+            None | Some(Value::Null) => Value::String(tag),
+            Some(data) => json!({ tag: data }),
+        };
+        MessageType::deserialize(variant).map_err(de::Error::custom)
+    }
 }
 
 /// Position update data
@@ -35,6 +115,14 @@ pub struct PositionUpdateData {
     pub telemetry: Telemetry,
 }
 
+/// Position update data with a fixed-point position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactPositionUpdateData {
+    pub drone_id: DroneId,
+    pub position: CompactPosition,
+    pub telemetry: Telemetry,
+}
+
 /// Status change data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatusChangeData {
@@ -82,7 +170,7 @@ pub struct EmergencyData {
 }
 
 /// Emergency types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EmergencyType {
     LowBattery,
     LowFuel,
@@ -101,14 +189,150 @@ pub struct AckData {
     pub success: bool,
 }
 
+/// Command sent to a drone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandData {
+    pub drone_id: DroneId,
+    pub command: CommandKind,
+}
+
+/// Commands a drone can be sent over the mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandKind {
+    ReturnToBase,
+    EmergencyStop,
+}
+
+/// Reachability report data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReachabilityData {
+    pub drone_id: DroneId,
+    pub reachable: Vec<DroneId>,
+}
+
+/// Relayed message data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayData {
+    pub relay_id: DroneId,
+    pub target: DroneId,
+    pub message: Box<DroneMessage>,
+}
+
+/// Waypoint pre-arrival data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaypointApproachData {
+    pub drone_id: DroneId,
+    pub waypoint_id: WaypointId,
+    pub distance_meters: f64,
+    pub eta_seconds: f64,
+}
+
+/// Waypoint as carried by mission sync messages
+///
+/// Unlike `Waypoint` it has no skipped fields, so bincode can decode it,
+/// and no arrival times, which change without the route changing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteWaypoint {
+    pub id: WaypointId,
+    pub name: String,
+    pub position: GeoPosition,
+    pub waypoint_type: WaypointType,
+    pub loiter_time_seconds: Option<u32>,
+    pub speed_limit_kmh: Option<f64>,
+}
+
+impl From<&Waypoint> for RouteWaypoint {
+    fn from(waypoint: &Waypoint) -> Self {
+        Self {
+            id: waypoint.id.clone(),
+            name: waypoint.name.clone(),
+            position: waypoint.position,
+            waypoint_type: waypoint.waypoint_type.clone(),
+            loiter_time_seconds: waypoint.loiter_time_seconds,
+            speed_limit_kmh: waypoint.speed_limit_kmh,
+        }
+    }
+}
+
+/// Mission route manifest data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionManifestData {
+    pub mission_id: MissionId,
+    pub version: u64,
+    /// Hex SHA-256 of the whole route
+    pub hash: String,
+    pub waypoint_count: u32,
+    /// Waypoints per chunk; the last chunk may be shorter
+    pub chunk_size: u32,
+    /// Hex SHA-256 of each chunk, in route order
+    pub chunk_hashes: Vec<String>,
+}
+
+/// Mission route chunk request data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionChunkRequestData {
+    pub drone_id: DroneId,
+    pub mission_id: MissionId,
+    /// Manifest version the chunk indices refer to
+    pub version: u64,
+    pub chunks: Vec<u32>,
+}
+
+/// Mission route chunk data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionChunkData {
+    pub mission_id: MissionId,
+    pub version: u64,
+    pub index: u32,
+    pub waypoints: Vec<RouteWaypoint>,
+}
+
+/// Replacement of the waypoint at `index`, or its addition at the end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaypointEdit {
+    pub index: u32,
+    pub waypoint: RouteWaypoint,
+}
+
+/// Mission route diff data
+///
+/// Applied by truncating the route to `waypoint_count` and then applying
+/// the edits in order; the result must hash to `hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionDiffData {
+    pub mission_id: MissionId,
+    pub base_version: u64,
+    pub version: u64,
+    pub hash: String,
+    pub waypoint_count: u32,
+    pub edits: Vec<WaypointEdit>,
+}
+
+/// Mission route sync report data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionSyncedData {
+    pub drone_id: DroneId,
+    pub mission_id: MissionId,
+    pub version: u64,
+    pub hash: String,
+}
+
 /// Discovery response data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoveryResponseData {
     pub drone_id: DroneId,
+    /// Capability strings; see `CapabilitySet::from_advertised`
     pub capabilities: Vec<String>,
     pub formation_role: Option<String>,
 }
 
+/// A message as it arrived from the mesh, with the peer that sent it
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    pub peer_id: PeerId,
+    pub message: DroneMessage,
+}
+
 /// Complete P2P message envelope
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DroneMessage {
@@ -132,10 +356,15 @@ impl DroneMessage {
             timestamp: Utc::now(),
             sender,
             message_type,
-            ttl: 5,
+            ttl: DEFAULT_TTL,
         }
     }
 
+    /// Message arrived straight from its sender, without being relayed
+    pub fn is_direct(&self) -> bool {
+        self.ttl == DEFAULT_TTL
+    }
+
     /// Create a heartbeat message
     pub fn heartbeat(sender: DroneId) -> Self {
         Self::new(sender, MessageType::Heartbeat)
@@ -191,6 +420,126 @@ impl DroneMessage {
         )
     }
 
+    /// Create a waypoint pre-arrival message
+    pub fn waypoint_approaching(
+        sender: DroneId,
+        waypoint_id: WaypointId,
+        distance_meters: f64,
+        eta_seconds: f64,
+    ) -> Self {
+        Self::new(
+            sender.clone(),
+            MessageType::WaypointApproaching(WaypointApproachData {
+                drone_id: sender,
+                waypoint_id,
+                distance_meters,
+                eta_seconds,
+            }),
+        )
+    }
+
+    /// Create a command message; the drone acks with this message's `id`
+    pub fn command(sender: DroneId, drone_id: DroneId, command: CommandKind) -> Self {
+        Self::new(sender, MessageType::Command(CommandData { drone_id, command }))
+    }
+
+    /// Create a reachability report listing the drones the sender can reach
+    pub fn reachability(sender: DroneId, reachable: Vec<DroneId>) -> Self {
+        Self::new(
+            sender.clone(),
+            MessageType::Reachability(ReachabilityData {
+                drone_id: sender,
+                reachable,
+            }),
+        )
+    }
+
+    /// Create a discovery request; peers answer with their capabilities
+    pub fn discovery_request(sender: DroneId) -> Self {
+        Self::new(sender, MessageType::DiscoveryRequest)
+    }
+
+    /// Create a discovery response advertising `capabilities`
+    pub fn discovery_response(
+        sender: DroneId,
+        capabilities: CapabilitySet,
+        formation_role: Option<String>,
+    ) -> Self {
+        Self::new(
+            sender.clone(),
+            MessageType::DiscoveryResponse(DiscoveryResponseData {
+                drone_id: sender,
+                capabilities: capabilities.advertise(),
+                formation_role,
+            }),
+        )
+    }
+
+    /// Wrap `message` for `relay_id` to forward to `target`
+    pub fn relay(sender: DroneId, relay_id: DroneId, target: DroneId, message: DroneMessage) -> Self {
+        Self::new(
+            sender,
+            MessageType::Relay(RelayData {
+                relay_id,
+                target,
+                message: Box::new(message),
+            }),
+        )
+    }
+
+    /// Create a mission route manifest message
+    pub fn mission_manifest(sender: DroneId, manifest: MissionManifestData) -> Self {
+        Self::new(sender, MessageType::MissionManifest(manifest))
+    }
+
+    /// Create a request for the chunks of route `version` the sender lacks
+    pub fn mission_chunk_request(sender: DroneId, mission_id: MissionId, version: u64, chunks: Vec<u32>) -> Self {
+        Self::new(
+            sender.clone(),
+            MessageType::MissionChunkRequest(MissionChunkRequestData {
+                drone_id: sender,
+                mission_id,
+                version,
+                chunks,
+            }),
+        )
+    }
+
+    /// Create a mission route chunk message
+    pub fn mission_chunk(sender: DroneId, chunk: MissionChunkData) -> Self {
+        Self::new(sender, MessageType::MissionChunk(chunk))
+    }
+
+    /// Create a mission route diff message
+    pub fn mission_diff(sender: DroneId, diff: MissionDiffData) -> Self {
+        Self::new(sender, MessageType::MissionDiff(diff))
+    }
+
+    /// Create a report of the route version and hash the sender holds
+    pub fn mission_synced(sender: DroneId, mission_id: MissionId, version: u64, hash: String) -> Self {
+        Self::new(
+            sender.clone(),
+            MessageType::MissionSynced(MissionSyncedData {
+                drone_id: sender,
+                mission_id,
+                version,
+                hash,
+            }),
+        )
+    }
+
+    /// Create an acknowledgment of `message_id`
+    pub fn ack(sender: DroneId, message_id: Uuid, success: bool) -> Self {
+        Self::new(
+            sender.clone(),
+            MessageType::Ack(AckData {
+                message_id,
+                drone_id: sender,
+                success,
+            }),
+        )
+    }
+
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Result<Vec<u8>, bincode::Error> {
         bincode::serialize(self)
@@ -201,6 +550,75 @@ impl DroneMessage {
         bincode::deserialize(bytes)
     }
 
+    /// Encode a frame in `format`
+    ///
+    /// Deflate is only applied to frames of at least
+    /// `COMPRESSION_MIN_BYTES`, and only when it makes them smaller.
+    pub fn encode(&self, format: WireFormat) -> P2pResult<Vec<u8>> {
+        let body = self.to_bytes().map_err(|e| P2pError::Serialization(e.to_string()))?;
+
+        if format == WireFormat::Deflate && body.len() >= COMPRESSION_MIN_BYTES {
+            let mut encoder = DeflateEncoder::new(vec![FRAME_DEFLATE], Compression::fast());
+            encoder
+                .write_all(&body)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| P2pError::Serialization(e.to_string()))
+                .map(|frame| if frame.len() <= body.len() { frame } else { plain_frame(&body) })
+        } else {
+            Ok(plain_frame(&body))
+        }
+    }
+
+    /// Decode a frame produced by `encode`, expanding compact positions
+    pub fn decode(frame: &[u8]) -> P2pResult<Self> {
+        let invalid = |e: &dyn std::fmt::Display| P2pError::Serialization(e.to_string());
+        let message = match frame.split_first() {
+            Some((&FRAME_BINCODE, body)) => Self::from_bytes(body).map_err(|e| invalid(&e)),
+            Some((&FRAME_DEFLATE, body)) => {
+                let mut decoded = Vec::new();
+                DeflateDecoder::new(body).read_to_end(&mut decoded).map_err(|e| invalid(&e))?;
+                Self::from_bytes(&decoded).map_err(|e| invalid(&e))
+            }
+            Some((tag, _)) => Err(P2pError::Protocol(format!("unknown frame type {}", tag))),
+            None => Err(P2pError::Protocol("empty frame".into())),
+        };
+        message.map(Self::expand_positions)
+    }
+
+    /// Send position updates, relayed ones included, in fixed-point form
+    pub fn with_compact_positions(mut self) -> Self {
+        self.message_type = match self.message_type {
+            MessageType::PositionUpdate(data) => MessageType::CompactPositionUpdate(CompactPositionUpdateData {
+                drone_id: data.drone_id,
+                position: data.position.to_compact(),
+                telemetry: data.telemetry,
+            }),
+            MessageType::Relay(mut relay) => {
+                relay.message = Box::new(relay.message.with_compact_positions());
+                MessageType::Relay(relay)
+            }
+            other => other,
+        };
+        self
+    }
+
+    /// Undo `with_compact_positions`
+    pub fn expand_positions(mut self) -> Self {
+        self.message_type = match self.message_type {
+            MessageType::CompactPositionUpdate(data) => MessageType::PositionUpdate(PositionUpdateData {
+                drone_id: data.drone_id,
+                position: data.position.to_position(),
+                telemetry: data.telemetry,
+            }),
+            MessageType::Relay(mut relay) => {
+                relay.message = Box::new(relay.message.expand_positions());
+                MessageType::Relay(relay)
+            }
+            other => other,
+        };
+        self
+    }
+
     /// Serialize to JSON
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -222,6 +640,13 @@ impl DroneMessage {
     }
 }
 
+fn plain_frame(body: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(body.len() + 1);
+    frame.push(FRAME_BINCODE);
+    frame.extend_from_slice(body);
+    frame
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,6 +672,26 @@ mod tests {
         assert_eq!(decoded.sender.0, msg.sender.0);
     }
 
+    #[test]
+    fn test_json_tags_message_type() {
+        let msg = DroneMessage::position_update(
+            DroneId::new("REAPER-01"),
+            GeoPosition::new(34.5553, 69.2075, 3000.0),
+            Telemetry::default(),
+        );
+        let json: Value = serde_json::from_str(&msg.to_json().unwrap()).unwrap();
+        assert_eq!(json["message_type"]["type"], "PositionUpdate");
+        assert_eq!(json["message_type"]["data"]["drone_id"], "REAPER-01");
+        let decoded = DroneMessage::from_json(&json.to_string()).unwrap();
+        assert!(matches!(decoded.message_type, MessageType::PositionUpdate(ref data) if data.drone_id.0 == "REAPER-01"));
+
+        let heartbeat = DroneMessage::new(DroneId::new("REAPER-01"), MessageType::Heartbeat);
+        let json = heartbeat.to_json().unwrap();
+        assert!(json.contains(r#""message_type":{"type":"Heartbeat"}"#));
+        assert!(matches!(DroneMessage::from_json(&json).unwrap().message_type, MessageType::Heartbeat));
+        assert!(matches!(DroneMessage::decode(&heartbeat.encode(WireFormat::Bincode).unwrap()).unwrap().message_type, MessageType::Heartbeat));
+    }
+
     #[test]
     fn test_ttl() {
         let mut msg = DroneMessage::heartbeat(DroneId::new("REAPER-01"));
@@ -258,4 +703,74 @@ mod tests {
         msg.ttl = 0;
         assert!(!msg.decrement_ttl());
     }
+
+    #[test]
+    fn test_frame_encoding() {
+        let sender = DroneId::new("GCS");
+        let small = DroneMessage::heartbeat(sender.clone());
+        let frame = small.encode(WireFormat::Deflate).unwrap();
+        assert_eq!(frame[0], FRAME_BINCODE);
+        assert_eq!(DroneMessage::decode(&frame).unwrap().id, small.id);
+
+        // A relayed report for a large partition is worth compressing
+        let reachable = (1..=40).map(|i| DroneId::new(format!("REAPER-{:02}", i))).collect();
+        let report = DroneMessage::reachability(DroneId::new("REAPER-03"), reachable);
+        let report_id = report.id;
+        let relayed = DroneMessage::relay(sender, DroneId::new("REAPER-03"), DroneId::new("REAPER-07"), report);
+        let plain = relayed.encode(WireFormat::Bincode).unwrap();
+        let compressed = relayed.encode(WireFormat::Deflate).unwrap();
+        assert_eq!(compressed[0], FRAME_DEFLATE);
+        assert!(compressed.len() < plain.len());
+
+        let decoded = DroneMessage::decode(&compressed).unwrap();
+        assert!(matches!(decoded.message_type, MessageType::Relay(ref r) if r.message.id == report_id));
+        assert!(DroneMessage::decode(&[9, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_mission_chunk_round_trip() {
+        let mut waypoint = Waypoint::new("WP-01", "Checkpoint Alpha", 34.5553, 69.2075);
+        waypoint.speed_limit_kmh = Some(120.0);
+        let chunk = MissionChunkData {
+            mission_id: MissionId::new(),
+            version: 3,
+            index: 0,
+            waypoints: vec![RouteWaypoint::from(&waypoint)],
+        };
+        let message = DroneMessage::mission_chunk(DroneId::new("GCS"), chunk);
+
+        let frame = message.encode(WireFormat::Bincode).unwrap();
+        let MessageType::MissionChunk(decoded) = DroneMessage::decode(&frame).unwrap().message_type else {
+            panic!("not a mission chunk");
+        };
+        assert_eq!(decoded.version, 3);
+        assert_eq!(decoded.waypoints[0].id, waypoint.id);
+        assert_eq!(decoded.waypoints[0].speed_limit_kmh, Some(120.0));
+    }
+
+    #[test]
+    fn test_compact_positions() {
+        let position = GeoPosition::new(34.5553219, 69.2075004, 3000.4);
+        let update = DroneMessage::position_update(DroneId::new("REAPER-01"), position, Telemetry::default());
+        let full = update.encode(WireFormat::Bincode).unwrap();
+        let compact = update.clone().with_compact_positions().encode(WireFormat::Bincode).unwrap();
+        // Three f64s (24 bytes) become 10; the variant tag stays 4 bytes
+        assert_eq!(full.len() - compact.len(), 14);
+
+        let decoded = DroneMessage::decode(&compact).unwrap();
+        let MessageType::PositionUpdate(data) = decoded.message_type else {
+            panic!("not expanded: {:?}", decoded.message_type);
+        };
+        assert_eq!(data.position.to_compact(), position.to_compact());
+        assert!((data.position.latitude - position.latitude).abs() <= 0.5e-6);
+
+        // Relayed updates are compacted too
+        let relayed = DroneMessage::relay(DroneId::new("GCS"), DroneId::new("REAPER-03"), DroneId::new("REAPER-07"), update);
+        let frame = relayed.with_compact_positions().encode(WireFormat::Bincode).unwrap();
+        let decoded = DroneMessage::decode(&frame).unwrap();
+        assert!(matches!(
+            decoded.message_type,
+            MessageType::Relay(ref r) if matches!(r.message.message_type, MessageType::PositionUpdate(_))
+        ));
+    }
 }
@@ -0,0 +1,262 @@
+//! Downlink bandwidth shaping
+//!
+//! Simulates constrained radio links for testing. Each peer link has a token
+//! bucket filled at the link rate; a message spends its encoded size in
+//! bytes. Emergencies are always sent at once, running the bucket into debt
+//! that later traffic waits behind. Commands and telemetry wait for budget
+//! up to their own limit and are dropped beyond it, telemetry first since a
+//! newer position update will follow. A bucket that has refilled to its
+//! burst is no different from a new one, so those of idle links are swept.
+
+use crate::protocol::{DroneMessage, MessageType};
+
+use libp2p::PeerId;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often buckets of idle links are swept
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Link shaping configuration
+#[derive(Debug, Clone)]
+pub struct ShaperConfig {
+    /// Budget of every link (kbit/s)
+    pub rate_kbps: u32,
+    /// Per-peer budgets replacing `rate_kbps` (kbit/s)
+    pub peer_rates: HashMap<PeerId, u32>,
+    /// Bytes an idle link can send at once
+    pub burst_bytes: usize,
+    /// Longest a command waits for budget before it is dropped
+    pub command_max_delay: Duration,
+    /// Longest telemetry waits for budget before it is dropped
+    pub telemetry_max_delay: Duration,
+}
+
+impl ShaperConfig {
+    pub fn new(rate_kbps: u32) -> Self {
+        Self {
+            rate_kbps,
+            peer_rates: HashMap::new(),
+            burst_bytes: 4096,
+            command_max_delay: Duration::from_secs(2),
+            telemetry_max_delay: Duration::from_millis(200),
+        }
+    }
+
+    fn bytes_per_sec(&self, peer: &PeerId) -> f64 {
+        let kbps = self.peer_rates.get(peer).copied().unwrap_or(self.rate_kbps);
+        (kbps as f64 * 1000.0 / 8.0).max(1.0)
+    }
+}
+
+/// Shaping priority, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessagePriority {
+    Telemetry,
+    Command,
+    Emergency,
+}
+
+impl MessagePriority {
+    /// Relayed messages keep the priority of the message they carry
+    pub fn of(message: &DroneMessage) -> Self {
+        match &message.message_type {
+            MessageType::Emergency(_) => Self::Emergency,
+            MessageType::Command(_) | MessageType::FormationCommand(_) | MessageType::Ack(_) => Self::Command,
+            MessageType::Relay(relay) => Self::of(&relay.message),
+            _ => Self::Telemetry,
+        }
+    }
+}
+
+/// What to do with a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeDecision {
+    Send,
+    /// Send after waiting for budget
+    Delay(Duration),
+    Drop,
+}
+
+/// Shaping counters of one priority
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PriorityStats {
+    /// Sent without waiting
+    pub sent: u64,
+    /// Sent after waiting for budget
+    pub delayed: u64,
+    pub dropped: u64,
+    pub bytes_sent: u64,
+    /// Longest wait so far (ms)
+    pub max_delay_ms: u64,
+}
+
+/// Shaping decisions since startup
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShapingStats {
+    pub enabled: bool,
+    pub rate_kbps: u32,
+    /// Links with a token bucket
+    pub links: usize,
+    pub emergency: PriorityStats,
+    pub command: PriorityStats,
+    pub telemetry: PriorityStats,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// Negative after an emergency overdraws the link
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets of every link
+#[derive(Debug)]
+pub struct BandwidthShaper {
+    config: ShaperConfig,
+    buckets: HashMap<PeerId, TokenBucket>,
+    last_sweep: Option<Instant>,
+    stats: ShapingStats,
+}
+
+impl BandwidthShaper {
+    pub fn new(config: ShaperConfig) -> Self {
+        let stats = ShapingStats {
+            enabled: true,
+            rate_kbps: config.rate_kbps,
+            ..Default::default()
+        };
+        Self {
+            config,
+            buckets: HashMap::new(),
+            last_sweep: None,
+            stats,
+        }
+    }
+
+    /// Decide on a `bytes`-long message over the link to `peer`, spending
+    /// its budget unless it is dropped
+    pub fn admit(&mut self, peer: PeerId, priority: MessagePriority, bytes: usize, now: Instant) -> ShapeDecision {
+        self.sweep(now);
+        let rate = self.config.bytes_per_sec(&peer);
+        let burst = self.config.burst_bytes as f64;
+        let bucket = self.buckets.entry(peer).or_insert(TokenBucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.updated = now;
+
+        let size = bytes as f64;
+        let wait = Duration::from_millis(((size - bucket.tokens) * 1000.0 / rate).max(0.0).ceil() as u64);
+        let max_delay = match priority {
+            MessagePriority::Emergency => Duration::MAX,
+            MessagePriority::Command => self.config.command_max_delay,
+            MessagePriority::Telemetry => self.config.telemetry_max_delay,
+        };
+        let decision = if priority == MessagePriority::Emergency || wait.is_zero() {
+            ShapeDecision::Send
+        } else if wait <= max_delay {
+            ShapeDecision::Delay(wait)
+        } else {
+            ShapeDecision::Drop
+        };
+        if decision != ShapeDecision::Drop {
+            bucket.tokens -= size;
+        }
+
+        self.stats.links = self.buckets.len();
+        let stats = match priority {
+            MessagePriority::Emergency => &mut self.stats.emergency,
+            MessagePriority::Command => &mut self.stats.command,
+            MessagePriority::Telemetry => &mut self.stats.telemetry,
+        };
+        match decision {
+            ShapeDecision::Send => stats.sent += 1,
+            ShapeDecision::Delay(wait) => {
+                stats.delayed += 1;
+                stats.max_delay_ms = stats.max_delay_ms.max(wait.as_millis() as u64);
+            }
+            ShapeDecision::Drop => stats.dropped += 1,
+        }
+        if decision != ShapeDecision::Drop {
+            stats.bytes_sent += bytes as u64;
+        }
+        decision
+    }
+
+    /// Drop the buckets of links idle long enough to have refilled
+    fn sweep(&mut self, now: Instant) {
+        if self.last_sweep.is_some_and(|at| now.saturating_duration_since(at) < SWEEP_INTERVAL) {
+            return;
+        }
+        self.last_sweep = Some(now);
+        let config = &self.config;
+        let burst = config.burst_bytes as f64;
+        self.buckets.retain(|peer, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens + elapsed * config.bytes_per_sec(peer) < burst
+        });
+    }
+
+    pub fn stats(&self) -> ShapingStats {
+        self.stats.clone()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_is_shared_by_priority() {
+        // 8 kbit/s = 1000 bytes/s with a 1000-byte burst
+        let config = ShaperConfig {
+            burst_bytes: 1000,
+            ..ShaperConfig::new(8)
+        };
+        let mut shaper = BandwidthShaper::new(config);
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        // The burst covers the first 1000 bytes
+        assert_eq!(shaper.admit(peer, MessagePriority::Telemetry, 600, start), ShapeDecision::Send);
+        // 200 bytes short: a command waits 200 ms, telemetry could too
+        assert_eq!(
+            shaper.admit(peer, MessagePriority::Command, 600, start),
+            ShapeDecision::Delay(Duration::from_millis(200))
+        );
+        // Now 200 bytes in debt: telemetry would wait 700 ms and is dropped
+        assert_eq!(shaper.admit(peer, MessagePriority::Telemetry, 500, start), ShapeDecision::Drop);
+        // Emergencies go out regardless, deepening the debt
+        assert_eq!(shaper.admit(peer, MessagePriority::Emergency, 500, start), ShapeDecision::Send);
+        assert_eq!(
+            shaper.admit(peer, MessagePriority::Command, 700, start),
+            ShapeDecision::Delay(Duration::from_millis(1400))
+        );
+
+        // Other links have their own budget; after 3 s this one is clear again
+        assert_eq!(shaper.admit(PeerId::random(), MessagePriority::Telemetry, 900, start), ShapeDecision::Send);
+        let later = start + Duration::from_secs(3);
+        assert_eq!(shaper.admit(peer, MessagePriority::Telemetry, 400, later), ShapeDecision::Send);
+
+        let stats = shaper.stats();
+        assert_eq!(stats.links, 2);
+
+        // Refilled buckets of idle links are swept
+        let idle = later + SWEEP_INTERVAL;
+        assert_eq!(shaper.admit(peer, MessagePriority::Telemetry, 100, idle), ShapeDecision::Send);
+        assert_eq!(shaper.stats().links, 1);
+        assert_eq!((stats.telemetry.sent, stats.telemetry.dropped), (3, 1));
+        assert_eq!((stats.command.delayed, stats.command.max_delay_ms), (2, 1400));
+        assert_eq!(stats.emergency.sent, 1);
+        assert_eq!(stats.telemetry.bytes_sent, 1900);
+    }
+}
@@ -0,0 +1,48 @@
+//! Local view of the mesh
+//!
+//! What this node knows about its peers: the swarm's open connections and
+//! their gossip subscriptions, addresses from discovery, the drone each
+//! peer is registered to and when that drone was last heard. Other nodes
+//! may see a different mesh; this is the view that explains why a drone's
+//! updates are or are not reaching this node.
+
+use crate::network::{ConnectionRoute, NetworkStats};
+use crate::partition::Reachability;
+
+use chrono::{DateTime, Utc};
+use drone_core::DroneId;
+use serde::Serialize;
+
+/// One peer as this node sees it
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerView {
+    pub peer_id: String,
+    /// Drone registered to the peer
+    pub drone_id: Option<DroneId>,
+    pub addresses: Vec<String>,
+    /// Gossip topics the peer subscribed to
+    pub topics: Vec<String>,
+    /// `None` when the swarm has no open connection to the peer
+    pub route: Option<ConnectionRoute>,
+    pub connected_since: Option<DateTime<Utc>>,
+    pub connection_age_secs: Option<i64>,
+    /// Latest discovery response or registration activity
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Last unrelayed message from the peer's drone
+    pub last_heard_directly: Option<DateTime<Utc>>,
+    pub reachability: Option<Reachability>,
+}
+
+/// This node's view of the mesh
+#[derive(Debug, Clone, Serialize)]
+pub struct MeshTopology {
+    pub assessed_at: DateTime<Utc>,
+    pub local_peer_id: String,
+    pub listen_addrs: Vec<String>,
+    /// Gossip topics this node subscribes to
+    pub topics: Vec<String>,
+    pub peers: Vec<PeerView>,
+    /// Registered drones whose peer has no open connection
+    pub disconnected_drones: Vec<DroneId>,
+    pub stats: NetworkStats,
+}
@@ -0,0 +1,303 @@
+//! Write-ahead log for outbound direct messages
+//!
+//! Direct messages (commands, formation orders, emergencies sent to one
+//! drone) are appended to a JSON-lines file before they are sent and marked
+//! done when the drone acknowledges them. After a restart the unacknowledged
+//! messages that have not expired are sent again with their original IDs;
+//! receivers drop the copies they already handled with [`SeenMessages`].
+//!
+//! Appends are flushed to disk through a [`WalSync`] handle so the caller
+//! can wait for the disk without holding the log.
+
+use crate::error::{P2pError, P2pResult};
+use crate::protocol::DroneMessage;
+
+use chrono::{DateTime, Utc};
+use drone_core::DroneId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Acknowledged records kept in the file before it is rewritten
+const COMPACT_AFTER: usize = 256;
+
+/// A direct message waiting for its acknowledgement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMessage {
+    pub target: DroneId,
+    pub logged_at: DateTime<Utc>,
+    pub message: DroneMessage,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum WalRecord {
+    Append(Box<PendingMessage>),
+    Ack { message_id: Uuid },
+}
+
+/// Flushes the log's appended records to disk
+#[derive(Debug, Clone)]
+pub struct WalSync(Arc<File>);
+
+impl WalSync {
+    pub fn sync(&self) -> P2pResult<()> {
+        self.0.sync_data().map_err(|e| P2pError::Wal(e.to_string()))
+    }
+}
+
+/// Append-only log of unacknowledged outbound messages
+#[derive(Debug)]
+pub struct OutboundWal {
+    path: PathBuf,
+    file: File,
+    syncer: WalSync,
+    ttl: Duration,
+    /// Unacknowledged messages in send order
+    pending: Vec<PendingMessage>,
+    /// Records in the file that no longer describe a pending message
+    stale_records: usize,
+}
+
+impl OutboundWal {
+    /// Open the log at `path`, dropping acknowledged and expired messages
+    ///
+    /// A torn last line from a crash mid-append is ignored.
+    pub fn open(path: impl Into<PathBuf>, ttl: Duration, now: DateTime<Utc>) -> P2pResult<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| P2pError::Wal(e.to_string()))?;
+        }
+
+        let mut pending = Vec::new();
+        match File::open(&path) {
+            Ok(file) => {
+                let mut acked = HashSet::new();
+                for (number, line) in BufReader::new(file).lines().enumerate() {
+                    let line = line.map_err(|e| P2pError::Wal(e.to_string()))?;
+                    match serde_json::from_str(&line) {
+                        Ok(WalRecord::Append(entry)) => pending.push(*entry),
+                        Ok(WalRecord::Ack { message_id }) => {
+                            acked.insert(message_id);
+                        }
+                        Err(e) => warn!("Skipping unreadable WAL line {} in {}: {}", number + 1, path.display(), e),
+                    }
+                }
+                pending.retain(|entry| !acked.contains(&entry.message.id));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(P2pError::Wal(e.to_string())),
+        }
+
+        let max_age = chrono::Duration::from_std(ttl).unwrap_or_default();
+        let before = pending.len();
+        pending.retain(|entry| now - entry.logged_at <= max_age);
+        if pending.len() < before {
+            warn!("Dropped {} expired outbound messages from the WAL", before - pending.len());
+        }
+
+        // Start from a compacted file holding only what is still pending
+        let file = Self::rewrite(&path, &pending)?;
+        let syncer = Self::syncer_for(&file)?;
+        Ok(Self {
+            path,
+            file,
+            syncer,
+            ttl,
+            pending,
+            stale_records: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Log a message before it is sent; the record reaches the disk once
+    /// [`OutboundWal::syncer`] is synced
+    pub fn append(&mut self, target: &DroneId, message: &DroneMessage, at: DateTime<Utc>) -> P2pResult<()> {
+        if self.pending.iter().any(|entry| entry.message.id == message.id) {
+            return Ok(());
+        }
+        let entry = PendingMessage {
+            target: target.clone(),
+            logged_at: at,
+            message: message.clone(),
+        };
+        self.write(&WalRecord::Append(Box::new(entry.clone())))?;
+        self.pending.push(entry);
+        Ok(())
+    }
+
+    /// Mark a message acknowledged; `false` if it was not pending
+    pub fn ack(&mut self, message_id: Uuid) -> P2pResult<bool> {
+        let Some(index) = self.pending.iter().position(|entry| entry.message.id == message_id) else {
+            return Ok(false);
+        };
+        self.pending.remove(index);
+        self.write(&WalRecord::Ack { message_id })?;
+        self.stale_records += 2;
+        self.compact_if_needed()?;
+        Ok(true)
+    }
+
+    /// Drop messages older than the TTL; returns how many were dropped
+    pub fn expire(&mut self, now: DateTime<Utc>) -> usize {
+        let ttl = chrono::Duration::from_std(self.ttl).unwrap_or_default();
+        let before = self.pending.len();
+        self.pending.retain(|entry| now - entry.logged_at <= ttl);
+        let expired = before - self.pending.len();
+        if expired > 0 {
+            warn!("Dropped {} expired outbound messages from the WAL", expired);
+            self.stale_records += expired;
+            if let Err(e) = self.compact_if_needed() {
+                warn!("Failed to compact outbound WAL: {}", e);
+            }
+        }
+        expired
+    }
+
+    /// Unacknowledged messages in send order
+    pub fn pending(&self) -> &[PendingMessage] {
+        &self.pending
+    }
+
+    /// Handle for flushing the current file
+    pub fn syncer(&self) -> WalSync {
+        self.syncer.clone()
+    }
+
+    fn write(&mut self, record: &WalRecord) -> P2pResult<()> {
+        let mut line = serde_json::to_string(record).map_err(|e| P2pError::Wal(e.to_string()))?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .map_err(|e| P2pError::Wal(e.to_string()))
+    }
+
+    fn syncer_for(file: &File) -> P2pResult<WalSync> {
+        file.try_clone()
+            .map(|file| WalSync(Arc::new(file)))
+            .map_err(|e| P2pError::Wal(e.to_string()))
+    }
+
+    fn compact_if_needed(&mut self) -> P2pResult<()> {
+        if self.stale_records < COMPACT_AFTER {
+            return Ok(());
+        }
+        self.file = Self::rewrite(&self.path, &self.pending)?;
+        self.syncer = Self::syncer_for(&self.file)?;
+        self.stale_records = 0;
+        Ok(())
+    }
+
+    /// Replace the file with one append record per pending message and
+    /// return it opened for appending
+    fn rewrite(path: &Path, pending: &[PendingMessage]) -> P2pResult<File> {
+        let tmp = path.with_extension("tmp");
+        let mut contents = String::new();
+        for entry in pending {
+            let record = serde_json::to_string(&WalRecord::Append(Box::new(entry.clone())))
+                .map_err(|e| P2pError::Wal(e.to_string()))?;
+            contents.push_str(&record);
+            contents.push('\n');
+        }
+        let mut file = File::create(&tmp).map_err(|e| P2pError::Wal(e.to_string()))?;
+        file.write_all(contents.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| P2pError::Wal(e.to_string()))?;
+        fs::rename(&tmp, path).map_err(|e| P2pError::Wal(e.to_string()))?;
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| P2pError::Wal(e.to_string()))
+    }
+}
+
+/// Recently handled message IDs, for dropping redelivered copies
+#[derive(Debug)]
+pub struct SeenMessages {
+    capacity: usize,
+    order: VecDeque<Uuid>,
+    seen: HashSet<Uuid>,
+}
+
+impl SeenMessages {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            order: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Record a message ID; `false` if it was already seen
+    pub fn insert(&mut self, id: Uuid) -> bool {
+        if !self.seen.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::CommandKind;
+
+    #[test]
+    fn test_unacknowledged_messages_survive_reopen() {
+        let path = std::env::temp_dir().join(format!("drone-p2p-wal-{}.jsonl", Uuid::new_v4()));
+        let ttl = Duration::from_secs(600);
+        let now = Utc::now();
+        let gcs = DroneId::new("GCS");
+        let target = DroneId::new("REAPER-01");
+        let [acked, kept, expired] =
+            [(); 3].map(|_| DroneMessage::command(gcs.clone(), target.clone(), CommandKind::ReturnToBase));
+
+        let mut wal = OutboundWal::open(&path, ttl, now).unwrap();
+        wal.append(&target, &expired, now - chrono::Duration::seconds(900)).unwrap();
+        wal.append(&target, &acked, now).unwrap();
+        wal.append(&target, &kept, now).unwrap();
+        wal.append(&target, &kept, now).unwrap();
+        wal.syncer().sync().unwrap();
+        assert!(wal.ack(acked.id).unwrap());
+        assert!(!wal.ack(acked.id).unwrap());
+        drop(wal);
+
+        // Simulate a crash in the middle of an append
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"op\":\"append\",\"tar").unwrap();
+
+        let wal = OutboundWal::open(&path, ttl, now).unwrap();
+        let pending: Vec<_> = wal.pending().iter().map(|entry| entry.message.id).collect();
+        assert_eq!(pending, vec![kept.id]);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+        let mut seen = SeenMessages::new(2);
+        assert!(seen.insert(kept.id));
+        assert!(!seen.insert(kept.id));
+        seen.insert(acked.id);
+        seen.insert(expired.id);
+        // Evicted once capacity is exceeded
+        assert!(seen.insert(kept.id));
+
+        fs::remove_file(&path).ok();
+    }
+}
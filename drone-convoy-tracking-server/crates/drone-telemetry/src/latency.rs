@@ -0,0 +1,134 @@
+//! Position update latency breakdown
+//!
+//! Each position update is timed at every hop from ingestion to the client
+//! (see [`LatencyHop`]) into the `drone_convoy_update_latency_seconds`
+//! histogram. A [`LatencyBreakdown`] summarises it per hop, with quantiles
+//! estimated from the buckets the way Prometheus' `histogram_quantile`
+//! does.
+
+use drone_core::LatencyHop;
+use prometheus::proto::Histogram as HistogramProto;
+use serde::Serialize;
+
+/// Upper bounds of the latency buckets, in seconds
+pub const LATENCY_BUCKETS: [f64; 12] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+/// Latency of one hop since startup
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HopLatency {
+    pub hop: LatencyHop,
+    pub samples: u64,
+    pub mean_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+impl HopLatency {
+    pub(crate) fn from_histogram(hop: LatencyHop, histogram: &HistogramProto) -> Self {
+        let samples = histogram.get_sample_count();
+        let buckets: Vec<(f64, u64)> = histogram
+            .get_bucket()
+            .iter()
+            .map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+            .collect();
+        let quantile = |q| bucket_quantile(q, &buckets, samples).map(|s| s * 1000.0);
+        Self {
+            hop,
+            samples,
+            mean_ms: (samples > 0).then(|| histogram.get_sample_sum() / samples as f64 * 1000.0),
+            p50_ms: quantile(0.5),
+            p95_ms: quantile(0.95),
+            p99_ms: quantile(0.99),
+        }
+    }
+}
+
+/// Per-hop latency of position updates, in path order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyBreakdown {
+    pub hops: Vec<HopLatency>,
+    /// Sum of the hop means: ingestion to client on average
+    pub mean_total_ms: Option<f64>,
+}
+
+impl LatencyBreakdown {
+    pub fn new(hops: Vec<HopLatency>) -> Self {
+        let means: Vec<f64> = hops.iter().filter_map(|hop| hop.mean_ms).collect();
+        Self {
+            mean_total_ms: (!means.is_empty()).then(|| means.iter().sum()),
+            hops,
+        }
+    }
+}
+
+/// Quantile `q` of `total` samples from cumulative `(upper bound, count)`
+/// buckets, interpolating linearly inside the bucket it falls in. Samples
+/// beyond the last bound are reported at that bound.
+pub fn bucket_quantile(q: f64, buckets: &[(f64, u64)], total: u64) -> Option<f64> {
+    if total == 0 {
+        return None;
+    }
+    let rank = q * total as f64;
+    let mut lower = (0.0, 0);
+    for &(upper, cumulative) in buckets.iter().filter(|(upper, _)| upper.is_finite()) {
+        if cumulative as f64 >= rank {
+            let in_bucket = (cumulative - lower.1) as f64;
+            if in_bucket == 0.0 {
+                return Some(upper);
+            }
+            return Some(lower.0 + (upper - lower.0) * (rank - lower.1 as f64) / in_bucket);
+        }
+        lower = (upper, cumulative);
+    }
+    Some(lower.0)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MetricsCollector;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_quantile() {
+        let buckets = [(0.01, 50), (0.1, 90), (1.0, 100)];
+        assert_eq!(bucket_quantile(0.5, &buckets, 100), Some(0.01));
+        // 40 samples spread over 0.01-0.1; the 70th is halfway
+        let p70 = bucket_quantile(0.7, &buckets, 100).unwrap();
+        assert!((p70 - 0.055).abs() < 1e-9);
+        assert_eq!(bucket_quantile(0.5, &buckets, 0), None);
+        // Beyond the last bound
+        assert_eq!(bucket_quantile(0.99, &[(0.01, 1)], 2), Some(0.01));
+    }
+
+    #[test]
+    fn test_latency_breakdown() {
+        let metrics = MetricsCollector::new().unwrap();
+        for ms in [2, 3, 4, 5] {
+            metrics.record_update_latency(LatencyHop::Tracker, Duration::from_millis(ms));
+        }
+        metrics.record_update_latency(LatencyHop::Client, Duration::from_millis(40));
+
+        let breakdown = metrics.latency_breakdown();
+        let hops: Vec<_> = breakdown.hops.iter().map(|hop| hop.hop).collect();
+        assert_eq!(hops, LatencyHop::ALL);
+
+        let tracker = &breakdown.hops[0];
+        assert_eq!(tracker.samples, 4);
+        assert!((tracker.mean_ms.unwrap() - 3.5).abs() < 1e-9);
+        let p50 = tracker.p50_ms.unwrap();
+        assert!((2.5..=5.0).contains(&p50));
+        assert_eq!(breakdown.hops[1].samples, 0);
+        assert_eq!(breakdown.hops[1].p99_ms, None);
+        assert!((breakdown.mean_total_ms.unwrap() - 43.5).abs() < 1e-9);
+
+        metrics
+            .snapshot("after")
+            .assert_value(r#"drone_convoy_update_latency_seconds_count{hop="tracker"}"#, 4.0);
+    }
+}
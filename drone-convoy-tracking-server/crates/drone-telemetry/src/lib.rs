@@ -3,18 +3,27 @@
 //! Prometheus metrics exporter for the drone convoy tracking system.
 //! Provides real-time metrics for:
 //! - Drone status and health
+//! - Mission KPIs (duration, leg times, punctuality, formation, alerts)
 //! - System performance
 //! - CV tracking statistics
 //! - WebSocket connections
+//! - Tracker memory (entry counts, evictions)
+//! - Position update latency per hop, ingestion to client
 
-use drone_core::{Drone, DroneId, DroneStatus};
-use parking_lot::RwLock;
+pub mod latency;
+pub mod snapshot;
+
+pub use latency::{HopLatency, LatencyBreakdown};
+pub use snapshot::{metric_key, MetricDiff, MetricSnapshot};
+
+use drone_core::{Drone, DroneStatus, LatencyHop};
+use prometheus::core::Metric;
 use prometheus::{
-    Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec,
+    Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec,
     IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
 };
-use std::sync::Arc;
-use tracing::{debug, info};
+use std::time::Duration;
+use tracing::info;
 
 /// Metrics collector for the drone convoy system
 pub struct MetricsCollector {
@@ -31,17 +40,32 @@ pub struct MetricsCollector {
     // Mission metrics
     mission_active: IntGauge,
     waypoints_reached: IntCounterVec,
+    mission_duration: GaugeVec,
+    leg_traversal_time: HistogramVec,
+    waypoint_punctuality: IntCounterVec,
+    formation_compliance: GaugeVec,
+    mission_alerts: IntCounterVec,
     
     // CV tracking metrics
     cv_tracks_active: IntGauge,
     cv_frames_processed: IntCounter,
     cv_detections_total: IntCounter,
     cv_processing_time: Histogram,
+    cv_frames_skipped: IntCounter,
+    cv_effective_fps: Gauge,
+    cv_skip_ratio: Gauge,
+    cv_frame_scale: Gauge,
+    cv_camera_frames: IntCounterVec,
+    cv_camera_latency: HistogramVec,
+    cv_camera_fps: GaugeVec,
     
     // WebSocket metrics
     ws_connections: IntGauge,
     ws_messages_sent: IntCounter,
     ws_messages_received: IntCounter,
+
+    // Position update latency
+    update_latency: HistogramVec,
     
     // Database metrics
     db_queries_total: IntCounterVec,
@@ -51,6 +75,12 @@ pub struct MetricsCollector {
     // System metrics
     api_requests_total: IntCounterVec,
     api_request_duration: HistogramVec,
+
+    // Tracker memory metrics
+    tracker_entries: IntGaugeVec,
+    tracker_memory_bytes: IntGauge,
+    tracker_evictions: IntCounterVec,
+    tracker_alerts_trimmed: IntCounter,
 }
 
 impl MetricsCollector {
@@ -108,6 +138,45 @@ impl MetricsCollector {
         )?;
         registry.register(Box::new(waypoints_reached.clone()))?;
 
+        let mission_duration = GaugeVec::new(
+            Opts::new("drone_convoy_mission_duration_seconds", "Time since the mission started"),
+            &["mission_id"]
+        )?;
+        registry.register(Box::new(mission_duration.clone()))?;
+
+        let leg_traversal_time = HistogramVec::new(
+            HistogramOpts::new(
+                "drone_convoy_mission_leg_traversal_seconds",
+                "Time drones take to fly a leg, from leaving one waypoint to reaching the next"
+            ).buckets(vec![30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0]),
+            &["leg"]
+        )?;
+        registry.register(Box::new(leg_traversal_time.clone()))?;
+
+        let waypoint_punctuality = IntCounterVec::new(
+            Opts::new(
+                "drone_convoy_mission_waypoint_arrivals_total",
+                "Arrivals at waypoints with an expected arrival time, on time or late"
+            ),
+            &["punctuality"]
+        )?;
+        registry.register(Box::new(waypoint_punctuality.clone()))?;
+
+        let formation_compliance = GaugeVec::new(
+            Opts::new(
+                "drone_convoy_mission_formation_compliance_percent",
+                "Share of convoy followers within tolerance of their formation slot"
+            ),
+            &["mission_id"]
+        )?;
+        registry.register(Box::new(formation_compliance.clone()))?;
+
+        let mission_alerts = IntCounterVec::new(
+            Opts::new("drone_convoy_mission_alerts_total", "Alerts raised during a mission"),
+            &["mission_id", "severity"]
+        )?;
+        registry.register(Box::new(mission_alerts.clone()))?;
+
         // CV tracking metrics
         let cv_tracks_active = IntGauge::new(
             "drone_convoy_cv_tracks_active",
@@ -135,6 +204,51 @@ impl MetricsCollector {
         )?;
         registry.register(Box::new(cv_processing_time.clone()))?;
 
+        let cv_frames_skipped = IntCounter::new(
+            "drone_convoy_cv_frames_skipped_total",
+            "Total CV frames skipped by the frame governor"
+        )?;
+        registry.register(Box::new(cv_frames_skipped.clone()))?;
+
+        let cv_effective_fps = Gauge::new(
+            "drone_convoy_cv_effective_fps",
+            "CV frames processed per second"
+        )?;
+        registry.register(Box::new(cv_effective_fps.clone()))?;
+
+        let cv_skip_ratio = Gauge::new(
+            "drone_convoy_cv_skip_ratio",
+            "Fraction of CV frames skipped"
+        )?;
+        registry.register(Box::new(cv_skip_ratio.clone()))?;
+
+        let cv_frame_scale = Gauge::new(
+            "drone_convoy_cv_frame_scale",
+            "CV processing resolution scale (1.0 = full resolution)"
+        )?;
+        registry.register(Box::new(cv_frame_scale.clone()))?;
+
+        let cv_camera_frames = IntCounterVec::new(
+            Opts::new("drone_convoy_cv_camera_frames_total", "CV frames processed per camera"),
+            &["camera"]
+        )?;
+        registry.register(Box::new(cv_camera_frames.clone()))?;
+
+        let cv_camera_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "drone_convoy_cv_camera_processing_seconds",
+                "CV frame processing time per camera"
+            ).buckets(vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+            &["camera"]
+        )?;
+        registry.register(Box::new(cv_camera_latency.clone()))?;
+
+        let cv_camera_fps = GaugeVec::new(
+            Opts::new("drone_convoy_cv_camera_fps", "CV frames processed per second per camera"),
+            &["camera"]
+        )?;
+        registry.register(Box::new(cv_camera_fps.clone()))?;
+
         // WebSocket metrics
         let ws_connections = IntGauge::new(
             "drone_convoy_ws_connections",
@@ -154,6 +268,16 @@ impl MetricsCollector {
         )?;
         registry.register(Box::new(ws_messages_received.clone()))?;
 
+        // Latency metrics
+        let update_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "drone_convoy_update_latency_seconds",
+                "Position update latency per hop, from ingestion to the client"
+            ).buckets(latency::LATENCY_BUCKETS.to_vec()),
+            &["hop"]
+        )?;
+        registry.register(Box::new(update_latency.clone()))?;
+
         // Database metrics
         let db_queries_total = IntCounterVec::new(
             Opts::new("drone_convoy_db_queries_total", "Database queries"),
@@ -192,6 +316,31 @@ impl MetricsCollector {
         )?;
         registry.register(Box::new(api_request_duration.clone()))?;
 
+        // Tracker memory metrics
+        let tracker_entries = IntGaugeVec::new(
+            Opts::new("drone_convoy_tracker_entries", "Entries held in tracker state per collection"),
+            &["collection"]
+        )?;
+        registry.register(Box::new(tracker_entries.clone()))?;
+
+        let tracker_memory_bytes = IntGauge::new(
+            "drone_convoy_tracker_memory_bytes",
+            "Estimated memory held by per-drone tracker state"
+        )?;
+        registry.register(Box::new(tracker_memory_bytes.clone()))?;
+
+        let tracker_evictions = IntCounterVec::new(
+            Opts::new("drone_convoy_tracker_evictions_total", "Drones dropped from tracking"),
+            &["reason"]
+        )?;
+        registry.register(Box::new(tracker_evictions.clone()))?;
+
+        let tracker_alerts_trimmed = IntCounter::new(
+            "drone_convoy_tracker_alerts_trimmed_total",
+            "Active alerts dropped by the per-drone cap"
+        )?;
+        registry.register(Box::new(tracker_alerts_trimmed.clone()))?;
+
         info!("📊 Metrics collector initialized");
 
         Ok(Self {
@@ -204,18 +353,35 @@ impl MetricsCollector {
             drone_altitude,
             mission_active,
             waypoints_reached,
+            mission_duration,
+            leg_traversal_time,
+            waypoint_punctuality,
+            formation_compliance,
+            mission_alerts,
             cv_tracks_active,
             cv_frames_processed,
             cv_detections_total,
             cv_processing_time,
+            cv_frames_skipped,
+            cv_effective_fps,
+            cv_skip_ratio,
+            cv_frame_scale,
+            cv_camera_frames,
+            cv_camera_latency,
+            cv_camera_fps,
             ws_connections,
             ws_messages_sent,
             ws_messages_received,
+            update_latency,
             db_queries_total,
             db_query_duration,
             db_connection_status,
             api_requests_total,
             api_request_duration,
+            tracker_entries,
+            tracker_memory_bytes,
+            tracker_evictions,
+            tracker_alerts_trimmed,
         })
     }
 
@@ -224,6 +390,11 @@ impl MetricsCollector {
         &self.registry
     }
 
+    /// Capture every counter and gauge value, for test assertions
+    pub fn snapshot(&self, name: impl Into<String>) -> MetricSnapshot {
+        MetricSnapshot::capture(name, &self.registry)
+    }
+
     /// Export metrics in Prometheus format
     pub fn export(&self) -> String {
         use prometheus::Encoder;
@@ -235,6 +406,37 @@ impl MetricsCollector {
         String::from_utf8(buffer).unwrap()
     }
 
+    /// Export only the mission metrics (`drone_convoy_mission_*`), for
+    /// appending to an exposition that already reports the other families
+    pub fn export_mission_kpis(&self) -> String {
+        self.export_prefixed("drone_convoy_mission_")
+    }
+
+    /// Export only the tracker memory metrics (`drone_convoy_tracker_*`)
+    pub fn export_tracker_metrics(&self) -> String {
+        self.export_prefixed("drone_convoy_tracker_")
+    }
+
+    /// Export only the position update latency histogram
+    pub fn export_latency_metrics(&self) -> String {
+        self.export_prefixed("drone_convoy_update_latency_")
+    }
+
+    fn export_prefixed(&self, prefix: &str) -> String {
+        use prometheus::Encoder;
+
+        let encoder = prometheus::TextEncoder::new();
+        let metric_families: Vec<_> = self
+            .registry
+            .gather()
+            .into_iter()
+            .filter(|family| family.get_name().starts_with(prefix))
+            .collect();
+        let mut buffer = Vec::new();
+        encoder.encode(&metric_families, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
     // ========================================================================
     // DRONE METRICS
     // ========================================================================
@@ -289,6 +491,42 @@ impl MetricsCollector {
             .inc();
     }
 
+    /// Update how long a mission has been running
+    pub fn set_mission_duration(&self, mission_id: &str, seconds: f64) {
+        self.mission_duration
+            .with_label_values(&[mission_id])
+            .set(seconds);
+    }
+
+    /// Record the time taken to fly a leg (e.g. `WP01-WP02`)
+    pub fn record_leg_traversal(&self, leg: &str, seconds: f64) {
+        self.leg_traversal_time
+            .with_label_values(&[leg])
+            .observe(seconds);
+    }
+
+    /// Record an arrival against the waypoint's expected arrival time
+    pub fn record_waypoint_punctuality(&self, on_time: bool) {
+        let punctuality = if on_time { "on_time" } else { "late" };
+        self.waypoint_punctuality
+            .with_label_values(&[punctuality])
+            .inc();
+    }
+
+    /// Update the share of followers holding their formation slot (0-100)
+    pub fn set_formation_compliance(&self, mission_id: &str, percent: f64) {
+        self.formation_compliance
+            .with_label_values(&[mission_id])
+            .set(percent);
+    }
+
+    /// Record an alert raised during a mission
+    pub fn record_mission_alert(&self, mission_id: &str, severity: &str) {
+        self.mission_alerts
+            .with_label_values(&[mission_id, severity])
+            .inc();
+    }
+
     // ========================================================================
     // CV METRICS
     // ========================================================================
@@ -305,6 +543,31 @@ impl MetricsCollector {
         self.cv_processing_time.observe(processing_time_secs);
     }
 
+    /// Record CV frame skipped by the frame governor
+    pub fn record_cv_frame_skipped(&self) {
+        self.cv_frames_skipped.inc();
+    }
+
+    /// Update CV frame governor gauges
+    pub fn set_cv_governor(&self, effective_fps: f64, skip_ratio: f64, scale: f64) {
+        self.cv_effective_fps.set(effective_fps);
+        self.cv_skip_ratio.set(skip_ratio);
+        self.cv_frame_scale.set(scale);
+    }
+
+    /// Record a frame processed by the multi-camera scheduler; also counts
+    /// towards the fleet-wide CV metrics
+    pub fn record_cv_camera_frame(&self, camera: &str, processing_time_secs: f64, detections: u64) {
+        self.record_cv_frame(processing_time_secs, detections);
+        self.cv_camera_frames.with_label_values(&[camera]).inc();
+        self.cv_camera_latency.with_label_values(&[camera]).observe(processing_time_secs);
+    }
+
+    /// Update a camera's processing rate
+    pub fn set_cv_camera_fps(&self, camera: &str, fps: f64) {
+        self.cv_camera_fps.with_label_values(&[camera]).set(fps);
+    }
+
     // ========================================================================
     // WEBSOCKET METRICS
     // ========================================================================
@@ -324,6 +587,29 @@ impl MetricsCollector {
         self.ws_messages_received.inc();
     }
 
+    // ========================================================================
+    // LATENCY METRICS
+    // ========================================================================
+
+    /// Record the time a position update spent on one hop
+    pub fn record_update_latency(&self, hop: LatencyHop, elapsed: Duration) {
+        self.update_latency
+            .with_label_values(&[hop.as_str()])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Position update latency per hop since startup
+    pub fn latency_breakdown(&self) -> LatencyBreakdown {
+        let hops = LatencyHop::ALL
+            .iter()
+            .map(|&hop| {
+                let metric = self.update_latency.with_label_values(&[hop.as_str()]).metric();
+                HopLatency::from_histogram(hop, metric.get_histogram())
+            })
+            .collect();
+        LatencyBreakdown::new(hops)
+    }
+
     // ========================================================================
     // DATABASE METRICS
     // ========================================================================
@@ -362,6 +648,30 @@ impl MetricsCollector {
             .with_label_values(&[method, path])
             .observe(duration_secs);
     }
+
+    // ========================================================================
+    // TRACKER METRICS
+    // ========================================================================
+
+    /// Set the number of entries in one tracker collection
+    pub fn set_tracker_entries(&self, collection: &str, count: i64) {
+        self.tracker_entries.with_label_values(&[collection]).set(count);
+    }
+
+    /// Set the estimated memory held by per-drone tracker state
+    pub fn set_tracker_memory_bytes(&self, bytes: i64) {
+        self.tracker_memory_bytes.set(bytes);
+    }
+
+    /// Record a drone dropped from tracking
+    pub fn record_tracker_eviction(&self, reason: &str) {
+        self.tracker_evictions.with_label_values(&[reason]).inc();
+    }
+
+    /// Record active alerts dropped by the per-drone cap
+    pub fn record_tracker_alerts_trimmed(&self, count: u64) {
+        self.tracker_alerts_trimmed.inc_by(count);
+    }
 }
 
 impl Default for MetricsCollector {
@@ -377,6 +687,7 @@ impl Default for MetricsCollector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use drone_core::DroneId;
 
     #[test]
     fn test_metrics_creation() {
@@ -398,6 +709,68 @@ mod tests {
         assert!(export.contains("drone_convoy_mission_active"));
     }
 
+    #[test]
+    fn test_cv_governor_metrics() {
+        let metrics = MetricsCollector::new().unwrap();
+
+        metrics.record_cv_frame_skipped();
+        metrics.set_cv_governor(24.0, 0.2, 0.8);
+
+        let export = metrics.export();
+        assert!(export.contains("drone_convoy_cv_frames_skipped_total 1"));
+        assert!(export.contains("drone_convoy_cv_effective_fps 24"));
+        assert!(export.contains("drone_convoy_cv_skip_ratio 0.2"));
+
+        let before = metrics.snapshot("before");
+        metrics.record_cv_camera_frame("gimbal-2", 0.02, 3);
+        metrics.set_cv_camera_fps("gimbal-2", 12.5);
+        let after = metrics.snapshot("after");
+        before
+            .diff(&after)
+            .assert_increased(r#"drone_convoy_cv_camera_frames_total{camera="gimbal-2"}"#, 1.0)
+            .assert_increased("drone_convoy_cv_frames_processed_total", 1.0)
+            .assert_increased("drone_convoy_cv_detections_total", 3.0);
+        after.assert_value(r#"drone_convoy_cv_camera_fps{camera="gimbal-2"}"#, 12.5);
+    }
+
+    #[test]
+    fn test_mission_kpi_export() {
+        let metrics = MetricsCollector::new().unwrap();
+
+        metrics.set_drone_count(12);
+        metrics.set_mission_duration("m1", 90.0);
+        metrics.record_leg_traversal("WP01-WP02", 45.0);
+        metrics.record_waypoint_punctuality(true);
+        metrics.record_waypoint_punctuality(false);
+        metrics.set_formation_compliance("m1", 75.0);
+        metrics.record_mission_alert("m1", "CRITICAL");
+
+        let export = metrics.export_mission_kpis();
+        assert!(export.contains(r#"drone_convoy_mission_duration_seconds{mission_id="m1"} 90"#));
+        assert!(export.contains(r#"drone_convoy_mission_leg_traversal_seconds_count{leg="WP01-WP02"} 1"#));
+        assert!(export.contains(r#"drone_convoy_mission_waypoint_arrivals_total{punctuality="late"} 1"#));
+        assert!(export.contains(r#"drone_convoy_mission_formation_compliance_percent{mission_id="m1"} 75"#));
+        assert!(export.contains(r#"drone_convoy_mission_alerts_total{mission_id="m1",severity="CRITICAL"} 1"#));
+        assert!(!export.contains("drone_convoy_drones_total"));
+    }
+
+    #[test]
+    fn test_tracker_metrics_export() {
+        let metrics = MetricsCollector::new().unwrap();
+
+        metrics.set_tracker_entries("drones", 12);
+        metrics.set_tracker_memory_bytes(4096);
+        metrics.record_tracker_eviction("offline");
+        metrics.record_tracker_alerts_trimmed(3);
+
+        let export = metrics.export_tracker_metrics();
+        assert!(export.contains(r#"drone_convoy_tracker_entries{collection="drones"} 12"#));
+        assert!(export.contains("drone_convoy_tracker_memory_bytes 4096"));
+        assert!(export.contains(r#"drone_convoy_tracker_evictions_total{reason="offline"} 1"#));
+        assert!(export.contains("drone_convoy_tracker_alerts_trimmed_total 3"));
+        assert!(!export.contains("drone_convoy_mission_"));
+    }
+
     #[test]
     fn test_drone_metrics() {
         let metrics = MetricsCollector::new().unwrap();
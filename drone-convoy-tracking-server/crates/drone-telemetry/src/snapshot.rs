@@ -0,0 +1,226 @@
+//! Metric snapshots for tests
+//!
+//! A [`MetricSnapshot`] records the value of every counter and gauge in a
+//! registry (histograms as their `_count` and `_sum`), keyed like the
+//! Prometheus exposition: `name` or `name{label="value",...}` with labels
+//! in name order. Subsystem tests take one snapshot before and one after
+//! the code under test and assert on the difference, instead of searching
+//! the text export:
+//!
+//! ```
+//! # use drone_telemetry::MetricsCollector;
+//! let metrics = MetricsCollector::new().unwrap();
+//! let before = metrics.snapshot("before");
+//! metrics.record_tracker_eviction("offline");
+//! metrics.set_tracker_memory_bytes(4096);
+//! let after = metrics.snapshot("after");
+//!
+//! before
+//!     .diff(&after)
+//!     .assert_increased(r#"drone_convoy_tracker_evictions_total{reason="offline"}"#, 1.0)
+//!     .assert_unchanged("drone_convoy_tracker_alerts_trimmed_total");
+//! after.assert_value("drone_convoy_tracker_memory_bytes", 4096.0);
+//! ```
+
+use prometheus::proto::MetricType;
+use prometheus::Registry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Metric key as written in the exposition, labels in name order
+pub fn metric_key(name: &str, labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return name.to_string();
+    }
+    let mut labels = labels.to_vec();
+    labels.sort();
+    let mut key = format!("{}{{", name);
+    for (i, (label, value)) in labels.iter().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        let _ = write!(key, r#"{}{}="{}""#, sep, label, value);
+    }
+    key.push('}');
+    key
+}
+
+/// Values of every counter and gauge at one point
+#[derive(Debug, Clone)]
+pub struct MetricSnapshot {
+    name: String,
+    values: BTreeMap<String, f64>,
+}
+
+impl MetricSnapshot {
+    /// Record the current value of every metric in `registry`
+    pub fn capture(name: impl Into<String>, registry: &Registry) -> Self {
+        let mut values = BTreeMap::new();
+        for family in registry.gather() {
+            let name = family.get_name();
+            for metric in family.get_metric() {
+                let labels: Vec<(&str, &str)> = metric
+                    .get_label()
+                    .iter()
+                    .map(|pair| (pair.get_name(), pair.get_value()))
+                    .collect();
+                match family.get_field_type() {
+                    MetricType::COUNTER => {
+                        values.insert(metric_key(name, &labels), metric.get_counter().get_value());
+                    }
+                    MetricType::GAUGE => {
+                        values.insert(metric_key(name, &labels), metric.get_gauge().get_value());
+                    }
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let count = format!("{}_count", name);
+                        let sum = format!("{}_sum", name);
+                        values.insert(metric_key(&count, &labels), histogram.get_sample_count() as f64);
+                        values.insert(metric_key(&sum, &labels), histogram.get_sample_sum());
+                    }
+                    MetricType::SUMMARY | MetricType::UNTYPED => {}
+                }
+            }
+        }
+        Self {
+            name: name.into(),
+            values,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value of `key`; `None` for metrics not exported yet (a labelled
+    /// metric appears once a label combination is first used)
+    pub fn get(&self, key: &str) -> Option<f64> {
+        self.values.get(key).copied()
+    }
+
+    /// Every recorded key and value
+    pub fn values(&self) -> &BTreeMap<String, f64> {
+        &self.values
+    }
+
+    /// Panic unless `key` equals `expected`
+    #[track_caller]
+    pub fn assert_value(&self, key: &str, expected: f64) -> &Self {
+        match self.get(key) {
+            Some(value) if value == expected => self,
+            Some(value) => panic!("snapshot {}: {} is {}, expected {}", self.name, key, value, expected),
+            None => panic!("snapshot {}: {} not recorded, expected {}", self.name, key, expected),
+        }
+    }
+
+    /// Changes from this snapshot to `later`
+    pub fn diff(&self, later: &MetricSnapshot) -> MetricDiff {
+        let keys: BTreeSet<&String> = self.values.keys().chain(later.values.keys()).collect();
+        let changes = keys
+            .into_iter()
+            .filter_map(|key| {
+                let (before, after) = (self.get(key), later.get(key));
+                (before != after).then(|| (key.clone(), (before, after)))
+            })
+            .collect();
+        MetricDiff {
+            from: self.name.clone(),
+            to: later.name.clone(),
+            changes,
+        }
+    }
+}
+
+/// Metrics that changed between two snapshots
+#[derive(Debug, Clone)]
+pub struct MetricDiff {
+    from: String,
+    to: String,
+    /// Before and after, `None` where the metric was not exported
+    changes: BTreeMap<String, (Option<f64>, Option<f64>)>,
+}
+
+impl MetricDiff {
+    /// Change of `key`, a metric first exported counting from 0
+    pub fn delta(&self, key: &str) -> f64 {
+        self.changes
+            .get(key)
+            .map(|(before, after)| after.unwrap_or(0.0) - before.unwrap_or(0.0))
+            .unwrap_or(0.0)
+    }
+
+    /// Keys that changed, in order
+    pub fn changed(&self) -> impl Iterator<Item = &str> {
+        self.changes.keys().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Panic unless `key` increased by exactly `by`
+    #[track_caller]
+    pub fn assert_increased(&self, key: &str, by: f64) -> &Self {
+        let delta = self.delta(key);
+        assert!(
+            delta == by,
+            "{} -> {}: {} changed by {}, expected +{}",
+            self.from,
+            self.to,
+            key,
+            delta,
+            by
+        );
+        self
+    }
+
+    /// Panic if `key` changed
+    #[track_caller]
+    pub fn assert_unchanged(&self, key: &str) -> &Self {
+        if let Some((before, after)) = self.changes.get(key) {
+            panic!("{} -> {}: {} changed from {:?} to {:?}", self.from, self.to, key, before, after);
+        }
+        self
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MetricsCollector;
+
+    #[test]
+    fn test_snapshot_diff() {
+        let metrics = MetricsCollector::new().unwrap();
+        metrics.set_drone_count(12);
+        metrics.record_mission_alert("m1", "WARNING");
+        let before = metrics.snapshot("before");
+
+        metrics.set_drone_count(10);
+        metrics.record_mission_alert("m1", "WARNING");
+        metrics.record_mission_alert("m1", "CRITICAL");
+        metrics.record_leg_traversal("WP01-WP02", 45.0);
+        let after = metrics.snapshot("after");
+
+        let critical = metric_key("drone_convoy_mission_alerts_total", &[("severity", "CRITICAL"), ("mission_id", "m1")]);
+        assert_eq!(critical, r#"drone_convoy_mission_alerts_total{mission_id="m1",severity="CRITICAL"}"#);
+        assert_eq!(before.get(&critical), None);
+
+        let diff = before.diff(&after);
+        diff.assert_increased(r#"drone_convoy_mission_alerts_total{mission_id="m1",severity="WARNING"}"#, 1.0)
+            .assert_increased(&critical, 1.0)
+            .assert_increased(r#"drone_convoy_mission_leg_traversal_seconds_sum{leg="WP01-WP02"}"#, 45.0)
+            .assert_unchanged("drone_convoy_ws_connections");
+        assert_eq!(diff.delta("drone_convoy_drones_total"), -2.0);
+        assert_eq!(diff.changed().count(), 5);
+        after.assert_value("drone_convoy_drones_total", 10.0);
+
+        assert!(before.diff(&before).is_empty());
+        let failed = std::panic::catch_unwind(|| {
+            before.diff(&after).assert_unchanged("drone_convoy_drones_total");
+        });
+        assert!(failed.is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! Mission abort sequence
+//!
+//! Aborting recalls every assigned drone, snapshots where each one was and
+//! how much fuel it had left, and waits for the drones to acknowledge the
+//! recall before the mission is marked `Aborted`.
+
+use crate::TrackedDrone;
+use drone_core::{DroneId, DroneStatus, GeoPosition, Mission, MissionId};
+use drone_p2p::protocol::CommandKind;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Sender ID used for commands issued by the ground station
+pub const GROUND_STATION_ID: &str = "GCS";
+
+/// Command sent to assigned drones on abort
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbortCommand {
+    ReturnToBase,
+    EmergencyStop,
+}
+
+impl From<AbortCommand> for CommandKind {
+    fn from(command: AbortCommand) -> Self {
+        match command {
+            AbortCommand::ReturnToBase => CommandKind::ReturnToBase,
+            AbortCommand::EmergencyStop => CommandKind::EmergencyStop,
+        }
+    }
+}
+
+/// Abort sequence policy
+#[derive(Debug, Clone)]
+pub struct AbortPolicy {
+    /// Command broadcast to assigned drones
+    pub command: AbortCommand,
+    /// How long to wait for acknowledgments before completing anyway
+    pub ack_timeout: Duration,
+}
+
+impl Default for AbortPolicy {
+    fn default() -> Self {
+        Self {
+            command: AbortCommand::ReturnToBase,
+            ack_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Progress of an abort sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbortState {
+    /// Waiting for drones to acknowledge
+    InProgress,
+    /// All drones acknowledged or timed out; mission is `Aborted`
+    Completed,
+}
+
+/// One drone's state at abort and its acknowledgment
+#[derive(Debug, Clone, Serialize)]
+pub struct DroneAbortStatus {
+    pub drone_id: DroneId,
+    pub position: GeoPosition,
+    pub fuel_level: u8,
+    pub battery_level: u8,
+    pub status_at_abort: DroneStatus,
+    /// ID of the command message the drone must acknowledge
+    pub command_id: Uuid,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub timed_out: bool,
+}
+
+impl DroneAbortStatus {
+    /// Snapshot a drone at the moment of abort
+    pub fn snapshot(tracked: &TrackedDrone, command_id: Uuid) -> Self {
+        Self {
+            drone_id: tracked.drone.id.clone(),
+            position: tracked.drone.position,
+            fuel_level: tracked.drone.telemetry.fuel_level,
+            battery_level: tracked.drone.telemetry.battery_level,
+            status_at_abort: tracked.drone.status,
+            command_id,
+            acknowledged_at: None,
+            timed_out: false,
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        self.acknowledged_at.is_none() && !self.timed_out
+    }
+}
+
+/// Report of an abort sequence
+#[derive(Debug, Clone, Serialize)]
+pub struct AbortReport {
+    pub mission_id: MissionId,
+    pub mission_name: String,
+    pub reason: Option<String>,
+    pub command: AbortCommand,
+    pub state: AbortState,
+    pub initiated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub drones: Vec<DroneAbortStatus>,
+}
+
+impl AbortReport {
+    pub fn new(
+        mission: &Mission,
+        reason: Option<String>,
+        command: AbortCommand,
+        drones: Vec<DroneAbortStatus>,
+    ) -> Self {
+        Self {
+            mission_id: mission.id.clone(),
+            mission_name: mission.name.clone(),
+            reason,
+            command,
+            state: AbortState::InProgress,
+            initiated_at: Utc::now(),
+            completed_at: None,
+            drones,
+        }
+    }
+
+    /// Record a drone's acknowledgment; `command_id` must match when given
+    ///
+    /// Returns true if a pending drone was acknowledged.
+    pub fn acknowledge(&mut self, drone_id: &DroneId, command_id: Option<Uuid>, at: DateTime<Utc>) -> bool {
+        if self.state != AbortState::InProgress {
+            return false;
+        }
+        let Some(drone) = self.drones.iter_mut().find(|d| {
+            &d.drone_id == drone_id && command_id.is_none_or(|id| id == d.command_id)
+        }) else {
+            return false;
+        };
+        if !drone.is_pending() {
+            return false;
+        }
+        drone.acknowledged_at = Some(at);
+        true
+    }
+
+    /// Whether every drone has acknowledged
+    pub fn all_acknowledged(&self) -> bool {
+        self.drones.iter().all(|d| d.acknowledged_at.is_some())
+    }
+
+    /// Drones that have neither acknowledged nor timed out
+    pub fn pending(&self) -> usize {
+        self.drones.iter().filter(|d| d.is_pending()).count()
+    }
+
+    /// Mark pending drones as timed out and complete the sequence
+    pub fn complete(&mut self, at: DateTime<Utc>) {
+        for drone in self.drones.iter_mut().filter(|d| d.is_pending()) {
+            drone.timed_out = true;
+        }
+        self.state = AbortState::Completed;
+        self.completed_at = Some(at);
+    }
+
+    /// One-line outcome summary
+    pub fn summary(&self) -> String {
+        let acknowledged = self.drones.iter().filter(|d| d.acknowledged_at.is_some()).count();
+        let timed_out = self.drones.iter().filter(|d| d.timed_out).count();
+        format!(
+            "Mission {} aborted: {} drones acknowledged, {} timed out",
+            self.mission_name, acknowledged, timed_out
+        )
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Drone;
+
+    #[test]
+    fn test_acknowledgments_and_timeouts() {
+        let mission = Mission::new("Test");
+        let drones: Vec<_> = ["REAPER-01", "REAPER-02"]
+            .iter()
+            .map(|id| {
+                let mut drone = Drone::new(DroneId::new(*id), *id);
+                drone.telemetry.fuel_level = 61;
+                DroneAbortStatus::snapshot(&TrackedDrone::new(drone, Utc::now()), Uuid::new_v4())
+            })
+            .collect();
+        let command_id = drones[0].command_id;
+        let mut report = AbortReport::new(&mission, None, AbortCommand::ReturnToBase, drones);
+
+        // A stale command ID is not an acknowledgment
+        assert!(!report.acknowledge(&DroneId::new("REAPER-01"), Some(Uuid::new_v4()), Utc::now()));
+        assert!(report.acknowledge(&DroneId::new("REAPER-01"), Some(command_id), Utc::now()));
+        assert!(!report.acknowledge(&DroneId::new("REAPER-01"), None, Utc::now()));
+        assert_eq!(report.pending(), 1);
+        assert!(!report.all_acknowledged());
+
+        report.complete(Utc::now());
+        assert_eq!(report.state, AbortState::Completed);
+        assert!(report.drones[1].timed_out);
+        assert_eq!(report.drones[1].fuel_level, 61);
+        assert!(!report.acknowledge(&DroneId::new("REAPER-02"), None, Utc::now()));
+        assert!(report.summary().ends_with("1 drones acknowledged, 1 timed out"));
+    }
+}
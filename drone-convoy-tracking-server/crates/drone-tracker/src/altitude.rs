@@ -0,0 +1,359 @@
+//! Altitude band deconfliction
+//!
+//! The airspace is split into horizontal bands `band_size_m` thick, band `n`
+//! centred on `n * band_size_m`. Every drone holds one band to itself: on
+//! its first report it is given the free band nearest its altitude, and
+//! operators can move it to another free band. A drone flying outside its
+//! band raises one alert until it is back inside, critical when it shares
+//! the band it strayed into with another drone. With `enforce`, it is also
+//! sent a `SetAltitude` command to its band's centre, repeated every
+//! `command_interval` while it stays out.
+
+use drone_core::DroneId;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Alert type raised when a drone leaves its altitude band
+pub const ALTITUDE_ALERT_TYPE: &str = "ALTITUDE_BAND_VIOLATION";
+
+/// Altitude band configuration
+#[derive(Debug, Clone)]
+pub struct AltitudeConfig {
+    /// Band thickness (m)
+    pub band_size_m: f64,
+    /// Send `SetAltitude` commands to drones outside their band
+    pub enforce: bool,
+    /// Time between repeated commands to a drone still outside its band
+    pub command_interval: Duration,
+}
+
+impl Default for AltitudeConfig {
+    fn default() -> Self {
+        Self {
+            band_size_m: 100.0,
+            enforce: false,
+            command_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl AltitudeConfig {
+    /// Defaults overridden by `ALTITUDE_BAND_M`, `ALTITUDE_ENFORCE` and
+    /// `ALTITUDE_COMMAND_INTERVAL_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            band_size_m: env("ALTITUDE_BAND_M")
+                .and_then(|s| s.parse().ok())
+                .filter(|m: &f64| *m > 0.0 && m.is_finite())
+                .unwrap_or(defaults.band_size_m),
+            enforce: env("ALTITUDE_ENFORCE")
+                .map(|s| matches!(s.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(defaults.enforce),
+            command_interval: env("ALTITUDE_COMMAND_INTERVAL_SECS")
+                .and_then(|s| s.parse().ok())
+                .filter(|s: &u64| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.command_interval),
+        }
+    }
+}
+
+/// Lowest band a drone can hold; band 0 is centred on the ground
+pub const LOWEST_BAND: i32 = 1;
+
+/// Why a drone cannot be moved to a band
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum BandError {
+    #[error("altitude band {band} is assigned to {holder}")]
+    Taken { band: i32, holder: DroneId },
+    #[error("altitude band {0} is below the lowest band {LOWEST_BAND}")]
+    BelowLowest(i32),
+}
+
+/// One altitude band
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AltitudeBand {
+    pub index: i32,
+    pub floor_m: f64,
+    pub ceiling_m: f64,
+    /// Centre, where drones are sent back to
+    pub altitude_m: f64,
+}
+
+impl AltitudeBand {
+    fn new(index: i32, size: f64) -> Self {
+        let altitude_m = index as f64 * size;
+        Self {
+            index,
+            floor_m: altitude_m - size / 2.0,
+            ceiling_m: altitude_m + size / 2.0,
+            altitude_m,
+        }
+    }
+}
+
+/// A drone's band and where it is flying
+#[derive(Debug, Clone, Serialize)]
+pub struct AltitudeAssignment {
+    pub drone_id: DroneId,
+    pub band: AltitudeBand,
+    pub assigned_at: DateTime<Utc>,
+    /// Last reported altitude
+    pub altitude_m: Option<f64>,
+    pub in_band: bool,
+    /// Drones flying in the same band as this one
+    pub conflicts_with: Vec<DroneId>,
+}
+
+/// A drone outside its band
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AltitudeViolation {
+    pub drone_id: DroneId,
+    pub altitude_m: f64,
+    pub assigned: AltitudeBand,
+    /// Band the drone is flying in
+    pub flying: AltitudeBand,
+    /// Drones also flying in that band
+    pub conflicts_with: Vec<DroneId>,
+    /// First report outside the band
+    pub alert: bool,
+    /// A `SetAltitude` command is due
+    pub command: bool,
+}
+
+#[derive(Debug)]
+struct DroneBand {
+    band: i32,
+    assigned_at: DateTime<Utc>,
+    altitude_m: Option<f64>,
+    /// Set while outside the band, with the last command sent
+    violation: Option<Option<DateTime<Utc>>>,
+}
+
+/// Band assignments of every drone
+#[derive(Debug)]
+pub struct AltitudeManager {
+    config: AltitudeConfig,
+    drones: RwLock<HashMap<DroneId, DroneBand>>,
+}
+
+impl AltitudeManager {
+    pub fn new(config: AltitudeConfig) -> Self {
+        Self {
+            config,
+            drones: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &AltitudeConfig {
+        &self.config
+    }
+
+    /// Band containing `altitude_m`
+    pub fn band_at(&self, altitude_m: f64) -> AltitudeBand {
+        let size = self.config.band_size_m;
+        AltitudeBand::new((altitude_m / size).round() as i32, size)
+    }
+
+    /// Move a drone to `band`, unless another drone holds it or it is
+    /// below [`LOWEST_BAND`]
+    pub fn assign(&self, drone_id: &DroneId, band: i32, now: DateTime<Utc>) -> Result<AltitudeBand, BandError> {
+        if band < LOWEST_BAND {
+            return Err(BandError::BelowLowest(band));
+        }
+        let mut drones = self.drones.write();
+        if let Some((holder, _)) = drones.iter().find(|(id, held)| held.band == band && *id != drone_id) {
+            return Err(BandError::Taken {
+                band,
+                holder: holder.clone(),
+            });
+        }
+        let altitude_m = drones.get(drone_id).and_then(|held| held.altitude_m);
+        drones.insert(
+            drone_id.clone(),
+            DroneBand {
+                band,
+                assigned_at: now,
+                altitude_m,
+                violation: None,
+            },
+        );
+        Ok(AltitudeBand::new(band, self.config.band_size_m))
+    }
+
+    /// Check a drone's reported altitude, giving it the free band nearest
+    /// that altitude if it has none; `None` when there is nothing to report
+    /// or send
+    pub fn observe(&self, drone_id: &DroneId, altitude_m: f64, now: DateTime<Utc>) -> Option<AltitudeViolation> {
+        let flying = self.band_at(altitude_m);
+        let mut drones = self.drones.write();
+        if !drones.contains_key(drone_id) {
+            let band = nearest_free(&drones, flying.index);
+            drones.insert(
+                drone_id.clone(),
+                DroneBand {
+                    band,
+                    assigned_at: now,
+                    altitude_m: None,
+                    violation: None,
+                },
+            );
+        }
+
+        let conflicts_with = in_band(&drones, &self.config, flying.index, drone_id);
+        let held = drones.get_mut(drone_id)?;
+        held.altitude_m = Some(altitude_m);
+        if held.band == flying.index {
+            held.violation = None;
+            return None;
+        }
+
+        let interval = chrono::Duration::from_std(self.config.command_interval).unwrap_or(chrono::Duration::MAX);
+        let enforce = self.config.enforce;
+        let (alert, command) = match &mut held.violation {
+            Some(last_command) => {
+                let due = enforce && last_command.is_none_or(|at| now - at >= interval);
+                if due {
+                    *last_command = Some(now);
+                }
+                (false, due)
+            }
+            None => {
+                held.violation = Some(enforce.then_some(now));
+                (true, enforce)
+            }
+        };
+
+        (alert || command).then(|| AltitudeViolation {
+            drone_id: drone_id.clone(),
+            altitude_m,
+            assigned: AltitudeBand::new(held.band, self.config.band_size_m),
+            flying,
+            conflicts_with,
+            alert,
+            command,
+        })
+    }
+
+    /// Every assignment, lowest band first
+    pub fn assignments(&self) -> Vec<AltitudeAssignment> {
+        let drones = self.drones.read();
+        let mut assignments: Vec<AltitudeAssignment> = drones
+            .iter()
+            .map(|(drone_id, held)| {
+                let band = AltitudeBand::new(held.band, self.config.band_size_m);
+                let flying = held.altitude_m.map(|altitude| self.band_at(altitude).index);
+                AltitudeAssignment {
+                    drone_id: drone_id.clone(),
+                    band,
+                    assigned_at: held.assigned_at,
+                    altitude_m: held.altitude_m,
+                    in_band: flying.is_none_or(|index| index == held.band),
+                    conflicts_with: flying
+                        .map(|index| in_band(&drones, &self.config, index, drone_id))
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        assignments.sort_by_key(|assignment| assignment.band.index);
+        assignments
+    }
+
+    /// Release a drone's band
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.drones.write().remove(drone_id);
+    }
+}
+
+/// Free band closest to `index`, the higher one on a tie, never below
+/// [`LOWEST_BAND`]
+fn nearest_free(drones: &HashMap<DroneId, DroneBand>, index: i32) -> i32 {
+    let index = index.max(LOWEST_BAND);
+    let taken = |band: i32| drones.values().any(|held| held.band == band);
+    (0..)
+        .flat_map(|offset| [index + offset, index - offset])
+        .find(|band| *band >= LOWEST_BAND && !taken(*band))
+        .unwrap_or(index)
+}
+
+/// Other drones whose last report is in band `index`
+fn in_band(drones: &HashMap<DroneId, DroneBand>, config: &AltitudeConfig, index: i32, except: &DroneId) -> Vec<DroneId> {
+    let mut ids: Vec<DroneId> = drones
+        .iter()
+        .filter(|(id, held)| {
+            *id != except
+                && held
+                    .altitude_m
+                    .is_some_and(|altitude| (altitude / config.band_size_m).round() as i32 == index)
+        })
+        .map(|(id, _)| id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assigns_free_bands_and_flags_conflicts() {
+        let manager = AltitudeManager::new(AltitudeConfig {
+            enforce: true,
+            ..Default::default()
+        });
+        let (a, b) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-11"));
+        let t0 = Utc::now();
+        let at = |secs| t0 + chrono::Duration::seconds(secs);
+
+        // The first drone gets the band it flies in, the second the nearest free one
+        assert!(manager.observe(&a, 3110.0, at(0)).is_none());
+        let first = manager.observe(&b, 3090.0, at(0)).unwrap();
+        assert_eq!((first.assigned.index, first.flying.index), (32, 31));
+        assert_eq!(first.assigned.altitude_m, 3200.0);
+        assert_eq!(first.conflicts_with, vec![a.clone()]);
+        assert!(first.alert && first.command);
+
+        // Commands repeat at the interval; the alert does not
+        assert!(manager.observe(&b, 3090.0, at(5)).is_none());
+        let repeat = manager.observe(&b, 3090.0, at(10)).unwrap();
+        assert!(!repeat.alert && repeat.command);
+
+        let assignments = manager.assignments();
+        assert_eq!(assignments.len(), 2);
+        assert!(assignments[0].in_band && !assignments[1].in_band);
+        assert_eq!(assignments[0].conflicts_with, vec![b.clone()]);
+
+        // Back in band re-arms the alert
+        assert!(manager.observe(&b, 3230.0, at(11)).is_none());
+        assert!(manager.observe(&b, 3090.0, at(12)).unwrap().alert);
+
+        // Held bands cannot be taken; released ones can
+        assert_eq!(
+            manager.assign(&b, 31, at(13)),
+            Err(BandError::Taken {
+                band: 31,
+                holder: a.clone()
+            })
+        );
+        assert_eq!(manager.assign(&b, 0, at(13)), Err(BandError::BelowLowest(0)));
+        manager.forget(&a);
+        assert_eq!(manager.assign(&b, 31, at(14)).unwrap().floor_m, 3050.0);
+        assert!(manager.observe(&b, 3090.0, at(15)).is_none());
+
+        // A drone on the ground is given band 1, not band 0
+        let c = DroneId::new("REAPER-21");
+        assert_eq!(manager.observe(&c, 10.0, at(16)).unwrap().assigned.index, 1);
+    }
+}
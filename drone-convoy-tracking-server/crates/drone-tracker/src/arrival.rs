@@ -0,0 +1,210 @@
+//! Waypoint arrival hysteresis
+//!
+//! GPS jitter around the arrival radius would otherwise report the same
+//! arrival over and over. A drone starts arriving when it comes within
+//! `arrival_radius_m` of its waypoint and only stops when it drifts beyond
+//! the wider `departure_radius_m`. At loiter waypoints the arrival is
+//! confirmed once it has stayed for `min_dwell`; other waypoints are flown
+//! through, so the first report inside arrives. Each confirmed arrival fires once: the waypoint
+//! stays latched until the drone leaves its departure radius, so a drone
+//! sent back to a waypoint it is still hovering over does not report it
+//! again.
+
+use drone_core::{GeoPosition, Waypoint, WaypointId};
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Waypoint arrival and departure radii
+#[derive(Debug, Clone)]
+pub struct ArrivalConfig {
+    /// A drone this close to its waypoint is arriving (meters)
+    pub arrival_radius_m: f64,
+    /// An arriving drone further than this has moved off again (meters);
+    /// at least `arrival_radius_m`
+    pub departure_radius_m: f64,
+    /// How long a drone has to stay at a loiter waypoint before the
+    /// arrival counts
+    pub min_dwell: Duration,
+}
+
+impl Default for ArrivalConfig {
+    fn default() -> Self {
+        Self {
+            arrival_radius_m: 100.0,
+            departure_radius_m: 150.0,
+            min_dwell: Duration::ZERO,
+        }
+    }
+}
+
+impl ArrivalConfig {
+    /// Defaults overridden by `WAYPOINT_ARRIVAL_METERS`,
+    /// `WAYPOINT_DEPARTURE_METERS` and `WAYPOINT_MIN_DWELL_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok());
+        let arrival_radius_m = env("WAYPOINT_ARRIVAL_METERS")
+            .filter(|m| *m > 0.0)
+            .unwrap_or(defaults.arrival_radius_m);
+        Self {
+            arrival_radius_m,
+            departure_radius_m: env("WAYPOINT_DEPARTURE_METERS")
+                .unwrap_or(defaults.departure_radius_m)
+                .max(arrival_radius_m),
+            min_dwell: env("WAYPOINT_MIN_DWELL_SECS")
+                .filter(|s| *s >= 0.0)
+                .map(Duration::from_secs_f64)
+                .unwrap_or(defaults.min_dwell),
+        }
+    }
+
+    /// Radii for an arrival threshold given in kilometers, with the
+    /// departure radius half again as wide
+    pub fn with_threshold_km(km: f64) -> Self {
+        Self {
+            arrival_radius_m: km * 1000.0,
+            departure_radius_m: km * 1500.0,
+            ..Self::default()
+        }
+    }
+}
+
+/// One drone's arrival state
+#[derive(Debug, Clone, Default)]
+pub struct ArrivalLatch {
+    /// Waypoint the drone came within the arrival radius of, and when
+    arriving: Option<(WaypointId, DateTime<Utc>)>,
+    /// Last confirmed arrival, until the drone leaves its departure radius
+    latched: Option<(WaypointId, GeoPosition)>,
+}
+
+impl ArrivalLatch {
+    /// Feed a position report heading for `waypoint`; true once, when the
+    /// arrival is confirmed
+    pub fn observe(
+        &mut self,
+        config: &ArrivalConfig,
+        waypoint: &Waypoint,
+        position: &GeoPosition,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let departed = |at: &GeoPosition| position.distance_to(at) * 1000.0 > config.departure_radius_m;
+        if self.latched.as_ref().is_some_and(|(_, at)| departed(at)) {
+            self.latched = None;
+        }
+        if self.latched.as_ref().is_some_and(|(id, _)| id == &waypoint.id) {
+            return false;
+        }
+
+        if self.arriving.as_ref().is_some_and(|(id, _)| id != &waypoint.id) {
+            self.arriving = None;
+        }
+        let distance_m = position.distance_to(&waypoint.position) * 1000.0;
+        if distance_m < config.arrival_radius_m {
+            self.arriving.get_or_insert_with(|| (waypoint.id.clone(), now));
+        } else if departed(&waypoint.position) {
+            self.arriving = None;
+        }
+
+        // Only loiter waypoints are meant to be stayed at
+        let dwell = if waypoint.loiter_time_seconds.is_some_and(|secs| secs > 0) {
+            chrono::Duration::from_std(config.min_dwell).unwrap_or(chrono::Duration::MAX)
+        } else {
+            chrono::Duration::zero()
+        };
+        match &self.arriving {
+            Some((_, since)) if now - *since >= dwell => {
+                self.arriving = None;
+                self.latched = Some((waypoint.id.clone(), waypoint.position));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the drone is within reach of a waypoint but not confirmed yet
+    pub fn is_arriving(&self) -> bool {
+        self.arriving.is_some()
+    }
+
+    /// Forget arrivals, e.g. when the mission changes
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jitter_at_the_threshold_arrives_once() {
+        let config = ArrivalConfig {
+            min_dwell: Duration::from_secs(3),
+            ..ArrivalConfig::default()
+        };
+        let mut waypoint = Waypoint::new("WP01", "Point Alpha", 34.50, 69.20);
+        waypoint.loiter_time_seconds = Some(30);
+        let at = |meters: f64| waypoint.position.destination(meters / 1000.0, 0.0);
+        let t0 = Utc::now();
+        let secs = |s: i64| t0 + chrono::Duration::seconds(s);
+        let mut latch = ArrivalLatch::default();
+
+        // In, then out between the radii, then in again: still arriving
+        assert!(!latch.observe(&config, &waypoint, &at(95.0), t0));
+        assert!(latch.is_arriving());
+        assert!(!latch.observe(&config, &waypoint, &at(120.0), secs(1)));
+        assert!(!latch.observe(&config, &waypoint, &at(98.0), secs(2)));
+        assert!(latch.observe(&config, &waypoint, &at(110.0), secs(3)));
+        // Latched while the jitter goes on
+        for s in 4..10 {
+            assert!(!latch.observe(&config, &waypoint, &at(90.0 + s as f64), secs(s)));
+        }
+
+        // Gone past the departure radius, the waypoint can be reached again
+        assert!(!latch.observe(&config, &waypoint, &at(400.0), secs(10)));
+        assert!(!latch.observe(&config, &waypoint, &at(50.0), secs(11)));
+        assert!(latch.observe(&config, &waypoint, &at(50.0), secs(14)));
+    }
+
+    #[test]
+    fn test_leaving_before_the_dwell_starts_over() {
+        let config = ArrivalConfig {
+            min_dwell: Duration::from_secs(3),
+            ..ArrivalConfig::default()
+        };
+        let mut waypoint = Waypoint::new("WP01", "Point Alpha", 34.50, 69.20);
+        waypoint.loiter_time_seconds = Some(30);
+        let t0 = Utc::now();
+        let mut latch = ArrivalLatch::default();
+
+        assert!(!latch.observe(&config, &waypoint, &waypoint.position, t0));
+        let away = waypoint.position.destination(0.2, 90.0);
+        assert!(!latch.observe(&config, &waypoint, &away, t0 + chrono::Duration::seconds(2)));
+        assert!(!latch.is_arriving());
+        assert!(!latch.observe(&config, &waypoint, &waypoint.position, t0 + chrono::Duration::seconds(4)));
+
+        // Without a dwell the first report inside arrives
+        let mut latch = ArrivalLatch::default();
+        assert!(latch.observe(&ArrivalConfig::default(), &waypoint, &waypoint.position, t0));
+        assert!(!latch.observe(&ArrivalConfig::default(), &waypoint, &waypoint.position, t0));
+    }
+
+    #[test]
+    fn test_fly_through_waypoints_skip_the_dwell() {
+        let config = ArrivalConfig {
+            min_dwell: Duration::from_secs(3),
+            ..ArrivalConfig::default()
+        };
+        let waypoint = Waypoint::new("WP01", "Point Alpha", 34.50, 69.20);
+        let mut latch = ArrivalLatch::default();
+
+        // One report inside on the way through is enough
+        assert!(latch.observe(&config, &waypoint, &waypoint.position, Utc::now()));
+    }
+}
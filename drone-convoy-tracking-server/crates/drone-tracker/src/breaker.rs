@@ -0,0 +1,342 @@
+//! Per-drone circuit breakers for telemetry writes
+//!
+//! One drone sending pathological telemetry (huge strings, timestamps far
+//! off, non-finite numbers) must not keep failing writes for everyone
+//! else. Rows are checked before they are written; a malformed row or a
+//! failed write counts against the drone's breaker, and after
+//! `failure_threshold` failures in a row the breaker opens. While open,
+//! the drone's rows go straight to the dead-letter table. After
+//! `cool_down` the breaker lets rows through again as probes, and closes
+//! once `probe_successes` of them are written; a failed probe opens it
+//! again. Failed writes from several drones since the last good one point
+//! at the database rather than a drone, so they count against no breaker.
+
+use drone_core::DroneId;
+use drone_db::TelemetryRecord;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Write breaker configuration
+#[derive(Debug, Clone)]
+pub struct WriteBreakerConfig {
+    /// Failures in a row that open a drone's breaker
+    pub failure_threshold: u32,
+    /// Time an open breaker refuses writes before probing
+    pub cool_down: Duration,
+    /// Successful probe writes that close the breaker
+    pub probe_successes: u32,
+    /// Longest accepted string field (drone ID, status)
+    pub max_field_len: usize,
+    /// How far a timestamp may be ahead of the server clock
+    pub max_future: Duration,
+    /// How far a timestamp may be behind it
+    pub max_age: Duration,
+}
+
+impl Default for WriteBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cool_down: Duration::from_secs(30),
+            probe_successes: 2,
+            max_field_len: 128,
+            max_future: Duration::from_secs(300),
+            max_age: Duration::from_secs(7 * 24 * 3600),
+        }
+    }
+}
+
+impl WriteBreakerConfig {
+    /// Defaults overridden by `DB_BREAKER_FAILURES`,
+    /// `DB_BREAKER_COOLDOWN_SECS` and `DB_BREAKER_PROBES`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|s| s.parse::<u64>().ok()).filter(|n| *n > 0);
+        Self {
+            failure_threshold: env("DB_BREAKER_FAILURES")
+                .map(|n| n.min(u32::MAX as u64) as u32)
+                .unwrap_or(defaults.failure_threshold),
+            cool_down: env("DB_BREAKER_COOLDOWN_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.cool_down),
+            probe_successes: env("DB_BREAKER_PROBES")
+                .map(|n| n.min(u32::MAX as u64) as u32)
+                .unwrap_or(defaults.probe_successes),
+            ..defaults
+        }
+    }
+
+    /// Why `record` should not be written, if it should not
+    pub fn defect(&self, record: &TelemetryRecord, now: DateTime<Utc>) -> Option<String> {
+        let fields = [("drone_id", Some(&record.drone_id)), ("status", record.status.as_ref())];
+        if let Some((name, value)) = fields
+            .into_iter()
+            .find_map(|(name, value)| value.filter(|v| v.len() > self.max_field_len).map(|v| (name, v)))
+        {
+            return Some(format!("{} is {} bytes, over {}", name, value.len(), self.max_field_len));
+        }
+
+        let numbers = [
+            ("latitude", Some(record.latitude)),
+            ("longitude", Some(record.longitude)),
+            ("altitude", Some(record.altitude)),
+            ("heading", Some(record.heading)),
+            ("speed", Some(record.speed)),
+            ("temperature", record.temperature),
+        ];
+        if let Some((name, _)) = numbers.iter().find(|(_, value)| value.is_some_and(|v| !v.is_finite())) {
+            return Some(format!("{} is not a finite number", name));
+        }
+
+        let ahead = chrono::Duration::from_std(self.max_future).unwrap_or(chrono::Duration::MAX);
+        let behind = chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX);
+        if record.timestamp > now + ahead {
+            return Some(format!("timestamp {} is in the future", record.timestamp.to_rfc3339()));
+        }
+        if record.timestamp < now - behind {
+            return Some(format!("timestamp {} is too old", record.timestamp.to_rfc3339()));
+        }
+        None
+    }
+}
+
+/// State of one drone's breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    /// Refusing writes until `until`
+    Open { until: DateTime<Utc> },
+    /// Letting probe writes through
+    HalfOpen { successes: u32 },
+}
+
+/// A drone's breaker, for the status endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub drone_id: DroneId,
+    #[serde(flatten)]
+    pub state: BreakerState,
+    /// Failures in a row
+    pub failures: u32,
+    /// Rows sent to the dead-letter table
+    pub quarantined: u64,
+    /// Last time the breaker opened
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: BreakerState,
+    failures: u32,
+    quarantined: u64,
+    opened_at: Option<DateTime<Utc>>,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            failures: 0,
+            quarantined: 0,
+            opened_at: None,
+        }
+    }
+}
+
+/// Write breakers of every drone
+#[derive(Debug)]
+pub struct WriteBreakers {
+    config: WriteBreakerConfig,
+    breakers: RwLock<HashMap<DroneId, Breaker>>,
+    /// Drones whose writes failed since any write last succeeded
+    failing: RwLock<HashSet<DroneId>>,
+}
+
+impl WriteBreakers {
+    pub fn new(config: WriteBreakerConfig) -> Self {
+        Self {
+            config,
+            breakers: RwLock::new(HashMap::new()),
+            failing: RwLock::new(HashSet::new()),
+        }
+    }
+
+    pub fn config(&self) -> &WriteBreakerConfig {
+        &self.config
+    }
+
+    /// Whether a row from `drone_id` may be written now; an open breaker
+    /// past its cool-down starts probing
+    pub fn admit(&self, drone_id: &DroneId, now: DateTime<Utc>) -> bool {
+        let mut breakers = self.breakers.write();
+        let Some(breaker) = breakers.get_mut(drone_id) else {
+            return true;
+        };
+        match breaker.state {
+            BreakerState::Open { until } if now < until => false,
+            BreakerState::Open { .. } => {
+                breaker.state = BreakerState::HalfOpen { successes: 0 };
+                true
+            }
+            _ => true,
+        }
+    }
+
+    /// A row was written; returns true when this closed the breaker
+    pub fn record_success(&self, drone_id: &DroneId) -> bool {
+        self.failing.write().clear();
+        let mut breakers = self.breakers.write();
+        let Some(breaker) = breakers.get_mut(drone_id) else {
+            return false;
+        };
+        breaker.failures = 0;
+        if let BreakerState::HalfOpen { successes } = &mut breaker.state {
+            *successes += 1;
+            if *successes >= self.config.probe_successes {
+                breaker.state = BreakerState::Closed;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// A row was malformed or failed to write; returns true when this
+    /// opened the breaker
+    pub fn record_failure(&self, drone_id: &DroneId, now: DateTime<Utc>) -> bool {
+        let cool_down = chrono::Duration::from_std(self.config.cool_down).unwrap_or(chrono::Duration::MAX);
+        let mut breakers = self.breakers.write();
+        let breaker = breakers.entry(drone_id.clone()).or_default();
+        breaker.failures += 1;
+        let open = match breaker.state {
+            BreakerState::Closed => breaker.failures >= self.config.failure_threshold,
+            BreakerState::HalfOpen { .. } => true,
+            BreakerState::Open { .. } => false,
+        };
+        if open {
+            breaker.state = BreakerState::Open { until: now + cool_down };
+            breaker.opened_at = Some(now);
+        }
+        open
+    }
+
+    /// A well-formed row failed to write; counted like `record_failure`
+    /// unless writes from other drones have failed too since the last
+    /// good one, which points at the database
+    pub fn record_write_failure(&self, drone_id: &DroneId, now: DateTime<Utc>) -> bool {
+        let outage = {
+            let mut failing = self.failing.write();
+            failing.insert(drone_id.clone());
+            failing.len() > 1
+        };
+        !outage && self.record_failure(drone_id, now)
+    }
+
+    /// Count a row sent to the dead-letter table
+    pub fn record_quarantined(&self, drone_id: &DroneId) {
+        self.breakers.write().entry(drone_id.clone()).or_default().quarantined += 1;
+    }
+
+    /// Drones that have had a failure or a quarantined row, by ID
+    pub fn status(&self) -> Vec<BreakerStatus> {
+        let mut status: Vec<BreakerStatus> = self
+            .breakers
+            .read()
+            .iter()
+            .map(|(drone_id, breaker)| BreakerStatus {
+                drone_id: drone_id.clone(),
+                state: breaker.state,
+                failures: breaker.failures,
+                quarantined: breaker.quarantined,
+                opened_at: breaker.opened_at,
+            })
+            .collect();
+        status.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+        status
+    }
+
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.breakers.write().remove(drone_id);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{DroneStatus, GeoPosition, Telemetry};
+
+    #[test]
+    fn test_breaker_opens_probes_and_closes() {
+        let breakers = WriteBreakers::new(WriteBreakerConfig::default());
+        let drone = DroneId::new("REAPER-01");
+        let t0 = Utc::now();
+        let at = |secs| t0 + chrono::Duration::seconds(secs);
+
+        // Malformed rows are caught before writing
+        let mut telemetry = Telemetry {
+            timestamp: t0,
+            ..Telemetry::default()
+        };
+        let record = |telemetry: &Telemetry| {
+            TelemetryRecord::new(&drone, &GeoPosition::new(34.5, 69.2, 3000.0), telemetry, DroneStatus::Moving, true, None)
+        };
+        let config = breakers.config();
+        assert_eq!(config.defect(&record(&telemetry), t0), None);
+        telemetry.speed = f64::NAN;
+        assert_eq!(config.defect(&record(&telemetry), t0).unwrap(), "speed is not a finite number");
+        telemetry.speed = 0.0;
+        telemetry.timestamp = at(3600);
+        assert!(config.defect(&record(&telemetry), t0).unwrap().contains("future"));
+        let mut long = record(&Telemetry::default());
+        long.drone_id = "X".repeat(1000);
+        assert!(config.defect(&long, t0).unwrap().starts_with("drone_id"));
+
+        // Three failures in a row open the breaker; a success in between resets them
+        assert!(!breakers.record_failure(&drone, at(0)));
+        breakers.record_success(&drone);
+        assert!(!breakers.record_failure(&drone, at(1)));
+        assert!(!breakers.record_failure(&drone, at(2)));
+        assert!(breakers.record_failure(&drone, at(3)));
+        assert!(!breakers.admit(&drone, at(20)));
+        assert!(breakers.admit(&DroneId::new("REAPER-02"), at(20)));
+
+        // After the cool-down a failed probe reopens it, two good ones close it
+        assert!(breakers.admit(&drone, at(33)));
+        assert!(breakers.record_failure(&drone, at(33)));
+        assert!(!breakers.admit(&drone, at(60)));
+        assert!(breakers.admit(&drone, at(63)));
+        assert!(!breakers.record_success(&drone));
+        assert!(breakers.record_success(&drone));
+        breakers.record_quarantined(&drone);
+
+        let status = breakers.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!((status[0].state, status[0].failures, status[0].quarantined), (BreakerState::Closed, 0, 1));
+        assert_eq!(status[0].opened_at, Some(at(33)));
+
+        // Write failures across drones are a database outage, not a drone's fault
+        let others = ["REAPER-02", "REAPER-03"].map(DroneId::new);
+        for secs in 100..110 {
+            for drone in &others {
+                assert!(!breakers.record_write_failure(drone, at(secs)));
+            }
+        }
+        assert!(breakers.admit(&others[0], at(110)) && breakers.admit(&others[1], at(110)));
+
+        // One drone failing alone still opens its breaker
+        breakers.record_success(&drone);
+        let alone = DroneId::new("REAPER-04");
+        for secs in 111..113 {
+            assert!(!breakers.record_write_failure(&alone, at(secs)));
+        }
+        assert!(breakers.record_write_failure(&alone, at(113)));
+    }
+}
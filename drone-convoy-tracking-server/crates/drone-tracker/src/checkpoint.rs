@@ -0,0 +1,216 @@
+//! Checkpoint acknowledgment
+//!
+//! A drone reaching a `Checkpoint` waypoint holds there until an operator
+//! acknowledges it. The hold raises an acknowledgment-required alert, and
+//! every `escalation_interval` without an acknowledgment the alert is raised
+//! again one severity higher, up to `Emergency`. Drones are never released
+//! by the timeout, only by an acknowledgment. Holds are off unless enabled
+//! (`CHECKPOINT_HOLDS`); without them checkpoints are flown like any other
+//! waypoint.
+
+use drone_core::{AlertSeverity, DroneId, WaypointId};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Alert type raised for drones waiting at a checkpoint
+pub const CHECKPOINT_ALERT_TYPE: &str = "CHECKPOINT_ACK_REQUIRED";
+
+/// Checkpoint workflow configuration
+#[derive(Debug, Clone)]
+pub struct CheckpointConfig {
+    /// Hold drones at checkpoints until acknowledged
+    pub enabled: bool,
+    /// Unacknowledged time before each escalation
+    pub escalation_interval: Duration,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            escalation_interval: Duration::from_secs(120),
+        }
+    }
+}
+
+impl CheckpointConfig {
+    /// Defaults overridden by `CHECKPOINT_HOLDS` and `CHECKPOINT_ESCALATION_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            enabled: env("CHECKPOINT_HOLDS")
+                .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
+                .unwrap_or(defaults.enabled),
+            escalation_interval: env("CHECKPOINT_ESCALATION_SECS")
+                .and_then(|s| s.parse().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.escalation_interval),
+        }
+    }
+}
+
+/// A drone waiting at a checkpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointHold {
+    pub drone_id: DroneId,
+    pub waypoint_id: WaypointId,
+    pub waypoint_name: String,
+    pub arrived_at: DateTime<Utc>,
+    /// Severity of the latest acknowledgment-required alert
+    pub severity: AlertSeverity,
+    pub escalations: u32,
+    /// When the alert escalates next (`None` once at `Emergency`)
+    pub escalates_at: Option<DateTime<Utc>>,
+}
+
+/// Drones held at checkpoints
+#[derive(Debug, Default)]
+pub struct CheckpointGate {
+    config: CheckpointConfig,
+    holds: RwLock<HashMap<DroneId, CheckpointHold>>,
+}
+
+impl CheckpointGate {
+    pub fn new(config: CheckpointConfig) -> Self {
+        Self {
+            config,
+            holds: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Hold a drone that just arrived at a checkpoint
+    pub fn hold(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        waypoint_name: &str,
+        now: DateTime<Utc>,
+    ) -> CheckpointHold {
+        let hold = CheckpointHold {
+            drone_id: drone_id.clone(),
+            waypoint_id: waypoint_id.clone(),
+            waypoint_name: waypoint_name.to_string(),
+            arrived_at: now,
+            severity: AlertSeverity::Warning,
+            escalations: 0,
+            escalates_at: Some(now + self.interval()),
+        };
+        self.holds.write().insert(drone_id.clone(), hold.clone());
+        hold
+    }
+
+    pub fn is_held(&self, drone_id: &DroneId) -> bool {
+        self.holds.read().contains_key(drone_id)
+    }
+
+    pub fn get(&self, drone_id: &DroneId) -> Option<CheckpointHold> {
+        self.holds.read().get(drone_id).cloned()
+    }
+
+    /// Waiting drones, longest waiting first
+    pub fn list(&self) -> Vec<CheckpointHold> {
+        let mut holds: Vec<_> = self.holds.read().values().cloned().collect();
+        holds.sort_by(|a, b| a.arrived_at.cmp(&b.arrived_at).then_with(|| a.drone_id.cmp(&b.drone_id)));
+        holds
+    }
+
+    /// Release the drones held at a waypoint (only `drone_id` if given)
+    pub fn acknowledge(&self, waypoint_id: &WaypointId, drone_id: Option<&DroneId>) -> Vec<CheckpointHold> {
+        let mut holds = self.holds.write();
+        let released: Vec<DroneId> = holds
+            .values()
+            .filter(|h| &h.waypoint_id == waypoint_id && drone_id.is_none_or(|d| d == &h.drone_id))
+            .map(|h| h.drone_id.clone())
+            .collect();
+        let mut released: Vec<_> = released.iter().filter_map(|d| holds.remove(d)).collect();
+        released.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+        released
+    }
+
+    /// Drop a drone's hold without an acknowledgment (e.g. mission reset)
+    pub fn clear(&self, drone_id: &DroneId) -> Option<CheckpointHold> {
+        self.holds.write().remove(drone_id)
+    }
+
+    /// Escalate holds whose interval has elapsed, returning them as escalated
+    pub fn escalate(&self, now: DateTime<Utc>) -> Vec<CheckpointHold> {
+        let interval = self.interval();
+        let mut escalated = Vec::new();
+        for hold in self.holds.write().values_mut() {
+            if hold.escalates_at.is_none_or(|at| now < at) {
+                continue;
+            }
+            hold.severity = next_severity(hold.severity);
+            hold.escalations += 1;
+            hold.escalates_at = (hold.severity != AlertSeverity::Emergency).then(|| now + interval);
+            escalated.push(hold.clone());
+        }
+        escalated.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+        escalated
+    }
+
+    fn interval(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.config.escalation_interval).unwrap_or_default()
+    }
+}
+
+fn next_severity(severity: AlertSeverity) -> AlertSeverity {
+    match severity {
+        AlertSeverity::Info => AlertSeverity::Warning,
+        AlertSeverity::Warning => AlertSeverity::Critical,
+        AlertSeverity::Critical | AlertSeverity::Emergency => AlertSeverity::Emergency,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_escalation_and_acknowledgment() {
+        let gate = CheckpointGate::new(CheckpointConfig {
+            enabled: true,
+            escalation_interval: Duration::from_secs(60),
+        });
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let [r1, r2] = ["REAPER-01", "REAPER-02"].map(DroneId::new);
+        let wp03 = WaypointId::new("WP03");
+
+        gate.hold(&r1, &wp03, "Checkpoint Bravo", at(0));
+        gate.hold(&r2, &wp03, "Checkpoint Bravo", at(30));
+        assert!(gate.escalate(at(59)).is_empty());
+
+        let escalated = gate.escalate(at(60));
+        assert_eq!(escalated.len(), 1);
+        assert_eq!(escalated[0].severity, AlertSeverity::Critical);
+
+        // Escalation stops at Emergency
+        assert_eq!(gate.escalate(at(120))[0].severity, AlertSeverity::Emergency);
+        assert_eq!(gate.get(&r1).unwrap().escalates_at, None);
+        assert!(gate.escalate(at(600)).iter().all(|h| h.drone_id == r2));
+
+        // Acknowledging one drone leaves the other waiting
+        assert!(gate.acknowledge(&WaypointId::new("WP04"), None).is_empty());
+        let released = gate.acknowledge(&wp03, Some(&r1));
+        assert_eq!(released[0].escalations, 2);
+        assert!(!gate.is_held(&r1) && gate.is_held(&r2));
+        assert_eq!(gate.acknowledge(&wp03, None).len(), 1);
+        assert!(gate.list().is_empty());
+    }
+}
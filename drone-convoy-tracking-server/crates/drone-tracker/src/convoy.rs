@@ -1,33 +1,16 @@
 //! Convoy formation management
+//!
+//! Drones may carry a convoy role. Cargo and drones without a role form the
+//! convoy body and fly the selected formation; scouts fly ahead of the body
+//! and escorts flank it on alternating sides.
 
-use drone_core::{Drone, DroneId, GeoPosition, Mission};
+use drone_core::{AlertSeverity, ConvoyRole, DroneId, GeoPosition};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::info;
 
-/// Convoy formation types
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Formation {
-    /// Single file line
-    Line,
-    /// V-shape formation
-    Vee,
-    /// Diamond formation
-    Diamond,
-    /// Echelon (diagonal) formation
-    Echelon,
-    /// Column formation
-    Column,
-    /// Spread formation
-    Spread,
-}
-
-impl Default for Formation {
-    fn default() -> Self {
-        Self::Line
-    }
-}
+pub use drone_core::Formation;
 
 /// Convoy manager
 pub struct ConvoyManager {
@@ -41,6 +24,10 @@ pub struct ConvoyManager {
     offsets: Arc<RwLock<HashMap<DroneId, FormationOffset>>>,
     /// Spacing between drones (meters)
     spacing: f64,
+    /// Convoy speed multiplier (1.0 = nominal)
+    speed_factor: Arc<RwLock<f64>>,
+    /// Assigned convoy roles
+    roles: Arc<RwLock<HashMap<DroneId, ConvoyRole>>>,
 }
 
 /// Offset from leader position
@@ -54,6 +41,36 @@ pub struct FormationOffset {
     pub vertical: f64,
 }
 
+/// Alert severities that depend on a drone's convoy role
+#[derive(Debug, Clone)]
+pub struct RoleAlertPolicy {
+    /// Signal strength (%) below which signal loss is alerted
+    pub signal_weak_below: u8,
+    /// Signal-loss severity per role
+    pub signal_loss: HashMap<ConvoyRole, AlertSeverity>,
+    /// Signal-loss severity for roles not listed (and drones without one)
+    pub default_signal_loss: AlertSeverity,
+}
+
+impl Default for RoleAlertPolicy {
+    fn default() -> Self {
+        Self {
+            signal_weak_below: 20,
+            // Losing the scout blinds the convoy
+            signal_loss: HashMap::from([(ConvoyRole::Scout, AlertSeverity::Critical)]),
+            default_signal_loss: AlertSeverity::Warning,
+        }
+    }
+}
+
+impl RoleAlertPolicy {
+    /// Severity of a signal-loss alert for a drone with `role`
+    pub fn signal_loss_severity(&self, role: Option<ConvoyRole>) -> AlertSeverity {
+        role.and_then(|r| self.signal_loss.get(&r).copied())
+            .unwrap_or(self.default_signal_loss)
+    }
+}
+
 impl ConvoyManager {
     /// Create a new convoy manager
     pub fn new() -> Self {
@@ -63,6 +80,8 @@ impl ConvoyManager {
             order: Arc::new(RwLock::new(Vec::new())),
             offsets: Arc::new(RwLock::new(HashMap::new())),
             spacing: 50.0, // 50 meters default spacing
+            speed_factor: Arc::new(RwLock::new(1.0)),
+            roles: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -78,6 +97,17 @@ impl ConvoyManager {
         *self.formation.read()
     }
 
+    /// Set convoy speed multiplier
+    pub fn set_speed_factor(&self, factor: f64) {
+        *self.speed_factor.write() = factor.clamp(0.0, 1.0);
+        info!("Convoy speed factor set to {:.2}", factor);
+    }
+
+    /// Get convoy speed multiplier
+    pub fn speed_factor(&self) -> f64 {
+        *self.speed_factor.read()
+    }
+
     /// Set convoy leader
     pub fn set_leader(&self, drone_id: DroneId) {
         *self.leader.write() = Some(drone_id.clone());
@@ -100,19 +130,89 @@ impl ConvoyManager {
         self.order.read().clone()
     }
 
+    /// Assign a convoy role (`None` clears it)
+    pub fn set_role(&self, drone_id: &DroneId, role: Option<ConvoyRole>) {
+        match role {
+            Some(role) => {
+                self.roles.write().insert(drone_id.clone(), role);
+                info!("Drone {} assigned convoy role {}", drone_id, role);
+            }
+            None => {
+                self.roles.write().remove(drone_id);
+                info!("Convoy role cleared for drone {}", drone_id);
+            }
+        }
+        self.recalculate_offsets();
+    }
+
+    /// Get a drone's convoy role
+    pub fn role(&self, drone_id: &DroneId) -> Option<ConvoyRole> {
+        self.roles.read().get(drone_id).copied()
+    }
+
+    /// All assigned convoy roles
+    pub fn roles(&self) -> HashMap<DroneId, ConvoyRole> {
+        self.roles.read().clone()
+    }
+
     /// Set spacing between drones
-    pub fn set_spacing(&self, meters: f64) {
+    pub fn set_spacing(&self, _meters: f64) {
         // self.spacing = meters;
         self.recalculate_offsets();
     }
 
-    /// Recalculate formation offsets based on current formation
+    /// Recalculate formation offsets based on current formation and roles
     fn recalculate_offsets(&self) {
         let formation = *self.formation.read();
+        let roles = self.roles.read().clone();
         let order = self.order.read().clone();
+        let with_role = |role: ConvoyRole| -> Vec<&DroneId> {
+            order.iter().filter(|id| roles.get(*id) == Some(&role)).collect()
+        };
+        let scouts = with_role(ConvoyRole::Scout);
+        let escorts = with_role(ConvoyRole::Escort);
+        let body: Vec<DroneId> = order
+            .iter()
+            .filter(|id| !matches!(roles.get(*id), Some(ConvoyRole::Scout | ConvoyRole::Escort)))
+            .cloned()
+            .collect();
+
         let mut offsets = self.offsets.write();
         offsets.clear();
+        self.body_offsets(formation, &body, &mut offsets);
+        let width = offsets.values().map(|o| o.lateral.abs()).fold(0.0, f64::max);
+        let length = offsets.values().map(|o| o.longitudinal).fold(0.0, f64::max);
+
+        // Scouts side by side, two spacings ahead of the body
+        let centre = (scouts.len() as f64 - 1.0) / 2.0;
+        for (k, drone_id) in scouts.into_iter().enumerate() {
+            offsets.insert(drone_id.clone(), FormationOffset {
+                lateral: self.spacing * (k as f64 - centre),
+                longitudinal: -2.0 * self.spacing,
+                vertical: 0.0,
+            });
+        }
+
+        // Escorts in pairs, right then left, one spacing outside the body
+        // and spread evenly along its length
+        let pairs = escorts.len().div_ceil(2);
+        for (k, drone_id) in escorts.into_iter().enumerate() {
+            let side = if k % 2 == 0 { 1.0 } else { -1.0 };
+            let longitudinal = if pairs > 1 {
+                length * (k / 2) as f64 / (pairs - 1) as f64
+            } else {
+                length / 2.0
+            };
+            offsets.insert(drone_id.clone(), FormationOffset {
+                lateral: side * (width + self.spacing),
+                longitudinal,
+                vertical: 0.0,
+            });
+        }
+    }
 
+    /// Formation offsets for the convoy body; the first drone is the origin
+    fn body_offsets(&self, formation: Formation, order: &[DroneId], offsets: &mut HashMap<DroneId, FormationOffset>) {
         for (i, drone_id) in order.iter().enumerate() {
             if i == 0 {
                 // Leader has no offset
@@ -132,7 +232,7 @@ impl ConvoyManager {
                 },
                 Formation::Vee => {
                     let side = if i % 2 == 1 { 1.0 } else { -1.0 };
-                    let row = ((i + 1) / 2) as f64;
+                    let row = i.div_ceil(2) as f64;
                     FormationOffset {
                         lateral: side * self.spacing * row * 0.7,
                         longitudinal: self.spacing * row,
@@ -203,6 +303,48 @@ impl ConvoyManager {
         self.offsets.read().get(drone_id).copied()
     }
 
+    /// Drone the formation is measured from: the set leader, or else the
+    /// drone at the formation origin
+    pub fn formation_leader(&self) -> Option<DroneId> {
+        self.get_leader().or_else(|| {
+            self.offsets
+                .read()
+                .iter()
+                .find(|(_, o)| o.lateral == 0.0 && o.longitudinal == 0.0)
+                .map(|(id, _)| id.clone())
+        })
+    }
+
+    /// Share (0-100) of drones holding their formation slot within
+    /// `tolerance_meters`, measured from the leader's position and heading.
+    /// `None` until the leader and at least one follower have a position.
+    pub fn compliance_percent(
+        &self,
+        positions: &HashMap<DroneId, (GeoPosition, f64)>,
+        tolerance_meters: f64,
+    ) -> Option<f64> {
+        let leader = self.formation_leader()?;
+        let followers: Vec<DroneId> = self
+            .offsets
+            .read()
+            .keys()
+            .filter(|id| **id != leader && positions.contains_key(*id))
+            .cloned()
+            .collect();
+        let (leader_position, leader_heading) = positions.get(&leader)?;
+        if followers.is_empty() {
+            return None;
+        }
+
+        let in_position = followers
+            .iter()
+            .filter(|id| {
+                self.is_in_position(id, &positions[*id].0, leader_position, *leader_heading, tolerance_meters)
+            })
+            .count();
+        Some(in_position as f64 * 100.0 / followers.len() as f64)
+    }
+
     /// Check if drone is in formation position
     pub fn is_in_position(
         &self,
@@ -262,4 +404,53 @@ mod tests {
         assert!(offset2.is_some());
         assert!(offset2.unwrap().longitudinal > 0.0); // Behind leader
     }
+
+    #[test]
+    fn test_formation_compliance() {
+        let convoy = ConvoyManager::new();
+        let ids: Vec<DroneId> = (1..=3).map(|n| DroneId::new(format!("REAPER-0{}", n))).collect();
+        convoy.set_order(ids.clone());
+        assert_eq!(convoy.formation_leader(), Some(ids[0].clone()));
+
+        let leader = GeoPosition::new(34.5, 69.2, 1000.0);
+        let slot = |n: usize| convoy.get_target_position(&ids[n], &leader, 0.0).unwrap();
+        let mut positions = HashMap::new();
+        positions.insert(ids[0].clone(), (leader, 0.0));
+        assert_eq!(convoy.compliance_percent(&positions, 50.0), None);
+
+        positions.insert(ids[1].clone(), (slot(1), 0.0));
+        positions.insert(ids[2].clone(), (slot(2).destination(0.2, 90.0), 0.0));
+        assert_eq!(convoy.compliance_percent(&positions, 50.0), Some(50.0));
+        assert_eq!(convoy.compliance_percent(&positions, 500.0), Some(100.0));
+    }
+
+    #[test]
+    fn test_role_offsets() {
+        let convoy = ConvoyManager::new();
+        let ids: Vec<DroneId> = (1..=6).map(|n| DroneId::new(format!("REAPER-0{}", n))).collect();
+        convoy.set_order(ids.clone());
+        convoy.set_role(&ids[0], Some(ConvoyRole::Scout));
+        convoy.set_role(&ids[1], Some(ConvoyRole::Escort));
+        convoy.set_role(&ids[2], Some(ConvoyRole::Cargo));
+        convoy.set_role(&ids[4], Some(ConvoyRole::Escort));
+        let offset = |n: usize| convoy.get_offset(&ids[n]).unwrap();
+
+        // Cargo and the unassigned drone form a line
+        assert_eq!((offset(2).lateral, offset(2).longitudinal), (0.0, 0.0));
+        assert_eq!((offset(3).lateral, offset(3).longitudinal), (0.0, 50.0));
+        assert_eq!(offset(5).longitudinal, 100.0);
+
+        // Scout ahead, escorts on either side of the body's middle
+        assert_eq!(offset(0).longitudinal, -100.0);
+        assert_eq!((offset(1).lateral, offset(1).longitudinal), (50.0, 50.0));
+        assert_eq!((offset(4).lateral, offset(4).longitudinal), (-50.0, 50.0));
+
+        let policy = RoleAlertPolicy::default();
+        assert_eq!(policy.signal_loss_severity(convoy.role(&ids[0])), AlertSeverity::Critical);
+        assert_eq!(policy.signal_loss_severity(convoy.role(&ids[3])), AlertSeverity::Warning);
+
+        convoy.set_role(&ids[0], None);
+        assert_eq!(offset(0).longitudinal, 0.0);
+        assert_eq!(convoy.roles().len(), 3);
+    }
 }
@@ -0,0 +1,109 @@
+//! Integrator-defined event types
+//!
+//! An integrator registers a JSON Schema for each of their event types
+//! (`acme.SENSOR_POD_STATUS`); events of a registered type are validated,
+//! broadcast on the bus as `CUSTOM` events and persisted by type. Events of
+//! unregistered types are refused, so the schema doubles as the contract
+//! WebSocket clients can rely on.
+
+use drone_core::custom::validate_type_name;
+use drone_core::{CustomEvent, CustomEventError, EventSchema};
+use drone_db::CustomEventSchemaRecord;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A custom event type and the schema its values must match
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredSchema {
+    pub event_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub schema: EventSchema,
+    pub registered_at: DateTime<Utc>,
+}
+
+impl RegisteredSchema {
+    /// Check the type name and compile the schema
+    pub fn new(
+        event_type: impl Into<String>,
+        schema: Value,
+        description: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, CustomEventError> {
+        let event_type = event_type.into();
+        validate_type_name(&event_type)?;
+        Ok(Self {
+            event_type,
+            description,
+            schema: EventSchema::compile(schema)?,
+            registered_at: now,
+        })
+    }
+
+    pub fn to_record(&self) -> CustomEventSchemaRecord {
+        CustomEventSchemaRecord {
+            event_type: self.event_type.clone(),
+            schema: self.schema.source().to_string(),
+            description: self.description.clone(),
+            registered_at: self.registered_at,
+        }
+    }
+
+    pub fn from_record(record: &CustomEventSchemaRecord) -> anyhow::Result<Self> {
+        let schema = serde_json::from_str(&record.schema)?;
+        Ok(Self::new(
+            record.event_type.clone(),
+            schema,
+            record.description.clone(),
+            record.registered_at,
+        )?)
+    }
+}
+
+/// Registered custom event types, by name
+#[derive(Debug, Default)]
+pub struct CustomEventRegistry {
+    schemas: RwLock<BTreeMap<String, RegisteredSchema>>,
+}
+
+impl CustomEventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a schema, or replace the schema for the same type
+    pub fn upsert(&self, schema: RegisteredSchema) {
+        self.schemas.write().insert(schema.event_type.clone(), schema);
+    }
+
+    pub fn remove(&self, event_type: &str) -> Option<RegisteredSchema> {
+        self.schemas.write().remove(event_type)
+    }
+
+    pub fn get(&self, event_type: &str) -> Option<RegisteredSchema> {
+        self.schemas.read().get(event_type).cloned()
+    }
+
+    /// All schemas, by type name
+    pub fn list(&self) -> Vec<RegisteredSchema> {
+        self.schemas.read().values().cloned().collect()
+    }
+
+    /// Replace the registry with persisted schemas
+    pub fn restore(&self, schemas: Vec<RegisteredSchema>) {
+        *self.schemas.write() = schemas.into_iter().map(|s| (s.event_type.clone(), s)).collect();
+    }
+
+    /// Validate an event against the schema of its type
+    pub fn check(&self, event: &CustomEvent) -> Result<(), CustomEventError> {
+        let schemas = self.schemas.read();
+        let registered = schemas
+            .get(&event.event_type)
+            .ok_or_else(|| CustomEventError::UnknownType(event.event_type.clone()))?;
+        registered.schema.check_event(event)
+    }
+}
@@ -0,0 +1,366 @@
+//! CV tracking result publishing
+//!
+//! The CV pipeline produces tracking results far faster than the map can
+//! draw them. The publisher admits at most `max_rate_hz` results per drone
+//! (by frame timestamp), hands each admitted result to a callback (the
+//! tracker broadcasts it as a `CvTrackingUpdate` event) and persists them in
+//! batches. Persistence runs on its own task behind a bounded queue; when
+//! the database falls behind, whole batches are dropped and counted so
+//! broadcasting never waits on a write. The writer runs on the publisher's
+//! task, so stopping or restarting the publisher stops it too.
+
+use drone_core::{DroneId, SubsystemHealth, TrackingResult};
+use drone_db::DbClient;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// CV publisher configuration
+#[derive(Debug, Clone)]
+pub struct CvPublisherConfig {
+    /// Maximum results published per drone per second (0 = unthrottled)
+    pub max_rate_hz: f64,
+    /// Results per database batch
+    pub batch_size: usize,
+    /// Flush a partial batch after this long
+    pub flush_interval: Duration,
+    /// Results waiting for the publisher before `submit` rejects them
+    pub queue_capacity: usize,
+    /// Batches waiting for the database before new ones are dropped
+    pub pending_batches: usize,
+}
+
+impl Default for CvPublisherConfig {
+    fn default() -> Self {
+        Self {
+            max_rate_hz: 5.0,
+            batch_size: 200,
+            flush_interval: Duration::from_secs(1),
+            queue_capacity: 1024,
+            pending_batches: 4,
+        }
+    }
+}
+
+impl CvPublisherConfig {
+    /// Defaults overridden by `CV_PUBLISH_RATE_HZ` and `CV_PERSIST_BATCH_SIZE`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            max_rate_hz: env("CV_PUBLISH_RATE_HZ")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.max_rate_hz),
+            batch_size: env("CV_PERSIST_BATCH_SIZE")
+                .and_then(|s| s.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.batch_size),
+            ..defaults
+        }
+    }
+
+    fn min_interval(&self) -> chrono::Duration {
+        if self.max_rate_hz > 0.0 {
+            chrono::Duration::microseconds((1_000_000.0 / self.max_rate_hz) as i64)
+        } else {
+            chrono::Duration::zero()
+        }
+    }
+}
+
+/// Publisher counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CvPublisherStats {
+    /// Results taken from the queue
+    pub received: u64,
+    /// Results skipped by the per-drone rate limit
+    pub throttled: u64,
+    /// Results broadcast
+    pub published: u64,
+    /// Results written to the database
+    pub persisted: u64,
+    /// Results rejected because the publisher queue was full
+    pub dropped: u64,
+    /// Results lost because the database queue was full or a write failed
+    pub dropped_unpersisted: u64,
+    pub persist_errors: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    received: AtomicU64,
+    throttled: AtomicU64,
+    published: AtomicU64,
+    persisted: AtomicU64,
+    dropped: AtomicU64,
+    dropped_unpersisted: AtomicU64,
+    persist_errors: AtomicU64,
+}
+
+impl Counters {
+    fn add(counter: &AtomicU64, n: usize) {
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> CvPublisherStats {
+        let get = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        CvPublisherStats {
+            received: get(&self.received),
+            throttled: get(&self.throttled),
+            published: get(&self.published),
+            persisted: get(&self.persisted),
+            dropped: get(&self.dropped),
+            dropped_unpersisted: get(&self.dropped_unpersisted),
+            persist_errors: get(&self.persist_errors),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    counters: Counters,
+    /// Start time and failed batch writes
+    health: SubsystemHealth,
+    /// Latest published result per drone
+    latest: RwLock<HashMap<DroneId, TrackingResult>>,
+    /// Set once the queue is drained and the last batch written
+    finished: watch::Sender<bool>,
+}
+
+/// Handle for submitting CV results to the publisher
+#[derive(Debug, Clone)]
+pub struct CvPipeline {
+    tx: mpsc::Sender<TrackingResult>,
+    shared: Arc<Shared>,
+}
+
+impl CvPipeline {
+    /// Queue a result; `false` if the queue is full and it was dropped
+    pub fn submit(&self, result: TrackingResult) -> bool {
+        match self.tx.try_send(result) {
+            Ok(()) => true,
+            Err(_) => {
+                Counters::add(&self.shared.counters.dropped, 1);
+                false
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CvPublisherStats {
+        self.shared.counters.snapshot()
+    }
+
+    /// When the publisher started and how many batch writes failed
+    pub fn health(&self) -> &SubsystemHealth {
+        &self.shared.health
+    }
+
+    /// Latest published result per drone, by drone ID
+    pub fn latest(&self) -> Vec<TrackingResult> {
+        let mut results: Vec<_> = self.shared.latest.read().values().cloned().collect();
+        results.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+        results
+    }
+
+    /// Drop this handle and wait until the publisher has published and
+    /// written every queued result (once no other handle is left)
+    pub async fn close(self) {
+        let Self { tx, shared } = self;
+        let mut finished = shared.finished.subscribe();
+        drop(tx);
+        let _ = finished.wait_for(|finished| *finished).await;
+    }
+}
+
+/// Throttles, broadcasts and batches CV results
+pub struct CvPublisher {
+    config: CvPublisherConfig,
+    shared: Arc<Shared>,
+    last_frame: HashMap<DroneId, DateTime<Utc>>,
+    batch: Vec<TrackingResult>,
+    /// Queue of the database writer
+    writer: Option<mpsc::Sender<Vec<TrackingResult>>>,
+}
+
+impl CvPublisher {
+    /// Start the publisher (and a database writer if `db` is given)
+    ///
+    /// The returned task finishes once every `CvPipeline` clone is dropped
+    /// and the remaining batches are written.
+    pub fn spawn(
+        config: CvPublisherConfig,
+        db: Option<Arc<DbClient>>,
+        publish: impl Fn(TrackingResult) + Send + 'static,
+    ) -> (CvPipeline, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let shared = Arc::new(Shared::default());
+
+        let (batch_tx, writer) = match db {
+            Some(db) => {
+                let (batch_tx, batch_rx) = mpsc::channel(config.pending_batches.max(1));
+                (Some(batch_tx), Some(write_batches(db, batch_rx, shared.clone())))
+            }
+            None => (None, None),
+        };
+
+        let publisher = Self {
+            config,
+            shared: shared.clone(),
+            last_frame: HashMap::new(),
+            batch: Vec::new(),
+            writer: batch_tx,
+        };
+        let finished = shared.clone();
+        let handle = tokio::spawn(async move {
+            let write = async {
+                if let Some(writer) = writer {
+                    writer.await;
+                }
+            };
+            tokio::join!(publisher.run(rx, publish), write);
+            finished.finished.send_replace(true);
+        });
+        (CvPipeline { tx, shared }, handle)
+    }
+
+    async fn run(mut self, mut rx: mpsc::Receiver<TrackingResult>, publish: impl Fn(TrackingResult)) {
+        let mut flush = tokio::time::interval(self.config.flush_interval);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                result = rx.recv() => match result {
+                    Some(result) => {
+                        self.accept(result, &publish);
+                        if self.batch.len() >= self.config.batch_size {
+                            self.flush();
+                        }
+                    }
+                    None => break,
+                },
+                _ = flush.tick() => self.flush(),
+            }
+        }
+
+        // Closing the writer's queue lets it finish the pending batches
+        self.flush();
+        self.writer = None;
+    }
+
+    fn accept(&mut self, result: TrackingResult, publish: &impl Fn(TrackingResult)) {
+        let counters = &self.shared.counters;
+        Counters::add(&counters.received, 1);
+
+        let min_interval = self.config.min_interval();
+        let too_soon = self
+            .last_frame
+            .get(&result.drone_id)
+            .is_some_and(|last| result.frame_timestamp - *last < min_interval);
+        if too_soon {
+            Counters::add(&counters.throttled, 1);
+            return;
+        }
+
+        self.last_frame.insert(result.drone_id.clone(), result.frame_timestamp);
+        self.shared.latest.write().insert(result.drone_id.clone(), result.clone());
+        if self.writer.is_some() {
+            self.batch.push(result.clone());
+        }
+        publish(result);
+        Counters::add(&counters.published, 1);
+    }
+
+    fn flush(&mut self) {
+        let Some(batch_tx) = &self.writer else {
+            return;
+        };
+        if self.batch.is_empty() {
+            return;
+        }
+        let batch = std::mem::take(&mut self.batch);
+        if let Err(TrySendError::Full(batch) | TrySendError::Closed(batch)) = batch_tx.try_send(batch) {
+            warn!("CV persistence is behind; dropping {} tracking results", batch.len());
+            Counters::add(&self.shared.counters.dropped_unpersisted, batch.len());
+        }
+    }
+}
+
+async fn write_batches(db: Arc<DbClient>, mut rx: mpsc::Receiver<Vec<TrackingResult>>, shared: Arc<Shared>) {
+    let counters = &shared.counters;
+    while let Some(batch) = rx.recv().await {
+        match db.tracking().insert_batch(&batch).await {
+            Ok(()) => Counters::add(&counters.persisted, batch.len()),
+            Err(e) => {
+                warn!("Failed to persist {} tracking results: {}", batch.len(), e);
+                Counters::add(&counters.persist_errors, 1);
+                shared.health.record_error();
+                db.health().record_error();
+                Counters::add(&counters.dropped_unpersisted, batch.len());
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::BoundingBox;
+    use drone_db::{DbConfig, SqliteStore};
+    use parking_lot::Mutex;
+
+    fn result(drone: &str, millis: i64) -> TrackingResult {
+        let mut result = TrackingResult::new(DroneId::new(drone), 1, BoundingBox::new(100, 80, 40, 30));
+        result.frame_timestamp = DateTime::from_timestamp_millis(1_700_000_000_000 + millis).unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn test_throttles_publishes_and_persists() {
+        let db = Arc::new(DbClient::from_sqlite(SqliteStore::open_in_memory().unwrap(), DbConfig::default()));
+        let config = CvPublisherConfig {
+            max_rate_hz: 2.0,
+            batch_size: 2,
+            ..Default::default()
+        };
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let sink = published.clone();
+        let (pipeline, handle) = CvPublisher::spawn(config, Some(db), move |r| {
+            sink.lock().push((r.drone_id.to_string(), r.frame_timestamp.timestamp_subsec_millis()))
+        });
+
+        // REAPER-01 at 0, 100, 400 and 500 ms: only 0 and 500 are half a second apart
+        for (drone, millis) in [("REAPER-01", 0), ("REAPER-01", 100), ("REAPER-02", 100), ("REAPER-01", 400), ("REAPER-01", 500)] {
+            assert!(pipeline.submit(result(drone, millis)));
+        }
+
+        // Closing the last handle drains the queue and the pending batches
+        let shared = pipeline.shared.clone();
+        pipeline.close().await;
+        assert!(*shared.finished.borrow());
+        handle.await.unwrap();
+
+        assert_eq!(
+            *published.lock(),
+            vec![("REAPER-01".into(), 0), ("REAPER-02".into(), 100), ("REAPER-01".into(), 500)]
+        );
+        let latest = &shared.latest.read()[&DroneId::new("REAPER-01")];
+        assert_eq!(latest.frame_timestamp.timestamp_subsec_millis(), 500);
+
+        let stats = shared.counters.snapshot();
+        assert_eq!((stats.received, stats.throttled, stats.published), (5, 2, 3));
+        assert_eq!((stats.persisted, stats.dropped_unpersisted), (3, 0));
+    }
+}
@@ -0,0 +1,135 @@
+//! Live CV parameter tuning
+//!
+//! Operators adjust halo detection and track association parameters while
+//! the CV pipeline runs. The store holds the current values with a revision
+//! that increases on every change; an update can name the revision it was
+//! based on, so two operators editing at once cannot silently overwrite
+//! each other. Changes are saved before they take effect and reloaded at
+//! startup. The CV pipeline picks them up from the `CvConfigChanged` event
+//! (see `drone_cv::follow_tuning`).
+
+use drone_core::{CvTuning, CvTuningError};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Why a tuning update was refused
+#[derive(Debug, Error)]
+pub enum CvTuningUpdateError {
+    #[error(transparent)]
+    Invalid(#[from] CvTuningError),
+
+    #[error("CV parameters changed since revision {expected} (now at {current})")]
+    RevisionMismatch { expected: u64, current: u64 },
+
+    #[error("failed to save CV parameters: {0}")]
+    Storage(String),
+}
+
+/// CV parameters in effect
+#[derive(Debug, Clone, Serialize)]
+pub struct CvTuningRevision {
+    /// Starts at 0 and increases with every change
+    pub revision: u64,
+    pub updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub tuning: CvTuning,
+}
+
+/// Current CV parameters and their revision
+#[derive(Debug)]
+pub struct CvTuningStore {
+    current: RwLock<CvTuningRevision>,
+}
+
+impl CvTuningStore {
+    pub fn new(tuning: CvTuning) -> Self {
+        Self {
+            current: RwLock::new(CvTuningRevision {
+                revision: 0,
+                updated_at: Utc::now(),
+                tuning,
+            }),
+        }
+    }
+
+    pub fn current(&self) -> CvTuningRevision {
+        self.current.read().clone()
+    }
+
+    /// Validate and swap in new parameters, provided the store is still at
+    /// `expected_revision` (any revision when `None`)
+    pub fn update(
+        &self,
+        tuning: CvTuning,
+        expected_revision: Option<u64>,
+        now: DateTime<Utc>,
+    ) -> Result<CvTuningRevision, CvTuningUpdateError> {
+        let next = self.next(tuning, expected_revision, now)?;
+        self.commit(next.clone());
+        Ok(next)
+    }
+
+    /// The revision `update` would swap in, without swapping it in. Callers
+    /// that save it before committing must serialize their updates.
+    pub fn next(
+        &self,
+        tuning: CvTuning,
+        expected_revision: Option<u64>,
+        now: DateTime<Utc>,
+    ) -> Result<CvTuningRevision, CvTuningUpdateError> {
+        tuning.validate()?;
+        let current = self.current.read();
+        if let Some(expected) = expected_revision.filter(|r| *r != current.revision) {
+            return Err(CvTuningUpdateError::RevisionMismatch {
+                expected,
+                current: current.revision,
+            });
+        }
+        Ok(CvTuningRevision {
+            revision: current.revision + 1,
+            updated_at: now,
+            tuning,
+        })
+    }
+
+    /// Swap in a revision from `next` or from storage
+    pub fn commit(&self, revision: CvTuningRevision) {
+        *self.current.write() = revision;
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_validates_and_checks_revision() {
+        let store = CvTuningStore::new(CvTuning::default());
+        let mut tuning = store.current().tuning;
+        tuning.halo.param2 = 40.0;
+
+        let updated = store.update(tuning.clone(), Some(0), Utc::now()).unwrap();
+        assert_eq!(updated.revision, 1);
+        assert_eq!(store.current().tuning.halo.param2, 40.0);
+
+        // A second operator still editing revision 0 is refused
+        assert!(matches!(
+            store.update(tuning.clone(), Some(0), Utc::now()),
+            Err(CvTuningUpdateError::RevisionMismatch { expected: 0, current: 1 })
+        ));
+
+        tuning.tracking.max_tracks = 0;
+        assert!(matches!(
+            store.update(tuning, None, Utc::now()),
+            Err(CvTuningUpdateError::Invalid(_))
+        ));
+        assert_eq!(store.current().revision, 1);
+    }
+}
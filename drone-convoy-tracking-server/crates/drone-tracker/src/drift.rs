@@ -0,0 +1,390 @@
+//! CV calibration drift detection
+//!
+//! A shifted camera mount moves every CV geo-estimate the same way. Each
+//! time a fresh CV estimate is fused with a GPS fix, the pair is kept in a
+//! sliding window and a rigid transform mapping CV onto GPS is fitted by
+//! least squares: a rotation about the CV centroid plus an east/north
+//! offset. Drift beyond either threshold raises an alert; with
+//! `auto_correct` the fitted transform is folded into the calibration
+//! correction applied to every later CV estimate.
+
+use drone_core::GeoPosition;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Alert type raised when the CV calibration drifts
+pub const DRIFT_ALERT_TYPE: &str = "CV_CALIBRATION_DRIFT";
+
+/// Drift monitor configuration
+#[derive(Debug, Clone)]
+pub struct DriftConfig {
+    /// CV/GPS pairs kept for the estimate
+    pub window: usize,
+    /// Pairs needed before drift is estimated
+    pub min_samples: usize,
+    /// Systematic offset above this is drift (meters)
+    pub offset_threshold_m: f64,
+    /// Systematic rotation above this is drift (degrees)
+    pub rotation_threshold_deg: f64,
+    /// Rotation is only estimated when the pairs spread at least this far
+    /// (RMS distance from their centroid, meters); a tight cluster cannot
+    /// tell a rotation from an offset
+    pub min_spread_m: f64,
+    /// Pairs further apart are CV locking onto the wrong target, not drift
+    pub max_separation_m: f64,
+    /// Apply the fitted correction as soon as drift is detected
+    pub auto_correct: bool,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            window: 100,
+            min_samples: 20,
+            offset_threshold_m: 20.0,
+            rotation_threshold_deg: 1.0,
+            min_spread_m: 100.0,
+            max_separation_m: 500.0,
+            auto_correct: false,
+        }
+    }
+}
+
+impl DriftConfig {
+    /// Defaults overridden by `CV_DRIFT_OFFSET_M`, `CV_DRIFT_ROTATION_DEG`
+    /// and `CV_DRIFT_AUTO_CORRECT`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            offset_threshold_m: env("CV_DRIFT_OFFSET_M")
+                .and_then(|s| s.parse().ok())
+                .filter(|m: &f64| *m > 0.0)
+                .unwrap_or(defaults.offset_threshold_m),
+            rotation_threshold_deg: env("CV_DRIFT_ROTATION_DEG")
+                .and_then(|s| s.parse().ok())
+                .filter(|d: &f64| *d > 0.0)
+                .unwrap_or(defaults.rotation_threshold_deg),
+            auto_correct: env("CV_DRIFT_AUTO_CORRECT")
+                .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
+                .unwrap_or(defaults.auto_correct),
+            ..defaults
+        }
+    }
+}
+
+/// Systematic CV error fitted over the window
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftEstimate {
+    pub samples: usize,
+    /// Offset to add to CV estimates (meters)
+    pub east_m: f64,
+    pub north_m: f64,
+    pub offset_m: f64,
+    /// Clockwise rotation to apply to CV estimates about `pivot` (degrees)
+    pub rotation_deg: f64,
+    /// Centroid of the CV estimates
+    pub pivot: GeoPosition,
+    /// RMS CV/GPS distance left after the fitted transform (meters)
+    pub residual_m: f64,
+    /// Offset or rotation is above its threshold
+    pub exceeded: bool,
+    /// The estimate was applied as a correction
+    pub corrected: bool,
+    pub estimated_at: DateTime<Utc>,
+}
+
+impl DriftEstimate {
+    fn correction(&self) -> CalibrationCorrection {
+        CalibrationCorrection {
+            pivot: self.pivot,
+            rotation_deg: self.rotation_deg,
+            east_m: self.east_m,
+            north_m: self.north_m,
+            applied_at: self.estimated_at,
+        }
+    }
+}
+
+/// Rigid correction applied to CV geo-estimates
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CalibrationCorrection {
+    /// Point the rotation is about
+    pub pivot: GeoPosition,
+    /// Clockwise rotation (degrees)
+    pub rotation_deg: f64,
+    /// Offset applied after the rotation (meters)
+    pub east_m: f64,
+    pub north_m: f64,
+    pub applied_at: DateTime<Utc>,
+}
+
+impl CalibrationCorrection {
+    /// Corrected CV estimate (altitude unchanged)
+    pub fn apply(&self, position: &GeoPosition) -> GeoPosition {
+        let (east, north) = rotate(self.pivot.offset_to_m(position), self.rotation_deg);
+        let corrected = self.pivot.offset_by_m(east + self.east_m, north + self.north_m);
+        GeoPosition::new(corrected.latitude, corrected.longitude, position.altitude)
+    }
+
+    /// This correction followed by `next`, as one correction about the same pivot
+    fn then(&self, next: &CalibrationCorrection) -> CalibrationCorrection {
+        // next(q) = p2 + R2 (q - p2) + t2 with q = p1 + R1 (x - p1) + t1, so
+        // about p1: rotation R2 R1 and offset R2 (t1 - d) + d + t2, d = p2 - p1
+        let d = self.pivot.offset_to_m(&next.pivot);
+        let (east, north) = rotate((self.east_m - d.0, self.north_m - d.1), next.rotation_deg);
+        CalibrationCorrection {
+            pivot: self.pivot,
+            rotation_deg: self.rotation_deg + next.rotation_deg,
+            east_m: east + d.0 + next.east_m,
+            north_m: north + d.1 + next.north_m,
+            applied_at: next.applied_at,
+        }
+    }
+}
+
+/// Rotate an east/north vector clockwise by `degrees`
+fn rotate((east, north): (f64, f64), degrees: f64) -> (f64, f64) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    (east * cos + north * sin, north * cos - east * sin)
+}
+
+/// Current drift estimate and applied correction
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    /// Pairs in the window
+    pub samples: usize,
+    /// Drift is above a threshold and not corrected
+    pub drifting: bool,
+    pub auto_correct: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<DriftEstimate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correction: Option<CalibrationCorrection>,
+    /// Corrections applied so far
+    pub corrections: u64,
+}
+
+#[derive(Debug, Default)]
+struct DriftState {
+    /// (corrected CV estimate, GPS fix)
+    pairs: VecDeque<(GeoPosition, GeoPosition)>,
+    estimate: Option<DriftEstimate>,
+    correction: Option<CalibrationCorrection>,
+    drifting: bool,
+    corrections: u64,
+}
+
+impl DriftState {
+    fn apply(&mut self, estimate: &DriftEstimate) -> CalibrationCorrection {
+        let fitted = estimate.correction();
+        let correction = match &self.correction {
+            Some(current) => current.then(&fitted),
+            None => fitted,
+        };
+        self.correction = Some(correction);
+        self.corrections += 1;
+        // The window was measured against the old correction
+        self.pairs.clear();
+        self.drifting = false;
+        correction
+    }
+}
+
+/// Sliding-window CV calibration drift estimation
+#[derive(Debug, Default)]
+pub struct DriftMonitor {
+    config: DriftConfig,
+    state: RwLock<DriftState>,
+}
+
+impl DriftMonitor {
+    pub fn new(config: DriftConfig) -> Self {
+        Self {
+            config,
+            state: RwLock::new(DriftState::default()),
+        }
+    }
+
+    /// CV estimate with the current calibration correction applied
+    pub fn correct(&self, position: &GeoPosition) -> GeoPosition {
+        match &self.state.read().correction {
+            Some(correction) => correction.apply(position),
+            None => *position,
+        }
+    }
+
+    /// Add a fused (corrected) CV estimate and the GPS fix it was fused
+    /// with; returns the estimate when drift is first detected
+    pub fn record(&self, cv: GeoPosition, gps: GeoPosition, at: DateTime<Utc>) -> Option<DriftEstimate> {
+        if cv.distance_to(&gps) * 1000.0 > self.config.max_separation_m {
+            return None;
+        }
+
+        let mut state = self.state.write();
+        state.pairs.push_back((cv, gps));
+        while state.pairs.len() > self.config.window.max(1) {
+            state.pairs.pop_front();
+        }
+        if state.pairs.len() < self.config.min_samples.max(2) {
+            return None;
+        }
+
+        let mut estimate = self.fit(&state.pairs, at);
+        let started = estimate.exceeded && !state.drifting;
+        state.drifting = estimate.exceeded;
+        if started && self.config.auto_correct {
+            state.apply(&estimate);
+            estimate.corrected = true;
+        }
+        state.estimate = Some(estimate.clone());
+        started.then_some(estimate)
+    }
+
+    /// Apply the latest estimate as a correction
+    pub fn apply_estimate(&self) -> Option<CalibrationCorrection> {
+        let mut state = self.state.write();
+        let estimate = state.estimate.take()?;
+        let correction = state.apply(&estimate);
+        state.estimate = Some(DriftEstimate { corrected: true, ..estimate });
+        Some(correction)
+    }
+
+    /// Drop the calibration correction; `false` if there was none
+    pub fn clear_correction(&self) -> bool {
+        let mut state = self.state.write();
+        state.pairs.clear();
+        state.drifting = false;
+        state.correction.take().is_some()
+    }
+
+    pub fn report(&self) -> DriftReport {
+        let state = self.state.read();
+        DriftReport {
+            samples: state.pairs.len(),
+            drifting: state.drifting,
+            auto_correct: self.config.auto_correct,
+            estimate: state.estimate.clone(),
+            correction: state.correction,
+            corrections: state.corrections,
+        }
+    }
+
+    /// Least-squares rotation and offset mapping the CV estimates onto GPS
+    fn fit(&self, pairs: &VecDeque<(GeoPosition, GeoPosition)>, at: DateTime<Utc>) -> DriftEstimate {
+        let origin = pairs[0].0;
+        let local: Vec<((f64, f64), (f64, f64))> = pairs
+            .iter()
+            .map(|(cv, gps)| (origin.offset_to_m(cv), origin.offset_to_m(gps)))
+            .collect();
+        let n = local.len() as f64;
+        let sum = local.iter().fold([0.0; 4], |s, ((ce, cn), (ge, gn))| {
+            [s[0] + ce, s[1] + cn, s[2] + ge, s[3] + gn]
+        });
+        let cv_c = (sum[0] / n, sum[1] / n);
+        let gps_c = (sum[2] / n, sum[3] / n);
+
+        // Counterclockwise angle maximizing the alignment of the centered points
+        let (mut cross, mut dot, mut spread) = (0.0, 0.0, 0.0);
+        for ((ce, cn), (ge, gn)) in &local {
+            let (ax, ay) = (ce - cv_c.0, cn - cv_c.1);
+            let (bx, by) = (ge - gps_c.0, gn - gps_c.1);
+            cross += ax * by - ay * bx;
+            dot += ax * bx + ay * by;
+            spread += ax * ax + ay * ay;
+        }
+        let rotation_deg = if (spread / n).sqrt() >= self.config.min_spread_m {
+            -cross.atan2(dot).to_degrees()
+        } else {
+            0.0
+        };
+
+        let residual_sq: f64 = local
+            .iter()
+            .map(|((ce, cn), (ge, gn))| {
+                let (re, rn) = rotate((ce - cv_c.0, cn - cv_c.1), rotation_deg);
+                (ge - gps_c.0 - re).powi(2) + (gn - gps_c.1 - rn).powi(2)
+            })
+            .sum();
+
+        let (east_m, north_m) = (gps_c.0 - cv_c.0, gps_c.1 - cv_c.1);
+        let offset_m = east_m.hypot(north_m);
+        DriftEstimate {
+            samples: pairs.len(),
+            east_m,
+            north_m,
+            offset_m,
+            rotation_deg,
+            pivot: origin.offset_by_m(cv_c.0, cv_c.1),
+            residual_m: (residual_sq / n).sqrt(),
+            exceeded: offset_m > self.config.offset_threshold_m
+                || rotation_deg.abs() > self.config.rotation_threshold_deg,
+            corrected: false,
+            estimated_at: at,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_and_corrects_rotated_offset_camera() {
+        let config = DriftConfig {
+            auto_correct: true,
+            ..Default::default()
+        };
+        let monitor = DriftMonitor::new(config);
+        let camera = GeoPosition::new(34.5553, 69.2075, 0.0);
+        let now = Utc::now();
+
+        // Mount turned 3° and shifted 30 m east: CV sees the ground rotated
+        // counterclockwise and offset west of where the drones are
+        let shifted = CalibrationCorrection {
+            pivot: camera,
+            rotation_deg: -3.0,
+            east_m: -30.0,
+            north_m: 0.0,
+            applied_at: now,
+        };
+        let truth: Vec<GeoPosition> = (0..40)
+            .map(|i| {
+                let angle = i as f64 * 9.0_f64.to_radians();
+                camera.offset_by_m(800.0 * angle.cos(), 800.0 * angle.sin())
+            })
+            .collect();
+
+        let mut detected = None;
+        for gps in &truth {
+            let cv = monitor.correct(&shifted.apply(gps));
+            if let Some(estimate) = monitor.record(cv, *gps, now) {
+                detected = Some(estimate);
+            }
+        }
+        let estimate = detected.unwrap();
+        assert!(estimate.exceeded && estimate.corrected);
+        assert!((estimate.rotation_deg - 3.0).abs() < 0.01);
+        assert!(estimate.residual_m < 0.5);
+
+        // Later estimates land on the true positions
+        for gps in &truth {
+            let corrected = monitor.correct(&shifted.apply(gps));
+            assert!(corrected.distance_to(gps) * 1000.0 < 0.5);
+        }
+        let report = monitor.report();
+        assert_eq!(report.corrections, 1);
+        assert!(!report.drifting);
+
+        assert!(monitor.clear_correction());
+        let uncorrected = monitor.correct(&shifted.apply(&truth[0]));
+        assert!(uncorrected.distance_to(&truth[0]) * 1000.0 > 20.0);
+    }
+}
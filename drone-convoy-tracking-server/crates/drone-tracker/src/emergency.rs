@@ -0,0 +1,370 @@
+//! Emergency broadcast handling and convoy response
+
+use crate::convoy::{ConvoyManager, Formation};
+use crate::TrackedDrone;
+use drone_core::{Alert, AlertSeverity, AlertType, DroneId, DroneStatus, GeoPosition};
+use drone_p2p::protocol::{EmergencyData, EmergencyType};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Maximum number of responses kept in the log
+const MAX_RESPONSE_LOG: usize = 100;
+
+/// Convoy-wide emergency response policy
+#[derive(Debug, Clone)]
+pub struct EmergencyPolicy {
+    /// Emergency types that trigger a convoy-wide response
+    pub convoy_response_types: Vec<EmergencyType>,
+    /// Convoy speed factor while responding (None = keep speed)
+    pub slow_down_factor: Option<f64>,
+    /// Formation to switch to while responding (None = keep formation)
+    pub response_formation: Option<Formation>,
+    /// Designate the nearest healthy drone to loiter over the affected one
+    pub assign_buddy: bool,
+}
+
+impl Default for EmergencyPolicy {
+    fn default() -> Self {
+        Self {
+            convoy_response_types: vec![
+                EmergencyType::SystemFailure,
+                EmergencyType::LostConnection,
+                EmergencyType::HostileContact,
+                EmergencyType::CollisionWarning,
+            ],
+            slow_down_factor: Some(0.5),
+            response_formation: Some(Formation::Spread),
+            assign_buddy: true,
+        }
+    }
+}
+
+/// Action taken in response to an emergency
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ResponseAction {
+    /// Affected drone status changed
+    MarkDrone { drone_id: DroneId, status: DroneStatus },
+    /// Emergency alert raised
+    RaiseAlert { alert_id: uuid::Uuid },
+    /// Convoy speed reduced
+    SlowConvoy { factor: f64 },
+    /// Convoy formation changed
+    ChangeFormation { from: String, to: String },
+    /// Buddy drone designated to loiter over the affected drone
+    AssignBuddy { buddy_id: DroneId, loiter_at: GeoPosition },
+}
+
+/// Record of a handled emergency
+#[derive(Debug, Clone, Serialize)]
+pub struct EmergencyResponse {
+    pub drone_id: DroneId,
+    pub emergency_type: String,
+    pub message: String,
+    pub convoy_response: bool,
+    pub actions: Vec<ResponseAction>,
+    pub handled_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub alert: Option<Alert>,
+}
+
+/// State saved before a convoy-wide response so it can be restored
+#[derive(Debug, Clone, Copy)]
+struct SavedConvoyState {
+    formation: Formation,
+    speed_factor: f64,
+}
+
+/// Coordinates responses to emergency broadcasts
+pub struct EmergencyCoordinator {
+    policy: EmergencyPolicy,
+    convoy: Arc<ConvoyManager>,
+    /// Drones with an active emergency, and their designated buddy
+    active: RwLock<HashMap<DroneId, Option<DroneId>>>,
+    /// Convoy state before the first active convoy-wide response
+    saved: RwLock<Option<SavedConvoyState>>,
+    /// Response log (most recent last)
+    log: RwLock<VecDeque<EmergencyResponse>>,
+}
+
+impl EmergencyCoordinator {
+    /// Create a new coordinator acting on the given convoy
+    pub fn new(policy: EmergencyPolicy, convoy: Arc<ConvoyManager>) -> Self {
+        Self {
+            policy,
+            convoy,
+            active: RwLock::new(HashMap::new()),
+            saved: RwLock::new(None),
+            log: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Plan and apply the response to an emergency
+    ///
+    /// Convoy-level actions (speed, formation) are applied here; the returned
+    /// drone-level actions (status, alert) are applied by the tracker.
+    pub fn respond(&self, emergency: &EmergencyData, drones: &[TrackedDrone]) -> EmergencyResponse {
+        let drone_id = emergency.drone_id.clone();
+        let mut actions = Vec::new();
+
+        if let Some(status) = status_for(emergency.emergency_type) {
+            actions.push(ResponseAction::MarkDrone { drone_id: drone_id.clone(), status });
+        }
+
+        let alert = Alert::new(
+            AlertSeverity::Emergency,
+            alert_type_for(emergency.emergency_type),
+            format!("{:?} reported by {}: {}", emergency.emergency_type, drone_id, emergency.message),
+        )
+        .for_drone(drone_id.clone());
+        actions.push(ResponseAction::RaiseAlert { alert_id: alert.id });
+
+        let convoy_response = self
+            .policy
+            .convoy_response_types
+            .contains(&emergency.emergency_type);
+
+        let mut buddy = None;
+        if convoy_response {
+            actions.extend(self.apply_convoy_response());
+
+            if self.policy.assign_buddy {
+                buddy = nearest_healthy(&drone_id, &emergency.position, drones, &self.active.read());
+                if let Some(buddy_id) = &buddy {
+                    actions.push(ResponseAction::AssignBuddy {
+                        buddy_id: buddy_id.clone(),
+                        loiter_at: emergency.position,
+                    });
+                }
+            }
+        }
+
+        self.active.write().insert(drone_id.clone(), buddy);
+
+        let response = EmergencyResponse {
+            drone_id,
+            emergency_type: format!("{:?}", emergency.emergency_type),
+            message: emergency.message.clone(),
+            convoy_response,
+            actions,
+            handled_at: Utc::now(),
+            alert: Some(alert),
+        };
+
+        for action in &response.actions {
+            info!("Emergency response for {}: {:?}", response.drone_id, action);
+        }
+
+        let mut log = self.log.write();
+        log.push_back(response.clone());
+        if log.len() > MAX_RESPONSE_LOG {
+            log.pop_front();
+        }
+
+        response
+    }
+
+    /// Mark a drone's emergency as resolved, restoring the convoy once none remain
+    pub fn resolve(&self, drone_id: &DroneId) -> bool {
+        let removed = self.active.write().remove(drone_id).is_some();
+
+        if removed && self.active.read().is_empty() {
+            if let Some(saved) = self.saved.write().take() {
+                self.convoy.set_formation(saved.formation);
+                self.convoy.set_speed_factor(saved.speed_factor);
+                info!("All emergencies resolved; convoy restored to {:?}", saved.formation);
+            }
+        }
+
+        removed
+    }
+
+    /// Buddy drone designated for an affected drone
+    pub fn buddy_for(&self, drone_id: &DroneId) -> Option<DroneId> {
+        self.active.read().get(drone_id).cloned().flatten()
+    }
+
+    /// Drones with an unresolved emergency
+    pub fn active_emergencies(&self) -> Vec<DroneId> {
+        self.active.read().keys().cloned().collect()
+    }
+
+    /// Response log, oldest first
+    pub fn history(&self) -> Vec<EmergencyResponse> {
+        self.log.read().iter().cloned().collect()
+    }
+
+    fn apply_convoy_response(&self) -> Vec<ResponseAction> {
+        let mut actions = Vec::new();
+
+        {
+            let mut saved = self.saved.write();
+            if saved.is_none() {
+                *saved = Some(SavedConvoyState {
+                    formation: self.convoy.get_formation(),
+                    speed_factor: self.convoy.speed_factor(),
+                });
+            }
+        }
+
+        if let Some(factor) = self.policy.slow_down_factor {
+            let base = self.saved.read().map(|s| s.speed_factor).unwrap_or(1.0);
+            let target = base * factor;
+            if (self.convoy.speed_factor() - target).abs() > f64::EPSILON {
+                self.convoy.set_speed_factor(target);
+                actions.push(ResponseAction::SlowConvoy { factor: target });
+            }
+        }
+
+        if let Some(formation) = self.policy.response_formation {
+            let current = self.convoy.get_formation();
+            if current != formation {
+                self.convoy.set_formation(formation);
+                actions.push(ResponseAction::ChangeFormation {
+                    from: format!("{:?}", current),
+                    to: format!("{:?}", formation),
+                });
+            }
+        }
+
+        if actions.is_empty() {
+            warn!("Convoy already in emergency posture");
+        }
+
+        actions
+    }
+}
+
+/// Status the affected drone should be marked with
+fn status_for(emergency_type: EmergencyType) -> Option<DroneStatus> {
+    match emergency_type {
+        EmergencyType::LowBattery | EmergencyType::LowFuel | EmergencyType::SystemFailure => {
+            Some(DroneStatus::Rtb)
+        }
+        EmergencyType::LostConnection => Some(DroneStatus::Offline),
+        EmergencyType::HostileContact => Some(DroneStatus::Engaged),
+        EmergencyType::WeatherAlert | EmergencyType::CollisionWarning => None,
+    }
+}
+
+fn alert_type_for(emergency_type: EmergencyType) -> AlertType {
+    match emergency_type {
+        EmergencyType::LowBattery => AlertType::BatteryLow,
+        EmergencyType::LowFuel => AlertType::FuelLow,
+        EmergencyType::SystemFailure => AlertType::SystemFailure,
+        EmergencyType::LostConnection => AlertType::SignalLost,
+        EmergencyType::HostileContact => AlertType::Custom("HOSTILE_CONTACT".into()),
+        EmergencyType::WeatherAlert => AlertType::WeatherAlert,
+        EmergencyType::CollisionWarning => AlertType::CollisionWarning,
+    }
+}
+
+/// Nearest operational drone that is not itself in (or covering) an emergency
+fn nearest_healthy(
+    affected: &DroneId,
+    position: &GeoPosition,
+    drones: &[TrackedDrone],
+    active: &HashMap<DroneId, Option<DroneId>>,
+) -> Option<DroneId> {
+    let busy: Vec<&DroneId> = active.values().flatten().collect();
+
+    drones
+        .iter()
+        .map(|t| &t.drone)
+        .filter(|d| &d.id != affected && d.is_operational())
+        .filter(|d| !active.contains_key(&d.id) && !busy.contains(&&d.id))
+        .min_by(|a, b| {
+            a.position
+                .distance_to(position)
+                .total_cmp(&b.position.distance_to(position))
+        })
+        .map(|d| d.id.clone())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Drone;
+
+    fn tracked(id: &str, lat: f64) -> TrackedDrone {
+        let mut drone = Drone::new(DroneId::new(id), id);
+        drone.position = GeoPosition::new(lat, 69.2, 3000.0);
+        TrackedDrone::new(drone, Utc::now())
+    }
+
+    fn emergency(id: &str, emergency_type: EmergencyType) -> EmergencyData {
+        EmergencyData {
+            drone_id: DroneId::new(id),
+            emergency_type,
+            position: GeoPosition::new(34.50, 69.2, 3000.0),
+            message: "test".into(),
+        }
+    }
+
+    #[test]
+    fn test_system_failure_triggers_convoy_response() {
+        let convoy = Arc::new(ConvoyManager::new());
+        let coordinator = EmergencyCoordinator::new(EmergencyPolicy::default(), convoy.clone());
+        let drones = vec![
+            tracked("REAPER-01", 34.50),
+            tracked("REAPER-02", 34.51),
+            tracked("REAPER-03", 34.90),
+        ];
+
+        let response = coordinator.respond(&emergency("REAPER-01", EmergencyType::SystemFailure), &drones);
+
+        assert!(response.convoy_response);
+        assert!(response.actions.iter().any(|a| matches!(
+            a,
+            ResponseAction::MarkDrone { status: DroneStatus::Rtb, .. }
+        )));
+        assert_eq!(convoy.get_formation(), Formation::Spread);
+        assert_eq!(convoy.speed_factor(), 0.5);
+        assert_eq!(coordinator.buddy_for(&DroneId::new("REAPER-01")), Some(DroneId::new("REAPER-02")));
+        assert_eq!(response.alert.unwrap().severity, AlertSeverity::Emergency);
+
+        assert!(coordinator.resolve(&DroneId::new("REAPER-01")));
+        assert_eq!(convoy.get_formation(), Formation::Line);
+        assert_eq!(convoy.speed_factor(), 1.0);
+    }
+
+    #[test]
+    fn test_low_fuel_is_local_only() {
+        let convoy = Arc::new(ConvoyManager::new());
+        let coordinator = EmergencyCoordinator::new(EmergencyPolicy::default(), convoy.clone());
+
+        let response = coordinator.respond(&emergency("REAPER-01", EmergencyType::LowFuel), &[]);
+
+        assert!(!response.convoy_response);
+        assert_eq!(response.actions.len(), 2);
+        assert_eq!(convoy.get_formation(), Formation::Line);
+        assert_eq!(coordinator.history().len(), 1);
+    }
+
+    #[test]
+    fn test_buddy_not_reused() {
+        let convoy = Arc::new(ConvoyManager::new());
+        let coordinator = EmergencyCoordinator::new(EmergencyPolicy::default(), convoy);
+        let drones = vec![
+            tracked("REAPER-01", 34.50),
+            tracked("REAPER-02", 34.51),
+            tracked("REAPER-03", 34.52),
+            tracked("REAPER-04", 34.90),
+        ];
+
+        coordinator.respond(&emergency("REAPER-01", EmergencyType::HostileContact), &drones);
+        coordinator.respond(&emergency("REAPER-03", EmergencyType::SystemFailure), &drones);
+
+        assert_eq!(coordinator.buddy_for(&DroneId::new("REAPER-01")), Some(DroneId::new("REAPER-02")));
+        assert_eq!(coordinator.buddy_for(&DroneId::new("REAPER-03")), Some(DroneId::new("REAPER-04")));
+    }
+}
@@ -0,0 +1,380 @@
+//! Endurance projection
+//!
+//! Projects each drone's battery and fuel at mission completion from the
+//! route distance it still has to fly. Consumption per kilometre comes from
+//! the levels observed since the drone was last topped up, or from the
+//! model rates until it has flown far enough for that to be meaningful.
+//! Rates are per still-air kilometre: flown distance is weighted by the
+//! wind factor at the time, and the route ahead by the estimated wind
+//! along each leg (see [`crate::wind`]).
+
+use crate::wind::WindVector;
+use drone_core::{DroneId, GeoPosition, Mission, Telemetry};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Alert type raised when a drone is projected to finish below its reserve
+pub const ENDURANCE_ALERT_TYPE: &str = "ENDURANCE_RESERVE";
+
+/// Endurance projection configuration
+#[derive(Debug, Clone)]
+pub struct EnduranceConfig {
+    /// Raise reserve alerts
+    pub enabled: bool,
+    /// Battery used per km until consumption has been observed (percent)
+    pub battery_pct_per_km: f64,
+    /// Fuel used per km until consumption has been observed (percent)
+    pub fuel_pct_per_km: f64,
+    /// Ground distance flown before observed consumption replaces the model rates
+    pub min_observed_km: f64,
+    /// Warn when battery or fuel at completion is projected below this (percent)
+    pub reserve_percent: f64,
+    /// Projections must climb this far above the reserve before re-arming
+    pub hysteresis_percent: f64,
+}
+
+impl Default for EnduranceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            battery_pct_per_km: 0.02,
+            fuel_pct_per_km: 0.015,
+            min_observed_km: 5.0,
+            reserve_percent: 20.0,
+            hysteresis_percent: 2.0,
+        }
+    }
+}
+
+/// Where a consumption rate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsumptionSource {
+    Model,
+    Observed,
+}
+
+/// Projected battery and fuel at mission completion
+#[derive(Debug, Clone, Serialize)]
+pub struct EnduranceProjection {
+    /// Route distance left, from the current position through the last waypoint
+    pub remaining_km: f64,
+    /// Still-air km per ground km over the rest of the route (1 in still air)
+    pub wind_factor: f64,
+    pub battery_pct_per_km: f64,
+    pub fuel_pct_per_km: f64,
+    pub source: ConsumptionSource,
+    pub battery_at_completion: f64,
+    pub fuel_at_completion: f64,
+    /// Either level is projected below the reserve margin
+    pub below_reserve: bool,
+}
+
+/// Levels and distance flown since a drone was last topped up
+#[derive(Debug, Clone)]
+struct Observation {
+    battery_start: u8,
+    fuel_start: u8,
+    last_position: GeoPosition,
+    distance_km: f64,
+    /// `distance_km` weighted by the wind factor of each step
+    air_km: f64,
+}
+
+/// Per-drone consumption tracking and reserve alert state
+#[derive(Debug)]
+pub struct EnduranceProjector {
+    config: EnduranceConfig,
+    observations: RwLock<HashMap<DroneId, Observation>>,
+    /// Drones with an outstanding reserve alert
+    warned: RwLock<HashSet<DroneId>>,
+}
+
+impl EnduranceProjector {
+    pub fn new(config: EnduranceConfig) -> Self {
+        Self {
+            config,
+            observations: RwLock::new(HashMap::new()),
+            warned: RwLock::new(HashSet::new()),
+        }
+    }
+
+    pub fn config(&self) -> &EnduranceConfig {
+        &self.config
+    }
+
+    /// Record a position update flown in `wind`, weighted by the still-air
+    /// km per ground km on the course of the step since the last update; a
+    /// rising level (recharge, refuel) restarts the observation
+    pub fn observe(&self, drone_id: &DroneId, position: GeoPosition, telemetry: &Telemetry, wind: Option<WindVector>) {
+        let mut observations = self.observations.write();
+        let fresh = Observation {
+            battery_start: telemetry.battery_level,
+            fuel_start: telemetry.fuel_level,
+            last_position: position,
+            distance_km: 0.0,
+            air_km: 0.0,
+        };
+        match observations.get_mut(drone_id) {
+            Some(obs)
+                if telemetry.battery_level <= obs.battery_start
+                    && telemetry.fuel_level <= obs.fuel_start =>
+            {
+                let step_km = obs.last_position.distance_to(&position);
+                let course = obs.last_position.bearing_to(&position);
+                let wind_factor = wind.map_or(1.0, |wind| wind.air_distance_factor(telemetry.speed, course));
+                obs.distance_km += step_km;
+                obs.air_km += step_km * wind_factor;
+                obs.last_position = position;
+            }
+            _ => {
+                observations.insert(drone_id.clone(), fresh);
+            }
+        }
+    }
+
+    /// Project levels at completion after flying `remaining_km` at
+    /// `wind_factor` still-air km per ground km
+    pub fn project(
+        &self,
+        drone_id: &DroneId,
+        telemetry: &Telemetry,
+        remaining_km: f64,
+        wind_factor: f64,
+    ) -> EnduranceProjection {
+        let observed = self
+            .observations
+            .read()
+            .get(drone_id)
+            .filter(|obs| obs.distance_km >= self.config.min_observed_km)
+            .map(|obs| {
+                let rate = |start: u8, now: u8| start.saturating_sub(now) as f64 / obs.air_km;
+                (rate(obs.battery_start, telemetry.battery_level), rate(obs.fuel_start, telemetry.fuel_level))
+            });
+        let (battery_rate, fuel_rate, source) = match observed {
+            Some((battery, fuel)) => (battery, fuel, ConsumptionSource::Observed),
+            None => (self.config.battery_pct_per_km, self.config.fuel_pct_per_km, ConsumptionSource::Model),
+        };
+
+        let air_km = remaining_km * wind_factor;
+        let at_completion = |level: u8, rate: f64| (level as f64 - rate * air_km).max(0.0);
+        let battery = at_completion(telemetry.battery_level, battery_rate);
+        let fuel = at_completion(telemetry.fuel_level, fuel_rate);
+        EnduranceProjection {
+            remaining_km,
+            wind_factor,
+            battery_pct_per_km: battery_rate,
+            fuel_pct_per_km: fuel_rate,
+            source,
+            battery_at_completion: battery,
+            fuel_at_completion: fuel,
+            below_reserve: battery.min(fuel) < self.config.reserve_percent,
+        }
+    }
+
+    /// Whether a projection should raise a reserve alert: only when a drone
+    /// first drops below the reserve, re-arming once it is clearly above again
+    pub fn should_warn(&self, drone_id: &DroneId, projection: &EnduranceProjection) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let mut warned = self.warned.write();
+        if projection.below_reserve {
+            return warned.insert(drone_id.clone());
+        }
+        let lowest = projection.battery_at_completion.min(projection.fuel_at_completion);
+        if lowest >= self.config.reserve_percent + self.config.hysteresis_percent {
+            warned.remove(drone_id);
+        }
+        false
+    }
+
+    /// Drop a drone's consumption history and reserve warning
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.observations.write().remove(drone_id);
+        self.warned.write().remove(drone_id);
+    }
+
+    /// Drones with consumption history
+    pub fn drone_count(&self) -> usize {
+        self.observations.read().len()
+    }
+}
+
+/// Legs left for a drone heading to the waypoint at `waypoint_index`, as
+/// start, end and length (km)
+fn remaining_legs(
+    mission: &Mission,
+    position: &GeoPosition,
+    waypoint_index: usize,
+) -> Vec<(GeoPosition, GeoPosition, f64)> {
+    let Some(next) = mission.waypoints.get(waypoint_index) else {
+        return Vec::new();
+    };
+    let mut legs = vec![(*position, next.position, position.distance_to(&next.position))];
+    legs.extend((waypoint_index + 1..mission.waypoints.len()).map(|i| {
+        let (from, to) = (mission.waypoints[i - 1].position, mission.waypoints[i].position);
+        let km = match mission.leg_to(i) {
+            Some(leg) => leg.distance_km,
+            None => from.distance_to(&to),
+        };
+        (from, to, km)
+    }));
+    legs
+}
+
+/// Route distance left for a drone heading to the waypoint at `waypoint_index`
+pub fn remaining_route_km(mission: &Mission, position: &GeoPosition, waypoint_index: usize) -> f64 {
+    remaining_legs(mission, position, waypoint_index)
+        .iter()
+        .map(|(_, _, km)| km)
+        .sum()
+}
+
+/// Still-air km per ground km over the rest of the route at `airspeed_kmh`,
+/// with `wind` giving the estimate at each leg's start; 1 where unknown
+pub fn route_wind_factor(
+    mission: &Mission,
+    position: &GeoPosition,
+    waypoint_index: usize,
+    airspeed_kmh: f64,
+    wind: impl Fn(&GeoPosition) -> Option<WindVector>,
+) -> f64 {
+    let legs = remaining_legs(mission, position, waypoint_index);
+    let ground_km: f64 = legs.iter().map(|(_, _, km)| km).sum();
+    if ground_km <= 0.0 {
+        return 1.0;
+    }
+    let air_km: f64 = legs
+        .iter()
+        .map(|(from, to, km)| {
+            let factor = wind(from).map_or(1.0, |wind| wind.air_distance_factor(airspeed_kmh, from.bearing_to(to)));
+            km * factor
+        })
+        .sum();
+    air_km / ground_km
+}
+
+/// Time (seconds) to fly the rest of the route at `airspeed_kmh` through
+/// `wind`; `None` without airspeed or where the wind stops the drone
+pub fn remaining_route_secs(
+    mission: &Mission,
+    position: &GeoPosition,
+    waypoint_index: usize,
+    airspeed_kmh: f64,
+    wind: impl Fn(&GeoPosition) -> Option<WindVector>,
+) -> Option<f64> {
+    let airspeed_ms = airspeed_kmh / 3.6;
+    if airspeed_ms <= 0.0 {
+        return None;
+    }
+    remaining_legs(mission, position, waypoint_index)
+        .iter()
+        .map(|(from, to, km)| {
+            let ground_ms = match wind(from) {
+                Some(wind) => wind.ground_speed_ms(airspeed_ms, from.bearing_to(to))?,
+                None => airspeed_ms,
+            };
+            Some(km * 1000.0 / ground_ms)
+        })
+        .sum()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Waypoint;
+
+    #[test]
+    fn test_projection_from_model_then_observed_consumption() {
+        let projector = EnduranceProjector::new(EnduranceConfig::default());
+        let id = DroneId::new("REAPER-01");
+        let telemetry = |battery: u8, fuel: u8| Telemetry {
+            battery_level: battery,
+            fuel_level: fuel,
+            ..Default::default()
+        };
+
+        // Three waypoints 0.1° of latitude (about 11.1 km) apart
+        let mut mission = Mission::new("Endurance");
+        for i in 0..3 {
+            mission.add_waypoint(Waypoint::new(format!("WP-{}", i), "Waypoint", 34.0 + 0.1 * i as f64, 69.0));
+        }
+        let start = GeoPosition::new(34.0, 69.0, 3000.0);
+        let remaining = remaining_route_km(&mission, &start, 1);
+        assert!((remaining - 22.24).abs() < 0.1, "{}", remaining);
+        assert_eq!(remaining_route_km(&mission, &start, 3), 0.0);
+
+        // Nothing observed yet: the model rates apply
+        projector.observe(&id, start, &telemetry(90, 80), None);
+        let projection = projector.project(&id, &telemetry(90, 80), remaining, 1.0);
+        assert_eq!(projection.source, ConsumptionSource::Model);
+        assert!(!projection.below_reserve);
+        assert!(!projector.should_warn(&id, &projection));
+
+        // Halfway the observed rates take over: the last leg costs as much
+        // as the first
+        let halfway = GeoPosition::new(34.1, 69.0, 3000.0);
+        projector.observe(&id, halfway, &telemetry(80, 55), None);
+        let projection = projector.project(&id, &telemetry(80, 55), remaining_route_km(&mission, &halfway, 2), 1.0);
+        assert_eq!(projection.source, ConsumptionSource::Observed);
+        assert!((projection.battery_at_completion - 70.0).abs() < 0.1, "{:?}", projection);
+        assert!((projection.fuel_at_completion - 30.0).abs() < 0.1, "{:?}", projection);
+        assert!(!projection.below_reserve);
+
+        // Burning fuel faster runs it dry before the end; warn once per crossing
+        let low = projector.project(&id, &telemetry(80, 40), 12.0, 1.0);
+        assert_eq!(low.fuel_at_completion, 0.0);
+        assert!(low.below_reserve);
+        assert!(projector.should_warn(&id, &low));
+        assert!(!projector.should_warn(&id, &low));
+
+        // Refuelling restarts observation and re-arms the alert
+        projector.observe(&id, halfway, &telemetry(80, 100), None);
+        let refuelled = projector.project(&id, &telemetry(80, 100), 11.1, 1.0);
+        assert_eq!(refuelled.source, ConsumptionSource::Model);
+        assert!(!projector.should_warn(&id, &refuelled));
+        assert!(projector.should_warn(&id, &low));
+    }
+
+    #[test]
+    fn test_headwind_costs_endurance_and_time() {
+        // Two legs due north, about 11.1 km each
+        let mut mission = Mission::new("Wind");
+        for i in 0..3 {
+            mission.add_waypoint(Waypoint::new(format!("WP-{}", i), "Waypoint", 34.0 + 0.1 * i as f64, 69.0));
+        }
+        let start = GeoPosition::new(34.0, 69.0, 3000.0);
+        let remaining = remaining_route_km(&mission, &start, 1);
+
+        // 10 m/s from the north against 180 km/h (50 m/s) of airspeed
+        let headwind = |_: &GeoPosition| Some(WindVector::new(0.0, -10.0));
+        let calm = |_: &GeoPosition| None;
+        assert!((route_wind_factor(&mission, &start, 1, 180.0, headwind) - 1.25).abs() < 1e-6);
+        assert_eq!(route_wind_factor(&mission, &start, 1, 180.0, calm), 1.0);
+        let still = remaining_route_secs(&mission, &start, 1, 180.0, calm).unwrap();
+        let windy = remaining_route_secs(&mission, &start, 1, 180.0, headwind).unwrap();
+        assert!((still - remaining * 20.0).abs() < 1e-6);
+        assert!((windy - remaining * 25.0).abs() < 1e-6);
+        assert_eq!(remaining_route_secs(&mission, &start, 1, 0.0, calm), None);
+
+        // Rates observed in a tailwind apply per still-air km
+        let projector = EnduranceProjector::new(EnduranceConfig::default());
+        let id = DroneId::new("REAPER-01");
+        let telemetry = |fuel: u8| Telemetry { fuel_level: fuel, speed: 180.0, ..Default::default() };
+        // 12.5 m/s from the south: 0.8 still-air km per ground km flying north
+        let tailwind = Some(WindVector::new(0.0, 12.5));
+        projector.observe(&id, start, &telemetry(90), tailwind);
+        projector.observe(&id, GeoPosition::new(34.1, 69.0, 3000.0), &telemetry(80), tailwind);
+        let projection = projector.project(&id, &telemetry(80), 11.1, 1.25);
+        assert_eq!(projection.wind_factor, 1.25);
+        // 10% over ~8.9 air km, then ~13.9 air km into the wind
+        assert!((projection.fuel_at_completion - 64.4).abs() < 0.2, "{:?}", projection);
+    }
+}
@@ -1,16 +1,17 @@
 //! Tracking engine core logic
 
-use crate::{TrackedDrone, TrackerConfig};
+use crate::TrackerConfig;
 use drone_core::{DroneId, Event, GeoPosition, Telemetry};
 
 use parking_lot::RwLock;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
-use tracing::{debug, info};
+use tracing::debug;
 
 /// Tracking engine that processes updates
 pub struct TrackingEngine {
+    #[allow(dead_code)]
     config: TrackerConfig,
     /// Last update timestamp per drone
     last_updates: Arc<RwLock<std::collections::HashMap<DroneId, Instant>>>,
@@ -45,8 +46,8 @@ impl TrackingEngine {
     pub fn process_update(
         &self,
         drone_id: &DroneId,
-        position: GeoPosition,
-        telemetry: Telemetry,
+        _position: GeoPosition,
+        _telemetry: Telemetry,
     ) {
         // Record update time
         self.last_updates.write().insert(drone_id.clone(), Instant::now());
@@ -91,7 +92,7 @@ impl TrackingEngine {
     }
 
     /// Get update rate for a drone (updates per second)
-    pub fn get_update_rate(&self, drone_id: &DroneId) -> Option<f64> {
+    pub fn get_update_rate(&self, _drone_id: &DroneId) -> Option<f64> {
         // Simplified - in real implementation would track update frequency
         Some(10.0) // Assume 10 Hz
     }
@@ -1,33 +1,83 @@
 //! Event bus for system-wide event distribution
+//!
+//! Published events are kept in a history for replay: at most
+//! `max_events`, none older than `max_age` before the newest timestamp
+//! published so far. The history is in publish order, which is not always
+//! timestamp order (events published late), so both eviction and time range
+//! queries scan it rather than search it.
 
-use drone_core::Event;
+use chrono::{DateTime, Utc};
+use drone_core::{Event, EventType};
 
 use parking_lot::RwLock;
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc};
+use std::time::Duration;
+use tokio::sync::broadcast;
 use tracing::debug;
+use uuid::Uuid;
+
+/// How much event history the bus keeps
+#[derive(Debug, Clone)]
+pub struct EventRetention {
+    /// Events older than this (relative to the newest) are dropped
+    pub max_age: Duration,
+    /// Cap on the number of events kept, whatever their age
+    pub max_events: usize,
+}
+
+impl Default for EventRetention {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(15 * 60),
+            max_events: 1000,
+        }
+    }
+}
+
+impl EventRetention {
+    /// Defaults overridden by `EVENT_HISTORY_SECS` and `EVENT_HISTORY_MAX`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|s| s.parse::<u64>().ok()).filter(|n| *n > 0);
+        Self {
+            max_age: env("EVENT_HISTORY_SECS").map(Duration::from_secs).unwrap_or(defaults.max_age),
+            max_events: env("EVENT_HISTORY_MAX").map(|n| n as usize).unwrap_or(defaults.max_events),
+        }
+    }
+}
 
 /// Event bus for distributing events across the system
 pub struct EventBus {
     /// Broadcast sender for events
     sender: broadcast::Sender<Event>,
-    /// Event history (last N events)
-    history: Arc<RwLock<Vec<Event>>>,
-    /// Maximum history size
-    max_history: usize,
+    /// Retained events, in publish order
+    history: Arc<RwLock<VecDeque<Event>>>,
+    /// Newest event timestamp published so far; retention ages against it
+    newest: Arc<RwLock<Option<DateTime<Utc>>>>,
+    retention: EventRetention,
+    /// Events dropped from the history so far
+    evicted: Arc<RwLock<u64>>,
     /// Event counter
     event_count: Arc<RwLock<u64>>,
 }
 
 impl EventBus {
-    /// Create a new event bus
+    /// Create a new event bus with the default retention
     pub fn new(capacity: usize) -> Self {
+        Self::with_retention(capacity, EventRetention::default())
+    }
+
+    /// Create an event bus keeping history per `retention`
+    pub fn with_retention(capacity: usize, retention: EventRetention) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        
+
         Self {
             sender,
-            history: Arc::new(RwLock::new(Vec::with_capacity(1000))),
-            max_history: 1000,
+            history: Arc::new(RwLock::new(VecDeque::with_capacity(retention.max_events.min(1024)))),
+            newest: Arc::new(RwLock::new(None)),
+            retention,
+            evicted: Arc::new(RwLock::new(0)),
             event_count: Arc::new(RwLock::new(0)),
         }
     }
@@ -42,9 +92,24 @@ impl EventBus {
         // Add to history
         {
             let mut history = self.history.write();
-            history.push(event.clone());
-            if history.len() > self.max_history {
-                history.remove(0);
+            history.push_back(event.clone());
+
+            // A late event neither moves the cutoff back nor hides older
+            // events behind newer ones at the front
+            let newest = {
+                let mut newest = self.newest.write();
+                let latest = newest.map_or(event.timestamp, |t| t.max(event.timestamp));
+                *newest = Some(latest);
+                latest
+            };
+            let cutoff = newest - chrono::Duration::from_std(self.retention.max_age).unwrap_or(chrono::Duration::MAX);
+            let before = history.len();
+            history.retain(|e| e.timestamp >= cutoff);
+            let excess = history.len().saturating_sub(self.retention.max_events);
+            history.drain(..excess);
+            let evicted = (before - history.len()) as u64;
+            if evicted > 0 {
+                *self.evicted.write() += evicted;
             }
         }
 
@@ -68,7 +133,46 @@ impl EventBus {
     pub fn get_recent(&self, count: usize) -> Vec<Event> {
         let history = self.history.read();
         let start = history.len().saturating_sub(count);
-        history[start..].to_vec()
+        history.range(start..).cloned().collect()
+    }
+
+    /// Events with `from <= timestamp < to`, in publish order
+    pub fn events_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<Event> {
+        let history = self.history.read();
+        history
+            .iter()
+            .filter(|e| e.timestamp >= from && e.timestamp < to)
+            .cloned()
+            .collect()
+    }
+
+    /// Events of the given types with `from <= timestamp < to`, in publish order
+    pub fn events_of_type(&self, types: &HashSet<EventType>, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<Event> {
+        let history = self.history.read();
+        history
+            .iter()
+            .filter(|e| e.timestamp >= from && e.timestamp < to && types.contains(&e.event_type))
+            .cloned()
+            .collect()
+    }
+
+    /// Timestamp of the oldest retained event
+    pub fn oldest_retained(&self) -> Option<DateTime<Utc>> {
+        self.history.read().iter().map(|e| e.timestamp).min()
+    }
+
+    /// Events dropped from the history by retention
+    pub fn evicted_count(&self) -> u64 {
+        *self.evicted.read()
+    }
+
+    /// Events published after the one with `id`, oldest first
+    ///
+    /// Returns `None` if `id` is no longer (or never was) in the history.
+    pub fn events_after(&self, id: Uuid) -> Option<Vec<Event>> {
+        let history = self.history.read();
+        let position = history.iter().rposition(|e| e.id == id)?;
+        Some(history.range(position + 1..).cloned().collect())
     }
 
     /// Get event count
@@ -79,6 +183,7 @@ impl EventBus {
     /// Clear history
     pub fn clear_history(&self) {
         self.history.write().clear();
+        *self.newest.write() = None;
     }
 
     /// Get subscriber count (approximate)
@@ -98,7 +203,9 @@ impl Clone for EventBus {
         Self {
             sender: self.sender.clone(),
             history: self.history.clone(),
-            max_history: self.max_history,
+            newest: self.newest.clone(),
+            retention: self.retention.clone(),
+            evicted: self.evicted.clone(),
             event_count: self.event_count.clone(),
         }
     }
@@ -145,6 +252,97 @@ mod tests {
         
         let recent = bus.get_recent(3);
         assert_eq!(recent.len(), 3);
+
+        let after = bus.events_after(recent[0].id).unwrap();
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[0].id, recent[1].id);
+        assert!(bus.events_after(Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn test_retention_by_age_and_count() {
+        let bus = EventBus::with_retention(
+            100,
+            EventRetention {
+                max_age: Duration::from_secs(60),
+                max_events: 4,
+            },
+        );
+        let t0 = Utc::now();
+        let publish = |secs: i64, event_type: EventType| {
+            let mut event = Event::drone_status_changed(DroneId::new("REAPER-01"), DroneStatus::Standby, DroneStatus::Moving);
+            event.event_type = event_type;
+            event.timestamp = t0 + chrono::Duration::seconds(secs);
+            bus.publish(event);
+        };
+
+        publish(0, EventType::DroneStatusChanged);
+        publish(10, EventType::AlertRaised);
+        publish(20, EventType::DroneStatusChanged);
+        assert_eq!(bus.events_between(t0, t0 + chrono::Duration::seconds(20)).len(), 2);
+        let alerts = HashSet::from([EventType::AlertRaised]);
+        assert_eq!(bus.events_of_type(&alerts, t0, DateTime::<Utc>::MAX_UTC).len(), 1);
+
+        // 65 s after the first event it is too old
+        publish(65, EventType::DroneStatusChanged);
+        assert_eq!(bus.oldest_retained(), Some(t0 + chrono::Duration::seconds(10)));
+        assert_eq!(bus.evicted_count(), 1);
+
+        // The count cap drops the oldest whatever its age
+        publish(66, EventType::DroneStatusChanged);
+        publish(67, EventType::DroneStatusChanged);
+        assert_eq!(bus.get_recent(10).len(), 4);
+        assert_eq!(bus.oldest_retained(), Some(t0 + chrono::Duration::seconds(20)));
+        assert!(bus.events_of_type(&alerts, t0, DateTime::<Utc>::MAX_UTC).is_empty());
+    }
+
+    #[test]
+    fn test_time_queries_do_not_assume_publish_order() {
+        let bus = EventBus::new(100);
+        let t0 = Utc::now();
+        for (secs, event_type) in [(30, EventType::AlertRaised), (10, EventType::AlertRaised), (20, EventType::DroneStatusChanged)] {
+            let mut event = Event::drone_status_changed(DroneId::new("REAPER-01"), DroneStatus::Standby, DroneStatus::Moving);
+            event.event_type = event_type;
+            event.timestamp = t0 + chrono::Duration::seconds(secs);
+            bus.publish(event);
+        }
+
+        // The event stamped 10 s was published after the one stamped 30 s
+        let since = bus.events_between(t0 + chrono::Duration::seconds(5), DateTime::<Utc>::MAX_UTC);
+        assert_eq!(since.len(), 3);
+        assert_eq!(bus.events_between(t0, t0 + chrono::Duration::seconds(15)).len(), 1);
+        let alerts = HashSet::from([EventType::AlertRaised]);
+        assert_eq!(bus.events_of_type(&alerts, t0 + chrono::Duration::seconds(5), t0 + chrono::Duration::seconds(25)).len(), 1);
+        assert_eq!(bus.oldest_retained(), Some(t0 + chrono::Duration::seconds(10)));
+    }
+
+    #[test]
+    fn test_retention_ages_against_newest_published() {
+        let bus = EventBus::with_retention(
+            100,
+            EventRetention {
+                max_age: Duration::from_secs(60),
+                max_events: 100,
+            },
+        );
+        let t0 = Utc::now();
+        let publish = |secs: i64| {
+            let mut event = Event::drone_status_changed(DroneId::new("REAPER-01"), DroneStatus::Standby, DroneStatus::Moving);
+            event.timestamp = t0 + chrono::Duration::seconds(secs);
+            bus.publish(event);
+        };
+
+        // The late event stamped 10 s sits behind the one stamped 50 s
+        publish(50);
+        publish(10);
+        publish(100);
+        assert_eq!(bus.oldest_retained(), Some(t0 + chrono::Duration::seconds(50)));
+        assert_eq!(bus.evicted_count(), 1);
+
+        // A late event does not move the cutoff back
+        publish(20);
+        assert_eq!(bus.get_recent(10).len(), 2);
+        assert_eq!(bus.evicted_count(), 2);
     }
 
     #[tokio::test]
@@ -0,0 +1,170 @@
+//! Stale track eviction and memory bounds
+//!
+//! Drones that churn through the fleet would otherwise stay in the
+//! tracker's maps for good. A periodic sweep drops drones that have been
+//! silent longer than `offline_after` and, above `max_drones`, the ones
+//! silent longest; each drone's final state is written to
+//! `drone_snapshots` first. Active alert lists are capped per drone, with
+//! the oldest alerts dropped first.
+
+use chrono::{DateTime, Utc};
+use drone_core::{Alert, Drone, DroneId, GeoPosition};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Eviction and memory bound configuration
+#[derive(Debug, Clone)]
+pub struct EvictionConfig {
+    /// Drones without an update for this long are dropped (`None` keeps them)
+    pub offline_after: Option<Duration>,
+    /// Most drones tracked at once (`None` for no cap)
+    pub max_drones: Option<usize>,
+    /// Active alerts kept per drone
+    pub max_alerts_per_drone: usize,
+    /// How often the sweep runs
+    pub sweep_interval: Duration,
+}
+
+impl Default for EvictionConfig {
+    fn default() -> Self {
+        Self {
+            offline_after: Some(Duration::from_secs(24 * 3600)),
+            max_drones: Some(10_000),
+            max_alerts_per_drone: 100,
+            sweep_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Why a drone was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionReason {
+    /// Silent for longer than `offline_after`
+    Offline,
+    /// Silent longest while the tracker was over `max_drones`
+    Capacity,
+}
+
+impl EvictionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Offline => "offline",
+            Self::Capacity => "capacity",
+        }
+    }
+}
+
+/// Final state of an evicted drone, stored as JSON in `drone_snapshots`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroneSnapshot {
+    pub drone: Drone,
+    pub waypoint_index: usize,
+    pub waypoint_progress: f64,
+    pub last_update: DateTime<Utc>,
+    pub position_history: Vec<(DateTime<Utc>, GeoPosition)>,
+    pub active_alerts: Vec<Alert>,
+    pub reason: EvictionReason,
+}
+
+/// Entry counts of the tracker's per-drone state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TrackerFootprint {
+    pub drones: usize,
+    pub position_history: usize,
+    pub active_alerts: usize,
+    pub motion: usize,
+    pub endurance: usize,
+    pub fusion: usize,
+    pub sequences: usize,
+    /// Rough size of the drone entries, histories and alerts
+    pub estimated_bytes: usize,
+}
+
+impl TrackerFootprint {
+    /// Per-collection counts, labelled for the `drone_convoy_tracker_entries` gauge
+    pub fn entries(&self) -> [(&'static str, usize); 7] {
+        [
+            ("drones", self.drones),
+            ("position_history", self.position_history),
+            ("active_alerts", self.active_alerts),
+            ("motion", self.motion),
+            ("endurance", self.endurance),
+            ("fusion", self.fusion),
+            ("sequences", self.sequences),
+        ]
+    }
+}
+
+/// Drones to drop, given each drone's last update: the ones silent past
+/// `offline_after`, then the longest silent while more than `max_drones`
+/// remain
+pub fn select_evictions(
+    last_updates: &[(DroneId, DateTime<Utc>)],
+    now: DateTime<Utc>,
+    config: &EvictionConfig,
+) -> Vec<(DroneId, EvictionReason)> {
+    let mut by_age: Vec<&(DroneId, DateTime<Utc>)> = last_updates.iter().collect();
+    by_age.sort_by_key(|(id, at)| (*at, id.0.clone()));
+
+    let cutoff = config
+        .offline_after
+        .and_then(|after| chrono::Duration::from_std(after).ok())
+        .map(|after| now - after);
+    let offline = cutoff.map_or(0, |cutoff| by_age.iter().take_while(|(_, at)| *at < cutoff).count());
+    let over_capacity = config
+        .max_drones
+        .map_or(0, |max| (by_age.len() - offline).saturating_sub(max));
+
+    by_age
+        .into_iter()
+        .take(offline + over_capacity)
+        .enumerate()
+        .map(|(i, (id, _))| {
+            let reason = if i < offline { EvictionReason::Offline } else { EvictionReason::Capacity };
+            (id.clone(), reason)
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_then_longest_silent_over_capacity() {
+        let now = Utc::now();
+        let ago = |hours| now - chrono::Duration::hours(hours);
+        let drones: Vec<(DroneId, DateTime<Utc>)> = [("A", 0), ("B", 30), ("C", 2), ("D", 48), ("E", 5)]
+            .into_iter()
+            .map(|(id, hours)| (DroneId::new(id), ago(hours)))
+            .collect();
+        let config = EvictionConfig {
+            offline_after: Some(Duration::from_secs(24 * 3600)),
+            max_drones: Some(2),
+            ..Default::default()
+        };
+
+        // D and B are offline; of the three left, E is silent longest
+        let evicted = select_evictions(&drones, now, &config);
+        assert_eq!(
+            evicted,
+            vec![
+                (DroneId::new("D"), EvictionReason::Offline),
+                (DroneId::new("B"), EvictionReason::Offline),
+                (DroneId::new("E"), EvictionReason::Capacity),
+            ]
+        );
+
+        let unbounded = EvictionConfig {
+            offline_after: None,
+            max_drones: None,
+            ..Default::default()
+        };
+        assert!(select_evictions(&drones, now, &unbounded).is_empty());
+    }
+}
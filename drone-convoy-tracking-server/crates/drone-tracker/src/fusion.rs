@@ -0,0 +1,338 @@
+//! Position source fusion
+//!
+//! A drone's GPS fix and the CV engine's geo-estimate of the same drone are
+//! combined by inverse-variance weighting into one fused position, which the
+//! tracker treats as authoritative. Each source's distance from the fused
+//! position is kept as a residual, and a GPS/CV separation above the
+//! disagreement threshold is flagged (spoofed GPS, or CV locked onto the
+//! wrong target).
+
+use drone_core::{DroneId, GeoPosition};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Position fusion configuration
+#[derive(Debug, Clone)]
+pub struct FusionConfig {
+    /// GPS horizontal error (1 sigma, meters)
+    pub gps_sigma_m: f64,
+    /// CV error when the estimate carries no uncertainty (1 sigma, meters)
+    pub cv_sigma_m: f64,
+    /// CV estimates older than this are not fused
+    pub cv_max_age: Duration,
+    /// GPS/CV separation above this is flagged as a disagreement
+    pub disagreement_threshold_m: f64,
+    /// Residuals kept per source and drone
+    pub residual_window: usize,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            gps_sigma_m: 5.0,
+            cv_sigma_m: 15.0,
+            cv_max_age: Duration::from_secs(2),
+            disagreement_threshold_m: 50.0,
+            residual_window: 50,
+        }
+    }
+}
+
+/// Position measurement source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSource {
+    Gps,
+    Cv,
+}
+
+/// Result of fusing a GPS fix
+#[derive(Debug, Clone, Serialize)]
+pub struct FusedPosition {
+    pub position: GeoPosition,
+    /// Combined horizontal error (1 sigma, meters)
+    pub sigma_m: f64,
+    pub sources: Vec<PositionSource>,
+    /// GPS/CV separation when a CV estimate was fused (meters)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separation_m: Option<f64>,
+    /// Separation is above the disagreement threshold
+    pub disagreement: bool,
+    /// This fix started a disagreement (the previous one agreed)
+    #[serde(skip)]
+    pub disagreement_started: bool,
+    /// CV estimate fused with this fix
+    #[serde(skip)]
+    pub cv_position: Option<GeoPosition>,
+}
+
+/// Recent distances between one source and the fused position
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceResiduals {
+    pub source: PositionSource,
+    pub samples: usize,
+    pub mean_m: f64,
+    pub rms_m: f64,
+    pub max_m: f64,
+}
+
+impl SourceResiduals {
+    fn from_window(source: PositionSource, window: &VecDeque<f64>) -> Self {
+        let samples = window.len();
+        let n = samples.max(1) as f64;
+        Self {
+            source,
+            samples,
+            mean_m: window.iter().sum::<f64>() / n,
+            rms_m: (window.iter().map(|r| r * r).sum::<f64>() / n).sqrt(),
+            max_m: window.iter().copied().fold(0.0, f64::max),
+        }
+    }
+}
+
+/// Fusion state for one drone
+#[derive(Debug, Clone, Serialize)]
+pub struct FusionReport {
+    pub drone_id: DroneId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fused: Option<FusedPosition>,
+    pub residuals: Vec<SourceResiduals>,
+    /// GPS and CV fixes disagreed beyond the threshold
+    pub disagreements: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CvEstimate {
+    position: GeoPosition,
+    sigma_m: f64,
+    at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct DroneFusion {
+    cv: Option<CvEstimate>,
+    fused: Option<FusedPosition>,
+    gps_residuals: VecDeque<f64>,
+    cv_residuals: VecDeque<f64>,
+    disagreements: u64,
+}
+
+/// Per-drone GPS/CV position fusion
+#[derive(Debug, Default)]
+pub struct PositionFusion {
+    config: FusionConfig,
+    drones: DashMap<DroneId, DroneFusion>,
+}
+
+impl PositionFusion {
+    pub fn new(config: FusionConfig) -> Self {
+        Self {
+            config,
+            drones: DashMap::new(),
+        }
+    }
+
+    /// Record a CV geo-estimate, fused with the next GPS fix
+    ///
+    /// Without an explicit uncertainty the default CV error is scaled up
+    /// by low detection confidence.
+    pub fn record_cv(
+        &self,
+        drone_id: &DroneId,
+        position: GeoPosition,
+        uncertainty_m: Option<f64>,
+        confidence: f64,
+        at: DateTime<Utc>,
+    ) {
+        let sigma_m = uncertainty_m
+            .filter(|u| u.is_finite() && *u > 0.0)
+            .unwrap_or(self.config.cv_sigma_m / confidence.clamp(0.1, 1.0));
+        self.drones.entry(drone_id.clone()).or_default().cv = Some(CvEstimate {
+            position,
+            sigma_m,
+            at,
+        });
+    }
+
+    /// Fuse a GPS fix with the latest fresh CV estimate
+    ///
+    /// Only the horizontal position is fused; altitude comes from GPS.
+    pub fn fuse_gps(&self, drone_id: &DroneId, gps: GeoPosition, at: DateTime<Utc>) -> FusedPosition {
+        let config = &self.config;
+        let mut state = self.drones.entry(drone_id.clone()).or_default();
+        let max_age = self.max_age();
+        // A stale estimate is dropped, not just skipped, so it cannot pair
+        // with a later fix
+        if state.cv.is_some_and(|cv| at - cv.at > max_age) {
+            state.cv = None;
+        }
+        let cv = state.cv.filter(|cv| cv.at - at <= max_age);
+
+        let fused = match cv {
+            Some(cv) => {
+                let w_gps = 1.0 / config.gps_sigma_m.powi(2);
+                let w_cv = 1.0 / cv.sigma_m.powi(2);
+                let share = w_cv / (w_gps + w_cv);
+                let position = GeoPosition::new(
+                    gps.latitude + (cv.position.latitude - gps.latitude) * share,
+                    gps.longitude + (cv.position.longitude - gps.longitude) * share,
+                    gps.altitude,
+                );
+                let separation_m = gps.distance_to(&cv.position) * 1000.0;
+                let disagreement = separation_m > config.disagreement_threshold_m;
+                let was_disagreeing = state.fused.as_ref().is_some_and(|f| f.disagreement);
+
+                push_residual(&mut state.cv_residuals, cv.position.distance_to(&position) * 1000.0, config);
+                push_residual(&mut state.gps_residuals, gps.distance_to(&position) * 1000.0, config);
+                if disagreement {
+                    state.disagreements += 1;
+                }
+
+                FusedPosition {
+                    position,
+                    sigma_m: (w_gps + w_cv).recip().sqrt(),
+                    sources: vec![PositionSource::Gps, PositionSource::Cv],
+                    separation_m: Some(separation_m),
+                    disagreement,
+                    disagreement_started: disagreement && !was_disagreeing,
+                    cv_position: Some(cv.position),
+                }
+            }
+            None => FusedPosition {
+                position: gps,
+                sigma_m: config.gps_sigma_m,
+                sources: vec![PositionSource::Gps],
+                separation_m: None,
+                disagreement: false,
+                disagreement_started: false,
+                cv_position: None,
+            },
+        };
+
+        state.fused = Some(fused.clone());
+        fused
+    }
+
+    /// Latest fused position and residuals for a drone
+    pub fn report(&self, drone_id: &DroneId) -> Option<FusionReport> {
+        let state = self.drones.get(drone_id)?;
+        Some(FusionReport {
+            drone_id: drone_id.clone(),
+            fused: state.fused.clone(),
+            residuals: vec![
+                SourceResiduals::from_window(PositionSource::Gps, &state.gps_residuals),
+                SourceResiduals::from_window(PositionSource::Cv, &state.cv_residuals),
+            ],
+            disagreements: state.disagreements,
+        })
+    }
+
+    /// Drop CV estimates older than `cv_max_age`, and the state of drones
+    /// only ever seen by CV
+    pub fn expire(&self, now: DateTime<Utc>) {
+        let max_age = self.max_age();
+        self.drones.retain(|_, state| {
+            if state.cv.is_some_and(|cv| now - cv.at > max_age) {
+                state.cv = None;
+            }
+            state.cv.is_some() || state.fused.is_some()
+        });
+    }
+
+    fn max_age(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.config.cv_max_age).unwrap_or_default()
+    }
+
+    /// Drop a drone's fusion state
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.drones.remove(drone_id);
+    }
+
+    /// Drones with fusion state
+    pub fn drone_count(&self) -> usize {
+        self.drones.len()
+    }
+}
+
+fn push_residual(window: &mut VecDeque<f64>, residual: f64, config: &FusionConfig) {
+    window.push_back(residual);
+    while window.len() > config.residual_window.max(1) {
+        window.pop_front();
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_fusion_and_disagreement() {
+        let fusion = PositionFusion::new(FusionConfig::default());
+        let drone_id = DroneId::new("REAPER-01");
+        let now = Utc::now();
+        let gps = GeoPosition::new(34.5000, 69.2000, 3000.0);
+
+        // GPS only until a CV estimate arrives
+        let fused = fusion.fuse_gps(&drone_id, gps, now);
+        assert_eq!(fused.sources, vec![PositionSource::Gps]);
+        assert_eq!(fused.position.latitude, gps.latitude);
+
+        // CV 20 m north with the same error as GPS lands halfway
+        let cv = GeoPosition::new(34.5000 + 20.0 / 111_195.0, 69.2000, 0.0);
+        fusion.record_cv(&drone_id, cv, Some(5.0), 0.9, now);
+        let fused = fusion.fuse_gps(&drone_id, gps, now);
+        assert!((fused.separation_m.unwrap() - 20.0).abs() < 0.5);
+        assert!((gps.distance_to(&fused.position) * 1000.0 - 10.0).abs() < 0.5);
+        assert_eq!(fused.position.altitude, 3000.0);
+        assert!(!fused.disagreement);
+
+        // 200 m off is flagged once, then stays flagged without re-triggering
+        let far = GeoPosition::new(34.5000 + 200.0 / 111_195.0, 69.2000, 0.0);
+        fusion.record_cv(&drone_id, far, None, 1.0, now);
+        let fused = fusion.fuse_gps(&drone_id, gps, now);
+        assert!(fused.disagreement && fused.disagreement_started);
+        let fused = fusion.fuse_gps(&drone_id, gps, now);
+        assert!(fused.disagreement && !fused.disagreement_started);
+
+        // Stale CV estimates are ignored
+        let later = now + chrono::Duration::seconds(5);
+        assert_eq!(fusion.fuse_gps(&drone_id, gps, later).sources.len(), 1);
+
+        let report = fusion.report(&drone_id).unwrap();
+        assert_eq!(report.disagreements, 2);
+        assert_eq!(report.residuals[0].samples, 3);
+        assert!(report.residuals[1].max_m > report.residuals[0].max_m);
+    }
+
+    #[test]
+    fn test_stale_cv_estimates_expire() {
+        let fusion = PositionFusion::new(FusionConfig::default());
+        let (tracked, unseen) = (DroneId::new("REAPER-01"), DroneId::new("GHOST-09"));
+        let now = Utc::now();
+        let gps = GeoPosition::new(34.5000, 69.2000, 3000.0);
+        let cv = GeoPosition::new(34.5001, 69.2000, 0.0);
+        fusion.fuse_gps(&tracked, gps, now);
+        fusion.record_cv(&tracked, cv, Some(5.0), 0.9, now);
+        fusion.record_cv(&unseen, cv, Some(5.0), 0.9, now);
+
+        // An estimate that went stale is gone, even for a fix stamped earlier
+        let later = now + chrono::Duration::seconds(5);
+        assert_eq!(fusion.fuse_gps(&tracked, gps, later).sources.len(), 1);
+        assert_eq!(fusion.fuse_gps(&tracked, gps, now).sources.len(), 1);
+
+        // The sweep drops CV-only drones once their estimate is stale
+        fusion.expire(now);
+        assert_eq!(fusion.drone_count(), 2);
+        fusion.expire(later);
+        assert_eq!(fusion.drone_count(), 1);
+        assert!(fusion.report(&unseen).is_none());
+    }
+}
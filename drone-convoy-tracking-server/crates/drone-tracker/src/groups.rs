@@ -0,0 +1,290 @@
+//! Named drone groups
+//!
+//! Operators command subsets of the fleet by group name. A group lists
+//! explicit members and may name a convoy role whose current holders are
+//! members too, so an "escorts" group follows role reassignments. Member
+//! lists accept ranges such as `REAPER-01..04`.
+
+use drone_core::{ConvoyRole, DroneId};
+use drone_db::DroneGroupRecord;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Most drones a single range may expand to
+pub const MAX_RANGE_MEMBERS: usize = 256;
+
+/// A named set of drones
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DroneGroup {
+    pub name: String,
+    /// Explicit members, in the order given
+    pub members: Vec<DroneId>,
+    /// Drones holding this role are members as well
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<ConvoyRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DroneGroup {
+    pub fn new(
+        name: impl Into<String>,
+        members: Vec<DroneId>,
+        role: Option<ConvoyRole>,
+        description: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut unique = Vec::with_capacity(members.len());
+        for member in members {
+            if !unique.contains(&member) {
+                unique.push(member);
+            }
+        }
+        Self {
+            name: name.into(),
+            members: unique,
+            role,
+            description,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Explicit members followed by other holders of the group's role (by ID)
+    pub fn resolve(&self, roles: &HashMap<DroneId, ConvoyRole>) -> Vec<DroneId> {
+        let mut members = self.members.clone();
+        if let Some(role) = self.role {
+            let mut holders: Vec<DroneId> = roles
+                .iter()
+                .filter(|(id, r)| **r == role && !members.contains(id))
+                .map(|(id, _)| id.clone())
+                .collect();
+            holders.sort();
+            members.extend(holders);
+        }
+        members
+    }
+
+    pub fn to_record(&self) -> DroneGroupRecord {
+        DroneGroupRecord {
+            name: self.name.clone(),
+            members: self.members.iter().map(|id| id.0.clone()).collect(),
+            role: self.role.map(|role| role.to_string()),
+            description: self.description.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub fn from_record(record: &DroneGroupRecord) -> Result<Self, serde_json::Error> {
+        let role = record
+            .role
+            .as_ref()
+            .map(|role| serde_json::from_value(serde_json::Value::String(role.clone())))
+            .transpose()?;
+        Ok(Self {
+            name: record.name.clone(),
+            members: record.members.iter().map(DroneId::new).collect(),
+            role,
+            description: record.description.clone(),
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        })
+    }
+}
+
+/// Expand a member entry: a drone ID, or a range like `REAPER-01..04`
+/// (the end may repeat the prefix: `REAPER-01..REAPER-04`)
+pub fn expand_members(entry: &str) -> Result<Vec<DroneId>, String> {
+    let Some((first, last)) = entry.split_once("..") else {
+        return Ok(vec![DroneId::new(entry)]);
+    };
+
+    let digits_at = first.len() - first.chars().rev().take_while(char::is_ascii_digit).count();
+    let (prefix, start_digits) = first.split_at(digits_at);
+    let end_digits = last.strip_prefix(prefix).unwrap_or(last);
+    let (Ok(start), Ok(end)) = (start_digits.parse::<usize>(), end_digits.parse::<usize>()) else {
+        return Err(format!("range {} must look like REAPER-01..04", entry));
+    };
+    if end < start {
+        return Err(format!("range {} ends before it starts", entry));
+    }
+    if end - start >= MAX_RANGE_MEMBERS {
+        return Err(format!("range {} covers more than {} drones", entry, MAX_RANGE_MEMBERS));
+    }
+
+    let width = start_digits.len();
+    Ok((start..=end)
+        .map(|n| DroneId::new(format!("{}{:0width$}", prefix, n, width = width)))
+        .collect())
+}
+
+/// Named drone groups, by name
+#[derive(Debug, Default)]
+pub struct GroupRegistry {
+    groups: RwLock<BTreeMap<String, DroneGroup>>,
+}
+
+impl GroupRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create or replace a group; a replaced group keeps its creation time
+    pub fn put(&self, mut group: DroneGroup) -> DroneGroup {
+        let mut groups = self.groups.write();
+        if let Some(existing) = groups.get(&group.name) {
+            group.created_at = existing.created_at;
+        }
+        groups.insert(group.name.clone(), group.clone());
+        group
+    }
+
+    pub fn remove(&self, name: &str) -> Option<DroneGroup> {
+        self.groups.write().remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<DroneGroup> {
+        self.groups.read().get(name).cloned()
+    }
+
+    /// All groups, by name
+    pub fn list(&self) -> Vec<DroneGroup> {
+        self.groups.read().values().cloned().collect()
+    }
+
+    /// Replace all groups (after loading them from the database)
+    pub fn restore(&self, groups: Vec<DroneGroup>) {
+        *self.groups.write() = groups.into_iter().map(|g| (g.name.clone(), g)).collect();
+    }
+}
+
+// ============================================================================
+// BULK COMMANDS
+// ============================================================================
+
+/// What happened to a command sent to one drone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandOutcome {
+    /// Delivered by the drone's transport
+    Sent,
+    /// Accepted; the transport has no message for this command
+    Accepted,
+    /// The drone is not tracked
+    NotFound,
+    /// Refused before reaching a transport
+    Failed,
+    /// The drone's transport could not deliver the command
+    TransportError,
+}
+
+impl CommandOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Sent | Self::Accepted)
+    }
+}
+
+/// Per-drone result of a command
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandResult {
+    pub drone_id: DroneId,
+    pub outcome: CommandOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CommandResult {
+    pub fn new(drone_id: DroneId, outcome: CommandOutcome) -> Self {
+        Self {
+            drone_id,
+            outcome,
+            error: None,
+        }
+    }
+
+    pub fn transport_error(drone_id: DroneId, error: impl Into<String>) -> Self {
+        Self {
+            drone_id,
+            outcome: CommandOutcome::TransportError,
+            error: Some(error.into()),
+        }
+    }
+
+    pub fn failed(drone_id: DroneId, error: impl Into<String>) -> Self {
+        Self {
+            drone_id,
+            outcome: CommandOutcome::Failed,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Results of a command fanned out to a group
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkCommandReport {
+    pub group: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<CommandResult>,
+}
+
+impl BulkCommandReport {
+    pub fn new(group: impl Into<String>, results: Vec<CommandResult>) -> Self {
+        let succeeded = results.iter().filter(|r| r.outcome.is_success()).count();
+        Self {
+            group: group.into(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_ranges_roles_and_records() {
+        let ids = |entry: &str| expand_members(entry).map(|ids| ids.into_iter().map(|id| id.0).collect::<Vec<_>>());
+        assert_eq!(ids("REAPER-01..03").unwrap(), vec!["REAPER-01", "REAPER-02", "REAPER-03"]);
+        assert_eq!(ids("REAPER-09..REAPER-10").unwrap(), vec!["REAPER-09", "REAPER-10"]);
+        assert_eq!(ids("GHOST").unwrap(), vec!["GHOST"]);
+        assert!(ids("REAPER-04..01").is_err());
+        assert!(ids("REAPER..04").is_err());
+        assert!(ids("R-0..9999").is_err());
+
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let [r1, r2, r5] = ["REAPER-01", "REAPER-02", "REAPER-05"].map(DroneId::new);
+        let group = DroneGroup::new("escorts", vec![r2.clone(), r2.clone()], Some(ConvoyRole::Escort), None, now);
+        let roles = HashMap::from([
+            (r5.clone(), ConvoyRole::Escort),
+            (r1.clone(), ConvoyRole::Escort),
+            (r2.clone(), ConvoyRole::Escort),
+            (DroneId::new("REAPER-03"), ConvoyRole::Scout),
+        ]);
+        assert_eq!(group.resolve(&roles), vec![r2, r1, r5]);
+
+        let record = group.to_record();
+        assert_eq!(record.role.as_deref(), Some("ESCORT"));
+        assert_eq!(DroneGroup::from_record(&record).unwrap(), group);
+
+        // Replacing a group keeps its creation time
+        let registry = GroupRegistry::new();
+        registry.put(group);
+        let later = now + chrono::Duration::minutes(5);
+        let replaced = registry.put(DroneGroup::new("escorts", vec![], None, None, later));
+        assert_eq!((replaced.created_at, replaced.updated_at), (now, later));
+    }
+}
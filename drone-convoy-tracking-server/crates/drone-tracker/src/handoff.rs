@@ -0,0 +1,142 @@
+//! Ground control station handoff
+//!
+//! When a convoy crosses a sector boundary, control of its drones moves to
+//! the station responsible for the next sector. The origin exports each
+//! drone's tracked state as a `HandoffPackage`, the receiving station
+//! imports it and takes ownership, and the origin keeps the drone as a
+//! read-only remote entry that points at its new owner. Only one handoff
+//! of a drone runs at a time, in either direction.
+
+use drone_core::{Alert, ConvoyRole, Drone, DroneId, GeoPosition, MissionId, ThresholdOverrides};
+use drone_db::HandoffOwnerRecord;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Everything a station needs to take over tracking a drone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffPackage {
+    pub handoff_id: Uuid,
+    /// Station handing the drone over
+    pub origin_station: String,
+    pub issued_at: DateTime<Utc>,
+    pub drone: Drone,
+    /// Mission the waypoint progress refers to
+    pub mission_id: Option<MissionId>,
+    pub waypoint_index: usize,
+    pub waypoint_progress: f64,
+    pub position_history: Vec<(DateTime<Utc>, GeoPosition)>,
+    pub active_alerts: Vec<Alert>,
+    pub role: Option<ConvoyRole>,
+    pub thresholds: Option<ThresholdOverrides>,
+}
+
+/// Station that controls a drone handed off from here
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteOwner {
+    pub drone_id: DroneId,
+    pub handoff_id: Uuid,
+    pub station: String,
+    /// Where the owning station's API can be reached
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub handed_off_at: DateTime<Utc>,
+}
+
+impl RemoteOwner {
+    pub fn to_record(&self) -> HandoffOwnerRecord {
+        HandoffOwnerRecord {
+            handoff_id: self.handoff_id,
+            station: self.station.clone(),
+            url: self.url.clone(),
+            handed_off_at: self.handed_off_at,
+        }
+    }
+
+    pub fn from_record(drone_id: DroneId, record: HandoffOwnerRecord) -> Self {
+        Self {
+            drone_id,
+            handoff_id: record.handoff_id,
+            station: record.station,
+            url: record.url,
+            handed_off_at: record.handed_off_at,
+        }
+    }
+}
+
+/// Why a handoff was refused
+#[derive(Debug, Error)]
+pub enum HandoffFailure {
+    #[error("a handoff of drone {0} is already in progress")]
+    InProgress(DroneId),
+
+    #[error("drone {0} is controlled by this station")]
+    ControlledHere(DroneId),
+
+    #[error(transparent)]
+    Store(#[from] anyhow::Error),
+}
+
+/// Marks a drone's handoff as in progress until dropped
+#[derive(Debug)]
+pub struct HandoffGuard {
+    drone_id: DroneId,
+    in_flight: Arc<Mutex<HashSet<DroneId>>>,
+}
+
+impl Drop for HandoffGuard {
+    fn drop(&mut self) {
+        self.in_flight.lock().remove(&self.drone_id);
+    }
+}
+
+/// Drones this station has handed off and no longer controls
+#[derive(Debug, Default)]
+pub struct HandoffRegistry {
+    remote: DashMap<DroneId, RemoteOwner>,
+    in_flight: Arc<Mutex<HashSet<DroneId>>>,
+}
+
+impl HandoffRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a handoff of `drone_id`; `None` while another is in progress
+    pub fn begin(&self, drone_id: &DroneId) -> Option<HandoffGuard> {
+        self.in_flight.lock().insert(drone_id.clone()).then(|| HandoffGuard {
+            drone_id: drone_id.clone(),
+            in_flight: self.in_flight.clone(),
+        })
+    }
+
+    pub fn mark_remote(&self, owner: RemoteOwner) {
+        self.remote.insert(owner.drone_id.clone(), owner);
+    }
+
+    /// Take back ownership (e.g. the drone was handed back); returns the previous owner
+    pub fn release(&self, drone_id: &DroneId) -> Option<RemoteOwner> {
+        self.remote.remove(drone_id).map(|(_, owner)| owner)
+    }
+
+    pub fn owner(&self, drone_id: &DroneId) -> Option<RemoteOwner> {
+        self.remote.get(drone_id).map(|o| o.clone())
+    }
+
+    pub fn is_remote(&self, drone_id: &DroneId) -> bool {
+        self.remote.contains_key(drone_id)
+    }
+
+    /// Remote drones, by drone ID
+    pub fn list(&self) -> Vec<RemoteOwner> {
+        let mut owners: Vec<RemoteOwner> = self.remote.iter().map(|o| o.clone()).collect();
+        owners.sort_by(|a, b| a.drone_id.as_str().cmp(b.drone_id.as_str()));
+        owners
+    }
+}
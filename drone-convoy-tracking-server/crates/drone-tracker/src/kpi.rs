@@ -0,0 +1,159 @@
+//! Mission KPIs
+//!
+//! Feeds the mission metrics of a `MetricsCollector` from tracker activity:
+//! how long the mission has run, how long each leg takes, whether drones
+//! make their expected arrival times, how well the convoy holds formation
+//! and how many alerts the mission raised.
+
+use drone_core::{AlertSeverity, DroneId, Mission, MissionId, MissionStatus, WaypointId};
+use drone_telemetry::MetricsCollector;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Mission KPI configuration
+#[derive(Debug, Clone)]
+pub struct KpiConfig {
+    /// Arrivals up to this long after a waypoint's expected arrival are on time
+    pub punctuality_tolerance: Duration,
+    /// Followers within this distance of their formation slot are compliant
+    pub formation_tolerance_meters: f64,
+}
+
+impl Default for KpiConfig {
+    fn default() -> Self {
+        Self {
+            punctuality_tolerance: Duration::from_secs(60),
+            formation_tolerance_meters: 100.0,
+        }
+    }
+}
+
+/// Mission KPI bookkeeping on top of the metrics collector
+pub struct MissionKpis {
+    config: KpiConfig,
+    metrics: Arc<MetricsCollector>,
+    /// Waypoint each drone last left and when, for leg traversal times
+    leg_starts: DashMap<DroneId, (WaypointId, DateTime<Utc>)>,
+}
+
+impl MissionKpis {
+    pub fn new(config: KpiConfig, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            config,
+            metrics,
+            leg_starts: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &KpiConfig {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> &Arc<MetricsCollector> {
+        &self.metrics
+    }
+
+    /// Drop an evicted drone's leg in flight
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.leg_starts.remove(drone_id);
+    }
+
+        /// A new mission became active; legs in flight belong to the old one
+    pub fn mission_changed(&self, mission: &Mission) {
+        self.leg_starts.clear();
+        self.metrics.set_mission_active(mission.status == MissionStatus::Active);
+    }
+
+    /// Record the leg just flown and the arrival's punctuality, and start the next leg
+    pub fn waypoint_reached(&self, drone_id: &DroneId, mission: &Mission, index: usize, now: DateTime<Utc>) {
+        let Some(waypoint) = mission.waypoints.get(index) else {
+            return;
+        };
+
+        if let Some((from, started)) = self.leg_starts.get(drone_id).map(|s| s.clone()) {
+            let seconds = (now - started).num_milliseconds() as f64 / 1000.0;
+            self.metrics
+                .record_leg_traversal(&format!("{}-{}", from, waypoint.id), seconds.max(0.0));
+        }
+
+        if let Some(expected) = waypoint.expected_arrival {
+            let tolerance = chrono::Duration::from_std(self.config.punctuality_tolerance)
+                .unwrap_or_else(|_| chrono::Duration::zero());
+            self.metrics.record_waypoint_punctuality(now <= expected + tolerance);
+        }
+
+        self.leg_starts.insert(drone_id.clone(), (waypoint.id.clone(), now));
+    }
+
+    /// A drone left a loiter or checkpoint hold; the hold does not count towards the next leg
+    pub fn waypoint_departed(&self, drone_id: &DroneId, waypoint_id: &WaypointId, now: DateTime<Utc>) {
+        self.leg_starts.insert(drone_id.clone(), (waypoint_id.clone(), now));
+    }
+
+    /// Refresh the duration gauge: time since start, or the final duration once ended
+    pub fn update_duration(&self, mission: &Mission, now: DateTime<Utc>) {
+        self.metrics.set_mission_active(mission.status == MissionStatus::Active);
+        let Some(start) = mission.start_time else {
+            return;
+        };
+        let end = mission.end_time.unwrap_or(now);
+        let seconds = (end - start).num_milliseconds() as f64 / 1000.0;
+        self.metrics.set_mission_duration(&mission.id.to_string(), seconds.max(0.0));
+    }
+
+    pub fn update_formation(&self, mission_id: &MissionId, percent: f64) {
+        self.metrics.set_formation_compliance(&mission_id.to_string(), percent);
+    }
+
+    pub fn alert_raised(&self, mission_id: &MissionId, severity: AlertSeverity) {
+        let severity = format!("{:?}", severity).to_uppercase();
+        self.metrics.record_mission_alert(&mission_id.to_string(), &severity);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Waypoint;
+
+    #[test]
+    fn test_leg_times_exclude_holds_and_punctuality_uses_tolerance() {
+        let kpis = MissionKpis::new(KpiConfig::default(), Arc::new(MetricsCollector::new().unwrap()));
+        let t0 = Utc::now();
+        let at = |s: i64| t0 + chrono::Duration::seconds(s);
+        let mut mission = Mission::new("Test");
+        mission.waypoints = vec![
+            Waypoint::new("WP01", "Start", 34.0, 69.0),
+            Waypoint::new("WP02", "Hold", 34.1, 69.0),
+            Waypoint::new("WP03", "End", 34.2, 69.0),
+        ];
+        mission.waypoints[1].expected_arrival = Some(at(100));
+        mission.waypoints[2].expected_arrival = Some(at(200));
+        mission.start_time = Some(t0);
+        let drone = DroneId::new("REAPER-01");
+
+        kpis.mission_changed(&mission);
+        kpis.waypoint_reached(&drone, &mission, 0, at(0));
+        kpis.waypoint_reached(&drone, &mission, 1, at(150)); // 50s late, within tolerance
+        kpis.waypoint_departed(&drone, &mission.waypoints[1].id, at(400));
+        kpis.waypoint_reached(&drone, &mission, 2, at(520)); // late
+        kpis.update_duration(&mission, at(600));
+
+        let export = kpis.metrics().export_mission_kpis();
+        assert!(export.contains(r#"drone_convoy_mission_leg_traversal_seconds_sum{leg="WP01-WP02"} 150"#));
+        assert!(export.contains(r#"drone_convoy_mission_leg_traversal_seconds_sum{leg="WP02-WP03"} 120"#));
+        assert!(export.contains(r#"drone_convoy_mission_waypoint_arrivals_total{punctuality="on_time"} 1"#));
+        assert!(export.contains(r#"drone_convoy_mission_waypoint_arrivals_total{punctuality="late"} 1"#));
+        assert!(export.contains(&format!(
+            r#"drone_convoy_mission_duration_seconds{{mission_id="{}"}} 600"#,
+            mission.id
+        )));
+    }
+}
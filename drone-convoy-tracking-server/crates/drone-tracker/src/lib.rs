@@ -11,63 +11,251 @@
 //! - Alert generation and handling
 //! - Integration with all subsystems
 
+pub mod abort;
+pub mod altitude;
+pub mod arrival;
+pub mod breaker;
+pub mod checkpoint;
 pub mod convoy;
+pub mod custom_events;
+pub mod cv_publisher;
+pub mod cv_tuning;
+pub mod drift;
+pub mod emergency;
+pub mod endurance;
 pub mod engine;
+pub mod eviction;
 pub mod events;
+pub mod fusion;
+pub mod groups;
+pub mod handoff;
+pub mod kpi;
+pub mod los;
+pub mod markings;
+pub mod mission;
+pub mod mission_sync;
+pub mod motion;
+pub mod proximity;
+pub mod quality;
+pub mod query;
+pub mod rules;
+pub mod scheduler;
+pub mod sequence;
+pub mod sources;
+pub mod speed;
+pub mod status;
+pub mod suppression;
+pub mod transport;
+pub mod wind;
+pub mod zones;
 
-pub use convoy::ConvoyManager;
+pub use abort::{AbortCommand, AbortPolicy, AbortReport, AbortState, DroneAbortStatus};
+pub use altitude::{
+    AltitudeAssignment, AltitudeBand, AltitudeConfig, AltitudeManager, AltitudeViolation, BandError, ALTITUDE_ALERT_TYPE, LOWEST_BAND,
+};
+pub use arrival::{ArrivalConfig, ArrivalLatch};
+pub use breaker::{BreakerState, BreakerStatus, WriteBreakerConfig, WriteBreakers};
+pub use checkpoint::{CheckpointConfig, CheckpointGate, CheckpointHold, CHECKPOINT_ALERT_TYPE};
+pub use convoy::{ConvoyManager, RoleAlertPolicy};
+pub use custom_events::{CustomEventRegistry, RegisteredSchema};
+pub use cv_publisher::{CvPipeline, CvPublisher, CvPublisherConfig, CvPublisherStats};
+pub use cv_tuning::{CvTuningRevision, CvTuningStore, CvTuningUpdateError};
+pub use drift::{
+    CalibrationCorrection, DriftConfig, DriftEstimate, DriftMonitor, DriftReport, DRIFT_ALERT_TYPE,
+};
+pub use emergency::{EmergencyCoordinator, EmergencyPolicy, EmergencyResponse, ResponseAction};
+pub use endurance::{
+    ConsumptionSource, EnduranceConfig, EnduranceProjection, EnduranceProjector, ENDURANCE_ALERT_TYPE,
+};
 pub use engine::TrackingEngine;
-pub use events::EventBus;
+pub use eviction::{DroneSnapshot, EvictionConfig, EvictionReason, TrackerFootprint};
+pub use events::{EventBus, EventRetention};
+pub use fusion::{FusedPosition, FusionConfig, FusionReport, PositionFusion, PositionSource};
+pub use groups::{
+    expand_members, BulkCommandReport, CommandOutcome, CommandResult, DroneGroup, GroupRegistry,
+};
+pub use handoff::{HandoffFailure, HandoffGuard, HandoffPackage, HandoffRegistry, RemoteOwner};
+pub use kpi::{KpiConfig, MissionKpis};
+pub use los::{LosConfig, LosLoss, LosMonitor, LOS_ALERT_TYPE};
+pub use markings::{ColorTaken, FleetMarkings, MIN_HALO_HUE_SEPARATION};
+pub use mission::MissionExecutor;
+pub use mission_sync::{
+    DroneSyncStatus, MissionSync, MissionSyncConfig, MissionSyncView, Publication, SyncReply, SyncState,
+};
+pub use motion::{MotionConfig, MotionEstimator};
+pub use proximity::{
+    Avoidance, CollisionWarning, ProjectedTrack, ProximityConfig, ProximityMonitor, VelocitySource,
+};
+pub use quality::{
+    DataQualityConfig, DataQualityMonitor, DataQualityReport, DroneDataQuality, TelemetryGap,
+};
+pub use query::{DronePredicate, DroneQuery};
+pub use rules::{AlertRule, Condition, RuleEngine, RuleFacts, RuleField, RuleOp, RULE_ALERT_TYPE};
+pub use suppression::{AlertSuppressor, SuppressionRule, SuppressionStats, SuppressionWindow};
+pub use scheduler::{
+    CommandScheduler, CommandTrigger, ScheduleState, ScheduledAction, ScheduledCommand,
+};
+pub use sequence::{DroneSequenceStats, SequenceCheck, SequenceTracker};
+pub use sources::{ReportRejected, SourceCheck, SourceConflict, TelemetrySources, SOURCE_CONFLICT_ALERT_TYPE};
+pub use speed::{SpeedLimitConfig, SpeedLimitMonitor, SpeedViolation, SPEED_LIMIT_ALERT_TYPE};
+pub use status::{StatusInference, StatusInferenceConfig, StatusReport};
+pub use transport::{
+    CommandDispatcher, CommandTransport, MavlinkTransport, P2pTransport, TransportError,
+};
+pub use wind::{WindCell, WindConfig, WindEstimator, WindField, WindVector, MAX_WIND_FACTOR};
+pub use zones::{DwellStats, Zone, ZoneCrossing, ZoneMonitor, ZoneStats};
 
 use drone_core::{
-    Alert, AlertSeverity, AlertType, Drone, DroneId, DroneStatus,
-    Event, GeoPosition, Mission, MissionId, MissionStatus, Telemetry,
-    TrackingResult, Waypoint, WaypointId,
+    Alert, AlertSeverity, AlertThresholds, AlertType, ConvoyRole, CorridorSpec, CustomEvent, CustomEventError, CvTuning, DerivedMotion, Drone, DroneCommandType, DroneId, DroneMarking,
+    DroneStatus, DroneType, Event, EventPayload, GeoPosition, GimbalState, Mission, MissionId, MissionStatus,
+    ScheduledCommandEvent, SimulationClock, SubsystemHealth, Telemetry, TenantId, TelemetrySource, TrackingResult, TelemetryLimits, TelemetryValidator,
+    ThresholdOverrides, TransportBinding, TransportKind, UpdateTimings, LatencyHop, WaypointApproachEvent, WaypointId,
+    WaypointType,
 };
 //use drone_cv::CvEngine;
-use drone_db::DbClient;
-use drone_p2p::P2pManager;
+use drone_db::{CustomEventRecord, CvTuningRecord, DbClient};
+use drone_p2p::protocol::EmergencyData;
+use drone_p2p::{
+    AllowListView, DroneMessage, InboundMessage, JitterStats, MeshTopology, MessageType, P2pManager, P2pResult, P2pStats, ReachabilityView,
+    ShapingStats,
+};
+use drone_telemetry::MetricsCollector;
 
 use chrono::{DateTime, Utc};
-use dashmap::DashMap;
-use parking_lot::RwLock;
+use dashmap::{DashMap, DashSet};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, mpsc};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, info_span, warn, Instrument};
+use uuid::Uuid;
 
 /// Tracking system configuration
 #[derive(Debug, Clone)]
 pub struct TrackerConfig {
     /// Update interval for position tracking
     pub update_interval: Duration,
-    /// Waypoint arrival threshold in meters
-    pub waypoint_threshold_meters: f64,
+    /// Waypoint arrival and departure radii and dwell
+    pub arrival: ArrivalConfig,
     /// Enable CV tracking
     //pub cv_enabled: bool,
     /// Enable P2P networking
     pub p2p_enabled: bool,
     /// Enable database persistence
     pub db_enabled: bool,
-    /// Alert thresholds
-    pub battery_warning_threshold: u8,
-    pub battery_critical_threshold: u8,
-    pub fuel_warning_threshold: u8,
-    pub fuel_critical_threshold: u8,
+    /// Global alert thresholds
+    pub alert_thresholds: AlertThresholds,
+    /// Alert threshold overrides per drone type
+    pub type_thresholds: HashMap<DroneType, ThresholdOverrides>,
+    /// Convoy response policy for emergency broadcasts
+    pub emergency_policy: EmergencyPolicy,
+    /// Pre-arrival waypoint notifications
+    pub pre_arrival: PreArrivalConfig,
+    /// Accepted telemetry ranges; out-of-range values are clamped
+    pub telemetry_limits: TelemetryLimits,
+    /// Mission abort sequence
+    pub abort_policy: AbortPolicy,
+    /// Telemetry gap detection
+    pub data_quality: DataQualityConfig,
+    /// GPS/CV position fusion
+    pub fusion: FusionConfig,
+    /// CV calibration drift detection and correction
+    pub drift: DriftConfig,
+    /// Acknowledgment holds at checkpoint waypoints
+    pub checkpoint: CheckpointConfig,
+    /// Alert severities by convoy role
+    pub role_alerts: RoleAlertPolicy,
+    /// Battery/fuel projection at mission completion
+    pub endurance: EnduranceConfig,
+    /// Punctuality and formation tolerances for mission KPIs
+    pub kpi: KpiConfig,
+    /// Terrain line-of-sight prediction
+    pub los: LosConfig,
+    /// Predictive collision warnings
+    pub proximity: ProximityConfig,
+    /// Heading, climb rate and acceleration smoothing
+    pub motion: MotionConfig,
+    /// Stale drone eviction and per-drone alert caps
+    pub eviction: EvictionConfig,
+    /// Initial CV detection and tracking parameters
+    pub cv_tuning: CvTuning,
+    /// Per-leg speed limit tolerance and command repeat interval
+    pub speed_limits: SpeedLimitConfig,
+    /// Altitude band size and enforcement
+    pub altitude: AltitudeConfig,
+    /// Per-drone circuit breakers for telemetry writes
+    pub write_breaker: WriteBreakerConfig,
+    /// Speed and staleness thresholds for automatic status changes
+    pub status_inference: StatusInferenceConfig,
+    /// Wind grid estimated from drone drift
+    pub wind: WindConfig,
+    /// Mission route sync to drone agents over P2P
+    pub mission_sync: MissionSyncConfig,
+    /// Tenant recorded on tracker spans so logs can be scoped
+    pub tenant: Option<TenantId>,
 }
 
 impl Default for TrackerConfig {
     fn default() -> Self {
         Self {
             update_interval: Duration::from_millis(100),
-            waypoint_threshold_meters: 100.0,
+            arrival: ArrivalConfig::default(),
             //cv_enabled: true,
             p2p_enabled: false, // Disabled by default for simplicity
             db_enabled: true,
-            battery_warning_threshold: 30,
-            battery_critical_threshold: 15,
-            fuel_warning_threshold: 25,
-            fuel_critical_threshold: 10,
+            alert_thresholds: AlertThresholds::default(),
+            type_thresholds: HashMap::new(),
+            emergency_policy: EmergencyPolicy::default(),
+            pre_arrival: PreArrivalConfig::default(),
+            telemetry_limits: TelemetryLimits::default(),
+            abort_policy: AbortPolicy::default(),
+            data_quality: DataQualityConfig::default(),
+            fusion: FusionConfig::default(),
+            drift: DriftConfig::default(),
+            checkpoint: CheckpointConfig::default(),
+            role_alerts: RoleAlertPolicy::default(),
+            endurance: EnduranceConfig::default(),
+            kpi: KpiConfig::default(),
+            los: LosConfig::default(),
+            proximity: ProximityConfig::default(),
+            motion: MotionConfig::default(),
+            eviction: EvictionConfig::default(),
+            cv_tuning: CvTuning::default(),
+            speed_limits: SpeedLimitConfig::default(),
+            altitude: AltitudeConfig::default(),
+            write_breaker: WriteBreakerConfig::default(),
+            status_inference: StatusInferenceConfig::default(),
+            wind: WindConfig::default(),
+            mission_sync: MissionSyncConfig::default(),
+            tenant: None,
+        }
+    }
+}
+
+/// Pre-arrival notification configuration
+#[derive(Debug, Clone)]
+pub struct PreArrivalConfig {
+    /// Enable pre-arrival notifications
+    pub enabled: bool,
+    /// Notify when the ETA to the next waypoint drops below this
+    pub eta_threshold: Duration,
+    /// ETA must climb this far above the threshold before re-arming
+    pub hysteresis: Duration,
+    /// Waypoint types that get a pre-arrival notification
+    pub waypoint_types: Vec<WaypointType>,
+    /// Below this speed (km/h) the ETA is considered unknown
+    pub min_speed_kmh: f64,
+}
+
+impl Default for PreArrivalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            eta_threshold: Duration::from_secs(30),
+            hysteresis: Duration::from_secs(10),
+            waypoint_types: vec![WaypointType::Checkpoint],
+            min_speed_kmh: 1.0,
         }
     }
 }
@@ -85,10 +273,93 @@ pub struct DroneTracker {
     db: Option<Arc<DbClient>>,
     /// P2P manager (optional)
     p2p: Option<Arc<P2pManager>>,
+    /// Incoming P2P messages, shared so a restarted listener picks up
+    /// where the last one stopped
+    p2p_inbox: Option<Arc<tokio::sync::Mutex<mpsc::Receiver<InboundMessage>>>>,
+    /// Start time and failed command dispatches
+    health: SubsystemHealth,
     /// Event broadcaster
     event_tx: broadcast::Sender<Event>,
     /// Alert sender
     alert_tx: mpsc::Sender<Alert>,
+    /// Alert receiver, until taken by a consumer
+    alert_rx: Mutex<Option<mpsc::Receiver<Alert>>>,
+    /// Alert threshold overrides per drone
+    drone_thresholds: Arc<DashMap<DroneId, ThresholdOverrides>>,
+    /// Alert threshold overrides per drone type
+    type_thresholds: Arc<RwLock<HashMap<DroneType, ThresholdOverrides>>>,
+    /// Convoy formation manager
+    convoy: Arc<ConvoyManager>,
+    /// Emergency broadcast coordinator
+    emergency: Arc<EmergencyCoordinator>,
+    /// Telemetry sanitization and rejection counters
+    validator: Arc<TelemetryValidator>,
+    /// Current or most recent mission abort sequence
+    abort: Arc<RwLock<Option<AbortReport>>>,
+    /// Telemetry update intervals and gaps per mission
+    quality: Arc<DataQualityMonitor>,
+    /// Time source for telemetry, loiter timers and data quality windows
+    clock: Arc<SimulationClock>,
+    /// Time- and waypoint-triggered operator commands
+    scheduler: Arc<CommandScheduler>,
+    /// Fused GPS/CV position per drone
+    fusion: Arc<PositionFusion>,
+    /// Systematic CV error against GPS, and the correction applied to CV
+    drift: Arc<DriftMonitor>,
+    /// Status each drone had before a mesh partition made it unreachable
+    partitioned: Arc<DashMap<DroneId, DroneStatus>>,
+    /// Evicted drones whose persisted settings are reloaded if they return
+    evicted: Arc<DashSet<DroneId>>,
+    /// Drones waiting at checkpoints for an operator acknowledgment
+    checkpoints: Arc<CheckpointGate>,
+    /// Alert suppression windows
+    suppressor: Arc<AlertSuppressor>,
+    /// Duplicate and gap detection on numbered position reports
+    sequences: Arc<SequenceTracker>,
+    /// Designated telemetry source per drone
+    sources: Arc<TelemetrySources>,
+    /// Halo color and icon per drone
+    markings: Arc<FleetMarkings>,
+    /// Named drone groups for bulk commands
+    groups: Arc<GroupRegistry>,
+    /// Consumption tracking and reserve alerts
+    endurance: Arc<EnduranceProjector>,
+    /// Smoothed heading, climb rate, acceleration and turn rate per drone
+    motion: Arc<MotionEstimator>,
+    /// Wind grid estimated from the drift of every drone
+    wind: Arc<WindEstimator>,
+    /// Route versions published to drone agents and their sync state
+    mission_sync: Arc<MissionSync>,
+    /// Zones of interest and per-mission dwell statistics
+    zones: Arc<ZoneMonitor>,
+    /// Conditional alert rules
+    rules: Arc<RuleEngine>,
+    /// Schemas of integrator-defined event types
+    custom_events: Arc<CustomEventRegistry>,
+    /// Terrain line-of-sight prediction (None without an elevation model)
+    los: Option<Arc<LosMonitor>>,
+    /// Closest-point-of-approach prediction between drones
+    proximity: Arc<ProximityMonitor>,
+    /// Mission KPIs for the Prometheus export
+    kpis: Arc<MissionKpis>,
+    /// Drones handed off to other ground control stations
+    handoffs: Arc<HandoffRegistry>,
+    /// Per-drone command transports
+    commands: Arc<CommandDispatcher>,
+    /// CV result intake, once the publisher is running
+    cv: RwLock<Option<CvPipeline>>,
+    /// Live CV detection and tracking parameters
+    cv_tuning: Arc<CvTuningStore>,
+    /// Held while a CV parameter change is saved, so saves land in revision order
+    cv_tuning_updates: tokio::sync::Mutex<()>,
+    /// Drones over their leg's speed limit
+    speed_limits: Arc<SpeedLimitMonitor>,
+    /// Altitude band of every flying drone
+    altitude: Arc<AltitudeManager>,
+    /// Telemetry writes refused per drone after repeated failures
+    write_breakers: Arc<WriteBreakers>,
+    /// Statuses inferred from speed, staleness and return-to-base progress
+    status_inference: Arc<StatusInference>,
     /// Running state
     running: Arc<RwLock<bool>>,
 }
@@ -110,26 +381,43 @@ pub struct TrackedDrone {
     pub position_history: Vec<(DateTime<Utc>, GeoPosition)>,
     /// Alerts for this drone
     pub active_alerts: Vec<Alert>,
+    /// Waypoint index a pre-arrival notification was sent for
+    pub approach_notified: Option<usize>,
+    /// Holding at the last reached waypoint until this time
+    pub loiter_until: Option<DateTime<Utc>>,
+    /// Status to restore once the loiter or checkpoint hold ends
+    pub status_before_loiter: Option<DroneStatus>,
+    /// Where a go-direct leg began; progress runs from here instead of the
+    /// previous waypoint
+    pub direct_from: Option<GeoPosition>,
+    /// Arrival hysteresis at the current waypoint
+    pub arrival: ArrivalLatch,
 }
 
 impl TrackedDrone {
-    pub fn new(drone: Drone) -> Self {
+    /// Start tracking `drone` as of `at` (simulation clock time)
+    pub fn new(drone: Drone, at: DateTime<Utc>) -> Self {
         Self {
             drone,
             waypoint_index: 0,
             waypoint_progress: 0.0,
             //last_cv_result: None,
-            last_update: Utc::now(),
+            last_update: at,
             position_history: Vec::with_capacity(100),
             active_alerts: Vec::new(),
+            approach_notified: None,
+            loiter_until: None,
+            status_before_loiter: None,
+            direct_from: None,
+            arrival: ArrivalLatch::default(),
         }
     }
 
-    /// Update position and add to history
-    pub fn update_position(&mut self, position: GeoPosition, telemetry: Telemetry) {
+    /// Update position as of `at` (simulation clock time) and add to history
+    pub fn update_position_at(&mut self, position: GeoPosition, telemetry: Telemetry, at: DateTime<Utc>) {
         self.drone.position = position;
         self.drone.telemetry = telemetry;
-        self.last_update = Utc::now();
+        self.last_update = at;
 
         // Keep last 100 positions
         self.position_history.push((self.last_update, position));
@@ -138,20 +426,28 @@ impl TrackedDrone {
         }
     }
 
-    /// Check if drone is stale (no updates)
-    pub fn is_stale(&self, timeout: Duration) -> bool {
-        Utc::now().signed_duration_since(self.last_update)
-            > chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::seconds(30))
+    /// Check if drone is stale (no updates for longer than `timeout` at
+    /// simulation clock time `now`)
+    pub fn is_stale(&self, timeout: Duration, now: DateTime<Utc>) -> bool {
+        now - self.last_update > chrono::Duration::from_std(timeout).unwrap_or(chrono::Duration::MAX)
     }
 }
 
+/// A waypoint reached by a position update
+#[derive(Debug)]
+struct WaypointArrival {
+    waypoint_id: WaypointId,
+    /// Hold started because the waypoint is a checkpoint
+    checkpoint: Option<CheckpointHold>,
+}
+
 impl DroneTracker {
     /// Create a new drone tracker
     pub async fn new(config: TrackerConfig) -> anyhow::Result<Self> {
         info!("Initializing Drone Tracker...");
 
         let (event_tx, _) = broadcast::channel(1024);
-        let (alert_tx, _alert_rx) = mpsc::channel(256);
+        let (alert_tx, alert_rx) = mpsc::channel(256);
 
         // Initialize CV engine if enabled
         // let cv_engine = if config.cv_enabled {
@@ -171,7 +467,7 @@ impl DroneTracker {
 
         // Initialize P2P if enabled
         let p2p = if config.p2p_enabled {
-            match P2pManager::new(drone_p2p::P2pConfig::default()).await {
+            match P2pManager::new(drone_p2p::P2pConfig::from_env()).await {
                 Ok(manager) => {
                     info!("P2P network initialized");
                     Some(Arc::new(manager))
@@ -185,6 +481,49 @@ impl DroneTracker {
             None
         };
 
+        let type_thresholds = Arc::new(RwLock::new(config.type_thresholds.clone()));
+        let convoy = Arc::new(ConvoyManager::new());
+        let emergency = Arc::new(EmergencyCoordinator::new(
+            config.emergency_policy.clone(),
+            convoy.clone(),
+        ));
+
+        let validator = Arc::new(TelemetryValidator::new(config.telemetry_limits));
+        let quality = Arc::new(DataQualityMonitor::new(config.data_quality.clone()));
+        let fusion = Arc::new(PositionFusion::new(config.fusion.clone()));
+        let drift = Arc::new(DriftMonitor::new(config.drift.clone()));
+        let checkpoints = Arc::new(CheckpointGate::new(config.checkpoint.clone()));
+        let endurance = Arc::new(EnduranceProjector::new(config.endurance.clone()));
+        let motion = Arc::new(MotionEstimator::new(config.motion.clone()));
+        let wind = Arc::new(WindEstimator::new(config.wind.clone()));
+        let mission_sync = Arc::new(MissionSync::new(config.mission_sync.clone()));
+        let los = match LosMonitor::load(config.los.clone()) {
+            Ok(monitor) => monitor.map(Arc::new),
+            Err(e) => {
+                warn!("Terrain line-of-sight checks disabled: {}", e);
+                None
+            }
+        };
+        let proximity = Arc::new(ProximityMonitor::new(config.proximity.clone()));
+        let kpis = Arc::new(MissionKpis::new(config.kpi.clone(), Arc::new(MetricsCollector::new()?)));
+        let commands = Arc::new(CommandDispatcher::new());
+        let cv_tuning = Arc::new(CvTuningStore::new(config.cv_tuning.clone()));
+        let speed_limits = Arc::new(SpeedLimitMonitor::new(config.speed_limits.clone()));
+        let status_inference = Arc::new(StatusInference::new(config.status_inference.clone()));
+        let altitude = Arc::new(AltitudeManager::new(config.altitude.clone()));
+        let write_breakers = Arc::new(WriteBreakers::new(config.write_breaker.clone()));
+        if let Some(p2p) = &p2p {
+            commands.register(Arc::new(P2pTransport::new(p2p.clone())));
+        }
+        let clock = Arc::new(SimulationClock::new());
+        if let Some(p2p) = &p2p {
+            p2p.set_clock(clock.clone());
+        }
+        let p2p_inbox = p2p
+            .as_ref()
+            .and_then(|p2p| p2p.take_inbound_receiver())
+            .map(|rx| Arc::new(tokio::sync::Mutex::new(rx)));
+
         Ok(Self {
             config,
             drones: Arc::new(DashMap::new()),
@@ -192,8 +531,49 @@ impl DroneTracker {
             //cv_engine,
             db: None, // Set via set_database
             p2p,
+            p2p_inbox,
             event_tx,
             alert_tx,
+            alert_rx: Mutex::new(Some(alert_rx)),
+            drone_thresholds: Arc::new(DashMap::new()),
+            type_thresholds,
+            convoy,
+            emergency,
+            validator,
+            abort: Arc::new(RwLock::new(None)),
+            quality,
+            clock,
+            scheduler: Arc::new(CommandScheduler::new()),
+            fusion,
+            drift,
+            partitioned: Arc::new(DashMap::new()),
+            evicted: Arc::new(DashSet::new()),
+            checkpoints,
+            suppressor: Arc::new(AlertSuppressor::new()),
+            sequences: Arc::new(SequenceTracker::new()),
+            sources: Arc::new(TelemetrySources::new()),
+            markings: Arc::new(FleetMarkings::new()),
+            groups: Arc::new(GroupRegistry::new()),
+            endurance,
+            motion,
+            wind,
+            mission_sync,
+            zones: Arc::new(ZoneMonitor::new()),
+            rules: Arc::new(RuleEngine::new()),
+            custom_events: Arc::new(CustomEventRegistry::new()),
+            los,
+            proximity,
+            kpis,
+            handoffs: Arc::new(HandoffRegistry::new()),
+            commands,
+            cv: RwLock::new(None),
+            health: SubsystemHealth::default(),
+            cv_tuning,
+            cv_tuning_updates: tokio::sync::Mutex::new(()),
+            speed_limits,
+            status_inference,
+            altitude,
+            write_breakers,
             running: Arc::new(RwLock::new(false)),
         })
     }
@@ -203,56 +583,376 @@ impl DroneTracker {
         self.db = Some(db);
     }
 
+    /// Share a simulation clock (defaults to wall-clock time)
+    pub fn set_clock(&mut self, clock: Arc<SimulationClock>) {
+        if let Some(p2p) = &self.p2p {
+            p2p.set_clock(clock.clone());
+        }
+        self.clock = clock;
+    }
+
+    /// Time source used for telemetry processing
+    pub fn clock(&self) -> &Arc<SimulationClock> {
+        &self.clock
+    }
+
     /// Subscribe to events
     pub fn subscribe(&self) -> broadcast::Receiver<Event> {
         self.event_tx.subscribe()
     }
 
+    /// Mission KPI metrics (`drone_convoy_mission_*`)
+    pub fn metrics(&self) -> Arc<MetricsCollector> {
+        self.kpis.metrics().clone()
+    }
+
+    /// Take the alert receiver (only the first caller gets it)
+    pub fn take_alert_receiver(&self) -> Option<mpsc::Receiver<Alert>> {
+        self.alert_rx.lock().take()
+    }
+
     /// Register a drone for tracking
     pub fn register_drone(&self, drone: Drone) {
         let id = drone.id.clone();
-        self.drones.insert(id.clone(), TrackedDrone::new(drone));
+        // Freshness checks compare against the simulation clock
+        let tracked = TrackedDrone::new(drone, self.clock.now());
+        self.drones.insert(id.clone(), tracked);
         info!("Registered drone: {}", id);
+        if self.evicted.remove(&id).is_some() {
+            self.restore_drone_settings(&id);
+        }
+        self.enforce_drone_cap(&id);
+    }
+
+    /// Reload a returning drone's persisted designation, marking, alert
+    /// thresholds, command transport and handoff owner in the background
+    fn restore_drone_settings(&self, drone_id: &DroneId) {
+        let (Some(db), Ok(handle)) = (&self.db, tokio::runtime::Handle::try_current()) else {
+            return;
+        };
+        let db = db.clone();
+        let drone_id = drone_id.clone();
+        let (sources, markings, thresholds) = (self.sources.clone(), self.markings.clone(), self.drone_thresholds.clone());
+        let (commands, handoffs) = (self.commands.clone(), self.handoffs.clone());
+        handle.spawn(async move {
+            let drones = db.drones();
+            let restored = async {
+                let mine = |id: &DroneId| *id == drone_id;
+                if let Some((_, source)) = drones.get_telemetry_sources().await?.into_iter().find(|(id, _)| mine(id)) {
+                    sources.designate(&drone_id, Some(source));
+                }
+                if let Some((_, marking)) = drones.get_drone_markings().await?.into_iter().find(|(id, _)| mine(id)) {
+                    markings.set(&drone_id, Some(marking));
+                }
+                if let Some((_, overrides)) = drones.get_alert_thresholds().await?.into_iter().find(|(id, _)| mine(id)) {
+                    thresholds.insert(drone_id.clone(), overrides);
+                }
+                if let Some((_, binding)) = drones.get_transport_bindings().await?.into_iter().find(|(id, _)| mine(id)) {
+                    commands.bind(drone_id.clone(), binding);
+                }
+                if let Some((_, record)) = drones.get_handoff_owners().await?.into_iter().find(|(id, _)| mine(id)) {
+                    handoffs.mark_remote(RemoteOwner::from_record(drone_id.clone(), record));
+                }
+                anyhow::Ok(())
+            };
+            if let Err(e) = restored.await {
+                warn!("Failed to restore settings of returning drone {}: {}", drone_id, e);
+                db.health().record_error();
+            }
+        });
     }
 
-    /// Update drone position
+    /// Update drone position from a report posted to the API
     pub async fn update_drone_position(
         &self,
         drone_id: &DroneId,
         position: GeoPosition,
         telemetry: Telemetry,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), ReportRejected> {
+        self.update_drone_position_from(drone_id, TelemetrySource::Api, position, telemetry)
+            .await
+    }
+
+    /// Update drone position from a report received from `source`
+    pub async fn update_drone_position_from(
+        &self,
+        drone_id: &DroneId,
+        source: TelemetrySource,
+        position: GeoPosition,
+        telemetry: Telemetry,
+    ) -> Result<(), ReportRejected> {
+        let ingested = Instant::now();
+        let span = info_span!(
+            "position_update",
+            drone_id = %drone_id,
+            mission_id = tracing::field::Empty,
+            tenant_id = self.config.tenant.as_ref().map(TenantId::as_str),
+            source = %source,
+        );
+        if let Some(mission_id) = self.mission_id() {
+            span.record("mission_id", tracing::field::display(mission_id));
+        }
+        self.apply_position_update(drone_id, source, position, telemetry, ingested)
+            .instrument(span)
+            .await
+    }
+
+    /// The current mission's ID, for log spans
+    fn mission_id(&self) -> Option<MissionId> {
+        self.mission.read().as_ref().map(|m| m.id.clone())
+    }
+
+    async fn apply_position_update(
+        &self,
+        drone_id: &DroneId,
+        source: TelemetrySource,
+        position: GeoPosition,
+        telemetry: Telemetry,
+        ingested: Instant,
+    ) -> Result<(), ReportRejected> {
+        // Every ingestion path (simulation, P2P, API) funnels through here
+        if let Some(owner) = self.handoffs.owner(drone_id) {
+            return Err(ReportRejected::RemotelyControlled {
+                drone_id: drone_id.clone(),
+                station: owner.station,
+            });
+        }
+        // Reports for drones not tracked here leave no per-drone state behind
+        if !self.drones.contains_key(drone_id) {
+            debug!("Ignored report from unregistered drone {}", drone_id);
+            return Ok(());
+        }
+        if let SourceCheck::Conflict { designated, new } = self.sources.check(drone_id, source, self.clock.now()) {
+            if new {
+                self.raise_source_conflict_alert(drone_id, designated, source);
+            }
+            return Err(ReportRejected::WrongSource {
+                drone_id: drone_id.clone(),
+                designated,
+                reported: source,
+            });
+        }
+        // Numbered reports are processed once, however often gossip delivers them
+        if let Some(sequence) = telemetry.sequence {
+            match self.sequences.observe(drone_id, sequence, self.clock.now()) {
+                SequenceCheck::Fresh { missing } if missing > 0 => {
+                    warn!("Sequence gap for {}: {} reports missing before #{}", drone_id, missing, sequence);
+                }
+                SequenceCheck::Fresh { .. } => {}
+                SequenceCheck::Restarted => info!("Drone {} restarted its report sequence at #{}", drone_id, sequence),
+                SequenceCheck::Duplicate => {
+                    debug!("Dropped duplicate report #{} from {}", sequence, drone_id);
+                    return Ok(());
+                }
+                SequenceCheck::Late => {
+                    debug!("Dropped late report #{} from {}", sequence, drone_id);
+                    return Ok(());
+                }
+            }
+        }
+        let now = self.clock.now();
+        let telemetry = match self.validator.check_at(&position, telemetry, now) {
+            Ok(telemetry) => telemetry,
+            Err(e) => {
+                warn!("Rejected telemetry from {}: {}", drone_id, e);
+                return Err(e.into());
+            }
+        };
+
         if let Some(mut tracked) = self.drones.get_mut(drone_id) {
             let old_status = tracked.drone.status;
+
+            // The fused GPS/CV position is the authoritative one from here on
+            let gps = position;
+            let fused = self.fusion.fuse_gps(drone_id, position, now);
+            let drift = fused.cv_position.and_then(|cv| self.drift.record(cv, position, now));
+            let position = fused.position;
             
-            tracked.update_position(position, telemetry.clone());
+            // Most drones only report the gimbal when it moves
+            let mut telemetry = telemetry;
+            if telemetry.gimbal.is_none() {
+                telemetry.gimbal = tracked.drone.telemetry.gimbal;
+            }
+            tracked.update_position_at(position, telemetry.clone(), now);
+            let report = StatusReport {
+                status: old_status,
+                position,
+                speed_kmh: telemetry.speed,
+                heading: telemetry.heading,
+                origin: self.mission.read().as_ref().and_then(mission_origin),
+            };
+            let inferred = self.status_inference.observe(drone_id, &report, now);
+            if let Some(status) = inferred {
+                tracked.drone.status = status;
+            }
+            let motion = self.motion.observe(drone_id, position, &telemetry, now);
+            // Drift is measured between GPS fixes at the times the drone took them
+            self.wind.observe(drone_id, gps, &telemetry, telemetry.timestamp);
+            self.endurance.observe(drone_id, position, &telemetry, self.wind.wind_at(&position, now));
             
-            // Check waypoint progress
+            // Check waypoint progress (recalled drones have left the route)
+            let mut approach = None;
+            let mut reached: Option<WaypointArrival> = None;
+            let mut endurance_warning = None;
+            let mut speed_violation = None;
+            let on_route = tracked.drone.status != DroneStatus::Rtb;
+            if let Some(mission) = self.mission.read().as_ref().filter(|_| on_route) {
+                reached = self.check_waypoint_progress(&mut tracked, mission);
+                approach = self.check_waypoint_approach(&mut tracked, mission);
+                endurance_warning = self.check_endurance(&tracked, mission);
+                speed_violation = self.speed_limits.check(
+                    drone_id,
+                    tracked.waypoint_index,
+                    mission.speed_limit_to(tracked.waypoint_index),
+                    telemetry.speed,
+                    mission.enforce_speed_limits,
+                    now,
+                );
+            }
             if let Some(mission) = self.mission.read().as_ref() {
-                self.check_waypoint_progress(&mut tracked, mission);
+                self.kpis.update_duration(mission, now);
             }
+            let flying = matches!(
+                tracked.drone.status,
+                DroneStatus::Moving | DroneStatus::Engaged | DroneStatus::Loitering | DroneStatus::Rtb
+            );
+            let altitude_violation = flying
+                .then(|| self.altitude.observe(drone_id, position.altitude, now))
+                .flatten();
 
             // Check for alerts
             self.check_alerts(&tracked);
+            let rule_alerts = self.rules.evaluate(&RuleFacts {
+                drone: &tracked.drone,
+                mission: self.mission.read().as_ref(),
+                waypoint_index: tracked.waypoint_index,
+                motion: Some(motion),
+            });
+
+            // Release the map entry before awaiting on the database
+            let (status, armed) = (tracked.drone.status, tracked.drone.armed);
+            drop(tracked);
+
+            if let Some(status) = inferred {
+                debug!("Drone {} inferred {} (was {})", drone_id, status, old_status);
+                self.emit(Event::drone_status_changed(drone_id.clone(), old_status, status));
+            }
+
+            for alert in rule_alerts {
+                self.raise_alert(alert);
+            }
+
+            // The leader moving shifts every slot, so compliance is measured on its updates
+            if self.convoy.formation_leader().as_ref() == Some(drone_id) {
+                self.update_formation_compliance();
+            }
+
+            if let Some(hold) = reached.as_ref().and_then(|r| r.checkpoint.as_ref()) {
+                self.raise_checkpoint_alert(hold);
+            }
+
+            if let Some(projection) = &endurance_warning {
+                self.raise_endurance_alert(drone_id, projection);
+            }
+
+            if let Some(estimate) = &drift {
+                self.raise_drift_alert(estimate);
+            }
+
+            if let Some(violation) = &speed_violation {
+                self.enforce_speed_limit(violation).await;
+            }
+
+            if let Some(violation) = &altitude_violation {
+                self.enforce_altitude_band(violation).await;
+            }
+
+            if fused.disagreement_started {
+                let separation = fused.separation_m.unwrap_or_default();
+                warn!("GPS and CV positions for {} disagree by {:.0} m", drone_id, separation);
+                self.raise_alert(
+                    Alert::new(
+                        AlertSeverity::Warning,
+                        AlertType::Custom("POSITION_DISAGREEMENT".into()),
+                        format!("GPS and CV positions {:.0} m apart", separation),
+                    )
+                    .for_drone(drone_id.clone()),
+                );
+            }
+
+            // Gaps are measured between report timestamps, not arrivals
+            let mission_id = self.mission.read().as_ref().map(|m| m.id.clone());
+            let gap = mission_id
+                .as_ref()
+                .and_then(|mission_id| self.quality.record_update(drone_id, mission_id, telemetry.timestamp));
+            if let Some(gap) = &gap {
+                warn!(
+                    "Telemetry gap for {}: {:.1}s without updates",
+                    drone_id, gap.duration_seconds
+                );
+            }
 
             // Broadcast position update
-            let event = Event::drone_position_updated(
+            let mut event = Event::drone_position_updated(
                 drone_id.clone(),
                 position,
                 telemetry.clone(),
             );
-            let _ = self.event_tx.send(event);
+            let processed = Instant::now();
+            if let EventPayload::DronePosition(update) = &mut event.payload {
+                update.role = self.convoy.role(drone_id);
+                update.motion = Some(motion);
+                update.timings = Some(UpdateTimings::new(ingested, processed));
+            }
+            self.emit(event);
+            self.metrics().record_update_latency(LatencyHop::Tracker, processed - ingested);
+
+            let crossings = match &mission_id {
+                Some(mission_id) => self.zones.update(drone_id, mission_id, &position, now),
+                None => Vec::new(),
+            };
+            for crossing in &crossings {
+                self.emit(Event::zone_transition(crossing.event.clone()));
+            }
+
+            if let Some(approach) = approach {
+                self.notify_waypoint_approach(approach).await;
+            }
+
+            if let Some(arrival) = reached {
+                let fired = self.scheduler.waypoint_reached(drone_id, &arrival.waypoint_id, now);
+                self.execute_scheduled(fired).await;
+            }
 
             // Persist to database
             if let Some(db) = &self.db {
-                let mission_id = self.mission.read().as_ref().map(|m| m.id.clone());
-                if let Err(e) = db.telemetry().insert(
+                let record = drone_db::TelemetryRecord::new(
                     drone_id,
                     &position,
                     &telemetry,
+                    status,
+                    armed,
                     mission_id.as_ref(),
-                ).await {
-                    warn!("Failed to persist telemetry: {}", e);
+                );
+                self.persist_telemetry(db, drone_id, record, now)
+                    .instrument(info_span!(
+                        "persist_telemetry",
+                        drone_id = %drone_id,
+                        mission_id = mission_id.as_ref().map(tracing::field::display),
+                        tenant_id = self.config.tenant.as_ref().map(TenantId::as_str),
+                    ))
+                    .await;
+                if let Some(gap) = &gap {
+                    if let Err(e) = db.quality().record_gap(&gap.into()).await {
+                        warn!("Failed to persist telemetry gap: {}", e);
+                        db.health().record_error();
+                    }
+                }
+                for crossing in &crossings {
+                    if let Err(e) = db.zones().save_dwell(&crossing.record).await {
+                        warn!("Failed to persist zone dwell: {}", e);
+                        db.health().record_error();
+                    }
                 }
             }
         }
@@ -260,19 +960,34 @@ impl DroneTracker {
         Ok(())
     }
 
-    /// Check and update waypoint progress
-    fn check_waypoint_progress(&self, tracked: &mut TrackedDrone, mission: &Mission) {
+    /// Check and update waypoint progress, returning the waypoint just reached
+    fn check_waypoint_progress(
+        &self,
+        tracked: &mut TrackedDrone,
+        mission: &Mission,
+    ) -> Option<WaypointArrival> {
+        // Progress is paused until the checkpoint is acknowledged
+        if self.checkpoints.is_held(&tracked.drone.id) {
+            return None;
+        }
+
+        // Progress is paused while holding at a loiter waypoint
+        if let Some(until) = tracked.loiter_until {
+            if self.clock.now() < until {
+                return None;
+            }
+            self.end_loiter(tracked, mission);
+        }
+
         if tracked.waypoint_index >= mission.waypoints.len() {
-            return;
+            return None;
         }
 
         let current_wp = &mission.waypoints[tracked.waypoint_index];
-        let distance = tracked.drone.position.distance_to(&current_wp.position);
+        let position = tracked.drone.position;
+        let now = self.clock.now();
 
-        // Convert to meters
-        let distance_meters = distance * 1000.0;
-
-        if distance_meters < self.config.waypoint_threshold_meters {
+        if tracked.arrival.observe(&self.config.arrival, current_wp, &position, now) {
             // Reached waypoint
             info!(
                 "Drone {} reached waypoint {}",
@@ -284,179 +999,4159 @@ impl DroneTracker {
                 tracked.drone.id.clone(),
                 current_wp.id.clone(),
                 tracked.drone.position,
-            );
-            let _ = self.event_tx.send(event);
+            )
+            .with_mission(mission.id.clone());
+            self.send_event(event);
+
+            let mut checkpoint = None;
+            if current_wp.waypoint_type == WaypointType::Checkpoint && self.checkpoints.enabled() {
+                info!(
+                    "Drone {} holding at checkpoint {} for acknowledgment",
+                    tracked.drone.id, current_wp.name
+                );
+                checkpoint = Some(self.checkpoints.hold(
+                    &tracked.drone.id,
+                    &current_wp.id,
+                    &current_wp.name,
+                    self.clock.now(),
+                ));
+                tracked.status_before_loiter = Some(tracked.drone.status);
+                self.change_status(tracked, mission, DroneStatus::Loitering);
+            } else if let Some(seconds) = current_wp.loiter_time_seconds.filter(|s| *s > 0) {
+                info!(
+                    "Drone {} loitering at {} for {}s",
+                    tracked.drone.id, current_wp.name, seconds
+                );
+                tracked.loiter_until = Some(self.clock.now() + chrono::Duration::seconds(seconds as i64));
+                tracked.status_before_loiter = Some(tracked.drone.status);
+                self.change_status(tracked, mission, DroneStatus::Loitering);
+            }
+
+            self.kpis.waypoint_reached(&tracked.drone.id, mission, tracked.waypoint_index, self.clock.now());
 
             // Advance to next waypoint
             tracked.waypoint_index += 1;
             tracked.waypoint_progress = 0.0;
-        } else if tracked.waypoint_index > 0 {
-            // Calculate progress between waypoints
-            let prev_wp = &mission.waypoints[tracked.waypoint_index - 1];
-            let total_distance = prev_wp.position.distance_to(&current_wp.position);
+            tracked.direct_from = None;
+            return Some(WaypointArrival {
+                waypoint_id: current_wp.id.clone(),
+                checkpoint,
+            });
+        } else if tracked.waypoint_index > 0 || tracked.direct_from.is_some() {
+            // Calculate progress between waypoints, or along a go-direct leg
+            let total_distance = match (tracked.direct_from, mission.leg_to(tracked.waypoint_index)) {
+                (Some(from), _) => from.distance_to(&current_wp.position),
+                (None, Some(leg)) => leg.distance_km,
+                (None, None) => mission.waypoints[tracked.waypoint_index - 1]
+                    .position
+                    .distance_to(&current_wp.position),
+            };
             let remaining = tracked.drone.position.distance_to(&current_wp.position);
             
             if total_distance > 0.0 {
                 tracked.waypoint_progress = 1.0 - (remaining / total_distance);
             }
         }
+        None
+    }
+
+    /// Leave the loiter or checkpoint waypoint once the hold is over
+    fn end_loiter(&self, tracked: &mut TrackedDrone, mission: &Mission) {
+        tracked.loiter_until = None;
+        let previous = tracked.status_before_loiter.take().unwrap_or(DroneStatus::Moving);
+
+        if let Some(waypoint) = tracked
+            .waypoint_index
+            .checked_sub(1)
+            .and_then(|i| mission.waypoints.get(i))
+        {
+            info!("Drone {} departed waypoint {}", tracked.drone.id, waypoint.name);
+            self.kpis.waypoint_departed(&tracked.drone.id, &waypoint.id, self.clock.now());
+            let event = Event::waypoint_departed(
+                tracked.drone.id.clone(),
+                waypoint.id.clone(),
+                tracked.drone.position,
+            )
+            .with_mission(mission.id.clone());
+            self.send_event(event);
+        }
+
+        // Leave statuses set elsewhere during the hold (e.g. emergencies) alone
+        if tracked.drone.status == DroneStatus::Loitering {
+            self.change_status(tracked, mission, previous);
+        }
+    }
+
+    /// Change a drone's status from within a mission update
+    fn change_status(&self, tracked: &mut TrackedDrone, mission: &Mission, status: DroneStatus) {
+        let old_status = tracked.drone.status;
+        if old_status == status {
+            return;
+        }
+        tracked.drone.status = status;
+        let event = Event::drone_status_changed(tracked.drone.id.clone(), old_status, status)
+            .with_mission(mission.id.clone());
+        self.send_event(event);
+    }
+
+    /// Detect the ETA to the next notifiable waypoint crossing the pre-arrival threshold
+    fn check_waypoint_approach(
+        &self,
+        tracked: &mut TrackedDrone,
+        mission: &Mission,
+    ) -> Option<WaypointApproachEvent> {
+        let config = &self.config.pre_arrival;
+        if !config.enabled || tracked.loiter_until.is_some() {
+            return None;
+        }
+
+        let index = tracked.waypoint_index;
+        let waypoint = mission.waypoints.get(index)?;
+        if !config.waypoint_types.contains(&waypoint.waypoint_type) {
+            return None;
+        }
+
+        let speed_kmh = tracked.drone.telemetry.speed;
+        if speed_kmh < config.min_speed_kmh {
+            return None;
+        }
+        let position = tracked.drone.position;
+        let distance_meters = position.distance_to(&waypoint.position) * 1000.0;
+        // Against a wind it cannot make headway in the ETA is unknown
+        let ground_speed_ms = match self.wind.wind_at(&position, self.clock.now()) {
+            Some(wind) => wind.ground_speed_ms(speed_kmh / 3.6, position.bearing_to(&waypoint.position))?,
+            None => speed_kmh / 3.6,
+        };
+        let eta_seconds = distance_meters / ground_speed_ms;
+        let threshold = config.eta_threshold.as_secs_f64();
+
+        if tracked.approach_notified == Some(index) {
+            // Re-arm only once the drone is clearly outside the threshold again
+            if eta_seconds > threshold + config.hysteresis.as_secs_f64() {
+                tracked.approach_notified = None;
+            }
+            return None;
+        }
+
+        if eta_seconds > threshold {
+            return None;
+        }
+
+        tracked.approach_notified = Some(index);
+        Some(WaypointApproachEvent {
+            drone_id: tracked.drone.id.clone(),
+            waypoint_id: waypoint.id.clone(),
+            waypoint_name: waypoint.name.clone(),
+            position: tracked.drone.position,
+            distance_meters,
+            eta_seconds,
+        })
+    }
+
+    /// Publish a pre-arrival notification to subscribers and the P2P mesh
+    async fn notify_waypoint_approach(&self, approach: WaypointApproachEvent) {
+        info!(
+            "Drone {} approaching waypoint {} (ETA {:.0}s)",
+            approach.drone_id, approach.waypoint_name, approach.eta_seconds
+        );
+
+        if let Some(p2p) = &self.p2p {
+            let message = DroneMessage::waypoint_approaching(
+                approach.drone_id.clone(),
+                approach.waypoint_id.clone(),
+                approach.distance_meters,
+                approach.eta_seconds,
+            );
+            if let Err(e) = p2p.broadcast(message).await {
+                warn!("Failed to broadcast waypoint approach: {}", e);
+                self.health.record_error();
+            }
+        }
+
+        self.emit(Event::waypoint_approaching(approach));
     }
 
     /// Check for alert conditions
     fn check_alerts(&self, tracked: &TrackedDrone) {
         let drone = &tracked.drone;
         let id = &drone.id;
+        let thresholds = self.thresholds_for(drone);
 
         // Battery alerts
-        if drone.telemetry.battery_level < self.config.battery_critical_threshold {
+        if drone.telemetry.battery_level < thresholds.battery_critical {
             let alert = Alert::new(
                 AlertSeverity::Critical,
                 AlertType::BatteryLow,
                 format!("Battery critical: {}%", drone.telemetry.battery_level),
             ).for_drone(id.clone());
             
-            let _ = self.alert_tx.try_send(alert);
-        } else if drone.telemetry.battery_level < self.config.battery_warning_threshold {
+            self.send_alert(alert, Some(drone.status));
+        } else if drone.telemetry.battery_level < thresholds.battery_warning {
             let alert = Alert::new(
                 AlertSeverity::Warning,
                 AlertType::BatteryLow,
                 format!("Battery low: {}%", drone.telemetry.battery_level),
             ).for_drone(id.clone());
             
-            let _ = self.alert_tx.try_send(alert);
+            self.send_alert(alert, Some(drone.status));
         }
 
         // Fuel alerts
-        if drone.telemetry.fuel_level < self.config.fuel_critical_threshold {
+        if drone.telemetry.fuel_level < thresholds.fuel_critical {
             let alert = Alert::new(
                 AlertSeverity::Critical,
                 AlertType::FuelLow,
                 format!("Fuel critical: {}%", drone.telemetry.fuel_level),
             ).for_drone(id.clone());
             
-            let _ = self.alert_tx.try_send(alert);
+            self.send_alert(alert, Some(drone.status));
+        } else if drone.telemetry.fuel_level < thresholds.fuel_warning {
+            let alert = Alert::new(
+                AlertSeverity::Warning,
+                AlertType::FuelLow,
+                format!("Fuel low: {}%", drone.telemetry.fuel_level),
+            ).for_drone(id.clone());
+            
+            self.send_alert(alert, Some(drone.status));
         }
-    }
 
-    /// Set active mission
-    pub fn set_mission(&self, mission: Mission) {
-        *self.mission.write() = Some(mission);
+        // Signal alerts, graded by convoy role
+        let policy = &self.config.role_alerts;
+        if drone.telemetry.signal_strength < policy.signal_weak_below {
+            let role = self.convoy.role(id);
+            let alert = Alert::new(
+                policy.signal_loss_severity(role),
+                AlertType::SignalLost,
+                format!("Signal weak: {}%{}", drone.telemetry.signal_strength, role_suffix(role)),
+            ).for_drone(id.clone());
+
+            self.send_alert(alert, Some(drone.status));
+        }
     }
 
-    /// Get active mission
-    pub fn get_mission(&self) -> Option<Mission> {
-        self.mission.read().clone()
+    // ========================================================================
+    // EMERGENCY HANDLING
+    // ========================================================================
+
+    /// Convoy formation manager
+    pub fn convoy(&self) -> Arc<ConvoyManager> {
+        self.convoy.clone()
     }
 
-    /// Get all tracked drones
-    pub fn get_all_drones(&self) -> Vec<TrackedDrone> {
-        self.drones.iter().map(|r| r.value().clone()).collect()
+    /// Assign a drone's convoy role (`None` clears it); `false` for unknown drones
+    pub fn set_drone_role(&self, drone_id: &DroneId, role: Option<ConvoyRole>) -> bool {
+        if !self.drones.contains_key(drone_id) {
+            return false;
+        }
+        self.convoy.set_role(drone_id, role);
+        true
     }
 
-    /// Get specific drone
-    pub fn get_drone(&self, id: &DroneId) -> Option<TrackedDrone> {
-        self.drones.get(id).map(|r| r.value().clone())
+    /// Emergency broadcast coordinator
+    pub fn emergency(&self) -> Arc<EmergencyCoordinator> {
+        self.emergency.clone()
     }
 
-    /// Get drone count
-    pub fn drone_count(&self) -> usize {
-        self.drones.len()
+    /// Change a drone's status, emitting a status event if it changed.
+    /// `false` if the drone is unknown or handed off, or its status cannot
+    /// change to `status` (see `DroneStatus::can_transition_to`).
+    pub fn set_drone_status(&self, drone_id: &DroneId, status: DroneStatus) -> bool {
+        if self.handoffs.is_remote(drone_id) {
+            return false;
+        }
+        let Some(mut tracked) = self.drones.get_mut(drone_id) else {
+            return false;
+        };
+
+        let old_status = tracked.drone.status;
+        if !old_status.can_transition_to(status) {
+            debug!("Drone {} cannot change from {} to {}", drone_id, old_status, status);
+            return false;
+        }
+        if old_status != status {
+            tracked.drone.status = status;
+            drop(tracked);
+            self.emit(Event::drone_status_changed(drone_id.clone(), old_status, status));
+        }
+        true
     }
 
-    /// Start the tracking engine
-    pub async fn start(&self) -> anyhow::Result<()> {
-        *self.running.write() = true;
-        info!("🚀 Drone Tracker started");
-        
-        // Start P2P if available
-        if let Some(p2p) = &self.p2p {
-            p2p.start().await?;
+    /// Raise an alert on the alert channel and the event stream, stamped
+    /// with the tracker's clock
+    pub fn raise_alert(&self, mut alert: Alert) {
+        alert.created_at = self.clock.now();
+        let status = alert
+            .drone_id
+            .as_ref()
+            .and_then(|id| self.inspect_drone(id, |t| t.drone.status));
+        if self.suppressor.suppresses(&alert, status, self.clock.now()) {
+            return;
+        }
+        if let Some(drone_id) = &alert.drone_id {
+            if let Some(mut tracked) = self.drones.get_mut(drone_id) {
+                tracked.active_alerts.push(alert.clone());
+                self.trim_alerts(&mut tracked);
+            }
         }
+        self.count_mission_alert(&alert);
+        self.emit(Event::alert(alert.clone()));
+        let _ = self.alert_tx.try_send(alert);
+    }
 
-        Ok(())
+    /// Queue an alert for the alert consumers unless a suppression window covers it
+    fn send_alert(&self, mut alert: Alert, status: Option<DroneStatus>) {
+        alert.created_at = self.clock.now();
+        if !self.suppressor.suppresses(&alert, status, self.clock.now()) {
+            self.count_mission_alert(&alert);
+            let _ = self.alert_tx.try_send(alert);
+        }
     }
 
-    /// Stop the tracking engine
-    pub async fn stop(&self) -> anyhow::Result<()> {
-        *self.running.write() = false;
-        info!("🛑 Drone Tracker stopped");
+    fn count_mission_alert(&self, alert: &Alert) {
+        if let Some(mission) = self.mission.read().as_ref() {
+            self.kpis.alert_raised(&mission.id, alert.severity);
+        }
+    }
 
-        if let Some(p2p) = &self.p2p {
-            p2p.stop().await?;
+    /// Measure how many followers hold their formation slot
+    fn update_formation_compliance(&self) {
+        let Some(mission_id) = self.mission.read().as_ref().map(|m| m.id.clone()) else {
+            return;
+        };
+        let positions: HashMap<DroneId, (GeoPosition, f64)> = self
+            .drones
+            .iter()
+            .map(|t| (t.key().clone(), (t.drone.position, t.drone.telemetry.heading)))
+            .collect();
+        let tolerance = self.kpis.config().formation_tolerance_meters;
+        if let Some(percent) = self.convoy.compliance_percent(&positions, tolerance) {
+            self.kpis.update_formation(&mission_id, percent);
         }
+    }
 
-        Ok(())
+    /// Broadcast an event tagged with the active mission
+    fn emit(&self, event: Event) {
+        let mission_id = self.mission.read().as_ref().map(|m| m.id.clone());
+        let event = match mission_id {
+            Some(mission_id) => event.with_mission(mission_id),
+            None => event,
+        };
+        self.send_event(event);
     }
 
-    /// Check if tracker is running
-    pub fn is_running(&self) -> bool {
-        *self.running.read()
+    /// Broadcast an event stamped on the tracker's clock, which orders the
+    /// event history with telemetry and custom events
+    fn send_event(&self, mut event: Event) {
+        event.timestamp = self.clock.now();
+        let _ = self.event_tx.send(event);
     }
-}
 
-// ============================================================================
-// TESTS
-// ============================================================================
+    /// Handle an emergency broadcast from a drone
+    pub fn handle_emergency(&self, emergency: &EmergencyData) -> EmergencyResponse {
+        warn!(
+            "Emergency from {}: {:?} - {}",
+            emergency.drone_id, emergency.emergency_type, emergency.message
+        );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let mut response = self.emergency.respond(emergency, &self.get_all_drones());
 
-    #[tokio::test]
-    async fn test_tracker_creation() {
-        let config = TrackerConfig {
-            //cv_enabled: false,
-            p2p_enabled: false,
-            db_enabled: false,
-            ..Default::default()
-        };
-        
-        let tracker = DroneTracker::new(config).await;
-        assert!(tracker.is_ok());
+        for action in &response.actions {
+            if let ResponseAction::MarkDrone { drone_id, status } = action {
+                // A lost link to a drone peers can still reach is a partition
+                let status = match status {
+                    DroneStatus::Offline if self.reported_alive(drone_id) => DroneStatus::Unreachable,
+                    status => *status,
+                };
+                self.set_drone_status(drone_id, status);
+            }
+        }
+        if let Some(alert) = response.alert.take() {
+            self.raise_alert(alert);
+        }
+
+        response
     }
 
-    #[tokio::test]
-    async fn test_drone_registration() {
-        let config = TrackerConfig {
-            //cv_enabled: false,
-            p2p_enabled: false,
-            db_enabled: false,
-            ..Default::default()
+    /// Dispatch an incoming P2P message
+    pub fn handle_p2p_message(&self, message: &DroneMessage) -> Option<EmergencyResponse> {
+        if let Some(p2p) = &self.p2p {
+            p2p.observe(message, self.clock.now());
+        }
+
+        match &message.message_type {
+            MessageType::Emergency(data) => Some(self.handle_emergency(data)),
+            MessageType::Ack(ack) if ack.success => {
+                self.acknowledge_abort(&ack.drone_id, Some(ack.message_id));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Spawn a task feeding P2P messages into the tracker
+    ///
+    /// Copies of messages already handled are dropped. Discovery requests
+    /// are answered with the ground station's capabilities and mission
+    /// sync messages with route chunks, diffs or manifests. Position updates pass through the P2P jitter buffer
+    /// and are applied in timestamp order once their hold window passes.
+    pub fn spawn_p2p_listener(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let p2p = self.p2p.clone()?;
+        let inbox = self.p2p_inbox.clone()?;
+        let tracker = Arc::clone(self);
+
+        Some(tokio::spawn(async move {
+            let mut rx = inbox.lock().await;
+            let mut release = tokio::time::interval((p2p.jitter_window() / 4).max(Duration::from_millis(10)));
+            loop {
+                tokio::select! {
+                    inbound = rx.recv() => {
+                        let Some(inbound) = inbound else {
+                            break;
+                        };
+                        let message = &inbound.message;
+                        if !p2p.accept(message) {
+                            debug!("Dropped duplicate P2P message {}", message.id);
+                            continue;
+                        }
+                        tracker.handle_p2p_message(message);
+                        tracker.answer_mission_sync(&p2p, &inbound).await;
+                        if matches!(message.message_type, MessageType::DiscoveryRequest) {
+                            let response = p2p.discovery_response(DroneId::new(abort::GROUND_STATION_ID));
+                            if let Err(e) = p2p.broadcast(response).await {
+                                debug!("Failed to answer discovery request: {}", e);
+                            }
+                        }
+                        p2p.buffer_position(message, Utc::now());
+                    }
+                    _ = release.tick() => {}
+                }
+
+                for update in p2p.release_positions(Utc::now()) {
+                    if let Err(e) = tracker
+                        .update_drone_position_from(&update.drone_id, TelemetrySource::P2p, update.position, update.telemetry)
+                        .await
+                    {
+                        debug!("Dropped P2P position update: {}", e);
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Jitter buffer counters for P2P position updates (`None` without P2P)
+    pub fn p2p_jitter_stats(&self) -> Option<JitterStats> {
+        Some(self.p2p.as_ref()?.jitter_stats())
+    }
+
+    /// Link shaping decisions per priority (`None` without P2P)
+    pub fn p2p_shaping_stats(&self) -> Option<ShapingStats> {
+        Some(self.p2p.as_ref()?.shaping_stats())
+    }
+
+    /// P2P uptime, errors and traffic counters (`None` without P2P)
+    pub fn p2p_stats(&self) -> Option<P2pStats> {
+        Some(self.p2p.as_ref()?.stats(Utc::now()))
+    }
+
+    // ========================================================================
+    // MISSION SYNC
+    // ========================================================================
+
+    /// Route version and sync state of every drone flying the mission
+    pub fn mission_sync_status(&self) -> MissionSyncView {
+        self.mission_sync.status(&self.mission_sync_drones())
+    }
+
+    /// Drones assigned to the active mission, or every tracked drone if
+    /// none are
+    fn mission_sync_drones(&self) -> Vec<DroneId> {
+        let assigned = self.mission.read().as_ref().map(|m| m.assigned_drones.clone()).unwrap_or_default();
+        if !assigned.is_empty() {
+            return assigned;
+        }
+        let mut drone_ids: Vec<DroneId> = self.drones.iter().map(|r| r.key().clone()).collect();
+        drone_ids.sort();
+        drone_ids
+    }
+
+    /// Send the agent behind a chunk request or sync report what it needs.
+    /// Only the peer registered for the drone may speak for it.
+    async fn answer_mission_sync(&self, p2p: &P2pManager, inbound: &InboundMessage) {
+        let drone_id = match &inbound.message.message_type {
+            MessageType::MissionChunkRequest(request) => &request.drone_id,
+            MessageType::MissionSynced(report) => &report.drone_id,
+            _ => return,
+        };
+        if p2p.get_drone_peer(drone_id) != Some(inbound.peer_id) {
+            warn!("Ignored mission sync for {} from peer {}, which is not registered to it", drone_id, inbound.peer_id);
+            return;
+        }
+        let now = Utc::now();
+        let reply = match &inbound.message.message_type {
+            MessageType::MissionChunkRequest(request) => self.mission_sync.chunks_requested(request, now),
+            MessageType::MissionSynced(report) => self.mission_sync.synced(report, now),
+            _ => return,
+        };
+        let sender = DroneId::new(abort::GROUND_STATION_ID);
+        let replies = match reply {
+            Some(SyncReply::Chunks(chunks)) => chunks
+                .into_iter()
+                .map(|chunk| DroneMessage::mission_chunk(sender.clone(), chunk))
+                .collect(),
+            Some(SyncReply::Diff(diff)) => vec![DroneMessage::mission_diff(sender, diff)],
+            Some(SyncReply::Manifest(manifest)) => vec![DroneMessage::mission_manifest(sender, manifest)],
+            None => Vec::new(),
+        };
+        for reply in replies {
+            if let Err(e) = p2p.send_to_drone(drone_id, reply).await {
+                debug!("Failed to answer mission sync from {}: {}", drone_id, e);
+            }
+        }
+    }
+
+    /// Spawn a task broadcasting route versions as they are published
+    ///
+    /// A new version goes out as its diff from the previous version, then
+    /// its manifest. The manifest is broadcast again every
+    /// `announce_interval` while a drone flying the mission is out of sync,
+    /// for drones that joined late or missed the update.
+    pub fn spawn_mission_sync(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let p2p = self.p2p.clone()?;
+        let tracker = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let sender = DroneId::new(abort::GROUND_STATION_ID);
+            let mut announce = tokio::time::interval(tracker.mission_sync.config().announce_interval);
+            loop {
+                let messages: Vec<DroneMessage> = tokio::select! {
+                    _ = tracker.mission_sync.published() => match tracker.mission_sync.take_publication() {
+                        Some(publication) => publication
+                            .diff
+                            .map(|diff| DroneMessage::mission_diff(sender.clone(), diff))
+                            .into_iter()
+                            .chain([DroneMessage::mission_manifest(sender.clone(), publication.manifest)])
+                            .collect(),
+                        None => Vec::new(),
+                    },
+                    _ = announce.tick() => {
+                        if tracker.mission_sync.all_synced(&tracker.mission_sync_drones()) {
+                            Vec::new()
+                        } else {
+                            tracker
+                                .mission_sync
+                                .manifest()
+                                .map(|manifest| DroneMessage::mission_manifest(sender.clone(), manifest))
+                                .into_iter()
+                                .collect()
+                        }
+                    }
+                };
+                for message in messages {
+                    if let Err(e) = p2p.broadcast(message).await {
+                        debug!("Failed to broadcast mission route: {}", e);
+                    }
+                }
+            }
+        }))
+    }
+
+    // ========================================================================
+    // MESH PARTITIONS
+    // ========================================================================
+
+    /// Reclassify drone reachability and apply the result
+    ///
+    /// Drones cut off by a partition are marked `Unreachable`; once heard
+    /// again they get back their previous status and any buffered direct
+    /// messages are replayed.
+    pub async fn check_reachability(&self) {
+        let Some(p2p) = &self.p2p else {
+            return;
+        };
+        let changes = p2p.assess_reachability(self.clock.now()).await;
+
+        for drone_id in changes.lost {
+            let Some(status) = self.get_drone(&drone_id).map(|t| t.drone.status) else {
+                continue;
+            };
+            warn!("Drone {} is unreachable behind a mesh partition", drone_id);
+            if status != DroneStatus::Unreachable {
+                self.partitioned.insert(drone_id.clone(), status);
+                let role = self.convoy.role(&drone_id);
+                self.raise_alert(
+                    Alert::new(
+                        self.config.role_alerts.signal_loss_severity(role),
+                        AlertType::SignalLost,
+                        format!("Unreachable behind a mesh partition{}", role_suffix(role)),
+                    )
+                    .for_drone(drone_id.clone()),
+                );
+            }
+            self.set_drone_status(&drone_id, DroneStatus::Unreachable);
+        }
+        for drone_id in changes.healed {
+            info!("Mesh partition healed for drone {}", drone_id);
+            let previous = self.partitioned.remove(&drone_id).map(|(_, status)| status);
+            let is_unreachable = self
+                .get_drone(&drone_id)
+                .is_some_and(|t| t.drone.status == DroneStatus::Unreachable);
+            if is_unreachable {
+                self.set_drone_status(&drone_id, previous.unwrap_or(DroneStatus::Moving));
+            }
+        }
+    }
+
+    /// Spawn a task checking reachability every P2P heartbeat interval
+    pub fn spawn_partition_monitor(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let period = self.p2p.as_ref()?.heartbeat_interval();
+        let tracker = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                tracker.check_reachability().await;
+            }
+        }))
+    }
+
+    /// Drone reachability and mesh partitions (`None` without P2P)
+    pub fn reachability(&self) -> Option<ReachabilityView> {
+        Some(self.p2p.as_ref()?.reachability(self.clock.now()))
+    }
+
+    /// This node's view of the mesh peers (`None` without P2P)
+    pub fn mesh_topology(&self) -> Option<MeshTopology> {
+        Some(self.p2p.as_ref()?.topology(self.clock.now()))
+    }
+
+    /// Peer allow-list mode, registrations and rejections (`None` without P2P)
+    pub fn peer_allow_list(&self) -> Option<AllowListView> {
+        Some(self.p2p.as_ref()?.allow_list())
+    }
+
+    /// Register a drone's peer public key (`None` without P2P)
+    pub fn register_peer_key(&self, drone_id: &DroneId, public_key: &str) -> Option<P2pResult<String>> {
+        let p2p = self.p2p.as_ref()?;
+        Some(p2p.register_peer_key(drone_id.clone(), public_key).map(|peer_id| peer_id.to_string()))
+    }
+
+    /// Remove a drone's peer registration, returning the peer ID it had
+    /// (`None` without P2P)
+    pub fn unregister_peer(&self, drone_id: &DroneId) -> Option<Option<String>> {
+        Some(self.p2p.as_ref()?.unregister_drone(drone_id).map(|peer_id| peer_id.to_string()))
+    }
+
+    fn reported_alive(&self, drone_id: &DroneId) -> bool {
+        self.p2p
+            .as_ref()
+            .is_some_and(|p2p| p2p.reported_alive(drone_id, self.clock.now()))
+    }
+
+    /// Telemetry validation counters
+    pub fn telemetry_validator(&self) -> Arc<TelemetryValidator> {
+        self.validator.clone()
+    }
+
+    // ========================================================================
+    // MISSION ABORT
+    // ========================================================================
+
+    /// Start the abort sequence for the active mission
+    ///
+    /// Sends the abort command to every assigned drone and sets it to `Rtb`.
+    /// The mission is marked `Aborted` once all drones acknowledge, or when
+    /// the acknowledgment timeout expires.
+    pub async fn begin_abort(self: &Arc<Self>, reason: Option<String>) -> anyhow::Result<AbortReport> {
+        let mission = self
+            .get_mission()
+            .ok_or_else(|| anyhow::anyhow!("No active mission"))?;
+        if matches!(mission.status, MissionStatus::Completed | MissionStatus::Aborted) {
+            anyhow::bail!("Mission {} is already {:?}", mission.name, mission.status);
+        }
+        if let Some(report) = self.abort.read().as_ref() {
+            if report.mission_id == mission.id && report.state == AbortState::InProgress {
+                return Ok(report.clone());
+            }
+        }
+
+        let policy = &self.config.abort_policy;
+        let drone_ids: Vec<DroneId> = if mission.assigned_drones.is_empty() {
+            self.drones.iter().map(|d| d.key().clone()).collect()
+        } else {
+            mission.assigned_drones.clone()
+        };
+
+        warn!("Aborting mission {}: recalling {} drones", mission.name, drone_ids.len());
+
+        let ground_station = DroneId::new(abort::GROUND_STATION_ID);
+        let mut statuses = Vec::with_capacity(drone_ids.len());
+        let mut commands = Vec::with_capacity(drone_ids.len());
+        for drone_id in drone_ids {
+            let Some(tracked) = self.get_drone(&drone_id) else {
+                warn!("Assigned drone {} is not tracked; skipping abort command", drone_id);
+                continue;
+            };
+            let message = DroneMessage::command(
+                ground_station.clone(),
+                drone_id.clone(),
+                policy.command.into(),
+            );
+            statuses.push(DroneAbortStatus::snapshot(&tracked, message.id));
+            commands.push((drone_id, message));
+        }
+
+        let report = AbortReport::new(&mission, reason, policy.command, statuses);
+        *self.abort.write() = Some(report.clone());
+
+        for (drone_id, message) in commands {
+            if let Some(p2p) = &self.p2p {
+                if let Err(e) = p2p.send_to_drone(&drone_id, message).await {
+                    warn!("Failed to send abort command to {}: {}", drone_id, e);
+                    self.health.record_error();
+                }
+            }
+            self.status_inference.return_commanded(&drone_id, self.clock.now());
+            self.set_drone_status(&drone_id, DroneStatus::Rtb);
+        }
+
+        if report.drones.is_empty() {
+            self.finish_abort(&mission.id);
+        } else {
+            let tracker = Arc::clone(self);
+            let mission_id = mission.id.clone();
+            let timeout = policy.ack_timeout;
+            tokio::spawn(async move {
+                tokio::time::sleep(timeout).await;
+                tracker.finish_abort(&mission_id);
+            });
+        }
+
+        Ok(self.abort_report().unwrap_or(report))
+    }
+
+    /// Record a drone's acknowledgment of the abort command
+    pub fn acknowledge_abort(&self, drone_id: &DroneId, command_id: Option<Uuid>) -> bool {
+        let (acknowledged, settled_mission) = {
+            let mut abort = self.abort.write();
+            let Some(report) = abort.as_mut() else {
+                return false;
+            };
+            let acknowledged = report.acknowledge(drone_id, command_id, Utc::now());
+            let settled = acknowledged && report.all_acknowledged();
+            (acknowledged, settled.then(|| report.mission_id.clone()))
+        };
+
+        if acknowledged {
+            info!("Drone {} acknowledged abort", drone_id);
+        }
+        if let Some(mission_id) = settled_mission {
+            self.finish_abort(&mission_id);
+        }
+        acknowledged
+    }
+
+    /// Data quality report for a mission
+    pub fn data_quality_report(&self, mission_id: &MissionId) -> Option<DataQualityReport> {
+        self.quality.report(mission_id, self.clock.now())
+    }
+
+    /// Current or most recent abort report
+    pub fn abort_report(&self) -> Option<AbortReport> {
+        self.abort.read().clone()
+    }
+
+    /// Complete an in-progress abort and mark the mission `Aborted`
+    fn finish_abort(&self, mission_id: &MissionId) {
+        let summary = {
+            let mut abort = self.abort.write();
+            let Some(report) = abort
+                .as_mut()
+                .filter(|r| &r.mission_id == mission_id && r.state == AbortState::InProgress)
+            else {
+                return;
+            };
+            report.complete(Utc::now());
+            report.summary()
+        };
+
+        if let Some(mission) = self.mission.write().as_mut().filter(|m| &m.id == mission_id) {
+            mission.abort();
+        }
+        warn!("{}", summary);
+        self.emit(Event::mission_status_changed(
+            mission_id.clone(),
+            MissionStatus::Aborted,
+            Some(summary),
+        ));
+    }
+
+    // ========================================================================
+    // ALERT ACKNOWLEDGEMENT
+    // ========================================================================
+
+    /// Mark an active alert acknowledged by `by`, persisting who and when
+    /// first; `None` if no drone has the alert
+    pub async fn acknowledge_alert(&self, alert_id: Uuid, by: &str) -> anyhow::Result<Option<Alert>> {
+        let Some((drone_id, alert)) = self.drones.iter().find_map(|tracked| {
+            tracked
+                .active_alerts
+                .iter()
+                .find(|alert| alert.id == alert_id)
+                .map(|alert| (tracked.key().clone(), alert.clone()))
+        }) else {
+            return Ok(None);
+        };
+
+        if let Some(db) = &self.db {
+            if let Err(e) = db.alerts().acknowledge(&drone_id, alert_id, alert.created_at, by, self.clock.now()).await {
+                db.health().record_error();
+                return Err(e.into());
+            }
+        }
+        if let Some(mut tracked) = self.drones.get_mut(&drone_id) {
+            if let Some(active) = tracked.active_alerts.iter_mut().find(|alert| alert.id == alert_id) {
+                active.acknowledged = true;
+            }
+        }
+        info!("Alert {} on drone {} acknowledged by {}", alert_id, drone_id, by);
+        Ok(Some(Alert { acknowledged: true, ..alert }))
+    }
+
+    // ========================================================================
+    // ALERT SUPPRESSION
+    // ========================================================================
+
+    /// Add a suppression rule
+    pub fn add_suppression(
+        &self,
+        drone_id: Option<DroneId>,
+        alert_type: Option<AlertType>,
+        window: SuppressionWindow,
+        reason: Option<String>,
+    ) -> SuppressionRule {
+        let rule = SuppressionRule::new(drone_id, alert_type, window, reason, self.clock.now());
+        info!(
+            "Alert suppression {} added for {} / {}",
+            rule.id,
+            rule.drone_id.as_ref().map(|d| d.to_string()).unwrap_or_else(|| "all drones".into()),
+            rule.alert_type.as_ref().map(|t| format!("{:?}", t)).unwrap_or_else(|| "all alerts".into())
+        );
+        self.suppressor.add(rule)
+    }
+
+    /// Remove a suppression rule
+    pub fn remove_suppression(&self, id: &uuid::Uuid) -> Option<SuppressionRule> {
+        self.suppressor.remove(id)
+    }
+
+    /// Suppression rules that have not expired
+    pub fn suppressions(&self) -> Vec<SuppressionRule> {
+        self.suppressor.list(self.clock.now())
+    }
+
+    pub fn suppression_stats(&self) -> SuppressionStats {
+        self.suppressor.stats()
+    }
+
+    /// Duplicate, gap, late and restart counters of numbered position reports
+    pub fn sequence_stats(&self) -> Vec<DroneSequenceStats> {
+        self.sequences.stats()
+    }
+
+    // ========================================================================
+    // MOTION
+    // ========================================================================
+
+    /// Smoothed heading, climb rate, acceleration and turn rate of a drone
+    pub fn motion(&self, drone_id: &DroneId) -> Option<DerivedMotion> {
+        self.motion.get(drone_id)
+    }
+
+    // ========================================================================
+    // ENDURANCE
+    // ========================================================================
+
+    /// Consumption model rates and reserve margin
+    pub fn endurance_config(&self) -> &EnduranceConfig {
+        self.endurance.config()
+    }
+
+    /// Projected battery and fuel at completion of the active mission
+    pub fn endurance_projection(&self, drone_id: &DroneId) -> Option<EnduranceProjection> {
+        // Drone before mission, the order position updates take them in
+        self.inspect_drone(drone_id, |tracked| {
+            let mission = self.mission.read();
+            Some(self.project_endurance(tracked, mission.as_ref()?))
+        })
+        .flatten()
+    }
+
+    /// Projection for a drone still on the route, if it just fell below the reserve
+    fn check_endurance(&self, tracked: &TrackedDrone, mission: &Mission) -> Option<EnduranceProjection> {
+        let projection = self.project_endurance(tracked, mission);
+        if projection.remaining_km <= 0.0 {
+            return None;
+        }
+        self.endurance.should_warn(&tracked.drone.id, &projection).then_some(projection)
+    }
+
+    /// Levels at completion over the rest of the route, through the wind
+    /// estimated along it
+    fn project_endurance(&self, tracked: &TrackedDrone, mission: &Mission) -> EnduranceProjection {
+        let (position, index) = (&tracked.drone.position, tracked.waypoint_index);
+        let remaining = endurance::remaining_route_km(mission, position, index);
+        let now = self.clock.now();
+        let wind_factor = endurance::route_wind_factor(mission, position, index, tracked.drone.telemetry.speed, |at| {
+            self.wind.wind_at(at, now)
+        });
+        self.endurance
+            .project(&tracked.drone.id, &tracked.drone.telemetry, remaining, wind_factor)
+    }
+
+    /// Seconds to fly the rest of the active mission's route at the drone's
+    /// airspeed through the estimated wind; `None` when it is not moving or
+    /// cannot make headway
+    pub fn route_eta_seconds(&self, drone_id: &DroneId) -> Option<f64> {
+        let now = self.clock.now();
+        self.inspect_drone(drone_id, |tracked| {
+            let mission = self.mission.read();
+            endurance::remaining_route_secs(
+                mission.as_ref()?,
+                &tracked.drone.position,
+                tracked.waypoint_index,
+                tracked.drone.telemetry.speed,
+                |at| self.wind.wind_at(at, now),
+            )
+        })
+        .flatten()
+    }
+
+    // ========================================================================
+    // WIND
+    // ========================================================================
+
+    /// Wind grid estimated from drone drift
+    pub fn wind_field(&self) -> WindField {
+        self.wind.field(self.clock.now())
+    }
+
+    /// Estimated wind at a position
+    pub fn wind_at(&self, position: &GeoPosition) -> Option<WindVector> {
+        self.wind.wind_at(position, self.clock.now())
+    }
+
+    fn raise_endurance_alert(&self, drone_id: &DroneId, projection: &EnduranceProjection) {
+        warn!(
+            "Drone {} projected to finish with {:.0}% battery, {:.0}% fuel",
+            drone_id, projection.battery_at_completion, projection.fuel_at_completion
+        );
+        self.raise_alert(
+            Alert::new(
+                AlertSeverity::Warning,
+                AlertType::Custom(ENDURANCE_ALERT_TYPE.into()),
+                format!(
+                    "Projected at mission completion: battery {:.0}%, fuel {:.0}% (reserve {:.0}%, {:.1} km to go)",
+                    projection.battery_at_completion,
+                    projection.fuel_at_completion,
+                    self.endurance.config().reserve_percent,
+                    projection.remaining_km
+                ),
+            )
+            .for_drone(drone_id.clone()),
+        );
+    }
+
+    // ========================================================================
+    // SPEED LIMITS
+    // ========================================================================
+
+    /// Replace the active mission's leg speed limits in flight; `false`
+    /// without an active mission
+    pub fn set_speed_limits(&self, limits: &HashMap<WaypointId, f64>, enforce: bool) -> bool {
+        let mut mission = self.mission.write();
+        let Some(mission) = mission.as_mut() else {
+            return false;
+        };
+        mission.set_speed_limits(limits, enforce);
+        self.mission_sync.publish(mission);
+        info!(
+            "Speed limits on {} legs of mission {} ({})",
+            limits.len(),
+            mission.name,
+            if enforce { "enforced" } else { "alert only" }
+        );
+        true
+    }
+
+    /// Generate the active mission's corridor from its route, or stop
+    /// generating it; false without a mission
+    pub fn set_auto_corridor(&self, spec: Option<CorridorSpec>) -> bool {
+        let mut mission = self.mission.write();
+        let Some(mission) = mission.as_mut() else {
+            return false;
+        };
+        mission.set_auto_corridor(spec);
+        match spec {
+            Some(spec) => info!("Corridor of mission {} generated {} m wide", mission.name, spec.width_m),
+            None => info!("Corridor of mission {} removed", mission.name),
+        }
+        true
+    }
+
+    /// Alert on a new speed limit violation and, on enforcing missions,
+    /// command the drone down to the limit
+    async fn enforce_speed_limit(&self, violation: &SpeedViolation) {
+        if violation.alert {
+            // The mission may have been replaced since the check
+            let leg = self.mission.read().as_ref().and_then(|mission| {
+                let to = mission.waypoints.get(violation.waypoint_index)?;
+                let from = mission.waypoints.get(violation.waypoint_index.checked_sub(1)?)?;
+                Some(format!("{}-{}", from.id, to.id))
+            });
+            warn!(
+                "Drone {} at {:.0} km/h over the {:.0} km/h limit",
+                violation.drone_id, violation.speed_kmh, violation.limit_kmh
+            );
+            self.raise_alert(
+                Alert::new(
+                    AlertSeverity::Warning,
+                    AlertType::Custom(SPEED_LIMIT_ALERT_TYPE.into()),
+                    format!(
+                        "{:.0} km/h on leg {} limited to {:.0} km/h",
+                        violation.speed_kmh,
+                        leg.unwrap_or_default(),
+                        violation.limit_kmh
+                    ),
+                )
+                .for_drone(violation.drone_id.clone()),
+            );
+        }
+        if violation.command {
+            let command = DroneCommandType::SetSpeed { speed: violation.limit_kmh };
+            self.dispatch_command(&violation.drone_id, &command).await;
+        }
+    }
+
+    // ========================================================================
+    // ALTITUDE BANDS
+    // ========================================================================
+
+    /// Band of every drone that has reported while flying
+    pub fn altitude_assignments(&self) -> Vec<AltitudeAssignment> {
+        self.altitude.assignments()
+    }
+
+    pub fn altitude_config(&self) -> &AltitudeConfig {
+        self.altitude.config()
+    }
+
+    /// Move a drone to another free altitude band
+    pub fn assign_altitude_band(&self, drone_id: &DroneId, band: i32) -> Result<AltitudeBand, BandError> {
+        let band = self.altitude.assign(drone_id, band, self.clock.now())?;
+        info!("Drone {} assigned altitude band {} ({:.0} m)", drone_id, band.index, band.altitude_m);
+        Ok(band)
+    }
+
+    /// Alert on a drone outside its altitude band and send it back
+    async fn enforce_altitude_band(&self, violation: &AltitudeViolation) {
+        if violation.alert {
+            warn!(
+                "Drone {} at {:.0} m outside its band {} ({:.0}-{:.0} m)",
+                violation.drone_id,
+                violation.altitude_m,
+                violation.assigned.index,
+                violation.assigned.floor_m,
+                violation.assigned.ceiling_m
+            );
+            let (severity, message) = if violation.conflicts_with.is_empty() {
+                (
+                    AlertSeverity::Warning,
+                    format!(
+                        "{:.0} m, outside altitude band {:.0}-{:.0} m",
+                        violation.altitude_m, violation.assigned.floor_m, violation.assigned.ceiling_m
+                    ),
+                )
+            } else {
+                let others: Vec<&str> = violation.conflicts_with.iter().map(|id| id.as_str()).collect();
+                (
+                    AlertSeverity::Critical,
+                    format!(
+                        "{:.0} m, sharing altitude band {:.0}-{:.0} m with {}",
+                        violation.altitude_m,
+                        violation.flying.floor_m,
+                        violation.flying.ceiling_m,
+                        others.join(", ")
+                    ),
+                )
+            };
+            self.raise_alert(
+                Alert::new(severity, AlertType::Custom(ALTITUDE_ALERT_TYPE.into()), message)
+                    .for_drone(violation.drone_id.clone()),
+            );
+        }
+        if violation.command {
+            let command = DroneCommandType::SetAltitude { altitude: violation.assigned.altitude_m };
+            self.dispatch_command(&violation.drone_id, &command).await;
+        }
+    }
+
+    // ========================================================================
+    // TELEMETRY WRITES
+    // ========================================================================
+
+    /// Write a telemetry row, or send it to the dead-letter table when it is
+    /// malformed, fails to write or the drone's breaker is open
+    async fn persist_telemetry(&self, db: &DbClient, drone_id: &DroneId, record: drone_db::TelemetryRecord, now: DateTime<Utc>) {
+        let breakers = &self.write_breakers;
+        let failed = |opened: bool| {
+            if opened {
+                warn!("Telemetry writes for {} failing; breaker open for {:?}", drone_id, breakers.config().cool_down);
+            }
+        };
+        let reason = match breakers.config().defect(&record, now) {
+            Some(defect) => {
+                failed(breakers.record_failure(drone_id, now));
+                defect
+            }
+            None if !breakers.admit(drone_id, now) => "write breaker open".to_string(),
+            None => match db.telemetry().insert_records(vec![record.clone()]).await {
+                Ok(()) => {
+                    if breakers.record_success(drone_id) {
+                        info!("Telemetry writes for {} recovered; breaker closed", drone_id);
+                    }
+                    return;
+                }
+                Err(e) => {
+                    warn!("Failed to persist telemetry: {}", e);
+                    db.health().record_error();
+                    failed(breakers.record_write_failure(drone_id, now));
+                    format!("write failed: {}", e)
+                }
+            },
+        };
+
+        breakers.record_quarantined(drone_id);
+        let letter = drone_db::DeadLetterRecord::new(&record, reason, now);
+        if let Err(e) = db.quality().quarantine(&letter).await {
+            warn!("Failed to quarantine telemetry from {}: {}", drone_id, e);
+            db.health().record_error();
+        }
+    }
+
+    /// Write breakers of drones that have had failed or quarantined rows
+    pub fn write_breakers(&self) -> Vec<BreakerStatus> {
+        self.write_breakers.status()
+    }
+
+    // ========================================================================
+    // LINE OF SIGHT
+    // ========================================================================
+
+    /// Project every flying drone along its route and warn about terrain
+    /// that will block its line of sight to the ground station
+    pub fn check_line_of_sight(&self) -> Vec<LosLoss> {
+        let Some(los) = &self.los else {
+            return Vec::new();
+        };
+        // Terrain sampling is slow; work on copies, holding no locks
+        let mission = self.mission.read().clone();
+        let Some(station) = los.ground_station(mission.as_ref()) else {
+            return Vec::new();
+        };
+        let drones: Vec<(Drone, usize)> = self
+            .drones
+            .iter()
+            .map(|tracked| (tracked.drone.clone(), tracked.waypoint_index))
+            .collect();
+        let losses: Vec<LosLoss> = drones
+            .iter()
+            .filter_map(|(drone, waypoint_index)| los.check(drone, mission.as_ref(), *waypoint_index, &station))
+            .collect();
+        for loss in &losses {
+            self.raise_los_alert(loss);
+        }
+        losses
+    }
+
+    fn raise_los_alert(&self, loss: &LosLoss) {
+        let when = match loss.in_secs {
+            0 => "now".to_string(),
+            secs => format!("in about {} s", secs),
+        };
+        warn!("Terrain will block line of sight to {} {}", loss.drone_id, when);
+        self.raise_alert(
+            Alert::new(
+                AlertSeverity::Warning,
+                AlertType::Custom(LOS_ALERT_TYPE.into()),
+                format!(
+                    "Terrain blocks line of sight to the ground station {} (at {:.4}, {:.4})",
+                    when, loss.position.latitude, loss.position.longitude
+                ),
+            )
+            .for_drone(loss.drone_id.clone()),
+        );
+    }
+
+    /// Spawn a task checking line of sight every `check_interval`
+    /// (`None` without an elevation model)
+    pub fn spawn_los_monitor(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let period = self.los.as_ref()?.config().check_interval;
+        let tracker = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                tracker.check_line_of_sight();
+            }
+        }))
+    }
+
+    // ========================================================================
+    // COLLISION PREDICTION
+    // ========================================================================
+
+    /// Project every flying drone along its velocity and warn about pairs
+    /// that will lose separation within the lookahead
+    ///
+    /// Drones with fresh telemetry are projected from speed, heading and
+    /// climb rate; the rest, and drones only the CV engine sees, from their
+    /// CV velocity filter.
+    pub fn check_proximity(&self) -> Vec<CollisionWarning> {
+        let now = self.clock.now();
+        let max_age = self.proximity.config().telemetry_max_age;
+        let mut known = HashSet::new();
+        let mut tracks = Vec::new();
+        for tracked in self.drones.iter() {
+            let drone = &tracked.drone;
+            known.insert(drone.id.clone());
+            let flying = matches!(
+                drone.status,
+                DroneStatus::Moving | DroneStatus::Engaged | DroneStatus::Loitering | DroneStatus::Rtb
+            );
+            if !flying {
+                continue;
+            }
+            let track = if !tracked.is_stale(max_age, now) {
+                Some(ProjectedTrack::from_telemetry(
+                    drone.id.clone(),
+                    drone.position,
+                    drone.telemetry.speed,
+                    drone.telemetry.heading,
+                    self.motion.get(&drone.id).map_or(0.0, |m| m.climb_rate),
+                ))
+            } else {
+                self.proximity.cv_track(&drone.id, now)
+            };
+            tracks.extend(track);
+        }
+        tracks.extend(self.proximity.cv_only_tracks(&known, now));
+
+        let warnings = self.proximity.check(&tracks);
+        for warning in &warnings {
+            self.raise_collision_warning(warning);
+        }
+        warnings
+    }
+
+    fn raise_collision_warning(&self, warning: &CollisionWarning) {
+        warn!(
+            "{} and {} predicted within {:.0} m in {:.0} s",
+            warning.drone_id, warning.other_drone_id, warning.cpa_horizontal_m, warning.time_to_cpa_secs
+        );
+        let severity = if warning.critical { AlertSeverity::Critical } else { AlertSeverity::Warning };
+        for (own, other) in warning.avoidance.iter().zip([&warning.other_drone_id, &warning.drone_id]) {
+            let mut suggestion = format!(
+                "{} {:.0} m",
+                if own.altitude_change_m >= 0.0 { "climb" } else { "descend" },
+                own.altitude_change_m.abs()
+            );
+            if let (Some(turn), Some(heading)) = (own.heading_change_deg, own.new_heading) {
+                suggestion.push_str(&format!(" or turn right {:.0}° to heading {:03.0}", turn, heading));
+            }
+            self.raise_alert(
+                Alert::new(
+                    severity,
+                    AlertType::CollisionWarning,
+                    format!(
+                        "Closest approach to {} in {:.0} s: {:.0} m horizontal, {:.0} m vertical; {}",
+                        other, warning.time_to_cpa_secs, warning.cpa_horizontal_m, warning.cpa_vertical_m, suggestion
+                    ),
+                )
+                .for_drone(own.drone_id.clone()),
+            );
+        }
+    }
+
+    /// Spawn a task checking for predicted conflicts every `check_interval`
+    pub fn spawn_proximity_monitor(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = self.proximity.config().check_interval;
+        let tracker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                tracker.check_proximity();
+            }
+        })
+    }
+
+    // ========================================================================
+    // STATUS INFERENCE
+    // ========================================================================
+
+    /// Take drones silent for `offline_after` offline; drones peers still
+    /// hear from are left to the partition monitor
+    pub fn mark_silent_drones_offline(&self) -> Vec<DroneId> {
+        let now = self.clock.now();
+        let silent: Vec<DroneId> = self
+            .drones
+            .iter()
+            .filter(|r| self.status_inference.is_offline(r.drone.status, r.last_update, now))
+            .map(|r| r.key().clone())
+            .collect();
+
+        silent
+            .into_iter()
+            .filter(|id| !self.reported_alive(id))
+            .filter(|id| {
+                info!("Drone {} is offline: no report for {:?}", id, self.status_inference.config().offline_after);
+                self.set_drone_status(id, DroneStatus::Offline)
+            })
+            .collect()
+    }
+
+    /// Spawn a task checking for silent drones every `check_interval`
+    pub fn spawn_status_monitor(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = self.status_inference.config().check_interval;
+        let tracker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                tracker.mark_silent_drones_offline();
+            }
+        })
+    }
+
+    // ========================================================================
+    // EVICTION
+    // ========================================================================
+
+    /// Drop drones offline longer than `offline_after`, then the longest
+    /// silent ones above `max_drones`, persisting a final snapshot of each
+    pub async fn evict_stale_drones(&self) -> Vec<(DroneId, EvictionReason)> {
+        let last_updates: Vec<_> = self
+            .drones
+            .iter()
+            .map(|r| (r.key().clone(), r.value().last_update))
+            .collect();
+        let selected = eviction::select_evictions(&last_updates, self.clock.now(), &self.config.eviction);
+        self.fusion.expire(self.clock.now());
+        let seen: HashMap<DroneId, DateTime<Utc>> = last_updates.into_iter().collect();
+
+        let mut evicted = Vec::with_capacity(selected.len());
+        for (drone_id, reason) in selected {
+            let Some(snapshot) = self.evict(&drone_id, reason, seen[&drone_id]) else {
+                continue;
+            };
+            if let Some(db) = &self.db {
+                persist_snapshot(db, &snapshot, self.clock.now()).await;
+            }
+            evicted.push((drone_id, reason));
+        }
+        self.update_footprint_metrics();
+        evicted
+    }
+
+    /// Keep the drone count within `max_drones` after registering `keep`
+    fn enforce_drone_cap(&self, keep: &DroneId) {
+        let Some(max) = self.config.eviction.max_drones else {
+            return;
+        };
+        if self.drones.len() <= max {
+            return;
+        }
+        let others: Vec<_> = self
+            .drones
+            .iter()
+            .filter(|r| r.key() != keep)
+            .map(|r| (r.key().clone(), r.value().last_update))
+            .collect();
+        let seen: HashMap<DroneId, DateTime<Utc>> = others.iter().cloned().collect();
+        let config = EvictionConfig {
+            offline_after: None,
+            max_drones: Some(max.saturating_sub(1)),
+            ..self.config.eviction.clone()
+        };
+
+        for (drone_id, reason) in eviction::select_evictions(&others, self.clock.now(), &config) {
+            let Some(snapshot) = self.evict(&drone_id, reason, seen[&drone_id]) else {
+                continue;
+            };
+            // Registration is synchronous; the snapshot is written in the background
+            if let (Some(db), Ok(handle)) = (&self.db, tokio::runtime::Handle::try_current()) {
+                let db = db.clone();
+                let evicted_at = self.clock.now();
+                handle.spawn(async move { persist_snapshot(&db, &snapshot, evicted_at).await });
+            }
+        }
+    }
+
+    /// Remove a drone and its per-drone state, returning its final snapshot;
+    /// `None` if it reported after `seen`, the update it was selected on
+    fn evict(&self, drone_id: &DroneId, reason: EvictionReason, seen: DateTime<Utc>) -> Option<DroneSnapshot> {
+        let (_, tracked) = self.drones.remove_if(drone_id, |_, tracked| tracked.last_update == seen)?;
+        self.evicted.insert(drone_id.clone());
+        let exits = self.zones.close_drone(drone_id, tracked.drone.position, self.clock.now());
+        self.record_zone_exits(exits);
+        self.partitioned.remove(drone_id);
+        self.motion.forget(drone_id);
+        self.wind.forget(drone_id);
+        self.mission_sync.forget(drone_id);
+        self.endurance.forget(drone_id);
+        self.fusion.forget(drone_id);
+        self.proximity.forget(drone_id);
+        self.sequences.forget(drone_id);
+        self.speed_limits.forget(drone_id);
+        self.altitude.forget(drone_id);
+        self.write_breakers.forget(drone_id);
+        self.status_inference.forget(drone_id);
+        self.kpis.forget(drone_id);
+        self.checkpoints.clear(drone_id);
+        self.emergency.resolve(drone_id);
+        // Persisted settings are reloaded if the drone returns
+        self.sources.designate(drone_id, None);
+        self.markings.set(drone_id, None);
+        self.drone_thresholds.remove(drone_id);
+        self.commands.unbind(drone_id);
+        self.handoffs.release(drone_id);
+
+        info!("Evicted drone {} ({})", drone_id, reason.as_str());
+        self.metrics().record_tracker_eviction(reason.as_str());
+        self.emit(Event::drone_evicted(drone_id.clone()));
+        Some(DroneSnapshot {
+            drone: tracked.drone,
+            waypoint_index: tracked.waypoint_index,
+            waypoint_progress: tracked.waypoint_progress,
+            last_update: tracked.last_update,
+            position_history: tracked.position_history,
+            active_alerts: tracked.active_alerts,
+            reason,
+        })
+    }
+
+    /// Drop the oldest active alerts beyond `max_alerts_per_drone`
+    fn trim_alerts(&self, tracked: &mut TrackedDrone) {
+        let excess = tracked
+            .active_alerts
+            .len()
+            .saturating_sub(self.config.eviction.max_alerts_per_drone);
+        if excess > 0 {
+            tracked.active_alerts.drain(..excess);
+            self.metrics().record_tracker_alerts_trimmed(excess as u64);
+        }
+    }
+
+    /// Entry counts of the per-drone state
+    pub fn footprint(&self) -> TrackerFootprint {
+        let mut footprint = TrackerFootprint {
+            drones: self.drones.len(),
+            motion: self.motion.drone_count(),
+            endurance: self.endurance.drone_count(),
+            fusion: self.fusion.drone_count(),
+            sequences: self.sequences.drone_count(),
+            ..Default::default()
+        };
+        for r in self.drones.iter() {
+            footprint.position_history += r.value().position_history.len();
+            footprint.active_alerts += r.value().active_alerts.len();
+        }
+        footprint.estimated_bytes = footprint.drones * std::mem::size_of::<TrackedDrone>()
+            + footprint.position_history * std::mem::size_of::<(DateTime<Utc>, GeoPosition)>()
+            + footprint.active_alerts * std::mem::size_of::<Alert>();
+        footprint
+    }
+
+    /// Publish the footprint through the `drone_convoy_tracker_*` gauges
+    pub fn update_footprint_metrics(&self) -> TrackerFootprint {
+        let footprint = self.footprint();
+        let metrics = self.metrics();
+        for (collection, count) in footprint.entries() {
+            metrics.set_tracker_entries(collection, count as i64);
+        }
+        metrics.set_tracker_memory_bytes(footprint.estimated_bytes as i64);
+        footprint
+    }
+
+    /// Spawn a task running the eviction sweep every `sweep_interval`
+    pub fn spawn_eviction_sweeper(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = self.config.eviction.sweep_interval;
+        let tracker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                tracker.evict_stale_drones().await;
+            }
+        })
+    }
+
+    // ========================================================================
+    // DRONE GROUPS
+    // ========================================================================
+
+    /// Create or replace a drone group and persist it
+    pub async fn put_group(&self, group: DroneGroup) -> anyhow::Result<DroneGroup> {
+        let group = self.groups.put(group);
+        if let Some(db) = &self.db {
+            db.drones().save_group(&group.to_record()).await?;
+        }
+        info!("Drone group {} saved ({} explicit members)", group.name, group.members.len());
+        Ok(group)
+    }
+
+    /// Delete a drone group; `None` if there was no such group
+    pub async fn delete_group(&self, name: &str) -> anyhow::Result<Option<DroneGroup>> {
+        let Some(group) = self.groups.remove(name) else {
+            return Ok(None);
+        };
+        if let Some(db) = &self.db {
+            db.drones().delete_group(name).await?;
+        }
+        info!("Drone group {} deleted", name);
+        Ok(Some(group))
+    }
+
+    pub fn group(&self, name: &str) -> Option<DroneGroup> {
+        self.groups.get(name)
+    }
+
+    /// All drone groups, by name
+    pub fn groups(&self) -> Vec<DroneGroup> {
+        self.groups.list()
+    }
+
+    /// Current members of a group, including holders of its role
+    pub fn group_members(&self, group: &DroneGroup) -> Vec<DroneId> {
+        group.resolve(&self.convoy.roles())
+    }
+
+    /// Send a command to every member of a group; `None` if there is no such group
+    pub async fn send_group_command(
+        &self,
+        name: &str,
+        command: &DroneCommandType,
+    ) -> Option<BulkCommandReport> {
+        let group = self.groups.get(name)?;
+        let mut results = Vec::new();
+        for drone_id in self.group_members(&group) {
+            if !self.drones.contains_key(&drone_id) {
+                results.push(CommandResult::new(drone_id, CommandOutcome::NotFound));
+                continue;
+            }
+            results.push(self.dispatch_command(&drone_id, command).await);
+        }
+
+        let report = BulkCommandReport::new(name, results);
+        info!(
+            "Command {:?} sent to group {}: {} succeeded, {} failed",
+            command, name, report.succeeded, report.failed
+        );
+        Some(report)
+    }
+
+    /// Load persisted drone groups
+    pub async fn load_groups(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut groups = Vec::new();
+        for record in db.drones().groups().await? {
+            match DroneGroup::from_record(&record) {
+                Ok(group) => groups.push(group),
+                Err(e) => warn!("Skipping unreadable drone group {}: {}", record.name, e),
+            }
+        }
+        info!("Loaded {} drone groups", groups.len());
+        self.groups.restore(groups);
+        Ok(())
+    }
+
+    // ========================================================================
+    // GROUND CONTROL HANDOFF
+    // ========================================================================
+
+    /// Export a drone's tracked state for handing it to another station.
+    /// `None` if the drone is unknown or already controlled elsewhere.
+    pub fn export_handoff(&self, drone_id: &DroneId, origin_station: &str) -> Option<HandoffPackage> {
+        if self.handoffs.is_remote(drone_id) {
+            return None;
+        }
+        let tracked = self.drones.get(drone_id)?;
+        Some(HandoffPackage {
+            handoff_id: Uuid::new_v4(),
+            origin_station: origin_station.to_string(),
+            issued_at: self.clock.now(),
+            drone: tracked.drone.clone(),
+            mission_id: self.mission.read().as_ref().map(|m| m.id.clone()),
+            waypoint_index: tracked.waypoint_index,
+            waypoint_progress: tracked.waypoint_progress,
+            position_history: tracked.position_history.clone(),
+            active_alerts: tracked.active_alerts.clone(),
+            role: self.convoy.role(drone_id),
+            thresholds: self.drone_thresholds.get(drone_id).map(|t| *t),
+        })
+    }
+
+    /// Start handing a drone over in either direction; `None` while another
+    /// handoff of it is in progress
+    pub fn begin_handoff(&self, drone_id: &DroneId) -> Option<HandoffGuard> {
+        self.handoffs.begin(drone_id)
+    }
+
+    /// The peer accepted the package: the drone is now read-only here
+    pub async fn complete_handoff(&self, package: &HandoffPackage, station: &str, url: Option<String>) {
+        let drone_id = &package.drone.id;
+        info!("Drone {} handed off to station {}", drone_id, station);
+        let owner = RemoteOwner {
+            drone_id: drone_id.clone(),
+            handoff_id: package.handoff_id,
+            station: station.to_string(),
+            url,
+            handed_off_at: self.clock.now(),
+        };
+        // The peer already controls the drone, so a failed write only warns
+        if let Some(db) = &self.db {
+            if let Err(e) = db.drones().set_handoff_owner(drone_id, Some(&owner.to_record())).await {
+                warn!("Failed to persist handoff of {}: {}", drone_id, e);
+                db.health().record_error();
+            }
+        }
+        self.handoffs.mark_remote(owner);
+        self.checkpoints.clear(drone_id);
+    }
+
+    /// Take ownership of a drone handed over by another station. Waypoint
+    /// progress only carries over when both stations fly the same mission.
+    /// A drone this station controls is refused; a failed write leaves
+    /// everything as it was.
+    pub async fn accept_handoff(&self, package: HandoffPackage) -> Result<(), HandoffFailure> {
+        let drone_id = package.drone.id.clone();
+        let _guard = self
+            .begin_handoff(&drone_id)
+            .ok_or_else(|| HandoffFailure::InProgress(drone_id.clone()))?;
+        let previous_owner = self.handoffs.owner(&drone_id);
+        if previous_owner.is_none() && self.drones.contains_key(&drone_id) {
+            return Err(HandoffFailure::ControlledHere(drone_id));
+        }
+
+        // Everything is written before anything changes in memory
+        if let Some(db) = &self.db {
+            db.drones().register(&package.drone).await.map_err(anyhow::Error::from)?;
+            if previous_owner.is_some() {
+                db.drones().set_handoff_owner(&drone_id, None).await.map_err(anyhow::Error::from)?;
+            }
+        }
+        if let Some(thresholds) = package.thresholds {
+            if let Err(e) = self.set_drone_thresholds(&drone_id, thresholds).await {
+                if let (Some(db), Some(owner)) = (&self.db, &previous_owner) {
+                    if let Err(e) = db.drones().set_handoff_owner(&drone_id, Some(&owner.to_record())).await {
+                        warn!("Failed to restore the handoff owner of {}: {}", drone_id, e);
+                    }
+                }
+                return Err(e.into());
+            }
+        }
+
+        let same_mission = package.mission_id.is_some()
+            && package.mission_id == self.mission.read().as_ref().map(|m| m.id.clone());
+        if !same_mission {
+            warn!(
+                "Handoff {} of {} is for another mission; waypoint progress restarts",
+                package.handoff_id, drone_id
+            );
+        }
+
+        let mut tracked = TrackedDrone::new(package.drone, self.clock.now());
+        if same_mission {
+            tracked.waypoint_index = package.waypoint_index;
+            tracked.waypoint_progress = package.waypoint_progress;
+        }
+        tracked.position_history = package.position_history;
+        tracked.active_alerts = package.active_alerts;
+        self.trim_alerts(&mut tracked);
+        let position = tracked.drone.position;
+        let telemetry = tracked.drone.telemetry.clone();
+        self.drones.insert(drone_id.clone(), tracked);
+        self.convoy.set_role(&drone_id, package.role);
+        if let Some(previous) = self.handoffs.release(&drone_id) {
+            info!("Drone {} handed back from station {}", drone_id, previous.station);
+        }
+
+        info!(
+            "Took control of drone {} from station {} (handoff {})",
+            drone_id, package.origin_station, package.handoff_id
+        );
+        self.emit(Event::drone_position_updated(drone_id, position, telemetry));
+        Ok(())
+    }
+
+    pub fn is_remote(&self, drone_id: &DroneId) -> bool {
+        self.handoffs.is_remote(drone_id)
+    }
+
+    pub fn remote_owner(&self, drone_id: &DroneId) -> Option<RemoteOwner> {
+        self.handoffs.owner(drone_id)
+    }
+
+    /// Drones handed off to other stations
+    pub fn remote_drones(&self) -> Vec<RemoteOwner> {
+        self.handoffs.list()
+    }
+
+    /// Load the persisted owners of drones handed off from here, so they
+    /// stay read-only across a restart
+    pub async fn load_handoffs(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let owners = db.drones().get_handoff_owners().await?;
+        info!("Loaded {} drones handed off to other stations", owners.len());
+        for (drone_id, record) in owners {
+            self.handoffs.mark_remote(RemoteOwner::from_record(drone_id, record));
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // ZONES OF INTEREST
+    // ========================================================================
+
+    /// Persist a zone of interest and start tracking it
+    pub async fn add_zone(&self, zone: Zone) -> anyhow::Result<Zone> {
+        if let Some(db) = &self.db {
+            db.zones().save_zone(&zone.to_record()).await?;
+        }
+        let zone = self.zones.add(zone);
+        info!("Zone of interest {} ({}) added", zone.area.name, zone.id);
+        Ok(zone)
+    }
+
+    /// Remove a zone and its statistics; `None` if there was no such zone
+    pub async fn remove_zone(&self, id: &Uuid) -> anyhow::Result<Option<Zone>> {
+        if self.zones.get(id).is_none() {
+            return Ok(None);
+        }
+        if let Some(db) = &self.db {
+            db.zones().delete_zone(*id).await?;
+        }
+        let Some(zone) = self.zones.remove(id) else {
+            return Ok(None);
+        };
+        info!("Zone of interest {} ({}) removed", zone.area.name, id);
+        Ok(Some(zone))
+    }
+
+    /// Emit the exits of visits ended other than by a position update and
+    /// persist their dwell rows in the background
+    fn record_zone_exits(&self, exits: Vec<ZoneCrossing>) {
+        if exits.is_empty() {
+            return;
+        }
+        for exit in &exits {
+            self.emit(Event::zone_transition(exit.event.clone()));
+        }
+        if let (Some(db), Ok(handle)) = (&self.db, tokio::runtime::Handle::try_current()) {
+            let db = db.clone();
+            handle.spawn(async move {
+                for exit in exits {
+                    if let Err(e) = db.zones().save_dwell(&exit.record).await {
+                        warn!("Failed to persist zone dwell: {}", e);
+                        db.health().record_error();
+                    }
+                }
+            });
+        }
+    }
+
+    pub fn zone(&self, id: &Uuid) -> Option<Zone> {
+        self.zones.get(id)
+    }
+
+    /// All zones of interest, oldest first
+    pub fn zones(&self) -> Vec<Zone> {
+        self.zones.list()
+    }
+
+    /// Dwell statistics for a zone during a mission. Missions this process
+    /// has not tracked are read from the database; `None` if the zone does
+    /// not exist.
+    pub async fn zone_stats(&self, zone_id: &Uuid, mission_id: &MissionId) -> anyhow::Result<Option<ZoneStats>> {
+        let now = self.clock.now();
+        if let Some(stats) = self.zones.stats(zone_id, mission_id, now) {
+            return Ok(Some(stats));
+        }
+        let records = match &self.db {
+            Some(db) => db.zones().dwell_for_zone(*zone_id, mission_id).await?,
+            None => Vec::new(),
+        };
+        Ok(self.zones.stats_from_records(zone_id, mission_id, &records, now))
+    }
+
+    /// Load persisted zones of interest
+    pub async fn load_zones(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut zones = Vec::new();
+        for record in db.zones().zones().await? {
+            match Zone::from_record(&record) {
+                Ok(zone) => zones.push(zone),
+                Err(e) => warn!("Skipping unreadable zone {}: {}", record.name, e),
+            }
+        }
+        info!("Loaded {} zones of interest", zones.len());
+        let zone_ids: Vec<Uuid> = zones.iter().map(|z| z.id).collect();
+        self.zones.restore(zones);
+
+        // Carry on the active mission's dwell figures from before a restart
+        let mission_id = self.mission.read().as_ref().map(|m| m.id.clone());
+        if let Some(mission_id) = mission_id {
+            for zone_id in zone_ids {
+                let records = db.zones().dwell_for_zone(zone_id, &mission_id).await?;
+                self.zones.hydrate(zone_id, &mission_id, &records);
+            }
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // ALERT RULES
+    // ========================================================================
+
+    /// Add or replace an alert rule and persist it
+    pub async fn save_rule(&self, rule: AlertRule) -> anyhow::Result<AlertRule> {
+        if let Some(db) = &self.db {
+            db.alerts().save_rule(&rule.to_record()).await?;
+        }
+        self.rules.upsert(rule.clone());
+        info!("Alert rule {} ({}) saved", rule.name, rule.id);
+        Ok(rule)
+    }
+
+    /// Remove an alert rule; `None` if there was no such rule
+    pub async fn remove_rule(&self, id: &Uuid) -> anyhow::Result<Option<AlertRule>> {
+        let Some(rule) = self.rules.remove(id) else {
+            return Ok(None);
+        };
+        if let Some(db) = &self.db {
+            db.alerts().delete_rule(*id).await?;
+        }
+        info!("Alert rule {} ({}) removed", rule.name, id);
+        Ok(Some(rule))
+    }
+
+    pub fn rule(&self, id: &Uuid) -> Option<AlertRule> {
+        self.rules.get(id)
+    }
+
+    /// All alert rules, oldest first
+    pub fn rules(&self) -> Vec<AlertRule> {
+        self.rules.list()
+    }
+
+    /// Load persisted alert rules
+    pub async fn load_rules(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut rules = Vec::new();
+        for record in db.alerts().rules().await? {
+            match AlertRule::from_record(&record) {
+                Ok(rule) => rules.push(rule),
+                Err(e) => warn!("Skipping unreadable alert rule {}: {}", record.name, e),
+            }
+        }
+        info!("Loaded {} alert rules", rules.len());
+        self.rules.restore(rules);
+        Ok(())
+    }
+
+    // ========================================================================
+    // CUSTOM EVENTS
+    // ========================================================================
+
+    /// Register or replace the schema of a custom event type and persist it
+    pub async fn save_event_schema(&self, schema: RegisteredSchema) -> anyhow::Result<RegisteredSchema> {
+        if let Some(db) = &self.db {
+            db.custom_events().save_schema(&schema.to_record()).await?;
+        }
+        self.custom_events.upsert(schema.clone());
+        info!("Custom event schema {} registered", schema.event_type);
+        Ok(schema)
+    }
+
+    /// Remove a custom event type and its persisted schema; `None` if it
+    /// was not registered
+    pub async fn remove_event_schema(&self, event_type: &str) -> anyhow::Result<Option<RegisteredSchema>> {
+        if self.custom_events.get(event_type).is_none() {
+            return Ok(None);
+        }
+        if let Some(db) = &self.db {
+            db.custom_events().delete_schema(event_type).await?;
+        }
+        let schema = self.custom_events.remove(event_type);
+        info!("Custom event schema {} removed", event_type);
+        Ok(schema)
+    }
+
+    pub fn event_schema(&self, event_type: &str) -> Option<RegisteredSchema> {
+        self.custom_events.get(event_type)
+    }
+
+    /// All custom event schemas, by type name
+    pub fn event_schemas(&self) -> Vec<RegisteredSchema> {
+        self.custom_events.list()
+    }
+
+    /// Load persisted custom event schemas
+    pub async fn load_event_schemas(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut schemas = Vec::new();
+        for record in db.custom_events().schemas().await? {
+            match RegisteredSchema::from_record(&record) {
+                Ok(schema) => schemas.push(schema),
+                Err(e) => warn!("Skipping unreadable custom event schema {}: {}", record.event_type, e),
+            }
+        }
+        info!("Loaded {} custom event schemas", schemas.len());
+        self.custom_events.restore(schemas);
+        Ok(())
+    }
+
+    /// Validate a custom event against its schema, broadcast it and persist it
+    pub async fn publish_custom_event(&self, event: CustomEvent) -> Result<Event, CustomEventError> {
+        self.custom_events.check(&event)?;
+
+        let mission_id = self.mission.read().as_ref().map(|m| m.id.clone());
+        let mut published = Event::custom(event);
+        // Stored and queried on the tracker's clock
+        published.timestamp = self.clock.now();
+        if let Some(mission_id) = mission_id {
+            published = published.with_mission(mission_id);
+        }
+        let _ = self.event_tx.send(published.clone());
+
+        if let (Some(db), EventPayload::Custom(custom)) = (&self.db, &published.payload) {
+            let record = CustomEventRecord {
+                event_type: custom.event_type.clone(),
+                event_time: published.timestamp,
+                event_id: published.id,
+                drone_id: custom.drone_id.as_ref().map(|d| d.to_string()),
+                mission_id: published.mission_id.as_ref().map(|m| m.0),
+                value: custom.value.to_string(),
+            };
+            if let Err(e) = db.custom_events().insert_event(&record).await {
+                warn!("Failed to persist custom event {}: {}", custom.event_type, e);
+                db.health().record_error();
+            }
+        }
+        Ok(published)
+    }
+
+    // ========================================================================
+    // CHECKPOINTS
+    // ========================================================================
+
+    /// Drones waiting at checkpoints, longest waiting first
+    pub fn checkpoint_holds(&self) -> Vec<CheckpointHold> {
+        self.checkpoints.list()
+    }
+
+    /// Release drones held at a checkpoint (only `drone_id` if given)
+    ///
+    /// Each released drone departs the waypoint with its previous status,
+    /// and its acknowledgment-required alert is resolved.
+    pub fn acknowledge_checkpoint(
+        &self,
+        waypoint_id: &WaypointId,
+        drone_id: Option<&DroneId>,
+        operator: Option<&str>,
+    ) -> Vec<CheckpointHold> {
+        let released = self.checkpoints.acknowledge(waypoint_id, drone_id);
+        let Some(mission) = self.get_mission() else {
+            return released;
+        };
+
+        for hold in &released {
+            info!(
+                "Checkpoint {} acknowledged for drone {}{}",
+                hold.waypoint_name,
+                hold.drone_id,
+                operator.map(|o| format!(" by {}", o)).unwrap_or_default()
+            );
+            if let Some(mut tracked) = self.drones.get_mut(&hold.drone_id) {
+                tracked
+                    .active_alerts
+                    .retain(|a| a.alert_type != AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()));
+                self.end_loiter(&mut tracked, &mission);
+            }
+
+            let mut resolved = Alert::new(
+                AlertSeverity::Info,
+                AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()),
+                format!("Checkpoint {} acknowledged", hold.waypoint_name),
+            )
+            .for_drone(hold.drone_id.clone());
+            resolved.acknowledged = true;
+            resolved.resolved = true;
+            self.emit(Event::alert(resolved.clone()));
+            let _ = self.alert_tx.try_send(resolved);
+        }
+        released
+    }
+
+    /// Re-raise unacknowledged checkpoint alerts one severity higher
+    pub fn escalate_checkpoints(&self) -> Vec<CheckpointHold> {
+        let escalated = self.checkpoints.escalate(self.clock.now());
+        for hold in &escalated {
+            warn!(
+                "Checkpoint {} still unacknowledged for drone {}; escalating to {:?}",
+                hold.waypoint_name, hold.drone_id, hold.severity
+            );
+            self.raise_checkpoint_alert(hold);
+        }
+        escalated
+    }
+
+    fn raise_checkpoint_alert(&self, hold: &CheckpointHold) {
+        let waited = (self.clock.now() - hold.arrived_at).num_seconds();
+        let message = if hold.escalations == 0 {
+            format!("Holding at checkpoint {}; acknowledgment required", hold.waypoint_name)
+        } else {
+            format!(
+                "Holding at checkpoint {} for {}s; acknowledgment required",
+                hold.waypoint_name, waited
+            )
+        };
+        self.raise_alert(
+            Alert::new(hold.severity, AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()), message)
+                .for_drone(hold.drone_id.clone()),
+        );
+    }
+
+    // ========================================================================
+    // SCHEDULED COMMANDS
+    // ========================================================================
+
+    /// Scheduled command queue
+    pub fn scheduler(&self) -> Arc<CommandScheduler> {
+        self.scheduler.clone()
+    }
+
+    /// Queue a command for a later time or waypoint arrival
+    pub async fn schedule_command(
+        &self,
+        trigger: CommandTrigger,
+        action: ScheduledAction,
+        description: Option<String>,
+    ) -> anyhow::Result<ScheduledCommand> {
+        let command = ScheduledCommand::new(trigger, action, description, self.clock.now());
+        info!("Scheduled command {}: {}", command.id, command.description);
+        self.scheduler.schedule(command.clone());
+        self.persist_scheduled(&command).await;
+        Ok(command)
+    }
+
+    /// Cancel a pending scheduled command
+    pub async fn cancel_scheduled_command(&self, id: &Uuid) -> Option<ScheduledCommand> {
+        let command = self.scheduler.cancel(id, self.clock.now())?;
+        info!("Cancelled scheduled command {}: {}", command.id, command.description);
+        self.persist_scheduled(&command).await;
+        Some(command)
+    }
+
+    /// Fire time-triggered commands that are due
+    pub async fn run_due_commands(&self) {
+        let fired = self.scheduler.due(self.clock.now());
+        self.execute_scheduled(fired).await;
+    }
+
+    /// Spawn a task firing time-triggered commands and escalating
+    /// unacknowledged checkpoints once a second
+    pub fn spawn_scheduler(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let tracker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                tracker.run_due_commands().await;
+                tracker.escalate_checkpoints();
+            }
+        })
+    }
+
+    async fn execute_scheduled(&self, fired: Vec<ScheduledCommand>) {
+        for command in fired {
+            info!("Scheduled command {} fired: {}", command.id, command.description);
+            match &command.action {
+                ScheduledAction::SetFormation { formation } => self.convoy.set_formation(*formation),
+                ScheduledAction::DroneCommand { drone_id, command } => {
+                    self.dispatch_command(drone_id, command).await;
+                }
+            }
+
+            self.emit(Event::scheduled_command_fired(ScheduledCommandEvent {
+                schedule_id: command.id,
+                drone_id: command.action.drone_id().cloned(),
+                description: command.description.clone(),
+            }));
+            self.persist_scheduled(&command).await;
+        }
+    }
+
+    /// Send a drone command over the drone's transport
+    async fn dispatch_command(&self, drone_id: &DroneId, command: &DroneCommandType) -> CommandResult {
+        let span = info_span!(
+            "command",
+            drone_id = %drone_id,
+            mission_id = tracing::field::Empty,
+            tenant_id = self.config.tenant.as_ref().map(TenantId::as_str),
+            command = ?command,
+        );
+        if let Some(mission_id) = self.mission_id() {
+            span.record("mission_id", tracing::field::display(mission_id));
+        }
+        self.deliver_command(drone_id, command).instrument(span).await
+    }
+
+    async fn deliver_command(&self, drone_id: &DroneId, command: &DroneCommandType) -> CommandResult {
+        if let Some(owner) = self.handoffs.owner(drone_id) {
+            return CommandResult::failed(
+                drone_id.clone(),
+                format!("controlled by station {}", owner.station),
+            );
+        }
+
+        // Pointing is resolved here, where the drone's position is known
+        let resolved;
+        let command = match command {
+            DroneCommandType::PointAt { target, zoom } => {
+                let Some(tracked) = self.drones.get(drone_id) else {
+                    return CommandResult::failed(drone_id.clone(), "drone is not tracked");
+                };
+                let current = tracked.drone.telemetry.gimbal.unwrap_or_default();
+                let gimbal = GimbalState::pointing_at(
+                    &tracked.drone.position,
+                    tracked.drone.telemetry.heading,
+                    target,
+                    zoom.unwrap_or(current.zoom),
+                );
+                resolved = DroneCommandType::SetGimbal {
+                    pan: gimbal.pan,
+                    tilt: gimbal.tilt,
+                    zoom: Some(gimbal.zoom),
+                };
+                &resolved
+            }
+            command => command,
+        };
+
+        let result = self.commands.dispatch(drone_id, command).await;
+        match result.outcome {
+            CommandOutcome::TransportError => {
+                warn!(
+                    "Failed to send command to {}: {}",
+                    drone_id,
+                    result.error.as_deref().unwrap_or_default()
+                );
+                self.health.record_error();
+            }
+            _ => info!("Command {:?} sent to drone {} ({:?})", command, drone_id, result.outcome),
+        }
+        if result.outcome.is_success() {
+            if !matches!(command, DroneCommandType::ReturnToBase) {
+                self.status_inference.return_cancelled(drone_id);
+            }
+            match command {
+                DroneCommandType::ReturnToBase => {
+                    self.status_inference.return_commanded(drone_id, self.clock.now());
+                    self.set_drone_status(drone_id, DroneStatus::Rtb);
+                }
+                DroneCommandType::GoToWaypoint { waypoint_id } => {
+                    self.skip_to_waypoint(drone_id, waypoint_id).await;
+                }
+                DroneCommandType::SetGimbal { pan, tilt, zoom } => {
+                    self.set_gimbal(drone_id, *pan, *tilt, *zoom);
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Record a commanded gimbal attitude and publish it with the drone's
+    /// position, so clients can redraw the sensor footprint straight away
+    fn set_gimbal(&self, drone_id: &DroneId, pan: f64, tilt: f64, zoom: Option<f64>) {
+        let Some(mut tracked) = self.drones.get_mut(drone_id) else {
+            return;
+        };
+        let current = tracked.drone.telemetry.gimbal.unwrap_or_default();
+        tracked.drone.telemetry.gimbal = Some(GimbalState {
+            pan,
+            tilt,
+            zoom: zoom.unwrap_or(current.zoom),
+        });
+        let (position, telemetry) = (tracked.drone.position, tracked.drone.telemetry.clone());
+        drop(tracked);
+        self.emit(Event::drone_position_updated(drone_id.clone(), position, telemetry));
+    }
+
+    /// After a go-direct command, mark every waypoint before the target as
+    /// skipped; progress then runs from the drone's current position.
+    /// Commands to the current or an earlier waypoint change nothing.
+    async fn skip_to_waypoint(&self, drone_id: &DroneId, waypoint_id: &WaypointId) {
+        let Some(mission) = self.mission.read().clone() else {
+            return;
+        };
+        let Some(target) = mission.waypoints.iter().position(|wp| &wp.id == waypoint_id) else {
+            return;
+        };
+        let Some(mut tracked) = self.drones.get_mut(drone_id) else {
+            return;
+        };
+        if target <= tracked.waypoint_index {
+            return;
+        }
+
+        let skipped: Vec<WaypointId> = mission.waypoints[tracked.waypoint_index..target]
+            .iter()
+            .map(|wp| wp.id.clone())
+            .collect();
+        // A go-direct command ends any hold. The drone was never cleared to
+        // depart a checkpoint, so only the skips are reported for it
+        let checkpoint = self.checkpoints.clear(drone_id);
+        if checkpoint.is_some() {
+            tracked
+                .active_alerts
+                .retain(|a| a.alert_type != AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()));
+            let previous = tracked.status_before_loiter.take().unwrap_or(DroneStatus::Moving);
+            if tracked.drone.status == DroneStatus::Loitering {
+                self.change_status(&mut tracked, &mission, previous);
+            }
+        } else if tracked.loiter_until.is_some() {
+            self.end_loiter(&mut tracked, &mission);
+        }
+        tracked.waypoint_index = target;
+        tracked.waypoint_progress = 0.0;
+        tracked.approach_notified = None;
+        tracked.direct_from = Some(tracked.drone.position);
+        let position = tracked.drone.position;
+        drop(tracked);
+
+        info!(
+            "Drone {} going direct to {}, skipping {} waypoints",
+            drone_id,
+            mission.waypoints[target].name,
+            skipped.len()
+        );
+        if let Some(hold) = checkpoint {
+            let mut resolved = Alert::new(
+                AlertSeverity::Info,
+                AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()),
+                format!("Checkpoint {} hold ended by a go-direct command", hold.waypoint_name),
+            )
+            .for_drone(drone_id.clone());
+            resolved.resolved = true;
+            self.emit(Event::alert(resolved.clone()));
+            let _ = self.alert_tx.try_send(resolved);
+        }
+        for waypoint_id in skipped {
+            self.emit(Event::waypoint_skipped(drone_id.clone(), waypoint_id.clone(), position));
+            if let Some(db) = &self.db {
+                if let Err(e) = db.waypoints().record_skipped(drone_id, &waypoint_id, &mission.id, &position).await {
+                    warn!("Failed to persist skip of waypoint {}: {}", waypoint_id, e);
+                    db.health().record_error();
+                }
+            }
+        }
+    }
+
+    // ========================================================================
+    // COMMAND TRANSPORT
+    // ========================================================================
+
+    /// Send an operator command to one drone
+    pub async fn send_command(&self, drone_id: &DroneId, command: &DroneCommandType) -> CommandResult {
+        self.dispatch_command(drone_id, command).await
+    }
+
+    /// Make a transport available for drones bound to its kind
+    pub fn register_transport(&self, transport: Arc<dyn CommandTransport>) {
+        info!("Command transport {} registered", transport.kind());
+        self.commands.register(transport);
+    }
+
+    /// Transport kinds that can currently carry commands
+    pub fn available_transports(&self) -> Vec<TransportKind> {
+        self.commands.available()
+    }
+
+    /// The drone's command transport; the mesh unless bound otherwise
+    pub fn transport_binding(&self, drone_id: &DroneId) -> TransportBinding {
+        self.commands.binding(drone_id)
+    }
+
+    /// Drones bound to a transport other than the default
+    pub fn transport_bindings(&self) -> Vec<(DroneId, TransportBinding)> {
+        self.commands.bindings()
+    }
+
+    /// Bind a drone to a transport (or back to the mesh, with `None`) and persist it
+    pub async fn set_transport_binding(
+        &self,
+        drone_id: &DroneId,
+        binding: Option<TransportBinding>,
+    ) -> anyhow::Result<()> {
+        if let Some(db) = &self.db {
+            db.drones().set_transport_binding(drone_id, binding.as_ref()).await?;
+        }
+
+        match binding {
+            Some(binding) => self.commands.bind(drone_id.clone(), binding),
+            None => {
+                self.commands.unbind(drone_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// The drone's designated telemetry source (`None` = any source)
+    pub fn telemetry_source(&self, drone_id: &DroneId) -> Option<TelemetrySource> {
+        self.sources.designated(drone_id)
+    }
+
+    /// Drones with a designated telemetry source
+    pub fn telemetry_sources(&self) -> Vec<(DroneId, TelemetrySource)> {
+        self.sources.designations()
+    }
+
+    /// Ongoing conflicts: drones reporting from an undesignated source
+    pub fn source_conflicts(&self) -> Vec<SourceConflict> {
+        self.sources.conflicts(self.clock.now())
+    }
+
+    /// Designate a drone's telemetry source (or accept any, with `None`) and persist it
+    pub async fn set_telemetry_source(
+        &self,
+        drone_id: &DroneId,
+        source: Option<TelemetrySource>,
+    ) -> anyhow::Result<()> {
+        if let Some(db) = &self.db {
+            db.drones().set_telemetry_source(drone_id, source).await?;
+        }
+        self.sources.designate(drone_id, source);
+        Ok(())
+    }
+
+    /// Load persisted telemetry source designations from the registry
+    pub async fn load_telemetry_sources(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let sources = db.drones().get_telemetry_sources().await?;
+        info!("Loaded telemetry source designations for {} drones", sources.len());
+        for (drone_id, source) in sources {
+            self.sources.designate(&drone_id, Some(source));
+        }
+        Ok(())
+    }
+
+    /// The drone's halo color and icon, if it has been marked
+    pub fn drone_marking(&self, drone_id: &DroneId) -> Option<DroneMarking> {
+        self.markings.get(drone_id)
+    }
+
+    /// All drone markings, by drone ID
+    pub fn drone_markings(&self) -> Vec<(DroneId, DroneMarking)> {
+        self.markings.all()
+    }
+
+    /// Mark a drone (or clear its marking, with `None`), persist it and
+    /// broadcast it as a `FleetMarkingChanged` event. Refused with
+    /// [`ColorTaken`] when another drone's halo color is too close to tell apart.
+    pub async fn set_drone_marking(&self, drone_id: &DroneId, marking: Option<DroneMarking>) -> anyhow::Result<()> {
+        // The color is taken before the write so a concurrent request for a
+        // close color is refused rather than both being stored
+        let previous = match &marking {
+            Some(marking) => Some(self.markings.reserve(drone_id, marking.clone())?),
+            None => None,
+        };
+        if let Some(db) = &self.db {
+            if let Err(e) = db.drones().set_drone_marking(drone_id, marking.as_ref()).await {
+                if let (Some(marking), Some(previous)) = (&marking, previous) {
+                    self.markings.release(drone_id, marking, previous);
+                }
+                db.health().record_error();
+                return Err(e.into());
+            }
+        }
+        if marking.is_none() {
+            self.markings.set(drone_id, None);
+        }
+        self.emit(Event::fleet_marking_changed(drone_id.clone(), marking));
+        Ok(())
+    }
+
+    /// Load persisted drone markings from the registry
+    pub async fn load_drone_markings(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let markings = db.drones().get_drone_markings().await?;
+        info!("Loaded markings for {} drones", markings.len());
+        for (drone_id, marking) in markings {
+            self.markings.set(&drone_id, Some(marking));
+        }
+        Ok(())
+    }
+
+    /// Load persisted transport bindings from the registry
+    pub async fn load_transport_bindings(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let bindings = db.drones().get_transport_bindings().await?;
+        info!("Loaded command transport bindings for {} drones", bindings.len());
+        for (drone_id, binding) in bindings {
+            self.commands.bind(drone_id, binding);
+        }
+        Ok(())
+    }
+
+    async fn persist_scheduled(&self, command: &ScheduledCommand) {
+        if let Some(db) = &self.db {
+            if let Err(e) = db.schedules().save_scheduled_command(&command.to_record()).await {
+                warn!("Failed to persist scheduled command {}: {}", command.id, e);
+                db.health().record_error();
+            }
+        }
+    }
+
+    /// Load persisted scheduled commands
+    pub async fn load_scheduled_commands(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let mut commands = Vec::new();
+        for record in db.schedules().scheduled_commands().await? {
+            match ScheduledCommand::from_record(&record) {
+                Ok(command) => commands.push(command),
+                Err(e) => warn!("Skipping unreadable scheduled command {}: {}", record.id, e),
+            }
+        }
+        let pending = commands.iter().filter(|c| c.state == ScheduleState::Pending).count();
+        self.scheduler.restore(commands);
+
+        info!("Loaded {} pending scheduled commands", pending);
+        Ok(())
+    }
+
+    /// Take over each drone's last persisted report from the past
+    /// `lookback`, e.g. on becoming leader after another instance flew the
+    /// fleet; nothing is persisted or raised again. Returns the mission of
+    /// the newest report.
+    pub async fn load_last_reports(&self, lookback: Duration) -> anyhow::Result<Option<MissionId>> {
+        let Some(db) = &self.db else {
+            return Ok(None);
+        };
+
+        let now = self.clock.now();
+        let since = now - chrono::Duration::from_std(lookback).unwrap_or_default();
+        let drone_ids: Vec<DroneId> = self.drones.iter().map(|r| r.key().clone()).collect();
+        let mut newest: Option<(DateTime<Utc>, MissionId)> = None;
+        let mut restored = 0;
+        for drone_id in drone_ids {
+            let Some(record) = db.telemetry().get_at(&drone_id, since, now).await? else {
+                continue;
+            };
+            if let Some(mission_id) = record.mission_id {
+                if newest.as_ref().is_none_or(|(at, _)| record.timestamp > *at) {
+                    newest = Some((record.timestamp, MissionId(mission_id)));
+                }
+            }
+            let (position, telemetry) = record.position_telemetry();
+            if let Some(mut tracked) = self.drones.get_mut(&drone_id) {
+                if let Some(status) = record.drone_status() {
+                    tracked.drone.status = status;
+                }
+                tracked.update_position_at(position, telemetry, record.timestamp);
+                restored += 1;
+            }
+        }
+
+        info!("Restored the last reports of {} drones", restored);
+        Ok(newest.map(|(_, mission_id)| mission_id))
+    }
+
+    // ========================================================================
+    // ALERT THRESHOLDS
+    // ========================================================================
+
+    /// Resolve thresholds for a drone: drone override, then type override, then global
+    pub fn thresholds_for(&self, drone: &Drone) -> AlertThresholds {
+        let drone_overrides = self.drone_thresholds(&drone.id);
+        self.resolve_thresholds(&drone.drone_type, drone_overrides.as_ref())
+    }
+
+    /// Resolve thresholds for a drone type with an explicit drone-level override
+    pub fn resolve_thresholds(
+        &self,
+        drone_type: &DroneType,
+        drone_overrides: Option<&ThresholdOverrides>,
+    ) -> AlertThresholds {
+        let mut thresholds = self.config.alert_thresholds;
+
+        if let Some(overrides) = self.type_thresholds.read().get(drone_type) {
+            thresholds = overrides.apply_to(thresholds);
+        }
+        if let Some(overrides) = drone_overrides {
+            thresholds = overrides.apply_to(thresholds);
+        }
+
+        thresholds
+    }
+
+    /// Global thresholds applied when no override matches
+    pub fn global_thresholds(&self) -> AlertThresholds {
+        self.config.alert_thresholds
+    }
+
+    /// Effective thresholds for a tracked drone
+    pub fn effective_thresholds(&self, drone_id: &DroneId) -> Option<AlertThresholds> {
+        self.drones
+            .get(drone_id)
+            .map(|tracked| tracked.drone.clone())
+            .map(|drone| self.thresholds_for(&drone))
+    }
+
+    /// Get the override set for a single drone
+    pub fn drone_thresholds(&self, drone_id: &DroneId) -> Option<ThresholdOverrides> {
+        self.drone_thresholds.get(drone_id).map(|o| *o)
+    }
+
+    /// Get the override set for a drone type
+    pub fn type_thresholds(&self, drone_type: &DroneType) -> Option<ThresholdOverrides> {
+        self.type_thresholds.read().get(drone_type).copied()
+    }
+
+    /// Set (or clear, when empty) a drone's threshold overrides and persist them
+    pub async fn set_drone_thresholds(
+        &self,
+        drone_id: &DroneId,
+        overrides: ThresholdOverrides,
+    ) -> anyhow::Result<()> {
+        if let Some(db) = &self.db {
+            db.drones().set_alert_thresholds(drone_id, &overrides).await?;
+        }
+
+        if overrides.is_empty() {
+            self.drone_thresholds.remove(drone_id);
+        } else {
+            self.drone_thresholds.insert(drone_id.clone(), overrides);
+        }
+
+        Ok(())
+    }
+
+    /// Set (or clear, when empty) a drone type's threshold overrides and persist them
+    pub async fn set_type_thresholds(
+        &self,
+        drone_type: DroneType,
+        overrides: ThresholdOverrides,
+    ) -> anyhow::Result<()> {
+        if let Some(db) = &self.db {
+            db.drones().set_type_alert_thresholds(&drone_type, &overrides).await?;
+        }
+
+        let mut type_thresholds = self.type_thresholds.write();
+        if overrides.is_empty() {
+            type_thresholds.remove(&drone_type);
+        } else {
+            type_thresholds.insert(drone_type, overrides);
+        }
+
+        Ok(())
+    }
+
+    /// Load persisted threshold overrides from the registry
+    pub async fn load_thresholds(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        for (drone_id, overrides) in db.drones().get_alert_thresholds().await? {
+            self.drone_thresholds.insert(drone_id, overrides);
+        }
+        let type_overrides = db.drones().get_type_alert_thresholds().await?;
+        self.type_thresholds.write().extend(type_overrides);
+
+        info!(
+            "Loaded alert threshold overrides for {} drones",
+            self.drone_thresholds.len()
+        );
+        Ok(())
+    }
+
+    /// The active mission started: drones recalled from an earlier one are
+    /// no longer returning
+    pub fn mission_started(&self) {
+        self.status_inference.mission_started();
+    }
+
+    /// Set active mission
+    pub fn set_mission(&self, mission: Mission) {
+        self.kpis.mission_changed(&mission);
+        if let Some(formation) = mission.formation {
+            self.convoy.set_formation(formation);
+        }
+        if let Some(version) = self.mission_sync.publish(&mission) {
+            info!("Published route version {} of mission {}", version, mission.name);
+        }
+        // Holds belong to the old route: drones held at its checkpoints or
+        // loiter waypoints fly on
+        let mut released = Vec::new();
+        for mut tracked in self.drones.iter_mut() {
+            tracked.arrival.reset();
+            tracked.loiter_until = None;
+            let held = self.checkpoints.clear(&tracked.drone.id).is_some();
+            if held {
+                tracked
+                    .active_alerts
+                    .retain(|a| a.alert_type != AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()));
+                released.push(tracked.drone.id.clone());
+            }
+            let previous = tracked.status_before_loiter.take().unwrap_or(DroneStatus::Moving);
+            if tracked.drone.status == DroneStatus::Loitering {
+                self.change_status(&mut tracked, &mission, previous);
+            }
+        }
+        let previous_mission = self.mission.read().as_ref().map(|m| m.id.clone());
+        if let Some(previous) = previous_mission.filter(|id| *id != mission.id) {
+            let positions = self
+                .drones
+                .iter()
+                .map(|r| (r.key().clone(), r.value().drone.position))
+                .collect();
+            let exits = self.zones.close_mission(&previous, &positions, self.clock.now());
+            self.record_zone_exits(exits);
+        }
+        for drone_id in released {
+            let mut resolved = Alert::new(
+                AlertSeverity::Info,
+                AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()),
+                "Checkpoint hold ended by a new mission".to_string(),
+            )
+            .for_drone(drone_id);
+            resolved.resolved = true;
+            self.emit(Event::alert(resolved.clone()));
+            let _ = self.alert_tx.try_send(resolved);
+        }
+        *self.mission.write() = Some(mission);
+    }
+
+    /// Get active mission
+    pub fn get_mission(&self) -> Option<Mission> {
+        self.mission.read().clone()
+    }
+
+    /// Get all tracked drones
+    pub fn get_all_drones(&self) -> Vec<TrackedDrone> {
+        self.drones.iter().map(|r| r.value().clone()).collect()
+    }
+
+    /// Feed a CV geo-estimate into position fusion
+    ///
+    /// The estimate is first corrected for calibration drift, in place.
+    /// Estimates are stamped on arrival with the simulation clock, the same
+    /// time base GPS fixes are fused against.
+    pub fn ingest_cv_result(&self, result: &mut TrackingResult) {
+        if let Some(position) = &mut result.estimated_position {
+            *position = self.drift.correct(position);
+            let position = *position;
+            self.fusion.record_cv(
+                &result.drone_id,
+                position,
+                result.position_uncertainty_m,
+                result.confidence,
+                self.clock.now(),
+            );
+            let sigma_m = result
+                .position_uncertainty_m
+                .filter(|u| u.is_finite() && *u > 0.0)
+                .unwrap_or(self.config.fusion.cv_sigma_m / result.confidence.clamp(0.1, 1.0));
+            self.proximity.record_cv(&result.drone_id, position, sigma_m, self.clock.now());
+        }
+    }
+
+    /// Start publishing CV results: each admitted result is fused, broadcast
+    /// as a `CvTrackingUpdate` event and persisted in batches
+    pub fn spawn_cv_publisher(self: &Arc<Self>, config: CvPublisherConfig) -> tokio::task::JoinHandle<()> {
+        let db = self.db.clone().filter(|_| self.config.db_enabled);
+        let tracker = Arc::clone(self);
+        let (pipeline, handle) = CvPublisher::spawn(config, db, move |mut result| {
+            tracker.ingest_cv_result(&mut result);
+            tracker.emit(Event::cv_tracking_update(result));
+        });
+        *self.cv.write() = Some(pipeline);
+        handle
+    }
+
+    /// Stop taking CV results and wait until the queued ones are published
+    /// and persisted
+    pub async fn close_cv_publisher(&self) {
+        let pipeline = self.cv.write().take();
+        if let Some(pipeline) = pipeline {
+            pipeline.close().await;
+        }
+    }
+
+    /// CV result intake (`None` until `spawn_cv_publisher`)
+    pub fn cv_pipeline(&self) -> Option<CvPipeline> {
+        self.cv.read().clone()
+    }
+
+    /// CV detection and tracking parameters in effect
+    pub fn cv_tuning(&self) -> CvTuningRevision {
+        self.cv_tuning.current()
+    }
+
+    /// Replace the CV parameters, save them and broadcast them as a
+    /// `CvConfigChanged` event; refused when invalid or no longer at
+    /// `expected_revision`
+    pub async fn set_cv_tuning(
+        &self,
+        tuning: CvTuning,
+        expected_revision: Option<u64>,
+    ) -> Result<CvTuningRevision, CvTuningUpdateError> {
+        let _update = self.cv_tuning_updates.lock().await;
+        let updated = self.cv_tuning.next(tuning, expected_revision, Utc::now())?;
+        if let Some(db) = &self.db {
+            let record = CvTuningRecord {
+                revision: updated.revision as i64,
+                tuning: serde_json::to_string(&updated.tuning)
+                    .map_err(|e| CvTuningUpdateError::Storage(e.to_string()))?,
+                updated_at: updated.updated_at,
+            };
+            if let Err(e) = db.tracking().save_cv_tuning(&record).await {
+                db.health().record_error();
+                return Err(CvTuningUpdateError::Storage(e.to_string()));
+            }
+        }
+        self.cv_tuning.commit(updated.clone());
+        info!("CV parameters updated to revision {}", updated.revision);
+        self.emit(Event::cv_config_changed(updated.revision, updated.tuning.clone()));
+        Ok(updated)
+    }
+
+    /// Restore the last saved CV parameters and announce them to the CV
+    /// pipeline
+    pub async fn load_cv_tuning(&self) -> anyhow::Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+        let Some(record) = db.tracking().cv_tuning().await? else {
+            return Ok(());
+        };
+
+        let tuning: CvTuning = serde_json::from_str(&record.tuning)?;
+        tuning.validate()?;
+        let restored = CvTuningRevision {
+            revision: record.revision.max(0) as u64,
+            updated_at: record.updated_at,
+            tuning,
+        };
+        info!("Loaded CV parameters at revision {}", restored.revision);
+        self.cv_tuning.commit(restored.clone());
+        self.emit(Event::cv_config_changed(restored.revision, restored.tuning));
+        Ok(())
+    }
+
+    /// Fused position and per-source residuals for a drone
+    pub fn fusion_report(&self, drone_id: &DroneId) -> Option<FusionReport> {
+        self.fusion.report(drone_id)
+    }
+
+    // ========================================================================
+    // CV CALIBRATION DRIFT
+    // ========================================================================
+
+    /// Fitted CV drift and the calibration correction in effect
+    pub fn drift_report(&self) -> DriftReport {
+        self.drift.report()
+    }
+
+    /// Apply the latest drift estimate to later CV estimates
+    pub fn apply_drift_correction(&self) -> Option<CalibrationCorrection> {
+        let correction = self.drift.apply_estimate()?;
+        info!(
+            "CV calibration correction applied: {:.1}° about {:.5},{:.5}, offset {:.1} m E {:.1} m N",
+            correction.rotation_deg,
+            correction.pivot.latitude,
+            correction.pivot.longitude,
+            correction.east_m,
+            correction.north_m
+        );
+        Some(correction)
+    }
+
+    /// Stop correcting CV estimates; `false` if no correction was applied
+    pub fn clear_drift_correction(&self) -> bool {
+        self.drift.clear_correction()
+    }
+
+    fn raise_source_conflict_alert(&self, drone_id: &DroneId, designated: TelemetrySource, source: TelemetrySource) {
+        let message = format!(
+            "Drone {} is reporting over {} but takes telemetry from {}; reports rejected",
+            drone_id, source, designated
+        );
+        warn!("{}", message);
+        self.raise_alert(
+            Alert::new(AlertSeverity::Warning, AlertType::Custom(SOURCE_CONFLICT_ALERT_TYPE.into()), message)
+                .for_drone(drone_id.clone()),
+        );
+    }
+
+    fn raise_drift_alert(&self, estimate: &DriftEstimate) {
+        let mut message = format!(
+            "CV calibration drifted {:.0} m and {:.1}° from GPS over {} fixes",
+            estimate.offset_m, estimate.rotation_deg, estimate.samples
+        );
+        if estimate.corrected {
+            message.push_str("; correction applied");
+        }
+        warn!("{}", message);
+        self.raise_alert(Alert::new(
+            AlertSeverity::Warning,
+            AlertType::Custom(DRIFT_ALERT_TYPE.into()),
+            message,
+        ));
+    }
+
+    /// Drones matching a query, evaluated against live tracker state
+    pub fn query_drones(&self, query: &DroneQuery) -> Vec<TrackedDrone> {
+        let mission = self.get_mission();
+        self.drones
+            .iter()
+            .filter(|r| query.matches(&r.value().drone, mission.as_ref()))
+            .map(|r| r.value().clone())
+            .collect()
+    }
+
+    /// Get specific drone
+    pub fn get_drone(&self, id: &DroneId) -> Option<TrackedDrone> {
+        self.drones.get(id).map(|r| r.value().clone())
+    }
+
+    /// Read a tracked drone in place, without cloning its history
+    pub fn inspect_drone<R>(&self, id: &DroneId, f: impl FnOnce(&TrackedDrone) -> R) -> Option<R> {
+        self.drones.get(id).map(|r| f(r.value()))
+    }
+
+    /// Get drone count
+    pub fn drone_count(&self) -> usize {
+        self.drones.len()
+    }
+
+    /// Start the tracking engine
+    pub async fn start(&self) -> anyhow::Result<()> {
+        *self.running.write() = true;
+        info!("🚀 Drone Tracker started");
+        
+        // Start P2P if available
+        if let Some(p2p) = &self.p2p {
+            p2p.start().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop the tracking engine
+    pub async fn stop(&self) -> anyhow::Result<()> {
+        *self.running.write() = false;
+        info!("🛑 Drone Tracker stopped");
+
+        if let Some(p2p) = &self.p2p {
+            p2p.stop().await?;
+        }
+
+        Ok(())
+    }
+
+    /// When the tracker started and how many command dispatches failed
+    pub fn health(&self) -> &SubsystemHealth {
+        &self.health
+    }
+
+    /// Check if tracker is running
+    pub fn is_running(&self) -> bool {
+        *self.running.read()
+    }
+}
+
+/// Write an evicted drone's final state to `drone_snapshots`
+async fn persist_snapshot(db: &DbClient, snapshot: &DroneSnapshot, evicted_at: DateTime<Utc>) {
+    let record = match serde_json::to_string(snapshot) {
+        Ok(json) => drone_db::DroneSnapshotRecord {
+            drone_id: snapshot.drone.id.0.clone(),
+            snapshot: json,
+            last_update: snapshot.last_update,
+            evicted_at,
+        },
+        Err(e) => {
+            warn!("Failed to serialize snapshot of {}: {}", snapshot.drone.id, e);
+            return;
+        }
+    };
+    if let Err(e) = db.drones().save_snapshot(&record).await {
+        warn!("Failed to persist snapshot of {}: {}", snapshot.drone.id, e);
+        db.health().record_error();
+    }
+}
+
+/// The mission's origin waypoint, else its first
+fn mission_origin(mission: &Mission) -> Option<GeoPosition> {
+    mission
+        .waypoints
+        .iter()
+        .find(|wp| wp.waypoint_type == WaypointType::Origin)
+        .or(mission.waypoints.first())
+        .map(|wp| wp.position)
+}
+
+/// " (SCOUT)" style suffix for alert messages
+fn role_suffix(role: Option<ConvoyRole>) -> String {
+    role.map(|r| format!(" ({})", r)).unwrap_or_default()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tracker_creation() {
+        let config = TrackerConfig {
+            //cv_enabled: false,
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        
+        let tracker = DroneTracker::new(config).await;
+        assert!(tracker.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_drone_registration() {
+        let config = TrackerConfig {
+            //cv_enabled: false,
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        
+        let tracker = DroneTracker::new(config).await.unwrap();
+        
+        let drone = Drone::new(DroneId::new("REAPER-01"), "Alpha Lead");
+        tracker.register_drone(drone);
+        
+        assert_eq!(tracker.drone_count(), 1);
+        assert!(tracker.get_drone(&DroneId::new("REAPER-01")).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_position_update() {
+        let config = TrackerConfig {
+            //cv_enabled: false,
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        
+        let tracker = DroneTracker::new(config).await.unwrap();
+        
+        let drone = Drone::new(DroneId::new("REAPER-01"), "Alpha Lead");
+        tracker.register_drone(drone);
+        
+        let position = GeoPosition::new(34.5553, 69.2075, 3000.0);
+        let telemetry = Telemetry::default();
+        
+        tracker.update_drone_position(
+            &DroneId::new("REAPER-01"),
+            position,
+            telemetry,
+        ).await.unwrap();
+        
+        let tracked = tracker.get_drone(&DroneId::new("REAPER-01")).unwrap();
+        assert_eq!(tracked.drone.position.latitude, 34.5553);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_report_processed_once() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+        let mut events = tracker.subscribe();
+
+        let report = |latitude: f64, sequence: u64| {
+            let telemetry = Telemetry { sequence: Some(sequence), ..Default::default() };
+            (GeoPosition::new(latitude, 69.2075, 3000.0), telemetry)
+        };
+        let positions_emitted = |events: &mut tokio::sync::broadcast::Receiver<Event>| {
+            std::iter::from_fn(|| events.try_recv().ok())
+                .filter(|e| matches!(e.payload, EventPayload::DronePosition(_)))
+                .count()
+        };
+
+        let (position, telemetry) = report(34.5553, 7);
+        tracker.update_drone_position(&drone_id, position, telemetry.clone()).await.unwrap();
+        tracker.update_drone_position(&drone_id, position, telemetry).await.unwrap();
+        assert_eq!(positions_emitted(&mut events), 1);
+
+        let (position, telemetry) = report(34.5560, 10);
+        tracker.update_drone_position(&drone_id, position, telemetry).await.unwrap();
+        let (position, telemetry) = report(34.5557, 9);
+        tracker.update_drone_position(&drone_id, position, telemetry).await.unwrap();
+        assert_eq!(positions_emitted(&mut events), 1);
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().drone.position.latitude, 34.5560);
+
+        let stats = &tracker.sequence_stats()[0];
+        assert_eq!((stats.duplicates, stats.gaps, stats.missing, stats.late), (1, 1, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_telemetry_sanitized_or_rejected() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+        let position = GeoPosition::new(34.5553, 69.2075, 3000.0);
+
+        let skewed = Telemetry { heading: 450.0, speed: -5.0, ..Telemetry::default() };
+        tracker.update_drone_position(&drone_id, position, skewed).await.unwrap();
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.telemetry.heading, 90.0);
+        assert_eq!(tracked.drone.telemetry.speed, 0.0);
+
+        let broken = Telemetry { temperature: f64::NAN, ..Telemetry::default() };
+        assert!(tracker.update_drone_position(&drone_id, position, broken).await.is_err());
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().drone.telemetry.temperature, 25.0);
+
+        let validator = tracker.telemetry_validator();
+        assert_eq!(validator.clamped(drone_core::TelemetryField::Heading), 1);
+        assert_eq!(validator.rejected(drone_core::TelemetryField::Temperature), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_reports_say_why() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+        let position = GeoPosition::new(34.5553, 69.2075, 3000.0);
+
+        let broken = Telemetry { temperature: f64::NAN, ..Telemetry::default() };
+        let rejected = tracker.update_drone_position(&drone_id, position, broken).await.unwrap_err();
+        assert!(matches!(rejected, ReportRejected::Invalid(ref e) if e.field() == drone_core::TelemetryField::Temperature));
+
+        tracker.sources.designate(&drone_id, Some(TelemetrySource::P2p));
+        let rejected = tracker.update_drone_position(&drone_id, position, Telemetry::default()).await.unwrap_err();
+        assert!(matches!(
+            rejected,
+            ReportRejected::WrongSource { designated: TelemetrySource::P2p, reported: TelemetrySource::Api, .. }
+        ));
+        tracker
+            .update_drone_position_from(&drone_id, TelemetrySource::P2p, position, Telemetry::default())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_emergency_marks_drone_and_raises_alert() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+        tracker.register_drone(Drone::new(DroneId::new("REAPER-01"), "Alpha Lead"));
+        tracker.register_drone(Drone::new(DroneId::new("REAPER-02"), "Alpha Two"));
+
+        let message = DroneMessage::emergency(
+            DroneId::new("REAPER-01"),
+            drone_p2p::protocol::EmergencyType::SystemFailure,
+            GeoPosition::new(34.5553, 69.2075, 3000.0),
+            "Engine failure".into(),
+        );
+        let response = tracker.handle_p2p_message(&message);
+        assert!(response.is_some());
+
+        let alert = alerts.try_recv().unwrap();
+        assert_eq!(alert.severity, AlertSeverity::Emergency);
+        assert_eq!(tracker.get_drone(&DroneId::new("REAPER-01")).unwrap().drone.status, DroneStatus::Rtb);
+        assert_eq!(tracker.emergency().buddy_for(&DroneId::new("REAPER-01")), Some(DroneId::new("REAPER-02")));
+        assert_eq!(tracker.convoy().get_formation(), convoy::Formation::Spread);
+    }
+
+    #[tokio::test]
+    async fn test_eviction_caps_drones_and_alerts() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            eviction: EvictionConfig {
+                offline_after: Some(Duration::from_secs(3600)),
+                max_drones: Some(2),
+                max_alerts_per_drone: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut events = tracker.subscribe();
+        let (a, b, c) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"), DroneId::new("REAPER-03"));
+        tracker.register_drone(Drone::new(a.clone(), "Alpha Lead"));
+        tracker.register_drone(Drone::new(b.clone(), "Alpha Wing"));
+        tracker.drones.get_mut(&a).unwrap().last_update -= chrono::Duration::minutes(5);
+
+        // Over the cap, the longest silent drone makes room
+        tracker.register_drone(Drone::new(c.clone(), "Alpha Trail"));
+        assert_eq!(tracker.drone_count(), 2);
+        assert!(tracker.get_drone(&a).is_none());
+        let evicted = events.try_recv().unwrap();
+        assert_eq!(evicted.event_type, drone_core::EventType::DroneEvicted);
+
+        for i in 0..5 {
+            tracker.raise_alert(Alert::new(AlertSeverity::Info, AlertType::BatteryLow, format!("#{}", i)).for_drone(b.clone()));
+        }
+        let alerts = tracker.get_drone(&b).unwrap().active_alerts;
+        assert_eq!(alerts.iter().map(|a| a.message.as_str()).collect::<Vec<_>>(), ["#2", "#3", "#4"]);
+
+        tracker.sources.designate(&c, Some(TelemetrySource::Simulated));
+        tracker.markings.set(&c, Some(DroneMarking::new(drone_core::HaloColor::GREEN)));
+        tracker.drones.get_mut(&c).unwrap().last_update -= chrono::Duration::hours(2);
+        assert_eq!(tracker.evict_stale_drones().await, vec![(c.clone(), EvictionReason::Offline)]);
+        let footprint = tracker.footprint();
+        assert_eq!((footprint.drones, footprint.active_alerts), (1, 3));
+        assert_eq!(tracker.sources.designated(&c), None);
+        assert_eq!(tracker.markings.get(&c), None);
+
+        // A drone that reported after it was selected stays
+        let selected_at = tracker.get_drone(&b).unwrap().last_update - chrono::Duration::seconds(1);
+        assert!(tracker.evict(&b, EvictionReason::Offline, selected_at).is_none());
+        assert_eq!(tracker.drone_count(), 1);
+
+        // Reports from drones not tracked here leave nothing behind
+        let telemetry = Telemetry { sequence: Some(7), ..Default::default() };
+        tracker.update_drone_position(&c, GeoPosition::new(34.5, 69.2, 100.0), telemetry).await.unwrap();
+        assert_eq!(tracker.footprint().sequences, 0);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_suppresses_alerts() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+        tracker.add_suppression(Some(drone_id.clone()), Some(AlertType::BatteryLow), SuppressionWindow::WhileMaintenance, None);
+        let battery = || Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "Battery low").for_drone(drone_id.clone());
+
+        tracker.set_drone_status(&drone_id, DroneStatus::Maintenance);
+        // Maintenance only ends in standby
+        assert!(!tracker.set_drone_status(&drone_id, DroneStatus::Moving));
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().drone.status, DroneStatus::Maintenance);
+        tracker.raise_alert(battery());
+        assert!(alerts.try_recv().is_err());
+        assert!(tracker.get_drone(&drone_id).unwrap().active_alerts.is_empty());
+
+        tracker.set_drone_status(&drone_id, DroneStatus::Standby);
+        tracker.raise_alert(battery());
+        assert!(alerts.try_recv().is_ok());
+        assert_eq!(tracker.suppression_stats().suppressed_total, 1);
+        assert_eq!(tracker.suppressions()[0].suppressed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_handoff_transfers_ownership() {
+        let config = || TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+        let origin = DroneTracker::new(config()).await.unwrap();
+        let peer = DroneTracker::new(config()).await.unwrap();
+        let mut mission = Mission::new("Sector crossing");
+        mission.waypoints = vec![
+            drone_core::Waypoint::new("WP01", "Start", 34.0, 69.0),
+            drone_core::Waypoint::new("WP02", "Boundary", 34.5, 69.0),
+        ];
+        origin.set_mission(mission.clone());
+        peer.set_mission(mission);
+
+        let id = DroneId::new("REAPER-01");
+        origin.register_drone(Drone::new(id.clone(), "Alpha Lead"));
+        origin.set_drone_role(&id, Some(ConvoyRole::Scout));
+        origin
+            .update_drone_position(&id, GeoPosition::new(34.0, 69.0, 3000.0), Telemetry::default())
+            .await
+            .unwrap();
+        assert!(origin.export_handoff(&DroneId::new("REAPER-99"), "gcs-north").is_none());
+
+        let package = origin.export_handoff(&id, "gcs-north").unwrap();
+        let package: HandoffPackage = serde_json::from_str(&serde_json::to_string(&package).unwrap()).unwrap();
+        let guard = origin.begin_handoff(&id).unwrap();
+        assert!(origin.begin_handoff(&id).is_none());
+        peer.accept_handoff(package.clone()).await.unwrap();
+        origin.complete_handoff(&package, "gcs-south", Some("https://gcs-south:3000".into())).await;
+        drop(guard);
+        // The peer now controls the drone and refuses it again
+        assert!(matches!(
+            peer.accept_handoff(package.clone()).await,
+            Err(HandoffFailure::ControlledHere(_))
+        ));
+
+        let received = peer.get_drone(&id).unwrap();
+        assert_eq!(received.waypoint_index, 1);
+        assert_eq!(peer.convoy().role(&id), Some(ConvoyRole::Scout));
+        assert!(!peer.is_remote(&id));
+        peer.update_drone_position(&id, GeoPosition::new(34.1, 69.0, 3000.0), Telemetry::default())
+            .await
+            .unwrap();
+
+        // The origin keeps a read-only copy
+        assert_eq!(origin.remote_owner(&id).unwrap().station, "gcs-south");
+        assert!(origin
+            .update_drone_position(&id, GeoPosition::new(34.1, 69.0, 3000.0), Telemetry::default())
+            .await
+            .is_err());
+        assert!(!origin.set_drone_status(&id, DroneStatus::Rtb));
+        assert!(origin.export_handoff(&id, "gcs-north").is_none());
+
+        // Handing the drone back restores local control
+        let back = peer.export_handoff(&id, "gcs-south").unwrap();
+        origin.accept_handoff(back).await.unwrap();
+        assert!(!origin.is_remote(&id));
+        assert_eq!(origin.get_drone(&id).unwrap().drone.position.latitude, 34.1);
+    }
+
+    #[tokio::test]
+    async fn test_group_command_fans_out() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let [r1, r2, r3] = ["REAPER-01", "REAPER-02", "REAPER-03"].map(DroneId::new);
+        for drone_id in [&r1, &r2, &r3] {
+            tracker.register_drone(Drone::new(drone_id.clone(), drone_id.as_str()));
+        }
+        tracker.set_drone_role(&r3, Some(ConvoyRole::Escort));
+
+        let members = vec![r1.clone(), DroneId::new("REAPER-99")];
+        let group = DroneGroup::new("alpha", members, Some(ConvoyRole::Escort), None, tracker.clock().now());
+        tracker.put_group(group).await.unwrap();
+
+        let report = tracker.send_group_command("alpha", &DroneCommandType::ReturnToBase).await.unwrap();
+        let outcomes: Vec<_> = report.results.iter().map(|r| (r.drone_id.as_str(), r.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("REAPER-01", CommandOutcome::Accepted),
+                ("REAPER-99", CommandOutcome::NotFound),
+                ("REAPER-03", CommandOutcome::Accepted),
+            ]
+        );
+        assert_eq!((report.succeeded, report.failed), (2, 1));
+        assert_eq!(tracker.get_drone(&r3).unwrap().drone.status, DroneStatus::Rtb);
+        assert_ne!(tracker.get_drone(&r2).unwrap().drone.status, DroneStatus::Rtb);
+
+        assert!(tracker.delete_group("alpha").await.unwrap().is_some());
+        assert!(tracker.send_group_command("alpha", &DroneCommandType::Pause).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_proximity_freshness_uses_simulation_clock() {
+        let config = TrackerConfig {
+            db_enabled: false,
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        // Simulated time an hour ahead of the wall clock
+        tracker.clock().pause();
+        tracker.clock().step(Duration::from_secs(3600));
+
+        let origin = GeoPosition::new(34.5, 69.2, 3000.0);
+        for (id, position, heading) in [
+            ("REAPER-01", origin, 0.0),
+            ("REAPER-02", origin.offset_by_m(40.0, 4000.0), 180.0),
+        ] {
+            let mut drone = Drone::new(DroneId::new(id), id);
+            drone.status = DroneStatus::Moving;
+            drone.position = position;
+            drone.telemetry.speed = 180.0;
+            drone.telemetry.heading = heading;
+            tracker.register_drone(drone);
+        }
+        assert_eq!(tracker.check_proximity().len(), 1);
+
+        // Past the telemetry max age there is nothing left to project
+        tracker.clock().step(Duration::from_secs(10));
+        assert!(tracker.check_proximity().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_events_stamped_on_simulation_clock() {
+        let config = TrackerConfig {
+            db_enabled: false,
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        tracker.clock().pause();
+        tracker.clock().step(Duration::from_secs(3600));
+        let mut events = tracker.subscribe();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        assert!(tracker.set_drone_status(&drone_id, DroneStatus::Moving));
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.event_type, drone_core::EventType::DroneStatusChanged);
+        assert_eq!(event.timestamp, tracker.clock().now());
+    }
+
+    #[tokio::test]
+    async fn test_staleness_on_simulation_clock() {
+        let config = TrackerConfig {
+            db_enabled: false,
+            ..Default::default()
         };
-        
         let tracker = DroneTracker::new(config).await.unwrap();
-        
-        let drone = Drone::new(DroneId::new("REAPER-01"), "Alpha Lead");
+        tracker.clock().pause();
+        tracker.clock().step(Duration::from_secs(3600));
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        // An hour ahead of the wall clock, the drone is fresh
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.last_update, tracker.clock().now());
+        assert!(!tracked.is_stale(Duration::from_secs(5), tracker.clock().now()));
+
+        tracker.clock().step(Duration::from_secs(6));
+        assert!(tracked.is_stale(Duration::from_secs(5), tracker.clock().now()));
+    }
+
+    #[tokio::test]
+    async fn test_partition_marks_unreachable_and_heals() {
+        let config = TrackerConfig {
+            p2p_enabled: true,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        tracker.clock().pause();
+        let [lead, wing] = ["REAPER-01", "REAPER-02"].map(DroneId::new);
+        for drone_id in [&lead, &wing] {
+            let mut drone = Drone::new(drone_id.clone(), drone_id.as_str());
+            drone.status = DroneStatus::Moving;
+            tracker.register_drone(drone);
+            tracker.handle_p2p_message(&DroneMessage::heartbeat(drone_id.clone()));
+        }
+        tracker.check_reachability().await;
+
+        // REAPER-02 falls silent, but a relayed report from REAPER-03 still reaches it
+        tracker.clock().step(Duration::from_secs(10));
+        tracker.handle_p2p_message(&DroneMessage::heartbeat(lead.clone()));
+        let mut report = DroneMessage::reachability(DroneId::new("REAPER-03"), vec![wing.clone()]);
+        report.decrement_ttl();
+        tracker.handle_p2p_message(&report);
+        tracker.check_reachability().await;
+        assert_eq!(tracker.get_drone(&wing).unwrap().drone.status, DroneStatus::Unreachable);
+
+        // A lost-connection emergency for it does not mark it offline
+        let lost = DroneMessage::emergency(
+            wing.clone(),
+            drone_p2p::protocol::EmergencyType::LostConnection,
+            GeoPosition::new(34.5553, 69.2075, 3000.0),
+            "Link lost".into(),
+        );
+        let mut relayed = lost.clone();
+        relayed.decrement_ttl();
+        tracker.handle_p2p_message(&relayed);
+        assert_eq!(tracker.get_drone(&wing).unwrap().drone.status, DroneStatus::Unreachable);
+
+        tracker.dispatch_command(&wing, &DroneCommandType::EmergencyStop).await;
+        let view = tracker.reachability().unwrap();
+        assert!(view.is_partitioned());
+        assert_eq!(view.buffered.get(&wing), Some(&1));
+
+        // Heard again: previous status restored and the buffer replayed
+        tracker.handle_p2p_message(&DroneMessage::heartbeat(wing.clone()));
+        tracker.check_reachability().await;
+        assert_eq!(tracker.get_drone(&wing).unwrap().drone.status, DroneStatus::Moving);
+        assert!(tracker.reachability().unwrap().buffered.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_p2p_listener_feeds_tracker() {
+        let config = TrackerConfig {
+            p2p_enabled: true,
+            db_enabled: false,
+            ..Default::default()
+        };
+        let tracker = Arc::new(DroneTracker::new(config).await.unwrap());
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Reaper 1"));
+        let p2p = tracker.p2p.clone().unwrap();
+        let position = |lat| GeoPosition::new(lat, 69.2075, 3000.0);
+        let arrived = |lat: f64| {
+            let tracker = tracker.clone();
+            let drone_id = drone_id.clone();
+            async move {
+                for _ in 0..100 {
+                    if tracker.get_drone(&drone_id).unwrap().drone.position.latitude == lat {
+                        return true;
+                    }
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                false
+            }
+        };
+
+        let listener = tracker.spawn_p2p_listener().unwrap();
+        p2p.receive(p2p.local_peer_id(), DroneMessage::position_update(drone_id.clone(), position(34.5), Telemetry::default()))
+            .await
+            .unwrap();
+        assert!(arrived(34.5).await);
+
+        // A restarted listener takes over the same receiver
+        listener.abort();
+        let _listener = tracker.spawn_p2p_listener().unwrap();
+        p2p.receive(p2p.local_peer_id(), DroneMessage::position_update(drone_id.clone(), position(34.6), Telemetry::default()))
+            .await
+            .unwrap();
+        assert!(arrived(34.6).await);
+    }
+
+    #[tokio::test]
+    async fn test_mission_sync_over_p2p() {
+        use drone_p2p::protocol::MissionManifestData;
+        use drone_p2p::MissionRoute;
+
+        let config = TrackerConfig {
+            p2p_enabled: true,
+            db_enabled: false,
+            mission_sync: MissionSyncConfig { chunk_size: 4, ..Default::default() },
+            ..Default::default()
+        };
+        let tracker = Arc::new(DroneTracker::new(config).await.unwrap());
+        let station = tracker.p2p.clone().unwrap();
+        let agent = Arc::new(P2pManager::new(drone_p2p::P2pConfig::default()).await.unwrap());
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Reaper 1"));
+        station.register_drone(drone_id.clone(), agent.local_peer_id());
+
+        // Each side's outgoing messages arrive at the other from its peer ID
+        let pump = |from: &Arc<P2pManager>, to: Arc<P2pManager>| {
+            let mut outbound = from.take_message_receiver().unwrap();
+            let peer_id = from.local_peer_id();
+            tokio::spawn(async move {
+                while let Some(message) = outbound.recv().await {
+                    let _ = to.receive(peer_id, message).await;
+                }
+            })
+        };
+        let _to_agent = pump(&station, agent.clone());
+        let _to_station = pump(&agent, station.clone());
+
+        // The agent fetches every chunk of the first manifest and reports the route
+        let mut inbox = agent.take_inbound_receiver().unwrap();
+        let agent_task = {
+            let agent = agent.clone();
+            let drone_id = drone_id.clone();
+            tokio::spawn(async move {
+                let mut manifest: Option<MissionManifestData> = None;
+                let mut chunks = Vec::new();
+                while let Some(InboundMessage { message, .. }) = inbox.recv().await {
+                    match message.message_type {
+                        MessageType::MissionManifest(announced) if manifest.is_none() => {
+                            let all = (0..announced.chunk_hashes.len() as u32).collect();
+                            let request = DroneMessage::mission_chunk_request(
+                                drone_id.clone(),
+                                announced.mission_id.clone(),
+                                announced.version,
+                                all,
+                            );
+                            agent.broadcast(request).await.unwrap();
+                            manifest = Some(announced);
+                        }
+                        MessageType::MissionChunk(chunk) => {
+                            chunks.push(chunk);
+                            let manifest = manifest.as_ref().unwrap();
+                            if chunks.len() == manifest.chunk_hashes.len() {
+                                let route = MissionRoute::assemble(manifest, None, &chunks).unwrap();
+                                let report = DroneMessage::mission_synced(
+                                    drone_id.clone(),
+                                    route.mission_id.clone(),
+                                    route.version,
+                                    route.hash(),
+                                );
+                                agent.broadcast(report).await.unwrap();
+                                return route;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                panic!("station stopped sending");
+            })
+        };
+
+        let _listener = tracker.spawn_p2p_listener().unwrap();
+        let _sync = tracker.spawn_mission_sync().unwrap();
+        let mut mission = Mission::new("Sync");
+        for i in 0..10 {
+            mission.add_waypoint(drone_core::Waypoint::new(format!("WP-{}", i), "Waypoint", 34.0 + 0.01 * i as f64, 69.0));
+        }
+        tracker.set_mission(mission.clone());
+
+        let route = tokio::time::timeout(Duration::from_secs(5), agent_task).await.unwrap().unwrap();
+        assert_eq!(route.mission_id, mission.id);
+        assert_eq!(route.waypoints.len(), 10);
+        for _ in 0..100 {
+            if tracker.mission_sync_status().drones[0].state == SyncState::InSync {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let status = &tracker.mission_sync_status().drones[0];
+        assert_eq!(status.state, SyncState::InSync);
+        assert_eq!(status.chunks_sent, 3);
+
+        // A peer not registered to the drone cannot speak for it
+        let forged = InboundMessage {
+            peer_id: station.local_peer_id(),
+            message: DroneMessage::mission_synced(drone_id.clone(), mission.id.clone(), route.version, "00".into()),
+        };
+        tracker.answer_mission_sync(&station, &forged).await;
+        assert_eq!(tracker.mission_sync_status().drones[0].state, SyncState::InSync);
+    }
+
+    #[tokio::test]
+    async fn test_waypoint_approach_hysteresis() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut events = tracker.subscribe();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        let mut mission = Mission::new("Approach Test");
+        let mut checkpoint = drone_core::Waypoint::new("WP01", "Checkpoint Bravo", 34.60, 69.20);
+        checkpoint.waypoint_type = WaypointType::Checkpoint;
+        mission.add_waypoint(checkpoint);
+        tracker.set_mission(mission);
+
+        // 360 km/h = 100 m/s
+        let telemetry = Telemetry { speed: 360.0, ..Telemetry::default() };
+        // Roughly 1.1 km, 2.2 km (ETA ~22s, inside the band) and 5.6 km (re-arm)
+        let track = [34.59, 34.59, 34.58, 34.55, 34.59];
+
+        let mut approaches = 0;
+        for lat in track {
+            let position = GeoPosition::new(lat, 69.20, 3000.0);
+            tracker.update_drone_position(&drone_id, position, telemetry.clone()).await.unwrap();
+            while let Ok(event) = events.try_recv() {
+                if event.event_type == drone_core::EventType::WaypointApproaching {
+                    approaches += 1;
+                }
+            }
+        }
+
+        assert_eq!(approaches, 2);
+    }
+
+    #[tokio::test]
+    async fn test_go_to_waypoint_skips_intermediate_waypoints() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut events = tracker.subscribe();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        let mut mission = Mission::new("Skip Test");
+        for (i, lat) in [34.0, 34.1, 34.2, 34.3].into_iter().enumerate() {
+            mission.add_waypoint(drone_core::Waypoint::new(format!("WP0{}", i + 1), "Leg", lat, 69.0));
+        }
+        tracker.set_mission(mission);
+        let fly_to = |lat: f64| tracker.update_drone_position(&drone_id, GeoPosition::new(lat, 69.0, 3000.0), Telemetry::default());
+        fly_to(34.0).await.unwrap();
+
+        let go_direct = DroneCommandType::GoToWaypoint { waypoint_id: WaypointId::new("WP04") };
+        assert!(tracker.send_command(&drone_id, &go_direct).await.outcome.is_success());
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.waypoint_index, 3);
+        assert!(tracked.direct_from.is_some_and(|from| from.distance_to(&GeoPosition::new(34.0, 69.0, 3000.0)) < 0.01));
+
+        // Progress runs along the direct leg from where the command was accepted
+        fly_to(34.15).await.unwrap();
+        assert!((tracker.get_drone(&drone_id).unwrap().waypoint_progress - 0.5).abs() < 0.01);
+        fly_to(34.3).await.unwrap();
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().waypoint_index, 4);
+
+        // Going back does not un-skip anything
+        let go_back = DroneCommandType::GoToWaypoint { waypoint_id: WaypointId::new("WP02") };
+        tracker.send_command(&drone_id, &go_back).await;
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().waypoint_index, 4);
+
+        let mut skipped = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let EventPayload::Waypoint(waypoint) = &event.payload {
+                if waypoint.event_type == drone_core::WaypointEventType::Skipped {
+                    skipped.push(waypoint.waypoint_id.to_string());
+                }
+            }
+        }
+        assert_eq!(skipped, ["WP02", "WP03"]);
+    }
+
+    #[tokio::test]
+    async fn test_custom_event_validated_and_published() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut events = tracker.subscribe();
+        let pod_status = |value: serde_json::Value| CustomEvent {
+            event_type: "acme.SENSOR_POD_STATUS".into(),
+            drone_id: Some(DroneId::new("REAPER-01")),
+            value,
+        };
+
+        assert!(matches!(
+            tracker.publish_custom_event(pod_status(serde_json::json!({}))).await,
+            Err(CustomEventError::UnknownType(_))
+        ));
+
+        let schema = RegisteredSchema::new(
+            "acme.SENSOR_POD_STATUS",
+            serde_json::json!({
+                "type": "object",
+                "required": ["pod"],
+                "properties": { "pod": { "enum": ["left", "right"] } }
+            }),
+            None,
+            Utc::now(),
+        )
+        .unwrap();
+        tracker.save_event_schema(schema).await.unwrap();
+
+        assert!(matches!(
+            tracker.publish_custom_event(pod_status(serde_json::json!({ "pod": "top" }))).await,
+            Err(CustomEventError::Invalid { .. })
+        ));
+        let published = tracker
+            .publish_custom_event(pod_status(serde_json::json!({ "pod": "left" })))
+            .await
+            .unwrap();
+        assert_eq!(published.event_type, drone_core::EventType::Custom);
+        assert_eq!(published.drone_id(), Some(&DroneId::new("REAPER-01")));
+
+        let received = events.try_recv().unwrap();
+        assert_eq!(received.id, published.id);
+        assert!(events.try_recv().is_err());
+
+        assert!(tracker.remove_event_schema("acme.SENSOR_POD_STATUS").await.unwrap().is_some());
+        assert!(tracker.event_schemas().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_endurance_reserve_alert_raised_once() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        // About 111 km due north
+        let mut mission = Mission::new("Endurance Test");
+        mission.add_waypoint(drone_core::Waypoint::new("WP01", "Start", 34.0, 69.0));
+        mission.add_waypoint(drone_core::Waypoint::new("WP02", "Finish", 35.0, 69.0));
+        tracker.set_mission(mission);
+
+        // Burning 1% of fuel per ~1.1 km, the drone cannot reach the finish
+        for step in 0..12u8 {
+            let position = GeoPosition::new(34.0 + 0.01 * step as f64, 69.0, 3000.0);
+            let telemetry = Telemetry { fuel_level: 90 - step, ..Telemetry::default() };
+            tracker.update_drone_position(&drone_id, position, telemetry).await.unwrap();
+        }
+
+        let projection = tracker.endurance_projection(&drone_id).unwrap();
+        assert_eq!(projection.source, ConsumptionSource::Observed);
+        assert!((projection.remaining_km - 99.0).abs() < 0.5, "{:?}", projection);
+        assert_eq!(projection.fuel_at_completion, 0.0);
+        assert!(projection.battery_at_completion > 99.0);
+
+        let mut endurance_alerts = 0;
+        while let Ok(alert) = alerts.try_recv() {
+            if alert.alert_type == AlertType::Custom(ENDURANCE_ALERT_TYPE.into()) {
+                assert_eq!(alert.severity, AlertSeverity::Warning);
+                endurance_alerts += 1;
+            }
+        }
+        assert_eq!(endurance_alerts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_loiter_holds_then_departs() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut events = tracker.subscribe();
+        let drone_id = DroneId::new("REAPER-01");
+        let mut drone = Drone::new(drone_id.clone(), "Alpha Lead");
+        drone.status = DroneStatus::Moving;
         tracker.register_drone(drone);
-        
-        assert_eq!(tracker.drone_count(), 1);
-        assert!(tracker.get_drone(&DroneId::new("REAPER-01")).is_some());
+
+        let mut mission = Mission::new("Loiter Test");
+        let mut rally = drone_core::Waypoint::new("WP01", "Point Foxtrot", 34.60, 69.20);
+        rally.loiter_time_seconds = Some(60);
+        mission.add_waypoint(rally);
+        mission.add_waypoint(drone_core::Waypoint::new("WP02", "Zone Golf", 34.70, 69.20));
+        tracker.set_mission(mission);
+
+        let at_rally = GeoPosition::new(34.60, 69.20, 3000.0);
+        tracker.update_drone_position(&drone_id, at_rally, Telemetry::default()).await.unwrap();
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.status, DroneStatus::Loitering);
+        assert_eq!(tracked.waypoint_index, 1);
+
+        // Progress stays paused while the timer runs
+        let near_next = GeoPosition::new(34.65, 69.20, 3000.0);
+        tracker.update_drone_position(&drone_id, near_next, Telemetry::default()).await.unwrap();
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().waypoint_progress, 0.0);
+
+        // Expire the hold
+        tracker.drones.get_mut(&drone_id).unwrap().loiter_until =
+            Some(Utc::now() - chrono::Duration::seconds(1));
+        tracker.update_drone_position(&drone_id, near_next, Telemetry::default()).await.unwrap();
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.status, DroneStatus::Moving);
+        assert!(tracked.waypoint_progress > 0.0);
+
+        let mut departed = 0;
+        while let Ok(event) = events.try_recv() {
+            if event.event_type == drone_core::EventType::WaypointDeparted {
+                departed += 1;
+            }
+        }
+        assert_eq!(departed, 1);
     }
 
     #[tokio::test]
-    async fn test_position_update() {
+    async fn test_waypoint_arrival_waits_out_jitter() {
         let config = TrackerConfig {
-            //cv_enabled: false,
             p2p_enabled: false,
             db_enabled: false,
+            arrival: ArrivalConfig {
+                min_dwell: Duration::from_secs(5),
+                ..ArrivalConfig::default()
+            },
             ..Default::default()
         };
-        
+
         let tracker = DroneTracker::new(config).await.unwrap();
-        
-        let drone = Drone::new(DroneId::new("REAPER-01"), "Alpha Lead");
+        tracker.clock().pause();
+        let mut events = tracker.subscribe();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        let mut mission = Mission::new("Jitter Test");
+        mission.add_waypoint(drone_core::Waypoint::new("WP01", "Point Alpha", 34.60, 69.20));
+        mission.add_waypoint(drone_core::Waypoint::new("WP02", "Point Bravo", 34.70, 69.20));
+        tracker.set_mission(mission);
+
+        // GPS fixes scattered 80-120 m around the waypoint
+        let waypoint = GeoPosition::new(34.60, 69.20, 3000.0);
+        for (i, meters) in [80.0, 120.0, 95.0, 110.0, 90.0, 105.0, 85.0, 115.0].into_iter().enumerate() {
+            let fix = waypoint.destination(meters / 1000.0, i as f64 * 45.0);
+            tracker.update_drone_position(&drone_id, fix, Telemetry::default()).await.unwrap();
+            tracker.clock().step(Duration::from_secs(1));
+        }
+
+        let mut reached = 0;
+        while let Ok(event) = events.try_recv() {
+            if event.event_type == drone_core::EventType::WaypointReached {
+                reached += 1;
+            }
+        }
+        assert_eq!(reached, 1);
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().waypoint_index, 1);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_holds_until_acknowledged() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            checkpoint: CheckpointConfig {
+                enabled: true,
+                escalation_interval: Duration::from_secs(60),
+            },
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        tracker.clock().pause();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let mut drone = Drone::new(drone_id.clone(), "Alpha Lead");
+        drone.status = DroneStatus::Moving;
         tracker.register_drone(drone);
-        
+
+        let mut mission = Mission::new("Checkpoint Test");
+        let mut checkpoint = drone_core::Waypoint::new("WP01", "Checkpoint Bravo", 34.60, 69.20);
+        checkpoint.waypoint_type = WaypointType::Checkpoint;
+        mission.add_waypoint(checkpoint);
+        mission.add_waypoint(drone_core::Waypoint::new("WP02", "Zone Golf", 34.70, 69.20));
+        tracker.set_mission(mission);
+
+        let at_checkpoint = GeoPosition::new(34.60, 69.20, 3000.0);
+        tracker.update_drone_position(&drone_id, at_checkpoint, Telemetry::default()).await.unwrap();
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().drone.status, DroneStatus::Loitering);
+        let alert = alerts.try_recv().unwrap();
+        assert_eq!(alert.alert_type, AlertType::Custom(CHECKPOINT_ALERT_TYPE.into()));
+        assert_eq!(alert.severity, AlertSeverity::Warning);
+
+        // No progress toward WP02 without an acknowledgment, however long it takes
+        let near_next = GeoPosition::new(34.65, 69.20, 3000.0);
+        tracker.clock().step(Duration::from_secs(61));
+        tracker.update_drone_position(&drone_id, near_next, Telemetry::default()).await.unwrap();
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().waypoint_progress, 0.0);
+        assert_eq!(tracker.escalate_checkpoints().len(), 1);
+        assert_eq!(alerts.try_recv().unwrap().severity, AlertSeverity::Critical);
+
+        assert!(tracker.acknowledge_checkpoint(&WaypointId::new("WP02"), None, None).is_empty());
+        let released = tracker.acknowledge_checkpoint(&WaypointId::new("WP01"), None, Some("ops"));
+        assert_eq!(released.len(), 1);
+        assert!(alerts.try_recv().unwrap().resolved);
+
+        tracker.update_drone_position(&drone_id, near_next, Telemetry::default()).await.unwrap();
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.status, DroneStatus::Moving);
+        assert!(tracked.waypoint_progress > 0.0);
+        assert!(tracked.active_alerts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_go_direct_from_checkpoint_skips_without_departing() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            checkpoint: CheckpointConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let mut drone = Drone::new(drone_id.clone(), "Alpha Lead");
+        drone.status = DroneStatus::Moving;
+        tracker.register_drone(drone);
+
+        let mut mission = Mission::new("Checkpoint Test");
+        let mut checkpoint = drone_core::Waypoint::new("WP01", "Checkpoint Bravo", 34.60, 69.20);
+        checkpoint.waypoint_type = WaypointType::Checkpoint;
+        mission.add_waypoint(checkpoint);
+        mission.add_waypoint(drone_core::Waypoint::new("WP02", "Zone Golf", 34.70, 69.20));
+        mission.add_waypoint(drone_core::Waypoint::new("WP03", "Zone Hotel", 34.80, 69.20));
+        tracker.set_mission(mission);
+        let at_checkpoint = GeoPosition::new(34.60, 69.20, 3000.0);
+        tracker.update_drone_position(&drone_id, at_checkpoint, Telemetry::default()).await.unwrap();
+        assert!(!alerts.try_recv().unwrap().resolved);
+
+        let mut events = tracker.subscribe();
+        let go_direct = DroneCommandType::GoToWaypoint { waypoint_id: WaypointId::new("WP03") };
+        assert!(tracker.send_command(&drone_id, &go_direct).await.outcome.is_success());
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.status, DroneStatus::Moving);
+        assert!(tracked.active_alerts.is_empty());
+        assert!(tracker.checkpoints.list().is_empty());
+        assert!(alerts.try_recv().unwrap().resolved);
+
+        let mut waypoint_events = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let EventPayload::Waypoint(waypoint) = &event.payload {
+                waypoint_events.push((waypoint.event_type, waypoint.waypoint_id.to_string()));
+            }
+        }
+        assert_eq!(waypoint_events, [(drone_core::WaypointEventType::Skipped, "WP02".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_new_mission_ends_checkpoint_hold() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            checkpoint: CheckpointConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+        let drone_id = DroneId::new("REAPER-01");
+        let mut drone = Drone::new(drone_id.clone(), "Alpha Lead");
+        drone.status = DroneStatus::Moving;
+        tracker.register_drone(drone);
+
+        let mut mission = Mission::new("Checkpoint Test");
+        let mut checkpoint = drone_core::Waypoint::new("WP01", "Checkpoint Bravo", 34.60, 69.20);
+        checkpoint.waypoint_type = WaypointType::Checkpoint;
+        mission.add_waypoint(checkpoint);
+        tracker.set_mission(mission);
+        let at_checkpoint = GeoPosition::new(34.60, 69.20, 3000.0);
+        tracker.update_drone_position(&drone_id, at_checkpoint, Telemetry::default()).await.unwrap();
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().drone.status, DroneStatus::Loitering);
+        assert!(!alerts.try_recv().unwrap().resolved);
+
+        tracker.set_mission(Mission::new("Next Sortie"));
+        let tracked = tracker.get_drone(&drone_id).unwrap();
+        assert_eq!(tracked.drone.status, DroneStatus::Moving);
+        assert!(tracked.active_alerts.is_empty());
+        assert!(tracker.checkpoints.list().is_empty());
+        assert!(alerts.try_recv().unwrap().resolved);
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_commands_fire_on_waypoint_and_time() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut events = tracker.subscribe();
+        let drone_id = DroneId::new("REAPER-01");
+        tracker.register_drone(Drone::new(drone_id.clone(), "Alpha Lead"));
+
+        let mut mission = Mission::new("Schedule Test");
+        mission.add_waypoint(drone_core::Waypoint::new("WP01", "Point Foxtrot", 34.60, 69.20));
+        mission.add_waypoint(drone_core::Waypoint::new("WP02", "Zone Golf", 34.70, 69.20));
+        tracker.set_mission(mission);
+
+        let vee = tracker
+            .schedule_command(
+                CommandTrigger::Waypoint { waypoint_id: WaypointId::new("WP01"), drone_id: None },
+                ScheduledAction::SetFormation { formation: convoy::Formation::Vee },
+                None,
+            )
+            .await
+            .unwrap();
+        tracker
+            .schedule_command(
+                CommandTrigger::At { at: tracker.clock().now() + chrono::Duration::seconds(30) },
+                ScheduledAction::DroneCommand {
+                    drone_id: drone_id.clone(),
+                    command: DroneCommandType::ReturnToBase,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let at_wp01 = GeoPosition::new(34.60, 69.20, 3000.0);
+        tracker.update_drone_position(&drone_id, at_wp01, Telemetry::default()).await.unwrap();
+        assert_eq!(tracker.convoy().get_formation(), convoy::Formation::Vee);
+        assert_eq!(tracker.scheduler().get(&vee.id).unwrap().state, ScheduleState::Fired);
+
+        // The RTB fires once simulated time passes its trigger
+        tracker.run_due_commands().await;
+        assert_ne!(tracker.get_drone(&drone_id).unwrap().drone.status, DroneStatus::Rtb);
+        tracker.clock().step(Duration::from_secs(31));
+        tracker.run_due_commands().await;
+        assert_eq!(tracker.get_drone(&drone_id).unwrap().drone.status, DroneStatus::Rtb);
+
+        let mut fired = 0;
+        while let Ok(event) = events.try_recv() {
+            if event.event_type == drone_core::EventType::ScheduledCommandFired {
+                fired += 1;
+            }
+        }
+        assert_eq!(fired, 2);
+    }
+
+    #[tokio::test]
+    async fn test_threshold_override_precedence() {
+        let mut type_thresholds = HashMap::new();
+        type_thresholds.insert(
+            DroneType::Rq4GlobalHawk,
+            ThresholdOverrides {
+                battery_warning: Some(50),
+                battery_critical: Some(40),
+                ..Default::default()
+            },
+        );
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            type_thresholds,
+            ..Default::default()
+        };
+
+        let tracker = DroneTracker::new(config).await.unwrap();
+        let mut alerts = tracker.take_alert_receiver().unwrap();
+
+        let mut hawk = Drone::new(DroneId::new("HAWK-01"), "Hawk");
+        hawk.drone_type = DroneType::Rq4GlobalHawk;
+        tracker.register_drone(hawk);
+        tracker.register_drone(Drone::new(DroneId::new("REAPER-01"), "Alpha Lead"));
+
+        let hawk_id = DroneId::new("HAWK-01");
+        assert_eq!(tracker.effective_thresholds(&hawk_id).unwrap().battery_critical, 40);
+
+        // Drone override wins over the type override, field by field
+        tracker.set_drone_thresholds(&hawk_id, ThresholdOverrides {
+            battery_critical: Some(35),
+            ..Default::default()
+        }).await.unwrap();
+        let effective = tracker.effective_thresholds(&hawk_id).unwrap();
+        assert_eq!(effective.battery_critical, 35);
+        assert_eq!(effective.battery_warning, 50);
+
+        // 38% is critical for the Global Hawk but fine for a Reaper
+        let telemetry = Telemetry { battery_level: 38, ..Telemetry::default() };
         let position = GeoPosition::new(34.5553, 69.2075, 3000.0);
-        let telemetry = Telemetry::default();
-        
-        tracker.update_drone_position(
-            &DroneId::new("REAPER-01"),
-            position,
-            telemetry,
-        ).await.unwrap();
-        
-        let tracked = tracker.get_drone(&DroneId::new("REAPER-01")).unwrap();
-        assert_eq!(tracked.drone.position.latitude, 34.5553);
+        tracker.update_drone_position(&DroneId::new("REAPER-01"), position, telemetry.clone()).await.unwrap();
+        tracker.update_drone_position(&hawk_id, position, telemetry).await.unwrap();
+
+        let alert = alerts.try_recv().unwrap();
+        assert_eq!(alert.drone_id, Some(hawk_id.clone()));
+        assert_eq!(alert.severity, AlertSeverity::Warning);
+        assert!(alerts.try_recv().is_err());
+
+        tracker.set_drone_thresholds(&hawk_id, ThresholdOverrides::default()).await.unwrap();
+        assert!(tracker.drone_thresholds(&hawk_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_abort_waits_for_acks_or_timeout() {
+        let config = TrackerConfig {
+            p2p_enabled: false,
+            db_enabled: false,
+            abort_policy: AbortPolicy {
+                ack_timeout: Duration::from_millis(50),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let tracker = Arc::new(DroneTracker::new(config).await.unwrap());
+        let mut events = tracker.subscribe();
+        let mut mission = Mission::new("Abort Test");
+        for id in ["REAPER-01", "REAPER-02"] {
+            let mut drone = Drone::new(DroneId::new(id), id);
+            drone.telemetry.fuel_level = 48;
+            tracker.register_drone(drone);
+            mission.assign_drone(DroneId::new(id));
+        }
+        tracker.set_mission(mission);
+
+        let report = tracker.begin_abort(Some("Weather".into())).await.unwrap();
+        assert_eq!(report.state, AbortState::InProgress);
+        assert_eq!(report.drones.len(), 2);
+        assert_eq!(report.drones[0].fuel_level, 48);
+        assert_eq!(tracker.get_drone(&DroneId::new("REAPER-01")).unwrap().drone.status, DroneStatus::Rtb);
+        assert_eq!(tracker.get_mission().unwrap().status, MissionStatus::Planning);
+
+        // One drone acks over the mesh, the other never answers
+        let ack = DroneMessage::ack(DroneId::new("REAPER-01"), report.drones[0].command_id, true);
+        tracker.handle_p2p_message(&ack);
+        assert_eq!(tracker.abort_report().unwrap().pending(), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let report = tracker.abort_report().unwrap();
+        assert_eq!(report.state, AbortState::Completed);
+        assert!(report.drones[0].acknowledged_at.is_some());
+        assert!(report.drones[1].timed_out);
+        assert_eq!(tracker.get_mission().unwrap().status, MissionStatus::Aborted);
+
+        let mut aborted = false;
+        while let Ok(event) = events.try_recv() {
+            aborted |= event.event_type == drone_core::EventType::MissionAborted;
+        }
+        assert!(aborted);
+        assert!(tracker.begin_abort(None).await.is_err());
     }
 }
@@ -0,0 +1,301 @@
+//! Terrain line-of-sight prediction
+//!
+//! With an elevation model loaded, every flying drone's path over the next
+//! few minutes is projected along its route at its current speed and
+//! altitude, and each projected point is checked for line of sight to the
+//! ground station. The first blocked point raises a warning before the link
+//! actually drops; the warning re-arms once the projected path is clear.
+
+use drone_core::{line_of_sight, Drone, DroneId, DroneStatus, ElevationGrid, ElevationModel, GeoPosition, Mission};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Alert type raised when terrain is predicted to block the link
+pub const LOS_ALERT_TYPE: &str = "LOS_LOSS_PREDICTED";
+
+/// Line-of-sight prediction configuration
+#[derive(Debug, Clone)]
+pub struct LosConfig {
+    /// ESRI ASCII grid of terrain heights; no checks without one
+    pub dem_path: Option<PathBuf>,
+    /// Ground station antenna; defaults to the mission's first waypoint
+    pub ground_station: Option<GeoPosition>,
+    /// Antenna height above the terrain when the station is the first waypoint
+    pub antenna_height_m: f64,
+    /// The path must pass at least this far above the terrain
+    pub min_clearance_m: f64,
+    /// How far ahead the drone's path is projected
+    pub lookahead: Duration,
+    /// Spacing of the projected points
+    pub sample_interval: Duration,
+    /// Terrain sampling distance along each sight line (meters)
+    pub step_m: f64,
+    /// How often the background check runs
+    pub check_interval: Duration,
+}
+
+impl Default for LosConfig {
+    fn default() -> Self {
+        Self {
+            dem_path: None,
+            ground_station: None,
+            antenna_height_m: 10.0,
+            min_clearance_m: 10.0,
+            lookahead: Duration::from_secs(300),
+            sample_interval: Duration::from_secs(10),
+            step_m: 90.0,
+            check_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl LosConfig {
+    /// Defaults overridden by `LOS_DEM_PATH`, `LOS_GROUND_STATION`
+    /// (`lat,lng,alt`) and `LOS_LOOKAHEAD_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            dem_path: env("LOS_DEM_PATH").map(PathBuf::from),
+            ground_station: env("LOS_GROUND_STATION").and_then(|s| {
+                let parts: Vec<f64> = s.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+                match parts[..] {
+                    [lat, lng, alt] => Some(GeoPosition::new(lat, lng, alt)),
+                    _ => None,
+                }
+            }),
+            lookahead: env("LOS_LOOKAHEAD_SECS")
+                .and_then(|s| s.parse().ok())
+                .filter(|s: &u64| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.lookahead),
+            ..defaults
+        }
+    }
+}
+
+/// Predicted loss of line of sight
+#[derive(Debug, Clone, Serialize)]
+pub struct LosLoss {
+    pub drone_id: DroneId,
+    /// Time until the drone reaches the blocked point (zero if blocked now)
+    pub in_secs: u64,
+    /// Where the drone will be
+    pub position: GeoPosition,
+    /// Terrain point in the way
+    pub obstruction: Option<GeoPosition>,
+    /// Height of the sight line above the terrain (meters, negative if below)
+    pub clearance_m: f64,
+}
+
+/// Checks projected drone paths against the terrain
+pub struct LosMonitor {
+    config: LosConfig,
+    terrain: Arc<dyn ElevationModel>,
+    /// Drones with an outstanding prediction
+    predicted: RwLock<HashSet<DroneId>>,
+}
+
+impl fmt::Debug for LosMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LosMonitor").field("config", &self.config).finish()
+    }
+}
+
+impl LosMonitor {
+    pub fn new(config: LosConfig, terrain: Arc<dyn ElevationModel>) -> Self {
+        Self {
+            config,
+            terrain,
+            predicted: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Monitor over the configured DEM file; `None` without one
+    pub fn load(config: LosConfig) -> anyhow::Result<Option<Self>> {
+        let Some(path) = &config.dem_path else {
+            return Ok(None);
+        };
+        let text = std::fs::read_to_string(path)?;
+        let grid = ElevationGrid::parse_ascii(&text).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        Ok(Some(Self::new(config, Arc::new(grid))))
+    }
+
+    pub fn config(&self) -> &LosConfig {
+        &self.config
+    }
+
+    /// Configured station, or an antenna on the terrain at the first waypoint
+    pub fn ground_station(&self, mission: Option<&Mission>) -> Option<GeoPosition> {
+        if let Some(station) = self.config.ground_station {
+            return Some(station);
+        }
+        let base = mission?.waypoints.first()?.position;
+        let ground = self.terrain.elevation_at(&base).unwrap_or(base.altitude);
+        Some(GeoPosition::new(base.latitude, base.longitude, ground + self.config.antenna_height_m))
+    }
+
+    /// First point on the drone's projected path without line of sight
+    ///
+    /// Drones moving along the route are projected through the remaining
+    /// waypoints from `waypoint_index` at their current speed and altitude;
+    /// other flying drones are checked where they are.
+    pub fn predict(
+        &self,
+        drone: &Drone,
+        mission: Option<&Mission>,
+        waypoint_index: usize,
+        station: &GeoPosition,
+    ) -> Option<LosLoss> {
+        let route: Vec<GeoPosition> = match (drone.status, mission) {
+            (DroneStatus::Moving | DroneStatus::Engaged, Some(mission)) => mission
+                .waypoints
+                .iter()
+                .skip(waypoint_index)
+                .map(|w| w.position)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let path = projected_path(
+            &drone.position,
+            drone.telemetry.speed,
+            &route,
+            self.config.lookahead,
+            self.config.sample_interval,
+        );
+
+        path.into_iter().find_map(|(in_secs, position)| {
+            let sight = line_of_sight(station, &position, self.terrain.as_ref(), self.config.step_m);
+            (sight.clearance_m < self.config.min_clearance_m).then(|| LosLoss {
+                drone_id: drone.id.clone(),
+                in_secs,
+                position,
+                obstruction: sight.obstruction,
+                clearance_m: sight.clearance_m,
+            })
+        })
+    }
+
+    /// Prediction for a drone if it is new; the drone re-arms once its
+    /// projected path is clear
+    pub fn check(
+        &self,
+        drone: &Drone,
+        mission: Option<&Mission>,
+        waypoint_index: usize,
+        station: &GeoPosition,
+    ) -> Option<LosLoss> {
+        let flying = matches!(
+            drone.status,
+            DroneStatus::Moving | DroneStatus::Engaged | DroneStatus::Loitering | DroneStatus::Rtb
+        );
+        let loss = flying.then(|| self.predict(drone, mission, waypoint_index, station)).flatten();
+        match loss {
+            Some(loss) => self.predicted.write().insert(drone.id.clone()).then_some(loss),
+            None => {
+                self.predicted.write().remove(&drone.id);
+                None
+            }
+        }
+    }
+}
+
+/// Positions at every `sample` from now to `lookahead`, following `route`
+/// from `position` at `speed_kmh` and holding the current altitude
+fn projected_path(
+    position: &GeoPosition,
+    speed_kmh: f64,
+    route: &[GeoPosition],
+    lookahead: Duration,
+    sample: Duration,
+) -> Vec<(u64, GeoPosition)> {
+    let altitude = position.altitude;
+    let at_altitude = |p: GeoPosition| GeoPosition::new(p.latitude, p.longitude, altitude);
+    let mut path = vec![(0, *position)];
+    if route.is_empty() || speed_kmh < 1.0 || sample.is_zero() {
+        return path;
+    }
+
+    let speed_ms = speed_kmh / 3.6;
+    let mut points = route.iter();
+    let mut next = points.next();
+    let mut from = *position;
+    // Distance flown from `position` to `from`
+    let mut covered_m = 0.0;
+    let mut elapsed = sample;
+    while elapsed <= lookahead {
+        let target_m = speed_ms * elapsed.as_secs_f64();
+        // Skip whole legs until the target distance falls inside one
+        let point = loop {
+            let Some(to) = next else {
+                break None;
+            };
+            let leg_m = from.distance_to(to) * 1000.0;
+            if covered_m + leg_m >= target_m {
+                let fraction = if leg_m > 0.0 { (target_m - covered_m) / leg_m } else { 1.0 };
+                break Some(from.interpolate(to, fraction));
+            }
+            covered_m += leg_m;
+            from = *to;
+            next = points.next();
+        };
+        // The route ends before the lookahead
+        let Some(point) = point else {
+            break;
+        };
+        path.push((elapsed.as_secs(), at_altitude(point)));
+        elapsed += sample;
+    }
+    path
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::Waypoint;
+
+    #[test]
+    fn test_ridge_ahead_is_predicted_once() {
+        // Flat 500 m ground with an 1100 m ridge along 69.30 E
+        let terrain = ElevationGrid::from_fn(34.4, 69.1, 0.005, 41, 81, |_, lng| {
+            if (lng - 69.30).abs() < 0.006 { 1100.0 } else { 500.0 }
+        })
+        .unwrap();
+        let monitor = LosMonitor::new(LosConfig::default(), Arc::new(terrain));
+
+        let mut mission = Mission::new("Ridge run");
+        mission.add_waypoint(Waypoint::new("WP01", "Base", 34.5, 69.15));
+        mission.add_waypoint(Waypoint::new("WP02", "Valley", 34.5, 69.45));
+        let station = monitor.ground_station(Some(&mission)).unwrap();
+        assert!((station.altitude - 510.0).abs() < 1e-6);
+
+        // 1200 m altitude, 7 km short of the ridge at 360 km/h (100 m/s); the
+        // ridge hides the drone once it is a little way past it
+        let mut drone = Drone::new("REAPER-01", "Reaper 1");
+        drone.status = DroneStatus::Moving;
+        drone.telemetry.speed = 360.0;
+        drone.position = GeoPosition::new(34.5, 69.225, 1200.0);
+
+        let loss = monitor.check(&drone, Some(&mission), 1, &station).unwrap();
+        assert!((60..=180).contains(&loss.in_secs), "blocked in {} s", loss.in_secs);
+        assert!(loss.position.longitude > 69.30);
+        assert!(loss.obstruction.is_some());
+        assert!(monitor.check(&drone, Some(&mission), 1, &station).is_none());
+
+        // Climbing above the ridge clears the prediction and re-arms it
+        drone.position.altitude = 4000.0;
+        assert!(monitor.check(&drone, Some(&mission), 1, &station).is_none());
+        drone.position.altitude = 1200.0;
+        assert!(monitor.check(&drone, Some(&mission), 1, &station).is_some());
+    }
+}
@@ -0,0 +1,164 @@
+//! Fleet markings: which halo color and icon each drone carries
+//!
+//! CV tells drones apart by the hue of the halo painted on them, so two
+//! drones may not carry hues closer than `MIN_HALO_HUE_SEPARATION`; the
+//! map draws each drone in its halo color so the CV overlay and the map
+//! agree. Markings are stored in the drone registry and broadcast as
+//! `FleetMarkingChanged` events, which the CV pipeline applies with
+//! `CvEngine::apply_marking` (see `drone_cv::follow_markings`).
+
+use drone_core::{DroneId, DroneMarking, HaloColor};
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Closest two drones' halo hues may be (degrees) for CV to tell them apart
+pub const MIN_HALO_HUE_SEPARATION: f64 = 30.0;
+
+/// A halo color too close to another drone's
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("halo color {color} is too close to {holder}'s {held}")]
+pub struct ColorTaken {
+    pub color: HaloColor,
+    pub holder: DroneId,
+    pub held: HaloColor,
+}
+
+/// Halo color and icon per drone. One lock covers the map, so a color is
+/// checked and taken in one step.
+#[derive(Debug, Default)]
+pub struct FleetMarkings {
+    markings: RwLock<HashMap<DroneId, DroneMarking>>,
+}
+
+impl FleetMarkings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, drone_id: &DroneId) -> Option<DroneMarking> {
+        self.markings.read().get(drone_id).cloned()
+    }
+
+    /// All markings, by drone ID
+    pub fn all(&self) -> Vec<(DroneId, DroneMarking)> {
+        let mut markings: Vec<_> = self
+            .markings
+            .read()
+            .iter()
+            .map(|(drone_id, marking)| (drone_id.clone(), marking.clone()))
+            .collect();
+        markings.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        markings
+    }
+
+    /// Whether `drone_id` can carry `color`
+    pub fn check(&self, drone_id: &DroneId, color: HaloColor) -> Result<(), ColorTaken> {
+        check(&self.markings.read(), drone_id, color)
+    }
+
+    /// Give `drone_id` `marking` if its color is free, checked and taken
+    /// under one lock; returns the marking it replaced, to hand back to
+    /// `release` if persisting the new one fails
+    pub fn reserve(&self, drone_id: &DroneId, marking: DroneMarking) -> Result<Option<DroneMarking>, ColorTaken> {
+        let mut markings = self.markings.write();
+        check(&markings, drone_id, marking.halo_color)?;
+        Ok(markings.insert(drone_id.clone(), marking))
+    }
+
+    /// Undo a `reserve` of `reserved`, unless the drone was marked again since
+    pub fn release(&self, drone_id: &DroneId, reserved: &DroneMarking, previous: Option<DroneMarking>) {
+        let mut markings = self.markings.write();
+        if markings.get(drone_id) != Some(reserved) {
+            return;
+        }
+        match previous {
+            Some(previous) => {
+                markings.insert(drone_id.clone(), previous);
+            }
+            None => {
+                markings.remove(drone_id);
+            }
+        }
+    }
+
+    /// Set (or clear, with `None`) a drone's marking without checking colors
+    pub fn set(&self, drone_id: &DroneId, marking: Option<DroneMarking>) {
+        match marking {
+            Some(marking) => {
+                self.markings.write().insert(drone_id.clone(), marking);
+            }
+            None => {
+                self.markings.write().remove(drone_id);
+            }
+        }
+    }
+}
+
+fn check(markings: &HashMap<DroneId, DroneMarking>, drone_id: &DroneId, color: HaloColor) -> Result<(), ColorTaken> {
+    match markings.iter().find(|(holder, marking)| {
+        *holder != drone_id
+            && marking
+                .halo_color
+                .hue_difference(&color)
+                .is_some_and(|difference| difference < MIN_HALO_HUE_SEPARATION)
+    }) {
+        Some((holder, marking)) => Err(ColorTaken {
+            color,
+            holder: holder.clone(),
+            held: marking.halo_color,
+        }),
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors_stay_apart() {
+        let markings = FleetMarkings::new();
+        let (a, b) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"));
+        markings.set(&a, Some(DroneMarking::new(HaloColor::RED)));
+
+        let near_red = HaloColor::new(230, 20, 10);
+        assert_eq!(
+            markings.check(&b, near_red),
+            Err(ColorTaken {
+                color: near_red,
+                holder: a.clone(),
+                held: HaloColor::RED
+            })
+        );
+        // A drone's own color never conflicts
+        assert!(markings.check(&a, near_red).is_ok());
+        assert!(markings.check(&b, HaloColor::CYAN).is_ok());
+
+        markings.set(&a, None);
+        assert!(markings.check(&b, near_red).is_ok());
+        assert!(markings.all().is_empty());
+    }
+
+    #[test]
+    fn test_reserve_takes_the_color_and_release_gives_it_back() {
+        let markings = FleetMarkings::new();
+        let (a, b) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"));
+        let red = DroneMarking::new(HaloColor::RED);
+
+        assert_eq!(markings.reserve(&a, red.clone()), Ok(None));
+        // Taken as soon as it is reserved, before anything is written
+        assert!(markings.reserve(&b, DroneMarking::new(HaloColor::new(230, 20, 10))).is_err());
+
+        let cyan = DroneMarking::new(HaloColor::CYAN);
+        let previous = markings.reserve(&a, cyan.clone()).unwrap();
+        assert_eq!(previous.as_ref(), Some(&red));
+        markings.release(&a, &cyan, previous);
+        assert_eq!(markings.get(&a), Some(red));
+    }
+}
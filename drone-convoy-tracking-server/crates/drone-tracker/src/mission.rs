@@ -1,9 +1,10 @@
 //! Mission execution and waypoint management
 
+use crate::arrival::{ArrivalConfig, ArrivalLatch};
 use drone_core::{DroneId, GeoPosition, Mission, MissionStatus, Waypoint, WaypointId};
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::time::Duration;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 
 /// Mission executor handles waypoint progression
 pub struct MissionExecutor {
@@ -13,8 +14,8 @@ pub struct MissionExecutor {
     drone_progress: HashMap<DroneId, WaypointProgress>,
     /// Mission start time
     start_time: Option<chrono::DateTime<chrono::Utc>>,
-    /// Waypoint proximity threshold (km)
-    threshold_km: f64,
+    /// Waypoint arrival and departure radii
+    arrival: ArrivalConfig,
 }
 
 /// Progress tracking for a drone
@@ -24,6 +25,10 @@ pub struct WaypointProgress {
     pub progress_to_next: f64,
     pub waypoints_completed: Vec<WaypointId>,
     pub estimated_arrival: Option<chrono::DateTime<chrono::Utc>>,
+    /// Holding at the last reached waypoint until this time
+    pub loiter_until: Option<DateTime<Utc>>,
+    /// Arrival hysteresis at the current waypoint
+    pub arrival: ArrivalLatch,
 }
 
 impl WaypointProgress {
@@ -33,6 +38,8 @@ impl WaypointProgress {
             progress_to_next: 0.0,
             waypoints_completed: Vec::new(),
             estimated_arrival: None,
+            loiter_until: None,
+            arrival: ArrivalLatch::default(),
         }
     }
 }
@@ -50,7 +57,7 @@ impl MissionExecutor {
             mission: None,
             drone_progress: HashMap::new(),
             start_time: None,
-            threshold_km: 0.5,
+            arrival: ArrivalConfig::with_threshold_km(0.5),
         }
     }
 
@@ -114,6 +121,17 @@ impl MissionExecutor {
         drone_id: &DroneId,
         position: &GeoPosition,
         speed: f64,
+    ) -> Option<WaypointReached> {
+        self.update_drone_position_at(drone_id, position, speed, Utc::now())
+    }
+
+    /// Update drone position as of `now` and check waypoint progress
+    pub fn update_drone_position_at(
+        &mut self,
+        drone_id: &DroneId,
+        position: &GeoPosition,
+        speed: f64,
+        now: DateTime<Utc>,
     ) -> Option<WaypointReached> {
         let mission = self.mission.as_ref()?;
         
@@ -122,20 +140,32 @@ impl MissionExecutor {
         }
 
         let progress = self.drone_progress.get_mut(drone_id)?;
+
+        // Progress is paused while holding at a loiter waypoint
+        if progress.loiter_until.is_some() {
+            return None;
+        }
+
         let current_wp = mission.waypoints.get(progress.current_index)?;
         
         // Calculate distance to current waypoint
         let distance = position.distance_to(&current_wp.position);
         
         // Check if waypoint reached
-        if distance < self.threshold_km {
+        if progress.arrival.observe(&self.arrival, current_wp, position, now) {
             let reached = WaypointReached {
                 drone_id: drone_id.clone(),
                 waypoint_id: current_wp.id.clone(),
                 waypoint_name: current_wp.name.clone(),
-                position: current_wp.position.clone(),
+                position: current_wp.position,
                 index: progress.current_index,
+                loiter_seconds: current_wp.loiter_time_seconds.filter(|s| *s > 0),
             };
+
+            if let Some(seconds) = reached.loiter_seconds {
+                progress.loiter_until = Some(now + chrono::Duration::seconds(seconds as i64));
+                info!("{} loitering at {} for {}s", drone_id, current_wp.name, seconds);
+            }
             
             progress.waypoints_completed.push(current_wp.id.clone());
             progress.current_index += 1;
@@ -167,13 +197,48 @@ impl MissionExecutor {
             if speed > 0.0 {
                 let time_hours = distance / speed;
                 let duration = chrono::Duration::seconds((time_hours * 3600.0) as i64);
-                progress.estimated_arrival = Some(chrono::Utc::now() + duration);
+                progress.estimated_arrival = Some(now + duration);
             }
         }
         
         None
     }
 
+    /// Release drones whose loiter timer has elapsed
+    pub fn poll_departures(&mut self, now: DateTime<Utc>) -> Vec<WaypointDeparted> {
+        let Some(mission) = self.mission.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut departed = Vec::new();
+        for (drone_id, progress) in self.drone_progress.iter_mut() {
+            if progress.loiter_until.is_some_and(|until| now >= until) {
+                progress.loiter_until = None;
+                let Some(waypoint) = progress
+                    .current_index
+                    .checked_sub(1)
+                    .and_then(|i| mission.waypoints.get(i))
+                else {
+                    continue;
+                };
+                info!("{} departed waypoint: {}", drone_id, waypoint.name);
+                departed.push(WaypointDeparted {
+                    drone_id: drone_id.clone(),
+                    waypoint_id: waypoint.id.clone(),
+                    position: waypoint.position,
+                });
+            }
+        }
+        departed
+    }
+
+    /// Whether a drone is holding at a loiter waypoint
+    pub fn is_loitering(&self, drone_id: &DroneId) -> bool {
+        self.drone_progress
+            .get(drone_id)
+            .is_some_and(|p| p.loiter_until.is_some())
+    }
+
     /// Get drone progress
     pub fn get_progress(&self, drone_id: &DroneId) -> Option<&WaypointProgress> {
         self.drone_progress.get(drone_id)
@@ -229,9 +294,18 @@ impl MissionExecutor {
         completed as f64 / total_waypoints as f64
     }
 
-    /// Set waypoint threshold
+    /// Set waypoint threshold; drones leave a waypoint at half again the
+    /// distance
     pub fn set_threshold(&mut self, km: f64) {
-        self.threshold_km = km.max(0.1);
+        self.arrival = ArrivalConfig {
+            min_dwell: self.arrival.min_dwell,
+            ..ArrivalConfig::with_threshold_km(km.max(0.1))
+        };
+    }
+
+    /// Set waypoint arrival and departure radii and dwell
+    pub fn set_arrival(&mut self, config: ArrivalConfig) {
+        self.arrival = config;
     }
 }
 
@@ -249,6 +323,16 @@ pub struct WaypointReached {
     pub waypoint_name: String,
     pub position: GeoPosition,
     pub index: usize,
+    /// Hold time at this waypoint, if it is a loiter waypoint
+    pub loiter_seconds: Option<u32>,
+}
+
+/// Event indicating a drone left a loiter waypoint
+#[derive(Debug, Clone)]
+pub struct WaypointDeparted {
+    pub drone_id: DroneId,
+    pub waypoint_id: WaypointId,
+    pub position: GeoPosition,
 }
 
 // ============================================================================
@@ -310,6 +394,77 @@ mod tests {
         assert_eq!(result.unwrap().waypoint_name, "Start");
     }
 
+    #[test]
+    fn test_loiter_pauses_progress_until_departure() {
+        let mut mission = create_test_mission();
+        mission.waypoints[0].loiter_time_seconds = Some(30);
+        let drone_id = DroneId::new("REAPER-01");
+
+        let mut executor = MissionExecutor::new();
+        executor.set_mission(mission);
+        executor.start();
+        executor.set_threshold(1.0);
+
+        let reached = executor
+            .update_drone_position(&drone_id, &GeoPosition::new(34.5, 69.2, 3000.0), 400.0)
+            .unwrap();
+        assert_eq!(reached.loiter_seconds, Some(30));
+        assert!(executor.is_loitering(&drone_id));
+
+        // Even at the next waypoint nothing advances while holding
+        assert!(executor
+            .update_drone_position(&drone_id, &GeoPosition::new(34.6, 69.1, 3000.0), 400.0)
+            .is_none());
+        assert!(executor.poll_departures(Utc::now()).is_empty());
+
+        let departed = executor.poll_departures(Utc::now() + chrono::Duration::seconds(31));
+        assert_eq!(departed.len(), 1);
+        assert_eq!(departed[0].waypoint_id, WaypointId::new("WP1"));
+        assert!(!executor.is_loitering(&drone_id));
+
+        let reached = executor
+            .update_drone_position(&drone_id, &GeoPosition::new(34.6, 69.1, 3000.0), 400.0)
+            .unwrap();
+        assert_eq!(reached.waypoint_name, "Middle");
+    }
+
+    #[test]
+    fn test_arrival_needs_dwell_and_fires_once() {
+        let mut mission = create_test_mission();
+        // The next waypoint is within the departure radius of the first
+        mission.waypoints[1].position = GeoPosition::new(34.5005, 69.2, 0.0);
+        // Only the loiter waypoint has to be stayed at
+        mission.waypoints[1].loiter_time_seconds = Some(30);
+        let drone_id = DroneId::new("REAPER-01");
+        let mut executor = MissionExecutor::new();
+        executor.set_mission(mission);
+        executor.start();
+        executor.set_arrival(ArrivalConfig {
+            arrival_radius_m: 100.0,
+            departure_radius_m: 300.0,
+            min_dwell: std::time::Duration::from_secs(2),
+        });
+
+        let t0 = Utc::now();
+        let at_start = GeoPosition::new(34.5, 69.2, 3000.0);
+        let update = |executor: &mut MissionExecutor, position: &GeoPosition, secs: i64| {
+            executor.update_drone_position_at(&drone_id, position, 0.0, t0 + chrono::Duration::seconds(secs))
+        };
+        // Flown through: the first report inside arrives
+        assert_eq!(update(&mut executor, &at_start, 0).unwrap().waypoint_name, "Start");
+
+        // Jitter out to 205 m is not arriving yet, then the dwell starts
+        assert!(update(&mut executor, &at_start.destination(0.15, 180.0), 1).is_none());
+        assert!(update(&mut executor, &at_start, 2).is_none());
+        assert!(update(&mut executor, &at_start, 3).is_none());
+        // Hovering between the two waypoints reaches the second once
+        assert_eq!(update(&mut executor, &at_start, 4).unwrap().waypoint_name, "Middle");
+        for secs in 5..10 {
+            assert!(update(&mut executor, &at_start, secs).is_none());
+        }
+        assert_eq!(executor.get_progress(&drone_id).unwrap().waypoints_completed.len(), 2);
+    }
+
     #[test]
     fn test_overall_progress() {
         let mut executor = MissionExecutor::new();
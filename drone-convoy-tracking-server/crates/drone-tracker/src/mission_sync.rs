@@ -0,0 +1,420 @@
+//! Mission route sync to drone agents
+//!
+//! Every mission set on the tracker whose waypoints differ from the last
+//! published route becomes a new route version; versions restart at 1 for
+//! a different mission. A new version is broadcast as a manifest, preceded
+//! by a diff from the previous version for agents that hold it. Agents
+//! request the chunks they are missing and report the version and hash
+//! they end up with. The last `history` versions are kept, so a drone
+//! reporting an older one is sent a diff rather than left to fetch chunks.
+
+use drone_core::{DroneId, Mission, MissionId};
+use drone_p2p::protocol::{
+    MissionChunkData, MissionChunkRequestData, MissionDiffData, MissionManifestData, MissionSyncedData,
+};
+use drone_p2p::{MissionRoute, DEFAULT_CHUNK_SIZE};
+
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Mission sync configuration
+#[derive(Debug, Clone)]
+pub struct MissionSyncConfig {
+    /// Waypoints per route chunk
+    pub chunk_size: usize,
+    /// Route versions kept for diffs
+    pub history: usize,
+    /// Re-broadcast the manifest this often while a drone is out of sync
+    pub announce_interval: Duration,
+}
+
+impl Default for MissionSyncConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            history: 8,
+            announce_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl MissionSyncConfig {
+    /// Defaults overridden by `MISSION_SYNC_CHUNK_SIZE` and
+    /// `MISSION_SYNC_ANNOUNCE_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            chunk_size: env("MISSION_SYNC_CHUNK_SIZE")
+                .and_then(|s| s.parse().ok())
+                .filter(|size: &usize| (1..=1024).contains(size))
+                .unwrap_or(defaults.chunk_size),
+            announce_interval: env("MISSION_SYNC_ANNOUNCE_SECS")
+                .and_then(|s| s.parse().ok())
+                .filter(|secs: &u64| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.announce_interval),
+            ..defaults
+        }
+    }
+}
+
+/// Where a drone's copy of the route stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    /// Nothing heard from the agent for this mission yet
+    Pending,
+    /// Fetching chunks of the current version
+    Syncing,
+    /// Holds the current version
+    InSync,
+    /// Holds an older version
+    Outdated,
+    /// Reported a route that does not match the version's hash
+    Diverged,
+}
+
+/// Route sync status of one drone
+#[derive(Debug, Clone, Serialize)]
+pub struct DroneSyncStatus {
+    pub drone_id: DroneId,
+    pub state: SyncState,
+    /// Route version the agent last reported holding
+    pub version: Option<u64>,
+    /// Chunks asked for in the agent's latest request
+    pub chunks_requested: usize,
+    /// Chunks sent to the agent for this mission
+    pub chunks_sent: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Published route and the sync status of each drone
+#[derive(Debug, Clone, Serialize)]
+pub struct MissionSyncView {
+    pub mission_id: Option<MissionId>,
+    pub version: Option<u64>,
+    pub hash: Option<String>,
+    pub waypoint_count: usize,
+    pub chunk_count: usize,
+    pub drones: Vec<DroneSyncStatus>,
+}
+
+/// Messages announcing a newly published route version
+#[derive(Debug, Clone)]
+pub struct Publication {
+    /// Edits from the previous version (none for a new mission)
+    pub diff: Option<MissionDiffData>,
+    pub manifest: MissionManifestData,
+}
+
+/// Answer to an agent's sync message
+#[derive(Debug, Clone)]
+pub enum SyncReply {
+    Chunks(Vec<MissionChunkData>),
+    Diff(MissionDiffData),
+    Manifest(MissionManifestData),
+}
+
+#[derive(Debug, Clone)]
+struct DroneSync {
+    state: SyncState,
+    version: Option<u64>,
+    chunks_requested: usize,
+    chunks_sent: u64,
+    updated_at: DateTime<Utc>,
+}
+
+/// Route versions and per-drone sync state
+#[derive(Debug, Default)]
+pub struct MissionSync {
+    config: MissionSyncConfig,
+    /// Published versions of the current mission, oldest first
+    versions: RwLock<VecDeque<MissionRoute>>,
+    drones: RwLock<HashMap<DroneId, DroneSync>>,
+    /// Latest version not yet broadcast
+    pending: Mutex<Option<Publication>>,
+    published: Notify,
+}
+
+impl MissionSync {
+    pub fn new(config: MissionSyncConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &MissionSyncConfig {
+        &self.config
+    }
+
+    /// Publish `mission`'s route; the new version, or `None` if the route
+    /// did not change
+    pub fn publish(&self, mission: &Mission) -> Option<u64> {
+        let mut versions = self.versions.write();
+        let latest = versions.back().filter(|route| route.mission_id == mission.id);
+        let route = MissionRoute::from_mission(mission, latest.map_or(1, |route| route.version + 1));
+        if latest.is_some_and(|latest| latest.same_route(&route)) {
+            return None;
+        }
+
+        let diff = latest.map(|latest| route.diff_from(latest));
+        if diff.is_none() {
+            versions.clear();
+            self.drones.write().clear();
+        }
+        *self.pending.lock() = Some(Publication {
+            diff,
+            manifest: route.manifest(self.config.chunk_size),
+        });
+        let version = route.version;
+        versions.push_back(route);
+        while versions.len() > self.config.history.max(1) {
+            versions.pop_front();
+        }
+        self.published.notify_one();
+        Some(version)
+    }
+
+    /// Wait until a version is published
+    pub async fn published(&self) {
+        self.published.notified().await
+    }
+
+    /// Take the latest version not yet broadcast
+    pub fn take_publication(&self) -> Option<Publication> {
+        self.pending.lock().take()
+    }
+
+    /// Manifest of the current version
+    pub fn manifest(&self) -> Option<MissionManifestData> {
+        Some(self.versions.read().back()?.manifest(self.config.chunk_size))
+    }
+
+    /// Every drone in `drone_ids` holds the current version
+    pub fn all_synced(&self, drone_ids: &[DroneId]) -> bool {
+        let version = self.versions.read().back().map(|route| route.version);
+        let drones = self.drones.read();
+        drone_ids.iter().all(|drone_id| {
+            drones
+                .get(drone_id)
+                .is_some_and(|d| d.state == SyncState::InSync && d.version == version)
+        })
+    }
+
+    /// Chunks an agent asked for, each once and only those the route has;
+    /// the current manifest if it asked for chunks of another version
+    pub fn chunks_requested(&self, request: &MissionChunkRequestData, now: DateTime<Utc>) -> Option<SyncReply> {
+        let versions = self.versions.read();
+        let current = versions.back().filter(|route| route.mission_id == request.mission_id)?;
+        if request.version != current.version {
+            return Some(SyncReply::Manifest(current.manifest(self.config.chunk_size)));
+        }
+
+        let chunk_count = current.chunk_count(self.config.chunk_size) as u32;
+        let mut indices: Vec<u32> = request.chunks.iter().copied().filter(|&index| index < chunk_count).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let chunks: Vec<_> = indices
+            .iter()
+            .filter_map(|&index| current.chunk(index, self.config.chunk_size))
+            .collect();
+        let mut drones = self.drones.write();
+        let drone = drones.entry(request.drone_id.clone()).or_insert_with(|| DroneSync::new(now));
+        drone.state = SyncState::Syncing;
+        drone.chunks_requested = chunks.len();
+        drone.chunks_sent += chunks.len() as u64;
+        drone.updated_at = now;
+        Some(SyncReply::Chunks(chunks))
+    }
+
+    /// Record the route an agent reports holding and bring it up to date
+    pub fn synced(&self, report: &MissionSyncedData, now: DateTime<Utc>) -> Option<SyncReply> {
+        let versions = self.versions.read();
+        let current = versions.back()?;
+        let held = versions
+            .iter()
+            .find(|route| route.mission_id == report.mission_id && route.version == report.version);
+
+        let (state, reply) = match held {
+            Some(route) if route.hash() != report.hash => {
+                (SyncState::Diverged, Some(SyncReply::Manifest(current.manifest(self.config.chunk_size))))
+            }
+            Some(route) if route.version == current.version => (SyncState::InSync, None),
+            Some(route) => (SyncState::Outdated, Some(SyncReply::Diff(current.diff_from(route)))),
+            None => (SyncState::Outdated, Some(SyncReply::Manifest(current.manifest(self.config.chunk_size)))),
+        };
+
+        let mut drones = self.drones.write();
+        let drone = drones.entry(report.drone_id.clone()).or_insert_with(|| DroneSync::new(now));
+        drone.state = state;
+        drone.version = (report.mission_id == current.mission_id).then_some(report.version);
+        if state == SyncState::InSync {
+            drone.chunks_requested = 0;
+        }
+        drone.updated_at = now;
+        reply
+    }
+
+    /// Sync status of `drone_ids`, in that order
+    pub fn status(&self, drone_ids: &[DroneId]) -> MissionSyncView {
+        let versions = self.versions.read();
+        let current = versions.back();
+        let drones = self.drones.read();
+        MissionSyncView {
+            mission_id: current.map(|route| route.mission_id.clone()),
+            version: current.map(|route| route.version),
+            hash: current.map(|route| route.hash()),
+            waypoint_count: current.map_or(0, |route| route.waypoints.len()),
+            chunk_count: current.map_or(0, |route| route.chunk_count(self.config.chunk_size)),
+            drones: drone_ids
+                .iter()
+                .map(|drone_id| match drones.get(drone_id) {
+                    Some(drone) => DroneSyncStatus {
+                        drone_id: drone_id.clone(),
+                        // A newer version went out since the agent was in sync
+                        state: match drone.state {
+                            SyncState::InSync if drone.version != current.map(|route| route.version) => {
+                                SyncState::Outdated
+                            }
+                            state => state,
+                        },
+                        version: drone.version,
+                        chunks_requested: drone.chunks_requested,
+                        chunks_sent: drone.chunks_sent,
+                        updated_at: Some(drone.updated_at),
+                    },
+                    None => DroneSyncStatus {
+                        drone_id: drone_id.clone(),
+                        state: SyncState::Pending,
+                        version: None,
+                        chunks_requested: 0,
+                        chunks_sent: 0,
+                        updated_at: None,
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.drones.write().remove(drone_id);
+    }
+}
+
+impl DroneSync {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            state: SyncState::Pending,
+            version: None,
+            chunks_requested: 0,
+            chunks_sent: 0,
+            updated_at: now,
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{MissionBuilder, Waypoint};
+
+    fn mission(count: usize) -> Mission {
+        MissionBuilder::new("Sync Test")
+            .with_waypoints((0..count).map(|i| {
+                Waypoint::new(format!("WP-{}", i), format!("Waypoint {}", i), 34.0 + i as f64 * 0.01, 69.0)
+            }))
+            .build()
+            .unwrap()
+    }
+
+    fn synced(drone: &str, mission: &Mission, manifest: &MissionManifestData) -> MissionSyncedData {
+        MissionSyncedData {
+            drone_id: DroneId::new(drone),
+            mission_id: mission.id.clone(),
+            version: manifest.version,
+            hash: manifest.hash.clone(),
+        }
+    }
+
+    #[test]
+    fn test_publish_versions() {
+        let sync = MissionSync::new(MissionSyncConfig { chunk_size: 4, ..Default::default() });
+        let mut mission = mission(10);
+        assert_eq!(sync.publish(&mission), Some(1));
+        let first = sync.take_publication().unwrap();
+        assert!(first.diff.is_none());
+        assert_eq!(first.manifest.chunk_hashes.len(), 3);
+
+        // Setting the same route again publishes nothing
+        assert_eq!(sync.publish(&mission), None);
+        assert!(sync.take_publication().is_none());
+
+        mission.set_speed_limits(&HashMap::from([(mission.waypoints[5].id.clone(), 80.0)]), false);
+        assert_eq!(sync.publish(&mission), Some(2));
+        let second = sync.take_publication().unwrap();
+        let diff = second.diff.unwrap();
+        assert_eq!((diff.base_version, diff.version), (1, 2));
+        assert_eq!(diff.edits.len(), 1);
+        assert_eq!(diff.edits[0].index, 5);
+
+        // Another mission starts over
+        assert_eq!(sync.publish(&self::mission(3)), Some(1));
+    }
+
+    #[test]
+    fn test_drone_sync_status() {
+        let sync = MissionSync::new(MissionSyncConfig { chunk_size: 4, ..Default::default() });
+        let mut mission = mission(10);
+        sync.publish(&mission);
+        let v1 = sync.manifest().unwrap();
+        let now = Utc::now();
+        let drones = [DroneId::new("REAPER-01"), DroneId::new("REAPER-02")];
+
+        // Repeated and out-of-range indices are not answered
+        let request = MissionChunkRequestData {
+            drone_id: drones[0].clone(),
+            mission_id: mission.id.clone(),
+            version: 1,
+            chunks: vec![2, 0, 1, 2, 0, 7, u32::MAX],
+        };
+        let Some(SyncReply::Chunks(chunks)) = sync.chunks_requested(&request, now) else {
+            panic!("expected chunks");
+        };
+        assert_eq!(chunks.iter().map(|c| c.index).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(sync.status(&drones).drones[0].chunks_requested, 3);
+        assert_eq!(sync.status(&drones).drones[0].state, SyncState::Syncing);
+        assert_eq!(sync.status(&drones).drones[1].state, SyncState::Pending);
+
+        assert!(sync.synced(&synced("REAPER-01", &mission, &v1), now).is_none());
+        let mut corrupt = synced("REAPER-02", &mission, &v1);
+        corrupt.hash = "00".into();
+        assert!(matches!(sync.synced(&corrupt, now), Some(SyncReply::Manifest(_))));
+        let view = sync.status(&drones);
+        assert_eq!(view.drones[0].state, SyncState::InSync);
+        assert_eq!(view.drones[0].chunks_sent, 3);
+        assert_eq!(view.drones[1].state, SyncState::Diverged);
+        assert!(!sync.all_synced(&drones));
+
+        // A waypoint edit leaves the drone outdated until it takes the diff
+        mission.waypoints[9].loiter_time_seconds = Some(30);
+        sync.publish(&mission);
+        assert_eq!(sync.status(&drones).drones[0].state, SyncState::Outdated);
+        let Some(SyncReply::Diff(diff)) = sync.synced(&synced("REAPER-01", &mission, &v1), now) else {
+            panic!("expected a diff");
+        };
+        assert_eq!(diff.base_version, 1);
+        let v2 = sync.manifest().unwrap();
+        assert!(sync.synced(&synced("REAPER-01", &mission, &v2), now).is_none());
+        assert_eq!(sync.status(&drones).drones[0].version, Some(2));
+    }
+}
@@ -0,0 +1,182 @@
+//! Derived motion
+//!
+//! Reported headings jump between route legs and reports carry no vertical
+//! speed, so the tracker derives both from successive positions. The course
+//! over ground is low-pass filtered into a smoothed heading; climb rate and
+//! acceleration are filtered altitude and speed deltas (a vario), and the
+//! turn rate follows from the smoothed heading.
+
+use chrono::{DateTime, Utc};
+use drone_core::{DerivedMotion, DroneId, GeoPosition, Telemetry};
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Motion smoothing configuration
+#[derive(Debug, Clone)]
+pub struct MotionConfig {
+    /// Time constant of the heading filter
+    pub heading_time_constant: Duration,
+    /// Time constant of the climb rate and acceleration filters
+    pub vario_time_constant: Duration,
+    /// Below this displacement (meters) the reported heading is used
+    /// instead of the bearing between positions
+    pub min_displacement_m: f64,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            heading_time_constant: Duration::from_secs(3),
+            vario_time_constant: Duration::from_secs(2),
+            min_displacement_m: 1.0,
+        }
+    }
+}
+
+/// Last report and filter state for one drone
+#[derive(Debug, Clone)]
+struct MotionState {
+    at: DateTime<Utc>,
+    position: GeoPosition,
+    /// m/s
+    speed: f64,
+    motion: DerivedMotion,
+}
+
+/// Per-drone motion filters
+#[derive(Debug)]
+pub struct MotionEstimator {
+    config: MotionConfig,
+    states: RwLock<HashMap<DroneId, MotionState>>,
+}
+
+impl MotionEstimator {
+    pub fn new(config: MotionConfig) -> Self {
+        Self {
+            config,
+            states: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Fold a position report into the drone's filters
+    ///
+    /// The first report seeds the heading from telemetry with the rates at
+    /// zero; reports not newer than the previous one change nothing.
+    pub fn observe(
+        &self,
+        drone_id: &DroneId,
+        position: GeoPosition,
+        telemetry: &Telemetry,
+        at: DateTime<Utc>,
+    ) -> DerivedMotion {
+        let speed = telemetry.speed / 3.6;
+        let mut states = self.states.write();
+        let Some(state) = states.get_mut(drone_id) else {
+            let motion = DerivedMotion {
+                smoothed_heading: telemetry.heading.rem_euclid(360.0),
+                ..Default::default()
+            };
+            states.insert(drone_id.clone(), MotionState { at, position, speed, motion });
+            return motion;
+        };
+
+        let dt = (at - state.at).num_milliseconds() as f64 / 1000.0;
+        if dt <= 0.0 {
+            return state.motion;
+        }
+        let gain = |time_constant: Duration| 1.0 - (-dt / time_constant.as_secs_f64().max(1e-3)).exp();
+
+        let course = if state.position.distance_to(&position) * 1000.0 >= self.config.min_displacement_m {
+            state.position.bearing_to(&position)
+        } else {
+            telemetry.heading
+        };
+        let previous = state.motion;
+        let turn = gain(self.config.heading_time_constant) * angle_difference(previous.smoothed_heading, course);
+
+        let vario = gain(self.config.vario_time_constant);
+        let climb = (position.altitude - state.position.altitude) / dt;
+        let acceleration = (speed - state.speed) / dt;
+
+        state.motion = DerivedMotion {
+            smoothed_heading: (previous.smoothed_heading + turn).rem_euclid(360.0),
+            climb_rate: previous.climb_rate + vario * (climb - previous.climb_rate),
+            acceleration: previous.acceleration + vario * (acceleration - previous.acceleration),
+            turn_rate: turn / dt,
+        };
+        state.at = at;
+        state.position = position;
+        state.speed = speed;
+        state.motion
+    }
+
+    /// Latest derived motion for a drone
+    pub fn get(&self, drone_id: &DroneId) -> Option<DerivedMotion> {
+        self.states.read().get(drone_id).map(|state| state.motion)
+    }
+
+    /// Drop a drone's smoothing state
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.states.write().remove(drone_id);
+    }
+
+    /// Drones with smoothing state
+    pub fn drone_count(&self) -> usize {
+        self.states.read().len()
+    }
+}
+
+/// Signed shortest rotation from `from` to `to` (degrees, -180..180)
+pub(crate) fn angle_difference(from: f64, to: f64) -> f64 {
+    (to - from + 540.0).rem_euclid(360.0) - 180.0
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heading_smoothing_and_vario() {
+        let estimator = MotionEstimator::new(MotionConfig::default());
+        let drone_id = DroneId::new("REAPER-01");
+        let start = Utc::now();
+        let telemetry = Telemetry { heading: 350.0, speed: 360.0, ..Default::default() };
+        let mut position = GeoPosition::new(34.5, 69.2, 1000.0);
+        estimator.observe(&drone_id, position, &telemetry, start);
+
+        // Flying due east while climbing 5 m/s and speeding up 2 m/s²: the
+        // heading turns right from 350° through north without jumping
+        let mut motion = DerivedMotion::default();
+        for second in 1..=20 {
+            let speed = 100.0 + 2.0 * second as f64;
+            position = position.destination(speed / 1000.0, 90.0);
+            position.altitude += 5.0;
+            let telemetry = Telemetry { heading: 90.0, speed: speed * 3.6, ..Default::default() };
+            let at = start + chrono::Duration::seconds(second);
+            let previous = motion.smoothed_heading;
+            motion = estimator.observe(&drone_id, position, &telemetry, at);
+            if second == 1 {
+                assert!(motion.smoothed_heading > 350.0 || motion.smoothed_heading < 90.0);
+                assert!(motion.turn_rate > 0.0);
+            } else {
+                assert!(angle_difference(previous, motion.smoothed_heading) >= 0.0);
+            }
+        }
+
+        assert!((motion.smoothed_heading - 90.0).abs() < 1.0, "heading {}", motion.smoothed_heading);
+        assert!(motion.turn_rate.abs() < 1.0);
+        assert!((motion.climb_rate - 5.0).abs() < 0.1, "climb {}", motion.climb_rate);
+        assert!((motion.acceleration - 2.0).abs() < 0.1, "acceleration {}", motion.acceleration);
+        assert_eq!(estimator.get(&drone_id), Some(motion));
+
+        // A stale report changes nothing
+        let stale = estimator.observe(&drone_id, GeoPosition::new(0.0, 0.0, 0.0), &telemetry, start);
+        assert_eq!(stale, motion);
+    }
+}
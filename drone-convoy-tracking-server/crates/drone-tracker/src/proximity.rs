@@ -0,0 +1,722 @@
+//! Predictive collision avoidance
+//!
+//! Every flying drone is extrapolated along its current velocity for the
+//! lookahead window: drones with fresh telemetry from their ground speed,
+//! heading and climb rate, drones seen only by the CV engine from a
+//! constant-velocity Kalman filter over their geo-estimates. Candidate pairs
+//! come from a sweep over each drone's swept bounding box, so only drones
+//! whose paths come near each other are compared. A pair that will be inside
+//! both separation minima at the same moment within the window raises a
+//! warning with the time to the closest point of approach (CPA) and a
+//! suggested climb/descent and right turn for each drone. A pair warns once
+//! per severity and re-arms when its projection is clear.
+
+use drone_core::{DroneId, GeoPosition};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Right turns tried, smallest first, when suggesting a heading change
+const TURN_STEPS_DEG: [f64; 6] = [15.0, 30.0, 45.0, 60.0, 75.0, 90.0];
+
+/// Suggested altitude changes are rounded up to this (m)
+const ALTITUDE_STEP_M: f64 = 10.0;
+
+/// Below this a drone is treated as stationary and gets no turn (m/s)
+const MIN_TURN_SPEED_MS: f64 = 1.0;
+
+/// Acceleration noise of the CV velocity filter (m/s², 1 sigma)
+const CV_ACCEL_SIGMA: f64 = 2.0;
+
+/// Swept boxes are padded by this much more than half the horizontal
+/// minimum, covering the flat-earth scale difference between nearby drones
+const SWEEP_MARGIN: f64 = 1.1;
+
+/// Proximity prediction configuration
+#[derive(Debug, Clone)]
+pub struct ProximityConfig {
+    /// How far ahead drones are projected (30-120 s)
+    pub lookahead: Duration,
+    /// Warn when a pair will pass closer than this horizontally...
+    pub horizontal_separation_m: f64,
+    /// ...while also closer than this vertically
+    pub vertical_separation_m: f64,
+    /// Warnings with less time to CPA than this are critical
+    pub critical_within: Duration,
+    /// Telemetry older than this is not extrapolated; the CV track is used
+    pub telemetry_max_age: Duration,
+    /// CV tracks without an estimate for this long are dropped
+    pub cv_max_age: Duration,
+    /// How often the background check runs
+    pub check_interval: Duration,
+}
+
+impl Default for ProximityConfig {
+    fn default() -> Self {
+        Self {
+            lookahead: Duration::from_secs(120),
+            horizontal_separation_m: 150.0,
+            vertical_separation_m: 60.0,
+            critical_within: Duration::from_secs(30),
+            telemetry_max_age: Duration::from_secs(5),
+            cv_max_age: Duration::from_secs(5),
+            check_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl ProximityConfig {
+    /// Defaults overridden by `PROXIMITY_LOOKAHEAD_SECS` (clamped to
+    /// 30-120), `PROXIMITY_HORIZONTAL_M` and `PROXIMITY_VERTICAL_M`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        let meters = |name: &str| {
+            env(name)
+                .and_then(|s| s.parse().ok())
+                .filter(|m: &f64| *m > 0.0 && m.is_finite())
+        };
+        Self {
+            lookahead: env("PROXIMITY_LOOKAHEAD_SECS")
+                .and_then(|s| s.parse().ok())
+                .map(|s: u64| Duration::from_secs(s.clamp(30, 120)))
+                .unwrap_or(defaults.lookahead),
+            horizontal_separation_m: meters("PROXIMITY_HORIZONTAL_M").unwrap_or(defaults.horizontal_separation_m),
+            vertical_separation_m: meters("PROXIMITY_VERTICAL_M").unwrap_or(defaults.vertical_separation_m),
+            ..defaults
+        }
+    }
+}
+
+/// Where a projected velocity comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VelocitySource {
+    /// Ground speed, heading and climb rate
+    Telemetry,
+    /// Kalman filter over CV geo-estimates
+    CvKalman,
+}
+
+/// A drone's position and velocity at the time of the check
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectedTrack {
+    pub drone_id: DroneId,
+    pub position: GeoPosition,
+    /// m/s
+    pub east_ms: f64,
+    pub north_ms: f64,
+    pub up_ms: f64,
+    pub source: VelocitySource,
+}
+
+impl ProjectedTrack {
+    /// Track from telemetry: `speed_kmh` along `heading_deg`
+    pub fn from_telemetry(
+        drone_id: DroneId,
+        position: GeoPosition,
+        speed_kmh: f64,
+        heading_deg: f64,
+        climb_rate_ms: f64,
+    ) -> Self {
+        let speed = speed_kmh / 3.6;
+        let heading = heading_deg.to_radians();
+        Self {
+            drone_id,
+            position,
+            east_ms: speed * heading.sin(),
+            north_ms: speed * heading.cos(),
+            up_ms: climb_rate_ms,
+            source: VelocitySource::Telemetry,
+        }
+    }
+
+    fn ground_speed(&self) -> f64 {
+        self.east_ms.hypot(self.north_ms)
+    }
+
+    /// Latitude/longitude box covering the track over `lookahead` seconds,
+    /// padded by `pad_m`: (min lat, max lat, min lon, max lon)
+    fn swept_box(&self, lookahead: f64, pad_m: f64) -> (f64, f64, f64, f64) {
+        let end = self.position.offset_by_m(self.east_ms * lookahead, self.north_ms * lookahead);
+        let mut bounds = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for p in [self.position, end] {
+            for corner in [p.offset_by_m(-pad_m, -pad_m), p.offset_by_m(pad_m, pad_m)] {
+                bounds.0 = bounds.0.min(corner.latitude);
+                bounds.1 = bounds.1.max(corner.latitude);
+                bounds.2 = bounds.2.min(corner.longitude);
+                bounds.3 = bounds.3.max(corner.longitude);
+            }
+        }
+        bounds
+    }
+
+    fn heading(&self) -> f64 {
+        (self.east_ms.atan2(self.north_ms).to_degrees() + 360.0) % 360.0
+    }
+
+    /// Same track turned right by `degrees`
+    fn turned(&self, degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self {
+            east_ms: self.east_ms * cos + self.north_ms * sin,
+            north_ms: self.north_ms * cos - self.east_ms * sin,
+            ..self.clone()
+        }
+    }
+}
+
+/// Closest point of approach of two tracks within the lookahead
+#[derive(Debug, Clone, Copy)]
+pub struct Approach {
+    /// Seconds from now
+    pub time_s: f64,
+    pub horizontal_m: f64,
+    pub vertical_m: f64,
+    /// Midpoint between the two drones at CPA
+    pub position: GeoPosition,
+}
+
+/// Relative position (m) and velocity (m/s) of `b` seen from `a`
+fn relative(a: &ProjectedTrack, b: &ProjectedTrack) -> ([f64; 3], [f64; 3]) {
+    let (rx, ry) = a.position.offset_to_m(&b.position);
+    let rz = b.position.altitude - a.position.altitude;
+    ([rx, ry, rz], [b.east_ms - a.east_ms, b.north_ms - a.north_ms, b.up_ms - a.up_ms])
+}
+
+/// Time of closest horizontal approach, unclamped
+fn horizontal_cpa_time(r: [f64; 3], v: [f64; 3]) -> f64 {
+    let closing = v[0] * v[0] + v[1] * v[1];
+    if closing > 1e-9 {
+        -(r[0] * v[0] + r[1] * v[1]) / closing
+    } else {
+        0.0
+    }
+}
+
+/// Separation of the two tracks `time_s` seconds from now
+fn approach_at(a: &ProjectedTrack, r: [f64; 3], v: [f64; 3], time_s: f64) -> Approach {
+    let (dx, dy, dz) = (r[0] + v[0] * time_s, r[1] + v[1] * time_s, r[2] + v[2] * time_s);
+
+    let a_at = a.position.offset_by_m(a.east_ms * time_s, a.north_ms * time_s);
+    let a_alt = a.position.altitude + a.up_ms * time_s;
+    let mut position = a_at.offset_by_m(dx / 2.0, dy / 2.0);
+    position.altitude = a_alt + dz / 2.0;
+
+    Approach {
+        time_s,
+        horizontal_m: dx.hypot(dy),
+        vertical_m: dz.abs(),
+        position,
+    }
+}
+
+/// Closest horizontal approach of `b` to `a`, within `lookahead` seconds
+pub fn closest_approach(a: &ProjectedTrack, b: &ProjectedTrack, lookahead: f64) -> Approach {
+    let (r, v) = relative(a, b);
+    approach_at(a, r, v, horizontal_cpa_time(r, v).clamp(0.0, lookahead))
+}
+
+/// Times `t` where `|r + v t| < limit`, as an open interval; `None` if never
+fn inside_interval(r: &[f64], v: &[f64], limit: f64) -> Option<(f64, f64)> {
+    let a: f64 = v.iter().map(|v| v * v).sum();
+    let b: f64 = 2.0 * r.iter().zip(v).map(|(r, v)| r * v).sum::<f64>();
+    let c: f64 = r.iter().map(|r| r * r).sum::<f64>() - limit * limit;
+    if a < 1e-12 {
+        return (c < 0.0).then_some((f64::NEG_INFINITY, f64::INFINITY));
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant <= 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some(((-b - root) / (2.0 * a), (-b + root) / (2.0 * a)))
+}
+
+/// Loss of separation between `a` and `b` within `lookahead` seconds
+///
+/// A pair conflicts only while it is inside both minima at once, so the
+/// horizontal and vertical conflict intervals are intersected over the
+/// window. The approach is reported at the horizontal CPA, moved into that
+/// intersection when the drones are vertically clear at the CPA itself.
+pub fn conflict(
+    a: &ProjectedTrack,
+    b: &ProjectedTrack,
+    lookahead: f64,
+    horizontal_m: f64,
+    vertical_m: f64,
+) -> Option<Approach> {
+    let (r, v) = relative(a, b);
+    let (h_from, h_to) = inside_interval(&r[..2], &v[..2], horizontal_m)?;
+    let (v_from, v_to) = inside_interval(&r[2..], &v[2..], vertical_m)?;
+    let from = h_from.max(v_from).max(0.0);
+    let to = h_to.min(v_to).min(lookahead);
+    if from >= to {
+        return None;
+    }
+    Some(approach_at(a, r, v, horizontal_cpa_time(r, v).clamp(from, to)))
+}
+
+/// Suggested maneuver for one drone of a conflicting pair
+#[derive(Debug, Clone, Serialize)]
+pub struct Avoidance {
+    pub drone_id: DroneId,
+    /// Positive to climb, negative to descend (m)
+    pub altitude_change_m: f64,
+    /// Smallest right turn that alone restores horizontal separation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_change_deg: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_heading: Option<f64>,
+}
+
+/// A predicted loss of separation between two drones
+#[derive(Debug, Clone, Serialize)]
+pub struct CollisionWarning {
+    pub drone_id: DroneId,
+    pub other_drone_id: DroneId,
+    pub time_to_cpa_secs: f64,
+    /// Horizontal and vertical distance at CPA (m)
+    pub cpa_horizontal_m: f64,
+    pub cpa_vertical_m: f64,
+    pub cpa_position: GeoPosition,
+    pub sources: [VelocitySource; 2],
+    /// One maneuver per drone; either one resolves the conflict
+    pub avoidance: [Avoidance; 2],
+    /// Less than `critical_within` to CPA
+    pub critical: bool,
+}
+
+// ============================================================================
+// CV VELOCITY FILTER
+// ============================================================================
+
+/// Constant-velocity Kalman filter along one axis
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    position: f64,
+    velocity: f64,
+    /// Covariance [[pp, pv], [pv, vv]]
+    pp: f64,
+    pv: f64,
+    vv: f64,
+}
+
+impl Axis {
+    fn new(position: f64, variance: f64) -> Self {
+        Self {
+            position,
+            velocity: 0.0,
+            pp: variance,
+            pv: 0.0,
+            // Unknown velocity: up to ~100 m/s
+            vv: 100.0 * 100.0,
+        }
+    }
+
+    fn predict(&mut self, dt: f64) {
+        let q = CV_ACCEL_SIGMA * CV_ACCEL_SIGMA;
+        self.position += self.velocity * dt;
+        self.pp += dt * (2.0 * self.pv + dt * self.vv) + q * dt.powi(4) / 4.0;
+        self.pv += dt * self.vv + q * dt.powi(3) / 2.0;
+        self.vv += q * dt * dt;
+    }
+
+    fn update(&mut self, measured: f64, variance: f64) {
+        let innovation = measured - self.position;
+        let s = self.pp + variance;
+        let (k_p, k_v) = (self.pp / s, self.pv / s);
+        self.position += k_p * innovation;
+        self.velocity += k_v * innovation;
+        let (pp, pv) = (self.pp, self.pv);
+        self.pp -= k_p * pp;
+        self.pv -= k_p * pv;
+        self.vv -= k_v * pv;
+    }
+}
+
+/// Kalman state of one CV track, in meters from its first estimate
+#[derive(Debug, Clone)]
+struct CvTrack {
+    origin: GeoPosition,
+    east: Axis,
+    north: Axis,
+    up: Axis,
+    updates: u32,
+    at: DateTime<Utc>,
+}
+
+impl CvTrack {
+    fn new(position: GeoPosition, sigma_m: f64, at: DateTime<Utc>) -> Self {
+        let variance = sigma_m * sigma_m;
+        Self {
+            origin: position,
+            east: Axis::new(0.0, variance),
+            north: Axis::new(0.0, variance),
+            up: Axis::new(position.altitude, variance),
+            updates: 1,
+            at,
+        }
+    }
+
+    fn update(&mut self, position: GeoPosition, sigma_m: f64, at: DateTime<Utc>) {
+        let dt = (at - self.at).num_milliseconds() as f64 / 1000.0;
+        if dt < 0.0 {
+            return;
+        }
+        let variance = sigma_m * sigma_m;
+        let (east, north) = self.origin.offset_to_m(&position);
+        for (axis, measured) in [
+            (&mut self.east, east),
+            (&mut self.north, north),
+            (&mut self.up, position.altitude),
+        ] {
+            axis.predict(dt);
+            axis.update(measured, variance);
+        }
+        self.updates += 1;
+        self.at = at;
+    }
+
+    /// Filtered track as of `now`; `None` until a velocity is observable
+    fn project(&self, drone_id: &DroneId, now: DateTime<Utc>) -> Option<ProjectedTrack> {
+        if self.updates < 2 {
+            return None;
+        }
+        let dt = ((now - self.at).num_milliseconds() as f64 / 1000.0).max(0.0);
+        let east = self.east.position + self.east.velocity * dt;
+        let north = self.north.position + self.north.velocity * dt;
+        let mut position = self.origin.offset_by_m(east, north);
+        position.altitude = self.up.position + self.up.velocity * dt;
+        Some(ProjectedTrack {
+            drone_id: drone_id.clone(),
+            position,
+            east_ms: self.east.velocity,
+            north_ms: self.north.velocity,
+            up_ms: self.up.velocity,
+            source: VelocitySource::CvKalman,
+        })
+    }
+}
+
+// ============================================================================
+// MONITOR
+// ============================================================================
+
+/// Pairwise CPA prediction over projected drone tracks
+#[derive(Debug, Default)]
+pub struct ProximityMonitor {
+    config: ProximityConfig,
+    cv_tracks: DashMap<DroneId, CvTrack>,
+    /// Pairs with an outstanding warning, lower drone ID first, and whether
+    /// it has been raised as critical
+    warned: RwLock<HashMap<(DroneId, DroneId), bool>>,
+}
+
+impl ProximityMonitor {
+    pub fn new(config: ProximityConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &ProximityConfig {
+        &self.config
+    }
+
+    /// Feed a CV geo-estimate into the drone's velocity filter
+    pub fn record_cv(&self, drone_id: &DroneId, position: GeoPosition, sigma_m: f64, at: DateTime<Utc>) {
+        let stale = chrono::Duration::from_std(self.config.cv_max_age).unwrap_or_default();
+        match self.cv_tracks.get_mut(drone_id) {
+            Some(mut track) if at - track.at <= stale => track.update(position, sigma_m, at),
+            _ => {
+                self.cv_tracks.insert(drone_id.clone(), CvTrack::new(position, sigma_m, at));
+            }
+        }
+    }
+
+    /// CV-filtered track of a drone, if its estimates are fresh
+    pub fn cv_track(&self, drone_id: &DroneId, now: DateTime<Utc>) -> Option<ProjectedTrack> {
+        let stale = chrono::Duration::from_std(self.config.cv_max_age).unwrap_or_default();
+        let track = self.cv_tracks.get(drone_id)?;
+        (now - track.at <= stale).then(|| track.project(drone_id, now)).flatten()
+    }
+
+    /// Fresh CV tracks of drones not in `known`, e.g. drones without telemetry
+    pub fn cv_only_tracks(&self, known: &HashSet<DroneId>, now: DateTime<Utc>) -> Vec<ProjectedTrack> {
+        let ids: Vec<DroneId> = self
+            .cv_tracks
+            .iter()
+            .map(|t| t.key().clone())
+            .filter(|id| !known.contains(id))
+            .collect();
+        ids.iter().filter_map(|id| self.cv_track(id, now)).collect()
+    }
+
+    /// Drop a drone's CV track and outstanding warnings
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.cv_tracks.remove(drone_id);
+        self.warned.write().retain(|(a, b), _| a != drone_id && b != drone_id);
+    }
+
+    /// Conflicting pairs among `tracks`
+    pub fn predict(&self, tracks: &[ProjectedTrack]) -> Vec<CollisionWarning> {
+        let config = &self.config;
+        let lookahead = config.lookahead.as_secs_f64();
+        let pad = config.horizontal_separation_m / 2.0 * SWEEP_MARGIN;
+
+        // Sweep the swept boxes west to east; only overlapping boxes can meet
+        let boxes: Vec<_> = tracks.iter().map(|t| t.swept_box(lookahead, pad)).collect();
+        let mut order: Vec<usize> = (0..tracks.len()).collect();
+        order.sort_by(|&i, &j| boxes[i].2.total_cmp(&boxes[j].2));
+
+        let mut warnings = Vec::new();
+        for (k, &i) in order.iter().enumerate() {
+            for &j in &order[k + 1..] {
+                if boxes[j].2 > boxes[i].3 {
+                    break;
+                }
+                if boxes[j].0 > boxes[i].1 || boxes[i].0 > boxes[j].1 {
+                    continue;
+                }
+                // Keep the callers' order within the pair
+                let (a, b) = if i < j { (&tracks[i], &tracks[j]) } else { (&tracks[j], &tracks[i]) };
+                if let Some(approach) =
+                    conflict(a, b, lookahead, config.horizontal_separation_m, config.vertical_separation_m)
+                {
+                    warnings.push(self.warning(a, b, &approach));
+                }
+            }
+        }
+        warnings
+    }
+
+    /// New warnings among `tracks`: a pair warns once as a warning and once
+    /// more if it turns critical, and re-arms once it no longer conflicts
+    pub fn check(&self, tracks: &[ProjectedTrack]) -> Vec<CollisionWarning> {
+        let warnings = self.predict(tracks);
+        let conflicting: HashSet<_> = warnings.iter().map(|w| pair_key(&w.drone_id, &w.other_drone_id)).collect();
+        let mut warned = self.warned.write();
+        warned.retain(|pair, _| conflicting.contains(pair));
+        warnings
+            .into_iter()
+            .filter(|w| {
+                let pair = pair_key(&w.drone_id, &w.other_drone_id);
+                match warned.get(&pair) {
+                    Some(true) => false,
+                    Some(false) if !w.critical => false,
+                    _ => {
+                        warned.insert(pair, w.critical);
+                        true
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn warning(&self, a: &ProjectedTrack, b: &ProjectedTrack, approach: &Approach) -> CollisionWarning {
+        let config = &self.config;
+        let lookahead = config.lookahead.as_secs_f64();
+
+        // Split the missing vertical separation: the drone higher at CPA
+        // climbs, the other descends
+        let a_alt = a.position.altitude + a.up_ms * approach.time_s;
+        let b_alt = b.position.altitude + b.up_ms * approach.time_s;
+        let half = ((config.vertical_separation_m - approach.vertical_m) / 2.0 / ALTITUDE_STEP_M).ceil() * ALTITUDE_STEP_M;
+        let a_climbs = a_alt >= b_alt;
+
+        let turn = |own: &ProjectedTrack, other: &ProjectedTrack| {
+            if own.ground_speed() < MIN_TURN_SPEED_MS {
+                return None;
+            }
+            TURN_STEPS_DEG.into_iter().find(|deg| {
+                closest_approach(&own.turned(*deg), other, lookahead).horizontal_m >= config.horizontal_separation_m
+            })
+        };
+        let avoidance = |own: &ProjectedTrack, other: &ProjectedTrack, climbs: bool| {
+            let turn_deg = turn(own, other);
+            Avoidance {
+                drone_id: own.drone_id.clone(),
+                altitude_change_m: if climbs { half } else { -half },
+                heading_change_deg: turn_deg,
+                new_heading: turn_deg.map(|deg| (own.heading() + deg) % 360.0),
+            }
+        };
+
+        CollisionWarning {
+            drone_id: a.drone_id.clone(),
+            other_drone_id: b.drone_id.clone(),
+            time_to_cpa_secs: approach.time_s,
+            cpa_horizontal_m: approach.horizontal_m,
+            cpa_vertical_m: approach.vertical_m,
+            cpa_position: approach.position,
+            sources: [a.source, b.source],
+            avoidance: [avoidance(a, b, a_climbs), avoidance(b, a, !a_climbs)],
+            critical: approach.time_s < config.critical_within.as_secs_f64(),
+        }
+    }
+}
+
+fn pair_key(a: &DroneId, b: &DroneId) -> (DroneId, DroneId) {
+    if a.as_str() <= b.as_str() {
+        (a.clone(), b.clone())
+    } else {
+        (b.clone(), a.clone())
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_on_pair_warned_once_with_avoidance() {
+        let monitor = ProximityMonitor::new(ProximityConfig::default());
+        let origin = GeoPosition::new(34.5, 69.2, 3000.0);
+
+        // 4 km apart, closing head-on at 50 m/s each: CPA in 40 s, 20 m apart vertically
+        let north = ProjectedTrack::from_telemetry(DroneId::new("REAPER-01"), origin, 180.0, 0.0, 0.0);
+        let mut far = origin.offset_by_m(40.0, 4000.0);
+        far.altitude = 3020.0;
+        let south = ProjectedTrack::from_telemetry(DroneId::new("REAPER-02"), far, 180.0, 180.0, 0.0);
+
+        let warnings = monitor.check(&[north.clone(), south.clone()]);
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert!((warning.time_to_cpa_secs - 40.0).abs() < 0.5, "CPA in {} s", warning.time_to_cpa_secs);
+        assert!((warning.cpa_horizontal_m - 40.0).abs() < 1.0);
+        assert!((warning.cpa_vertical_m - 20.0).abs() < 1e-6);
+        assert!(!warning.critical);
+
+        // REAPER-02 is higher: it climbs and REAPER-01 descends 20 m each
+        let [first, second] = &warning.avoidance;
+        assert_eq!((first.altitude_change_m, second.altitude_change_m), (-20.0, 20.0));
+        assert!(first.heading_change_deg.is_some_and(|deg| deg > 0.0));
+        assert!(first.new_heading.is_some_and(|h| h > 0.0 && h <= 90.0));
+
+        // Warned once while the conflict lasts, re-armed once clear
+        assert!(monitor.check(&[north.clone(), south.clone()]).is_empty());
+        let climbed = ProjectedTrack {
+            position: GeoPosition::new(far.latitude, far.longitude, 3200.0),
+            ..south.clone()
+        };
+        assert!(monitor.check(&[north.clone(), climbed]).is_empty());
+        assert_eq!(monitor.check(&[north, south]).len(), 1);
+    }
+
+    #[test]
+    fn test_vertical_separation_checked_over_window() {
+        let monitor = ProximityMonitor::new(ProximityConfig::default());
+        let origin = GeoPosition::new(34.5, 69.2, 3000.0);
+
+        // Head-on as above, but REAPER-01 descends 20 m/s onto REAPER-02's
+        // altitude: 70 m apart vertically at the horizontal CPA, inside the
+        // minimum shortly after while still horizontally close
+        let north = ProjectedTrack::from_telemetry(DroneId::new("REAPER-01"), origin, 180.0, 0.0, -20.0);
+        let mut far = origin.offset_by_m(40.0, 4000.0);
+        far.altitude = 2130.0;
+        let south = ProjectedTrack::from_telemetry(DroneId::new("REAPER-02"), far, 180.0, 180.0, 0.0);
+
+        assert!(closest_approach(&north, &south, 120.0).vertical_m > 60.0);
+        let warnings = monitor.predict(&[north, south]);
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert!(warning.time_to_cpa_secs > 40.0 && warning.time_to_cpa_secs < 41.5);
+        assert!(warning.cpa_horizontal_m < 150.0 && warning.cpa_vertical_m <= 60.0 + 1e-6);
+
+        // Vertically clear for the whole time they are horizontally close
+        let level = ProjectedTrack::from_telemetry(DroneId::new("REAPER-01"), origin, 180.0, 0.0, 0.0);
+        let below = ProjectedTrack {
+            position: GeoPosition::new(far.latitude, far.longitude, 2900.0),
+            ..ProjectedTrack::from_telemetry(DroneId::new("REAPER-02"), far, 180.0, 180.0, 10.0)
+        };
+        assert!(monitor.predict(&[level, below]).is_empty());
+    }
+
+    #[test]
+    fn test_pair_warned_again_when_it_turns_critical() {
+        let monitor = ProximityMonitor::new(ProximityConfig::default());
+        let origin = GeoPosition::new(34.5, 69.2, 3000.0);
+        let north = ProjectedTrack::from_telemetry(DroneId::new("REAPER-01"), origin, 180.0, 0.0, 0.0);
+        let south_at = |north_m: f64| {
+            ProjectedTrack::from_telemetry(DroneId::new("REAPER-02"), origin.offset_by_m(40.0, north_m), 180.0, 180.0, 0.0)
+        };
+
+        // CPA in 40 s, then 20 s: one warning, one critical, then quiet
+        let first = monitor.check(&[north.clone(), south_at(4000.0)]);
+        assert!(first.len() == 1 && !first[0].critical);
+        assert!(monitor.check(&[north.clone(), south_at(3500.0)]).is_empty());
+        let second = monitor.check(&[north.clone(), south_at(2000.0)]);
+        assert!(second.len() == 1 && second[0].critical);
+        assert!(monitor.check(&[north.clone(), south_at(1500.0)]).is_empty());
+        assert!(monitor.check(&[north, south_at(3000.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_sweep_finds_every_brute_force_conflict() {
+        let config = ProximityConfig::default();
+        let monitor = ProximityMonitor::new(config.clone());
+        let origin = GeoPosition::new(34.5, 69.2, 3000.0);
+        let lookahead = config.lookahead.as_secs_f64();
+
+        // A dense block of drones on assorted headings and altitudes
+        let tracks: Vec<_> = (0..150)
+            .map(|i| {
+                let (east, north) = ((i % 15) as f64 * 700.0, (i / 15) as f64 * 700.0);
+                let mut position = origin.offset_by_m(east, north);
+                position.altitude = 2900.0 + (i * 37 % 200) as f64;
+                let heading = (i * 97 % 360) as f64;
+                let speed = 60.0 + (i * 53 % 150) as f64;
+                ProjectedTrack::from_telemetry(DroneId::new(format!("D-{:03}", i)), position, speed, heading, 0.0)
+            })
+            .collect();
+
+        let mut expected = HashSet::new();
+        for (i, a) in tracks.iter().enumerate() {
+            for b in &tracks[i + 1..] {
+                if conflict(a, b, lookahead, config.horizontal_separation_m, config.vertical_separation_m).is_some() {
+                    expected.insert(pair_key(&a.drone_id, &b.drone_id));
+                }
+            }
+        }
+        let found: HashSet<_> = monitor
+            .predict(&tracks)
+            .iter()
+            .map(|w| pair_key(&w.drone_id, &w.other_drone_id))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_cv_track_velocity_converges() {
+        let monitor = ProximityMonitor::new(ProximityConfig::default());
+        let drone_id = DroneId::new("GHOST-01");
+        let start = GeoPosition::new(34.5, 69.2, 1500.0);
+        let t0 = Utc::now();
+
+        // Eastbound at 30 m/s, estimates every 200 ms with a few meters of jitter
+        for i in 0..50 {
+            let jitter = if i % 2 == 0 { 3.0 } else { -3.0 };
+            let position = start.offset_by_m(30.0 * i as f64 * 0.2 + jitter, jitter);
+            monitor.record_cv(&drone_id, position, 5.0, t0 + chrono::Duration::milliseconds(200 * i));
+        }
+
+        let now = t0 + chrono::Duration::milliseconds(200 * 49);
+        let track = monitor.cv_track(&drone_id, now).unwrap();
+        assert_eq!(track.source, VelocitySource::CvKalman);
+        assert!((track.east_ms - 30.0).abs() < 2.0, "east {} m/s", track.east_ms);
+        assert!(track.north_ms.abs() < 2.0);
+        assert_eq!(monitor.cv_only_tracks(&HashSet::new(), now).len(), 1);
+        assert!(monitor.cv_track(&drone_id, now + chrono::Duration::seconds(10)).is_none());
+    }
+}
@@ -0,0 +1,330 @@
+//! Telemetry data quality monitoring
+//!
+//! Tracks the interval between telemetry updates for every drone in a
+//! mission. An interval longer than the gap threshold is recorded as a gap;
+//! the per-mission report gives each drone's completeness (share of the
+//! mission window not covered by gaps) for after-action review.
+
+use drone_core::{DroneId, MissionId};
+use drone_db::TelemetryGapRecord;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Data quality monitoring configuration
+#[derive(Debug, Clone)]
+pub struct DataQualityConfig {
+    /// Update intervals longer than this are recorded as gaps
+    pub gap_threshold: Duration,
+    /// Missions kept in memory (oldest dropped first)
+    pub max_missions: usize,
+}
+
+impl Default for DataQualityConfig {
+    fn default() -> Self {
+        Self {
+            gap_threshold: Duration::from_secs(5),
+            max_missions: 16,
+        }
+    }
+}
+
+/// A period without telemetry from one drone
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TelemetryGap {
+    pub drone_id: DroneId,
+    pub mission_id: MissionId,
+    /// Last update before the gap (or the mission window start)
+    pub start: DateTime<Utc>,
+    /// First update after the gap (or now, while still open)
+    pub end: DateTime<Utc>,
+    pub duration_seconds: f64,
+    /// No update has arrived yet to close the gap
+    pub ongoing: bool,
+}
+
+impl TelemetryGap {
+    fn new(drone_id: &DroneId, mission_id: &MissionId, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            drone_id: drone_id.clone(),
+            mission_id: mission_id.clone(),
+            start,
+            end,
+            duration_seconds: seconds_between(start, end),
+            ongoing: false,
+        }
+    }
+}
+
+impl From<&TelemetryGap> for TelemetryGapRecord {
+    fn from(gap: &TelemetryGap) -> Self {
+        Self {
+            mission_id: gap.mission_id.0,
+            drone_id: gap.drone_id.as_str().to_string(),
+            gap_start: gap.start,
+            gap_end: gap.end,
+            duration_ms: (gap.duration_seconds * 1000.0).round() as i64,
+        }
+    }
+}
+
+/// Data quality summary for one drone
+#[derive(Debug, Clone, Serialize)]
+pub struct DroneDataQuality {
+    pub drone_id: DroneId,
+    pub updates: u64,
+    pub first_update: DateTime<Utc>,
+    pub last_update: DateTime<Utc>,
+    pub mean_interval_seconds: Option<f64>,
+    pub max_interval_seconds: f64,
+    pub gap_count: usize,
+    pub gap_seconds: f64,
+    /// Share of the mission window with telemetry (0-100)
+    pub completeness_pct: f64,
+}
+
+/// Per-mission data quality report
+#[derive(Debug, Clone, Serialize)]
+pub struct DataQualityReport {
+    pub mission_id: MissionId,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub gap_threshold_seconds: f64,
+    /// Mean completeness across drones (0-100)
+    pub completeness_pct: f64,
+    pub drones: Vec<DroneDataQuality>,
+    /// All gaps, oldest first
+    pub gaps: Vec<TelemetryGap>,
+}
+
+#[derive(Debug)]
+struct DroneStats {
+    first_update: DateTime<Utc>,
+    last_update: DateTime<Utc>,
+    updates: u64,
+    max_interval: f64,
+}
+
+#[derive(Debug)]
+struct MissionQuality {
+    mission_id: MissionId,
+    /// First update seen for the mission
+    started_at: DateTime<Utc>,
+    drones: HashMap<DroneId, DroneStats>,
+    gaps: Vec<TelemetryGap>,
+}
+
+/// Per-drone update interval and gap tracking
+#[derive(Debug, Default)]
+pub struct DataQualityMonitor {
+    config: DataQualityConfig,
+    /// Missions by first update, most recent last
+    missions: RwLock<VecDeque<MissionQuality>>,
+}
+
+impl DataQualityMonitor {
+    pub fn new(config: DataQualityConfig) -> Self {
+        Self {
+            config,
+            missions: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    fn threshold_seconds(&self) -> f64 {
+        self.config.gap_threshold.as_secs_f64()
+    }
+
+    /// Record a telemetry update reported at `at`, returning the gap it
+    /// closed (if any)
+    ///
+    /// A report older than the drone's latest one is counted but opens or
+    /// closes no gap.
+    pub fn record_update(
+        &self,
+        drone_id: &DroneId,
+        mission_id: &MissionId,
+        at: DateTime<Utc>,
+    ) -> Option<TelemetryGap> {
+        let threshold = self.threshold_seconds();
+        let mut missions = self.missions.write();
+
+        if !missions.iter().any(|m| &m.mission_id == mission_id) {
+            missions.push_back(MissionQuality {
+                mission_id: mission_id.clone(),
+                started_at: at,
+                drones: HashMap::new(),
+                gaps: Vec::new(),
+            });
+            while missions.len() > self.config.max_missions.max(1) {
+                missions.pop_front();
+            }
+        }
+        let mission = missions.iter_mut().find(|m| &m.mission_id == mission_id)?;
+
+        let gap_start = match mission.drones.get_mut(drone_id) {
+            Some(stats) => {
+                stats.updates += 1;
+                if at < stats.last_update {
+                    return None;
+                }
+                let interval = seconds_between(stats.last_update, at);
+                stats.max_interval = stats.max_interval.max(interval);
+                let last = std::mem::replace(&mut stats.last_update, at);
+                (interval > threshold).then_some(last)
+            }
+            None => {
+                mission.drones.insert(
+                    drone_id.clone(),
+                    DroneStats {
+                        first_update: at,
+                        last_update: at,
+                        updates: 1,
+                        max_interval: 0.0,
+                    },
+                );
+                // A drone that reports late has a gap from the mission start
+                (seconds_between(mission.started_at, at) > threshold).then_some(mission.started_at)
+            }
+        };
+
+        let gap = TelemetryGap::new(drone_id, mission_id, gap_start?, at);
+        mission.gaps.push(gap.clone());
+        Some(gap)
+    }
+
+    /// Data quality report for a mission
+    ///
+    /// The most recent mission's window runs until `now`, so drones that have
+    /// gone quiet show an ongoing gap; older missions end at their last update.
+    pub fn report(&self, mission_id: &MissionId, now: DateTime<Utc>) -> Option<DataQualityReport> {
+        let threshold = self.threshold_seconds();
+        let missions = self.missions.read();
+        let is_current = missions.back().is_some_and(|m| &m.mission_id == mission_id);
+        let mission = missions.iter().find(|m| &m.mission_id == mission_id)?;
+
+        let last_update = mission
+            .drones
+            .values()
+            .map(|s| s.last_update)
+            .max()
+            .unwrap_or(mission.started_at);
+        let window_end = if is_current { now.max(last_update) } else { last_update };
+        let window_seconds = seconds_between(mission.started_at, window_end);
+
+        let mut gaps = mission.gaps.clone();
+        let mut drones: Vec<DroneDataQuality> = mission
+            .drones
+            .iter()
+            .map(|(drone_id, stats)| {
+                let mut drone_gaps: Vec<_> = mission.gaps.iter().filter(|g| &g.drone_id == drone_id).collect();
+                let open = (seconds_between(stats.last_update, window_end) > threshold).then(|| TelemetryGap {
+                    ongoing: true,
+                    ..TelemetryGap::new(drone_id, mission_id, stats.last_update, window_end)
+                });
+                drone_gaps.extend(open.as_ref());
+
+                let gap_seconds: f64 = drone_gaps.iter().map(|g| g.duration_seconds).sum();
+                let completeness_pct = if window_seconds > 0.0 {
+                    (100.0 * (1.0 - gap_seconds / window_seconds)).clamp(0.0, 100.0)
+                } else {
+                    100.0
+                };
+                let quality = DroneDataQuality {
+                    drone_id: drone_id.clone(),
+                    updates: stats.updates,
+                    first_update: stats.first_update,
+                    last_update: stats.last_update,
+                    mean_interval_seconds: (stats.updates > 1).then(|| {
+                        seconds_between(stats.first_update, stats.last_update) / (stats.updates - 1) as f64
+                    }),
+                    max_interval_seconds: stats.max_interval,
+                    gap_count: drone_gaps.len(),
+                    gap_seconds,
+                    completeness_pct,
+                };
+                gaps.extend(open);
+                quality
+            })
+            .collect();
+
+        drones.sort_by(|a, b| a.drone_id.as_str().cmp(b.drone_id.as_str()));
+        gaps.sort_by_key(|g| g.start);
+        let completeness_pct = if drones.is_empty() {
+            100.0
+        } else {
+            drones.iter().map(|d| d.completeness_pct).sum::<f64>() / drones.len() as f64
+        };
+
+        Some(DataQualityReport {
+            mission_id: mission_id.clone(),
+            window_start: mission.started_at,
+            window_end,
+            gap_threshold_seconds: threshold,
+            completeness_pct,
+            drones,
+            gaps,
+        })
+    }
+}
+
+fn seconds_between(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_milliseconds().max(0) as f64 / 1000.0
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_gaps_and_completeness() {
+        let monitor = DataQualityMonitor::new(DataQualityConfig::default());
+        let mission = MissionId::new();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let reaper_1 = DroneId::new("REAPER-01");
+        let reaper_2 = DroneId::new("REAPER-02");
+
+        // REAPER-01 reports every second except for a 20 s dropout
+        for s in (0..=30).chain(50..=100) {
+            let gap = monitor.record_update(&reaper_1, &mission, at(s));
+            assert_eq!(gap.is_some(), s == 50);
+        }
+        // REAPER-02 joins 10 s late and goes quiet at 80 s
+        let late = monitor.record_update(&reaper_2, &mission, at(10)).unwrap();
+        assert_eq!(late.start, start);
+        for s in 11..=80 {
+            assert!(monitor.record_update(&reaper_2, &mission, at(s)).is_none());
+        }
+        // A report delivered late does not move the drone back in time
+        assert!(monitor.record_update(&reaper_2, &mission, at(40)).is_none());
+
+        let report = monitor.report(&mission, at(100)).unwrap();
+        assert_eq!(report.window_end, at(100));
+        let d1 = &report.drones[0];
+        assert_eq!(d1.gap_count, 1);
+        assert_eq!(d1.max_interval_seconds, 20.0);
+        assert!((d1.completeness_pct - 80.0).abs() < 1e-9);
+
+        // 10 s leading gap plus a 20 s gap still open at the end of the window
+        let d2 = &report.drones[1];
+        assert_eq!(d2.gap_count, 2);
+        assert!((d2.completeness_pct - 70.0).abs() < 1e-9);
+        assert_eq!(report.gaps.len(), 3);
+        assert!(report.gaps.last().unwrap().ongoing);
+        assert!((report.completeness_pct - 75.0).abs() < 1e-9);
+
+        // Once another mission starts, the old window ends at its last update
+        monitor.record_update(&reaper_1, &MissionId::new(), at(200));
+        let report = monitor.report(&mission, at(300)).unwrap();
+        assert_eq!(report.window_end, at(100));
+        assert!(monitor.report(&MissionId::new(), at(300)).is_none());
+    }
+}
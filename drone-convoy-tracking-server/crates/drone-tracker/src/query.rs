@@ -0,0 +1,163 @@
+//! Tracked drone queries
+//!
+//! Composable predicates over status, telemetry ranges, geographic area and
+//! mission assignment, evaluated against the tracker's live drone state so
+//! clients can ask for a subset of the fleet instead of filtering it
+//! themselves.
+
+use drone_core::{Drone, DroneStatus, GeoBounds, GeoPosition, Mission, MissionId, TelemetryField};
+
+/// A condition on a single drone
+#[derive(Debug, Clone)]
+pub enum DronePredicate {
+    /// Status is one of these
+    Status(Vec<DroneStatus>),
+    /// Telemetry value within `[min, max]` (either bound optional)
+    Telemetry {
+        field: TelemetryField,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// Position inside a bounding box
+    Within(GeoBounds),
+    /// Position within `radius_km` of `center` (great-circle distance)
+    Near { center: GeoPosition, radius_km: f64 },
+    /// Assigned to a mission
+    Mission(MissionId),
+    /// All of the predicates hold
+    All(Vec<DronePredicate>),
+    /// At least one of the predicates holds
+    Any(Vec<DronePredicate>),
+    Not(Box<DronePredicate>),
+}
+
+impl DronePredicate {
+    /// Evaluate against a drone; `mission` is the active mission, whose
+    /// assignment list also counts for [`DronePredicate::Mission`]
+    pub fn matches(&self, drone: &Drone, mission: Option<&Mission>) -> bool {
+        match self {
+            Self::Status(statuses) => statuses.contains(&drone.status),
+            Self::Telemetry { field, min, max } => telemetry_value(drone, *field).is_some_and(|value| {
+                min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+            }),
+            Self::Within(bounds) => bounds.contains(&drone.position),
+            Self::Near { center, radius_km } => drone.position.distance_to(center) <= *radius_km,
+            Self::Mission(mission_id) => {
+                drone.mission_id == Some(mission_id.0)
+                    || mission.is_some_and(|m| &m.id == mission_id && m.assigned_drones.contains(&drone.id))
+            }
+            Self::All(predicates) => predicates.iter().all(|p| p.matches(drone, mission)),
+            Self::Any(predicates) => predicates.iter().any(|p| p.matches(drone, mission)),
+            Self::Not(predicate) => !predicate.matches(drone, mission),
+        }
+    }
+}
+
+/// Conjunction of predicates; an empty query matches every drone
+#[derive(Debug, Clone, Default)]
+pub struct DroneQuery {
+    predicates: Vec<DronePredicate>,
+}
+
+impl DroneQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn filter(mut self, predicate: DronePredicate) -> Self {
+        self.predicates.push(predicate);
+        self
+    }
+
+    pub fn status(self, statuses: impl IntoIterator<Item = DroneStatus>) -> Self {
+        self.filter(DronePredicate::Status(statuses.into_iter().collect()))
+    }
+
+    pub fn telemetry(self, field: TelemetryField, min: Option<f64>, max: Option<f64>) -> Self {
+        self.filter(DronePredicate::Telemetry { field, min, max })
+    }
+
+    pub fn within(self, bounds: GeoBounds) -> Self {
+        self.filter(DronePredicate::Within(bounds))
+    }
+
+    pub fn near(self, center: GeoPosition, radius_km: f64) -> Self {
+        self.filter(DronePredicate::Near { center, radius_km })
+    }
+
+    pub fn mission(self, mission_id: MissionId) -> Self {
+        self.filter(DronePredicate::Mission(mission_id))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+
+    pub fn matches(&self, drone: &Drone, mission: Option<&Mission>) -> bool {
+        self.predicates.iter().all(|p| p.matches(drone, mission))
+    }
+}
+
+fn telemetry_value(drone: &Drone, field: TelemetryField) -> Option<f64> {
+    let telemetry = &drone.telemetry;
+    match field {
+        TelemetryField::BatteryLevel => Some(telemetry.battery_level as f64),
+        TelemetryField::FuelLevel => Some(telemetry.fuel_level as f64),
+        TelemetryField::SystemHealth => Some(telemetry.system_health as f64),
+        TelemetryField::SignalStrength => Some(telemetry.signal_strength as f64),
+        TelemetryField::Speed => Some(telemetry.speed),
+        TelemetryField::Heading => Some(telemetry.heading),
+        TelemetryField::Temperature => Some(telemetry.temperature),
+        TelemetryField::Timestamp => Some(telemetry.timestamp.timestamp() as f64),
+        TelemetryField::Position => None,
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::DroneId;
+
+    fn drone(id: &str, status: DroneStatus, battery: u8, lat: f64) -> Drone {
+        let mut drone = Drone::new(DroneId::new(id), id);
+        drone.status = status;
+        drone.telemetry.battery_level = battery;
+        drone.position = GeoPosition::new(lat, 69.20, 3000.0);
+        drone
+    }
+
+    #[test]
+    fn test_composed_predicates() {
+        let mut mission = Mission::new("Query Test");
+        mission.assign_drone(DroneId::new("REAPER-01"));
+        let reaper_1 = drone("REAPER-01", DroneStatus::Moving, 80, 34.50);
+        let reaper_2 = drone("REAPER-02", DroneStatus::Rtb, 25, 34.90);
+
+        let low_battery = DroneQuery::new()
+            .status([DroneStatus::Moving, DroneStatus::Rtb])
+            .telemetry(TelemetryField::BatteryLevel, None, Some(30.0));
+        assert!(!low_battery.matches(&reaper_1, Some(&mission)));
+        assert!(low_battery.matches(&reaper_2, Some(&mission)));
+
+        // REAPER-02 is ~44 km north of the center
+        let near = DroneQuery::new().near(GeoPosition::new(34.50, 69.20, 0.0), 10.0);
+        assert!(near.matches(&reaper_1, None));
+        assert!(!near.matches(&reaper_2, None));
+
+        let assigned = DroneQuery::new().mission(mission.id.clone());
+        assert!(assigned.matches(&reaper_1, Some(&mission)));
+        assert!(!assigned.matches(&reaper_2, Some(&mission)));
+
+        let either = DronePredicate::Any(vec![
+            DronePredicate::Within(GeoBounds::new(34.8, 35.0, 69.0, 69.4)),
+            DronePredicate::Not(Box::new(DronePredicate::Status(vec![DroneStatus::Moving]))),
+        ]);
+        assert!(!either.matches(&reaper_1, None));
+        assert!(either.matches(&reaper_2, None));
+        assert!(DroneQuery::new().matches(&reaper_1, None));
+    }
+}
@@ -0,0 +1,434 @@
+//! Conditional alert rules
+//!
+//! Threshold checks look at one value at a time. A rule combines conditions
+//! over telemetry, position and mission progress into a tree of `all`,
+//! `any` and `not` nodes, for example "battery below 40% and more than
+//! 50 km from base". Rules are evaluated on every position update and raise
+//! their alert when the condition becomes true for a drone; the rule fires
+//! again for that drone only after the condition has been false.
+
+use drone_core::{Alert, AlertSeverity, AlertType, DerivedMotion, Drone, DroneId, DroneStatus, Mission};
+use drone_db::AlertRuleRecord;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use uuid::Uuid;
+
+/// Alert type of rule alerts; the message names the rule
+pub const RULE_ALERT_TYPE: &str = "ALERT_RULE";
+
+/// Deepest nesting of `all`/`any`/`not` nodes
+pub const MAX_CONDITION_DEPTH: usize = 8;
+
+/// Most nodes in one condition tree
+pub const MAX_CONDITION_NODES: usize = 64;
+
+/// Value a comparison reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleField {
+    BatteryLevel,
+    FuelLevel,
+    SystemHealth,
+    SignalStrength,
+    Temperature,
+    /// km/h
+    Speed,
+    Heading,
+    /// Meters
+    Altitude,
+    Latitude,
+    Longitude,
+    /// To the mission's first waypoint
+    DistanceToBaseKm,
+    DistanceToNextWaypointKm,
+    /// Waypoints passed, in percent of the route
+    MissionProgress,
+    /// Low-pass filtered course over ground (degrees)
+    SmoothedHeading,
+    /// m/s, negative when descending
+    ClimbRate,
+    /// m/s²
+    Acceleration,
+    /// deg/s, negative when turning left
+    TurnRate,
+}
+
+impl RuleField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BatteryLevel => "battery_level",
+            Self::FuelLevel => "fuel_level",
+            Self::SystemHealth => "system_health",
+            Self::SignalStrength => "signal_strength",
+            Self::Temperature => "temperature",
+            Self::Speed => "speed",
+            Self::Heading => "heading",
+            Self::Altitude => "altitude",
+            Self::Latitude => "latitude",
+            Self::Longitude => "longitude",
+            Self::DistanceToBaseKm => "distance_to_base_km",
+            Self::DistanceToNextWaypointKm => "distance_to_next_waypoint_km",
+            Self::MissionProgress => "mission_progress",
+            Self::SmoothedHeading => "smoothed_heading",
+            Self::ClimbRate => "climb_rate",
+            Self::Acceleration => "acceleration",
+            Self::TurnRate => "turn_rate",
+        }
+    }
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl RuleOp {
+    fn holds(&self, left: f64, right: f64) -> bool {
+        match self {
+            Self::Lt => left < right,
+            Self::Le => left <= right,
+            Self::Gt => left > right,
+            Self::Ge => left >= right,
+            Self::Eq => left == right,
+            Self::Ne => left != right,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        }
+    }
+}
+
+/// Condition tree
+///
+/// JSON: `{"all": [...]}`, `{"any": [...]}`, `{"not": {...}}`,
+/// `{"field": "battery_level", "op": "lt", "value": 40}` or
+/// `{"status": "MOVING"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Condition {
+    All { all: Vec<Condition> },
+    Any { any: Vec<Condition> },
+    Not { not: Box<Condition> },
+    Compare { field: RuleField, op: RuleOp, value: f64 },
+    Status { status: DroneStatus },
+}
+
+impl Condition {
+    /// True if the drone matches; comparisons on values that do not exist
+    /// (no mission, no next waypoint) are false
+    pub fn matches(&self, facts: &RuleFacts) -> bool {
+        match self {
+            Self::All { all } => all.iter().all(|c| c.matches(facts)),
+            Self::Any { any } => any.iter().any(|c| c.matches(facts)),
+            Self::Not { not } => !not.matches(facts),
+            Self::Compare { field, op, value } => facts.value(*field).is_some_and(|v| op.holds(v, *value)),
+            Self::Status { status } => facts.drone.status == *status,
+        }
+    }
+
+    /// Check the tree's shape; the error names the problem
+    pub fn check(&self) -> Result<(), String> {
+        let mut nodes = 0;
+        self.check_node(1, &mut nodes)
+    }
+
+    fn check_node(&self, depth: usize, nodes: &mut usize) -> Result<(), String> {
+        *nodes += 1;
+        if *nodes > MAX_CONDITION_NODES {
+            return Err(format!("must have at most {} nodes", MAX_CONDITION_NODES));
+        }
+        if depth > MAX_CONDITION_DEPTH {
+            return Err(format!("must nest at most {} levels", MAX_CONDITION_DEPTH));
+        }
+        match self {
+            Self::All { all: children } | Self::Any { any: children } => {
+                if children.is_empty() {
+                    return Err("all/any must list at least one condition".into());
+                }
+                children.iter().try_for_each(|c| c.check_node(depth + 1, nodes))
+            }
+            Self::Not { not } => not.check_node(depth + 1, nodes),
+            Self::Compare { value, .. } if !value.is_finite() => Err("comparison value must be finite".into()),
+            Self::Compare { .. } | Self::Status { .. } => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, children: &[Condition], op: &str| {
+            f.write_str("(")?;
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                write!(f, "{}", child)?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            Self::All { all } => join(f, all, "AND"),
+            Self::Any { any } => join(f, any, "OR"),
+            Self::Not { not } => write!(f, "NOT {}", not),
+            Self::Compare { field, op, value } => write!(f, "{} {} {}", field.as_str(), op.symbol(), value),
+            Self::Status { status } => write!(f, "status == {:?}", status),
+        }
+    }
+}
+
+/// What a rule is evaluated against: one drone and the active mission
+pub struct RuleFacts<'a> {
+    pub drone: &'a Drone,
+    pub mission: Option<&'a Mission>,
+    /// Index of the waypoint the drone is heading to
+    pub waypoint_index: usize,
+    /// Derived motion, once the drone has reported
+    pub motion: Option<DerivedMotion>,
+}
+
+impl RuleFacts<'_> {
+    pub fn value(&self, field: RuleField) -> Option<f64> {
+        let telemetry = &self.drone.telemetry;
+        let position = &self.drone.position;
+        let waypoints = self.mission.map(|m| m.waypoints.as_slice()).unwrap_or_default();
+        Some(match field {
+            RuleField::BatteryLevel => telemetry.battery_level as f64,
+            RuleField::FuelLevel => telemetry.fuel_level as f64,
+            RuleField::SystemHealth => telemetry.system_health as f64,
+            RuleField::SignalStrength => telemetry.signal_strength as f64,
+            RuleField::Temperature => telemetry.temperature,
+            RuleField::Speed => telemetry.speed,
+            RuleField::Heading => telemetry.heading,
+            RuleField::Altitude => position.altitude,
+            RuleField::Latitude => position.latitude,
+            RuleField::Longitude => position.longitude,
+            RuleField::DistanceToBaseKm => position.distance_to(&waypoints.first()?.position),
+            RuleField::DistanceToNextWaypointKm => {
+                position.distance_to(&waypoints.get(self.waypoint_index)?.position)
+            }
+            RuleField::MissionProgress => {
+                if waypoints.is_empty() {
+                    return None;
+                }
+                (self.waypoint_index.min(waypoints.len()) as f64 / waypoints.len() as f64) * 100.0
+            }
+            RuleField::SmoothedHeading => self.motion?.smoothed_heading,
+            RuleField::ClimbRate => self.motion?.climb_rate,
+            RuleField::Acceleration => self.motion?.acceleration,
+            RuleField::TurnRate => self.motion?.turn_rate,
+        })
+    }
+}
+
+/// A named condition and the alert it raises
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertRule {
+    pub id: Uuid,
+    pub name: String,
+    pub condition: Condition,
+    pub severity: AlertSeverity,
+    /// Alert message (defaults to the rule name and condition)
+    pub message: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AlertRule {
+    pub fn new(name: impl Into<String>, condition: Condition, severity: AlertSeverity, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            condition,
+            severity,
+            message: None,
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Alert for a drone that just matched
+    pub fn alert(&self, drone_id: &DroneId) -> Alert {
+        let message = self
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("Rule {}: {}", self.name, self.condition));
+        Alert::new(self.severity, AlertType::Custom(RULE_ALERT_TYPE.into()), message).for_drone(drone_id.clone())
+    }
+
+    pub fn to_record(&self) -> AlertRuleRecord {
+        AlertRuleRecord {
+            id: self.id,
+            name: self.name.clone(),
+            condition: serde_json::to_string(&self.condition).unwrap_or_default(),
+            severity: serde_json::to_value(self.severity)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            message: self.message.clone(),
+            enabled: self.enabled,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub fn from_record(record: &AlertRuleRecord) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: record.id,
+            name: record.name.clone(),
+            condition: serde_json::from_str(&record.condition)?,
+            severity: serde_json::from_value(record.severity.clone().into())?,
+            message: record.message.clone(),
+            enabled: record.enabled,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        })
+    }
+}
+
+/// Rule set and which drones each rule is currently firing for
+#[derive(Debug, Default)]
+pub struct RuleEngine {
+    rules: RwLock<Vec<AlertRule>>,
+    firing: RwLock<HashSet<(Uuid, DroneId)>>,
+}
+
+impl RuleEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule, or replace the rule with the same ID
+    pub fn upsert(&self, rule: AlertRule) {
+        let mut rules = self.rules.write();
+        match rules.iter_mut().find(|r| r.id == rule.id) {
+            Some(existing) => *existing = rule,
+            None => rules.push(rule),
+        }
+    }
+
+    pub fn remove(&self, id: &Uuid) -> Option<AlertRule> {
+        let mut rules = self.rules.write();
+        let index = rules.iter().position(|r| r.id == *id)?;
+        self.firing.write().retain(|(rule_id, _)| rule_id != id);
+        Some(rules.remove(index))
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<AlertRule> {
+        self.rules.read().iter().find(|r| r.id == *id).cloned()
+    }
+
+    /// All rules, oldest first
+    pub fn list(&self) -> Vec<AlertRule> {
+        self.rules.read().clone()
+    }
+
+    /// Replace the rule set with persisted rules
+    pub fn restore(&self, rules: Vec<AlertRule>) {
+        *self.rules.write() = rules;
+        self.firing.write().clear();
+    }
+
+    /// Alerts of the enabled rules that started matching the drone
+    pub fn evaluate(&self, facts: &RuleFacts) -> Vec<Alert> {
+        let drone_id = &facts.drone.id;
+        let rules = self.rules.read();
+        let mut firing = self.firing.write();
+        let mut alerts = Vec::new();
+        for rule in rules.iter() {
+            let key = (rule.id, drone_id.clone());
+            if rule.enabled && rule.condition.matches(facts) {
+                if firing.insert(key) {
+                    alerts.push(rule.alert(drone_id));
+                }
+            } else {
+                firing.remove(&key);
+            }
+        }
+        alerts
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{GeoPosition, Waypoint};
+
+    #[test]
+    fn test_rule_fires_once_while_condition_holds() {
+        let condition: Condition = serde_json::from_str(
+            r#"{"all": [
+                {"field": "battery_level", "op": "lt", "value": 40},
+                {"any": [
+                    {"field": "distance_to_base_km", "op": "gt", "value": 50},
+                    {"not": {"status": "MOVING"}}
+                ]}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(condition.check().is_ok());
+        assert_eq!(
+            condition.to_string(),
+            "(battery_level < 40 AND (distance_to_base_km > 50 OR NOT status == Moving))"
+        );
+
+        let engine = RuleEngine::new();
+        let rule = AlertRule::new("Low battery far out", condition, AlertSeverity::Critical, Utc::now());
+        engine.upsert(AlertRule::from_record(&rule.to_record()).unwrap());
+
+        let mut mission = Mission::new("Route");
+        mission.add_waypoint(Waypoint::new("WP01", "Base Alpha", 34.5553, 69.2075));
+        let mut drone = Drone::new("REAPER-01", "Reaper 1");
+        drone.status = DroneStatus::Moving;
+        drone.telemetry.battery_level = 35;
+        // About 67 km north of base
+        drone.position = GeoPosition::new(35.16, 69.2075, 3000.0);
+
+        let facts = RuleFacts { drone: &drone, mission: Some(&mission), waypoint_index: 0, motion: None };
+        let alerts = engine.evaluate(&facts);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert!(engine.evaluate(&facts).is_empty());
+
+        // Without a mission there is no base; the drone is still moving
+        assert!(engine.evaluate(&RuleFacts { drone: &drone, mission: None, waypoint_index: 0, motion: None }).is_empty());
+        assert_eq!(engine.evaluate(&facts).len(), 1);
+
+        // Derived motion fields only match once the drone has reported
+        let descending: Condition = serde_json::from_str(r#"{"field": "climb_rate", "op": "lt", "value": -10}"#).unwrap();
+        let motion = DerivedMotion { climb_rate: -15.0, ..Default::default() };
+        assert!(!descending.matches(&RuleFacts { motion: None, ..facts }));
+        assert!(descending.matches(&RuleFacts { motion: Some(motion), ..facts }));
+
+        let mut invalid: Condition = serde_json::from_str(r#"{"any": []}"#).unwrap();
+        assert!(invalid.check().is_err());
+        for _ in 0..MAX_CONDITION_DEPTH {
+            invalid = Condition::Not { not: Box::new(invalid) };
+        }
+        assert!(invalid.check().unwrap_err().contains("nest"));
+    }
+}
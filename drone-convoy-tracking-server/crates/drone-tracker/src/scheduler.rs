@@ -0,0 +1,298 @@
+//! Scheduled commands
+//!
+//! Operators queue commands to run later: at a point in (simulated) time,
+//! e.g. "RTB at 14:00Z", or when a drone reaches a waypoint, e.g. "switch to
+//! Vee formation at WP06". Each command fires at most once; fired and
+//! cancelled commands are kept for the listing and persisted so the queue
+//! survives a restart.
+
+use crate::convoy::Formation;
+
+use drone_core::{DroneCommandType, DroneId, WaypointId};
+use drone_db::ScheduledCommandRecord;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// When a scheduled command fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CommandTrigger {
+    /// At a simulated time
+    At { at: DateTime<Utc> },
+    /// When a drone reaches a waypoint (any drone if `drone_id` is unset)
+    Waypoint {
+        waypoint_id: WaypointId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        drone_id: Option<DroneId>,
+    },
+}
+
+impl CommandTrigger {
+    fn describe(&self) -> String {
+        match self {
+            Self::At { at } => format!("at {}", at.format("%H:%M:%SZ")),
+            Self::Waypoint { waypoint_id, drone_id: Some(drone_id) } => {
+                format!("when {} reaches {}", drone_id, waypoint_id)
+            }
+            Self::Waypoint { waypoint_id, drone_id: None } => format!("at {}", waypoint_id),
+        }
+    }
+}
+
+/// What a scheduled command does when it fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledAction {
+    /// Send a command to one drone
+    DroneCommand {
+        drone_id: DroneId,
+        command: DroneCommandType,
+    },
+    /// Change the convoy formation
+    SetFormation { formation: Formation },
+}
+
+impl ScheduledAction {
+    /// Target drone (`None` for convoy-wide actions)
+    pub fn drone_id(&self) -> Option<&DroneId> {
+        match self {
+            Self::DroneCommand { drone_id, .. } => Some(drone_id),
+            Self::SetFormation { .. } => None,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::DroneCommand { drone_id, command } => format!("{:?} to {}", command, drone_id),
+            Self::SetFormation { formation } => format!("switch to {:?} formation", formation),
+        }
+    }
+}
+
+/// Lifecycle of a scheduled command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleState {
+    Pending,
+    Fired,
+    Cancelled,
+}
+
+impl ScheduleState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Fired => "fired",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A command waiting for (or past) its trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledCommand {
+    pub id: Uuid,
+    pub trigger: CommandTrigger,
+    pub action: ScheduledAction,
+    pub state: ScheduleState,
+    /// Operator note; defaults to a generated description
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    /// Simulated time the command fired or was cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledCommand {
+    pub fn new(
+        trigger: CommandTrigger,
+        action: ScheduledAction,
+        description: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let description = description
+            .unwrap_or_else(|| format!("{} {}", action.describe(), trigger.describe()));
+        Self {
+            id: Uuid::new_v4(),
+            trigger,
+            action,
+            state: ScheduleState::Pending,
+            description,
+            created_at: now,
+            settled_at: None,
+        }
+    }
+
+    fn settle(&mut self, state: ScheduleState, at: DateTime<Utc>) {
+        self.state = state;
+        self.settled_at = Some(at);
+    }
+
+    pub fn to_record(&self) -> ScheduledCommandRecord {
+        ScheduledCommandRecord {
+            id: self.id,
+            state: self.state.as_str().to_string(),
+            payload: serde_json::to_string(self).unwrap_or_default(),
+            created_at: self.created_at,
+            updated_at: self.settled_at.unwrap_or(self.created_at),
+        }
+    }
+
+    pub fn from_record(record: &ScheduledCommandRecord) -> serde_json::Result<Self> {
+        serde_json::from_str(&record.payload)
+    }
+}
+
+/// Pending and settled scheduled commands, oldest first
+#[derive(Debug, Default)]
+pub struct CommandScheduler {
+    commands: RwLock<Vec<ScheduledCommand>>,
+}
+
+impl CommandScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a command to the queue
+    pub fn schedule(&self, command: ScheduledCommand) {
+        self.commands.write().push(command);
+    }
+
+    /// Replace the queue with persisted commands
+    pub fn restore(&self, mut commands: Vec<ScheduledCommand>) {
+        commands.sort_by_key(|c| c.created_at);
+        *self.commands.write() = commands;
+    }
+
+    pub fn list(&self) -> Vec<ScheduledCommand> {
+        self.commands.read().clone()
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<ScheduledCommand> {
+        self.commands.read().iter().find(|c| &c.id == id).cloned()
+    }
+
+    /// Cancel a pending command; returns `None` if unknown or already settled
+    pub fn cancel(&self, id: &Uuid, now: DateTime<Utc>) -> Option<ScheduledCommand> {
+        let mut commands = self.commands.write();
+        let command = commands
+            .iter_mut()
+            .find(|c| &c.id == id && c.state == ScheduleState::Pending)?;
+        command.settle(ScheduleState::Cancelled, now);
+        Some(command.clone())
+    }
+
+    /// Mark time-triggered commands due at `now` as fired and return them
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<ScheduledCommand> {
+        self.fire_where(now, |trigger| matches!(trigger, CommandTrigger::At { at } if *at <= now))
+    }
+
+    /// Mark commands triggered by a drone reaching a waypoint as fired and return them
+    pub fn waypoint_reached(
+        &self,
+        drone_id: &DroneId,
+        waypoint_id: &WaypointId,
+        now: DateTime<Utc>,
+    ) -> Vec<ScheduledCommand> {
+        self.fire_where(now, |trigger| match trigger {
+            CommandTrigger::Waypoint { waypoint_id: wp, drone_id: target } => {
+                wp == waypoint_id && target.as_ref().is_none_or(|d| d == drone_id)
+            }
+            CommandTrigger::At { .. } => false,
+        })
+    }
+
+    fn fire_where(
+        &self,
+        now: DateTime<Utc>,
+        triggered: impl Fn(&CommandTrigger) -> bool,
+    ) -> Vec<ScheduledCommand> {
+        let mut commands = self.commands.write();
+        commands
+            .iter_mut()
+            .filter(|c| c.state == ScheduleState::Pending && triggered(&c.trigger))
+            .map(|c| {
+                c.settle(ScheduleState::Fired, now);
+                c.clone()
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_triggers_fire_once_and_cancel() {
+        let scheduler = CommandScheduler::new();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let reaper_1 = DroneId::new("REAPER-01");
+        let reaper_2 = DroneId::new("REAPER-02");
+        let wp06 = WaypointId::new("WP06");
+
+        let rtb = ScheduledCommand::new(
+            CommandTrigger::At { at: at(60) },
+            ScheduledAction::DroneCommand {
+                drone_id: reaper_1.clone(),
+                command: DroneCommandType::ReturnToBase,
+            },
+            None,
+            start,
+        );
+        let vee = ScheduledCommand::new(
+            CommandTrigger::Waypoint { waypoint_id: wp06.clone(), drone_id: Some(reaper_2.clone()) },
+            ScheduledAction::SetFormation { formation: Formation::Vee },
+            Some("Vee at WP06".into()),
+            start,
+        );
+        let spread = ScheduledCommand::new(
+            CommandTrigger::Waypoint { waypoint_id: wp06.clone(), drone_id: None },
+            ScheduledAction::SetFormation { formation: Formation::Spread },
+            None,
+            start,
+        );
+        assert_eq!(rtb.description, "ReturnToBase to REAPER-01 at 22:14:20Z");
+        let spread_id = spread.id;
+        scheduler.schedule(rtb);
+        scheduler.schedule(vee);
+        scheduler.schedule(spread);
+
+        assert!(scheduler.due(at(59)).is_empty());
+        let fired = scheduler.due(at(61));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].settled_at, Some(at(61)));
+        assert!(scheduler.due(at(120)).is_empty());
+
+        // Cancelled commands never fire; settled commands can't be cancelled
+        assert!(scheduler.cancel(&spread_id, at(70)).is_some());
+        assert!(scheduler.cancel(&spread_id, at(71)).is_none());
+
+        // Only the targeted drone triggers the Vee change
+        assert!(scheduler.waypoint_reached(&reaper_1, &wp06, at(80)).is_empty());
+        let fired = scheduler.waypoint_reached(&reaper_2, &wp06, at(90));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].description, "Vee at WP06");
+
+        // Persisted commands come back as they were
+        let restored = CommandScheduler::new();
+        let records: Vec<_> = scheduler.list().iter().map(ScheduledCommand::to_record).collect();
+        assert_eq!(records[2].state, "cancelled");
+        restored.restore(records.iter().map(|r| ScheduledCommand::from_record(r).unwrap()).collect());
+        let states: Vec<_> = restored.list().iter().map(|c| c.state).collect();
+        assert_eq!(
+            states,
+            [ScheduleState::Fired, ScheduleState::Fired, ScheduleState::Cancelled]
+        );
+    }
+}
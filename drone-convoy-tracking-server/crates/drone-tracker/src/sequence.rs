@@ -0,0 +1,221 @@
+//! Per-drone sequence numbers on position reports
+//!
+//! Gossip can deliver the same position report more than once. Reports
+//! carry a per-drone sequence number; the last `WINDOW` numbers seen are
+//! remembered so a repeat is recognized and dropped instead of processed
+//! twice. A jump ahead means reports were lost, and is counted as a gap.
+//! A report older than the newest one but not yet seen arrived too late to
+//! be applied without moving the drone backwards, and is dropped as late.
+//! A number at or below the newest one after `RESTART_SILENCE` without an
+//! accepted report means the sender restarted its counter; the size of the
+//! drop says nothing, as a drone may restart after only a few reports and a
+//! replay may be far behind.
+
+use chrono::{DateTime, Utc};
+use drone_core::DroneId;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Sequence numbers remembered per drone
+pub const WINDOW: u64 = 64;
+
+/// Silence after which a report numbered at or below the newest one starts
+/// a new sequence; a reboot takes longer, gossip redelivery less
+pub const RESTART_SILENCE: Duration = Duration::from_secs(10);
+
+/// What a report's sequence number says about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceCheck {
+    /// Newer than every report so far; `missing` reports were skipped
+    Fresh { missing: u64 },
+    /// Already seen
+    Duplicate,
+    /// Older than the newest report and not seen before, or too far
+    /// behind it to tell
+    Late,
+    /// Not newer than the newest report, after a silence; the sender
+    /// started counting again
+    Restarted,
+}
+
+impl SequenceCheck {
+    /// Whether the report should be processed
+    pub fn accepted(&self) -> bool {
+        matches!(self, Self::Fresh { .. } | Self::Restarted)
+    }
+}
+
+/// Sequence counters for one drone
+#[derive(Debug, Clone, Serialize)]
+pub struct DroneSequenceStats {
+    pub drone_id: DroneId,
+    /// Newest sequence number seen
+    pub last_sequence: u64,
+    pub duplicates: u64,
+    /// Jumps ahead in the sequence
+    pub gaps: u64,
+    /// Reports skipped by those jumps
+    pub missing: u64,
+    pub late: u64,
+    pub restarts: u64,
+}
+
+#[derive(Debug)]
+struct DroneWindow {
+    last: u64,
+    /// Bit `i` set = `last - i` seen
+    seen: u64,
+    /// When the last fresh or restarting report was observed
+    accepted_at: DateTime<Utc>,
+    stats: DroneSequenceStats,
+}
+
+/// Duplicate and gap detection for numbered position reports
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    drones: Mutex<HashMap<DroneId, DroneWindow>>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check and record `sequence` for `drone_id`, observed at `now`
+    pub fn observe(&self, drone_id: &DroneId, sequence: u64, now: DateTime<Utc>) -> SequenceCheck {
+        let mut drones = self.drones.lock();
+        let Some(window) = drones.get_mut(drone_id) else {
+            drones.insert(
+                drone_id.clone(),
+                DroneWindow {
+                    last: sequence,
+                    seen: 1,
+                    accepted_at: now,
+                    stats: DroneSequenceStats {
+                        drone_id: drone_id.clone(),
+                        last_sequence: sequence,
+                        duplicates: 0,
+                        gaps: 0,
+                        missing: 0,
+                        late: 0,
+                        restarts: 0,
+                    },
+                },
+            );
+            return SequenceCheck::Fresh { missing: 0 };
+        };
+
+        let check = if sequence > window.last {
+            let ahead = sequence - window.last;
+            window.seen = if ahead >= WINDOW { 1 } else { (window.seen << ahead) | 1 };
+            window.last = sequence;
+            window.accepted_at = now;
+            let missing = ahead - 1;
+            if missing > 0 {
+                window.stats.gaps += 1;
+                window.stats.missing += missing;
+            }
+            SequenceCheck::Fresh { missing }
+        } else if (now - window.accepted_at).to_std().is_ok_and(|silence| silence >= RESTART_SILENCE) {
+            window.last = sequence;
+            window.seen = 1;
+            window.accepted_at = now;
+            window.stats.restarts += 1;
+            SequenceCheck::Restarted
+        } else if window.last - sequence >= WINDOW {
+            window.stats.late += 1;
+            SequenceCheck::Late
+        } else {
+            let bit = 1u64 << (window.last - sequence);
+            if window.seen & bit != 0 {
+                window.stats.duplicates += 1;
+                SequenceCheck::Duplicate
+            } else {
+                window.seen |= bit;
+                window.stats.late += 1;
+                SequenceCheck::Late
+            }
+        };
+        window.stats.last_sequence = window.last;
+        check
+    }
+
+    /// Counters per drone, by drone ID
+    pub fn stats(&self) -> Vec<DroneSequenceStats> {
+        let mut stats: Vec<_> = self.drones.lock().values().map(|w| w.stats.clone()).collect();
+        stats.sort_by(|a, b| a.drone_id.0.cmp(&b.drone_id.0));
+        stats
+    }
+
+    /// Drop a drone's sequence window
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.drones.lock().remove(drone_id);
+    }
+
+    /// Drones with a sequence window
+    pub fn drone_count(&self) -> usize {
+        self.drones.lock().len()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates_gaps_late_and_restart() {
+        let sequences = SequenceTracker::new();
+        let id = DroneId::new("REAPER-01");
+        let t0 = Utc::now();
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+
+        assert_eq!(sequences.observe(&id, 1, at(0)), SequenceCheck::Fresh { missing: 0 });
+        assert_eq!(sequences.observe(&id, 2, at(1)), SequenceCheck::Fresh { missing: 0 });
+        assert_eq!(sequences.observe(&id, 2, at(1)), SequenceCheck::Duplicate);
+        assert_eq!(sequences.observe(&id, 6, at(2)), SequenceCheck::Fresh { missing: 3 });
+        assert_eq!(sequences.observe(&id, 4, at(2)), SequenceCheck::Late);
+        assert_eq!(sequences.observe(&id, 4, at(3)), SequenceCheck::Duplicate);
+        assert_eq!(sequences.observe(&id, 1, at(3)), SequenceCheck::Duplicate);
+        assert_eq!(sequences.observe(&id, 200, at(4)), SequenceCheck::Fresh { missing: 193 });
+        assert!(!SequenceCheck::Duplicate.accepted());
+
+        // A replay far behind, soon after the newest report, is dropped
+        // without losing the window
+        assert_eq!(sequences.observe(&id, 1, at(5)), SequenceCheck::Late);
+        assert_eq!(sequences.observe(&id, 200, at(5)), SequenceCheck::Duplicate);
+        assert_eq!(sequences.observe(&id, 201, at(6)), SequenceCheck::Fresh { missing: 0 });
+
+        let stats = &sequences.stats()[0];
+        assert_eq!(stats.last_sequence, 201);
+        assert_eq!((stats.duplicates, stats.gaps, stats.missing, stats.late, stats.restarts), (4, 2, 196, 2, 0));
+    }
+
+    #[test]
+    fn test_restart_after_silence() {
+        let sequences = SequenceTracker::new();
+        let id = DroneId::new("REAPER-01");
+        let t0 = Utc::now();
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+
+        // A drone that restarts after only a few reports
+        for sequence in 1..=5 {
+            assert!(sequences.observe(&id, sequence, at(sequence as i64)).accepted());
+        }
+        // Dropped while redelivery is still plausible, even if that keeps up
+        assert_eq!(sequences.observe(&id, 1, at(8)), SequenceCheck::Duplicate);
+        assert_eq!(sequences.observe(&id, 2, at(12)), SequenceCheck::Duplicate);
+        assert_eq!(sequences.observe(&id, 1, at(15)), SequenceCheck::Restarted);
+        assert_eq!(sequences.observe(&id, 2, at(16)), SequenceCheck::Fresh { missing: 0 });
+        assert_eq!(sequences.observe(&id, 1, at(16)), SequenceCheck::Duplicate);
+
+        let stats = &sequences.stats()[0];
+        assert_eq!((stats.last_sequence, stats.restarts, stats.duplicates), (2, 1, 3));
+    }
+}
@@ -0,0 +1,173 @@
+//! Per-drone telemetry source designation
+//!
+//! Real and simulated drones can fly side by side: each drone may be
+//! designated one telemetry source, and reports for it from any other source
+//! are rejected as conflicts. Drones without a designation accept reports
+//! from every source. A conflict is flagged once when it starts; it ends when
+//! the conflicting source has been quiet for `CONFLICT_QUIET`.
+
+use drone_core::{DroneId, TelemetryError, TelemetrySource};
+
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Alert type raised when a drone reports from an undesignated source
+pub const SOURCE_CONFLICT_ALERT_TYPE: &str = "TELEMETRY_SOURCE_CONFLICT";
+
+/// How long a conflicting source has to stay quiet for the conflict to end
+pub const CONFLICT_QUIET: Duration = Duration::seconds(60);
+
+/// What the designation says about a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceCheck {
+    /// From the designated source, or the drone has none
+    Accepted,
+    /// From another source; `new` when this starts a conflict
+    Conflict { designated: TelemetrySource, new: bool },
+}
+
+/// Why a position report was refused
+#[derive(Debug, Error)]
+pub enum ReportRejected {
+    #[error("drone {drone_id} is controlled by station {station}")]
+    RemotelyControlled { drone_id: DroneId, station: String },
+
+    #[error("drone {drone_id} takes telemetry from {designated}, not {reported}")]
+    WrongSource {
+        drone_id: DroneId,
+        designated: TelemetrySource,
+        reported: TelemetrySource,
+    },
+
+    #[error(transparent)]
+    Invalid(#[from] TelemetryError),
+}
+
+/// Reports rejected from one undesignated source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceConflict {
+    pub drone_id: DroneId,
+    pub designated: TelemetrySource,
+    pub source: TelemetrySource,
+    pub rejected: u64,
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+}
+
+/// Telemetry source designations and the conflicts against them
+#[derive(Debug, Default)]
+pub struct TelemetrySources {
+    designated: DashMap<DroneId, TelemetrySource>,
+    conflicts: DashMap<(DroneId, TelemetrySource), SourceConflict>,
+}
+
+impl TelemetrySources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The drone's designated source, if any
+    pub fn designated(&self, drone_id: &DroneId) -> Option<TelemetrySource> {
+        self.designated.get(drone_id).map(|source| *source)
+    }
+
+    /// All designations
+    pub fn designations(&self) -> Vec<(DroneId, TelemetrySource)> {
+        self.designated.iter().map(|r| (r.key().clone(), *r.value())).collect()
+    }
+
+    /// Designate a source (or none, with `None`); conflicts against the old
+    /// designation are dropped
+    pub fn designate(&self, drone_id: &DroneId, source: Option<TelemetrySource>) {
+        match source {
+            Some(source) => {
+                self.designated.insert(drone_id.clone(), source);
+            }
+            None => {
+                self.designated.remove(drone_id);
+            }
+        }
+        self.conflicts.retain(|(id, _), _| id != drone_id);
+    }
+
+    /// Check and record a report from `source`
+    pub fn check(&self, drone_id: &DroneId, source: TelemetrySource, now: DateTime<Utc>) -> SourceCheck {
+        let Some(designated) = self.designated(drone_id).filter(|designated| *designated != source) else {
+            return SourceCheck::Accepted;
+        };
+
+        let mut new = false;
+        let mut conflict = self
+            .conflicts
+            .entry((drone_id.clone(), source))
+            .or_insert_with(|| {
+                new = true;
+                SourceConflict {
+                    drone_id: drone_id.clone(),
+                    designated,
+                    source,
+                    rejected: 0,
+                    first_at: now,
+                    last_at: now,
+                }
+            });
+        if now - conflict.last_at > CONFLICT_QUIET {
+            new = true;
+            conflict.first_at = now;
+            conflict.rejected = 0;
+        }
+        conflict.rejected += 1;
+        conflict.last_at = now;
+        SourceCheck::Conflict { designated, new }
+    }
+
+    /// Conflicts with a rejected report within `CONFLICT_QUIET` of `now`
+    pub fn conflicts(&self, now: DateTime<Utc>) -> Vec<SourceConflict> {
+        let mut conflicts: Vec<_> = self
+            .conflicts
+            .iter()
+            .filter(|r| now - r.last_at <= CONFLICT_QUIET)
+            .map(|r| r.value().clone())
+            .collect();
+        conflicts.sort_by(|a, b| a.drone_id.as_str().cmp(b.drone_id.as_str()));
+        conflicts
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_designated_source_accepted() {
+        let sources = TelemetrySources::new();
+        let id = DroneId::new("REAPER-01");
+        let t0 = Utc::now();
+
+        // Undesignated drones take reports from anywhere
+        assert_eq!(sources.check(&id, TelemetrySource::Simulated, t0), SourceCheck::Accepted);
+
+        sources.designate(&id, Some(TelemetrySource::P2p));
+        assert_eq!(sources.check(&id, TelemetrySource::P2p, t0), SourceCheck::Accepted);
+        let conflict = |new| SourceCheck::Conflict { designated: TelemetrySource::P2p, new };
+        assert_eq!(sources.check(&id, TelemetrySource::Simulated, t0), conflict(true));
+        assert_eq!(sources.check(&id, TelemetrySource::Simulated, t0 + Duration::seconds(1)), conflict(false));
+        assert_eq!(sources.conflicts(t0)[0].rejected, 2);
+
+        // A conflict that went quiet starts over, and is no longer listed meanwhile
+        let later = t0 + Duration::seconds(120);
+        assert!(sources.conflicts(later).is_empty());
+        assert_eq!(sources.check(&id, TelemetrySource::Simulated, later), conflict(true));
+
+        // Switching the source clears its conflicts
+        sources.designate(&id, Some(TelemetrySource::Simulated));
+        assert!(sources.conflicts(later).is_empty());
+        assert_eq!(sources.check(&id, TelemetrySource::Simulated, later), SourceCheck::Accepted);
+    }
+}
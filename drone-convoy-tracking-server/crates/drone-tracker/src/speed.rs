@@ -0,0 +1,170 @@
+//! Per-leg speed limits
+//!
+//! Route legs over populated areas can carry a speed limit, set on the
+//! waypoint the leg arrives at. A drone faster than the limit plus
+//! `tolerance_kmh` raises one alert per leg; the alert re-arms once the
+//! drone is back at or below the limit. On missions with
+//! `enforce_speed_limits`, the violation also sends the drone a `SetSpeed`
+//! command to the limit, repeated every `command_interval` while it lasts.
+
+use drone_core::DroneId;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Alert type raised when a drone exceeds a leg's speed limit
+pub const SPEED_LIMIT_ALERT_TYPE: &str = "SPEED_LIMIT_EXCEEDED";
+
+/// Speed limit monitoring configuration
+#[derive(Debug, Clone)]
+pub struct SpeedLimitConfig {
+    /// Speed allowed over the limit before it counts as a violation (km/h)
+    pub tolerance_kmh: f64,
+    /// Time between repeated `SetSpeed` commands to a drone still too fast
+    pub command_interval: Duration,
+}
+
+impl Default for SpeedLimitConfig {
+    fn default() -> Self {
+        Self {
+            tolerance_kmh: 2.0,
+            command_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A drone over the speed limit of the leg it is flying
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpeedViolation {
+    pub drone_id: DroneId,
+    /// Index of the waypoint the leg arrives at
+    pub waypoint_index: usize,
+    pub limit_kmh: f64,
+    pub speed_kmh: f64,
+    /// First report over the limit on this leg
+    pub alert: bool,
+    /// A `SetSpeed` command is due
+    pub command: bool,
+}
+
+#[derive(Debug)]
+struct ActiveViolation {
+    waypoint_index: usize,
+    last_command: Option<DateTime<Utc>>,
+}
+
+/// Tracks which drones are over their leg's limit
+#[derive(Debug)]
+pub struct SpeedLimitMonitor {
+    config: SpeedLimitConfig,
+    active: RwLock<HashMap<DroneId, ActiveViolation>>,
+}
+
+impl SpeedLimitMonitor {
+    pub fn new(config: SpeedLimitConfig) -> Self {
+        Self {
+            config,
+            active: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &SpeedLimitConfig {
+        &self.config
+    }
+
+    /// Check a drone's speed on the leg arriving at `waypoint_index`;
+    /// `None` when there is nothing to report or send
+    pub fn check(
+        &self,
+        drone_id: &DroneId,
+        waypoint_index: usize,
+        limit_kmh: Option<f64>,
+        speed_kmh: f64,
+        enforce: bool,
+        now: DateTime<Utc>,
+    ) -> Option<SpeedViolation> {
+        let mut active = self.active.write();
+        let Some(limit_kmh) = limit_kmh.filter(|limit| speed_kmh > *limit) else {
+            active.remove(drone_id);
+            return None;
+        };
+        if speed_kmh <= limit_kmh + self.config.tolerance_kmh {
+            return None;
+        }
+
+        let interval = chrono::Duration::from_std(self.config.command_interval).unwrap_or(chrono::Duration::MAX);
+        let (alert, command) = match active.get_mut(drone_id) {
+            Some(violation) if violation.waypoint_index == waypoint_index => {
+                let due = enforce && violation.last_command.is_none_or(|at| now - at >= interval);
+                if due {
+                    violation.last_command = Some(now);
+                }
+                (false, due)
+            }
+            _ => {
+                active.insert(
+                    drone_id.clone(),
+                    ActiveViolation {
+                        waypoint_index,
+                        last_command: enforce.then_some(now),
+                    },
+                );
+                (true, enforce)
+            }
+        };
+
+        (alert || command).then(|| SpeedViolation {
+            drone_id: drone_id.clone(),
+            waypoint_index,
+            limit_kmh,
+            speed_kmh,
+            alert,
+            command,
+        })
+    }
+
+    /// Drop a drone's violation state
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.active.write().remove(drone_id);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_once_per_leg_and_repeat_commands() {
+        let monitor = SpeedLimitMonitor::new(SpeedLimitConfig::default());
+        let id = DroneId::new("REAPER-01");
+        let t0 = Utc::now();
+        let at = |secs| t0 + chrono::Duration::seconds(secs);
+
+        // Within the tolerance
+        assert!(monitor.check(&id, 1, Some(60.0), 61.5, true, at(0)).is_none());
+
+        let first = monitor.check(&id, 1, Some(60.0), 75.0, true, at(1)).unwrap();
+        assert!(first.alert && first.command);
+        assert!(monitor.check(&id, 1, Some(60.0), 75.0, true, at(5)).is_none());
+        let repeat = monitor.check(&id, 1, Some(60.0), 75.0, true, at(11)).unwrap();
+        assert!(!repeat.alert && repeat.command);
+
+        // Slowing into the tolerance band does not re-arm; down to the limit does
+        assert!(monitor.check(&id, 1, Some(60.0), 61.0, true, at(12)).is_none());
+        assert!(monitor.check(&id, 1, Some(60.0), 75.0, true, at(13)).is_none());
+        assert!(monitor.check(&id, 1, Some(60.0), 59.0, true, at(14)).is_none());
+        assert!(monitor.check(&id, 1, Some(60.0), 75.0, false, at(15)).unwrap().alert);
+
+        // A new leg alerts again; unenforced missions get no command
+        let next = monitor.check(&id, 2, Some(40.0), 75.0, false, at(16)).unwrap();
+        assert!(next.alert && !next.command);
+        assert!(monitor.check(&id, 3, None, 75.0, false, at(17)).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! Status inference from telemetry
+//!
+//! A standby drone reporting more than `moving_above_kmh` is moving; a
+//! moving drone below `standby_below_kmh` for `standby_after` is standing
+//! by. After a return-to-base command, a drone that loses its status (e.g.
+//! to a dropped link) is returning again once it flies toward the mission
+//! origin, and stands by once it stops within `home_radius_m` of it. Any
+//! other command, or a mission start, ends the return.
+//! Drones silent for `offline_after` go offline. Engaged, loitering and
+//! maintenance statuses are left to operators and the mission, and every
+//! change goes through `DroneStatus::can_transition_to`.
+
+use drone_core::{DroneId, DroneStatus, GeoPosition};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::time::Duration;
+
+/// Status inference configuration
+#[derive(Debug, Clone)]
+pub struct StatusInferenceConfig {
+    /// Infer statuses at all
+    pub enabled: bool,
+    /// Standby drones faster than this are moving (km/h)
+    pub moving_above_kmh: f64,
+    /// Moving drones slower than this for `standby_after` are standing by (km/h)
+    pub standby_below_kmh: f64,
+    pub standby_after: Duration,
+    /// Drones without a report for this long are offline
+    pub offline_after: Duration,
+    /// How close to the origin a returning drone has to stop
+    pub home_radius_m: f64,
+    /// Most a heading can differ from the bearing to the origin for the
+    /// drone to count as flying home
+    pub homebound_within_deg: f64,
+    /// How often staleness is checked
+    pub check_interval: Duration,
+}
+
+impl Default for StatusInferenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            moving_above_kmh: 5.0,
+            standby_below_kmh: 1.0,
+            standby_after: Duration::from_secs(30),
+            offline_after: Duration::from_secs(60),
+            home_radius_m: 200.0,
+            homebound_within_deg: 60.0,
+            check_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// One position report, as far as status inference is concerned
+#[derive(Debug, Clone, Copy)]
+pub struct StatusReport {
+    pub status: DroneStatus,
+    pub position: GeoPosition,
+    pub speed_kmh: f64,
+    pub heading: f64,
+    /// Origin waypoint of the active mission
+    pub origin: Option<GeoPosition>,
+}
+
+/// Per-drone inference state
+#[derive(Debug)]
+pub struct StatusInference {
+    config: StatusInferenceConfig,
+    /// When each drone dropped below `standby_below_kmh`
+    slow_since: DashMap<DroneId, DateTime<Utc>>,
+    /// Drones told to return to base that have not got home yet
+    returning: DashMap<DroneId, DateTime<Utc>>,
+}
+
+impl StatusInference {
+    pub fn new(config: StatusInferenceConfig) -> Self {
+        Self {
+            config,
+            slow_since: DashMap::new(),
+            returning: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &StatusInferenceConfig {
+        &self.config
+    }
+
+    /// Note a return-to-base command to the drone
+    pub fn return_commanded(&self, drone_id: &DroneId, at: DateTime<Utc>) {
+        self.returning.insert(drone_id.clone(), at);
+    }
+
+    /// Note a command other than return-to-base: the drone is no longer
+    /// returning
+    pub fn return_cancelled(&self, drone_id: &DroneId) {
+        self.returning.remove(drone_id);
+    }
+
+    /// A mission started: no drone is returning from the last one
+    pub fn mission_started(&self) {
+        self.returning.clear();
+    }
+
+    /// Status the drone should change to after a report, if any
+    pub fn observe(&self, drone_id: &DroneId, report: &StatusReport, now: DateTime<Utc>) -> Option<DroneStatus> {
+        if !self.config.enabled {
+            return None;
+        }
+        // Time stopped at a hold or on an engagement is not idling
+        if !matches!(
+            report.status,
+            DroneStatus::Offline | DroneStatus::Standby | DroneStatus::Moving | DroneStatus::Rtb
+        ) {
+            self.slow_since.remove(drone_id);
+            return None;
+        }
+
+        let stopped = if report.speed_kmh < self.config.standby_below_kmh {
+            let since = *self.slow_since.entry(drone_id.clone()).or_insert(now);
+            now - since >= chrono::Duration::from_std(self.config.standby_after).unwrap_or_default()
+        } else {
+            self.slow_since.remove(drone_id);
+            false
+        };
+        let moving = report.speed_kmh > self.config.moving_above_kmh;
+        let homebound = moving && self.returning.contains_key(drone_id) && self.heading_home(report);
+
+        let next = match report.status {
+            DroneStatus::Offline if homebound => DroneStatus::Rtb,
+            DroneStatus::Offline if moving => DroneStatus::Moving,
+            DroneStatus::Offline => DroneStatus::Standby,
+            DroneStatus::Standby if homebound => DroneStatus::Rtb,
+            DroneStatus::Standby if moving => DroneStatus::Moving,
+            DroneStatus::Moving if stopped => DroneStatus::Standby,
+            DroneStatus::Rtb if stopped && self.at_home(report) => {
+                self.returning.remove(drone_id);
+                DroneStatus::Standby
+            }
+            _ => return None,
+        };
+        (next != report.status && report.status.can_transition_to(next)).then_some(next)
+    }
+
+    /// Whether a drone last heard from at `last_update` has gone offline
+    pub fn is_offline(&self, status: DroneStatus, last_update: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let silent = chrono::Duration::from_std(self.config.offline_after).unwrap_or(chrono::Duration::MAX);
+        self.config.enabled
+            && !matches!(status, DroneStatus::Offline | DroneStatus::Unreachable)
+            && status.can_transition_to(DroneStatus::Offline)
+            && now - last_update > silent
+    }
+
+    /// Drop a drone's state
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.slow_since.remove(drone_id);
+        self.returning.remove(drone_id);
+    }
+
+    fn heading_home(&self, report: &StatusReport) -> bool {
+        report.origin.is_some_and(|origin| {
+            let bearing = report.position.bearing_to(&origin);
+            let off = (report.heading - bearing).rem_euclid(360.0);
+            off.min(360.0 - off) <= self.config.homebound_within_deg
+        })
+    }
+
+    fn at_home(&self, report: &StatusReport) -> bool {
+        report
+            .origin
+            .is_some_and(|origin| report.position.distance_to(&origin) * 1000.0 <= self.config.home_radius_m)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_follows_speed_and_return_home() {
+        let inference = StatusInference::new(StatusInferenceConfig::default());
+        let id = DroneId::new("REAPER-01");
+        let origin = GeoPosition::new(34.50, 69.20, 0.0);
+        let away = origin.destination(5.0, 0.0);
+        let t0 = Utc::now();
+        let report = |status, position, speed_kmh, heading| StatusReport {
+            status,
+            position,
+            speed_kmh,
+            heading,
+            origin: Some(origin),
+        };
+
+        assert_eq!(
+            inference.observe(&id, &report(DroneStatus::Standby, away, 80.0, 0.0), t0),
+            Some(DroneStatus::Moving)
+        );
+        // Stopping only counts once it has lasted
+        assert_eq!(inference.observe(&id, &report(DroneStatus::Moving, away, 0.0, 0.0), t0), None);
+        let later = t0 + chrono::Duration::seconds(31);
+        assert_eq!(
+            inference.observe(&id, &report(DroneStatus::Moving, away, 0.0, 0.0), later),
+            Some(DroneStatus::Standby)
+        );
+
+        // A recalled drone comes back online flying south, toward the origin
+        inference.return_commanded(&id, t0);
+        assert_eq!(
+            inference.observe(&id, &report(DroneStatus::Offline, away, 80.0, 0.0), later),
+            Some(DroneStatus::Moving)
+        );
+        assert_eq!(
+            inference.observe(&id, &report(DroneStatus::Offline, away, 80.0, 180.0), later),
+            Some(DroneStatus::Rtb)
+        );
+        // Stopped away from home it is still returning; at home it stands by
+        let t1 = later + chrono::Duration::seconds(1);
+        let t2 = t1 + chrono::Duration::seconds(31);
+        inference.observe(&id, &report(DroneStatus::Rtb, away, 0.0, 180.0), t1);
+        assert_eq!(inference.observe(&id, &report(DroneStatus::Rtb, away, 0.0, 180.0), t2), None);
+        assert_eq!(
+            inference.observe(&id, &report(DroneStatus::Rtb, origin, 0.0, 180.0), t2),
+            Some(DroneStatus::Standby)
+        );
+
+        // Maintenance is never inferred away, and silence takes drones offline
+        assert_eq!(inference.observe(&id, &report(DroneStatus::Maintenance, away, 80.0, 0.0), t2), None);
+        assert!(inference.is_offline(DroneStatus::Moving, t0, t0 + chrono::Duration::seconds(61)));
+        assert!(!inference.is_offline(DroneStatus::Maintenance, t0, t0 + chrono::Duration::seconds(61)));
+        assert!(!inference.is_offline(DroneStatus::Moving, t0, t0 + chrono::Duration::seconds(59)));
+    }
+
+    #[test]
+    fn test_other_commands_and_mission_start_end_the_return() {
+        let inference = StatusInference::new(StatusInferenceConfig::default());
+        let id = DroneId::new("REAPER-01");
+        let origin = GeoPosition::new(34.50, 69.20, 0.0);
+        let report = StatusReport {
+            status: DroneStatus::Offline,
+            position: origin.destination(5.0, 0.0),
+            speed_kmh: 80.0,
+            heading: 180.0,
+            origin: Some(origin),
+        };
+        let t0 = Utc::now();
+
+        // Sent on to a waypoint that happens to lie toward home
+        inference.return_commanded(&id, t0);
+        inference.return_cancelled(&id);
+        assert_eq!(inference.observe(&id, &report, t0), Some(DroneStatus::Moving));
+
+        inference.return_commanded(&id, t0);
+        inference.mission_started();
+        assert_eq!(inference.observe(&id, &report, t0), Some(DroneStatus::Moving));
+    }
+}
@@ -0,0 +1,197 @@
+//! Alert suppression windows
+//!
+//! A suppression rule silences alerts for one drone, one alert type, or
+//! both, either between two times or while the drone is in `Maintenance`.
+//! Suppressed alerts are dropped before they reach the event stream or the
+//! alert consumers, and counted per rule and per alert type.
+
+use drone_core::{Alert, AlertType, DroneId, DroneStatus};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// When a suppression rule applies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SuppressionWindow {
+    /// From `start` (inclusive) to `end` (exclusive)
+    Scheduled { start: DateTime<Utc>, end: DateTime<Utc> },
+    /// While the alerting drone's status is `Maintenance`
+    WhileMaintenance,
+}
+
+/// A suppression rule
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressionRule {
+    pub id: Uuid,
+    /// Drone whose alerts are suppressed (`None` = every drone)
+    pub drone_id: Option<DroneId>,
+    /// Alert type suppressed (`None` = every type)
+    pub alert_type: Option<AlertType>,
+    pub window: SuppressionWindow,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Alerts this rule has suppressed
+    pub suppressed: u64,
+}
+
+impl SuppressionRule {
+    pub fn new(
+        drone_id: Option<DroneId>,
+        alert_type: Option<AlertType>,
+        window: SuppressionWindow,
+        reason: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            drone_id,
+            alert_type,
+            window,
+            reason,
+            created_at: now,
+            suppressed: 0,
+        }
+    }
+
+    /// The rule can no longer suppress anything
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.window, SuppressionWindow::Scheduled { end, .. } if end <= now)
+    }
+
+    /// Whether the rule covers `alert` from a drone currently in `status`
+    fn covers(&self, alert: &Alert, status: Option<DroneStatus>, now: DateTime<Utc>) -> bool {
+        if self.drone_id.as_ref().is_some_and(|id| alert.drone_id.as_ref() != Some(id)) {
+            return false;
+        }
+        if self.alert_type.as_ref().is_some_and(|t| t != &alert.alert_type) {
+            return false;
+        }
+        match &self.window {
+            SuppressionWindow::Scheduled { start, end } => *start <= now && now < *end,
+            SuppressionWindow::WhileMaintenance => status == Some(DroneStatus::Maintenance),
+        }
+    }
+}
+
+/// Suppressed-alert counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SuppressionStats {
+    pub suppressed_total: u64,
+    /// Suppressed alerts by alert type (`Custom` types by their name)
+    pub by_type: HashMap<String, u64>,
+}
+
+/// Alert suppression rules and counters
+#[derive(Debug, Default)]
+pub struct AlertSuppressor {
+    rules: RwLock<HashMap<Uuid, SuppressionRule>>,
+    stats: RwLock<SuppressionStats>,
+}
+
+impl AlertSuppressor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, rule: SuppressionRule) -> SuppressionRule {
+        self.rules.write().insert(rule.id, rule.clone());
+        rule
+    }
+
+    pub fn remove(&self, id: &Uuid) -> Option<SuppressionRule> {
+        self.rules.write().remove(id)
+    }
+
+    /// Rules that have not expired, oldest first; expired rules are dropped
+    pub fn list(&self, now: DateTime<Utc>) -> Vec<SuppressionRule> {
+        let mut rules = self.rules.write();
+        rules.retain(|_, rule| !rule.expired(now));
+        let mut list: Vec<_> = rules.values().cloned().collect();
+        list.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        list
+    }
+
+    pub fn stats(&self) -> SuppressionStats {
+        self.stats.read().clone()
+    }
+
+    /// Whether `alert` is suppressed, counting it if so
+    ///
+    /// `status` is the alerting drone's current status, if known.
+    pub fn suppresses(&self, alert: &Alert, status: Option<DroneStatus>, now: DateTime<Utc>) -> bool {
+        let mut rules = self.rules.write();
+        let Some(rule) = rules.values_mut().find(|rule| rule.covers(alert, status, now)) else {
+            return false;
+        };
+        rule.suppressed += 1;
+        drop(rules);
+
+        let mut stats = self.stats.write();
+        stats.suppressed_total += 1;
+        *stats.by_type.entry(alert_type_name(&alert.alert_type)).or_default() += 1;
+        true
+    }
+}
+
+fn alert_type_name(alert_type: &AlertType) -> String {
+    match alert_type {
+        AlertType::Custom(name) => name.clone(),
+        other => serde_json::to_value(other)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", other)),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use drone_core::AlertSeverity;
+
+    #[test]
+    fn test_scheduled_and_maintenance_windows() {
+        let suppressor = AlertSuppressor::new();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let [r1, r2] = ["REAPER-01", "REAPER-02"].map(DroneId::new);
+        let battery = |drone: &DroneId| Alert::new(AlertSeverity::Warning, AlertType::BatteryLow, "Battery low").for_drone(drone.clone());
+        let fuel = |drone: &DroneId| Alert::new(AlertSeverity::Warning, AlertType::FuelLow, "Fuel low").for_drone(drone.clone());
+
+        let scheduled = suppressor.add(SuppressionRule::new(
+            Some(r1.clone()),
+            Some(AlertType::BatteryLow),
+            SuppressionWindow::Scheduled { start: at(0), end: at(600) },
+            Some("Battery swap".into()),
+            at(0),
+        ));
+        suppressor.add(SuppressionRule::new(None, None, SuppressionWindow::WhileMaintenance, None, at(1)));
+
+        // Only REAPER-01's battery alerts, only inside the window
+        assert!(suppressor.suppresses(&battery(&r1), Some(DroneStatus::Moving), at(10)));
+        assert!(!suppressor.suppresses(&fuel(&r1), Some(DroneStatus::Moving), at(10)));
+        assert!(!suppressor.suppresses(&battery(&r2), Some(DroneStatus::Moving), at(10)));
+        assert!(!suppressor.suppresses(&battery(&r1), Some(DroneStatus::Moving), at(600)));
+
+        // Anything from a drone in maintenance
+        assert!(suppressor.suppresses(&fuel(&r2), Some(DroneStatus::Maintenance), at(700)));
+
+        let stats = suppressor.stats();
+        assert_eq!(stats.suppressed_total, 2);
+        assert_eq!(stats.by_type["BATTERY_LOW"], 1);
+        assert_eq!(stats.by_type["FUEL_LOW"], 1);
+
+        // The scheduled rule is gone once it expires
+        assert_eq!(suppressor.list(at(10))[0].suppressed, 1);
+        assert_eq!(suppressor.list(at(600)).len(), 1);
+        assert!(suppressor.remove(&scheduled.id).is_none());
+    }
+}
@@ -0,0 +1,420 @@
+//! Pluggable command transports
+//!
+//! Depending on the airframe, commands reach a drone over the P2P mesh,
+//! MAVLink or an HTTP sidecar next to its autopilot. Each drone is bound to
+//! one transport (the mesh unless bound otherwise), and the
+//! `CommandDispatcher` routes every command over the bound transport.
+//! Failures of the link itself are reported as `TransportError` outcomes,
+//! separately from commands the tracker refuses.
+
+use crate::abort;
+use crate::groups::{CommandOutcome, CommandResult};
+
+use drone_core::{DroneCommandType, DroneId, TransportBinding, TransportKind};
+use drone_p2p::protocol::CommandKind;
+use drone_p2p::{DroneMessage, P2pManager};
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::net::UdpSocket;
+
+/// Why a transport could not deliver a command
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error("no {0} transport is configured")]
+    Unavailable(TransportKind),
+
+    #[error("invalid {kind} binding: {message}")]
+    Binding { kind: TransportKind, message: String },
+
+    #[error("{kind} delivery failed: {message}")]
+    Delivery { kind: TransportKind, message: String },
+}
+
+impl TransportError {
+    pub fn delivery(kind: TransportKind, message: impl Into<String>) -> Self {
+        Self::Delivery {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// A link that carries commands to drones
+#[async_trait]
+pub trait CommandTransport: Send + Sync {
+    fn kind(&self) -> TransportKind;
+
+    /// Deliver `command`; `Sent` if it went out, `Accepted` if the link has
+    /// no message for it
+    async fn send(
+        &self,
+        drone_id: &DroneId,
+        binding: &TransportBinding,
+        command: &DroneCommandType,
+    ) -> Result<CommandOutcome, TransportError>;
+}
+
+// ============================================================================
+// P2P MESH
+// ============================================================================
+
+/// Commands over the P2P mesh; only abort-class commands have mesh messages
+pub struct P2pTransport {
+    p2p: Arc<P2pManager>,
+}
+
+impl P2pTransport {
+    pub fn new(p2p: Arc<P2pManager>) -> Self {
+        Self { p2p }
+    }
+}
+
+#[async_trait]
+impl CommandTransport for P2pTransport {
+    fn kind(&self) -> TransportKind {
+        TransportKind::P2p
+    }
+
+    async fn send(
+        &self,
+        drone_id: &DroneId,
+        _binding: &TransportBinding,
+        command: &DroneCommandType,
+    ) -> Result<CommandOutcome, TransportError> {
+        let kind = match command {
+            DroneCommandType::ReturnToBase => CommandKind::ReturnToBase,
+            DroneCommandType::EmergencyStop => CommandKind::EmergencyStop,
+            _ => return Ok(CommandOutcome::Accepted),
+        };
+        let message = DroneMessage::command(
+            DroneId::new(abort::GROUND_STATION_ID),
+            drone_id.clone(),
+            kind,
+        );
+        self.p2p
+            .send_to_drone(drone_id, message)
+            .await
+            .map(|()| CommandOutcome::Sent)
+            .map_err(|e| TransportError::delivery(TransportKind::P2p, e.to_string()))
+    }
+}
+
+// ============================================================================
+// MAVLINK
+// ============================================================================
+
+/// MAVLink system ID of this ground station
+pub const MAVLINK_GCS_SYSTEM_ID: u8 = 255;
+/// MAVLink component ID of this ground station (MAV_COMP_ID_MISSIONPLANNER)
+pub const MAVLINK_GCS_COMPONENT_ID: u8 = 190;
+
+const MAVLINK_V2_STX: u8 = 0xFD;
+const COMMAND_LONG_ID: u32 = 76;
+const COMMAND_LONG_CRC_EXTRA: u8 = 152;
+
+const MAV_CMD_NAV_RETURN_TO_LAUNCH: u16 = 20;
+const MAV_CMD_DO_CHANGE_SPEED: u16 = 178;
+const MAV_CMD_DO_CHANGE_ALTITUDE: u16 = 186;
+const MAV_CMD_DO_PAUSE_CONTINUE: u16 = 193;
+const MAV_CMD_DO_MOUNT_CONTROL: u16 = 205;
+/// MAV_MOUNT_MODE_MAVLINK_TARGETING: angles come from the command
+const MOUNT_MODE_MAVLINK_TARGETING: f32 = 2.0;
+const MAV_CMD_MISSION_START: u16 = 300;
+const MAV_CMD_COMPONENT_ARM_DISARM: u16 = 400;
+/// `param2` that makes ARM_DISARM disarm even in flight
+const FORCE_DISARM_MAGIC: f32 = 21196.0;
+
+/// Commands as MAVLink v2 COMMAND_LONG frames over UDP
+pub struct MavlinkTransport {
+    socket: UdpSocket,
+    sequence: AtomicU8,
+}
+
+impl MavlinkTransport {
+    /// Bind the local UDP socket frames are sent from
+    pub async fn bind(addr: &str) -> std::io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(addr).await?,
+            sequence: AtomicU8::new(0),
+        })
+    }
+}
+
+/// MAV_CMD and params for a command; `None` if MAVLink has no equivalent
+fn mavlink_command(command: &DroneCommandType) -> Option<(u16, [f32; 7])> {
+    let mut params = [0.0; 7];
+    let cmd = match command {
+        DroneCommandType::Start => MAV_CMD_MISSION_START,
+        DroneCommandType::Pause => MAV_CMD_DO_PAUSE_CONTINUE,
+        DroneCommandType::Resume => {
+            params[0] = 1.0;
+            MAV_CMD_DO_PAUSE_CONTINUE
+        }
+        DroneCommandType::ReturnToBase => MAV_CMD_NAV_RETURN_TO_LAUNCH,
+        DroneCommandType::EmergencyStop => {
+            params[1] = FORCE_DISARM_MAGIC;
+            MAV_CMD_COMPONENT_ARM_DISARM
+        }
+        DroneCommandType::SetSpeed { speed } => {
+            // Ground speed in m/s; -1 leaves the throttle unchanged
+            params[0] = 1.0;
+            params[1] = (*speed / 3.6) as f32;
+            params[2] = -1.0;
+            MAV_CMD_DO_CHANGE_SPEED
+        }
+        DroneCommandType::SetAltitude { altitude } => {
+            // Metres above mean sea level (MAV_FRAME_GLOBAL)
+            params[0] = *altitude as f32;
+            MAV_CMD_DO_CHANGE_ALTITUDE
+        }
+        DroneCommandType::SetGimbal { pan, tilt, .. } => {
+            // Pitch and yaw in degrees; zoom is a camera setting, not a mount one
+            params[0] = *tilt as f32;
+            params[2] = *pan as f32;
+            params[6] = MOUNT_MODE_MAVLINK_TARGETING;
+            MAV_CMD_DO_MOUNT_CONTROL
+        }
+        // Waypoints are addressed by mission sequence number, the payload
+        // is not on the autopilot's bus, and the tracker resolves PointAt to
+        // SetGimbal before dispatch
+        DroneCommandType::GoToWaypoint { .. } | DroneCommandType::SetArmed { .. } | DroneCommandType::PointAt { .. } => {
+            return None
+        }
+    };
+    Some((cmd, params))
+}
+
+/// Encode a MAVLink v2 COMMAND_LONG frame
+pub fn command_long_frame(sequence: u8, target_system: u8, command: u16, params: [f32; 7]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(33);
+    for param in params {
+        payload.extend_from_slice(&param.to_le_bytes());
+    }
+    payload.extend_from_slice(&command.to_le_bytes());
+    payload.extend_from_slice(&[target_system, 1, 0]);
+    // v2 drops trailing zero bytes, keeping at least one
+    while payload.len() > 1 && payload.last() == Some(&0) {
+        payload.pop();
+    }
+
+    let msg_id = COMMAND_LONG_ID.to_le_bytes();
+    let mut frame = vec![
+        MAVLINK_V2_STX,
+        payload.len() as u8,
+        0, // incompat flags
+        0, // compat flags
+        sequence,
+        MAVLINK_GCS_SYSTEM_ID,
+        MAVLINK_GCS_COMPONENT_ID,
+        msg_id[0],
+        msg_id[1],
+        msg_id[2],
+    ];
+    frame.extend_from_slice(&payload);
+    let crc = frame[1..]
+        .iter()
+        .chain(std::iter::once(&COMMAND_LONG_CRC_EXTRA))
+        .fold(0xFFFF, |crc, byte| x25_accumulate(crc, *byte));
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// CRC-16/MCRF4XX step used by MAVLink checksums
+fn x25_accumulate(crc: u16, byte: u8) -> u16 {
+    let mut tmp = byte ^ (crc & 0xFF) as u8;
+    tmp ^= tmp << 4;
+    let tmp = tmp as u16;
+    (crc >> 8) ^ (tmp << 8) ^ (tmp << 3) ^ (tmp >> 4)
+}
+
+#[async_trait]
+impl CommandTransport for MavlinkTransport {
+    fn kind(&self) -> TransportKind {
+        TransportKind::Mavlink
+    }
+
+    async fn send(
+        &self,
+        _drone_id: &DroneId,
+        binding: &TransportBinding,
+        command: &DroneCommandType,
+    ) -> Result<CommandOutcome, TransportError> {
+        let endpoint = binding.endpoint.as_deref().ok_or_else(|| TransportError::Binding {
+            kind: TransportKind::Mavlink,
+            message: "no endpoint".into(),
+        })?;
+        let Some((cmd, params)) = mavlink_command(command) else {
+            return Ok(CommandOutcome::Accepted);
+        };
+
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let frame = command_long_frame(sequence, binding.system_id.unwrap_or(1), cmd, params);
+        self.socket
+            .send_to(&frame, endpoint)
+            .await
+            .map(|_| CommandOutcome::Sent)
+            .map_err(|e| TransportError::delivery(TransportKind::Mavlink, e.to_string()))
+    }
+}
+
+// ============================================================================
+// DISPATCHER
+// ============================================================================
+
+/// Routes each drone's commands over the transport it is bound to
+#[derive(Default)]
+pub struct CommandDispatcher {
+    transports: RwLock<HashMap<TransportKind, Arc<dyn CommandTransport>>>,
+    bindings: DashMap<DroneId, TransportBinding>,
+}
+
+impl CommandDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) the transport for its kind
+    pub fn register(&self, transport: Arc<dyn CommandTransport>) {
+        self.transports.write().insert(transport.kind(), transport);
+    }
+
+    /// Transport kinds that can currently carry commands
+    pub fn available(&self) -> Vec<TransportKind> {
+        let mut kinds: Vec<TransportKind> = self.transports.read().keys().copied().collect();
+        kinds.sort_by_key(|kind| kind.to_string());
+        kinds
+    }
+
+    pub fn bind(&self, drone_id: DroneId, binding: TransportBinding) {
+        self.bindings.insert(drone_id, binding);
+    }
+
+    /// Return a drone to the mesh; returns the previous binding
+    pub fn unbind(&self, drone_id: &DroneId) -> Option<TransportBinding> {
+        self.bindings.remove(drone_id).map(|(_, binding)| binding)
+    }
+
+    /// The drone's binding; the mesh unless bound otherwise
+    pub fn binding(&self, drone_id: &DroneId) -> TransportBinding {
+        self.bindings.get(drone_id).map(|b| b.clone()).unwrap_or_default()
+    }
+
+    /// Explicit bindings, by drone ID
+    pub fn bindings(&self) -> Vec<(DroneId, TransportBinding)> {
+        let mut bindings: Vec<_> = self
+            .bindings
+            .iter()
+            .map(|b| (b.key().clone(), b.value().clone()))
+            .collect();
+        bindings.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        bindings
+    }
+
+    /// Send a command over the drone's transport
+    ///
+    /// Without a mesh, mesh-bound commands are accepted without being sent,
+    /// as they were before transports were pluggable; any other missing
+    /// transport is a transport error.
+    pub async fn dispatch(&self, drone_id: &DroneId, command: &DroneCommandType) -> CommandResult {
+        let binding = self.binding(drone_id);
+        let transport = self.transports.read().get(&binding.kind).cloned();
+        let result = match transport {
+            Some(transport) => transport.send(drone_id, &binding, command).await,
+            None if binding.kind == TransportKind::P2p => Ok(CommandOutcome::Accepted),
+            None => Err(TransportError::Unavailable(binding.kind)),
+        };
+
+        match result {
+            Ok(outcome) => CommandResult::new(drone_id.clone(), outcome),
+            Err(e) => CommandResult::transport_error(drone_id.clone(), e.to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// Records what it was asked to send; fails when `down`
+    struct Recording {
+        kind: TransportKind,
+        down: bool,
+        sent: Mutex<Vec<DroneId>>,
+    }
+
+    #[async_trait]
+    impl CommandTransport for Recording {
+        fn kind(&self) -> TransportKind {
+            self.kind
+        }
+
+        async fn send(
+            &self,
+            drone_id: &DroneId,
+            _binding: &TransportBinding,
+            _command: &DroneCommandType,
+        ) -> Result<CommandOutcome, TransportError> {
+            if self.down {
+                return Err(TransportError::delivery(self.kind, "link down"));
+            }
+            self.sent.lock().push(drone_id.clone());
+            Ok(CommandOutcome::Sent)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_routes_by_binding() {
+        let dispatcher = CommandDispatcher::new();
+        let mavlink = Arc::new(Recording { kind: TransportKind::Mavlink, down: false, sent: Mutex::new(Vec::new()) });
+        let sidecar = Arc::new(Recording { kind: TransportKind::HttpSidecar, down: true, sent: Mutex::new(Vec::new()) });
+        dispatcher.register(mavlink.clone());
+        dispatcher.register(sidecar);
+
+        let (mesh, mav, http) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"), DroneId::new("REAPER-03"));
+        dispatcher.bind(mav.clone(), TransportBinding::new(TransportKind::Mavlink).with_endpoint("127.0.0.1:14550"));
+        dispatcher.bind(http.clone(), TransportBinding::new(TransportKind::HttpSidecar).with_endpoint("http://sidecar"));
+
+        let command = DroneCommandType::ReturnToBase;
+        assert_eq!(dispatcher.dispatch(&mav, &command).await.outcome, CommandOutcome::Sent);
+        assert_eq!(*mavlink.sent.lock(), vec![mav.clone()]);
+        // No mesh: accepted, as before
+        assert_eq!(dispatcher.dispatch(&mesh, &command).await.outcome, CommandOutcome::Accepted);
+
+        let failed = dispatcher.dispatch(&http, &command).await;
+        assert_eq!(failed.outcome, CommandOutcome::TransportError);
+        assert_eq!(failed.error.as_deref(), Some("http_sidecar delivery failed: link down"));
+
+        dispatcher.unbind(&mav);
+        assert_eq!(dispatcher.binding(&mav).kind, TransportKind::P2p);
+    }
+
+    #[test]
+    fn test_command_long_frame() {
+        let crc = b"123456789".iter().fold(0xFFFF, |crc, byte| x25_accumulate(crc, *byte));
+        assert_eq!(crc, 0x6F91);
+
+        let (cmd, params) = mavlink_command(&DroneCommandType::ReturnToBase).unwrap();
+        let frame = command_long_frame(7, 3, cmd, params);
+        // 28 zero param bytes, command, target system; component 1 is kept,
+        // the zero confirmation byte is dropped
+        assert_eq!(&frame[..10], &[0xFD, 32, 0, 0, 7, 255, 190, 76, 0, 0]);
+        assert_eq!(&frame[38..42], &[20, 0, 3, 1]);
+        assert_eq!(frame.len(), 10 + 32 + 2);
+        let crc = frame[1..42]
+            .iter()
+            .chain(std::iter::once(&COMMAND_LONG_CRC_EXTRA))
+            .fold(0xFFFF, |crc, byte| x25_accumulate(crc, *byte));
+        assert_eq!(&frame[42..], &crc.to_le_bytes());
+    }
+}
@@ -0,0 +1,442 @@
+//! Wind estimation from drone drift
+//!
+//! Drones report the heading they steer and their airspeed; the wind is
+//! what sets their track over the ground apart from that. Two GPS reports
+//! of a drone at least `min_sample_interval` apart (by report timestamp)
+//! give its ground velocity, and the difference to its air velocity is one
+//! wind sample. A drone that turned by more than `max_heading_change_deg`
+//! in between gives none, as its air velocity was not constant. Samples from
+//! every drone are averaged per grid cell of `cell_deg` degrees, as a plain
+//! mean at first and exponentially smoothed once a cell has more than
+//! `1 / smoothing` samples. Cells without a sample for `max_age` are dropped.
+
+use crate::motion::angle_difference;
+use drone_core::{DroneId, GeoPosition, Telemetry};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Largest still-air distance per ground km a projection assumes; a drone
+/// that cannot make headway at all is projected at this
+pub const MAX_WIND_FACTOR: f64 = 4.0;
+
+/// Wind estimation configuration
+#[derive(Debug, Clone)]
+pub struct WindConfig {
+    /// Estimate wind from position reports
+    pub enabled: bool,
+    /// Grid cell size (degrees of latitude and longitude)
+    pub cell_deg: f64,
+    /// Shortest span between the two reports of a sample; shorter spans
+    /// are dominated by GPS noise
+    pub min_sample_interval: Duration,
+    /// Reports further apart give no sample
+    pub max_sample_interval: Duration,
+    /// Below this airspeed (km/h) the reported heading says little
+    pub min_airspeed_kmh: f64,
+    /// A drone that turned further than this between the two reports of a
+    /// sample gives none (degrees)
+    pub max_heading_change_deg: f64,
+    /// Samples stronger than this are bad data, not wind (m/s)
+    pub max_wind_ms: f64,
+    /// Weight of a new sample in a cell's estimate once it has enough
+    pub smoothing: f64,
+    /// Cells without a sample for this long are dropped
+    pub max_age: Duration,
+}
+
+impl Default for WindConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cell_deg: 0.1,
+            min_sample_interval: Duration::from_secs(5),
+            max_sample_interval: Duration::from_secs(60),
+            min_airspeed_kmh: 20.0,
+            max_heading_change_deg: 5.0,
+            max_wind_ms: 40.0,
+            smoothing: 0.1,
+            max_age: Duration::from_secs(900),
+        }
+    }
+}
+
+impl WindConfig {
+    /// Defaults overridden by `WIND_ENABLED`, `WIND_CELL_DEG` and
+    /// `WIND_MAX_AGE_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            enabled: env("WIND_ENABLED")
+                .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
+                .unwrap_or(defaults.enabled),
+            cell_deg: env("WIND_CELL_DEG")
+                .and_then(|s| s.parse().ok())
+                .filter(|deg: &f64| *deg > 0.0 && *deg <= 10.0)
+                .unwrap_or(defaults.cell_deg),
+            max_age: env("WIND_MAX_AGE_SECS")
+                .and_then(|s| s.parse().ok())
+                .filter(|secs: &u64| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.max_age),
+            ..defaults
+        }
+    }
+}
+
+/// Horizontal wind, as the velocity of the air over the ground
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct WindVector {
+    /// m/s towards the east
+    pub east_ms: f64,
+    /// m/s towards the north
+    pub north_ms: f64,
+}
+
+impl WindVector {
+    pub fn new(east_ms: f64, north_ms: f64) -> Self {
+        Self { east_ms, north_ms }
+    }
+
+    pub fn speed_ms(&self) -> f64 {
+        self.east_ms.hypot(self.north_ms)
+    }
+
+    /// Direction the wind blows from (degrees, 0-360), as in weather reports
+    pub fn from_deg(&self) -> f64 {
+        (-self.east_ms).atan2(-self.north_ms).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Ground speed (m/s) of a drone holding `course_deg` over the ground at
+    /// `airspeed_ms`; `None` when the wind keeps it from making headway
+    pub fn ground_speed_ms(&self, airspeed_ms: f64, course_deg: f64) -> Option<f64> {
+        let course = course_deg.to_radians();
+        let along = self.east_ms * course.sin() + self.north_ms * course.cos();
+        let cross = self.east_ms * course.cos() - self.north_ms * course.sin();
+        let crab = airspeed_ms * airspeed_ms - cross * cross;
+        if crab < 0.0 {
+            return None;
+        }
+        Some(along + crab.sqrt()).filter(|speed| *speed > 0.0)
+    }
+
+    /// Still-air distance flown per ground km on `course_deg`: above 1 into
+    /// a headwind, below with a tailwind, at most `MAX_WIND_FACTOR`. 1
+    /// without an airspeed to compare with.
+    pub fn air_distance_factor(&self, airspeed_kmh: f64, course_deg: f64) -> f64 {
+        let airspeed_ms = airspeed_kmh / 3.6;
+        if airspeed_ms <= 0.0 {
+            return 1.0;
+        }
+        self.ground_speed_ms(airspeed_ms, course_deg)
+            .map(|ground| (airspeed_ms / ground).min(MAX_WIND_FACTOR))
+            .unwrap_or(MAX_WIND_FACTOR)
+    }
+}
+
+/// Wind estimated in one grid cell
+#[derive(Debug, Clone, Serialize)]
+pub struct WindCell {
+    /// Cell center
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(flatten)]
+    pub wind: WindVector,
+    pub speed_ms: f64,
+    pub from_deg: f64,
+    pub samples: u64,
+    /// Drones that contributed samples
+    pub drones: usize,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The coarse wind grid
+#[derive(Debug, Clone, Serialize)]
+pub struct WindField {
+    pub cell_deg: f64,
+    /// Cells with a sample within `max_age`, south to north, west to east
+    pub cells: Vec<WindCell>,
+}
+
+#[derive(Debug, Clone)]
+struct Cell {
+    wind: WindVector,
+    samples: u64,
+    drones: HashSet<DroneId>,
+    updated_at: DateTime<Utc>,
+}
+
+/// The report a drone's next sample starts from
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    at: DateTime<Utc>,
+    position: GeoPosition,
+    heading: f64,
+}
+
+/// Per-drone sample anchors and the wind grid
+#[derive(Debug)]
+pub struct WindEstimator {
+    config: WindConfig,
+    /// Report each drone's next sample starts from
+    anchors: RwLock<HashMap<DroneId, Anchor>>,
+    cells: RwLock<HashMap<(i64, i64), Cell>>,
+}
+
+impl WindEstimator {
+    pub fn new(config: WindConfig) -> Self {
+        Self {
+            config,
+            anchors: RwLock::new(HashMap::new()),
+            cells: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &WindConfig {
+        &self.config
+    }
+
+    /// Fold a GPS position report, taken `at` its report timestamp, into
+    /// the grid, returning the wind sample it completed, if any
+    pub fn observe(
+        &self,
+        drone_id: &DroneId,
+        position: GeoPosition,
+        telemetry: &Telemetry,
+        at: DateTime<Utc>,
+    ) -> Option<WindVector> {
+        if !self.config.enabled {
+            return None;
+        }
+        let (since, from) = {
+            let mut anchors = self.anchors.write();
+            let anchor = Anchor { at, position, heading: telemetry.heading };
+            let Some(&Anchor { at: since, position: from, heading }) = anchors.get(drone_id) else {
+                anchors.insert(drone_id.clone(), anchor);
+                return None;
+            };
+            let elapsed = (at - since).to_std().ok()?;
+            if elapsed < self.config.min_sample_interval {
+                return None;
+            }
+            anchors.insert(drone_id.clone(), anchor);
+            if elapsed > self.config.max_sample_interval {
+                return None;
+            }
+            if angle_difference(heading, telemetry.heading).abs() > self.config.max_heading_change_deg {
+                return None;
+            }
+            (since, from)
+        };
+        if telemetry.speed < self.config.min_airspeed_kmh {
+            return None;
+        }
+
+        let seconds = (at - since).num_milliseconds() as f64 / 1000.0;
+        let ground_ms = from.distance_to(&position) * 1000.0 / seconds;
+        let course = from.bearing_to(&position).to_radians();
+        let airspeed_ms = telemetry.speed / 3.6;
+        let heading = telemetry.heading.to_radians();
+        let sample = WindVector::new(
+            ground_ms * course.sin() - airspeed_ms * heading.sin(),
+            ground_ms * course.cos() - airspeed_ms * heading.cos(),
+        );
+        if sample.speed_ms() > self.config.max_wind_ms {
+            return None;
+        }
+
+        let midpoint = GeoPosition::new(
+            (from.latitude + position.latitude) / 2.0,
+            (from.longitude + position.longitude) / 2.0,
+            position.altitude,
+        );
+        self.record(drone_id, &midpoint, sample, at);
+        Some(sample)
+    }
+
+    fn record(&self, drone_id: &DroneId, position: &GeoPosition, sample: WindVector, at: DateTime<Utc>) {
+        let mut cells = self.cells.write();
+        let cell = cells.entry(self.cell_of(position)).or_insert_with(|| Cell {
+            wind: WindVector::default(),
+            samples: 0,
+            drones: HashSet::new(),
+            updated_at: at,
+        });
+        cell.samples += 1;
+        let weight = (1.0 / cell.samples as f64).max(self.config.smoothing);
+        cell.wind.east_ms += weight * (sample.east_ms - cell.wind.east_ms);
+        cell.wind.north_ms += weight * (sample.north_ms - cell.wind.north_ms);
+        cell.drones.insert(drone_id.clone());
+        cell.updated_at = cell.updated_at.max(at);
+
+        let cutoff = self.cutoff(at);
+        cells.retain(|_, cell| cell.updated_at >= cutoff);
+    }
+
+    /// Wind at a position: its cell's estimate, else the mean of the
+    /// neighbouring cells'
+    pub fn wind_at(&self, position: &GeoPosition, now: DateTime<Utc>) -> Option<WindVector> {
+        let cutoff = self.cutoff(now);
+        let cells = self.cells.read();
+        let (lat, lon) = self.cell_of(position);
+        let fresh = |key: &(i64, i64)| cells.get(key).filter(|cell| cell.updated_at >= cutoff);
+        if let Some(cell) = fresh(&(lat, lon)) {
+            return Some(cell.wind);
+        }
+
+        let neighbours: Vec<WindVector> = (-1..=1)
+            .flat_map(|dlat| (-1..=1).map(move |dlon| (lat + dlat, lon + dlon)))
+            .filter_map(|key| fresh(&key).map(|cell| cell.wind))
+            .collect();
+        (!neighbours.is_empty()).then(|| {
+            let n = neighbours.len() as f64;
+            WindVector::new(
+                neighbours.iter().map(|w| w.east_ms).sum::<f64>() / n,
+                neighbours.iter().map(|w| w.north_ms).sum::<f64>() / n,
+            )
+        })
+    }
+
+    /// Every cell with a recent estimate
+    pub fn field(&self, now: DateTime<Utc>) -> WindField {
+        let cutoff = self.cutoff(now);
+        let size = self.config.cell_deg;
+        let mut keyed: Vec<_> = self
+            .cells
+            .read()
+            .iter()
+            .filter(|(_, cell)| cell.updated_at >= cutoff)
+            .map(|(&(lat, lon), cell)| {
+                let cell = WindCell {
+                    latitude: (lat as f64 + 0.5) * size,
+                    longitude: (lon as f64 + 0.5) * size,
+                    wind: cell.wind,
+                    speed_ms: cell.wind.speed_ms(),
+                    from_deg: cell.wind.from_deg(),
+                    samples: cell.samples,
+                    drones: cell.drones.len(),
+                    updated_at: cell.updated_at,
+                };
+                ((lat, lon), cell)
+            })
+            .collect();
+        keyed.sort_by_key(|(key, _)| *key);
+        WindField {
+            cell_deg: size,
+            cells: keyed.into_iter().map(|(_, cell)| cell).collect(),
+        }
+    }
+
+    /// Drop a drone's sample anchor
+    pub fn forget(&self, drone_id: &DroneId) {
+        self.anchors.write().remove(drone_id);
+    }
+
+    fn cell_of(&self, position: &GeoPosition) -> (i64, i64) {
+        let size = self.config.cell_deg;
+        (
+            (position.latitude / size).floor() as i64,
+            (position.longitude / size).floor() as i64,
+        )
+    }
+
+    fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - chrono::Duration::from_std(self.config.max_age).unwrap_or_else(|_| chrono::Duration::weeks(52))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wind_triangle() {
+        // 10 m/s from the west
+        let wind = WindVector::new(10.0, 0.0);
+        assert!((wind.from_deg() - 270.0).abs() < 1e-9);
+        assert!((wind.ground_speed_ms(50.0, 90.0).unwrap() - 60.0).abs() < 1e-9);
+        assert!((wind.ground_speed_ms(50.0, 270.0).unwrap() - 40.0).abs() < 1e-9);
+        // Crabbing into a crosswind costs speed
+        let north = wind.ground_speed_ms(50.0, 0.0).unwrap();
+        assert!((north - 2400f64.sqrt()).abs() < 1e-9);
+        // Too slow to hold the course
+        assert_eq!(wind.ground_speed_ms(5.0, 0.0), None);
+
+        assert!((wind.air_distance_factor(180.0, 270.0) - 1.25).abs() < 1e-9);
+        assert!(wind.air_distance_factor(180.0, 90.0) < 1.0);
+        assert_eq!(wind.air_distance_factor(18.0, 270.0), MAX_WIND_FACTOR);
+        assert_eq!(wind.air_distance_factor(0.0, 270.0), 1.0);
+    }
+
+    #[test]
+    fn test_drift_across_drones_builds_the_grid() {
+        let estimator = WindEstimator::new(WindConfig::default());
+        let t0 = Utc::now();
+        // Both drones steer north at 180 km/h (50 m/s) and drift east at 10 m/s
+        let telemetry = Telemetry { heading: 0.0, speed: 180.0, ..Default::default() };
+        let drift = |start: GeoPosition, seconds: f64| {
+            start
+                .destination(50.0 * seconds / 1000.0, 0.0)
+                .destination(10.0 * seconds / 1000.0, 90.0)
+        };
+
+        for (n, drone) in ["REAPER-01", "REAPER-02"].into_iter().enumerate() {
+            let id = DroneId::new(drone);
+            let start = GeoPosition::new(34.51 + 0.01 * n as f64, 69.21, 3000.0);
+            assert_eq!(estimator.observe(&id, start, &telemetry, t0), None);
+            // Too soon after the first report
+            let early = t0 + chrono::Duration::seconds(2);
+            assert_eq!(estimator.observe(&id, drift(start, 2.0), &telemetry, early), None);
+            let at = t0 + chrono::Duration::seconds(10);
+            let sample = estimator.observe(&id, drift(start, 10.0), &telemetry, at).unwrap();
+            assert!((sample.east_ms - 10.0).abs() < 0.1, "{:?}", sample);
+            assert!(sample.north_ms.abs() < 0.1, "{:?}", sample);
+        }
+
+        let now = t0 + chrono::Duration::seconds(10);
+        let field = estimator.field(now);
+        assert_eq!(field.cells.len(), 1);
+        let cell = &field.cells[0];
+        assert_eq!((cell.samples, cell.drones), (2, 2));
+        assert!((cell.from_deg - 270.0).abs() < 1.0, "{:?}", cell);
+        assert!((cell.latitude - 34.55).abs() < 1e-9 && (cell.longitude - 69.25).abs() < 1e-9);
+
+        // Next door falls back on the neighbouring cell, far away on nothing
+        let wind = estimator.wind_at(&GeoPosition::new(34.65, 69.25, 0.0), now).unwrap();
+        assert!((wind.east_ms - 10.0).abs() < 0.1);
+        assert_eq!(estimator.wind_at(&GeoPosition::new(35.5, 69.25, 0.0), now), None);
+
+        // Estimates expire
+        let later = now + chrono::Duration::from_std(WindConfig::default().max_age).unwrap() + chrono::Duration::seconds(1);
+        assert!(estimator.field(later).cells.is_empty());
+        let center = GeoPosition::new(cell.latitude, cell.longitude, 0.0);
+        assert_eq!(estimator.wind_at(&center, later), None);
+    }
+
+    #[test]
+    fn test_turning_drones_give_no_sample() {
+        let estimator = WindEstimator::new(WindConfig::default());
+        let id = DroneId::new("REAPER-01");
+        let t0 = Utc::now();
+        let start = GeoPosition::new(34.51, 69.21, 3000.0);
+        let steering = |heading: f64| Telemetry { heading, speed: 180.0, ..Default::default() };
+
+        estimator.observe(&id, start, &steering(0.0), t0);
+        // Turned from north to east in between
+        let turned = start.destination(0.25, 45.0);
+        let at = t0 + chrono::Duration::seconds(10);
+        assert_eq!(estimator.observe(&id, turned, &steering(90.0), at), None);
+        // Holding the new heading, the next span counts
+        let ahead = turned.destination(0.5, 90.0);
+        let later = at + chrono::Duration::seconds(10);
+        assert!(estimator.observe(&id, ahead, &steering(92.0), later).is_some());
+    }
+}
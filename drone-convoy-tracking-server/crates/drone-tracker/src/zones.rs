@@ -0,0 +1,460 @@
+//! Zones of interest
+//!
+//! A zone is a named polygon that raises no alerts of its own: the tracker
+//! records each drone's entries and exits and accumulates, per mission, how
+//! many visits it made and how long it stayed inside. The statistics feed
+//! surveillance-coverage reporting and are persisted per mission.
+
+use drone_core::{DroneId, Geofence, GeoPosition, MissionId, ZoneEvent};
+use drone_db::{ZoneDwellRecord, ZoneRecord};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A named polygon whose visits are tracked
+#[derive(Debug, Clone, Serialize)]
+pub struct Zone {
+    pub id: Uuid,
+    /// Name, vertices and optional ceiling
+    #[serde(flatten)]
+    pub area: Geofence,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Zone {
+    pub fn new(area: Geofence, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            area,
+            created_at: now,
+        }
+    }
+
+    pub fn to_record(&self) -> ZoneRecord {
+        let vertices: Vec<[f64; 2]> = self.area.vertices.iter().map(|v| [v.latitude, v.longitude]).collect();
+        ZoneRecord {
+            id: self.id,
+            name: self.area.name.clone(),
+            vertices: serde_json::to_string(&vertices).unwrap_or_default(),
+            max_altitude: self.area.max_altitude,
+            created_at: self.created_at,
+        }
+    }
+
+    pub fn from_record(record: &ZoneRecord) -> Result<Self, serde_json::Error> {
+        let vertices: Vec<[f64; 2]> = serde_json::from_str(&record.vertices)?;
+        let mut area = Geofence::new(
+            record.name.clone(),
+            vertices.into_iter().map(|[lat, lng]| GeoPosition::new(lat, lng, 0.0)).collect(),
+        );
+        area.max_altitude = record.max_altitude;
+        Ok(Self {
+            id: record.id,
+            area,
+            created_at: record.created_at,
+        })
+    }
+}
+
+/// One drone's visits to one zone during one mission
+#[derive(Debug, Clone, Default)]
+struct Dwell {
+    visits: u32,
+    /// Time inside over completed visits
+    completed_ms: i64,
+    first_entry: Option<DateTime<Utc>>,
+    last_entry: Option<DateTime<Utc>>,
+    last_exit: Option<DateTime<Utc>>,
+}
+
+impl Dwell {
+    fn inside(&self) -> bool {
+        match (self.last_entry, self.last_exit) {
+            (Some(entry), Some(exit)) => entry > exit,
+            (entry, _) => entry.is_some(),
+        }
+    }
+
+    /// End the open visit at `now`, returning its length in milliseconds
+    fn exit(&mut self, now: DateTime<Utc>) -> i64 {
+        let visit_ms = self.last_entry.map_or(0, |at| (now - at).num_milliseconds().max(0));
+        self.completed_ms += visit_ms;
+        self.last_exit = Some(now);
+        visit_ms
+    }
+
+    fn to_record(&self, zone_id: Uuid, mission_id: &MissionId, drone_id: &DroneId) -> ZoneDwellRecord {
+        ZoneDwellRecord {
+            zone_id,
+            mission_id: mission_id.0,
+            drone_id: drone_id.0.clone(),
+            visits: self.visits as i32,
+            time_inside_ms: self.completed_ms,
+            first_entry: self.first_entry,
+            last_entry: self.last_entry,
+            last_exit: self.last_exit,
+        }
+    }
+
+    fn from_record(record: &ZoneDwellRecord) -> Self {
+        Self {
+            visits: record.visits.max(0) as u32,
+            completed_ms: record.time_inside_ms,
+            first_entry: record.first_entry,
+            last_entry: record.last_entry,
+            last_exit: record.last_exit,
+        }
+    }
+
+    fn stats(&self, drone_id: &DroneId, now: DateTime<Utc>) -> DwellStats {
+        let inside = self.inside();
+        let ongoing_ms = match self.last_entry {
+            Some(entry) if inside => (now - entry).num_milliseconds().max(0),
+            _ => 0,
+        };
+        DwellStats {
+            drone_id: drone_id.clone(),
+            visits: self.visits,
+            time_inside_seconds: (self.completed_ms + ongoing_ms) as f64 / 1000.0,
+            inside,
+            first_entry: self.first_entry,
+            last_entry: self.last_entry,
+            last_exit: self.last_exit,
+        }
+    }
+}
+
+/// A drone's visits to a zone
+#[derive(Debug, Clone, Serialize)]
+pub struct DwellStats {
+    pub drone_id: DroneId,
+    pub visits: u32,
+    /// Total time inside, including the current visit
+    pub time_inside_seconds: f64,
+    /// The drone is inside now
+    pub inside: bool,
+    pub first_entry: Option<DateTime<Utc>>,
+    pub last_entry: Option<DateTime<Utc>>,
+    pub last_exit: Option<DateTime<Utc>>,
+}
+
+/// Coverage of one zone during one mission
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneStats {
+    pub zone_id: Uuid,
+    pub zone_name: String,
+    pub mission_id: MissionId,
+    /// Drones that entered the zone at least once
+    pub drones_visited: usize,
+    pub total_visits: u32,
+    pub total_time_inside_seconds: f64,
+    /// Per-drone statistics, by drone ID
+    pub drones: Vec<DwellStats>,
+}
+
+impl ZoneStats {
+    fn new(zone: &Zone, mission_id: &MissionId, mut drones: Vec<DwellStats>) -> Self {
+        drones.sort_by(|a, b| a.drone_id.cmp(&b.drone_id));
+        Self {
+            zone_id: zone.id,
+            zone_name: zone.area.name.clone(),
+            mission_id: mission_id.clone(),
+            drones_visited: drones.iter().filter(|d| d.visits > 0).count(),
+            total_visits: drones.iter().map(|d| d.visits).sum(),
+            total_time_inside_seconds: drones.iter().map(|d| d.time_inside_seconds).sum(),
+            drones,
+        }
+    }
+}
+
+/// A zone boundary crossing and the dwell row to persist for it
+#[derive(Debug, Clone)]
+pub struct ZoneCrossing {
+    pub event: ZoneEvent,
+    pub record: ZoneDwellRecord,
+}
+
+type DwellKey = (Uuid, MissionId);
+
+/// Zones and the dwell statistics collected for them
+#[derive(Debug, Default)]
+pub struct ZoneMonitor {
+    zones: RwLock<HashMap<Uuid, Zone>>,
+    dwell: RwLock<HashMap<DwellKey, HashMap<DroneId, Dwell>>>,
+}
+
+impl ZoneMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, zone: Zone) -> Zone {
+        self.zones.write().insert(zone.id, zone.clone());
+        zone
+    }
+
+    /// Remove a zone along with its statistics
+    pub fn remove(&self, id: &Uuid) -> Option<Zone> {
+        self.dwell.write().retain(|(zone_id, _), _| zone_id != id);
+        self.zones.write().remove(id)
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<Zone> {
+        self.zones.read().get(id).cloned()
+    }
+
+    /// All zones, oldest first
+    pub fn list(&self) -> Vec<Zone> {
+        let mut zones: Vec<Zone> = self.zones.read().values().cloned().collect();
+        zones.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        zones
+    }
+
+    /// Replace all zones (after loading them from the database)
+    pub fn restore(&self, zones: Vec<Zone>) {
+        *self.zones.write() = zones.into_iter().map(|z| (z.id, z)).collect();
+    }
+
+    /// Record a position, returning the zones the drone entered or left
+    pub fn update(
+        &self,
+        drone_id: &DroneId,
+        mission_id: &MissionId,
+        position: &GeoPosition,
+        now: DateTime<Utc>,
+    ) -> Vec<ZoneCrossing> {
+        let zones = self.zones.read();
+        let mut dwell = self.dwell.write();
+        let mut crossings = Vec::new();
+
+        for zone in zones.values() {
+            let inside = zone.area.contains(position);
+            let key = (zone.id, mission_id.clone());
+            let was_inside = dwell
+                .get(&key)
+                .and_then(|drones| drones.get(drone_id))
+                .is_some_and(Dwell::inside);
+            if inside == was_inside {
+                continue;
+            }
+
+            let entry = dwell.entry(key).or_default().entry(drone_id.clone()).or_default();
+            let dwell_seconds = if inside {
+                entry.visits += 1;
+                entry.first_entry.get_or_insert(now);
+                entry.last_entry = Some(now);
+                None
+            } else {
+                Some(entry.exit(now) as f64 / 1000.0)
+            };
+
+            crossings.push(ZoneCrossing {
+                event: ZoneEvent {
+                    drone_id: drone_id.clone(),
+                    zone_id: zone.id,
+                    zone_name: zone.area.name.clone(),
+                    position: *position,
+                    dwell_seconds,
+                },
+                record: entry.to_record(zone.id, mission_id, drone_id),
+            });
+        }
+        crossings
+    }
+
+    /// End the open visits of a mission's drones, at their last `positions`,
+    /// for when the mission is replaced
+    pub fn close_mission(
+        &self,
+        mission_id: &MissionId,
+        positions: &HashMap<DroneId, GeoPosition>,
+        now: DateTime<Utc>,
+    ) -> Vec<ZoneCrossing> {
+        self.close_visits(|key_mission, _| key_mission == mission_id, |id| positions.get(id).copied(), now)
+    }
+
+    /// End a drone's open visits, for when it stops being tracked
+    pub fn close_drone(&self, drone_id: &DroneId, position: GeoPosition, now: DateTime<Utc>) -> Vec<ZoneCrossing> {
+        self.close_visits(|_, id| id == drone_id, |_| Some(position), now)
+    }
+
+    fn close_visits(
+        &self,
+        matches: impl Fn(&MissionId, &DroneId) -> bool,
+        position_of: impl Fn(&DroneId) -> Option<GeoPosition>,
+        now: DateTime<Utc>,
+    ) -> Vec<ZoneCrossing> {
+        let zones = self.zones.read();
+        let mut dwell = self.dwell.write();
+        let mut crossings = Vec::new();
+        for ((zone_id, mission_id), drones) in dwell.iter_mut() {
+            let Some(zone) = zones.get(zone_id) else {
+                continue;
+            };
+            for (drone_id, entry) in drones.iter_mut() {
+                if !entry.inside() || !matches(mission_id, drone_id) {
+                    continue;
+                }
+                let visit_ms = entry.exit(now);
+                crossings.push(ZoneCrossing {
+                    event: ZoneEvent {
+                        drone_id: drone_id.clone(),
+                        zone_id: *zone_id,
+                        zone_name: zone.area.name.clone(),
+                        position: position_of(drone_id).unwrap_or_default(),
+                        dwell_seconds: Some(visit_ms as f64 / 1000.0),
+                    },
+                    record: entry.to_record(*zone_id, mission_id, drone_id),
+                });
+            }
+        }
+        crossings
+    }
+
+    /// Take up persisted dwell rows for a zone and mission, so a restarted
+    /// process keeps counting where the last one stopped; drones already
+    /// tracked in memory keep their figures
+    pub fn hydrate(&self, zone_id: Uuid, mission_id: &MissionId, records: &[ZoneDwellRecord]) {
+        let mut dwell = self.dwell.write();
+        let drones = dwell.entry((zone_id, mission_id.clone())).or_default();
+        for record in records {
+            drones
+                .entry(DroneId::new(record.drone_id.as_str()))
+                .or_insert_with(|| Dwell::from_record(record));
+        }
+    }
+
+    /// Statistics collected in memory for a zone and mission, if any
+    pub fn stats(&self, zone_id: &Uuid, mission_id: &MissionId, now: DateTime<Utc>) -> Option<ZoneStats> {
+        let zone = self.get(zone_id)?;
+        let dwell = self.dwell.read();
+        let drones = dwell.get(&(*zone_id, mission_id.clone()))?;
+        let stats = drones.iter().map(|(id, d)| d.stats(id, now)).collect();
+        Some(ZoneStats::new(&zone, mission_id, stats))
+    }
+
+    /// Statistics for a zone from persisted dwell rows; open visits are
+    /// counted up to `now`
+    pub fn stats_from_records(
+        &self,
+        zone_id: &Uuid,
+        mission_id: &MissionId,
+        records: &[ZoneDwellRecord],
+        now: DateTime<Utc>,
+    ) -> Option<ZoneStats> {
+        let zone = self.get(zone_id)?;
+        let stats = records
+            .iter()
+            .map(|r| Dwell::from_record(r).stats(&DroneId::new(r.drone_id.as_str()), now))
+            .collect();
+        Some(ZoneStats::new(&zone, mission_id, stats))
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_entries_exits_and_dwell_time() {
+        let monitor = ZoneMonitor::new();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let square = Geofence::new(
+            "Objective Alpha",
+            vec![
+                GeoPosition::new(34.0, 69.0, 0.0),
+                GeoPosition::new(34.0, 69.1, 0.0),
+                GeoPosition::new(34.1, 69.1, 0.0),
+                GeoPosition::new(34.1, 69.0, 0.0),
+            ],
+        );
+        let zone = monitor.add(Zone::new(square, start));
+        let mission = MissionId::new();
+        let drone = DroneId::new("REAPER-01");
+        let inside = GeoPosition::new(34.05, 69.05, 3000.0);
+        let outside = GeoPosition::new(34.2, 69.05, 3000.0);
+
+        assert!(monitor.update(&drone, &mission, &outside, at(0)).is_empty());
+        assert!(monitor.stats(&zone.id, &mission, at(0)).is_none());
+
+        // Two visits: 60 s, then one still open
+        let entered = monitor.update(&drone, &mission, &inside, at(10));
+        assert_eq!(entered.len(), 1);
+        assert!(entered[0].event.dwell_seconds.is_none());
+        assert!(monitor.update(&drone, &mission, &inside, at(30)).is_empty());
+        let exited = monitor.update(&drone, &mission, &outside, at(70));
+        assert_eq!(exited[0].event.dwell_seconds, Some(60.0));
+        monitor.update(&drone, &mission, &inside, at(100));
+
+        let stats = monitor.stats(&zone.id, &mission, at(130)).unwrap();
+        assert_eq!((stats.drones_visited, stats.total_visits), (1, 2));
+        assert_eq!(stats.total_time_inside_seconds, 90.0);
+        assert!(stats.drones[0].inside);
+
+        // The persisted row gives the same figures; other missions start empty
+        let record = monitor.update(&drone, &mission, &outside, at(130)).remove(0).record;
+        assert_eq!((record.visits, record.time_inside_ms), (2, 90_000));
+        let restored = monitor.stats_from_records(&zone.id, &mission, &[record], at(500)).unwrap();
+        assert_eq!(restored.total_time_inside_seconds, 90.0);
+        assert!(!restored.drones[0].inside);
+        assert!(monitor.stats(&zone.id, &MissionId::new(), at(130)).is_none());
+
+        let reloaded = Zone::from_record(&zone.to_record()).unwrap();
+        assert_eq!(reloaded.area.vertices.len(), 4);
+        assert!(reloaded.area.contains(&inside));
+
+        monitor.remove(&zone.id);
+        assert!(monitor.stats(&zone.id, &mission, at(130)).is_none());
+    }
+
+    #[test]
+    fn test_open_visits_close_and_hydrate() {
+        let monitor = ZoneMonitor::new();
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let at = |s: i64| start + chrono::Duration::seconds(s);
+        let square = Geofence::new(
+            "Objective Bravo",
+            vec![
+                GeoPosition::new(34.0, 69.0, 0.0),
+                GeoPosition::new(34.0, 69.1, 0.0),
+                GeoPosition::new(34.1, 69.1, 0.0),
+                GeoPosition::new(34.1, 69.0, 0.0),
+            ],
+        );
+        let zone = monitor.add(Zone::new(square, start));
+        let mission = MissionId::new();
+        let inside = GeoPosition::new(34.05, 69.05, 3000.0);
+        let (alpha, bravo) = (DroneId::new("REAPER-01"), DroneId::new("REAPER-02"));
+        let record = monitor.update(&alpha, &mission, &inside, at(0)).remove(0).record;
+        monitor.update(&bravo, &mission, &inside, at(0));
+
+        // Eviction ends only that drone's visit
+        let closed = monitor.close_drone(&alpha, inside, at(40));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].event.dwell_seconds, Some(40.0));
+        assert_eq!(closed[0].record.last_exit, Some(at(40)));
+        let closed = monitor.close_mission(&mission, &HashMap::new(), at(50));
+        assert_eq!(closed.iter().map(|c| &c.event.drone_id).collect::<Vec<_>>(), [&bravo]);
+        assert!(monitor.close_mission(&mission, &HashMap::new(), at(60)).is_empty());
+        let stats = monitor.stats(&zone.id, &mission, at(500)).unwrap();
+        assert_eq!(stats.total_time_inside_seconds, 90.0);
+
+        // A fresh process picks up the persisted visit and counts on from it
+        let restarted = ZoneMonitor::new();
+        restarted.add(zone.clone());
+        restarted.hydrate(zone.id, &mission, &[record]);
+        assert!(restarted.update(&alpha, &mission, &inside, at(10)).is_empty());
+        let exited = restarted.update(&alpha, &mission, &GeoPosition::new(34.2, 69.05, 3000.0), at(30));
+        assert_eq!(exited[0].record.visits, 1);
+        assert_eq!(exited[0].record.time_inside_ms, 30_000);
+    }
+}
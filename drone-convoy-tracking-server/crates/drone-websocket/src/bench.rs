@@ -0,0 +1,497 @@
+//! WebSocket hub load testing
+//!
+//! Runs an in-process hub and server, connects N simulated clients over real
+//! TCP with a mix of subscription filters, publishes position events at a
+//! fixed rate and reports delivery latency and dropped frames per filter.
+//! Used by the `ws-bench` binary.
+
+use crate::{serve, WebSocketHub, WsError, WsResult};
+use drone_core::{ClientMessage, DroneId, Event, EventPayload, GeoPosition, ServerMessage, Telemetry};
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::{watch, Barrier};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// Publisher tick; events are emitted in bursts per tick to reach high rates
+const PUBLISH_TICK: Duration = Duration::from_millis(10);
+
+/// Time allowed for subscriptions to reach the hub before publishing starts
+const SUBSCRIBE_SETTLE: Duration = Duration::from_millis(200);
+
+/// Load test configuration
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// Number of simulated clients
+    pub clients: usize,
+    /// Number of distinct drones events are published for
+    pub drones: usize,
+    /// Events published per second (across all drones)
+    pub event_rate: u32,
+    /// How long to publish for
+    pub duration: Duration,
+    /// How long clients keep reading after publishing stops
+    pub drain: Duration,
+    /// Filters assigned to clients round-robin
+    pub filters: Vec<SubscriptionFilter>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            clients: 100,
+            drones: 12,
+            event_rate: 500,
+            duration: Duration::from_secs(10),
+            drain: Duration::from_secs(1),
+            filters: vec![
+                SubscriptionFilter::All,
+                SubscriptionFilter::Single,
+                SubscriptionFilter::Half,
+            ],
+        }
+    }
+}
+
+/// Subscription a simulated client sends after connecting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionFilter {
+    /// No filter, all drones
+    All,
+    /// One drone (rotates with the client index)
+    Single,
+    /// Every other drone
+    Half,
+}
+
+impl SubscriptionFilter {
+    /// Drones this filter selects for a given client, `None` meaning all
+    fn drone_ids(self, client: usize, drones: &[DroneId]) -> Option<Vec<DroneId>> {
+        match self {
+            Self::All => None,
+            Self::Single => Some(vec![drones[client % drones.len()].clone()]),
+            Self::Half => Some(drones.iter().step_by(2).cloned().collect()),
+        }
+    }
+}
+
+impl std::str::FromStr for SubscriptionFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            "single" => Ok(Self::Single),
+            "half" => Ok(Self::Half),
+            other => Err(format!("unknown filter '{}' (expected all, single or half)", other)),
+        }
+    }
+}
+
+/// What a single client saw
+#[derive(Debug, Default)]
+struct ClientOutcome {
+    /// Events received per drone
+    received: HashMap<DroneId, u64>,
+    /// Latencies of received events, in microseconds
+    latencies_us: Vec<u64>,
+}
+
+/// Results for one subscription filter
+#[derive(Debug, Clone)]
+pub struct FilterReport {
+    pub filter: SubscriptionFilter,
+    pub clients: usize,
+    /// Matching events the clients should have received
+    pub expected: u64,
+    /// Matching events the clients received
+    pub delivered: u64,
+    /// Matching events that never arrived
+    pub dropped: u64,
+    /// Events received for drones outside the filter
+    pub unfiltered: u64,
+    pub latency: LatencySummary,
+}
+
+/// Latency distribution in microseconds
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+impl LatencySummary {
+    fn from_samples(mut samples: Vec<u64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+            samples[rank.saturating_sub(1).min(samples.len() - 1)]
+        };
+
+        Self {
+            samples: samples.len(),
+            mean_us: samples.iter().sum::<u64>() / samples.len() as u64,
+            p50_us: percentile(50.0),
+            p90_us: percentile(90.0),
+            p99_us: percentile(99.0),
+            max_us: *samples.last().unwrap(),
+        }
+    }
+}
+
+/// Load test summary
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub config: BenchConfig,
+    /// Clients that connected and subscribed
+    pub connected: usize,
+    /// Clients that failed to connect
+    pub failed: usize,
+    pub events_published: u64,
+    /// Achieved publish rate (events/s)
+    pub publish_rate: f64,
+    pub overall: LatencySummary,
+    pub filters: Vec<FilterReport>,
+}
+
+impl BenchReport {
+    /// Total matching events that never arrived, across all clients
+    pub fn dropped(&self) -> u64 {
+        self.filters.iter().map(|f| f.dropped).sum()
+    }
+
+    /// Total matching events delivered, across all clients
+    pub fn delivered(&self) -> u64 {
+        self.filters.iter().map(|f| f.delivered).sum()
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |us: u64| us as f64 / 1000.0;
+
+        writeln!(f, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")?;
+        writeln!(f, "WebSocket hub load test")?;
+        writeln!(f, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")?;
+        writeln!(
+            f,
+            "clients: {} connected, {} failed | drones: {} | duration: {:.1}s",
+            self.connected,
+            self.failed,
+            self.config.drones,
+            self.config.duration.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "published: {} events ({:.0}/s, target {}/s)",
+            self.events_published, self.publish_rate, self.config.event_rate
+        )?;
+        let expected = self.delivered() + self.dropped();
+        let drop_pct = if expected > 0 {
+            self.dropped() as f64 * 100.0 / expected as f64
+        } else {
+            0.0
+        };
+        writeln!(
+            f,
+            "delivered: {} | dropped: {} ({:.2}%)",
+            self.delivered(),
+            self.dropped(),
+            drop_pct
+        )?;
+        writeln!(
+            f,
+            "latency ms: mean {:.2} | p50 {:.2} | p90 {:.2} | p99 {:.2} | max {:.2}",
+            ms(self.overall.mean_us),
+            ms(self.overall.p50_us),
+            ms(self.overall.p90_us),
+            ms(self.overall.p99_us),
+            ms(self.overall.max_us)
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<8} {:>7} {:>10} {:>10} {:>8} {:>10} {:>8} {:>8}",
+            "filter", "clients", "expected", "delivered", "dropped", "unfiltered", "p50 ms", "p99 ms"
+        )?;
+        for report in &self.filters {
+            writeln!(
+                f,
+                "{:<8} {:>7} {:>10} {:>10} {:>8} {:>10} {:>8.2} {:>8.2}",
+                format!("{:?}", report.filter).to_lowercase(),
+                report.clients,
+                report.expected,
+                report.delivered,
+                report.dropped,
+                report.unfiltered,
+                ms(report.latency.p50_us),
+                ms(report.latency.p99_us)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run a load test against an in-process hub
+pub async fn run(config: BenchConfig) -> WsResult<BenchReport> {
+    if config.clients == 0 || config.drones == 0 || config.filters.is_empty() {
+        return Err(WsError::Bench(
+            "bench needs at least one client, drone and filter".to_string(),
+        ));
+    }
+
+    let hub = Arc::new(WebSocketHub::new());
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("ws://{}", listener.local_addr()?);
+    let server = tokio::spawn(serve(hub.clone(), listener));
+
+    let drones: Arc<Vec<DroneId>> = Arc::new(
+        (1..=config.drones)
+            .map(|i| DroneId::new(format!("BENCH-{:03}", i)))
+            .collect(),
+    );
+
+    // Every client plus the publisher waits here before publishing starts
+    let ready = Arc::new(Barrier::new(config.clients + 1));
+    let (stop_tx, stop_rx) = watch::channel(false);
+
+    let mut handles = Vec::with_capacity(config.clients);
+    for client in 0..config.clients {
+        let filter = config.filters[client % config.filters.len()];
+        let subscription = filter.drone_ids(client, &drones);
+        let url = url.clone();
+        let ready = ready.clone();
+        let stop_rx = stop_rx.clone();
+        handles.push(tokio::spawn(async move {
+            let outcome = run_client(&url, subscription.clone(), ready, stop_rx).await;
+            (filter, subscription, outcome)
+        }));
+    }
+
+    ready.wait().await;
+    tokio::time::sleep(SUBSCRIBE_SETTLE).await;
+
+    let (published, elapsed) = publish(&hub, &drones, config.event_rate, config.duration).await;
+    tokio::time::sleep(config.drain).await;
+    let _ = stop_tx.send(true);
+
+    let mut connected = 0;
+    let mut failed = 0;
+    let mut all_latencies = Vec::new();
+    let mut per_filter: HashMap<SubscriptionFilter, (FilterReport, Vec<u64>)> = HashMap::new();
+
+    for handle in handles {
+        let (filter, subscription, outcome) = handle
+            .await
+            .map_err(|e| WsError::Bench(format!("bench client panicked: {}", e)))?;
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(_) => {
+                failed += 1;
+                continue;
+            }
+        };
+        connected += 1;
+
+        let wanted: Option<HashSet<&DroneId>> = subscription.as_ref().map(|ids| ids.iter().collect());
+        let matches = |id: &DroneId| wanted.as_ref().is_none_or(|w| w.contains(id));
+
+        let expected: u64 = published.iter().filter(|(id, _)| matches(id)).map(|(_, n)| n).sum();
+        let mut delivered = 0;
+        let mut unfiltered = 0;
+        for (id, count) in &outcome.received {
+            if matches(id) {
+                delivered += count;
+            } else {
+                unfiltered += count;
+            }
+        }
+
+        let (report, latencies) = per_filter.entry(filter).or_insert_with(|| {
+            (
+                FilterReport {
+                    filter,
+                    clients: 0,
+                    expected: 0,
+                    delivered: 0,
+                    dropped: 0,
+                    unfiltered: 0,
+                    latency: LatencySummary::default(),
+                },
+                Vec::new(),
+            )
+        });
+        report.clients += 1;
+        report.expected += expected;
+        report.delivered += delivered.min(expected);
+        report.dropped += expected.saturating_sub(delivered);
+        report.unfiltered += unfiltered;
+        latencies.extend_from_slice(&outcome.latencies_us);
+        all_latencies.extend(outcome.latencies_us);
+    }
+
+    server.abort();
+
+    let mut filters: Vec<FilterReport> = per_filter
+        .into_values()
+        .map(|(mut report, latencies)| {
+            report.latency = LatencySummary::from_samples(latencies);
+            report
+        })
+        .collect();
+    filters.sort_by_key(|r| config.filters.iter().position(|f| *f == r.filter));
+
+    let events_published = published.values().sum();
+    Ok(BenchReport {
+        publish_rate: events_published as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        config,
+        connected,
+        failed,
+        events_published,
+        overall: LatencySummary::from_samples(all_latencies),
+        filters,
+    })
+}
+
+/// Publish position events round-robin across drones; returns counts per drone
+async fn publish(
+    hub: &WebSocketHub,
+    drones: &[DroneId],
+    event_rate: u32,
+    duration: Duration,
+) -> (HashMap<DroneId, u64>, Duration) {
+    let per_tick = event_rate as f64 * PUBLISH_TICK.as_secs_f64();
+    let mut published: HashMap<DroneId, u64> = drones.iter().map(|id| (id.clone(), 0)).collect();
+    let mut interval = tokio::time::interval(PUBLISH_TICK);
+    let mut budget = 0.0;
+    let mut next = 0;
+
+    let started = Instant::now();
+    while started.elapsed() < duration {
+        interval.tick().await;
+        budget += per_tick;
+        while budget >= 1.0 {
+            let drone_id = drones[next % drones.len()].clone();
+            next += 1;
+            budget -= 1.0;
+            *published.entry(drone_id.clone()).or_default() += 1;
+            hub.broadcast(Event::drone_position_updated(
+                drone_id,
+                GeoPosition::default(),
+                Telemetry::default(),
+            ))
+            .await;
+        }
+    }
+
+    (published, started.elapsed())
+}
+
+/// Connect, subscribe and record received events until told to stop
+async fn run_client(
+    url: &str,
+    subscription: Option<Vec<DroneId>>,
+    ready: Arc<Barrier>,
+    mut stop_rx: watch::Receiver<bool>,
+) -> WsResult<ClientOutcome> {
+    let connection = connect_async(url).await;
+
+    // Always reach the barrier so a failed client can't stall the run
+    let (mut ws_sender, mut ws_receiver) = match connection {
+        Ok((stream, _)) => stream.split(),
+        Err(e) => {
+            ready.wait().await;
+            return Err(e.into());
+        }
+    };
+
+    let subscribe = serde_json::to_string(&ClientMessage::Subscribe {
+        drone_ids: subscription,
+        mission_ids: None,
+        event_types: None,
+    })?;
+    let subscribed = ws_sender.send(Message::Text(subscribe.into())).await;
+    ready.wait().await;
+    subscribed?;
+
+    let mut outcome = ClientOutcome::default();
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => break,
+            msg = ws_receiver.next() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.into()),
+                };
+                if let Ok(ServerMessage::Event(event)) = serde_json::from_str(&text) {
+                    let latency = (Utc::now() - event.timestamp).num_microseconds().unwrap_or(0);
+                    outcome.latencies_us.push(latency.max(0) as u64);
+                    if let EventPayload::DronePosition(position) = event.payload {
+                        *outcome.received.entry(position.drone_id).or_default() += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = ws_sender.send(Message::Close(None)).await;
+    Ok(outcome)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let summary = LatencySummary::from_samples((1..=100).collect());
+        assert_eq!(summary.samples, 100);
+        assert_eq!(summary.p50_us, 50);
+        assert_eq!(summary.p90_us, 90);
+        assert_eq!(summary.p99_us, 99);
+        assert_eq!(summary.max_us, 100);
+        assert_eq!(LatencySummary::from_samples(Vec::new()).samples, 0);
+    }
+
+    #[tokio::test]
+    async fn test_small_run_accounts_for_every_event() {
+        let report = run(BenchConfig {
+            clients: 6,
+            drones: 4,
+            event_rate: 200,
+            duration: Duration::from_millis(300),
+            drain: Duration::from_millis(300),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.connected, 6);
+        assert_eq!(report.failed, 0);
+        assert!(report.events_published > 0);
+        assert_eq!(report.filters.len(), 3);
+        for filter in &report.filters {
+            assert_eq!(filter.delivered + filter.dropped, filter.expected);
+        }
+        assert!(report.delivered() > 0);
+        assert!(report.to_string().contains("WebSocket hub load test"));
+    }
+}
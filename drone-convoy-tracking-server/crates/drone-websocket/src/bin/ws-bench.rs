@@ -0,0 +1,83 @@
+//! # WebSocket Hub Load Test
+//!
+//! Spawns an in-process hub and N simulated clients and prints a delivery
+//! latency / dropped frame report.
+//!
+//! ```text
+//! cargo run --release -p drone-websocket --bin ws-bench -- \
+//!     --clients 500 --drones 12 --rate 1000 --duration 30 --filters all,single,half
+//! ```
+
+use drone_websocket::bench::{self, BenchConfig, SubscriptionFilter};
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: ws-bench [OPTIONS]
+
+Options:
+  --clients <N>      Simulated clients (default 100)
+  --drones <N>       Distinct drones to publish for (default 12)
+  --rate <N>         Events per second across all drones (default 500)
+  --duration <SECS>  Publish duration in seconds (default 10)
+  --drain <SECS>     Time clients keep reading after publishing (default 1)
+  --filters <LIST>   Comma-separated filters assigned round-robin: all, single, half
+  -h, --help         Print this help";
+
+#[tokio::main]
+async fn main() {
+    let config = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match bench::run(config).await {
+        Ok(report) => print!("{}", report),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse command line flags; `Ok(None)` means help was requested
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<BenchConfig>, String> {
+    let mut config = BenchConfig::default();
+
+    while let Some(flag) = args.next() {
+        if flag == "-h" || flag == "--help" {
+            return Ok(None);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("invalid value for {}: {}", flag, value))
+        };
+
+        match flag.as_str() {
+            "--clients" => config.clients = number()? as usize,
+            "--drones" => config.drones = number()? as usize,
+            "--rate" => config.event_rate = number()? as u32,
+            "--duration" => config.duration = Duration::from_secs(number()?),
+            "--drain" => config.drain = Duration::from_secs(number()?),
+            "--filters" => {
+                config.filters = value
+                    .split(',')
+                    .map(|f| f.trim().parse::<SubscriptionFilter>())
+                    .collect::<Result<_, _>>()?;
+            }
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+
+    Ok(Some(config))
+}
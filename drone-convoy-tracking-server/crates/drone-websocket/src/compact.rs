@@ -0,0 +1,84 @@
+//! Compact position mode
+//!
+//! Clients connecting with `?compact=true` get every position in server
+//! messages as a fixed-point `[lat_e6, lng_e6, alt_m]` array (see
+//! [`CompactPosition`]) instead of a `{latitude, longitude, altitude}`
+//! object, which roughly halves the size of position updates. Everything
+//! else in the message is unchanged.
+
+use drone_core::{CompactPosition, GeoPosition, ServerMessage};
+
+use serde_json::Value;
+
+/// Whether the handshake query asks for compact positions
+pub fn requested(query: Option<&str>) -> bool {
+    query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter_map(|pair| pair.split_once('='))
+        .any(|(name, value)| name == "compact" && matches!(value, "1" | "true"))
+}
+
+/// Serialize a message, with compact positions when `compact` is set
+pub fn to_json(message: &ServerMessage, compact: bool) -> serde_json::Result<String> {
+    if !compact {
+        return serde_json::to_string(message);
+    }
+    let mut value = serde_json::to_value(message)?;
+    compact_positions(&mut value);
+    serde_json::to_string(&value)
+}
+
+/// Replace every serialized `GeoPosition` in `value`
+fn compact_positions(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let is_position = map.len() == 3
+                && ["latitude", "longitude", "altitude"].iter().all(|key| map.get(*key).is_some_and(Value::is_number));
+            if is_position {
+                if let Ok(position) = serde_json::from_value::<GeoPosition>(Value::Object(std::mem::take(map))) {
+                    *value = serde_json::to_value(CompactPosition::from(position)).unwrap_or(Value::Null);
+                }
+                return;
+            }
+            map.values_mut().for_each(compact_positions);
+        }
+        Value::Array(items) => items.iter_mut().for_each(compact_positions),
+        _ => {}
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use drone_core::{DroneId, Event, Telemetry};
+
+    #[test]
+    fn test_positions_become_fixed_point_arrays() {
+        assert!(requested(Some("api_key=k&compact=true")));
+        assert!(!requested(Some("compact=0")));
+        assert!(!requested(None));
+
+        let event = Event::drone_position_updated(
+            DroneId::new("REAPER-01"),
+            GeoPosition::new(34.5553219, 69.2075004, 3000.4),
+            Telemetry::default(),
+        );
+        let message = ServerMessage::Event(event);
+        let full = to_json(&message, false).unwrap();
+        let compact = to_json(&message, true).unwrap();
+        assert!(compact.len() < full.len());
+        assert!(compact.contains("[34555322,69207500,3000]"));
+        assert!(!compact.contains("latitude"));
+
+        // Apart from positions the message is unchanged
+        let value: Value = serde_json::from_str(&compact).unwrap();
+        let original: Value = serde_json::from_str(&full).unwrap();
+        assert_eq!(value["type"], original["type"]);
+        assert_eq!(value["payload"]["event_type"], original["payload"]["event_type"]);
+    }
+}
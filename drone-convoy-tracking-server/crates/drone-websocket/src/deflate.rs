@@ -0,0 +1,498 @@
+//! permessage-deflate (RFC 7692)
+//!
+//! tungstenite negotiates no extensions and rejects frames with RSV1 set, so
+//! the extension is handled around it: the accept callback answers the
+//! client's offer, outgoing messages above a size threshold are compressed
+//! into RSV1 frames, and [`InflateStream`] sits under tungstenite and turns
+//! compressed client frames back into plain ones. Both directions run
+//! without context takeover, so every message is compressed on its own.
+//! `negotiate`, `compress` and `decompress` do not depend on the transport
+//! and can serve other WebSocket routes.
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use serde::Serialize;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+use tokio_tungstenite::tungstenite::protocol::frame::Frame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Extension name in `Sec-WebSocket-Extensions`
+pub const EXTENSION: &str = "permessage-deflate";
+
+/// Largest message a compressed frame may inflate to
+pub const MAX_INFLATED_BYTES: usize = 16 * 1024 * 1024;
+
+/// Tail every sync-flushed deflate block ends with; stripped on the wire
+const SYNC_TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// WebSocket compression settings
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    /// Accept permessage-deflate offers from clients
+    pub enabled: bool,
+    /// Messages smaller than this are sent uncompressed (bytes)
+    pub min_size: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_size: 1024,
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Load from `WS_COMPRESSION` and `WS_COMPRESSION_MIN_BYTES`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("WS_COMPRESSION")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(defaults.enabled),
+            min_size: std::env::var("WS_COMPRESSION_MIN_BYTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.min_size),
+        }
+    }
+}
+
+/// Response to a client's `Sec-WebSocket-Extensions` header, if one of its
+/// permessage-deflate offers can be accepted
+///
+/// Offers asking for a server window below 15 bits are declined, since the
+/// compressor always uses the full window.
+pub fn negotiate(offers: &str) -> Option<&'static str> {
+    offers.split(',').find_map(|offer| {
+        let mut parts = offer.split(';');
+        let name = parts.next()?.trim();
+        (name.eq_ignore_ascii_case(EXTENSION) && parts.all(acceptable_param))
+            .then_some("permessage-deflate; server_no_context_takeover; client_no_context_takeover")
+    })
+}
+
+/// Whether an offer parameter is one this server can honour
+fn acceptable_param(param: &str) -> bool {
+    let (name, value) = match param.split_once('=') {
+        Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+        None => (param.trim(), None),
+    };
+    match name {
+        "" | "server_no_context_takeover" | "client_no_context_takeover" => value.is_none(),
+        "server_max_window_bits" => value == Some("15"),
+        "client_max_window_bits" => {
+            value.is_none_or(|bits| bits.parse::<u8>().is_ok_and(|bits| (8..=15).contains(&bits)))
+        }
+        _ => false,
+    }
+}
+
+/// Compress a message payload (without the trailing sync marker)
+pub fn compress(payload: &[u8]) -> Vec<u8> {
+    let mut compressor = Compress::new(Compression::default(), false);
+    let mut out = Vec::with_capacity(payload.len() / 2 + 64);
+    loop {
+        let consumed = compressor.total_in() as usize;
+        out.reserve(payload.len().saturating_sub(consumed) / 2 + 64);
+        compressor
+            .compress_vec(&payload[consumed..], &mut out, FlushCompress::Sync)
+            .expect("in-memory deflate cannot fail");
+        if compressor.total_in() as usize == payload.len() && out.ends_with(&SYNC_TAIL) {
+            break;
+        }
+    }
+    out.truncate(out.len() - SYNC_TAIL.len());
+    out
+}
+
+/// Inflate a compressed message payload, refusing output above `max_size`
+pub fn decompress(payload: &[u8], max_size: usize) -> io::Result<Vec<u8>> {
+    let mut input = Vec::with_capacity(payload.len() + SYNC_TAIL.len());
+    input.extend_from_slice(payload);
+    input.extend_from_slice(&SYNC_TAIL);
+
+    let mut decompressor = Decompress::new(false);
+    let mut out = Vec::with_capacity(payload.len() * 4);
+    loop {
+        let consumed = decompressor.total_in() as usize;
+        out.reserve(16 * 1024);
+        let status = decompressor
+            .decompress_vec(&input[consumed..], &mut out, FlushDecompress::Sync)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if out.len() > max_size {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "inflated message too large"));
+        }
+        let done = decompressor.total_in() as usize == input.len();
+        if done || status == Status::StreamEnd {
+            return Ok(out);
+        }
+        if status == Status::BufError && out.len() < out.capacity() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated deflate data"));
+        }
+    }
+}
+
+// ============================================================================
+// METRICS
+// ============================================================================
+
+/// Compression counters across all connections
+#[derive(Debug, Default)]
+pub struct CompressionMetrics {
+    negotiated: AtomicU64,
+    compressed: AtomicU64,
+    skipped: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+/// Snapshot of [`CompressionMetrics`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CompressionStats {
+    /// Connections that negotiated permessage-deflate
+    pub negotiated_connections: u64,
+    /// Messages sent compressed
+    pub compressed_messages: u64,
+    /// Messages below the size threshold, sent uncompressed
+    pub skipped_messages: u64,
+    /// Size of compressed messages before compression
+    pub bytes_in: u64,
+    /// Size of compressed messages on the wire
+    pub bytes_out: u64,
+}
+
+impl CompressionStats {
+    /// Wire size over original size for compressed messages (1.0 if none)
+    pub fn ratio(&self) -> f64 {
+        if self.bytes_in == 0 {
+            return 1.0;
+        }
+        self.bytes_out as f64 / self.bytes_in as f64
+    }
+}
+
+impl CompressionMetrics {
+    pub fn record_negotiated(&self) {
+        self.negotiated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> CompressionStats {
+        CompressionStats {
+            negotiated_connections: self.negotiated.load(Ordering::Relaxed),
+            compressed_messages: self.compressed.load(Ordering::Relaxed),
+            skipped_messages: self.skipped.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Text message for a connection, compressed when `deflate` was
+    /// negotiated and the text reaches `min_size`
+    pub fn text_message(&self, text: String, deflate: bool, min_size: usize) -> Message {
+        if !deflate {
+            return Message::Text(text.into());
+        }
+        if text.len() < min_size {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return Message::Text(text.into());
+        }
+
+        let compressed = compress(text.as_bytes());
+        self.compressed.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(text.len() as u64, Ordering::Relaxed);
+        self.bytes_out.fetch_add(compressed.len() as u64, Ordering::Relaxed);
+
+        let mut frame = Frame::message(compressed, OpCode::Data(Data::Text), true);
+        frame.header_mut().rsv1 = true;
+        Message::Frame(frame)
+    }
+}
+
+// ============================================================================
+// INFLATING STREAM
+// ============================================================================
+
+/// A compressed message being reassembled from fragments
+struct Pending {
+    opcode: u8,
+    masked: bool,
+    payload: Vec<u8>,
+}
+
+/// Byte stream adapter that inflates compressed WebSocket frames
+///
+/// Until `enabled` is set (by the accept callback once the extension is
+/// negotiated) bytes pass through untouched. After that, incoming frames
+/// are parsed: compressed messages, fragmented or not, are replaced by a
+/// single uncompressed frame (masked with a zero key if the original was
+/// masked), and everything else passes through as is. Writes are never
+/// touched.
+pub struct InflateStream<S> {
+    inner: S,
+    enabled: Arc<AtomicBool>,
+    /// Bytes read but not yet parsed into frames
+    raw: Vec<u8>,
+    /// Bytes ready for the reader
+    out: Vec<u8>,
+    pending: Option<Pending>,
+}
+
+impl<S> InflateStream<S> {
+    pub fn new(inner: S, enabled: Arc<AtomicBool>) -> Self {
+        Self {
+            inner,
+            enabled,
+            raw: Vec::new(),
+            out: Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Move complete frames from `raw` to `out`
+    fn process(&mut self) -> io::Result<()> {
+        while let Some(header) = FrameHeader::parse(&self.raw)? {
+            let total = header.header_len + header.payload_len;
+            let frame: Vec<u8> = self.raw.drain(..total).collect();
+            let is_control = header.opcode & 0x08 != 0;
+
+            if is_control || (!header.rsv1 && (header.opcode != 0 || self.pending.is_none())) {
+                self.out.extend_from_slice(&frame);
+                continue;
+            }
+
+            let mut payload = frame[header.header_len..].to_vec();
+            if let Some(key) = header.mask {
+                payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= key[i % 4]);
+            }
+
+            let pending = match self.pending.take() {
+                Some(mut pending) if header.opcode == 0 => {
+                    pending.payload.extend_from_slice(&payload);
+                    pending
+                }
+                Some(_) => return Err(invalid("new message started inside a fragmented one")),
+                None => Pending {
+                    opcode: header.opcode,
+                    masked: header.mask.is_some(),
+                    payload,
+                },
+            };
+            if pending.payload.len() > MAX_INFLATED_BYTES {
+                return Err(invalid("compressed message too large"));
+            }
+            if !header.fin {
+                self.pending = Some(pending);
+                continue;
+            }
+
+            let inflated = decompress(&pending.payload, MAX_INFLATED_BYTES)?;
+            write_frame(&mut self.out, pending.opcode, pending.masked, &inflated);
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InflateStream<S> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.out.is_empty() {
+                let n = this.out.len().min(buf.remaining());
+                buf.put_slice(&this.out[..n]);
+                this.out.drain(..n);
+                return Poll::Ready(Ok(()));
+            }
+            if !this.enabled.load(Ordering::Acquire) && this.raw.is_empty() {
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            }
+
+            let mut chunk = [0u8; 8192];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf) {
+                Poll::Ready(Ok(())) if chunk_buf.filled().is_empty() => {
+                    // EOF: hand over whatever is left unparsed
+                    this.out.append(&mut this.raw);
+                    if this.out.is_empty() {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Poll::Ready(Ok(())) => {
+                    this.raw.extend_from_slice(chunk_buf.filled());
+                    this.process()?;
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InflateStream<S> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// The parts of a frame header the adapter needs
+struct FrameHeader {
+    fin: bool,
+    rsv1: bool,
+    opcode: u8,
+    mask: Option<[u8; 4]>,
+    header_len: usize,
+    payload_len: usize,
+}
+
+impl FrameHeader {
+    /// Header of the first frame in `raw`, once the whole frame has arrived
+    fn parse(raw: &[u8]) -> io::Result<Option<Self>> {
+        let [b0, b1, ..] = *raw else {
+            return Ok(None);
+        };
+        let masked = b1 & 0x80 != 0;
+        let (len_bytes, short_len) = match b1 & 0x7f {
+            126 => (2, None),
+            127 => (8, None),
+            len => (0, Some(len as usize)),
+        };
+        let header_len = 2 + len_bytes + if masked { 4 } else { 0 };
+        if raw.len() < header_len {
+            return Ok(None);
+        }
+
+        let payload_len = match short_len {
+            Some(len) => len,
+            None => raw[2..2 + len_bytes].iter().fold(0u64, |acc, b| acc << 8 | *b as u64) as usize,
+        };
+        if payload_len > MAX_INFLATED_BYTES {
+            return Err(invalid("frame too large"));
+        }
+        if raw.len() < header_len + payload_len {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            fin: b0 & 0x80 != 0,
+            rsv1: b0 & 0x40 != 0,
+            opcode: b0 & 0x0f,
+            mask: masked.then(|| raw[header_len - 4..header_len].try_into().expect("4 bytes")),
+            header_len,
+            payload_len,
+        }))
+    }
+}
+
+/// Append a final, uncompressed frame
+fn write_frame(out: &mut Vec<u8>, opcode: u8, masked: bool, payload: &[u8]) {
+    let mask_bit = if masked { 0x80 } else { 0 };
+    out.push(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => out.push(mask_bit | len as u8),
+        len if len <= u16::MAX as usize => {
+            out.push(mask_bit | 126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(mask_bit | 127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    if masked {
+        // XOR with a zero key leaves the payload as is
+        out.extend_from_slice(&[0; 4]);
+    }
+    out.extend_from_slice(payload);
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("permessage-deflate: {}", reason))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// A masked client frame
+    fn client_frame(fin: bool, rsv1: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let key = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![(fin as u8) << 7 | (rsv1 as u8) << 6 | opcode, 0x80 | 126];
+        frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&key);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ key[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn test_negotiate_and_roundtrip() {
+        let accepted = "permessage-deflate; server_no_context_takeover; client_no_context_takeover";
+        assert_eq!(negotiate("permessage-deflate; client_max_window_bits"), Some(accepted));
+        assert_eq!(negotiate("permessage-deflate; server_max_window_bits=10, permessage-deflate"), Some(accepted));
+        assert_eq!(negotiate("permessage-deflate; server_max_window_bits=10"), None);
+        assert_eq!(negotiate("x-webkit-deflate-frame"), None);
+
+        let text = r#"{"type":"Event","data":{"drone_id":"REAPER-01"}}"#.repeat(50);
+        let compressed = compress(text.as_bytes());
+        assert!(compressed.len() < text.len() / 5);
+        assert_eq!(decompress(&compressed, MAX_INFLATED_BYTES).unwrap(), text.as_bytes());
+        assert!(decompress(&compressed, 100).is_err());
+
+        let metrics = CompressionMetrics::default();
+        assert!(matches!(metrics.text_message("small".into(), true, 1024), Message::Text(_)));
+        assert!(matches!(metrics.text_message(text.clone(), false, 1024), Message::Text(_)));
+        match metrics.text_message(text, true, 1024) {
+            Message::Frame(frame) => assert!(frame.header().rsv1),
+            other => panic!("expected a compressed frame, got {:?}", other),
+        }
+        let stats = metrics.snapshot();
+        assert_eq!((stats.compressed_messages, stats.skipped_messages), (1, 1));
+        assert!(stats.ratio() < 0.2);
+    }
+
+    #[tokio::test]
+    async fn test_inflate_stream_rewrites_compressed_frames() {
+        let text = b"{\"type\":\"RequestState\"}".repeat(20);
+        let compressed = compress(&text);
+        let (first, rest) = compressed.split_at(compressed.len() / 2);
+
+        let mut wire = Vec::new();
+        wire.extend(client_frame(false, true, 0x1, first));
+        wire.extend(client_frame(true, false, 0x9, b"ping"));
+        wire.extend(client_frame(true, false, 0x0, rest));
+        wire.extend(client_frame(true, false, 0x1, b"plain"));
+
+        let mut stream = InflateStream::new(wire.as_slice(), Arc::new(AtomicBool::new(true)));
+        let mut rewritten = Vec::new();
+        stream.read_to_end(&mut rewritten).await.unwrap();
+
+        // The interleaved ping, then the reassembled message, then the plain one
+        let ping = client_frame(true, false, 0x9, b"ping");
+        assert_eq!(&rewritten[..ping.len()], ping.as_slice());
+        let message = &rewritten[ping.len()..];
+        let header = FrameHeader::parse(message).unwrap().unwrap();
+        assert!(header.fin && !header.rsv1);
+        assert_eq!((header.opcode, header.mask), (0x1, Some([0; 4])));
+        let end = header.header_len + header.payload_len;
+        assert_eq!(&message[header.header_len..end], text.as_slice());
+        assert_eq!(&message[end..], client_frame(true, false, 0x1, b"plain").as_slice());
+    }
+}
@@ -22,6 +22,12 @@ pub enum WsError {
 
     #[error("Broadcast error: {0}")]
     Broadcast(String),
+
+    #[error("Socket.IO error: {0}")]
+    SocketIo(#[from] crate::socketio::PacketError),
+
+    #[error("Benchmark error: {0}")]
+    Bench(String),
 }
 
 pub type WsResult<T> = Result<T, WsError>;
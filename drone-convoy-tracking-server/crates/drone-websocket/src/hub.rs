@@ -2,20 +2,40 @@
 //!
 //! Manages all connected WebSocket clients and handles message broadcasting.
 
-use drone_core::{DroneCommand, DroneId, Event};
+use crate::deflate::{CompressionConfig, CompressionMetrics, CompressionStats};
+use crate::ratelimit::{
+    ClientLimiter, ClientRole, MessageKind, RateLimitConfig, ThrottleMetrics, Throttled, ThrottledCount,
+};
+use crate::presence::PresenceConfig;
+use crate::socketio::SocketIoConfig;
+use drone_core::{
+    DroneCommand, DroneId, Event, EventFilter, EventPayload, EventType, LatencyHop, MissionId, OperatorPresence,
+    SubsystemHealth, TenantId,
+};
 
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 /// Broadcast channel capacity
 const BROADCAST_CAPACITY: usize = 1024;
 
+/// Callback invoked for drone commands received from clients, with the
+/// sending client's tenant
+type CommandHandler = Box<dyn Fn(Option<TenantId>, DroneCommand) + Send + Sync>;
+
+/// Maps a connecting client's API key to its tenant
+type TenantResolver = Box<dyn Fn(&str) -> Option<TenantId> + Send + Sync>;
+
+/// Callback receiving how long position updates of a tenant's fleet spent
+/// on a hop
+type LatencyObserver = Box<dyn Fn(LatencyHop, Duration) + Send + Sync>;
+
 /// WebSocket connection hub
 pub struct WebSocketHub {
     /// Broadcast sender for events
@@ -25,16 +45,38 @@ pub struct WebSocketHub {
     /// Total message count
     message_count: AtomicUsize,
     /// Command handler callback
-    command_handler: RwLock<Option<Box<dyn Fn(DroneCommand) + Send + Sync>>>,
+    command_handler: RwLock<Option<CommandHandler>>,
+    /// Latency callbacks by tenant (`None` in single-tenant deployments)
+    latency_observers: DashMap<Option<TenantId>, LatencyObserver>,
+    /// Set once shutdown starts; connections close and the listener stops
+    shutdown_tx: watch::Sender<bool>,
+    /// permessage-deflate settings
+    compression: CompressionConfig,
+    /// Compression counters across connections
+    compression_metrics: CompressionMetrics,
+    /// Set in multi-tenant deployments; connections must then present an API key
+    tenant_resolver: Option<TenantResolver>,
+    /// Inbound message limits and command quotas
+    rate_limits: RateLimitConfig,
+    /// Socket.IO endpoint settings
+    socketio: SocketIoConfig,
+    /// Operator identities for presence
+    presence: PresenceConfig,
+    /// Dropped-message counters across connections
+    throttle_metrics: ThrottleMetrics,
+    /// Start time and failed connections, sends and receives
+    health: SubsystemHealth,
 }
 
 /// State for a connected client
 #[derive(Debug)]
 struct ClientState {
-    /// Subscribed drone IDs (None = all)
-    subscriptions: Option<HashSet<DroneId>>,
-    /// Connection timestamp
-    connected_at: chrono::DateTime<chrono::Utc>,
+    /// Drone, mission and event type subscriptions
+    filter: EventFilter,
+    /// Rate limit buckets and role
+    limiter: ClientLimiter,
+    /// Operator, connection time and current focus, shared with other clients
+    presence: OperatorPresence,
 }
 
 impl WebSocketHub {
@@ -47,26 +89,190 @@ impl WebSocketHub {
             clients: DashMap::new(),
             message_count: AtomicUsize::new(0),
             command_handler: RwLock::new(None),
+            latency_observers: DashMap::new(),
+            shutdown_tx: watch::channel(false).0,
+            compression: CompressionConfig::default(),
+            compression_metrics: CompressionMetrics::default(),
+            tenant_resolver: None,
+            rate_limits: RateLimitConfig::default(),
+            socketio: SocketIoConfig::default(),
+            presence: PresenceConfig::default(),
+            throttle_metrics: ThrottleMetrics::default(),
+            health: SubsystemHealth::default(),
         }
     }
 
+    /// Use these permessage-deflate settings for new connections
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compression = config;
+        self
+    }
+
+    /// Require an API key on every connection and scope each client to the
+    /// tenant it resolves to
+    pub fn with_tenant_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<TenantId> + Send + Sync + 'static,
+    {
+        self.tenant_resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Use these inbound message limits and role quotas for new connections
+    pub fn with_rate_limits(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limits = config;
+        self
+    }
+
+    /// Serve Socket.IO clients with these settings
+    pub fn with_socketio(mut self, config: SocketIoConfig) -> Self {
+        self.socketio = config;
+        self
+    }
+
+    /// Identify operators by API key for presence
+    pub fn with_presence(mut self, config: PresenceConfig) -> Self {
+        self.presence = config;
+        self
+    }
+
+    pub fn presence_config(&self) -> &PresenceConfig {
+        &self.presence
+    }
+
+    pub fn socketio_config(&self) -> &SocketIoConfig {
+        &self.socketio
+    }
+
+    pub fn rate_limits(&self) -> &RateLimitConfig {
+        &self.rate_limits
+    }
+
+    pub fn requires_tenant(&self) -> bool {
+        self.tenant_resolver.is_some()
+    }
+
+    /// Tenant for an API key; `None` if the key is unknown or the hub is single-tenant
+    pub fn resolve_tenant(&self, api_key: &str) -> Option<TenantId> {
+        self.tenant_resolver.as_ref().and_then(|resolve| resolve(api_key))
+    }
+
+    pub fn compression_config(&self) -> &CompressionConfig {
+        &self.compression
+    }
+
+    pub(crate) fn compression_metrics(&self) -> &CompressionMetrics {
+        &self.compression_metrics
+    }
+
+    /// Compression counters across all connections
+    pub fn compression_stats(&self) -> CompressionStats {
+        self.compression_metrics.snapshot()
+    }
+
+    /// When the hub started and how many connections, sends or receives failed
+    pub fn health(&self) -> &SubsystemHealth {
+        &self.health
+    }
+
     /// Register a new client and return a broadcast receiver
     pub fn register_client(&self, client_id: Uuid) -> broadcast::Receiver<Event> {
+        self.register_tenant_client(client_id, None)
+    }
+
+    /// Register a client that only ever receives `tenant`'s events
+    pub fn register_tenant_client(&self, client_id: Uuid, tenant: Option<TenantId>) -> broadcast::Receiver<Event> {
+        self.register_client_with_role(client_id, tenant, self.rate_limits.default_role)
+    }
+
+    /// Register a client whose messages are limited by `role`'s quotas
+    pub fn register_client_with_role(
+        &self,
+        client_id: Uuid,
+        tenant: Option<TenantId>,
+        role: ClientRole,
+    ) -> broadcast::Receiver<Event> {
+        self.register_operator(client_id, tenant, role, None)
+    }
+
+    /// Register an operator's console and announce it to the other clients
+    pub fn register_operator(
+        &self,
+        client_id: Uuid,
+        tenant: Option<TenantId>,
+        role: ClientRole,
+        operator_id: Option<String>,
+    ) -> broadcast::Receiver<Event> {
+        let presence = OperatorPresence {
+            client_id,
+            operator_id,
+            connected_since: chrono::Utc::now(),
+            viewing_drone: None,
+            viewing_mission: None,
+        };
         let state = ClientState {
-            subscriptions: None, // Subscribe to all by default
-            connected_at: chrono::Utc::now(),
+            // Subscribe to all by default
+            filter: EventFilter {
+                tenant_id: tenant.clone(),
+                ..EventFilter::default()
+            },
+            limiter: ClientLimiter::new(role),
+            presence: presence.clone(),
         };
         
         self.clients.insert(client_id, state);
         info!("Client {} registered ({} total)", client_id, self.clients.len());
         
+        // Announced before subscribing: a console only hears about the others
+        self.publish_presence(Event::operator_joined(presence), tenant);
         self.broadcast_tx.subscribe()
     }
 
     /// Unregister a client
     pub fn unregister_client(&self, client_id: Uuid) {
-        self.clients.remove(&client_id);
+        let removed = self.clients.remove(&client_id);
         info!("Client {} unregistered ({} remaining)", client_id, self.clients.len());
+        if let Some((_, client)) = removed {
+            self.publish_presence(Event::operator_left(client.presence), client.filter.tenant_id);
+        }
+    }
+
+    /// Record what a client is viewing; other clients hear about changes
+    pub fn set_focus(&self, client_id: Uuid, drone_id: Option<DroneId>, mission_id: Option<MissionId>) {
+        let changed = self.clients.get_mut(&client_id).and_then(|mut client| {
+            let presence = &mut client.presence;
+            if presence.viewing_drone == drone_id && presence.viewing_mission == mission_id {
+                return None;
+            }
+            presence.viewing_drone = drone_id;
+            presence.viewing_mission = mission_id;
+            Some((presence.clone(), client.filter.tenant_id.clone()))
+        });
+        if let Some((presence, tenant)) = changed {
+            self.publish_presence(Event::operator_focus_changed(presence), tenant);
+        }
+    }
+
+    /// Connected consoles of `tenant` (untenanted ones for `None`), oldest first
+    pub fn presence(&self, tenant: Option<&TenantId>) -> Vec<OperatorPresence> {
+        let mut consoles: Vec<_> = self
+            .clients
+            .iter()
+            .filter(|client| client.filter.tenant_id.as_ref() == tenant)
+            .map(|client| client.presence.clone())
+            .collect();
+        consoles.sort_by_key(|presence| presence.connected_since);
+        consoles
+    }
+
+    /// Presence events go to the clients of the console's tenant
+    fn publish_presence(&self, event: Event, tenant: Option<TenantId>) {
+        let event = match tenant {
+            Some(tenant) => event.with_tenant(tenant),
+            None => event,
+        };
+        self.message_count.fetch_add(1, Ordering::Relaxed);
+        let _ = self.broadcast_tx.send(event);
     }
 
     /// Get number of connected clients
@@ -74,9 +280,50 @@ impl WebSocketHub {
         self.clients.len()
     }
 
+    /// Connected clients of one tenant
+    pub fn tenant_client_count(&self, tenant: &TenantId) -> usize {
+        self.clients
+            .iter()
+            .filter(|client| client.filter.tenant_id.as_ref() == Some(tenant))
+            .count()
+    }
+
+    pub fn client_tenant(&self, client_id: Uuid) -> Option<TenantId> {
+        self.clients.get(&client_id).and_then(|client| client.filter.tenant_id.clone())
+    }
+
+    pub fn client_role(&self, client_id: Uuid) -> Option<ClientRole> {
+        self.clients.get(&client_id).map(|client| client.limiter.role())
+    }
+
+    /// Take a rate limit token for a message of type `kind` from the client.
+    /// Dropped messages are counted.
+    pub fn check_message(&self, client_id: Uuid, kind: MessageKind, now: Instant) -> Result<(), Throttled> {
+        let Some(mut client) = self.clients.get_mut(&client_id) else {
+            return Ok(());
+        };
+        let checked = client.limiter.check(&self.rate_limits, kind, now);
+        if let Err(throttled) = &checked {
+            self.throttle_metrics.record(throttled);
+            debug!("Client {} throttled: {} {}", client_id, kind, throttled.reason.as_str());
+        }
+        checked
+    }
+
+    /// Dropped inbound messages by type and reason
+    pub fn throttle_stats(&self) -> Vec<ThrottledCount> {
+        self.throttle_metrics.snapshot()
+    }
+
     /// Broadcast an event to all clients
-    pub async fn broadcast(&self, event: Event) {
+    pub async fn broadcast(&self, mut event: Event) {
         self.message_count.fetch_add(1, Ordering::Relaxed);
+        if let Some(timings) = event.timings_mut() {
+            let now = Instant::now();
+            timings.broadcast = Some(now);
+            let processed = timings.processed;
+            self.record_latency(event.tenant_id.as_ref(), LatencyHop::Broadcast, now - processed);
+        }
         
         // Send to broadcast channel (drops if no receivers)
         let _ = self.broadcast_tx.send(event);
@@ -92,23 +339,49 @@ impl WebSocketHub {
     /// Subscribe client to specific drones
     pub fn subscribe(&self, client_id: Uuid, drone_ids: Option<Vec<DroneId>>) {
         if let Some(mut client) = self.clients.get_mut(&client_id) {
-            client.subscriptions = drone_ids.map(|ids| ids.into_iter().collect());
+            client.filter.drone_ids = drone_ids.map(|ids| ids.into_iter().collect());
             debug!("Client {} subscriptions updated", client_id);
         }
     }
 
+    /// Scope client to specific missions
+    pub fn subscribe_missions(&self, client_id: Uuid, mission_ids: Option<Vec<MissionId>>) {
+        if let Some(mut client) = self.clients.get_mut(&client_id) {
+            client.filter.mission_ids = mission_ids.map(|ids| ids.into_iter().collect());
+            debug!("Client {} mission scope updated", client_id);
+        }
+    }
+
+    /// Limit client to specific event types
+    pub fn subscribe_event_types(&self, client_id: Uuid, event_types: Option<Vec<EventType>>) {
+        if let Some(mut client) = self.clients.get_mut(&client_id) {
+            client.filter.event_types = event_types.map(|types| types.into_iter().collect());
+            debug!("Client {} event types updated", client_id);
+        }
+    }
+
+    /// Whether an event passes a client's drone, mission and event type filters.
+    /// Events without a drone or mission tag pass those filters. Presence
+    /// only reaches consoles of the same tenant, untenanted ones included.
+    pub fn should_deliver(&self, client_id: Uuid, event: &Event) -> bool {
+        self.clients.get(&client_id).is_some_and(|client| {
+            let presence = matches!(event.payload, EventPayload::Presence(_));
+            (!presence || client.filter.tenant_id == event.tenant_id) && client.filter.matches(event)
+        })
+    }
+
     /// Unsubscribe client from specific drones
     pub fn unsubscribe(&self, client_id: Uuid, drone_ids: Option<Vec<DroneId>>) {
         if let Some(mut client) = self.clients.get_mut(&client_id) {
             if let Some(ref ids) = drone_ids {
-                if let Some(ref mut subs) = client.subscriptions {
+                if let Some(ref mut subs) = client.filter.drone_ids {
                     for id in ids {
                         subs.remove(id);
                     }
                 }
             } else {
                 // Unsubscribe from all
-                client.subscriptions = Some(HashSet::new());
+                client.filter.drone_ids = Some(HashSet::new());
             }
             debug!("Client {} unsubscribed", client_id);
         }
@@ -117,15 +390,42 @@ impl WebSocketHub {
     /// Set command handler callback
     pub fn set_command_handler<F>(&self, handler: F)
     where
-        F: Fn(DroneCommand) + Send + Sync + 'static,
+        F: Fn(Option<TenantId>, DroneCommand) + Send + Sync + 'static,
     {
         *self.command_handler.write() = Some(Box::new(handler));
     }
 
+    /// Receive the latency of position updates of `tenant`'s fleet
+    pub fn set_latency_observer<F>(&self, tenant: Option<TenantId>, observer: F)
+    where
+        F: Fn(LatencyHop, Duration) + Send + Sync + 'static,
+    {
+        self.latency_observers.insert(tenant, Box::new(observer));
+    }
+
+    /// Report time a position update of `tenant`'s fleet spent on a hop
+    pub fn record_latency(&self, tenant: Option<&TenantId>, hop: LatencyHop, elapsed: Duration) {
+        if let Some(observer) = self.latency_observers.get(&tenant.cloned()) {
+            observer(hop, elapsed);
+        }
+    }
+
+    /// Report an event written to a client's socket
+    pub fn record_delivery(&self, event: &Event) {
+        if let Some(broadcast) = event.timings().and_then(|timings| timings.broadcast) {
+            self.record_latency(event.tenant_id.as_ref(), LatencyHop::Delivery, broadcast.elapsed());
+        }
+    }
+
+    /// Report a client's answer to a latency ping sent `round_trip` ago
+    pub fn record_round_trip(&self, client_id: Uuid, round_trip: Duration) {
+        self.record_latency(self.client_tenant(client_id).as_ref(), LatencyHop::Client, round_trip / 2);
+    }
+
     /// Handle a command from a client
-    pub async fn handle_command(&self, command: DroneCommand) {
+    pub async fn handle_command(&self, client_id: Uuid, command: DroneCommand) {
         if let Some(ref handler) = *self.command_handler.read() {
-            handler(command);
+            handler(self.client_tenant(client_id), command);
         } else {
             warn!("No command handler registered");
         }
@@ -145,6 +445,33 @@ impl WebSocketHub {
     pub fn is_client_connected(&self, client_id: Uuid) -> bool {
         self.clients.contains_key(&client_id)
     }
+
+    /// Receiver that flips to `true` when shutdown starts
+    pub fn shutdown_receiver(&self) -> watch::Receiver<bool> {
+        self.shutdown_tx.subscribe()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown_tx.borrow()
+    }
+
+    /// Stop accepting connections, tell every client to go away and wait up
+    /// to `drain` for them to disconnect. Returns the clients still connected.
+    pub async fn shutdown(&self, drain: Duration) -> usize {
+        self.shutdown_tx.send_replace(true);
+        info!("WebSocket shutdown: closing {} client(s)", self.clients.len());
+
+        let deadline = tokio::time::Instant::now() + drain;
+        while !self.clients.is_empty() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+
+        let remaining = self.clients.len();
+        if remaining > 0 {
+            warn!("WebSocket drain timed out with {} client(s) connected", remaining);
+        }
+        remaining
+    }
 }
 
 impl Default for WebSocketHub {
@@ -160,7 +487,8 @@ impl Default for WebSocketHub {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use drone_core::DroneStatus;
+    use crate::presence::MAX_FOCUS_ID_LEN;
+    use drone_core::{ClientMessage, DroneStatus, ServerMessage};
 
     #[test]
     fn test_client_registration() {
@@ -195,6 +523,163 @@ mod tests {
         hub.unregister_client(id);
     }
 
+    #[test]
+    fn test_mission_and_drone_filtering() {
+        let hub = WebSocketHub::new();
+        let id = Uuid::new_v4();
+        let _rx = hub.register_client(id);
+
+        let mission_a = MissionId::new();
+        let mission_b = MissionId::new();
+        let event = |drone: &str, mission: &MissionId| {
+            Event::drone_status_changed(DroneId::new(drone), DroneStatus::Standby, DroneStatus::Moving)
+                .with_mission(mission.clone())
+        };
+
+        assert!(hub.should_deliver(id, &event("REAPER-01", &mission_b)));
+
+        hub.subscribe_missions(id, Some(vec![mission_a.clone()]));
+        assert!(hub.should_deliver(id, &event("REAPER-01", &mission_a)));
+        assert!(!hub.should_deliver(id, &event("REAPER-01", &mission_b)));
+
+        hub.subscribe(id, Some(vec![DroneId::new("REAPER-02")]));
+        assert!(!hub.should_deliver(id, &event("REAPER-01", &mission_a)));
+        assert!(hub.should_deliver(id, &event("REAPER-02", &mission_a)));
+
+        // Untagged system-wide events still go through
+        let untagged = Event::drone_status_changed(
+            DroneId::new("REAPER-02"),
+            DroneStatus::Standby,
+            DroneStatus::Moving,
+        );
+        assert!(hub.should_deliver(id, &untagged));
+    }
+
+    #[test]
+    fn test_presence_events() {
+        let hub = WebSocketHub::new();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut rx = hub.register_operator(alice, None, ClientRole::Operator, Some("alice".into()));
+        assert!(rx.try_recv().is_err());
+
+        let _bob_rx = hub.register_client(bob);
+        assert_eq!(rx.try_recv().unwrap().event_type, EventType::OperatorJoined);
+
+        // Only actual focus changes are announced
+        let drone = Some(DroneId::new("REAPER-03"));
+        hub.set_focus(alice, drone.clone(), None);
+        hub.set_focus(alice, drone.clone(), None);
+        let focus = rx.try_recv().unwrap();
+        assert_eq!(focus.event_type, EventType::OperatorFocusChanged);
+        assert!(rx.try_recv().is_err());
+
+        let consoles = hub.presence(None);
+        assert_eq!(consoles.len(), 2);
+        let alice_presence = consoles.iter().find(|p| p.client_id == alice).unwrap();
+        assert_eq!(alice_presence.operator_id.as_deref(), Some("alice"));
+        assert_eq!(alice_presence.viewing_drone, drone);
+
+        hub.unregister_client(bob);
+        let left = rx.try_recv().unwrap();
+        assert_eq!(left.event_type, EventType::OperatorLeft);
+        assert!(matches!(left.payload, drone_core::EventPayload::Presence(ref e) if e.presence.client_id == bob));
+        assert_eq!(hub.presence(None).len(), 1);
+        assert!(hub.presence(Some(&TenantId::parse("acme").unwrap())).is_empty());
+
+        // Another tenant's consoles are neither listed nor announced here
+        let acme = TenantId::parse("acme").unwrap();
+        let carol = Uuid::new_v4();
+        let _carol_rx = hub.register_operator(carol, Some(acme.clone()), ClientRole::Operator, Some("carol".into()));
+        let joined = rx.try_recv().unwrap();
+        assert!(!hub.should_deliver(alice, &joined));
+        assert!(hub.should_deliver(carol, &joined));
+        assert_eq!(hub.presence(None).len(), 1);
+        assert_eq!(hub.presence(Some(&acme)).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_overlong_focus_is_refused() {
+        let hub = WebSocketHub::new();
+        let client_id = Uuid::new_v4();
+        let _rx = hub.register_client(client_id);
+
+        let focus = |drone: String| ClientMessage::Focus { drone_id: Some(DroneId::new(drone)), mission_id: None };
+        let refused = crate::dispatch_client_message(&hub, client_id, focus("R".repeat(MAX_FOCUS_ID_LEN + 1))).await;
+        assert!(matches!(refused, Some(ServerMessage::Error { ref code, .. }) if code == "INVALID_FOCUS"));
+        assert_eq!(hub.presence(None)[0].viewing_drone, None);
+
+        assert!(crate::dispatch_client_message(&hub, client_id, focus("REAPER-01".into())).await.is_none());
+        assert_eq!(hub.presence(None)[0].viewing_drone, Some(DroneId::new("REAPER-01")));
+    }
+
+    #[tokio::test]
+    async fn test_commands_within_quota_reach_the_handler() {
+        let hub = WebSocketHub::new();
+        let acme = TenantId::parse("acme").unwrap();
+        let handled = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let recorded = handled.clone();
+        hub.set_command_handler(move |tenant, command| recorded.lock().push((tenant, command.drone_id)));
+        let operator = Uuid::new_v4();
+        let _rx = hub.register_client_with_role(operator, Some(acme.clone()), ClientRole::Operator);
+
+        let command = || {
+            ClientMessage::DroneCommand(DroneCommand {
+                drone_id: DroneId::new("REAPER-01"),
+                command: drone_core::DroneCommandType::ReturnToBase,
+            })
+        };
+        // The operator quota allows a burst of ten
+        for _ in 0..10 {
+            assert!(crate::dispatch_client_message(&hub, operator, command()).await.is_none());
+        }
+        let refused = crate::dispatch_client_message(&hub, operator, command()).await;
+        assert!(matches!(refused, Some(ServerMessage::Error { ref code, .. }) if code == "COMMAND_QUOTA_EXCEEDED"));
+        assert_eq!(handled.lock().len(), 10);
+        assert!(handled.lock().iter().all(|(tenant, _)| tenant.as_ref() == Some(&acme)));
+
+        // Viewers have no quota at all
+        let viewer = Uuid::new_v4();
+        let _viewer_rx = hub.register_client_with_role(viewer, None, ClientRole::Viewer);
+        assert!(crate::dispatch_client_message(&hub, viewer, command()).await.is_some());
+        assert_eq!(handled.lock().len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_latency_hops_are_reported_per_tenant() {
+        let hub = WebSocketHub::new();
+        let acme = TenantId::parse("acme").unwrap();
+        let seen = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        hub.set_latency_observer(Some(acme.clone()), move |hop, _| recorded.lock().push(hop));
+        let client = Uuid::new_v4();
+        let mut rx = hub.register_tenant_client(client, Some(acme.clone()));
+
+        let mut event = Event::drone_position_updated(
+            DroneId::new("REAPER-01"),
+            drone_core::GeoPosition::new(34.5, 69.1, 1500.0),
+            drone_core::Telemetry::default(),
+        )
+        .with_tenant(acme);
+        let now = Instant::now();
+        if let drone_core::EventPayload::DronePosition(update) = &mut event.payload {
+            update.timings = Some(drone_core::UpdateTimings::new(now, now));
+        }
+        hub.broadcast(event).await;
+        let delivered = rx.try_recv().unwrap();
+        assert!(delivered.timings().unwrap().broadcast.is_some());
+        hub.record_delivery(&delivered);
+        hub.record_round_trip(client, Duration::from_millis(30));
+
+        // Untimed events and other tenants' clients report nothing
+        hub.broadcast(Event::drone_status_changed(DroneId::new("REAPER-01"), DroneStatus::Standby, DroneStatus::Moving))
+            .await;
+        let other = Uuid::new_v4();
+        let _other_rx = hub.register_client(other);
+        hub.record_round_trip(other, Duration::from_millis(30));
+
+        assert_eq!(*seen.lock(), [LatencyHop::Broadcast, LatencyHop::Delivery, LatencyHop::Client]);
+    }
+
     #[tokio::test]
     async fn test_broadcast_message_count() {
         let hub = WebSocketHub::new();
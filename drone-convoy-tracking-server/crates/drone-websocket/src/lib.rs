@@ -5,6 +5,12 @@
 //! - Broadcast to all connected clients
 //! - Per-drone subscriptions
 //! - Bidirectional communication for commands
+//! - permessage-deflate compression for large messages
+//! - Per-tenant isolation, keyed by the client's API key
+//! - Per-client rate limits and role-based command quotas
+//! - Fixed-point positions for clients connecting with `?compact=true`
+//! - Optional Socket.IO endpoint for dashboards that cannot use raw JSON
+//! - Operator presence: who is connected and what each console is viewing
 //!
 //! ## Protocol
 //!
@@ -12,30 +18,54 @@
 //! - Server → Client: `ServerMessage`
 //! - Client → Server: `ClientMessage`
 
+pub mod bench;
+pub mod compact;
+pub mod deflate;
 pub mod error;
 pub mod hub;
+pub mod presence;
+pub mod ratelimit;
+pub mod socketio;
 
+pub use deflate::{CompressionConfig, CompressionStats};
 pub use error::{WsError, WsResult};
 pub use hub::WebSocketHub;
+pub use presence::{PresenceConfig, MAX_FOCUS_ID_LEN};
+pub use ratelimit::{BucketLimit, ClientRole, MessageKind, RateLimitConfig, ThrottleReason, Throttled, ThrottledCount};
+pub use socketio::SocketIoConfig;
 
 use drone_core::{
-    Event, ServerMessage, ClientMessage, FullStateEvent,
-    Drone, DroneId, Mission, TrackingResult,
+    ServerMessage, ClientMessage, FullStateEvent,
 };
 
-use dashmap::DashMap;
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
-use parking_lot::RwLock;
-use std::collections::HashSet;
+use parking_lot::Mutex;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, mpsc};
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::{HeaderValue, StatusCode};
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message, WebSocketStream};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// How long a closing connection waits for the client's close reply
+const CLOSE_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Error replies queued by the reader before the writer drops them
+const REPLY_CAPACITY: usize = 16;
+
+/// How often each client is pinged to sample its round trip time
+const LATENCY_PING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Connection stream, inflating compressed client frames once negotiated
+pub(crate) type ClientStream = WebSocketStream<deflate::InflateStream<TcpStream>>;
+
 /// Start the WebSocket server
 pub async fn start_server(hub: Arc<WebSocketHub>, port: u16) -> WsResult<()> {
     let addr = format!("0.0.0.0:{}", port);
@@ -43,18 +73,34 @@ pub async fn start_server(hub: Arc<WebSocketHub>, port: u16) -> WsResult<()> {
     
     info!("🔌 WebSocket server listening on ws://{}", addr);
 
+    serve(hub, listener).await
+}
+
+/// Accept WebSocket connections on an already bound listener until the
+/// hub shuts down
+pub async fn serve(hub: Arc<WebSocketHub>, listener: TcpListener) -> WsResult<()> {
+    let mut shutdown_rx = hub.shutdown_receiver();
     loop {
-        match listener.accept().await {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = wait_for_shutdown(&mut shutdown_rx) => {
+                info!("WebSocket server stopped accepting connections");
+                return Ok(());
+            }
+        };
+        match accepted {
             Ok((stream, addr)) => {
                 let hub = hub.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = handle_connection(hub, stream, addr).await {
+                    if let Err(e) = handle_connection(hub.clone(), stream, addr).await {
                         error!("WebSocket connection error from {}: {}", addr, e);
+                        hub.health().record_error();
                     }
                 });
             }
             Err(e) => {
                 error!("Failed to accept WebSocket connection: {}", e);
+                hub.health().record_error();
             }
         }
     }
@@ -66,7 +112,81 @@ async fn handle_connection(
     stream: TcpStream,
     addr: SocketAddr,
 ) -> WsResult<()> {
-    let ws_stream = accept_async(stream).await?;
+    // Set by the handshake callback when permessage-deflate is agreed
+    let deflate = Arc::new(AtomicBool::new(false));
+    // Set by the handshake callback in multi-tenant deployments
+    let tenant = Arc::new(Mutex::new(None));
+    // Set by the handshake callback from the client's API key
+    let role = Arc::new(Mutex::new(hub.rate_limits().default_role));
+    let operator = Arc::new(Mutex::new(None));
+    // Set by the handshake callback from the `compact` query parameter
+    let compact = Arc::new(AtomicBool::new(false));
+    // Set by the handshake callback for requests to the Socket.IO path
+    let socketio = Arc::new(Mutex::new(None));
+    #[allow(clippy::result_large_err)] // callback signature is fixed by tungstenite
+    let negotiate = {
+        let deflate = deflate.clone();
+        let tenant = tenant.clone();
+        let role = role.clone();
+        let operator = operator.clone();
+        let compact = compact.clone();
+        let socketio = socketio.clone();
+        let hub = hub.clone();
+        let enabled = hub.compression_config().enabled;
+        move |request: &Request, mut response: Response| -> Result<Response, ErrorResponse> {
+            let key = api_key(request);
+            compact.store(compact::requested(request.uri().query()), Ordering::Release);
+            *role.lock() = hub.rate_limits().role_for(key.as_deref());
+            *operator.lock() = hub.presence_config().operator_for(key.as_deref());
+            if hub.socketio_config().matches(request.uri().path()) {
+                let Some(version) = socketio::EngineVersion::from_query(request.uri().query()) else {
+                    let mut rejection = ErrorResponse::new(Some("only the websocket transport of Engine.IO 3 or 4 is supported".into()));
+                    *rejection.status_mut() = StatusCode::BAD_REQUEST;
+                    return Err(rejection);
+                };
+                *socketio.lock() = Some(version);
+            }
+            if hub.requires_tenant() {
+                let resolved = key.and_then(|key| hub.resolve_tenant(&key));
+                let Some(resolved) = resolved else {
+                    warn!("Rejected WebSocket connection from {} without a valid API key", addr);
+                    let mut rejection = ErrorResponse::new(Some("missing or unknown API key".into()));
+                    *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+                    return Err(rejection);
+                };
+                *tenant.lock() = Some(resolved);
+            }
+
+            let offers = request
+                .headers()
+                .get_all("Sec-WebSocket-Extensions")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if let Some(accepted) = enabled.then(|| deflate::negotiate(&offers)).flatten() {
+                response
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Extensions", HeaderValue::from_static(accepted));
+                deflate.store(true, Ordering::Release);
+            }
+            Ok(response)
+        }
+    };
+    let ws_stream = accept_hdr_async(deflate::InflateStream::new(stream, deflate.clone()), negotiate).await?;
+    let deflate = deflate.load(Ordering::Acquire);
+    if deflate {
+        hub.compression_metrics().record_negotiated();
+    }
+    let min_size = hub.compression_config().min_size;
+    let compact = compact.load(Ordering::Acquire);
+    let tenant = tenant.lock().take();
+    let role = *role.lock();
+    let operator = operator.lock().take();
+    let socketio = socketio.lock().take();
+    if let Some(version) = socketio {
+        return socketio::run_session(hub, ws_stream, addr, version, tenant, role, operator, deflate).await;
+    }
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
     // Generate client ID
@@ -74,7 +194,7 @@ async fn handle_connection(
     info!("🔗 WebSocket client {} connected from {}", client_id, addr);
 
     // Register client and get broadcast receiver
-    let mut broadcast_rx = hub.register_client(client_id);
+    let mut broadcast_rx = hub.register_operator(client_id, tenant, role, operator);
 
     // Send initial state
     let initial_state = ServerMessage::InitialState(FullStateEvent {
@@ -83,26 +203,41 @@ async fn handle_connection(
         tracking_results: Vec::new(),
     });
     
-    let msg = serde_json::to_string(&initial_state)?;
-    ws_sender.send(Message::Text(msg.into())).await?;
+    let msg = compact::to_json(&initial_state, compact)?;
+    ws_sender.send(hub.compression_metrics().text_message(msg, deflate, min_size)).await?;
+
+    // Latency pings carry the time since connecting; browsers echo them
+    let connected = Instant::now();
+    let mut latency_ping = tokio::time::interval_at(
+        tokio::time::Instant::now() + LATENCY_PING_INTERVAL,
+        LATENCY_PING_INTERVAL,
+    );
 
-    // Spawn task to handle incoming messages from client
+    // Spawn task to handle incoming messages from client; throttled
+    // messages are answered through the writer below
+    let (reply_tx, mut reply_rx) = mpsc::channel(REPLY_CAPACITY);
     let hub_clone = hub.clone();
     let client_id_clone = client_id;
-    let incoming_handle = tokio::spawn(async move {
+    let mut incoming_handle = tokio::spawn(async move {
         while let Some(msg) = ws_receiver.next().await {
             match msg {
-                Ok(Message::Text(text)) => {
-                    if let Err(e) = handle_client_message(&hub_clone, client_id_clone, &text).await {
-                        warn!("Error handling client message: {}", e);
+                Ok(Message::Text(text)) => match handle_client_message(&hub_clone, client_id_clone, &text).await {
+                    Ok(Some(reply)) => {
+                        // A client too fast to read its errors just loses some
+                        let _ = reply_tx.try_send(reply);
                     }
-                }
-                Ok(Message::Ping(data)) => {
+                    Ok(None) => {}
+                    Err(e) => warn!("Error handling client message: {}", e),
+                },
+                Ok(Message::Ping(_data)) => {
                     debug!("Received ping from {}", client_id_clone);
                     // Pong is handled automatically by tungstenite
                 }
-                Ok(Message::Pong(_)) => {
+                Ok(Message::Pong(data)) => {
                     debug!("Received pong from {}", client_id_clone);
+                    if let Some(round_trip) = ping_round_trip(connected, &data) {
+                        hub_clone.record_round_trip(client_id_clone, round_trip);
+                    }
                 }
                 Ok(Message::Close(_)) => {
                     info!("Client {} sent close frame", client_id_clone);
@@ -113,6 +248,7 @@ async fn handle_connection(
                 }
                 Err(e) => {
                     error!("Error receiving message from {}: {}", client_id_clone, e);
+                    hub_clone.health().record_error();
                     break;
                 }
                 _ => {}
@@ -120,20 +256,56 @@ async fn handle_connection(
         }
     });
 
-    // Forward broadcast messages to this client
+    // Forward broadcast messages to this client until either side goes away
+    let mut shutdown_rx = hub.shutdown_receiver();
+    let mut closing = false;
     loop {
-        match broadcast_rx.recv().await {
+        let received = tokio::select! {
+            received = broadcast_rx.recv() => received,
+            Some(reply) = reply_rx.recv() => {
+                let json = compact::to_json(&reply, compact)?;
+                if let Err(e) = ws_sender.send(hub.compression_metrics().text_message(json, deflate, min_size)).await {
+                    error!("Failed to send to client {}: {}", client_id, e);
+                    hub.health().record_error();
+                    break;
+                }
+                continue;
+            }
+            _ = latency_ping.tick() => {
+                if let Err(e) = ws_sender.send(Message::Ping(latency_ping_payload(connected).into())).await {
+                    error!("Failed to ping client {}: {}", client_id, e);
+                    hub.health().record_error();
+                    break;
+                }
+                continue;
+            }
+            _ = wait_for_shutdown(&mut shutdown_rx) => {
+                close_connection(&mut ws_sender, client_id).await;
+                closing = true;
+                break;
+            }
+        };
+        match received {
             Ok(event) => {
+                if !hub.should_deliver(client_id, &event) {
+                    continue;
+                }
                 let msg = ServerMessage::Event(event);
-                match serde_json::to_string(&msg) {
+                match compact::to_json(&msg, compact) {
                     Ok(json) => {
-                        if let Err(e) = ws_sender.send(Message::Text(json.into())).await {
+                        let message = hub.compression_metrics().text_message(json, deflate, min_size);
+                        if let Err(e) = ws_sender.send(message).await {
                             error!("Failed to send to client {}: {}", client_id, e);
+                    hub.health().record_error();
                             break;
                         }
+                        if let ServerMessage::Event(event) = &msg {
+                            hub.record_delivery(event);
+                        }
                     }
                     Err(e) => {
                         error!("Failed to serialize event: {}", e);
+                        hub.health().record_error();
                     }
                 }
             }
@@ -147,7 +319,10 @@ async fn handle_connection(
         }
     }
 
-    // Cancel incoming handler
+    // Give the client a chance to answer our close frame, then stop reading
+    if closing {
+        let _ = tokio::time::timeout(CLOSE_HANDSHAKE_TIMEOUT, &mut incoming_handle).await;
+    }
     incoming_handle.abort();
 
     // Cleanup
@@ -157,18 +332,91 @@ async fn handle_connection(
     Ok(())
 }
 
-/// Handle a message from a client
+/// Ping payload: microseconds since the connection opened
+fn latency_ping_payload(connected: Instant) -> Vec<u8> {
+    (connected.elapsed().as_micros() as u64).to_be_bytes().to_vec()
+}
+
+/// Round trip of a pong echoing a latency ping; `None` for other pongs
+fn ping_round_trip(connected: Instant, data: &[u8]) -> Option<Duration> {
+    let sent = Duration::from_micros(u64::from_be_bytes(data.try_into().ok()?));
+    connected.elapsed().checked_sub(sent)
+}
+
+/// API key from the `api_key` query parameter (browsers cannot set headers
+/// on WebSocket requests), `X-Api-Key` or a bearer `Authorization` header
+fn api_key(request: &Request) -> Option<String> {
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == "api_key")
+            .map(|(_, value)| value.to_string())
+    });
+    let header = |name: &str| request.headers().get(name).and_then(|v| v.to_str().ok());
+    from_query
+        .or_else(|| header("x-api-key").map(str::to_string))
+        .or_else(|| {
+            header("authorization")
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .map(str::to_string)
+        })
+        .filter(|key| !key.is_empty())
+}
+
+pub(crate) async fn wait_for_shutdown(shutdown_rx: &mut watch::Receiver<bool>) {
+    let _ = shutdown_rx.wait_for(|down| *down).await;
+}
+
+/// Send a final ping and a going-away close frame
+pub(crate) async fn close_connection(
+    ws_sender: &mut SplitSink<ClientStream, Message>,
+    client_id: Uuid,
+) {
+    let close = Message::Close(Some(CloseFrame {
+        code: CloseCode::Away,
+        reason: "server shutting down".into(),
+    }));
+    for msg in [Message::Ping(Vec::new().into()), close] {
+        if let Err(e) = ws_sender.send(msg).await {
+            debug!("Client {} gone before close completed: {}", client_id, e);
+            return;
+        }
+    }
+    debug!("Sent close frame to client {}", client_id);
+}
+
+/// Handle a message from a client; returns the error to send back when the
+/// message was throttled
 async fn handle_client_message(
     hub: &WebSocketHub,
     client_id: Uuid,
     text: &str,
-) -> WsResult<()> {
+) -> WsResult<Option<ServerMessage>> {
     let msg: ClientMessage = serde_json::from_str(text)?;
+    Ok(dispatch_client_message(hub, client_id, msg).await)
+}
+
+/// Apply a parsed client message; returns the error to send back when the
+/// message was throttled or invalid
+pub(crate) async fn dispatch_client_message(
+    hub: &WebSocketHub,
+    client_id: Uuid,
+    msg: ClientMessage,
+) -> Option<ServerMessage> {
+    if let Err(throttled) = hub.check_message(client_id, MessageKind::of(&msg), Instant::now()) {
+        return Some(throttled.to_message());
+    }
 
     match msg {
-        ClientMessage::Subscribe { drone_ids } => {
-            debug!("Client {} subscribing to {:?}", client_id, drone_ids);
+        ClientMessage::Subscribe { drone_ids, mission_ids, event_types } => {
+            debug!(
+                "Client {} subscribing to drones {:?}, missions {:?}, event types {:?}",
+                client_id, drone_ids, mission_ids, event_types
+            );
             hub.subscribe(client_id, drone_ids);
+            hub.subscribe_missions(client_id, mission_ids);
+            hub.subscribe_event_types(client_id, event_types);
         }
         ClientMessage::Unsubscribe { drone_ids } => {
             debug!("Client {} unsubscribing from {:?}", client_id, drone_ids);
@@ -182,14 +430,28 @@ async fn handle_client_message(
             info!("Client {} sending command to {}: {:?}", 
                   client_id, cmd.drone_id, cmd.command);
             // Forward to command handler
-            hub.handle_command(cmd).await;
+            hub.handle_command(client_id, cmd).await;
         }
         ClientMessage::Pong { timestamp } => {
+            // The server never sends a `Ping` message, so this timestamp is
+            // the client's own claim; latency comes from WebSocket ping frames
             debug!("Client {} pong: {}", client_id, timestamp);
         }
+        ClientMessage::Focus { drone_id, mission_id } => {
+            // Focus is echoed to every other console, so it is bounded
+            if drone_id.as_ref().is_some_and(|id| id.as_str().chars().count() > MAX_FOCUS_ID_LEN) {
+                return Some(ServerMessage::Error {
+                    code: "INVALID_FOCUS".into(),
+                    message: format!("drone_id: longer than {} characters", MAX_FOCUS_ID_LEN),
+                    retry_after_ms: None,
+                });
+            }
+            debug!("Client {} viewing drone {:?}, mission {:?}", client_id, drone_id, mission_id);
+            hub.set_focus(client_id, drone_id, mission_id);
+        }
     }
 
-    Ok(())
+    None
 }
 
 // ============================================================================
@@ -199,6 +461,17 @@ async fn handle_client_message(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use drone_core::{DroneId, Event};
+
+    #[test]
+    fn test_ping_round_trip() {
+        let connected = Instant::now() - Duration::from_millis(500);
+        let payload = latency_ping_payload(connected);
+        let round_trip = ping_round_trip(connected, &payload).unwrap();
+        assert!(round_trip < Duration::from_millis(100));
+        // Keep-alive and close pings carry no timestamp
+        assert_eq!(ping_round_trip(connected, &[]), None);
+    }
 
     #[test]
     fn test_hub_creation() {
@@ -233,4 +506,151 @@ mod tests {
         hub.unregister_client(client_id);
         assert_eq!(hub.client_count(), 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_shutdown_closes_clients() {
+        let hub = Arc::new(WebSocketHub::new());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(hub.clone(), listener));
+
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        // Initial state message
+        assert!(matches!(client.next().await, Some(Ok(Message::Text(_)))));
+
+        let shutdown = tokio::spawn({
+            let hub = hub.clone();
+            async move { hub.shutdown(Duration::from_secs(5)).await }
+        });
+
+        assert!(matches!(client.next().await, Some(Ok(Message::Ping(_)))));
+        match client.next().await {
+            Some(Ok(Message::Close(Some(frame)))) => assert_eq!(frame.code, CloseCode::Away),
+            other => panic!("expected close frame, got {:?}", other),
+        }
+        // Completes the close handshake
+        assert!(client.next().await.is_none());
+
+        assert_eq!(shutdown.await.unwrap(), 0);
+        assert!(server.await.unwrap().is_ok());
+        assert!(tokio_tungstenite::connect_async(&url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deflate_negotiation_and_compressed_client_frames() {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+        use tokio_tungstenite::tungstenite::protocol::frame::{coding::{Data, OpCode}, Frame};
+
+        let hub = Arc::new(WebSocketHub::new());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(hub.clone(), listener));
+
+        let mut request = url.into_client_request().unwrap();
+        request.headers_mut().insert(
+            "Sec-WebSocket-Extensions",
+            HeaderValue::from_static("permessage-deflate; client_max_window_bits"),
+        );
+        let (mut client, response) = tokio_tungstenite::connect_async(request).await.unwrap();
+        let accepted = response.headers()["Sec-WebSocket-Extensions"].to_str().unwrap();
+        assert!(accepted.starts_with("permessage-deflate"));
+
+        // The initial state is below the threshold and arrives uncompressed
+        assert!(matches!(client.next().await, Some(Ok(Message::Text(_)))));
+        assert_eq!(hub.compression_stats().negotiated_connections, 1);
+        assert_eq!(hub.compression_stats().skipped_messages, 1);
+
+        // A compressed subscribe from the client is inflated and handled
+        let subscribe = r#"{"type":"Subscribe","payload":{"drone_ids":["REAPER-02"]}}"#;
+        let mut frame = Frame::message(deflate::compress(subscribe.as_bytes()), OpCode::Data(Data::Text), true);
+        frame.header_mut().rsv1 = true;
+        client.send(Message::Frame(frame)).await.unwrap();
+
+        let client_id = hub.client_ids()[0];
+        let event = |drone: &str| {
+            drone_core::Event::drone_status_changed(
+                DroneId::new(drone),
+                drone_core::DroneStatus::Standby,
+                drone_core::DroneStatus::Moving,
+            )
+        };
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while hub.should_deliver(client_id, &event("REAPER-01")) {
+            assert!(tokio::time::Instant::now() < deadline, "subscribe was not applied");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(hub.should_deliver(client_id, &event("REAPER-02")));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_clients_need_a_key_and_see_only_their_events() {
+        use drone_core::TenantId;
+
+        let acme = TenantId::parse("acme").unwrap();
+        let globex = TenantId::parse("globex").unwrap();
+        let keys = [("acme-key", acme.clone()), ("globex-key", globex.clone())];
+        let hub = Arc::new(WebSocketHub::new().with_tenant_resolver(move |key| {
+            keys.iter().find(|(k, _)| *k == key).map(|(_, tenant)| tenant.clone())
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(hub.clone(), listener));
+
+        assert!(tokio_tungstenite::connect_async(&url).await.is_err());
+        assert!(tokio_tungstenite::connect_async(format!("{}/?api_key=wrong", url)).await.is_err());
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("{}/?api_key=acme-key", url))
+            .await
+            .unwrap();
+        assert!(matches!(client.next().await, Some(Ok(Message::Text(_)))));
+        assert_eq!(hub.tenant_client_count(&acme), 1);
+        assert_eq!(hub.tenant_client_count(&globex), 0);
+
+        let event = |drone: &str, tenant: &TenantId| {
+            Event::drone_status_changed(
+                DroneId::new(drone),
+                drone_core::DroneStatus::Standby,
+                drone_core::DroneStatus::Moving,
+            )
+            .with_tenant(tenant.clone())
+        };
+        hub.broadcast(event("GLOBEX-01", &globex)).await;
+        hub.broadcast(event("ACME-01", &acme)).await;
+
+        let Some(Ok(Message::Text(text))) = client.next().await else {
+            panic!("expected an event");
+        };
+        assert!(text.contains("ACME-01"), "{}", text);
+        assert!(!text.contains("GLOBEX-01"));
+    }
+
+    #[tokio::test]
+    async fn test_viewer_commands_are_refused_with_an_error() {
+        let limits = RateLimitConfig {
+            role_keys: [("watch-key".to_string(), ClientRole::Viewer)].into(),
+            ..Default::default()
+        };
+        let hub = Arc::new(WebSocketHub::new().with_rate_limits(limits));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/?api_key=watch-key", listener.local_addr().unwrap());
+        tokio::spawn(serve(hub.clone(), listener));
+
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        assert!(matches!(client.next().await, Some(Ok(Message::Text(_)))));
+        assert_eq!(hub.client_role(hub.client_ids()[0]), Some(ClientRole::Viewer));
+
+        let command = r#"{"type":"DroneCommand","payload":{"drone_id":"REAPER-01","command":{"type":"Pause"}}}"#;
+        client.send(Message::Text(command.into())).await.unwrap();
+
+        let Some(Ok(Message::Text(text))) = client.next().await else {
+            panic!("expected an error reply");
+        };
+        let reply: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(reply["type"], "Error");
+        assert_eq!(reply["payload"]["code"], "FORBIDDEN");
+
+        let stats = hub.throttle_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].message_type, stats[0].reason, stats[0].count), (MessageKind::DroneCommand, ThrottleReason::Forbidden, 1));
+    }
+}
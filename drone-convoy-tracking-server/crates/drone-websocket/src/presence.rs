@@ -0,0 +1,50 @@
+//! Operator presence
+//!
+//! Every connection shows up to the other consoles of its tenant: the
+//! operator its API key belongs to, when it connected and the drone and
+//! mission the client says it is viewing (sent as a `Focus` message).
+//! Joins, focus changes and departures are broadcast as `OperatorJoined`,
+//! `OperatorFocusChanged` and `OperatorLeft` events.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Longest drone ID a console may report viewing, as for the REST API
+pub const MAX_FOCUS_ID_LEN: usize = 64;
+
+/// Operator identities by API key
+#[derive(Clone, Default)]
+pub struct PresenceConfig {
+    /// Operator ID by API key; connections with other keys are anonymous
+    pub operator_keys: HashMap<String, String>,
+}
+
+// Keeps API keys out of logs
+impl fmt::Debug for PresenceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PresenceConfig")
+            .field("operator_keys", &self.operator_keys.len())
+            .finish()
+    }
+}
+
+impl PresenceConfig {
+    /// Operators from `WS_OPERATOR_KEYS` (`key:operator,key:operator`)
+    pub fn from_env() -> Self {
+        let operator_keys = std::env::var("WS_OPERATOR_KEYS")
+            .map(|s| {
+                s.split(',')
+                    .filter_map(|pair| pair.rsplit_once(':'))
+                    .map(|(key, operator)| (key.trim().to_string(), operator.trim().to_string()))
+                    .filter(|(key, operator)| !key.is_empty() && !operator.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { operator_keys }
+    }
+
+    /// Operator for a connection's API key
+    pub fn operator_for(&self, api_key: Option<&str>) -> Option<String> {
+        api_key.and_then(|key| self.operator_keys.get(key)).cloned()
+    }
+}
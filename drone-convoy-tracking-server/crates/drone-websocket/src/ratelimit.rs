@@ -0,0 +1,411 @@
+//! Inbound message limits
+//!
+//! Every client gets a token bucket per message type, so a misbehaving
+//! client cannot flood the hub with subscriptions or commands. Drone
+//! commands also draw from a quota set by the client's role; viewers may
+//! not send commands at all. A message over a limit is dropped, answered
+//! with an `Error` message and counted per message type and reason.
+
+use drone_core::{ClientMessage, ServerMessage};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Inbound message type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    Subscribe,
+    Unsubscribe,
+    RequestState,
+    DroneCommand,
+    Pong,
+    Focus,
+}
+
+impl MessageKind {
+    pub fn of(message: &ClientMessage) -> Self {
+        match message {
+            ClientMessage::Subscribe { .. } => Self::Subscribe,
+            ClientMessage::Unsubscribe { .. } => Self::Unsubscribe,
+            ClientMessage::RequestState => Self::RequestState,
+            ClientMessage::DroneCommand(_) => Self::DroneCommand,
+            ClientMessage::Pong { .. } => Self::Pong,
+            ClientMessage::Focus { .. } => Self::Focus,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Subscribe => "subscribe",
+            Self::Unsubscribe => "unsubscribe",
+            Self::RequestState => "request_state",
+            Self::DroneCommand => "drone_command",
+            Self::Pong => "pong",
+            Self::Focus => "focus",
+        }
+    }
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a WebSocket client may do, resolved from its API key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientRole {
+    /// Watches the convoy; cannot command drones
+    Viewer,
+    #[default]
+    Operator,
+    /// Flight lead with a larger command quota
+    Commander,
+}
+
+impl std::str::FromStr for ClientRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Self::Viewer),
+            "operator" => Ok(Self::Operator),
+            "commander" => Ok(Self::Commander),
+            other => Err(format!("unknown role {}", other)),
+        }
+    }
+}
+
+/// Token bucket size and refill rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketLimit {
+    /// Tokens added per second
+    pub rate_per_second: f64,
+    /// Bucket size: messages allowed in a burst
+    pub burst: f64,
+}
+
+impl BucketLimit {
+    pub const fn per_second(rate_per_second: f64, burst: f64) -> Self {
+        Self { rate_per_second, burst }
+    }
+
+    pub const fn per_minute(rate_per_minute: f64, burst: f64) -> Self {
+        Self {
+            rate_per_second: rate_per_minute / 60.0,
+            burst,
+        }
+    }
+}
+
+/// Inbound message limits and command quotas
+#[derive(Clone)]
+pub struct RateLimitConfig {
+    /// Apply the limits at all
+    pub enabled: bool,
+    /// Per-client limit for each message type (missing = unlimited)
+    pub limits: HashMap<MessageKind, BucketLimit>,
+    /// Per-client drone command quota by role (missing = no commands)
+    pub command_quotas: HashMap<ClientRole, BucketLimit>,
+    /// Role of clients whose key has none assigned
+    pub default_role: ClientRole,
+    /// Roles by API key
+    pub role_keys: HashMap<String, ClientRole>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            limits: HashMap::from([
+                (MessageKind::Subscribe, BucketLimit::per_second(2.0, 10.0)),
+                (MessageKind::Unsubscribe, BucketLimit::per_second(2.0, 10.0)),
+                (MessageKind::RequestState, BucketLimit::per_second(1.0, 5.0)),
+                (MessageKind::DroneCommand, BucketLimit::per_second(5.0, 20.0)),
+                (MessageKind::Pong, BucketLimit::per_second(1.0, 5.0)),
+                (MessageKind::Focus, BucketLimit::per_second(2.0, 10.0)),
+            ]),
+            command_quotas: HashMap::from([
+                (ClientRole::Operator, BucketLimit::per_minute(30.0, 10.0)),
+                (ClientRole::Commander, BucketLimit::per_minute(120.0, 20.0)),
+            ]),
+            default_role: ClientRole::default(),
+            role_keys: HashMap::new(),
+        }
+    }
+}
+
+// Keeps API keys out of logs
+impl fmt::Debug for RateLimitConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitConfig")
+            .field("enabled", &self.enabled)
+            .field("limits", &self.limits)
+            .field("command_quotas", &self.command_quotas)
+            .field("default_role", &self.default_role)
+            .field("role_keys", &self.role_keys.len())
+            .finish()
+    }
+}
+
+impl RateLimitConfig {
+    /// Defaults overridden by `WS_RATE_LIMIT`, `WS_DEFAULT_ROLE` and
+    /// `WS_ROLE_KEYS` (`key:role,key:role`)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+        Self {
+            enabled: env("WS_RATE_LIMIT")
+                .map(|s| s != "false" && s != "0")
+                .unwrap_or(defaults.enabled),
+            default_role: env("WS_DEFAULT_ROLE")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.default_role),
+            role_keys: env("WS_ROLE_KEYS")
+                .map(|s| {
+                    s.split(',')
+                        .filter_map(|pair| pair.rsplit_once(':'))
+                        .filter_map(|(key, role)| Some((key.trim().to_string(), role.parse().ok()?)))
+                        .filter(|(key, _)| !key.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ..defaults
+        }
+    }
+
+    /// Role for a connection's API key
+    pub fn role_for(&self, api_key: Option<&str>) -> ClientRole {
+        api_key
+            .and_then(|key| self.role_keys.get(key))
+            .copied()
+            .unwrap_or(self.default_role)
+    }
+}
+
+/// Why a message was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleReason {
+    /// Over the message type's rate limit
+    RateLimited,
+    /// Over the role's command quota
+    QuotaExceeded,
+    /// The role may not send this message
+    Forbidden,
+}
+
+impl ThrottleReason {
+    /// Error code sent to the client
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::RateLimited => "RATE_LIMITED",
+            Self::QuotaExceeded => "COMMAND_QUOTA_EXCEEDED",
+            Self::Forbidden => "FORBIDDEN",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RateLimited => "rate_limited",
+            Self::QuotaExceeded => "quota_exceeded",
+            Self::Forbidden => "forbidden",
+        }
+    }
+}
+
+/// A dropped message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throttled {
+    pub kind: MessageKind,
+    pub reason: ThrottleReason,
+    pub role: ClientRole,
+    /// When the next message of this type would be accepted
+    pub retry_after: Option<Duration>,
+}
+
+impl Throttled {
+    /// Error message answering the dropped message
+    pub fn to_message(&self) -> ServerMessage {
+        let message = match self.reason {
+            ThrottleReason::RateLimited => format!("too many {} messages", self.kind),
+            ThrottleReason::QuotaExceeded => format!("{:?} command quota exhausted", self.role).to_lowercase(),
+            ThrottleReason::Forbidden => format!("{:?} clients may not send {}", self.role, self.kind).to_lowercase(),
+        };
+        ServerMessage::Error {
+            code: self.reason.code().into(),
+            message,
+            retry_after_ms: self.retry_after.map(|d| d.as_millis() as u64),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &BucketLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst,
+            updated: now,
+        }
+    }
+
+    /// Take a token, or the wait until one is available
+    fn take(&mut self, limit: &BucketLimit, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.rate_per_second).min(limit.burst);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else if limit.rate_per_second > 0.0 {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / limit.rate_per_second))
+        } else {
+            Err(Duration::MAX)
+        }
+    }
+}
+
+/// Buckets of one client
+#[derive(Debug)]
+pub(crate) struct ClientLimiter {
+    role: ClientRole,
+    buckets: HashMap<MessageKind, TokenBucket>,
+    commands: Option<TokenBucket>,
+}
+
+impl ClientLimiter {
+    pub(crate) fn new(role: ClientRole) -> Self {
+        Self {
+            role,
+            buckets: HashMap::new(),
+            commands: None,
+        }
+    }
+
+    pub(crate) fn role(&self) -> ClientRole {
+        self.role
+    }
+
+    /// Take the tokens `kind` needs, or say why not
+    pub(crate) fn check(&mut self, config: &RateLimitConfig, kind: MessageKind, now: Instant) -> Result<(), Throttled> {
+        if !config.enabled {
+            return Ok(());
+        }
+        let throttled = |reason, retry_after| Throttled {
+            kind,
+            reason,
+            role: self.role,
+            retry_after,
+        };
+
+        let quota = config.command_quotas.get(&self.role);
+        if kind == MessageKind::DroneCommand && quota.is_none() {
+            return Err(throttled(ThrottleReason::Forbidden, None));
+        }
+        if let Some(limit) = config.limits.get(&kind) {
+            let bucket = self.buckets.entry(kind).or_insert_with(|| TokenBucket::full(limit, now));
+            bucket
+                .take(limit, now)
+                .map_err(|wait| throttled(ThrottleReason::RateLimited, Some(wait)))?;
+        }
+        if let (MessageKind::DroneCommand, Some(quota)) = (kind, quota) {
+            let bucket = self.commands.get_or_insert_with(|| TokenBucket::full(quota, now));
+            bucket
+                .take(quota, now)
+                .map_err(|wait| throttled(ThrottleReason::QuotaExceeded, Some(wait)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Dropped messages of one type for one reason
+#[derive(Debug, Clone, Serialize)]
+pub struct ThrottledCount {
+    pub message_type: MessageKind,
+    pub reason: ThrottleReason,
+    pub count: u64,
+}
+
+/// Dropped-message counters across all clients
+#[derive(Debug, Default)]
+pub(crate) struct ThrottleMetrics {
+    counts: Mutex<HashMap<(MessageKind, ThrottleReason), u64>>,
+}
+
+impl ThrottleMetrics {
+    pub(crate) fn record(&self, throttled: &Throttled) {
+        *self.counts.lock().entry((throttled.kind, throttled.reason)).or_default() += 1;
+    }
+
+    /// Counters by message type, then reason
+    pub(crate) fn snapshot(&self) -> Vec<ThrottledCount> {
+        let mut counts: Vec<ThrottledCount> = self
+            .counts
+            .lock()
+            .iter()
+            .map(|(&(message_type, reason), &count)| ThrottledCount { message_type, reason, count })
+            .collect();
+        counts.sort_by_key(|c| (c.message_type, c.reason));
+        counts
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_and_role_quotas() {
+        let config = RateLimitConfig {
+            role_keys: HashMap::from([("watch-key".to_string(), ClientRole::Viewer)]),
+            ..Default::default()
+        };
+        let start = Instant::now();
+
+        // Ten subscribes in a burst, then one every half second
+        let mut operator = ClientLimiter::new(config.role_for(Some("ops-key")));
+        for _ in 0..10 {
+            operator.check(&config, MessageKind::Subscribe, start).unwrap();
+        }
+        let err = operator.check(&config, MessageKind::Subscribe, start).unwrap_err();
+        assert_eq!(err.reason, ThrottleReason::RateLimited);
+        assert_eq!(err.retry_after, Some(Duration::from_millis(500)));
+        operator
+            .check(&config, MessageKind::Subscribe, start + Duration::from_millis(500))
+            .unwrap();
+
+        // Commands pass the type limit but run out of the operator quota
+        for _ in 0..10 {
+            operator.check(&config, MessageKind::DroneCommand, start).unwrap();
+        }
+        let err = operator.check(&config, MessageKind::DroneCommand, start).unwrap_err();
+        assert_eq!(err.reason, ThrottleReason::QuotaExceeded);
+        assert!(matches!(
+            err.to_message(),
+            ServerMessage::Error { ref code, retry_after_ms: Some(2000), .. } if code == "COMMAND_QUOTA_EXCEEDED"
+        ));
+
+        let mut viewer = ClientLimiter::new(config.role_for(Some("watch-key")));
+        assert_eq!(viewer.role(), ClientRole::Viewer);
+        let err = viewer.check(&config, MessageKind::DroneCommand, start).unwrap_err();
+        assert_eq!(err.reason, ThrottleReason::Forbidden);
+        viewer.check(&config, MessageKind::Pong, start).unwrap();
+
+        let disabled = RateLimitConfig { enabled: false, ..config };
+        viewer.check(&disabled, MessageKind::DroneCommand, start).unwrap();
+    }
+}
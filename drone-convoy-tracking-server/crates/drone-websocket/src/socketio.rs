@@ -0,0 +1,772 @@
+//! Socket.IO compatibility
+//!
+//! Lets dashboards built on Socket.IO connect to the hub without speaking the
+//! raw JSON protocol. Connections to the configured path (`/socket.io` by
+//! default) run the Engine.IO handshake and heartbeat over the WebSocket
+//! transport; long-polling is not offered, so clients must connect with
+//! `transports: ["websocket"]`. Engine.IO v4 (Socket.IO 3/4) and v3
+//! (Socket.IO 2) are both accepted.
+//!
+//! Each [`Namespace`] carries a slice of the event stream: `/` everything,
+//! `/drones`, `/missions`, `/cv`, `/alerts` and `/system` their own event
+//! types. Server messages become Socket.IO events named after their type
+//! (`drone_position_updated`, `initial_state`, `server_error`, ...), with the
+//! message payload as the single argument. Clients emit `subscribe`,
+//! `unsubscribe`, `request_state`, `drone_command`, `focus` and `pong` with the
+//! matching `ClientMessage` payload; an acknowledgement callback receives
+//! the error if the message was throttled.
+
+use crate::error::{WsError, WsResult};
+use crate::hub::WebSocketHub;
+use crate::ratelimit::ClientRole;
+use crate::{close_connection, deflate, dispatch_client_message, wait_for_shutdown, ClientStream};
+use drone_core::{ClientMessage, Event, EventPayload, EventType, FullStateEvent, ServerMessage, TenantId};
+
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use thiserror::Error;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// Socket.IO compatibility settings
+#[derive(Debug, Clone)]
+pub struct SocketIoConfig {
+    /// Serve Socket.IO clients on `path`
+    pub enabled: bool,
+    /// Request path Socket.IO clients connect to
+    pub path: String,
+    /// How often the heartbeat runs
+    pub ping_interval: Duration,
+    /// How long a heartbeat may go unanswered before the connection is dropped
+    pub ping_timeout: Duration,
+}
+
+impl Default for SocketIoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "/socket.io".into(),
+            ping_interval: Duration::from_secs(25),
+            ping_timeout: Duration::from_secs(20),
+        }
+    }
+}
+
+impl SocketIoConfig {
+    /// Load from `WS_SOCKETIO` and `WS_SOCKETIO_PATH`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            enabled: std::env::var("WS_SOCKETIO")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(defaults.enabled),
+            path: std::env::var("WS_SOCKETIO_PATH")
+                .ok()
+                .filter(|path| path.starts_with('/'))
+                .unwrap_or(defaults.path),
+            ..defaults
+        }
+    }
+
+    /// Whether a handshake request path is a Socket.IO connection
+    pub fn matches(&self, path: &str) -> bool {
+        self.enabled && path.trim_end_matches('/') == self.path.trim_end_matches('/')
+    }
+}
+
+// ============================================================================
+// ENGINE.IO
+// ============================================================================
+
+/// Engine.IO protocol revision a client connected with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineVersion {
+    /// Socket.IO 2: the client pings, the server answers
+    V3,
+    /// Socket.IO 3 and 4: the server pings, the client answers
+    V4,
+}
+
+impl EngineVersion {
+    /// Version from the handshake query, if it asks for a supported
+    /// revision over the WebSocket transport
+    pub fn from_query(query: Option<&str>) -> Option<Self> {
+        let params: Vec<(&str, &str)> = query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+        let param = |name: &str| params.iter().find(|(n, _)| *n == name).map(|(_, value)| *value);
+        if param("transport") != Some("websocket") {
+            return None;
+        }
+        match param("EIO") {
+            Some("3") => Some(Self::V3),
+            Some("4") => Some(Self::V4),
+            _ => None,
+        }
+    }
+}
+
+/// Engine.IO packet types, sent as the first character of each frame
+const ENGINE_OPEN: char = '0';
+const ENGINE_CLOSE: char = '1';
+const ENGINE_PING: char = '2';
+const ENGINE_PONG: char = '3';
+const ENGINE_MESSAGE: char = '4';
+
+// ============================================================================
+// SOCKET.IO PACKETS
+// ============================================================================
+
+/// Socket.IO packet type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketType {
+    Connect,
+    Disconnect,
+    Event,
+    Ack,
+    ConnectError,
+    BinaryEvent,
+    BinaryAck,
+}
+
+impl PacketType {
+    const ALL: [Self; 7] = [
+        Self::Connect,
+        Self::Disconnect,
+        Self::Event,
+        Self::Ack,
+        Self::ConnectError,
+        Self::BinaryEvent,
+        Self::BinaryAck,
+    ];
+
+    fn code(self) -> char {
+        char::from(b'0' + Self::ALL.iter().position(|t| *t == self).unwrap_or_default() as u8)
+    }
+}
+
+/// Malformed or unsupported Socket.IO packet
+#[derive(Debug, Error)]
+#[error("{reason}: {packet:?}")]
+pub struct PacketError {
+    pub reason: String,
+    pub packet: String,
+}
+
+/// Socket.IO packet: `<type>[<namespace>,][<ack id>][<json>]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    pub packet_type: PacketType,
+    /// `/` unless the packet targets another namespace
+    pub namespace: String,
+    pub ack_id: Option<u64>,
+    pub data: Option<Value>,
+}
+
+impl Packet {
+    pub fn new(packet_type: PacketType, namespace: Namespace, data: Option<Value>) -> Self {
+        Self {
+            packet_type,
+            namespace: namespace.path().to_string(),
+            ack_id: None,
+            data,
+        }
+    }
+
+    /// Event packet with a single argument
+    pub fn event(namespace: Namespace, name: &str, arg: Value) -> Self {
+        Self::new(PacketType::Event, namespace, Some(json!([name, arg])))
+    }
+
+    pub fn encode(&self) -> String {
+        let mut packet = String::new();
+        packet.push(self.packet_type.code());
+        if self.namespace != "/" {
+            packet.push_str(&self.namespace);
+            packet.push(',');
+        }
+        if let Some(ack_id) = self.ack_id {
+            let _ = write!(packet, "{}", ack_id);
+        }
+        if let Some(data) = &self.data {
+            packet.push_str(&data.to_string());
+        }
+        packet
+    }
+
+    /// Parse a packet; binary packets are not supported
+    pub fn decode(text: &str) -> Result<Self, PacketError> {
+        let invalid = |reason: &str| PacketError {
+            reason: reason.to_string(),
+            packet: text.to_string(),
+        };
+        let mut chars = text.chars();
+        let packet_type = chars
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .and_then(|code| PacketType::ALL.get(code as usize).copied())
+            .ok_or_else(|| invalid("unknown packet type"))?;
+        if matches!(packet_type, PacketType::BinaryEvent | PacketType::BinaryAck) {
+            return Err(invalid("binary packets are not supported"));
+        }
+
+        let mut rest = chars.as_str();
+        let namespace = match rest.strip_prefix('/') {
+            Some(_) => {
+                let (namespace, tail) = rest.split_once(',').unwrap_or((rest, ""));
+                rest = tail;
+                namespace.to_string()
+            }
+            None => "/".to_string(),
+        };
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let ack_id = match digits {
+            0 => None,
+            _ => Some(rest[..digits].parse().map_err(|_| invalid("invalid ack id"))?),
+        };
+        rest = &rest[digits..];
+        let data = match rest {
+            "" => None,
+            json => Some(serde_json::from_str(json).map_err(|e| invalid(&e.to_string()))?),
+        };
+
+        Ok(Self {
+            packet_type,
+            namespace,
+            ack_id,
+            data,
+        })
+    }
+}
+
+// ============================================================================
+// NAMESPACES AND EVENTS
+// ============================================================================
+
+/// Socket.IO namespace and the events it carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    /// Every event, the initial state and error replies
+    Root,
+    Drones,
+    /// Mission, waypoint, zone and scheduled command events
+    Missions,
+    Cv,
+    Alerts,
+    System,
+    /// Integrator-defined events
+    Custom,
+}
+
+impl Namespace {
+    pub const ALL: [Self; 7] = [
+        Self::Root,
+        Self::Drones,
+        Self::Missions,
+        Self::Cv,
+        Self::Alerts,
+        Self::System,
+        Self::Custom,
+    ];
+
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Root => "/",
+            Self::Drones => "/drones",
+            Self::Missions => "/missions",
+            Self::Cv => "/cv",
+            Self::Alerts => "/alerts",
+            Self::System => "/system",
+            Self::Custom => "/custom",
+        }
+    }
+
+    pub fn parse(path: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ns| ns.path() == path)
+    }
+
+    /// Namespace an event type belongs to, besides the root
+    pub fn of(event_type: EventType) -> Self {
+        use EventType::*;
+        match event_type {
+            DronePositionUpdated | DroneStatusChanged | DroneTelemetryUpdated | DroneConnected
+            | DroneDisconnected | DroneEvicted => Self::Drones,
+            MissionStarted | MissionCompleted | MissionPaused | MissionAborted | WaypointReached
+            | WaypointDeparted | WaypointApproaching | WaypointSkipped | ZoneEntered | ZoneExited | ScheduledCommandFired => {
+                Self::Missions
+            }
+            CvTrackingUpdate | HaloDetected | TrackingLost | CvConfigChanged | FleetMarkingChanged => Self::Cv,
+            AlertRaised | AlertAcknowledged | AlertResolved => Self::Alerts,
+            SystemHealthUpdate | ConnectionEstablished | ConnectionLost | OperatorJoined
+            | OperatorFocusChanged | OperatorLeft => Self::System,
+            Custom => Self::Custom,
+        }
+    }
+
+    /// Whether a message with this event type (`None` for anything other
+    /// than an event) is emitted on the namespace
+    pub fn carries(self, event_type: Option<EventType>) -> bool {
+        self == Self::Root || event_type.is_some_and(|event_type| Self::of(event_type) == self)
+    }
+}
+
+/// Socket.IO event name of an event type, e.g. `drone_position_updated`
+pub fn event_name(event_type: EventType) -> String {
+    serde_json::to_value(event_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_ascii_lowercase))
+        .unwrap_or_default()
+}
+
+/// Socket.IO event emitted for a server message
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketEvent {
+    /// Set for drone events, which decide the namespaces
+    pub event_type: Option<EventType>,
+    pub name: String,
+    pub arg: Value,
+}
+
+/// Socket.IO events for a server message: one per event in a batch, none
+/// for pings, which the Engine.IO heartbeat replaces
+pub fn to_events(message: &ServerMessage) -> serde_json::Result<Vec<SocketEvent>> {
+    let other = |name: &str| -> serde_json::Result<Vec<SocketEvent>> {
+        let mut value = serde_json::to_value(message)?;
+        Ok(vec![SocketEvent {
+            event_type: None,
+            name: name.to_string(),
+            arg: value["payload"].take(),
+        }])
+    };
+    let events = match message {
+        ServerMessage::InitialState(_) => other("initial_state")?,
+        ServerMessage::Error { .. } => other("server_error")?,
+        ServerMessage::Ping { .. } => Vec::new(),
+        ServerMessage::Event(event) => vec![socket_event(event)?],
+        ServerMessage::EventBatch(events) => events
+            .iter()
+            .map(socket_event)
+            .collect::<serde_json::Result<_>>()?,
+    };
+    Ok(events)
+}
+
+/// Custom events keep their own type as the event name, e.g.
+/// `acme.SENSOR_POD_STATUS`
+fn socket_event(event: &Event) -> serde_json::Result<SocketEvent> {
+    let name = match &event.payload {
+        EventPayload::Custom(custom) => custom.event_type.clone(),
+        _ => event_name(event.event_type),
+    };
+    Ok(SocketEvent {
+        event_type: Some(event.event_type),
+        name,
+        arg: serde_json::to_value(event)?,
+    })
+}
+
+/// Client message for an event emitted by a Socket.IO client
+pub fn client_message(name: &str, arg: Option<Value>) -> serde_json::Result<ClientMessage> {
+    let variant = match name {
+        "subscribe" => "Subscribe",
+        "unsubscribe" => "Unsubscribe",
+        "request_state" => "RequestState",
+        "drone_command" => "DroneCommand",
+        "pong" => "Pong",
+        "focus" => "Focus",
+        other => return Err(serde::de::Error::custom(format!("unknown event {:?}", other))),
+    };
+    let mut message = json!({ "type": variant });
+    if let Some(arg) = arg.filter(|arg| !arg.is_null()) {
+        message["payload"] = arg;
+    }
+    serde_json::from_value(message)
+}
+
+// ============================================================================
+// SESSION
+// ============================================================================
+
+/// Socket.IO connection state
+struct Session {
+    hub: Arc<WebSocketHub>,
+    sender: SplitSink<ClientStream, Message>,
+    client_id: Uuid,
+    version: EngineVersion,
+    deflate: bool,
+    /// Namespaces the client has joined, in join order
+    namespaces: Vec<Namespace>,
+}
+
+impl Session {
+    async fn send_raw(&mut self, text: String) -> WsResult<()> {
+        let min_size = self.hub.compression_config().min_size;
+        let message = self.hub.compression_metrics().text_message(text, self.deflate, min_size);
+        self.sender.send(message).await?;
+        Ok(())
+    }
+
+    async fn send(&mut self, packet: Packet) -> WsResult<()> {
+        self.send_raw(format!("{}{}", ENGINE_MESSAGE, packet.encode())).await
+    }
+
+    /// Emit a server message on `namespace`, or on every joined namespace
+    /// that carries it
+    async fn emit(&mut self, message: &ServerMessage, namespace: Option<Namespace>) -> WsResult<()> {
+        for event in to_events(message)? {
+            let targets: Vec<Namespace> = match namespace {
+                Some(namespace) => vec![namespace],
+                None => self
+                    .namespaces
+                    .iter()
+                    .copied()
+                    .filter(|ns| ns.carries(event.event_type))
+                    .collect(),
+            };
+            for target in targets {
+                self.send(Packet::event(target, &event.name, event.arg.clone())).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn connect(&mut self, path: &str) -> WsResult<()> {
+        let Some(namespace) = Namespace::parse(path) else {
+            let data = match self.version {
+                EngineVersion::V3 => json!("Invalid namespace"),
+                EngineVersion::V4 => json!({ "message": "Invalid namespace" }),
+            };
+            let mut packet = Packet::new(PacketType::ConnectError, Namespace::Root, Some(data));
+            packet.namespace = path.to_string();
+            return self.send(packet).await;
+        };
+        if !self.namespaces.contains(&namespace) {
+            self.namespaces.push(namespace);
+        }
+        let data = (self.version == EngineVersion::V4).then(|| json!({ "sid": self.client_id }));
+        self.send(Packet::new(PacketType::Connect, namespace, data)).await?;
+        debug!("Socket.IO client {} joined {}", self.client_id, path);
+
+        if namespace == Namespace::Root {
+            let initial_state = ServerMessage::InitialState(FullStateEvent {
+                drones: Vec::new(), // Will be populated by API
+                mission: None,
+                tracking_results: Vec::new(),
+            });
+            self.emit(&initial_state, Some(namespace)).await?;
+        }
+        Ok(())
+    }
+
+    /// Handle a Socket.IO packet from the client
+    async fn receive(&mut self, packet: Packet) -> WsResult<()> {
+        let joined = Namespace::parse(&packet.namespace).filter(|ns| self.namespaces.contains(ns));
+        match packet.packet_type {
+            PacketType::Connect => self.connect(&packet.namespace).await?,
+            PacketType::Disconnect => self.namespaces.retain(|ns| Some(*ns) != joined),
+            PacketType::Event => {
+                let Some(namespace) = joined else {
+                    debug!("Socket.IO client {} emitted on unjoined {}", self.client_id, packet.namespace);
+                    return Ok(());
+                };
+                let mut args = match packet.data {
+                    Some(Value::Array(args)) => args.into_iter(),
+                    data => {
+                        return Err(PacketError {
+                            reason: "event without arguments".into(),
+                            packet: data.map(|data| data.to_string()).unwrap_or_default(),
+                        }
+                        .into())
+                    }
+                };
+                let name = args.next().and_then(|name| name.as_str().map(str::to_string)).unwrap_or_default();
+                let message = client_message(&name, args.next())?;
+                let reply = dispatch_client_message(&self.hub, self.client_id, message).await;
+
+                if let Some(ack_id) = packet.ack_id {
+                    let reply = match &reply {
+                        Some(reply) => to_events(reply)?.into_iter().map(|event| event.arg).collect(),
+                        None => Vec::new(),
+                    };
+                    let mut ack = Packet::new(PacketType::Ack, namespace, Some(Value::Array(reply)));
+                    ack.ack_id = Some(ack_id);
+                    self.send(ack).await?;
+                } else if let Some(reply) = reply {
+                    self.emit(&reply, Some(namespace)).await?;
+                }
+            }
+            PacketType::Ack | PacketType::ConnectError | PacketType::BinaryEvent | PacketType::BinaryAck => {}
+        }
+        Ok(())
+    }
+}
+
+/// Run a Socket.IO connection until either side closes it or the hub shuts
+/// down
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_session(
+    hub: Arc<WebSocketHub>,
+    ws_stream: ClientStream,
+    addr: SocketAddr,
+    version: EngineVersion,
+    tenant: Option<TenantId>,
+    role: ClientRole,
+    operator: Option<String>,
+    deflate: bool,
+) -> WsResult<()> {
+    let config = hub.socketio_config().clone();
+    let (sender, mut receiver) = ws_stream.split();
+    let client_id = Uuid::new_v4();
+    info!("🔗 Socket.IO client {} connected from {} ({:?})", client_id, addr, version);
+
+    let mut broadcast_rx = hub.register_operator(client_id, tenant, role, operator);
+    let mut session = Session {
+        hub: hub.clone(),
+        sender,
+        client_id,
+        version,
+        deflate,
+        namespaces: Vec::new(),
+    };
+
+    let open = json!({
+        "sid": client_id,
+        "upgrades": [],
+        "pingInterval": config.ping_interval.as_millis() as u64,
+        "pingTimeout": config.ping_timeout.as_millis() as u64,
+        "maxPayload": deflate::MAX_INFLATED_BYTES,
+    });
+    let result = async {
+        session.send_raw(format!("{}{}", ENGINE_OPEN, open)).await?;
+        // Socket.IO 2 clients join the root namespace implicitly
+        if version == EngineVersion::V3 {
+            session.connect(Namespace::Root.path()).await?;
+        }
+        serve_session(&mut session, &mut receiver, &mut broadcast_rx, &config).await
+    }
+    .await;
+
+    hub.unregister_client(client_id);
+    info!("🔌 Socket.IO client {} disconnected", client_id);
+    result
+}
+
+async fn serve_session(
+    session: &mut Session,
+    receiver: &mut futures_util::stream::SplitStream<ClientStream>,
+    broadcast_rx: &mut broadcast::Receiver<drone_core::Event>,
+    config: &SocketIoConfig,
+) -> WsResult<()> {
+    let client_id = session.client_id;
+    let hub = session.hub.clone();
+    let mut shutdown_rx = hub.shutdown_receiver();
+    let mut heartbeat = interval_at(Instant::now() + config.ping_interval, config.ping_interval);
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // Clients of either version must show heartbeat traffic within an
+    // interval plus the timeout, as the handshake advertises
+    let session_timeout = config.ping_interval + config.ping_timeout;
+    let expiry = tokio::time::sleep(session_timeout);
+    tokio::pin!(expiry);
+
+    loop {
+        tokio::select! {
+            received = receiver.next() => {
+                let text = match received {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        hub.health().record_error();
+                        return Err(e.into());
+                    }
+                };
+                let mut chars = text.chars();
+                match chars.next() {
+                    Some(ENGINE_PING) => {
+                        expiry.as_mut().reset(Instant::now() + session_timeout);
+                        session.send_raw(format!("{}{}", ENGINE_PONG, chars.as_str())).await?;
+                    }
+                    Some(ENGINE_PONG) => expiry.as_mut().reset(Instant::now() + session_timeout),
+                    Some(ENGINE_CLOSE) => return Ok(()),
+                    Some(ENGINE_MESSAGE) => {
+                        let handled = match Packet::decode(chars.as_str()) {
+                            Ok(packet) => session.receive(packet).await,
+                            Err(e) => Err(e.into()),
+                        };
+                        match handled {
+                            Err(WsError::WebSocket(e)) => return Err(e.into()),
+                            Err(e) => warn!("Error handling Socket.IO message from {}: {}", client_id, e),
+                            Ok(()) => {}
+                        }
+                    }
+                    // Upgrade and noop packets
+                    _ => {}
+                }
+            }
+            received = broadcast_rx.recv() => match received {
+                Ok(event) => {
+                    if !hub.should_deliver(client_id, &event) {
+                        continue;
+                    }
+                    if let Err(e) = session.emit(&ServerMessage::Event(event), None).await {
+                        error!("Failed to send to Socket.IO client {}: {}", client_id, e);
+                        hub.health().record_error();
+                        return Ok(());
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("Socket.IO client {} lagged by {} messages", client_id, n);
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = heartbeat.tick(), if session.version == EngineVersion::V4 => {
+                session.send_raw(ENGINE_PING.to_string()).await?;
+            }
+            _ = &mut expiry => {
+                info!("Socket.IO client {} missed its heartbeat", client_id);
+                return Ok(());
+            }
+            _ = wait_for_shutdown(&mut shutdown_rx) => {
+                close_connection(&mut session.sender, client_id).await;
+                return Ok(());
+            }
+        }
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serve;
+    use drone_core::{DroneId, DroneStatus, Event, GeoPosition, WaypointId};
+    use tokio::net::TcpListener;
+
+    async fn next_text<S>(client: &mut S) -> String
+    where
+        S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        match client.next().await {
+            Some(Ok(Message::Text(text))) => text.to_string(),
+            other => panic!("expected a text frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_packets_and_event_mapping() {
+        let packet = Packet::decode(r#"2/drones,12["subscribe",{"drone_ids":["REAPER-01"]}]"#).unwrap();
+        assert_eq!(packet.packet_type, PacketType::Event);
+        assert_eq!((packet.namespace.as_str(), packet.ack_id), ("/drones", Some(12)));
+        assert_eq!(Packet::decode(&packet.encode()).unwrap(), packet);
+        assert_eq!(Packet::decode("0").unwrap().namespace, "/");
+        assert!(Packet::decode("51-[\"x\"]").is_err());
+
+        let args = packet.data.unwrap();
+        let message = client_message(args[0].as_str().unwrap(), Some(args[1].clone())).unwrap();
+        assert!(matches!(message, ClientMessage::Subscribe { drone_ids: Some(ids), .. } if ids == [DroneId::new("REAPER-01")]));
+        assert!(matches!(client_message("request_state", None), Ok(ClientMessage::RequestState)));
+        assert!(client_message("self_destruct", None).is_err());
+
+        let event = Event::drone_status_changed(DroneId::new("REAPER-01"), DroneStatus::Standby, DroneStatus::Moving);
+        let events = to_events(&ServerMessage::EventBatch(vec![event.clone(), event])).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name, "drone_status_changed");
+        assert!(Namespace::Drones.carries(events[0].event_type) && Namespace::Root.carries(events[0].event_type));
+        assert!(!Namespace::Alerts.carries(events[0].event_type));
+
+        let error = ServerMessage::Error { code: "RATE_LIMITED".into(), message: "slow down".into(), retry_after_ms: None };
+        let events = to_events(&error).unwrap();
+        assert_eq!((events[0].name.as_str(), &events[0].arg["code"]), ("server_error", &json!("RATE_LIMITED")));
+        assert!(!Namespace::Drones.carries(events[0].event_type));
+        assert!(to_events(&ServerMessage::Ping { timestamp: 0 }).unwrap().is_empty());
+
+        assert_eq!(EngineVersion::from_query(Some("EIO=4&transport=websocket")), Some(EngineVersion::V4));
+        assert_eq!(EngineVersion::from_query(Some("EIO=3&transport=websocket&t=abc")), Some(EngineVersion::V3));
+        assert_eq!(EngineVersion::from_query(Some("EIO=4&transport=polling")), None);
+    }
+
+    #[tokio::test]
+    async fn test_session_times_out_after_interval_and_timeout() {
+        let config = SocketIoConfig {
+            enabled: true,
+            ping_interval: Duration::from_millis(200),
+            ping_timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+        let hub = Arc::new(WebSocketHub::new().with_socketio(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(hub.clone(), listener));
+        let url = format!("ws://{}/socket.io/?EIO=4&transport=websocket", addr);
+
+        // Answering every ping keeps the session open past several timeouts
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        assert!(next_text(&mut client).await.starts_with('0'));
+        for _ in 0..4 {
+            assert_eq!(next_text(&mut client).await, "2");
+            client.send(Message::Text("3".into())).await.unwrap();
+        }
+
+        // Unanswered, the first ping comes after the interval and the session
+        // ends a timeout after that
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        assert!(next_text(&mut client).await.starts_with('0'));
+        let started = Instant::now();
+        assert_eq!(next_text(&mut client).await, "2");
+        while let Some(Ok(Message::Text(text))) = client.next().await {
+            assert_eq!(text.as_str(), "2");
+        }
+        assert!(started.elapsed() >= Duration::from_millis(350), "{:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_socketio_client_receives_namespaced_events() {
+        let config = SocketIoConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let hub = Arc::new(WebSocketHub::new().with_socketio(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(hub.clone(), listener));
+
+        let polling = format!("ws://{}/socket.io/?EIO=4&transport=polling", addr);
+        assert!(tokio_tungstenite::connect_async(&polling).await.is_err());
+
+        let url = format!("ws://{}/socket.io/?EIO=4&transport=websocket", addr);
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let open = next_text(&mut client).await;
+        let handshake: Value = serde_json::from_str(open.strip_prefix('0').unwrap()).unwrap();
+        assert_eq!(handshake["pingInterval"], 25000);
+
+        client.send(Message::Text("40/drones,".into())).await.unwrap();
+        assert!(next_text(&mut client).await.starts_with(r#"40/drones,{"sid":"#));
+
+        let position = GeoPosition::new(34.5, 69.2, 1000.0);
+        hub.broadcast(Event::waypoint_reached(DroneId::new("REAPER-01"), WaypointId::new("WP01"), position)).await;
+        hub.broadcast(Event::drone_status_changed(
+            DroneId::new("REAPER-01"),
+            DroneStatus::Standby,
+            DroneStatus::Moving,
+        ))
+        .await;
+
+        // The waypoint event is not carried on /drones
+        let event = next_text(&mut client).await;
+        assert!(event.starts_with(r#"42/drones,["drone_status_changed","#), "{}", event);
+        assert!(event.contains("REAPER-01"));
+    }
+}